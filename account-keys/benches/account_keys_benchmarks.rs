@@ -22,6 +22,12 @@ fn account_keys_benchmarks(c: &mut Criterion) {
         })
     });
 
+    group.bench_function("::subaddresses(1000)", |b| {
+        b.iter(|| {
+            let _public_addresses = account_key.subaddresses(1..1001);
+        })
+    });
+
     group.finish();
 }
 
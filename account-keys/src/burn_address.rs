@@ -62,6 +62,11 @@ pub fn burn_address() -> PublicAddress {
     )
 }
 
+/// Whether a public address is the burn address.
+pub fn is_burn_address(address: &PublicAddress) -> bool {
+    *address == burn_address()
+}
+
 // The burn address spend public key, in the curve25519-dalek ristretto point
 // type
 //
@@ -21,7 +21,9 @@ pub use crate::{
         DEFAULT_SUBADDRESS_INDEX, GIFT_CODE_SUBADDRESS_INDEX, INVALID_SUBADDRESS_INDEX,
     },
     address_hash::ShortAddressHash,
-    burn_address::{burn_address, burn_address_view_private, BURN_ADDRESS_VIEW_PRIVATE},
+    burn_address::{
+        burn_address, burn_address_view_private, is_burn_address, BURN_ADDRESS_VIEW_PRIVATE,
+    },
     error::{Error, Result},
     identity::{RootEntropy, RootIdentity},
 };
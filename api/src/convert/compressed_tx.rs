@@ -0,0 +1,183 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Convert to/from external::CompressedTx.
+
+use crate::{external, ConversionError};
+use mc_transaction_core::{
+    tx::{Tx, TxOutMembershipElement},
+    CompressedTx, CompressedTxOutMembershipProof, CompressedTxOutMembershipProofs,
+};
+use protobuf::RepeatedField;
+
+/// Convert CompressedTxOutMembershipProof -->
+/// external::CompressedTxOutMembershipProof.
+impl From<&CompressedTxOutMembershipProof> for external::CompressedTxOutMembershipProof {
+    fn from(source: &CompressedTxOutMembershipProof) -> Self {
+        let mut proof = external::CompressedTxOutMembershipProof::new();
+        proof.set_index(source.index);
+        proof.set_highest_index(source.highest_index);
+        proof.set_element_indices(source.element_indices.clone());
+        proof
+    }
+}
+
+/// Convert external::CompressedTxOutMembershipProof -->
+/// CompressedTxOutMembershipProof.
+impl From<&external::CompressedTxOutMembershipProof> for CompressedTxOutMembershipProof {
+    fn from(source: &external::CompressedTxOutMembershipProof) -> Self {
+        CompressedTxOutMembershipProof {
+            index: source.get_index(),
+            highest_index: source.get_highest_index(),
+            element_indices: source.get_element_indices().to_vec(),
+        }
+    }
+}
+
+/// Convert CompressedTxOutMembershipProofs -->
+/// external::CompressedTxOutMembershipProofs.
+impl From<&CompressedTxOutMembershipProofs> for external::CompressedTxOutMembershipProofs {
+    fn from(source: &CompressedTxOutMembershipProofs) -> Self {
+        let mut proofs = external::CompressedTxOutMembershipProofs::new();
+        proofs.set_elements(RepeatedField::from_vec(
+            source
+                .elements
+                .iter()
+                .map(external::TxOutMembershipElement::from)
+                .collect(),
+        ));
+        proofs.set_proofs(RepeatedField::from_vec(
+            source
+                .proofs
+                .iter()
+                .map(external::CompressedTxOutMembershipProof::from)
+                .collect(),
+        ));
+        proofs
+    }
+}
+
+/// Convert external::CompressedTxOutMembershipProofs -->
+/// CompressedTxOutMembershipProofs.
+impl TryFrom<&external::CompressedTxOutMembershipProofs> for CompressedTxOutMembershipProofs {
+    type Error = ConversionError;
+
+    fn try_from(source: &external::CompressedTxOutMembershipProofs) -> Result<Self, Self::Error> {
+        let elements = source
+            .get_elements()
+            .iter()
+            .map(TxOutMembershipElement::try_from)
+            .collect::<Result<_, _>>()?;
+        let proofs = source
+            .get_proofs()
+            .iter()
+            .map(CompressedTxOutMembershipProof::from)
+            .collect();
+        Ok(CompressedTxOutMembershipProofs { elements, proofs })
+    }
+}
+
+/// Convert CompressedTx --> external::CompressedTx.
+impl From<&CompressedTx> for external::CompressedTx {
+    fn from(source: &CompressedTx) -> Self {
+        let mut compressed_tx = external::CompressedTx::new();
+        compressed_tx.set_tx(external::Tx::from(&source.tx));
+        compressed_tx.set_proofs(external::CompressedTxOutMembershipProofs::from(
+            &source.proofs,
+        ));
+        compressed_tx
+    }
+}
+
+/// Convert external::CompressedTx --> CompressedTx.
+impl TryFrom<&external::CompressedTx> for CompressedTx {
+    type Error = ConversionError;
+
+    fn try_from(source: &external::CompressedTx) -> Result<Self, Self::Error> {
+        let tx = Tx::try_from(source.get_tx())?;
+        let proofs = CompressedTxOutMembershipProofs::try_from(source.get_proofs())?;
+        Ok(CompressedTx { tx, proofs })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_account_keys::AccountKey;
+    use mc_crypto_ring_signature_signer::NoKeysRingSigner;
+    use mc_fog_report_validation_test_utils::MockFogResolver;
+    use mc_transaction_core::{tokens::Mob, Amount, BlockVersion, Token};
+    use mc_transaction_std::{
+        test_utils::get_input_credentials, EmptyMemoBuilder, TransactionBuilder,
+    };
+    use protobuf::Message;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    /// Tx --> CompressedTx --> external::CompressedTx --> CompressedTx --> Tx
+    /// should be the identity function.
+    fn test_convert_compressed_tx() {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+
+        for block_version in BlockVersion::iterator() {
+            let alice = AccountKey::random(&mut rng);
+            let bob = AccountKey::random(&mut rng);
+
+            let fpr = MockFogResolver::default();
+
+            let mut transaction_builder = TransactionBuilder::new(
+                block_version,
+                Amount::new(Mob::MINIMUM_FEE, Mob::ID),
+                fpr.clone(),
+                EmptyMemoBuilder::default(),
+            )
+            .unwrap();
+
+            for _ in 0..2 {
+                transaction_builder.add_input(get_input_credentials(
+                    block_version,
+                    Amount::new(65536, Mob::ID),
+                    &alice,
+                    &fpr,
+                    &mut rng,
+                ));
+            }
+            transaction_builder
+                .add_output(
+                    Amount::new(2 * 65536 - Mob::MINIMUM_FEE, Mob::ID),
+                    &bob.default_subaddress(),
+                    &mut rng,
+                )
+                .unwrap();
+
+            let tx = transaction_builder
+                .build(&NoKeysRingSigner {}, &mut rng)
+                .unwrap();
+            let compressed_tx = CompressedTx::from(&tx);
+
+            // Converting CompressedTx -> external::CompressedTx -> CompressedTx should be
+            // the identity function, and decompress to the original Tx.
+            {
+                let external_compressed_tx = external::CompressedTx::from(&compressed_tx);
+                let recovered = CompressedTx::try_from(&external_compressed_tx).unwrap();
+                assert_eq!(compressed_tx, recovered);
+                assert_eq!(tx, Tx::try_from(recovered).unwrap());
+            }
+
+            // Encoding with prost, decoding with protobuf should be the identity function.
+            {
+                let bytes = mc_util_serial::encode(&compressed_tx);
+                let recovered = external::CompressedTx::parse_from_bytes(&bytes).unwrap();
+                assert_eq!(recovered, external::CompressedTx::from(&compressed_tx));
+            }
+
+            // Encoding with protobuf, decoding with prost should be the identity function.
+            {
+                let external_compressed_tx = external::CompressedTx::from(&compressed_tx);
+                let bytes = external_compressed_tx.write_to_bytes().unwrap();
+                let recovered: CompressedTx = mc_util_serial::decode(&bytes).unwrap();
+                assert_eq!(compressed_tx, recovered);
+                assert_eq!(tx, Tx::try_from(recovered).unwrap());
+            }
+        }
+    }
+}
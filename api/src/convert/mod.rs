@@ -20,6 +20,7 @@ mod block_signature;
 mod account_key;
 mod amount;
 mod compressed_ristretto;
+mod compressed_tx;
 mod curve_scalar;
 mod ed25519_multisig;
 mod ed25519_signature;
@@ -32,6 +33,7 @@ mod quorum_set;
 mod ring_mlsag;
 mod ristretto_private;
 mod signature_rct_bulletproofs;
+mod signed_contingent_input;
 mod tx;
 mod tx_hash;
 mod tx_in;
@@ -43,6 +45,7 @@ mod tx_prefix;
 mod validated_mint_config;
 mod verification_report;
 mod verification_signature;
+mod view_account_key;
 mod watcher;
 
 // printable
@@ -0,0 +1,135 @@
+//! Convert to/from external::SignedContingentInput.
+
+use crate::{external, ConversionError};
+use mc_transaction_core::{
+    ring_signature::{CurveScalar, RingMLSAG},
+    tx::TxIn,
+    SignedContingentInput, UnmaskedAmount,
+};
+
+/// Convert UnmaskedAmount --> external::UnmaskedAmount.
+impl From<&UnmaskedAmount> for external::UnmaskedAmount {
+    fn from(source: &UnmaskedAmount) -> Self {
+        let mut unmasked_amount = external::UnmaskedAmount::new();
+        unmasked_amount.set_value(source.value);
+        unmasked_amount.set_token_id(source.token_id);
+        unmasked_amount.set_blinding((&source.blinding).into());
+        unmasked_amount
+    }
+}
+
+/// Convert external::UnmaskedAmount --> UnmaskedAmount.
+impl TryFrom<&external::UnmaskedAmount> for UnmaskedAmount {
+    type Error = ConversionError;
+
+    fn try_from(source: &external::UnmaskedAmount) -> Result<Self, Self::Error> {
+        Ok(UnmaskedAmount {
+            value: source.get_value(),
+            token_id: source.get_token_id(),
+            blinding: CurveScalar::try_from(source.get_blinding())?,
+        })
+    }
+}
+
+/// Convert SignedContingentInput --> external::SignedContingentInput.
+impl From<&SignedContingentInput> for external::SignedContingentInput {
+    fn from(source: &SignedContingentInput) -> Self {
+        let mut sci = external::SignedContingentInput::new();
+        sci.set_block_version(source.block_version);
+        sci.set_tx_in((&source.tx_in).into());
+        sci.set_mlsag((&source.mlsag).into());
+        sci.set_pseudo_output_amount((&source.pseudo_output_amount).into());
+        sci.set_required_output_amounts(
+            source
+                .required_output_amounts
+                .iter()
+                .map(external::UnmaskedAmount::from)
+                .collect(),
+        );
+        sci.set_tx_out_global_indices(source.tx_out_global_indices.clone());
+        sci
+    }
+}
+
+/// Convert external::SignedContingentInput --> SignedContingentInput.
+impl TryFrom<&external::SignedContingentInput> for SignedContingentInput {
+    type Error = ConversionError;
+
+    fn try_from(source: &external::SignedContingentInput) -> Result<Self, Self::Error> {
+        let required_output_amounts = source
+            .get_required_output_amounts()
+            .iter()
+            .map(UnmaskedAmount::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(SignedContingentInput {
+            block_version: source.get_block_version(),
+            tx_in: TxIn::try_from(source.get_tx_in())?,
+            mlsag: RingMLSAG::try_from(source.get_mlsag())?,
+            pseudo_output_amount: UnmaskedAmount::try_from(source.get_pseudo_output_amount())?,
+            required_output_amounts,
+            tx_out_global_indices: source.get_tx_out_global_indices().to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_account_keys::AccountKey;
+    use mc_crypto_ring_signature_signer::NoKeysRingSigner;
+    use mc_fog_report_validation_test_utils::MockFogResolver;
+    use mc_transaction_core::{
+        constants::MILLIMOB_TO_PICOMOB, tokens::Mob, Amount, BlockVersion, Token, TokenId,
+    };
+    use mc_transaction_std::{
+        test_utils::get_input_credentials, EmptyMemoBuilder, SignedContingentInputBuilder,
+    };
+    use protobuf::Message;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    /// SignedContingentInput --> external::SignedContingentInput -->
+    /// SignedContingentInput should be the identity function
+    fn test_convert_signed_contingent_input() {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+
+        for block_version in BlockVersion::iterator().skip(3) {
+            let charlie = AccountKey::random(&mut rng);
+            let fpr = MockFogResolver::default();
+
+            // Charlie makes a signed contingent input, offering 1000 token2's for 1 MOB
+            let input_credentials = get_input_credentials(
+                block_version,
+                Amount::new(1000, TokenId::from(2)),
+                &charlie,
+                &fpr,
+                &mut rng,
+            );
+            let mut sci_builder = SignedContingentInputBuilder::new(
+                block_version,
+                input_credentials,
+                fpr,
+                EmptyMemoBuilder::default(),
+            )
+            .unwrap();
+            sci_builder
+                .add_required_output(
+                    Amount::new(1000 * MILLIMOB_TO_PICOMOB, Mob::ID),
+                    &charlie.default_subaddress(),
+                    &mut rng,
+                )
+                .unwrap();
+            let sci = sci_builder.build(&NoKeysRingSigner {}, &mut rng).unwrap();
+
+            let external_sci = external::SignedContingentInput::from(&sci);
+            let recovered_sci = SignedContingentInput::try_from(&external_sci).unwrap();
+            assert_eq!(sci, recovered_sci);
+
+            // Encoding with prost, decoding with protobuf should be the identity function.
+            let bytes = mc_util_serial::encode(&sci);
+            let recovered_sci = external::SignedContingentInput::parse_from_bytes(&bytes).unwrap();
+            assert_eq!(recovered_sci, external_sci);
+        }
+    }
+}
@@ -27,6 +27,18 @@ impl From<&tx::TxOut> for external::TxOut {
                 .set_data(AsRef::<[u8]>::as_ref(memo).to_vec());
         }
 
+        tx_out.set_additional_e_fog_hints(
+            source
+                .additional_e_fog_hints
+                .iter()
+                .map(|hint| {
+                    let mut e_fog_hint = external::EncryptedFogHint::new();
+                    e_fog_hint.set_data(hint.as_ref().to_vec());
+                    e_fog_hint
+                })
+                .collect(),
+        );
+
         tx_out
     }
 }
@@ -61,12 +73,22 @@ impl TryFrom<&external::TxOut> for tx::TxOut {
             )
         };
 
+        let additional_e_fog_hints = source
+            .get_additional_e_fog_hints()
+            .iter()
+            .map(|hint| {
+                EncryptedFogHint::try_from(hint.get_data())
+                    .map_err(|_| ConversionError::ArrayCastError)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
         let tx_out = tx::TxOut {
             masked_amount,
             target_key,
             public_key,
             e_fog_hint,
             e_memo,
+            additional_e_fog_hints,
         };
         Ok(tx_out)
     }
@@ -99,6 +121,7 @@ mod tests {
             public_key: RistrettoPublic::from_random(&mut rng).into(),
             e_fog_hint: (&[0u8; ENCRYPTED_FOG_HINT_LEN]).into(),
             e_memo: None,
+            additional_e_fog_hints: Default::default(),
         };
 
         let converted = external::TxOut::from(&source);
@@ -123,6 +146,7 @@ mod tests {
             public_key: RistrettoPublic::from_random(&mut rng).into(),
             e_fog_hint: (&[0u8; ENCRYPTED_FOG_HINT_LEN]).into(),
             e_memo: Some((*GenericArray::from_slice(&[9u8; 66])).into()),
+            additional_e_fog_hints: vec![(&[7u8; ENCRYPTED_FOG_HINT_LEN]).into()],
         };
 
         let converted = external::TxOut::from(&source);
@@ -133,5 +157,9 @@ mod tests {
         assert_eq!(source.public_key, recovered_tx_out.public_key);
         assert_eq!(source.e_fog_hint, recovered_tx_out.e_fog_hint);
         assert_eq!(source.e_memo, recovered_tx_out.e_memo);
+        assert_eq!(
+            source.additional_e_fog_hints,
+            recovered_tx_out.additional_e_fog_hints
+        );
     }
 }
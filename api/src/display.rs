@@ -202,6 +202,8 @@ mod display_tests {
         transfer_payload
             .mut_tx_out_public_key()
             .set_data(vec![2u8; 32]);
+        transfer_payload.set_value(1337);
+        transfer_payload.set_token_id(2);
 
         let mut wrapper = PrintableWrapper::new();
         wrapper.set_transfer_payload(transfer_payload);
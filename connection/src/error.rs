@@ -8,7 +8,10 @@ use crate::traits::AttestationError;
 use displaydoc::Display;
 use grpcio::Error as GrpcError;
 use mc_blockchain_types::ConvertError;
-use mc_consensus_api::{consensus_common::ProposeTxResult, ConversionError};
+use mc_consensus_api::{
+    consensus_common::{ProposeTxResponse, ProposeTxResult},
+    ConversionError,
+};
 use mc_crypto_noise::CipherError;
 use mc_transaction_core::validation::TransactionValidationError;
 use std::{array::TryFromSliceError, result::Result as StdResult};
@@ -97,6 +100,14 @@ impl From<ProposeTxResult> for Error {
     }
 }
 
+impl From<&ProposeTxResponse> for Error {
+    fn from(src: &ProposeTxResponse) -> Self {
+        src.try_into()
+            .map(Self::TransactionValidation)
+            .unwrap_or_else(|err| Error::Other(err.into()))
+    }
+}
+
 impl From<ConvertError> for Error {
     fn from(_src: ConvertError) -> Self {
         ConversionError::ArrayCastError.into()
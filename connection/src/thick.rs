@@ -38,7 +38,7 @@ use mc_consensus_api::{
 use mc_crypto_keys::X25519;
 use mc_crypto_noise::CipherError;
 use mc_crypto_rand::McRng;
-use mc_transaction_core::tx::Tx;
+use mc_transaction_core::{tx::Tx, CompressedTx, COMPRESSED_TX_AAD};
 use mc_util_grpc::{ConnectionUriGrpcioChannel, GrpcCookieStore};
 use mc_util_serial::encode;
 use mc_util_uri::{ConnectionUri, ConsensusClientUri as ClientUri, UriConversionError};
@@ -403,10 +403,12 @@ impl<CP: CredentialsProvider> UserTxConnection for ThickClient<CP> {
         let mut msg = Message::new();
         msg.set_channel_id(Vec::from(enclave_connection.binding()));
 
-        // Don't leave the plaintext serialization floating around
-        let tx_plaintext = SecretVec::new(encode(tx));
+        // Send the membership proofs compressed, since they are most of the size of
+        // the transaction. Don't leave the plaintext serialization floating around
+        let tx_plaintext = SecretVec::new(encode(&CompressedTx::from(tx)));
         let tx_ciphertext =
-            enclave_connection.encrypt(&[], tx_plaintext.expose_secret().as_ref())?;
+            enclave_connection.encrypt(COMPRESSED_TX_AAD, tx_plaintext.expose_secret().as_ref())?;
+        msg.set_aad(COMPRESSED_TX_AAD.to_vec());
         msg.set_data(tx_ciphertext);
 
         let resp = self.authenticated_attested_call(|this, call_option| {
@@ -417,7 +419,7 @@ impl<CP: CredentialsProvider> UserTxConnection for ThickClient<CP> {
         if resp.get_result() == ProposeTxResult::Ok {
             Ok(resp.get_block_count())
         } else {
-            Err(resp.get_result().into())
+            Err((&resp).into())
         }
     }
 }
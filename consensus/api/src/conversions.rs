@@ -10,7 +10,7 @@
 
 use crate::{
     consensus_client::{MintValidationResult, MintValidationResultCode},
-    consensus_common::ProposeTxResult,
+    consensus_common::{ProposeTxResponse, ProposeTxResult},
     consensus_config,
 };
 use mc_api::ConversionError;
@@ -25,32 +25,32 @@ impl From<Error> for ProposeTxResult {
         match src {
             Error::InputsProofsLengthMismatch => Self::InputsProofsLengthMismatch,
             Error::NoInputs => Self::NoInputs,
-            Error::TooManyInputs => Self::TooManyInputs,
+            Error::TooManyInputs(..) => Self::TooManyInputs,
             Error::InsufficientInputSignatures => Self::InsufficientInputSignatures,
             Error::InvalidInputSignature => Self::InvalidInputSignature,
             Error::InvalidTransactionSignature(_e) => Self::InvalidTransactionSignature,
             Error::InvalidRangeProof => Self::InvalidRangeProof,
-            Error::InsufficientRingSize => Self::InsufficientRingSize,
-            Error::TombstoneBlockExceeded => Self::TombstoneBlockExceeded,
-            Error::TombstoneBlockTooFar => Self::TombstoneBlockTooFar,
+            Error::InsufficientRingSize(..) => Self::InsufficientRingSize,
+            Error::TombstoneBlockExceeded(..) => Self::TombstoneBlockExceeded,
+            Error::TombstoneBlockTooFar(..) => Self::TombstoneBlockTooFar,
             Error::NoOutputs => Self::NoOutputs,
-            Error::TooManyOutputs => Self::TooManyOutputs,
-            Error::ExcessiveRingSize => Self::ExcessiveRingSize,
+            Error::TooManyOutputs(..) => Self::TooManyOutputs,
+            Error::ExcessiveRingSize(..) => Self::ExcessiveRingSize,
             Error::DuplicateRingElements => Self::DuplicateRingElements,
-            Error::UnsortedRingElements => Self::UnsortedRingElements,
+            Error::UnsortedRingElements(..) => Self::UnsortedRingElements,
             Error::UnequalRingSizes => Self::UnequalRingSizes,
             Error::UnsortedKeyImages => Self::UnsortedKeyImages,
             Error::ContainsSpentKeyImage => Self::ContainsSpentKeyImage,
             Error::DuplicateKeyImages => Self::DuplicateKeyImages,
             Error::DuplicateOutputPublicKey => Self::DuplicateOutputPublicKey,
             Error::ContainsExistingOutputPublicKey => Self::ContainsExistingOutputPublicKey,
-            Error::MissingTxOutMembershipProof => Self::MissingTxOutMembershipProof,
-            Error::InvalidTxOutMembershipProof => Self::InvalidTxOutMembershipProof,
+            Error::MissingTxOutMembershipProof(..) => Self::MissingTxOutMembershipProof,
+            Error::InvalidTxOutMembershipProof(..) => Self::InvalidTxOutMembershipProof,
             Error::InvalidRistrettoPublicKey => Self::InvalidRistrettoPublicKey,
             Error::InvalidLedgerContext => Self::InvalidLedgerContext,
             Error::Ledger(_) => Self::Ledger,
             Error::MembershipProofValidationError => Self::MembershipProofValidationError,
-            Error::TxFeeError => Self::TxFeeError,
+            Error::TxFeeError(..) => Self::TxFeeError,
             Error::KeyError => Self::KeyError,
             Error::UnsortedInputs => Self::UnsortedInputs,
             Error::MissingMemo => Self::MissingMemo,
@@ -66,47 +66,138 @@ impl From<Error> for ProposeTxResult {
             Error::InputRule(InputRuleError::MaxTombstoneBlockExceeded) => {
                 Self::InputRuleMaxTombstoneBlockExceeded
             }
+            Error::AdditionalFogHintsNotAllowed => Self::AdditionalFogHintsNotAllowed,
+            Error::TooManyAdditionalFogHints(..) => Self::TooManyAdditionalFogHints,
+        }
+    }
+}
+
+/// Convert TransactionValidationError --> ProposeTxResponse, with the context
+/// of the error.
+impl From<Error> for ProposeTxResponse {
+    fn from(src: Error) -> Self {
+        let mut response = Self::new();
+        match src {
+            Error::TooManyInputs(actual, expected)
+            | Error::TooManyOutputs(actual, expected)
+            | Error::TxFeeError(actual, expected)
+            | Error::TombstoneBlockTooFar(actual, expected)
+            | Error::TooManyAdditionalFogHints(actual, expected) => {
+                response.set_actual(actual);
+                response.set_expected(expected);
+            }
+            Error::TombstoneBlockExceeded(current_block_index, tombstone_block_index) => {
+                response.set_actual(tombstone_block_index);
+                response.set_expected(current_block_index);
+            }
+            Error::InsufficientRingSize(input_index, actual, expected)
+            | Error::ExcessiveRingSize(input_index, actual, expected) => {
+                response.set_input_index(input_index);
+                response.set_actual(actual);
+                response.set_expected(expected);
+            }
+            Error::UnsortedRingElements(input_index)
+            | Error::MissingTxOutMembershipProof(input_index) => {
+                response.set_input_index(input_index);
+            }
+            Error::InvalidTxOutMembershipProof(input_index, ring_element_index) => {
+                response.set_input_index(input_index);
+                response.set_ring_element_index(ring_element_index);
+            }
+            _ => {}
+        }
+        response.set_result(src.into());
+        response
+    }
+}
+
+/// Convert ProposeTxResponse --> TransactionValidationError, with the context
+/// of the error.
+impl TryInto<Error> for &ProposeTxResponse {
+    type Error = &'static str;
+
+    fn try_into(self) -> Result<Error, Self::Error> {
+        let (input_index, ring_element_index, actual, expected) = (
+            self.get_input_index(),
+            self.get_ring_element_index(),
+            self.get_actual(),
+            self.get_expected(),
+        );
+        match self.get_result() {
+            ProposeTxResult::TooManyInputs => Ok(Error::TooManyInputs(actual, expected)),
+            ProposeTxResult::TooManyOutputs => Ok(Error::TooManyOutputs(actual, expected)),
+            ProposeTxResult::TxFeeError => Ok(Error::TxFeeError(actual, expected)),
+            ProposeTxResult::TombstoneBlockTooFar => {
+                Ok(Error::TombstoneBlockTooFar(actual, expected))
+            }
+            ProposeTxResult::TombstoneBlockExceeded => {
+                Ok(Error::TombstoneBlockExceeded(expected, actual))
+            }
+            ProposeTxResult::InsufficientRingSize => {
+                Ok(Error::InsufficientRingSize(input_index, actual, expected))
+            }
+            ProposeTxResult::ExcessiveRingSize => {
+                Ok(Error::ExcessiveRingSize(input_index, actual, expected))
+            }
+            ProposeTxResult::UnsortedRingElements => Ok(Error::UnsortedRingElements(input_index)),
+            ProposeTxResult::MissingTxOutMembershipProof => {
+                Ok(Error::MissingTxOutMembershipProof(input_index))
+            }
+            ProposeTxResult::InvalidTxOutMembershipProof => Ok(Error::InvalidTxOutMembershipProof(
+                input_index,
+                ring_element_index,
+            )),
+            ProposeTxResult::TooManyAdditionalFogHints => {
+                Ok(Error::TooManyAdditionalFogHints(actual, expected))
+            }
+            result => result.try_into(),
         }
     }
 }
 
 /// Convert ProposeTxResult --> TransactionValidationError.
+///
+/// This fails for the results whose errors have context, which only a
+/// ProposeTxResponse has.
 impl TryInto<Error> for ProposeTxResult {
     type Error = &'static str;
 
     fn try_into(self) -> Result<Error, Self::Error> {
         match self {
             Self::Ok => Err("Ok value cannot be convererted into TransactionValidationError"),
+            Self::TooManyInputs
+            | Self::TooManyOutputs
+            | Self::TxFeeError
+            | Self::TombstoneBlockTooFar
+            | Self::TombstoneBlockExceeded
+            | Self::InsufficientRingSize
+            | Self::ExcessiveRingSize
+            | Self::UnsortedRingElements
+            | Self::MissingTxOutMembershipProof
+            | Self::InvalidTxOutMembershipProof
+            | Self::TooManyAdditionalFogHints => {
+                Err("This value needs the context of a ProposeTxResponse to be converted")
+            }
             Self::InputsProofsLengthMismatch => Ok(Error::InputsProofsLengthMismatch),
             Self::NoInputs => Ok(Error::NoInputs),
-            Self::TooManyInputs => Ok(Error::TooManyInputs),
             Self::InsufficientInputSignatures => Ok(Error::InsufficientInputSignatures),
             Self::InvalidInputSignature => Ok(Error::InvalidInputSignature),
             Self::InvalidTransactionSignature => Ok(Error::InvalidTransactionSignature(
                 ring_ct::Error::RingSignature(RingSignatureError::InvalidSignature),
             )),
             Self::InvalidRangeProof => Ok(Error::InvalidRangeProof),
-            Self::InsufficientRingSize => Ok(Error::InsufficientRingSize),
-            Self::TombstoneBlockExceeded => Ok(Error::TombstoneBlockExceeded),
-            Self::TombstoneBlockTooFar => Ok(Error::TombstoneBlockTooFar),
             Self::NoOutputs => Ok(Error::NoOutputs),
-            Self::TooManyOutputs => Ok(Error::TooManyOutputs),
-            Self::ExcessiveRingSize => Ok(Error::ExcessiveRingSize),
             Self::DuplicateRingElements => Ok(Error::DuplicateRingElements),
-            Self::UnsortedRingElements => Ok(Error::UnsortedRingElements),
             Self::UnequalRingSizes => Ok(Error::UnequalRingSizes),
             Self::UnsortedKeyImages => Ok(Error::UnsortedKeyImages),
             Self::ContainsSpentKeyImage => Ok(Error::ContainsSpentKeyImage),
             Self::DuplicateKeyImages => Ok(Error::DuplicateKeyImages),
             Self::DuplicateOutputPublicKey => Ok(Error::DuplicateOutputPublicKey),
             Self::ContainsExistingOutputPublicKey => Ok(Error::ContainsExistingOutputPublicKey),
-            Self::MissingTxOutMembershipProof => Ok(Error::MissingTxOutMembershipProof),
-            Self::InvalidTxOutMembershipProof => Ok(Error::InvalidTxOutMembershipProof),
             Self::InvalidRistrettoPublicKey => Ok(Error::InvalidRistrettoPublicKey),
             Self::InvalidLedgerContext => Ok(Error::InvalidLedgerContext),
             Self::Ledger => Ok(Error::Ledger(String::default())),
             Self::MembershipProofValidationError => Ok(Error::MembershipProofValidationError),
-            Self::TxFeeError => Ok(Error::TxFeeError),
             Self::KeyError => Ok(Error::KeyError),
             Self::UnsortedInputs => Ok(Error::UnsortedInputs),
             Self::MissingMemo => Ok(Error::MissingMemo),
@@ -122,6 +213,7 @@ impl TryInto<Error> for ProposeTxResult {
             Self::InputRuleMaxTombstoneBlockExceeded => {
                 Ok(Error::InputRule(InputRuleError::MaxTombstoneBlockExceeded))
             }
+            Self::AdditionalFogHintsNotAllowed => Ok(Error::AdditionalFogHintsNotAllowed),
         }
     }
 }
@@ -295,6 +387,41 @@ mod conversion_tests {
     use rand_core::SeedableRng;
     use rand_hc::Hc128Rng;
 
+    #[test]
+    // Errors with context keep it when they are converted through a
+    // ProposeTxResponse
+    fn test_convert_transaction_validation_error_through_propose_tx_response() {
+        for err in [
+            Error::TooManyInputs(17, 16),
+            Error::TooManyOutputs(17, 16),
+            Error::InsufficientRingSize(1, 10, 11),
+            Error::ExcessiveRingSize(2, 12, 11),
+            Error::TombstoneBlockExceeded(10, 9),
+            Error::TombstoneBlockTooFar(200, 110),
+            Error::UnsortedRingElements(3),
+            Error::MissingTxOutMembershipProof(4),
+            Error::InvalidTxOutMembershipProof(5, 6),
+            Error::TxFeeError(100, 400),
+            Error::TooManyAdditionalFogHints(5, 4),
+            Error::NoInputs,
+        ] {
+            let response = ProposeTxResponse::from(err.clone());
+            assert_eq!(response.get_result(), ProposeTxResult::from(err.clone()));
+
+            let bytes = response.write_to_bytes().unwrap();
+            let response = ProposeTxResponse::parse_from_bytes(&bytes).unwrap();
+            let converted: Error = (&response).try_into().unwrap();
+            assert_eq!(converted, err);
+        }
+
+        // A ProposeTxResult alone doesn't have the context of the error
+        assert!(TryInto::<Error>::try_into(ProposeTxResult::TooManyInputs).is_err());
+        assert_eq!(
+            TryInto::<Error>::try_into(ProposeTxResult::NoInputs),
+            Ok(Error::NoInputs)
+        );
+    }
+
     #[test]
     fn test_convert_active_mint_config() {
         let mut rng = Hc128Rng::from_seed([1u8; 32]);
@@ -10,7 +10,9 @@ use mc_attest_enclave_api::Error as AttestEnclaveError;
 use mc_crypto_keys::{KeyError, SignatureError};
 use mc_crypto_message_cipher::CipherError as MessageCipherError;
 use mc_sgx_compat::sync::PoisonError;
-use mc_transaction_core::{mint::MintValidationError, validation::TransactionValidationError};
+use mc_transaction_core::{
+    mint::MintValidationError, validation::TransactionValidationError, CompressedTxError,
+};
 use mc_util_serial::{
     decode::Error as RmpDecodeError, encode::Error as RmpEncodeError,
     DecodeError as ProstDecodeError, EncodeError as ProstEncodeError,
@@ -132,6 +134,12 @@ impl From<ProstDecodeError> for Error {
     }
 }
 
+impl From<CompressedTxError> for Error {
+    fn from(_src: CompressedTxError) -> Error {
+        Error::Serialization
+    }
+}
+
 impl From<TransactionValidationError> for Error {
     fn from(src: TransactionValidationError) -> Error {
         Error::MalformedTx(src)
@@ -88,8 +88,8 @@ impl ClientApiService {
         // Cache the transaction. This performs the well-formedness checks.
         let tx_hash = self.tx_manager.insert(tx_context).map_err(|err| {
             if let TxManagerError::TransactionValidation(cause) = &err {
-                counters::TX_VALIDATION_ERROR_COUNTER.inc(&format!("{:?}", cause));
                 let result = ProposeTxResult::from(cause.clone());
+                counters::TX_VALIDATION_ERROR_COUNTER.inc(&format!("{:?}", result));
                 response.set_result(result);
             }
             err
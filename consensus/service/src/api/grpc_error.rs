@@ -6,7 +6,7 @@ use grpcio::{RpcStatus, RpcStatusCode};
 use mc_common::logger::global_log;
 use mc_consensus_api::{
     consensus_client::{MintValidationResult, ProposeMintConfigTxResponse, ProposeMintTxResponse},
-    consensus_common::{ProposeTxResponse, ProposeTxResult},
+    consensus_common::ProposeTxResponse,
 };
 use mc_consensus_enclave::Error as EnclaveError;
 use mc_ledger_db::Error as LedgerError;
@@ -137,11 +137,7 @@ impl From<ConsensusGrpcError> for RpcStatus {
 impl From<ConsensusGrpcError> for Result<ProposeTxResponse, RpcStatus> {
     fn from(src: ConsensusGrpcError) -> Result<ProposeTxResponse, RpcStatus> {
         match src {
-            ConsensusGrpcError::TransactionValidation(err) => {
-                let mut resp = ProposeTxResponse::new();
-                resp.set_result(ProposeTxResult::from(err));
-                Ok(resp)
-            }
+            ConsensusGrpcError::TransactionValidation(err) => Ok(ProposeTxResponse::from(err)),
             _ => Err(RpcStatus::from(src)),
         }
     }
@@ -17,7 +17,7 @@ use mc_common::{
     ResponderId,
 };
 use mc_consensus_api::{
-    consensus_common::ProposeTxResponse,
+    consensus_common::{ProposeTxResponse, ProposeTxResult},
     consensus_peer::{
         ConsensusMsg as GrpcConsensusMsg, ConsensusMsgResponse, ConsensusMsgResult,
         GetLatestMsgResponse, GetTxsRequest, GetTxsResponse, TxHashesNotInCache,
@@ -164,7 +164,8 @@ impl PeerApiService {
                         tx_hash = tx_hash.to_string(),
                         err = format!("{:?}", err)
                     );
-                    counters::TX_VALIDATION_ERROR_COUNTER.inc(&format!("{:?}", err));
+                    counters::TX_VALIDATION_ERROR_COUNTER
+                        .inc(&format!("{:?}", ProposeTxResult::from(err)));
                 }
 
                 Err(err) => {
@@ -187,7 +187,7 @@ mod tests {
             .expect_validate()
             .with(eq(values[1]))
             .return_const(Err(TxManagerError::TransactionValidation(
-                TransactionValidationError::TombstoneBlockExceeded,
+                TransactionValidationError::TombstoneBlockExceeded(1, 1),
             )));
         tx_manager
             .expect_validate()
@@ -272,7 +272,7 @@ mod tests {
             .expect_validate()
             .with(eq(tx_hashes[1]))
             .return_const(Err(TxManagerError::TransactionValidation(
-                TransactionValidationError::TombstoneBlockExceeded,
+                TransactionValidationError::TombstoneBlockExceeded(1, 1),
             )));
         tx_manager
             .expect_validate()
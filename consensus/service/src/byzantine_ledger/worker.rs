@@ -1332,7 +1332,7 @@ mod tests {
                 .expect_validate()
                 .with(eq(*tx_hash))
                 .return_const(Err(TxManagerError::TransactionValidation(
-                    TransactionValidationError::TombstoneBlockExceeded,
+                    TransactionValidationError::TombstoneBlockExceeded(1, 1),
                 )));
         }
 
@@ -1438,7 +1438,7 @@ mod tests {
                 .expect_validate()
                 .with(eq(*tx_hash))
                 .return_const(Err(TxManagerError::TransactionValidation(
-                    TransactionValidationError::TombstoneBlockExceeded,
+                    TransactionValidationError::TombstoneBlockExceeded(1, 1),
                 )));
         }
 
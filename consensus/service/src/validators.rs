@@ -345,7 +345,9 @@ mod is_valid_tests {
 
         assert_eq!(
             untrusted.is_valid(Arc::new(well_formed_tx_context)),
-            Err(TransactionValidationError::TombstoneBlockExceeded),
+            Err(TransactionValidationError::TombstoneBlockExceeded(
+                num_blocks, 17
+            )),
         );
     }
 
@@ -378,7 +380,10 @@ mod is_valid_tests {
 
         assert_eq!(
             untrusted.is_valid(Arc::new(well_formed_tx_context)),
-            Err(TransactionValidationError::TombstoneBlockTooFar),
+            Err(TransactionValidationError::TombstoneBlockTooFar(
+                num_blocks + MAX_TOMBSTONE_BLOCKS + 1,
+                num_blocks + MAX_TOMBSTONE_BLOCKS
+            )),
         );
     }
 
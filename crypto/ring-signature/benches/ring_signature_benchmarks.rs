@@ -0,0 +1,108 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Benchmarks of signing, which is faster with the `precomputed-tables`
+//! feature:
+//!
+//! cargo bench -p mc-crypto-ring-signature --features precomputed-tables
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPrivate, RistrettoPublic};
+use mc_crypto_ring_signature::{generators, CompressedCommitment, ReducedTxOut, RingMLSAG, Scalar};
+use mc_util_from_random::FromRandom;
+use mc_util_test_helper::{RngCore, RngType, SeedableRng};
+
+const RING_SIZE: usize = 11;
+
+fn ring_signature_benchmarks(c: &mut Criterion) {
+    let mut rng = RngType::from_seed([100u8; 32]);
+    let mut group = c.benchmark_group("RingMLSAG");
+
+    let generator = generators(0);
+
+    group.bench_function("PedersenGens::commit", |b| {
+        let value = Scalar::from(rng.next_u64());
+        let blinding = Scalar::random(&mut rng);
+        b.iter(|| generator.commit(value, blinding))
+    });
+
+    let mut message = [0u8; 32];
+    rng.fill_bytes(&mut message);
+
+    let mut ring: Vec<ReducedTxOut> = (1..RING_SIZE)
+        .map(|_| ReducedTxOut {
+            public_key: CompressedRistrettoPublic::from_random(&mut rng),
+            target_key: CompressedRistrettoPublic::from_random(&mut rng),
+            commitment: CompressedCommitment::new(
+                rng.next_u64(),
+                Scalar::random(&mut rng),
+                &generator,
+            ),
+        })
+        .collect();
+
+    // The real input.
+    let onetime_private_key = RistrettoPrivate::from_random(&mut rng);
+    let value = rng.next_u64();
+    let blinding = Scalar::random(&mut rng);
+    let real_index = RING_SIZE / 2;
+    ring.insert(
+        real_index,
+        ReducedTxOut {
+            public_key: CompressedRistrettoPublic::from_random(&mut rng),
+            target_key: CompressedRistrettoPublic::from(RistrettoPublic::from(
+                &onetime_private_key,
+            )),
+            commitment: CompressedCommitment::new(value, blinding, &generator),
+        },
+    );
+    let output_blinding = Scalar::random(&mut rng);
+
+    group.bench_function("::sign", |b| {
+        b.iter(|| {
+            RingMLSAG::sign(
+                &message,
+                &ring,
+                real_index,
+                &onetime_private_key,
+                value,
+                &blinding,
+                &output_blinding,
+                &generator,
+                &mut rng,
+            )
+            .unwrap()
+        })
+    });
+
+    let signature = RingMLSAG::sign(
+        &message,
+        &ring,
+        real_index,
+        &onetime_private_key,
+        value,
+        &blinding,
+        &output_blinding,
+        &generator,
+        &mut rng,
+    )
+    .unwrap();
+    let output_commitment = CompressedCommitment::new(value, output_blinding, &generator);
+
+    group.bench_function("::verify", |b| {
+        b.iter(|| {
+            signature
+                .verify(&message, &ring, &output_commitment)
+                .unwrap()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(100);
+    targets = ring_signature_benchmarks
+}
+
+criterion_main!(benches);
@@ -156,6 +156,30 @@ pub fn recover_public_subaddress_spend_key(
     RistrettoPublic::from(P - Hs * G)
 }
 
+/// Recovers the subaddress spend key D_i that an output was sent to, from the
+/// shared secret of the output.
+///
+/// This computes `P - Hs( r * C ) * G`. The shared secret `r * C` is equal to
+/// `a * R`, so this lets the sender of an output check who it was sent to
+/// without knowing the recipient's view private key.
+///
+/// # Arguments
+/// * `shared_secret` - The output's shared secret `r * C`.
+/// * `tx_out_target_key` - The output's target_key.
+pub fn recover_public_subaddress_spend_key_from_shared_secret(
+    shared_secret: &RistrettoPublic,
+    tx_out_target_key: &RistrettoPublic,
+) -> RistrettoPublic {
+    // `Hs( r * C )`
+    let Hs: Scalar = {
+        let S: &RistrettoPoint = shared_secret.as_ref();
+        hash_to_scalar(*S)
+    };
+
+    let P = tx_out_target_key.as_ref();
+    RistrettoPublic::from(P - Hs * G)
+}
+
 /// Computes the onetime private key `Hs( a * R ) + d`.
 ///
 /// This assumes that the output belongs to the provided private keys.
@@ -314,6 +338,38 @@ mod tests {
         })
     }
 
+    #[test]
+    // The sender should recover the same subaddress spend key from the shared
+    // secret as the recipient does from their view private key.
+    fn test_recover_public_subaddress_spend_key_from_shared_secret() {
+        run_with_several_seeds(|mut rng| {
+            let account: AccountKey = AccountKey::random(&mut rng);
+            let (_c, _d, recipient) = get_subaddress(&account, 7);
+
+            let tx_private_key = RistrettoPrivate::from_random(&mut rng);
+            let (tx_target_key, _tx_public_key) =
+                get_output_public_keys(&tx_private_key, &recipient);
+            let shared_secret = create_shared_secret(recipient.view_public_key(), &tx_private_key);
+
+            assert_eq!(
+                recipient.spend_public_key(),
+                &recover_public_subaddress_spend_key_from_shared_secret(
+                    &shared_secret,
+                    &tx_target_key
+                )
+            );
+
+            let wrong_shared_secret = RistrettoPublic::from_random(&mut rng);
+            assert_ne!(
+                recipient.spend_public_key(),
+                &recover_public_subaddress_spend_key_from_shared_secret(
+                    &wrong_shared_secret,
+                    &tx_target_key
+                )
+            );
+        })
+    }
+
     #[test]
     // Should not panic if the output contains the wrong tx_target_key.
     fn test_recover_public_subaddress_spend_key_wrong_tx_target_key() {
@@ -15,7 +15,7 @@ pub use self::{
     curve_scalar::CurveScalar,
     error::Error,
     key_image::KeyImage,
-    mlsag::{CryptoRngCore, ReducedTxOut, RingMLSAG},
+    mlsag::{CryptoRngCore, MlsagVerification, ReducedTxOut, RingMLSAG},
 };
 
 use crate::domain_separators::HASH_TO_POINT_DOMAIN_TAG;
@@ -26,9 +26,35 @@ use curve25519_dalek::{
 use mc_crypto_hashes::{Blake2b512, Digest};
 use mc_crypto_keys::RistrettoPublic;
 
+#[cfg(feature = "precomputed-tables")]
+use alloc::{boxed::Box, vec::Vec};
+#[cfg(feature = "precomputed-tables")]
+use core::fmt;
+#[cfg(feature = "precomputed-tables")]
+use curve25519_dalek::ristretto::RistrettoBasepointTable;
+
 /// The base point for blinding factors used with all amount commitments
 pub const B_BLINDING: RistrettoPoint = RISTRETTO_BASEPOINT_POINT;
 
+/// Multiply `B_BLINDING`, which is the Ristretto basepoint, by a scalar.
+///
+/// With the `precomputed-tables` feature, this looks up multiples of the
+/// basepoint in a precomputed table, which is several times faster than a
+/// variable-base multiplication, but adds the table (about 30KB) to the binary.
+/// The value base points of `generators` get such tables too, see
+/// `PedersenGens::commit`.
+#[inline]
+pub(crate) fn mul_blinding_basepoint(scalar: &Scalar) -> RistrettoPoint {
+    #[cfg(feature = "precomputed-tables")]
+    {
+        &curve25519_dalek::constants::RISTRETTO_BASEPOINT_TABLE * scalar
+    }
+    #[cfg(not(feature = "precomputed-tables"))]
+    {
+        scalar * B_BLINDING
+    }
+}
+
 /// This is a structure which contains a pair of orthogonal generators for
 /// Pedersen commitments.
 /// This tracks `bulletproofs::PedersenGens`, but we do not import it, to avoid
@@ -39,16 +65,64 @@ pub struct PedersenGens {
     pub B: RistrettoPoint,
     /// Base point corresponding to the blinding factor of a Pedersen commitment
     pub B_blinding: RistrettoPoint,
+    /// The precomputed multiples of `B`, if these are the generators of a
+    /// token id
+    #[cfg(feature = "precomputed-tables")]
+    B_table: Option<BasepointTable>,
 }
 
 impl PedersenGens {
     /// Creates a Pedersen commitment using the value scalar and a blinding
     /// factor.
+    ///
+    /// With the `precomputed-tables` feature, the generators returned by
+    /// `generators` are multiplied using precomputed tables of their multiples.
     pub fn commit(&self, value: Scalar, blinding: Scalar) -> RistrettoPoint {
+        #[cfg(feature = "precomputed-tables")]
+        if let Some(BasepointTable(B_table)) = self.B_table {
+            // The base points are public fields, so they must be checked
+            // against the tables, which is cheap.
+            if B_table.basepoint() == self.B && self.B_blinding == B_BLINDING {
+                return B_table * &value + mul_blinding_basepoint(&blinding);
+            }
+        }
         RistrettoPoint::multiscalar_mul(&[value, blinding], &[self.B, self.B_blinding])
     }
 }
 
+/// A precomputed table of multiples of the value base point of a token id.
+/// These are created once per token id, and never freed.
+#[cfg(feature = "precomputed-tables")]
+#[derive(Clone, Copy)]
+struct BasepointTable(&'static RistrettoBasepointTable);
+
+#[cfg(feature = "precomputed-tables")]
+impl fmt::Debug for BasepointTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("BasepointTable")
+    }
+}
+
+#[cfg(feature = "precomputed-tables")]
+impl BasepointTable {
+    /// Get the table of the value base point `B` of `token_id`, creating it if
+    /// this is the first time. Unlike `generators`, this is not constant-time
+    /// with respect to the token id.
+    fn get(token_id: u64, B: &RistrettoPoint) -> Self {
+        static TABLES: spin::Mutex<Vec<(u64, &'static RistrettoBasepointTable)>> =
+            spin::Mutex::new(Vec::new());
+
+        let mut tables = TABLES.lock();
+        if let Some((_, table)) = tables.iter().find(|(id, _)| *id == token_id) {
+            return Self(*table);
+        }
+        let table: &'static RistrettoBasepointTable =
+            Box::leak(Box::new(RistrettoBasepointTable::create(B)));
+        tables.push((token_id, table));
+        Self(table)
+    }
+}
+
 /// Generators (base points) for Pedersen commitments to amounts.
 ///
 /// For commitment to amount 'v' with blinding 'b', we want 'C = v*H + b*G'
@@ -82,8 +156,11 @@ pub fn generators(token_id: u64) -> PedersenGens {
         hasher.update(buf);
     }
 
+    let B = RistrettoPoint::from_hash(hasher);
     PedersenGens {
-        B: RistrettoPoint::from_hash(hasher),
+        #[cfg(feature = "precomputed-tables")]
+        B_table: Some(BasepointTable::get(token_id, &B)),
+        B,
         B_blinding: B_BLINDING,
     }
 }
@@ -106,4 +183,29 @@ mod tests {
             hash_to_point(&RistrettoPublic::from(RISTRETTO_BASEPOINT_POINT))
         )
     }
+
+    #[test]
+    fn test_mul_blinding_basepoint() {
+        let scalar = Scalar::from(123456789u64);
+        assert_eq!(mul_blinding_basepoint(&scalar), scalar * B_BLINDING);
+
+        let mut gens = generators(3);
+        let value = Scalar::from(42u64);
+        assert_eq!(
+            gens.commit(value, scalar),
+            value * gens.B + scalar * B_BLINDING
+        );
+
+        // The commitment follows changes to the base points.
+        gens.B = generators(4).B;
+        assert_eq!(
+            gens.commit(value, scalar),
+            value * gens.B + scalar * B_BLINDING
+        );
+        gens.B_blinding = generators(5).B;
+        assert_eq!(
+            gens.commit(value, scalar),
+            value * gens.B + scalar * gens.B_blinding
+        );
+    }
 }
@@ -31,6 +31,14 @@ impl TryFrom<&ingest_common::IngestSummary> for mc_fog_types::ingest_common::Ing
         let ingress_pubkey: CompressedRistrettoPublic =
             CompressedRistrettoPublic::try_from(proto_ingest_summary.get_ingress_pubkey())?;
 
+        let peer_sync = if proto_ingest_summary.has_peer_sync() {
+            Some(mc_fog_types::ingest_common::PeerSyncProgress::try_from(
+                proto_ingest_summary.get_peer_sync(),
+            )?)
+        } else {
+            None
+        };
+
         let result = mc_fog_types::ingest_common::IngestSummary {
             ingest_controller_mode,
             next_block_index: proto_ingest_summary.next_block_index,
@@ -40,8 +48,37 @@ impl TryFrom<&ingest_common::IngestSummary> for mc_fog_types::ingest_common::Ing
             kex_rng_version: proto_ingest_summary.kex_rng_version,
             peers: proto_ingest_summary.peers.to_vec(),
             ingest_invocation_id: proto_ingest_summary.ingest_invocation_id,
+            omap_capacity: proto_ingest_summary.omap_capacity,
+            peer_sync,
         };
 
         Ok(result)
     }
 }
+
+impl TryFrom<&ingest_common::PeerSyncProgress> for mc_fog_types::ingest_common::PeerSyncProgress {
+    type Error = ConversionError;
+    fn try_from(proto_peer_sync: &ingest_common::PeerSyncProgress) -> Result<Self, Self::Error> {
+        let stage = match proto_peer_sync.stage {
+            ingest_common::PeerSyncStage::CopyingKey => {
+                mc_fog_types::ingest_common::PeerSyncStage::COPYING_KEY
+            }
+            ingest_common::PeerSyncStage::RefreshingReport => {
+                mc_fog_types::ingest_common::PeerSyncStage::REFRESHING_REPORT
+            }
+            ingest_common::PeerSyncStage::Synced => {
+                mc_fog_types::ingest_common::PeerSyncStage::SYNCED
+            }
+        };
+
+        Ok(mc_fog_types::ingest_common::PeerSyncProgress {
+            peer_uri: proto_peer_sync.peer_uri.clone(),
+            ingress_pubkey: CompressedRistrettoPublic::try_from(
+                proto_peer_sync.get_ingress_pubkey(),
+            )?,
+            stage,
+            attempts: proto_peer_sync.attempts,
+            last_error: proto_peer_sync.last_error.clone(),
+        })
+    }
+}
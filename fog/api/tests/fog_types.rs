@@ -34,6 +34,7 @@ fn fog_view_query_request_round_trip() {
             get_txos: (0..num_txos as usize)
                 .map(|_| <[u8; 32]>::sample(&mut rng).to_vec())
                 .collect(),
+            compress_response: rng.next_u32() % 2 == 0,
         };
         round_trip_message::<mc_fog_types::view::QueryRequest, mc_fog_api::view::QueryRequest>(
             &test_val,
@@ -74,6 +75,7 @@ fn fog_view_query_request_aad_round_trip() {
         let test_val = mc_fog_types::view::QueryRequestAAD {
             start_from_user_event_id: rng.next_u64() as i64,
             start_from_block_index: rng.next_u64(),
+            processed_block_count: rng.next_u64(),
         };
         round_trip_message::<mc_fog_types::view::QueryRequestAAD, mc_fog_api::view::QueryRequestAAD>(
             &test_val,
@@ -89,6 +91,7 @@ fn fog_view_query_request_aad_protobuf_round_trip() {
         let mut test_val = mc_fog_api::view::QueryRequestAAD::new();
         test_val.start_from_user_event_id = rng.next_u64() as i64;
         test_val.start_from_block_index = rng.next_u64();
+        test_val.processed_block_count = rng.next_u64();
 
         round_trip_protobuf_object::<
             mc_fog_api::view::QueryRequestAAD,
@@ -100,6 +103,7 @@ fn fog_view_query_request_aad_protobuf_round_trip() {
         let mut test_val = mc_fog_api::view::QueryRequestAAD::new();
         test_val.start_from_user_event_id = rng.next_u64() as i64;
         test_val.start_from_block_index = rng.next_u64();
+        test_val.processed_block_count = rng.next_u64();
         round_trip_protobuf_object::<
             mc_fog_api::view::QueryRequestAAD,
             mc_fog_types::view::QueryRequestAAD,
@@ -135,6 +139,7 @@ fn fog_view_query_response_round_trip() {
                 .collect(),
             last_known_block_count: rng.next_u32() as u64,
             last_known_block_cumulative_txo_count: rng.next_u32() as u64,
+            compressed_response: Default::default(),
         };
         round_trip_message::<mc_fog_types::view::QueryResponse, mc_fog_api::view::QueryResponse>(
             &test_val,
@@ -158,6 +163,7 @@ fn fog_view_query_response_round_trip() {
                 .collect(),
             last_known_block_count: rng.next_u32() as u64,
             last_known_block_cumulative_txo_count: rng.next_u32() as u64,
+            compressed_response: Default::default(),
         };
         round_trip_message::<mc_fog_types::view::QueryResponse, mc_fog_api::view::QueryResponse>(
             &test_val,
@@ -188,6 +194,7 @@ fn fog_view_query_response_round_trip() {
                 .collect(),
             last_known_block_count: rng.next_u32() as u64,
             last_known_block_cumulative_txo_count: rng.next_u32() as u64,
+            compressed_response: Default::default(),
         };
         round_trip_message::<mc_fog_types::view::QueryResponse, mc_fog_api::view::QueryResponse>(
             &test_val,
@@ -195,6 +202,35 @@ fn fog_view_query_response_round_trip() {
     });
 }
 
+/// Test that compressed QueryResponses decompress to the original, and that
+/// uncompressed ones are left as is
+#[test]
+fn fog_view_query_response_compression_round_trip() {
+    run_with_several_seeds(|mut rng| {
+        let test_val = mc_fog_types::view::QueryResponse {
+            highest_processed_block_count: rng.next_u64(),
+            rng_records: (0..20)
+                .map(|_| mc_fog_types::view::RngRecord::sample(&mut rng))
+                .collect(),
+            tx_out_search_results: (0..40)
+                .map(|_| mc_fog_types::view::TxOutSearchResult::sample(&mut rng))
+                .collect(),
+            last_known_block_count: rng.next_u32() as u64,
+            ..Default::default()
+        };
+
+        let compressed = test_val.compress();
+        assert!(!compressed.compressed_response.is_empty());
+        assert_eq!(compressed.highest_processed_block_count, 0);
+        round_trip_message::<mc_fog_types::view::QueryResponse, mc_fog_api::view::QueryResponse>(
+            &compressed,
+        );
+
+        assert_eq!(compressed.decompress().unwrap(), test_val);
+        assert_eq!(test_val.clone().decompress().unwrap(), test_val);
+    });
+}
+
 /// Test that many random instances of prosty TxOutRecord round trip with
 /// protobufy TxOutRecord
 #[test]
@@ -456,6 +492,7 @@ impl Sample for TxOut {
             public_key: RistrettoPublic::from_random(rng).into(),
             e_fog_hint: EncryptedFogHint::fake_onetime_hint(rng),
             e_memo: Option::<EncryptedMemo>::sample(rng),
+            additional_e_fog_hints: Default::default(),
         }
     }
 }
@@ -0,0 +1,161 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Progress checkpoints for the fog distribution utility
+//!
+//! A checkpoint file records the public keys of the source account TxOuts
+//! which fog distribution has already spent, one hex encoded key per line.
+//! Each line is written and synced as soon as the transaction spending it has
+//! been accepted, so after an interruption a distribution can be resumed from
+//! the checkpoint, without spending (or trying to spend) any TxOut twice.
+//!
+//! Resuming only makes sense with the same sample data and the same options
+//! which select and split the spendable TxOuts (e.g. num_tx_to_send,
+//! start_offset and num_seed_transactions_per_destination_account).
+
+use mc_common::HashSet;
+use mc_crypto_keys::CompressedRistrettoPublic;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::Path,
+    sync::Mutex,
+};
+
+/// The progress of a distribution, backed by a checkpoint file
+pub struct Checkpoint {
+    /// The TxOuts which were already spent when the checkpoint was loaded
+    spent: HashSet<CompressedRistrettoPublic>,
+    /// The checkpoint file, which newly spent TxOuts are appended to
+    file: Mutex<File>,
+}
+
+impl Checkpoint {
+    /// Start a new checkpoint file, discarding any previous progress in it
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let file = File::create(path)?;
+        Ok(Self {
+            spent: Default::default(),
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Load the progress from an existing checkpoint file, and keep appending
+    /// to it. A missing file is treated as an empty checkpoint.
+    pub fn resume(path: &Path) -> io::Result<Self> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+
+        // A partially written last line is ignored. That TxOut is retried, and
+        // reported as already spent by consensus if it was.
+        let spent = contents.lines().filter_map(parse_public_key).collect();
+
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        // Terminate a partially written last line, so that it does not run
+        // into the next recorded public key
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            file.write_all(b"\n")?;
+        }
+
+        Ok(Self {
+            spent,
+            file: Mutex::new(file),
+        })
+    }
+
+    /// The number of TxOuts which were already spent when the checkpoint was
+    /// loaded
+    pub fn num_spent(&self) -> usize {
+        self.spent.len()
+    }
+
+    /// Whether the TxOut with this public key was already spent when the
+    /// checkpoint was loaded
+    pub fn is_spent(&self, tx_out_public_key: &CompressedRistrettoPublic) -> bool {
+        self.spent.contains(tx_out_public_key)
+    }
+
+    /// Record that the TxOuts with these public keys have been spent
+    pub fn record_spent<'a>(
+        &self,
+        tx_out_public_keys: impl IntoIterator<Item = &'a CompressedRistrettoPublic>,
+    ) -> io::Result<()> {
+        let lines: String = tx_out_public_keys
+            .into_iter()
+            .map(|public_key| format!("{}\n", hex::encode(public_key.as_bytes())))
+            .collect();
+
+        let mut file = self.file.lock().expect("mutex poisoned");
+        file.write_all(lines.as_bytes())?;
+        file.sync_data()
+    }
+}
+
+fn parse_public_key(line: &str) -> Option<CompressedRistrettoPublic> {
+    let bytes = hex::decode(line.trim()).ok()?;
+    CompressedRistrettoPublic::try_from(&bytes[..]).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn public_key(byte: u8) -> CompressedRistrettoPublic {
+        CompressedRistrettoPublic::from(&[byte; 32])
+    }
+
+    #[test]
+    fn resume_loads_recorded_tx_outs() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("checkpoint");
+
+        let checkpoint = Checkpoint::create(&path).unwrap();
+        assert_eq!(checkpoint.num_spent(), 0);
+        checkpoint
+            .record_spent(&[public_key(1), public_key(2)])
+            .unwrap();
+        checkpoint.record_spent(&[public_key(3)]).unwrap();
+        drop(checkpoint);
+
+        let checkpoint = Checkpoint::resume(&path).unwrap();
+        assert_eq!(checkpoint.num_spent(), 3);
+        assert!(checkpoint.is_spent(&public_key(1)));
+        assert!(checkpoint.is_spent(&public_key(3)));
+        assert!(!checkpoint.is_spent(&public_key(4)));
+
+        // Resuming appends to the existing progress
+        checkpoint.record_spent(&[public_key(4)]).unwrap();
+        drop(checkpoint);
+        assert_eq!(Checkpoint::resume(&path).unwrap().num_spent(), 4);
+
+        // Creating discards it
+        Checkpoint::create(&path).unwrap();
+        assert_eq!(Checkpoint::resume(&path).unwrap().num_spent(), 0);
+    }
+
+    #[test]
+    fn resume_missing_file_and_partial_line() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("checkpoint");
+
+        assert_eq!(Checkpoint::resume(&path).unwrap().num_spent(), 0);
+
+        let mut contents = format!("{}\n", hex::encode(public_key(5).as_bytes()));
+        contents.push_str("0a0b");
+        std::fs::write(&path, contents).unwrap();
+
+        let checkpoint = Checkpoint::resume(&path).unwrap();
+        assert_eq!(checkpoint.num_spent(), 1);
+        assert!(checkpoint.is_spent(&public_key(5)));
+
+        checkpoint.record_spent(&[public_key(6)]).unwrap();
+        drop(checkpoint);
+
+        let checkpoint = Checkpoint::resume(&path).unwrap();
+        assert_eq!(checkpoint.num_spent(), 2);
+        assert!(checkpoint.is_spent(&public_key(6)));
+    }
+}
@@ -69,6 +69,12 @@ pub struct Config {
     #[clap(long, default_value = "32", env = "MC_MAX_THREADS")]
     pub max_threads: usize,
 
+    /// Maximum number of spendable TxOuts, and of transactions, queued ahead
+    /// of the worker threads. This bounds the memory used, however many
+    /// TxOuts and destination accounts there are.
+    #[clap(long, default_value = "1000", env = "MC_QUEUE_SIZE")]
+    pub queue_size: usize,
+
     /// Delay (in milliseconds) before each add_transaction call
     #[clap(long, default_value = "0", env = "MC_ADD_TX_DELAY_MS")]
     pub add_tx_delay_ms: u64,
@@ -89,6 +95,17 @@ pub struct Config {
     /// ledger)
     #[clap(long)]
     pub dry_run: bool,
+
+    /// File in which to record the TxOuts which have been spent, so that an
+    /// interrupted distribution can be resumed with --resume
+    #[clap(long, parse(from_os_str), env = "MC_CHECKPOINT_FILE")]
+    pub checkpoint_file: Option<PathBuf>,
+
+    /// Resume from the progress recorded in the checkpoint file, skipping the
+    /// TxOuts which were already spent. This requires the same sample data
+    /// and options as the interrupted distribution.
+    #[clap(long, requires = "checkpoint-file")]
+    pub resume: bool,
 }
 
 impl Config {
@@ -7,6 +7,7 @@
 
 #![deny(missing_docs)]
 
+pub mod checkpoint;
 pub mod config;
 
-pub use crate::config::Config;
+pub use crate::{checkpoint::Checkpoint, config::Config};
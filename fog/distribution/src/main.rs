@@ -16,6 +16,15 @@
 //! Tx's rather than checking to see if they land, once it is in the slam step.
 //!
 //! Fog distro guarantees to pay each destination account at least once.
+//!
+//! The spendable TxOuts are streamed from the ledger through bounded queues,
+//! seeding the destination accounts one transaction at a time, and then
+//! feeding a pool of worker threads for the slam step, so the memory used does
+//! not grow with the number of TxOuts.
+//!
+//! With a checkpoint file, fog distro records each TxOut it spends, and an
+//! interrupted distribution can be resumed with --resume, skipping the TxOuts
+//! which were already spent.
 
 #![deny(missing_docs)]
 
@@ -25,15 +34,15 @@ use mc_account_keys::AccountKey;
 use mc_attest_verifier::{Verifier, DEBUG_ENCLAVE};
 use mc_common::{
     logger::{create_app_logger, log, o, Logger},
-    HashMap, HashSet,
+    HashSet,
 };
 use mc_connection::{
     Error as ConnectionError, HardcodedCredentialsProvider, RetryError,
     RetryableBlockchainConnection, RetryableUserTxConnection, SyncConnection, ThickClient,
 };
-use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPublic};
+use mc_crypto_keys::RistrettoPublic;
 use mc_crypto_ring_signature_signer::NoKeysRingSigner;
-use mc_fog_distribution::Config;
+use mc_fog_distribution::{Checkpoint, Config};
 use mc_fog_report_connection::{Error as ReportConnError, GrpcFogReportConnection};
 use mc_fog_report_validation::FogResolver;
 use mc_ledger_db::{Ledger, LedgerDB};
@@ -59,7 +68,7 @@ use std::{
     str::FromStr,
     sync::{
         atomic::{AtomicU32, AtomicU64, Ordering},
-        Arc, Mutex,
+        Arc,
     },
     thread,
     time::Duration,
@@ -99,10 +108,6 @@ lazy_static! {
 
     /// Keeps track of the current MOB fee value
     pub static ref MOB_FEE: AtomicU64 = AtomicU64::default();
-
-    /// A map of tx pub keys to account index. This is used in conjunction with ledger syncing to
-    /// identify which new txs belong to which accounts without having to do any slow crypto.
-    pub static ref TX_PUB_KEY_TO_ACCOUNT_KEY: Mutex<HashMap::<CompressedRistrettoPublic, AccountKey>> = Mutex::new(HashMap::default());
 }
 
 /// A TxOut found from the bootstrapped ledger that we can spend
@@ -115,6 +120,17 @@ struct SpendableTxOut {
     pub from_account_key: AccountKey,
 }
 
+/// A transaction for a worker thread to build and submit
+struct Job {
+    /// The number of the transaction, which spreads submissions across the
+    /// consensus nodes
+    index: usize,
+    /// The tx outs to spend
+    inputs: Vec<SpendableTxOut>,
+    /// The index of the destination account to pay
+    destination: usize,
+}
+
 fn main() {
     mc_common::setup_panic_handler();
     let (logger, _global_logger_guard) = create_app_logger(o!());
@@ -127,12 +143,15 @@ fn main() {
     )
     .expect("Could not read default mnemonics from keys");
 
-    let dest_accounts: Vec<AccountKey> = mc_util_keyfile::keygen::read_default_keyfiles(
-        config
-            .sample_data_dir
-            .join(Path::new(&config.fog_keys_subdir)),
-    )
-    .expect("Could not read fog keys");
+    // The destination accounts are shared by all the worker threads
+    let dest_accounts: Arc<Vec<AccountKey>> = Arc::new(
+        mc_util_keyfile::keygen::read_default_keyfiles(
+            config
+                .sample_data_dir
+                .join(Path::new(&config.fog_keys_subdir)),
+        )
+        .expect("Could not read fog keys"),
+    );
 
     // Open the ledger_db to process the bootstrapped ledger
     log::info!(logger, "Loading ledger");
@@ -174,35 +193,55 @@ fn main() {
         Ordering::SeqCst,
     );
 
-    // Load the bootstrapped transactions.
-    let spendable_tx_outs = select_spendable_tx_outs(&ledger_db, &config, src_accounts, &logger);
-
-    // Count how many of each token type
-    {
-        let mut token_count: BTreeMap<TokenId, usize> = Default::default();
-        for tx_out in &spendable_tx_outs {
-            *token_count.entry(tx_out.amount.token_id).or_default() += 1;
+    // Load or start the checkpoint of the distribution's progress
+    let checkpoint = config.checkpoint_file.as_ref().map(|path| {
+        let checkpoint = if config.resume {
+            Checkpoint::resume(path)
+        } else {
+            Checkpoint::create(path)
         }
-
+        .unwrap_or_else(|err| panic!("Could not open checkpoint file {:?}: {}", path, err));
         log::info!(
             logger,
-            "Loaded {} spendable tx outs",
-            spendable_tx_outs.len()
+            "Checkpoint {:?} has {} spent tx outs",
+            path,
+            checkpoint.num_spent()
         );
-        for (token_id, count) in token_count {
-            log::info!(logger, "{}: {} tx outs", token_id, count);
-        }
-    }
+        Arc::new(checkpoint)
+    });
+    let is_spent = |spendable_tx_out: &SpendableTxOut| {
+        checkpoint.as_ref().map_or(false, |checkpoint| {
+            checkpoint.is_spent(&spendable_tx_out.tx_out.public_key)
+        })
+    };
 
-    // If we got this far and it's a dry-run, end successfully
+    // If it's a dry-run, load the bootstrapped transactions and end successfully
     if config.dry_run {
+        let token_count =
+            select_spendable_tx_outs(&ledger_db, &config, &src_accounts, &logger, |_| {});
+        log_token_count(&token_count, &logger);
         return;
     }
 
-    // A channel to load with spendable tx outs, where worker threads will grab them
-    // from.
+    // A bounded channel which a scanner thread loads with the spendable tx outs
+    // of the bootstrapped ledger, as the main thread takes them.
     let (spendable_txouts_sender, spendable_txouts_receiver) =
-        crossbeam_channel::unbounded::<SpendableTxOut>();
+        crossbeam_channel::bounded::<SpendableTxOut>(config.queue_size);
+    let scanner = {
+        let ledger_db = ledger_db.clone();
+        let config = config.clone();
+        let logger = logger.clone();
+        thread::Builder::new()
+            .name("scanner".to_string())
+            .spawn(move || {
+                select_spendable_tx_outs(&ledger_db, &config, &src_accounts, &logger, |tx_out| {
+                    spendable_txouts_sender
+                        .send(tx_out)
+                        .expect("failed sending to spendable_txouts_sender")
+                })
+            })
+            .expect("failed starting thread")
+    };
 
     let env = Arc::new(
         grpcio::EnvBuilder::new()
@@ -218,36 +257,37 @@ fn main() {
     )
     .expect("Could not parse fog url");
 
-    // A channel for worker threads to communicate when they have finished
-    let (running_threads_sender, running_threads_receiver) =
-        crossbeam_channel::unbounded::<usize>();
-
-    let mut running_threads: usize = 0;
-
     // Seed each fog account with a TxOut. This ensures that integration tests
     // that check to make sure each fog account has a non-zero balance do not
-    // fail.
+    // fail. The seed transactions are submitted one at a time, before the slam
+    // step starts.
     let mut seed_fog_resolver = build_fog_resolver(&fog_uri, &env, &logger);
     let conns = get_conns(&config, &logger);
 
-    // Split tx outs into a group for the seed step and a group for the slam step
-    let (seed_tx_outs, slam_tx_outs) = spendable_tx_outs
-        .split_at(dest_accounts.len() * config.num_seed_transactions_per_destination_account);
-
     log::info!(
         logger,
         "Seeding Fog Accounts with {} initial TxOuts.",
-        seed_tx_outs.len()
+        dest_accounts.len() * config.num_seed_transactions_per_destination_account
     );
+    let mut num_jobs: usize = 0;
     for (i, fog_account) in dest_accounts.iter().enumerate() {
-        // We now send this account the next j seed tx outs, looping infinitely
+        // We now send this account the next seed tx outs, looping infinitely
         // until success
-        for j in 0..config.num_seed_transactions_per_destination_account {
-            let idx = i * config.num_seed_transactions_per_destination_account + j;
+        for _ in 0..config.num_seed_transactions_per_destination_account {
+            let spendable_txout = spendable_txouts_receiver
+                .recv()
+                .expect("Not enough spendable tx outs to seed the fog accounts");
+            let idx = num_jobs;
+            num_jobs += 1;
+            // When resuming, this account already got the seed tx outs which
+            // were spent
+            if is_spent(&spendable_txout) {
+                continue;
+            }
             seed_fog_resolver = build_and_submit_transaction(
                 idx,
                 // For this seed phase, only use one TxOut for each transaction.
-                vec![seed_tx_outs[idx].clone()],
+                vec![spendable_txout],
                 fog_account,
                 &config,
                 &ledger_db,
@@ -256,6 +296,7 @@ fn main() {
                 &conns,
                 &env,
                 &fog_uri,
+                checkpoint.as_deref(),
             );
         }
         log::info!(
@@ -266,52 +307,95 @@ fn main() {
         );
     }
 
-    // Submit remaining tx outs to the crossbeam queue where the worker threads will
-    // find them. Don't use spendable_txouts that were used in the seed step.
-    for spendable_txout in slam_tx_outs {
-        spendable_txouts_sender
-            .send(spendable_txout.clone())
-            .expect("failed sending to spendable_txouts_sender");
-    }
+    // A bounded channel which the main thread loads with transactions to build
+    // and submit, where worker threads will grab them from.
+    let (jobs_sender, jobs_receiver) = crossbeam_channel::bounded::<Job>(config.queue_size);
 
-    log::info!(logger, "Spawning workers for slam step");
+    log::info!(
+        logger,
+        "Spawning {} workers for slam step",
+        config.max_threads
+    );
 
     // Spawn worker threads
-    for i in 0..config.max_threads {
-        let spendable_txouts_receiver2 = spendable_txouts_receiver.clone();
-        let running_threads_sender2 = running_threads_sender.clone();
-        let config2 = config.clone();
-        let ledger_db2 = ledger_db.clone();
-        let dest_accounts2 = dest_accounts.clone();
-        let logger2 = logger.new(o!("num" => i));
-        let env2 = env.clone();
-        let fog_resolver = build_fog_resolver(&fog_uri, &env2, &logger);
+    let workers: Vec<_> = (0..config.max_threads)
+        .map(|i| {
+            let jobs_receiver2 = jobs_receiver.clone();
+            let config2 = config.clone();
+            let ledger_db2 = ledger_db.clone();
+            let dest_accounts2 = dest_accounts.clone();
+            let logger2 = logger.new(o!("num" => i));
+            let env2 = env.clone();
+            let fog_uri2 = fog_uri.clone();
+            let checkpoint2 = checkpoint.clone();
+            let fog_resolver = build_fog_resolver(&fog_uri, &env2, &logger);
+
+            thread::Builder::new()
+                .name(format!("worker{}", i))
+                .spawn(move || {
+                    worker_thread_entry(
+                        jobs_receiver2,
+                        config2,
+                        ledger_db2,
+                        dest_accounts2,
+                        fog_resolver,
+                        logger2,
+                        env2,
+                        fog_uri2,
+                        checkpoint2,
+                    )
+                })
+                .expect("failed starting thread")
+        })
+        .collect();
+    drop(jobs_receiver);
 
-        thread::Builder::new()
-            .name(format!("worker{}", i))
-            .spawn(move || {
-                worker_thread_entry(
-                    spendable_txouts_receiver2,
-                    running_threads_sender2,
-                    config2,
-                    ledger_db2,
-                    dest_accounts2,
-                    fog_resolver,
-                    logger2,
-                    env2,
-                )
-            })
-            .expect("failed starting thread");
+    // Queue the remaining tx outs, paying the destination accounts in turn.
+    // Don't use spendable_txouts that were spent before resuming.
+    log::info!(logger, "Queueing transactions for slam step");
+    let mut destination = 0;
+    loop {
+        let mut inputs = Vec::with_capacity(config.num_inputs);
+        while inputs.len() < config.num_inputs {
+            match spendable_txouts_receiver.recv() {
+                Ok(spendable_txout) if is_spent(&spendable_txout) => {}
+                Ok(spendable_txout) => inputs.push(spendable_txout),
+                Err(_) => break,
+            }
+        }
+        if inputs.len() < config.num_inputs {
+            log::debug!(logger, "No more inputs");
+            break;
+        }
 
-        running_threads += 1;
+        jobs_sender
+            .send(Job {
+                index: num_jobs,
+                inputs,
+                destination,
+            })
+            .expect("failed sending to jobs_sender");
+        num_jobs += 1;
+        destination = (destination + 1) % dest_accounts.len();
     }
+    drop(jobs_sender);
 
-    log::info!(logger, "Main thread entering infinite loop");
-    while running_threads > 0 {
-        let _thread_died = running_threads_receiver.recv().unwrap();
-        running_threads -= 1;
+    let token_count = scanner.join().expect("scanner thread panicked");
+    log_token_count(&token_count, &logger);
 
-        log::info!(logger, "A thread finished, {} remaining", running_threads);
+    log::info!(
+        logger,
+        "Queued {} transactions, waiting for workers to finish",
+        num_jobs
+    );
+    let num_workers = workers.len();
+    for (i, worker) in workers.into_iter().enumerate() {
+        worker.join().expect("worker thread panicked");
+        log::info!(
+            logger,
+            "A thread finished, {} remaining",
+            num_workers - i - 1
+        );
     }
 
     log::info!(logger, "Done!");
@@ -320,7 +404,20 @@ fn main() {
     thread::sleep(Duration::from_secs(1));
 }
 
-/// Reads TxOut's from the ledger, adding them to a queue of spendable TxOuts:
+/// Log how many spendable tx outs of each token there are
+fn log_token_count(token_count: &BTreeMap<TokenId, usize>, logger: &Logger) {
+    log::info!(
+        logger,
+        "Loaded {} spendable tx outs",
+        token_count.values().sum::<usize>()
+    );
+    for (token_id, count) in token_count {
+        log::info!(logger, "{}: {} tx outs", token_id, count);
+    }
+}
+
+/// Reads TxOut's from the ledger, passing them to on_tx_out as spendable
+/// TxOuts as they are found:
 /// * Confirms that they are owned by the expected owner
 /// * Notes their amount and token id
 ///
@@ -329,8 +426,8 @@ fn main() {
 ///   (src_accounts)
 /// * We either discover that number from the ledger, or take it as config
 ///
-/// Returns all the tx outs we matched, their amounts, and the account that owns
-/// them.
+/// Each tx out we matched comes with its amount, and the account that owns it.
+/// Returns how many tx outs of each token we matched.
 ///
 /// Arguments:
 /// * ledger_db: The ledger db to read tx outs from
@@ -343,16 +440,18 @@ fn main() {
 ///       each block
 /// * src_accounts: The source accounts which currently own the TxOut's in the
 ///   ledger db
+/// * on_tx_out: Called with each spendable tx out, in ledger order
 fn select_spendable_tx_outs(
     ledger_db: &LedgerDB,
     config: &Config,
-    src_accounts: Vec<AccountKey>,
+    src_accounts: &[AccountKey],
     logger: &Logger,
-) -> Vec<SpendableTxOut> {
+    mut on_tx_out: impl FnMut(SpendableTxOut),
+) -> BTreeMap<TokenId, usize> {
     log::info!(logger, "Processing transactions");
     let mut num_transactions_per_account = config.num_transactions_per_source_account;
 
-    let mut spendable_tx_outs: Vec<SpendableTxOut> = Vec::new();
+    let mut token_count: BTreeMap<TokenId, usize> = Default::default();
 
     let mut block_count = 0;
     while let Ok(block_contents) = ledger_db.get_block_contents(block_count) {
@@ -426,8 +525,8 @@ fn select_spendable_tx_outs(
                     amount,
                 );
 
-                // Push to queue
-                spendable_tx_outs.push(SpendableTxOut {
+                *token_count.entry(amount.token_id).or_default() += 1;
+                on_tx_out(SpendableTxOut {
                     tx_out: tx_out.clone(),
                     amount,
                     from_account_key: account.clone(),
@@ -438,7 +537,7 @@ fn select_spendable_tx_outs(
         block_count += 1;
     }
 
-    spendable_tx_outs
+    token_count
 }
 
 /// Make a request to fog report server, return fog resolver object
@@ -484,57 +583,30 @@ fn build_fog_resolver(
     FogResolver::new(responses, &report_verifier).expect("Could not get FogResolver")
 }
 
-/// Entry point for a worker thread which tries to pull spendable tx outs rom
-/// queue and then build and submit transactions from them.
+/// Entry point for a worker thread which pulls jobs from the queue and then
+/// builds and submits their transactions, until the queue is closed.
 fn worker_thread_entry(
-    spendable_txouts_receiver: crossbeam_channel::Receiver<SpendableTxOut>,
-    running_threads_sender: crossbeam_channel::Sender<usize>,
+    jobs_receiver: crossbeam_channel::Receiver<Job>,
     config: Config,
     ledger_db: LedgerDB,
-    dest_accounts: Vec<AccountKey>,
+    dest_accounts: Arc<Vec<AccountKey>>,
     mut fog_resolver: FogResolver,
     logger: Logger,
     env: Arc<grpcio::Environment>,
+    fog_uri: FogUri,
+    checkpoint: Option<Arc<Checkpoint>>,
 ) {
     log::info!(logger, "Worker started.");
-    let mut txs_created: usize = 0;
 
     let mut conns = get_conns(&config, &logger);
     conns.shuffle(&mut thread_rng());
 
-    loop {
-        let mut pending_spendable_txouts = Vec::<SpendableTxOut>::new();
-        while pending_spendable_txouts.len() < config.num_inputs {
-            log::trace!(
-                logger,
-                "Waiting for {} more inputs",
-                config.num_inputs - pending_spendable_txouts.len()
-            );
-
-            match spendable_txouts_receiver.try_recv() {
-                Ok(tx_out) => pending_spendable_txouts.push(tx_out),
-                Err(_) => {
-                    log::debug!(logger, "No more inputs kill thread");
-
-                    running_threads_sender.send(1).unwrap();
-                    return;
-                }
-            }
-        }
-
-        // Send to the next fog account
-        let to_account = &dest_accounts[txs_created % dest_accounts.len()];
-        let fog_uri = FogUri::from_str(
-            dest_accounts[0]
-                .default_subaddress()
-                .fog_report_url()
-                .expect("No fog report url"),
-        )
-        .expect("Could not parse fog url");
+    for job in jobs_receiver.iter() {
+        let to_account = &dest_accounts[job.destination];
 
         fog_resolver = build_and_submit_transaction(
-            txs_created,
-            pending_spendable_txouts,
+            job.index,
+            job.inputs,
             to_account,
             &config,
             &ledger_db,
@@ -543,9 +615,11 @@ fn worker_thread_entry(
             &conns,
             &env,
             &fog_uri,
+            checkpoint.as_deref(),
         );
-        txs_created += 1;
     }
+
+    log::debug!(logger, "No more jobs, worker finished");
 }
 
 /// Builds and submits a transaction to a given FogAccount.
@@ -555,6 +629,9 @@ fn worker_thread_entry(
 /// to build and submit transactions. In this case, we return this new
 /// FogResolver to the caller so that it can be used in subsequent transactions.
 /// If a transaction error doesn't occur, we return the old FogResolver.
+///
+/// Once the transaction is submitted, its inputs are recorded as spent in the
+/// checkpoint, if there is one.
 fn build_and_submit_transaction(
     txs_created: usize,
     pending_spendable_txouts: Vec<SpendableTxOut>,
@@ -566,6 +643,7 @@ fn build_and_submit_transaction(
     conns: &[SyncConnection<ThickClient<HardcodedCredentialsProvider>>],
     env: &Arc<grpcio::Environment>,
     fog_uri: &FogUri,
+    checkpoint: Option<&Checkpoint>,
 ) -> FogResolver {
     // Sometimes transactions can not be submitted before the tombstone block
     // has passed, so loop until transactions can be submmitted
@@ -581,8 +659,15 @@ fn build_and_submit_transaction(
         );
 
         if submit_tx(txs_created, conns, &tx, config, logger) {
-            let mut map = TX_PUB_KEY_TO_ACCOUNT_KEY.lock().unwrap();
-            map.insert(tx.prefix.outputs[0].public_key, to_account.clone());
+            if let Some(checkpoint) = checkpoint {
+                checkpoint
+                    .record_spent(
+                        pending_spendable_txouts
+                            .iter()
+                            .map(|spendable_txout| &spendable_txout.tx_out.public_key),
+                    )
+                    .expect("Could not write to checkpoint file");
+            }
             return current_fog_resolver;
         } else {
             // If submit fails, trash and rebuild the FogResolver to ensure it's
@@ -642,7 +727,7 @@ fn submit_tx(
                 tries,
             }) => {
                 if let ConnectionError::TransactionValidation(
-                    TransactionValidationError::TombstoneBlockExceeded,
+                    TransactionValidationError::TombstoneBlockExceeded(..),
                 ) = error
                 {
                     log::debug!(
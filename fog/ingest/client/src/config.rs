@@ -6,7 +6,7 @@
 use clap::{Parser, Subcommand};
 use mc_crypto_keys::CompressedRistrettoPublic;
 use mc_util_parse::parse_duration_in_seconds;
-use std::time::Duration;
+use std::{path::PathBuf, time::Duration};
 
 /// Configuration parameters for the Fog ingest client
 #[derive(Clone, Debug, Parser)]
@@ -84,6 +84,31 @@ pub enum IngestConfigCommand {
         peer_uri: String,
     },
 
+    /// Retrieves a private key from a remote ingest enclave and seals it to
+    /// the current enclave as a backup, which is written to a file.
+    /// The current enclave's private key is not changed.
+    BackupIngressKeyFromRemote {
+        /// The Fog Ingest Peer URI of the server whose key is backed up.
+        #[clap(env = "MC_PEER_URI")]
+        peer_uri: String,
+
+        /// The file to write the backup to.
+        #[clap(long, short, env = "MC_BACKUP_PATH")]
+        backup_path: PathBuf,
+    },
+
+    /// Retrieves a private key from a backup sealed by a remote ingest
+    /// enclave, and sets it as the current enclave's private key.
+    RestoreIngressKeyFromBackup {
+        /// The Fog Ingest Peer URI of the server which made the backup.
+        #[clap(env = "MC_PEER_URI")]
+        peer_uri: String,
+
+        /// The file to read the backup from.
+        #[clap(long, short, env = "MC_BACKUP_PATH")]
+        backup_path: PathBuf,
+    },
+
     ///  Retrieves the ingress public keys for the entire system (as opposed to
     ///  those of a single IngestServer) and filters according to the provided
     ///  parameters.
@@ -10,7 +10,8 @@ use mc_crypto_keys::CompressedRistrettoPublic;
 use mc_fog_api::{
     empty::Empty,
     ingest::{
-        GetIngressKeyRecordsRequest, IngressPublicKeyRecord, ReportLostIngressKeyRequest,
+        BackupIngressKeyFromRemoteRequest, GetIngressKeyRecordsRequest, IngressKeyBackup,
+        IngressPublicKeyRecord, ReportLostIngressKeyRequest, RestoreIngressKeyFromBackupRequest,
         SetPubkeyExpiryWindowRequest, SyncKeysFromRemoteRequest,
     },
     ingest_common::{IngestSummary, SetPeersRequest},
@@ -196,6 +197,38 @@ impl FogIngestGrpcClient {
         })
     }
 
+    pub fn backup_ingress_key_from_remote(
+        &self,
+        peer_uri: String,
+    ) -> ClientResult<IngressKeyBackup> {
+        log::trace!(self.logger, "backup_ingress_key_from_remote()");
+        let mut req = BackupIngressKeyFromRemoteRequest::new();
+        req.set_peer_uri(peer_uri);
+
+        retry(self.get_retries(), || -> Result<_, Error> {
+            Ok(self
+                .ingest_api_client
+                .backup_ingress_key_from_remote_opt(&req, self.creds.call_option()?)?)
+        })
+    }
+
+    pub fn restore_ingress_key_from_backup(
+        &self,
+        peer_uri: String,
+        backup: IngressKeyBackup,
+    ) -> ClientResult<IngestSummary> {
+        log::trace!(self.logger, "restore_ingress_key_from_backup()");
+        let mut req = RestoreIngressKeyFromBackupRequest::new();
+        req.set_peer_uri(peer_uri);
+        req.set_backup(backup);
+
+        retry(self.get_retries(), || -> Result<_, Error> {
+            Ok(self
+                .ingest_api_client
+                .restore_ingress_key_from_backup_opt(&req, self.creds.call_option()?)?)
+        })
+    }
+
     pub fn get_ingress_key_records(
         &self,
         start_block_at_least: u64,
@@ -4,15 +4,16 @@
 
 use mc_common::logger::{create_root_logger, log, Logger};
 use mc_crypto_keys::CompressedRistrettoPublic;
-use mc_fog_api::ingest_common::IngestSummary;
+use mc_fog_api::{ingest::IngressKeyBackup, ingest_common::IngestSummary};
 use mc_fog_ingest_client::{
     config::{IngestConfig, IngestConfigCommand},
     ClientResult, FogIngestGrpcClient,
 };
 use mc_fog_uri::FogIngestUri;
 use mc_util_cli::ParserWithBuildInfo;
+use protobuf::Message;
 use serde_json::{json, to_string_pretty};
-use std::{str::FromStr, sync::Arc};
+use std::{fs, path::Path, str::FromStr, sync::Arc};
 
 fn main() -> ClientResult<()> {
     // Logging must go to stderr to not interfere with STDOUT
@@ -57,6 +58,16 @@ fn main() -> ClientResult<()> {
             sync_keys_from_remote(&logger, &ingest_client, peer_uri)
         }
 
+        IngestConfigCommand::BackupIngressKeyFromRemote {
+            peer_uri,
+            backup_path,
+        } => backup_ingress_key_from_remote(&logger, &ingest_client, peer_uri, &backup_path),
+
+        IngestConfigCommand::RestoreIngressKeyFromBackup {
+            peer_uri,
+            backup_path,
+        } => restore_ingress_key_from_backup(&logger, &ingest_client, peer_uri, &backup_path),
+
         IngestConfigCommand::GetIngressPublicKeyRecords {
             start_block_at_least,
             should_include_lost_keys,
@@ -182,6 +193,56 @@ fn sync_keys_from_remote(
     Ok(())
 }
 
+fn backup_ingress_key_from_remote(
+    logger: &Logger,
+    ingest_client: &FogIngestGrpcClient,
+    peer_uri: String,
+    backup_path: &Path,
+) -> ClientResult<()> {
+    let backup = ingest_client
+        .backup_ingress_key_from_remote(peer_uri)
+        .expect("rpc failed");
+    let backup_bytes = backup.write_to_bytes().expect("Failed serializing backup");
+    fs::write(backup_path, backup_bytes).expect("Failed writing backup");
+
+    let ingress_pubkey = hex::encode(backup.get_ingress_public_key().get_data());
+    log::info!(
+        logger,
+        "Ingress key {} backed up to {:?}",
+        ingress_pubkey,
+        backup_path
+    );
+    println!(
+        "{}",
+        to_string_pretty(&json!({ "ingress_pubkey": ingress_pubkey }))
+            .expect("could not pretty print")
+    );
+    Ok(())
+}
+
+fn restore_ingress_key_from_backup(
+    logger: &Logger,
+    ingest_client: &FogIngestGrpcClient,
+    peer_uri: String,
+    backup_path: &Path,
+) -> ClientResult<()> {
+    let backup_bytes = fs::read(backup_path).expect("Failed reading backup");
+    let backup = IngressKeyBackup::parse_from_bytes(&backup_bytes).expect("Failed parsing backup");
+    let expected_pubkey = backup.get_ingress_public_key().get_data().to_vec();
+
+    let status = ingest_client
+        .restore_ingress_key_from_backup(peer_uri, backup)
+        .expect("rpc failed");
+    log::info!(logger, "Done, status: {:?}", status);
+    assert_eq!(
+        status.get_ingress_pubkey().get_data(),
+        &expected_pubkey[..],
+        "Restored ingress key does not match the backup"
+    );
+    println!("{}", ingest_summary_to_json(&status));
+    Ok(())
+}
+
 fn get_ingress_key_records(
     logger: &Logger,
     ingest_client: &FogIngestGrpcClient,
@@ -220,6 +281,16 @@ fn get_ingress_key_records(
 }
 
 fn ingest_summary_to_json(summary: &IngestSummary) -> String {
+    let peer_sync = summary.has_peer_sync().then(|| {
+        let peer_sync = summary.get_peer_sync();
+        json!({
+            "peer_uri": peer_sync.peer_uri,
+            "ingress_pubkey": hex::encode(peer_sync.get_ingress_pubkey().get_data()),
+            "stage": format!("{:?}", peer_sync.stage),
+            "attempts": peer_sync.attempts,
+            "last_error": peer_sync.last_error,
+        })
+    });
     to_string_pretty(&json!({
         "mode": format!("{:?}", summary.mode),
         "next_block_index": summary.next_block_index,
@@ -229,6 +300,7 @@ fn ingest_summary_to_json(summary: &IngestSummary) -> String {
         "kex_rng_version": summary.kex_rng_version,
         "peers": summary.get_peers(),
         "ingest_invocation_id": summary.ingest_invocation_id,
+        "peer_sync": peer_sync,
     }))
     .expect("could not pretty print")
 }
@@ -84,6 +84,25 @@ pub trait IngestEnclave: ReportableEnclave {
         msg: EnclaveMessage<PeerSession>,
     ) -> Result<SetIngressPrivateKeyResult>;
 
+    /// Seal the private key of a peer enclave, encrypted by the peer for this
+    /// enclave, as a backup. This does not change the ingress private key of
+    /// this enclave.
+    /// The public key corresponding to the backed up key is also returned.
+    fn seal_ingress_key_backup(
+        &self,
+        msg: EnclaveMessage<PeerSession>,
+    ) -> Result<(SealedIngestKey, CompressedRistrettoPublic)>;
+
+    /// Unseal a backup made by `seal_ingress_key_backup`, and encrypt it for
+    /// the peer, so that the peer can restore it with
+    /// `set_ingress_private_key`.
+    /// The public key corresponding to the backed up key is also returned.
+    fn get_ingress_key_from_backup(
+        &self,
+        sealed_key: SealedIngestKey,
+        peer: PeerSession,
+    ) -> Result<(EnclaveMessage<PeerSession>, CompressedRistrettoPublic)>;
+
     /// Retrieve the current KexRngPubkey for the enclave. This corresponds to
     /// the egress key.
     fn get_kex_rng_pubkey(&self) -> Result<KexRngPubkey>;
@@ -56,6 +56,18 @@ pub enum EnclaveCall {
     /// peer) of this service.
     SetIngressPrivateKey(EnclaveMessage<PeerSession>),
 
+    /// The [IngestEnclave::seal_ingress_key_backup()] method.
+    ///
+    /// Seals the ingress private key (passed as attest::Message from attested
+    /// peer) of another service as a backup.
+    SealIngressKeyBackup(EnclaveMessage<PeerSession>),
+
+    /// The [IngestEnclave::get_ingress_key_from_backup()] method.
+    ///
+    /// Retrieves a sealed backup of an ingress private key, encrypted for an
+    /// attested peer.
+    GetIngressKeyFromBackup(SealedIngestKey, PeerSession),
+
     /// The [IngestEnclave::get_kex_rng_pubkey()] method.
     ///
     /// Retrieves the KexRngPubkey object, containing public key of egress key
@@ -101,7 +101,12 @@ impl<OSC: ORAMStorageCreator<StorageDataSize, StorageMetaSize>> SgxIngestEnclave
             // Note: This is ignored because the semantic we want is, user_id should be
             // random if decryption failed, and ct_decrypt has no side-effects
             // if decryption fails.
-            let _success = FogHint::ct_decrypt(ingress_key, &txo.e_fog_hint, &mut user_id);
+            //
+            // We try every fog hint of the TxOut, since any of them may be for this
+            // ingress key, so that which one (if any) decrypts isn't revealed.
+            for e_fog_hint in txo.e_fog_hints() {
+                let _success = FogHint::ct_decrypt(ingress_key, e_fog_hint, &mut user_id);
+            }
 
             let mut aligned_view_pubkey: A8Bytes<U32> = Aligned(*GenericArray::from_slice(
                 user_id.get_view_pubkey().as_bytes(),
@@ -273,6 +278,40 @@ impl<OSC: ORAMStorageCreator<StorageDataSize, StorageMetaSize>> IngestEnclave
         })
     }
 
+    fn seal_ingress_key_backup(
+        &self,
+        msg: EnclaveMessage<PeerSession>,
+    ) -> Result<(SealedIngestKey, CompressedRistrettoPublic)> {
+        let key = self.ake.peer_decrypt(msg)?;
+        let backup_private_key = RistrettoPrivate::try_from(&key[..])?;
+        let backup_public_key = RistrettoPublic::from(&backup_private_key);
+
+        // Only seal the key, our own ingress key is unchanged
+        let sealed_key = seal_private_key(&backup_private_key)?;
+        Ok((sealed_key, backup_public_key.into()))
+    }
+
+    fn get_ingress_key_from_backup(
+        &self,
+        sealed_key: SealedIngestKey,
+        peer: PeerSession,
+    ) -> Result<(EnclaveMessage<PeerSession>, CompressedRistrettoPublic)> {
+        if !self.ake.is_peer_known(&peer)? {
+            return Err(Error::Attest(AttestEnclaveError::NotFound));
+        }
+
+        let sealed = IntelSealed::try_from(sealed_key)?;
+        let (key, _mac) = sealed.unseal_raw()?;
+        let backup_private_key = RistrettoPrivate::try_from(&key[..])?;
+        let backup_public_key = RistrettoPublic::from(&backup_private_key);
+
+        Ok((
+            self.ake
+                .peer_encrypt(&peer, &[], backup_private_key.as_ref())?,
+            backup_public_key.into(),
+        ))
+    }
+
     fn get_kex_rng_pubkey(&self) -> Result<KexRngPubkey> {
         let egress_key = self.egress_key.lock()?;
         let public_key = CompressedRistrettoPublic::from(&RistrettoPublic::from(&*egress_key));
@@ -162,6 +162,83 @@ fn test_ingest_enclave(logger: Logger) {
     })
 }
 
+// Test that the ingest enclave finds TxOuts whose hint for its ingress key is
+// one of the additional fog hints
+#[test_with_logger]
+fn test_ingest_enclave_additional_fog_hints(logger: Logger) {
+    mc_util_test_helper::run_with_several_seeds(|mut rng| {
+        let bob_account = AccountKey::random_with_fog(&mut rng);
+        let bob_public_address = bob_account.default_subaddress();
+
+        let enclave = SgxIngestEnclave::<HeapORAMStorageCreator>::new(logger.clone());
+        let params = IngestEnclaveInitParams {
+            responder_id: ResponderId::default(),
+            sealed_key: None,
+            desired_capacity: 128,
+        };
+        enclave.enclave_init(params).unwrap();
+
+        let fog_pubkey = enclave.get_ingress_pubkey().unwrap();
+        let kex_rng_pubkey = enclave.get_kex_rng_pubkey().unwrap();
+        // The ingress key of another fog deployment
+        let other_fog_pubkey = RistrettoPublic::from_random(&mut rng);
+
+        // The main fog hint is for the other deployment, and the hint for this
+        // one is in either position among the additional hints
+        let tx_outs: Vec<_> = (0..10usize)
+            .map(|idx| {
+                let hint = FogHint::from(&bob_public_address);
+                let mut tx_out = TxOut::new(
+                    BlockVersion::MAX,
+                    Amount::new(10, Mob::ID),
+                    &bob_public_address,
+                    &RistrettoPrivate::from_random(&mut rng),
+                    hint.encrypt(&other_fog_pubkey, &mut rng),
+                )
+                .unwrap();
+                tx_out.additional_e_fog_hints = vec![
+                    EncryptedFogHint::fake_onetime_hint(&mut rng),
+                    hint.encrypt(&fog_pubkey, &mut rng),
+                ];
+                if idx % 2 == 0 {
+                    tx_out.additional_e_fog_hints.swap(0, 1);
+                }
+                tx_out
+            })
+            .collect();
+
+        let txs_for_ingest = TxsForIngest {
+            block_index: 1,
+            global_txo_index: 100,
+            redacted_txs: tx_outs.clone(),
+            timestamp: 10,
+        };
+        let (tx_rows, _) = enclave.ingest_txs(txs_for_ingest).unwrap();
+        assert_eq!(tx_rows.len(), 10);
+
+        // Bob finds all of the rows, and can decrypt them
+        let bob_fog_credential = UserPrivate::from(&bob_account);
+        let mut bob_rng = VersionedKexRng::try_from_kex_pubkey(
+            &kex_rng_pubkey,
+            bob_fog_credential.get_view_key(),
+        )
+        .unwrap();
+        let search_keys: HashSet<_> = tx_rows.iter().map(|row| row.search_key.clone()).collect();
+        for _ in 0..10 {
+            assert!(search_keys.contains(&bob_rng.next().unwrap()));
+        }
+        for (tx_row, tx_out) in tx_rows.iter().zip(tx_outs.iter()) {
+            let tx_out_record = bob_fog_credential
+                .decrypt_tx_out_result(tx_row.payload.clone())
+                .unwrap();
+            assert_eq!(
+                tx_out_record.get_fog_tx_out().unwrap(),
+                FogTxOut::from(tx_out)
+            );
+        }
+    })
+}
+
 // This fog hint has a valid curve point, but invalid magic bytes
 fn make_malformed_fog_hint<T: RngCore + CryptoRng>(
     ingress_pubkey: &RistrettoPublic,
@@ -6,6 +6,8 @@
 
 extern crate mc_fog_ocall_oram_storage_untrusted;
 
+pub mod omap_capacity;
+
 pub use mc_fog_ingest_enclave_api::{
     Error, IngestEnclave, IngestEnclaveInitParams, IngestEnclaveProxy, Result, SealedIngestKey,
 };
@@ -31,6 +33,7 @@ use mc_sgx_types::{
     sgx_attributes_t, sgx_enclave_id_t, sgx_launch_token_t, sgx_misc_attribute_t, sgx_status_t,
 };
 use mc_sgx_urts::SgxEnclave;
+use omap_capacity::{epc_size, validate_omap_capacity, OmapCapacityError};
 use std::{path, result::Result as StdResult, sync::Arc};
 
 /// The default filename of the fog ingest's SGX enclave binary.
@@ -43,6 +46,8 @@ pub enum NewEnclaveError {
     Create(SgxError),
     /// When initializing the new enclave: {0}
     Init(EnclaveError),
+    /// Invalid OMAP capacity: {0}
+    OmapCapacity(OmapCapacityError),
 }
 
 impl From<EnclaveError> for NewEnclaveError {
@@ -51,6 +56,12 @@ impl From<EnclaveError> for NewEnclaveError {
     }
 }
 
+impl From<OmapCapacityError> for NewEnclaveError {
+    fn from(src: OmapCapacityError) -> Self {
+        Self::OmapCapacity(src)
+    }
+}
+
 /// A handle to an ingest enclave, on the untrusted side
 #[derive(Clone)]
 pub struct IngestSgxEnclave {
@@ -77,7 +88,9 @@ impl IngestSgxEnclave {
     ///   will create. Total memory usage should be about 64 * this value, +
     ///   some overhead, and about 70% of the capacity won't be usable due to
     ///   hash table overflow. So the *number of users* the enclave can support
-    ///   is about 70% times this.
+    ///   is about 70% times this. It must be a power of two, and the part of
+    ///   the table kept in the enclave must fit in the EPC if SGX reports its
+    ///   size.
     ///
     /// Returns:
     /// - The enclave proxy object, and the sealed ingest private key.
@@ -88,6 +101,24 @@ impl IngestSgxEnclave {
         omap_capacity: u64,
         logger: &Logger,
     ) -> StdResult<IngestSgxEnclave, NewEnclaveError> {
+        let epc_size = epc_size();
+        validate_omap_capacity(omap_capacity, epc_size)?;
+        match epc_size {
+            Some(epc_size) => log::info!(
+                logger,
+                "Using OMAP capacity {} (about {} of {} EPC bytes, and {} untrusted bytes)",
+                omap_capacity,
+                omap_capacity::omap_trusted_memory_bytes(omap_capacity),
+                epc_size,
+                omap_capacity::omap_untrusted_memory_bytes(omap_capacity)
+            ),
+            None => log::warn!(
+                logger,
+                "Using OMAP capacity {}, EPC size is unknown",
+                omap_capacity
+            ),
+        }
+
         let mut launch_token: sgx_launch_token_t = [0; 1024];
         let mut launch_token_updated: i32 = 0;
         // FIXME: this must be filled in from the build.rs
@@ -217,6 +248,26 @@ impl IngestEnclave for IngestSgxEnclave {
         mc_util_serial::deserialize(&outbuf[..])?
     }
 
+    fn seal_ingress_key_backup(
+        &self,
+        msg: EnclaveMessage<PeerSession>,
+    ) -> Result<(SealedIngestKey, CompressedRistrettoPublic)> {
+        let inbuf = mc_util_serial::serialize(&EnclaveCall::SealIngressKeyBackup(msg))?;
+        let outbuf = self.enclave_call(&inbuf)?;
+        mc_util_serial::deserialize(&outbuf[..])?
+    }
+
+    fn get_ingress_key_from_backup(
+        &self,
+        sealed_key: SealedIngestKey,
+        peer: PeerSession,
+    ) -> Result<(EnclaveMessage<PeerSession>, CompressedRistrettoPublic)> {
+        let inbuf =
+            mc_util_serial::serialize(&EnclaveCall::GetIngressKeyFromBackup(sealed_key, peer))?;
+        let outbuf = self.enclave_call(&inbuf)?;
+        mc_util_serial::deserialize(&outbuf[..])?
+    }
+
     fn get_kex_rng_pubkey(&self) -> Result<KexRngPubkey> {
         let inbuf = mc_util_serial::serialize(&EnclaveCall::GetKexRngPubkey)?;
         let outbuf = self.enclave_call(&inbuf)?;
@@ -0,0 +1,214 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Validation of the capacity of the ingest enclave's Oblivious Map, which
+//! holds the users' rngs.
+//!
+//! The capacity is picked when the server starts, so it can be scaled with the
+//! number of users without rebuilding the enclave, but what the Oblivious Map
+//! keeps inside the enclave must fit in the SGX EPC (enclave page cache) of
+//! the machine.
+//!
+//! The Oblivious Map is a cuckoo hash table made of two Path ORAMs. The ORAM
+//! trees live in untrusted memory, so only the stashes, the branch being
+//! accessed and the position maps of the ORAMs take EPC memory. The position
+//! map of a large ORAM is itself a smaller ORAM, down to a size where it is
+//! a plain array.
+//!
+//! The parameters below mirror the choices of the enclave's `RngStore` and of
+//! `mc-oblivious`, and must be kept in sync with them.
+
+use displaydoc::Display;
+
+/// The stash size of the ORAMs, in blocks
+const STASH_SIZE: u64 = 32;
+/// The size of an ORAM block, which is a bucket of the hash table, in bytes
+const BLOCK_SIZE: u64 = 1024;
+/// The number of blocks in a bucket of an ORAM tree
+const BLOCKS_PER_BUCKET: u64 = 4;
+/// The size of an ORAM tree bucket, with its metadata, in bytes
+const BUCKET_SIZE: u64 = 4096 + 64;
+/// The size of the metadata of a block in the stash, in bytes
+const BLOCK_META_SIZE: u64 = 64 / BLOCKS_PER_BUCKET;
+/// The size of a hash table item, a 32 byte key and an 8 byte value
+const ITEM_SIZE: u64 = 32 + 8;
+/// The number of positions which fit in a block of a position map ORAM
+const POSITIONS_PER_BLOCK: u64 = BLOCK_SIZE / 4;
+/// The largest position map which is kept as a plain array of u32 positions
+const TRIVIAL_POSITION_MAP_MAX_SIZE: u64 = 4096;
+
+/// An error with the Oblivious Map capacity the ingest enclave was configured
+/// with
+#[derive(Clone, Debug, Display, Eq, PartialEq)]
+pub enum OmapCapacityError {
+    /// The OMAP capacity {0} is not a power of two
+    NotPowerOfTwo(u64),
+    /// The OMAP capacity {capacity} needs about {required} bytes of EPC, but
+    /// the EPC is {available} bytes
+    ExceedsEpc {
+        /// The configured capacity
+        capacity: u64,
+        /// The approximate EPC memory the Oblivious Map needs, in bytes
+        required: u64,
+        /// The size of the EPC, in bytes
+        available: u64,
+    },
+}
+
+/// The approximate enclave memory an Oblivious Map of this capacity uses, in
+/// bytes.
+pub fn omap_trusted_memory_bytes(omap_capacity: u64) -> u64 {
+    2 * oram_trusted_memory_bytes(blocks_per_oram(omap_capacity))
+}
+
+/// The approximate untrusted memory the ORAM trees of an Oblivious Map of this
+/// capacity use, in bytes.
+pub fn omap_untrusted_memory_bytes(omap_capacity: u64) -> u64 {
+    2 * oram_untrusted_memory_bytes(blocks_per_oram(omap_capacity))
+}
+
+// The number of blocks of each of the two ORAMs of the hash table
+fn blocks_per_oram(omap_capacity: u64) -> u64 {
+    let items_per_block = BLOCK_SIZE / ITEM_SIZE;
+    let blocks = (omap_capacity / 2 + items_per_block - 1) / items_per_block;
+    blocks.max(1).next_power_of_two()
+}
+
+// The enclave memory of an ORAM with this many blocks: its stash, the branch
+// copied in for each access, and its position map
+fn oram_trusted_memory_bytes(num_blocks: u64) -> u64 {
+    let num_buckets = (num_blocks / BLOCKS_PER_BUCKET).max(1);
+    let branch_len = 64 - num_buckets.leading_zeros() as u64;
+    let position_map = if num_blocks <= TRIVIAL_POSITION_MAP_MAX_SIZE {
+        4 * num_blocks
+    } else {
+        oram_trusted_memory_bytes(
+            ((num_blocks + POSITIONS_PER_BLOCK - 1) / POSITIONS_PER_BLOCK).next_power_of_two(),
+        )
+    };
+    STASH_SIZE * (BLOCK_SIZE + BLOCK_META_SIZE) + branch_len * BUCKET_SIZE + position_map
+}
+
+// The untrusted memory of an ORAM with this many blocks: its tree, and the
+// trees of its position map
+fn oram_untrusted_memory_bytes(num_blocks: u64) -> u64 {
+    let tree = 2 * (num_blocks / BLOCKS_PER_BUCKET).max(1) * BUCKET_SIZE;
+    if num_blocks <= TRIVIAL_POSITION_MAP_MAX_SIZE {
+        tree
+    } else {
+        tree + oram_untrusted_memory_bytes(
+            ((num_blocks + POSITIONS_PER_BLOCK - 1) / POSITIONS_PER_BLOCK).next_power_of_two(),
+        )
+    }
+}
+
+/// Check that an Oblivious Map capacity can be used by the ingest enclave: it
+/// must be a power of two, and its enclave memory must fit in the EPC if its
+/// size is known.
+pub fn validate_omap_capacity(
+    omap_capacity: u64,
+    epc_size: Option<u64>,
+) -> Result<(), OmapCapacityError> {
+    if !omap_capacity.is_power_of_two() {
+        return Err(OmapCapacityError::NotPowerOfTwo(omap_capacity));
+    }
+    if let Some(available) = epc_size {
+        let required = omap_trusted_memory_bytes(omap_capacity);
+        if required > available {
+            return Err(OmapCapacityError::ExceedsEpc {
+                capacity: omap_capacity,
+                required,
+                available,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// The total size of the EPC sections of this CPU, in bytes, as reported by
+/// CPUID. This is None if the CPU does not support SGX.
+#[cfg(target_arch = "x86_64")]
+pub fn epc_size() -> Option<u64> {
+    use core::arch::x86_64::{__cpuid, __cpuid_count};
+
+    // SAFETY: CPUID is available on every x86_64 CPU, and leaves above the
+    // maximum basic leaf are not queried.
+    unsafe {
+        if __cpuid(0).eax < 0x12 {
+            return None;
+        }
+        // CPUID.(EAX=07H, ECX=0H):EBX.SGX[bit 2]
+        if __cpuid_count(0x7, 0).ebx & (1 << 2) == 0 {
+            return None;
+        }
+
+        // Sub-leaves 2 and onward of leaf 0x12 enumerate the EPC sections,
+        // until one which is not a valid section
+        let mut total = 0u64;
+        for subleaf in 2.. {
+            let section = __cpuid_count(0x12, subleaf);
+            if section.eax & 0xf != 1 {
+                break;
+            }
+            total +=
+                (u64::from(section.edx & 0xf_ffff) << 32) | u64::from(section.ecx & 0xffff_f000);
+        }
+        (total > 0).then(|| total)
+    }
+}
+
+/// The total size of the EPC sections of this CPU, in bytes. This is always
+/// None on CPUs other than x86_64.
+#[cfg(not(target_arch = "x86_64"))]
+pub fn epc_size() -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_omap_capacity_checks_power_of_two_and_epc() {
+        assert_eq!(validate_omap_capacity(262144, None), Ok(()));
+        assert_eq!(validate_omap_capacity(262144, Some(128 << 20)), Ok(()));
+        assert_eq!(
+            validate_omap_capacity(0, None),
+            Err(OmapCapacityError::NotPowerOfTwo(0))
+        );
+        assert_eq!(
+            validate_omap_capacity(1000, Some(128 << 20)),
+            Err(OmapCapacityError::NotPowerOfTwo(1000))
+        );
+        assert_eq!(
+            validate_omap_capacity(1 << 22, Some(256 << 10)),
+            Err(OmapCapacityError::ExceedsEpc {
+                capacity: 1 << 22,
+                required: omap_trusted_memory_bytes(1 << 22),
+                available: 256 << 10,
+            })
+        );
+    }
+
+    #[test]
+    fn omap_trees_are_not_counted_against_the_epc() {
+        // The trees of a billion entry table take over 100 GB, but they are in
+        // untrusted memory.
+        assert!(omap_untrusted_memory_bytes(1 << 30) > 100 << 30);
+        assert!(omap_trusted_memory_bytes(1 << 30) < 1 << 20);
+        assert_eq!(validate_omap_capacity(1 << 30, Some(128 << 20)), Ok(()));
+    }
+
+    #[test]
+    fn omap_trusted_memory_grows_with_capacity() {
+        // The stashes and branches take EPC memory even for a tiny table.
+        let stashes = 2 * STASH_SIZE * (BLOCK_SIZE + BLOCK_META_SIZE);
+        assert!(omap_trusted_memory_bytes(1) > stashes);
+
+        let mut last = 0;
+        for log_capacity in 0..40 {
+            let bytes = omap_trusted_memory_bytes(1 << log_capacity);
+            assert!(bytes >= last, "{} < {}", bytes, last);
+            last = bytes;
+        }
+    }
+}
@@ -48,6 +48,10 @@ pub fn ecall_dispatcher(inbuf: &[u8]) -> Result<Vec<u8>, sgx_status_t> {
             serialize(&ENCLAVE.get_ingress_private_key(peer_session))
         }
         EnclaveCall::SetIngressPrivateKey(msg) => serialize(&ENCLAVE.set_ingress_private_key(msg)),
+        EnclaveCall::SealIngressKeyBackup(msg) => serialize(&ENCLAVE.seal_ingress_key_backup(msg)),
+        EnclaveCall::GetIngressKeyFromBackup(sealed_key, peer_session) => {
+            serialize(&ENCLAVE.get_ingress_key_from_backup(sealed_key, peer_session))
+        }
         // Public key for rng's
         EnclaveCall::GetKexRngPubkey => serialize(&ENCLAVE.get_kex_rng_pubkey()),
         EnclaveCall::IngestTxs(chunk) => serialize(&ENCLAVE.ingest_txs(chunk)),
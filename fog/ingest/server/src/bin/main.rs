@@ -4,14 +4,18 @@
 //! Fog Ingest target
 
 use mc_attest_net::{Client, RaClient};
-use mc_common::logger::{log, o};
+use mc_common::logger::{log, o, Logger};
 use mc_fog_ingest_enclave::ENCLAVE_FILE;
 use mc_fog_ingest_server::{
     config::IngestConfig,
+    error::IngestServiceError,
     server::{IngestServer, IngestServerConfig},
     state_file::StateFile,
 };
+use mc_fog_recovery_db_iface::{RecoveryDb, ReportDb};
 use mc_fog_sql_recovery_db::SqlRecoveryDb;
+#[cfg(feature = "sqlite")]
+use mc_fog_sqlite_recovery_db::{sqlite_path_from_url, SqliteRecoveryDb};
 use mc_ledger_db::LedgerDB;
 use mc_util_cli::ParserWithBuildInfo;
 use mc_util_grpc::AdminServer;
@@ -32,6 +36,42 @@ fn main() {
     )
     .expect("Failed setting telemetry tracer");
 
+    // Open the recovery database. With the sqlite feature, a sqlite:// url
+    // selects the SQLite backend, which is meant for local development.
+    let database_url =
+        std::env::var("DATABASE_URL").expect("DATABASE_URL environment variable missing");
+    #[cfg(feature = "sqlite")]
+    if let Some(db_path) = sqlite_path_from_url(&database_url) {
+        let recovery_db =
+            SqliteRecoveryDb::new_from_path(db_path, Default::default(), logger.clone())
+                .unwrap_or_else(|err| {
+                    panic!(
+                        "fog-ingest cannot open database '{}': {:?}",
+                        database_url, err
+                    )
+                });
+        return run(config, recovery_db, logger);
+    }
+
+    let recovery_db = SqlRecoveryDb::new_from_url(
+        &database_url,
+        config.postgres_config.clone(),
+        logger.clone(),
+    )
+    .unwrap_or_else(|err| {
+        panic!(
+            "fog-ingest cannot connect to database '{}': {:?}",
+            database_url, err
+        )
+    });
+    run(config, recovery_db, logger)
+}
+
+fn run<DB>(config: IngestConfig, recovery_db: DB, logger: Logger)
+where
+    DB: RecoveryDb + ReportDb + Clone + Send + Sync + 'static,
+    IngestServiceError: From<<DB as RecoveryDb>::Error>,
+{
     // Get path to our state file.
     let state_file_path = config.state_file.clone().unwrap_or_else(|| {
         let mut home_dir = dirs::home_dir().unwrap_or_else(|| panic!("Unable to get home directory, please specify state file explicitly with --state-file"));
@@ -50,21 +90,7 @@ fn main() {
         .with_file_name(ENCLAVE_FILE);
     log::info!(logger, "Enclave path is: {:?}", enclave_path);
 
-    // Open databases.
-    let database_url =
-        std::env::var("DATABASE_URL").expect("DATABASE_URL environment variable missing");
-    let recovery_db = SqlRecoveryDb::new_from_url(
-        &database_url,
-        config.postgres_config.clone(),
-        logger.clone(),
-    )
-    .unwrap_or_else(|err| {
-        panic!(
-            "fog-ingest cannot connect to database '{}': {:?}",
-            database_url, err
-        )
-    });
-
+    // Open the other databases.
     let ledger_db = LedgerDB::open(&config.ledger_db).expect("Could not read ledger DB");
 
     let watcher =
@@ -94,7 +120,8 @@ fn main() {
         watcher,
         ledger_db,
         logger.clone(),
-    );
+    )
+    .expect("Failed creating Ingest Service");
 
     server.start().expect("Failed starting Ingest Service");
 
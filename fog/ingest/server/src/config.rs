@@ -6,6 +6,7 @@
 use clap::Parser;
 use mc_attest_core::ProviderId;
 use mc_common::ResponderId;
+use mc_fog_ingest_enclave::omap_capacity::{epc_size, validate_omap_capacity};
 use mc_fog_sql_recovery_db::SqlRecoveryDbConnectionConfig;
 use mc_fog_uri::{FogIngestUri, IngestPeerUri};
 use mc_util_parse::parse_duration_in_seconds;
@@ -61,7 +62,9 @@ pub struct IngestConfig {
     pub fog_report_id: String,
 
     /// Capacity of table for user rng's.
-    /// Must be a power of two at time of writing.
+    /// Must be a power of two at time of writing, and the part of the table
+    /// kept in the enclave (its ORAM stashes and position maps) must fit in
+    /// the SGX EPC. The server reports the capacity in its IngestSummary.
     ///
     /// One entry is added to this table with every transaction.
     /// When the table overflows, egress key rotation will occur and the table
@@ -70,7 +73,7 @@ pub struct IngestConfig {
     ///
     /// This determines the memory utilization / storage requirement of the
     /// server.
-    #[clap(long, default_value = "262144", env = "MC_USER_CAPACITY")]
+    #[clap(long, default_value = "262144", parse(try_from_str = parse_user_capacity), env = "MC_USER_CAPACITY")]
     pub user_capacity: u64,
 
     /// Max number of transactions ingest can eat at one time.  This is mostly
@@ -107,6 +110,13 @@ pub struct IngestConfig {
     pub postgres_config: SqlRecoveryDbConnectionConfig,
 }
 
+// Parse the user capacity, and check that the enclave can be created with it
+fn parse_user_capacity(src: &str) -> Result<u64, String> {
+    let user_capacity = src.parse::<u64>().map_err(|err| err.to_string())?;
+    validate_omap_capacity(user_capacity, epc_size()).map_err(|err| err.to_string())?;
+    Ok(user_capacity)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -19,7 +19,7 @@ use mc_connection::{AttestedConnection, Connection};
 use mc_crypto_keys::CompressedRistrettoPublic;
 use mc_fog_api::{
     ingest_common::{IngestSummary, SetPeersRequest},
-    ingest_peer::GetPrivateKeyRequest,
+    ingest_peer::{GetIngressKeyFromBackupRequest, GetPrivateKeyRequest},
     ingest_peer_grpc::AccountIngestPeerApiClient,
 };
 use mc_fog_ingest_enclave_api::IngestEnclaveProxy;
@@ -217,6 +217,29 @@ impl<Enclave: IngestEnclaveProxy> IngestConnection for PeerConnection<Enclave> {
         }
     }
 
+    fn get_ingress_key_from_backup(&mut self, sealed_key: &[u8]) -> Result<Message> {
+        trace_time!(self.logger, "PeerConnection::get_ingress_key_from_backup");
+
+        if self.channel_id.is_none() {
+            self.attest()?;
+        }
+
+        match self.channel_id.clone() {
+            Some(peer_session) => {
+                let mut request = GetIngressKeyFromBackupRequest::new();
+                request.set_channel_id(peer_session.into());
+                request.set_sealed_key(sealed_key.to_vec());
+
+                let message = self.attested_call(|this| {
+                    this.ingest_peer_api_client
+                        .get_ingress_key_from_backup(&request)
+                })?;
+                Ok(message)
+            }
+            None => Err(Error::ChannelSend),
+        }
+    }
+
     fn set_ingress_private_key(
         &mut self,
         current_ingress_public_key: &CompressedRistrettoPublic,
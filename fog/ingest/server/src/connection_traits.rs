@@ -28,6 +28,9 @@ pub trait IngestConnection: Connection {
     /// Get the ingress private key from the remote peer.
     fn get_ingress_private_key(&mut self) -> Result<Message>;
 
+    /// Get an ingress private key from a backup that the remote peer sealed.
+    fn get_ingress_key_from_backup(&mut self, sealed_key: &[u8]) -> Result<Message>;
+
     /// Send the ingress private key from ourselves to the remote peer.
     ///
     /// Note: The enclave has a thread-safe API to change its private keys.
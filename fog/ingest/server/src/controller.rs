@@ -4,11 +4,12 @@ use crate::{
     connection::PeerConnection,
     connection_error::Error as ConnectionError,
     connection_traits::IngestConnection,
-    controller_state::{IngestControllerState, StateChangeError},
+    controller_state::{IngestControllerState, PeerSync, StateChangeError},
     counters,
     error::{IngestServiceError as Error, PeerBackupError, RestoreStateError, SetPeersError},
     server::IngestServerConfig,
 };
+use mc_attest_api::attest::Message;
 use mc_attest_enclave_api::{EnclaveMessage, PeerAuthRequest, PeerAuthResponse, PeerSession};
 use mc_attest_net::RaClient;
 use mc_blockchain_types::{Block, BlockContents, BlockIndex};
@@ -19,26 +20,27 @@ use mc_common::{
 use mc_connection::Connection;
 use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPublic};
 use mc_fog_api::{
-    ingest_common::{IngestControllerMode, IngestStateFile, IngestSummary},
+    ingest_common::{IngestControllerMode, IngestStateFile, IngestSummary, PeerSyncStage},
     report_parse::try_extract_unvalidated_ingress_pubkey_from_fog_report,
 };
 use mc_fog_ingest_enclave::{
-    Error as EnclaveError, IngestEnclave, IngestSgxEnclave, NewEnclaveError,
+    Error as EnclaveError, IngestEnclave, IngestSgxEnclave, NewEnclaveError, SealedIngestKey,
 };
 use mc_fog_recovery_db_iface::{
-    IngressPublicKeyRecord, IngressPublicKeyRecordFilters, IngressPublicKeyStatus, RecoveryDb,
-    ReportData, ReportDb,
+    IngestInvocationId, IngressPublicKeyRecord, IngressPublicKeyRecordFilters,
+    IngressPublicKeyStatus, RecoveryDb, ReportData, ReportDb,
 };
-use mc_fog_types::{common::BlockRange, ingest::TxsForIngest};
+use mc_fog_types::{common::BlockRange, ingest::TxsForIngest, ETxOutRecord};
 use mc_fog_uri::IngestPeerUri;
 use mc_sgx_report_cache_api::ReportableEnclave;
 use mc_sgx_report_cache_untrusted::{Error as ReportCacheError, ReportCache};
 use mc_util_parse::SeqDisplay;
 use mc_util_uri::ConnectionUri;
 use std::{
-    collections::{BTreeMap, BTreeSet},
+    collections::{BTreeMap, BTreeSet, VecDeque},
     io::ErrorKind,
-    sync::{Arc, Mutex, MutexGuard},
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    time::{Duration, Instant},
 };
 
 /// The ingest controller sits under the grpc / networking layer, and implements
@@ -48,7 +50,9 @@ use std::{
 ///
 /// The ingest controller API is thread-safe and an Arc to this can be shared
 /// with GRPC services. There is also an ingest worker, which periodically calls
-/// "process_next_block". There should only be one thread doing that.
+/// "process_next_block", and a thread which calls "write_next_pending_block"
+/// to write the blocks it processed to the recovery db. There should only be
+/// one thread doing each.
 ///
 /// In consensus service, there is a roughly analogous object ByzantineLedger,
 /// which also has a worker thread, and owns its worker thread. In this case, we
@@ -83,10 +87,33 @@ pub struct IngestController<
     /// The last sealed key. We don't bother asking the enclave for the sealed
     /// key again unless the private key changes.
     last_sealed_key: Arc<Mutex<Option<(Vec<u8>, CompressedRistrettoPublic)>>>,
+    /// The blocks which went through the enclave, and whose rows are waiting
+    /// to be written to the recovery db, oldest first. They immediately
+    /// follow the next block index of the state.
+    pending_blocks: Arc<Mutex<VecDeque<Arc<PendingBlockData>>>>,
+    /// Signalled each time blocks are added to or removed from pending_blocks
+    pending_blocks_changed: Arc<Condvar>,
     /// Logger object
     logger: Logger,
 }
 
+/// A block which went through the enclave, whose rows are not written to the
+/// recovery db yet
+struct PendingBlockData {
+    /// The ingest invocation the rows were produced under
+    iid: IngestInvocationId,
+    /// The block
+    block: Block,
+    /// The timestamp of the block
+    timestamp: u64,
+    /// The rows produced by the enclave for the block
+    tx_rows: Vec<ETxOutRecord>,
+    /// When the block was first seen in the ledger
+    available_at: Instant,
+    /// When the block came out of the enclave
+    queued_at: Instant,
+}
+
 impl<
         R: RaClient + Send + Sync + 'static,
         DB: RecoveryDb + ReportDb + Clone + Send + Sync + 'static,
@@ -95,7 +122,15 @@ where
     Error: From<<DB as RecoveryDb>::Error>,
 {
     /// Create a new ingest controller
-    pub fn new(config: IngestServerConfig, ra_client: R, recovery_db: DB, logger: Logger) -> Self {
+    ///
+    /// Returns an error if the enclave can't be created with the configured
+    /// OMAP capacity.
+    pub fn new(
+        config: IngestServerConfig,
+        ra_client: R,
+        recovery_db: DB,
+        logger: Logger,
+    ) -> Result<Self, Error> {
         let controller_state = Arc::new(Mutex::new(IngestControllerState::new(
             &config,
             logger.clone(),
@@ -155,6 +190,9 @@ where
             Err(NewEnclaveError::Create(err)) => {
                 panic!("Could not create new enclave: {}", err);
             }
+            Err(NewEnclaveError::OmapCapacity(err)) => {
+                return Err(err.into());
+            }
             Err(NewEnclaveError::Init(err)) => {
                 log::error!(
                     logger,
@@ -205,6 +243,8 @@ where
             report_cache,
             grpc_env,
             last_sealed_key: Arc::new(Mutex::new(None)),
+            pending_blocks: Default::default(),
+            pending_blocks_changed: Default::default(),
             logger: logger.clone(),
         };
 
@@ -249,7 +289,7 @@ where
             result.get_state()
         );
 
-        result
+        Ok(result)
     }
 
     /// Forward peer_accept function from ingest enclave api
@@ -385,14 +425,22 @@ where
         }
     }
 
-    /// Process the next block through ingest enclave, and write all resulting
-    /// ETxOutRecord's to recovery db Then increment the next_block_index.
+    /// Process the next block through ingest enclave, and queue all resulting
+    /// ETxOutRecord's to be written to recovery db by write_next_pending_block,
+    /// which then increments the next_block_index.
+    ///
+    /// This waits for the blocks already queued to be written before anything
+    /// which changes the ingest invocation, so it must not be called unless
+    /// another thread calls write_next_pending_block.
     ///
     /// Additionally, if we are active, try to publish a fog report.
     /// If we cannot, because the ingress key is retired and there is no more
     /// work to do with it, set ourselves to the idle state and early
     /// return.
     ///
+    /// `available_at` is when the block was first seen in the ledger, which
+    /// the block ingest latency is measured from.
+    ///
     /// This function must maintain an invariant around the ingest invocation
     /// id:
     /// * The first time we publish data using an invocation id, the egress key
@@ -409,6 +457,7 @@ where
         block: &Block,
         block_contents: &BlockContents,
         timestamp: u64,
+        available_at: Instant,
     ) {
         let _process_next_block_timer = counters::PROCESS_NEXT_BLOCK_TIME.start_timer();
 
@@ -425,10 +474,23 @@ where
 
         // Scope for mutex: Get invocation id, confirm next_block_index
         let mut iid = {
-            let mut state = self.get_state();
+            // If we are retiring or have no ingest invocation yet, the blocks
+            // already queued must be written under the current invocation
+            // before we make any change to it.
+            let mut state = loop {
+                let state = self.get_state();
+                if (!state.is_active() || state.get_ingest_invocation_id().is_none())
+                    && !self.get_pending_blocks().is_empty()
+                {
+                    drop(state);
+                    self.wait_for_pending_blocks();
+                } else {
+                    break state;
+                }
+            };
             assert_eq!(
                 block.index,
-                state.get_next_block_index(),
+                state.get_next_block_index() + self.get_pending_blocks().len() as u64,
                 "We were asked to process the wrong block"
             );
 
@@ -461,6 +523,19 @@ where
                                 && block.index > ingress_key_status.pubkey_expiry
                             {
                                 log::warn!(self.logger, "When preparing to process block index {}, we discovered that our ingress key is expired: {:?}. Switching to idle and nuking keys.", block.index, ingress_key_status);
+                                // The blocks already queued are within the pubkey expiry, so
+                                // they must be written under the current invocation before it
+                                // is decommissioned by new_egress_key.
+                                if !self.get_pending_blocks().is_empty() {
+                                    drop(state);
+                                    self.wait_for_pending_blocks();
+                                    state = self.get_state();
+                                    // Writing them may have made us idle already, e.g. if
+                                    // another server published one of them first.
+                                    if !state.is_active() {
+                                        return;
+                                    }
+                                }
                                 state.set_idle();
                                 self.new_egress_key(&mut state)
                                     .expect("Failure to rotate egress key can't be recovered from");
@@ -569,6 +644,9 @@ where
             // atomic in the add-block-data operation, so that none of them happen,
             // and the clients never see them, unless we manage to publish a block
             if let Some(new_kex_rng_pubkey) = maybe_kex_rng_pubkey {
+                // The blocks already queued must be written under the old
+                // invocation first.
+                self.wait_for_pending_blocks();
                 let mut retry_seconds = 1;
                 let new_iid = loop {
                     match self.recovery_db.new_ingest_invocation(
@@ -600,6 +678,73 @@ where
             );
         }
 
+        log::debug!(
+            self.logger,
+            "Queueing {} rows of block #{:?} for the database",
+            tx_rows.len(),
+            block.index
+        );
+        let mut pending_blocks = self.get_pending_blocks();
+        pending_blocks.push_back(Arc::new(PendingBlockData {
+            iid: iid.expect("no ingest invocation id"),
+            block: block.clone(),
+            timestamp,
+            tx_rows,
+            available_at,
+            queued_at: Instant::now(),
+        }));
+        update_pending_block_counters(&pending_blocks);
+        self.pending_blocks_changed.notify_all();
+    }
+
+    /// The number of blocks which went through the enclave and are waiting to
+    /// be written to the recovery db
+    pub fn num_pending_blocks(&self) -> usize {
+        self.get_pending_blocks().len()
+    }
+
+    /// Write the oldest block queued by process_next_block to the recovery db,
+    /// waiting at most `timeout` for one if there is none. Then increment the
+    /// next_block_index.
+    ///
+    /// If the block no longer follows the state, because the ingest invocation
+    /// it was processed under was decommissioned in the meantime, all the
+    /// queued blocks are dropped instead.
+    pub fn write_next_pending_block(&self, timeout: Duration) {
+        let pending = {
+            let (pending_blocks, _) = self
+                .pending_blocks_changed
+                .wait_timeout_while(self.get_pending_blocks(), timeout, |pending_blocks| {
+                    pending_blocks.is_empty()
+                })
+                .expect("mutex poisoned");
+            match pending_blocks.front() {
+                Some(pending) => pending.clone(),
+                None => return,
+            }
+        };
+        let block = &pending.block;
+
+        {
+            let state = self.get_state();
+            if state.is_idle()
+                || state.get_ingest_invocation_id() != Some(pending.iid)
+                || state.get_next_block_index() != block.index
+            {
+                log::warn!(
+                    self.logger,
+                    "Dropping the pending data of block #{} and the blocks after it, invocation id {:?} is no longer in use",
+                    block.index,
+                    pending.iid
+                );
+                let mut pending_blocks = self.get_pending_blocks();
+                pending_blocks.clear();
+                update_pending_block_counters(&pending_blocks);
+                self.pending_blocks_changed.notify_all();
+                return;
+            }
+        }
+
         log::info!(self.logger, "add_block_data");
 
         // Commit all the new data to the database,
@@ -615,20 +760,23 @@ where
         // constraint violation). A constraint violation indicates that a
         // different ingest server with the same ingress public key
         // as this server has already published data for this block.
+        //
+        // Meanwhile the blocks behind this one wait in the queue, and the worker
+        // stops polling new blocks once the queue is full.
         let mut retry_seconds = 1;
         loop {
+            update_pending_block_counters(&self.get_pending_blocks());
             let db_metrics_timer = counters::DB_ADD_BLOCK_DATA_TIME.start_timer();
             match self.recovery_db.add_block_data(
-                // It's okay to .expect here since this code should not run if we did not get an
-                // ingest invocation id.
-                iid.as_ref().expect("no ingest invocation id"),
+                &pending.iid,
                 block,
-                timestamp,
-                &tx_rows,
+                pending.timestamp,
+                &pending.tx_rows,
             ) {
                 Ok(add_blocks_result) => {
                     log::trace!(self.logger, "state update");
                     let mut state = self.get_state();
+                    let mut pending_blocks = self.get_pending_blocks();
 
                     if add_blocks_result.block_already_scanned_with_this_key {
                         // We lost the race to publish this block
@@ -638,24 +786,31 @@ where
                         // scanned something that didn't get published
                         // new_egress_key also makes sure our rng is decommissioned
                         self.new_egress_key(&mut state).expect("Failure to rotate egress key after we can't publish data isn't recoverable, the RNGs would have gaps that the clients can't deal with");
+                        // The blocks after this one were scanned with the same egress key
+                        pending_blocks.clear();
                     } else {
                         // We won the race to publish this block
                         log::info!(
                             self.logger,
-                            "Succeeded writing block {} to the database, invocation id {:?} ingress key {:?}",
+                            "Succeeded writing block {} to the database, invocation id {:?}",
                             block.index,
-                            iid,
-                            ingress_pubkey
+                            pending.iid
                         );
                         log::trace!(self.logger, "increment_next_block_index");
                         state.increment_next_block_index();
+                        pending_blocks.pop_front();
+                        counters::BLOCK_INGEST_LATENCY
+                            .observe(pending.available_at.elapsed().as_secs_f64());
                     }
+                    update_pending_block_counters(&pending_blocks);
+                    self.pending_blocks_changed.notify_all();
 
                     log::debug!(self.logger, "Controller state: {}", state);
                     break;
                 }
                 Err(err) => {
-                    log::crit!(self.logger, "add_block_data failed while attempting to add {} rows for block #{}: {}. Retrying in {} seconds", tx_rows.len(), block.index, err, retry_seconds);
+                    log::crit!(self.logger, "add_block_data failed while attempting to add {} rows for block #{}: {}. Retrying in {} seconds", pending.tx_rows.len(), block.index, err, retry_seconds);
+                    counters::DB_ADD_BLOCK_DATA_RETRY_COUNT.inc();
                     std::thread::sleep(std::time::Duration::from_secs(retry_seconds));
                     retry_seconds = std::cmp::min(retry_seconds + 1, 30);
                     let _ = db_metrics_timer.stop_and_discard();
@@ -670,6 +825,17 @@ where
         self.write_state_file();
     }
 
+    // Wait until all the blocks queued by process_next_block are written to the
+    // recovery db. This must not be called while holding the state mutex.
+    fn wait_for_pending_blocks(&self) {
+        let _pending_blocks = self
+            .pending_blocks_changed
+            .wait_while(self.get_pending_blocks(), |pending_blocks| {
+                !pending_blocks.is_empty()
+            })
+            .expect("mutex poisoned");
+    }
+
     /// Attempt to put this server safely in the active mode
     ///
     /// - Check peers, if any is active or retiring, abort
@@ -886,7 +1052,26 @@ where
 
     /// Attempt to sync ingress keys from a remote server, which may be idle or
     /// active. We can only do this while we are idle.
+    ///
+    /// The sync is checkpointed in our state, which is written to the state
+    /// file, so that an interrupted sync resumes when this is called again with
+    /// the same remote. The key is only copied if the key in our enclave does
+    /// not already match the key that the remote reports, and the copied key
+    /// is verified against it.
     pub fn sync_keys_from_remote(&self, remote: &IngestPeerUri) -> Result<IngestSummary, Error> {
+        let result = self.sync_keys_from_remote_inner(remote);
+        if let Err(err) = &result {
+            let mut state = self.get_state();
+            if state.set_peer_sync_error(remote, err.to_string()) {
+                self.write_state_file_inner(&mut state);
+            }
+        }
+        result
+    }
+
+    // Helper for sync_keys_from_remote, which does not record errors in the
+    // checkpoint
+    fn sync_keys_from_remote_inner(&self, remote: &IngestPeerUri) -> Result<IngestSummary, Error> {
         // A valid report cache is required to initiate an outgoing attested connection.
         log::debug!(
             self.logger,
@@ -894,7 +1079,7 @@ where
         );
         self.update_enclave_report_cache()?;
 
-        // Lock the state for the duration of this call
+        // Lock the state while the key is copied
         let mut state = self.get_state();
         if !state.is_idle() {
             return Err(Error::ServerNotIdle);
@@ -902,17 +1087,165 @@ where
 
         log::info!(self.logger, "Syncing from Remote URI: {}", remote);
 
-        let mut connection = PeerConnection::<IngestSgxEnclave>::new(
+        let mut connection = self.peer_connection(remote);
+
+        let remote_summary = connection.get_status()?;
+        let remote_pubkey =
+            CompressedRistrettoPublic::try_from(remote_summary.get_ingress_pubkey())?;
+        state.start_peer_sync(remote, &remote_pubkey);
+
+        let our_pubkey = CompressedRistrettoPublic::from(&self.enclave.get_ingress_pubkey()?);
+        if our_pubkey == remote_pubkey {
+            // The report cache was refreshed with this key above, so there is
+            // nothing left to do.
+            log::info!(
+                self.logger,
+                "Our enclave already has the remote's ingress key {}, not copying it",
+                remote_pubkey
+            );
+            state.set_peer_sync_stage(PeerSyncStage::Synced);
+            self.write_state_file_inner(&mut state);
+            return Ok(self.get_ingest_summary_inner(&mut state));
+        }
+
+        log::info!(self.logger, "Asking remote for private key");
+        let msg = connection.get_ingress_private_key()?;
+
+        log::info!(self.logger, "Setting new private key on local enclave");
+        let set_ingress_private_key_result = self.enclave.set_ingress_private_key(msg.into())?;
+        let new_pubkey =
+            CompressedRistrettoPublic::from(&set_ingress_private_key_result.new_public_key);
+        log::info!(
+            self.logger,
+            "Key successfully set in enclave: {}",
+            new_pubkey
+        );
+        *self.last_sealed_key.lock().unwrap() = None;
+
+        // The remote's key may have changed since it reported its status
+        if new_pubkey != remote_pubkey {
+            self.write_state_file_inner(&mut state);
+            return Err(Error::PeerSyncKeyMismatch(
+                remote.clone(),
+                remote_pubkey,
+                new_pubkey,
+            ));
+        }
+        state.set_peer_sync_stage(PeerSyncStage::RefreshingReport);
+        self.write_state_file_inner(&mut state);
+
+        // Don't hold the state mutex while we are talking to IAS
+        drop(state);
+
+        // Update our report cache since we changed the private key
+        log::debug!(
+            self.logger,
+            "Refreshing enclave report cache after remote private key fetch"
+        );
+        self.update_enclave_report_cache()?;
+
+        let mut state = self.get_state();
+        // Our key may have changed while the state was unlocked, in which case
+        // the sync is stale.
+        if CompressedRistrettoPublic::from(&self.enclave.get_ingress_pubkey()?) == remote_pubkey {
+            state.set_peer_sync_stage(PeerSyncStage::Synced);
+            self.write_state_file_inner(&mut state);
+        }
+        Ok(self.get_ingest_summary_inner(&mut state))
+    }
+
+    /// Attempt to back up the ingress key of a remote server, which may be
+    /// idle or active. The key is sealed to our enclave, and our own ingress
+    /// key is not changed, so we can do this in any mode.
+    ///
+    /// Returns the sealed key, and the public key of the backed up key.
+    pub fn backup_ingress_key_from_remote(
+        &self,
+        remote: &IngestPeerUri,
+    ) -> Result<(SealedIngestKey, CompressedRistrettoPublic), Error> {
+        // A valid report cache is required to initiate an outgoing attested connection.
+        log::debug!(
+            self.logger,
+            "Refreshing enclave report cache before attesting to remote"
+        );
+        self.update_enclave_report_cache()?;
+
+        log::info!(self.logger, "Backing up key from Remote URI: {}", remote);
+
+        let mut connection = self.peer_connection(remote);
+
+        log::info!(self.logger, "Asking remote for private key");
+        let msg = connection.get_ingress_private_key()?;
+
+        let (sealed_key, backup_public_key) = self.enclave.seal_ingress_key_backup(msg.into())?;
+        log::info!(
+            self.logger,
+            "Key successfully sealed as a backup: {}",
+            backup_public_key
+        );
+
+        Ok((sealed_key, backup_public_key))
+    }
+
+    /// Attempt to restore an ingress key from a backup that a remote server
+    /// sealed, which may be idle or active. We can only do this while we are
+    /// idle.
+    pub fn restore_ingress_key_from_backup(
+        &self,
+        remote: &IngestPeerUri,
+        sealed_key: &[u8],
+    ) -> Result<IngestSummary, Error> {
+        // A valid report cache is required to initiate an outgoing attested connection.
+        log::debug!(
+            self.logger,
+            "Refreshing enclave report cache before attesting to remote"
+        );
+        self.update_enclave_report_cache()?;
+
+        // Lock the state for the duration of this call
+        let state = self.get_state();
+        if !state.is_idle() {
+            return Err(Error::ServerNotIdle);
+        }
+
+        log::info!(self.logger, "Restoring backup from Remote URI: {}", remote);
+
+        let mut connection = self.peer_connection(remote);
+
+        log::info!(self.logger, "Asking remote for backed up private key");
+        let msg = connection.get_ingress_key_from_backup(sealed_key)?;
+
+        self.set_ingress_private_key_from_remote(state, msg)
+    }
+
+    /// Forward get_ingress_key_from_backup function from ingest enclave api
+    pub fn get_ingress_key_from_backup(
+        &self,
+        sealed_key: SealedIngestKey,
+        session: PeerSession,
+    ) -> Result<(EnclaveMessage<PeerSession>, CompressedRistrettoPublic), EnclaveError> {
+        self.enclave
+            .get_ingress_key_from_backup(sealed_key, session)
+    }
+
+    // Make a connection to a remote server, attesting from our enclave
+    fn peer_connection(&self, remote: &IngestPeerUri) -> PeerConnection<IngestSgxEnclave> {
+        PeerConnection::<IngestSgxEnclave>::new(
             self.enclave.clone(),
             self.config.local_node_id.clone(),
             remote.clone(),
             self.grpc_env.clone(),
             self.logger.clone(),
-        );
-
-        log::info!(self.logger, "Asking remote for private key");
-        let msg = connection.get_ingress_private_key()?;
+        )
+    }
 
+    // Set a private key sent by a remote server on our enclave, and update the
+    // state file and report cache. Takes the lock on our (idle) state.
+    fn set_ingress_private_key_from_remote(
+        &self,
+        mut state: MutexGuard<IngestControllerState>,
+        msg: Message,
+    ) -> Result<IngestSummary, Error> {
         log::info!(self.logger, "Setting new private key on local enclave");
         let set_ingress_private_key_result = self.enclave.set_ingress_private_key(msg.into())?;
         log::info!(
@@ -994,10 +1327,15 @@ where
     /// Get the next block index, and whether we are idle, atomically together.
     ///
     /// This can be used by the worker thread to figure out if it should provide
-    /// the next block and which block.
+    /// the next block and which block. The blocks queued for the recovery db
+    /// count as provided.
     pub fn get_next_block_index(&self) -> (BlockIndex, bool) {
         let state = self.get_state();
-        (state.get_next_block_index(), state.is_idle())
+        let num_pending_blocks = self.get_pending_blocks().len() as u64;
+        (
+            state.get_next_block_index() + num_pending_blocks,
+            state.is_idle(),
+        )
     }
 
     /// Check if the controller is idle
@@ -1032,6 +1370,10 @@ where
         result.set_ingress_pubkey((&ingress_pubkey).into());
         result.set_egress_pubkey(kex_rng_pubkey.public_key);
         result.kex_rng_version = kex_rng_pubkey.version;
+        result.omap_capacity = self.config.omap_capacity;
+        if let Some(peer_sync) = state.get_peer_sync_progress(&ingress_pubkey) {
+            result.set_peer_sync(peer_sync);
+        }
         result
     }
 
@@ -1130,6 +1472,16 @@ where
         state
             .set_next_block_index(state_data.next_block_index)
             .expect("Modification should have been allowed, this is a logic error");
+        if state_data.has_peer_sync() {
+            match PeerSync::try_from(state_data.get_peer_sync()) {
+                Ok(peer_sync) => state.set_peer_sync(Some(peer_sync)),
+                Err(err) => log::warn!(
+                    self.logger,
+                    "Could not restore the peer sync checkpoint, ignoring it: {}",
+                    err
+                ),
+            }
+        }
 
         match state_data.mode {
             IngestControllerMode::Idle => {}
@@ -1176,6 +1528,12 @@ where
         self.controller_state.lock().expect("mutex poisoned")
     }
 
+    // Helper to get the pending blocks mutex. When both are needed, the state
+    // mutex is locked first.
+    fn get_pending_blocks(&self) -> MutexGuard<VecDeque<Arc<PendingBlockData>>> {
+        self.pending_blocks.lock().expect("mutex poisoned")
+    }
+
     // Helper which causes the enclave verification report to be published
     //
     // The pubkey_expiry will be computed as state.next_block_index +
@@ -1565,3 +1923,21 @@ where
         )
     }
 }
+
+// Helper which updates the counters describing the blocks waiting to be
+// written to the recovery db
+fn update_pending_block_counters(pending_blocks: &VecDeque<Arc<PendingBlockData>>) {
+    counters::PENDING_BLOCKS.set(pending_blocks.len() as i64);
+    counters::PENDING_BLOCK_DATA_ROWS.set(
+        pending_blocks
+            .iter()
+            .map(|pending| pending.tx_rows.len() as i64)
+            .sum(),
+    );
+    counters::PENDING_BLOCK_DATA_AGE.set(
+        pending_blocks
+            .front()
+            .map(|pending| pending.queued_at.elapsed().as_secs() as i64)
+            .unwrap_or(0),
+    );
+}
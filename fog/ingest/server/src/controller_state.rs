@@ -5,13 +5,17 @@
 
 use crate::{counters, server::IngestServerConfig};
 use displaydoc::Display;
+use mc_api::ConversionError;
 use mc_blockchain_types::BlockIndex;
 use mc_common::logger::{log, Logger};
-use mc_fog_api::ingest_common::{IngestControllerMode, IngestSummary};
+use mc_crypto_keys::CompressedRistrettoPublic;
+use mc_fog_api::ingest_common::{
+    IngestControllerMode, IngestSummary, PeerSyncProgress, PeerSyncStage,
+};
 use mc_fog_recovery_db_iface::IngestInvocationId;
 use mc_fog_uri::IngestPeerUri;
 use mc_util_parse::SeqDisplay;
-use std::{collections::BTreeSet, fmt::Display};
+use std::{collections::BTreeSet, fmt::Display, str::FromStr};
 
 /// The ingest server is, at any time, in one of two modes:
 ///
@@ -34,6 +38,47 @@ pub enum IngestMode {
     Active,
 }
 
+/// The checkpoint of a sync of our ingress key from a peer
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PeerSync {
+    /// The peer which the key is synced from
+    pub peer_uri: IngestPeerUri,
+    /// The ingress public key of the peer, which is synced
+    pub ingress_pubkey: CompressedRistrettoPublic,
+    /// The stage which the sync reached
+    pub stage: PeerSyncStage,
+    /// How many times the sync was attempted
+    pub attempts: u32,
+    /// The error which interrupted the latest attempt, if any
+    pub last_error: Option<String>,
+}
+
+impl From<&PeerSync> for PeerSyncProgress {
+    fn from(src: &PeerSync) -> Self {
+        let mut result = PeerSyncProgress::new();
+        result.peer_uri = src.peer_uri.to_string();
+        result.set_ingress_pubkey((&src.ingress_pubkey).into());
+        result.stage = src.stage;
+        result.attempts = src.attempts;
+        result.last_error = src.last_error.clone().unwrap_or_default();
+        result
+    }
+}
+
+impl TryFrom<&PeerSyncProgress> for PeerSync {
+    type Error = ConversionError;
+    fn try_from(src: &PeerSyncProgress) -> Result<Self, Self::Error> {
+        Ok(Self {
+            peer_uri: IngestPeerUri::from_str(&src.peer_uri)
+                .map_err(|_| ConversionError::InvalidContents)?,
+            ingress_pubkey: CompressedRistrettoPublic::try_from(src.get_ingress_pubkey())?,
+            stage: src.stage,
+            attempts: src.attempts,
+            last_error: (!src.last_error.is_empty()).then(|| src.last_error.clone()),
+        })
+    }
+}
+
 /// State controlling the operation of the ingest controller.
 ///
 /// This data is set from the server configuration initially, but then can be
@@ -74,6 +119,8 @@ pub struct IngestControllerState {
     /// Our current set of known peers. Only one should be active at a time, the
     /// others should be backups in idle state.
     peers: BTreeSet<IngestPeerUri>,
+    /// The checkpoint of the latest sync of our ingress key from a peer
+    peer_sync: Option<PeerSync>,
     /// Logger
     logger: Logger,
 }
@@ -88,6 +135,7 @@ impl IngestControllerState {
             pubkey_expiry_window: config.pubkey_expiry_window,
             ingest_invocation_id: None,
             peers,
+            peer_sync: None,
             logger,
         };
         result.update_metrics();
@@ -228,6 +276,103 @@ impl IngestControllerState {
         }
     }
 
+    /// Set the checkpoint of the latest sync of our ingress key from a peer,
+    /// e.g. when restoring it from the state file
+    pub fn set_peer_sync(&mut self, peer_sync: Option<PeerSync>) {
+        self.peer_sync = peer_sync;
+    }
+
+    /// Start an attempt to sync the given ingress key from a peer, and return
+    /// the stage to resume from.
+    ///
+    /// The previous sync is resumed if it was from the same peer, of the same
+    /// key. Otherwise a new sync starts at the CopyingKey stage.
+    pub fn start_peer_sync(
+        &mut self,
+        peer_uri: &IngestPeerUri,
+        ingress_pubkey: &CompressedRistrettoPublic,
+    ) -> PeerSyncStage {
+        match self.peer_sync.as_mut() {
+            Some(peer_sync)
+                if &peer_sync.peer_uri == peer_uri
+                    && &peer_sync.ingress_pubkey == ingress_pubkey =>
+            {
+                peer_sync.attempts += 1;
+                peer_sync.last_error = None;
+                log::info!(
+                    self.logger,
+                    "Resuming sync of ingress key {} from {} at stage {:?}, attempt {}",
+                    ingress_pubkey,
+                    peer_uri,
+                    peer_sync.stage,
+                    peer_sync.attempts
+                );
+            }
+            _ => {
+                log::info!(
+                    self.logger,
+                    "Starting sync of ingress key {} from {}",
+                    ingress_pubkey,
+                    peer_uri
+                );
+                self.peer_sync = Some(PeerSync {
+                    peer_uri: peer_uri.clone(),
+                    ingress_pubkey: *ingress_pubkey,
+                    stage: PeerSyncStage::CopyingKey,
+                    attempts: 1,
+                    last_error: None,
+                });
+            }
+        }
+        self.peer_sync
+            .as_ref()
+            .expect("peer sync was just set")
+            .stage
+    }
+
+    /// Record that the sync from a peer reached a stage
+    pub fn set_peer_sync_stage(&mut self, stage: PeerSyncStage) {
+        if let Some(peer_sync) = self.peer_sync.as_mut() {
+            log::info!(
+                self.logger,
+                "Sync of ingress key {} from {} reached stage {:?}",
+                peer_sync.ingress_pubkey,
+                peer_sync.peer_uri,
+                stage
+            );
+            peer_sync.stage = stage;
+        }
+    }
+
+    /// Record the error which interrupted a sync from a peer. Returns false if
+    /// the latest sync is not from this peer, in which case nothing is
+    /// recorded.
+    pub fn set_peer_sync_error(&mut self, peer_uri: &IngestPeerUri, error: String) -> bool {
+        match self.peer_sync.as_mut() {
+            Some(peer_sync) if &peer_sync.peer_uri == peer_uri => {
+                peer_sync.last_error = Some(error);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Get the progress of the latest sync from a peer, if it is still
+    /// relevant given the ingress key in our enclave: a sync which got past
+    /// copying the key is stale once our key changes.
+    pub fn get_peer_sync_progress(
+        &self,
+        our_ingress_pubkey: &CompressedRistrettoPublic,
+    ) -> Option<PeerSyncProgress> {
+        self.peer_sync
+            .as_ref()
+            .filter(|peer_sync| {
+                peer_sync.stage == PeerSyncStage::CopyingKey
+                    || &peer_sync.ingress_pubkey == our_ingress_pubkey
+            })
+            .map(PeerSyncProgress::from)
+    }
+
     /// Get an ingest summary protobuf object containing the data from self
     pub fn get_ingest_summary(&self) -> IngestSummary {
         let mut result = IngestSummary::new();
@@ -1,11 +1,18 @@
 // Copyright (c) 2018-2022 The MobileCoin Foundation
 
-use mc_util_metrics::{Histogram, IntCounter, IntGauge, OpMetrics};
+use mc_util_metrics::{register_histogram, Histogram, IntCounter, IntGauge, OpMetrics};
 
 // Numerical values for the 2 possible modes.
 pub const MODE_IDLE: i64 = 0;
 pub const MODE_ACTIVE: i64 = 1;
 
+// Histogram buckets for BLOCK_INGEST_LATENCY, in seconds. A caught up server
+// takes well under a second, while a server catching up on a backlog, or
+// retrying database writes, can take minutes.
+const BLOCK_INGEST_LATENCY_BUCKETS: &[f64] = &[
+    0.05, 0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0,
+];
+
 lazy_static::lazy_static! {
     pub static ref OP_COUNTERS: OpMetrics = OpMetrics::new_and_registered("fog_ingest");
 
@@ -27,6 +34,30 @@ lazy_static::lazy_static! {
     // Time it takes to perform the db add_block_data call.
     pub static ref DB_ADD_BLOCK_DATA_TIME: Histogram = OP_COUNTERS.histogram("db_add_block_data_time");
 
+    // Number of blocks processed by the enclave which are not yet committed to the database.
+    // Block polling pauses when this reaches the limit of the ingest worker.
+    pub static ref PENDING_BLOCKS: IntGauge = OP_COUNTERS.gauge("pending_blocks");
+
+    // Number of rows produced by the enclave which are not yet committed to the database.
+    pub static ref PENDING_BLOCK_DATA_ROWS: IntGauge = OP_COUNTERS.gauge("pending_block_data_rows");
+
+    // How long the oldest block not yet committed to the database has been waiting, in seconds.
+    pub static ref PENDING_BLOCK_DATA_AGE: IntGauge = OP_COUNTERS.gauge("pending_block_data_age_seconds");
+
+    // Number of failed db add_block_data calls, which were retried.
+    pub static ref DB_ADD_BLOCK_DATA_RETRY_COUNT: IntCounter = OP_COUNTERS.counter("db_add_block_data_retry_count");
+
+    // Time from this server first seeing a block in the ledger, to the block's rows being committed to the database.
+    pub static ref BLOCK_INGEST_LATENCY: Histogram = register_histogram!(
+        "fog_ingest_block_ingest_latency_seconds",
+        "Time from a block appearing in the ledger to its ETxOutRecords being committed to the recovery db",
+        BLOCK_INGEST_LATENCY_BUCKETS.to_vec()
+    )
+    .unwrap();
+
+    // Number of blocks in the ledger which are not processed yet, including the one being processed.
+    pub static ref BLOCKS_BEHIND_LEDGER: IntGauge = OP_COUNTERS.gauge("blocks_behind_ledger");
+
     // Ingest enclave report timestamp, represented as seconds of UTC time since Unix epoch 1970-01-01T00:00:00Z.
     pub static ref ENCLAVE_REPORT_TIMESTAMP: IntGauge = OP_COUNTERS.gauge("enclave_report_timestamp");
 
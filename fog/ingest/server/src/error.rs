@@ -10,7 +10,7 @@ use mc_api::ConversionError;
 use mc_common::ResponderId;
 use mc_crypto_keys::CompressedRistrettoPublic;
 use mc_fog_api::report_parse::ReportParseError;
-use mc_fog_ingest_enclave::Error as EnclaveError;
+use mc_fog_ingest_enclave::{omap_capacity::OmapCapacityError, Error as EnclaveError};
 use mc_fog_recovery_db_iface::RecoveryDbError;
 use mc_fog_sql_recovery_db::Error as SqlRecoveryDbError;
 use mc_fog_uri::IngestPeerUri;
@@ -56,6 +56,19 @@ pub enum IngestServiceError {
     Grpc(GrpcError),
     /// Report Parse: {0}
     ReportParse(ReportParseError),
+    /// Conversion error: {0}
+    Conversion(ConversionError),
+    /**
+     * While syncing from peer {0}, which reported the ingress key {1}, we
+     * were sent the key {2}
+     */
+    PeerSyncKeyMismatch(
+        IngestPeerUri,
+        CompressedRistrettoPublic,
+        CompressedRistrettoPublic,
+    ),
+    /// Invalid OMAP capacity: {0}
+    OmapCapacity(OmapCapacityError),
 }
 
 impl From<EnclaveError> for IngestServiceError {
@@ -64,6 +77,12 @@ impl From<EnclaveError> for IngestServiceError {
     }
 }
 
+impl From<OmapCapacityError> for IngestServiceError {
+    fn from(src: OmapCapacityError) -> Self {
+        Self::OmapCapacity(src)
+    }
+}
+
 impl From<LedgerDbError> for IngestServiceError {
     fn from(src: LedgerDbError) -> Self {
         Self::LedgerDb(src)
@@ -136,6 +155,12 @@ impl From<ReportParseError> for IngestServiceError {
     }
 }
 
+impl From<ConversionError> for IngestServiceError {
+    fn from(src: ConversionError) -> Self {
+        Self::Conversion(src)
+    }
+}
+
 /// An error which occurs when making or checking on a peer backup
 #[derive(Debug, Display)]
 pub enum PeerBackupError {
@@ -113,6 +113,30 @@ where
 
         Ok(self.controller.get_ingest_summary())
     }
+
+    /// Unseal a backup of an ingress private key, and get it encrypted for the
+    /// peer
+    pub fn get_ingress_key_from_backup_impl(
+        &mut self,
+        mut request: GetIngressKeyFromBackupRequest,
+        logger: &Logger,
+    ) -> Result<Message, RpcStatus> {
+        log::debug!(&self.logger, "Now getting private key from backup",);
+
+        let peer_session = PeerSession::from(request.get_channel_id());
+
+        let (private_key, _) = self
+            .controller
+            .get_ingress_key_from_backup(request.take_sealed_key(), peer_session)
+            .map_err(|err| match err {
+                EnclaveError::Attest(_) => {
+                    rpc_permissions_error("get_ingress_key_from_backup", err, logger)
+                }
+                _ => rpc_internal_error("get_ingress_key_from_backup", err, logger),
+            })?;
+
+        Ok(private_key.into())
+    }
 }
 
 impl<
@@ -173,4 +197,21 @@ where
             )
         })
     }
+
+    fn get_ingress_key_from_backup(
+        &mut self,
+        ctx: RpcContext,
+        request: GetIngressKeyFromBackupRequest,
+        sink: UnarySink<Message>,
+    ) {
+        let _timer = SVC_COUNTERS.req(&ctx);
+        mc_common::logger::scoped_global_logger(&rpc_logger(&ctx, &self.logger), |logger| {
+            send_result(
+                ctx,
+                sink,
+                self.get_ingress_key_from_backup_impl(request, logger),
+                logger,
+            )
+        })
+    }
 }
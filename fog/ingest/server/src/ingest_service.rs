@@ -215,7 +215,9 @@ where
                 Error::ServerNotIdle => {
                     rpc_precondition_error("sync_keys_from_remote", err, logger)
                 }
-                Error::Connection(_) => rpc_unavailable_error("sync_keys_from_remote", err, logger),
+                Error::Connection(_) | Error::PeerSyncKeyMismatch(..) => {
+                    rpc_unavailable_error("sync_keys_from_remote", err, logger)
+                }
                 Error::Enclave(EnclaveError::Attest(_)) => {
                     rpc_permissions_error("sync_keys_from_remote", err, logger)
                 }
@@ -223,6 +225,61 @@ where
             })
     }
 
+    /// Retrieves a private key from a remote enclave and then seals it as a
+    /// backup on the current enclave.
+    pub fn backup_ingress_key_from_remote_impl(
+        &mut self,
+        request: BackupIngressKeyFromRemoteRequest,
+        logger: &Logger,
+    ) -> Result<IngressKeyBackup, RpcStatus> {
+        let peer_uri = IngestPeerUri::from_str(request.get_peer_uri())
+            .map_err(|err| rpc_invalid_arg_error("invalid peer uri", err, logger))?;
+
+        let (sealed_key, ingress_public_key) = self
+            .controller
+            .backup_ingress_key_from_remote(&peer_uri)
+            .map_err(|err| match err {
+                Error::Connection(_) => {
+                    rpc_unavailable_error("backup_ingress_key_from_remote", err, logger)
+                }
+                Error::Enclave(EnclaveError::Attest(_)) => {
+                    rpc_permissions_error("backup_ingress_key_from_remote", err, logger)
+                }
+                _ => rpc_internal_error("backup_ingress_key_from_remote", err, logger),
+            })?;
+
+        let mut response = IngressKeyBackup::new();
+        response.set_ingress_public_key(external::CompressedRistretto::from(&ingress_public_key));
+        response.set_sealed_key(sealed_key);
+        Ok(response)
+    }
+
+    /// Retrieves a private key from a backup sealed by a remote enclave, and
+    /// then sets it as the current enclave's private key.
+    pub fn restore_ingress_key_from_backup_impl(
+        &mut self,
+        request: RestoreIngressKeyFromBackupRequest,
+        logger: &Logger,
+    ) -> Result<IngestSummary, RpcStatus> {
+        let peer_uri = IngestPeerUri::from_str(request.get_peer_uri())
+            .map_err(|err| rpc_invalid_arg_error("invalid peer uri", err, logger))?;
+
+        self.controller
+            .restore_ingress_key_from_backup(&peer_uri, request.get_backup().get_sealed_key())
+            .map_err(|err| match err {
+                Error::ServerNotIdle => {
+                    rpc_precondition_error("restore_ingress_key_from_backup", err, logger)
+                }
+                Error::Connection(_) => {
+                    rpc_unavailable_error("restore_ingress_key_from_backup", err, logger)
+                }
+                Error::Enclave(EnclaveError::Attest(_)) => {
+                    rpc_permissions_error("restore_ingress_key_from_backup", err, logger)
+                }
+                _ => rpc_internal_error("restore_ingress_key_from_backup", err, logger),
+            })
+    }
+
     /// Retrieves the ingress public keys and filters according to the request's
     /// parameters.
     pub fn get_ingress_key_records_impl(
@@ -401,4 +458,38 @@ where
             )
         })
     }
+
+    fn backup_ingress_key_from_remote(
+        &mut self,
+        ctx: RpcContext,
+        request: BackupIngressKeyFromRemoteRequest,
+        sink: UnarySink<IngressKeyBackup>,
+    ) {
+        let _timer = SVC_COUNTERS.req(&ctx);
+        mc_common::logger::scoped_global_logger(&rpc_logger(&ctx, &self.logger), |logger| {
+            send_result(
+                ctx,
+                sink,
+                self.backup_ingress_key_from_remote_impl(request, logger),
+                logger,
+            )
+        })
+    }
+
+    fn restore_ingress_key_from_backup(
+        &mut self,
+        ctx: RpcContext,
+        request: RestoreIngressKeyFromBackupRequest,
+        sink: UnarySink<IngestSummary>,
+    ) {
+        let _timer = SVC_COUNTERS.req(&ctx);
+        mc_common::logger::scoped_global_logger(&rpc_logger(&ctx, &self.logger), |logger| {
+            send_result(
+                ctx,
+                sink,
+                self.restore_ingress_key_from_backup_impl(request, logger),
+                logger,
+            )
+        })
+    }
 }
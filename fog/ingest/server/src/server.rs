@@ -19,10 +19,12 @@ use mc_common::{
     logger::{log, Logger},
     ResponderId,
 };
+use mc_crypto_keys::CompressedRistrettoPublic;
 use mc_fog_api::{
     ingest_common::{IngestControllerMode, IngestSummary},
     ingest_grpc, ingest_peer_grpc,
 };
+use mc_fog_ingest_enclave::SealedIngestKey;
 use mc_fog_recovery_db_iface::{RecoveryDb, ReportDb};
 use mc_fog_uri::{FogIngestUri, IngestPeerUri};
 use mc_ledger_db::{Ledger, LedgerDB};
@@ -133,7 +135,7 @@ where
         watcher: WatcherDB,
         ledger_db: LedgerDB,
         logger: Logger,
-    ) -> Self {
+    ) -> Result<Self, IngestServiceError> {
         // Validate peer list in config:
         // - Each peers responder id should be unique
         // - Our responder id ("local-node-id") should be one of them
@@ -158,9 +160,9 @@ where
             ra_client,
             recovery_db,
             logger.clone(),
-        ));
+        )?);
 
-        Self {
+        Ok(Self {
             config,
             ledger_db,
             watcher,
@@ -171,7 +173,7 @@ where
             peer_checkup_worker: None,
             report_cache_worker: None,
             logger,
-        }
+        })
     }
 
     /// Start all the grpc services and threads in the server
@@ -393,6 +395,29 @@ where
     ) -> Result<IngestSummary, IngestServiceError> {
         self.controller.sync_keys_from_remote(remote_peer_uri)
     }
+
+    /// Attest to another ingest node and seal the private key from its enclave
+    /// as a backup in our enclave
+    /// This is used in tests when it would be simpler than making an RPC client
+    pub fn backup_ingress_key_from_remote(
+        &self,
+        remote_peer_uri: &IngestPeerUri,
+    ) -> Result<(SealedIngestKey, CompressedRistrettoPublic), IngestServiceError> {
+        self.controller
+            .backup_ingress_key_from_remote(remote_peer_uri)
+    }
+
+    /// Attest to the ingest node which made a backup, and store the backed up
+    /// private key in our enclave
+    /// This is used in tests when it would be simpler than making an RPC client
+    pub fn restore_ingress_key_from_backup(
+        &mut self,
+        remote_peer_uri: &IngestPeerUri,
+        sealed_key: &[u8],
+    ) -> Result<IngestSummary, IngestServiceError> {
+        self.controller
+            .restore_ingress_key_from_backup(remote_peer_uri, sealed_key)
+    }
 }
 
 impl<
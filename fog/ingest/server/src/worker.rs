@@ -1,6 +1,6 @@
 // Copyright (c) 2018-2022 The MobileCoin Foundation
 
-use crate::{controller::IngestController, error::IngestServiceError};
+use crate::{controller::IngestController, counters, error::IngestServiceError};
 use mc_attest_net::RaClient;
 use mc_blockchain_types::BlockIndex;
 use mc_common::logger::{log, Logger};
@@ -12,6 +12,7 @@ use mc_util_telemetry::{
 };
 use mc_watcher::watcher_db::WatcherDB;
 use std::{
+    collections::VecDeque,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
@@ -24,10 +25,13 @@ use std::{
 const TELEMETRY_BLOCK_INDEX_KEY: Key = telemetry_static_key!("block-index");
 
 /// The ingest worker is a thread responsible for driving the polling loop which
-/// checks if there are new blocks in the ledger to be processed
+/// checks if there are new blocks in the ledger to be processed. A second
+/// thread writes the processed blocks to the recovery db.
 pub struct IngestWorker {
     stop_requested: Arc<AtomicBool>,
     thread: Option<JoinHandle<()>>,
+    writer_stop_requested: Arc<AtomicBool>,
+    writer_thread: Option<JoinHandle<()>>,
 }
 
 impl IngestWorker {
@@ -44,6 +48,10 @@ impl IngestWorker {
     /// progress. Another strategy might be for the server to enter a
     /// "paused" state and signal for intervention.
     const ERROR_RETRY_FREQUENCY: Duration = Duration::from_millis(1000);
+    /// Block polling pauses while this many processed blocks are waiting to be
+    /// written to the recovery db, so that the rows produced by the enclave
+    /// don't pile up in memory when the database falls behind.
+    const MAX_PENDING_BLOCKS: usize = 10;
 
     /// Create a new IngestWorker thread
     ///
@@ -68,10 +76,29 @@ impl IngestWorker {
         IngestServiceError: From<<DB as RecoveryDb>::Error>,
     {
         let stop_requested = Arc::new(AtomicBool::new(false));
+        let writer_stop_requested = Arc::new(AtomicBool::new(false));
+
+        // The writer keeps going until the polling loop has stopped and all the
+        // blocks it processed are written.
+        let writer_thread = {
+            let controller = controller.clone();
+            let writer_stop_requested = writer_stop_requested.clone();
+            std::thread::spawn(move || {
+                while !(writer_stop_requested.load(Ordering::SeqCst)
+                    && controller.num_pending_blocks() == 0)
+                {
+                    controller.write_next_pending_block(Self::POLLING_FREQUENCY);
+                }
+            })
+        };
+
         Self {
             stop_requested: stop_requested.clone(),
+            writer_stop_requested,
+            writer_thread: Some(writer_thread),
             thread: Some(std::thread::spawn(move || {
                 let mut last_not_found_log: Option<LastNotFound> = None;
+                let mut ledger_arrivals = LedgerArrivals::default();
                 loop {
                     let (next_block_index, is_idle) = controller.get_next_block_index();
 
@@ -90,10 +117,18 @@ impl IngestWorker {
                         continue;
                     }
 
+                    // Backpressure: don't poll more blocks until the database
+                    // catches up.
+                    if controller.num_pending_blocks() >= Self::MAX_PENDING_BLOCKS {
+                        std::thread::sleep(Self::POLLING_FREQUENCY);
+                        continue;
+                    }
+
                     let start_time = SystemTime::now();
 
                     match db.get_block_data(next_block_index) {
                         Err(LedgerError::NotFound) => {
+                            counters::BLOCKS_BEHIND_LEDGER.set(0);
                             if let Some(rec) = &mut last_not_found_log {
                                 if rec.block_index == next_block_index {
                                     // Log at debug level every 1 min
@@ -133,6 +168,16 @@ impl IngestWorker {
                             if let Err(err) = db.update_metrics() {
                                 log::warn!(logger, "Failed updating ledger db metrics: {}", err);
                             }
+                            match db.num_blocks() {
+                                Ok(num_blocks) => {
+                                    counters::BLOCKS_BEHIND_LEDGER
+                                        .set(num_blocks.saturating_sub(next_block_index) as i64);
+                                    ledger_arrivals.observe(num_blocks);
+                                }
+                                Err(err) => {
+                                    log::warn!(logger, "Failed getting ledger num blocks: {}", err)
+                                }
+                            }
 
                             // Tracing
                             let tracer = tracer!();
@@ -158,6 +203,7 @@ impl IngestWorker {
                                     block_data.block(),
                                     block_data.contents(),
                                     timestamp,
+                                    ledger_arrivals.available_at(next_block_index),
                                 );
                             });
                         }
@@ -174,6 +220,12 @@ impl Drop for IngestWorker {
             self.stop_requested.store(true, Ordering::SeqCst);
             thread.join().expect("Could not join ingest worker thread")
         }
+        if let Some(writer_thread) = self.writer_thread.take() {
+            self.writer_stop_requested.store(true, Ordering::SeqCst);
+            writer_thread
+                .join()
+                .expect("Could not join ingest writer thread")
+        }
     }
 }
 
@@ -193,6 +245,41 @@ impl LastNotFound {
     }
 }
 
+// Helper struct: Keeps track of when we first saw each number of blocks in the
+// ledger, so that we know how long a block waited before we ingested it
+#[derive(Default)]
+struct LedgerArrivals {
+    // Increasing ledger block counts, and when we first saw them
+    seen: VecDeque<(u64, Instant)>,
+}
+
+impl LedgerArrivals {
+    // Record the current number of blocks in the ledger
+    pub fn observe(&mut self, num_blocks: u64) {
+        if self
+            .seen
+            .back()
+            .map_or(true, |(count, _)| *count < num_blocks)
+        {
+            self.seen.push_back((num_blocks, Instant::now()));
+        }
+    }
+
+    // When we first saw the block with this index in the ledger. Blocks before
+    // it are forgotten, since they are processed in order.
+    pub fn available_at(&mut self, block_index: BlockIndex) -> Instant {
+        while let Some((count, _)) = self.seen.front() {
+            if *count > block_index {
+                break;
+            }
+            self.seen.pop_front();
+        }
+        self.seen
+            .front()
+            .map_or_else(Instant::now, |(_, seen_at)| *seen_at)
+    }
+}
+
 /// The peer checkup worker is a thread responsible for periodically checking up
 /// on our peers, if we are active, and making sure they are functioning as
 /// backups. This is a separate thread so that it can be on a time-based
@@ -242,7 +242,8 @@ impl IngestServerTestHelper {
             self.watcher.clone(),
             self.ledger.clone(),
             logger,
-        );
+        )
+        .expect("Failed to create IngestServer");
         server.start().expect("Failed to start IngestServer");
         assert!(
             !server.is_active(),
@@ -385,5 +386,6 @@ pub fn random_output(rng: &mut (impl RngCore + CryptoRng)) -> TxOut {
         public_key: RistrettoPublic::from_random(rng).into(),
         e_fog_hint: EncryptedFogHint::default(),
         e_memo: None,
+        additional_e_fog_hints: Default::default(),
     }
 }
@@ -0,0 +1,62 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+// This integration-level test checks that an ingress key backed up by one
+// ingest node can be restored on a replacement node, after the node which
+// had the key is gone.
+
+use mc_common::logger::{test_with_logger, Logger};
+use mc_fog_ingest_server_test_utils::IngestServerTestHelper;
+
+const BASE_PORT: u16 = 3600;
+
+#[test_with_logger]
+fn test_ingress_key_backup_and_restore(logger: Logger) {
+    let primary_helper = IngestServerTestHelper::new(BASE_PORT, logger.clone());
+    let primary = primary_helper.make_node(1, 1..=1);
+    primary
+        .activate()
+        .expect("Could not activate primary server");
+    let primary_key = primary.get_ingress_key();
+
+    // The backup node and the replacement node only share the recovery DB with
+    // the primary, they are not its peers.
+    let backup_helper = IngestServerTestHelper::from_existing(
+        BASE_PORT,
+        None,
+        None,
+        primary_helper.db_test_context.clone(),
+        logger.clone(),
+    );
+    let backup = backup_helper.make_node(3, 3..=3);
+    let backup_key = backup.get_ingress_key();
+
+    let (sealed_key, backed_up_key) = backup
+        .backup_ingress_key_from_remote(&primary.peer_listen_uri)
+        .expect("failed backing up key from primary");
+    assert_eq!(primary_key, backed_up_key);
+    assert_eq!(
+        backup_key,
+        backup.get_ingress_key(),
+        "Making a backup should not change the ingress key of the backup node"
+    );
+
+    // Lose the primary
+    drop(primary);
+
+    let mut replacement = backup_helper.make_node(5, 5..=5);
+    assert_ne!(primary_key, replacement.get_ingress_key());
+
+    replacement
+        .restore_ingress_key_from_backup(&backup.peer_listen_uri, &sealed_key)
+        .expect("failed restoring key from backup");
+    assert_eq!(primary_key, replacement.get_ingress_key());
+
+    // A backup that is not sealed by the backup node's enclave is rejected
+    let result = replacement.restore_ingress_key_from_backup(&backup.peer_listen_uri, &[0u8; 64]);
+    assert!(
+        result.is_err(),
+        "Restoring a corrupt backup should fail: {:?}",
+        result
+    );
+    assert_eq!(primary_key, replacement.get_ingress_key());
+}
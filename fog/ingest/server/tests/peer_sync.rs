@@ -0,0 +1,52 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+use mc_common::logger::{test_with_logger, Logger};
+use mc_fog_api::ingest_common::PeerSyncStage;
+use mc_fog_ingest_server_test_utils::IngestServerTestHelper;
+use std::{thread::sleep, time::Duration};
+
+const BASE_PORT: u16 = 3700;
+
+// Syncing the ingress key from a peer is checkpointed in the state file, and a
+// repeated sync resumes the same checkpoint without copying the key again.
+#[test_with_logger]
+fn sync_keys_from_remote_is_checkpointed(logger: Logger) {
+    let helper = IngestServerTestHelper::new(BASE_PORT, logger.clone());
+
+    let primary = helper.make_node(1, 1..=1);
+    let mut backup = helper.make_node(2, 2..=2);
+    assert_ne!(primary.get_ingress_key(), backup.get_ingress_key());
+
+    // Give RPC etc. time to start
+    sleep(Duration::from_millis(1000));
+
+    let summary = backup
+        .sync_keys_from_remote(&primary.peer_listen_uri)
+        .expect("failed syncing key from primary");
+    assert_eq!(primary.get_ingress_key(), backup.get_ingress_key());
+    let peer_sync = summary.get_peer_sync();
+    assert_eq!(peer_sync.peer_uri, primary.peer_listen_uri.to_string());
+    assert_eq!(peer_sync.stage, PeerSyncStage::Synced);
+    assert_eq!(peer_sync.attempts, 1);
+    assert!(peer_sync.last_error.is_empty());
+
+    let summary = backup
+        .sync_keys_from_remote(&primary.peer_listen_uri)
+        .expect("failed syncing key from primary again");
+    assert_eq!(summary.get_peer_sync().stage, PeerSyncStage::Synced);
+    assert_eq!(summary.get_peer_sync().attempts, 2);
+
+    // The checkpoint survives a restart
+    let state_file_path = backup.state_file_path.clone();
+    drop(backup);
+    let backup = helper.make_node_with_state(2, 2..=2, state_file_path);
+    assert_eq!(primary.get_ingress_key(), backup.get_ingress_key());
+    let summary = backup.get_ingest_summary();
+    assert_eq!(summary.get_peer_sync().stage, PeerSyncStage::Synced);
+    assert_eq!(summary.get_peer_sync().attempts, 2);
+
+    // Once the backup has another key, the sync is stale and no longer reported
+    backup.set_new_keys().expect("failed setting new keys");
+    assert_ne!(primary.get_ingress_key(), backup.get_ingress_key());
+    assert!(!backup.get_ingest_summary().has_peer_sync());
+}
@@ -0,0 +1,70 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Test that an ingest server whose key expires while processed blocks are
+//! still waiting for the recovery db writes them before it becomes idle
+
+use diesel::connection::SimpleConnection;
+use mc_common::logger::{test_with_logger, Logger};
+use mc_fog_ingest_server_test_utils::IngestServerTestHelper;
+use mc_fog_recovery_db_iface::RecoveryDb;
+use mc_ledger_db::Ledger;
+use std::{thread::sleep, time::Duration};
+
+const BASE_PORT: u16 = 3800;
+
+#[test_with_logger]
+fn retire_with_pending_blocks(logger: Logger) {
+    let mut helper = IngestServerTestHelper::new(BASE_PORT, logger.clone());
+    helper.add_origin_block();
+
+    let nodes = helper.make_nodes(1);
+    let node = &nodes[0];
+    let key = node.get_ingress_key();
+    node.activate().expect("node failed to activate");
+
+    helper.add_test_blocks(3);
+    helper.wait_till_recovery_db_in_sync();
+
+    node.retire().unwrap();
+    let pubkey_expiry = helper
+        .recovery_db
+        .get_ingress_key_status(&key)
+        .unwrap()
+        .expect("ingress key not found")
+        .pubkey_expiry;
+
+    // Get within a few blocks of the pubkey expiry
+    let num_blocks = helper.ledger.num_blocks().unwrap();
+    assert!(num_blocks + 4 <= pubkey_expiry);
+    helper.add_test_blocks((pubkey_expiry - 4 - num_blocks) as u16);
+    helper.wait_till_recovery_db_in_sync();
+
+    // Lock the ingested blocks table, so that the blocks the node processes
+    // wait in its queue
+    let conn = helper.db_test_context.new_conn();
+    conn.batch_execute("BEGIN; LOCK TABLE ingested_blocks IN EXCLUSIVE MODE;")
+        .unwrap();
+
+    // Queue the blocks up to the pubkey expiry, and then the block after it,
+    // which makes the node switch off once the queue is written.
+    helper.add_test_blocks(6);
+    sleep(Duration::from_secs(3));
+    assert!(
+        node.is_active(),
+        "Node should stay active until the blocks before the pubkey expiry are written"
+    );
+
+    conn.batch_execute("COMMIT;").unwrap();
+
+    helper.wait_till_recovery_db_callback(|| pubkey_expiry + 1, &Duration::from_secs(60));
+    sleep(Duration::from_secs(1));
+    assert!(
+        !node.is_active(),
+        "Node should become inactive after it hits the pubkey expiry"
+    );
+    assert_eq!(
+        helper.recovery_db.get_highest_known_block_index().unwrap(),
+        Some(pubkey_expiry),
+        "The block after the pubkey expiry should not have been written"
+    );
+}
@@ -5,22 +5,38 @@ use displaydoc::Display;
 use grpcio::{ChannelBuilder, Environment};
 use mc_attest_verifier::Verifier;
 use mc_blockchain_types::BlockIndex;
-use mc_common::logger::{o, Logger};
-use mc_fog_api::{ledger::KeyImageResultCode, ledger_grpc::FogKeyImageApiClient};
+use mc_common::logger::{log, o, Logger};
+use mc_fog_api::{
+    empty::Empty,
+    external, ledger,
+    ledger::KeyImageResultCode,
+    ledger_grpc::{FogKeyImageApiClient, FogUntrustedKeyImageApiClient},
+};
 use mc_fog_enclave_connection::EnclaveConnection;
 use mc_fog_types::ledger::{
     CheckKeyImagesRequest, CheckKeyImagesResponse, KeyImageQuery, KeyImageResult,
 };
 use mc_fog_uri::FogLedgerUri;
 use mc_transaction_core::ring_signature::KeyImage;
-use mc_util_grpc::{ConnectionUriGrpcioChannel, GrpcRetryConfig};
+use mc_util_grpc::{BasicCredentials, ConnectionUriGrpcioChannel, GrpcRetryConfig};
+use mc_util_uri::ConnectionUri;
 use std::sync::Arc;
 
+/// The number of key images checked per request, when the server does not
+/// advertise a limit, e.g. because it predates the GetCapabilities call.
+pub const DEFAULT_MAX_QUERIES_PER_REQUEST: usize = 1000;
+
 /// An attested connection to the Fog Key Image service.
 pub struct FogKeyImageGrpcClient {
     conn: EnclaveConnection<FogLedgerUri, FogKeyImageApiClient>,
+    /// Client for the calls which are not attested
+    grpc_client: FogKeyImageApiClient,
+    creds: BasicCredentials,
     grpc_retry_config: GrpcRetryConfig,
     uri: FogLedgerUri,
+    /// The number of key images to check per request, once known
+    max_queries_per_request: Option<usize>,
+    logger: Logger,
 }
 
 impl FogKeyImageGrpcClient {
@@ -44,18 +60,81 @@ impl FogKeyImageGrpcClient {
         let ch = ChannelBuilder::default_channel_builder(env).connect_to_uri(&uri, &logger);
 
         let grpc_client = FogKeyImageApiClient::new(ch);
+        let creds = BasicCredentials::new(&uri.username(), &uri.password());
 
         Self {
-            conn: EnclaveConnection::new(uri.clone(), grpc_client, verifier, logger),
+            conn: EnclaveConnection::new(
+                uri.clone(),
+                grpc_client.clone(),
+                verifier,
+                logger.clone(),
+            ),
+            grpc_client,
+            creds,
             grpc_retry_config,
             uri,
+            max_queries_per_request: None,
+            logger,
+        }
+    }
+
+    /// Get the number of key images to check per request.
+    ///
+    /// This is asked from the server once, and falls back to
+    /// DEFAULT_MAX_QUERIES_PER_REQUEST if the server does not say.
+    pub fn max_queries_per_request(&mut self) -> usize {
+        if let Some(max_queries) = self.max_queries_per_request {
+            return max_queries;
         }
+
+        let max_queries = match self.grpc_retry_config.retry(|| {
+            self.grpc_client
+                .get_capabilities_opt(&Empty::new(), self.creds.call_option()?)
+        }) {
+            Ok(capabilities) => match capabilities.get_max_queries_per_request() {
+                0 => usize::MAX,
+                max_queries => usize::try_from(max_queries).unwrap_or(usize::MAX),
+            },
+            Err(err) => {
+                log::debug!(
+                    self.logger,
+                    "Could not get key image service capabilities, checking {} key images per request: {}",
+                    DEFAULT_MAX_QUERIES_PER_REQUEST,
+                    err
+                );
+                DEFAULT_MAX_QUERIES_PER_REQUEST
+            }
+        };
+
+        self.max_queries_per_request = Some(max_queries);
+        max_queries
     }
 
     /// Make a private request to check the validity of several key images
+    ///
+    /// The key images are split across as many requests as the server's
+    /// limit on queries per request requires, and the responses merged.
     pub fn check_key_images(
         &mut self,
         key_images: &[KeyImage],
+    ) -> Result<CheckKeyImagesResponse, Error> {
+        let max_queries = self.max_queries_per_request();
+        if key_images.len() <= max_queries {
+            return self.check_key_images_chunk(key_images);
+        }
+
+        let responses = key_images
+            .chunks(max_queries)
+            .map(|chunk| self.check_key_images_chunk(chunk))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(merge_check_key_images_responses(responses))
+    }
+
+    /// Check key images in one request
+    fn check_key_images_chunk(
+        &mut self,
+        key_images: &[KeyImage],
     ) -> Result<CheckKeyImagesResponse, Error> {
         let request = CheckKeyImagesRequest {
             queries: key_images
@@ -77,6 +156,170 @@ impl FogKeyImageGrpcClient {
     }
 }
 
+/// A non-attested connection to a standalone key image status service.
+///
+/// The server sees which key images are checked, so this is only meant for
+/// watch-only wallets which accept that, e.g. because they run the server
+/// themselves.
+pub struct FogUntrustedKeyImageGrpcClient {
+    grpc_client: FogUntrustedKeyImageApiClient,
+    creds: BasicCredentials,
+    grpc_retry_config: GrpcRetryConfig,
+    uri: FogLedgerUri,
+    /// The number of key images to check per request, once known
+    max_queries_per_request: Option<usize>,
+    logger: Logger,
+}
+
+impl FogUntrustedKeyImageGrpcClient {
+    /// Create a new client object
+    ///
+    /// Arguments:
+    /// uri: The uri to connect to
+    /// grpc_retry_config: The retry policy to use when connecting
+    /// env: The grpc environment (thread pool) to use for this connection
+    /// logger: for logging
+    pub fn new(
+        uri: FogLedgerUri,
+        grpc_retry_config: GrpcRetryConfig,
+        env: Arc<Environment>,
+        logger: Logger,
+    ) -> Self {
+        let logger = logger.new(o!("mc.ledger.cxn" => uri.to_string()));
+
+        let ch = ChannelBuilder::default_channel_builder(env).connect_to_uri(&uri, &logger);
+
+        let grpc_client = FogUntrustedKeyImageApiClient::new(ch);
+        let creds = BasicCredentials::new(&uri.username(), &uri.password());
+
+        Self {
+            grpc_client,
+            creds,
+            grpc_retry_config,
+            uri,
+            max_queries_per_request: None,
+            logger,
+        }
+    }
+
+    /// Get the number of key images to check per request.
+    ///
+    /// This is asked from the server once, and falls back to
+    /// DEFAULT_MAX_QUERIES_PER_REQUEST if the server does not say.
+    pub fn max_queries_per_request(&mut self) -> usize {
+        if let Some(max_queries) = self.max_queries_per_request {
+            return max_queries;
+        }
+
+        let max_queries = match self.grpc_retry_config.retry(|| {
+            self.grpc_client
+                .get_capabilities_opt(&Empty::new(), self.creds.call_option()?)
+        }) {
+            Ok(capabilities) => match capabilities.get_max_queries_per_request() {
+                0 => usize::MAX,
+                max_queries => usize::try_from(max_queries).unwrap_or(usize::MAX),
+            },
+            Err(err) => {
+                log::debug!(
+                    self.logger,
+                    "Could not get key image status service capabilities, checking {} key images per request: {}",
+                    DEFAULT_MAX_QUERIES_PER_REQUEST,
+                    err
+                );
+                DEFAULT_MAX_QUERIES_PER_REQUEST
+            }
+        };
+
+        self.max_queries_per_request = Some(max_queries);
+        max_queries
+    }
+
+    /// Make a non-private request to check the validity of several key images
+    ///
+    /// The key images are split across as many requests as the server's
+    /// limit on queries per request requires, and the responses merged.
+    pub fn check_key_images(
+        &mut self,
+        key_images: &[KeyImage],
+    ) -> Result<CheckKeyImagesResponse, Error> {
+        let max_queries = self.max_queries_per_request();
+        if key_images.len() <= max_queries {
+            return self.check_key_images_chunk(key_images);
+        }
+
+        let responses = key_images
+            .chunks(max_queries)
+            .map(|chunk| self.check_key_images_chunk(chunk))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(merge_check_key_images_responses(responses))
+    }
+
+    /// Check key images in one request
+    fn check_key_images_chunk(
+        &mut self,
+        key_images: &[KeyImage],
+    ) -> Result<CheckKeyImagesResponse, Error> {
+        let mut request = ledger::CheckKeyImagesRequest::new();
+        for key_image in key_images {
+            let mut query = ledger::KeyImageQuery::new();
+            query.set_key_image(external::KeyImage::from(key_image));
+            request.queries.push(query);
+        }
+
+        let response = self
+            .grpc_retry_config
+            .retry(|| {
+                self.grpc_client
+                    .check_key_images_opt(&request, self.creds.call_option()?)
+            })
+            .map_err(|err| Error::Grpc(self.uri.clone(), err))?;
+
+        Ok(CheckKeyImagesResponse {
+            num_blocks: response.num_blocks,
+            global_txo_count: response.global_txo_count,
+            results: response
+                .get_results()
+                .iter()
+                .map(|result| {
+                    Ok(KeyImageResult {
+                        key_image: KeyImage::try_from(result.get_key_image())?,
+                        spent_at: result.spent_at,
+                        timestamp: result.timestamp,
+                        timestamp_result_code: result.timestamp_result_code,
+                        key_image_result_code: result.key_image_result_code,
+                    })
+                })
+                .collect::<Result<Vec<_>, Error>>()?,
+            latest_block_version: response.latest_block_version,
+            max_block_version: response.max_block_version,
+        })
+    }
+}
+
+/// Merge the responses to several check key images requests.
+///
+/// The results are concatenated in order. The ledger may have grown between
+/// the requests, so the block and txo counts are taken from the response with
+/// the fewest blocks, which every result is valid for.
+fn merge_check_key_images_responses(
+    responses: Vec<CheckKeyImagesResponse>,
+) -> CheckKeyImagesResponse {
+    let mut merged = CheckKeyImagesResponse::default();
+    let mut is_first = true;
+    for response in responses {
+        if is_first || response.num_blocks < merged.num_blocks {
+            merged.num_blocks = response.num_blocks;
+            merged.global_txo_count = response.global_txo_count;
+            merged.latest_block_version = response.latest_block_version;
+            merged.max_block_version = response.max_block_version;
+        }
+        is_first = false;
+        merged.results.extend(response.results);
+    }
+    merged
+}
+
 /// An extension trait that adds a convenience method to check the status of a
 /// key image result.
 pub trait KeyImageResultExtension {
@@ -114,3 +357,48 @@ pub enum KeyImageQueryError {
     /// Unknown status code: {0}
     UnknownStatus(u32),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(num_blocks: u64, first_key_image: u64, count: u64) -> CheckKeyImagesResponse {
+        CheckKeyImagesResponse {
+            num_blocks,
+            global_txo_count: num_blocks * 3,
+            results: (first_key_image..first_key_image + count)
+                .map(|i| KeyImageResult {
+                    key_image: KeyImage::from(i),
+                    spent_at: i,
+                    timestamp: 0,
+                    timestamp_result_code: 0,
+                    key_image_result_code: KeyImageResultCode::Spent as u32,
+                })
+                .collect(),
+            latest_block_version: num_blocks as u32,
+            max_block_version: num_blocks as u32 + 1,
+        }
+    }
+
+    #[test]
+    fn merge_keeps_result_order_and_lowest_block_count() {
+        let merged = merge_check_key_images_responses(vec![
+            response(10, 0, 3),
+            response(9, 3, 3),
+            response(11, 6, 2),
+        ]);
+
+        assert_eq!(merged.num_blocks, 9);
+        assert_eq!(merged.global_txo_count, 27);
+        assert_eq!(merged.latest_block_version, 9);
+        assert_eq!(merged.max_block_version, 10);
+        assert_eq!(
+            merged
+                .results
+                .iter()
+                .map(|result| result.spent_at)
+                .collect::<Vec<_>>(),
+            (0..8).collect::<Vec<_>>()
+        );
+    }
+}
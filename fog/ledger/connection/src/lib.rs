@@ -10,7 +10,10 @@ mod error;
 pub use error::Error;
 
 mod key_image;
-pub use key_image::{FogKeyImageGrpcClient, KeyImageQueryError, KeyImageResultExtension};
+pub use key_image::{
+    FogKeyImageGrpcClient, FogUntrustedKeyImageGrpcClient, KeyImageQueryError,
+    KeyImageResultExtension,
+};
 
 mod merkle_proof;
 pub use merkle_proof::{FogMerkleProofGrpcClient, OutputError, OutputResultExtension};
@@ -53,6 +53,12 @@ impl FogMerkleProofGrpcClient {
     }
 
     /// Make a private request for membership proofs for given TxOuts
+    ///
+    /// Arguments:
+    /// * indices: The global indices of the TxOuts
+    /// * merkle_root_block: The block as of which the proofs are made, so that
+    ///   they share its TxOut merkle root. If this is zero, they are made
+    ///   against the current root.
     pub fn get_outputs(
         &mut self,
         indices: Vec<u64>,
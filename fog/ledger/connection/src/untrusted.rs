@@ -3,14 +3,18 @@
 use super::Error;
 use grpcio::{ChannelBuilder, Environment};
 use mc_blockchain_types::BlockIndex;
-use mc_common::logger::Logger;
+use mc_common::logger::{log, Logger};
 use mc_crypto_keys::CompressedRistrettoPublic;
-use mc_fog_api::{fog_common::BlockRange, ledger, ledger_grpc};
+use mc_fog_api::{empty::Empty, fog_common::BlockRange, ledger, ledger_grpc};
 use mc_fog_uri::FogLedgerUri;
 use mc_util_grpc::{BasicCredentials, ConnectionUriGrpcioChannel, GrpcRetryConfig};
 use mc_util_uri::ConnectionUri;
 use std::{ops::Range, sync::Arc};
 
+/// The number of TxOuts looked up per request, when the server does not
+/// advertise a limit, e.g. because it predates the GetCapabilities call.
+pub const DEFAULT_MAX_TX_OUT_QUERIES_PER_REQUEST: usize = 1000;
+
 /// A non-attested connection to untrusted fog ledger endpoints
 pub struct FogUntrustedLedgerGrpcClient {
     uri: FogLedgerUri,
@@ -18,7 +22,8 @@ pub struct FogUntrustedLedgerGrpcClient {
     tx_out_client: ledger_grpc::FogUntrustedTxOutApiClient,
     creds: BasicCredentials,
     grpc_retry_config: GrpcRetryConfig,
-    #[allow(dead_code)]
+    /// The number of TxOuts to look up per request, once known
+    max_queries_per_request: Option<usize>,
     logger: Logger,
 }
 
@@ -44,6 +49,7 @@ impl FogUntrustedLedgerGrpcClient {
             tx_out_client,
             creds,
             grpc_retry_config,
+            max_queries_per_request: None,
             logger,
         }
     }
@@ -74,21 +80,99 @@ impl FogUntrustedLedgerGrpcClient {
             .map_err(|grpcio_error| Error::Grpc(self.uri.clone(), grpcio_error))
     }
 
+    /// Get the number of TxOuts to look up per request.
+    ///
+    /// This is asked from the server once, and falls back to
+    /// DEFAULT_MAX_TX_OUT_QUERIES_PER_REQUEST if the server does not say.
+    pub fn max_queries_per_request(&mut self) -> usize {
+        if let Some(max_queries) = self.max_queries_per_request {
+            return max_queries;
+        }
+
+        let max_queries = match self.grpc_retry_config.retry(|| {
+            self.tx_out_client
+                .get_capabilities_opt(&Empty::new(), self.creds.call_option()?)
+        }) {
+            Ok(capabilities) => match capabilities.get_max_queries_per_request() {
+                0 => usize::MAX,
+                max_queries => usize::try_from(max_queries).unwrap_or(usize::MAX),
+            },
+            Err(err) => {
+                log::debug!(
+                    self.logger,
+                    "Could not get untrusted tx out service capabilities, looking up {} tx outs per request: {}",
+                    DEFAULT_MAX_TX_OUT_QUERIES_PER_REQUEST,
+                    err
+                );
+                DEFAULT_MAX_TX_OUT_QUERIES_PER_REQUEST
+            }
+        };
+
+        self.max_queries_per_request = Some(max_queries);
+        max_queries
+    }
+
     /// Make (non-private) request to check if particular TxOut public keys
     /// exist in the ledger. Note that these are guaranteed by consensus to
     /// be unique.
     ///
+    /// The public keys are split across as many requests as the server's
+    /// limit on queries per request requires, and the responses merged.
+    ///
     /// TODO: Make this marshall the protobuf-generated type into a nicer rust
     /// type?
     pub fn get_tx_outs(
-        &self,
+        &mut self,
         tx_out_pubkeys: impl IntoIterator<Item = CompressedRistrettoPublic>,
+    ) -> Result<ledger::TxOutResponse, Error> {
+        let tx_out_pubkeys: Vec<_> = tx_out_pubkeys.into_iter().collect();
+        let max_queries = self.max_queries_per_request();
+        if tx_out_pubkeys.len() <= max_queries {
+            return self.get_tx_outs_chunk(&tx_out_pubkeys, &[]);
+        }
+
+        let responses = tx_out_pubkeys
+            .chunks(max_queries)
+            .map(|chunk| self.get_tx_outs_chunk(chunk, &[]))
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(merge_tx_out_responses(responses))
+    }
+
+    /// Make (non-private) request to get the TxOuts with particular global
+    /// indices, e.g. to assemble rings. Indices which are not in the ledger
+    /// yet are NotFound.
+    ///
+    /// The indices are split across as many requests as the server's limit on
+    /// queries per request requires, and the responses merged.
+    pub fn get_tx_outs_by_index(
+        &mut self,
+        tx_out_global_indices: impl IntoIterator<Item = u64>,
+    ) -> Result<ledger::TxOutResponse, Error> {
+        let tx_out_global_indices: Vec<_> = tx_out_global_indices.into_iter().collect();
+        let max_queries = self.max_queries_per_request();
+        if tx_out_global_indices.len() <= max_queries {
+            return self.get_tx_outs_chunk(&[], &tx_out_global_indices);
+        }
+
+        let responses = tx_out_global_indices
+            .chunks(max_queries)
+            .map(|chunk| self.get_tx_outs_chunk(&[], chunk))
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(merge_tx_out_responses(responses))
+    }
+
+    /// Look up TxOuts in one request
+    fn get_tx_outs_chunk(
+        &self,
+        tx_out_pubkeys: &[CompressedRistrettoPublic],
+        tx_out_global_indices: &[u64],
     ) -> Result<ledger::TxOutResponse, Error> {
         let mut request = ledger::TxOutRequest::new();
-        for pubkey in tx_out_pubkeys.into_iter() {
+        for pubkey in tx_out_pubkeys {
             // Convert to external::CompressedRistretto
-            request.tx_out_pubkeys.push((&pubkey).into());
+            request.tx_out_pubkeys.push(pubkey.into());
         }
+        request.tx_out_global_indices = tx_out_global_indices.to_vec();
 
         self.grpc_retry_config
             .retry(|| {
@@ -98,3 +182,55 @@ impl FogUntrustedLedgerGrpcClient {
             .map_err(|grpcio_error| Error::Grpc(self.uri.clone(), grpcio_error))
     }
 }
+
+/// Merge the responses to several get tx outs requests.
+///
+/// The results are concatenated in order. The ledger may have grown between
+/// the requests, so the block and txo counts are taken from the response with
+/// the fewest blocks, which every NotFound result is valid for.
+fn merge_tx_out_responses(responses: Vec<ledger::TxOutResponse>) -> ledger::TxOutResponse {
+    let mut merged = ledger::TxOutResponse::new();
+    let mut is_first = true;
+    for mut response in responses {
+        if is_first || response.num_blocks < merged.num_blocks {
+            merged.num_blocks = response.num_blocks;
+            merged.global_txo_count = response.global_txo_count;
+        }
+        is_first = false;
+        merged.results.append(&mut response.results);
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(num_blocks: u64, first_index: u64, count: u64) -> ledger::TxOutResponse {
+        let mut response = ledger::TxOutResponse::new();
+        response.num_blocks = num_blocks;
+        response.global_txo_count = num_blocks * 3;
+        for index in first_index..first_index + count {
+            let mut result = ledger::TxOutResult::new();
+            result.tx_out_global_index = index;
+            result.result_code = ledger::TxOutResultCode::Found;
+            response.results.push(result);
+        }
+        response
+    }
+
+    #[test]
+    fn merge_keeps_result_order_and_lowest_block_count() {
+        let merged = merge_tx_out_responses(vec![response(10, 0, 3), response(9, 3, 2)]);
+        assert_eq!(merged.num_blocks, 9);
+        assert_eq!(merged.global_txo_count, 27);
+        let indices: Vec<u64> = merged
+            .results
+            .iter()
+            .map(|result| result.tx_out_global_index)
+            .collect();
+        assert_eq!(indices, vec![0, 1, 2, 3, 4]);
+
+        assert_eq!(merge_tx_out_responses(vec![]).results.len(), 0);
+    }
+}
@@ -40,6 +40,15 @@ pub enum Error {
 
     /// Prost decode error
     ProstDecode,
+
+    /// Too many key image queries in one request: {0} > {1}
+    TooManyQueries(u64, u64),
+
+    /// No responses from store enclaves to collate
+    NoShardQueryResponses,
+
+    /// A store enclave's response did not match the client's query
+    ShardQueryResponseMismatch,
 }
 
 /// An error when something goes wrong with adding a record
@@ -15,7 +15,11 @@ pub use crate::{
 };
 use alloc::vec::Vec;
 use core::result::Result as StdResult;
-use mc_attest_enclave_api::{ClientAuthRequest, ClientAuthResponse, ClientSession, EnclaveMessage};
+use mc_attest_core::VerificationReport;
+use mc_attest_enclave_api::{
+    ClientAuthRequest, ClientAuthResponse, ClientSession, EnclaveMessage, PeerAuthRequest,
+    PeerAuthResponse, PeerSession,
+};
 use mc_common::ResponderId;
 use mc_crypto_keys::X25519Public;
 pub use mc_fog_types::ledger::{
@@ -57,6 +61,35 @@ pub struct UntrustedKeyImageQueryResponse {
 
     /// The (max of) latest_block_version and mc_transaction_core::BLOCK_VERSION
     pub max_block_version: u32,
+
+    /// The most key image queries the server accepts in one request, if
+    /// limited
+    pub max_queries: Option<u64>,
+}
+
+/// A client's key image check, forwarded by a router enclave to a store
+/// enclave.
+///
+/// This is the plaintext of the messages exchanged between the router and the
+/// store enclaves, which are encrypted for the peer session.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ShardKeyImageQueryRequest {
+    /// The client session the check was made on. The store echoes it back, so
+    /// that the router cannot be made to send a response to another client.
+    pub client_session: Vec<u8>,
+
+    /// An encoded fog_types::ledger::CheckKeyImagesRequest
+    pub check_key_images_request: Vec<u8>,
+}
+
+/// A store enclave's response to a [ShardKeyImageQueryRequest].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ShardKeyImageQueryResponse {
+    /// The client session from the request.
+    pub client_session: Vec<u8>,
+
+    /// An encoded fog_types::ledger::CheckKeyImagesResponse
+    pub check_key_images_response: Vec<u8>,
 }
 
 /// The API for interacting with a ledger node's enclave.
@@ -98,6 +131,56 @@ pub trait LedgerEnclave: ReportableEnclave {
 
     /// Add a key image data to the oram Using thrm -rf targete key image
     fn add_key_image_data(&self, records: Vec<KeyImageData>) -> Result<()>;
+
+    // SHARDING
+    //
+    // A router enclave accepts client key image checks, and forwards them to
+    // store enclaves which each hold a part of the key images. Every store
+    // enclave gets every key image, so that the untrusted side cannot learn
+    // which store holds the key images of a user.
+
+    /// Initiate peering with a store enclave
+    fn peer_init(&self, peer_id: &ResponderId) -> Result<PeerAuthRequest>;
+
+    /// Accept a connection proposal from a router enclave
+    fn peer_accept(&self, req: PeerAuthRequest) -> Result<(PeerAuthResponse, PeerSession)>;
+
+    /// Handle the store enclave's peer_accept response to form the connection
+    fn peer_connect(
+        &self,
+        peer_id: &ResponderId,
+        msg: PeerAuthResponse,
+    ) -> Result<(PeerSession, VerificationReport)>;
+
+    /// Close a connection with a peer
+    fn peer_close(&self, session_id: &PeerSession) -> Result<()>;
+
+    /// Decrypt a client's CheckKeyImagesRequest, and encrypt it for each of
+    /// the store enclaves, in the same order as the sessions.
+    ///
+    /// Requests with more than `max_queries` key images are rejected.
+    fn create_shard_key_image_queries(
+        &self,
+        client_query: EnclaveMessage<ClientSession>,
+        shard_sessions: Vec<PeerSession>,
+        max_queries: Option<u64>,
+    ) -> Result<Vec<EnclaveMessage<PeerSession>>>;
+
+    /// Service a router enclave's encrypted ShardKeyImageQueryRequest
+    fn shard_check_key_images(
+        &self,
+        payload: EnclaveMessage<PeerSession>,
+        untrusted_keyimagequery_response: UntrustedKeyImageQueryResponse,
+    ) -> Result<EnclaveMessage<PeerSession>>;
+
+    /// Merge the responses of the store enclaves to a client's key image check
+    /// made with `create_shard_key_image_queries`, and encrypt the result for
+    /// the client.
+    fn collate_shard_key_image_responses(
+        &self,
+        client_session: ClientSession,
+        shard_responses: Vec<EnclaveMessage<PeerSession>>,
+    ) -> Result<Vec<u8>>;
 }
 
 /// Helper trait which reduces boiler-plate in untrusted side
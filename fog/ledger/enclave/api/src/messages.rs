@@ -4,7 +4,10 @@
 use crate::UntrustedKeyImageQueryResponse;
 use alloc::vec::Vec;
 use mc_attest_core::{Quote, Report, TargetInfo, VerificationReport};
-use mc_attest_enclave_api::{ClientAuthRequest, ClientSession, EnclaveMessage};
+use mc_attest_enclave_api::{
+    ClientAuthRequest, ClientSession, EnclaveMessage, PeerAuthRequest, PeerAuthResponse,
+    PeerSession,
+};
 use mc_common::ResponderId;
 use mc_fog_types::ledger::GetOutputsResponse;
 use mc_transaction_core::ring_signature::KeyImage;
@@ -101,4 +104,39 @@ pub enum EnclaveCall {
     ///
     ///  Add key image data to the ORAM.
     AddKeyImageData(Vec<KeyImageData>),
+
+    /// The [LedgerEnclave::peer_init()] method.
+    ///
+    /// Initiate peering with a store enclave.
+    PeerInit(ResponderId),
+
+    /// The [LedgerEnclave::peer_accept()] method.
+    ///
+    /// Accept a connection from a router enclave.
+    PeerAccept(PeerAuthRequest),
+
+    /// The [LedgerEnclave::peer_connect()] method.
+    ///
+    /// Handle the store enclave's peer_accept response to form the connection.
+    PeerConnect(ResponderId, PeerAuthResponse),
+
+    /// The [LedgerEnclave::peer_close()] method.
+    ///
+    /// Tears down any in-enclave state about a peer association.
+    PeerClose(PeerSession),
+
+    /// The [LedgerEnclave::create_shard_key_image_queries()] method.
+    ///
+    /// Forward a key image check from a client to each of the store enclaves.
+    CreateShardKeyImageQueries(EnclaveMessage<ClientSession>, Vec<PeerSession>, Option<u64>),
+
+    /// The [LedgerEnclave::shard_check_key_images()] method.
+    ///
+    /// Check the key images forwarded by a router enclave.
+    ShardCheckKeyImages(EnclaveMessage<PeerSession>, UntrustedKeyImageQueryResponse),
+
+    /// The [LedgerEnclave::collate_shard_key_image_responses()] method.
+    ///
+    /// Merge the responses of the store enclaves for a client.
+    CollateShardKeyImageResponses(ClientSession, Vec<EnclaveMessage<PeerSession>>),
 }
@@ -13,10 +13,14 @@
 extern crate alloc;
 
 mod key_image_store;
+mod sharding;
 use alloc::vec::Vec;
 use key_image_store::{KeyImageStore, StorageDataSize, StorageMetaSize};
 use mc_attest_core::{IasNonce, Quote, QuoteNonce, Report, TargetInfo, VerificationReport};
-use mc_attest_enclave_api::{ClientAuthRequest, ClientAuthResponse, ClientSession, EnclaveMessage};
+use mc_attest_enclave_api::{
+    ClientAuthRequest, ClientAuthResponse, ClientSession, EnclaveMessage, PeerAuthRequest,
+    PeerAuthResponse, PeerSession,
+};
 use mc_common::{
     logger::{log, Logger},
     ResponderId,
@@ -24,7 +28,8 @@ use mc_common::{
 use mc_crypto_ake_enclave::{AkeEnclaveState, NullIdentity};
 use mc_crypto_keys::X25519Public;
 use mc_fog_ledger_enclave_api::{
-    Error, KeyImageData, LedgerEnclave, OutputContext, Result, UntrustedKeyImageQueryResponse,
+    Error, KeyImageData, LedgerEnclave, OutputContext, Result, ShardKeyImageQueryRequest,
+    ShardKeyImageQueryResponse, UntrustedKeyImageQueryResponse,
 };
 use mc_fog_types::ledger::{
     CheckKeyImagesRequest, CheckKeyImagesResponse, GetOutputsRequest, GetOutputsResponse,
@@ -32,6 +37,7 @@ use mc_fog_types::ledger::{
 use mc_oblivious_traits::ORAMStorageCreator;
 use mc_sgx_compat::sync::Mutex;
 use mc_sgx_report_cache_api::{ReportableEnclave, Result as ReportableEnclaveResult};
+use sharding::merge_shard_key_image_responses;
 
 /// In-enclave state associated to the ledger enclaves
 pub struct SgxLedgerEnclave<OSC>
@@ -61,6 +67,58 @@ where
             logger,
         }
     }
+
+    /// Decode a key image check request, and reject it if it has more than
+    /// `max_queries` key images
+    fn decode_check_key_images_request(
+        &self,
+        plaintext: &[u8],
+        max_queries: Option<u64>,
+    ) -> Result<CheckKeyImagesRequest> {
+        let req: CheckKeyImagesRequest = mc_util_serial::decode(plaintext).map_err(|e| {
+            log::error!(self.logger, "Could not decode user request: {}", e);
+            Error::ProstDecode
+        })?;
+
+        if let Some(max_queries) = max_queries {
+            let num_queries = req.queries.len() as u64;
+            if num_queries > max_queries {
+                return Err(Error::TooManyQueries(num_queries, max_queries));
+            }
+        }
+
+        Ok(req)
+    }
+
+    /// Evaluate a decoded key image check request against the key image store
+    fn check_key_images_impl(
+        &self,
+        req: CheckKeyImagesRequest,
+        untrusted_key_image_query_response: UntrustedKeyImageQueryResponse,
+    ) -> Result<CheckKeyImagesResponse> {
+        let mut resp = CheckKeyImagesResponse {
+            num_blocks: untrusted_key_image_query_response.highest_processed_block_count,
+            results: Default::default(),
+            global_txo_count: untrusted_key_image_query_response
+                .last_known_block_cumulative_txo_count,
+            latest_block_version: untrusted_key_image_query_response.latest_block_version,
+            max_block_version: untrusted_key_image_query_response.max_block_version,
+        };
+
+        // Do the scope lock of keyimagetore
+        {
+            let mut lk = self.key_image_store.lock()?;
+            let store = lk.as_mut().ok_or(Error::EnclaveNotInitialized)?;
+
+            resp.results = req
+                .queries
+                .iter() //  get the key images used to find the key image data using the oram
+                .map(|key| store.find_record(&key.key_image))
+                .collect();
+        }
+
+        Ok(resp)
+    }
 }
 
 /// Implementation of the reportable enclave for sgxledger enclave
@@ -92,7 +150,9 @@ where
     OSC: ORAMStorageCreator<StorageDataSize, StorageMetaSize>,
 {
     fn enclave_init(&self, self_id: &ResponderId, desired_capacity: u64) -> Result<()> {
-        self.ake.init(Default::default(), self_id.clone())?;
+        // The client responder id doubles as the peer responder id, so that a
+        // router enclave can connect to this enclave through its client port.
+        self.ake.init(self_id.clone(), self_id.clone())?;
         let mut lk = self.key_image_store.lock()?;
 
         *lk = Some(KeyImageStore::new(desired_capacity, self.logger.clone()));
@@ -145,31 +205,12 @@ where
         let channel_id = msg.channel_id.clone(); //client session does not implement copy trait so clone
         let user_plaintext = self.ake.client_decrypt(msg)?;
 
-        let req: CheckKeyImagesRequest = mc_util_serial::decode(&user_plaintext).map_err(|e| {
-            log::error!(self.logger, "Could not decode user request: {}", e);
-            Error::ProstDecode
-        })?;
-
-        let mut resp = CheckKeyImagesResponse {
-            num_blocks: untrusted_key_image_query_response.highest_processed_block_count,
-            results: Default::default(),
-            global_txo_count: untrusted_key_image_query_response
-                .last_known_block_cumulative_txo_count,
-            latest_block_version: untrusted_key_image_query_response.latest_block_version,
-            max_block_version: untrusted_key_image_query_response.max_block_version,
-        };
-
-        // Do the scope lock of keyimagetore
-        {
-            let mut lk = self.key_image_store.lock()?;
-            let store = lk.as_mut().ok_or(Error::EnclaveNotInitialized)?;
+        let req = self.decode_check_key_images_request(
+            &user_plaintext,
+            untrusted_key_image_query_response.max_queries,
+        )?;
 
-            resp.results = req
-                .queries
-                .iter() //  get the key images used to find the key image data using the oram
-                .map(|key| store.find_record(&key.key_image))
-                .collect();
-        }
+        let resp = self.check_key_images_impl(req, untrusted_key_image_query_response)?;
 
         let response_plaintext_bytes = mc_util_serial::encode(&resp);
 
@@ -191,6 +232,110 @@ where
 
         Ok(())
     }
+
+    // Sharding
+
+    fn peer_init(&self, peer_id: &ResponderId) -> Result<PeerAuthRequest> {
+        Ok(self.ake.peer_init(peer_id)?)
+    }
+
+    fn peer_accept(&self, req: PeerAuthRequest) -> Result<(PeerAuthResponse, PeerSession)> {
+        Ok(self.ake.peer_accept(req)?)
+    }
+
+    fn peer_connect(
+        &self,
+        peer_id: &ResponderId,
+        msg: PeerAuthResponse,
+    ) -> Result<(PeerSession, VerificationReport)> {
+        Ok(self.ake.peer_connect(peer_id, msg)?)
+    }
+
+    fn peer_close(&self, session_id: &PeerSession) -> Result<()> {
+        Ok(self.ake.peer_close(session_id)?)
+    }
+
+    fn create_shard_key_image_queries(
+        &self,
+        client_query: EnclaveMessage<ClientSession>,
+        shard_sessions: Vec<PeerSession>,
+        max_queries: Option<u64>,
+    ) -> Result<Vec<EnclaveMessage<PeerSession>>> {
+        let client_session = client_query.channel_id.clone();
+        let aad = client_query.aad.clone();
+        let check_key_images_request = self.ake.client_decrypt(client_query)?;
+
+        // Reject invalid requests here, rather than in every store enclave
+        self.decode_check_key_images_request(&check_key_images_request, max_queries)?;
+
+        let shard_query_request = mc_util_serial::serialize(&ShardKeyImageQueryRequest {
+            client_session: client_session.0,
+            check_key_images_request,
+        })?;
+
+        shard_sessions
+            .iter()
+            .map(|session| Ok(self.ake.peer_encrypt(session, &aad, &shard_query_request)?))
+            .collect()
+    }
+
+    fn shard_check_key_images(
+        &self,
+        payload: EnclaveMessage<PeerSession>,
+        untrusted_key_image_query_response: UntrustedKeyImageQueryResponse,
+    ) -> Result<EnclaveMessage<PeerSession>> {
+        let peer_session = payload.channel_id.clone();
+        let aad = payload.aad.clone();
+        let plaintext = self.ake.peer_decrypt(payload)?;
+        let shard_query_request: ShardKeyImageQueryRequest =
+            mc_util_serial::deserialize(&plaintext)?;
+
+        let req = self.decode_check_key_images_request(
+            &shard_query_request.check_key_images_request,
+            untrusted_key_image_query_response.max_queries,
+        )?;
+
+        let resp = self.check_key_images_impl(req, untrusted_key_image_query_response)?;
+
+        let shard_query_response = mc_util_serial::serialize(&ShardKeyImageQueryResponse {
+            client_session: shard_query_request.client_session,
+            check_key_images_response: mc_util_serial::encode(&resp),
+        })?;
+
+        Ok(self
+            .ake
+            .peer_encrypt(&peer_session, &aad, &shard_query_response)?)
+    }
+
+    fn collate_shard_key_image_responses(
+        &self,
+        client_session: ClientSession,
+        shard_responses: Vec<EnclaveMessage<PeerSession>>,
+    ) -> Result<Vec<u8>> {
+        let responses = shard_responses
+            .into_iter()
+            .map(|msg| {
+                let plaintext = self.ake.peer_decrypt(msg)?;
+                let shard_query_response: ShardKeyImageQueryResponse =
+                    mc_util_serial::deserialize(&plaintext)?;
+                if shard_query_response.client_session != client_session.0 {
+                    return Err(Error::ShardQueryResponseMismatch);
+                }
+                Ok(mc_util_serial::decode(
+                    &shard_query_response.check_key_images_response,
+                )?)
+            })
+            .collect::<Result<Vec<CheckKeyImagesResponse>>>()?;
+
+        let resp = merge_shard_key_image_responses(responses)?;
+        let response_plaintext_bytes = mc_util_serial::encode(&resp);
+
+        let response = self
+            .ake
+            .client_encrypt(&client_session, &[], &response_plaintext_bytes)?;
+
+        Ok(response.data)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,158 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Merging the key image check responses of the store enclaves of a sharded
+//! key image store.
+//!
+//! Every store enclave is asked about every key image, and at most one of
+//! them holds a given key image. The merge must not reveal which store
+//! answered, so the key image results are combined with conditional moves.
+
+use aligned_cmov::{subtle::ConstantTimeEq, CMov};
+use alloc::vec::Vec;
+use mc_fog_ledger_enclave_api::{Error, Result};
+use mc_fog_types::ledger::{CheckKeyImagesResponse, KeyImageResult, KeyImageResultCode};
+
+/// Merge the key image check responses of the store enclaves into one
+/// response for the client.
+///
+/// * A key image result is taken from the store which found the key image
+///   spent, if any, and from the first store otherwise.
+/// * The block and txo counts and block versions are taken from the store with
+///   the lowest block count, since the client may only assume that every store
+///   has processed those blocks.
+pub fn merge_shard_key_image_responses(
+    responses: Vec<CheckKeyImagesResponse>,
+) -> Result<CheckKeyImagesResponse> {
+    let mut responses = responses.into_iter();
+    let mut merged = responses.next().ok_or(Error::NoShardQueryResponses)?;
+
+    for response in responses {
+        if response.results.len() != merged.results.len() {
+            return Err(Error::ShardQueryResponseMismatch);
+        }
+        for (merged_result, result) in merged.results.iter_mut().zip(response.results.iter()) {
+            merge_key_image_result(merged_result, result)?;
+        }
+
+        if response.num_blocks < merged.num_blocks {
+            merged.num_blocks = response.num_blocks;
+            merged.global_txo_count = response.global_txo_count;
+            merged.latest_block_version = response.latest_block_version;
+            merged.max_block_version = response.max_block_version;
+        }
+    }
+
+    Ok(merged)
+}
+
+/// Replace `merged` with `result` if `result` found the key image spent,
+/// without branching on the result code.
+fn merge_key_image_result(merged: &mut KeyImageResult, result: &KeyImageResult) -> Result<()> {
+    if merged.key_image != result.key_image {
+        return Err(Error::ShardQueryResponseMismatch);
+    }
+
+    let spent = result
+        .key_image_result_code
+        .ct_eq(&(KeyImageResultCode::Spent as u32));
+
+    merged.spent_at.cmov(spent, &result.spent_at);
+    merged.timestamp.cmov(spent, &result.timestamp);
+    merged
+        .timestamp_result_code
+        .cmov(spent, &result.timestamp_result_code);
+    merged
+        .key_image_result_code
+        .cmov(spent, &result.key_image_result_code);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use mc_transaction_core::ring_signature::KeyImage;
+
+    fn key_image_result(key_image: u64, spent_at: Option<u64>) -> KeyImageResult {
+        KeyImageResult {
+            key_image: KeyImage::from(key_image),
+            spent_at: spent_at.unwrap_or(u64::MAX),
+            timestamp: spent_at.map(|index| index * 10).unwrap_or(u64::MAX),
+            timestamp_result_code: 1,
+            key_image_result_code: if spent_at.is_some() {
+                KeyImageResultCode::Spent as u32
+            } else {
+                KeyImageResultCode::NotSpent as u32
+            },
+        }
+    }
+
+    fn response(num_blocks: u64, results: Vec<KeyImageResult>) -> CheckKeyImagesResponse {
+        CheckKeyImagesResponse {
+            num_blocks,
+            global_txo_count: num_blocks * 2,
+            results,
+            latest_block_version: num_blocks as u32,
+            max_block_version: num_blocks as u32,
+        }
+    }
+
+    #[test]
+    fn merge_prefers_spent_results() {
+        let merged = merge_shard_key_image_responses(vec![
+            response(
+                5,
+                vec![
+                    key_image_result(1, None),
+                    key_image_result(2, Some(3)),
+                    key_image_result(3, None),
+                ],
+            ),
+            response(
+                4,
+                vec![
+                    key_image_result(1, Some(2)),
+                    key_image_result(2, None),
+                    key_image_result(3, None),
+                ],
+            ),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            merged.results,
+            vec![
+                key_image_result(1, Some(2)),
+                key_image_result(2, Some(3)),
+                key_image_result(3, None),
+            ]
+        );
+        assert_eq!(merged.num_blocks, 4);
+        assert_eq!(merged.global_txo_count, 8);
+        assert_eq!(merged.latest_block_version, 4);
+    }
+
+    #[test]
+    fn merge_rejects_mismatched_responses() {
+        assert!(matches!(
+            merge_shard_key_image_responses(vec![]),
+            Err(Error::NoShardQueryResponses)
+        ));
+
+        assert!(matches!(
+            merge_shard_key_image_responses(vec![
+                response(1, vec![key_image_result(1, None)]),
+                response(1, vec![key_image_result(2, None)]),
+            ]),
+            Err(Error::ShardQueryResponseMismatch)
+        ));
+
+        assert!(matches!(
+            merge_shard_key_image_responses(vec![
+                response(1, vec![key_image_result(1, None)]),
+                response(1, vec![]),
+            ]),
+            Err(Error::ShardQueryResponseMismatch)
+        ));
+    }
+}
@@ -61,6 +61,24 @@ pub fn ecall_dispatcher(inbuf: &[u8]) -> Result<Vec<u8>, sgx_status_t> {
         }
         // Add Key Image Data
         EnclaveCall::AddKeyImageData(records) => serialize(&ENCLAVE.add_key_image_data(records)),
+        // Sharding
+        EnclaveCall::PeerInit(peer_id) => serialize(&ENCLAVE.peer_init(&peer_id)),
+        EnclaveCall::PeerAccept(req) => serialize(&ENCLAVE.peer_accept(req)),
+        EnclaveCall::PeerConnect(peer_id, msg) => serialize(&ENCLAVE.peer_connect(&peer_id, msg)),
+        EnclaveCall::PeerClose(session_id) => serialize(&ENCLAVE.peer_close(&session_id)),
+        EnclaveCall::CreateShardKeyImageQueries(client_query, shard_sessions, max_queries) => {
+            serialize(&ENCLAVE.create_shard_key_image_queries(
+                client_query,
+                shard_sessions,
+                max_queries,
+            ))
+        }
+        EnclaveCall::ShardCheckKeyImages(payload, untrusted_keyimagequery_response) => {
+            serialize(&ENCLAVE.shard_check_key_images(payload, untrusted_keyimagequery_response))
+        }
+        EnclaveCall::CollateShardKeyImageResponses(client_session, shard_responses) => {
+            serialize(&ENCLAVE.collate_shard_key_image_responses(client_session, shard_responses))
+        }
     }
     .or(Err(sgx_status_t::SGX_ERROR_UNEXPECTED))
 }
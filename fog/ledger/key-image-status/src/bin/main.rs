@@ -0,0 +1,78 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+#![deny(missing_docs)]
+
+//! Key Image Status Server target
+
+use grpcio::{RpcStatus, RpcStatusCode};
+use mc_common::{
+    logger::{create_app_logger, log, o},
+    time::SystemTimeProvider,
+};
+use mc_fog_ledger_key_image_status::{KeyImageStatusServer, KeyImageStatusServerConfig};
+use mc_ledger_db::{Ledger, LedgerDB};
+use mc_util_cli::ParserWithBuildInfo;
+use mc_util_grpc::AdminServer;
+use mc_watcher::watcher_db::WatcherDB;
+use std::sync::Arc;
+
+fn main() {
+    mc_common::setup_panic_handler();
+    let _sentry_guard = mc_common::sentry::init();
+
+    let (logger, _global_logger_guard) = create_app_logger(o!());
+    let config = KeyImageStatusServerConfig::parse();
+
+    let db = LedgerDB::open(&config.ledger_db).expect("Could not read ledger DB");
+    // This server doesn't sync the ledger, the ledger db must be kept in sync
+    // by another process.
+    if db.num_blocks().expect("Could not read ledger DB") == 0 {
+        panic!(
+            "The ledger DB {:?} is empty, it must be synced by another process, such as mobilecoind",
+            config.ledger_db
+        );
+    }
+    let watcher = config.watcher_db.as_ref().map(|watcher_db| {
+        WatcherDB::open_ro(watcher_db, logger.clone()).expect("Could not open watcher DB")
+    });
+    log::info!(
+        logger,
+        "Serving key image checks from ledger DB {:?}, with {} block timestamps",
+        config.ledger_db,
+        if watcher.is_some() {
+            "watcher DB"
+        } else {
+            "block signature"
+        }
+    );
+
+    let mut server = KeyImageStatusServer::new(
+        config.clone(),
+        db,
+        watcher,
+        SystemTimeProvider::default(),
+        logger.clone(),
+    );
+
+    server.start().expect("Server failed to start");
+
+    let config2 = config.clone();
+    let get_config_json = Arc::new(move || {
+        serde_json::to_string(&config2)
+            .map_err(|err| RpcStatus::with_message(RpcStatusCode::INTERNAL, format!("{:?}", err)))
+    });
+    let _admin_server = config.admin_listen_uri.as_ref().map(|admin_listen_uri| {
+        AdminServer::start(
+            None,
+            admin_listen_uri,
+            "Fog Ledger Key Image Status".to_owned(),
+            config.client_listen_uri.to_string(),
+            Some(get_config_json),
+            logger,
+        )
+        .expect("Failed starting admin server")
+    });
+
+    loop {
+        std::thread::sleep(std::time::Duration::from_millis(1000));
+    }
+}
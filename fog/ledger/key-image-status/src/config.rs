@@ -0,0 +1,61 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Configuration parameters for the key image status server
+
+use clap::Parser;
+use mc_fog_uri::FogLedgerUri;
+use mc_util_parse::parse_duration_in_seconds;
+use mc_util_uri::AdminUri;
+use serde::Serialize;
+use std::{path::PathBuf, time::Duration};
+
+/// Configuration parameters for the key image status server
+#[derive(Clone, Parser, Serialize)]
+#[clap(version)]
+pub struct KeyImageStatusServerConfig {
+    /// gRPC listening URI for client requests.
+    #[clap(long, env = "MC_CLIENT_LISTEN_URI")]
+    pub client_listen_uri: FogLedgerUri,
+
+    /// Path to ledger db (lmdb), which another process such as mobilecoind
+    /// keeps in sync with the network
+    #[clap(long, parse(from_os_str), env = "MC_LEDGER_DB")]
+    pub ledger_db: PathBuf,
+
+    /// Optional path to watcher db (lmdb) - includes block timestamps.
+    /// Without it, timestamps come from the block signatures in the ledger db.
+    #[clap(long, parse(from_os_str), env = "MC_WATCHER_DB")]
+    pub watcher_db: Option<PathBuf>,
+
+    /// Optional admin listening URI.
+    #[clap(long, env = "MC_ADMIN_LISTEN_URI")]
+    pub admin_listen_uri: Option<AdminUri>,
+
+    /// Enables authenticating client requests using Authorization tokens using
+    /// the provided hex-encoded 32 bytes shared secret.
+    #[clap(long, parse(try_from_str = hex::FromHex::from_hex), env = "MC_CLIENT_AUTH_TOKEN_SECRET")]
+    pub client_auth_token_secret: Option<[u8; 32]>,
+
+    /// Maximal client authentication token lifetime, in seconds (only relevant
+    /// when --client-auth-token-secret is used. Defaults to 86400 - 24
+    /// hours).
+    #[clap(long, default_value = "86400", parse(try_from_str = parse_duration_in_seconds), env = "MC_CLIENT_AUTH_TOKEN_MAX_LIFETIME")]
+    pub client_auth_token_max_lifetime: Duration,
+
+    /// The most key image queries accepted in one CheckKeyImages request.
+    /// Requests with more queries are rejected, and clients are expected to
+    /// split their queries using the limit from GetCapabilities. Zero means
+    /// there is no limit.
+    #[clap(
+        long,
+        default_value = "10000",
+        env = "MC_MAX_KEY_IMAGE_QUERIES_PER_REQUEST"
+    )]
+    pub max_key_image_queries_per_request: u64,
+
+    /// How long the ledger db may go without growing, in seconds, before the
+    /// server assumes that it is no longer kept in sync by another process, and
+    /// refuses requests until it grows again. Zero disables the check.
+    #[clap(long, default_value = "3600", parse(try_from_str = parse_duration_in_seconds), env = "MC_MAX_LEDGER_STALENESS")]
+    pub max_ledger_staleness: Duration,
+}
@@ -0,0 +1,98 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Checks that the ledger db is kept in sync with the network.
+//!
+//! The key image status server does not sync the ledger itself, another
+//! process such as `mobilecoind` must append blocks to the ledger db. If that
+//! process stops, every key image spent since would be reported as not spent,
+//! so the server stops answering once the ledger hasn't grown for a while.
+
+use mc_ledger_db::Ledger;
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Tracks when the ledger db last grew
+#[derive(Clone)]
+pub struct LedgerProgress<L: Ledger + Clone> {
+    ledger: L,
+    /// How long the ledger may go without growing. Zero disables the check.
+    max_staleness: Duration,
+    /// The last number of blocks seen, and when it was first seen
+    last_growth: Arc<Mutex<(u64, Instant)>>,
+}
+
+impl<L: Ledger + Clone> LedgerProgress<L> {
+    /// Start tracking the growth of a ledger
+    pub fn new(ledger: L, max_staleness: Duration) -> Self {
+        let num_blocks = ledger.num_blocks().unwrap_or(0);
+        Self {
+            ledger,
+            max_staleness,
+            last_growth: Arc::new(Mutex::new((num_blocks, Instant::now()))),
+        }
+    }
+
+    /// Check that the ledger grew within the max staleness, or return how long
+    /// it has not grown for. A ledger which can't be read counts as not
+    /// growing.
+    pub fn check(&self) -> Result<(), Duration> {
+        let mut last_growth = self.last_growth.lock().expect("mutex poisoned");
+        if let Ok(num_blocks) = self.ledger.num_blocks() {
+            if num_blocks > last_growth.0 {
+                *last_growth = (num_blocks, Instant::now());
+            }
+        }
+
+        let stale_for = last_growth.1.elapsed();
+        if self.max_staleness.is_zero() || stale_for <= self.max_staleness {
+            Ok(())
+        } else {
+            Err(stale_for)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_account_keys::AccountKey;
+    use mc_blockchain_types::BlockVersion;
+    use mc_ledger_db::{
+        test_utils::{add_block_to_ledger, create_ledger},
+        LedgerDB,
+    };
+    use mc_transaction_core::{tokens::Mob, Amount, Token};
+    use mc_util_test_helper::{RngType, SeedableRng};
+
+    #[test]
+    fn ledger_must_keep_growing() {
+        let mut rng = RngType::from_seed([1u8; 32]);
+        let mut ledger = create_ledger();
+        let recipient = AccountKey::random(&mut rng).default_subaddress();
+        let mut add_block = |ledger: &mut LedgerDB| {
+            add_block_to_ledger(
+                ledger,
+                BlockVersion::MAX,
+                &[recipient.clone()],
+                Amount::new(10, Mob::ID),
+                &[],
+                &mut rng,
+            )
+            .unwrap()
+        };
+        add_block(&mut ledger);
+
+        let progress = LedgerProgress::new(ledger.clone(), Duration::from_millis(100));
+        let never_stale = LedgerProgress::new(ledger.clone(), Duration::ZERO);
+        assert_eq!(progress.check(), Ok(()));
+
+        std::thread::sleep(Duration::from_millis(150));
+        assert!(progress.check().unwrap_err() > Duration::from_millis(100));
+        assert_eq!(never_stale.check(), Ok(()));
+
+        add_block(&mut ledger);
+        assert_eq!(progress.check(), Ok(()));
+    }
+}
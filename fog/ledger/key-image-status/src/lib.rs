@@ -0,0 +1,16 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! A server answering key image spent-status queries from a ledger db,
+//! without an enclave, for watch-only wallets and auditing tools.
+
+#![deny(missing_docs)]
+
+mod config;
+mod ledger_progress;
+mod server;
+mod service;
+
+pub use crate::{
+    config::KeyImageStatusServerConfig, ledger_progress::LedgerProgress,
+    server::KeyImageStatusServer, service::KeyImageStatusService,
+};
@@ -0,0 +1,132 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! The gRPC server of the key image status service
+
+use crate::{
+    config::KeyImageStatusServerConfig, ledger_progress::LedgerProgress,
+    service::KeyImageStatusService,
+};
+use futures::executor::block_on;
+use grpcio::Error as GrpcError;
+use mc_common::{
+    logger::{log, Logger},
+    time::TimeProvider,
+};
+use mc_fog_api::{ledger::KeyImageServiceCapabilities, ledger_grpc};
+use mc_ledger_db::LedgerDB;
+use mc_util_grpc::{
+    AnonymousAuthenticator, Authenticator, ConnectionUriGrpcioServer, HealthCheckStatus,
+    HealthService, TokenAuthenticator,
+};
+use mc_util_uri::ConnectionUri;
+use mc_watcher::watcher_db::WatcherDB;
+use std::sync::Arc;
+
+/// A server answering key image checks from a ledger db
+pub struct KeyImageStatusServer {
+    config: KeyImageStatusServerConfig,
+    server: Option<grpcio::Server>,
+    key_image_status_service: KeyImageStatusService<LedgerDB>,
+    ledger_progress: LedgerProgress<LedgerDB>,
+    logger: Logger,
+}
+
+impl KeyImageStatusServer {
+    /// Create a new key image status server
+    pub fn new(
+        config: KeyImageStatusServerConfig,
+        ledger: LedgerDB,
+        watcher: Option<WatcherDB>,
+        time_provider: impl TimeProvider + 'static,
+        logger: Logger,
+    ) -> Self {
+        let client_authenticator: Arc<dyn Authenticator + Sync + Send> =
+            if let Some(shared_secret) = config.client_auth_token_secret.as_ref() {
+                Arc::new(TokenAuthenticator::new(
+                    *shared_secret,
+                    config.client_auth_token_max_lifetime,
+                    time_provider,
+                ))
+            } else {
+                Arc::new(AnonymousAuthenticator::default())
+            };
+
+        let mut capabilities = KeyImageServiceCapabilities::new();
+        capabilities.set_max_queries_per_request(config.max_key_image_queries_per_request);
+
+        let ledger_progress = LedgerProgress::new(ledger.clone(), config.max_ledger_staleness);
+        let key_image_status_service = KeyImageStatusService::new(
+            ledger,
+            ledger_progress.clone(),
+            watcher,
+            capabilities,
+            client_authenticator,
+            logger.clone(),
+        );
+
+        Self {
+            config,
+            server: None,
+            key_image_status_service,
+            ledger_progress,
+            logger,
+        }
+    }
+
+    /// Start serving client requests
+    pub fn start(&mut self) -> Result<(), GrpcError> {
+        let env = Arc::new(
+            grpcio::EnvBuilder::new()
+                .name_prefix("KeyImageStatus-RPC".to_string())
+                .build(),
+        );
+
+        // Package endpoints into grpc service
+        let key_image_status_service =
+            ledger_grpc::create_fog_untrusted_key_image_api(self.key_image_status_service.clone());
+
+        // Health check service, which reports that we are not serving while the
+        // ledger is not kept in sync
+        let ledger_progress = self.ledger_progress.clone();
+        let health_check_callback: Arc<dyn Fn(&str) -> HealthCheckStatus + Sync + Send> =
+            Arc::new(move |_| {
+                if ledger_progress.check().is_ok() {
+                    HealthCheckStatus::SERVING
+                } else {
+                    HealthCheckStatus::NOT_SERVING
+                }
+            });
+        let health_service =
+            HealthService::new(Some(health_check_callback), self.logger.clone()).into_service();
+
+        // Package service into grpc server
+        log::info!(
+            self.logger,
+            "Starting Key Image Status server on {}",
+            self.config.client_listen_uri.addr(),
+        );
+        let server_builder = grpcio::ServerBuilder::new(env)
+            .register_service(key_image_status_service)
+            .register_service(health_service)
+            .bind_using_uri(&self.config.client_listen_uri, self.logger.clone());
+
+        let mut server = server_builder.build()?;
+        server.start();
+
+        self.server = Some(server);
+        Ok(())
+    }
+
+    /// Stop serving client requests
+    pub fn stop(&mut self) {
+        if let Some(mut server) = self.server.take() {
+            block_on(server.shutdown()).expect("Could not stop grpc server");
+        }
+    }
+}
+
+impl Drop for KeyImageStatusServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
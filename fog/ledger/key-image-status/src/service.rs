@@ -0,0 +1,344 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! The FogUntrustedKeyImageApi service, answering key image checks directly
+//! from the ledger db.
+
+use crate::ledger_progress::LedgerProgress;
+use grpcio::{RpcContext, RpcStatus, RpcStatusCode, UnarySink};
+use mc_blockchain_types::MAX_BLOCK_VERSION;
+use mc_common::logger::{log, Logger};
+use mc_fog_api::{
+    empty::Empty,
+    ledger::{
+        CheckKeyImagesRequest, CheckKeyImagesResponse, KeyImageQuery, KeyImageResult,
+        KeyImageResultCode, KeyImageServiceCapabilities,
+    },
+    ledger_grpc::FogUntrustedKeyImageApi,
+};
+use mc_ledger_db::{Error as DbError, Ledger};
+use mc_transaction_core::ring_signature::KeyImage;
+use mc_util_grpc::{
+    rpc_database_err, rpc_invalid_arg_error, rpc_logger, send_result, Authenticator,
+};
+use mc_util_metrics::SVC_COUNTERS;
+use mc_watcher::watcher_db::WatcherDB;
+use mc_watcher_api::TimestampResultCode;
+use std::sync::Arc;
+
+/// Answers key image checks from a ledger db
+#[derive(Clone)]
+pub struct KeyImageStatusService<L: Ledger + Clone> {
+    ledger: L,
+    ledger_progress: LedgerProgress<L>,
+    watcher: Option<WatcherDB>,
+    capabilities: KeyImageServiceCapabilities,
+    authenticator: Arc<dyn Authenticator + Send + Sync>,
+    logger: Logger,
+}
+
+impl<L: Ledger + Clone> KeyImageStatusService<L> {
+    /// Create a new key image status service
+    ///
+    /// Arguments:
+    /// * ledger: The ledger to check key images in
+    /// * ledger_progress: Tells whether the ledger is kept in sync. Requests
+    ///   are refused while it isn't.
+    /// * watcher: The watcher db to get block timestamps from. If this is None,
+    ///   they come from the block signatures in the ledger.
+    /// * capabilities: The limits put on requests
+    /// * authenticator: Authenticates client requests
+    /// * logger: For logging
+    pub fn new(
+        ledger: L,
+        ledger_progress: LedgerProgress<L>,
+        watcher: Option<WatcherDB>,
+        capabilities: KeyImageServiceCapabilities,
+        authenticator: Arc<dyn Authenticator + Send + Sync>,
+        logger: Logger,
+    ) -> Self {
+        Self {
+            ledger,
+            ledger_progress,
+            watcher,
+            capabilities,
+            authenticator,
+            logger,
+        }
+    }
+
+    fn check_key_images_impl(
+        &mut self,
+        request: CheckKeyImagesRequest,
+    ) -> Result<CheckKeyImagesResponse, RpcStatus> {
+        mc_common::trace_time!(self.logger, "Check Key Images");
+
+        let max_queries = self.capabilities.get_max_queries_per_request();
+        if max_queries != 0 && request.queries.len() as u64 > max_queries {
+            return Err(rpc_invalid_arg_error(
+                "check_key_images",
+                format!(
+                    "{} queries exceed the limit of {} per request",
+                    request.queries.len(),
+                    max_queries
+                ),
+                &self.logger,
+            ));
+        }
+
+        if let Err(stale_for) = self.ledger_progress.check() {
+            log::warn!(
+                self.logger,
+                "Refusing key image checks, the ledger has not grown for {:?}",
+                stale_for
+            );
+            return Err(RpcStatus::with_message(
+                RpcStatusCode::UNAVAILABLE,
+                format!(
+                    "The ledger has not grown for {} seconds, it is likely not in sync",
+                    stale_for.as_secs()
+                ),
+            ));
+        }
+
+        let mut response = CheckKeyImagesResponse::new();
+
+        // The ledger is read before the key images are checked, so that the key
+        // images which are not spent are known not to be spent as of these
+        // numbers, even if blocks are appended in the meantime.
+        response.num_blocks = self
+            .ledger
+            .num_blocks()
+            .map_err(|err| rpc_database_err(err, &self.logger))?;
+        response.global_txo_count = self
+            .ledger
+            .num_txos()
+            .map_err(|err| rpc_database_err(err, &self.logger))?;
+        response.latest_block_version = match self.ledger.get_latest_block() {
+            Ok(block) => block.version,
+            Err(DbError::NotFound) => 0,
+            Err(err) => return Err(rpc_database_err(err, &self.logger)),
+        };
+        response.max_block_version = response.latest_block_version.max(*MAX_BLOCK_VERSION);
+
+        for query in request.queries.iter() {
+            response
+                .results
+                .push(self.check_key_image(query, response.num_blocks));
+        }
+
+        Ok(response)
+    }
+
+    /// Check one key image, as of the given number of blocks
+    fn check_key_image(&self, query: &KeyImageQuery, num_blocks: u64) -> KeyImageResult {
+        let mut result = KeyImageResult::new();
+        result.set_key_image(query.get_key_image().clone());
+        result.timestamp = u64::MAX;
+        result.timestamp_result_code = TimestampResultCode::Unavailable as u32;
+
+        let key_image = match KeyImage::try_from(query.get_key_image()) {
+            Ok(key_image) => key_image,
+            Err(err) => {
+                log::debug!(
+                    self.logger,
+                    "Request was not a valid key image {:?}: {}",
+                    query.get_key_image(),
+                    err
+                );
+                result.key_image_result_code = KeyImageResultCode::KeyImageError as u32;
+                return result;
+            }
+        };
+
+        match self.ledger.check_key_image(&key_image) {
+            Ok(Some(spent_at)) if spent_at < num_blocks => {
+                result.key_image_result_code = KeyImageResultCode::Spent as u32;
+                result.spent_at = spent_at;
+                let (timestamp, timestamp_result_code) = self.get_block_timestamp(spent_at);
+                result.timestamp = timestamp;
+                result.timestamp_result_code = timestamp_result_code as u32;
+            }
+            // A key image spent in a block appended after num_blocks was read
+            // is reported as not spent as of num_blocks.
+            Ok(_) => {
+                result.key_image_result_code = KeyImageResultCode::NotSpent as u32;
+            }
+            Err(err) => {
+                log::error!(
+                    self.logger,
+                    "DbError checking key image {:?}: {}",
+                    key_image,
+                    err
+                );
+                result.key_image_result_code = KeyImageResultCode::KeyImageError as u32;
+            }
+        }
+        result
+    }
+
+    /// Get the timestamp of a block, from the watcher db if there is one, and
+    /// from the block signature in the ledger otherwise
+    fn get_block_timestamp(&self, block_index: u64) -> (u64, TimestampResultCode) {
+        if let Some(watcher) = self.watcher.as_ref() {
+            return match watcher.get_block_timestamp(block_index) {
+                Ok((timestamp, result_code)) => (timestamp, result_code),
+                Err(err) => {
+                    log::error!(
+                        self.logger,
+                        "Could not obtain timestamp for block {} due to error {:?}",
+                        block_index,
+                        err
+                    );
+                    (u64::MAX, TimestampResultCode::WatcherDatabaseError)
+                }
+            };
+        }
+
+        match self.ledger.get_block_signature(block_index) {
+            Ok(signature) => (signature.signed_at(), TimestampResultCode::TimestampFound),
+            Err(DbError::NotFound) => (u64::MAX, TimestampResultCode::Unavailable),
+            Err(err) => {
+                log::error!(
+                    self.logger,
+                    "Could not obtain the signature of block {} due to error {}",
+                    block_index,
+                    err
+                );
+                (u64::MAX, TimestampResultCode::Unavailable)
+            }
+        }
+    }
+}
+
+impl<L: Ledger + Clone> FogUntrustedKeyImageApi for KeyImageStatusService<L> {
+    fn check_key_images(
+        &mut self,
+        ctx: RpcContext,
+        request: CheckKeyImagesRequest,
+        sink: UnarySink<CheckKeyImagesResponse>,
+    ) {
+        let _timer = SVC_COUNTERS.req(&ctx);
+        mc_common::logger::scoped_global_logger(&rpc_logger(&ctx, &self.logger), |logger| {
+            if let Err(err) = self.authenticator.authenticate_rpc(&ctx) {
+                return send_result(ctx, sink, err.into(), logger);
+            }
+
+            send_result(ctx, sink, self.check_key_images_impl(request), logger)
+        })
+    }
+
+    fn get_capabilities(
+        &mut self,
+        ctx: RpcContext,
+        _request: Empty,
+        sink: UnarySink<KeyImageServiceCapabilities>,
+    ) {
+        let _timer = SVC_COUNTERS.req(&ctx);
+        mc_common::logger::scoped_global_logger(&rpc_logger(&ctx, &self.logger), |logger| {
+            if let Err(err) = self.authenticator.authenticate_rpc(&ctx) {
+                return send_result(ctx, sink, err.into(), logger);
+            }
+
+            send_result(ctx, sink, Ok(self.capabilities.clone()), logger)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_account_keys::AccountKey;
+    use mc_blockchain_types::BlockVersion;
+    use mc_common::logger::{test_with_logger, Logger};
+    use mc_fog_api::external;
+    use mc_ledger_db::test_utils::{add_block_to_ledger, create_ledger};
+    use mc_transaction_core::{tokens::Mob, Amount, Token};
+    use mc_util_grpc::AnonymousAuthenticator;
+    use mc_util_test_helper::{RngType, SeedableRng};
+    use std::time::Duration;
+
+    fn query(key_image: &KeyImage) -> KeyImageQuery {
+        let mut query = KeyImageQuery::new();
+        query.set_key_image(external::KeyImage::from(key_image));
+        query
+    }
+
+    fn request(queries: Vec<KeyImageQuery>) -> CheckKeyImagesRequest {
+        let mut request = CheckKeyImagesRequest::new();
+        request.set_queries(queries.into());
+        request
+    }
+
+    #[test_with_logger]
+    fn check_key_images_reports_spent_and_not_spent(logger: Logger) {
+        let mut rng = RngType::from_seed([1u8; 32]);
+        let mut ledger = create_ledger();
+        let recipient = AccountKey::random(&mut rng).default_subaddress();
+        let amount = Amount::new(10, Mob::ID);
+        for block_index in 0..3 {
+            let key_images = if block_index == 0 {
+                vec![]
+            } else {
+                vec![KeyImage::from(block_index)]
+            };
+            add_block_to_ledger(
+                &mut ledger,
+                BlockVersion::MAX,
+                &[recipient.clone()],
+                amount,
+                &key_images,
+                &mut rng,
+            )
+            .unwrap();
+        }
+
+        let mut capabilities = KeyImageServiceCapabilities::new();
+        capabilities.set_max_queries_per_request(3);
+        let mut service = KeyImageStatusService::new(
+            ledger.clone(),
+            LedgerProgress::new(ledger.clone(), Duration::ZERO),
+            None,
+            capabilities,
+            Arc::new(AnonymousAuthenticator::default()),
+            logger,
+        );
+
+        let response = service
+            .check_key_images_impl(request(vec![
+                query(&KeyImage::from(2)),
+                query(&KeyImage::from(7)),
+                query(&KeyImage::from(1)),
+            ]))
+            .unwrap();
+        assert_eq!(response.num_blocks, 3);
+        assert_eq!(response.global_txo_count, ledger.num_txos().unwrap());
+        assert_eq!(response.latest_block_version, *BlockVersion::MAX);
+
+        let results = response.get_results();
+        assert_eq!(results.len(), 3);
+        assert_eq!(
+            results[0].key_image_result_code,
+            KeyImageResultCode::Spent as u32
+        );
+        assert_eq!(results[0].spent_at, 2);
+        assert_eq!(
+            results[0].get_key_image(),
+            &external::KeyImage::from(&KeyImage::from(2))
+        );
+        assert_eq!(
+            results[1].key_image_result_code,
+            KeyImageResultCode::NotSpent as u32
+        );
+        assert_eq!(
+            results[2].key_image_result_code,
+            KeyImageResultCode::Spent as u32
+        );
+        assert_eq!(results[2].spent_at, 1);
+
+        // Requests over the limit are rejected
+        let status = service
+            .check_key_images_impl(request(vec![query(&KeyImage::from(1)); 4]))
+            .unwrap_err();
+        assert_eq!(status.code(), grpcio::RpcStatusCode::INVALID_ARGUMENT);
+    }
+}
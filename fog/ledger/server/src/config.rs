@@ -4,6 +4,7 @@
 
 #![deny(missing_docs)]
 
+use crate::sharding::KeyImageRange;
 use clap::Parser;
 use mc_attest_core::ProviderId;
 use mc_common::ResponderId;
@@ -71,4 +72,97 @@ pub struct LedgerServerConfig {
     /// to disk by linux kernel.
     #[clap(long, default_value = "1048576", env = "MC_OMAP_CAPACITY")]
     pub omap_capacity: u64,
+
+    /// The most key image queries accepted in one CheckKeyImages request.
+    /// Requests with more queries are rejected, and clients are expected to
+    /// split their queries using the limit from GetCapabilities. Zero means
+    /// there is no limit.
+    #[clap(
+        long,
+        default_value = "10000",
+        env = "MC_MAX_KEY_IMAGE_QUERIES_PER_REQUEST"
+    )]
+    pub max_key_image_queries_per_request: u64,
+
+    /// The most TxOut queries, by public key or global index, accepted in one
+    /// untrusted GetTxOuts request. Requests with more queries are rejected,
+    /// and clients are expected to split their queries using the limit from
+    /// GetCapabilities. Zero means there is no limit.
+    #[clap(
+        long,
+        default_value = "1000",
+        env = "MC_MAX_TX_OUT_QUERIES_PER_REQUEST"
+    )]
+    pub max_tx_out_queries_per_request: u64,
+
+    /// The largest gRPC message, in bytes, the server accepts. Defaults to
+    /// the gRPC default limit.
+    #[clap(long, env = "MC_GRPC_MAX_MESSAGE_SIZE")]
+    pub grpc_max_message_size: Option<usize>,
+
+    /// The range of key images to load into the enclave, when this server is
+    /// one of the stores of a sharded key image store, behind a fog ledger
+    /// router. Two hex numbers separated by a dash, compared with the first 8
+    /// bytes of the key images, e.g. `0-7fffffffffffffff`. Defaults to all
+    /// key images.
+    #[clap(long, env = "MC_KEY_IMAGE_RANGE")]
+    pub key_image_range: Option<KeyImageRange>,
+}
+
+/// Configuration parameters for the ledger router
+#[derive(Clone, Parser, Serialize)]
+#[clap(version)]
+pub struct LedgerRouterConfig {
+    /// gRPC listening URI for client requests.
+    #[clap(long, env = "MC_CLIENT_LISTEN_URI")]
+    pub client_listen_uri: FogLedgerUri,
+
+    /// Client Responder id.
+    ///
+    /// This ID needs to match the host:port clients use in their URI when
+    /// referencing this node.
+    #[clap(long, env = "MC_CLIENT_RESPONDER_ID")]
+    pub client_responder_id: ResponderId,
+
+    /// The client URIs of the fog ledger store servers. Together, the
+    /// `--key-image-range`s of the stores must cover all key images.
+    #[clap(
+        long = "shard-uri",
+        required = true,
+        env = "MC_SHARD_URIS",
+        use_value_delimiter = true
+    )]
+    pub shard_uris: Vec<FogLedgerUri>,
+
+    /// IAS Api Key.
+    #[clap(long, env = "MC_IAS_API_KEY")]
+    pub ias_api_key: String,
+
+    /// IAS Service Provider ID.
+    #[clap(long, env = "MC_IAS_SPID")]
+    pub ias_spid: ProviderId,
+
+    /// Optional admin listening URI.
+    #[clap(long, env = "MC_ADMIN_LISTEN_URI")]
+    pub admin_listen_uri: Option<AdminUri>,
+
+    /// Enables authenticating client requests using Authorization tokens using
+    /// the provided hex-encoded 32 bytes shared secret.
+    #[clap(long, parse(try_from_str = hex::FromHex::from_hex), env = "MC_CLIENT_AUTH_TOKEN_SECRET")]
+    pub client_auth_token_secret: Option<[u8; 32]>,
+
+    /// Maximal client authentication token lifetime, in seconds (only relevant
+    /// when --client-auth-token-secret is used. Defaults to 86400 - 24
+    /// hours).
+    #[clap(long, default_value = "86400", parse(try_from_str = parse_duration_in_seconds), env = "MC_CLIENT_AUTH_TOKEN_MAX_LIFETIME")]
+    pub client_auth_token_max_lifetime: Duration,
+
+    /// The most key image queries accepted in one CheckKeyImages request.
+    /// Zero means there is no limit.
+    #[clap(
+        long,
+        default_value = "10000",
+        env = "MC_MAX_KEY_IMAGE_QUERIES_PER_REQUEST"
+    )]
+    pub max_key_image_queries_per_request: u64,
 }
@@ -3,7 +3,7 @@
 //! A background thread, in the server side, that continuously checks the
 //! LedgerDB for new blocks, then gets all the key images associated to those
 //! blocks and adds them to the enclave.
-use crate::{counters, server::DbPollSharedState};
+use crate::{counters, server::DbPollSharedState, sharding::KeyImageRange};
 use mc_common::{
     logger::{log, Logger},
     trace_time,
@@ -44,6 +44,7 @@ impl DbFetcher {
         enclave: E,
         watcher: WatcherDB,
         db_poll_shared_state: Arc<Mutex<DbPollSharedState>>,
+        key_image_range: KeyImageRange,
         readiness_indicator: ReadinessIndicator,
         logger: Logger,
     ) -> Self {
@@ -61,6 +62,7 @@ impl DbFetcher {
                         enclave,
                         watcher,
                         thread_shared_state,
+                        key_image_range,
                         readiness_indicator,
                         logger,
                     )
@@ -98,6 +100,8 @@ struct DbFetcherThread<DB: Ledger, E: LedgerEnclaveProxy + Clone + Send + Sync +
     enclave: E,
     watcher: WatcherDB,
     db_poll_shared_state: Arc<Mutex<DbPollSharedState>>,
+    /// Only the key images in this range are added to the enclave
+    key_image_range: KeyImageRange,
     readiness_indicator: ReadinessIndicator,
     logger: Logger,
 }
@@ -115,6 +119,7 @@ impl<DB: Ledger, E: LedgerEnclaveProxy + Clone + Send + Sync + 'static> DbFetche
         enclave: E,
         watcher: WatcherDB,
         db_poll_shared_state: Arc<Mutex<DbPollSharedState>>,
+        key_image_range: KeyImageRange,
         readiness_indicator: ReadinessIndicator,
         logger: Logger,
     ) {
@@ -125,6 +130,7 @@ impl<DB: Ledger, E: LedgerEnclaveProxy + Clone + Send + Sync + 'static> DbFetche
             enclave,
             watcher,
             db_poll_shared_state,
+            key_image_range,
             readiness_indicator,
             logger,
         };
@@ -215,6 +221,7 @@ impl<DB: Ledger, E: LedgerEnclaveProxy + Clone + Send + Sync + 'static> DbFetche
                 let records = block_contents
                     .key_images
                     .iter()
+                    .filter(|key_image| self.key_image_range.contains(key_image))
                     .map(|key_image| KeyImageData {
                         key_image: *key_image,
                         block_index: self.next_block_index,
@@ -0,0 +1,52 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+use displaydoc::Display;
+use mc_fog_ledger_enclave::Error as LedgerEnclaveError;
+use mc_util_serial::DecodeError as ProstDecodeError;
+use mc_util_uri::UriConversionError;
+use protobuf::error::ProtobufError;
+
+/// An error when a fog ledger router talks to a fog ledger store
+#[derive(Debug, Display)]
+pub enum RouterError {
+    /// Ledger Enclave error: {0}
+    Enclave(LedgerEnclaveError),
+    /// gRPC error: {0}
+    Grpc(grpcio::Error),
+    /// Invalid store URI: {0}
+    UriConversion(UriConversionError),
+    /// Protobuf error: {0}
+    Protobuf(ProtobufError),
+    /// Could not decode a prost message: {0}
+    ProstDecode(ProstDecodeError),
+}
+
+impl From<LedgerEnclaveError> for RouterError {
+    fn from(src: LedgerEnclaveError) -> Self {
+        Self::Enclave(src)
+    }
+}
+
+impl From<grpcio::Error> for RouterError {
+    fn from(src: grpcio::Error) -> Self {
+        Self::Grpc(src)
+    }
+}
+
+impl From<UriConversionError> for RouterError {
+    fn from(src: UriConversionError) -> Self {
+        Self::UriConversion(src)
+    }
+}
+
+impl From<ProtobufError> for RouterError {
+    fn from(src: ProtobufError) -> Self {
+        Self::Protobuf(src)
+    }
+}
+
+impl From<ProstDecodeError> for RouterError {
+    fn from(src: ProstDecodeError) -> Self {
+        Self::ProstDecode(src)
+    }
+}
@@ -7,13 +7,13 @@ use mc_attest_api::{
 };
 use mc_blockchain_types::MAX_BLOCK_VERSION;
 use mc_common::logger::{log, Logger};
-use mc_fog_api::ledger_grpc::FogKeyImageApi;
+use mc_fog_api::{empty::Empty, ledger::KeyImageServiceCapabilities, ledger_grpc::FogKeyImageApi};
 use mc_fog_ledger_enclave::LedgerEnclaveProxy;
 use mc_fog_ledger_enclave_api::{Error as EnclaveError, UntrustedKeyImageQueryResponse};
 use mc_ledger_db::Ledger;
 use mc_util_grpc::{
-    rpc_internal_error, rpc_invalid_arg_error, rpc_logger, rpc_permissions_error, send_result,
-    Authenticator,
+    rpc_internal_error, rpc_invalid_arg_error, rpc_logger, rpc_out_of_range_error,
+    rpc_permissions_error, send_result, Authenticator,
 };
 use mc_util_metrics::SVC_COUNTERS;
 use mc_watcher::watcher_db::WatcherDB;
@@ -24,6 +24,8 @@ pub struct KeyImageService<L: Ledger + Clone, E: LedgerEnclaveProxy> {
     ledger: L,
     watcher: WatcherDB,
     enclave: E,
+    /// Limits on key image requests, advertised to clients.
+    capabilities: KeyImageServiceCapabilities,
     authenticator: Arc<dyn Authenticator + Send + Sync>,
     logger: Logger,
     /// Shared state from db polling thread.
@@ -36,6 +38,7 @@ impl<L: Ledger + Clone, E: LedgerEnclaveProxy> KeyImageService<L, E> {
         watcher: WatcherDB,
         enclave: E,
         db_poll_shared_state: Arc<Mutex<DbPollSharedState>>,
+        capabilities: KeyImageServiceCapabilities,
         authenticator: Arc<dyn Authenticator + Send + Sync>,
         logger: Logger,
     ) -> Self {
@@ -43,6 +46,7 @@ impl<L: Ledger + Clone, E: LedgerEnclaveProxy> KeyImageService<L, E> {
             ledger,
             watcher,
             enclave,
+            capabilities,
             authenticator,
             logger,
             db_poll_shared_state,
@@ -73,48 +77,68 @@ impl<L: Ledger + Clone, E: LedgerEnclaveProxy> KeyImageService<L, E> {
     ) -> Result<attest::Message, RpcStatus> {
         log::trace!(self.logger, "Getting encrypted request");
 
-        let (
-            highest_processed_block_count,
-            last_known_block_cumulative_txo_count,
-            latest_block_version,
-        ) = {
-            let shared_state = self.db_poll_shared_state.lock().expect("mutex poisoned");
-            (
-                shared_state.highest_processed_block_count,
-                shared_state.last_known_block_cumulative_txo_count,
-                shared_state.latest_block_version,
-            )
-        };
-
-        let untrusted_query_response = UntrustedKeyImageQueryResponse {
-            highest_processed_block_count,
-            last_known_block_cumulative_txo_count,
-            latest_block_version,
-            max_block_version: latest_block_version.max(*MAX_BLOCK_VERSION),
-        };
+        let untrusted_query_response = get_untrusted_key_image_query_response(
+            &self.db_poll_shared_state,
+            match self.capabilities.get_max_queries_per_request() {
+                0 => None,
+                max_queries => Some(max_queries),
+            },
+        );
 
         let result_blob = self
             .enclave
             .check_key_images(request.into(), untrusted_query_response)
-            .map_err(|e| self.enclave_err_to_rpc_status("enclave request", e))?;
+            .map_err(|e| enclave_err_to_rpc_status("enclave request", e, &self.logger))?;
 
         let mut resp = attest::Message::new();
         resp.set_data(result_blob);
         Ok(resp)
     }
+}
 
-    // Helper function that is common
-    fn enclave_err_to_rpc_status(&self, context: &str, src: EnclaveError) -> RpcStatus {
-        // Treat prost-decode error as an invalid arg,
-        // treat attest error as permission denied,
-        // everything else is an internal error
-        match src {
-            EnclaveError::ProstDecode => {
-                rpc_invalid_arg_error(context, "Prost decode failed", &self.logger)
-            }
-            EnclaveError::Attest(err) => rpc_permissions_error(context, err, &self.logger),
-            other => rpc_internal_error(context, format!("{}", &other), &self.logger),
-        }
+/// Get the data from the db poll thread which goes in a key image check
+/// response, but is not in the ORAM.
+pub(crate) fn get_untrusted_key_image_query_response(
+    db_poll_shared_state: &Mutex<DbPollSharedState>,
+    max_queries: Option<u64>,
+) -> UntrustedKeyImageQueryResponse {
+    let (
+        highest_processed_block_count,
+        last_known_block_cumulative_txo_count,
+        latest_block_version,
+    ) = {
+        let shared_state = db_poll_shared_state.lock().expect("mutex poisoned");
+        (
+            shared_state.highest_processed_block_count,
+            shared_state.last_known_block_cumulative_txo_count,
+            shared_state.latest_block_version,
+        )
+    };
+
+    UntrustedKeyImageQueryResponse {
+        highest_processed_block_count,
+        last_known_block_cumulative_txo_count,
+        latest_block_version,
+        max_block_version: latest_block_version.max(*MAX_BLOCK_VERSION),
+        max_queries,
+    }
+}
+
+// Helper function that is common
+pub(crate) fn enclave_err_to_rpc_status(
+    context: &str,
+    src: EnclaveError,
+    logger: &Logger,
+) -> RpcStatus {
+    // Treat prost-decode error as an invalid arg,
+    // treat attest error as permission denied,
+    // treat too many queries as out of range,
+    // everything else is an internal error
+    match src {
+        EnclaveError::ProstDecode => rpc_invalid_arg_error(context, "Prost decode failed", logger),
+        EnclaveError::Attest(err) => rpc_permissions_error(context, err, logger),
+        err @ EnclaveError::TooManyQueries(..) => rpc_out_of_range_error(context, err, logger),
+        other => rpc_internal_error(context, format!("{}", &other), logger),
     }
 }
 
@@ -130,6 +154,22 @@ impl<L: Ledger + Clone, E: LedgerEnclaveProxy> FogKeyImageApi for KeyImageServic
         })
     }
 
+    fn get_capabilities(
+        &mut self,
+        ctx: RpcContext,
+        _request: Empty,
+        sink: UnarySink<KeyImageServiceCapabilities>,
+    ) {
+        let _timer = SVC_COUNTERS.req(&ctx);
+        mc_common::logger::scoped_global_logger(&rpc_logger(&ctx, &self.logger), |logger| {
+            if let Err(err) = self.authenticator.authenticate_rpc(&ctx) {
+                return send_result(ctx, sink, err.into(), logger);
+            }
+
+            send_result(ctx, sink, Ok(self.capabilities.clone()), logger)
+        })
+    }
+
     fn auth(&mut self, ctx: RpcContext, request: AuthMessage, sink: UnarySink<AuthMessage>) {
         let _timer = SVC_COUNTERS.req(&ctx);
         mc_common::logger::scoped_global_logger(&rpc_logger(&ctx, &self.logger), |logger| {
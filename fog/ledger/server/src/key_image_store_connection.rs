@@ -0,0 +1,127 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! A fog ledger router's connection to one of the fog ledger stores.
+
+use crate::error::RouterError;
+use grpcio::{ChannelBuilder, ClientUnaryReceiver, Environment};
+use mc_attest_api::attest;
+use mc_attest_enclave_api::{EnclaveMessage, PeerSession};
+use mc_common::{
+    logger::{log, Logger},
+    ResponderId,
+};
+use mc_fog_api::{ledger, ledger_grpc::KeyImageStoreApiClient};
+use mc_fog_ledger_enclave::{GetOutputsResponse, LedgerEnclaveProxy, OutputContext};
+use mc_fog_uri::FogLedgerUri;
+use mc_util_grpc::ConnectionUriGrpcioChannel;
+use mc_util_uri::ConnectionUri;
+use protobuf::Message;
+use std::sync::Arc;
+
+/// A connection to a fog ledger store, attested lazily by the router enclave.
+pub struct KeyImageStoreConnection<E: LedgerEnclaveProxy> {
+    /// The URI of the store.
+    uri: FogLedgerUri,
+
+    /// The responder id the store enclave attests with.
+    responder_id: ResponderId,
+
+    /// The grpc client for the store.
+    grpc_client: KeyImageStoreApiClient,
+
+    /// The router enclave.
+    enclave: E,
+
+    /// The peer session with the store enclave, if attested.
+    peer_session: Option<PeerSession>,
+
+    /// Logger.
+    logger: Logger,
+}
+
+impl<E: LedgerEnclaveProxy> KeyImageStoreConnection<E> {
+    /// Create a new connection to a store. This does not attest yet.
+    pub fn new(
+        uri: FogLedgerUri,
+        enclave: E,
+        env: Arc<Environment>,
+        logger: Logger,
+    ) -> Result<Self, RouterError> {
+        let responder_id = uri.responder_id()?;
+        let ch = ChannelBuilder::default_channel_builder(env).connect_to_uri(&uri, &logger);
+        let grpc_client = KeyImageStoreApiClient::new(ch);
+
+        Ok(Self {
+            uri,
+            responder_id,
+            grpc_client,
+            enclave,
+            peer_session: None,
+            logger,
+        })
+    }
+
+    /// The URI of the store.
+    pub fn uri(&self) -> &FogLedgerUri {
+        &self.uri
+    }
+
+    /// Get the peer session with the store enclave, attesting first if needed.
+    pub fn attest(&mut self) -> Result<PeerSession, RouterError> {
+        if let Some(peer_session) = self.peer_session.as_ref() {
+            return Ok(peer_session.clone());
+        }
+
+        let auth_request = self.enclave.peer_init(&self.responder_id)?;
+        let auth_response = self.grpc_client.auth(&auth_request.into())?;
+        let (peer_session, _verification_report) = self
+            .enclave
+            .peer_connect(&self.responder_id, auth_response.into())?;
+
+        log::debug!(self.logger, "Attested fog ledger store {}", self.uri);
+        self.peer_session = Some(peer_session.clone());
+        Ok(peer_session)
+    }
+
+    /// Forget the peer session with the store enclave, so that the next query
+    /// attests again.
+    pub fn deattest(&mut self) {
+        if let Some(peer_session) = self.peer_session.take() {
+            if let Err(err) = self.enclave.peer_close(&peer_session) {
+                log::warn!(
+                    self.logger,
+                    "Failed closing peer session with fog ledger store {}: {}",
+                    self.uri,
+                    err
+                );
+            }
+        }
+    }
+
+    /// Send a shard key image query to the store, without waiting for the
+    /// response.
+    pub fn check_key_images_async(
+        &self,
+        shard_query: EnclaveMessage<PeerSession>,
+    ) -> Result<ClientUnaryReceiver<attest::Message>, RouterError> {
+        Ok(self
+            .grpc_client
+            .check_key_images_async(&shard_query.into())?)
+    }
+
+    /// Get outputs and merkle proofs from the store. This does not use the
+    /// peer session.
+    pub fn get_outputs(
+        &self,
+        output_context: &OutputContext,
+    ) -> Result<GetOutputsResponse, RouterError> {
+        let mut request = ledger::GetOutputsRequest::new();
+        request.set_indices(output_context.indexes.clone());
+        request.set_merkle_root_block(output_context.merkle_root_block);
+
+        let response = self.grpc_client.get_outputs(&request)?;
+
+        // The prost and protobuf GetOutputsResponse have the same encoding
+        Ok(mc_util_serial::decode(&response.write_to_bytes()?)?)
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! The service a fog ledger server provides to fog ledger routers, when it is
+//! one of the stores of a sharded key image store.
+
+use crate::{
+    key_image_service::{enclave_err_to_rpc_status, get_untrusted_key_image_query_response},
+    merkle_proof_service::get_outputs_response,
+    server::DbPollSharedState,
+};
+use grpcio::{RpcContext, RpcStatus, UnarySink};
+use mc_attest_api::attest;
+use mc_common::logger::{log, Logger};
+use mc_fog_api::{ledger, ledger_grpc::KeyImageStoreApi};
+use mc_fog_ledger_enclave::{LedgerEnclaveProxy, OutputContext};
+use mc_ledger_db::Ledger;
+use mc_util_grpc::{rpc_internal_error, rpc_logger, rpc_permissions_error, send_result};
+use mc_util_metrics::SVC_COUNTERS;
+use protobuf::Message;
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone)]
+pub struct KeyImageStoreService<L: Ledger + Clone, E: LedgerEnclaveProxy> {
+    ledger: L,
+    enclave: E,
+    /// Shared state from db polling thread.
+    db_poll_shared_state: Arc<Mutex<DbPollSharedState>>,
+    logger: Logger,
+}
+
+impl<L: Ledger + Clone, E: LedgerEnclaveProxy> KeyImageStoreService<L, E> {
+    pub fn new(
+        ledger: L,
+        enclave: E,
+        db_poll_shared_state: Arc<Mutex<DbPollSharedState>>,
+        logger: Logger,
+    ) -> Self {
+        Self {
+            ledger,
+            enclave,
+            db_poll_shared_state,
+            logger,
+        }
+    }
+
+    /// Accept a peer connection from a router enclave
+    fn auth_impl(
+        &mut self,
+        request: attest::AuthMessage,
+        logger: &Logger,
+    ) -> Result<attest::AuthMessage, RpcStatus> {
+        let (response, _) = self
+            .enclave
+            .peer_accept(request.into())
+            .map_err(|peer_error| {
+                // This is debug because there's no requirement on the remote party to trigger
+                // it.
+                log::debug!(logger, "LedgerEnclave::peer_accept failed: {}", peer_error);
+                rpc_permissions_error("peer_auth", "Permission denied", logger)
+            })?;
+
+        Ok(response.into())
+    }
+
+    /// Unwrap and forward to enclave
+    fn check_key_images_impl(
+        &mut self,
+        request: attest::Message,
+    ) -> Result<attest::Message, RpcStatus> {
+        log::trace!(self.logger, "Getting encrypted shard request");
+
+        // The router enforces the limit on queries per request
+        let untrusted_query_response =
+            get_untrusted_key_image_query_response(&self.db_poll_shared_state, None);
+
+        let response = self
+            .enclave
+            .shard_check_key_images(request.into(), untrusted_query_response)
+            .map_err(|e| enclave_err_to_rpc_status("enclave request", e, &self.logger))?;
+
+        Ok(response.into())
+    }
+
+    /// Get outputs and merkle proofs for the router, which re-encrypts them
+    /// for its client
+    fn get_outputs_impl(
+        &mut self,
+        request: ledger::GetOutputsRequest,
+    ) -> Result<ledger::GetOutputsResponse, RpcStatus> {
+        let output_context = OutputContext {
+            indexes: request.get_indices().to_vec(),
+            merkle_root_block: request.get_merkle_root_block(),
+        };
+
+        let response = get_outputs_response(&self.ledger, output_context, &self.logger)?;
+
+        // The prost and protobuf GetOutputsResponse have the same encoding
+        ledger::GetOutputsResponse::parse_from_bytes(&mc_util_serial::encode(&response))
+            .map_err(|err| rpc_internal_error("get_outputs", err, &self.logger))
+    }
+}
+
+impl<L: Ledger + Clone, E: LedgerEnclaveProxy> KeyImageStoreApi for KeyImageStoreService<L, E> {
+    fn auth(
+        &mut self,
+        ctx: RpcContext,
+        request: attest::AuthMessage,
+        sink: UnarySink<attest::AuthMessage>,
+    ) {
+        let _timer = SVC_COUNTERS.req(&ctx);
+        mc_common::logger::scoped_global_logger(&rpc_logger(&ctx, &self.logger), |logger| {
+            send_result(ctx, sink, self.auth_impl(request, logger), logger)
+        });
+    }
+
+    fn check_key_images(
+        &mut self,
+        ctx: RpcContext,
+        request: attest::Message,
+        sink: UnarySink<attest::Message>,
+    ) {
+        let _timer = SVC_COUNTERS.req(&ctx);
+        mc_common::logger::scoped_global_logger(&rpc_logger(&ctx, &self.logger), |logger| {
+            send_result(ctx, sink, self.check_key_images_impl(request), logger)
+        })
+    }
+
+    fn get_outputs(
+        &mut self,
+        ctx: RpcContext,
+        request: ledger::GetOutputsRequest,
+        sink: UnarySink<ledger::GetOutputsResponse>,
+    ) {
+        let _timer = SVC_COUNTERS.req(&ctx);
+        mc_common::logger::scoped_global_logger(&rpc_logger(&ctx, &self.logger), |logger| {
+            send_result(ctx, sink, self.get_outputs_impl(request), logger)
+        })
+    }
+}
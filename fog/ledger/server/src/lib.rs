@@ -4,14 +4,22 @@ mod block_service;
 mod config;
 mod counters;
 mod db_fetcher;
+mod error;
 mod key_image_service;
+mod key_image_store_connection;
+mod key_image_store_service;
 mod merkle_proof_service;
+mod router_server;
+mod router_service;
 mod server;
+mod sharding;
 mod untrusted_tx_out_service;
 
 pub use block_service::BlockService;
-pub use config::LedgerServerConfig;
+pub use config::{LedgerRouterConfig, LedgerServerConfig};
 pub use key_image_service::KeyImageService;
 pub use merkle_proof_service::MerkleProofService;
+pub use router_server::{LedgerRouterServer, ROUTER_OMAP_CAPACITY};
 pub use server::LedgerServer;
+pub use sharding::{KeyImageRange, KeyImageRangeParseError};
 pub use untrusted_tx_out_service::UntrustedTxOutService;
@@ -9,7 +9,10 @@ use mc_fog_api::{ledger::OutputResultCode, ledger_grpc::FogMerkleProofApi};
 use mc_fog_ledger_enclave::{GetOutputsResponse, LedgerEnclaveProxy, OutputContext, OutputResult};
 use mc_fog_ledger_enclave_api::Error as EnclaveError;
 use mc_ledger_db::{Error as DbError, Ledger};
-use mc_transaction_core::tx::{TxOut, TxOutMembershipProof};
+use mc_transaction_core::{
+    membership_proofs::derive_proof_at_highest_index,
+    tx::{TxOut, TxOutMembershipProof},
+};
 use mc_util_grpc::{
     rpc_database_err, rpc_internal_error, rpc_invalid_arg_error, rpc_logger, rpc_permissions_error,
     send_result, Authenticator,
@@ -96,35 +99,62 @@ impl<L: Ledger + Clone, E: LedgerEnclaveProxy> MerkleProofService<L, E> {
         &mut self,
         output_context: OutputContext,
     ) -> Result<GetOutputsResponse, RpcStatus> {
-        let num_requested = output_context.indexes.len();
-        if num_requested > MAX_REQUEST_SIZE {
-            return Err(rpc_invalid_arg_error(
-                "get_outputs",
-                "Request size exceeds limit",
-                &self.logger,
-            ));
+        get_outputs_response(&self.ledger, output_context, &self.logger)
+    }
+}
+
+/// Get the outputs and merkle proofs of membership requested by a client from
+/// the ledger.
+pub(crate) fn get_outputs_response<L: Ledger>(
+    ledger: &L,
+    output_context: OutputContext,
+    logger: &Logger,
+) -> Result<GetOutputsResponse, RpcStatus> {
+    let num_requested = output_context.indexes.len();
+    if num_requested > MAX_REQUEST_SIZE {
+        return Err(rpc_invalid_arg_error(
+            "get_outputs",
+            "Request size exceeds limit",
+            logger,
+        ));
+    }
+
+    // The number of TxOuts in the tree whose root hash all the proofs share.
+    // If no merkle root block is requested, this is the current tree.
+    let num_tx_outs_at_root = if output_context.merkle_root_block == 0 {
+        None
+    } else {
+        match ledger.get_block(output_context.merkle_root_block) {
+            Ok(block) => Some(block.cumulative_txo_count),
+            Err(DbError::NotFound) => {
+                return Err(rpc_invalid_arg_error(
+                    "get_outputs",
+                    "Merkle root block is not in the ledger",
+                    logger,
+                ))
+            }
+            Err(err) => return Err(rpc_database_err(err, logger)),
         }
+    };
 
-        let latest_block_version = self
-            .ledger
-            .get_latest_block()
-            .map_err(|err| rpc_database_err(err, &self.logger))?
-            .version;
-
-        Ok(GetOutputsResponse {
-            num_blocks: self
-                .ledger
-                .num_blocks()
-                .map_err(|err| rpc_database_err(err, &self.logger))?,
-            global_txo_count: self
-                .ledger
-                .num_txos()
-                .map_err(|err| rpc_database_err(err, &self.logger))?,
-            results: output_context
-                .indexes
-                .iter()
-                .map(|idx| -> Result<OutputResult, DbError> {
-                    Ok(match self.get_output_impl(*idx)? {
+    let latest_block_version = ledger
+        .get_latest_block()
+        .map_err(|err| rpc_database_err(err, logger))?
+        .version;
+
+    Ok(GetOutputsResponse {
+        num_blocks: ledger
+            .num_blocks()
+            .map_err(|err| rpc_database_err(err, logger))?,
+        global_txo_count: ledger
+            .num_txos()
+            .map_err(|err| rpc_database_err(err, logger))?,
+        results: output_context
+            .indexes
+            .iter()
+            .map(|idx| -> Result<OutputResult, RpcStatus> {
+                Ok(
+                    match get_output(ledger, *idx, num_tx_outs_at_root, logger)? {
                         Some((output, proof)) => OutputResult {
                             index: *idx,
                             result_code: OutputResultCode::Exists as u32,
@@ -137,28 +167,49 @@ impl<L: Ledger + Clone, E: LedgerEnclaveProxy> MerkleProofService<L, E> {
                             output: Default::default(),
                             proof: Default::default(),
                         },
-                    })
-                })
-                .collect::<Result<Vec<_>, DbError>>()
-                .map_err(|err| rpc_database_err(err, &self.logger))?,
-            latest_block_version,
-            max_block_version: latest_block_version.max(*MAX_BLOCK_VERSION),
-        })
-    }
+                    },
+                )
+            })
+            .collect::<Result<Vec<_>, RpcStatus>>()?,
+        latest_block_version,
+        max_block_version: latest_block_version.max(*MAX_BLOCK_VERSION),
+    })
+}
 
-    fn get_output_impl(
-        &mut self,
-        idx: u64,
-    ) -> Result<Option<(TxOut, TxOutMembershipProof)>, DbError> {
-        match self.ledger.get_tx_out_by_index(idx).and_then(|tx_out| {
-            let proofs = self.ledger.get_tx_out_proof_of_memberships(&[idx])?;
-            Ok(Some((tx_out, proofs[0].clone())))
-        }) {
-            Ok(result) => Ok(result),
-            Err(DbError::NotFound) => Ok(None),
-            Err(err) => Err(err),
-        }
-    }
+/// Get a TxOut and its merkle proof of membership, against the root of the
+/// tree of the first `num_tx_outs_at_root` TxOuts if it is set, and of the
+/// current tree otherwise.
+///
+/// TxOuts which are not in that tree do not exist as far as the request is
+/// concerned.
+fn get_output<L: Ledger>(
+    ledger: &L,
+    idx: u64,
+    num_tx_outs_at_root: Option<u64>,
+    logger: &Logger,
+) -> Result<Option<(TxOut, TxOutMembershipProof)>, RpcStatus> {
+    // The proofs of the TxOut, and of the last TxOut of the tree at the root,
+    // if it is not the current tree
+    let indexes = match num_tx_outs_at_root {
+        None => vec![idx],
+        Some(num_tx_outs) if idx >= num_tx_outs => return Ok(None),
+        Some(num_tx_outs) => vec![idx, num_tx_outs - 1],
+    };
+    let (tx_out, proofs) = match ledger.get_tx_out_by_index(idx).and_then(|tx_out| {
+        let proofs = ledger.get_tx_out_proof_of_memberships(&indexes)?;
+        Ok((tx_out, proofs))
+    }) {
+        Ok(result) => result,
+        Err(DbError::NotFound) => return Ok(None),
+        Err(err) => return Err(rpc_database_err(err, logger)),
+    };
+
+    let proof = match proofs.get(1) {
+        None => proofs[0].clone(),
+        Some(last_proof) => derive_proof_at_highest_index(&proofs[0], last_proof)
+            .map_err(|err| rpc_internal_error("get_outputs", err, logger))?,
+    };
+    Ok(Some((tx_out, proof)))
 }
 
 impl<L: Ledger + Clone, E: LedgerEnclaveProxy> FogMerkleProofApi for MerkleProofService<L, E> {
@@ -265,6 +316,7 @@ mod test {
                 public_key: public_key.into(),
                 e_fog_hint: EncryptedFogHint::new(&[7u8; ENCRYPTED_FOG_HINT_LEN]),
                 e_memo: None,
+                additional_e_fog_hints: Default::default(),
             };
             tx_outs.push(tx_out);
         }
@@ -371,4 +423,25 @@ mod test {
         }
         assert_eq!(tx_out_set.len(), 50);
     }
+
+    // `get_outputs` should reject a merkle root block which is not in the ledger.
+    #[test_with_logger]
+    fn test_get_outputs_unknown_merkle_root_block(logger: Logger) {
+        let mut mock_ledger = MockLedger::default();
+        mock_ledger.num_tx_outs = 10;
+        mock_ledger.num_blocks = 2;
+
+        let enclave = MockEnclave::default();
+        let authenticator = Arc::new(AnonymousAuthenticator::default());
+        let mut ledger_server_node =
+            MerkleProofService::new(mock_ledger, enclave, authenticator, logger.clone());
+
+        let request = OutputContext {
+            indexes: vec![0, 1],
+            merkle_root_block: 2,
+        };
+
+        let status = ledger_server_node.get_outputs_impl(request).unwrap_err();
+        assert_eq!(status.code(), grpcio::RpcStatusCode::INVALID_ARGUMENT);
+    }
 }
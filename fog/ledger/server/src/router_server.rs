@@ -0,0 +1,175 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Server object containing a ledger router node
+//! Constructible from config (for testability) and with a mechanism for
+//! stopping it
+
+use crate::{
+    config::LedgerRouterConfig, counters, key_image_store_connection::KeyImageStoreConnection,
+    router_service::LedgerRouterService,
+};
+use futures::executor::block_on;
+use mc_attest_net::RaClient;
+use mc_common::{
+    logger::{log, Logger},
+    time::TimeProvider,
+};
+use mc_fog_api::{ledger::KeyImageServiceCapabilities, ledger_grpc};
+use mc_fog_ledger_enclave::LedgerEnclaveProxy;
+use mc_fog_uri::FogLedgerUri;
+use mc_sgx_report_cache_untrusted::ReportCacheThread;
+use mc_util_grpc::{
+    AnonymousAuthenticator, Authenticator, ConnectionUriGrpcioServer, TokenAuthenticator,
+};
+use mc_util_uri::ConnectionUri;
+use std::sync::Arc;
+
+/// The capacity of the ORAM of a router enclave. The router does not store any
+/// key images, so a small capacity is enough.
+pub const ROUTER_OMAP_CAPACITY: u64 = 512;
+
+pub struct LedgerRouterServer<E, RC>
+where
+    E: LedgerEnclaveProxy,
+    RC: RaClient + Send + Sync + 'static,
+{
+    config: LedgerRouterConfig,
+    server: grpcio::Server,
+    enclave: E,
+    ra_client: RC,
+    report_cache_thread: Option<ReportCacheThread>,
+    logger: Logger,
+}
+
+impl<E, RC> LedgerRouterServer<E, RC>
+where
+    E: LedgerEnclaveProxy,
+    RC: RaClient + Send + Sync + 'static,
+{
+    /// Make a new ledger router server instance
+    pub fn new(
+        config: LedgerRouterConfig,
+        enclave: E,
+        ra_client: RC,
+        time_provider: impl TimeProvider + 'static,
+        logger: Logger,
+    ) -> LedgerRouterServer<E, RC> {
+        let env = Arc::new(
+            grpcio::EnvBuilder::new()
+                .name_prefix("Main-RPC".to_string())
+                .build(),
+        );
+
+        let shard_env = Arc::new(
+            grpcio::EnvBuilder::new()
+                .name_prefix("Store-RPC".to_string())
+                .build(),
+        );
+        let connect_to_shards = |uris: &[FogLedgerUri]| -> Vec<KeyImageStoreConnection<E>> {
+            uris.iter()
+                .map(|uri| {
+                    KeyImageStoreConnection::new(
+                        uri.clone(),
+                        enclave.clone(),
+                        shard_env.clone(),
+                        logger.clone(),
+                    )
+                    .unwrap_or_else(|err| panic!("Invalid fog ledger store uri {}: {}", uri, err))
+                })
+                .collect()
+        };
+        let shards = connect_to_shards(&config.shard_uris);
+        let output_shards = connect_to_shards(&config.shard_uris);
+
+        let client_authenticator: Arc<dyn Authenticator + Sync + Send> =
+            if let Some(shared_secret) = config.client_auth_token_secret.as_ref() {
+                Arc::new(TokenAuthenticator::new(
+                    *shared_secret,
+                    config.client_auth_token_max_lifetime,
+                    time_provider,
+                ))
+            } else {
+                Arc::new(AnonymousAuthenticator::default())
+            };
+
+        let mut capabilities = KeyImageServiceCapabilities::new();
+        capabilities.set_max_queries_per_request(config.max_key_image_queries_per_request);
+
+        let ledger_router_service = LedgerRouterService::new(
+            enclave.clone(),
+            shards,
+            output_shards,
+            capabilities,
+            client_authenticator,
+            logger.clone(),
+        );
+        let key_image_service =
+            ledger_grpc::create_fog_key_image_api(ledger_router_service.clone());
+        let merkle_proof_service = ledger_grpc::create_fog_merkle_proof_api(ledger_router_service);
+        log::debug!(logger, "Constructed Ledger Router GRPC Services");
+
+        // Health check service
+        let health_service = mc_util_grpc::HealthService::new(None, logger.clone()).into_service();
+
+        // Package service into grpc server
+        log::info!(
+            logger,
+            "Starting Ledger Router server on {}",
+            config.client_listen_uri.addr(),
+        );
+        let server_builder = grpcio::ServerBuilder::new(env)
+            .register_service(key_image_service)
+            .register_service(merkle_proof_service)
+            .register_service(health_service)
+            .bind_using_uri(&config.client_listen_uri, logger.clone());
+
+        let server = server_builder.build().unwrap();
+
+        Self {
+            config,
+            server,
+            enclave,
+            ra_client,
+            report_cache_thread: None,
+            logger,
+        }
+    }
+
+    /// Start the server, which starts all the worker threads
+    pub fn start(&mut self) {
+        self.report_cache_thread = Some(
+            ReportCacheThread::start(
+                self.enclave.clone(),
+                self.ra_client.clone(),
+                self.config.ias_spid,
+                &counters::ENCLAVE_REPORT_TIMESTAMP,
+                self.logger.clone(),
+            )
+            .expect("failed starting report cache thread"),
+        );
+
+        self.server.start();
+        for (host, port) in self.server.bind_addrs() {
+            log::info!(self.logger, "API listening on {}:{}", host, port);
+        }
+    }
+
+    /// Stop the server and all worker threads
+    pub fn stop(&mut self) {
+        if let Some(ref mut thread) = self.report_cache_thread.take() {
+            thread.stop().expect("Could not stop report cache thread");
+        }
+
+        block_on(self.server.shutdown()).expect("Could not stop grpc server");
+    }
+}
+
+impl<E, RC> Drop for LedgerRouterServer<E, RC>
+where
+    E: LedgerEnclaveProxy,
+    RC: RaClient + Send + Sync + 'static,
+{
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
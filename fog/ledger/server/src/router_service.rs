@@ -0,0 +1,337 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! The client facing services of a fog ledger router.
+//!
+//! The router enclave decrypts each client key image check, and forwards it
+//! to every fog ledger store over their attested peer sessions. The stores
+//! each answer for their own range of key images, and the router enclave
+//! merges their answers into the response for the client.
+//!
+//! Every store has the whole ledger, so a merkle proof request is answered by
+//! any one of the stores, and re-encrypted for the client by the router
+//! enclave. As with an unsharded ledger server, the untrusted side sees which
+//! outputs are requested.
+//!
+//! Clients talk to a router in the same way as to a fog ledger server.
+
+use crate::{
+    error::RouterError, key_image_service::enclave_err_to_rpc_status,
+    key_image_store_connection::KeyImageStoreConnection,
+};
+use futures::executor::block_on;
+use grpcio::{RpcContext, RpcStatus, UnarySink};
+use mc_attest_api::attest;
+use mc_attest_enclave_api::{ClientSession, EnclaveMessage, PeerSession};
+use mc_common::logger::{log, Logger};
+use mc_fog_api::{
+    empty::Empty,
+    ledger::KeyImageServiceCapabilities,
+    ledger_grpc::{FogKeyImageApi, FogMerkleProofApi},
+};
+use mc_fog_ledger_enclave::LedgerEnclaveProxy;
+use mc_util_grpc::{
+    rpc_logger, rpc_permissions_error, rpc_unavailable_error, send_result, Authenticator,
+};
+use mc_util_metrics::SVC_COUNTERS;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+#[derive(Clone)]
+pub struct LedgerRouterService<E: LedgerEnclaveProxy> {
+    /// The router enclave
+    enclave: E,
+
+    /// The connections to the fog ledger stores used for key image checks.
+    ///
+    /// The peer sessions are stateful, so messages must reach a store in the
+    /// order they were encrypted in. The lock is held for the whole round
+    /// trip of a check to the stores.
+    shards: Arc<Mutex<Vec<KeyImageStoreConnection<E>>>>,
+
+    /// The connections to the fog ledger stores used for merkle proofs, which
+    /// are not attested.
+    output_shards: Arc<Vec<KeyImageStoreConnection<E>>>,
+
+    /// The store to ask for the next merkle proof request.
+    next_output_shard: Arc<AtomicUsize>,
+
+    /// Limits on key image requests, advertised to clients.
+    capabilities: KeyImageServiceCapabilities,
+
+    /// GRPC request authenticator.
+    authenticator: Arc<dyn Authenticator + Send + Sync>,
+
+    /// Slog logger object
+    logger: Logger,
+}
+
+impl<E: LedgerEnclaveProxy> LedgerRouterService<E> {
+    /// Creates a new ledger router service (but does not create sockets and
+    /// start it etc.)
+    pub fn new(
+        enclave: E,
+        shards: Vec<KeyImageStoreConnection<E>>,
+        output_shards: Vec<KeyImageStoreConnection<E>>,
+        capabilities: KeyImageServiceCapabilities,
+        authenticator: Arc<dyn Authenticator + Send + Sync>,
+        logger: Logger,
+    ) -> Self {
+        Self {
+            enclave,
+            shards: Arc::new(Mutex::new(shards)),
+            output_shards: Arc::new(output_shards),
+            next_output_shard: Arc::new(AtomicUsize::new(0)),
+            capabilities,
+            authenticator,
+            logger,
+        }
+    }
+
+    /// Accept a client connection
+    fn auth_impl(
+        &mut self,
+        request: attest::AuthMessage,
+        logger: &Logger,
+    ) -> Result<attest::AuthMessage, RpcStatus> {
+        let (response, _) = self
+            .enclave
+            .client_accept(request.into())
+            .map_err(|client_error| {
+                // This is debug because there's no requirement on the remote party to trigger
+                // it.
+                log::debug!(
+                    logger,
+                    "LedgerEnclave::client_accept failed: {}",
+                    client_error
+                );
+                rpc_permissions_error("client_auth", "Permission denied", logger)
+            })?;
+
+        Ok(response.into())
+    }
+
+    /// Forward the key image check to the stores, and collate their responses
+    fn check_key_images_impl(
+        &mut self,
+        request: attest::Message,
+    ) -> Result<attest::Message, RpcStatus> {
+        log::trace!(self.logger, "Getting encrypted request");
+
+        let client_session = ClientSession::from(request.get_channel_id());
+        let max_queries = match self.capabilities.get_max_queries_per_request() {
+            0 => None,
+            max_queries => Some(max_queries),
+        };
+
+        let mut shards = self.shards.lock().expect("mutex poisoned");
+
+        let mut shard_sessions = Vec::with_capacity(shards.len());
+        for shard in shards.iter_mut() {
+            let peer_session = shard.attest().map_err(|err| {
+                shard.deattest();
+                rpc_unavailable_error(
+                    "attest",
+                    format!("fog ledger store {}: {}", shard.uri(), err),
+                    &self.logger,
+                )
+            })?;
+            shard_sessions.push(peer_session);
+        }
+
+        let shard_queries = self
+            .enclave
+            .create_shard_key_image_queries(request.into(), shard_sessions, max_queries)
+            .map_err(|e| {
+                enclave_err_to_rpc_status("create_shard_key_image_queries", e, &self.logger)
+            })?;
+
+        // Once the queries are encrypted, the peer sessions only stay in sync
+        // if every response is decrypted, so any failure deattests all stores.
+        let result_blob = self
+            .query_shards(&shards, shard_queries)
+            .and_then(|shard_responses| {
+                self.enclave
+                    .collate_shard_key_image_responses(client_session, shard_responses)
+                    .map_err(|e| {
+                        enclave_err_to_rpc_status(
+                            "collate_shard_key_image_responses",
+                            e,
+                            &self.logger,
+                        )
+                    })
+            })
+            .map_err(|status| {
+                shards
+                    .iter_mut()
+                    .for_each(KeyImageStoreConnection::deattest);
+                status
+            })?;
+
+        let mut resp = attest::Message::new();
+        resp.set_data(result_blob);
+        Ok(resp)
+    }
+
+    /// Send the shard queries to the stores in parallel, and wait for all of
+    /// their responses.
+    fn query_shards(
+        &self,
+        shards: &[KeyImageStoreConnection<E>],
+        shard_queries: Vec<EnclaveMessage<PeerSession>>,
+    ) -> Result<Vec<EnclaveMessage<PeerSession>>, RpcStatus> {
+        let responses = shards
+            .iter()
+            .zip(shard_queries)
+            .map(|(shard, shard_query)| {
+                let receiver = shard.check_key_images_async(shard_query);
+                async move {
+                    let response = receiver?.await.map_err(RouterError::from)?;
+                    Ok::<_, RouterError>(EnclaveMessage::from(response))
+                }
+            });
+
+        block_on(futures::future::join_all(responses))
+            .into_iter()
+            .zip(shards)
+            .map(|(result, shard)| {
+                result.map_err(|err| {
+                    rpc_unavailable_error(
+                        "shard_check_key_images",
+                        format!("fog ledger store {}: {}", shard.uri(), err),
+                        &self.logger,
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Get the outputs and merkle proofs from one of the stores, trying the
+    /// next store if one fails, and encrypt them for the client
+    fn get_outputs_impl(&mut self, request: attest::Message) -> Result<attest::Message, RpcStatus> {
+        let client_session = ClientSession::from(request.get_channel_id());
+
+        let output_context = self
+            .enclave
+            .get_outputs(request.into())
+            .map_err(|e| enclave_err_to_rpc_status("get_outputs", e, &self.logger))?;
+
+        let num_shards = self.output_shards.len();
+        let first_shard = self.next_output_shard.fetch_add(1, Ordering::Relaxed);
+        let mut last_error = None;
+        for offset in 0..num_shards {
+            let shard = &self.output_shards[(first_shard + offset) % num_shards];
+            match shard.get_outputs(&output_context) {
+                Ok(output_data) => {
+                    let result = self
+                        .enclave
+                        .get_outputs_data(output_data, client_session)
+                        .map_err(|e| {
+                            enclave_err_to_rpc_status("get_outputs_data", e, &self.logger)
+                        })?;
+                    return Ok(result.into());
+                }
+                Err(err) => {
+                    log::warn!(
+                        self.logger,
+                        "Failed getting outputs from fog ledger store {}: {}",
+                        shard.uri(),
+                        err
+                    );
+                    last_error = Some(format!("fog ledger store {}: {}", shard.uri(), err));
+                }
+            }
+        }
+
+        Err(rpc_unavailable_error(
+            "get_outputs",
+            last_error.unwrap_or_else(|| "no fog ledger stores".to_string()),
+            &self.logger,
+        ))
+    }
+}
+
+impl<E: LedgerEnclaveProxy> FogKeyImageApi for LedgerRouterService<E> {
+    fn auth(
+        &mut self,
+        ctx: RpcContext,
+        request: attest::AuthMessage,
+        sink: UnarySink<attest::AuthMessage>,
+    ) {
+        let _timer = SVC_COUNTERS.req(&ctx);
+        mc_common::logger::scoped_global_logger(&rpc_logger(&ctx, &self.logger), |logger| {
+            if let Err(err) = self.authenticator.authenticate_rpc(&ctx) {
+                return send_result(ctx, sink, err.into(), logger);
+            }
+
+            send_result(ctx, sink, self.auth_impl(request, logger), logger)
+        });
+    }
+
+    fn check_key_images(
+        &mut self,
+        ctx: RpcContext,
+        request: attest::Message,
+        sink: UnarySink<attest::Message>,
+    ) {
+        let _timer = SVC_COUNTERS.req(&ctx);
+        mc_common::logger::scoped_global_logger(&rpc_logger(&ctx, &self.logger), |logger| {
+            if let Err(err) = self.authenticator.authenticate_rpc(&ctx) {
+                return send_result(ctx, sink, err.into(), logger);
+            }
+
+            send_result(ctx, sink, self.check_key_images_impl(request), logger)
+        })
+    }
+
+    fn get_capabilities(
+        &mut self,
+        ctx: RpcContext,
+        _request: Empty,
+        sink: UnarySink<KeyImageServiceCapabilities>,
+    ) {
+        let _timer = SVC_COUNTERS.req(&ctx);
+        mc_common::logger::scoped_global_logger(&rpc_logger(&ctx, &self.logger), |logger| {
+            if let Err(err) = self.authenticator.authenticate_rpc(&ctx) {
+                return send_result(ctx, sink, err.into(), logger);
+            }
+
+            send_result(ctx, sink, Ok(self.capabilities.clone()), logger)
+        })
+    }
+}
+
+impl<E: LedgerEnclaveProxy> FogMerkleProofApi for LedgerRouterService<E> {
+    fn auth(
+        &mut self,
+        ctx: RpcContext,
+        request: attest::AuthMessage,
+        sink: UnarySink<attest::AuthMessage>,
+    ) {
+        let _timer = SVC_COUNTERS.req(&ctx);
+        mc_common::logger::scoped_global_logger(&rpc_logger(&ctx, &self.logger), |logger| {
+            if let Err(err) = self.authenticator.authenticate_rpc(&ctx) {
+                return send_result(ctx, sink, err.into(), logger);
+            }
+
+            send_result(ctx, sink, self.auth_impl(request, logger), logger)
+        });
+    }
+
+    fn get_outputs(
+        &mut self,
+        ctx: RpcContext,
+        request: attest::Message,
+        sink: UnarySink<attest::Message>,
+    ) {
+        let _timer = SVC_COUNTERS.req(&ctx);
+        mc_common::logger::scoped_global_logger(&rpc_logger(&ctx, &self.logger), |logger| {
+            if let Err(err) = self.authenticator.authenticate_rpc(&ctx) {
+                return send_result(ctx, sink, err.into(), logger);
+            }
+
+            send_result(ctx, sink, self.get_outputs_impl(request), logger)
+        })
+    }
+}
@@ -1,7 +1,8 @@
 // Copyright (c) 2018-2022 The MobileCoin Foundation
 
 use crate::{
-    config::LedgerServerConfig, counters, db_fetcher::DbFetcher, BlockService, KeyImageService,
+    config::LedgerServerConfig, counters, db_fetcher::DbFetcher,
+    key_image_store_service::KeyImageStoreService, BlockService, KeyImageService,
     MerkleProofService, UntrustedTxOutService,
 };
 use displaydoc::Display;
@@ -12,7 +13,10 @@ use mc_common::{
     logger::{log, Logger},
     time::TimeProvider,
 };
-use mc_fog_api::ledger_grpc;
+use mc_fog_api::{
+    ledger::{KeyImageServiceCapabilities, UntrustedTxOutServiceCapabilities},
+    ledger_grpc,
+};
 use mc_fog_ledger_enclave::{Error as EnclaveError, LedgerEnclaveProxy};
 use mc_ledger_db::LedgerDB;
 use mc_sgx_report_cache_untrusted::{Error as ReportCacheError, ReportCacheThread};
@@ -69,6 +73,7 @@ pub struct LedgerServer<E: LedgerEnclaveProxy, R: RaClient + Send + Sync + 'stat
     config: LedgerServerConfig,
     server: Option<grpcio::Server>,
     key_image_service: KeyImageService<LedgerDB, E>,
+    key_image_store_service: KeyImageStoreService<LedgerDB, E>,
     merkle_proof_service: MerkleProofService<LedgerDB, E>,
     block_service: BlockService<LedgerDB>,
     untrusted_tx_out_service: UntrustedTxOutService<LedgerDB>,
@@ -102,14 +107,25 @@ impl<E: LedgerEnclaveProxy, R: RaClient + Send + Sync + 'static> LedgerServer<E,
 
         let shared_state = Arc::new(Mutex::new(DbPollSharedState::default()));
 
+        let mut capabilities = KeyImageServiceCapabilities::new();
+        capabilities.set_max_queries_per_request(config.max_key_image_queries_per_request);
+        capabilities.set_max_message_size(config.grpc_max_message_size.unwrap_or(0) as u64);
+
         let key_image_service = KeyImageService::new(
             ledger.clone(),
             watcher.clone(),
             enclave.clone(),
-            shared_state,
+            shared_state.clone(),
+            capabilities,
             client_authenticator.clone(),
             logger.clone(),
         );
+        let key_image_store_service = KeyImageStoreService::new(
+            ledger.clone(),
+            enclave.clone(),
+            shared_state,
+            logger.clone(),
+        );
         let merkle_proof_service = MerkleProofService::new(
             ledger.clone(),
             enclave.clone(),
@@ -122,9 +138,13 @@ impl<E: LedgerEnclaveProxy, R: RaClient + Send + Sync + 'static> LedgerServer<E,
             client_authenticator.clone(),
             logger.clone(),
         );
+        let mut untrusted_tx_out_capabilities = UntrustedTxOutServiceCapabilities::new();
+        untrusted_tx_out_capabilities
+            .set_max_queries_per_request(config.max_tx_out_queries_per_request);
         let untrusted_tx_out_service = UntrustedTxOutService::new(
             ledger,
             watcher,
+            untrusted_tx_out_capabilities,
             client_authenticator.clone(),
             logger.clone(),
         );
@@ -133,6 +153,7 @@ impl<E: LedgerEnclaveProxy, R: RaClient + Send + Sync + 'static> LedgerServer<E,
             config,
             server: None,
             key_image_service,
+            key_image_store_service,
             merkle_proof_service,
             block_service,
             untrusted_tx_out_service,
@@ -161,6 +182,7 @@ impl<E: LedgerEnclaveProxy, R: RaClient + Send + Sync + 'static> LedgerServer<E,
                 self.enclave.clone(),
                 self.key_image_service.get_watcher(),
                 self.key_image_service.get_db_poll_shared_state(),
+                self.config.key_image_range.unwrap_or_default(),
                 readiness_indicator.clone(),
                 self.logger.clone(),
             ));
@@ -174,6 +196,8 @@ impl<E: LedgerEnclaveProxy, R: RaClient + Send + Sync + 'static> LedgerServer<E,
             // Package endpoints into grpc service
             let key_image_service =
                 ledger_grpc::create_fog_key_image_api(self.key_image_service.clone());
+            let key_image_store_service =
+                ledger_grpc::create_key_image_store_api(self.key_image_store_service.clone());
             let merkle_proof_service =
                 ledger_grpc::create_fog_merkle_proof_api(self.merkle_proof_service.clone());
             let block_service = ledger_grpc::create_fog_block_api(self.block_service.clone());
@@ -193,8 +217,19 @@ impl<E: LedgerEnclaveProxy, R: RaClient + Send + Sync + 'static> LedgerServer<E,
                 "Starting Ledger server on {}",
                 self.config.client_listen_uri.addr(),
             );
-            let server_builder = grpcio::ServerBuilder::new(env)
+            let mut server_builder = grpcio::ServerBuilder::new(env.clone());
+            if let Some(max_message_size) = self.config.grpc_max_message_size {
+                let max_message_size = i32::try_from(max_message_size).unwrap_or(i32::MAX);
+                server_builder = server_builder.channel_args(
+                    grpcio::ChannelBuilder::new(env)
+                        .max_receive_message_len(max_message_size)
+                        .max_send_message_len(max_message_size)
+                        .build_args(),
+                );
+            }
+            let server_builder = server_builder
                 .register_service(key_image_service)
+                .register_service(key_image_store_service)
                 .register_service(merkle_proof_service)
                 .register_service(block_service)
                 .register_service(untrusted_tx_out_service)
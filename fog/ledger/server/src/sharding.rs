@@ -0,0 +1,134 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Partitioning of the key image store between fog ledger store servers.
+//!
+//! Each store server only loads the key images which fall in its range into
+//! its enclave. The router then asks every store about every key image of a
+//! query, and merges their answers.
+
+use displaydoc::Display;
+use mc_transaction_core::ring_signature::KeyImage;
+use serde::Serialize;
+use std::{fmt, str::FromStr};
+
+/// An inclusive range of key images, compared by their first 8 bytes read as
+/// a big-endian integer. Parsed from and displayed as two hex numbers
+/// separated by a dash, e.g. `0-7fffffffffffffff`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub struct KeyImageRange {
+    /// The lowest key image prefix in the range.
+    pub start: u64,
+
+    /// The highest key image prefix in the range.
+    pub end: u64,
+}
+
+impl KeyImageRange {
+    /// Whether a key image is in the range.
+    pub fn contains(&self, key_image: &KeyImage) -> bool {
+        let mut prefix = [0u8; 8];
+        prefix.copy_from_slice(&key_image.as_bytes()[..8]);
+        let prefix = u64::from_be_bytes(prefix);
+        self.start <= prefix && prefix <= self.end
+    }
+}
+
+impl Default for KeyImageRange {
+    /// The range of all key images.
+    fn default() -> Self {
+        Self {
+            start: 0,
+            end: u64::MAX,
+        }
+    }
+}
+
+impl fmt::Display for KeyImageRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:x}-{:x}", self.start, self.end)
+    }
+}
+
+/// An error parsing a KeyImageRange
+#[derive(Clone, Debug, Display, Eq, PartialEq)]
+pub enum KeyImageRangeParseError {
+    /// Expected two hex numbers separated by a dash
+    MissingDash,
+    /// Invalid hex number: {0}
+    InvalidNumber(String),
+    /// The start of the range is after its end
+    Empty,
+}
+
+impl std::error::Error for KeyImageRangeParseError {}
+
+impl FromStr for KeyImageRange {
+    type Err = KeyImageRangeParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let (start, end) = src
+            .split_once('-')
+            .ok_or(KeyImageRangeParseError::MissingDash)?;
+        let parse = |num: &str| {
+            u64::from_str_radix(num.trim(), 16)
+                .map_err(|_| KeyImageRangeParseError::InvalidNumber(num.to_string()))
+        };
+        let range = Self {
+            start: parse(start)?,
+            end: parse(end)?,
+        };
+        if range.start > range.end {
+            return Err(KeyImageRangeParseError::Empty);
+        }
+        Ok(range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_display() {
+        let range = KeyImageRange::from_str("0-7fffffffffffffff").unwrap();
+        assert_eq!(
+            range,
+            KeyImageRange {
+                start: 0,
+                end: 0x7fff_ffff_ffff_ffff
+            }
+        );
+        assert_eq!(range.to_string(), "0-7fffffffffffffff");
+        assert_eq!(
+            KeyImageRange::from_str(&KeyImageRange::default().to_string()).unwrap(),
+            KeyImageRange::default()
+        );
+
+        assert_eq!(
+            KeyImageRange::from_str("1234"),
+            Err(KeyImageRangeParseError::MissingDash)
+        );
+        assert_eq!(
+            KeyImageRange::from_str("0-xyz"),
+            Err(KeyImageRangeParseError::InvalidNumber("xyz".to_string()))
+        );
+        assert_eq!(
+            KeyImageRange::from_str("10-f"),
+            Err(KeyImageRangeParseError::Empty)
+        );
+    }
+
+    #[test]
+    fn contains() {
+        let lower = KeyImageRange::from_str("0-7fffffffffffffff").unwrap();
+        let upper = KeyImageRange::from_str("8000000000000000-ffffffffffffffff").unwrap();
+
+        for key_image in [[0u8; 32], [0x7f; 32], [0x80; 32], [0xff; 32]] {
+            let key_image = KeyImage::from(key_image);
+            assert_ne!(lower.contains(&key_image), upper.contains(&key_image));
+            assert!(KeyImageRange::default().contains(&key_image));
+        }
+        assert!(lower.contains(&KeyImage::from([0x7f; 32])));
+        assert!(upper.contains(&KeyImage::from([0x80; 32])));
+    }
+}
@@ -4,11 +4,18 @@ use grpcio::{RpcContext, RpcStatus, UnarySink};
 use mc_common::logger::{log, Logger};
 use mc_crypto_keys::CompressedRistrettoPublic;
 use mc_fog_api::{
-    ledger::{TxOutRequest, TxOutResponse, TxOutResult, TxOutResultCode},
+    empty::Empty,
+    external,
+    ledger::{
+        TxOutRequest, TxOutResponse, TxOutResult, TxOutResultCode,
+        UntrustedTxOutServiceCapabilities,
+    },
     ledger_grpc::FogUntrustedTxOutApi,
 };
 use mc_ledger_db::{self, Error as DbError, Ledger};
-use mc_util_grpc::{rpc_internal_error, rpc_logger, send_result, Authenticator};
+use mc_util_grpc::{
+    rpc_internal_error, rpc_invalid_arg_error, rpc_logger, send_result, Authenticator,
+};
 use mc_util_metrics::SVC_COUNTERS;
 use mc_watcher::watcher_db::WatcherDB;
 use mc_watcher_api::TimestampResultCode;
@@ -18,6 +25,7 @@ use std::sync::Arc;
 pub struct UntrustedTxOutService<L: Ledger + Clone> {
     ledger: L,
     watcher: WatcherDB,
+    capabilities: UntrustedTxOutServiceCapabilities,
     authenticator: Arc<dyn Authenticator + Send + Sync>,
     logger: Logger,
 }
@@ -26,12 +34,14 @@ impl<L: Ledger + Clone> UntrustedTxOutService<L> {
     pub fn new(
         ledger: L,
         watcher: WatcherDB,
+        capabilities: UntrustedTxOutServiceCapabilities,
         authenticator: Arc<dyn Authenticator + Send + Sync>,
         logger: Logger,
     ) -> Self {
         Self {
             ledger,
             watcher,
+            capabilities,
             authenticator,
             logger,
         }
@@ -40,6 +50,19 @@ impl<L: Ledger + Clone> UntrustedTxOutService<L> {
     fn get_tx_outs_impl(&mut self, request: TxOutRequest) -> Result<TxOutResponse, RpcStatus> {
         mc_common::trace_time!(self.logger, "Get Blocks");
 
+        let num_queries = request.tx_out_pubkeys.len() + request.tx_out_global_indices.len();
+        let max_queries = self.capabilities.get_max_queries_per_request();
+        if max_queries != 0 && num_queries as u64 > max_queries {
+            return Err(rpc_invalid_arg_error(
+                "get_tx_outs",
+                format!(
+                    "{} queries exceed the limit of {} per request",
+                    num_queries, max_queries
+                ),
+                &self.logger,
+            ));
+        }
+
         let mut response = TxOutResponse::new();
 
         response.num_blocks = self
@@ -85,6 +108,26 @@ impl<L: Ledger + Clone> UntrustedTxOutService<L> {
             )
         }
 
+        for tx_out_global_index in request.tx_out_global_indices.iter() {
+            response
+                .results
+                .push(match self.get_tx_out_by_index(*tx_out_global_index) {
+                    Ok(result) => result,
+                    Err(err) => {
+                        log::error!(
+                            self.logger,
+                            "DbError getting tx_out {}: {}",
+                            tx_out_global_index,
+                            err
+                        );
+                        let mut result = TxOutResult::new();
+                        result.tx_out_global_index = *tx_out_global_index;
+                        result.result_code = TxOutResultCode::DatabaseError;
+                        result
+                    }
+                })
+        }
+
         Ok(response)
     }
 
@@ -106,9 +149,28 @@ impl<L: Ledger + Clone> UntrustedTxOutService<L> {
             }
         };
 
-        result.result_code = TxOutResultCode::Found;
+        self.get_tx_out_by_index(tx_out_index)
+    }
+
+    fn get_tx_out_by_index(&mut self, tx_out_index: u64) -> Result<TxOutResult, DbError> {
+        let mut result = TxOutResult::new();
         result.tx_out_global_index = tx_out_index;
 
+        let tx_out = match self.ledger.get_tx_out_by_index(tx_out_index) {
+            Ok(tx_out) => tx_out,
+            Err(DbError::NotFound) => {
+                result.result_code = TxOutResultCode::NotFound;
+                return Ok(result);
+            }
+            Err(err) => {
+                return Err(err);
+            }
+        };
+
+        result.result_code = TxOutResultCode::Found;
+        result.set_tx_out_pubkey((&tx_out.public_key).into());
+        result.set_tx_out(external::TxOut::from(&tx_out));
+
         let block_index = self
             .ledger
             .get_block_index_by_tx_out_index(tx_out_index)
@@ -161,4 +223,20 @@ impl<L: Ledger + Clone> FogUntrustedTxOutApi for UntrustedTxOutService<L> {
             send_result(ctx, sink, self.get_tx_outs_impl(request), logger)
         })
     }
+
+    fn get_capabilities(
+        &mut self,
+        ctx: RpcContext,
+        _request: Empty,
+        sink: UnarySink<UntrustedTxOutServiceCapabilities>,
+    ) {
+        let _timer = SVC_COUNTERS.req(&ctx);
+        mc_common::logger::scoped_global_logger(&rpc_logger(&ctx, &self.logger), |logger| {
+            if let Err(err) = self.authenticator.authenticate_rpc(&ctx) {
+                return send_result(ctx, sink, err.into(), logger);
+            }
+
+            send_result(ctx, sink, Ok(self.capabilities.clone()), logger)
+        })
+    }
 }
@@ -14,7 +14,7 @@ use mc_common::{
     ResponderId,
 };
 use mc_crypto_keys::{CompressedRistrettoPublic, Ed25519Pair};
-use mc_fog_api::ledger::TxOutResultCode;
+use mc_fog_api::{external, ledger::TxOutResultCode};
 use mc_fog_ledger_connection::{
     FogKeyImageGrpcClient, FogMerkleProofGrpcClient, FogUntrustedLedgerGrpcClient,
     KeyImageResultExtension, OutputResultExtension,
@@ -25,8 +25,10 @@ use mc_fog_test_infra::get_enclave_path;
 use mc_fog_uri::{ConnectionUri, FogLedgerUri};
 use mc_ledger_db::{test_utils::recreate_ledger_db, Ledger, LedgerDB};
 use mc_transaction_core::{
-    membership_proofs::compute_implied_merkle_root, ring_signature::KeyImage, tokens::Mob, Amount,
-    Token,
+    membership_proofs::{compute_implied_merkle_root, derive_proof_at_index},
+    ring_signature::KeyImage,
+    tokens::Mob,
+    Amount, Token,
 };
 use mc_util_from_random::FromRandom;
 use mc_util_grpc::GrpcRetryConfig;
@@ -125,6 +127,10 @@ fn fog_ledger_merkle_proofs_test(logger: Logger) {
                 client_auth_token_secret: None,
                 client_auth_token_max_lifetime: Default::default(),
                 omap_capacity: OMAP_CAPACITY,
+                max_key_image_queries_per_request: Default::default(),
+                max_tx_out_queries_per_request: Default::default(),
+                grpc_max_message_size: None,
+                key_image_range: None,
             };
 
             let enclave = LedgerSgxEnclave::new(
@@ -213,6 +219,39 @@ fn fog_ledger_merkle_proofs_test(logger: Logger) {
             assert!(response.results[1].status().as_ref().unwrap().is_some());
             assert!(response.results[2].status().as_ref().unwrap().is_none());
             assert!(response.results[3].status().as_ref().unwrap().is_none());
+
+            // Get merkle root of block 1, which ends with the last of its 6 TxOuts
+            let merkle_root_1 = {
+                let temp = ledger.get_tx_out_proof_of_memberships(&[5u64]).unwrap();
+                let merkle_proof = derive_proof_at_index(&temp[0]).unwrap();
+                compute_implied_merkle_root(&merkle_proof).unwrap()
+            };
+
+            // Get tx outs and merkle proofs as of block 1
+            let response = client
+                .get_outputs(vec![0u64, 3u64, 5u64, 6u64, 8u64], 1)
+                .expect("get outputs failed");
+
+            // Test the basic fields
+            assert_eq!(response.num_blocks, num_blocks);
+            assert_eq!(response.global_txo_count, ledger.num_txos().unwrap());
+            assert_eq!(response.results.len(), 5);
+
+            // The TxOuts of blocks 0 and 1 have proofs against the root of block 1
+            for res in response.results[..3].iter() {
+                let (tx_out, proof) = res.status().unwrap().unwrap();
+                let result = mc_transaction_core::membership_proofs::is_membership_proof_valid(
+                    &tx_out,
+                    &proof,
+                    merkle_root_1.hash.as_ref(),
+                )
+                .expect("membership proof structure failed!");
+                assert!(result, "membership proof was invalid! idx = {}, output = {:?}, proof = {:?}, merkle_root = {:?}", res.index, tx_out, proof, merkle_root_1);
+            }
+
+            // The TxOuts of block 2 are not in the tree as of block 1
+            assert!(response.results[3].status().as_ref().unwrap().is_none());
+            assert!(response.results[4].status().as_ref().unwrap().is_none());
         }
 
         // grpcio detaches all its threads and does not join them :(
@@ -316,6 +355,11 @@ fn fog_ledger_key_images_test(logger: Logger) {
                 client_auth_token_secret: None,
                 client_auth_token_max_lifetime: Default::default(),
                 omap_capacity: OMAP_CAPACITY,
+                // Small enough that the client splits its key image checks
+                max_key_image_queries_per_request: 2,
+                max_tx_out_queries_per_request: Default::default(),
+                grpc_max_message_size: None,
+                key_image_range: None,
             };
 
             let enclave = LedgerSgxEnclave::new(
@@ -360,6 +404,9 @@ fn fog_ledger_key_images_test(logger: Logger) {
                 logger.clone(),
             );
 
+            // The client learns the server's limit on queries per request
+            assert_eq!(client.max_queries_per_request(), 2);
+
             // Check on key images
             let mut response = client
                 .check_key_images(&[keys[0], keys[1], keys[3], keys[7], keys[19]])
@@ -514,6 +561,10 @@ fn fog_ledger_blocks_api_test(logger: Logger) {
             client_auth_token_secret: None,
             client_auth_token_max_lifetime: Default::default(),
             omap_capacity: OMAP_CAPACITY,
+            max_key_image_queries_per_request: Default::default(),
+            max_tx_out_queries_per_request: Default::default(),
+            grpc_max_message_size: None,
+            key_image_range: None,
         };
 
         let enclave = LedgerSgxEnclave::new(
@@ -675,6 +726,10 @@ fn fog_ledger_untrusted_tx_out_api_test(logger: Logger) {
             client_auth_token_secret: None,
             client_auth_token_max_lifetime: Default::default(),
             omap_capacity: OMAP_CAPACITY,
+            max_key_image_queries_per_request: Default::default(),
+            max_tx_out_queries_per_request: 2,
+            grpc_max_message_size: None,
+            key_image_range: None,
         };
 
         let enclave = LedgerSgxEnclave::new(
@@ -704,7 +759,7 @@ fn fog_ledger_untrusted_tx_out_api_test(logger: Logger) {
             .expect("Failed starting ledger server");
 
         // Make unattested ledger client
-        let client = FogUntrustedLedgerGrpcClient::new(
+        let mut client = FogUntrustedLedgerGrpcClient::new(
             client_uri,
             GRPC_RETRY_CONFIG,
             grpc_env,
@@ -738,6 +793,35 @@ fn fog_ledger_untrusted_tx_out_api_test(logger: Logger) {
             result.results[1].timestamp_result_code,
             TimestampResultCode::BlockIndexOutOfBounds as u32
         );
+        assert_eq!(
+            result.results[1].get_tx_out(),
+            &external::TxOut::from(&real_tx_out0)
+        );
+
+        // The server advertises its limit on queries per request, and the
+        // client splits its lookups by it
+        assert_eq!(client.max_queries_per_request(), 2);
+
+        // Look up tx outs by global index, including one out of bounds
+        let real_tx_out1 = ledger.get_tx_out_by_index(1).unwrap();
+        let result = client.get_tx_outs_by_index(vec![1, 0, 100]).unwrap();
+        assert_eq!(result.num_blocks, 4);
+        assert_eq!(result.results.len(), 3);
+        assert_eq!(result.results[0].result_code, TxOutResultCode::Found);
+        assert_eq!(result.results[0].tx_out_global_index, 1);
+        assert_eq!(result.results[0].block_index, 1);
+        assert_eq!(
+            &result.results[0].tx_out_pubkey.clone().unwrap().data[..],
+            &real_tx_out1.public_key.as_bytes()[..]
+        );
+        assert_eq!(
+            result.results[0].get_tx_out(),
+            &external::TxOut::from(&real_tx_out1)
+        );
+        assert_eq!(result.results[1].result_code, TxOutResultCode::Found);
+        assert_eq!(result.results[1].tx_out_global_index, 0);
+        assert_eq!(result.results[2].result_code, TxOutResultCode::NotFound);
+        assert_eq!(result.results[2].tx_out_global_index, 100);
     }
 
     // grpcio detaches all its threads and does not join them :(
@@ -0,0 +1,342 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Integration tests of a fog ledger router in front of sharded fog ledger
+//! stores, at the level of the fog ledger connection / fog ledger grpc API
+
+use mc_account_keys::{AccountKey, PublicAddress};
+use mc_attest_net::{Client as AttestClient, RaClient};
+use mc_attest_verifier::{MrSignerVerifier, Verifier, DEBUG_ENCLAVE};
+use mc_blockchain_types::BlockVersion;
+use mc_common::{
+    logger::{test_with_logger, Logger},
+    time::SystemTimeProvider,
+    ResponderId,
+};
+use mc_fog_ledger_connection::{
+    FogKeyImageGrpcClient, FogMerkleProofGrpcClient, KeyImageResultExtension, OutputResultExtension,
+};
+use mc_fog_ledger_enclave::LedgerSgxEnclave;
+use mc_fog_ledger_server::{
+    KeyImageRange, LedgerRouterConfig, LedgerRouterServer, LedgerServer, LedgerServerConfig,
+    ROUTER_OMAP_CAPACITY,
+};
+use mc_fog_test_infra::get_enclave_path;
+use mc_fog_uri::{ConnectionUri, FogLedgerUri};
+use mc_ledger_db::{test_utils::recreate_ledger_db, Ledger, LedgerDB};
+use mc_transaction_core::{
+    membership_proofs::compute_implied_merkle_root, ring_signature::KeyImage, tokens::Mob, Amount,
+    Token,
+};
+use mc_util_grpc::GrpcRetryConfig;
+use mc_util_test_helper::{CryptoRng, RngCore, RngType, SeedableRng};
+use mc_watcher::watcher_db::WatcherDB;
+use std::{path::PathBuf, str::FromStr, sync::Arc, thread::sleep, time::Duration};
+use tempdir::TempDir;
+use url::Url;
+
+const TEST_URL: &str = "http://www.my_url1.com";
+
+const OMAP_CAPACITY: u64 = 128 * 128;
+
+const GRPC_RETRY_CONFIG: GrpcRetryConfig = GrpcRetryConfig {
+    grpc_retry_count: 3,
+    grpc_retry_millis: 20,
+};
+
+/// The key image ranges of the two stores
+const STORE_KEY_IMAGE_RANGES: [&str; 2] =
+    ["0-7fffffffffffffff", "8000000000000000-ffffffffffffffff"];
+
+fn setup_watcher_db(logger: Logger) -> (WatcherDB, PathBuf) {
+    let url = Url::parse(TEST_URL).unwrap();
+
+    let db_tmp = TempDir::new("wallet_db").expect("Could not make tempdir for wallet db");
+    WatcherDB::create(db_tmp.path()).unwrap();
+    let watcher = WatcherDB::open_rw(db_tmp.path(), &[url], logger).unwrap();
+    let watcher_dir = db_tmp.path().to_path_buf();
+    (watcher, watcher_dir)
+}
+
+fn make_verifier() -> Verifier {
+    let mut mr_signer_verifier =
+        MrSignerVerifier::from(mc_fog_ledger_enclave_measurement::sigstruct());
+    mr_signer_verifier.allow_hardening_advisory("INTEL-SA-00334");
+
+    let mut verifier = Verifier::default();
+    verifier.mr_signer(mr_signer_verifier).debug(DEBUG_ENCLAVE);
+    verifier
+}
+
+// Test that a fog ledger connection is able to check key images and get
+// merkle proofs by hitting a fog ledger router, which asks two fog ledger
+// stores each holding half of the key images
+#[test_with_logger]
+fn fog_ledger_router_test(logger: Logger) {
+    let base_port = 3270;
+
+    let mut rng = RngType::from_seed([0u8; 32]);
+
+    let alice = AccountKey::random_with_fog(&mut rng);
+    let recipients = vec![alice.default_subaddress()];
+
+    let keys: Vec<KeyImage> = (0..20).map(|x| KeyImage::from(x as u64)).collect();
+    let lower_range = KeyImageRange::from_str(STORE_KEY_IMAGE_RANGES[0]).unwrap();
+    // Make sure both stores have some of the spent key images
+    assert!(keys[0..9].iter().any(|key| lower_range.contains(key)));
+    assert!(keys[0..9].iter().any(|key| !lower_range.contains(key)));
+
+    // Make LedgerDB
+    let ledger_dir = TempDir::new("fog-ledger").expect("Could not get test_ledger tempdir");
+    let db_full_path = ledger_dir.path();
+    let mut ledger = recreate_ledger_db(db_full_path);
+
+    // Make WatcherDB
+    let (mut watcher, watcher_dir) = setup_watcher_db(logger.clone());
+
+    // Populate ledger with some data
+    // Origin block cannot have key images
+    add_block_to_ledger(
+        BlockVersion::MAX,
+        &mut ledger,
+        &recipients,
+        &[],
+        &mut rng,
+        &mut watcher,
+    );
+    add_block_to_ledger(
+        BlockVersion::MAX,
+        &mut ledger,
+        &recipients,
+        &keys[0..3],
+        &mut rng,
+        &mut watcher,
+    );
+    add_block_to_ledger(
+        BlockVersion::MAX,
+        &mut ledger,
+        &recipients,
+        &keys[3..6],
+        &mut rng,
+        &mut watcher,
+    );
+    let num_blocks = add_block_to_ledger(
+        BlockVersion::MAX,
+        &mut ledger,
+        &recipients,
+        &keys[6..9],
+        &mut rng,
+        &mut watcher,
+    );
+
+    {
+        // Make the LedgerServers acting as stores
+        let mut store_uris = Vec::new();
+        let mut stores = Vec::new();
+        for (index, key_image_range) in STORE_KEY_IMAGE_RANGES.iter().enumerate() {
+            let store_uri = FogLedgerUri::from_str(&format!(
+                "insecure-fog-ledger://127.0.0.1:{}",
+                base_port + 1 + index
+            ))
+            .unwrap();
+            let config = LedgerServerConfig {
+                ledger_db: db_full_path.to_path_buf(),
+                watcher_db: watcher_dir.clone(),
+                admin_listen_uri: Default::default(),
+                client_listen_uri: store_uri.clone(),
+                client_responder_id: ResponderId::from_str(&store_uri.addr()).unwrap(),
+                ias_spid: Default::default(),
+                ias_api_key: Default::default(),
+                client_auth_token_secret: None,
+                client_auth_token_max_lifetime: Default::default(),
+                omap_capacity: OMAP_CAPACITY,
+                max_key_image_queries_per_request: Default::default(),
+                max_tx_out_queries_per_request: Default::default(),
+                grpc_max_message_size: None,
+                key_image_range: Some(KeyImageRange::from_str(key_image_range).unwrap()),
+            };
+
+            let enclave = LedgerSgxEnclave::new(
+                get_enclave_path(mc_fog_ledger_enclave::ENCLAVE_FILE),
+                &config.client_responder_id,
+                OMAP_CAPACITY,
+                logger.clone(),
+            );
+
+            let ra_client =
+                AttestClient::new(&config.ias_api_key).expect("Could not create IAS client");
+
+            let mut store = LedgerServer::new(
+                config,
+                enclave,
+                ledger.clone(),
+                watcher.clone(),
+                ra_client,
+                SystemTimeProvider::default(),
+                logger.clone(),
+            );
+            store.start().expect("Failed starting ledger store");
+
+            store_uris.push(store_uri);
+            stores.push(store);
+        }
+
+        // Make the LedgerRouterServer
+        let client_uri = FogLedgerUri::from_str(&format!(
+            "insecure-fog-ledger://127.0.0.1:{}",
+            base_port + 7
+        ))
+        .unwrap();
+        let config = LedgerRouterConfig {
+            client_listen_uri: client_uri.clone(),
+            client_responder_id: ResponderId::from_str(&client_uri.addr()).unwrap(),
+            shard_uris: store_uris,
+            ias_api_key: Default::default(),
+            ias_spid: Default::default(),
+            admin_listen_uri: Default::default(),
+            client_auth_token_secret: None,
+            client_auth_token_max_lifetime: Default::default(),
+            max_key_image_queries_per_request: 2,
+        };
+
+        let enclave = LedgerSgxEnclave::new(
+            get_enclave_path(mc_fog_ledger_enclave::ENCLAVE_FILE),
+            &config.client_responder_id,
+            ROUTER_OMAP_CAPACITY,
+            logger.clone(),
+        );
+
+        let ra_client =
+            AttestClient::new(&config.ias_api_key).expect("Could not create IAS client");
+
+        let mut router = LedgerRouterServer::new(
+            config,
+            enclave,
+            ra_client,
+            SystemTimeProvider::default(),
+            logger.clone(),
+        );
+        router.start();
+
+        let grpc_env = Arc::new(grpcio::EnvBuilder::new().build());
+
+        // The router advertises its own limit, and the client splits its
+        // key image checks by it
+        let mut key_image_client = FogKeyImageGrpcClient::new(
+            client_uri.clone(),
+            GRPC_RETRY_CONFIG,
+            make_verifier(),
+            grpc_env.clone(),
+            logger.clone(),
+        );
+        assert_eq!(key_image_client.max_queries_per_request(), 2);
+
+        let check = [keys[0], keys[1], keys[3], keys[7], keys[19]];
+        let mut response = key_image_client
+            .check_key_images(&check)
+            .expect("check_key_images failed");
+
+        let mut n = 1;
+        // adding a delay to give the fog ledger stores time to fully initialize
+        while response.num_blocks != num_blocks {
+            sleep(Duration::from_secs(10));
+            response = key_image_client
+                .check_key_images(&check)
+                .expect("check_key_images failed");
+
+            // panic on the 20th time
+            n += 1;
+            if n > 20 {
+                panic!("Fog ledger stores not fully initialized");
+            }
+        }
+
+        let expected_spent_at = [Some(1), Some(1), Some(2), Some(3), None];
+        for ((result, key_image), spent_at) in
+            response.results.iter().zip(check).zip(expected_spent_at)
+        {
+            assert_eq!(result.key_image, key_image);
+            assert_eq!(result.status(), Ok(spent_at));
+        }
+
+        // Get merkle proofs through the router
+        let mut merkle_proof_client = FogMerkleProofGrpcClient::new(
+            client_uri,
+            GRPC_RETRY_CONFIG,
+            make_verifier(),
+            grpc_env,
+            logger.clone(),
+        );
+
+        // Get merkle root of num_blocks - 1
+        let merkle_root = {
+            let temp = ledger.get_tx_out_proof_of_memberships(&[0u64]).unwrap();
+            let merkle_proof = &temp[0];
+            compute_implied_merkle_root(merkle_proof).unwrap()
+        };
+
+        // Get some tx outs and merkle proofs, including one out of bounds
+        let response = merkle_proof_client
+            .get_outputs(vec![0u64, 1u64, 2u64, 3u64, 14u64], num_blocks - 1)
+            .expect("get outputs failed");
+
+        // Test the basic fields
+        assert_eq!(response.num_blocks, num_blocks);
+        assert_eq!(response.global_txo_count, ledger.num_txos().unwrap());
+        assert_eq!(response.results.len(), 5);
+
+        // Validate merkle proofs
+        for res in response.results[0..4].iter() {
+            let (tx_out, proof) = res.status().unwrap().unwrap();
+            let result = mc_transaction_core::membership_proofs::is_membership_proof_valid(
+                &tx_out,
+                &proof,
+                merkle_root.hash.as_ref(),
+            )
+            .expect("membership proof structure failed!");
+            assert!(result, "membership proof was invalid! idx = {}", res.index);
+        }
+        assert!(response.results[4].status().as_ref().unwrap().is_none());
+    }
+
+    // grpcio detaches all its threads and does not join them :(
+    // we opened a PR here: https://github.com/tikv/grpc-rs/pull/455
+    // in the meantime we can just sleep after grpcio env and all related
+    // objects have been destroyed, and hope that those 6 threads see the
+    // shutdown requests within 1 second.
+    sleep(Duration::from_millis(1000));
+}
+
+fn add_block_to_ledger(
+    block_version: BlockVersion,
+    ledger_db: &mut LedgerDB,
+    recipients: &[PublicAddress],
+    key_images: &[KeyImage],
+    rng: &mut (impl CryptoRng + RngCore),
+    watcher: &mut WatcherDB,
+) -> u64 {
+    let amount = Amount::new(10, Mob::ID);
+    let block_data = mc_ledger_db::test_utils::add_block_to_ledger(
+        ledger_db,
+        block_version,
+        recipients,
+        amount,
+        key_images,
+        rng,
+    )
+    .expect("failed to add block");
+    let block_index = block_data.block().index;
+
+    let signature = block_data.signature().expect("missing signature");
+    for src_url in watcher.get_config_urls().unwrap().iter() {
+        watcher
+            .add_block_signature(
+                src_url,
+                block_index,
+                signature.clone(),
+                format!("00/{}", block_index),
+            )
+            .expect("Could not add block signature");
+    }
+
+    block_index + 1
+}
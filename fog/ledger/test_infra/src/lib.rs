@@ -3,7 +3,10 @@
 //! Functionality for mocking and testing components in the ledger server
 
 use mc_attest_core::{IasNonce, Quote, QuoteNonce, Report, TargetInfo, VerificationReport};
-use mc_attest_enclave_api::{ClientAuthRequest, ClientAuthResponse, ClientSession, EnclaveMessage};
+use mc_attest_enclave_api::{
+    ClientAuthRequest, ClientAuthResponse, ClientSession, EnclaveMessage, PeerAuthRequest,
+    PeerAuthResponse, PeerSession,
+};
 use mc_blockchain_types::{
     Block, BlockContents, BlockData, BlockIndex, BlockMetadata, BlockSignature,
 };
@@ -84,6 +87,51 @@ impl LedgerEnclave for MockEnclave {
     ) -> Result<(), mc_fog_ledger_enclave::Error> {
         unimplemented!()
     }
+
+    fn peer_init(&self, _peer_id: &ResponderId) -> EnclaveResult<PeerAuthRequest> {
+        unimplemented!()
+    }
+
+    fn peer_accept(&self, _req: PeerAuthRequest) -> EnclaveResult<(PeerAuthResponse, PeerSession)> {
+        unimplemented!()
+    }
+
+    fn peer_connect(
+        &self,
+        _peer_id: &ResponderId,
+        _msg: PeerAuthResponse,
+    ) -> EnclaveResult<(PeerSession, VerificationReport)> {
+        unimplemented!()
+    }
+
+    fn peer_close(&self, _session_id: &PeerSession) -> EnclaveResult<()> {
+        unimplemented!()
+    }
+
+    fn create_shard_key_image_queries(
+        &self,
+        _client_query: EnclaveMessage<ClientSession>,
+        _shard_sessions: Vec<PeerSession>,
+        _max_queries: Option<u64>,
+    ) -> EnclaveResult<Vec<EnclaveMessage<PeerSession>>> {
+        unimplemented!()
+    }
+
+    fn shard_check_key_images(
+        &self,
+        _payload: EnclaveMessage<PeerSession>,
+        _untrusted_keyimagequery_response: UntrustedKeyImageQueryResponse,
+    ) -> EnclaveResult<EnclaveMessage<PeerSession>> {
+        unimplemented!()
+    }
+
+    fn collate_shard_key_image_responses(
+        &self,
+        _client_session: ClientSession,
+        _shard_responses: Vec<EnclaveMessage<PeerSession>>,
+    ) -> EnclaveResult<Vec<u8>> {
+        unimplemented!()
+    }
 }
 
 #[derive(Clone, Default)]
@@ -0,0 +1,296 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Alerts about Fog Ingest cluster conditions that need an operator.
+//!
+//! Alerts are always logged. When webhook urls are configured, each one is
+//! also POSTed to every webhook as a JSON object whose `type` field identifies
+//! the kind of alert. When a PagerDuty routing key is configured, each one
+//! also triggers a PagerDuty event, with the JSON object as its custom details.
+
+use mc_common::logger::{log, Logger};
+use mc_crypto_keys::CompressedRistrettoPublic;
+use mc_fog_types::common::BlockRange;
+use reqwest::blocking::{Client, Response};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+use url::Url;
+
+/// How long to wait for a webhook to accept an alert.
+pub const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An identical alert is not sent again within this long. The conditions are
+/// checked on every polling round, and would otherwise page every few seconds
+/// while they last.
+pub const ALERT_REPEAT_INTERVAL: Duration = Duration::from_secs(600);
+
+/// The PagerDuty Events API v2 endpoint.
+pub const PAGERDUTY_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
+/// A condition of the Fog Ingest cluster worth alerting about.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Alert {
+    /// All the Fog Ingest nodes are idle, while there are outstanding ingress
+    /// keys which blocks must still be scanned with.
+    IdleWithOutstandingKeys {
+        /// The hex-encoded outstanding ingress keys.
+        outstanding_keys: Vec<String>,
+    },
+
+    /// Automatic failover did not manage to activate a node.
+    FailoverFailed {
+        /// The failover error.
+        error: String,
+    },
+
+    /// Block ranges were reported as missed in the recovery db, so users must
+    /// download these blocks to find their TxOuts.
+    MissedBlocks {
+        /// The newly reported missed block ranges.
+        block_ranges: Vec<BlockRange>,
+    },
+}
+
+impl Alert {
+    /// An alert for idle nodes with outstanding ingress keys.
+    pub fn idle_with_outstanding_keys(outstanding_keys: &[CompressedRistrettoPublic]) -> Self {
+        Self::IdleWithOutstandingKeys {
+            outstanding_keys: outstanding_keys
+                .iter()
+                .map(|key| hex::encode(key.as_bytes()))
+                .collect(),
+        }
+    }
+
+    /// An alert for a failed failover.
+    pub fn failover_failed(error: impl Display) -> Self {
+        Self::FailoverFailed {
+            error: error.to_string(),
+        }
+    }
+
+    /// The name of the kind of alert, as in its `type` field.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::IdleWithOutstandingKeys { .. } => "idle_with_outstanding_keys",
+            Self::FailoverFailed { .. } => "failover_failed",
+            Self::MissedBlocks { .. } => "missed_blocks",
+        }
+    }
+
+    /// A one line description of the alert.
+    pub fn summary(&self) -> String {
+        match self {
+            Self::IdleWithOutstandingKeys { outstanding_keys } => format!(
+                "No Fog Ingest node is active, with {} outstanding ingress key(s)",
+                outstanding_keys.len()
+            ),
+            Self::FailoverFailed { error } => {
+                format!("Fog Overseer automatic failover failed: {}", error)
+            }
+            Self::MissedBlocks { block_ranges } => format!(
+                "Fog Ingest missed {} block(s) in {} range(s)",
+                block_ranges
+                    .iter()
+                    .map(|range| range.end_block.saturating_sub(range.start_block))
+                    .sum::<u64>(),
+                block_ranges.len()
+            ),
+        }
+    }
+}
+
+/// A PagerDuty Events API v2 trigger event.
+#[derive(Serialize)]
+struct PagerDutyEvent<'a> {
+    routing_key: &'a str,
+    event_action: &'static str,
+    dedup_key: String,
+    payload: PagerDutyPayload<'a>,
+}
+
+#[derive(Serialize)]
+struct PagerDutyPayload<'a> {
+    summary: String,
+    source: &'a str,
+    severity: &'static str,
+    custom_details: &'a Alert,
+}
+
+/// Delivers alerts to the configured webhooks and PagerDuty. Delivery
+/// happens on a background thread so that a slow endpoint never stalls the
+/// overseer; failures are logged and not retried.
+#[derive(Clone)]
+pub struct AlertNotifier {
+    webhook_urls: Arc<Vec<Url>>,
+    pagerduty_routing_key: Option<Arc<String>>,
+    /// The id of this overseer replica, the source of the PagerDuty events.
+    source: Arc<String>,
+    /// When each alert was last sent.
+    last_sent: Arc<Mutex<HashMap<Alert, Instant>>>,
+    client: Client,
+    logger: Logger,
+}
+
+impl AlertNotifier {
+    /// Create a new notifier POSTing to `webhook_urls`, and to PagerDuty if
+    /// there is a routing key.
+    pub fn new(
+        webhook_urls: Vec<Url>,
+        pagerduty_routing_key: Option<String>,
+        source: String,
+        logger: Logger,
+    ) -> Result<Self, reqwest::Error> {
+        let client = Client::builder().timeout(WEBHOOK_TIMEOUT).build()?;
+        Ok(Self {
+            webhook_urls: Arc::new(webhook_urls),
+            pagerduty_routing_key: pagerduty_routing_key.map(Arc::new),
+            source: Arc::new(source),
+            last_sent: Default::default(),
+            client,
+            logger,
+        })
+    }
+
+    /// Log an alert, and send it to every webhook and PagerDuty, unless it was
+    /// sent less than ALERT_REPEAT_INTERVAL ago.
+    pub fn notify(&self, alert: Alert) {
+        log::error!(self.logger, "Alert: {}", alert.summary());
+
+        if self.webhook_urls.is_empty() && self.pagerduty_routing_key.is_none() {
+            return;
+        }
+
+        {
+            let mut last_sent = self.last_sent.lock().expect("mutex poisoned");
+            let now = Instant::now();
+            last_sent.retain(|_, sent_at| now.duration_since(*sent_at) < ALERT_REPEAT_INTERVAL);
+            if last_sent.contains_key(&alert) {
+                log::debug!(self.logger, "Not repeating alert {:?}", alert);
+                return;
+            }
+            last_sent.insert(alert.clone(), now);
+        }
+
+        let notifier = self.clone();
+        if let Err(err) = thread::Builder::new()
+            .name("OverseerAlert".into())
+            .spawn(move || notifier.deliver(&alert))
+        {
+            log::error!(
+                self.logger,
+                "Failed spawning alert delivery thread: {}",
+                err
+            );
+        }
+    }
+
+    fn deliver(&self, alert: &Alert) {
+        for webhook_url in self.webhook_urls.iter() {
+            self.post(webhook_url.as_str(), alert);
+        }
+
+        if let Some(routing_key) = self.pagerduty_routing_key.as_ref() {
+            let event = PagerDutyEvent {
+                routing_key,
+                event_action: "trigger",
+                dedup_key: format!("fog-overseer/{}", alert.kind()),
+                payload: PagerDutyPayload {
+                    summary: alert.summary(),
+                    source: &self.source,
+                    severity: "critical",
+                    custom_details: alert,
+                },
+            };
+            self.post(PAGERDUTY_EVENTS_URL, &event);
+        }
+    }
+
+    fn post(&self, url: &str, body: &impl Serialize) {
+        match self
+            .client
+            .post(url)
+            .json(body)
+            .send()
+            .and_then(Response::error_for_status)
+        {
+            Ok(_) => log::debug!(self.logger, "Delivered alert to {}", url),
+            Err(err) => log::error!(self.logger, "Failed delivering alert to {}: {}", url, err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn alert_json() {
+        let key = CompressedRistrettoPublic::from(&[1; 32]);
+        assert_eq!(
+            serde_json::to_value(Alert::idle_with_outstanding_keys(&[key])).unwrap(),
+            json!({
+                "type": "idle_with_outstanding_keys",
+                "outstanding_keys": [hex::encode([1; 32])],
+            })
+        );
+
+        let alert = Alert::MissedBlocks {
+            block_ranges: vec![BlockRange::new(10, 15), BlockRange::new(20, 21)],
+        };
+        assert_eq!(
+            serde_json::to_value(&alert).unwrap(),
+            json!({
+                "type": "missed_blocks",
+                "block_ranges": [
+                    { "start_block": 10, "end_block": 15 },
+                    { "start_block": 20, "end_block": 21 },
+                ],
+            })
+        );
+        assert_eq!(
+            alert.summary(),
+            "Fog Ingest missed 6 block(s) in 2 range(s)"
+        );
+    }
+
+    #[test]
+    fn pagerduty_event_json() {
+        let alert = Alert::failover_failed("no idle nodes");
+        let event = PagerDutyEvent {
+            routing_key: "key",
+            event_action: "trigger",
+            dedup_key: format!("fog-overseer/{}", alert.kind()),
+            payload: PagerDutyPayload {
+                summary: alert.summary(),
+                source: "overseer-a",
+                severity: "critical",
+                custom_details: &alert,
+            },
+        };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({
+                "routing_key": "key",
+                "event_action": "trigger",
+                "dedup_key": "fog-overseer/failover_failed",
+                "payload": {
+                    "summary": "Fog Overseer automatic failover failed: no idle nodes",
+                    "source": "overseer-a",
+                    "severity": "critical",
+                    "custom_details": {
+                        "type": "failover_failed",
+                        "error": "no idle nodes",
+                    },
+                },
+            })
+        );
+    }
+}
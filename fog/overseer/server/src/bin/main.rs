@@ -34,8 +34,12 @@ async fn main() -> Result<(), rocket::Error> {
         )
     });
 
-    let mut overseer_service =
-        OverseerService::new(config.ingest_cluster_uris, recovery_db, logger.clone());
+    let mut overseer_service = OverseerService::new(
+        config.ingest_cluster_uris,
+        config.worker_config,
+        recovery_db,
+        logger.clone(),
+    );
     overseer_service
         .start()
         .expect("OverseerService failed to start");
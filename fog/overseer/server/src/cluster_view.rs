@@ -0,0 +1,135 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! The overseer's view of the Fog Ingest cluster as of its latest polling
+//! round, and the action it is about to take on the cluster.
+//!
+//! The worker keeps polling the cluster and planning its next action while it
+//! is disabled, and while it is a standby replica, so that operators can check
+//! what the automation would do before enabling it.
+
+use mc_fog_types::ingest_common::IngestSummary;
+use mc_fog_uri::FogIngestUri;
+use serde::Serialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The overseer's view of the Fog Ingest cluster.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ClusterView {
+    /// Whether this replica is enabled.
+    pub is_enabled: bool,
+
+    /// Whether this replica was the enabled leader at its latest polling
+    /// round, so that it takes the next action.
+    pub is_leader: bool,
+
+    /// When the latest polling round happened, in seconds since the Unix
+    /// epoch. This is None until the first round.
+    pub last_poll_timestamp: Option<u64>,
+
+    /// What the overseer knows about each node, in the order of the ingest
+    /// cluster URIs.
+    pub nodes: Vec<NodeView>,
+
+    /// The action which the polling round takes, or would take if this
+    /// replica were the enabled leader. The leader takes it right after the
+    /// view is updated.
+    pub next_action: Option<PlannedAction>,
+}
+
+/// What the overseer knows about one Fog Ingest node.
+#[derive(Clone, Debug, Serialize)]
+pub struct NodeView {
+    /// The node's URI.
+    pub uri: FogIngestUri,
+
+    /// The ingest summary of the node's latest response, including its mode
+    /// and keys. This is None if it never responded.
+    pub ingest_summary: Option<IngestSummary>,
+
+    /// When the node last responded, in seconds since the Unix epoch.
+    pub last_response_timestamp: Option<u64>,
+
+    /// The error of the latest polling round, if the node did not respond.
+    pub error: Option<String>,
+
+    /// For how many seconds the node has been unresponsive, if it is.
+    pub unresponsive_secs: Option<u64>,
+
+    /// Whether the node has been unresponsive for longer than the unresponsive
+    /// node timeout, so that failover proceeds without it.
+    pub is_failed: bool,
+}
+
+impl NodeView {
+    /// The view of a node which has not been polled yet.
+    pub fn new(uri: FogIngestUri) -> Self {
+        Self {
+            uri,
+            ingest_summary: None,
+            last_response_timestamp: None,
+            error: None,
+            unresponsive_secs: None,
+            is_failed: false,
+        }
+    }
+}
+
+/// An action of the overseer on the Fog Ingest cluster.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PlannedAction {
+    /// A node is unresponsive, but not for long enough to be considered
+    /// failed, so nothing is done until it responds or fails.
+    WaitForUnresponsiveNode {
+        /// The error of the unresponsive node.
+        error: String,
+    },
+
+    /// There is one active node, so nothing is done.
+    NoAction,
+
+    /// There are several active nodes, which is logged as an error.
+    ReportMultipleActiveNodes {
+        /// The URIs of the active nodes.
+        uris: Vec<FogIngestUri>,
+    },
+
+    /// No node is active, and an idle node has the only outstanding ingress
+    /// key, so it is activated.
+    ActivateNode {
+        /// The URI of the node.
+        uri: FogIngestUri,
+        /// The hex-encoded outstanding ingress key.
+        ingress_key: String,
+    },
+
+    /// No node is active, and no idle node has an outstanding ingress key, so
+    /// new keys are set on an idle node, which is activated.
+    SetNewKeysAndActivate {
+        /// The hex-encoded outstanding ingress key which no node has, if any.
+        /// It is reported as lost first.
+        lost_key: Option<String>,
+    },
+
+    /// No node is active, and there are several outstanding ingress keys,
+    /// which needs an operator, so the overseer disables itself.
+    Disable {
+        /// The hex-encoded outstanding ingress keys.
+        outstanding_keys: Vec<String>,
+    },
+
+    /// No node is active, but failover cannot be planned, which is alerted
+    /// about.
+    FailoverFailed {
+        /// The error.
+        error: String,
+    },
+}
+
+/// The current time in seconds since the Unix epoch.
+pub fn now_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
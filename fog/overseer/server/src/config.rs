@@ -6,7 +6,10 @@
 use clap::Parser;
 use mc_fog_sql_recovery_db::SqlRecoveryDbConnectionConfig;
 use mc_fog_uri::FogIngestUri;
+use mc_util_parse::parse_duration_in_seconds;
 use serde::Serialize;
+use std::time::Duration;
+use url::Url;
 
 /// Parser configuration options for an Overseer Server
 #[derive(Clone, Serialize, Parser)]
@@ -29,11 +32,67 @@ pub struct OverseerConfig {
     #[clap(long, use_value_delimiter = true, env = "MC_INGEST_CLUSTER_URIS")]
     pub ingest_cluster_uris: Vec<FogIngestUri>,
 
+    /// Leader election and failover config
+    #[clap(flatten)]
+    pub worker_config: OverseerWorkerConfig,
+
     /// Postgres config
     #[clap(flatten)]
     pub postgres_config: SqlRecoveryDbConnectionConfig,
 }
 
+/// Configuration of leader election between Fog Overseer replicas, and of the
+/// failover they perform.
+#[derive(Clone, Debug, Serialize, Parser)]
+pub struct OverseerWorkerConfig {
+    /// A unique id for this Fog Overseer replica, used to hold the leader
+    /// lease in the recovery db. A random id is used if this is not set.
+    #[clap(long, env = "MC_OVERSEER_ID")]
+    pub overseer_id: Option<String>,
+
+    /// How long the leader lease lasts before another replica can take over,
+    /// unless the leader renews it. The leader renews it every polling
+    /// period, so this should be several times longer than that.
+    #[clap(long, default_value = "60", parse(try_from_str = parse_duration_in_seconds), env = "MC_OVERSEER_LEADER_LEASE_DURATION")]
+    pub leader_lease_duration: Duration,
+
+    /// How long a Fog Ingest node must be unresponsive before it is considered
+    /// failed, and failover proceeds without it.
+    #[clap(long, default_value = "120", parse(try_from_str = parse_duration_in_seconds), env = "MC_OVERSEER_UNRESPONSIVE_NODE_TIMEOUT")]
+    pub unresponsive_node_timeout: Duration,
+
+    /// Webhook URLs to POST a JSON alert to when all nodes are idle with
+    /// outstanding ingress keys, failover fails to activate a node, or blocks
+    /// are reported missed. Alerts are logged regardless.
+    ///
+    /// Webhook URLs often embed a token, so they are not serialized.
+    #[clap(
+        long = "alert-webhook-url",
+        use_value_delimiter = true,
+        env = "MC_OVERSEER_ALERT_WEBHOOK_URL"
+    )]
+    #[serde(skip)]
+    pub alert_webhook_urls: Vec<Url>,
+
+    /// PagerDuty Events API v2 routing key, to trigger a PagerDuty event for
+    /// each alert.
+    #[clap(long, env = "MC_OVERSEER_PAGERDUTY_ROUTING_KEY")]
+    #[serde(skip)]
+    pub pagerduty_routing_key: Option<String>,
+}
+
+impl Default for OverseerWorkerConfig {
+    fn default() -> Self {
+        Self {
+            overseer_id: None,
+            leader_lease_duration: Duration::from_secs(60),
+            unresponsive_node_timeout: Duration::from_secs(120),
+            alert_webhook_urls: Vec::new(),
+            pagerduty_routing_key: None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -55,5 +114,13 @@ mod tests {
 
         assert_eq!(config.ingest_cluster_uris[0].port(), 3226);
         assert_eq!(config.ingest_cluster_uris[1].port(), 3227);
+
+        assert_eq!(config.worker_config.overseer_id, None);
+        assert_eq!(
+            config.worker_config.leader_lease_duration,
+            Duration::from_secs(60)
+        );
+        assert!(config.worker_config.alert_webhook_urls.is_empty());
+        assert_eq!(config.worker_config.pagerduty_routing_key, None);
     }
 }
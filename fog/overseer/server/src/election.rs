@@ -0,0 +1,161 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Leader election between Fog Overseer replicas.
+//!
+//! Replicas compete for a lease in the recovery db. Only the replica holding
+//! the lease performs failover, and it renews the lease every polling period.
+//! If the leader stops, or cannot reach the recovery db, another replica takes
+//! the lease once it expires.
+//!
+//! A leader may stall, e.g. in a slow RPC to an ingest node, for longer than
+//! the lease lasts, and another replica may take over meanwhile. So the leader
+//! checks the lease, with the fencing token it took it with, right before each
+//! action it takes on the cluster, and stops acting as soon as the check
+//! fails.
+
+use crate::{error::OverseerError, metrics::counters};
+use mc_common::logger::{log, Logger};
+use mc_fog_recovery_db_iface::RecoveryDb;
+use std::time::Duration;
+
+/// The name of the lease that Fog Overseer replicas compete for.
+pub const OVERSEER_LEASE_NAME: &str = "fog_overseer";
+
+/// Tracks whether this replica is the leader.
+pub struct LeaderElection<DB: RecoveryDb> {
+    /// The database holding the lease.
+    recovery_db: DB,
+
+    /// The unique id of this replica.
+    overseer_id: String,
+
+    /// How long the lease lasts unless it is renewed.
+    lease_duration: Duration,
+
+    /// The fencing token of the lease, if this replica held it the last time
+    /// it polled.
+    token: Option<u64>,
+
+    logger: Logger,
+}
+
+impl<DB: RecoveryDb> LeaderElection<DB>
+where
+    OverseerError: From<DB::Error>,
+{
+    /// Create a new LeaderElection. The replica is not the leader until its
+    /// first call to `poll`.
+    pub fn new(
+        recovery_db: DB,
+        overseer_id: String,
+        lease_duration: Duration,
+        logger: Logger,
+    ) -> Self {
+        counters::IS_LEADER.set(0);
+        Self {
+            recovery_db,
+            overseer_id,
+            lease_duration,
+            token: None,
+            logger,
+        }
+    }
+
+    /// Take the lease, or renew it if this replica already holds it.
+    ///
+    /// Returns whether this replica is the leader. Failing to reach the
+    /// recovery db counts as losing the lease, since another replica may take
+    /// it once it expires.
+    pub fn poll(&mut self) -> bool {
+        let token = match self.recovery_db.try_acquire_lease(
+            OVERSEER_LEASE_NAME,
+            &self.overseer_id,
+            self.lease_duration,
+        ) {
+            Ok(token) => token,
+            Err(err) => {
+                log::error!(
+                    self.logger,
+                    "Could not renew the leader lease: {}",
+                    OverseerError::from(err)
+                );
+                None
+            }
+        };
+
+        match (self.token, token) {
+            (old_token, Some(token)) if old_token != Some(token) => log::info!(
+                self.logger,
+                "Overseer replica {} is now the leader, with fencing token {}.",
+                self.overseer_id,
+                token
+            ),
+            (Some(_), None) => log::warn!(
+                self.logger,
+                "Overseer replica {} is no longer the leader.",
+                self.overseer_id
+            ),
+            _ => {}
+        }
+
+        self.set_token(token);
+        token.is_some()
+    }
+
+    /// Check that this replica still holds the lease it took at its last
+    /// poll, right before it takes an action on the cluster.
+    ///
+    /// Returns an error if it does not, or if the recovery db could not be
+    /// reached, in which case the action should not be taken.
+    pub fn check(&self) -> Result<(), OverseerError> {
+        let token = self
+            .token
+            .ok_or_else(|| OverseerError::NotLeader(self.overseer_id.clone()))?;
+
+        if self
+            .recovery_db
+            .check_lease(OVERSEER_LEASE_NAME, &self.overseer_id, token)?
+        {
+            Ok(())
+        } else {
+            log::warn!(
+                self.logger,
+                "Overseer replica {} lost the leader lease with fencing token {}.",
+                self.overseer_id,
+                token
+            );
+            Err(OverseerError::NotLeader(self.overseer_id.clone()))
+        }
+    }
+
+    /// Release the lease if this replica holds it, so that another replica can
+    /// take over without waiting for it to expire.
+    pub fn step_down(&mut self) {
+        if self.token.is_none() {
+            return;
+        }
+
+        match self
+            .recovery_db
+            .release_lease(OVERSEER_LEASE_NAME, &self.overseer_id)
+        {
+            Ok(()) => log::info!(
+                self.logger,
+                "Overseer replica {} released the leader lease.",
+                self.overseer_id
+            ),
+            Err(err) => log::warn!(
+                self.logger,
+                "Could not release the leader lease, it will expire instead: {}",
+                OverseerError::from(err)
+            ),
+        }
+
+        self.set_token(None);
+    }
+
+    fn set_token(&mut self, token: Option<u64>) {
+        self.token = token;
+        counters::IS_LEADER.set(token.is_some() as i64);
+    }
+}
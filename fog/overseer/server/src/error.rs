@@ -30,6 +30,9 @@ pub enum OverseerError {
 
     /// There are multiple active Fog Ingest nodes at once: {0}
     MultipleActiveNodes(String),
+
+    /// Fog Overseer replica {0} does not hold the leader lease anymore
+    NotLeader(String),
 }
 
 impl From<SqlRecoveryDbError> for OverseerError {
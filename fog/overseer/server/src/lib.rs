@@ -5,11 +5,14 @@
 #![feature(proc_macro_hygiene, decl_macro)]
 #![deny(missing_docs)]
 
+pub mod alerts;
+pub mod cluster_view;
 pub mod config;
 pub mod metrics;
 pub mod responses;
 pub mod server;
 pub mod service;
 
+mod election;
 mod error;
 mod worker;
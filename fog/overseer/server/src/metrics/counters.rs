@@ -24,4 +24,7 @@ lazy_static::lazy_static! {
 
     /// Number of idle Fog Ingest nodes.
     pub static ref UNRESPONSIVE_NODE_COUNT: IntCounter = OP_COUNTERS.counter("unresponsive_node_count");
+
+    /// Whether this Fog Overseer replica holds the leader lease (1) or not (0).
+    pub static ref IS_LEADER: IntGauge = OP_COUNTERS.gauge("is_leader");
 }
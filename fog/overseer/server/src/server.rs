@@ -6,7 +6,8 @@
 //! HTTP Client -> *Overseer Rocket Server* -> OverseerService -> OverseerWorker
 
 use crate::{
-    error::OverseerError, responses::GetIngestSummariesResponse, service::OverseerService,
+    cluster_view::ClusterView, error::OverseerError, responses::GetIngestSummariesResponse,
+    service::OverseerService,
 };
 use mc_fog_recovery_db_iface::RecoveryDb;
 use mc_fog_sql_recovery_db::SqlRecoveryDb;
@@ -34,6 +35,15 @@ fn get_ingest_summaries(
     state.overseer_service.get_ingest_summaries().map(Json)
 }
 
+/// Tells what the overseer knows about each node, and the action it takes
+/// next.
+#[get("/cluster_view")]
+fn get_cluster_view(
+    state: &rocket::State<OverseerState<SqlRecoveryDb>>,
+) -> Result<Json<ClusterView>, String> {
+    state.overseer_service.get_cluster_view().map(Json)
+}
+
 /// Produces metrics for Prometheus.
 ///
 /// Meant to be called only by the Prometheus pull mechanism.
@@ -66,7 +76,8 @@ pub fn initialize_rocket_server<T: rocket::figment::Provider>(
             disable,
             get_status,
             get_metrics,
-            get_ingest_summaries
+            get_ingest_summaries,
+            get_cluster_view
         ],
     )
 }
@@ -7,7 +7,10 @@
 //!
 //! HTTP Client -> Overseer Rocket Server -> *OverseerService* -> OverseerWorker
 
-use crate::{error::OverseerError, responses::GetIngestSummariesResponse, worker::OverseerWorker};
+use crate::{
+    cluster_view::ClusterView, config::OverseerWorkerConfig, election::OVERSEER_LEASE_NAME,
+    error::OverseerError, responses::GetIngestSummariesResponse, worker::OverseerWorker,
+};
 use mc_common::logger::{log, Logger};
 use mc_fog_ingest_client::FogIngestGrpcClient;
 use mc_fog_recovery_db_iface::RecoveryDb;
@@ -18,7 +21,7 @@ use std::{
     collections::HashMap,
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc,
+        Arc, Mutex,
     },
     time::Duration,
 };
@@ -30,9 +33,12 @@ where
 {
     ingest_clients: Arc<Vec<FogIngestGrpcClient>>,
     logger: Logger,
+    overseer_id: String,
+    worker_config: OverseerWorkerConfig,
     overseer_worker: Option<OverseerWorker>,
     recovery_db: DB,
     is_enabled: Arc<AtomicBool>,
+    cluster_view: Arc<Mutex<ClusterView>>,
 }
 
 impl<DB: RecoveryDb + Clone + Send + Sync + 'static> OverseerService<DB>
@@ -42,8 +48,13 @@ where
     /// Retry failed GRPC requests every 10 seconds.
     const GRPC_RETRY_SECONDS: Duration = Duration::from_millis(10000);
 
-    /// Insantiate the service with the given URIs and DB.
-    pub fn new(ingest_cluster_uris: Vec<FogIngestUri>, recovery_db: DB, logger: Logger) -> Self {
+    /// Insantiate the service with the given URIs, config and DB.
+    pub fn new(
+        ingest_cluster_uris: Vec<FogIngestUri>,
+        worker_config: OverseerWorkerConfig,
+        recovery_db: DB,
+        logger: Logger,
+    ) -> Self {
         let grpcio_env = Arc::new(grpcio::EnvBuilder::new().build());
         let ingest_clients: Vec<FogIngestGrpcClient> = ingest_cluster_uris
             .iter()
@@ -56,12 +67,20 @@ where
                 )
             })
             .collect();
+        let overseer_id = worker_config
+            .overseer_id
+            .clone()
+            .unwrap_or_else(|| format!("fog-overseer-{:016x}", rand::random::<u64>()));
+        log::info!(logger, "Overseer replica id: {}", overseer_id);
         Self {
             ingest_clients: Arc::new(ingest_clients),
             logger,
+            overseer_id,
+            worker_config,
             overseer_worker: None,
             recovery_db,
             is_enabled: Arc::new(AtomicBool::new(false)),
+            cluster_view: Default::default(),
         }
     }
 
@@ -82,8 +101,11 @@ where
         self.overseer_worker = Some(OverseerWorker::new(
             self.ingest_clients.clone(),
             self.recovery_db.clone(),
+            self.overseer_id.clone(),
+            self.worker_config.clone(),
             self.logger.clone(),
             self.is_enabled.clone(),
+            self.cluster_view.clone(),
         ));
 
         Ok(())
@@ -121,7 +143,7 @@ where
         Ok(response_message.to_string())
     }
 
-    /// Get the status for Overseer.
+    /// Get the status for Overseer, and which replica is the leader.
     pub fn get_status(&self) -> Result<String, String> {
         let is_enabled: bool = self.is_enabled.load(Ordering::SeqCst);
         let response_message = match is_enabled {
@@ -129,7 +151,25 @@ where
             false => "Fog Overseer is disabled.",
         };
 
-        Ok(response_message.to_string())
+        let leader = self
+            .recovery_db
+            .get_lease_holder(OVERSEER_LEASE_NAME)
+            .map_err(|err| format!("Could not get the leader: {}", OverseerError::from(err)))?;
+        let leader_message = match leader {
+            Some(leader) if leader == self.overseer_id => {
+                format!("This replica ({}) is the leader.", self.overseer_id)
+            }
+            Some(leader) => format!(
+                "This replica ({}) is a standby, the leader is {}.",
+                self.overseer_id, leader
+            ),
+            None => format!(
+                "This replica ({}) is a standby, there is no leader.",
+                self.overseer_id
+            ),
+        };
+
+        Ok(format!("{} {}", response_message, leader_message))
     }
 
     /// Get metrics to publish.
@@ -144,6 +184,19 @@ where
             .map_err(|err| format!("Get prometheus metrics from_utf8 failed: {}", err))
     }
 
+    /// Get the overseer's view of the cluster as of its latest polling round,
+    /// including the action it takes next.
+    pub fn get_cluster_view(&self) -> Result<ClusterView, String> {
+        let mut cluster_view = self
+            .cluster_view
+            .lock()
+            .map_err(|_| "The cluster view mutex is poisoned".to_string())?
+            .clone();
+        cluster_view.is_enabled = self.is_enabled.load(Ordering::SeqCst);
+
+        Ok(cluster_view)
+    }
+
     /// Try and fetch summaries from all ingest clients.
     pub fn get_ingest_summaries(&self) -> Result<GetIngestSummariesResponse, String> {
         let ingest_summaries: HashMap<FogIngestUri, Result<IngestSummary, String>> = self
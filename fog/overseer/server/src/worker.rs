@@ -7,23 +7,30 @@
 //!
 //! HTTP Client -> Overseer Rocket Server -> OverseerService -> *OverseerWorker*
 
-use crate::{error::OverseerError, metrics};
-use mc_api::external;
+use crate::{
+    alerts::{Alert, AlertNotifier},
+    cluster_view::{now_timestamp, ClusterView, NodeView, PlannedAction},
+    config::OverseerWorkerConfig,
+    election::LeaderElection,
+    error::OverseerError,
+    metrics,
+};
 use mc_common::logger::{log, Logger};
 use mc_crypto_keys::CompressedRistrettoPublic;
 use mc_fog_api::ingest_common::{IngestControllerMode, IngestSummary};
 use mc_fog_ingest_client::FogIngestGrpcClient;
 use mc_fog_recovery_db_iface::{IngressPublicKeyRecord, IngressPublicKeyRecordFilters, RecoveryDb};
+use mc_fog_types::{common::BlockRange, ingest_common::IngestSummary as NativeIngestSummary};
 use mc_fog_uri::FogIngestUri;
 use retry::{delay::Fixed, retry_with_index, OperationResult};
 use std::{
-    collections::HashSet,
+    collections::HashMap,
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc,
+        Arc, Mutex,
     },
     thread::{sleep, Builder as ThreadBuilder, JoinHandle},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 /// Wraps a thread that is responsible for overseeing the active Fog Ingest
@@ -33,6 +40,10 @@ use std::{
 /// there is no active key, then it promotes an idle node to active, and in the
 /// case where none of the idle nodes contain the previously active ingress key,
 /// it reports that key as lost.
+///
+/// When several Fog Overseer replicas oversee the same cluster, only the one
+/// holding the leader lease in the recovery db acts on it, and alerts about
+/// it.
 pub struct OverseerWorker {
     /// Join handle used to wait for the thread to terminate.
     join_handle: Option<JoinHandle<()>>,
@@ -45,12 +56,22 @@ impl OverseerWorker {
     pub fn new<DB: RecoveryDb + Clone + Send + Sync + 'static>(
         ingest_clients: Arc<Vec<FogIngestGrpcClient>>,
         recovery_db: DB,
+        overseer_id: String,
+        config: OverseerWorkerConfig,
         logger: Logger,
         is_enabled: Arc<AtomicBool>,
+        cluster_view: Arc<Mutex<ClusterView>>,
     ) -> Self
     where
         OverseerError: From<DB::Error>,
     {
+        let alert_source = overseer_id.clone();
+        let leader_election = LeaderElection::new(
+            recovery_db.clone(),
+            overseer_id,
+            config.leader_lease_duration,
+            logger.clone(),
+        );
         let thread_is_enabled = is_enabled;
         let stop_requested = Arc::new(AtomicBool::new(false));
         let thread_stop_requested = stop_requested.clone();
@@ -58,12 +79,25 @@ impl OverseerWorker {
             ThreadBuilder::new()
                 .name("OverseerWorker".to_string())
                 .spawn(move || {
+                    // The blocking http client must not be created or dropped
+                    // on an async runtime, so it lives on this thread.
+                    let alert_notifier = AlertNotifier::new(
+                        config.alert_webhook_urls,
+                        config.pagerduty_routing_key,
+                        alert_source,
+                        logger.clone(),
+                    )
+                    .expect("Failed creating alert notifier");
                     OverseerWorkerThread::start(
                         ingest_clients,
                         recovery_db,
+                        leader_election,
+                        config.unresponsive_node_timeout,
                         thread_is_enabled,
                         thread_stop_requested,
-                        HashSet::new(),
+                        HashMap::new(),
+                        alert_notifier,
+                        cluster_view,
                         logger,
                     )
                 })
@@ -103,16 +137,38 @@ struct OverseerWorkerThread<DB: RecoveryDb> {
     /// cluster's ingress keys.
     recovery_db: DB,
 
+    /// Decides whether this replica is the one that acts on the cluster.
+    leader_election: LeaderElection<DB>,
+
+    /// How long a node must be unresponsive before failover proceeds without
+    /// it.
+    unresponsive_node_timeout: Duration,
+
     /// If this is true, the worker will not perform it's monitoring logic.
     is_enabled: Arc<AtomicBool>,
 
     /// If this is true, the thread will stop.
     stop_requested: Arc<AtomicBool>,
 
-    /// If a node doesn't respond to a status request, it goes here.
+    /// If a node doesn't respond to a status request, it goes here, along with
+    /// the time since which it has been unresponsive.
     ///
-    /// This helps us debug when a node starts responding again.
-    unresponsive_node_urls: HashSet<FogIngestUri>,
+    /// This helps us debug when a node starts responding again, and tells
+    /// when a node is considered failed.
+    unresponsive_node_urls: HashMap<FogIngestUri, Instant>,
+
+    /// Sends alerts about conditions that need an operator.
+    alert_notifier: AlertNotifier,
+
+    /// The number of missed block ranges in the recovery db at the last
+    /// polling round, so that only newly missed blocks are alerted about.
+    missed_block_range_count: Option<usize>,
+
+    /// What is known about each node, by node index.
+    node_views: Vec<NodeView>,
+
+    /// The view of the cluster which is published to the OverseerService.
+    cluster_view: Arc<Mutex<ClusterView>>,
 
     logger: Logger,
 }
@@ -125,6 +181,40 @@ struct IngestSummaryNodeMapping {
     ingest_summary: IngestSummary,
 }
 
+/// The action to take on the Fog Ingest cluster, as planned from a polling
+/// round.
+enum Action {
+    /// There is one active node.
+    None,
+
+    /// There are several active nodes, by node index.
+    ReportMultipleActiveNodes(Vec<usize>),
+
+    /// No node is active, so failover is performed.
+    Failover {
+        /// The outstanding ingress keys which no active node scans with.
+        inactive_outstanding_keys: Vec<CompressedRistrettoPublic>,
+        /// What failover does.
+        plan: FailoverPlan,
+    },
+}
+
+/// How automatic failover activates a node.
+enum FailoverPlan {
+    /// Activate the idle node, by node index, which has the only outstanding
+    /// key.
+    ActivateNode(usize),
+
+    /// Report the outstanding key which no node has as lost, if there is one,
+    /// then set new keys on an idle node and activate it.
+    SetNewKeys {
+        lost_key: Option<CompressedRistrettoPublic>,
+    },
+
+    /// Disable overseer, because there are several outstanding keys.
+    Disable,
+}
+
 impl<DB: RecoveryDb> OverseerWorkerThread<DB>
 where
     OverseerError: From<DB::Error>,
@@ -137,20 +227,35 @@ where
     const NUMBER_OF_TRIES: usize = 3;
 
     /// Start this worker.
+    #[allow(clippy::too_many_arguments)]
     pub fn start(
         ingest_clients: Arc<Vec<FogIngestGrpcClient>>,
         recovery_db: DB,
+        leader_election: LeaderElection<DB>,
+        unresponsive_node_timeout: Duration,
         is_enabled: Arc<AtomicBool>,
         stop_requested: Arc<AtomicBool>,
-        unresponsive_node_urls: HashSet<FogIngestUri>,
+        unresponsive_node_urls: HashMap<FogIngestUri, Instant>,
+        alert_notifier: AlertNotifier,
+        cluster_view: Arc<Mutex<ClusterView>>,
         logger: Logger,
     ) {
+        let node_views = ingest_clients
+            .iter()
+            .map(|ingest_client| NodeView::new(ingest_client.get_uri().clone()))
+            .collect();
         let thread = Self {
             ingest_clients,
             recovery_db,
+            leader_election,
+            unresponsive_node_timeout,
             is_enabled,
             stop_requested,
             unresponsive_node_urls,
+            alert_notifier,
+            missed_block_range_count: None,
+            node_views,
+            cluster_view,
             logger,
         };
         thread.run();
@@ -163,12 +268,17 @@ where
 
             if self.stop_requested.load(Ordering::SeqCst) {
                 log::info!(self.logger, "Overseer worker thread stopping.");
+                self.leader_election.step_down();
                 break;
             }
 
-            if !self.is_enabled.load(Ordering::SeqCst) {
+            // A disabled replica keeps polling the cluster and planning, so that
+            // its cluster view tells what it would do once enabled.
+            let is_enabled = self.is_enabled.load(Ordering::SeqCst);
+            if !is_enabled {
                 log::trace!(self.logger, "Overseer worker is currently disabled.");
-                continue;
+                // Let an enabled replica take over.
+                self.leader_election.step_down();
             }
 
             let ingest_summary_node_mappings: Vec<IngestSummaryNodeMapping> = match self
@@ -178,6 +288,12 @@ where
                 Err(err) => {
                     log::error!(self.logger, "Encountered an error while retrieving ingest summaries: {}. Returning to beginning of overseer logic.", err);
                     metrics::increment_unresponsive_node_count(&self.logger);
+                    self.publish_cluster_view(
+                        false,
+                        PlannedAction::WaitForUnresponsiveNode {
+                            error: err.to_string(),
+                        },
+                    );
                     continue;
                 }
             };
@@ -188,108 +304,291 @@ where
                 .collect();
             metrics::set_metrics(&self.logger, ingest_summaries.as_slice());
 
-            let active_ingest_summary_node_mappings: Vec<&IngestSummaryNodeMapping> =
-                ingest_summary_node_mappings
+            // Every replica keeps track of the missed blocks, so that a new
+            // leader does not alert about blocks that were missed before it
+            // took over.
+            let new_missed_block_ranges = self.poll_missed_block_ranges();
+
+            let action = self.plan_action(&ingest_summary_node_mappings);
+
+            // Check the lease right before acting on the cluster, so that a replica
+            // that lost it, e.g. because it stalled, does not act concurrently with
+            // the new leader.
+            let is_leader = is_enabled && self.leader_election.poll();
+            self.publish_cluster_view(is_leader, self.planned_action(&action));
+            if !is_leader {
+                log::trace!(
+                    self.logger,
+                    "Overseer replica is not the enabled leader, not acting on the cluster."
+                );
+                continue;
+            }
+
+            if !new_missed_block_ranges.is_empty() {
+                self.alert_notifier.notify(Alert::MissedBlocks {
+                    block_ranges: new_missed_block_ranges,
+                });
+            }
+
+            self.take_action(action, ingest_summary_node_mappings);
+        }
+    }
+
+    /// Plans the action to take on the cluster, from the latest round of
+    /// ingest summaries.
+    fn plan_action(
+        &self,
+        ingest_summary_node_mappings: &[IngestSummaryNodeMapping],
+    ) -> Result<Action, OverseerError> {
+        let active_node_indices: Vec<usize> = ingest_summary_node_mappings
+            .iter()
+            .filter(|ingest_summary_node_mapping| {
+                ingest_summary_node_mapping.ingest_summary.mode == IngestControllerMode::Active
+            })
+            .map(|ingest_summary_node_mapping| ingest_summary_node_mapping.node_index)
+            .collect();
+
+        match active_node_indices.len() {
+            0 => {
+                let inactive_outstanding_keys = self.get_inactive_outstanding_keys()?;
+                let plan = self.plan_automatic_failover(
+                    &inactive_outstanding_keys,
+                    ingest_summary_node_mappings,
+                );
+                Ok(Action::Failover {
+                    inactive_outstanding_keys,
+                    plan,
+                })
+            }
+            1 => Ok(Action::None),
+            _ => Ok(Action::ReportMultipleActiveNodes(active_node_indices)),
+        }
+    }
+
+    /// Takes the planned action on the cluster.
+    fn take_action(
+        &self,
+        action: Result<Action, OverseerError>,
+        ingest_summary_node_mappings: Vec<IngestSummaryNodeMapping>,
+    ) {
+        match action {
+            Ok(Action::None) => {
+                log::trace!(
+                    self.logger,
+                    "There is one active node in the Fog Ingest cluster."
+                );
+            }
+            Ok(Action::ReportMultipleActiveNodes(active_node_indices)) => {
+                let active_node_ingress_pubkeys: Vec<_> = ingest_summary_node_mappings
                     .iter()
                     .filter(|ingest_summary_node_mapping| {
-                        ingest_summary_node_mapping.ingest_summary.mode
-                            == IngestControllerMode::Active
+                        active_node_indices.contains(&ingest_summary_node_mapping.node_index)
+                    })
+                    .map(|ingest_summary_node_mapping| {
+                        ingest_summary_node_mapping
+                            .ingest_summary
+                            .get_ingress_pubkey()
                     })
                     .collect();
+                let error_message =
+                    format!("Active ingress keys: {:?}", active_node_ingress_pubkeys);
+                let error = OverseerError::MultipleActiveNodes(error_message);
+                log::error!(self.logger, "{}", error);
+            }
+            Ok(Action::Failover {
+                inactive_outstanding_keys,
+                plan,
+            }) => {
+                log::warn!(
+                    self.logger,
+                    "There are currently no active nodes in the Fog Ingest cluster. Initiating automatic failover.",
+                );
+                match self.perform_automatic_failover(
+                    &inactive_outstanding_keys,
+                    plan,
+                    &ingest_summary_node_mappings,
+                ) {
+                    Ok(_) => {
+                        log::info!(self.logger, "Automatic failover completed successfully.")
+                    }
+                    // The new leader takes over failover.
+                    Err(err @ OverseerError::NotLeader(_)) => {
+                        log::warn!(self.logger, "Automatic failover stopped: {}", err);
+                    }
+                    Err(err) => {
+                        log::error!(self.logger, "Automatic failover failed: {}", err);
+                        self.alert_notifier.notify(Alert::failover_failed(err));
+                    }
+                };
+            }
+            Err(err) => {
+                log::error!(self.logger, "Automatic failover failed: {}", err);
+                self.alert_notifier.notify(Alert::failover_failed(err));
+            }
+        }
+    }
 
-            let active_node_count = active_ingest_summary_node_mappings.len();
-            match active_node_count {
-                0 => {
-                    log::warn!(
-                        self.logger,
-                        "There are currently no active nodes in the Fog Ingest cluster. Initiating automatic failover.",
-                    );
-                    match self.perform_automatic_failover(ingest_summary_node_mappings) {
-                        Ok(_) => {
-                            log::info!(self.logger, "Automatic failover completed successfully.")
-                        }
-                        Err(err) => {
-                            log::error!(self.logger, "Automatic failover failed: {}", err)
-                        }
-                    };
-                }
-                1 => {
-                    log::trace!(
-                        self.logger,
-                        "There is one active node in the Fog Ingest cluster. Active ingress key: {:?}",
-                        active_ingest_summary_node_mappings[0]
-                        .ingest_summary
-                        .get_ingress_pubkey()
-                    );
-                    continue;
-                }
-                _ => {
-                    let active_node_ingress_pubkeys: Vec<&external::CompressedRistretto> =
-                        active_ingest_summary_node_mappings
-                            .iter()
-                            .map(|active_ingest_summary_node_mapping| {
-                                active_ingest_summary_node_mapping
-                                    .ingest_summary
-                                    .get_ingress_pubkey()
-                            })
-                            .collect();
-                    let error_message =
-                        format!("Active ingress keys: {:?}", active_node_ingress_pubkeys);
-                    let error = OverseerError::MultipleActiveNodes(error_message);
-                    log::error!(self.logger, "{}", error);
+    /// Describes a planned action for the cluster view.
+    fn planned_action(&self, action: &Result<Action, OverseerError>) -> PlannedAction {
+        let uri = |node_index: usize| self.ingest_clients[node_index].get_uri().clone();
+        let hex_key = |key: &CompressedRistrettoPublic| hex::encode(key.as_bytes());
+        match action {
+            Ok(Action::None) => PlannedAction::NoAction,
+            Ok(Action::ReportMultipleActiveNodes(active_node_indices)) => {
+                PlannedAction::ReportMultipleActiveNodes {
+                    uris: active_node_indices.iter().copied().map(uri).collect(),
                 }
             }
+            Ok(Action::Failover {
+                inactive_outstanding_keys,
+                plan,
+            }) => match plan {
+                FailoverPlan::ActivateNode(node_index) => PlannedAction::ActivateNode {
+                    uri: uri(*node_index),
+                    ingress_key: hex_key(&inactive_outstanding_keys[0]),
+                },
+                FailoverPlan::SetNewKeys { lost_key } => PlannedAction::SetNewKeysAndActivate {
+                    lost_key: lost_key.as_ref().map(hex_key),
+                },
+                FailoverPlan::Disable => PlannedAction::Disable {
+                    outstanding_keys: inactive_outstanding_keys.iter().map(hex_key).collect(),
+                },
+            },
+            Err(err) => PlannedAction::FailoverFailed {
+                error: err.to_string(),
+            },
         }
     }
 
+    /// Publishes the latest polling round to the cluster view.
+    fn publish_cluster_view(&self, is_leader: bool, next_action: PlannedAction) {
+        let mut cluster_view = self
+            .cluster_view
+            .lock()
+            .expect("cluster view mutex poisoned");
+        cluster_view.is_leader = is_leader;
+        cluster_view.last_poll_timestamp = Some(now_timestamp());
+        cluster_view.nodes = self.node_views.clone();
+        cluster_view.next_action = Some(next_action);
+    }
+
     /// Returns the latest round of ingest summaries for each
-    /// FogIngestGrpcClient that communicates with a node that is online.
+    /// FogIngestGrpcClient that communicates with a node that is online, and
+    /// updates the node views.
+    ///
+    /// Nodes that have been unresponsive for longer than the unresponsive node
+    /// timeout are considered failed, and are left out. It is an error if any
+    /// other node is unresponsive.
     fn retrieve_ingest_summary_node_mappings(
         &mut self,
     ) -> Result<Vec<IngestSummaryNodeMapping>, OverseerError> {
         let logger = &self.logger;
         let unresponsive_node_urls = &mut self.unresponsive_node_urls;
-        self.ingest_clients
-            .iter()
-            .enumerate()
-            .map(|(node_index, ingest_client)| {
-                let uri = ingest_client.get_uri();
-                match ingest_client.get_status() {
-                    Ok(ingest_summary) => {
-                        log::trace!(
-                            logger,
-                            "Ingest summary retrieved from '{}': {:?}",
-                            uri,
-                            ingest_summary
-                        );
-                        if unresponsive_node_urls.remove(uri) {
-                            log::info!(
+        let node_views = &mut self.node_views;
+        let mut ingest_summary_node_mappings = Vec::new();
+        let mut failed_node_urls = Vec::new();
+        let mut unresponsive_node_error = None;
+        // Every node is polled, even after one is unresponsive, so that the
+        // node views are all up to date.
+        for (node_index, ingest_client) in self.ingest_clients.iter().enumerate() {
+            let uri = ingest_client.get_uri();
+            let node_view = &mut node_views[node_index];
+            match ingest_client.get_status() {
+                Ok(ingest_summary) => {
+                    log::trace!(
+                        logger,
+                        "Ingest summary retrieved from '{}': {:?}",
+                        uri,
+                        ingest_summary
+                    );
+                    if unresponsive_node_urls.remove(uri).is_some() {
+                        log::info!(
                             logger,
                             "Node {} was previously unresponsive, but just successfully responded!",
                             uri,
                         );
+                    }
+                    node_view.last_response_timestamp = Some(now_timestamp());
+                    node_view.unresponsive_secs = None;
+                    node_view.is_failed = false;
+                    match NativeIngestSummary::try_from(&ingest_summary) {
+                        Ok(native_ingest_summary) => {
+                            node_view.ingest_summary = Some(native_ingest_summary);
+                            node_view.error = None;
+                        }
+                        Err(err) => {
+                            node_view.error =
+                                Some(format!("Could not parse ingest summary: {}", err));
                         }
-                        Ok(IngestSummaryNodeMapping {
-                            node_index,
-                            ingest_summary,
-                        })
                     }
+                    ingest_summary_node_mappings.push(IngestSummaryNodeMapping {
+                        node_index,
+                        ingest_summary,
+                    });
+                }
 
-                    Err(err) => {
-                        let error_message = format!(
-                            "Unable to retrieve ingest summary for node ({}): {}",
-                            uri, err
-                        );
-                        log::trace!(logger, "{}", error_message);
-                        unresponsive_node_urls.insert(uri.clone());
-                        Err(OverseerError::UnresponsiveNodeError(error_message))
+                Err(err) => {
+                    let error_message = format!(
+                        "Unable to retrieve ingest summary for node ({}): {}",
+                        uri, err
+                    );
+                    log::trace!(logger, "{}", error_message);
+                    let unresponsive_since = *unresponsive_node_urls
+                        .entry(uri.clone())
+                        .or_insert_with(Instant::now);
+                    let is_failed = unresponsive_since.elapsed() >= self.unresponsive_node_timeout;
+                    node_view.error = Some(err.to_string());
+                    node_view.unresponsive_secs = Some(unresponsive_since.elapsed().as_secs());
+                    node_view.is_failed = is_failed;
+                    if is_failed {
+                        failed_node_urls.push(uri.clone());
+                    } else {
+                        unresponsive_node_error.get_or_insert(error_message);
                     }
                 }
-            })
-            .collect()
+            }
+        }
+
+        if let Some(error_message) = unresponsive_node_error {
+            return Err(OverseerError::UnresponsiveNodeError(error_message));
+        }
+
+        if !failed_node_urls.is_empty() {
+            log::warn!(
+                logger,
+                "Nodes unresponsive for more than {:?} are considered failed: {:?}",
+                self.unresponsive_node_timeout,
+                failed_node_urls
+            );
+        }
+
+        Ok(ingest_summary_node_mappings)
+    }
+
+    /// Returns the missed block ranges which were reported in the recovery db
+    /// since the last polling round. Nothing is returned on the first round,
+    /// or if the recovery db cannot be read.
+    fn poll_missed_block_ranges(&mut self) -> Vec<BlockRange> {
+        let missed_block_ranges = match self.recovery_db.get_missed_block_ranges() {
+            Ok(missed_block_ranges) => missed_block_ranges,
+            Err(err) => {
+                log::warn!(self.logger, "Could not get missed block ranges: {}", err);
+                return Vec::new();
+            }
+        };
+
+        let previous_count = self
+            .missed_block_range_count
+            .replace(missed_block_ranges.len());
+        match previous_count {
+            Some(previous_count) if previous_count < missed_block_ranges.len() => {
+                missed_block_ranges[previous_count..].to_vec()
+            }
+            _ => Vec::new(),
+        }
     }
 
-    /// Performs automatic failover, which means that we try to activate nodes
+    /// Plans automatic failover, which means that we try to activate nodes
     /// for an outstanding ingress key, if it exists.
     ///
     /// The logic is as follows:
@@ -312,31 +611,96 @@ where
     ///                      new keys on an idle node, and activate that node.
     ///        c) > 1 outstanding key:
     ///             (i) Disable
-    ///             (ii) TODO: Send an alert.
-    fn perform_automatic_failover(
+    ///             (ii) Return an error, which is alerted about.
+    fn plan_automatic_failover(
         &self,
-        ingest_summary_node_mappings: Vec<IngestSummaryNodeMapping>,
-    ) -> Result<(), OverseerError> {
-        let inactive_outstanding_keys: Vec<CompressedRistrettoPublic> =
-            self.get_inactive_outstanding_keys()?;
-
+        inactive_outstanding_keys: &[CompressedRistrettoPublic],
+        ingest_summary_node_mappings: &[IngestSummaryNodeMapping],
+    ) -> FailoverPlan {
         match inactive_outstanding_keys.len() {
-            0 => {
-                log::info!(self.logger, "Found 0 outstanding keys.");
-                let activated_node_index = self.set_new_key_on_a_node()?;
-                self.activate_a_node(activated_node_index)?;
-                Ok(())
-            }
+            0 => FailoverPlan::SetNewKeys { lost_key: None },
             1 => {
-                log::info!(self.logger, "Found 1 outstanding key.");
                 let inactive_outstanding_key = inactive_outstanding_keys[0];
-                self.handle_one_inactive_outstanding_key(
-                    inactive_outstanding_key,
+                match Self::find_node_with_ingress_key(
+                    &inactive_outstanding_key,
                     ingest_summary_node_mappings,
-                )?;
+                ) {
+                    Some(node_index) => FailoverPlan::ActivateNode(node_index),
+                    None => FailoverPlan::SetNewKeys {
+                        lost_key: Some(inactive_outstanding_key),
+                    },
+                }
+            }
+            _ => FailoverPlan::Disable,
+        }
+    }
+
+    /// Performs automatic failover as planned.
+    ///
+    /// If there are outstanding keys, an alert is sent before failover starts.
+    fn perform_automatic_failover(
+        &self,
+        inactive_outstanding_keys: &[CompressedRistrettoPublic],
+        plan: FailoverPlan,
+        ingest_summary_node_mappings: &[IngestSummaryNodeMapping],
+    ) -> Result<(), OverseerError> {
+        if !inactive_outstanding_keys.is_empty() {
+            self.alert_notifier
+                .notify(Alert::idle_with_outstanding_keys(inactive_outstanding_keys));
+        }
+        log::info!(
+            self.logger,
+            "Found {} outstanding key(s).",
+            inactive_outstanding_keys.len()
+        );
+
+        match plan {
+            FailoverPlan::ActivateNode(node_index) => {
+                let node = &self.ingest_clients[node_index];
+                log::info!(
+                    self.logger,
+                    "Trying to activate node {} with inactive outstanding key: {:?}",
+                    node.get_uri(),
+                    &inactive_outstanding_keys[0]
+                );
+                self.leader_election.check()?;
+                match node.activate() {
+                    Ok(_) => {
+                        log::info!(
+                            self.logger,
+                            "Successfully activated node {}.",
+                            node.get_uri()
+                        );
+                        Ok(())
+                    }
+                    Err(err) => {
+                        let error_message = format!(
+                            "Tried activating node {}, but it failed: {}.",
+                            node.get_uri(),
+                            err
+                        );
+                        Err(OverseerError::ActivateNode(error_message))
+                    }
+                }
+            }
+            FailoverPlan::SetNewKeys { lost_key } => {
+                // None of the Fog Ingest nodes' keys matches the inactive
+                // outstanding key, if there is one. We must report it as lost,
+                // set new keys on an idle node, and activate that node.
+                if let Some(lost_key) = lost_key {
+                    log::warn!(
+                        self.logger,
+                        "Could not find a node that has the inactive outstanding key: {:?}",
+                        &lost_key
+                    );
+                    self.report_lost_ingress_key(lost_key)?;
+                }
+                let activated_node_index =
+                    self.set_new_key_on_a_node(ingest_summary_node_mappings)?;
+                self.activate_a_node(activated_node_index)?;
                 Ok(())
             }
-            _ => {
+            FailoverPlan::Disable => {
                 self.is_enabled.store(false, Ordering::SeqCst);
                 let error_message = format!("This is unexpected and requires manual intervention. As such, we've disabled overseer. Take the appropriate action and then re-enable overseer by calling the /enable endpoint. Inactive oustanding keys: {:?}", inactive_outstanding_keys);
                 Err(OverseerError::MultipleInactiveOutstandingKeys(
@@ -382,69 +746,22 @@ where
             .collect())
     }
 
-    /// Performs the following logic when one inactive outstanding key is found:
-    ///   1) Tries to find an idle node that contains that key.
-    ///      (i)  If it's found, it activates the node that contains it. If
-    ///           activation is unsuccessful, then it returns an error.
-    ///      (ii) If no idle node is found that contains the key, then it
-    ///           reports that key as lost, sets new keys on an idle node, and
-    ///           activates that node.
-    fn handle_one_inactive_outstanding_key(
-        &self,
-        inactive_outstanding_key: CompressedRistrettoPublic,
-        ingest_summary_node_mappings: Vec<IngestSummaryNodeMapping>,
-    ) -> Result<(), OverseerError> {
-        log::info!(
-            self.logger,
-            "Trying to activate an idle node with inactive outstanding key: {:?}",
-            &inactive_outstanding_key
-        );
-        for ingest_summary_node_mapping in &ingest_summary_node_mappings {
-            let node_ingress_key = match CompressedRistrettoPublic::try_from(
-                ingest_summary_node_mapping
-                    .ingest_summary
-                    .get_ingress_pubkey(),
-            ) {
-                Ok(key) => key,
-                Err(_) => continue,
-            };
-            if inactive_outstanding_key.eq(&node_ingress_key) {
-                let node = &self.ingest_clients[ingest_summary_node_mapping.node_index];
-                match node.activate() {
-                    Ok(_) => {
-                        log::info!(
-                            self.logger,
-                            "Successfully activated node {}.",
-                            node.get_uri()
-                        );
-                        return Ok(());
-                    }
-                    Err(err) => {
-                        let error_message = format!(
-                            "Tried activating node {}, but it failed: {}.",
-                            node.get_uri(),
-                            err
-                        );
-                        return Err(OverseerError::ActivateNode(error_message));
-                    }
-                }
-            }
-        }
-
-        // We've gone through all the Fog Ingest nodes' keys,
-        // and none of them matches the inactive outstanding key. We must
-        // report the inactive outstanding key as lost, set new keys
-        // on an idle node, and activate that node.
-        log::warn!(
-            self.logger,
-            "Could not find a node that has the inactive outstanding key: {:?}",
-            &inactive_outstanding_key
-        );
-        self.report_lost_ingress_key(inactive_outstanding_key)?;
-        let activated_node_index = self.set_new_key_on_a_node()?;
-        self.activate_a_node(activated_node_index)?;
-
-        Ok(())
+    /// Returns the index of a node which has the given ingress key.
+    fn find_node_with_ingress_key(
+        ingress_key: &CompressedRistrettoPublic,
+        ingest_summary_node_mappings: &[IngestSummaryNodeMapping],
+    ) -> Option<usize> {
+        ingest_summary_node_mappings
+            .iter()
+            .find(|ingest_summary_node_mapping| {
+                CompressedRistrettoPublic::try_from(
+                    ingest_summary_node_mapping
+                        .ingest_summary
+                        .get_ingress_pubkey(),
+                )
+                .map_or(false, |node_ingress_key| node_ingress_key.eq(ingress_key))
+            })
+            .map(|ingest_summary_node_mapping| ingest_summary_node_mapping.node_index)
     }
 
     /// Tries to report a lost ingress key.
@@ -454,10 +771,11 @@ where
     ) -> Result<(), OverseerError> {
         let result = retry_with_index(
             Fixed::from_millis(200).take(Self::NUMBER_OF_TRIES),
-            |current_try| match self
-                .recovery_db
-                .report_lost_ingress_key(inactive_outstanding_key)
-            {
+            |current_try| match self.leader_election.check().and_then(|()| {
+                self.recovery_db
+                    .report_lost_ingress_key(inactive_outstanding_key)
+                    .map_err(OverseerError::from)
+            }) {
                 Ok(_) => {
                     log::info!(
                         self.logger,
@@ -466,6 +784,7 @@ where
                     );
                     OperationResult::Ok(())
                 }
+                Err(err @ OverseerError::NotLeader(_)) => OperationResult::Err(err),
                 Err(err) => {
                     let number_of_remaining_tries = Self::NUMBER_OF_TRIES - current_try as usize;
                     let error_message = format!("The following key was not successfully reported as lost: {}. Will try {} more times. Underlying error: {}", inactive_outstanding_key, number_of_remaining_tries, err);
@@ -477,13 +796,21 @@ where
         Ok(result?)
     }
 
-    /// Tries to set a new ingress key on a node. The node is assumed to be
-    /// idle.
-    fn set_new_key_on_a_node(&self) -> Result<usize, OverseerError> {
-        for (i, ingest_client) in self.ingest_clients.iter().enumerate() {
+    /// Tries to set a new ingress key on one of the nodes that responded. The
+    /// node is assumed to be idle.
+    fn set_new_key_on_a_node(
+        &self,
+        ingest_summary_node_mappings: &[IngestSummaryNodeMapping],
+    ) -> Result<usize, OverseerError> {
+        for ingest_summary_node_mapping in ingest_summary_node_mappings {
+            let i = ingest_summary_node_mapping.node_index;
+            let ingest_client = &self.ingest_clients[i];
             let result = retry_with_index(
                 Fixed::from_millis(200).take(Self::NUMBER_OF_TRIES),
                 |current_try| {
+                    if let Err(err) = self.leader_election.check() {
+                        return OperationResult::Err(err);
+                    }
                     match ingest_client.new_keys() {
                         Ok(_) => {
                             log::info!(
@@ -493,7 +820,6 @@ where
                             );
                             OperationResult::Ok(())
                         }
-                        // TODO: We'll need to alert Ops to take manual action at this point.
                         Err(err) => {
                             let number_of_remaining_tries =
                                 Self::NUMBER_OF_TRIES - current_try as usize;
@@ -504,8 +830,10 @@ where
                 },
             );
 
-            if result.is_ok() {
-                return Ok(i);
+            match result.map_err(OverseerError::from) {
+                Ok(()) => return Ok(i),
+                Err(err @ OverseerError::NotLeader(_)) => return Err(err),
+                Err(_) => {}
             }
         }
 
@@ -519,6 +847,9 @@ where
         let result = retry_with_index(
             Fixed::from_millis(200).take(Self::NUMBER_OF_TRIES),
             |current_try| {
+                if let Err(err) = self.leader_election.check() {
+                    return OperationResult::Err(err);
+                }
                 let ingest_client = &self.ingest_clients[activated_node_index];
                 match ingest_client.activate() {
                     Ok(_) => {
@@ -529,7 +860,6 @@ where
                         );
                         OperationResult::Ok(())
                     }
-                    // TODO: Alert Ops to take manual action at this point.
                     Err(err) => {
                         let number_of_remaining_tries =
                             Self::NUMBER_OF_TRIES - current_try as usize;
@@ -0,0 +1,57 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+mod utils;
+
+use mc_common::logger::{test_with_logger, Logger};
+use mc_fog_ingest_server_test_utils::{get_ingress_keys, IngestServerTestHelper};
+use mc_fog_overseer_server::config::OverseerWorkerConfig;
+use std::{
+    thread::sleep,
+    time::{Duration, Instant},
+};
+use utils::TestHelperExt;
+
+const BASE_PORT: u16 = 8950;
+
+// Tests the scenario in which the active node goes down and never comes back.
+// The idle nodes have the active key.
+//
+// Once the node has been unresponsive for longer than the timeout, Fog
+// Overseer should activate an idle node without it.
+#[test_with_logger]
+fn active_node_is_unresponsive_idle_node_with_key_is_activated(logger: Logger) {
+    let mut helper = IngestServerTestHelper::new(BASE_PORT, logger.clone());
+    helper.add_origin_block();
+    let mut nodes = helper.make_nodes(3);
+
+    nodes[0].activate().expect("nodes[0] failed to activate");
+    let original_ingress_keys = get_ingress_keys(&nodes);
+
+    let config = OverseerWorkerConfig {
+        unresponsive_node_timeout: Duration::from_secs(1),
+        ..Default::default()
+    };
+    let _client = helper.enable_overseer_with_config(
+        nodes
+            .iter()
+            .map(|node| node.client_listen_uri.clone())
+            .collect(),
+        config,
+    );
+
+    // Stop the active node for good.
+    drop(nodes.remove(0));
+
+    let deadline = Instant::now() + Duration::from_secs(90);
+    while !nodes.iter().any(|node| node.is_active()) {
+        assert!(
+            Instant::now() < deadline,
+            "Fog Overseer did not activate an idle node"
+        );
+        sleep(Duration::from_secs(1));
+    }
+
+    // The activated node uses the original key, which was not reported lost.
+    assert_eq!(get_ingress_keys(&nodes), original_ingress_keys[1..]);
+    helper.check_ingress_key(&original_ingress_keys[0], false, false);
+}
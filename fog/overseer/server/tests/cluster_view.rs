@@ -0,0 +1,86 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+mod utils;
+
+use mc_common::logger::{test_with_logger, Logger};
+use mc_fog_ingest_server_test_utils::IngestServerTestHelper;
+use serde_json::Value;
+use std::{thread::sleep, time::Duration};
+use utils::TestHelperExt;
+
+const BASE_PORT: u16 = 9000;
+
+fn get_cluster_view(client: &rocket::local::blocking::Client) -> Value {
+    let body = client
+        .get("/cluster_view")
+        .dispatch()
+        .into_string()
+        .unwrap();
+    serde_json::from_str(&body).expect("cluster view is not JSON")
+}
+
+// When the Fog Ingest cluster has one active node, the enabled leader reports
+// every node and no action.
+#[test_with_logger]
+fn one_active_node_cluster_view_has_no_action(logger: Logger) {
+    let mut helper = IngestServerTestHelper::new(BASE_PORT, logger.clone());
+    helper.add_origin_block();
+    let nodes = helper.make_nodes(3);
+
+    nodes[0].activate().expect("first node failed to activate");
+
+    let client = helper.enable_overseer_for_nodes(&nodes);
+
+    let cluster_view = get_cluster_view(&client);
+    assert_eq!(cluster_view["is_enabled"], true, "{}", cluster_view);
+    assert_eq!(cluster_view["is_leader"], true, "{}", cluster_view);
+    assert_eq!(
+        cluster_view["next_action"]["type"], "no_action",
+        "{}",
+        cluster_view
+    );
+
+    let node_views = cluster_view["nodes"].as_array().unwrap();
+    assert_eq!(node_views.len(), 3);
+    for (node, node_view) in nodes.iter().zip(node_views) {
+        assert_eq!(
+            node_view["uri"],
+            serde_json::to_value(&node.client_listen_uri).unwrap()
+        );
+        assert!(node_view["last_response_timestamp"].is_u64());
+        assert!(node_view["error"].is_null());
+    }
+    assert_eq!(
+        node_views[0]["ingest_summary"]["ingest_controller_mode"],
+        "ACTIVE"
+    );
+}
+
+// A disabled overseer tells which action it would take, but does not take it.
+#[test_with_logger]
+fn disabled_overseer_cluster_view_plans_failover_without_acting(logger: Logger) {
+    let mut helper = IngestServerTestHelper::new(BASE_PORT + 25, logger.clone());
+    helper.add_origin_block();
+    let nodes = helper.make_nodes(3);
+
+    let client = helper.start_overseer_with_config(
+        nodes
+            .iter()
+            .map(|node| node.client_listen_uri.clone())
+            .collect(),
+        Default::default(),
+    );
+    // Give overseer time to poll the cluster.
+    sleep(Duration::from_secs(10));
+
+    let cluster_view = get_cluster_view(&client);
+    assert_eq!(cluster_view["is_enabled"], false, "{}", cluster_view);
+    assert_eq!(cluster_view["is_leader"], false, "{}", cluster_view);
+    assert_eq!(
+        cluster_view["next_action"]["type"], "set_new_keys_and_activate",
+        "{}",
+        cluster_view
+    );
+    assert!(cluster_view["next_action"]["lost_key"].is_null());
+    assert!(nodes.iter().all(|node| !node.is_active()));
+}
@@ -0,0 +1,63 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+mod utils;
+
+use mc_common::logger::{test_with_logger, Logger};
+use mc_fog_ingest_server_test_utils::IngestServerTestHelper;
+use mc_fog_overseer_server::config::OverseerWorkerConfig;
+use utils::TestHelperExt;
+
+const BASE_PORT: u16 = 8900;
+
+// In this scenario, two Fog Overseer replicas oversee the same Fog Ingest
+// cluster.
+//
+// Exactly one of them should be the leader, and the cluster should have one
+// active node.
+#[test_with_logger]
+fn two_overseers_elect_one_leader(logger: Logger) {
+    let mut helper = IngestServerTestHelper::new(BASE_PORT, logger.clone());
+    helper.add_origin_block();
+    let nodes = helper.make_nodes(3);
+
+    let config_a = OverseerWorkerConfig {
+        overseer_id: Some("overseer-a".to_string()),
+        ..Default::default()
+    };
+    let config_b = OverseerWorkerConfig {
+        overseer_id: Some("overseer-b".to_string()),
+        ..Default::default()
+    };
+
+    let ingest_uris: Vec<_> = nodes
+        .iter()
+        .map(|node| node.client_listen_uri.clone())
+        .collect();
+    let client_a = helper.enable_overseer_with_config(ingest_uris.clone(), config_a);
+    let client_b = helper.enable_overseer_with_config(ingest_uris, config_b);
+
+    let status_a = client_a.get("/status").dispatch().into_string().unwrap();
+    let status_b = client_b.get("/status").dispatch().into_string().unwrap();
+    let is_leader = |status: &str| status.contains("is the leader");
+    assert!(
+        is_leader(&status_a) ^ is_leader(&status_b),
+        "Exactly one overseer should be the leader: {} / {}",
+        status_a,
+        status_b
+    );
+
+    // The leader activated exactly one node.
+    assert_eq!(nodes.iter().filter(|node| node.is_active()).count(), 1);
+
+    // Once the leader is disabled, the other replica takes over.
+    let (leader, standby) = if is_leader(&status_a) {
+        (client_a, client_b)
+    } else {
+        (client_b, client_a)
+    };
+    leader.post("/disable").dispatch();
+    std::thread::sleep(std::time::Duration::from_secs(15));
+
+    let standby_status = standby.get("/status").dispatch().into_string().unwrap();
+    assert!(is_leader(&standby_status), "{}", standby_status);
+}
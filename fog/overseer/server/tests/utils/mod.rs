@@ -4,6 +4,7 @@
 
 use mc_fog_ingest_server_test_utils::{IngestServerTestHelper, TestIngestNode};
 use mc_fog_overseer_server::{
+    config::OverseerWorkerConfig,
     server::{initialize_rocket_server, OverseerState},
     service::OverseerService,
 };
@@ -12,7 +13,27 @@ use rocket::local::blocking::Client;
 use std::{thread::sleep, time::Duration};
 
 pub trait TestHelperExt {
-    fn enable_overseer(&self, ingest_uris: Vec<FogIngestUri>) -> Client;
+    fn start_overseer_with_config(
+        &self,
+        ingest_uris: Vec<FogIngestUri>,
+        worker_config: OverseerWorkerConfig,
+    ) -> Client;
+
+    fn enable_overseer_with_config(
+        &self,
+        ingest_uris: Vec<FogIngestUri>,
+        worker_config: OverseerWorkerConfig,
+    ) -> Client {
+        let client = self.start_overseer_with_config(ingest_uris, worker_config);
+        client.post("/enable").dispatch();
+        // Give overseer time to perform its logic.
+        sleep(Duration::from_secs(10));
+        client
+    }
+
+    fn enable_overseer(&self, ingest_uris: Vec<FogIngestUri>) -> Client {
+        self.enable_overseer_with_config(ingest_uris, Default::default())
+    }
 
     fn enable_overseer_for_nodes(&self, nodes: &[TestIngestNode]) -> Client {
         let ingest_uris = nodes
@@ -24,9 +45,17 @@ pub trait TestHelperExt {
 }
 
 impl TestHelperExt for IngestServerTestHelper {
-    fn enable_overseer(&self, ingest_uris: Vec<FogIngestUri>) -> Client {
-        let mut overseer_service =
-            OverseerService::new(ingest_uris, self.recovery_db.clone(), self.logger.clone());
+    fn start_overseer_with_config(
+        &self,
+        ingest_uris: Vec<FogIngestUri>,
+        worker_config: OverseerWorkerConfig,
+    ) -> Client {
+        let mut overseer_service = OverseerService::new(
+            ingest_uris,
+            worker_config,
+            self.recovery_db.clone(),
+            self.logger.clone(),
+        );
         overseer_service
             .start()
             .expect("OverseerService failed to start");
@@ -37,10 +66,6 @@ impl TestHelperExt for IngestServerTestHelper {
             .merge(("address", "127.0.0.1"));
         let rocket = initialize_rocket_server(rocket_config, overseer_state);
         // TODO: Consider testing the CLI instead.
-        let client = Client::tracked(rocket).expect("valid rocket instance");
-        client.post("/enable").dispatch();
-        // Give overseer time to perform its logic.
-        sleep(Duration::from_secs(10));
-        client
+        Client::tracked(rocket).expect("valid rocket instance")
     }
 }
@@ -10,7 +10,10 @@ extern crate alloc;
 mod types;
 
 use alloc::{string::String, vec::Vec};
-use core::fmt::{Debug, Display};
+use core::{
+    fmt::{Debug, Display},
+    time::Duration,
+};
 use mc_crypto_keys::CompressedRistrettoPublic;
 use mc_fog_kex_rng::KexRngPubkey;
 use mc_fog_types::view::TxOutSearchResult;
@@ -18,8 +21,8 @@ use mc_fog_types::view::TxOutSearchResult;
 pub use mc_blockchain_types::Block;
 pub use mc_fog_types::{common::BlockRange, ETxOutRecord};
 pub use types::{
-    AddBlockDataStatus, FogUserEvent, IngestInvocationId, IngestableRange, IngressPublicKeyRecord,
-    IngressPublicKeyStatus, ReportData,
+    skip_processed_user_events, AddBlockDataStatus, FogUserEvent, IngestInvocationId,
+    IngestableRange, IngressPublicKeyRecord, IngressPublicKeyStatus, ReportData,
 };
 
 /// Contains fields that are used as filters in  queries for ingress keys.
@@ -309,6 +312,63 @@ pub trait RecoveryDb {
 
     /// Get the highest block index for which we have any data at all.
     fn get_highest_known_block_index(&self) -> Result<Option<u64>, Self::Error>;
+
+    /// Try to take the lease with a given name, or to extend it if the holder
+    /// already holds it. A lease that is not extended expires after the given
+    /// duration, and can then be taken by another holder.
+    ///
+    /// Leases allow services running several replicas to elect the single
+    /// replica that is allowed to act, e.g. the Fog Overseer replica that
+    /// performs ingest failover.
+    ///
+    /// Each time a lease is taken, rather than extended, it gets a new fencing
+    /// token, greater than all the previous tokens of the lease. A holder that
+    /// stalled long enough for its lease to expire can then tell that it lost
+    /// the lease, even if it took it again since, with `check_lease`.
+    ///
+    /// Arguments:
+    /// * name: The name of the lease
+    /// * holder: A unique id of the caller
+    /// * duration: How long the lease lasts if it is not extended
+    ///
+    /// Returns:
+    /// * The fencing token of the lease if the holder holds it, None if someone
+    ///   else does, or an error if the query failed.
+    fn try_acquire_lease(
+        &self,
+        name: &str,
+        holder: &str,
+        duration: Duration,
+    ) -> Result<Option<u64>, Self::Error>;
+
+    /// Check that a holder still holds a lease with a given fencing token, i.e.
+    /// that the lease did not expire or get released since the holder took it
+    /// with this token.
+    ///
+    /// Holders should check their lease right before each action the lease
+    /// protects, since they may have stalled since they last extended it.
+    fn check_lease(&self, name: &str, holder: &str, token: u64) -> Result<bool, Self::Error>;
+
+    /// Release a lease if the holder holds it, so that others can take it
+    /// without waiting for it to expire.
+    fn release_lease(&self, name: &str, holder: &str) -> Result<(), Self::Error>;
+
+    /// Get the holder of a lease, if it is held and did not expire.
+    fn get_lease_holder(&self, name: &str) -> Result<Option<String>, Self::Error>;
+
+    /// Record how far a Fog View shard has loaded the ETxOutRecords in the
+    /// database. Pruning expired data never removes records of blocks which a
+    /// shard that recently reported its progress has not loaded yet.
+    ///
+    /// Arguments:
+    /// * shard_id: A unique id of the Fog View shard
+    /// * highest_processed_block_count: The shard has loaded all the
+    ///   ETxOutRecords of the blocks below this count
+    fn report_view_shard_progress(
+        &self,
+        shard_id: &str,
+        highest_processed_block_count: u64,
+    ) -> Result<(), Self::Error>;
 }
 
 /// The report database interface.
@@ -323,12 +383,19 @@ pub trait ReportDb {
     /// If there are many reports, then this should be redesigned to use an
     /// oblivious lookup strategy inside of an sgx enclave.
     ///
+    /// There may be several reports with the same report id, one per ingress
+    /// key, e.g. while an ingress key is being rotated, or when several
+    /// ingest servers publish under the same report id. Within a report id,
+    /// the report with the latest pubkey expiry comes first. Reports of lost
+    /// ingress keys are not returned.
+    ///
     /// Returns:
     /// * Pairs of the form report-id, report-data
     fn get_all_reports(&self) -> Result<Vec<(String, ReportData)>, Self::Error>;
 
-    /// Set report data associated with a given report id, unless the public key
-    /// is retired.
+    /// Set report data associated with a given report id and ingress public
+    /// key, unless the public key is retired. This replaces an earlier report
+    /// of the same report id and key, but not those of other keys.
     ///
     /// Arguments:
     /// * ingress_public_key - the public key signed by this report
@@ -347,6 +414,7 @@ pub trait ReportDb {
         data: &ReportData,
     ) -> Result<IngressPublicKeyStatus, Self::Error>;
 
-    /// Remove report data associated with a given report id.
+    /// Remove the report data of every ingress key associated with a given
+    /// report id.
     fn remove_report(&self, report_id: &str) -> Result<(), Self::Error>;
 }
@@ -3,8 +3,10 @@
 //! Database API types
 //! These are not user-facing, the user facing versions are in fog-types crate.
 
+use alloc::vec::Vec;
 use core::{fmt, ops::Deref};
 use mc_attest_core::VerificationReport;
+use mc_common::HashSet;
 use mc_crypto_keys::CompressedRistrettoPublic;
 use mc_fog_types::{
     common::BlockRange,
@@ -133,6 +135,52 @@ pub enum FogUserEvent {
     MissingBlocks(BlockRange),
 }
 
+/// Drop the user events which can't matter to a client that has already
+/// processed `processed_block_count` blocks.
+///
+/// These are the rng records of ingest invocations which were decommissioned
+/// before that block, together with their decommission events, when both are
+/// among `events`. All of such an invocation's TxOuts are in blocks the client
+/// has processed, and since its rng record is in `events` the client never had
+/// its rng.
+pub fn skip_processed_user_events(
+    events: Vec<FogUserEvent>,
+    processed_block_count: u64,
+) -> Vec<FogUserEvent> {
+    let rng_record_ids: HashSet<i64> = events
+        .iter()
+        .filter_map(|event| match event {
+            FogUserEvent::NewRngRecord(rng_record) => Some(rng_record.ingest_invocation_id),
+            _ => None,
+        })
+        .collect();
+    let processed_ids: HashSet<i64> = events
+        .iter()
+        .filter_map(|event| match event {
+            FogUserEvent::DecommissionIngestInvocation(decommissioned)
+                if decommissioned.last_ingested_block < processed_block_count
+                    && rng_record_ids.contains(&decommissioned.ingest_invocation_id) =>
+            {
+                Some(decommissioned.ingest_invocation_id)
+            }
+            _ => None,
+        })
+        .collect();
+
+    events
+        .into_iter()
+        .filter(|event| match event {
+            FogUserEvent::NewRngRecord(rng_record) => {
+                !processed_ids.contains(&rng_record.ingest_invocation_id)
+            }
+            FogUserEvent::DecommissionIngestInvocation(decommissioned) => {
+                !processed_ids.contains(&decommissioned.ingest_invocation_id)
+            }
+            FogUserEvent::MissingBlocks(_) => true,
+        })
+        .collect()
+}
+
 /// An ingest invocation begins consuming the blockchain at some particular
 /// block index, and eventually stops. The IngestableRange tracks the start
 /// block, what the last scanned block is, and whether it has stopped.
@@ -228,6 +276,52 @@ pub struct ReportData {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::vec;
+    use mc_fog_kex_rng::KexRngPubkey;
+
+    #[test]
+    fn skip_processed_user_events_drops_decommissioned_invocations() {
+        let rng_record = |ingest_invocation_id| {
+            FogUserEvent::NewRngRecord(RngRecord {
+                ingest_invocation_id,
+                pubkey: KexRngPubkey {
+                    public_key: vec![ingest_invocation_id as u8; 32],
+                    version: 0,
+                },
+                start_block: 0,
+            })
+        };
+        let decommissioned = |ingest_invocation_id, last_ingested_block| {
+            FogUserEvent::DecommissionIngestInvocation(DecommissionedIngestInvocation {
+                ingest_invocation_id,
+                last_ingested_block,
+            })
+        };
+        let missing = FogUserEvent::MissingBlocks(BlockRange::new(3, 5));
+
+        let events = vec![
+            rng_record(1),
+            rng_record(2),
+            decommissioned(1, 9),
+            missing.clone(),
+            decommissioned(2, 10),
+            // The client may have the rng of invocation 3 from an earlier query
+            decommissioned(3, 5),
+            rng_record(4),
+        ];
+
+        assert_eq!(skip_processed_user_events(events.clone(), 0), events);
+        assert_eq!(
+            skip_processed_user_events(events, 10),
+            vec![
+                rng_record(2),
+                missing,
+                decommissioned(2, 10),
+                decommissioned(3, 5),
+                rng_record(4),
+            ]
+        );
+    }
 
     #[test]
     fn active_ingress_public_key_record_covers_block_index() {
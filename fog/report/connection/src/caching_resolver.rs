@@ -0,0 +1,229 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! A FogPubkeyResolver which fetches fog reports on demand, and caches them
+//! per report server for a while.
+
+use crate::GrpcFogReportConnection;
+use mc_account_keys::PublicAddress;
+use mc_attest_verifier::Verifier;
+use mc_common::logger::{log, Logger};
+use mc_fog_report_validation::{
+    FogPubkeyError, FogPubkeyResolver, FogReportResponses, FogResolver, FullyValidatedFogPubkey,
+};
+use mc_util_uri::FogUri;
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// A sensible default for how long to use a fetched fog report. A report stays
+/// usable until its pubkey expiry, which is usually far away, so this mostly
+/// bounds how long it takes to pick up a new fog ingress key.
+pub const DEFAULT_FOG_REPORT_TTL: Duration = Duration::from_secs(60);
+
+/// A FogPubkeyResolver which fetches the report of each fog report server the
+/// first time it is needed, and then reuses it until `ttl` has passed.
+///
+/// This lets a transaction builder create many transactions to recipients on
+/// the same fog, while only contacting the report server once per `ttl`.
+/// Concurrent lookups of the same report server wait for a single fetch.
+/// Failed fetches are not cached.
+///
+/// Reports are validated against the verifier on every lookup, since the
+/// authority signature depends on the recipient.
+pub struct CachingFogResolver {
+    /// The connection used to fetch reports
+    conn: GrpcFogReportConnection,
+    /// The fog ingest attestation verifier
+    verifier: Verifier,
+    /// The report of each report server, by normalized report uri, ready to
+    /// resolve fog pubkeys with
+    cache: TtlCache<Arc<FogResolver>>,
+    /// Logger
+    logger: Logger,
+}
+
+impl CachingFogResolver {
+    /// Create a new caching resolver, fetching reports with `conn` and
+    /// validating them with `verifier`.
+    pub fn new(
+        conn: GrpcFogReportConnection,
+        verifier: Verifier,
+        ttl: Duration,
+        logger: Logger,
+    ) -> Self {
+        Self {
+            conn,
+            verifier,
+            cache: TtlCache::new(ttl),
+            logger,
+        }
+    }
+
+    /// Forget all the cached reports, e.g. after a transaction was rejected
+    /// because of an expired fog pubkey.
+    pub fn clear(&self) {
+        self.cache.clear();
+    }
+
+    /// Get a resolver for the report of this report server, fetching it if it
+    /// isn't cached or is older than the ttl.
+    fn get_resolver(&self, uri: &FogUri) -> Result<Arc<FogResolver>, FogPubkeyError> {
+        let uri_str = uri.to_string();
+        self.cache.get_or_try_insert_with(uri_str.clone(), || {
+            log::debug!(self.logger, "Fetching fog report from {}", uri_str);
+            let response = self
+                .conn
+                .fetch_fog_report(uri)
+                .map_err(|err| FogPubkeyError::Fetch(err.to_string()))?;
+
+            let mut responses = FogReportResponses::default();
+            responses.insert(uri_str.clone(), response);
+            Ok(Arc::new(FogResolver::new(responses, &self.verifier)?))
+        })
+    }
+}
+
+/// A map of values which are computed on demand and kept for a while.
+struct TtlCache<T: Clone> {
+    /// How long to keep a value for
+    ttl: Duration,
+    /// The slot of each key. A slot is locked while its value is computed.
+    slots: Mutex<HashMap<String, Arc<Mutex<Option<(T, Instant)>>>>>,
+}
+
+impl<T: Clone> TtlCache<T> {
+    fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            slots: Default::default(),
+        }
+    }
+
+    fn clear(&self) {
+        self.slots.lock().expect("mutex poisoned").clear();
+    }
+
+    /// Get the value of a key, computing it with `f` if there is none or it is
+    /// older than the ttl. Errors are not cached.
+    fn get_or_try_insert_with<E>(
+        &self,
+        key: String,
+        f: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E> {
+        let slot = self
+            .slots
+            .lock()
+            .expect("mutex poisoned")
+            .entry(key)
+            .or_default()
+            .clone();
+
+        // Holding the slot lock while computing makes concurrent lookups wait
+        // for this computation instead of making their own.
+        let mut cached = slot.lock().expect("mutex poisoned");
+        if let Some((value, inserted_at)) = cached.as_ref() {
+            if inserted_at.elapsed() < self.ttl {
+                return Ok(value.clone());
+            }
+        }
+
+        let value = f()?;
+        *cached = Some((value.clone(), Instant::now()));
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Barrier,
+        },
+        thread,
+    };
+
+    #[test]
+    fn test_values_expire_after_ttl() {
+        let cache = TtlCache::new(Duration::from_millis(100));
+        let num_fetches = AtomicUsize::new(0);
+        let fetch = || -> Result<usize, ()> { Ok(num_fetches.fetch_add(1, Ordering::SeqCst)) };
+
+        assert_eq!(cache.get_or_try_insert_with("a".to_string(), fetch), Ok(0));
+        assert_eq!(cache.get_or_try_insert_with("a".to_string(), fetch), Ok(0));
+        // Keys are cached independently
+        assert_eq!(cache.get_or_try_insert_with("b".to_string(), fetch), Ok(1));
+
+        thread::sleep(Duration::from_millis(150));
+        assert_eq!(cache.get_or_try_insert_with("a".to_string(), fetch), Ok(2));
+        assert_eq!(cache.get_or_try_insert_with("a".to_string(), fetch), Ok(2));
+
+        cache.clear();
+        assert_eq!(cache.get_or_try_insert_with("a".to_string(), fetch), Ok(3));
+    }
+
+    #[test]
+    fn test_errors_are_not_cached() {
+        let cache = TtlCache::new(Duration::from_secs(60));
+
+        assert_eq!(
+            cache.get_or_try_insert_with("a".to_string(), || Err("fetch failed")),
+            Err("fetch failed")
+        );
+        assert_eq!(
+            cache.get_or_try_insert_with("a".to_string(), || Ok::<_, &str>(7)),
+            Ok(7)
+        );
+        assert_eq!(
+            cache.get_or_try_insert_with("a".to_string(), || Err("fetch failed")),
+            Ok(7)
+        );
+    }
+
+    #[test]
+    fn test_concurrent_lookups_share_one_fetch() {
+        const NUM_THREADS: usize = 8;
+        let cache = Arc::new(TtlCache::new(Duration::from_secs(60)));
+        let num_fetches = Arc::new(AtomicUsize::new(0));
+        let barrier = Arc::new(Barrier::new(NUM_THREADS));
+
+        let threads: Vec<_> = (0..NUM_THREADS)
+            .map(|_| {
+                let cache = cache.clone();
+                let num_fetches = num_fetches.clone();
+                let barrier = barrier.clone();
+                thread::spawn(move || {
+                    barrier.wait();
+                    cache.get_or_try_insert_with("a".to_string(), || -> Result<_, ()> {
+                        // A slow fetch, which the other lookups wait for
+                        thread::sleep(Duration::from_millis(100));
+                        Ok(num_fetches.fetch_add(1, Ordering::SeqCst))
+                    })
+                })
+            })
+            .collect();
+
+        for thread in threads {
+            assert_eq!(thread.join().unwrap(), Ok(0));
+        }
+        assert_eq!(num_fetches.load(Ordering::SeqCst), 1);
+    }
+}
+
+impl FogPubkeyResolver for CachingFogResolver {
+    fn get_fog_pubkey(
+        &self,
+        recipient: &PublicAddress,
+    ) -> Result<FullyValidatedFogPubkey, FogPubkeyError> {
+        let url = recipient
+            .fog_report_url()
+            .ok_or(FogPubkeyError::NoFogReportUrl)?;
+        let uri = FogUri::from_str(url)?;
+
+        self.get_resolver(&uri)?.get_fog_pubkey(recipient)
+    }
+}
@@ -15,6 +15,9 @@ use mc_util_grpc::ConnectionUriGrpcioChannel;
 use mc_util_uri::FogUri;
 use std::sync::Arc;
 
+mod caching_resolver;
+
+pub use caching_resolver::{CachingFogResolver, DEFAULT_FOG_REPORT_TTL};
 pub use mc_fog_report_validation::FogReportResponses;
 
 /// Fog report server connection based on grpcio
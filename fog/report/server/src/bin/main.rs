@@ -2,9 +2,15 @@
 
 //! Main Method for the Fog Report Server
 
-use mc_common::{logger, sentry};
+use mc_common::{
+    logger::{self, Logger},
+    sentry,
+};
+use mc_fog_recovery_db_iface::ReportDb;
 use mc_fog_report_server::{Config, Materials, Server};
 use mc_fog_sql_recovery_db::SqlRecoveryDb;
+#[cfg(feature = "sqlite")]
+use mc_fog_sqlite_recovery_db::{sqlite_path_from_url, SqliteRecoveryDb};
 use mc_util_cli::ParserWithBuildInfo;
 use mc_util_grpc::AdminServer;
 use std::{env, sync::Arc};
@@ -20,8 +26,25 @@ fn main() {
     let materials = Materials::try_from(&config).expect("Could not read cryptographic materials");
 
     let database_url = env::var("DATABASE_URL").expect("DATABASE_URL environment variable missing");
-    let db = SqlRecoveryDb::new_from_url(
+
+    // With the sqlite feature, a sqlite:// url selects the SQLite backend,
+    // which is meant for local development.
+    #[cfg(feature = "sqlite")]
+    if let Some(db_path) = sqlite_path_from_url(&database_url) {
+        let db = SqliteRecoveryDb::new_from_path(db_path, Default::default(), logger.clone())
+            .unwrap_or_else(|err| {
+                panic!(
+                    "fog-report cannot open database '{}': {:?}",
+                    database_url, err
+                )
+            });
+        return run(config, materials, db, logger);
+    }
+
+    let read_replica_url = env::var("DATABASE_READ_REPLICA_URL").ok();
+    let db = SqlRecoveryDb::new_from_urls(
         &database_url,
+        read_replica_url.as_deref(),
         config.postgres_config.clone(),
         logger.clone(),
     )
@@ -31,7 +54,15 @@ fn main() {
             database_url, err
         )
     });
+    run(config, materials, db, logger)
+}
 
+fn run<R: ReportDb + Clone + Send + Sync + 'static>(
+    config: Config,
+    materials: Materials,
+    db: R,
+    logger: Logger,
+) {
     let mut server = Server::new(db, &config.client_listen_uri, materials, logger.clone());
     server.start();
 
@@ -79,6 +79,9 @@ impl<R: ReportDb + Clone + Send + Sync> Service<R> {
 
     /// Loads report data from the database, signs it, and puts the results into
     /// constructs a new response structure.
+    ///
+    /// The reports keep the order of the database, where the report which
+    /// expires last comes first among those with the same report id.
     fn build_response(&self) -> Result<ReportResponse, Error<R::Error>> {
         mc_common::trace_time!(self.logger, "Building prost response from report DB");
         let reports = self
@@ -154,4 +154,37 @@ fn report_server_grpc_tests(logger: Logger) {
         verification_report0
     );
     assert_eq!(resp.reports[0].get_pubkey_expiry(), report2.pubkey_expiry);
+
+    // Publish a report for a new ingress key under report2, as during a key
+    // rotation. Both are served, the one which expires last first.
+    let new_ingress_key = CompressedRistrettoPublic::from(&RistrettoPublic::from_random(&mut rng));
+    db.new_ingress_key(&new_ingress_key, 1).unwrap();
+    let new_report2 = ReportData {
+        ingest_invocation_id: None,
+        report: verification_report1.clone(),
+        pubkey_expiry: report2.pubkey_expiry + 10,
+    };
+    db.set_report(&new_ingress_key, report_id2, &new_report2)
+        .unwrap();
+
+    // Request reports
+    let req = ProtobufReportRequest::new();
+    let resp = report_client.get_reports(&req).unwrap();
+
+    assert_eq!(resp.reports.len(), 2);
+    assert_eq!(resp.reports[0].get_fog_report_id(), report_id2);
+    assert_eq!(
+        VerificationReport::from(resp.reports[0].get_report()),
+        verification_report1
+    );
+    assert_eq!(
+        resp.reports[0].get_pubkey_expiry(),
+        new_report2.pubkey_expiry
+    );
+    assert_eq!(resp.reports[1].get_fog_report_id(), report_id2);
+    assert_eq!(
+        VerificationReport::from(resp.reports[1].get_report()),
+        verification_report0
+    );
+    assert_eq!(resp.reports[1].get_pubkey_expiry(), report2.pubkey_expiry);
 }
@@ -27,11 +27,12 @@ use crate::ingest_report::IngestReportVerifier;
 use alloc::{
     collections::BTreeMap,
     string::{String, ToString},
+    vec::Vec,
 };
-use core::str::FromStr;
+use core::{cmp::Reverse, str::FromStr};
 use mc_account_keys::PublicAddress;
 use mc_attest_verifier::Verifier;
-use mc_fog_report_types::ReportResponse;
+use mc_fog_report_types::{Report, ReportResponse};
 use mc_fog_sig::Verifier as FogSigVerifier;
 use mc_util_uri::{FogUri, UriParseError};
 use serde::{Deserialize, Serialize};
@@ -116,20 +117,37 @@ impl FogPubkeyResolver for FogResolver {
             if let Some(result) = self.responses.get(&url) {
                 // Verify the authority signature chain
                 recipient.verify_fog_sig(result)?;
-                // Get the report corresponding to our ID
+                // Get the reports corresponding to our ID. There may be one per ingress
+                // key, e.g. during a key rotation, so prefer the one which expires last
+                // and fall back to the others if it doesn't validate.
                 let report_id = recipient.fog_report_id().unwrap_or("").to_string();
-                for report in result.reports.iter() {
-                    if report_id == report.fog_report_id {
-                        let pubkey = self
-                            .verifier
-                            .validate_ingest_ias_report(report.report.clone())?;
-                        return Ok(FullyValidatedFogPubkey {
-                            pubkey,
-                            pubkey_expiry: report.pubkey_expiry,
-                        });
+                let mut reports: Vec<&Report> = result
+                    .reports
+                    .iter()
+                    .filter(|report| report.fog_report_id == report_id)
+                    .collect();
+                reports.sort_by_key(|report| Reverse(report.pubkey_expiry));
+
+                let mut first_error = None;
+                for report in reports {
+                    match self
+                        .verifier
+                        .validate_ingest_ias_report(report.report.clone())
+                    {
+                        Ok(pubkey) => {
+                            return Ok(FullyValidatedFogPubkey {
+                                pubkey,
+                                pubkey_expiry: report.pubkey_expiry,
+                            })
+                        }
+                        Err(err) => {
+                            first_error.get_or_insert(err);
+                        }
                     }
                 }
-                Err(FogPubkeyError::NoMatchingReportId(url, report_id))
+                Err(first_error
+                    .map(FogPubkeyError::from)
+                    .unwrap_or_else(|| FogPubkeyError::NoMatchingReportId(url, report_id)))
             } else {
                 Err(FogPubkeyError::NoMatchingReportResponse(url))
             }
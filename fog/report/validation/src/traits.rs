@@ -58,6 +58,8 @@ pub enum FogPubkeyError {
     IngestReport(IngestReportError),
     /// Authority verification error: {0}
     Authority(String),
+    /// Could not fetch fog report: {0}
+    Fetch(String),
 }
 
 impl From<IngestReportError> for FogPubkeyError {
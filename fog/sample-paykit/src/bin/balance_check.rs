@@ -6,7 +6,9 @@
 //!
 //! It takes path to account key, and fog urls, as command line parameters, and
 //! prints balance check results on STDOUT, in a json format `{ 'block_count':
-//! XXX, 'balance': YYY }`.
+//! XXX, 'balance': YYY, 'unscanned_block_ranges': ZZZ }`. The unscanned block
+//! ranges are missed blocks which could not be scanned yet, so that the balance
+//! is as of the first of them.
 //!
 //! If STDIN is not closed, the program will block on STDIN until a byte is
 //! written there, and then print another balance. If the byte is 'd', that
@@ -68,12 +70,16 @@ fn main() {
 
     loop {
         // Do a balance check and print result on one line in stdout
-        let (balance, block_count) = sample_paykit
-            .check_balance()
+        let balance = sample_paykit
+            .check_balance_with_unscanned_ranges()
             .expect("Failed to compute balance!");
         println!(
             "{}",
-            json!({ "block_count": u64::from(block_count), "balance": balance})
+            json!({
+                "block_count": u64::from(balance.block_count),
+                "balance": balance.balances,
+                "unscanned_block_ranges": balance.unscanned_block_ranges,
+            })
         );
 
         // Read one byte and block on this. Exit if pipe is closed.
@@ -7,24 +7,30 @@
 use clap::Parser;
 use grpcio::{RpcContext, RpcStatus, UnarySink};
 use mc_account_keys::AccountKey;
+use mc_api::external;
 use mc_common::logger::{create_root_logger, log, Logger};
+use mc_crypto_keys::CompressedRistrettoPublic;
 use mc_fog_sample_paykit::{
     empty::Empty,
     remote_wallet::{
-        BalanceCheckResponse, DebugRequest, DebugResponse, FollowupBalanceCheckRequest,
-        FreshBalanceCheckRequest, StopRequest,
+        BalanceCheckResponse, BuildSwapProposalRequest, BuildSwapProposalResponse,
+        BuildSwapTransactionRequest, BuildSwapTransactionResponse, DebugRequest, DebugResponse,
+        FollowupBalanceCheckRequest, FreshBalanceCheckRequest, GetUnspentTxOutsRequest,
+        GetUnspentTxOutsResponse, StopRequest, UnspentTxOut,
     },
     remote_wallet_grpc::{create_remote_wallet_api, RemoteWalletApi},
-    Client, ClientBuilder,
+    BlockCount, Client, ClientBuilder,
 };
 use mc_fog_uri::{FogLedgerUri, FogViewUri};
-use mc_transaction_core::{tokens::Mob, Token};
+use mc_transaction_core::{tokens::Mob, Amount, SignedContingentInput, Token, TokenId};
 use mc_util_grpc::{
     rpc_internal_error, rpc_invalid_arg_error, send_result, ConnectionUriGrpcioServer,
 };
 use mc_util_keyfile::UncheckedMnemonicAccount;
 use mc_util_uri::{ConsensusClientUri, Uri, UriScheme};
+use protobuf::Message;
 use std::{
+    collections::HashMap,
     str::FromStr,
     sync::{Arc, Mutex},
     thread::sleep,
@@ -136,19 +142,11 @@ impl RemoteWalletService {
             .check_balance()
             .map_err(|err| rpc_internal_error("check_balance", err, &self.logger))?;
 
-        // conformance tests only does MOB right now
-        let balance = balances.get(&Mob::ID).cloned().unwrap_or_default();
-
         let mut state = self.state.lock().expect("mutex poisoned");
         let client_id = state.clients.len();
         state.clients.push(Some(client));
 
-        let response = BalanceCheckResponse {
-            client_id: client_id as u32,
-            balance,
-            block_count: block_count.into(),
-            ..Default::default()
-        };
+        let response = balance_check_response(client_id as u32, balances, block_count);
         log::info!(self.logger, "Fresh balance check: {:?}", response);
         Ok(response)
     }
@@ -164,14 +162,7 @@ impl RemoteWalletService {
                     .check_balance()
                     .map_err(|err| rpc_internal_error("check_balance", err, &self.logger))?;
 
-                let balance = balances.get(&Mob::ID).cloned().unwrap_or_default();
-
-                let response = BalanceCheckResponse {
-                    client_id: request.client_id,
-                    balance,
-                    block_count: block_count.into(),
-                    ..Default::default()
-                };
+                let response = balance_check_response(request.client_id, balances, block_count);
                 log::info!(self.logger, "Followup balance check: {:?}", response);
                 Ok(response)
             }
@@ -205,6 +196,131 @@ impl RemoteWalletService {
             )),
         }
     }
+
+    /// Run `f` with a previously-started client.
+    fn with_client<T>(
+        &self,
+        client_id: u32,
+        f: impl FnOnce(&mut Client) -> Result<T, RpcStatus>,
+    ) -> Result<T, RpcStatus> {
+        let mut state = self.state.lock().expect("mutex poisoned");
+        match state.clients.get_mut(client_id as usize) {
+            Some(Some(client)) => f(client),
+            _ => Err(rpc_invalid_arg_error(
+                "client_id",
+                "invalid client id",
+                &self.logger,
+            )),
+        }
+    }
+
+    fn get_unspent_tx_outs_impl(
+        &self,
+        request: GetUnspentTxOutsRequest,
+    ) -> Result<GetUnspentTxOutsResponse, RpcStatus> {
+        self.with_client(request.client_id, |client| {
+            let tx_outs: Vec<UnspentTxOut> = client
+                .get_unspent_tx_outs()
+                .into_iter()
+                .map(|(public_key, amount)| UnspentTxOut {
+                    public_key: public_key.as_bytes().to_vec(),
+                    token_id: *amount.token_id,
+                    value: amount.value,
+                    ..Default::default()
+                })
+                .collect();
+
+            Ok(GetUnspentTxOutsResponse {
+                tx_outs: tx_outs.into(),
+                ..Default::default()
+            })
+        })
+    }
+
+    fn build_swap_proposal_impl(
+        &self,
+        request: BuildSwapProposalRequest,
+    ) -> Result<BuildSwapProposalResponse, RpcStatus> {
+        let requested = Amount::new(
+            request.requested_value,
+            TokenId::from(request.requested_token_id),
+        );
+        let tx_out_public_key = if request.get_tx_out_public_key().is_empty() {
+            None
+        } else {
+            Some(
+                CompressedRistrettoPublic::try_from(request.get_tx_out_public_key())
+                    .map_err(|err| rpc_invalid_arg_error("tx_out_public_key", err, &self.logger))?,
+            )
+        };
+
+        self.with_client(request.client_id, |client| {
+            let mut rng = rand::thread_rng();
+            let sci = match tx_out_public_key {
+                Some(tx_out_public_key) => {
+                    client.build_swap_proposal_from_tx_out(&tx_out_public_key, requested, &mut rng)
+                }
+                None => client.build_swap_proposal(
+                    Amount::new(
+                        request.offered_value,
+                        TokenId::from(request.offered_token_id),
+                    ),
+                    requested,
+                    &mut rng,
+                ),
+            }
+            .map_err(|err| rpc_internal_error("build_swap_proposal", err, &self.logger))?;
+
+            let signed_contingent_input = external::SignedContingentInput::from(&sci)
+                .write_to_bytes()
+                .map_err(|err| rpc_internal_error("write_to_bytes", err, &self.logger))?;
+            log::info!(
+                self.logger,
+                "Built swap proposal spending key image {:?}",
+                sci.key_image()
+            );
+
+            Ok(BuildSwapProposalResponse {
+                signed_contingent_input,
+                ..Default::default()
+            })
+        })
+    }
+
+    fn build_swap_transaction_impl(
+        &self,
+        request: BuildSwapTransactionRequest,
+    ) -> Result<BuildSwapTransactionResponse, RpcStatus> {
+        let sci = external::SignedContingentInput::parse_from_bytes(
+            request.get_signed_contingent_input(),
+        )
+        .map_err(|err| rpc_invalid_arg_error("signed_contingent_input", err, &self.logger))
+        .and_then(|sci| {
+            SignedContingentInput::try_from(&sci)
+                .map_err(|err| rpc_invalid_arg_error("signed_contingent_input", err, &self.logger))
+        })?;
+        let fee = Amount::new(request.fee_value, TokenId::from(request.fee_token_id));
+
+        self.with_client(request.client_id, |client| {
+            let tx = client
+                .build_swap_transaction(sci, fee, &mut rand::thread_rng())
+                .map_err(|err| rpc_internal_error("build_swap_transaction", err, &self.logger))?;
+
+            let tx_bytes = external::Tx::from(&tx)
+                .write_to_bytes()
+                .map_err(|err| rpc_internal_error("write_to_bytes", err, &self.logger))?;
+            log::info!(
+                self.logger,
+                "Built swap transaction with tombstone block {}",
+                tx.prefix.tombstone_block
+            );
+
+            Ok(BuildSwapTransactionResponse {
+                tx: tx_bytes,
+                ..Default::default()
+            })
+        })
+    }
 }
 
 impl RemoteWalletApi for RemoteWalletService {
@@ -265,6 +381,67 @@ impl RemoteWalletApi for RemoteWalletService {
 
         send_result(ctx, sink, Ok(Empty::default()), &self.logger)
     }
+
+    fn get_unspent_tx_outs(
+        &mut self,
+        ctx: RpcContext,
+        request: GetUnspentTxOutsRequest,
+        sink: UnarySink<GetUnspentTxOutsResponse>,
+    ) {
+        send_result(
+            ctx,
+            sink,
+            self.get_unspent_tx_outs_impl(request),
+            &self.logger,
+        )
+    }
+
+    fn build_swap_proposal(
+        &mut self,
+        ctx: RpcContext,
+        request: BuildSwapProposalRequest,
+        sink: UnarySink<BuildSwapProposalResponse>,
+    ) {
+        send_result(
+            ctx,
+            sink,
+            self.build_swap_proposal_impl(request),
+            &self.logger,
+        )
+    }
+
+    fn build_swap_transaction(
+        &mut self,
+        ctx: RpcContext,
+        request: BuildSwapTransactionRequest,
+        sink: UnarySink<BuildSwapTransactionResponse>,
+    ) {
+        send_result(
+            ctx,
+            sink,
+            self.build_swap_transaction_impl(request),
+            &self.logger,
+        )
+    }
+}
+
+/// Make a balance check response, with the balance of every token, and the
+/// balance of MOB on its own for the conformance tests which only do MOB
+fn balance_check_response(
+    client_id: u32,
+    balances: HashMap<TokenId, u64>,
+    block_count: BlockCount,
+) -> BalanceCheckResponse {
+    BalanceCheckResponse {
+        client_id,
+        balance: balances.get(&Mob::ID).cloned().unwrap_or_default(),
+        block_count: block_count.into(),
+        balances: balances
+            .into_iter()
+            .map(|(token_id, balance)| (*token_id, balance))
+            .collect(),
+        ..Default::default()
+    }
 }
 
 fn main() {
@@ -41,9 +41,15 @@ impl MemoHandler {
         &self.last_memo
     }
 
-    /// Handle a memo
-    pub fn handle_memo(&mut self, tx_out: &TxOut, account_key: &AccountKey) {
+    /// Handle a memo, returning the memo, or memo handler error, for this
+    /// tx_out. This also becomes the last memo.
+    pub fn handle_memo(
+        &mut self,
+        tx_out: &TxOut,
+        account_key: &AccountKey,
+    ) -> Result<Option<MemoType>, MemoHandlerError> {
         self.last_memo = self.handle_memo_helper(tx_out, account_key);
+        self.last_memo.clone()
     }
 
     // Helper for handle_memo function. The result of this gets assigned to
@@ -101,6 +107,25 @@ impl MemoHandler {
                     Err(MemoHandlerError::UnknownSender)
                 }
             }
+            MemoType::AuthenticatedPaymentRequest(memo) => {
+                if let Some(addr) = self.contacts.get(&memo.sender_address_hash()) {
+                    if !bool::from(memo.validate(
+                        addr,
+                        &account_key.default_subaddress_view_private(),
+                        &tx_out.public_key,
+                    )) {
+                        Err(MemoHandlerError::FailedHmacValidation)
+                    } else if !bool::from(
+                        memo.validate_amount_commitment(&tx_out.masked_amount.commitment),
+                    ) {
+                        Err(MemoHandlerError::FailedAmountCommitmentValidation)
+                    } else {
+                        Ok(Some(memo_type))
+                    }
+                } else {
+                    Err(MemoHandlerError::UnknownSender)
+                }
+            }
             MemoType::Destination(_) => {
                 if subaddress_matches_tx_out(account_key, CHANGE_SUBADDRESS_INDEX, tx_out)? {
                     Ok(Some(memo_type))
@@ -136,6 +161,9 @@ pub enum MemoHandlerError {
     /// Failed subaddress validation
     FailedSubaddressValidation,
 
+    /// Failed amount commitment validation
+    FailedAmountCommitmentValidation,
+
     /// Key: {0}
     Key(KeyError),
 
@@ -1,6 +1,9 @@
 // Copyright (c) 2018-2022 The MobileCoin Foundation
 
-use crate::error::{Error, Result, TxOutMatchingError};
+use crate::{
+    chunked_view_client::ChunkedFogViewClient,
+    error::{Error, Result, TxOutMatchingError},
+};
 use core::{
     cmp::{max, min},
     ops::{Range, RangeInclusive},
@@ -8,12 +11,12 @@ use core::{
 };
 use displaydoc::Display;
 use mc_account_keys::{
-    AccountKey, PublicAddress, CHANGE_SUBADDRESS_INDEX, DEFAULT_SUBADDRESS_INDEX,
+    AccountKey, PublicAddress, ShortAddressHash, CHANGE_SUBADDRESS_INDEX, DEFAULT_SUBADDRESS_INDEX,
     INVALID_SUBADDRESS_INDEX,
 };
 use mc_blockchain_types::BlockIndex;
 use mc_common::logger::{log, Logger};
-use mc_crypto_keys::RistrettoPublic;
+use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPublic};
 use mc_fog_api::{fog_common, ledger};
 use mc_fog_ledger_connection::{
     Error as LedgerConnectionError, FogBlockGrpcClient, FogKeyImageGrpcClient,
@@ -24,7 +27,6 @@ use mc_fog_types::{
     view::{FogTxOut, FogTxOutMetadata, TxOutRecord},
     BlockCount,
 };
-use mc_fog_view_connection::FogViewGrpcClient;
 use mc_fog_view_protocol::{FogViewConnection, UserPrivate, UserRngSet};
 use mc_transaction_core::{
     get_tx_out_shared_secret,
@@ -155,18 +157,13 @@ impl CachedTxData {
 
     /// Get the num_blocks value that we can compute balances for.
     /// This is the minimum of all the sources of data that we have from
-    /// different servers.
-    ///
-    /// Note: Missed Blocks are not implemented in the paykit at this revision,
-    /// the implementation would have to keep track of if there are any blocks
-    /// that fog view told us about that we have to download and scan, and
-    /// then we would have to track if we did that. Then this `min`
-    /// expression would also take the min of outstanding missed blocks.
+    /// different servers, and of the start of the missed block ranges that
+    /// we have not scanned yet.
     pub fn get_num_blocks(&self) -> BlockCount {
         let missing_block_limit = self
             .missed_block_ranges
             .iter()
-            .map(|block_range| BlockCount::from(block_range.start_block + 1))
+            .map(|block_range| BlockCount::from(block_range.start_block))
             .min()
             .unwrap_or(BlockCount::MAX);
         *[
@@ -179,6 +176,15 @@ impl CachedTxData {
         .unwrap()
     }
 
+    /// Get the block ranges that Fog View has reported as missed, and that we
+    /// have not downloaded and scanned yet.
+    ///
+    /// Balances do not cover these blocks, nor any block after the start of
+    /// the first of them.
+    pub fn get_missed_block_ranges(&self) -> &[common::BlockRange] {
+        &self.missed_block_ranges
+    }
+
     /// Get the latest_global_txo_count.
     ///
     /// This can be the largest global_txo_count value
@@ -231,6 +237,36 @@ impl CachedTxData {
             .collect::<Vec<_>>()
     }
 
+    /// Get the public keys and amounts of the Txos contributing to the
+    /// balance, not known to be spent at all.
+    pub fn get_unspent_tx_out_amounts(&self) -> Vec<(CompressedRistrettoPublic, Amount)> {
+        self.get_unspent_txos()
+            .into_iter()
+            .map(|txo| (txo.tx_out.public_key, txo.amount))
+            .collect()
+    }
+
+    /// Find one of the Txos contributing to the balance by its public key.
+    ///
+    /// Returns Error::UnspentTxOutNotFound if we don't own such a Txo, or it
+    /// is spent.
+    pub fn get_unspent_tx_out(&self, public_key: &CompressedRistrettoPublic) -> Result<OwnedTxOut> {
+        self.get_unspent_txos()
+            .into_iter()
+            .find(|txo| txo.tx_out.public_key == *public_key)
+            .cloned()
+            .ok_or(Error::UnspentTxOutNotFound(*public_key))
+    }
+
+    /// Get records of all the Txos we have received, in order of global
+    /// index, with their memos.
+    pub fn get_received_tx_outs(&self) -> Vec<ReceivedTxOut> {
+        self.owned_tx_outs
+            .values()
+            .map(ReceivedTxOut::from)
+            .collect()
+    }
+
     /// Compute our current balance
     ///
     /// Returns (map<TokenId, u64>, block_count)
@@ -368,7 +404,12 @@ impl CachedTxData {
 
         for record in records {
             match OwnedTxOut::new(record, &self.account_key, &self.spsk_to_index) {
-                Ok(otxo) => {
+                Ok(mut otxo) => {
+                    // Handle memo
+                    otxo.memo = self
+                        .memo_handler
+                        .handle_memo(&otxo.tx_out, &self.account_key);
+
                     // Insert into owned_tx_outs
                     log::trace!(
                         self.logger,
@@ -398,9 +439,6 @@ impl CachedTxData {
                                 min(self.key_image_data_completeness, as_of);
                         }
                     }
-                    // Handle memo
-                    self.memo_handler
-                        .handle_memo(&otxo.tx_out, &self.account_key);
                 }
                 Err(err) => {
                     errors.push(err);
@@ -417,7 +455,7 @@ impl CachedTxData {
     /// discovered.
     pub fn poll_fog_for_txos(
         &mut self,
-        fog_view_client: &mut FogViewGrpcClient,
+        fog_view_client: &mut ChunkedFogViewClient,
         fog_block_client: &mut FogBlockGrpcClient,
     ) -> Result<usize> {
         let old_rng_num_blocks = self.rng_set.get_highest_processed_block_count();
@@ -459,22 +497,9 @@ impl CachedTxData {
         }
 
         self.missed_block_ranges.extend(new_missed_block_ranges);
-        let fog_common_block_ranges: Vec<fog_common::BlockRange> = self
-            .missed_block_ranges
-            .iter()
-            .map(fog_common::BlockRange::from)
-            .collect::<Vec<_>>();
-        match fog_block_client.get_missed_block_ranges(fog_common_block_ranges) {
-            Ok(block_response) => {
-                let tx_out_records_from_missed_blocks: Vec<TxOutRecord> =
-                    self.create_tx_out_records(&block_response);
+        match self.download_missed_block_ranges(fog_block_client) {
+            Ok(tx_out_records_from_missed_blocks) => {
                 txo_records.extend(tx_out_records_from_missed_blocks);
-                let updated_missed_block_ranges =
-                    CachedTxData::calculate_updated_missed_block_ranges(
-                        &self.missed_block_ranges,
-                        &block_response.blocks.into_vec(),
-                    );
-                self.missed_block_ranges = updated_missed_block_ranges;
             }
             Err(err) => {
                 log::error!(
@@ -506,6 +531,57 @@ impl CachedTxData {
         Ok(num_txos)
     }
 
+    /// Download the missed block ranges from Fog Ledger and scan them for our
+    /// TxOuts, retrying the ranges which a previous poll could not download.
+    ///
+    /// Returns the number of txos discovered. Their key images still have to
+    /// be checked before they contribute to the balance.
+    pub fn scan_missed_block_ranges(
+        &mut self,
+        fog_block_client: &mut FogBlockGrpcClient,
+    ) -> Result<usize> {
+        let txo_records = self.download_missed_block_ranges(fog_block_client)?;
+        let num_txos = txo_records.len();
+
+        let errors = self.consume_new_txo_records(txo_records.into_iter());
+        for err in errors {
+            // Note: this could be caused by a griefing attack, but isn't normally expected
+            log::warn!(
+                self.logger,
+                "View key scanning failed, fog gave us a TXO that wasn't ours: {}",
+                err
+            );
+        }
+        Ok(num_txos)
+    }
+
+    /// Download the blocks of the missed block ranges from Fog Ledger, and
+    /// remove the blocks we got from the missed block ranges.
+    ///
+    /// Returns the TxOutRecords of the TxOuts in these blocks which are
+    /// ours.
+    fn download_missed_block_ranges(
+        &mut self,
+        fog_block_client: &mut FogBlockGrpcClient,
+    ) -> StdResult<Vec<TxOutRecord>, LedgerConnectionError> {
+        if self.missed_block_ranges.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let fog_common_block_ranges: Vec<fog_common::BlockRange> = self
+            .missed_block_ranges
+            .iter()
+            .map(fog_common::BlockRange::from)
+            .collect::<Vec<_>>();
+        let block_response = fog_block_client.get_missed_block_ranges(fog_common_block_ranges)?;
+        let tx_out_records = self.create_tx_out_records(&block_response);
+        self.missed_block_ranges = CachedTxData::calculate_updated_missed_block_ranges(
+            &self.missed_block_ranges,
+            &block_response.blocks.into_vec(),
+        );
+        Ok(tx_out_records)
+    }
+
     /// Determines the new missed block ranges given the blocks that are
     /// retrieved in the BlockData object.
     fn calculate_updated_missed_block_ranges(
@@ -790,7 +866,7 @@ impl CachedTxData {
     /// Poll for txos and then key images, with some appropriate debug logging
     pub fn poll_fog(
         &mut self,
-        fog_view_client: &mut FogViewGrpcClient,
+        fog_view_client: &mut ChunkedFogViewClient,
         key_image_client: &mut FogKeyImageGrpcClient,
         fog_block_client: &mut FogBlockGrpcClient,
     ) -> Result<()> {
@@ -884,6 +960,9 @@ pub struct OwnedTxOut {
     /// server. This is either `spent_at(index)` or
     /// `not_spent_as_of(count)`.
     pub status: KeyImageStatus,
+    /// The memo of the tx_out, or the error that the memo handler found when
+    /// decrypting or validating it.
+    pub memo: StdResult<Option<MemoType>, MemoHandlerError>,
 }
 
 impl OwnedTxOut {
@@ -940,10 +1019,93 @@ impl OwnedTxOut {
             amount,
             subaddress_index: *subaddress_index,
             status,
+            // The memo is handled once the tx_out is added to the cache
+            memo: Ok(None),
         })
     }
 }
 
+/// A record of a TxOut that we received, with what its memo tells us about the
+/// transaction that created it.
+///
+/// Sender address hashes and payment request ids only come from memos which
+/// were validated against our contacts, and destination details only from
+/// memos which were validated as being on our change subaddress.
+#[derive(Debug, Clone)]
+pub struct ReceivedTxOut {
+    /// The public key of the tx_out
+    pub public_key: CompressedRistrettoPublic,
+    /// The global index of the tx_out
+    pub global_index: u64,
+    /// The block in which the tx_out appeared
+    pub block_index: BlockIndex,
+    /// The amount we received
+    pub amount: Amount,
+    /// The subaddress index the tx_out was sent to
+    pub subaddress_index: u64,
+    /// The block in which we spent the tx_out, if we know that it was spent
+    pub spent_at: Option<BlockIndex>,
+    /// The memo of the tx_out, or the error that the memo handler found when
+    /// decrypting or validating it
+    pub memo: StdResult<Option<MemoType>, MemoHandlerError>,
+    /// The address hash of the sender, from an authenticated sender memo
+    pub sender_address_hash: Option<ShortAddressHash>,
+    /// The payment request id the sender was paying, from an authenticated
+    /// sender memo with payment request id
+    pub payment_request_id: Option<u64>,
+    /// The address hash of the recipient, when this is change from our own
+    /// transaction with a destination memo
+    pub recipient_address_hash: Option<ShortAddressHash>,
+    /// The fee our own transaction paid, from its destination memo
+    pub fee: Option<u64>,
+    /// The total outlay of our own transaction, from its destination memo
+    pub total_outlay: Option<u64>,
+}
+
+impl From<&OwnedTxOut> for ReceivedTxOut {
+    fn from(src: &OwnedTxOut) -> Self {
+        let mut result = Self {
+            public_key: src.tx_out.public_key,
+            global_index: src.global_index,
+            block_index: src.block_index,
+            amount: src.amount,
+            subaddress_index: src.subaddress_index,
+            spent_at: match src.status {
+                KeyImageStatus::SpentAt(block_index) => Some(block_index),
+                KeyImageStatus::NotSpent(_) => None,
+            },
+            memo: src.memo.clone(),
+            sender_address_hash: None,
+            payment_request_id: None,
+            recipient_address_hash: None,
+            fee: None,
+            total_outlay: None,
+        };
+
+        match &src.memo {
+            Ok(Some(MemoType::AuthenticatedSender(memo))) => {
+                result.sender_address_hash = Some(memo.sender_address_hash());
+            }
+            Ok(Some(MemoType::AuthenticatedSenderWithPaymentRequestId(memo))) => {
+                result.sender_address_hash = Some(memo.sender_address_hash());
+                result.payment_request_id = Some(memo.payment_request_id());
+            }
+            Ok(Some(MemoType::AuthenticatedPaymentRequest(memo))) => {
+                result.sender_address_hash = Some(memo.sender_address_hash());
+                result.payment_request_id = Some(memo.payment_request_id());
+            }
+            Ok(Some(MemoType::Destination(memo))) => {
+                result.recipient_address_hash = Some(memo.get_address_hash().clone());
+                result.fee = Some(memo.get_fee());
+                result.total_outlay = Some(memo.get_total_outlay());
+            }
+            _ => {}
+        }
+
+        result
+    }
+}
+
 /// Implementation detail: Input selection heuristic
 ///
 /// The input selection heuristic chooses which of our Txos to use as inputs to
@@ -1125,6 +1287,8 @@ impl From<InputSelectionError> for Error {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use mc_common::logger::test_with_logger;
+    use rand::{rngs::StdRng, SeedableRng};
 
     #[test]
     fn input_selection_heuristic_3_inputs() {
@@ -1340,6 +1504,28 @@ mod tests {
         );
     }
 
+    #[test_with_logger]
+    fn get_num_blocks_stops_at_the_first_unscanned_block(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+        let mut cached_tx_data =
+            CachedTxData::new(AccountKey::random(&mut rng), Vec::new(), logger);
+        cached_tx_data
+            .rng_set
+            .set_highest_processed_block_count(100);
+        assert_eq!(cached_tx_data.get_num_blocks(), BlockCount::from(100));
+        assert!(cached_tx_data.get_missed_block_ranges().is_empty());
+
+        cached_tx_data.missed_block_ranges = vec![
+            common::BlockRange::new(40, 45),
+            common::BlockRange::new(12, 13),
+        ];
+        assert_eq!(cached_tx_data.get_num_blocks(), BlockCount::from(12));
+        assert_eq!(cached_tx_data.get_missed_block_ranges().len(), 2);
+
+        let (_balances, block_count) = cached_tx_data.get_balance();
+        assert_eq!(block_count, BlockCount::from(12));
+    }
+
     #[test]
     fn calculate_updated_missed_block_ranges_empty_missed_block_ranges_returns_empty_vector() {
         let empty_missed_block_ranges = vec![];
@@ -0,0 +1,234 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! A fog view connection which splits large sets of search keys across several
+//! attested view queries.
+//!
+//! An account with a long history has many rngs, and the polling protocol asks
+//! for several search keys from each of them in every round, which can exceed
+//! what the view enclave handles well in one query. The chunks are sent over a
+//! bounded number of connections at a time, each with its own attested
+//! session, and their responses are merged into one.
+
+use mc_common::logger::{log, Logger};
+use mc_fog_types::view::QueryResponse;
+use mc_fog_view_connection::{Error, FogViewGrpcClient};
+use mc_fog_view_protocol::FogViewConnection;
+use std::thread;
+
+/// The default number of search keys sent in one view query.
+pub const DEFAULT_MAX_SEARCH_KEYS_PER_VIEW_QUERY: usize = 1000;
+
+/// The default number of view queries which are in flight at once.
+pub const DEFAULT_MAX_CONCURRENT_VIEW_QUERIES: usize = 4;
+
+/// A fog view connection which splits the search keys of a request into
+/// chunks, queried concurrently over a pool of connections.
+pub struct ChunkedFogViewClient {
+    /// The connections, one for each query which can be in flight at once
+    conns: Vec<FogViewGrpcClient>,
+    /// The most search keys sent in one query
+    max_search_keys_per_query: usize,
+    /// A logger object
+    logger: Logger,
+}
+
+impl ChunkedFogViewClient {
+    /// Create a new chunked fog view client
+    ///
+    /// Arguments:
+    /// * conns: Connections to the fog view service. As many queries as there
+    ///   are connections are in flight at once.
+    /// * max_search_keys_per_query: The most search keys sent in one query
+    /// * logger: For logging
+    pub fn new(
+        conns: Vec<FogViewGrpcClient>,
+        max_search_keys_per_query: usize,
+        logger: Logger,
+    ) -> Self {
+        assert!(!conns.is_empty(), "at least one connection is needed");
+        assert!(
+            max_search_keys_per_query > 0,
+            "max_search_keys_per_query must be positive"
+        );
+        Self {
+            conns,
+            max_search_keys_per_query,
+            logger,
+        }
+    }
+}
+
+impl FogViewConnection for ChunkedFogViewClient {
+    type Error = Error;
+
+    fn request_with_processed_block_count(
+        &mut self,
+        start_from_user_event_id: i64,
+        start_from_block_index: u64,
+        processed_block_count: u64,
+        search_keys: Vec<Vec<u8>>,
+    ) -> Result<QueryResponse, Self::Error> {
+        if search_keys.len() <= self.max_search_keys_per_query {
+            return self.conns[0].request_with_processed_block_count(
+                start_from_user_event_id,
+                start_from_block_index,
+                processed_block_count,
+                search_keys,
+            );
+        }
+
+        let mut chunks: Vec<Vec<Vec<u8>>> = search_keys
+            .chunks(self.max_search_keys_per_query)
+            .map(<[Vec<u8>]>::to_vec)
+            .collect();
+        log::debug!(
+            self.logger,
+            "Splitting {} search keys into {} view queries",
+            search_keys.len(),
+            chunks.len()
+        );
+
+        let mut responses = Vec::with_capacity(chunks.len());
+        let mut first_err = None;
+        let mut is_first_chunk = true;
+        while !chunks.is_empty() {
+            let num_queries = chunks.len().min(self.conns.len());
+            let handles: Vec<_> = self
+                .conns
+                .drain(..num_queries)
+                .zip(chunks.drain(..num_queries))
+                .map(|(mut conn, chunk)| {
+                    // Only the first query asks for user events, so that they
+                    // are not sent more than once
+                    let user_event_id = if is_first_chunk {
+                        start_from_user_event_id
+                    } else {
+                        i64::MAX
+                    };
+                    is_first_chunk = false;
+                    thread::spawn(move || {
+                        let response = conn.request_with_processed_block_count(
+                            user_event_id,
+                            start_from_block_index,
+                            processed_block_count,
+                            chunk,
+                        );
+                        (conn, response)
+                    })
+                })
+                .collect();
+
+            // Every connection is returned to the pool before any error is
+            // reported
+            for handle in handles {
+                let (conn, response) = handle.join().expect("view query thread panicked");
+                self.conns.push(conn);
+                match response {
+                    Ok(response) => responses.push(response),
+                    Err(err) => {
+                        first_err.get_or_insert(err);
+                    }
+                }
+            }
+            if first_err.is_some() {
+                break;
+            }
+        }
+        if let Some(err) = first_err {
+            return Err(err);
+        }
+
+        Ok(merge_query_responses(responses))
+    }
+}
+
+/// Merge the responses to the chunks of a request, in order, into one
+/// response.
+///
+/// The user events come from the first response, which is the only one which
+/// asked for them. The other chunks may have been answered by servers which
+/// had processed different numbers of blocks, so the lowest block counts are
+/// reported, which is correct for all the search results.
+fn merge_query_responses(responses: Vec<QueryResponse>) -> QueryResponse {
+    let mut responses = responses.into_iter();
+    let mut merged = responses.next().unwrap_or_default();
+    for response in responses {
+        if response.highest_processed_block_count < merged.highest_processed_block_count {
+            merged.highest_processed_block_count = response.highest_processed_block_count;
+            merged.highest_processed_block_signature_timestamp =
+                response.highest_processed_block_signature_timestamp;
+        }
+        if response.last_known_block_count < merged.last_known_block_count {
+            merged.last_known_block_count = response.last_known_block_count;
+            merged.last_known_block_cumulative_txo_count =
+                response.last_known_block_cumulative_txo_count;
+        }
+        merged
+            .tx_out_search_results
+            .extend(response.tx_out_search_results);
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_fog_types::view::{RngRecord, TxOutSearchResult};
+
+    fn search_result(key: u8) -> TxOutSearchResult {
+        TxOutSearchResult {
+            search_key: vec![key; 16],
+            result_code: 1,
+            ciphertext: vec![key; 32],
+        }
+    }
+
+    #[test]
+    fn merge_query_responses_takes_events_from_the_first_and_the_lowest_block_counts() {
+        let first = QueryResponse {
+            highest_processed_block_count: 10,
+            highest_processed_block_signature_timestamp: 100,
+            next_start_from_user_event_id: 7,
+            rng_records: vec![RngRecord::default()],
+            tx_out_search_results: vec![search_result(1), search_result(2)],
+            last_known_block_count: 12,
+            last_known_block_cumulative_txo_count: 120,
+            ..Default::default()
+        };
+        let second = QueryResponse {
+            highest_processed_block_count: 9,
+            highest_processed_block_signature_timestamp: 90,
+            next_start_from_user_event_id: i64::MAX,
+            tx_out_search_results: vec![search_result(3)],
+            last_known_block_count: 13,
+            last_known_block_cumulative_txo_count: 130,
+            ..Default::default()
+        };
+        let third = QueryResponse {
+            highest_processed_block_count: 11,
+            highest_processed_block_signature_timestamp: 110,
+            next_start_from_user_event_id: i64::MAX,
+            tx_out_search_results: vec![search_result(4)],
+            last_known_block_count: 11,
+            last_known_block_cumulative_txo_count: 110,
+            ..Default::default()
+        };
+
+        let merged = merge_query_responses(vec![first, second, third]);
+        assert_eq!(merged.highest_processed_block_count, 9);
+        assert_eq!(merged.highest_processed_block_signature_timestamp, 90);
+        assert_eq!(merged.next_start_from_user_event_id, 7);
+        assert_eq!(merged.rng_records.len(), 1);
+        assert_eq!(
+            merged.tx_out_search_results,
+            vec![
+                search_result(1),
+                search_result(2),
+                search_result(3),
+                search_result(4)
+            ]
+        );
+        assert_eq!(merged.last_known_block_count, 11);
+        assert_eq!(merged.last_known_block_cumulative_txo_count, 110);
+    }
+}
@@ -3,9 +3,10 @@
 //! MobileCoin Client Object
 
 use crate::{
-    cached_tx_data::{CachedTxData, OwnedTxOut},
+    cached_tx_data::{CachedTxData, OwnedTxOut, ReceivedTxOut},
+    chunked_view_client::ChunkedFogViewClient,
     error::{Error, Result},
-    BlockInfo, MemoHandlerError, TransactionStatus,
+    Balance, BlockInfo, MemoHandlerError, TransactionStatus,
 };
 use core::{result::Result as StdResult, str::FromStr};
 use mc_account_keys::{AccountKey, PublicAddress};
@@ -26,7 +27,6 @@ use mc_fog_ledger_connection::{
 use mc_fog_report_connection::GrpcFogReportConnection;
 use mc_fog_report_validation::{FogPubkeyResolver, FogResolver};
 use mc_fog_types::{ledger::KeyImageResultCode, BlockCount};
-use mc_fog_view_connection::FogViewGrpcClient;
 use mc_transaction_core::{
     tx::{Tx, TxOut, TxOutMembershipProof},
     Amount, SignedContingentInput, TokenId,
@@ -51,7 +51,7 @@ const TELEMETRY_BLOCK_INDEX_KEY: Key = telemetry_static_key!("block-index");
 /// sending transactions
 pub struct Client {
     consensus_service_conn: ThickClient<HardcodedCredentialsProvider>,
-    fog_view: FogViewGrpcClient,
+    fog_view: ChunkedFogViewClient,
     fog_merkle_proof: FogMerkleProofGrpcClient,
     fog_key_image: FogKeyImageGrpcClient,
     fog_block: FogBlockGrpcClient,
@@ -74,7 +74,7 @@ impl Client {
     /// Create a new sample paykit object
     pub fn new(
         consensus_service_conn: ThickClient<HardcodedCredentialsProvider>,
-        fog_view: FogViewGrpcClient,
+        fog_view: ChunkedFogViewClient,
         fog_merkle_proof: FogMerkleProofGrpcClient,
         fog_key_image: FogKeyImageGrpcClient,
         fog_block: FogBlockGrpcClient,
@@ -156,6 +156,46 @@ impl Client {
         self.tx_data.get_balance()
     }
 
+    /// Check this user's current available balance, and the block ranges
+    /// which it does not cover because they have not been scanned yet.
+    pub fn check_balance_with_unscanned_ranges(&mut self) -> Result<Balance> {
+        self.check_balance()?;
+        Ok(self.compute_balance_with_unscanned_ranges())
+    }
+
+    /// Compute the balance, and the block ranges which it does not cover
+    /// because they have not been scanned yet, based on locally available
+    /// data. Does NOT make any new network calls.
+    pub fn compute_balance_with_unscanned_ranges(&self) -> Balance {
+        let (balances, block_count) = self.compute_balance();
+        Balance {
+            balances,
+            block_count,
+            unscanned_block_ranges: self.tx_data.get_missed_block_ranges().to_vec(),
+        }
+    }
+
+    /// Download the unscanned block ranges from fog ledger, view-key scan
+    /// them for this user's TxOuts, and check the key images of any TxOuts
+    /// found.
+    ///
+    /// Returns the balance after scanning. Ranges which still could not be
+    /// downloaded remain in its unscanned block ranges.
+    pub fn scan_unscanned_block_ranges(&mut self) -> Result<Balance> {
+        mc_common::trace_time!(self.logger, "MobileCoinClient.scan_unscanned_block_ranges");
+        let num_txos = self.tx_data.scan_missed_block_ranges(&mut self.fog_block)?;
+        log::debug!(
+            self.logger,
+            "Scanning unscanned block ranges found {} txos",
+            num_txos
+        );
+        if num_txos > 0 {
+            self.tx_data
+                .poll_fog_for_key_images(&mut self.fog_key_image)?;
+        }
+        Ok(self.compute_balance_with_unscanned_ranges())
+    }
+
     /// Get balance debug print message
     pub fn debug_balance(&mut self) -> String {
         self.tx_data.debug_balance()
@@ -166,6 +206,12 @@ impl Client {
         self.tx_data.get_last_memo()
     }
 
+    /// Get records of all the TxOuts we have received, as of the last balance
+    /// check, with their memos, sender address hashes and payment request ids
+    pub fn get_received_tx_outs(&self) -> Vec<ReceivedTxOut> {
+        self.tx_data.get_received_tx_outs()
+    }
+
     /// Get the latest block version that we heard about from fog
     /// Note that this may not be a "valid" block version if our software is old
     pub fn get_latest_block_version(&self) -> u32 {
@@ -297,7 +343,9 @@ impl Client {
     ///   fee.
     /// * `target_address` - the recipient's address.
     /// * `rng` - Randomness.
-    /// * `fee` - The transaction fee to use
+    /// * `fee` - The transaction fee to use. The fee is paid in the token of
+    ///   `amount`, from the same inputs, so it must be the fee for that token
+    ///   (see `get_minimum_fee`).
     pub fn build_transaction<T: RngCore + CryptoRng>(
         &mut self,
         amount: Amount,
@@ -363,6 +411,30 @@ impl Client {
         )
     }
 
+    /// Builds a transaction like `build_transaction`, paying the minimum fee
+    /// which consensus currently requires for the token of `amount`.
+    ///
+    /// Returns `Error::NoMinimumFee` if consensus has no minimum fee for the
+    /// token, which means that it does not accept transactions in it.
+    pub fn build_transaction_with_minimum_fee<T: RngCore + CryptoRng>(
+        &mut self,
+        amount: Amount,
+        target_address: &PublicAddress,
+        rng: &mut T,
+    ) -> Result<Tx> {
+        let fee = self
+            .get_minimum_fee(amount.token_id)?
+            .ok_or(Error::NoMinimumFee(amount.token_id))?;
+        self.build_transaction(amount, target_address, rng, fee)
+    }
+
+    /// Get the public keys and amounts of our unspent TxOuts, as of the last
+    /// balance check. Any of them can be offered in a swap with
+    /// `build_swap_proposal_from_tx_out`.
+    pub fn get_unspent_tx_outs(&self) -> Vec<(CompressedRistrettoPublic, Amount)> {
+        self.tx_data.get_unspent_tx_out_amounts()
+    }
+
     /// Builds a signed contingent input that offers to trade "this" amount
     /// for "that" amount.
     ///
@@ -380,7 +452,47 @@ impl Client {
 
         // Only one input can be used, otherwise defragmentation is required
         let inputs = self.tx_data.get_transaction_inputs(offered, 1)?;
-        let inputs: Vec<(OwnedTxOut, TxOutMembershipProof)> = self.get_proofs(&inputs)?;
+        assert_eq!(inputs.len(), 1);
+
+        self.build_swap_proposal_helper(inputs[0].clone(), offered, requested, rng)
+    }
+
+    /// Builds a signed contingent input that offers the whole amount of one of
+    /// our unspent TxOuts, recovered from fog, for "that" amount.
+    ///
+    /// # Arguments
+    /// * `tx_out_public_key` - The public key of the TxOut that we are
+    ///   offering, as returned by `get_unspent_tx_outs`
+    /// * `requested` - The amount that we want in return
+    /// * `rng` - Randomness.
+    pub fn build_swap_proposal_from_tx_out<T: RngCore + CryptoRng>(
+        &mut self,
+        tx_out_public_key: &CompressedRistrettoPublic,
+        requested: Amount,
+        rng: &mut T,
+    ) -> Result<SignedContingentInput> {
+        mc_common::trace_time!(
+            self.logger,
+            "MobileCoinClient.build_swap_proposal_from_tx_out"
+        );
+
+        let input = self.tx_data.get_unspent_tx_out(tx_out_public_key)?;
+        let offered = input.amount;
+
+        self.build_swap_proposal_helper(input, offered, requested, rng)
+    }
+
+    /// Helper: Builds a signed contingent input spending `input`, that offers
+    /// to trade the offered part of it for the requested amount, and requires
+    /// the rest of it as change.
+    fn build_swap_proposal_helper<T: RngCore + CryptoRng>(
+        &mut self,
+        input: OwnedTxOut,
+        offered: Amount,
+        requested: Amount,
+        rng: &mut T,
+    ) -> Result<SignedContingentInput> {
+        let inputs: Vec<(OwnedTxOut, TxOutMembershipProof)> = self.get_proofs(&[input])?;
         let rings: Vec<Vec<(TxOut, TxOutMembershipProof)>> = self.get_rings(&inputs, rng)?;
 
         assert_eq!(inputs.len(), 1);
@@ -393,11 +505,11 @@ impl Client {
         let input_amount = input.amount;
         assert!(
             offered.token_id == input_amount.token_id,
-            "get_transaction_inputs post condition failed"
+            "swap proposal input has the wrong token id"
         );
         assert!(
             offered.value <= input_amount.value,
-            "get_transaction_inputs post condition failed"
+            "swap proposal input is smaller than the offered amount"
         );
         let change = Amount::new(input_amount.value - offered.value, offered.token_id);
 
@@ -861,6 +973,7 @@ impl Client {
 /// * `tombstone_block` - The block index after which this transaction is no
 ///   longer valid.
 /// * `rng` -
+/// * `fee` - The transaction fee, in the token of `amount`.
 fn build_transaction_helper<T: RngCore + CryptoRng>(
     block_version: BlockVersion,
     inputs: Vec<(OwnedTxOut, TxOutMembershipProof)>,
@@ -900,6 +1013,18 @@ fn build_transaction_helper<T: RngCore + CryptoRng>(
         )?
     };
 
+    // The fee and the change are in the token of the amount, so every input must
+    // be in it too
+    if let Some((txo, _)) = inputs
+        .iter()
+        .find(|(txo, _)| txo.amount.token_id != amount.token_id)
+    {
+        return Err(Error::InputTokenMismatch(
+            txo.amount.token_id,
+            amount.token_id,
+        ));
+    }
+
     // Check amount found, calculate change
     let input_amount = inputs
         .iter()
@@ -1050,6 +1175,75 @@ mod test_build_transaction_helper {
         }
     }
 
+    // Create `num_inputs` TxOuts of `amount` owned by `account_key`, with
+    // (empty) membership proofs
+    fn create_inputs(
+        block_version: BlockVersion,
+        account_key: &AccountKey,
+        amount: Amount,
+        num_inputs: usize,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Vec<(OwnedTxOut, TxOutMembershipProof)> {
+        let recipient_and_amount = (0..num_inputs)
+            .map(|_| (account_key.default_subaddress(), amount))
+            .collect::<Vec<_>>();
+        let outputs = get_outputs(block_version, &recipient_and_amount, rng);
+
+        outputs
+            .into_iter()
+            .map(|tx_out| {
+                let fog_tx_out = FogTxOut::from(&tx_out);
+                let meta = FogTxOutMetadata::default();
+                let txo_record = TxOutRecord::new(fog_tx_out, meta);
+
+                let tx_out_target_key = RistrettoPublic::try_from(&tx_out.target_key).unwrap();
+                let tx_public_key = RistrettoPublic::try_from(&tx_out.public_key).unwrap();
+
+                let subaddress_spk = recover_public_subaddress_spend_key(
+                    account_key.view_private_key(),
+                    &tx_out_target_key,
+                    &tx_public_key,
+                );
+                let spsk_to_index =
+                    HashMap::from_iter(vec![(subaddress_spk, DEFAULT_SUBADDRESS_INDEX)]);
+
+                let owned_tx_out =
+                    OwnedTxOut::new(txo_record, account_key, &spsk_to_index).unwrap();
+
+                let proof = TxOutMembershipProof::new(0, 0, Default::default());
+
+                (owned_tx_out, proof)
+            })
+            .collect()
+    }
+
+    // Create `num_rings` rings of `ring_size` TxOuts, with (empty) membership
+    // proofs
+    fn create_rings(
+        block_version: BlockVersion,
+        recipient: &PublicAddress,
+        num_rings: usize,
+        ring_size: usize,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Vec<Vec<(TxOut, TxOutMembershipProof)>> {
+        (0..num_rings)
+            .map(|_| {
+                let recipient_and_amount = (0..ring_size)
+                    .map(|_| (recipient.clone(), Amount::new(33, Mob::ID)))
+                    .collect::<Vec<_>>();
+                let ring = get_outputs(block_version, &recipient_and_amount, rng);
+                assert_eq!(ring.len(), ring_size);
+
+                ring.into_iter()
+                    .map(|tx_out| {
+                        let membership_proof = TxOutMembershipProof::new(0, 0, Default::default());
+                        (tx_out, membership_proof)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
     // `build_transaction_helper` should return a Tx when `rings` contains TxOuts
     // that do not appear in `inputs`.
     #[test_with_logger]
@@ -1067,73 +1261,24 @@ mod test_build_transaction_helper {
             let ring_size = 1;
 
             // Create inputs.
-            let inputs = {
-                let recipient_and_amount = (0..num_inputs)
-                    .map(|_| (sender_public_address.clone(), initial_amount))
-                    .collect::<Vec<_>>();
-                let outputs = get_outputs(block_version, &recipient_and_amount, &mut rng);
-
-                let cached_inputs: Vec<(OwnedTxOut, TxOutMembershipProof)> = outputs
-                    .into_iter()
-                    .map(|tx_out| {
-                        let fog_tx_out = FogTxOut::from(&tx_out);
-                        let meta = FogTxOutMetadata::default();
-                        let txo_record = TxOutRecord::new(fog_tx_out, meta);
-
-                        let tx_out_target_key =
-                            RistrettoPublic::try_from(&tx_out.target_key).unwrap();
-                        let tx_public_key = RistrettoPublic::try_from(&tx_out.public_key).unwrap();
-
-                        let subaddress_spk = recover_public_subaddress_spend_key(
-                            sender_account_key.view_private_key(),
-                            &tx_out_target_key,
-                            &tx_public_key,
-                        );
-                        let spsk_to_index =
-                            HashMap::from_iter(vec![(subaddress_spk, DEFAULT_SUBADDRESS_INDEX)]);
-
-                        let owned_tx_out =
-                            OwnedTxOut::new(txo_record, &sender_account_key, &spsk_to_index)
-                                .unwrap();
-
-                        let proof = TxOutMembershipProof::new(0, 0, Default::default());
-
-                        (owned_tx_out, proof)
-                    })
-                    .collect();
-
-                cached_inputs
-            };
-
+            let inputs = create_inputs(
+                block_version,
+                &sender_account_key,
+                initial_amount,
+                num_inputs,
+                &mut rng,
+            );
             assert_eq!(inputs.len(), num_inputs);
 
             // Create rings.
-            let mut rings: Vec<Vec<TxOut>> = Vec::new();
-            for _i in 0..num_inputs {
-                let ring: Vec<TxOut> = {
-                    let recipient_and_amount = (0..ring_size)
-                        .map(|_| (sender_public_address.clone(), Amount::new(33, Mob::ID)))
-                        .collect::<Vec<_>>();
-                    get_outputs(block_version, &recipient_and_amount, &mut rng)
-                };
-                assert_eq!(ring.len(), ring_size);
-                rings.push(ring);
-            }
-
-            assert_eq!(inputs.len(), rings.len());
-
-            let mut rings_and_membership_proofs: Vec<Vec<(TxOut, TxOutMembershipProof)>> =
-                Vec::new();
-            for ring in rings.into_iter() {
-                let ring_with_proofs = ring
-                    .into_iter()
-                    .map(|tx_out| {
-                        let membership_proof = TxOutMembershipProof::new(0, 0, Default::default());
-                        (tx_out, membership_proof)
-                    })
-                    .collect();
-                rings_and_membership_proofs.push(ring_with_proofs);
-            }
+            let rings_and_membership_proofs = create_rings(
+                block_version,
+                &sender_public_address,
+                num_inputs,
+                ring_size,
+                &mut rng,
+            );
+            assert_eq!(inputs.len(), rings_and_membership_proofs.len());
 
             let recipient_account_key = AccountKey::random(&mut rng);
 
@@ -1171,6 +1316,100 @@ mod test_build_transaction_helper {
         }
     }
 
+    // `build_transaction_helper` should pay the fee in the token being sent.
+    #[test_with_logger]
+    fn test_build_transaction_helper_pays_fee_in_sent_token(logger: Logger) {
+        let mut rng = get_seeded_rng();
+        let token_id = TokenId::from(2);
+
+        for block_version in BlockVersion::iterator() {
+            if !block_version.masked_token_id_feature_is_supported() {
+                continue;
+            }
+
+            let sender_account_key = AccountKey::random(&mut rng);
+            let inputs = create_inputs(
+                block_version,
+                &sender_account_key,
+                Amount::new(1000, token_id),
+                2,
+                &mut rng,
+            );
+            let rings = create_rings(
+                block_version,
+                &sender_account_key.default_subaddress(),
+                2,
+                1,
+                &mut rng,
+            );
+
+            let tx = build_transaction_helper(
+                block_version,
+                inputs,
+                rings,
+                Amount::new(1500, token_id),
+                &sender_account_key,
+                &AccountKey::random(&mut rng).default_subaddress(),
+                super::BlockIndex::max_value(),
+                FakeAcctResolver {},
+                &LocalRingSigner::from(&sender_account_key),
+                &mut rng,
+                &logger,
+                128,
+            )
+            .unwrap();
+
+            assert_eq!(tx.prefix.fee_token_id, *token_id);
+            assert_eq!(tx.prefix.fee, 128);
+        }
+    }
+
+    // `build_transaction_helper` should not spend inputs of another token than
+    // the one being sent.
+    #[test_with_logger]
+    fn test_build_transaction_helper_rejects_inputs_of_other_token(logger: Logger) {
+        let mut rng = get_seeded_rng();
+        let block_version = BlockVersion::MAX;
+
+        let sender_account_key = AccountKey::random(&mut rng);
+        let inputs = create_inputs(
+            block_version,
+            &sender_account_key,
+            Amount::new(1000 * MILLIMOB_TO_PICOMOB, TokenId::from(2)),
+            1,
+            &mut rng,
+        );
+        let rings = create_rings(
+            block_version,
+            &sender_account_key.default_subaddress(),
+            1,
+            1,
+            &mut rng,
+        );
+
+        let result = build_transaction_helper(
+            block_version,
+            inputs,
+            rings,
+            Amount::new(MILLIMOB_TO_PICOMOB, Mob::ID),
+            &sender_account_key,
+            &AccountKey::random(&mut rng).default_subaddress(),
+            super::BlockIndex::max_value(),
+            FakeAcctResolver {},
+            &LocalRingSigner::from(&sender_account_key),
+            &mut rng,
+            &logger,
+            Mob::MINIMUM_FEE,
+        );
+        match result {
+            Err(Error::InputTokenMismatch(input_token_id, token_id)) => {
+                assert_eq!(input_token_id, TokenId::from(2));
+                assert_eq!(token_id, Mob::ID);
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
     #[test]
     #[ignore]
     // `build_transaction_helper` should return a Tx when `rings` contains an input.
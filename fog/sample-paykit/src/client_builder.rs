@@ -2,7 +2,13 @@
 
 //! Client Builder
 
-use crate::client::Client;
+use crate::{
+    chunked_view_client::{
+        ChunkedFogViewClient, DEFAULT_MAX_CONCURRENT_VIEW_QUERIES,
+        DEFAULT_MAX_SEARCH_KEYS_PER_VIEW_QUERY,
+    },
+    client::Client,
+};
 use grpcio::EnvBuilder;
 use mc_account_keys::{AccountKey, PublicAddress};
 use mc_attest_verifier::{MrSignerVerifier, Verifier, DEBUG_ENCLAVE};
@@ -34,6 +40,10 @@ pub struct ClientBuilder {
     // Optional, has sane defaults
     ring_size: usize,
 
+    // Optional, has sane defaults
+    max_search_keys_per_view_query: usize,
+    max_concurrent_view_queries: usize,
+
     // Uris to fog services
     fog_view_address: FogViewUri,
     ledger_server_address: FogLedgerUri,
@@ -63,6 +73,8 @@ impl ClientBuilder {
             logger,
             grpc_retry_config: Default::default(),
             ring_size: RING_SIZE,
+            max_search_keys_per_view_query: DEFAULT_MAX_SEARCH_KEYS_PER_VIEW_QUERY,
+            max_concurrent_view_queries: DEFAULT_MAX_CONCURRENT_VIEW_QUERIES,
             fog_view_address,
             ledger_server_address,
             address_book: Default::default(),
@@ -87,6 +99,22 @@ impl ClientBuilder {
         self
     }
 
+    /// Sets the most fog search keys sent in one fog view query. Balance
+    /// checks which need more are split across several queries.
+    #[must_use]
+    pub fn max_search_keys_per_view_query(mut self, max_search_keys: usize) -> Self {
+        self.max_search_keys_per_view_query = max_search_keys;
+        self
+    }
+
+    /// Sets the number of fog view queries which are in flight at once, when
+    /// a balance check is split across several queries.
+    #[must_use]
+    pub fn max_concurrent_view_queries(mut self, max_concurrent_queries: usize) -> Self {
+        self.max_concurrent_view_queries = max_concurrent_queries;
+        self
+    }
+
     /// Sets the address book for the client, used with memos
     #[must_use]
     pub fn address_book(mut self, address_book: Vec<PublicAddress>) -> Self {
@@ -183,18 +211,28 @@ impl ClientBuilder {
         )
     }
 
-    // Build a Fog View connection, taking into account acct_host_override
+    // Build the Fog View connections, taking into account acct_host_override
     // and default port
-    fn build_fog_view_conn(&self, grpc_env: Arc<grpcio::Environment>) -> FogViewGrpcClient {
+    fn build_fog_view_conn(&self, grpc_env: Arc<grpcio::Environment>) -> ChunkedFogViewClient {
         let verifier = self.get_fog_view_verifier();
 
         log::debug!(self.logger, "Fog view attestation verifier: {:?}", verifier);
 
-        FogViewGrpcClient::new(
-            self.fog_view_address.clone(),
-            self.grpc_retry_config,
-            verifier,
-            grpc_env,
+        let conns = (0..self.max_concurrent_view_queries.max(1))
+            .map(|_| {
+                FogViewGrpcClient::new(
+                    self.fog_view_address.clone(),
+                    self.grpc_retry_config,
+                    verifier.clone(),
+                    grpc_env.clone(),
+                    self.logger.clone(),
+                )
+            })
+            .collect();
+
+        ChunkedFogViewClient::new(
+            conns,
+            self.max_search_keys_per_view_query,
             self.logger.clone(),
         )
     }
@@ -5,7 +5,7 @@
 use displaydoc::Display;
 use mc_connection::Error as ConnectionError;
 use mc_consensus_api::ConversionError;
-use mc_crypto_keys::KeyError;
+use mc_crypto_keys::{CompressedRistrettoPublic, KeyError};
 use mc_fog_enclave_connection::Error as EnclaveConnectionError;
 use mc_fog_ledger_connection::{Error as LedgerConnectionError, KeyImageQueryError};
 use mc_fog_report_connection::Error as FogResolutionError;
@@ -13,7 +13,7 @@ use mc_fog_types::view::FogTxOutError;
 use mc_fog_view_protocol::TxOutPollingError;
 use mc_transaction_core::{
     validation::TransactionValidationError, AmountError, BlockVersionError,
-    SignedContingentInputError,
+    SignedContingentInputError, TokenId,
 };
 use mc_transaction_std::{SignedContingentInputBuilderError, TxBuilderError};
 use mc_util_uri::UriParseError;
@@ -85,6 +85,12 @@ pub enum Error {
     /// Insufficient funds available or given
     InsufficientFunds,
 
+    /// Input of token id {0} cannot be spent to send token id {1}
+    InputTokenMismatch(TokenId, TokenId),
+
+    /// Consensus has no minimum fee for token id {0}, so it does not accept it
+    NoMinimumFee(TokenId),
+
     /// Wallet Compacting needed, recommended self-payment amount: {0}
     WalletCompactingNeeded(u64),
 
@@ -135,6 +141,9 @@ pub enum Error {
 
     /// Fog merkle proof: {0}
     FogMerkleProof(String),
+
+    /// No unspent TxOut with public key {0:?}
+    UnspentTxOutNotFound(CompressedRistrettoPublic),
 }
 
 impl From<ConnectionError> for Error {
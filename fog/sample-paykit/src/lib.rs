@@ -19,20 +19,26 @@ mod autogenerated_code {
 pub use autogenerated_code::*;
 
 mod cached_tx_data;
+mod chunked_view_client;
 mod client;
 mod client_builder;
 mod error;
 
 pub use crate::{
+    chunked_view_client::{
+        ChunkedFogViewClient, DEFAULT_MAX_CONCURRENT_VIEW_QUERIES,
+        DEFAULT_MAX_SEARCH_KEYS_PER_VIEW_QUERY,
+    },
     client::Client,
     client_builder::ClientBuilder,
     error::{Error, Result, TxOutMatchingError},
 };
-pub use cached_tx_data::MemoHandlerError;
-pub use mc_account_keys::{AccountKey, PublicAddress};
+pub use cached_tx_data::{MemoHandlerError, ReceivedTxOut};
+pub use mc_account_keys::{AccountKey, PublicAddress, ShortAddressHash};
 pub use mc_blockchain_types::BlockIndex;
 pub use mc_connection::BlockInfo;
 pub use mc_crypto_keys::{RistrettoPrivate, RistrettoPublic};
+pub use mc_fog_types::{common::BlockRange, BlockCount};
 pub use mc_transaction_core::{
     onetime_keys::recover_onetime_private_key,
     ring_signature::KeyImage,
@@ -40,6 +46,23 @@ pub use mc_transaction_core::{
     TokenId,
 };
 
+use std::collections::HashMap;
+
+/// A balance check result, along with the block ranges it does not cover
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Balance {
+    /// Balances (for all token types) (in picomob or equivalent)
+    pub balances: HashMap<TokenId, u64>,
+    /// Number of blocks in the chain at the time that this was the correct
+    /// balance
+    pub block_count: BlockCount,
+    /// Block ranges which fog view reported as missed, and which could not be
+    /// downloaded from fog ledger and scanned yet. The balance is computed as
+    /// of the start of the first of them, and they can be scanned again with
+    /// `Client::scan_unscanned_block_ranges`.
+    pub unscanned_block_ranges: Vec<BlockRange>,
+}
+
 /// A status that a submitted transaction can have
 pub enum TransactionStatus {
     /// The transaction has appeared at a particular block index
@@ -0,0 +1,184 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Verification of the X509 certificate chain in a fog report server response
+//! against the fog authority signature of a recipient.
+//!
+//! The recipient signs the subjectPublicKeyInfo of the root certificate of
+//! their fog operator. A response is only trusted if its chain is a valid
+//! chain up to a root whose subjectPublicKeyInfo carries that signature. The
+//! key of the leaf certificate is then the one which must have signed the
+//! reports in the response.
+
+#![deny(missing_docs)]
+
+use core::fmt::{Debug, Display};
+use displaydoc::Display;
+use mc_crypto_keys::KeyError;
+use mc_crypto_x509_utils::{ChainError, PublicKeyType, X509CertificateChain, X509KeyExtrator};
+use mc_fog_sig_authority::Verifier as AuthorityVerifier;
+use signature::{Error as SignatureError, Signature};
+use x509_signature::X509Certificate;
+
+/// An error which can occur when verifying a certificate chain against a fog
+/// authority signature
+#[derive(Debug, Display)]
+pub enum Error<A: Debug + Display> {
+    /// The public address does not have a fog authority signature
+    NoSignature,
+    /// The fog authority signature could not be parsed: {0}
+    SignatureParse(SignatureError),
+    /// The certificate chain is empty
+    EmptyChain,
+    /// Certificate {0} in the chain could not be parsed
+    CertificateParse(usize),
+    /// The certificate chain is not valid: {0}
+    Chain(ChainError),
+    /// The fog authority signature does not match the root certificate: {0}
+    Authority(A),
+    /// The leaf certificate's public key is not supported: {0}
+    Pubkey(KeyError),
+}
+
+impl<A: Debug + Display> From<ChainError> for Error<A> {
+    fn from(src: ChainError) -> Self {
+        match src {
+            ChainError::Empty => Error::EmptyChain,
+            other => Error::Chain(other),
+        }
+    }
+}
+
+/// Verify a certificate chain of DER-encoded certificates against a fog
+/// authority signature, and return the public key of the leaf certificate.
+///
+/// Arguments:
+/// * verifier: The key which made the fog authority signature, e.g. the
+///   recipient's public address
+/// * authority_sig: The fog authority signature, if there is one
+/// * chain: The DER-encoded certificates, from the leaf to the root
+pub fn verify_authority_chain<V: AuthorityVerifier>(
+    verifier: &V,
+    authority_sig: Option<&[u8]>,
+    chain: &[impl AsRef<[u8]>],
+) -> Result<PublicKeyType, Error<V::Error>> {
+    let authority_sig = authority_sig.ok_or(Error::NoSignature)?;
+    let authority_sig = V::Sig::from_bytes(authority_sig).map_err(Error::SignatureParse)?;
+
+    if chain.is_empty() {
+        return Err(Error::EmptyChain);
+    }
+    let certs = chain
+        .iter()
+        .enumerate()
+        .map(|(index, der)| {
+            x509_signature::parse_certificate(der.as_ref())
+                .map_err(|_| Error::CertificateParse(index))
+        })
+        .collect::<Result<Vec<X509Certificate>, _>>()?;
+
+    // Verify the chain and signature over the resulting authority
+    verifier
+        .verify_authority(
+            certs.verified_root()?.subject_public_key_info().spki(),
+            &authority_sig,
+        )
+        .map_err(Error::Authority)?;
+
+    certs.leaf()?.mc_public_key().map_err(Error::Pubkey)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_account_keys::{AccountKey, PublicAddress, RootIdentity};
+    use mc_crypto_x509_utils::X509CertificateIterable;
+    use rand_core::SeedableRng;
+    use rand_hc::Hc128Rng;
+
+    /// A public address which signed the root of the test chain, and the chain
+    /// as DER bytestrings
+    fn setup() -> (PublicAddress, Vec<Vec<u8>>) {
+        let (pem_chain, _keypair) = mc_crypto_x509_test_vectors::ok_rsa_chain_25519_leaf();
+        let der_chain = pem::parse_many(pem_chain).expect("Could not parse PEM chain");
+        let x509_chain = der_chain.iter_x509().collect::<Vec<X509Certificate>>();
+
+        let mut csprng = Hc128Rng::seed_from_u64(0);
+        let root_identity = RootIdentity::random_with_fog(
+            &mut csprng,
+            "fog://fog.unittest.mobilecoin.foundation",
+            "1",
+            x509_chain
+                .verified_root()
+                .expect("Could not verify test chain")
+                .subject_public_key_info()
+                .spki(),
+        );
+        let public_address = AccountKey::from(&root_identity).default_subaddress();
+
+        (
+            public_address,
+            der_chain.into_iter().map(|pem| pem.contents).collect(),
+        )
+    }
+
+    #[test]
+    fn success() {
+        let (public_address, chain) = setup();
+        let (_pem_chain, keypair) = mc_crypto_x509_test_vectors::ok_rsa_chain_25519_leaf();
+
+        match verify_authority_chain(&public_address, public_address.fog_authority_sig(), &chain)
+            .expect("Valid chain was rejected")
+        {
+            PublicKeyType::Ed25519(pubkey) => assert_eq!(pubkey, keypair.public_key()),
+        }
+    }
+
+    #[test]
+    fn missing_signature() {
+        let (public_address, chain) = setup();
+        assert!(matches!(
+            verify_authority_chain(&public_address, None, &chain),
+            Err(Error::NoSignature)
+        ));
+    }
+
+    #[test]
+    fn empty_chain() {
+        let (public_address, _chain) = setup();
+        assert!(matches!(
+            verify_authority_chain(
+                &public_address,
+                public_address.fog_authority_sig(),
+                &Vec::<Vec<u8>>::new()
+            ),
+            Err(Error::EmptyChain)
+        ));
+    }
+
+    #[test]
+    fn garbage_certificate() {
+        let (public_address, mut chain) = setup();
+        chain[1] = vec![1, 2, 3];
+        assert!(matches!(
+            verify_authority_chain(&public_address, public_address.fog_authority_sig(), &chain),
+            Err(Error::CertificateParse(1))
+        ));
+    }
+
+    #[test]
+    fn other_authority() {
+        let (_public_address, chain) = setup();
+        let mut csprng = Hc128Rng::seed_from_u64(1);
+        let root_identity = RootIdentity::random_with_fog(
+            &mut csprng,
+            "fog://fog.unittest.mobilecoin.foundation",
+            "1",
+            b"not the root spki",
+        );
+        let other_address = AccountKey::from(&root_identity).default_subaddress();
+        assert!(matches!(
+            verify_authority_chain(&other_address, other_address.fog_authority_sig(), &chain),
+            Err(Error::Authority(_))
+        ));
+    }
+}
@@ -48,10 +48,10 @@
 
 mod public_address;
 
+pub use mc_fog_sig_chain::{verify_authority_chain, Error as AuthorityChainError};
+
 use core::fmt::{Debug, Display};
 use displaydoc::Display;
-use mc_crypto_keys::KeyError;
-use mc_crypto_x509_utils::ChainError;
 use mc_fog_report_types::ReportResponse;
 use mc_fog_sig_authority::Verifier as AuthorityVerifier;
 use signature::Error as SignatureError;
@@ -59,16 +59,11 @@ use signature::Error as SignatureError;
 /// An eneumeration of errors which can occur when verifying a signature set.
 #[derive(Debug, Display)]
 pub enum Error<A: Debug + Display, R: Debug + Display> {
-    /// The public address does not have a fog authority signature
-    NoSignature,
-    /// There was an error parsing the signature
+    /// There was an error verifying the chain against the authority signature:
+    /// {0}
+    AuthorityChain(AuthorityChainError<A>),
+    /// There was an error parsing the report signature
     SignatureParse(SignatureError),
-    /// There as an error verifying the authority signature: {0}
-    Authority(A),
-    /// There was an error parsing or verifying the chain: {0}
-    Chain(ChainError),
-    /// There was an error parsing a public key: {0}
-    Pubkey(KeyError),
     /// There was an error verifying the report signature: {0}
     Report(R),
 }
@@ -79,9 +74,9 @@ impl<A: Debug + Display, R: Debug + Display> From<SignatureError> for Error<A, R
     }
 }
 
-impl<A: Debug + Display, R: Debug + Display> From<ChainError> for Error<A, R> {
-    fn from(src: ChainError) -> Self {
-        Error::Chain(src)
+impl<A: Debug + Display, R: Debug + Display> From<AuthorityChainError<A>> for Error<A, R> {
+    fn from(src: AuthorityChainError<A>) -> Self {
+        Error::AuthorityChain(src)
     }
 }
 
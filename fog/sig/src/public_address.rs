@@ -6,14 +6,12 @@
 use crate::{Error, Verifier};
 use mc_account_keys::PublicAddress;
 use mc_crypto_keys::Ed25519Signature;
-use mc_crypto_x509_utils::{
-    PublicKeyType, X509CertificateChain, X509CertificateIter, X509KeyExtrator,
-};
+use mc_crypto_x509_utils::PublicKeyType;
 use mc_fog_report_types::ReportResponse;
 use mc_fog_sig_authority::Verifier as AuthorityVerifier;
+use mc_fog_sig_chain::verify_authority_chain;
 use mc_fog_sig_report::Verifier as ReportVerifier;
 use signature::{Error as SignatureError, Signature};
-use x509_signature::X509Certificate;
 
 impl Verifier for PublicAddress {
     type ReportSigError = SignatureError;
@@ -22,32 +20,11 @@ impl Verifier for PublicAddress {
         &self,
         report_response: &ReportResponse,
     ) -> Result<(), Error<<Self as AuthorityVerifier>::Error, Self::ReportSigError>> {
-        // Vec<Vec<u8>> -> Vec<&[u8]>
-        // Vec<&[u8]> -> Vec<X509Certificate>
-        let certs = X509CertificateIter::from(
-            report_response
-                .chain
-                .iter()
-                .map(|der| der.as_slice())
-                .collect::<Vec<&[u8]>>(),
-        )
-        .collect::<Vec<X509Certificate>>();
-
-        // Get the authority signature
-        let authority_sig = self
-            .fog_authority_sig()
-            .ok_or(Error::NoSignature)?
-            .try_into()?;
-
-        // Verify the chain and signature over the resulting authority
-        self.verify_authority(
-            certs.verified_root()?.subject_public_key_info().spki(),
-            &authority_sig,
-        )
-        .map_err(Error::Authority)?;
+        let leaf_pubkey =
+            verify_authority_chain(self, self.fog_authority_sig(), &report_response.chain)?;
 
         // Verify the signature over the reports matches the leaf cert in the chain
-        match certs.leaf()?.mc_public_key().map_err(Error::Pubkey)? {
+        match leaf_pubkey {
             PublicKeyType::Ed25519(pubkey) => {
                 let sig = Ed25519Signature::from_bytes(&report_response.signature)
                     .map_err(Error::SignatureParse)?;
@@ -65,11 +42,12 @@ mod tests {
     use mc_account_keys::{AccountKey, RootIdentity};
     use mc_attest_core::VerificationReport;
     use mc_crypto_keys::Ed25519Pair;
-    use mc_crypto_x509_utils::X509CertificateIterable;
+    use mc_crypto_x509_utils::{X509CertificateChain, X509CertificateIterable};
     use mc_fog_report_types::Report;
     use mc_fog_sig_report::Signer;
     use rand_core::SeedableRng;
     use rand_hc::Hc128Rng;
+    use x509_signature::X509Certificate;
 
     /// Setup a functional fog authority scheme.
     ///
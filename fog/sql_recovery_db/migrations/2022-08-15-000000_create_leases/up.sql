@@ -0,0 +1,18 @@
+-- Copyright (c) 2018-2022 The MobileCoin Foundation
+
+-- Leases, used by services that run several replicas to elect the one replica that is allowed to act,
+-- e.g. the Fog Overseer replica that performs ingest failover.
+CREATE TABLE leases (
+    -- The name of the lease
+    name VARCHAR(64) PRIMARY KEY,
+
+    -- The id of the replica holding the lease
+    holder VARCHAR(256) NOT NULL,
+
+    -- The lease is free once this time is reached, unless the holder renews it before
+    expires_at TIMESTAMP NOT NULL,
+
+    -- The fencing token of the lease, which is incremented each time the lease is taken rather than
+    -- renewed. Released leases are expired rather than deleted, so that it never decreases.
+    token BIGINT NOT NULL
+);
@@ -0,0 +1,7 @@
+-- Copyright (c) 2018-2022 The MobileCoin Foundation
+
+ALTER TABLE reports DROP CONSTRAINT reports__fog_report_id__ingress_public_key;
+
+-- Only keep the most recently created report of each fog_report_id
+DELETE FROM reports a USING reports b WHERE a.fog_report_id = b.fog_report_id AND a.id < b.id;
+ALTER TABLE reports ADD CONSTRAINT reports_fog_report_id_key UNIQUE (fog_report_id);
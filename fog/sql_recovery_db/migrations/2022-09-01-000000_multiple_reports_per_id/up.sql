@@ -0,0 +1,7 @@
+-- Copyright (c) 2018-2022 The MobileCoin Foundation
+
+-- Allow a report for each ingress key under the same fog_report_id, so that a new ingress key can be
+-- published while clients still use the previous one, and so that several ingest servers can publish
+-- under the same fog_report_id.
+ALTER TABLE reports DROP CONSTRAINT reports_fog_report_id_key;
+ALTER TABLE reports ADD CONSTRAINT reports__fog_report_id__ingress_public_key UNIQUE (fog_report_id, ingress_public_key);
@@ -0,0 +1,6 @@
+-- Copyright (c) 2018-2022 The MobileCoin Foundation
+
+ALTER TABLE user_events SET (autovacuum_enabled = false, toast.autovacuum_enabled = false);
+ALTER TABLE ingested_blocks SET (autovacuum_enabled = false, toast.autovacuum_enabled = false);
+
+DROP TABLE view_shard_progress;
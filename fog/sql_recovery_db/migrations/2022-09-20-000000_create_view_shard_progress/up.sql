@@ -0,0 +1,18 @@
+-- Copyright (c) 2018-2022 The MobileCoin Foundation
+
+-- How far each Fog View shard has loaded the ETxOutRecords of the recovery db, so that pruning never
+-- removes records which an active shard has not loaded yet.
+CREATE TABLE view_shard_progress (
+    -- The id of the Fog View shard
+    shard_id VARCHAR(256) PRIMARY KEY,
+
+    -- The shard has loaded every ETxOutRecord of the blocks below this block count
+    highest_processed_block_count BIGINT NOT NULL,
+
+    -- When the shard last reported its progress
+    updated_at TIMESTAMP NOT NULL
+);
+
+-- Pruning deletes rows from these tables, so they are no longer append-only
+ALTER TABLE user_events SET (autovacuum_enabled = true, toast.autovacuum_enabled = true);
+ALTER TABLE ingested_blocks SET (autovacuum_enabled = true, toast.autovacuum_enabled = true);
@@ -2,23 +2,79 @@
 
 //! A helper utility for running migrations on a database configured via
 //! DATABASE_URL.
+//!
+//! The migrations are run while holding a PostgreSQL advisory lock, so that
+//! two deployments starting at the same time can't race on schema changes.
+//! With --dry-run, the pending migrations and the table locks they would take
+//! are printed, and nothing is run.
 
 #[macro_use]
 extern crate diesel_migrations;
 
+use clap::Parser;
 use diesel::{prelude::*, PgConnection};
 use diesel_migrations::embed_migrations;
-use std::env;
+use mc_fog_sql_recovery_db::migrations::{
+    estimated_rows, lock_impacts, lock_migrations, pending_migrations, try_lock_migrations,
+    unlock_migrations, MIGRATION_LOCK_KEY,
+};
+use std::io::stdout;
 
 embed_migrations!("migrations/");
 
+/// Command line configuration of the recovery db migrations utility
+#[derive(Clone, Debug, Parser)]
+#[clap(version)]
+struct Config {
+    /// The PostgreSQL database url
+    #[clap(long, env = "DATABASE_URL")]
+    database_url: String,
+
+    /// Print the pending migrations and the table locks they would take,
+    /// without running them
+    #[clap(long)]
+    dry_run: bool,
+}
+
 fn main() {
-    let database_url = env::var("DATABASE_URL").expect("Missing DATABASE_URL environment variable");
+    let config = Config::parse();
 
-    let conn = PgConnection::establish(&database_url)
+    let conn = PgConnection::establish(&config.database_url)
         .expect("fog-sql-recovery-db-migrations cannot connect to PG database");
 
-    embedded_migrations::run(&conn).expect("Failed running migrations");
+    let pending = pending_migrations(&conn).expect("Failed getting pending migrations");
+    if pending.is_empty() {
+        println!("No pending migrations");
+    }
+    for migration in &pending {
+        println!("Pending migration {}", migration.name);
+        for impact in lock_impacts(migration.up_sql) {
+            let rows = estimated_rows(&conn, &impact.table).expect("Failed estimating table rows");
+            match rows {
+                Some(rows) => println!("    {} (about {} rows)", impact, rows),
+                None => println!("    {}", impact),
+            }
+        }
+    }
+
+    if config.dry_run {
+        println!("Dry run, not running migrations");
+        return;
+    }
+
+    if !try_lock_migrations(&conn).expect("Failed taking migrations lock") {
+        println!(
+            "Waiting for another migration to finish (advisory lock {})",
+            MIGRATION_LOCK_KEY
+        );
+        lock_migrations(&conn).expect("Failed taking migrations lock");
+    }
+
+    // Whoever held the lock may have run some of the migrations, which diesel
+    // skips
+    embedded_migrations::run_with_output(&conn, &mut stdout()).expect("Failed running migrations");
+
+    unlock_migrations(&conn).expect("Failed releasing migrations lock");
 
     println!("Done migrating Fog recovery DB!");
 }
@@ -0,0 +1,73 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! A utility for pruning expired data from a recovery db configured via
+//! DATABASE_URL.
+//!
+//! The ETxOutRecords and rng records of the decommissioned ingest invocations
+//! which were last active longer ago than the horizon are removed, as long as
+//! no active Fog View shard still needs them. With --dry-run, what would be
+//! pruned is printed, and nothing is deleted.
+
+use clap::Parser;
+use mc_common::logger::create_null_logger;
+use mc_fog_sql_recovery_db::{pruning::PruneConfig, SqlRecoveryDb, SqlRecoveryDbConnectionConfig};
+
+/// Command line configuration of the recovery db pruning utility
+#[derive(Clone, Debug, Parser)]
+#[clap(version)]
+struct Config {
+    /// The PostgreSQL database url
+    #[clap(long, env = "DATABASE_URL")]
+    database_url: String,
+
+    /// What to prune
+    #[clap(flatten)]
+    prune: PruneConfig,
+
+    /// Postgres connection config
+    #[clap(flatten)]
+    postgres_config: SqlRecoveryDbConnectionConfig,
+}
+
+fn main() {
+    let config = Config::parse();
+
+    let db = SqlRecoveryDb::new_from_url(
+        &config.database_url,
+        config.postgres_config,
+        create_null_logger(),
+    )
+    .expect("fog-sql-recovery-db-prune cannot connect to PG database");
+
+    let summary = db
+        .prune_expired_data(&config.prune)
+        .expect("Failed pruning expired data");
+
+    match summary.view_block_count {
+        Some(view_block_count) => println!(
+            "Active Fog View shards have processed {} blocks",
+            view_block_count
+        ),
+        None => {
+            println!("No Fog View shard reported its progress recently, not pruning");
+            return;
+        }
+    }
+
+    for invocation in &summary.pruned_invocations {
+        println!("Ingest invocation {}", invocation);
+    }
+    if config.prune.dry_run {
+        println!(
+            "Dry run, would prune {} ingest invocations and {} blocks",
+            summary.pruned_invocations.len(),
+            summary.pruned_blocks
+        );
+    } else {
+        println!(
+            "Pruned {} ingest invocations and {} blocks",
+            summary.pruned_invocations.len(),
+            summary.pruned_blocks
+        );
+    }
+}
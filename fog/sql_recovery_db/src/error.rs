@@ -1,6 +1,7 @@
 // Copyright (c) 2018-2022 The MobileCoin Foundation
 
 use diesel::{result::Error as DieselError, ConnectionError};
+use diesel_migrations::RunMigrationsError;
 use displaydoc::Display;
 use mc_crypto_keys::CompressedRistrettoPublic;
 use mc_fog_recovery_db_iface::RecoveryDbError;
@@ -20,6 +21,9 @@ pub enum Error {
     /// Connection error: {0}
     Connection(ConnectionError),
 
+    /// Migration error: {0}
+    Migration(RunMigrationsError),
+
     /// The following ingress key was not found: {0:?}
     MissingIngressKey(CompressedRistrettoPublic),
 
@@ -49,15 +53,22 @@ pub enum Error {
 
     /// The data could not be encoded for storage in the database: {0:?}
     Encode(EncodeError),
+
+    /// Overseer leases are not supported by the SQLite recovery db
+    LeasesNotSupported,
 }
 
 impl RecoveryDbError for Error {
     /// Policy decision, whether the call should be retried.
+    ///
+    /// SQLite allows one writer at a time, and a writer which keeps waiting
+    /// for the lock past the busy timeout gets a "database is locked" error.
     fn should_retry(&self) -> bool {
         match self {
             Self::Orm(DieselError::DatabaseError(_, info)) => {
                 info.message() == "no connection to the server\n"
                     || info.message() == "terminating connection due to administrator command"
+                    || info.message().contains("database is locked")
             }
             Self::R2d2(_) => true,
             _ => false,
@@ -83,6 +94,12 @@ impl From<ConnectionError> for Error {
     }
 }
 
+impl From<RunMigrationsError> for Error {
+    fn from(src: RunMigrationsError) -> Self {
+        Self::Migration(src)
+    }
+}
+
 impl From<DecodeError> for Error {
     fn from(src: DecodeError) -> Self {
         Self::Decode(src)
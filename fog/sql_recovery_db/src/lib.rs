@@ -10,13 +10,22 @@ extern crate diesel_migrations;
 
 pub use error::Error;
 
+pub mod migrations;
+pub mod pruning;
 pub mod test_utils;
 
 mod error;
-mod models;
-mod proto_types;
-mod schema;
-mod sql_types;
+
+// These are shared with the mc-fog-sqlite-recovery-db crate, which stores the
+// same tables in SQLite.
+#[doc(hidden)]
+pub mod models;
+#[doc(hidden)]
+pub mod proto_types;
+#[doc(hidden)]
+pub mod schema;
+#[doc(hidden)]
+pub mod sql_types;
 
 use crate::sql_types::{SqlCompressedRistrettoPublic, UserEventType};
 use clap::Parser;
@@ -112,20 +121,28 @@ impl Default for SqlRecoveryDbConnectionConfig {
 /// SQL-backed recovery database.
 #[derive(Clone)]
 pub struct SqlRecoveryDb {
+    /// The connection pool of the primary database, used for writes and for
+    /// reads which must see them.
     pool: Pool<ConnectionManager<PgConnection>>,
+    /// The connection pool used by the fog-view and fog-report query paths.
+    /// This is a read replica of the primary database if one is configured,
+    /// and the primary pool otherwise.
+    read_pool: Pool<ConnectionManager<PgConnection>>,
     config: SqlRecoveryDbConnectionConfig,
     logger: Logger,
 }
 
 impl SqlRecoveryDb {
-    /// Create a new instance using a pre-existing connection pool.
+    /// Create a new instance using pre-existing connection pools.
     fn new(
         pool: Pool<ConnectionManager<PgConnection>>,
+        read_pool: Pool<ConnectionManager<PgConnection>>,
         config: SqlRecoveryDbConnectionConfig,
         logger: Logger,
     ) -> Self {
         Self {
             pool,
+            read_pool,
             config,
             logger,
         }
@@ -138,15 +155,43 @@ impl SqlRecoveryDb {
         config: SqlRecoveryDbConnectionConfig,
         logger: Logger,
     ) -> Result<Self, Error> {
+        Self::new_from_urls(database_url, None, config, logger)
+    }
+
+    /// Create a new instance using a database URL, an optional read replica
+    /// URL, and connection parameters. The parameters have sane defaults.
+    ///
+    /// The read replica serves the fog-view and fog-report queries, and every
+    /// write goes to the primary database. Reads from a replica may lag behind
+    /// the primary, so only processes which tolerate that, i.e. which don't
+    /// read back their own writes, should use one.
+    pub fn new_from_urls(
+        database_url: &str,
+        read_replica_url: Option<&str>,
+        config: SqlRecoveryDbConnectionConfig,
+        logger: Logger,
+    ) -> Result<Self, Error> {
+        let pool = Self::build_pool(database_url, &config)?;
+        let read_pool = match read_replica_url {
+            Some(read_replica_url) => Self::build_pool(read_replica_url, &config)?,
+            None => pool.clone(),
+        };
+        Ok(Self::new(pool, read_pool, config, logger))
+    }
+
+    // Helper function for building a connection pool
+    fn build_pool(
+        database_url: &str,
+        config: &SqlRecoveryDbConnectionConfig,
+    ) -> Result<Pool<ConnectionManager<PgConnection>>, Error> {
         let manager = ConnectionManager::<PgConnection>::new(database_url);
-        let pool = Pool::builder()
+        Ok(Pool::builder()
             .max_size(config.postgres_max_connections)
             .idle_timeout(Some(config.postgres_idle_timeout))
             .max_lifetime(Some(config.postgres_max_lifetime))
             .connection_timeout(config.postgres_connection_timeout)
             .test_on_check_out(true)
-            .build(manager)?;
-        Ok(Self::new(pool, config, logger))
+            .build(manager)?)
     }
 
     // Helper function for retries config
@@ -352,7 +397,7 @@ impl SqlRecoveryDb {
         start_block_at_least: u64,
         ingress_public_key_record_filters: &IngressPublicKeyRecordFilters,
     ) -> Result<Vec<IngressPublicKeyRecord>, Error> {
-        let conn = self.pool.get()?;
+        let conn = self.read_pool.get()?;
 
         use schema::ingress_keys::dsl;
         let last_scanned_block = diesel::dsl::sql::<diesel::sql_types::BigInt>(
@@ -668,7 +713,7 @@ impl SqlRecoveryDb {
     }
 
     fn get_missed_block_ranges_retriable(&self) -> Result<Vec<BlockRange>, Error> {
-        let conn = self.pool.get()?;
+        let conn = self.read_pool.get()?;
         self.get_missed_block_ranges_impl(&conn)
     }
 
@@ -681,7 +726,7 @@ impl SqlRecoveryDb {
             return Ok((Default::default(), i64::MAX));
         }
 
-        let conn = self.pool.get()?;
+        let conn = self.read_pool.get()?;
         let mut events: Vec<(i64, FogUserEvent)> = Vec::new();
 
         // Collect all events of interest
@@ -851,7 +896,7 @@ impl SqlRecoveryDb {
         start_block: u64,
         search_keys: &[Vec<u8>],
     ) -> Result<Vec<TxOutSearchResult>, Error> {
-        let conn = self.pool.get()?;
+        let conn = self.read_pool.get()?;
 
         let query = schema::ingested_blocks::dsl::ingested_blocks
             .filter(schema::ingested_blocks::dsl::block_number.ge(start_block as i64))
@@ -894,6 +939,106 @@ impl SqlRecoveryDb {
         self.update_last_active_at_impl(&conn, ingest_invocation_id)
     }
 
+    /// Take or extend a lease.
+    fn try_acquire_lease_retriable(
+        &self,
+        name: &str,
+        holder: &str,
+        duration: Duration,
+    ) -> Result<Option<u64>, Error> {
+        let conn = self.pool.get()?;
+
+        // The insert only takes over an existing lease if it expired, or if it is
+        // ours, in which case it is extended. This happens atomically, so two
+        // holders can never both succeed. Taking a lease rather than extending it
+        // increments its fencing token.
+        let tokens: Vec<models::LeaseToken> = diesel::sql_query(
+            "INSERT INTO leases (name, holder, expires_at, token) \
+             VALUES ($1, $2, NOW() + $3 * INTERVAL '1 millisecond', 1) \
+             ON CONFLICT (name) DO UPDATE \
+             SET holder = EXCLUDED.holder, expires_at = EXCLUDED.expires_at, \
+             token = CASE WHEN leases.holder = EXCLUDED.holder AND leases.expires_at > NOW() \
+             THEN leases.token ELSE leases.token + 1 END \
+             WHERE leases.holder = EXCLUDED.holder OR leases.expires_at <= NOW() \
+             RETURNING token",
+        )
+        .bind::<diesel::sql_types::Varchar, _>(name)
+        .bind::<diesel::sql_types::Varchar, _>(holder)
+        .bind::<diesel::sql_types::BigInt, _>(duration.as_millis() as i64)
+        .load(&conn)?;
+
+        Ok(tokens.first().map(|lease| lease.token as u64))
+    }
+
+    /// Check that a lease that did not expire has a given holder and token.
+    fn check_lease_retriable(&self, name: &str, holder: &str, token: u64) -> Result<bool, Error> {
+        let conn = self.pool.get()?;
+
+        use schema::leases::dsl;
+        Ok(dsl::leases
+            .filter(dsl::name.eq(name))
+            .filter(dsl::holder.eq(holder))
+            .filter(dsl::token.eq(token as i64))
+            .filter(dsl::expires_at.gt(diesel::dsl::now))
+            .select(dsl::name)
+            .first::<String>(&conn)
+            .optional()?
+            .is_some())
+    }
+
+    /// Release a lease held by a given holder. The lease is expired rather
+    /// than deleted, so that its fencing token keeps increasing.
+    fn release_lease_retriable(&self, name: &str, holder: &str) -> Result<(), Error> {
+        let conn = self.pool.get()?;
+
+        use schema::leases::dsl;
+        diesel::update(
+            dsl::leases
+                .filter(dsl::name.eq(name))
+                .filter(dsl::holder.eq(holder))
+                .filter(dsl::expires_at.gt(diesel::dsl::now)),
+        )
+        .set(dsl::expires_at.eq(diesel::dsl::now))
+        .execute(&conn)?;
+
+        Ok(())
+    }
+
+    /// Get the holder of a lease that did not expire.
+    fn get_lease_holder_retriable(&self, name: &str) -> Result<Option<String>, Error> {
+        let conn = self.pool.get()?;
+
+        use schema::leases::dsl;
+        Ok(dsl::leases
+            .filter(dsl::name.eq(name))
+            .filter(dsl::expires_at.gt(diesel::dsl::now))
+            .select(dsl::holder)
+            .first::<String>(&conn)
+            .optional()?)
+    }
+
+    /// Record the progress of a Fog View shard.
+    fn report_view_shard_progress_retriable(
+        &self,
+        shard_id: &str,
+        highest_processed_block_count: u64,
+    ) -> Result<(), Error> {
+        let conn = self.pool.get()?;
+
+        diesel::sql_query(
+            "INSERT INTO view_shard_progress (shard_id, highest_processed_block_count, updated_at) \
+             VALUES ($1, $2, NOW()) \
+             ON CONFLICT (shard_id) DO UPDATE \
+             SET highest_processed_block_count = EXCLUDED.highest_processed_block_count, \
+             updated_at = EXCLUDED.updated_at",
+        )
+        .bind::<diesel::sql_types::Varchar, _>(shard_id)
+        .bind::<diesel::sql_types::BigInt, _>(highest_processed_block_count as i64)
+        .execute(&conn)?;
+
+        Ok(())
+    }
+
     /// Get any ETxOutRecords produced by a given ingress key for a given
     /// block index.
     ///
@@ -909,7 +1054,7 @@ impl SqlRecoveryDb {
         ingress_key: CompressedRistrettoPublic,
         block_index: u64,
     ) -> Result<Option<Vec<ETxOutRecord>>, Error> {
-        let conn = self.pool.get()?;
+        let conn = self.read_pool.get()?;
 
         let key_bytes: &[u8] = ingress_key.as_ref();
         let query = schema::ingested_blocks::dsl::ingested_blocks
@@ -972,7 +1117,7 @@ impl SqlRecoveryDb {
         &self,
         block_index: u64,
     ) -> Result<Option<u64>, Error> {
-        let conn = self.pool.get()?;
+        let conn = self.read_pool.get()?;
 
         let query = schema::ingested_blocks::dsl::ingested_blocks
             .filter(schema::ingested_blocks::dsl::block_number.eq(block_index as i64))
@@ -1009,7 +1154,7 @@ impl SqlRecoveryDb {
         &self,
         block_index: u64,
     ) -> Result<Option<u64>, Error> {
-        let conn = self.pool.get()?;
+        let conn = self.read_pool.get()?;
 
         let query = schema::ingested_blocks::dsl::ingested_blocks
             .filter(schema::ingested_blocks::dsl::block_number.eq(block_index as i64))
@@ -1021,7 +1166,7 @@ impl SqlRecoveryDb {
 
     /// Get the highest block index for which we have any data at all.
     fn get_highest_known_block_index_retriable(&self) -> Result<Option<u64>, Error> {
-        let conn = self.pool.get()?;
+        let conn = self.read_pool.get()?;
         SqlRecoveryDb::get_highest_known_block_index_impl(&conn)
     }
 
@@ -1031,16 +1176,26 @@ impl SqlRecoveryDb {
     ////
 
     fn get_all_reports_retriable(&self) -> Result<Vec<(String, ReportData)>, Error> {
-        let conn = self.pool.get()?;
+        let conn = self.read_pool.get()?;
 
+        // Reports of lost ingress keys are not served, since no ingest server scans
+        // with them anymore. Within a report id, the report with the latest pubkey
+        // expiry comes first, so that clients which use the first matching report
+        // use the newest ingress key.
         let query = schema::reports::dsl::reports
+            .inner_join(schema::ingress_keys::dsl::ingress_keys)
+            .filter(schema::ingress_keys::dsl::lost.eq(false))
             .select((
                 schema::reports::dsl::ingest_invocation_id,
                 schema::reports::dsl::fog_report_id,
                 schema::reports::dsl::report,
                 schema::reports::dsl::pubkey_expiry,
             ))
-            .order_by(schema::reports::dsl::id);
+            .order_by((
+                schema::reports::dsl::fog_report_id,
+                schema::reports::dsl::pubkey_expiry.desc(),
+                schema::reports::dsl::id,
+            ));
 
         query
             .load::<(Option<i64>, String, Vec<u8>, i64)>(&conn)?
@@ -1059,7 +1214,7 @@ impl SqlRecoveryDb {
             .collect()
     }
 
-    /// Set report data associated with a given report id.
+    /// Set report data associated with a given report id and ingress key.
     fn set_report_retriable(
         &self,
         ingress_key: &CompressedRistrettoPublic,
@@ -1128,10 +1283,12 @@ impl SqlRecoveryDb {
 
                 diesel::insert_into(schema::reports::dsl::reports)
                     .values(&report)
-                    .on_conflict(schema::reports::dsl::fog_report_id)
+                    .on_conflict((
+                        schema::reports::dsl::fog_report_id,
+                        schema::reports::dsl::ingress_public_key,
+                    ))
                     .do_update()
                     .set((
-                        schema::reports::dsl::ingress_public_key.eq(report.ingress_public_key),
                         schema::reports::dsl::ingest_invocation_id.eq(report.ingest_invocation_id),
                         schema::reports::dsl::report.eq(report_bytes.clone()),
                         schema::reports::dsl::pubkey_expiry.eq(report.pubkey_expiry),
@@ -1141,7 +1298,8 @@ impl SqlRecoveryDb {
             })
     }
 
-    /// Remove report data associated with a given report id.
+    /// Remove the report data of every ingress key associated with a given
+    /// report id.
     fn remove_report_retriable(&self, report_id: &str) -> Result<(), Error> {
         let conn = self.pool.get()?;
         diesel::delete(
@@ -1401,6 +1559,49 @@ impl RecoveryDb for SqlRecoveryDb {
             self.get_highest_known_block_index_retriable()
         })
     }
+
+    /// Take the lease with a given name, or extend it if the holder already
+    /// holds it.
+    fn try_acquire_lease(
+        &self,
+        name: &str,
+        holder: &str,
+        duration: Duration,
+    ) -> Result<Option<u64>, Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.try_acquire_lease_retriable(name, holder, duration)
+        })
+    }
+
+    /// Check that a holder still holds a lease with a given fencing token.
+    fn check_lease(&self, name: &str, holder: &str, token: u64) -> Result<bool, Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.check_lease_retriable(name, holder, token)
+        })
+    }
+
+    /// Release a lease if the holder holds it.
+    fn release_lease(&self, name: &str, holder: &str) -> Result<(), Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.release_lease_retriable(name, holder)
+        })
+    }
+
+    /// Get the holder of a lease, if it is held and did not expire.
+    fn get_lease_holder(&self, name: &str) -> Result<Option<String>, Self::Error> {
+        our_retry(self.get_retries(), || self.get_lease_holder_retriable(name))
+    }
+
+    /// Record how far a Fog View shard has loaded the ETxOutRecords.
+    fn report_view_shard_progress(
+        &self,
+        shard_id: &str,
+        highest_processed_block_count: u64,
+    ) -> Result<(), Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.report_view_shard_progress_retriable(shard_id, highest_processed_block_count)
+        })
+    }
 }
 
 /// See trait `fog_recovery_db_iface::ReportDb` for documentation.
@@ -1411,7 +1612,7 @@ impl ReportDb for SqlRecoveryDb {
         our_retry(self.get_retries(), || self.get_all_reports_retriable())
     }
 
-    /// Set report data associated with a given report id.
+    /// Set report data associated with a given report id and ingress key.
     fn set_report(
         &self,
         ingress_key: &CompressedRistrettoPublic,
@@ -1423,7 +1624,8 @@ impl ReportDb for SqlRecoveryDb {
         })
     }
 
-    /// Remove report data associated with a given report id.
+    /// Remove the report data of every ingress key associated with a given
+    /// report id.
     fn remove_report(&self, report_id: &str) -> Result<(), Self::Error> {
         our_retry(self.get_retries(), || {
             self.remove_report_retriable(report_id)
@@ -2287,6 +2489,48 @@ mod tests {
         assert_eq!(tx_outs, records2);
     }
 
+    #[test_with_logger]
+    fn test_read_replica(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([123u8; 32]);
+        let db_test_context = test_utils::SqlRecoveryDbTestContext::new(logger);
+        let db = db_test_context.get_db_instance_with_read_replica();
+
+        // Writes go to the primary, and the queries of the read replica see them.
+        let ingress_key = CompressedRistrettoPublic::from(RistrettoPublic::from_random(&mut rng));
+        db.new_ingress_key(&ingress_key, 10).unwrap();
+
+        let invoc_id = db
+            .new_ingest_invocation(None, &ingress_key, &random_kex_rng_pubkey(&mut rng), 10)
+            .unwrap();
+
+        let (block, records) = random_block(&mut rng, 10, 10);
+        db.add_block_data(&invoc_id, &block, 0, &records).unwrap();
+
+        assert_eq!(db.get_highest_known_block_index().unwrap(), Some(10));
+        assert_eq!(
+            db.get_cumulative_txo_count_for_block(10).unwrap(),
+            Some(block.cumulative_txo_count)
+        );
+
+        let results = db.get_tx_outs(0, &[records[3].search_key.clone()]).unwrap();
+        assert_eq!(
+            results,
+            vec![TxOutSearchResult {
+                search_key: records[3].search_key.clone(),
+                result_code: TxOutSearchResultCode::Found as u32,
+                ciphertext: records[3].payload.clone(),
+            }]
+        );
+
+        let report = ReportData {
+            ingest_invocation_id: Some(invoc_id),
+            report: create_report("replica"),
+            pubkey_expiry: 50,
+        };
+        db.set_report(&ingress_key, "", &report).unwrap();
+        assert_eq!(db.get_all_reports().unwrap(), vec![("".into(), report)]);
+    }
+
     #[test_with_logger]
     fn test_get_highest_block_index(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([123u8; 32]);
@@ -2453,6 +2697,95 @@ mod tests {
         );
     }
 
+    #[test_with_logger]
+    fn test_multiple_reports_per_id(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([123u8; 32]);
+        let db_test_context = test_utils::SqlRecoveryDbTestContext::new(logger);
+        let db = db_test_context.get_db_instance();
+
+        let old_key = CompressedRistrettoPublic::from(RistrettoPublic::from_random(&mut rng));
+        let new_key = CompressedRistrettoPublic::from(RistrettoPublic::from_random(&mut rng));
+        let other_key = CompressedRistrettoPublic::from(RistrettoPublic::from_random(&mut rng));
+        for key in [&old_key, &new_key, &other_key] {
+            db.new_ingress_key(key, 123).unwrap();
+        }
+
+        let report_id = "";
+        let old_report = ReportData {
+            ingest_invocation_id: None,
+            report: create_report("old"),
+            pubkey_expiry: 200,
+        };
+        let new_report = ReportData {
+            ingest_invocation_id: None,
+            report: create_report("new"),
+            pubkey_expiry: 250,
+        };
+        let other_report = ReportData {
+            ingest_invocation_id: None,
+            report: create_report("other"),
+            pubkey_expiry: 300,
+        };
+
+        // Publishing a report for a new key keeps the report of the old key, and
+        // the report with the latest expiry comes first.
+        db.set_report(&old_key, report_id, &old_report).unwrap();
+        db.set_report(&new_key, report_id, &new_report).unwrap();
+        assert_eq!(
+            db.get_all_reports().unwrap(),
+            vec![
+                (report_id.into(), new_report.clone()),
+                (report_id.into(), old_report.clone()),
+            ]
+        );
+
+        // Reports of other report ids are listed separately.
+        db.set_report(&other_key, "other", &other_report).unwrap();
+        assert_eq!(
+            db.get_all_reports().unwrap(),
+            vec![
+                (report_id.into(), new_report.clone()),
+                (report_id.into(), old_report.clone()),
+                ("other".into(), other_report.clone()),
+            ]
+        );
+
+        // Publishing again for the old key only updates its own report.
+        let updated_old_report = ReportData {
+            ingest_invocation_id: None,
+            report: create_report("updated old"),
+            pubkey_expiry: 260,
+        };
+        db.set_report(&old_key, report_id, &updated_old_report)
+            .unwrap();
+        assert_eq!(
+            db.get_all_reports().unwrap(),
+            vec![
+                (report_id.into(), updated_old_report),
+                (report_id.into(), new_report.clone()),
+                ("other".into(), other_report.clone()),
+            ]
+        );
+
+        // Reports of lost keys are not served.
+        db.retire_ingress_key(&old_key, true).unwrap();
+        db.report_lost_ingress_key(old_key).unwrap();
+        assert_eq!(
+            db.get_all_reports().unwrap(),
+            vec![
+                (report_id.into(), new_report),
+                ("other".into(), other_report.clone()),
+            ]
+        );
+
+        // Removing a report id removes the reports of all of its keys.
+        db.remove_report(report_id).unwrap();
+        assert_eq!(
+            db.get_all_reports().unwrap(),
+            vec![("other".into(), other_report)]
+        );
+    }
+
     #[test_with_logger]
     fn test_get_ingress_key_records(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([123u8; 32]);
@@ -3415,4 +3748,77 @@ mod tests {
         };
         assert_eq!(actual, vec![expected]);
     }
+
+    #[test_with_logger]
+    fn test_leases(logger: Logger) {
+        let db_test_context = test_utils::SqlRecoveryDbTestContext::new(logger);
+        let db = db_test_context.get_db_instance();
+
+        let duration = Duration::from_secs(60);
+        assert_eq!(db.get_lease_holder("lease").unwrap(), None);
+
+        // The first holder takes the lease, and can extend it with the same token.
+        let token = db
+            .try_acquire_lease("lease", "a", duration)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            db.try_acquire_lease("lease", "a", duration).unwrap(),
+            Some(token)
+        );
+        assert_eq!(db.get_lease_holder("lease").unwrap(), Some("a".to_string()));
+        assert!(db.check_lease("lease", "a", token).unwrap());
+        assert!(!db.check_lease("lease", "a", token + 1).unwrap());
+        assert!(!db.check_lease("lease", "b", token).unwrap());
+
+        // Others cannot take it until it is released.
+        assert_eq!(db.try_acquire_lease("lease", "b", duration).unwrap(), None);
+        assert_eq!(db.get_lease_holder("lease").unwrap(), Some("a".to_string()));
+
+        // Leases with other names are independent.
+        assert!(db
+            .try_acquire_lease("other lease", "b", duration)
+            .unwrap()
+            .is_some());
+
+        // Releasing a lease someone else holds does nothing.
+        db.release_lease("lease", "b").unwrap();
+        assert_eq!(db.try_acquire_lease("lease", "b", duration).unwrap(), None);
+
+        // A released lease is taken with a greater token.
+        db.release_lease("lease", "a").unwrap();
+        assert_eq!(db.get_lease_holder("lease").unwrap(), None);
+        assert!(!db.check_lease("lease", "a", token).unwrap());
+        let token_b = db
+            .try_acquire_lease("lease", "b", duration)
+            .unwrap()
+            .unwrap();
+        assert!(token_b > token);
+        assert_eq!(db.try_acquire_lease("lease", "a", duration).unwrap(), None);
+
+        // Expired leases can be taken by others, and by their last holder, with a
+        // greater token, so that the last holder can tell it lost the lease.
+        let token_b = db
+            .try_acquire_lease("lease", "b", Duration::from_millis(100))
+            .unwrap()
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+        assert_eq!(db.get_lease_holder("lease").unwrap(), None);
+        assert!(!db.check_lease("lease", "b", token_b).unwrap());
+        let token_b2 = db
+            .try_acquire_lease("lease", "b", duration)
+            .unwrap()
+            .unwrap();
+        assert!(token_b2 > token_b);
+        assert!(!db.check_lease("lease", "b", token_b).unwrap());
+        assert!(db.check_lease("lease", "b", token_b2).unwrap());
+
+        db.release_lease("lease", "b").unwrap();
+        let token_a = db
+            .try_acquire_lease("lease", "a", duration)
+            .unwrap()
+            .unwrap();
+        assert!(token_a > token_b2);
+        assert_eq!(db.get_lease_holder("lease").unwrap(), Some("a".to_string()));
+    }
 }
@@ -0,0 +1,342 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Helpers for inspecting and safely running the recovery db migrations, used
+//! by the fog-sql-recovery-db-migrations utility.
+
+use diesel::{
+    migration::MigrationConnection,
+    pg::PgConnection,
+    prelude::*,
+    sql_types::{BigInt, Bool, Text},
+};
+use std::fmt;
+
+/// The key of the PostgreSQL advisory lock which is held while running
+/// migrations, so that two deployments can't run them at the same time.
+/// (The ASCII bytes of "fogmigra".)
+pub const MIGRATION_LOCK_KEY: i64 = 0x666f_676d_6967_7261;
+
+/// A migration embedded in this binary.
+#[derive(Debug)]
+pub struct EmbeddedMigration {
+    /// The name of the migration directory
+    pub name: &'static str,
+    /// The SQL which applies the migration
+    pub up_sql: &'static str,
+}
+
+impl EmbeddedMigration {
+    /// The version diesel records for the migration: the digits of the
+    /// directory name before the first underscore.
+    pub fn version(&self) -> String {
+        self.name
+            .split('_')
+            .next()
+            .unwrap_or_default()
+            .replace('-', "")
+    }
+}
+
+macro_rules! embedded_migration {
+    ($name:literal) => {
+        EmbeddedMigration {
+            name: $name,
+            up_sql: include_str!(concat!("../migrations/", $name, "/up.sql")),
+        }
+    };
+}
+
+/// All the migrations, in the order they run. This must list the same
+/// migrations as the migrations directory, which a test enforces.
+pub const MIGRATIONS: &[EmbeddedMigration] = &[
+    embedded_migration!("00000000000000_diesel_initial_setup"),
+    embedded_migration!("2020-11-03-203017_create_initial_db"),
+    embedded_migration!("2022-08-15-000000_create_leases"),
+    embedded_migration!("2022-09-01-000000_multiple_reports_per_id"),
+    embedded_migration!("2022-09-20-000000_create_view_shard_progress"),
+];
+
+/// Get the migrations which have not been run on this database yet. This
+/// doesn't modify the database.
+pub fn pending_migrations(conn: &PgConnection) -> QueryResult<Vec<&'static EmbeddedMigration>> {
+    #[derive(QueryableByName)]
+    struct TableExists {
+        #[sql_type = "Bool"]
+        exists: bool,
+    }
+
+    // Diesel creates its migrations table when it first runs migrations
+    let TableExists { exists } =
+        diesel::sql_query("SELECT to_regclass('__diesel_schema_migrations') IS NOT NULL AS exists")
+            .get_result(conn)?;
+
+    let run_versions = if exists {
+        conn.previously_run_migration_versions()?
+    } else {
+        Default::default()
+    };
+
+    Ok(MIGRATIONS
+        .iter()
+        .filter(|migration| !run_versions.contains(&migration.version()))
+        .collect())
+}
+
+/// Try to take the migration advisory lock without waiting. Returns whether
+/// it was taken. The lock is held until it is released with
+/// `unlock_migrations`, or the connection is closed.
+pub fn try_lock_migrations(conn: &PgConnection) -> QueryResult<bool> {
+    #[derive(QueryableByName)]
+    struct Locked {
+        #[sql_type = "Bool"]
+        locked: bool,
+    }
+
+    let Locked { locked } = diesel::sql_query("SELECT pg_try_advisory_lock($1) AS locked")
+        .bind::<BigInt, _>(MIGRATION_LOCK_KEY)
+        .get_result(conn)?;
+    Ok(locked)
+}
+
+/// Take the migration advisory lock, waiting for whoever holds it.
+pub fn lock_migrations(conn: &PgConnection) -> QueryResult<()> {
+    diesel::sql_query("SELECT pg_advisory_lock($1)")
+        .bind::<BigInt, _>(MIGRATION_LOCK_KEY)
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Release the migration advisory lock.
+pub fn unlock_migrations(conn: &PgConnection) -> QueryResult<()> {
+    diesel::sql_query("SELECT pg_advisory_unlock($1)")
+        .bind::<BigInt, _>(MIGRATION_LOCK_KEY)
+        .execute(conn)?;
+    Ok(())
+}
+
+/// The planner's estimate of the number of rows in a table, if it exists.
+pub fn estimated_rows(conn: &PgConnection, table: &str) -> QueryResult<Option<i64>> {
+    #[derive(QueryableByName)]
+    struct Rows {
+        #[sql_type = "BigInt"]
+        rows: i64,
+    }
+
+    let rows: Vec<Rows> = diesel::sql_query(
+        "SELECT reltuples::BIGINT AS rows FROM pg_class WHERE relname = $1 AND relkind = 'r'",
+    )
+    .bind::<Text, _>(table)
+    .load(conn)?;
+    Ok(rows.first().map(|rows| rows.rows.max(0)))
+}
+
+/// A table lock that a migration statement takes.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LockImpact {
+    /// The first words of the statement, e.g. "ALTER TABLE"
+    pub statement: &'static str,
+    /// The table which is locked
+    pub table: String,
+    /// The PostgreSQL lock mode
+    pub lock: &'static str,
+    /// What the lock blocks while the statement runs
+    pub blocks: &'static str,
+}
+
+impl fmt::Display for LockImpact {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: {} lock, blocks {}",
+            self.statement, self.table, self.lock, self.blocks
+        )
+    }
+}
+
+/// Estimate the table locks that running this SQL takes on existing tables.
+///
+/// This recognizes the statements that our migrations use, and is not a
+/// complete model of PostgreSQL locking. Statements which don't lock existing
+/// tables, like CREATE TABLE and CREATE FUNCTION, are left out.
+pub fn lock_impacts(sql: &str) -> Vec<LockImpact> {
+    split_statements(sql)
+        .iter()
+        .filter_map(|statement| {
+            let words: Vec<String> = statement
+                .split_whitespace()
+                .map(|word| word.to_ascii_uppercase())
+                .collect();
+            let words: Vec<&str> = words.iter().map(String::as_str).collect();
+            let original: Vec<&str> = statement.split_whitespace().collect();
+            let word_after = |keyword: &str| {
+                words
+                    .iter()
+                    .position(|word| *word == keyword)
+                    .and_then(|pos| original.get(pos + 1))
+                    .map(|table| table.trim_matches(|c| c == '"' || c == '(').to_lowercase())
+            };
+
+            let (statement, lock, blocks, table) = match words.as_slice() {
+                ["ALTER", "TABLE", ..] => (
+                    "ALTER TABLE",
+                    "ACCESS EXCLUSIVE",
+                    "reads and writes",
+                    word_after("TABLE"),
+                ),
+                ["DROP", "TABLE", ..] => (
+                    "DROP TABLE",
+                    "ACCESS EXCLUSIVE",
+                    "reads and writes",
+                    word_after("TABLE"),
+                ),
+                ["TRUNCATE", ..] => (
+                    "TRUNCATE",
+                    "ACCESS EXCLUSIVE",
+                    "reads and writes",
+                    word_after("TRUNCATE"),
+                ),
+                ["CREATE", .., "INDEX", "CONCURRENTLY", ..] => (
+                    "CREATE INDEX CONCURRENTLY",
+                    "SHARE UPDATE EXCLUSIVE",
+                    "other schema changes",
+                    word_after("ON"),
+                ),
+                ["CREATE", "INDEX", ..] | ["CREATE", "UNIQUE", "INDEX", ..] => {
+                    ("CREATE INDEX", "SHARE", "writes", word_after("ON"))
+                }
+                ["UPDATE", ..] => (
+                    "UPDATE",
+                    "ROW EXCLUSIVE",
+                    "writes to the updated rows",
+                    word_after("UPDATE"),
+                ),
+                ["DELETE", "FROM", ..] => (
+                    "DELETE FROM",
+                    "ROW EXCLUSIVE",
+                    "writes to the deleted rows",
+                    word_after("FROM"),
+                ),
+                _ => return None,
+            };
+
+            Some(LockImpact {
+                statement,
+                table: table.unwrap_or_default(),
+                lock,
+                blocks,
+            })
+        })
+        .collect()
+}
+
+/// Split SQL into statements, dropping comments, and keeping dollar-quoted
+/// function bodies intact.
+fn split_statements(sql: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut in_dollar_quote = false;
+
+    for line in sql.lines() {
+        let line = if in_dollar_quote {
+            line
+        } else {
+            line.split("--").next().unwrap_or_default()
+        };
+
+        for (i, part) in line.split("$$").enumerate() {
+            if i > 0 {
+                current.push_str("$$");
+                in_dollar_quote = !in_dollar_quote;
+            }
+            if in_dollar_quote {
+                current.push_str(part);
+                continue;
+            }
+            let mut pieces = part.split(';');
+            current.push_str(pieces.next().unwrap_or_default());
+            for piece in pieces {
+                statements.push(std::mem::take(&mut current));
+                current.push_str(piece);
+            }
+        }
+        current.push('\n');
+    }
+    statements.push(current);
+
+    statements
+        .into_iter()
+        .map(|statement| statement.trim().to_string())
+        .filter(|statement| !statement.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embedded_migrations_match_migrations_directory() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/migrations");
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+
+        let embedded: Vec<&str> = MIGRATIONS.iter().map(|migration| migration.name).collect();
+        assert_eq!(names, embedded);
+
+        assert_eq!(MIGRATIONS[1].version(), "20201103203017");
+    }
+
+    #[test]
+    fn lock_impacts_of_migrations() {
+        // The initial migrations only create functions and tables
+        assert_eq!(lock_impacts(MIGRATIONS[0].up_sql), vec![]);
+        assert_eq!(lock_impacts(MIGRATIONS[2].up_sql), vec![]);
+
+        let impacts = lock_impacts(MIGRATIONS[3].up_sql);
+        assert_eq!(impacts.len(), 2);
+        assert_eq!(
+            impacts[0].to_string(),
+            "ALTER TABLE reports: ACCESS EXCLUSIVE lock, blocks reads and writes"
+        );
+
+        let impacts = lock_impacts(MIGRATIONS[4].up_sql);
+        assert_eq!(
+            impacts
+                .iter()
+                .map(|impact| impact.table.as_str())
+                .collect::<Vec<_>>(),
+            vec!["user_events", "ingested_blocks"]
+        );
+
+        assert_eq!(
+            lock_impacts(
+                "-- A comment; with a semicolon\n\
+                 CREATE INDEX CONCURRENTLY idx ON \"Users\" (id);\n\
+                 create unique index idx2 on users (id); DELETE FROM users WHERE id = 1;"
+            ),
+            vec![
+                LockImpact {
+                    statement: "CREATE INDEX CONCURRENTLY",
+                    table: "users".to_string(),
+                    lock: "SHARE UPDATE EXCLUSIVE",
+                    blocks: "other schema changes",
+                },
+                LockImpact {
+                    statement: "CREATE INDEX",
+                    table: "users".to_string(),
+                    lock: "SHARE",
+                    blocks: "writes",
+                },
+                LockImpact {
+                    statement: "DELETE FROM",
+                    table: "users".to_string(),
+                    lock: "ROW EXCLUSIVE",
+                    blocks: "writes to the deleted rows",
+                },
+            ]
+        );
+    }
+}
@@ -120,3 +120,9 @@ pub struct NewReport<'a> {
     pub report: &'a [u8],
     pub pubkey_expiry: i64,
 }
+
+#[derive(Debug, QueryableByName)]
+#[table_name = "leases"]
+pub struct LeaseToken {
+    pub token: i64,
+}
@@ -0,0 +1,361 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Pruning of expired data from the recovery db, used by the
+//! fog-sql-recovery-db-prune utility.
+//!
+//! The ETxOutRecords of an ingest invocation, and its rng record (the
+//! ingest_invocations row, announced to clients by a user event), are removed
+//! once the invocation is decommissioned and has been inactive for longer than
+//! the configured horizon. This is only done when it is safe:
+//! * Nothing is pruned unless a Fog View shard reported its progress recently,
+//!   and no blocks are pruned which such a shard has not loaded yet.
+//! * The invocations of an ingress key are only pruned from its first block on,
+//!   without gaps, and the start block of the key is moved past the pruned
+//!   blocks, so that Fog View servers which start later do not wait for them.
+//! * The highest known block is never pruned, since new ingress keys start from
+//!   it.
+
+use crate::{schema, Error, SqlRecoveryDb, SQL_MAX_PARAMS};
+use clap::Parser;
+use diesel::{
+    pg::PgConnection,
+    prelude::*,
+    sql_types::{BigInt, Bool, Bytea, Nullable},
+};
+use mc_fog_recovery_db_iface::IngestInvocationId;
+use mc_util_parse::parse_duration_in_seconds;
+use serde::Serialize;
+use std::{cmp::min, time::Duration};
+
+/// Configuration of a pruning run
+#[derive(Clone, Debug, Parser, Serialize)]
+pub struct PruneConfig {
+    /// Prune the decommissioned ingest invocations which were last active
+    /// longer ago than this, in seconds.
+    #[clap(long, parse(try_from_str = parse_duration_in_seconds), env = "MC_PRUNE_HORIZON")]
+    pub horizon: Duration,
+
+    /// Fog View shards which reported their progress within this many seconds
+    /// are active, and prevent pruning the blocks they have not loaded yet.
+    #[clap(long, default_value = "600", parse(try_from_str = parse_duration_in_seconds), env = "MC_VIEW_SHARD_STALENESS")]
+    pub view_shard_staleness: Duration,
+
+    /// Find what would be pruned, without deleting anything.
+    #[clap(long)]
+    pub dry_run: bool,
+}
+
+/// The outcome of a pruning run
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PruneSummary {
+    /// The lowest block count which an active Fog View shard has processed.
+    /// Only blocks below it can be pruned, and nothing is pruned if this is
+    /// None, because no shard is active.
+    pub view_block_count: Option<u64>,
+
+    /// The ingest invocations which were pruned, or would be in a dry run.
+    pub pruned_invocations: Vec<IngestInvocationId>,
+
+    /// The number of blocks of ETxOutRecords which were pruned, or would be in
+    /// a dry run.
+    pub pruned_blocks: u64,
+}
+
+/// An ingest invocation, and the blocks it scanned
+#[derive(Debug, QueryableByName)]
+struct InvocationExtent {
+    #[sql_type = "BigInt"]
+    id: i64,
+    #[sql_type = "Bytea"]
+    ingress_public_key: Vec<u8>,
+    /// Whether the invocation is decommissioned and older than the horizon
+    #[sql_type = "Bool"]
+    expired: bool,
+    #[sql_type = "Nullable<BigInt>"]
+    last_block: Option<i64>,
+}
+
+impl SqlRecoveryDb {
+    /// Remove the ETxOutRecords and rng records of the decommissioned ingest
+    /// invocations which were last active longer ago than the horizon, as far
+    /// as it is safe. This happens in a single transaction.
+    pub fn prune_expired_data(&self, config: &PruneConfig) -> Result<PruneSummary, Error> {
+        let conn = self.pool.get()?;
+
+        conn.build_transaction()
+            .read_write()
+            .run(|| prune_expired_data_impl(&conn, config))
+    }
+}
+
+fn prune_expired_data_impl(
+    conn: &PgConnection,
+    config: &PruneConfig,
+) -> Result<PruneSummary, Error> {
+    let mut summary = PruneSummary::default();
+
+    #[derive(QueryableByName)]
+    struct ViewBlockCount {
+        #[sql_type = "Nullable<BigInt>"]
+        view_block_count: Option<i64>,
+    }
+
+    let ViewBlockCount { view_block_count } = diesel::sql_query(
+        "SELECT MIN(highest_processed_block_count) AS view_block_count \
+         FROM view_shard_progress \
+         WHERE updated_at > NOW() - $1 * INTERVAL '1 millisecond'",
+    )
+    .bind::<BigInt, _>(config.view_shard_staleness.as_millis() as i64)
+    .get_result(conn)?;
+
+    let view_block_count = match view_block_count {
+        Some(view_block_count) => view_block_count,
+        None => return Ok(summary),
+    };
+    summary.view_block_count = Some(view_block_count as u64);
+
+    let highest_known_block_index = match SqlRecoveryDb::get_highest_known_block_index_impl(conn)? {
+        Some(index) => index as i64,
+        None => return Ok(summary),
+    };
+    // Only blocks below this can be pruned
+    let block_limit = min(view_block_count, highest_known_block_index);
+
+    // The invocations of each ingress key, in the order of the blocks they
+    // scanned. An ingress key never scans a block twice, so their blocks don't
+    // overlap.
+    let extents: Vec<InvocationExtent> = diesel::sql_query(
+        "SELECT ingest_invocations.id, ingest_invocations.ingress_public_key, \
+         (ingest_invocations.decommissioned \
+          AND ingest_invocations.last_active_at < NOW() - $1 * INTERVAL '1 millisecond') AS expired, \
+         MAX(ingested_blocks.block_number) AS last_block \
+         FROM ingest_invocations \
+         LEFT JOIN ingested_blocks ON ingested_blocks.ingest_invocation_id = ingest_invocations.id \
+         GROUP BY ingest_invocations.id \
+         ORDER BY ingest_invocations.ingress_public_key, \
+         COALESCE(MIN(ingested_blocks.block_number), ingest_invocations.start_block), \
+         ingest_invocations.id",
+    )
+    .bind::<BigInt, _>(config.horizon.as_millis() as i64)
+    .load(conn)?;
+
+    // The new start block of each ingress key which has blocks pruned
+    let mut key_start_blocks: Vec<(Vec<u8>, i64)> = Vec::new();
+    let mut pruning_key: Option<&[u8]> = None;
+    let mut key_stopped = false;
+    for extent in extents.iter() {
+        if pruning_key != Some(&extent.ingress_public_key[..]) {
+            pruning_key = Some(&extent.ingress_public_key);
+            key_stopped = false;
+        }
+        if key_stopped {
+            continue;
+        }
+
+        let prunable =
+            extent.expired && extent.last_block.map_or(true, |block| block < block_limit);
+        if !prunable {
+            // The later invocations of this key are kept, so that the pruned
+            // blocks of the key are a prefix
+            key_stopped = true;
+            continue;
+        }
+
+        summary
+            .pruned_invocations
+            .push(IngestInvocationId::from(extent.id));
+        if let Some(last_block) = extent.last_block {
+            match key_start_blocks.last_mut() {
+                Some((key, start_block)) if *key == extent.ingress_public_key => {
+                    *start_block = last_block + 1;
+                }
+                _ => key_start_blocks.push((extent.ingress_public_key.clone(), last_block + 1)),
+            }
+        }
+    }
+
+    let pruned_ids: Vec<i64> = summary.pruned_invocations.iter().map(|id| **id).collect();
+
+    for ids in pruned_ids.chunks(SQL_MAX_PARAMS) {
+        use schema::ingested_blocks::dsl;
+        summary.pruned_blocks += dsl::ingested_blocks
+            .filter(dsl::ingest_invocation_id.eq_any(ids))
+            .count()
+            .get_result::<i64>(conn)? as u64;
+    }
+
+    if config.dry_run {
+        return Ok(summary);
+    }
+
+    for ids in pruned_ids.chunks(SQL_MAX_PARAMS / 2) {
+        diesel::delete(
+            schema::ingested_blocks::dsl::ingested_blocks
+                .filter(schema::ingested_blocks::dsl::ingest_invocation_id.eq_any(ids)),
+        )
+        .execute(conn)?;
+
+        diesel::delete(
+            schema::user_events::dsl::user_events.filter(
+                schema::user_events::dsl::new_ingest_invocation_id
+                    .eq_any(ids)
+                    .or(schema::user_events::dsl::decommission_ingest_invocation_id.eq_any(ids)),
+            ),
+        )
+        .execute(conn)?;
+
+        diesel::update(
+            schema::reports::dsl::reports
+                .filter(schema::reports::dsl::ingest_invocation_id.eq_any(ids)),
+        )
+        .set(schema::reports::dsl::ingest_invocation_id.eq(None::<i64>))
+        .execute(conn)?;
+
+        diesel::delete(
+            schema::ingest_invocations::dsl::ingest_invocations
+                .filter(schema::ingest_invocations::dsl::id.eq_any(ids)),
+        )
+        .execute(conn)?;
+    }
+
+    for (key, start_block) in key_start_blocks {
+        use schema::ingress_keys::dsl;
+        diesel::update(
+            dsl::ingress_keys
+                .filter(dsl::ingress_public_key.eq(key))
+                .filter(dsl::start_block.lt(start_block)),
+        )
+        .set(dsl::start_block.eq(start_block))
+        .execute(conn)?;
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::SqlRecoveryDbTestContext;
+    use mc_common::logger::{test_with_logger, Logger};
+    use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPublic};
+    use mc_fog_recovery_db_iface::{FogUserEvent, RecoveryDb};
+    use mc_fog_test_infra::db_tests::{random_block, random_kex_rng_pubkey};
+    use mc_util_from_random::FromRandom;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn prune_config(horizon: Duration, dry_run: bool) -> PruneConfig {
+        PruneConfig {
+            horizon,
+            view_shard_staleness: Duration::from_secs(600),
+            dry_run,
+        }
+    }
+
+    fn num_rng_records(db: &SqlRecoveryDb) -> usize {
+        let (events, _) = db.search_user_events(0).unwrap();
+        events
+            .iter()
+            .filter(|event| matches!(event, FogUserEvent::NewRngRecord(_)))
+            .count()
+    }
+
+    #[test_with_logger]
+    fn prune_decommissioned_invocations_loaded_by_view_shards(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([123u8; 32]);
+        let db_test_context = SqlRecoveryDbTestContext::new(logger);
+        let db = db_test_context.get_db_instance();
+
+        let ingress_key = CompressedRistrettoPublic::from(RistrettoPublic::from_random(&mut rng));
+        db.new_ingress_key(&ingress_key, 0).unwrap();
+
+        // The first invocation scans blocks 0 to 2 and is decommissioned, and
+        // the second one scans blocks 3 and 4.
+        let invoc_id1 = db
+            .new_ingest_invocation(None, &ingress_key, &random_kex_rng_pubkey(&mut rng), 0)
+            .unwrap();
+        for block_index in 0..3 {
+            let (block, records) = random_block(&mut rng, block_index, 5);
+            db.add_block_data(&invoc_id1, &block, 0, &records).unwrap();
+        }
+        let invoc_id2 = db
+            .new_ingest_invocation(
+                Some(invoc_id1),
+                &ingress_key,
+                &random_kex_rng_pubkey(&mut rng),
+                3,
+            )
+            .unwrap();
+        for block_index in 3..5 {
+            let (block, records) = random_block(&mut rng, block_index, 5);
+            db.add_block_data(&invoc_id2, &block, 0, &records).unwrap();
+        }
+        assert_eq!(num_rng_records(&db), 2);
+
+        // Nothing is pruned while no view shard reported its progress
+        let summary = db
+            .prune_expired_data(&prune_config(Duration::ZERO, false))
+            .unwrap();
+        assert_eq!(summary, PruneSummary::default());
+
+        // Nor while a view shard has not loaded the blocks yet
+        db.report_view_shard_progress("shard", 2).unwrap();
+        let summary = db
+            .prune_expired_data(&prune_config(Duration::ZERO, false))
+            .unwrap();
+        assert_eq!(summary.view_block_count, Some(2));
+        assert!(summary.pruned_invocations.is_empty());
+
+        // Nor before the horizon
+        db.report_view_shard_progress("shard", 5).unwrap();
+        let summary = db
+            .prune_expired_data(&prune_config(Duration::from_secs(3600), false))
+            .unwrap();
+        assert!(summary.pruned_invocations.is_empty());
+
+        // A dry run finds what can be pruned, without deleting it
+        let summary = db
+            .prune_expired_data(&prune_config(Duration::ZERO, true))
+            .unwrap();
+        assert_eq!(summary.pruned_invocations, vec![invoc_id1]);
+        assert_eq!(summary.pruned_blocks, 3);
+        assert!(db
+            .get_tx_outs_by_block_and_key(ingress_key, 0)
+            .unwrap()
+            .is_some());
+
+        let summary = db
+            .prune_expired_data(&prune_config(Duration::ZERO, false))
+            .unwrap();
+        assert_eq!(summary.pruned_invocations, vec![invoc_id1]);
+        assert_eq!(summary.pruned_blocks, 3);
+
+        for block_index in 0..3 {
+            assert_eq!(
+                db.get_tx_outs_by_block_and_key(ingress_key, block_index)
+                    .unwrap(),
+                None
+            );
+        }
+        assert!(db
+            .get_tx_outs_by_block_and_key(ingress_key, 3)
+            .unwrap()
+            .is_some());
+        assert_eq!(num_rng_records(&db), 1);
+
+        // The key now starts after the pruned blocks, so that view servers
+        // don't wait for them
+        assert_eq!(
+            db.get_ingress_key_status(&ingress_key)
+                .unwrap()
+                .unwrap()
+                .start_block,
+            3
+        );
+
+        // The active invocation is never pruned
+        let summary = db
+            .prune_expired_data(&prune_config(Duration::ZERO, false))
+            .unwrap();
+        assert!(summary.pruned_invocations.is_empty());
+    }
+}
@@ -45,6 +45,18 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::*;
+
+    leases (name) {
+        name -> Varchar,
+        holder -> Varchar,
+        expires_at -> Timestamp,
+        token -> Int8,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use crate::sql_types::*;
@@ -73,6 +85,17 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::*;
+
+    view_shard_progress (shard_id) {
+        shard_id -> Varchar,
+        highest_processed_block_count -> Int8,
+        updated_at -> Timestamp,
+    }
+}
+
 joinable!(ingested_blocks -> ingest_invocations (ingest_invocation_id));
 joinable!(reports -> ingest_invocations (ingest_invocation_id));
 joinable!(reports -> ingress_keys (ingress_public_key));
@@ -81,6 +104,8 @@ allow_tables_to_appear_in_same_query!(
     ingest_invocations,
     ingested_blocks,
     ingress_keys,
+    leases,
     reports,
     user_events,
+    view_shard_progress,
 );
@@ -85,6 +85,17 @@ impl SqlRecoveryDbTestContext {
         .expect("failed creating new SqlRecoveryDb")
     }
 
+    /// Get DB instance, using the same database as its own read replica.
+    pub fn get_db_instance_with_read_replica(&self) -> SqlRecoveryDb {
+        SqlRecoveryDb::new_from_urls(
+            &self.db_url(),
+            Some(&self.db_url()),
+            SqlRecoveryDbConnectionConfig::default(),
+            self.logger.clone(),
+        )
+        .expect("failed creating new SqlRecoveryDb")
+    }
+
     /// Establish a connection.
     pub fn new_conn(&self) -> PgConnection {
         let db_url = self.db_url();
@@ -0,0 +1,8 @@
+-- Copyright (c) 2018-2022 The MobileCoin Foundation
+
+DROP TABLE view_shard_progress;
+DROP TABLE reports;
+DROP TABLE user_events;
+DROP TABLE ingested_blocks;
+DROP TABLE ingest_invocations;
+DROP TABLE ingress_keys;
@@ -0,0 +1,76 @@
+-- Copyright (c) 2018-2022 The MobileCoin Foundation
+
+-- The SQLite schema of the recovery db. This mirrors the PostgreSQL schema of the fog-sql-recovery-db crate, see
+-- its migrations for the meaning of each column, with these differences:
+-- * Ids are `INTEGER PRIMARY KEY` columns, which SQLite auto-increments. They are 64 bit.
+-- * Times are TIMESTAMP columns, which SQLite stores as text.
+-- * The user event type is stored as text.
+-- * There is no leases table, since the overseer does not run against SQLite.
+
+CREATE TABLE ingress_keys (
+    ingress_public_key BLOB PRIMARY KEY,
+    start_block BIGINT NOT NULL,
+    pubkey_expiry BIGINT NOT NULL DEFAULT 0,
+    retired BOOLEAN NOT NULL DEFAULT false,
+    lost BOOLEAN NOT NULL DEFAULT false
+);
+
+CREATE TABLE ingest_invocations (
+    id INTEGER PRIMARY KEY,
+    ingress_public_key BLOB NOT NULL,
+    egress_public_key BLOB NOT NULL UNIQUE,
+    last_active_at TIMESTAMP NOT NULL,
+    start_block BIGINT NOT NULL,
+    decommissioned BOOLEAN NOT NULL DEFAULT false,
+    rng_version INT NOT NULL,
+    FOREIGN KEY (ingress_public_key) REFERENCES ingress_keys(ingress_public_key)
+);
+
+CREATE TABLE ingested_blocks (
+    id INTEGER PRIMARY KEY,
+    ingest_invocation_id BIGINT NOT NULL,
+    ingress_public_key BLOB NOT NULL,
+    block_number BIGINT NOT NULL,
+    cumulative_txo_count BIGINT NOT NULL,
+    block_signature_timestamp BIGINT NOT NULL,
+    proto_ingested_block_data BLOB NOT NULL,
+    FOREIGN KEY (ingest_invocation_id) REFERENCES ingest_invocations(id),
+    FOREIGN KEY (ingress_public_key) REFERENCES ingress_keys(ingress_public_key),
+    UNIQUE (ingest_invocation_id, block_number),
+    UNIQUE (ingress_public_key, block_number)
+);
+
+CREATE INDEX idx_ingested_blocks__block_number ON ingested_blocks (block_number);
+
+CREATE TABLE user_events (
+    id INTEGER PRIMARY KEY,
+    -- One of 'new_ingest_invocation', 'decommission_ingest_invocation' and 'missing_blocks'
+    event_type TEXT NOT NULL,
+    new_ingest_invocation_id BIGINT NULL UNIQUE,
+    decommission_ingest_invocation_id BIGINT NULL UNIQUE,
+    missing_blocks_start BIGINT NULL,
+    missing_blocks_end BIGINT NULL,
+    FOREIGN KEY (new_ingest_invocation_id) REFERENCES ingest_invocations(id),
+    FOREIGN KEY (decommission_ingest_invocation_id) REFERENCES ingest_invocations(id),
+    UNIQUE (missing_blocks_start, missing_blocks_end)
+);
+
+CREATE INDEX idx_user_events__event_type__id ON user_events (event_type, id);
+
+CREATE TABLE reports (
+    id INTEGER PRIMARY KEY,
+    ingress_public_key BLOB NOT NULL,
+    ingest_invocation_id BIGINT,
+    fog_report_id VARCHAR(64) NOT NULL,
+    report BLOB NOT NULL,
+    pubkey_expiry BIGINT NOT NULL,
+    FOREIGN KEY (ingress_public_key) REFERENCES ingress_keys(ingress_public_key),
+    FOREIGN KEY (ingest_invocation_id) REFERENCES ingest_invocations(id),
+    UNIQUE (fog_report_id, ingress_public_key)
+);
+
+CREATE TABLE view_shard_progress (
+    shard_id VARCHAR(256) PRIMARY KEY,
+    highest_processed_block_count BIGINT NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
@@ -0,0 +1,1617 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+#![deny(missing_docs)]
+
+//! Recovery db implementation using a SQLite database backend.
+//!
+//! This is meant for local development and integration tests, so that the
+//! fog services can run without provisioning PostgreSQL. It stores the same
+//! data as the fog-sql-recovery-db crate, in a single database file, which is
+//! created and migrated when it is opened.
+//!
+//! SQLite allows one writer at a time, so this does not scale like the
+//! PostgreSQL backend does, and it has no support for read replicas, pruning
+//! or overseer leases.
+//!
+//! The schema, models and error type are those of the fog-sql-recovery-db
+//! crate.
+
+#[macro_use]
+extern crate diesel;
+#[macro_use]
+extern crate diesel_migrations;
+
+pub use mc_fog_sql_recovery_db::Error;
+
+pub mod test_utils;
+
+use clap::Parser;
+use diesel::{
+    connection::SimpleConnection,
+    prelude::*,
+    r2d2::{self, ConnectionManager, Pool},
+    sql_types::{BigInt, Binary, Nullable, Text},
+    SqliteConnection,
+};
+use mc_attest_core::VerificationReport;
+use mc_blockchain_types::Block;
+use mc_common::{
+    logger::{log, Logger},
+    HashMap,
+};
+use mc_crypto_keys::CompressedRistrettoPublic;
+use mc_fog_kex_rng::KexRngPubkey;
+use mc_fog_recovery_db_iface::{
+    AddBlockDataStatus, FogUserEvent, IngestInvocationId, IngressPublicKeyRecord,
+    IngressPublicKeyRecordFilters, IngressPublicKeyStatus, RecoveryDb, RecoveryDbError, ReportData,
+    ReportDb,
+};
+use mc_fog_sql_recovery_db::{
+    models,
+    proto_types::ProtoIngestedBlockData,
+    schema,
+    sql_types::{SqlCompressedRistrettoPublic, UserEventType},
+};
+use mc_fog_types::{
+    common::BlockRange,
+    view::{TxOutSearchResult, TxOutSearchResultCode},
+    ETxOutRecord,
+};
+use mc_util_parse::parse_duration_in_seconds;
+use prost::Message;
+use retry::{delay, Error as RetryError, OperationResult};
+use serde::Serialize;
+use std::{cmp::max, time::Duration};
+
+embed_migrations!("migrations/");
+
+no_arg_sql_function!(
+    last_insert_rowid,
+    diesel::sql_types::BigInt,
+    "Represents the SQLite last_insert_rowid() function"
+);
+
+/// The scheme of database urls which point at a SQLite database file, e.g.
+/// `sqlite:///tmp/fog-recovery.db`. Other database urls are PostgreSQL urls.
+pub const SQLITE_URL_SCHEME: &str = "sqlite://";
+
+/// Get the path of the SQLite database file a database url points at, if it
+/// is a SQLite url.
+pub fn sqlite_path_from_url(database_url: &str) -> Option<&str> {
+    database_url.strip_prefix(SQLITE_URL_SCHEME)
+}
+
+/// SQLite recovery DB connection configuration parameters
+#[derive(Debug, Clone, Parser, Serialize)]
+pub struct SqliteRecoveryDbConnectionConfig {
+    /// The maximum number of connections managed by the pool.
+    #[clap(long, default_value = "4", env = "MC_SQLITE_MAX_CONNECTIONS")]
+    pub sqlite_max_connections: u32,
+
+    /// How long a connection waits for another connection to finish writing,
+    /// before failing with a "database is locked" error.
+    #[clap(long, default_value = "5", parse(try_from_str = parse_duration_in_seconds), env = "MC_SQLITE_BUSY_TIMEOUT")]
+    pub sqlite_busy_timeout: Duration,
+
+    /// How many times to retry when we get retriable errors (connection /
+    /// locking errors)
+    #[clap(long, default_value = "3", env = "MC_SQLITE_RETRY_COUNT")]
+    pub sqlite_retry_count: usize,
+
+    /// How long to back off (milliseconds) when we get retriable errors
+    /// (connection / locking errors)
+    #[clap(long, default_value = "20", env = "MC_SQLITE_RETRY_MILLIS")]
+    pub sqlite_retry_millis: u64,
+}
+
+impl Default for SqliteRecoveryDbConnectionConfig {
+    fn default() -> Self {
+        Self {
+            sqlite_max_connections: 4,
+            sqlite_busy_timeout: Duration::from_secs(5),
+            sqlite_retry_count: 3,
+            sqlite_retry_millis: 20,
+        }
+    }
+}
+
+/// Options set on each connection of the pool.
+#[derive(Debug)]
+struct ConnectionOptions {
+    /// Time to wait while the database is locked.
+    busy_timeout: Duration,
+}
+
+impl r2d2::CustomizeConnection<SqliteConnection, r2d2::Error> for ConnectionOptions {
+    fn on_acquire(&self, conn: &mut SqliteConnection) -> Result<(), r2d2::Error> {
+        (|| {
+            conn.batch_execute(&format!(
+                "PRAGMA busy_timeout = {};",
+                self.busy_timeout.as_millis()
+            ))?;
+            // The WAL lets readers proceed while a connection writes.
+            // See https://sqlite.org/wal.html for details.
+            conn.batch_execute(
+                "
+                PRAGMA journal_mode = WAL;
+                PRAGMA synchronous = NORMAL;
+                PRAGMA foreign_keys = ON;
+            ",
+            )?;
+
+            Ok(())
+        })()
+        .map_err(r2d2::Error::QueryError)
+    }
+}
+
+/// SQLite-backed recovery database.
+#[derive(Clone)]
+pub struct SqliteRecoveryDb {
+    pool: Pool<ConnectionManager<SqliteConnection>>,
+    config: SqliteRecoveryDbConnectionConfig,
+    logger: Logger,
+}
+
+impl SqliteRecoveryDb {
+    /// Create a new instance using a path to a database file, and connection
+    /// parameters. The parameters have sane defaults.
+    ///
+    /// The file is created if it does not exist, and the migrations which were
+    /// not run on it yet are run.
+    pub fn new_from_path(
+        db_file_path: &str,
+        config: SqliteRecoveryDbConnectionConfig,
+        logger: Logger,
+    ) -> Result<Self, Error> {
+        let manager = ConnectionManager::<SqliteConnection>::new(db_file_path);
+        let pool = Pool::builder()
+            .max_size(config.sqlite_max_connections)
+            .connection_customizer(Box::new(ConnectionOptions {
+                busy_timeout: config.sqlite_busy_timeout,
+            }))
+            .test_on_check_out(true)
+            .build(manager)?;
+
+        let conn = pool.get()?;
+        embedded_migrations::run(&conn)?;
+
+        Ok(Self {
+            pool,
+            config,
+            logger,
+        })
+    }
+
+    // Helper function for retries config
+    fn get_retries(&self) -> Box<dyn Iterator<Item = Duration>> {
+        Box::new(
+            delay::Fixed::from_millis(self.config.sqlite_retry_millis)
+                .take(self.config.sqlite_retry_count)
+                .map(delay::jitter),
+        )
+    }
+
+    /// Mark a given ingest invocation as decommissioned.
+    fn decommission_ingest_invocation_impl(
+        &self,
+        conn: &SqliteConnection,
+        ingest_invocation_id: &IngestInvocationId,
+    ) -> Result<(), Error> {
+        // Mark the ingest invocation as decommissioned.
+        diesel::update(
+            schema::ingest_invocations::dsl::ingest_invocations
+                .filter(schema::ingest_invocations::dsl::id.eq(**ingest_invocation_id)),
+        )
+        .set((
+            schema::ingest_invocations::dsl::decommissioned.eq(true),
+            schema::ingest_invocations::dsl::last_active_at.eq(diesel::dsl::now),
+        ))
+        .execute(conn)?;
+
+        // Write a user event.
+        let new_event =
+            models::NewUserEvent::decommission_ingest_invocation(**ingest_invocation_id);
+
+        diesel::insert_into(schema::user_events::table)
+            .values(&new_event)
+            .execute(conn)?;
+
+        Ok(())
+    }
+
+    /// Mark a given ingest invocation as still being alive.
+    fn update_last_active_at_impl(
+        &self,
+        conn: &SqliteConnection,
+        ingest_invocation_id: &IngestInvocationId,
+    ) -> Result<(), Error> {
+        diesel::update(
+            schema::ingest_invocations::dsl::ingest_invocations
+                .filter(schema::ingest_invocations::dsl::id.eq(**ingest_invocation_id)),
+        )
+        .set(schema::ingest_invocations::dsl::last_active_at.eq(diesel::dsl::now))
+        .execute(conn)?;
+
+        Ok(())
+    }
+
+    fn get_ingress_key_status_impl(
+        &self,
+        conn: &SqliteConnection,
+        key: &CompressedRistrettoPublic,
+    ) -> Result<Option<IngressPublicKeyStatus>, Error> {
+        let key_bytes: &[u8] = key.as_ref();
+        use schema::ingress_keys::dsl;
+        let key_records: Vec<models::IngressKey> = dsl::ingress_keys
+            .filter(dsl::ingress_public_key.eq(key_bytes))
+            .load(conn)?;
+
+        if key_records.is_empty() {
+            Ok(None)
+        } else if key_records.len() == 1 {
+            Ok(Some(IngressPublicKeyStatus {
+                start_block: key_records[0].start_block as u64,
+                pubkey_expiry: key_records[0].pubkey_expiry as u64,
+                retired: key_records[0].retired,
+                lost: key_records[0].lost,
+            }))
+        } else {
+            Err(Error::IngressKeysSchemaViolation(format!(
+                "Found multiple entries for key: {:?}",
+                key
+            )))
+        }
+    }
+
+    fn get_last_scanned_block_index_impl(
+        conn: &SqliteConnection,
+        key: &CompressedRistrettoPublic,
+    ) -> Result<Option<u64>, Error> {
+        let key_bytes: &[u8] = key.as_ref();
+
+        use schema::ingested_blocks::dsl;
+        let maybe_index: Option<i64> = dsl::ingested_blocks
+            .filter(dsl::ingress_public_key.eq(key_bytes))
+            .select(diesel::dsl::max(dsl::block_number))
+            .first(conn)?;
+
+        Ok(maybe_index.map(|val| val as u64))
+    }
+
+    fn get_highest_known_block_index_impl(conn: &SqliteConnection) -> Result<Option<u64>, Error> {
+        Ok(schema::ingested_blocks::dsl::ingested_blocks
+            .select(diesel::dsl::max(schema::ingested_blocks::dsl::block_number))
+            .first::<Option<i64>>(conn)?
+            .map(|val| val as u64))
+    }
+
+    ////
+    // RecoveryDb functions that are meant to be retriable (don't take a conn as
+    // argument)
+    ////
+
+    fn get_ingress_key_status_retriable(
+        &self,
+        key: &CompressedRistrettoPublic,
+    ) -> Result<Option<IngressPublicKeyStatus>, Error> {
+        let conn = self.pool.get()?;
+        self.get_ingress_key_status_impl(&conn, key)
+    }
+
+    fn new_ingress_key_retriable(
+        &self,
+        key: &CompressedRistrettoPublic,
+        start_block_count: u64,
+    ) -> Result<u64, Error> {
+        let conn = self.pool.get()?;
+        conn.immediate_transaction(|| -> Result<u64, Error> {
+            let highest_known_block_count: u64 =
+                SqliteRecoveryDb::get_highest_known_block_index_impl(&conn)?
+                    .map(|index| index + 1)
+                    .unwrap_or(0);
+
+            let accepted_start_block_count = max(start_block_count, highest_known_block_count);
+            let obj = models::NewIngressKey {
+                ingress_public_key: key.as_ref().to_vec(),
+                start_block: accepted_start_block_count as i64,
+                pubkey_expiry: 0,
+                retired: false,
+                lost: false,
+            };
+
+            let inserted_row_count = diesel::insert_or_ignore_into(schema::ingress_keys::table)
+                .values(&obj)
+                .execute(&conn)?;
+
+            if inserted_row_count > 0 {
+                Ok(accepted_start_block_count)
+            } else {
+                Err(Error::IngressKeyUnsuccessfulInsert(format!(
+                    "Unable to insert ingress key: {:?}",
+                    key
+                )))
+            }
+        })
+    }
+
+    fn retire_ingress_key_retriable(
+        &self,
+        key: &CompressedRistrettoPublic,
+        set_retired: bool,
+    ) -> Result<(), Error> {
+        let key_bytes: &[u8] = key.as_ref();
+
+        let conn = self.pool.get()?;
+        use schema::ingress_keys::dsl;
+        diesel::update(dsl::ingress_keys.filter(dsl::ingress_public_key.eq(key_bytes)))
+            .set(dsl::retired.eq(set_retired))
+            .execute(&conn)?;
+        Ok(())
+    }
+
+    fn get_last_scanned_block_index_retriable(
+        &self,
+        key: &CompressedRistrettoPublic,
+    ) -> Result<Option<u64>, Error> {
+        let conn = self.pool.get()?;
+        Self::get_last_scanned_block_index_impl(&conn, key)
+    }
+
+    fn get_ingress_key_records_retriable(
+        &self,
+        start_block_at_least: u64,
+        ingress_public_key_record_filters: &IngressPublicKeyRecordFilters,
+    ) -> Result<Vec<IngressPublicKeyRecord>, Error> {
+        let conn = self.pool.get()?;
+
+        use schema::ingress_keys::dsl;
+        let last_scanned_block = diesel::dsl::sql::<BigInt>(
+                    "(SELECT MAX(block_number) FROM ingested_blocks WHERE ingress_keys.ingress_public_key = ingested_blocks.ingress_public_key)"
+                );
+        let mut query = dsl::ingress_keys
+            .select((
+                dsl::ingress_public_key,
+                dsl::start_block,
+                dsl::pubkey_expiry,
+                dsl::retired,
+                dsl::lost,
+                last_scanned_block.clone().nullable(),
+            ))
+            .filter(dsl::start_block.ge(start_block_at_least as i64))
+            // Allows for conditional queries, which means additional filter
+            // clauses can be added to this query.
+            .into_boxed();
+
+        if ingress_public_key_record_filters.should_only_include_unexpired_keys {
+            query = query
+                .filter(last_scanned_block.clone().is_not_null())
+                .filter(dsl::pubkey_expiry.gt(last_scanned_block));
+        }
+        if !ingress_public_key_record_filters.should_include_lost_keys {
+            // Adds this filter to the existing query (rather than replacing it).
+            query = query.filter(dsl::lost.eq(false));
+        }
+
+        if !ingress_public_key_record_filters.should_include_retired_keys {
+            // Adds this filter to the existing query (rather than replacing it).
+            query = query.filter(dsl::retired.eq(false));
+        }
+
+        // The list of fields here must match the .select() clause above.
+        Ok(query
+            .load::<(
+                SqlCompressedRistrettoPublic,
+                i64,
+                i64,
+                bool,
+                bool,
+                Option<i64>,
+            )>(&conn)?
+            .into_iter()
+            .map(
+                |(
+                    ingress_public_key,
+                    start_block,
+                    pubkey_expiry,
+                    retired,
+                    lost,
+                    last_scanned_block,
+                )| {
+                    let status = IngressPublicKeyStatus {
+                        start_block: start_block as u64,
+                        pubkey_expiry: pubkey_expiry as u64,
+                        retired,
+                        lost,
+                    };
+
+                    IngressPublicKeyRecord {
+                        key: *ingress_public_key,
+                        status,
+                        last_scanned_block: last_scanned_block.map(|v| v as u64),
+                    }
+                },
+            )
+            .collect())
+    }
+
+    fn new_ingest_invocation_retriable(
+        &self,
+        prev_ingest_invocation_id: Option<IngestInvocationId>,
+        ingress_public_key: &CompressedRistrettoPublic,
+        egress_public_key: &KexRngPubkey,
+        start_block: u64,
+    ) -> Result<IngestInvocationId, Error> {
+        let conn = self.pool.get()?;
+        conn.immediate_transaction(|| {
+            // Optionally decommission old invocation.
+            if let Some(prev_ingest_invocation_id) = prev_ingest_invocation_id {
+                self.decommission_ingest_invocation_impl(&conn, &prev_ingest_invocation_id)?;
+            }
+
+            // Write new invocation.
+            let now =
+                diesel::select(diesel::dsl::now).get_result::<chrono::NaiveDateTime>(&conn)?;
+
+            let obj = models::NewIngestInvocation {
+                ingress_public_key: ingress_public_key.as_ref().to_vec(),
+                egress_public_key: egress_public_key.public_key.clone(),
+                last_active_at: now,
+                start_block: start_block as i64,
+                decommissioned: false,
+                rng_version: egress_public_key.version as i32,
+            };
+
+            diesel::insert_into(schema::ingest_invocations::table)
+                .values(&obj)
+                .execute(&conn)?;
+            let ingest_invocation_id =
+                diesel::select(last_insert_rowid).get_result::<i64>(&conn)?;
+
+            // Write a user event.
+            let new_event = models::NewUserEvent::new_ingest_invocation(ingest_invocation_id);
+
+            diesel::insert_into(schema::user_events::table)
+                .values(&new_event)
+                .execute(&conn)?;
+
+            // Success.
+            Ok(IngestInvocationId::from(ingest_invocation_id))
+        })
+    }
+
+    fn get_ingestable_ranges_retriable(
+        &self,
+    ) -> Result<Vec<mc_fog_recovery_db_iface::IngestableRange>, Error> {
+        let conn = self.pool.get()?;
+
+        // For each ingest invocation we are aware of get its id, start block, is
+        // decommissioned and the max block number it has ingested (if
+        // available).
+        let query = schema::ingest_invocations::dsl::ingest_invocations
+            .select((
+                schema::ingest_invocations::dsl::id,
+                schema::ingest_invocations::dsl::start_block,
+                schema::ingest_invocations::dsl::decommissioned,
+                diesel::dsl::sql::<BigInt>(
+                    "(SELECT MAX(block_number) FROM ingested_blocks WHERE ingest_invocations.id = ingested_blocks.ingest_invocation_id)"
+                ).nullable(),
+            ))
+            .order_by(schema::ingest_invocations::dsl::id);
+
+        // The list of fields here must match the .select() clause above.
+        let data = query.load::<(i64, i64, bool, Option<i64>)>(&conn)?;
+        Ok(data
+            .into_iter()
+            .map(|row| {
+                let (ingest_invocation_id, start_block, decommissioned, last_ingested_block) = row;
+
+                mc_fog_recovery_db_iface::IngestableRange {
+                    id: IngestInvocationId::from(ingest_invocation_id),
+                    start_block: start_block as u64,
+                    decommissioned,
+                    last_ingested_block: last_ingested_block.map(|v| v as u64),
+                }
+            })
+            .collect())
+    }
+
+    fn decommission_ingest_invocation_retriable(
+        &self,
+        ingest_invocation_id: &IngestInvocationId,
+    ) -> Result<(), Error> {
+        let conn = self.pool.get()?;
+
+        conn.immediate_transaction(|| {
+            self.decommission_ingest_invocation_impl(&conn, ingest_invocation_id)
+        })
+    }
+
+    fn add_block_data_retriable(
+        &self,
+        ingest_invocation_id: &IngestInvocationId,
+        block: &Block,
+        block_signature_timestamp: u64,
+        txs: &[mc_fog_types::ETxOutRecord],
+    ) -> Result<AddBlockDataStatus, Error> {
+        let conn = self.pool.get()?;
+
+        match conn.immediate_transaction(|| -> Result<(), Error> {
+            // Get ingress pubkey of this ingest invocation id, which is also stored in the
+            // ingested_block record
+            let ingress_key_bytes: Vec<u8> = schema::ingest_invocations::table
+                .filter(schema::ingest_invocations::dsl::id.eq(**ingest_invocation_id))
+                .select(schema::ingest_invocations::ingress_public_key)
+                .first(&conn)?;
+
+            // Get bytes of encoded proto ingested block data
+            let proto_bytes = {
+                let proto_ingested_block_data = ProtoIngestedBlockData {
+                    e_tx_out_records: txs.to_vec(),
+                };
+                let mut bytes = Vec::<u8>::with_capacity(proto_ingested_block_data.encoded_len());
+                proto_ingested_block_data.encode(&mut bytes)?;
+                bytes
+            };
+
+            // Add an IngestedBlock record.
+            let new_ingested_block = models::NewIngestedBlock {
+                ingress_public_key: ingress_key_bytes,
+                ingest_invocation_id: **ingest_invocation_id,
+                block_number: block.index as i64,
+                cumulative_txo_count: block.cumulative_txo_count as i64,
+                block_signature_timestamp: block_signature_timestamp as i64,
+                proto_ingested_block_data: proto_bytes,
+            };
+
+            diesel::insert_into(schema::ingested_blocks::table)
+                .values(&new_ingested_block)
+                .execute(&conn)?;
+
+            // Update last active at.
+            self.update_last_active_at_impl(&conn, ingest_invocation_id)?;
+
+            // Success.
+            Ok(())
+        }) {
+            Ok(()) => Ok(AddBlockDataStatus {
+                block_already_scanned_with_this_key: false,
+            }),
+            // If a unique constraint is violated, the block was already scanned with this
+            // key, see SqlRecoveryDb.
+            Err(Error::Orm(diesel::result::Error::DatabaseError(
+                diesel::result::DatabaseErrorKind::UniqueViolation,
+                _,
+            ))) => Ok(AddBlockDataStatus {
+                block_already_scanned_with_this_key: true,
+            }),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn report_lost_ingress_key_retriable(
+        &self,
+        lost_ingress_key: CompressedRistrettoPublic,
+    ) -> Result<(), Error> {
+        let conn = self.pool.get()?;
+
+        conn.immediate_transaction(|| {
+            // Find the ingress key and update it to be marked lost
+            let key_bytes: &[u8] = lost_ingress_key.as_ref();
+            {
+                use schema::ingress_keys::dsl;
+                diesel::update(dsl::ingress_keys.filter(dsl::ingress_public_key.eq(key_bytes)))
+                    .set(dsl::lost.eq(true))
+                    .execute(&conn)?;
+            }
+
+            // Compute a missed block range based on looking at the key status,
+            // which is correct if no blocks have actually been scanned using the key.
+            let key_status = self
+                .get_ingress_key_status_impl(&conn, &lost_ingress_key)?
+                .ok_or(Error::MissingIngressKey(lost_ingress_key))?;
+            let mut missed_block_range = BlockRange {
+                start_block: key_status.start_block,
+                end_block: key_status.pubkey_expiry,
+            };
+
+            // Find the last scanned block index (if any block has been scanned with this
+            // key)
+            let maybe_block_index =
+                Self::get_last_scanned_block_index_impl(&conn, &lost_ingress_key)?;
+
+            if let Some(block_index) = maybe_block_index {
+                if block_index + 1 >= missed_block_range.end_block {
+                    // There aren't actually any blocks that need to be scanned, so we are done
+                    // without creating a user event.
+                    return Ok(());
+                }
+                // If we did actually scan some blocks, then report a smaller range
+                if block_index + 1 > missed_block_range.start_block {
+                    missed_block_range.start_block = block_index + 1;
+                }
+            }
+
+            // If the missed block range is invalid (empty), we don't have to add it.
+            // This can happen if the ingress key was never actually published to the report
+            // server, and then pubkey_expiry is zero.
+            if !missed_block_range.is_valid() {
+                return Ok(());
+            }
+
+            // Add new range.
+            let new_event = models::NewUserEvent::missing_blocks(&missed_block_range);
+
+            diesel::insert_into(schema::user_events::table)
+                .values(&new_event)
+                .execute(&conn)?;
+
+            Ok(())
+        })
+    }
+
+    fn get_missed_block_ranges_retriable(&self) -> Result<Vec<BlockRange>, Error> {
+        let conn = self.pool.get()?;
+
+        let query = schema::user_events::dsl::user_events
+            .filter(schema::user_events::dsl::event_type.eq(UserEventType::MissingBlocks))
+            .select((
+                schema::user_events::dsl::id,
+                schema::user_events::dsl::missing_blocks_start,
+                schema::user_events::dsl::missing_blocks_end,
+            ))
+            .order_by(schema::user_events::dsl::id);
+
+        let rows = query.load::<(i64, Option<i64>, Option<i64>)>(&conn)?;
+
+        rows.iter()
+            .map(|row| match row {
+                (_, Some(start_index), Some(end_index)) => {
+                    Ok(BlockRange::new(*start_index as u64, *end_index as u64))
+                }
+                (id, _, _) => Err(Error::UserEventSchemaViolation(
+                    *id,
+                    "missing start or end block indices",
+                )),
+            })
+            .collect::<Result<Vec<BlockRange>, Error>>()
+    }
+
+    fn search_user_events_retriable(
+        &self,
+        start_from_user_event_id: i64,
+    ) -> Result<(Vec<FogUserEvent>, i64), Error> {
+        // Early return if start_from_user_event_id is max
+        if start_from_user_event_id == i64::MAX {
+            return Ok((Default::default(), i64::MAX));
+        }
+
+        let conn = self.pool.get()?;
+
+        // Collect all events of interest
+        let query = schema::user_events::dsl::user_events
+            // Left-join ingest invocation information, needed for NewRngRecord events
+            .left_join(
+                schema::ingest_invocations::dsl::ingest_invocations.on(
+                    schema::user_events::dsl::new_ingest_invocation_id.eq(
+                        schema::ingest_invocations::dsl::id.nullable()
+                    )
+                )
+            )
+            // Filtered by the subset of ids we are exploring
+            // NOTE: sql auto increment columns start from 1, so "start_from_user_event_id = 0"
+            // will capture everything
+            .filter(schema::user_events::dsl::id.gt(start_from_user_event_id))
+            .order_by(schema::user_events::dsl::id)
+            // Get only the fields that we need
+            .select((
+                // Fields for every event type
+                schema::user_events::dsl::id,
+                schema::user_events::dsl::event_type,
+                // Fields for NewIngestInvocation events
+                schema::ingest_invocations::dsl::id.nullable(),
+                schema::ingest_invocations::dsl::egress_public_key.nullable(),
+                schema::ingest_invocations::dsl::rng_version.nullable(),
+                schema::ingest_invocations::dsl::start_block.nullable(),
+                // Fields for DecommissionIngestInvocation
+                schema::user_events::dsl::decommission_ingest_invocation_id,
+                diesel::dsl::sql::<BigInt>("(SELECT COALESCE(MAX(block_number), 0) FROM ingested_blocks WHERE user_events.event_type = 'decommission_ingest_invocation' AND ingested_blocks.ingest_invocation_id = user_events.decommission_ingest_invocation_id)"),
+                // Fields for MissingBlocks events
+                schema::user_events::dsl::missing_blocks_start,
+                schema::user_events::dsl::missing_blocks_end,
+            ));
+
+        // The list of fields here must match the .select() clause above.
+        let data = query.load::<(
+            // For all event types
+            i64,           // user_events.id
+            UserEventType, // user_events.event_type
+            // For NewRngRecord events
+            Option<i64>,     // rng_record.ingest_invocation_id
+            Option<Vec<u8>>, // rng_record.egress_public_key
+            Option<i32>,     // rng_record.rng_version
+            Option<i64>,     // rng_record.start_block
+            // For DecommissionIngestInvocation events
+            Option<i64>, // ingest_invocations.id
+            i64,         // MAX(ingested_blocks.block_number)
+            // For MissingBlocks events
+            Option<i64>, // user_events.missing_blocks_start
+            Option<i64>, // user_events.missing_blocks_end
+        )>(&conn)?;
+
+        // If no events are found, return start_from_user_event_id and not 0
+        let mut max_user_event_id = start_from_user_event_id;
+        let mut events = Vec::with_capacity(data.len());
+        for row in data.into_iter() {
+            // The list of fields here must match the .select() clause above.
+            let (
+                user_event_id,
+                user_event_type,
+                rng_record_ingest_invocation_id,
+                rng_record_egress_public_key,
+                rng_record_rng_version,
+                rng_record_start_block,
+                decommission_ingest_invocation_id,
+                decommission_ingest_invocation_max_block,
+                missing_blocks_start,
+                missing_blocks_end,
+            ) = row;
+
+            // Update running max
+            max_user_event_id = max(max_user_event_id, user_event_id);
+
+            let missing = |field| Error::UserEventSchemaViolation(user_event_id, field);
+            events.push(match user_event_type {
+                UserEventType::NewIngestInvocation => {
+                    FogUserEvent::NewRngRecord(mc_fog_types::view::RngRecord {
+                        ingest_invocation_id: rng_record_ingest_invocation_id
+                            .ok_or_else(|| missing("missing rng_record_ingest_invocation_id"))?,
+                        pubkey: mc_fog_types::view::KexRngPubkey {
+                            public_key: rng_record_egress_public_key
+                                .ok_or_else(|| missing("missing rng_record_egress_public_key"))?,
+                            version: rng_record_rng_version
+                                .ok_or_else(|| missing("missing rng_record_rng_version"))?
+                                as u32,
+                        },
+                        start_block: rng_record_start_block
+                            .ok_or_else(|| missing("missing rng_record_start_block"))?
+                            as u64,
+                    })
+                }
+                UserEventType::DecommissionIngestInvocation => {
+                    FogUserEvent::DecommissionIngestInvocation(
+                        mc_fog_types::view::DecommissionedIngestInvocation {
+                            ingest_invocation_id: decommission_ingest_invocation_id.ok_or_else(
+                                || missing("missing decommission_ingest_invocation_id"),
+                            )?,
+                            last_ingested_block: decommission_ingest_invocation_max_block as u64,
+                        },
+                    )
+                }
+                UserEventType::MissingBlocks => FogUserEvent::MissingBlocks(BlockRange {
+                    start_block: missing_blocks_start
+                        .ok_or_else(|| missing("missing missing_blocks_start"))?
+                        as u64,
+                    end_block: missing_blocks_end
+                        .ok_or_else(|| missing("missing missing_blocks_end"))?
+                        as u64,
+                }),
+            });
+        }
+
+        Ok((events, max_user_event_id))
+    }
+
+    /// Get any TxOutSearchResults corresponding to given search keys.
+    /// Nonzero start_block can be provided as an optimization opportunity.
+    ///
+    /// Note: This scans every ETxOutRecord from start_block on, and should not
+    /// be used except in tests.
+    fn get_tx_outs_retriable(
+        &self,
+        start_block: u64,
+        search_keys: &[Vec<u8>],
+    ) -> Result<Vec<TxOutSearchResult>, Error> {
+        let conn = self.pool.get()?;
+
+        let query = schema::ingested_blocks::dsl::ingested_blocks
+            .filter(schema::ingested_blocks::dsl::block_number.ge(start_block as i64))
+            .select(schema::ingested_blocks::dsl::proto_ingested_block_data);
+
+        let mut search_key_to_payload = HashMap::<Vec<u8>, Vec<u8>>::default();
+        for proto_bytes in query.load::<Vec<u8>>(&conn)? {
+            let proto = ProtoIngestedBlockData::decode(&*proto_bytes)?;
+            for e_tx_out_record in proto.e_tx_out_records {
+                search_key_to_payload.insert(e_tx_out_record.search_key, e_tx_out_record.payload);
+            }
+        }
+
+        Ok(search_keys
+            .iter()
+            .map(|search_key| match search_key_to_payload.get(search_key) {
+                Some(payload) => TxOutSearchResult {
+                    search_key: search_key.clone(),
+                    result_code: TxOutSearchResultCode::Found as u32,
+                    ciphertext: payload.clone(),
+                },
+
+                None => TxOutSearchResult {
+                    search_key: search_key.clone(),
+                    result_code: TxOutSearchResultCode::NotFound as u32,
+                    ciphertext: Default::default(),
+                },
+            })
+            .collect())
+    }
+
+    fn update_last_active_at_retriable(
+        &self,
+        ingest_invocation_id: &IngestInvocationId,
+    ) -> Result<(), Error> {
+        let conn = self.pool.get()?;
+        self.update_last_active_at_impl(&conn, ingest_invocation_id)
+    }
+
+    fn report_view_shard_progress_retriable(
+        &self,
+        shard_id: &str,
+        highest_processed_block_count: u64,
+    ) -> Result<(), Error> {
+        let conn = self.pool.get()?;
+
+        diesel::sql_query(
+            "INSERT INTO view_shard_progress (shard_id, highest_processed_block_count, updated_at) \
+             VALUES (?, ?, CURRENT_TIMESTAMP) \
+             ON CONFLICT (shard_id) DO UPDATE \
+             SET highest_processed_block_count = excluded.highest_processed_block_count, \
+             updated_at = excluded.updated_at",
+        )
+        .bind::<Text, _>(shard_id)
+        .bind::<BigInt, _>(highest_processed_block_count as i64)
+        .execute(&conn)?;
+
+        Ok(())
+    }
+
+    fn get_tx_outs_by_block_and_key_retriable(
+        &self,
+        ingress_key: CompressedRistrettoPublic,
+        block_index: u64,
+    ) -> Result<Option<Vec<ETxOutRecord>>, Error> {
+        let conn = self.pool.get()?;
+
+        let key_bytes: &[u8] = ingress_key.as_ref();
+        let query = schema::ingested_blocks::dsl::ingested_blocks
+            .filter(schema::ingested_blocks::dsl::ingress_public_key.eq(key_bytes))
+            .filter(schema::ingested_blocks::dsl::block_number.eq(block_index as i64))
+            .select(schema::ingested_blocks::dsl::proto_ingested_block_data);
+
+        // The result of load should be 0 or 1, since there is a database constraint
+        // around ingress keys and block indices
+        let protos: Vec<Vec<u8>> = query.load::<Vec<u8>>(&conn)?;
+
+        if protos.is_empty() {
+            Ok(None)
+        } else if protos.len() == 1 {
+            let proto = ProtoIngestedBlockData::decode(&*protos[0])?;
+            Ok(Some(proto.e_tx_out_records))
+        } else {
+            Err(Error::IngestedBlockSchemaViolation(format!("Found {} different entries for ingress_key {:?} and block_index {}, which goes against the constraint", protos.len(), ingress_key, block_index)))
+        }
+    }
+
+    fn get_invocation_id_by_block_and_key_retriable(
+        &self,
+        ingress_key: CompressedRistrettoPublic,
+        block_index: u64,
+    ) -> Result<Option<IngestInvocationId>, Error> {
+        let conn = self.pool.get()?;
+
+        let key_bytes: &[u8] = ingress_key.as_ref();
+        let query = schema::ingested_blocks::dsl::ingested_blocks
+            .filter(schema::ingested_blocks::dsl::ingress_public_key.eq(key_bytes))
+            .filter(schema::ingested_blocks::dsl::block_number.eq(block_index as i64))
+            .select(schema::ingested_blocks::dsl::ingest_invocation_id);
+
+        // The result of load should be 0 or 1, since there is a database constraint
+        // around ingress keys and block indices
+        let iids: Vec<i64> = query.load::<i64>(&conn)?;
+
+        if iids.is_empty() {
+            Ok(None)
+        } else if iids.len() == 1 {
+            Ok(Some(iids[0].into()))
+        } else {
+            Err(Error::IngestedBlockSchemaViolation(format!("Found {} different entries for ingress_key {:?} and block_index {}, which goes against the constraint", iids.len(), ingress_key, block_index)))
+        }
+    }
+
+    fn get_cumulative_txo_count_for_block_retriable(
+        &self,
+        block_index: u64,
+    ) -> Result<Option<u64>, Error> {
+        let conn = self.pool.get()?;
+
+        let query = schema::ingested_blocks::dsl::ingested_blocks
+            .filter(schema::ingested_blocks::dsl::block_number.eq(block_index as i64))
+            .select(schema::ingested_blocks::dsl::cumulative_txo_count);
+
+        let data = query.load::<i64>(&conn)?;
+        if data.is_empty() {
+            Ok(None)
+        } else {
+            let cumulative_txo_count = data[0];
+            if data.iter().all(|val| *val == cumulative_txo_count) {
+                Ok(Some(cumulative_txo_count as u64))
+            } else {
+                Err(Error::IngestedBlockSchemaViolation(format!(
+                    "Found multiple cumulative_txo_count values for block {}: {:?}",
+                    block_index, data
+                )))
+            }
+        }
+    }
+
+    fn get_block_signature_timestamp_for_block_retriable(
+        &self,
+        block_index: u64,
+    ) -> Result<Option<u64>, Error> {
+        let conn = self.pool.get()?;
+
+        let query = schema::ingested_blocks::dsl::ingested_blocks
+            .filter(schema::ingested_blocks::dsl::block_number.eq(block_index as i64))
+            .select(schema::ingested_blocks::dsl::block_signature_timestamp);
+
+        let data = query.load::<i64>(&conn)?;
+        Ok(data.first().map(|val| *val as u64))
+    }
+
+    fn get_highest_known_block_index_retriable(&self) -> Result<Option<u64>, Error> {
+        let conn = self.pool.get()?;
+        SqliteRecoveryDb::get_highest_known_block_index_impl(&conn)
+    }
+
+    ////
+    // ReportDb functions that are meant to be retriable (don't take a conn as
+    // argument)
+    ////
+
+    fn get_all_reports_retriable(&self) -> Result<Vec<(String, ReportData)>, Error> {
+        let conn = self.pool.get()?;
+
+        // Reports of lost ingress keys are not served, and within a report id,
+        // the report with the latest pubkey expiry comes first, like in
+        // SqlRecoveryDb.
+        let query = schema::reports::dsl::reports
+            .inner_join(schema::ingress_keys::dsl::ingress_keys)
+            .filter(schema::ingress_keys::dsl::lost.eq(false))
+            .select((
+                schema::reports::dsl::ingest_invocation_id,
+                schema::reports::dsl::fog_report_id,
+                schema::reports::dsl::report,
+                schema::reports::dsl::pubkey_expiry,
+            ))
+            .order_by((
+                schema::reports::dsl::fog_report_id,
+                schema::reports::dsl::pubkey_expiry.desc(),
+                schema::reports::dsl::id,
+            ));
+
+        query
+            .load::<(Option<i64>, String, Vec<u8>, i64)>(&conn)?
+            .into_iter()
+            .map(|(ingest_invocation_id, report_id, report, pubkey_expiry)| {
+                let report = VerificationReport::decode(&*report)?;
+                Ok((
+                    report_id,
+                    ReportData {
+                        ingest_invocation_id: ingest_invocation_id.map(IngestInvocationId::from),
+                        report,
+                        pubkey_expiry: pubkey_expiry as u64,
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    fn set_report_retriable(
+        &self,
+        ingress_key: &CompressedRistrettoPublic,
+        report_id: &str,
+        data: &ReportData,
+    ) -> Result<IngressPublicKeyStatus, Error> {
+        let conn = self.pool.get()?;
+
+        conn.immediate_transaction(|| -> Result<IngressPublicKeyStatus, Error> {
+            // First, try to update the pubkey_expiry value on this ingress key, only
+            // allowing it to increase, and only if it is not retired
+            let key_bytes: &[u8] = ingress_key.as_ref();
+            {
+                use schema::ingress_keys::dsl;
+                diesel::update(
+                    dsl::ingress_keys
+                        .filter(dsl::ingress_public_key.eq(key_bytes))
+                        .filter(dsl::retired.eq(false))
+                        .filter(dsl::pubkey_expiry.lt(data.pubkey_expiry as i64)),
+                )
+                .set(dsl::pubkey_expiry.eq(data.pubkey_expiry as i64))
+                .execute(&conn)?;
+            }
+
+            // The key might not exist, or it might have had a larger pubkey expiry
+            // (because this server is behind), which the status tells.
+            let result = self
+                .get_ingress_key_status_impl(&conn, ingress_key)?
+                .ok_or(Error::MissingIngressKey(*ingress_key))?;
+
+            log::info!(self.logger, "Got status for key: {:?}", result);
+            if result.retired {
+                log::info!(self.logger, "Cannot publish key because it is retired");
+                return Ok(result);
+            }
+
+            let mut report_bytes = Vec::with_capacity(data.report.encoded_len());
+            data.report.encode(&mut report_bytes)?;
+
+            diesel::sql_query(
+                "INSERT INTO reports \
+                 (ingress_public_key, ingest_invocation_id, fog_report_id, report, pubkey_expiry) \
+                 VALUES (?, ?, ?, ?, ?) \
+                 ON CONFLICT (fog_report_id, ingress_public_key) DO UPDATE \
+                 SET ingest_invocation_id = excluded.ingest_invocation_id, \
+                 report = excluded.report, \
+                 pubkey_expiry = excluded.pubkey_expiry",
+            )
+            .bind::<Binary, _>(key_bytes)
+            .bind::<Nullable<BigInt>, _>(data.ingest_invocation_id.map(i64::from))
+            .bind::<Text, _>(report_id)
+            .bind::<Binary, _>(report_bytes)
+            .bind::<BigInt, _>(data.pubkey_expiry as i64)
+            .execute(&conn)?;
+
+            Ok(result)
+        })
+    }
+
+    fn remove_report_retriable(&self, report_id: &str) -> Result<(), Error> {
+        let conn = self.pool.get()?;
+        diesel::delete(
+            schema::reports::dsl::reports.filter(schema::reports::dsl::fog_report_id.eq(report_id)),
+        )
+        .execute(&conn)?;
+        Ok(())
+    }
+}
+
+/// See trait `fog_recovery_db_iface::RecoveryDb` for documentation.
+impl RecoveryDb for SqliteRecoveryDb {
+    type Error = Error;
+
+    fn get_ingress_key_status(
+        &self,
+        key: &CompressedRistrettoPublic,
+    ) -> Result<Option<IngressPublicKeyStatus>, Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.get_ingress_key_status_retriable(key)
+        })
+    }
+
+    fn new_ingress_key(
+        &self,
+        key: &CompressedRistrettoPublic,
+        start_block_count: u64,
+    ) -> Result<u64, Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.new_ingress_key_retriable(key, start_block_count)
+        })
+    }
+
+    fn retire_ingress_key(
+        &self,
+        key: &CompressedRistrettoPublic,
+        set_retired: bool,
+    ) -> Result<(), Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.retire_ingress_key_retriable(key, set_retired)
+        })
+    }
+
+    fn get_last_scanned_block_index(
+        &self,
+        key: &CompressedRistrettoPublic,
+    ) -> Result<Option<u64>, Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.get_last_scanned_block_index_retriable(key)
+        })
+    }
+
+    fn get_ingress_key_records(
+        &self,
+        start_block_at_least: u64,
+        ingress_public_key_record_filters: &IngressPublicKeyRecordFilters,
+    ) -> Result<Vec<IngressPublicKeyRecord>, Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.get_ingress_key_records_retriable(
+                start_block_at_least,
+                ingress_public_key_record_filters,
+            )
+        })
+    }
+
+    fn new_ingest_invocation(
+        &self,
+        prev_ingest_invocation_id: Option<IngestInvocationId>,
+        ingress_public_key: &CompressedRistrettoPublic,
+        egress_public_key: &KexRngPubkey,
+        start_block: u64,
+    ) -> Result<IngestInvocationId, Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.new_ingest_invocation_retriable(
+                prev_ingest_invocation_id,
+                ingress_public_key,
+                egress_public_key,
+                start_block,
+            )
+        })
+    }
+
+    fn get_ingestable_ranges(
+        &self,
+    ) -> Result<Vec<mc_fog_recovery_db_iface::IngestableRange>, Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.get_ingestable_ranges_retriable()
+        })
+    }
+
+    fn decommission_ingest_invocation(
+        &self,
+        ingest_invocation_id: &IngestInvocationId,
+    ) -> Result<(), Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.decommission_ingest_invocation_retriable(ingest_invocation_id)
+        })
+    }
+
+    fn add_block_data(
+        &self,
+        ingest_invocation_id: &IngestInvocationId,
+        block: &Block,
+        block_signature_timestamp: u64,
+        txs: &[mc_fog_types::ETxOutRecord],
+    ) -> Result<AddBlockDataStatus, Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.add_block_data_retriable(
+                ingest_invocation_id,
+                block,
+                block_signature_timestamp,
+                txs,
+            )
+        })
+    }
+
+    fn report_lost_ingress_key(
+        &self,
+        lost_ingress_key: CompressedRistrettoPublic,
+    ) -> Result<(), Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.report_lost_ingress_key_retriable(lost_ingress_key)
+        })
+    }
+
+    fn get_missed_block_ranges(&self) -> Result<Vec<BlockRange>, Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.get_missed_block_ranges_retriable()
+        })
+    }
+
+    fn search_user_events(
+        &self,
+        start_from_user_event_id: i64,
+    ) -> Result<(Vec<FogUserEvent>, i64), Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.search_user_events_retriable(start_from_user_event_id)
+        })
+    }
+
+    fn get_tx_outs(
+        &self,
+        start_block: u64,
+        search_keys: &[Vec<u8>],
+    ) -> Result<Vec<TxOutSearchResult>, Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.get_tx_outs_retriable(start_block, search_keys)
+        })
+    }
+
+    fn update_last_active_at(
+        &self,
+        ingest_invocation_id: &IngestInvocationId,
+    ) -> Result<(), Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.update_last_active_at_retriable(ingest_invocation_id)
+        })
+    }
+
+    fn get_tx_outs_by_block_and_key(
+        &self,
+        ingress_key: CompressedRistrettoPublic,
+        block_index: u64,
+    ) -> Result<Option<Vec<ETxOutRecord>>, Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.get_tx_outs_by_block_and_key_retriable(ingress_key, block_index)
+        })
+    }
+
+    fn get_invocation_id_by_block_and_key(
+        &self,
+        ingress_key: CompressedRistrettoPublic,
+        block_index: u64,
+    ) -> Result<Option<IngestInvocationId>, Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.get_invocation_id_by_block_and_key_retriable(ingress_key, block_index)
+        })
+    }
+
+    fn get_cumulative_txo_count_for_block(
+        &self,
+        block_index: u64,
+    ) -> Result<Option<u64>, Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.get_cumulative_txo_count_for_block_retriable(block_index)
+        })
+    }
+
+    fn get_block_signature_timestamp_for_block(
+        &self,
+        block_index: u64,
+    ) -> Result<Option<u64>, Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.get_block_signature_timestamp_for_block_retriable(block_index)
+        })
+    }
+
+    fn get_highest_known_block_index(&self) -> Result<Option<u64>, Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.get_highest_known_block_index_retriable()
+        })
+    }
+
+    // The overseer, which is the only user of leases, is not meant to run
+    // against a SQLite database.
+
+    fn try_acquire_lease(
+        &self,
+        _name: &str,
+        _holder: &str,
+        _duration: Duration,
+    ) -> Result<Option<u64>, Self::Error> {
+        Err(Error::LeasesNotSupported)
+    }
+
+    fn check_lease(&self, _name: &str, _holder: &str, _token: u64) -> Result<bool, Self::Error> {
+        Err(Error::LeasesNotSupported)
+    }
+
+    fn release_lease(&self, _name: &str, _holder: &str) -> Result<(), Self::Error> {
+        Err(Error::LeasesNotSupported)
+    }
+
+    fn get_lease_holder(&self, _name: &str) -> Result<Option<String>, Self::Error> {
+        Err(Error::LeasesNotSupported)
+    }
+
+    fn report_view_shard_progress(
+        &self,
+        shard_id: &str,
+        highest_processed_block_count: u64,
+    ) -> Result<(), Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.report_view_shard_progress_retriable(shard_id, highest_processed_block_count)
+        })
+    }
+}
+
+/// See trait `fog_recovery_db_iface::ReportDb` for documentation.
+impl ReportDb for SqliteRecoveryDb {
+    type Error = Error;
+
+    fn get_all_reports(&self) -> Result<Vec<(String, ReportData)>, Self::Error> {
+        our_retry(self.get_retries(), || self.get_all_reports_retriable())
+    }
+
+    fn set_report(
+        &self,
+        ingress_key: &CompressedRistrettoPublic,
+        report_id: &str,
+        data: &ReportData,
+    ) -> Result<IngressPublicKeyStatus, Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.set_report_retriable(ingress_key, report_id, data)
+        })
+    }
+
+    fn remove_report(&self, report_id: &str) -> Result<(), Self::Error> {
+        our_retry(self.get_retries(), || {
+            self.remove_report_retriable(report_id)
+        })
+    }
+}
+
+// Helper for using the retry crate's retry function, which only retries the
+// errors which are retriable. See the same helper in fog-sql-recovery-db.
+fn our_retry<I, O, R>(iterable: I, mut operation: O) -> Result<R, Error>
+where
+    I: IntoIterator<Item = Duration>,
+    O: FnMut() -> Result<R, Error>,
+{
+    retry::retry(iterable, || match operation() {
+        Ok(ok) => OperationResult::Ok(ok),
+        Err(err) => {
+            if err.should_retry() {
+                OperationResult::Retry(err)
+            } else {
+                OperationResult::Err(err)
+            }
+        }
+    })
+    .map_err(unpack_retry_error)
+}
+
+fn unpack_retry_error(src: RetryError<Error>) -> Error {
+    match src {
+        RetryError::Operation { error, .. } => error,
+        RetryError::Internal(_) => {
+            panic!("This is unreachable, see https://github.com/jimmycuadra/retry/issues/38")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_common::logger::{test_with_logger, Logger};
+    use mc_crypto_keys::RistrettoPublic;
+    use mc_fog_test_infra::db_tests::{random_block, random_kex_rng_pubkey};
+    use mc_util_from_random::FromRandom;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn create_report(name: &str) -> VerificationReport {
+        let chain = pem::parse_many(mc_crypto_x509_test_vectors::ok_rsa_chain_25519_leaf().0)
+            .expect("Could not parse PEM contents")
+            .into_iter()
+            .map(|p| p.contents)
+            .collect();
+
+        VerificationReport {
+            sig: format!("{} sig", name).into_bytes().into(),
+            chain,
+            http_body: format!("{} body", name),
+        }
+    }
+
+    #[test_with_logger]
+    fn test_ingest_invocations_and_user_events(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([123u8; 32]);
+        let db_test_context = test_utils::SqliteRecoveryDbTestContext::new(logger);
+        let db = db_test_context.get_db_instance();
+
+        let ingress_key = CompressedRistrettoPublic::from(RistrettoPublic::from_random(&mut rng));
+        assert_eq!(db.new_ingress_key(&ingress_key, 0).unwrap(), 0);
+        // A key can only be added once
+        assert!(db.new_ingress_key(&ingress_key, 0).is_err());
+
+        let egress_key1 = random_kex_rng_pubkey(&mut rng);
+        let invoc_id1 = db
+            .new_ingest_invocation(None, &ingress_key, &egress_key1, 0)
+            .unwrap();
+
+        let (block, records) = random_block(&mut rng, 0, 10);
+        let status = db.add_block_data(&invoc_id1, &block, 0, &records).unwrap();
+        assert!(!status.block_already_scanned_with_this_key);
+        let (block, records) = random_block(&mut rng, 1, 10);
+        db.add_block_data(&invoc_id1, &block, 0, &records).unwrap();
+
+        // The new invocation takes over from the first one, and learns that the
+        // block was already scanned with the key
+        let egress_key2 = random_kex_rng_pubkey(&mut rng);
+        let invoc_id2 = db
+            .new_ingest_invocation(Some(invoc_id1), &ingress_key, &egress_key2, 2)
+            .unwrap();
+        let status = db.add_block_data(&invoc_id2, &block, 0, &records).unwrap();
+        assert!(status.block_already_scanned_with_this_key);
+
+        assert_eq!(
+            db.get_last_scanned_block_index(&ingress_key).unwrap(),
+            Some(1)
+        );
+        assert_eq!(db.get_highest_known_block_index().unwrap(), Some(1));
+        assert_eq!(
+            db.get_invocation_id_by_block_and_key(ingress_key, 1)
+                .unwrap(),
+            Some(invoc_id1)
+        );
+        assert_eq!(
+            db.get_tx_outs_by_block_and_key(ingress_key, 1).unwrap(),
+            Some(records)
+        );
+        assert_eq!(
+            db.get_tx_outs_by_block_and_key(ingress_key, 2).unwrap(),
+            None
+        );
+        assert_eq!(
+            db.get_cumulative_txo_count_for_block(1).unwrap(),
+            Some(block.cumulative_txo_count)
+        );
+
+        let ranges = db.get_ingestable_ranges().unwrap();
+        assert_eq!(ranges.len(), 2);
+        assert_eq!(ranges[0].id, invoc_id1);
+        assert!(ranges[0].decommissioned);
+        assert_eq!(ranges[0].last_ingested_block, Some(1));
+        assert_eq!(ranges[1].id, invoc_id2);
+        assert!(!ranges[1].decommissioned);
+        assert_eq!(ranges[1].last_ingested_block, None);
+
+        let (events, next_start_from_user_event_id) = db.search_user_events(0).unwrap();
+        assert_eq!(
+            events,
+            vec![
+                FogUserEvent::NewRngRecord(mc_fog_types::view::RngRecord {
+                    ingest_invocation_id: *invoc_id1,
+                    pubkey: mc_fog_types::view::KexRngPubkey {
+                        public_key: egress_key1.public_key.clone(),
+                        version: egress_key1.version,
+                    },
+                    start_block: 0,
+                }),
+                FogUserEvent::DecommissionIngestInvocation(
+                    mc_fog_types::view::DecommissionedIngestInvocation {
+                        ingest_invocation_id: *invoc_id1,
+                        last_ingested_block: 1,
+                    }
+                ),
+                FogUserEvent::NewRngRecord(mc_fog_types::view::RngRecord {
+                    ingest_invocation_id: *invoc_id2,
+                    pubkey: mc_fog_types::view::KexRngPubkey {
+                        public_key: egress_key2.public_key.clone(),
+                        version: egress_key2.version,
+                    },
+                    start_block: 2,
+                }),
+            ]
+        );
+        assert_eq!(
+            db.search_user_events(next_start_from_user_event_id)
+                .unwrap(),
+            (vec![], next_start_from_user_event_id)
+        );
+
+        // Losing the key before its pubkey expiry reports the blocks it missed
+        db.set_report(
+            &ingress_key,
+            "",
+            &ReportData {
+                ingest_invocation_id: Some(invoc_id2),
+                report: create_report(""),
+                pubkey_expiry: 10,
+            },
+        )
+        .unwrap();
+        db.report_lost_ingress_key(ingress_key).unwrap();
+        assert_eq!(
+            db.get_missed_block_ranges().unwrap(),
+            vec![BlockRange::new(2, 10)]
+        );
+        assert_eq!(
+            db.search_user_events(next_start_from_user_event_id)
+                .unwrap()
+                .0,
+            vec![FogUserEvent::MissingBlocks(BlockRange::new(2, 10))]
+        );
+        assert!(
+            db.get_ingress_key_status(&ingress_key)
+                .unwrap()
+                .unwrap()
+                .lost
+        );
+
+        // New keys start after the highest known block
+        let ingress_key2 = CompressedRistrettoPublic::from(RistrettoPublic::from_random(&mut rng));
+        assert_eq!(db.new_ingress_key(&ingress_key2, 0).unwrap(), 2);
+        let records = db
+            .get_ingress_key_records(
+                0,
+                &IngressPublicKeyRecordFilters {
+                    should_include_lost_keys: false,
+                    should_include_retired_keys: false,
+                    should_only_include_unexpired_keys: false,
+                },
+            )
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].key, ingress_key2);
+    }
+
+    #[test_with_logger]
+    fn test_reports_db(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([123u8; 32]);
+        let db_test_context = test_utils::SqliteRecoveryDbTestContext::new(logger);
+        let db = db_test_context.get_db_instance();
+
+        let ingress_key = CompressedRistrettoPublic::from(RistrettoPublic::from_random(&mut rng));
+        db.new_ingress_key(&ingress_key, 123).unwrap();
+
+        let invoc_id = db
+            .new_ingest_invocation(None, &ingress_key, &random_kex_rng_pubkey(&mut rng), 123)
+            .unwrap();
+
+        assert_eq!(db.get_all_reports().unwrap(), vec![]);
+
+        let report1 = ReportData {
+            ingest_invocation_id: Some(invoc_id),
+            report: create_report("report 1"),
+            pubkey_expiry: 102030,
+        };
+        let key_status = db.set_report(&ingress_key, "", &report1).unwrap();
+        assert_eq!(key_status.pubkey_expiry, 102030);
+        assert_eq!(
+            db.get_all_reports().unwrap(),
+            vec![("".to_string(), report1)]
+        );
+
+        // Updating the report does not decrease the pubkey expiry of the key
+        let report2 = ReportData {
+            ingest_invocation_id: None,
+            report: create_report("report 2"),
+            pubkey_expiry: 424242 - 400000,
+        };
+        let key_status = db.set_report(&ingress_key, "", &report2).unwrap();
+        assert_eq!(key_status.pubkey_expiry, 102030);
+        assert_eq!(
+            db.get_all_reports().unwrap(),
+            vec![("".to_string(), report2)]
+        );
+
+        // Retired keys are not published
+        db.retire_ingress_key(&ingress_key, true).unwrap();
+        let report3 = ReportData {
+            ingest_invocation_id: Some(invoc_id),
+            report: create_report("report 3"),
+            pubkey_expiry: 10203050,
+        };
+        let key_status = db.set_report(&ingress_key, "other", &report3).unwrap();
+        assert!(key_status.retired);
+        assert_eq!(key_status.pubkey_expiry, 102030);
+        assert_eq!(db.get_all_reports().unwrap().len(), 1);
+
+        db.remove_report("").unwrap();
+        assert_eq!(db.get_all_reports().unwrap(), vec![]);
+
+        // Reports of unknown keys are rejected
+        let unknown_key = CompressedRistrettoPublic::from(RistrettoPublic::from_random(&mut rng));
+        assert!(db.set_report(&unknown_key, "", &report3).is_err());
+    }
+
+    #[test_with_logger]
+    fn test_leases_are_not_supported(logger: Logger) {
+        let db_test_context = test_utils::SqliteRecoveryDbTestContext::new(logger);
+        let db = db_test_context.get_db_instance();
+
+        let duration = Duration::from_secs(60);
+        assert!(matches!(
+            db.try_acquire_lease("lease", "a", duration),
+            Err(Error::LeasesNotSupported)
+        ));
+        assert!(matches!(
+            db.check_lease("lease", "a", 1),
+            Err(Error::LeasesNotSupported)
+        ));
+        assert!(matches!(
+            db.release_lease("lease", "a"),
+            Err(Error::LeasesNotSupported)
+        ));
+        assert!(matches!(
+            db.get_lease_holder("lease"),
+            Err(Error::LeasesNotSupported)
+        ));
+    }
+}
@@ -0,0 +1,53 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Utilities for testing.
+
+use crate::{SqliteRecoveryDb, SqliteRecoveryDbConnectionConfig};
+use mc_common::logger::Logger;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// Context for tests, holding a database file in a temporary directory which
+/// is removed when the context is dropped.
+pub struct SqliteRecoveryDbTestContext {
+    // Kept so that the directory lives as long as the context.
+    _temp_dir: TempDir,
+    db_path: PathBuf,
+    logger: Logger,
+}
+
+impl SqliteRecoveryDbTestContext {
+    /// Intantiate a context.
+    pub fn new(logger: Logger) -> Self {
+        let temp_dir = TempDir::new().expect("Could not create temporary directory");
+        let db_path = temp_dir.path().join("fog-recovery.db");
+
+        Self {
+            _temp_dir: temp_dir,
+            db_path,
+            logger,
+        }
+    }
+
+    /// Get the path of the database file.
+    pub fn db_path(&self) -> &str {
+        self.db_path
+            .to_str()
+            .expect("temporary directory path is not valid UTF-8")
+    }
+
+    /// Get DB URL.
+    pub fn db_url(&self) -> String {
+        format!("{}{}", crate::SQLITE_URL_SCHEME, self.db_path())
+    }
+
+    /// Get DB instance. Every instance uses the same database file.
+    pub fn get_db_instance(&self) -> SqliteRecoveryDb {
+        SqliteRecoveryDb::new_from_path(
+            self.db_path(),
+            SqliteRecoveryDbConnectionConfig::default(),
+            self.logger.clone(),
+        )
+        .expect("failed creating new SqliteRecoveryDb")
+    }
+}
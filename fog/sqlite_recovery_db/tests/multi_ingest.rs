@@ -0,0 +1,83 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+use mc_common::logger::{test_with_logger, Logger};
+use mc_fog_recovery_db_iface::RecoveryDb;
+use mc_fog_sqlite_recovery_db::{test_utils::SqliteRecoveryDbTestContext, SqliteRecoveryDb};
+use mc_fog_test_infra::{
+    ingest_harness::{MultiIngestHarness, PUBKEY_EXPIRY_WINDOW},
+    mock_client::PassThroughViewClient,
+};
+use mc_fog_types::common::BlockRange;
+use mc_ledger_db::test_utils::create_ledger;
+
+type Harness = MultiIngestHarness<SqliteRecoveryDb>;
+
+/// Add a block, and check that the users find exactly their TxOuts which the
+/// harness expects fog to have scanned
+fn add_block_and_poll(harness: &mut Harness, view: &mut PassThroughViewClient<SqliteRecoveryDb>) {
+    let checkpoint = harness.users().get_checkpoint();
+    let expected = harness.add_block(10);
+    harness.users_mut().poll(view);
+    assert_eq!(harness.users().compute_delta(&checkpoint), expected);
+}
+
+#[test_with_logger]
+fn multi_ingest_failover_rotation_and_missed_blocks(logger: Logger) {
+    let db_test_context = SqliteRecoveryDbTestContext::new(logger);
+    let mut harness = Harness::new(
+        db_test_context.get_db_instance(),
+        create_ledger(),
+        3,
+        5,
+        [7u8; 32],
+    );
+    let mut view = harness.pass_through_view_client();
+
+    for _ in 0..3 {
+        add_block_and_poll(&mut harness, &mut view);
+    }
+
+    // Failing over keeps the ingress key, and misses no blocks
+    let ingress_key = harness.report_key();
+    harness.failover(0, 1);
+    assert!(!harness.node(0).is_active());
+    assert!(harness.node(1).is_active());
+    assert_eq!(harness.report_key(), ingress_key);
+    for _ in 0..2 {
+        add_block_and_poll(&mut harness, &mut view);
+    }
+
+    // After rotating keys, the retired key is scanned until its pubkey expiry
+    harness.rotate_keys(1, 2);
+    assert_ne!(harness.report_key(), ingress_key);
+    assert_eq!(harness.report_key(), harness.node(2).ingress_public_key());
+    for _ in 0..PUBKEY_EXPIRY_WINDOW {
+        add_block_and_poll(&mut harness, &mut view);
+        assert!(harness.node(1).is_active());
+    }
+    add_block_and_poll(&mut harness, &mut view);
+    assert!(!harness.node(1).is_active());
+
+    // Blocks are not scanned while no node has the key users send TxOuts to
+    let stopped_at = harness.num_blocks();
+    harness.stop_node(2);
+    for _ in 0..2 {
+        assert!(harness.add_block(10).is_empty());
+    }
+
+    // Until the key is reported lost, and its unscanned blocks are missed
+    harness.report_lost_key(2);
+    assert_eq!(
+        harness.db().get_missed_block_ranges().unwrap(),
+        vec![BlockRange::new(
+            stopped_at,
+            stopped_at + PUBKEY_EXPIRY_WINDOW
+        )]
+    );
+
+    harness.new_keys(0);
+    harness.activate_node(0);
+    for _ in 0..2 {
+        add_block_and_poll(&mut harness, &mut view);
+    }
+}
@@ -0,0 +1,464 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! A deterministic stand-in for a cluster of fog ingest nodes.
+//!
+//! The harness owns a recovery db, a ledger db and a pool of users, and plays
+//! the part of N fog ingest nodes writing to the recovery db. The mock nodes
+//! write the same ingress keys, reports, rng records and ETxOutRecords that
+//! the ingest enclaves would (just not obliviously), so fog view servers and
+//! fog ledger servers started against the harness' databases serve real data,
+//! and the users can poll them and decrypt what they find.
+//!
+//! Integration tests script key rotations, failovers and missed blocks by
+//! calling the harness, e.g. `failover`, `rotate_keys` and `report_lost_key`.
+//! All the randomness comes from the seed, so a scenario is reproducible.
+
+use crate::{
+    mock_client::PassThroughViewClient,
+    mock_users::{Delta, UserPool},
+};
+use mc_blockchain_types::BlockVersion;
+use mc_crypto_box::{CryptoBox, VersionedCryptoBox};
+use mc_crypto_keys::{CompressedRistrettoPublic, Ristretto, RistrettoPrivate, RistrettoPublic};
+use mc_fog_kex_rng::{BufferedRng, KexRngPubkey, LatestKexRngCore, NewFromKex, VersionedKexRng};
+use mc_fog_recovery_db_iface::{
+    ETxOutRecord, IngestInvocationId, RecoveryDb, ReportData, ReportDb,
+};
+use mc_fog_types::view::{FogTxOut, FogTxOutMetadata, TxOutRecord};
+use mc_ledger_db::{test_utils::add_txos_and_key_images_to_ledger, Ledger, LedgerDB};
+use mc_transaction_core::{fog_hint::FogHint, ring_signature::KeyImage, tx::TxOut};
+use mc_util_from_random::FromRandom;
+use rand_core::{CryptoRng, RngCore, SeedableRng};
+use rand_hc::Hc128Rng;
+use std::collections::{HashMap, HashSet};
+
+/// How many blocks past the next block the reports of the mock nodes promise
+/// to keep scanning with their ingress keys.
+pub const PUBKEY_EXPIRY_WINDOW: u64 = 10;
+
+/// The block signature timestamp of the origin block. Each later block is
+/// signed BLOCK_INTERVAL_SECONDS later.
+pub const ORIGIN_BLOCK_TIMESTAMP: u64 = 1_600_000_000;
+
+/// The seconds between the block signature timestamps of consecutive blocks.
+pub const BLOCK_INTERVAL_SECONDS: u64 = 5;
+
+/// A mock fog ingest node
+pub struct MockIngestNode {
+    /// The ingress key, which fog hints are encrypted to
+    ingress_key: RistrettoPrivate,
+    /// The ingest invocation, while the node is active
+    invocation: Option<MockIngestInvocation>,
+}
+
+/// The state of a mock ingest node while it is active
+struct MockIngestInvocation {
+    /// The id of the ingest invocation in the recovery db
+    id: IngestInvocationId,
+    /// The egress key, which the rngs of the users are derived from
+    egress_key: RistrettoPrivate,
+    /// The rng of each user which has received a TxOut, by view public key
+    rngs: HashMap<CompressedRistrettoPublic, VersionedKexRng>,
+}
+
+impl MockIngestNode {
+    /// The ingress public key of this node
+    pub fn ingress_public_key(&self) -> CompressedRistrettoPublic {
+        CompressedRistrettoPublic::from(&RistrettoPublic::from(&self.ingress_key))
+    }
+
+    /// The ingest invocation id of this node, if it is active
+    pub fn ingest_invocation_id(&self) -> Option<IngestInvocationId> {
+        self.invocation.as_ref().map(|invocation| invocation.id)
+    }
+
+    /// Whether this node is active, i.e. scanning blocks
+    pub fn is_active(&self) -> bool {
+        self.invocation.is_some()
+    }
+}
+
+impl MockIngestInvocation {
+    /// Make the ETxOutRecords of the TxOuts of a block whose fog hints are
+    /// encrypted to this ingress key.
+    fn ingest_tx_outs(
+        &mut self,
+        ingress_key: &RistrettoPrivate,
+        tx_outs: &[TxOut],
+        meta: impl Fn(usize) -> FogTxOutMetadata,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Vec<ETxOutRecord> {
+        let mut records = Vec::new();
+        for (index, tx_out) in tx_outs.iter().enumerate() {
+            let mut hint = FogHint::new(RistrettoPublic::from_random(rng));
+            if !bool::from(FogHint::ct_decrypt(
+                ingress_key,
+                &tx_out.e_fog_hint,
+                &mut hint,
+            )) {
+                continue;
+            }
+            let view_pubkey = match RistrettoPublic::try_from(hint.get_view_pubkey()) {
+                Ok(view_pubkey) => view_pubkey,
+                Err(_) => continue,
+            };
+
+            let egress_key = &self.egress_key;
+            let kex_rng = self.rngs.entry(*hint.get_view_pubkey()).or_insert_with(|| {
+                VersionedKexRng::new_from_static_static(egress_key, &view_pubkey).1
+            });
+            let search_key = kex_rng.peek().to_vec();
+            kex_rng.advance();
+
+            let tx_out_record = TxOutRecord::new(FogTxOut::from(tx_out), meta(index));
+            let payload = VersionedCryptoBox::default()
+                .encrypt(rng, &view_pubkey, &mc_util_serial::encode(&tx_out_record))
+                .expect("CryptoBox encryption should not fail");
+
+            records.push(ETxOutRecord {
+                search_key,
+                payload,
+            });
+        }
+        records
+    }
+}
+
+/// A recovery db and ledger written to by a cluster of mock fog ingest nodes,
+/// with a pool of users receiving TxOuts in each block.
+pub struct MultiIngestHarness<DB: RecoveryDb + ReportDb> {
+    db: DB,
+    ledger_db: LedgerDB,
+    nodes: Vec<MockIngestNode>,
+    users: UserPool,
+    /// The ingress key which the fog hints of new TxOuts are encrypted to, as
+    /// found by the users in the fog report
+    report_key: RistrettoPublic,
+    rng: Hc128Rng,
+}
+
+impl<DB: RecoveryDb + ReportDb> MultiIngestHarness<DB> {
+    /// Make a harness with num_nodes mock ingest nodes, which start out
+    /// sharing one ingress key, as after the cluster has synced keys, and with
+    /// node 0 active.
+    ///
+    /// Arguments:
+    /// * db: An empty recovery db
+    /// * ledger_db: The ledger, which may already contain blocks
+    /// * num_nodes: The number of mock ingest nodes
+    /// * num_users: The number of users receiving TxOuts
+    /// * seed: The seed of all the randomness of the harness
+    pub fn new(
+        db: DB,
+        ledger_db: LedgerDB,
+        num_nodes: usize,
+        num_users: usize,
+        seed: [u8; 32],
+    ) -> Self {
+        assert!(num_nodes > 0, "the harness needs at least one ingest node");
+
+        let mut rng = Hc128Rng::from_seed(seed);
+        let users = UserPool::new(num_users, &mut rng);
+        let ingress_key = RistrettoPrivate::from_random(&mut rng);
+        let nodes = (0..num_nodes)
+            .map(|_| MockIngestNode {
+                ingress_key,
+                invocation: None,
+            })
+            .collect();
+
+        let mut harness = Self {
+            db,
+            ledger_db,
+            nodes,
+            users,
+            report_key: RistrettoPublic::from(&ingress_key),
+            rng,
+        };
+        harness.activate_node(0);
+        harness
+    }
+
+    /// The recovery db
+    pub fn db(&self) -> &DB {
+        &self.db
+    }
+
+    /// The ledger db
+    pub fn ledger_db(&self) -> &LedgerDB {
+        &self.ledger_db
+    }
+
+    /// The users receiving TxOuts
+    pub fn users(&self) -> &UserPool {
+        &self.users
+    }
+
+    /// The users receiving TxOuts, e.g. to poll fog view
+    pub fn users_mut(&mut self) -> &mut UserPool {
+        &mut self.users
+    }
+
+    /// A mock ingest node
+    pub fn node(&self, index: usize) -> &MockIngestNode {
+        &self.nodes[index]
+    }
+
+    /// The ingress key users currently send TxOuts to
+    pub fn report_key(&self) -> CompressedRistrettoPublic {
+        CompressedRistrettoPublic::from(&self.report_key)
+    }
+
+    /// The number of blocks in the ledger
+    pub fn num_blocks(&self) -> u64 {
+        self.ledger_db
+            .num_blocks()
+            .expect("Could not get num blocks")
+    }
+
+    /// A view client which reads the recovery db directly, with no fog view
+    /// server in between
+    pub fn pass_through_view_client(&self) -> PassThroughViewClient<DB>
+    where
+        DB: Clone,
+    {
+        PassThroughViewClient::new(self.db.clone())
+    }
+
+    /// Append a block with num_txs TxOuts for random users to the ledger, and
+    /// have the active nodes scan it.
+    ///
+    /// A retired node which has scanned up to the pubkey expiry of its key
+    /// goes idle instead of scanning the block.
+    ///
+    /// Returns the TxOutRecords the users should find in this block through
+    /// fog view, which is none of them if no active node has the ingress key
+    /// they were sent to.
+    pub fn add_block(&mut self, num_txs: usize) -> Delta {
+        let block_index = self.num_blocks();
+        let global_txo_index = self.ledger_db.num_txos().expect("Could not get num txos");
+        let timestamp = ORIGIN_BLOCK_TIMESTAMP + block_index * BLOCK_INTERVAL_SECONDS;
+        let meta = |index: usize| FogTxOutMetadata {
+            global_index: global_txo_index + index as u64,
+            block_index,
+            timestamp,
+        };
+
+        let pairs = self
+            .users
+            .random_tx_outs(num_txs, &self.report_key, &mut self.rng);
+        let tx_outs: Vec<TxOut> = pairs.iter().map(|(_, tx_out)| tx_out.clone()).collect();
+
+        // The origin block cannot have key images, and other blocks must
+        let key_images = if block_index == 0 {
+            vec![]
+        } else {
+            vec![KeyImage::from(block_index)]
+        };
+        let block_data = add_txos_and_key_images_to_ledger(
+            &mut self.ledger_db,
+            BlockVersion::ZERO,
+            tx_outs.clone(),
+            key_images,
+            &mut self.rng,
+        )
+        .expect("Could not add block to ledger");
+
+        let mut scanned_keys = HashSet::new();
+        for node in self.nodes.iter_mut() {
+            let ingress_public_key = node.ingress_public_key();
+            let invocation = match node.invocation.as_mut() {
+                Some(invocation) => invocation,
+                None => continue,
+            };
+
+            let status = self
+                .db
+                .get_ingress_key_status(&ingress_public_key)
+                .expect("get_ingress_key_status failed")
+                .expect("ingress key of an active node is not in the recovery db");
+            if status.retired && block_index >= status.pubkey_expiry {
+                self.db
+                    .decommission_ingest_invocation(&invocation.id)
+                    .expect("decommission_ingest_invocation failed");
+                node.invocation = None;
+                continue;
+            }
+
+            let records =
+                invocation.ingest_tx_outs(&node.ingress_key, &tx_outs, meta, &mut self.rng);
+            self.db
+                .add_block_data(&invocation.id, block_data.block(), timestamp, &records)
+                .expect("add_block_data failed");
+            if !status.retired {
+                publish_report(
+                    &self.db,
+                    &ingress_public_key,
+                    invocation.id,
+                    block_index + 1,
+                );
+            }
+            scanned_keys.insert(ingress_public_key);
+        }
+
+        let mut expected = Delta::default();
+        if scanned_keys.contains(&self.report_key()) {
+            for (index, (user, tx_out)) in pairs.into_iter().enumerate() {
+                expected
+                    .entry(user)
+                    .or_default()
+                    .insert(TxOutRecord::new(FogTxOut::from(&tx_out), meta(index)));
+            }
+        }
+        expected
+    }
+
+    /// Start a new ingest invocation on an idle node, which scans from the
+    /// next block. Its ingress key is added to the recovery db if it is new,
+    /// and becomes the key users send TxOuts to.
+    pub fn activate_node(&mut self, index: usize) {
+        let start_block = self.num_blocks();
+        let node = &mut self.nodes[index];
+        assert!(!node.is_active(), "node {} is already active", index);
+
+        let ingress_public_key = node.ingress_public_key();
+        match self
+            .db
+            .get_ingress_key_status(&ingress_public_key)
+            .expect("get_ingress_key_status failed")
+        {
+            Some(status) => assert!(
+                !status.retired && !status.lost,
+                "node {} has a retired or lost ingress key",
+                index
+            ),
+            None => {
+                self.db
+                    .new_ingress_key(&ingress_public_key, start_block)
+                    .expect("new_ingress_key failed");
+            }
+        }
+
+        let egress_key = RistrettoPrivate::from_random(&mut self.rng);
+        let egress_public_key = KexRngPubkey::from_public_key::<LatestKexRngCore, Ristretto>(
+            &RistrettoPublic::from(&egress_key),
+        );
+        let id = self
+            .db
+            .new_ingest_invocation(None, &ingress_public_key, &egress_public_key, start_block)
+            .expect("new_ingest_invocation failed");
+        node.invocation = Some(MockIngestInvocation {
+            id,
+            egress_key,
+            rngs: Default::default(),
+        });
+
+        publish_report(&self.db, &ingress_public_key, id, start_block);
+        self.report_key = RistrettoPublic::from(&node.ingress_key);
+    }
+
+    /// Stop an active node, e.g. because it crashed, decommissioning its
+    /// ingest invocation. Blocks go unscanned with its ingress key until
+    /// another node with the key is activated, or the key is reported lost.
+    pub fn stop_node(&mut self, index: usize) {
+        let invocation = self.nodes[index]
+            .invocation
+            .take()
+            .unwrap_or_else(|| panic!("node {} is not active", index));
+        self.db
+            .decommission_ingest_invocation(&invocation.id)
+            .expect("decommission_ingest_invocation failed");
+    }
+
+    /// Give an idle node a new random ingress key
+    pub fn new_keys(&mut self, index: usize) {
+        let node = &mut self.nodes[index];
+        assert!(!node.is_active(), "node {} is active", index);
+        node.ingress_key = RistrettoPrivate::from_random(&mut self.rng);
+    }
+
+    /// Copy the ingress key of one node to an idle node, like the peer key
+    /// backup of a fog ingest cluster
+    pub fn sync_keys(&mut self, from: usize, to: usize) {
+        assert!(!self.nodes[to].is_active(), "node {} is active", to);
+        self.nodes[to].ingress_key = self.nodes[from].ingress_key;
+    }
+
+    /// Retire the ingress key of a node. Its reports are no longer published,
+    /// and the nodes using it keep scanning until its pubkey expiry, and then
+    /// go idle. Users switch to the key of another active node, if any.
+    pub fn retire_node(&mut self, index: usize) {
+        let ingress_public_key = self.nodes[index].ingress_public_key();
+        self.db
+            .retire_ingress_key(&ingress_public_key, true)
+            .expect("retire_ingress_key failed");
+
+        if self.report_key() == ingress_public_key {
+            let replacement = self.nodes.iter().find(|node| {
+                node.is_active()
+                    && !self
+                        .db
+                        .get_ingress_key_status(&node.ingress_public_key())
+                        .expect("get_ingress_key_status failed")
+                        .map_or(true, |status| status.retired)
+            });
+            if let Some(node) = replacement {
+                self.report_key = RistrettoPublic::from(&node.ingress_key);
+            }
+        }
+    }
+
+    /// Report the ingress key of a node lost, as if every node holding it
+    /// lost it. The blocks it had promised to scan, and did not, become a
+    /// missed block range.
+    pub fn report_lost_key(&mut self, index: usize) {
+        let ingress_public_key = self.nodes[index].ingress_public_key();
+        assert!(
+            !self
+                .nodes
+                .iter()
+                .any(|node| node.is_active() && node.ingress_public_key() == ingress_public_key),
+            "the ingress key of node {} is still in use",
+            index
+        );
+        self.db
+            .report_lost_ingress_key(ingress_public_key)
+            .expect("report_lost_ingress_key failed");
+    }
+
+    /// Fail over from an active node to an idle node, which takes over its
+    /// ingress key, so that no blocks are missed.
+    pub fn failover(&mut self, from: usize, to: usize) {
+        self.sync_keys(from, to);
+        self.stop_node(from);
+        self.activate_node(to);
+    }
+
+    /// Rotate ingress keys, by activating an idle node with a new key, and
+    /// retiring the key of an active node.
+    pub fn rotate_keys(&mut self, from: usize, to: usize) {
+        self.new_keys(to);
+        self.activate_node(to);
+        self.retire_node(from);
+    }
+}
+
+/// Publish a report for an ingress key, promising to scan
+/// PUBKEY_EXPIRY_WINDOW blocks past next_block_index with it.
+fn publish_report(
+    db: &impl ReportDb,
+    ingress_public_key: &CompressedRistrettoPublic,
+    ingest_invocation_id: IngestInvocationId,
+    next_block_index: u64,
+) {
+    db.set_report(
+        ingress_public_key,
+        "",
+        &ReportData {
+            ingest_invocation_id: Some(ingest_invocation_id),
+            report: Default::default(),
+            pubkey_expiry: next_block_index + PUBKEY_EXPIRY_WINDOW,
+        },
+    )
+    .expect("set_report failed");
+}
@@ -6,6 +6,7 @@
 #![deny(missing_docs)]
 
 pub mod db_tests;
+pub mod ingest_harness;
 pub mod mock_client;
 pub mod mock_users;
 
@@ -2,7 +2,7 @@
 
 //! Mock Fog client
 
-use mc_fog_recovery_db_iface::{FogUserEvent, RecoveryDb};
+use mc_fog_recovery_db_iface::{skip_processed_user_events, FogUserEvent, RecoveryDb};
 use mc_fog_types::view::QueryResponse;
 use mc_fog_view_protocol::FogViewConnection;
 
@@ -25,14 +25,16 @@ impl<R: RecoveryDb> PassThroughViewClient<R> {
 impl<R: RecoveryDb> FogViewConnection for PassThroughViewClient<R> {
     type Error = R::Error;
 
-    fn request(
+    fn request_with_processed_block_count(
         &mut self,
         start_from_user_event_id: i64,
         start_from_block_index: u64,
+        processed_block_count: u64,
         search_keys: Vec<Vec<u8>>,
     ) -> Result<QueryResponse, Self::Error> {
         let (user_events, next_start_from_user_event_id) =
             self.db.search_user_events(start_from_user_event_id)?;
+        let user_events = skip_processed_user_events(user_events, processed_block_count);
 
         let highest_known_block_count = self
             .db
@@ -75,6 +77,7 @@ impl<R: RecoveryDb> FogViewConnection for PassThroughViewClient<R> {
             tx_out_search_results: Default::default(),
             last_known_block_count: highest_known_block_count,
             last_known_block_cumulative_txo_count: cumulative_txo_count,
+            compressed_response: Default::default(),
         };
 
         resp.tx_out_search_results = self.db.get_tx_outs(start_from_block_index, &search_keys)?;
@@ -51,7 +51,7 @@ type Checkpoint = HashMap<UserPrivate, usize>;
 /// random transactions, submit a block to ingest, poll the view node, and then
 /// measure delta against the checkpoint. If the generated transactions match
 /// the delta, we accept. If not we retry a few times, and then eventually fail.
-type Delta = HashMap<UserPrivate, HashSet<TxOutRecord>>;
+pub type Delta = HashMap<UserPrivate, HashSet<TxOutRecord>>;
 
 /// Take a test block, order the TxOuts arbitrarily, compute their global
 /// TxOut indices, and finally compute what we expect the users to see on
@@ -120,6 +120,7 @@ pub fn make_random_tx<T: RngCore + CryptoRng>(
         public_key: public_key.into(),
         e_fog_hint: recipient.encrypt(acct_server_pubkey, rng),
         e_memo: None,
+        additional_e_fog_hints: Default::default(),
     }
 }
 
@@ -204,6 +205,23 @@ impl UserPool {
         result
     }
 
+    /// Make random TxOuts for random users, in the order they were made
+    pub fn random_tx_outs<T: RngCore + CryptoRng>(
+        &self,
+        tx_count: usize,
+        acct_server_pubkey: &RistrettoPublic,
+        rng: &mut T,
+    ) -> Vec<(UserPrivate, TxOut)> {
+        (0..tx_count)
+            .map(|_| {
+                let user_idx = (rng.next_u64() % self.users.len() as u64) as usize;
+                let user_id = &self.users[user_idx].0;
+                let tx_out = make_random_tx(rng, acct_server_pubkey, &user_id.get_hint());
+                (user_id.clone(), tx_out)
+            })
+            .collect()
+    }
+
     /// Make each of the users poll consecutively, and add any Txos that they
     /// find to their little cache.
     /// Return the final num blocks values for each user
@@ -11,7 +11,7 @@ use serde::{Deserialize, Serialize};
 ///
 /// TODO: Once protobuf v3 becomes stable, use its JSON conversion feature
 /// where this struct is needed, and delete this struct.
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct IngestSummary {
     /// The current mode of the server
     pub ingest_controller_mode: IngestControllerMode,
@@ -32,10 +32,14 @@ pub struct IngestSummary {
     pub peers: Vec<String>,
     /// The current ingest invocation id of this server (only if Active)
     pub ingest_invocation_id: i64,
+    /// The capacity of the enclave's Oblivious Map of user rngs
+    pub omap_capacity: u64,
+    /// The progress of the latest sync of the ingress key from a peer
+    pub peer_sync: Option<PeerSyncProgress>,
 }
 
 /// Represents the possible modes of an ingest server controller
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum IngestControllerMode {
     /// Server is not actively consuming and scanning the blockchain
     IDLE,
@@ -44,3 +48,30 @@ pub enum IngestControllerMode {
     /// retired.
     ACTIVE,
 }
+
+/// Mirrors the proto definition of PeerSyncProgress
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PeerSyncProgress {
+    /// The uri of the peer which the key is synced from
+    pub peer_uri: String,
+    /// The ingress public key of the peer, which is synced
+    pub ingress_pubkey: CompressedRistrettoPublic,
+    /// The stage which the sync reached
+    pub stage: PeerSyncStage,
+    /// How many times the sync was attempted
+    pub attempts: u32,
+    /// The error which interrupted the latest attempt, or empty
+    pub last_error: String,
+}
+
+/// Represents the stages of syncing the ingress key from a peer
+#[allow(non_camel_case_types)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum PeerSyncStage {
+    /// The peer's ingress key is being copied into our enclave
+    COPYING_KEY,
+    /// The copied key was verified, and our enclave report is being refreshed
+    REFRESHING_REPORT,
+    /// The key was copied and verified, and our enclave report is up to date
+    SYNCED,
+}
@@ -10,7 +10,8 @@ use mc_transaction_core::{
     tx::TxOut,
     AmountError, EncryptedMemo, MaskedAmount, MemoError,
 };
-use prost::Message;
+use miniz_oxide::{deflate::compress_to_vec, inflate::decompress_to_vec};
+use prost::{DecodeError, Message};
 use serde::{Deserialize, Serialize};
 
 pub use mc_fog_kex_rng::KexRngPubkey;
@@ -33,6 +34,13 @@ pub struct QueryRequestAAD {
     // TODO this is currently unused
     #[prost(uint64, tag = "2")]
     pub start_from_block_index: u64,
+
+    /// The number of blocks the client has already processed, or 0. The rng
+    /// records and decommissions of ingest invocations which were
+    /// decommissioned before it are skipped, when both are newer than
+    /// start_from_user_event_id.
+    #[prost(uint64, tag = "3")]
+    pub processed_block_count: u64,
 }
 
 /// The QueryRequest structure, which should be passed as the encrypted data
@@ -43,6 +51,17 @@ pub struct QueryRequest {
     /// These should all be values that came from KexRng's
     #[prost(bytes, repeated, tag = "1")]
     pub get_txos: Vec<Vec<u8>>,
+
+    /// Whether the enclave should compress the response. An enclave which
+    /// supports this returns a QueryResponse with only compressed_response
+    /// set, and one which doesn't ignores it, so clients must call
+    /// QueryResponse::decompress either way.
+    ///
+    /// Note that the length of a compressed response depends on how many of
+    /// the search keys were found, so it is visible to the network and the
+    /// untrusted server.
+    #[prost(bool, tag = "2")]
+    pub compress_response: bool,
 }
 
 /// The QueryResponse structure, returned by the enclave in response to an
@@ -93,8 +112,43 @@ pub struct QueryResponse {
     /// clients sample for mixins.
     #[prost(uint64, tag = "9")]
     pub last_known_block_cumulative_txo_count: u64,
+
+    /// The deflate-compressed encoding of the whole QueryResponse, when the
+    /// request asked for compression. All the other fields are then empty.
+    #[prost(bytes, tag = "10")]
+    pub compressed_response: Vec<u8>,
+}
+
+impl QueryResponse {
+    /// Compress this response, for a request with compress_response set.
+    pub fn compress(&self) -> Self {
+        Self {
+            compressed_response: compress_to_vec(
+                &self.encode_to_vec(),
+                QUERY_RESPONSE_COMPRESSION_LEVEL,
+            ),
+            ..Default::default()
+        }
+    }
+
+    /// Get the original response back from a compressed response. Responses
+    /// which aren't compressed are returned as is.
+    pub fn decompress(self) -> Result<Self, DecodeError> {
+        if self.compressed_response.is_empty() {
+            return Ok(self);
+        }
+
+        let bytes = decompress_to_vec(&self.compressed_response)
+            .map_err(|_| DecodeError::new("invalid compressed QueryResponse"))?;
+        Self::decode(&bytes[..])
+    }
 }
 
+/// The deflate compression level used for query responses. Search keys and
+/// the ciphertexts of found TxOuts are random, so a higher level gains little
+/// and costs enclave time.
+pub const QUERY_RESPONSE_COMPRESSION_LEVEL: u8 = 1;
+
 /// A record that can be used by the user to produce an Rng shared with fog
 /// ingest
 #[derive(Clone, Eq, PartialEq, Hash, Message, Serialize, Deserialize)]
@@ -397,6 +451,7 @@ impl FogTxOut {
             public_key: self.public_key,
             e_fog_hint: EncryptedFogHint::from(&[0u8; ENCRYPTED_FOG_HINT_LEN]),
             e_memo: self.e_memo,
+            additional_e_fog_hints: Default::default(),
         })
     }
 }
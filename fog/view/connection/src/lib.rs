@@ -18,7 +18,7 @@ use mc_fog_uri::FogViewUri;
 use mc_fog_view_protocol::FogViewConnection;
 use mc_util_grpc::{ConnectionUriGrpcioChannel, GrpcRetryConfig};
 use mc_util_telemetry::{tracer, Tracer};
-use retry::Error as RetryError;
+use retry::{Error as RetryError, OperationResult};
 use std::{fmt::Display, sync::Arc};
 
 /// A high-level object mediating requests to the fog view service
@@ -29,6 +29,8 @@ pub struct FogViewGrpcClient {
     grpc_retry_config: GrpcRetryConfig,
     /// The uri we connected to
     uri: FogViewUri,
+    /// Whether to ask the enclave to compress its responses
+    compress_responses: bool,
     /// A logger object
     logger: Logger,
 }
@@ -59,18 +61,27 @@ impl FogViewGrpcClient {
             conn: EnclaveConnection::new(uri.clone(), grpc_client, verifier, logger.clone()),
             grpc_retry_config,
             uri,
+            compress_responses: false,
             logger,
         }
     }
+
+    /// Ask the enclave to compress its responses, which cuts their size when
+    /// many of the search keys are not found. This reveals how many of them
+    /// were found to anyone who sees the size of the responses.
+    pub fn set_compress_responses(&mut self, compress_responses: bool) {
+        self.compress_responses = compress_responses;
+    }
 }
 
 impl FogViewConnection for FogViewGrpcClient {
     type Error = Error;
 
-    fn request(
+    fn request_with_processed_block_count(
         &mut self,
         start_from_user_event_id: i64,
         start_from_block_index: u64,
+        processed_block_count: u64,
         search_keys: Vec<Vec<u8>>,
     ) -> Result<QueryResponse, Self::Error> {
         tracer!().in_span("fog_view_grpc_request", |_cx_| {
@@ -78,19 +89,22 @@ impl FogViewConnection for FogViewGrpcClient {
 
             log::trace!(
                 self.logger,
-                "request: start_from_user_event_id={} start_from_block_index={} num_search_keys={}",
+                "request: start_from_user_event_id={} start_from_block_index={} processed_block_count={} num_search_keys={}",
                 start_from_user_event_id,
                 start_from_block_index,
+                processed_block_count,
                 search_keys.len()
             );
 
             let req = QueryRequest {
                 get_txos: search_keys,
+                compress_response: self.compress_responses,
             };
 
             let req_aad = QueryRequestAAD {
                 start_from_user_event_id,
                 start_from_block_index,
+                processed_block_count,
             };
 
             let aad_bytes = mc_util_serial::encode(&req_aad);
@@ -98,8 +112,16 @@ impl FogViewConnection for FogViewGrpcClient {
             let retry_config = self.grpc_retry_config;
             retry_config
                 .retry(|| {
-                    self.conn
-                        .retriable_encrypted_enclave_request(&req, &aad_bytes)
+                    match self
+                        .conn
+                        .retriable_encrypted_enclave_request::<_, QueryResponse>(&req, &aad_bytes)
+                    {
+                        OperationResult::Ok(resp) => resp
+                            .decompress()
+                            .map_err(EnclaveConnectionError::from)
+                            .into(),
+                        other => other,
+                    }
                 })
                 .map_err(|error| Error {
                     uri: self.uri.clone(),
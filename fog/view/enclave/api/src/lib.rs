@@ -14,7 +14,7 @@ use displaydoc::Display;
 use mc_attest_core::{Quote, Report, SgxError, TargetInfo, VerificationReport};
 use mc_attest_enclave_api::{
     ClientAuthRequest, ClientAuthResponse, ClientSession, EnclaveMessage,
-    Error as AttestEnclaveError,
+    Error as AttestEnclaveError, PeerAuthRequest, PeerAuthResponse, PeerSession,
 };
 use mc_common::ResponderId;
 use mc_crypto_keys::X25519Public;
@@ -50,6 +50,33 @@ pub struct UntrustedQueryResponse {
     pub last_known_block_cumulative_txo_count: u64,
 }
 
+/// A client's query, forwarded by a router enclave to a store enclave.
+///
+/// This is the plaintext of the messages exchanged between the router and the
+/// store enclaves, which are encrypted for the peer session.
+#[derive(Serialize, Deserialize)]
+pub struct ShardQueryRequest {
+    /// The client session the query was made on. The store echoes it back, so
+    /// that the router cannot be made to send a response to another client.
+    pub client_session: Vec<u8>,
+
+    /// An encoded fog_types::view::QueryRequest
+    pub query_request: Vec<u8>,
+}
+
+/// A store enclave's response to a [ShardQueryRequest].
+#[derive(Serialize, Deserialize)]
+pub struct ShardQueryResponse {
+    /// The client session from the request.
+    pub client_session: Vec<u8>,
+
+    /// An encoded fog_types::view::QueryResponse, which is never compressed
+    pub query_response: Vec<u8>,
+
+    /// Whether the client asked for the collated response to be compressed
+    pub compress_response: bool,
+}
+
 /// Represents a serialized request for the view enclave to service
 #[derive(Serialize, Deserialize)]
 pub enum ViewEnclaveRequest {
@@ -83,6 +110,25 @@ pub enum ViewEnclaveRequest {
     Query(EnclaveMessage<ClientSession>, UntrustedQueryResponse),
     /// Request from untrusted to add encrypted tx out records to ORAM
     AddRecords(Vec<ETxOutRecord>),
+
+    // Sharding
+    /// Initiate peering with a store enclave
+    PeerInit(ResponderId),
+    /// Accept a connection from a router enclave
+    PeerAccept(PeerAuthRequest),
+    /// Handle the store enclave's peer_accept response to form the connection
+    PeerConnect(ResponderId, PeerAuthResponse),
+    /// Close a connection with a peer
+    PeerClose(PeerSession),
+    /// An encrypted fog_types::view::QueryRequest from a client, to be
+    /// forwarded to each of the store enclaves
+    CreateShardQueries(EnclaveMessage<ClientSession>, Vec<PeerSession>),
+    /// An encrypted ShardQueryRequest from a router enclave
+    /// Respond with an encrypted ShardQueryResponse
+    ShardQuery(EnclaveMessage<PeerSession>, UntrustedQueryResponse),
+    /// The encrypted ShardQueryResponses of the store enclaves, to be merged
+    /// into a fog_types::view::QueryResponse for the client
+    CollateShardQueryResponses(ClientSession, Vec<EnclaveMessage<PeerSession>>),
 }
 
 /// The parameters needed to initialize the view enclave
@@ -96,8 +142,16 @@ pub struct ViewEnclaveInitParams {
     pub self_client_id: ResponderId,
     /// The desired capacity of the store of records
     pub desired_capacity: u64,
+    /// The stash size of the ORAM backing the store of records
+    pub stash_size: usize,
 }
 
+/// The default stash size of the ORAM backing the store of records.
+///
+/// A larger stash makes it less likely that the ORAM overflows its stash when
+/// it is nearly full, at the cost of slower accesses.
+pub const DEFAULT_STASH_SIZE: usize = 32;
+
 /// The API for the view enclave
 pub trait ViewEnclaveApi: ReportableEnclave {
     /// Perform one-time initialization upon enclave startup.
@@ -134,6 +188,54 @@ pub trait ViewEnclaveApi: ReportableEnclave {
     /// Add encrypted tx out records from the fog recovery db to the view
     /// enclave's ORAM
     fn add_records(&self, records: Vec<ETxOutRecord>) -> Result<()>;
+
+    // SHARDING
+    //
+    // A router enclave accepts client queries, and forwards them to store
+    // enclaves which each hold a part of the ETxOutRecords. Every store
+    // enclave gets every search key, so that the untrusted side cannot learn
+    // which store holds the records of a user.
+
+    /// Initiate peering with a store enclave
+    fn peer_init(&self, peer_id: &ResponderId) -> Result<PeerAuthRequest>;
+
+    /// Accept a connection proposal from a router enclave
+    fn peer_accept(&self, req: PeerAuthRequest) -> Result<(PeerAuthResponse, PeerSession)>;
+
+    /// Handle the store enclave's peer_accept response to form the connection
+    fn peer_connect(
+        &self,
+        peer_id: &ResponderId,
+        msg: PeerAuthResponse,
+    ) -> Result<(PeerSession, VerificationReport)>;
+
+    /// Close a connection with a peer
+    fn peer_close(&self, session_id: &PeerSession) -> Result<()>;
+
+    /// Decrypt a client's QueryRequest, and encrypt it for each of the store
+    /// enclaves, in the same order as the sessions.
+    ///
+    /// The aad of the client's message is forwarded to the store enclaves.
+    fn create_shard_queries(
+        &self,
+        client_query: EnclaveMessage<ClientSession>,
+        shard_sessions: Vec<PeerSession>,
+    ) -> Result<Vec<EnclaveMessage<PeerSession>>>;
+
+    /// Service a router enclave's encrypted ShardQueryRequest
+    fn shard_query(
+        &self,
+        payload: EnclaveMessage<PeerSession>,
+        untrusted_query_response: UntrustedQueryResponse,
+    ) -> Result<EnclaveMessage<PeerSession>>;
+
+    /// Merge the responses of the store enclaves to a client's query made with
+    /// `create_shard_queries`, and encrypt the result for the client.
+    fn collate_shard_query_responses(
+        &self,
+        client_session: ClientSession,
+        shard_query_responses: Vec<EnclaveMessage<PeerSession>>,
+    ) -> Result<Vec<u8>>;
 }
 
 /// Helper trait which reduces boiler-plate in untrusted side
@@ -187,6 +289,10 @@ pub enum Error {
     Poison,
     /// Enclave not initialized
     EnclaveNotInitialized,
+    /// No responses from store enclaves to collate
+    NoShardQueryResponses,
+    /// A store enclave's response did not match the client's query
+    ShardQueryResponseMismatch,
 }
 
 impl From<SgxError> for Error {
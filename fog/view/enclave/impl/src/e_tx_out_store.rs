@@ -41,9 +41,6 @@ type ObliviousRAMAlgo<OSC> = PathORAM4096Z4Creator<McRng, OSC>;
 pub type StorageDataSize = U4096;
 pub type StorageMetaSize = U64;
 
-// This selects the stash size we will construct the oram with
-const STASH_SIZE: usize = 32;
-
 // This selects the oblivious map algorithm
 type ObliviousMapCreator<OSC> = CuckooHashTableCreator<BlockSize, McRng, ObliviousRAMAlgo<OSC>>;
 
@@ -72,14 +69,14 @@ pub struct ETxOutStore<OSC: ORAMStorageCreator<StorageDataSize, StorageMetaSize>
 }
 
 impl<OSC: ORAMStorageCreator<StorageDataSize, StorageMetaSize>> ETxOutStore<OSC> {
-    pub fn new(desired_capacity: u64, logger: Logger) -> Self {
+    pub fn new(desired_capacity: u64, stash_size: usize, logger: Logger) -> Self {
         Self {
             omap: Box::new(<ObliviousMapCreator<OSC> as OMapCreator<
                 KeySize,
                 ValueSize,
                 McRng,
             >>::create(
-                desired_capacity, STASH_SIZE, McRng::default
+                desired_capacity, stash_size, McRng::default
             )),
             last_ciphertext_size_byte: 0,
             logger,
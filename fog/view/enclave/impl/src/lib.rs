@@ -9,10 +9,19 @@ extern crate alloc;
 mod e_tx_out_store;
 use e_tx_out_store::{ETxOutStore, StorageDataSize, StorageMetaSize};
 
+mod sharding;
+use sharding::merge_shard_query_responses;
+
 use alloc::vec::Vec;
 use mc_attest_core::{IasNonce, Quote, QuoteNonce, Report, TargetInfo, VerificationReport};
-use mc_attest_enclave_api::{ClientAuthRequest, ClientAuthResponse, ClientSession, EnclaveMessage};
-use mc_common::logger::{log, Logger};
+use mc_attest_enclave_api::{
+    ClientAuthRequest, ClientAuthResponse, ClientSession, EnclaveMessage, PeerAuthRequest,
+    PeerAuthResponse, PeerSession,
+};
+use mc_common::{
+    logger::{log, Logger},
+    ResponderId,
+};
 use mc_crypto_ake_enclave::{AkeEnclaveState, NullIdentity};
 use mc_crypto_keys::X25519Public;
 use mc_fog_recovery_db_iface::FogUserEvent;
@@ -21,7 +30,8 @@ use mc_fog_types::{
     ETxOutRecord,
 };
 use mc_fog_view_enclave_api::{
-    Error, Result, UntrustedQueryResponse, ViewEnclaveApi, ViewEnclaveInitParams,
+    Error, Result, ShardQueryRequest, ShardQueryResponse, UntrustedQueryResponse, ViewEnclaveApi,
+    ViewEnclaveInitParams,
 };
 use mc_oblivious_traits::ORAMStorageCreator;
 use mc_sgx_compat::sync::Mutex;
@@ -52,6 +62,59 @@ where
             logger,
         }
     }
+
+    /// Evaluate a decrypted query request against the e_tx_out_store
+    fn query_impl(
+        &self,
+        req: QueryRequest,
+        untrusted_query_response: UntrustedQueryResponse,
+    ) -> Result<QueryResponse> {
+        // Prepare the untrusted part of the response.
+        let mut missed_block_ranges = Vec::new();
+        let mut rng_records = Vec::new();
+        let mut decommissioned_ingest_invocations = Vec::new();
+
+        for event in untrusted_query_response.user_events.into_iter() {
+            match event {
+                FogUserEvent::NewRngRecord(rng_record) => rng_records.push(rng_record),
+
+                FogUserEvent::DecommissionIngestInvocation(decommissioned_ingest_invocation) => {
+                    decommissioned_ingest_invocations.push(decommissioned_ingest_invocation)
+                }
+
+                FogUserEvent::MissingBlocks(range) => missed_block_ranges.push(range),
+            }
+        }
+
+        let mut resp = QueryResponse {
+            highest_processed_block_count: untrusted_query_response.highest_processed_block_count,
+            highest_processed_block_signature_timestamp: untrusted_query_response
+                .highest_processed_block_signature_timestamp,
+            next_start_from_user_event_id: untrusted_query_response.next_start_from_user_event_id,
+            missed_block_ranges,
+            rng_records,
+            decommissioned_ingest_invocations,
+            tx_out_search_results: Default::default(),
+            last_known_block_count: untrusted_query_response.last_known_block_count,
+            last_known_block_cumulative_txo_count: untrusted_query_response
+                .last_known_block_cumulative_txo_count,
+            compressed_response: Default::default(),
+        };
+
+        // Do the txos part, scope lock of e_tx_out_store
+        {
+            let mut lk = self.e_tx_out_store.lock()?;
+            let store = lk.as_mut().ok_or(Error::EnclaveNotInitialized)?;
+
+            resp.tx_out_search_results = req
+                .get_txos
+                .iter()
+                .map(|key| store.find_record(&key[..]))
+                .collect();
+        }
+
+        Ok(resp)
+    }
 }
 
 impl<OSC> ReportableEnclave for ViewEnclave<OSC>
@@ -84,11 +147,15 @@ where
         // Note: eid is passed to sgx_enclave_id crate earlier in the system, because
         // that crate is not under sgx_compat and isn't meant to be used outside of
         // enclave
-        self.ake.init(Default::default(), params.self_client_id)?;
+        // The client responder id doubles as the peer responder id, so that a
+        // router enclave can connect to this enclave through its client port.
+        self.ake
+            .init(params.self_client_id.clone(), params.self_client_id)?;
         {
             let mut lk = self.e_tx_out_store.lock()?;
             *lk = Some(ETxOutStore::new(
                 params.desired_capacity,
+                params.stash_size,
                 self.logger.clone(),
             ));
         }
@@ -124,47 +191,10 @@ where
             Error::ProstDecode
         })?;
 
-        // Prepare the untrusted part of the response.
-        let mut missed_block_ranges = Vec::new();
-        let mut rng_records = Vec::new();
-        let mut decommissioned_ingest_invocations = Vec::new();
-
-        for event in untrusted_query_response.user_events.into_iter() {
-            match event {
-                FogUserEvent::NewRngRecord(rng_record) => rng_records.push(rng_record),
-
-                FogUserEvent::DecommissionIngestInvocation(decommissioned_ingest_invocation) => {
-                    decommissioned_ingest_invocations.push(decommissioned_ingest_invocation)
-                }
-
-                FogUserEvent::MissingBlocks(range) => missed_block_ranges.push(range),
-            }
-        }
-
-        let mut resp = QueryResponse {
-            highest_processed_block_count: untrusted_query_response.highest_processed_block_count,
-            highest_processed_block_signature_timestamp: untrusted_query_response
-                .highest_processed_block_signature_timestamp,
-            next_start_from_user_event_id: untrusted_query_response.next_start_from_user_event_id,
-            missed_block_ranges,
-            rng_records,
-            decommissioned_ingest_invocations,
-            tx_out_search_results: Default::default(),
-            last_known_block_count: untrusted_query_response.last_known_block_count,
-            last_known_block_cumulative_txo_count: untrusted_query_response
-                .last_known_block_cumulative_txo_count,
-        };
-
-        // Do the txos part, scope lock of e_tx_out_store
-        {
-            let mut lk = self.e_tx_out_store.lock()?;
-            let store = lk.as_mut().ok_or(Error::EnclaveNotInitialized)?;
-
-            resp.tx_out_search_results = req
-                .get_txos
-                .iter()
-                .map(|key| store.find_record(&key[..]))
-                .collect();
+        let compress_response = req.compress_response;
+        let mut resp = self.query_impl(req, untrusted_query_response)?;
+        if compress_response {
+            resp = resp.compress();
         }
 
         let response_plaintext_bytes = mc_util_serial::encode(&resp);
@@ -184,4 +214,117 @@ where
         }
         Ok(())
     }
+
+    // Sharding
+
+    fn peer_init(&self, peer_id: &ResponderId) -> Result<PeerAuthRequest> {
+        Ok(self.ake.peer_init(peer_id)?)
+    }
+
+    fn peer_accept(&self, req: PeerAuthRequest) -> Result<(PeerAuthResponse, PeerSession)> {
+        Ok(self.ake.peer_accept(req)?)
+    }
+
+    fn peer_connect(
+        &self,
+        peer_id: &ResponderId,
+        msg: PeerAuthResponse,
+    ) -> Result<(PeerSession, VerificationReport)> {
+        Ok(self.ake.peer_connect(peer_id, msg)?)
+    }
+
+    fn peer_close(&self, session_id: &PeerSession) -> Result<()> {
+        Ok(self.ake.peer_close(session_id)?)
+    }
+
+    fn create_shard_queries(
+        &self,
+        client_query: EnclaveMessage<ClientSession>,
+        shard_sessions: Vec<PeerSession>,
+    ) -> Result<Vec<EnclaveMessage<PeerSession>>> {
+        let client_session = client_query.channel_id.clone();
+        let aad = client_query.aad.clone();
+        let query_request = self.ake.client_decrypt(client_query)?;
+
+        let shard_query_request = mc_util_serial::serialize(&ShardQueryRequest {
+            client_session: client_session.0,
+            query_request,
+        })?;
+
+        shard_sessions
+            .iter()
+            .map(|session| Ok(self.ake.peer_encrypt(session, &aad, &shard_query_request)?))
+            .collect()
+    }
+
+    fn shard_query(
+        &self,
+        payload: EnclaveMessage<PeerSession>,
+        untrusted_query_response: UntrustedQueryResponse,
+    ) -> Result<EnclaveMessage<PeerSession>> {
+        let peer_session = payload.channel_id.clone();
+        let aad = payload.aad.clone();
+        let plaintext = self.ake.peer_decrypt(payload)?;
+        let shard_query_request: ShardQueryRequest = mc_util_serial::deserialize(&plaintext)?;
+
+        let req: QueryRequest = mc_util_serial::decode(&shard_query_request.query_request)
+            .map_err(|e| {
+                log::error!(
+                    self.logger,
+                    "Could not decode forwarded user request: {}",
+                    e
+                );
+                Error::ProstDecode
+            })?;
+
+        // The router compresses the collated response instead
+        let compress_response = req.compress_response;
+        let resp = self.query_impl(req, untrusted_query_response)?;
+
+        let shard_query_response = mc_util_serial::serialize(&ShardQueryResponse {
+            client_session: shard_query_request.client_session,
+            query_response: mc_util_serial::encode(&resp),
+            compress_response,
+        })?;
+
+        Ok(self
+            .ake
+            .peer_encrypt(&peer_session, &aad, &shard_query_response)?)
+    }
+
+    fn collate_shard_query_responses(
+        &self,
+        client_session: ClientSession,
+        shard_query_responses: Vec<EnclaveMessage<PeerSession>>,
+    ) -> Result<Vec<u8>> {
+        // Every store saw the same client request
+        let mut compress_response = false;
+        let responses = shard_query_responses
+            .into_iter()
+            .map(|msg| {
+                let plaintext = self.ake.peer_decrypt(msg)?;
+                let shard_query_response: ShardQueryResponse =
+                    mc_util_serial::deserialize(&plaintext)?;
+                if shard_query_response.client_session != client_session.0 {
+                    return Err(Error::ShardQueryResponseMismatch);
+                }
+                compress_response |= shard_query_response.compress_response;
+                Ok(mc_util_serial::decode(
+                    &shard_query_response.query_response,
+                )?)
+            })
+            .collect::<Result<Vec<QueryResponse>>>()?;
+
+        let mut resp = merge_shard_query_responses(responses)?;
+        if compress_response {
+            resp = resp.compress();
+        }
+        let response_plaintext_bytes = mc_util_serial::encode(&resp);
+
+        let response = self
+            .ake
+            .client_encrypt(&client_session, &[], &response_plaintext_bytes)?;
+
+        Ok(response.data)
+    }
 }
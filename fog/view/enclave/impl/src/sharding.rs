@@ -0,0 +1,198 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Merging the query responses of the store enclaves of a sharded view store.
+//!
+//! Every store enclave is asked about every search key, and at most one of
+//! them holds the record for it. The merge must not reveal which store
+//! answered, so the tx out search results are combined with conditional moves.
+
+use aligned_cmov::{
+    subtle::{ConditionallySelectable, ConstantTimeEq},
+    CMov,
+};
+use alloc::vec::Vec;
+use mc_fog_types::view::{QueryResponse, TxOutSearchResult, TxOutSearchResultCode};
+use mc_fog_view_enclave_api::{Error, Result};
+
+/// An upper bound on the length of a tx out search result ciphertext.
+/// This matches the size of the values in the ORAM of the e_tx_out_store,
+/// less the byte used to record the ciphertext length.
+const MAX_CIPHERTEXT_LEN: usize = 239;
+
+/// Merge the query responses of the store enclaves into one response for the
+/// client.
+///
+/// * A tx out search result is taken from the store which found the record, if
+///   any, and from the first store otherwise.
+/// * The block counts are the lowest across stores, since the client may only
+///   assume that every store has processed those blocks.
+/// * The user events come from the first store, since they are read from the
+///   recovery db and not from the enclave.
+pub fn merge_shard_query_responses(responses: Vec<QueryResponse>) -> Result<QueryResponse> {
+    let mut responses = responses.into_iter();
+    let mut merged = responses.next().ok_or(Error::NoShardQueryResponses)?;
+
+    for response in responses {
+        if response.tx_out_search_results.len() != merged.tx_out_search_results.len() {
+            return Err(Error::ShardQueryResponseMismatch);
+        }
+        for (merged_result, result) in merged
+            .tx_out_search_results
+            .iter_mut()
+            .zip(response.tx_out_search_results.iter())
+        {
+            merge_tx_out_search_result(merged_result, result)?;
+        }
+
+        if response.highest_processed_block_count < merged.highest_processed_block_count {
+            merged.highest_processed_block_count = response.highest_processed_block_count;
+            merged.highest_processed_block_signature_timestamp =
+                response.highest_processed_block_signature_timestamp;
+        }
+
+        if response.last_known_block_count < merged.last_known_block_count {
+            merged.last_known_block_count = response.last_known_block_count;
+            merged.last_known_block_cumulative_txo_count =
+                response.last_known_block_cumulative_txo_count;
+        }
+    }
+
+    Ok(merged)
+}
+
+/// Replace `merged` with `result` if `result` found the record, without
+/// branching on the result code.
+fn merge_tx_out_search_result(
+    merged: &mut TxOutSearchResult,
+    result: &TxOutSearchResult,
+) -> Result<()> {
+    if merged.search_key != result.search_key
+        || merged.ciphertext.len() > MAX_CIPHERTEXT_LEN
+        || result.ciphertext.len() > MAX_CIPHERTEXT_LEN
+    {
+        return Err(Error::ShardQueryResponseMismatch);
+    }
+
+    let found = result
+        .result_code
+        .ct_eq(&(TxOutSearchResultCode::Found as u32));
+
+    merged.result_code.cmov(found, &result.result_code);
+
+    let mut ciphertext_len = merged.ciphertext.len() as u64;
+    ciphertext_len.cmov(found, &(result.ciphertext.len() as u64));
+
+    let mut ciphertext = merged.ciphertext.clone();
+    ciphertext.resize(MAX_CIPHERTEXT_LEN, 0u8);
+    let mut other_ciphertext = result.ciphertext.clone();
+    other_ciphertext.resize(MAX_CIPHERTEXT_LEN, 0u8);
+    for (byte, other_byte) in ciphertext.iter_mut().zip(other_ciphertext.iter()) {
+        byte.conditional_assign(other_byte, found);
+    }
+    ciphertext.truncate(ciphertext_len as usize);
+
+    merged.ciphertext = ciphertext;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn search_result(search_key: u8, code: TxOutSearchResultCode, fill: u8) -> TxOutSearchResult {
+        let len = if code == TxOutSearchResultCode::Found {
+            200
+        } else {
+            232
+        };
+        TxOutSearchResult {
+            search_key: vec![search_key; 16],
+            result_code: code as u32,
+            ciphertext: vec![fill; len],
+        }
+    }
+
+    fn response(
+        highest_processed_block_count: u64,
+        tx_out_search_results: Vec<TxOutSearchResult>,
+    ) -> QueryResponse {
+        QueryResponse {
+            highest_processed_block_count,
+            highest_processed_block_signature_timestamp: highest_processed_block_count * 10,
+            last_known_block_count: highest_processed_block_count,
+            last_known_block_cumulative_txo_count: highest_processed_block_count * 2,
+            tx_out_search_results,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn merge_prefers_found_results() {
+        let merged = merge_shard_query_responses(vec![
+            response(
+                5,
+                vec![
+                    search_result(1, TxOutSearchResultCode::NotFound, 0),
+                    search_result(2, TxOutSearchResultCode::Found, 2),
+                    search_result(3, TxOutSearchResultCode::NotFound, 0),
+                ],
+            ),
+            response(
+                4,
+                vec![
+                    search_result(1, TxOutSearchResultCode::Found, 1),
+                    search_result(2, TxOutSearchResultCode::NotFound, 0),
+                    search_result(3, TxOutSearchResultCode::NotFound, 0),
+                ],
+            ),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            merged.tx_out_search_results,
+            vec![
+                search_result(1, TxOutSearchResultCode::Found, 1),
+                search_result(2, TxOutSearchResultCode::Found, 2),
+                search_result(3, TxOutSearchResultCode::NotFound, 0),
+            ]
+        );
+        assert_eq!(merged.highest_processed_block_count, 4);
+        assert_eq!(merged.highest_processed_block_signature_timestamp, 40);
+        assert_eq!(merged.last_known_block_count, 4);
+        assert_eq!(merged.last_known_block_cumulative_txo_count, 8);
+    }
+
+    #[test]
+    fn merge_rejects_mismatched_responses() {
+        assert!(matches!(
+            merge_shard_query_responses(vec![]),
+            Err(Error::NoShardQueryResponses)
+        ));
+
+        assert!(matches!(
+            merge_shard_query_responses(vec![
+                response(
+                    1,
+                    vec![search_result(1, TxOutSearchResultCode::NotFound, 0)]
+                ),
+                response(
+                    1,
+                    vec![search_result(2, TxOutSearchResultCode::NotFound, 0)]
+                ),
+            ]),
+            Err(Error::ShardQueryResponseMismatch)
+        ));
+
+        assert!(matches!(
+            merge_shard_query_responses(vec![
+                response(
+                    1,
+                    vec![search_result(1, TxOutSearchResultCode::NotFound, 0)]
+                ),
+                response(1, vec![]),
+            ]),
+            Err(Error::ShardQueryResponseMismatch)
+        ));
+    }
+}
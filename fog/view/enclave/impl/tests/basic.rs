@@ -6,7 +6,7 @@ use mc_common::{
     ResponderId,
 };
 use mc_fog_types::ETxOutRecord;
-use mc_fog_view_enclave_api::{ViewEnclaveApi, ViewEnclaveInitParams};
+use mc_fog_view_enclave_api::{ViewEnclaveApi, ViewEnclaveInitParams, DEFAULT_STASH_SIZE};
 use mc_fog_view_enclave_impl::ViewEnclave;
 use mc_oblivious_traits::HeapORAMStorageCreator;
 
@@ -18,6 +18,7 @@ fn basic(logger: Logger) {
         eid: 0,
         self_client_id: ResponderId::from_str("abc:123").unwrap(),
         desired_capacity: 1024 * 1024,
+        stash_size: DEFAULT_STASH_SIZE,
     };
 
     enclave.init(params).unwrap();
@@ -11,7 +11,10 @@ use std::{path, result::Result as StdResult, sync::Arc};
 use mc_attest_core::{
     IasNonce, Quote, QuoteNonce, Report, SgxError, TargetInfo, VerificationReport,
 };
-use mc_attest_enclave_api::{ClientAuthRequest, ClientAuthResponse, ClientSession, EnclaveMessage};
+use mc_attest_enclave_api::{
+    ClientAuthRequest, ClientAuthResponse, ClientSession, EnclaveMessage, PeerAuthRequest,
+    PeerAuthResponse, PeerSession,
+};
 use mc_attest_verifier::DEBUG_ENCLAVE;
 use mc_common::{logger::Logger, ResponderId};
 use mc_crypto_keys::X25519Public;
@@ -24,6 +27,7 @@ use mc_sgx_urts::SgxEnclave;
 
 pub use mc_fog_view_enclave_api::{
     Error, Result, ViewEnclaveApi, ViewEnclaveInitParams, ViewEnclaveProxy, ViewEnclaveRequest,
+    DEFAULT_STASH_SIZE,
 };
 
 mod ecall;
@@ -50,11 +54,14 @@ impl SgxViewEnclave {
     ///   oblivious map. Must be a power of two. Actual capacity will be ~70% of
     ///   this. Memory utilization will be about 256 bytes * this + some
     ///   overhead
+    /// * stash_size: The stash size of the ORAM backing the oblivious map, see
+    ///   [DEFAULT_STASH_SIZE].
     /// * logger: Logger to use
     pub fn new(
         enclave_path: path::PathBuf,
         client_responder_id: ResponderId,
         desired_capacity: u64,
+        stash_size: usize,
         _logger: Logger,
     ) -> Self {
         let mut launch_token: sgx_launch_token_t = [0; 1024];
@@ -89,6 +96,7 @@ impl SgxViewEnclave {
             eid,
             self_client_id: client_responder_id,
             desired_capacity,
+            stash_size,
         };
 
         result.init(params).expect("Could not initialize enclave");
@@ -179,4 +187,72 @@ impl ViewEnclaveApi for SgxViewEnclave {
         let outbuf = self.enclave_call(&inbuf)?;
         mc_util_serial::deserialize(&outbuf[..])?
     }
+
+    fn peer_init(&self, peer_id: &ResponderId) -> Result<PeerAuthRequest> {
+        let inbuf = mc_util_serial::serialize(&ViewEnclaveRequest::PeerInit(peer_id.clone()))?;
+        let outbuf = self.enclave_call(&inbuf)?;
+        mc_util_serial::deserialize(&outbuf[..])?
+    }
+
+    fn peer_accept(&self, req: PeerAuthRequest) -> Result<(PeerAuthResponse, PeerSession)> {
+        let inbuf = mc_util_serial::serialize(&ViewEnclaveRequest::PeerAccept(req))?;
+        let outbuf = self.enclave_call(&inbuf)?;
+        mc_util_serial::deserialize(&outbuf[..])?
+    }
+
+    fn peer_connect(
+        &self,
+        peer_id: &ResponderId,
+        msg: PeerAuthResponse,
+    ) -> Result<(PeerSession, VerificationReport)> {
+        let inbuf =
+            mc_util_serial::serialize(&ViewEnclaveRequest::PeerConnect(peer_id.clone(), msg))?;
+        let outbuf = self.enclave_call(&inbuf)?;
+        mc_util_serial::deserialize(&outbuf[..])?
+    }
+
+    fn peer_close(&self, session_id: &PeerSession) -> Result<()> {
+        let inbuf = mc_util_serial::serialize(&ViewEnclaveRequest::PeerClose(session_id.clone()))?;
+        let outbuf = self.enclave_call(&inbuf)?;
+        mc_util_serial::deserialize(&outbuf[..])?
+    }
+
+    fn create_shard_queries(
+        &self,
+        client_query: EnclaveMessage<ClientSession>,
+        shard_sessions: Vec<PeerSession>,
+    ) -> Result<Vec<EnclaveMessage<PeerSession>>> {
+        let inbuf = mc_util_serial::serialize(&ViewEnclaveRequest::CreateShardQueries(
+            client_query,
+            shard_sessions,
+        ))?;
+        let outbuf = self.enclave_call(&inbuf)?;
+        mc_util_serial::deserialize(&outbuf[..])?
+    }
+
+    fn shard_query(
+        &self,
+        payload: EnclaveMessage<PeerSession>,
+        untrusted_query_response: UntrustedQueryResponse,
+    ) -> Result<EnclaveMessage<PeerSession>> {
+        let inbuf = mc_util_serial::serialize(&ViewEnclaveRequest::ShardQuery(
+            payload,
+            untrusted_query_response,
+        ))?;
+        let outbuf = self.enclave_call(&inbuf)?;
+        mc_util_serial::deserialize(&outbuf[..])?
+    }
+
+    fn collate_shard_query_responses(
+        &self,
+        client_session: ClientSession,
+        shard_query_responses: Vec<EnclaveMessage<PeerSession>>,
+    ) -> Result<Vec<u8>> {
+        let inbuf = mc_util_serial::serialize(&ViewEnclaveRequest::CollateShardQueryResponses(
+            client_session,
+            shard_query_responses,
+        ))?;
+        let outbuf = self.enclave_call(&inbuf)?;
+        mc_util_serial::deserialize(&outbuf[..])?
+    }
 }
@@ -120,6 +120,21 @@ pub fn ecall_dispatcher(inbuf: &[u8]) -> Result<Vec<u8>, sgx_status_t> {
             serialize(&ENCLAVE.query(req, untrusted_query_response))
         }
         ViewEnclaveRequest::AddRecords(records) => serialize(&ENCLAVE.add_records(records)),
+        ViewEnclaveRequest::PeerInit(peer_id) => serialize(&ENCLAVE.peer_init(&peer_id)),
+        ViewEnclaveRequest::PeerAccept(msg) => serialize(&ENCLAVE.peer_accept(msg)),
+        ViewEnclaveRequest::PeerConnect(peer_id, msg) => {
+            serialize(&ENCLAVE.peer_connect(&peer_id, msg))
+        }
+        ViewEnclaveRequest::PeerClose(session) => serialize(&ENCLAVE.peer_close(&session)),
+        ViewEnclaveRequest::CreateShardQueries(msg, shard_sessions) => {
+            serialize(&ENCLAVE.create_shard_queries(msg, shard_sessions))
+        }
+        ViewEnclaveRequest::ShardQuery(msg, untrusted_query_response) => {
+            serialize(&ENCLAVE.shard_query(msg, untrusted_query_response))
+        }
+        ViewEnclaveRequest::CollateShardQueryResponses(client_session, shard_query_responses) => {
+            serialize(&ENCLAVE.collate_shard_query_responses(client_session, shard_query_responses))
+        }
     }
     .or(Err(sgx_status_t::SGX_ERROR_UNEXPECTED))
 }
@@ -48,6 +48,25 @@ pub trait FogViewConnection {
         start_from_user_event_id: i64,
         start_from_block_index: u64,
         search_keys: Vec<Vec<u8>>,
+    ) -> Result<QueryResponse, Self::Error> {
+        self.request_with_processed_block_count(
+            start_from_user_event_id,
+            start_from_block_index,
+            0,
+            search_keys,
+        )
+    }
+
+    /// Like request, but also skips the events of ingest invocations which
+    /// were started and decommissioned since the last request without
+    /// ingesting any block after the `processed_block_count` blocks the client
+    /// has already processed.
+    fn request_with_processed_block_count(
+        &mut self,
+        start_from_user_event_id: i64,
+        start_from_block_index: u64,
+        processed_block_count: u64,
+        search_keys: Vec<Vec<u8>>,
     ) -> Result<QueryResponse, Self::Error>;
 
     /// Take a view endpoint and use the above two functions to poll for updates
@@ -77,9 +96,10 @@ pub trait FogViewConnection {
         // Update seeds, get block count
         let mut new_highest_processed_block_count = {
             match self
-                .request(
+                .request_with_processed_block_count(
                     user_rng_set.get_next_start_from_user_event_id(),
                     user_rng_set.get_highest_processed_block_count().into(),
+                    user_rng_set.get_highest_processed_block_count().into(),
                     Default::default(),
                 )
                 .map_err(TxOutPollingError::Conn)
@@ -90,14 +110,18 @@ pub trait FogViewConnection {
                     return (vec![], vec![], vec![err]);
                 }
                 Ok(result) => {
-                    // TODO: Handle decommissioning of ingest invocations
-
                     for rng_record in result.rng_records.iter() {
                         if let Err(err) = user_rng_set.ingest_rng_record(upriv, rng_record) {
                             errs.push(TxOutPollingError::from(err));
                         }
                     }
 
+                    // The rngs of decommissioned ingest invocations are retired
+                    // below, once they have been searched through the last block
+                    for decommissioned in result.decommissioned_ingest_invocations.iter() {
+                        user_rng_set.ingest_decommissioned_ingest_invocation(decommissioned);
+                    }
+
                     // Missed block ranges are reported once, so we can add
                     // directly without fear of repeating ranges.
                     missed_block_ranges.extend(result.missed_block_ranges);
@@ -208,6 +232,12 @@ pub trait FogViewConnection {
             missed_block_ranges.extend(resp.missed_block_ranges);
         }
 
+        // Every rng has now been searched until it found nothing, in responses from
+        // servers which had processed at least new_highest_processed_block_count
+        // blocks. So an rng of an ingest invocation which was decommissioned before
+        // then has no TxOuts left to find.
+        user_rng_set.retire_exhausted_rngs(new_highest_processed_block_count);
+
         // Don't update the num_blocks value in reverse. If this time the servers
         // are behind where they were the last time we talked to them (due to load
         // balancer), that doesn't mean our previous balance computation was
@@ -8,7 +8,10 @@ use mc_crypto_box::Error as CryptoBoxError;
 use mc_crypto_keys::KeyError;
 use mc_fog_kex_rng::{BufferedRng, Error as KexRngError, NewFromKex, VersionedKexRng};
 use mc_fog_types::{
-    view::{RngRecord, TxOutRecord, TxOutSearchResult, TxOutSearchResultCode},
+    view::{
+        DecommissionedIngestInvocation, RngRecord, TxOutRecord, TxOutSearchResult,
+        TxOutSearchResultCode,
+    },
     BlockCount,
 };
 use mc_util_serial::DecodeError;
@@ -25,6 +28,28 @@ pub struct UserRngSet {
 
     /// Last next_start_from_user_event_id reported by the server
     next_start_from_user_event_id: i64,
+
+    /// nonce -> id of the ingest invocation which produced the rng
+    rng_ingest_invocation_ids: HashMap<Vec<u8>, i64>,
+
+    /// ingest invocation id -> the last block it ingested, for decommissioned
+    /// ingest invocations
+    decommissioned_ingest_invocations: HashMap<i64, u64>,
+
+    /// nonce -> rngs which were searched through the last block their ingest
+    /// invocation ingested, and so will not produce any more TxOuts
+    retired_rngs: HashMap<Vec<u8>, RetiredRng>,
+}
+
+/// An rng of a decommissioned ingest invocation, which is no longer searched
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RetiredRng {
+    /// The ingest invocation which produced the rng
+    pub ingest_invocation_id: i64,
+    /// The last block which the ingest invocation ingested
+    pub last_ingested_block: u64,
+    /// The number of TxOuts that were found with the rng
+    pub num_outputs: u64,
 }
 
 impl UserRngSet {
@@ -33,13 +58,30 @@ impl UserRngSet {
             rngs: HashMap::default(),
             highest_processed_block_count: 0,
             next_start_from_user_event_id: 0,
+            rng_ingest_invocation_ids: HashMap::default(),
+            decommissioned_ingest_invocations: HashMap::default(),
+            retired_rngs: HashMap::default(),
         }
     }
 
+    /// The rngs which are still searched for TxOuts
     pub fn get_rngs(&self) -> &HashMap<Vec<u8>, VersionedKexRng> {
         &self.rngs
     }
 
+    /// The rngs of decommissioned ingest invocations which have been searched
+    /// to the end, and so are no longer searched
+    pub fn get_retired_rngs(&self) -> &HashMap<Vec<u8>, RetiredRng> {
+        &self.retired_rngs
+    }
+
+    /// The last block which the ingest invocation that produced this rng
+    /// ingested, if it was decommissioned
+    pub fn get_rng_last_ingested_block(&self, nonce: &[u8]) -> Option<u64> {
+        let iid = self.rng_ingest_invocation_ids.get(nonce)?;
+        self.decommissioned_ingest_invocations.get(iid).copied()
+    }
+
     pub fn get_highest_processed_block_count(&self) -> BlockCount {
         BlockCount::from(self.highest_processed_block_count)
     }
@@ -64,13 +106,61 @@ impl UserRngSet {
         upriv: &UserPrivate,
         rec: &RngRecord,
     ) -> Result<(), TxOutRecoveryError> {
+        if self.retired_rngs.contains_key(&rec.pubkey.public_key) {
+            return Ok(());
+        }
         let rng = VersionedKexRng::try_from_kex_pubkey(&rec.pubkey, upriv.get_view_key())?;
         self.rngs
             .entry(rec.pubkey.public_key.clone())
             .or_insert(rng);
+        self.rng_ingest_invocation_ids
+            .insert(rec.pubkey.public_key.clone(), rec.ingest_invocation_id);
         Ok(())
     }
 
+    // Record that an ingest invocation was decommissioned, so that its rng can
+    // be retired once it has been searched through its last ingested block
+    pub fn ingest_decommissioned_ingest_invocation(
+        &mut self,
+        rec: &DecommissionedIngestInvocation,
+    ) {
+        self.decommissioned_ingest_invocations
+            .insert(rec.ingest_invocation_id, rec.last_ingested_block);
+    }
+
+    // Retire the rngs of decommissioned ingest invocations whose last ingested
+    // block is below `highest_processed_block_count`. This must only be called
+    // once every rng has been searched until it found nothing, in responses
+    // from a server which had processed this many blocks, since then all of
+    // their TxOuts have been found.
+    pub fn retire_exhausted_rngs(&mut self, highest_processed_block_count: u64) {
+        let exhausted: Vec<(Vec<u8>, RetiredRng)> = self
+            .rngs
+            .iter()
+            .filter_map(|(nonce, rng)| {
+                let ingest_invocation_id = *self.rng_ingest_invocation_ids.get(nonce)?;
+                let last_ingested_block = *self
+                    .decommissioned_ingest_invocations
+                    .get(&ingest_invocation_id)?;
+                (last_ingested_block < highest_processed_block_count).then(|| {
+                    (
+                        nonce.clone(),
+                        RetiredRng {
+                            ingest_invocation_id,
+                            last_ingested_block,
+                            num_outputs: rng.index(),
+                        },
+                    )
+                })
+            })
+            .collect();
+
+        for (nonce, retired_rng) in exhausted {
+            self.rngs.remove(&nonce);
+            self.retired_rngs.insert(nonce, retired_rng);
+        }
+    }
+
     // Take a collection of TxOutSearchResult's and match them up with rngs,
     // matching as much as possible before stopping
     pub fn ingest_tx_out_search_results(
@@ -189,3 +279,70 @@ impl From<KexRngError> for RngSetError {
         Self::KexRng(err)
     }
 }
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use mc_account_keys::AccountKey;
+    use mc_crypto_keys::RistrettoPublic;
+    use mc_fog_kex_rng::KexRngPubkey;
+    use mc_util_from_random::FromRandom;
+    use rand_core::SeedableRng;
+    use rand_hc::Hc128Rng;
+
+    #[test]
+    fn test_retire_exhausted_rngs() {
+        let mut rng = Hc128Rng::from_seed([1u8; 32]);
+        let upriv = UserPrivate::from(&AccountKey::random(&mut rng));
+        let records: Vec<RngRecord> = (1..=2)
+            .map(|ingest_invocation_id| RngRecord {
+                ingest_invocation_id,
+                pubkey: KexRngPubkey {
+                    public_key: Vec::from(&RistrettoPublic::from_random(&mut rng)),
+                    version: 0,
+                },
+                start_block: 0,
+            })
+            .collect();
+
+        let mut rng_set = UserRngSet::new();
+        for record in records.iter() {
+            rng_set.ingest_rng_record(&upriv, record).unwrap();
+        }
+        rng_set.ingest_decommissioned_ingest_invocation(&DecommissionedIngestInvocation {
+            ingest_invocation_id: 1,
+            last_ingested_block: 5,
+        });
+        let nonce = &records[0].pubkey.public_key;
+        assert_eq!(rng_set.get_rng_last_ingested_block(nonce), Some(5));
+        assert_eq!(
+            rng_set.get_rng_last_ingested_block(&records[1].pubkey.public_key),
+            None
+        );
+
+        // Block 5 is not processed yet, so the rng may still find TxOuts there.
+        rng_set.retire_exhausted_rngs(5);
+        assert_eq!(rng_set.get_rngs().len(), 2);
+        assert!(rng_set.get_retired_rngs().is_empty());
+
+        // Once it is, the rng is retired, and the other one is kept.
+        rng_set.retire_exhausted_rngs(6);
+        assert_eq!(rng_set.get_rngs().len(), 1);
+        assert!(rng_set
+            .get_rngs()
+            .contains_key(&records[1].pubkey.public_key));
+        assert_eq!(
+            rng_set.get_retired_rngs().get(nonce),
+            Some(&RetiredRng {
+                ingest_invocation_id: 1,
+                last_ingested_block: 5,
+                num_outputs: 0,
+            })
+        );
+
+        // Seeing the rng record again doesn't bring the retired rng back.
+        rng_set.ingest_rng_record(&upriv, &records[0]).unwrap();
+        assert_eq!(rng_set.get_rngs().len(), 1);
+        assert_eq!(rng_set.get_retired_rngs().len(), 1);
+    }
+}
@@ -3,10 +3,18 @@
 
 //! MobileCoin Fog View target
 use mc_attest_net::{Client, RaClient};
-use mc_common::{logger::log, time::SystemTimeProvider};
+use mc_common::{
+    logger::{log, Logger},
+    time::SystemTimeProvider,
+};
+use mc_fog_recovery_db_iface::RecoveryDb;
 use mc_fog_sql_recovery_db::SqlRecoveryDb;
+#[cfg(feature = "sqlite")]
+use mc_fog_sqlite_recovery_db::{sqlite_path_from_url, SqliteRecoveryDb};
 use mc_fog_view_enclave::{SgxViewEnclave, ENCLAVE_FILE};
-use mc_fog_view_server::{config::MobileAcctViewConfig, server::ViewServer};
+use mc_fog_view_server::{
+    config::MobileAcctViewConfig, omap_sizing::get_omap_capacity, server::ViewServer,
+};
 use mc_util_cli::ParserWithBuildInfo;
 use mc_util_grpc::AdminServer;
 use std::{env, sync::Arc};
@@ -19,8 +27,26 @@ fn main() {
     let config = MobileAcctViewConfig::parse();
 
     let database_url = env::var("DATABASE_URL").expect("Missing DATABASE_URL environment variable");
-    let recovery_db = SqlRecoveryDb::new_from_url(
+
+    // With the sqlite feature, a sqlite:// url selects the SQLite backend,
+    // which is meant for local development.
+    #[cfg(feature = "sqlite")]
+    if let Some(db_path) = sqlite_path_from_url(&database_url) {
+        let recovery_db =
+            SqliteRecoveryDb::new_from_path(db_path, Default::default(), logger.clone())
+                .unwrap_or_else(|err| {
+                    panic!(
+                        "fog-view cannot open database '{}': {:?}",
+                        database_url, err
+                    )
+                });
+        return run(config, recovery_db, logger);
+    }
+
+    let read_replica_url = env::var("DATABASE_READ_REPLICA_URL").ok();
+    let recovery_db = SqlRecoveryDb::new_from_urls(
         &database_url,
+        read_replica_url.as_deref(),
         config.postgres_config.clone(),
         logger.clone(),
     )
@@ -30,7 +56,14 @@ fn main() {
             database_url, err
         )
     });
+    run(config, recovery_db, logger)
+}
 
+fn run<DB: RecoveryDb + Clone + Send + Sync + 'static>(
+    config: MobileAcctViewConfig,
+    recovery_db: DB,
+    logger: Logger,
+) {
     let _tracer = mc_util_telemetry::setup_default_tracer_with_tags(
         env!("CARGO_PKG_NAME"),
         &[(
@@ -49,10 +82,13 @@ fn main() {
         enclave_path.to_str().unwrap(),
         &config.client_responder_id
     );
+    let omap_capacity = get_omap_capacity(&config, &recovery_db, &logger)
+        .unwrap_or_else(|err| panic!("fog-view cannot estimate the OMAP capacity: {}", err));
     let sgx_enclave = SgxViewEnclave::new(
         enclave_path,
         config.client_responder_id.clone(),
-        config.omap_capacity,
+        omap_capacity,
+        config.omap_stash_size,
         logger.clone(),
     );
 
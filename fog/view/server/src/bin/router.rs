@@ -0,0 +1,77 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+#![deny(missing_docs)]
+
+//! MobileCoin Fog View Router target
+use mc_attest_net::{Client, RaClient};
+use mc_common::{logger::log, time::SystemTimeProvider};
+use mc_fog_view_enclave::{SgxViewEnclave, DEFAULT_STASH_SIZE, ENCLAVE_FILE};
+use mc_fog_view_server::{
+    config::FogViewRouterConfig,
+    router_server::{FogViewRouterServer, ROUTER_OMAP_CAPACITY},
+};
+use mc_util_cli::ParserWithBuildInfo;
+use mc_util_grpc::AdminServer;
+use std::{env, sync::Arc};
+
+fn main() {
+    mc_common::setup_panic_handler();
+    let _sentry_guard = mc_common::sentry::init();
+    let (logger, _global_logger_guard) =
+        mc_common::logger::create_app_logger(mc_common::logger::o!());
+    let config = FogViewRouterConfig::parse();
+
+    let _tracer = mc_util_telemetry::setup_default_tracer_with_tags(
+        env!("CARGO_PKG_NAME"),
+        &[(
+            "client_responser_id",
+            config.client_responder_id.to_string(),
+        )],
+    )
+    .expect("Failed setting telemetry tracer");
+
+    let enclave_path = env::current_exe()
+        .expect("Could not get the path of our executable")
+        .with_file_name(ENCLAVE_FILE);
+    log::info!(
+        logger,
+        "enclave path {}, responder ID {}",
+        enclave_path.to_str().unwrap(),
+        &config.client_responder_id
+    );
+    let sgx_enclave = SgxViewEnclave::new(
+        enclave_path,
+        config.client_responder_id.clone(),
+        ROUTER_OMAP_CAPACITY,
+        DEFAULT_STASH_SIZE,
+        logger.clone(),
+    );
+
+    let ias_client = Client::new(&config.ias_api_key).expect("Could not create IAS client");
+
+    let mut server = FogViewRouterServer::new(
+        config.clone(),
+        sgx_enclave,
+        ias_client,
+        SystemTimeProvider::default(),
+        logger.clone(),
+    );
+    server.start();
+
+    let config_json = serde_json::to_string(&config).expect("failed to serialize config to JSON");
+    let get_config_json = Arc::new(move || Ok(config_json.clone()));
+    let _admin_server = config.admin_listen_uri.as_ref().map(|admin_listen_uri| {
+        AdminServer::start(
+            None,
+            admin_listen_uri,
+            "Fog View Router".to_owned(),
+            config.client_responder_id.to_string(),
+            Some(get_config_json),
+            logger,
+        )
+        .expect("Failed starting fog-view-router admin server")
+    });
+
+    loop {
+        std::thread::sleep(std::time::Duration::from_millis(1000));
+    }
+}
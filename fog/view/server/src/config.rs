@@ -3,6 +3,7 @@
 //! Configuration parameters for the MobileCoin Fog View Node
 #![deny(missing_docs)]
 
+use crate::sharding::SearchKeyRange;
 use clap::Parser;
 use mc_attest_core::ProviderId;
 use mc_common::ResponderId;
@@ -56,15 +57,93 @@ pub struct MobileAcctViewConfig {
     /// The hash table will overflow when there are more TxOut's than this,
     /// and the server will have to be restarted with a larger number.
     ///
+    /// When this is not set, the capacity is estimated at startup from the
+    /// number of TxOuts in the recovery database, see
+    /// --omap-capacity-headroom-percent.
+    ///
     /// Note: At time of writing, the hash table will be allocated to use all
     /// available SGX EPC memory, and then beyond that it will be allocated on
     /// the heap in the untrusted side. Once the needed capacity exceeds RAM,
     /// you will either get killed by OOM killer, or it will start being swapped
     /// to disk by linux kernel.
-    #[clap(long, default_value = "1048576", env = "MC_OMAP_CAPACITY")]
-    pub omap_capacity: u64,
+    #[clap(long, env = "MC_OMAP_CAPACITY")]
+    pub omap_capacity: Option<u64>,
+
+    /// When the OMAP capacity is estimated, how much room to leave for TxOuts
+    /// added after startup, as a percentage of the TxOuts already in the
+    /// recovery database.
+    #[clap(long, default_value = "100", env = "MC_OMAP_CAPACITY_HEADROOM_PERCENT")]
+    pub omap_capacity_headroom_percent: u64,
+
+    /// The stash size of the ORAM backing the OMAP.
+    #[clap(long, default_value = "32", env = "MC_OMAP_STASH_SIZE")]
+    pub omap_stash_size: usize,
+
+    /// The range of search keys to load into the enclave, when this server is
+    /// one of the stores of a sharded view store, behind a fog view router.
+    /// Two hex numbers separated by a dash, compared with the first 8 bytes
+    /// of the search keys, e.g. `0-7fffffffffffffff`. Defaults to all search
+    /// keys.
+    #[clap(long, env = "MC_SEARCH_KEY_RANGE")]
+    pub search_key_range: Option<SearchKeyRange>,
+
+    /// The ID with which this server reports its progress loading blocks to
+    /// the recovery db, so that pruning does not remove blocks it still needs.
+    /// Defaults to a random ID, which is new each time the server starts.
+    #[clap(long, env = "MC_VIEW_SHARD_ID")]
+    pub view_shard_id: Option<String>,
 
     /// Postgres config
     #[clap(flatten)]
     pub postgres_config: SqlRecoveryDbConnectionConfig,
 }
+
+/// Configuration parameters for the MobileCoin Fog View Router, which serves
+/// clients from a view store that is sharded across several fog view servers.
+#[derive(Clone, Parser, Serialize)]
+#[clap(version)]
+pub struct FogViewRouterConfig {
+    /// The ID with which to respond to client attestation requests.
+    ///
+    /// This ID needs to match the host:port clients use in their URI when
+    /// referencing this node.
+    #[clap(long, env = "MC_CLIENT_RESPONDER_ID")]
+    pub client_responder_id: ResponderId,
+
+    /// PEM-formatted keypair to send with an Attestation Request.
+    #[clap(long, env = "MC_IAS_API_KEY")]
+    pub ias_api_key: String,
+
+    /// The IAS SPID to use when getting a quote
+    #[clap(long, env = "MC_IAS_SPID")]
+    pub ias_spid: ProviderId,
+
+    /// gRPC listening URI for client requests.
+    #[clap(long, env = "MC_CLIENT_LISTEN_URI")]
+    pub client_listen_uri: FogViewUri,
+
+    /// The client URIs of the fog view store servers. Together, the
+    /// `--search-key-range`s of the stores must cover all search keys.
+    #[clap(
+        long = "shard-uri",
+        required = true,
+        env = "MC_SHARD_URIS",
+        use_value_delimiter = true
+    )]
+    pub shard_uris: Vec<FogViewUri>,
+
+    /// Optional admin listening URI.
+    #[clap(long, env = "MC_ADMIN_LISTEN_URI")]
+    pub admin_listen_uri: Option<AdminUri>,
+
+    /// Enables authenticating client requests using Authorization tokens using
+    /// the provided hex-encoded 32 bytes shared secret.
+    #[clap(long, parse(try_from_str = hex::FromHex::from_hex), env = "MC_CLIENT_AUTH_TOKEN_SECRET")]
+    pub client_auth_token_secret: Option<[u8; 32]>,
+
+    /// Maximal client authentication token lifetime, in seconds (only relevant
+    /// when --client-auth-token-secret is used. Defaults to 86400 - 24
+    /// hours).
+    #[clap(long, default_value = "86400", parse(try_from_str = parse_duration_in_seconds), env = "MC_CLIENT_AUTH_TOKEN_MAX_LIFETIME")]
+    pub client_auth_token_max_lifetime: Duration,
+}
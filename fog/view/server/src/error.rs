@@ -3,6 +3,7 @@
 use displaydoc::Display;
 use mc_fog_view_enclave::Error as ViewEnclaveError;
 use mc_sgx_report_cache_untrusted::Error as ReportCacheError;
+use mc_util_uri::UriConversionError;
 
 #[derive(Debug, Display)]
 pub enum ViewServerError {
@@ -27,3 +28,32 @@ impl From<ReportCacheError> for ViewServerError {
         Self::ReportCache(src)
     }
 }
+
+/// An error when a fog view router talks to a fog view store
+#[derive(Debug, Display)]
+pub enum RouterError {
+    /// View Enclave error: {0}
+    Enclave(ViewEnclaveError),
+    /// gRPC error: {0}
+    Grpc(grpcio::Error),
+    /// Invalid store URI: {0}
+    UriConversion(UriConversionError),
+}
+
+impl From<ViewEnclaveError> for RouterError {
+    fn from(src: ViewEnclaveError) -> Self {
+        Self::Enclave(src)
+    }
+}
+
+impl From<grpcio::Error> for RouterError {
+    fn from(src: grpcio::Error) -> Self {
+        Self::Grpc(src)
+    }
+}
+
+impl From<UriConversionError> for RouterError {
+    fn from(src: UriConversionError) -> Self {
+        Self::UriConversion(src)
+    }
+}
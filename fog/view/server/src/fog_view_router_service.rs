@@ -0,0 +1,210 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! The client facing service of a fog view router.
+//!
+//! The router enclave decrypts each client query, and forwards it to every
+//! fog view store over their attested peer sessions. The stores each answer
+//! for their own range of search keys, and the router enclave merges their
+//! answers into the response for the client. Clients talk to a router in the
+//! same way as to a fog view server.
+
+use crate::{
+    error::RouterError, fog_view_service::enclave_err_to_rpc_status,
+    view_store_connection::ViewStoreConnection,
+};
+use futures::executor::block_on;
+use grpcio::{RpcContext, RpcStatus, UnarySink};
+use mc_attest_api::attest;
+use mc_attest_enclave_api::{ClientSession, EnclaveMessage, PeerSession};
+use mc_common::logger::{log, Logger};
+use mc_fog_api::view_grpc::FogViewApi;
+use mc_fog_view_enclave::ViewEnclaveProxy;
+use mc_util_grpc::{
+    rpc_logger, rpc_permissions_error, rpc_unavailable_error, send_result, Authenticator,
+};
+use mc_util_metrics::SVC_COUNTERS;
+use mc_util_telemetry::{tracer, Tracer};
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone)]
+pub struct FogViewRouterService<E: ViewEnclaveProxy> {
+    /// The router enclave
+    enclave: E,
+
+    /// The connections to the fog view stores.
+    ///
+    /// The peer sessions are stateful, so messages must reach a store in the
+    /// order they were encrypted in. The lock is held for the whole round
+    /// trip of a query to the stores.
+    shards: Arc<Mutex<Vec<ViewStoreConnection<E>>>>,
+
+    /// GRPC request authenticator.
+    authenticator: Arc<dyn Authenticator + Send + Sync>,
+
+    /// Slog logger object
+    logger: Logger,
+}
+
+impl<E: ViewEnclaveProxy> FogViewRouterService<E> {
+    /// Creates a new fog-view-router-service (but does not create sockets and
+    /// start it etc.)
+    pub fn new(
+        enclave: E,
+        shards: Arc<Mutex<Vec<ViewStoreConnection<E>>>>,
+        authenticator: Arc<dyn Authenticator + Send + Sync>,
+        logger: Logger,
+    ) -> Self {
+        Self {
+            enclave,
+            shards,
+            authenticator,
+            logger,
+        }
+    }
+
+    /// Forward the query to the stores, and collate their responses
+    pub fn query_impl(&mut self, request: attest::Message) -> Result<attest::Message, RpcStatus> {
+        log::trace!(self.logger, "Getting encrypted request");
+        let tracer = tracer!();
+
+        tracer.in_span("router_query_impl", |_cx| {
+            let client_session = ClientSession::from(request.get_channel_id());
+
+            let mut shards = self.shards.lock().expect("mutex poisoned");
+
+            let mut shard_sessions = Vec::with_capacity(shards.len());
+            for shard in shards.iter_mut() {
+                let peer_session = shard.attest().map_err(|err| {
+                    shard.deattest();
+                    rpc_unavailable_error(
+                        "attest",
+                        format!("fog view store {}: {}", shard.uri(), err),
+                        &self.logger,
+                    )
+                })?;
+                shard_sessions.push(peer_session);
+            }
+
+            let shard_queries = self
+                .enclave
+                .create_shard_queries(request.into(), shard_sessions)
+                .map_err(|e| enclave_err_to_rpc_status("create_shard_queries", e, &self.logger))?;
+
+            // Once the queries are encrypted, the peer sessions only stay in sync
+            // if every response is decrypted, so any failure deattests all stores.
+            let result_blob = tracer
+                .in_span("shard_queries", |_cx| {
+                    self.query_shards(&shards, shard_queries)
+                })
+                .and_then(|shard_responses| {
+                    self.enclave
+                        .collate_shard_query_responses(client_session, shard_responses)
+                        .map_err(|e| {
+                            enclave_err_to_rpc_status(
+                                "collate_shard_query_responses",
+                                e,
+                                &self.logger,
+                            )
+                        })
+                })
+                .map_err(|status| {
+                    shards.iter_mut().for_each(ViewStoreConnection::deattest);
+                    status
+                })?;
+
+            let mut resp = attest::Message::new();
+            resp.set_data(result_blob);
+            Ok(resp)
+        })
+    }
+
+    /// Send the shard queries to the stores in parallel, and wait for all of
+    /// their responses.
+    fn query_shards(
+        &self,
+        shards: &[ViewStoreConnection<E>],
+        shard_queries: Vec<EnclaveMessage<PeerSession>>,
+    ) -> Result<Vec<EnclaveMessage<PeerSession>>, RpcStatus> {
+        let responses = shards
+            .iter()
+            .zip(shard_queries)
+            .map(|(shard, shard_query)| {
+                let receiver = shard.query_async(shard_query);
+                async move {
+                    let response = receiver?.await.map_err(RouterError::from)?;
+                    Ok::<_, RouterError>(EnclaveMessage::from(response))
+                }
+            });
+
+        block_on(futures::future::join_all(responses))
+            .into_iter()
+            .zip(shards)
+            .map(|(result, shard)| {
+                result.map_err(|err| {
+                    rpc_unavailable_error(
+                        "shard_query",
+                        format!("fog view store {}: {}", shard.uri(), err),
+                        &self.logger,
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+// Implement grpc trait
+impl<E: ViewEnclaveProxy> FogViewApi for FogViewRouterService<E> {
+    fn auth(
+        &mut self,
+        ctx: RpcContext,
+        mut request: attest::AuthMessage,
+        sink: UnarySink<attest::AuthMessage>,
+    ) {
+        let _timer = SVC_COUNTERS.req(&ctx);
+        mc_common::logger::scoped_global_logger(&rpc_logger(&ctx, &self.logger), |logger| {
+            if let Err(err) = self.authenticator.authenticate_rpc(&ctx) {
+                return send_result(ctx, sink, err.into(), logger);
+            }
+
+            let result = self
+                .enclave
+                .client_accept(request.take_data().into())
+                .map(|(response, _)| {
+                    let mut result = attest::AuthMessage::new();
+                    result.set_data(response.into());
+                    result
+                })
+                .map_err(|client_error| {
+                    // This is debug because there's no requirement on the remote party to trigger
+                    // it.
+                    log::debug!(
+                        logger,
+                        "ViewEnclaveApi::client_accept failed: {}",
+                        client_error
+                    );
+                    rpc_permissions_error(
+                        "client_auth",
+                        format!("Permission denied: {}", client_error),
+                        logger,
+                    )
+                });
+            send_result(ctx, sink, result, logger);
+        });
+    }
+
+    fn query(
+        &mut self,
+        ctx: RpcContext,
+        request: attest::Message,
+        sink: UnarySink<attest::Message>,
+    ) {
+        let _timer = SVC_COUNTERS.req(&ctx);
+        mc_common::logger::scoped_global_logger(&rpc_logger(&ctx, &self.logger), |logger| {
+            if let Err(err) = self.authenticator.authenticate_rpc(&ctx) {
+                return send_result(ctx, sink, err.into(), logger);
+            }
+
+            send_result(ctx, sink, self.query_impl(request), logger)
+        })
+    }
+}
@@ -5,7 +5,7 @@ use grpcio::{RpcContext, RpcStatus, RpcStatusCode, UnarySink};
 use mc_attest_api::attest;
 use mc_common::logger::{log, Logger};
 use mc_fog_api::view_grpc::FogViewApi;
-use mc_fog_recovery_db_iface::RecoveryDb;
+use mc_fog_recovery_db_iface::{skip_processed_user_events, RecoveryDb};
 use mc_fog_types::view::QueryRequestAAD;
 use mc_fog_view_enclave::{Error as ViewEnclaveError, ViewEnclaveProxy};
 use mc_fog_view_enclave_api::UntrustedQueryResponse;
@@ -60,50 +60,17 @@ impl<E: ViewEnclaveProxy, DB: RecoveryDb + Send + Sync> FogViewService<E, DB> {
         let tracer = tracer!();
 
         tracer.in_span("query_impl", |_cx| {
-            // Attempt and deserialize the untrusted portion of this request.
-            let query_request_aad: QueryRequestAAD = mc_util_serial::decode(request.get_aad())
-                .map_err(|err| {
-                    RpcStatus::with_message(
-                        RpcStatusCode::INVALID_ARGUMENT,
-                        format!("AAD deserialization error: {}", err),
-                    )
-                })?;
-
-            let (user_events, next_start_from_user_event_id) =
-                tracer.in_span("search_user_events", |_cx| {
-                    self.db
-                        .search_user_events(query_request_aad.start_from_user_event_id)
-                        .map_err(|e| rpc_internal_error("search_user_events", e, &self.logger))
-                })?;
-
-            let (
-                highest_processed_block_count,
-                highest_processed_block_signature_timestamp,
-                last_known_block_count,
-                last_known_block_cumulative_txo_count,
-            ) = tracer.in_span("get_shared_state", |_cx_| {
-                let shared_state = self.db_poll_shared_state.lock().expect("mutex poisoned");
-                (
-                    shared_state.highest_processed_block_count,
-                    shared_state.highest_processed_block_signature_timestamp,
-                    shared_state.last_known_block_count,
-                    shared_state.last_known_block_cumulative_txo_count,
-                )
-            });
-
-            let untrusted_query_response = UntrustedQueryResponse {
-                user_events,
-                next_start_from_user_event_id,
-                highest_processed_block_count,
-                highest_processed_block_signature_timestamp,
-                last_known_block_count,
-                last_known_block_cumulative_txo_count,
-            };
+            let untrusted_query_response = get_untrusted_query_response(
+                request.get_aad(),
+                &*self.db,
+                &self.db_poll_shared_state,
+                &self.logger,
+            )?;
 
             let result_blob = tracer.in_span("enclave_query", |_cx| {
                 self.enclave
                     .query(request.into(), untrusted_query_response)
-                    .map_err(|e| self.enclave_err_to_rpc_status("enclave request", e))
+                    .map_err(|e| enclave_err_to_rpc_status("enclave request", e, &self.logger))
             })?;
 
             let mut resp = attest::Message::new();
@@ -111,21 +78,74 @@ impl<E: ViewEnclaveProxy, DB: RecoveryDb + Send + Sync> FogViewService<E, DB> {
             Ok(resp)
         })
     }
+}
 
-    // Helper function that is common
-    fn enclave_err_to_rpc_status(&self, context: &str, src: ViewEnclaveError) -> RpcStatus {
-        // Treat prost-decode error as an invalid arg,
-        // treat attest error as permission denied,
-        // everything else is an internal error
-        match src {
-            ViewEnclaveError::ProstDecode => {
-                rpc_invalid_arg_error(context, "Prost decode failed", &self.logger)
-            }
-            ViewEnclaveError::AttestEnclave(err) => {
-                rpc_permissions_error(context, err, &self.logger)
-            }
-            other => rpc_internal_error(context, format!("{}", &other), &self.logger),
+/// Get the untrusted part of the response to a query, from the recovery db and
+/// the state of the db polling thread.
+pub(crate) fn get_untrusted_query_response<DB: RecoveryDb>(
+    aad: &[u8],
+    db: &DB,
+    db_poll_shared_state: &Mutex<DbPollSharedState>,
+    logger: &Logger,
+) -> Result<UntrustedQueryResponse, RpcStatus> {
+    let tracer = tracer!();
+
+    // Attempt and deserialize the untrusted portion of this request.
+    let query_request_aad: QueryRequestAAD = mc_util_serial::decode(aad).map_err(|err| {
+        RpcStatus::with_message(
+            RpcStatusCode::INVALID_ARGUMENT,
+            format!("AAD deserialization error: {}", err),
+        )
+    })?;
+
+    let (user_events, next_start_from_user_event_id) =
+        tracer.in_span("search_user_events", |_cx| {
+            db.search_user_events(query_request_aad.start_from_user_event_id)
+                .map_err(|e| rpc_internal_error("search_user_events", e, logger))
+        })?;
+    let user_events =
+        skip_processed_user_events(user_events, query_request_aad.processed_block_count);
+
+    let (
+        highest_processed_block_count,
+        highest_processed_block_signature_timestamp,
+        last_known_block_count,
+        last_known_block_cumulative_txo_count,
+    ) = tracer.in_span("get_shared_state", |_cx_| {
+        let shared_state = db_poll_shared_state.lock().expect("mutex poisoned");
+        (
+            shared_state.highest_processed_block_count,
+            shared_state.highest_processed_block_signature_timestamp,
+            shared_state.last_known_block_count,
+            shared_state.last_known_block_cumulative_txo_count,
+        )
+    });
+
+    Ok(UntrustedQueryResponse {
+        user_events,
+        next_start_from_user_event_id,
+        highest_processed_block_count,
+        highest_processed_block_signature_timestamp,
+        last_known_block_count,
+        last_known_block_cumulative_txo_count,
+    })
+}
+
+// Helper function that is common
+pub(crate) fn enclave_err_to_rpc_status(
+    context: &str,
+    src: ViewEnclaveError,
+    logger: &Logger,
+) -> RpcStatus {
+    // Treat prost-decode error as an invalid arg,
+    // treat attest error as permission denied,
+    // everything else is an internal error
+    match src {
+        ViewEnclaveError::ProstDecode => {
+            rpc_invalid_arg_error(context, "Prost decode failed", logger)
         }
+        ViewEnclaveError::AttestEnclave(err) => rpc_permissions_error(context, err, logger),
+        other => rpc_internal_error(context, format!("{}", &other), logger),
     }
 }
 
@@ -0,0 +1,129 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! The service a fog view server provides to fog view routers, when it is one
+//! of the stores of a sharded view store.
+
+use crate::{
+    fog_view_service::{enclave_err_to_rpc_status, get_untrusted_query_response},
+    server::DbPollSharedState,
+};
+use grpcio::{RpcContext, RpcStatus, UnarySink};
+use mc_attest_api::attest;
+use mc_common::logger::{log, Logger};
+use mc_fog_api::view_grpc::FogViewStoreApi;
+use mc_fog_recovery_db_iface::RecoveryDb;
+use mc_fog_view_enclave::ViewEnclaveProxy;
+use mc_util_grpc::{rpc_logger, rpc_permissions_error, send_result};
+use mc_util_metrics::SVC_COUNTERS;
+use mc_util_telemetry::{tracer, Tracer};
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone)]
+pub struct FogViewStoreService<E: ViewEnclaveProxy, DB: RecoveryDb + Send + Sync> {
+    /// Enclave providing access to the Recovery DB
+    enclave: E,
+
+    /// Recovery DB.
+    db: Arc<DB>,
+
+    /// Shared state from db polling thread.
+    db_poll_shared_state: Arc<Mutex<DbPollSharedState>>,
+
+    /// Slog logger object
+    logger: Logger,
+}
+
+impl<E: ViewEnclaveProxy, DB: RecoveryDb + Send + Sync> FogViewStoreService<E, DB> {
+    /// Creates a new fog-view-store-service (but does not create sockets and
+    /// start it etc.)
+    pub fn new(
+        enclave: E,
+        db: Arc<DB>,
+        db_poll_shared_state: Arc<Mutex<DbPollSharedState>>,
+        logger: Logger,
+    ) -> Self {
+        Self {
+            enclave,
+            db,
+            db_poll_shared_state,
+            logger,
+        }
+    }
+
+    /// Accept a peer connection from a router enclave
+    pub fn auth_impl(
+        &mut self,
+        mut request: attest::AuthMessage,
+        logger: &Logger,
+    ) -> Result<attest::AuthMessage, RpcStatus> {
+        let (response, _) = self
+            .enclave
+            .peer_accept(request.take_data().into())
+            .map_err(|peer_error| {
+                // This is debug because there's no requirement on the remote party to trigger
+                // it.
+                log::debug!(logger, "ViewEnclaveApi::peer_accept failed: {}", peer_error);
+                rpc_permissions_error(
+                    "peer_auth",
+                    format!("Permission denied: {}", peer_error),
+                    logger,
+                )
+            })?;
+
+        let mut result = attest::AuthMessage::new();
+        result.set_data(response.into());
+        Ok(result)
+    }
+
+    /// Unwrap and forward to enclave
+    pub fn query_impl(&mut self, request: attest::Message) -> Result<attest::Message, RpcStatus> {
+        log::trace!(self.logger, "Getting encrypted shard request");
+        let tracer = tracer!();
+
+        tracer.in_span("shard_query_impl", |_cx| {
+            let untrusted_query_response = get_untrusted_query_response(
+                request.get_aad(),
+                &*self.db,
+                &self.db_poll_shared_state,
+                &self.logger,
+            )?;
+
+            let response = tracer.in_span("enclave_shard_query", |_cx| {
+                self.enclave
+                    .shard_query(request.into(), untrusted_query_response)
+                    .map_err(|e| enclave_err_to_rpc_status("enclave request", e, &self.logger))
+            })?;
+
+            Ok(response.into())
+        })
+    }
+}
+
+// Implement grpc trait
+impl<E: ViewEnclaveProxy, DB: RecoveryDb + Send + Sync> FogViewStoreApi
+    for FogViewStoreService<E, DB>
+{
+    fn auth(
+        &mut self,
+        ctx: RpcContext,
+        request: attest::AuthMessage,
+        sink: UnarySink<attest::AuthMessage>,
+    ) {
+        let _timer = SVC_COUNTERS.req(&ctx);
+        mc_common::logger::scoped_global_logger(&rpc_logger(&ctx, &self.logger), |logger| {
+            send_result(ctx, sink, self.auth_impl(request, logger), logger)
+        });
+    }
+
+    fn query(
+        &mut self,
+        ctx: RpcContext,
+        request: attest::Message,
+        sink: UnarySink<attest::Message>,
+    ) {
+        let _timer = SVC_COUNTERS.req(&ctx);
+        mc_common::logger::scoped_global_logger(&rpc_logger(&ctx, &self.logger), |logger| {
+            send_result(ctx, sink, self.query_impl(request), logger)
+        })
+    }
+}
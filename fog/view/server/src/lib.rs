@@ -4,8 +4,14 @@
 
 pub mod config;
 pub mod error;
+pub mod fog_view_router_service;
 pub mod fog_view_service;
+pub mod fog_view_store_service;
+pub mod omap_sizing;
+pub mod router_server;
 pub mod server;
+pub mod sharding;
+pub mod view_store_connection;
 
 mod block_tracker;
 mod counters;
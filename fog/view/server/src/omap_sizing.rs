@@ -0,0 +1,104 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Sizing of the OMAP (ORAM hash table) holding the ETxOutRecords.
+//!
+//! The OMAP cannot grow once the enclave is initialized, so its capacity is
+//! picked at startup. Unless the operator sets it, it is estimated from the
+//! number of TxOuts in the recovery database.
+
+use crate::config::MobileAcctViewConfig;
+use mc_common::logger::{log, Logger};
+use mc_fog_recovery_db_iface::RecoveryDb;
+
+/// The smallest capacity the estimator picks.
+pub const MIN_OMAP_CAPACITY: u64 = 512;
+
+/// The percentage of the capacity of the OMAP which can be used before it
+/// overflows, with some margin.
+pub const OMAP_USABLE_CAPACITY_PERCENT: u64 = 70;
+
+/// The OMAP capacity to hold `num_records` ETxOutRecords, plus
+/// `headroom_percent` percent more for records added later.
+///
+/// The capacity is a power of two, as the OMAP requires.
+pub fn omap_capacity_for_records(num_records: u64, headroom_percent: u64) -> u64 {
+    let expected_records =
+        num_records.saturating_add(num_records.saturating_mul(headroom_percent) / 100);
+    let capacity = expected_records
+        .saturating_mul(100)
+        .saturating_add(OMAP_USABLE_CAPACITY_PERCENT - 1)
+        / OMAP_USABLE_CAPACITY_PERCENT;
+    capacity
+        .max(MIN_OMAP_CAPACITY)
+        .checked_next_power_of_two()
+        .unwrap_or(1 << 63)
+}
+
+/// Estimate the OMAP capacity from the number of TxOuts in the recovery
+/// database.
+///
+/// This counts every TxOut up to the highest known block, which is an upper
+/// bound on the number of ETxOutRecords the view server loads.
+pub fn estimate_omap_capacity<DB: RecoveryDb>(
+    db: &DB,
+    headroom_percent: u64,
+) -> Result<u64, DB::Error> {
+    let num_records = match db.get_highest_known_block_index()? {
+        Some(block_index) => db
+            .get_cumulative_txo_count_for_block(block_index)?
+            .unwrap_or(0),
+        None => 0,
+    };
+    Ok(omap_capacity_for_records(num_records, headroom_percent))
+}
+
+/// Get the OMAP capacity to start the view enclave with: the configured one if
+/// any, and an estimate otherwise.
+pub fn get_omap_capacity<DB: RecoveryDb>(
+    config: &MobileAcctViewConfig,
+    db: &DB,
+    logger: &Logger,
+) -> Result<u64, DB::Error> {
+    if let Some(omap_capacity) = config.omap_capacity {
+        log::info!(logger, "Using configured OMAP capacity {}", omap_capacity);
+        return Ok(omap_capacity);
+    }
+
+    let omap_capacity = estimate_omap_capacity(db, config.omap_capacity_headroom_percent)?;
+    log::info!(
+        logger,
+        "Estimated OMAP capacity {} from the recovery database, with {}% headroom",
+        omap_capacity,
+        config.omap_capacity_headroom_percent
+    );
+    Ok(omap_capacity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn omap_capacity_for_records_is_a_large_enough_power_of_two() {
+        assert_eq!(omap_capacity_for_records(0, 100), MIN_OMAP_CAPACITY);
+        assert_eq!(omap_capacity_for_records(100, 0), MIN_OMAP_CAPACITY);
+
+        // 1000 records need 1429 slots, doubled is 2858 slots
+        assert_eq!(omap_capacity_for_records(1000, 0), 2048);
+        assert_eq!(omap_capacity_for_records(1000, 100), 4096);
+
+        for num_records in [1u64, 1000, 123_456, 10_000_000] {
+            for headroom_percent in [0u64, 50, 100, 300] {
+                let capacity = omap_capacity_for_records(num_records, headroom_percent);
+                assert!(capacity.is_power_of_two());
+                let expected_records = num_records + num_records * headroom_percent / 100;
+                assert!(
+                    capacity * OMAP_USABLE_CAPACITY_PERCENT / 100 >= expected_records,
+                    "capacity {} too small for {} records",
+                    capacity,
+                    expected_records
+                );
+            }
+        }
+    }
+}
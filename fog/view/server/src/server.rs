@@ -6,7 +6,8 @@
 
 use crate::{
     block_tracker::BlockTracker, config::MobileAcctViewConfig, counters, db_fetcher::DbFetcher,
-    fog_view_service::FogViewService,
+    fog_view_service::FogViewService, fog_view_store_service::FogViewStoreService,
+    sharding::SearchKeyRange,
 };
 use futures::executor::block_on;
 use mc_attest_net::RaClient;
@@ -73,6 +74,11 @@ where
         let db_poll_thread = DbPollThread::new(
             enclave.clone(),
             recovery_db.clone(),
+            config.search_key_range.unwrap_or_default(),
+            config
+                .view_shard_id
+                .clone()
+                .unwrap_or_else(|| format!("fog-view-{:016x}", rand::random::<u64>())),
             readiness_indicator.clone(),
             logger.clone(),
         );
@@ -94,15 +100,28 @@ where
                 Arc::new(AnonymousAuthenticator::default())
             };
 
+        let recovery_db = Arc::new(recovery_db);
+
         let fog_view_service = view_grpc::create_fog_view_api(FogViewService::new(
             enclave.clone(),
-            Arc::new(recovery_db),
+            recovery_db.clone(),
             db_poll_thread.get_shared_state(),
             client_authenticator,
             logger.clone(),
         ));
         log::debug!(logger, "Constructed View GRPC Service");
 
+        // The store service is for fog view routers, which are authenticated by
+        // peer attestation rather than by client auth tokens.
+        let fog_view_store_service =
+            view_grpc::create_fog_view_store_api(FogViewStoreService::new(
+                enclave.clone(),
+                recovery_db,
+                db_poll_thread.get_shared_state(),
+                logger.clone(),
+            ));
+        log::debug!(logger, "Constructed View Store GRPC Service");
+
         // Health check service
         let health_service =
             mc_util_grpc::HealthService::new(Some(readiness_indicator.into()), logger.clone())
@@ -116,6 +135,7 @@ where
         );
         let server_builder = grpcio::ServerBuilder::new(env)
             .register_service(fog_view_service)
+            .register_service(fog_view_store_service)
             .register_service(health_service)
             .bind_using_uri(&config.client_listen_uri, logger.clone());
 
@@ -215,6 +235,12 @@ where
     /// Recovery db.
     db: DB,
 
+    /// The range of search keys whose records are loaded into the enclave.
+    search_key_range: SearchKeyRange,
+
+    /// The ID with which progress is reported to the recovery db.
+    view_shard_id: String,
+
     /// Join handle used to wait for the thread to terminate.
     join_handle: Option<JoinHandle<()>>,
 
@@ -234,6 +260,10 @@ where
 /// How long to wait between polling db
 const DB_POLL_INTERNAL: Duration = Duration::from_millis(100);
 
+/// How often to report progress to the recovery db while it doesn't change,
+/// so that the shard keeps counting as active
+const PROGRESS_REPORT_INTERVAL: Duration = Duration::from_secs(30);
+
 impl<E, DB> DbPollThread<E, DB>
 where
     E: ViewEnclaveProxy,
@@ -248,6 +278,8 @@ where
     pub fn new(
         enclave: E,
         db: DB,
+        search_key_range: SearchKeyRange,
+        view_shard_id: String,
         readiness_indicator: ReadinessIndicator,
         logger: Logger,
     ) -> Self {
@@ -257,6 +289,8 @@ where
         Self {
             enclave,
             db,
+            search_key_range,
+            view_shard_id,
             join_handle: None,
             stop_requested,
             shared_state,
@@ -276,6 +310,8 @@ where
 
         let thread_enclave = self.enclave.clone();
         let thread_db = self.db.clone();
+        let thread_search_key_range = self.search_key_range;
+        let thread_view_shard_id = self.view_shard_id.clone();
         let thread_stop_requested = self.stop_requested.clone();
         let thread_shared_state = self.shared_state.clone();
         let thread_readiness_indicator = self.readiness_indicator.clone();
@@ -288,6 +324,8 @@ where
                     Self::thread_entrypoint(
                         thread_enclave,
                         thread_db,
+                        thread_search_key_range,
+                        thread_view_shard_id,
                         thread_stop_requested,
                         thread_shared_state,
                         thread_readiness_indicator,
@@ -311,6 +349,8 @@ where
     fn thread_entrypoint(
         enclave: E,
         db: DB,
+        search_key_range: SearchKeyRange,
+        view_shard_id: String,
         stop_requested: Arc<AtomicBool>,
         shared_state: Arc<Mutex<DbPollSharedState>>,
         readiness_indicator: ReadinessIndicator,
@@ -322,6 +362,8 @@ where
             stop_requested,
             enclave,
             db,
+            search_key_range,
+            view_shard_id,
             shared_state,
             readiness_indicator,
             logger.clone(),
@@ -367,6 +409,16 @@ where
     /// Recovery database.
     db: DB,
 
+    /// The range of search keys whose records are loaded into the enclave.
+    search_key_range: SearchKeyRange,
+
+    /// The ID with which progress is reported to the recovery db.
+    view_shard_id: String,
+
+    /// The highest processed block count we last reported to the recovery db,
+    /// and when.
+    last_progress_report: Option<(u64, Instant)>,
+
     /// Shared state.
     shared_state: Arc<Mutex<DbPollSharedState>>,
 
@@ -404,6 +456,8 @@ where
         stop_requested: Arc<AtomicBool>,
         enclave: E,
         db: DB,
+        search_key_range: SearchKeyRange,
+        view_shard_id: String,
         shared_state: Arc<Mutex<DbPollSharedState>>,
         readiness_indicator: ReadinessIndicator,
         logger: Logger,
@@ -412,6 +466,9 @@ where
             stop_requested,
             enclave,
             db: db.clone(),
+            search_key_range,
+            view_shard_id,
+            last_progress_report: None,
             shared_state,
             db_fetcher: DbFetcher::new(db, readiness_indicator, logger.clone()),
             enclave_block_tracker: BlockTracker::new(logger.clone()),
@@ -493,6 +550,8 @@ where
         counters::HIGHEST_PROCESSED_BLOCK_COUNT
             .set(shared_state.highest_processed_block_count as i64);
 
+        self.report_progress(highest_processed_block_count);
+
         // Try to update the timestamp associated to highest_processed_block_count
         if let Some(timestamp) =
             self.get_block_signature_timestamp_for_block_count(highest_processed_block_count)
@@ -544,8 +603,12 @@ where
         &mut self,
         ingress_key: CompressedRistrettoPublic,
         block_index: u64,
-        records: Vec<ETxOutRecord>,
+        mut records: Vec<ETxOutRecord>,
     ) {
+        // Only load the records of our part of a sharded view store. The block
+        // still counts as processed, since the other records are loaded by the
+        // other stores.
+        records.retain(|record| self.search_key_range.contains(&record.search_key));
         let num_records = records.len();
 
         let add_records_result = {
@@ -598,6 +661,38 @@ where
         }
     }
 
+    // Report the highest processed block count to the recovery db when it
+    // changes, and periodically otherwise, so that pruning does not remove
+    // blocks which this shard has not loaded yet.
+    // If it doesn't work, we try again on the next tick.
+    fn report_progress(&mut self, highest_processed_block_count: u64) {
+        if let Some((reported_count, reported_at)) = self.last_progress_report {
+            if reported_count == highest_processed_block_count
+                && reported_at.elapsed() < PROGRESS_REPORT_INTERVAL
+            {
+                return;
+            }
+        }
+
+        match self
+            .db
+            .report_view_shard_progress(&self.view_shard_id, highest_processed_block_count)
+        {
+            Ok(()) => {
+                self.last_progress_report = Some((highest_processed_block_count, Instant::now()));
+            }
+            Err(err) => {
+                log::warn!(
+                    self.logger,
+                    "Unable to report progress of view shard {} at block count {}: {}",
+                    self.view_shard_id,
+                    highest_processed_block_count,
+                    err
+                );
+            }
+        }
+    }
+
     // The client needs a timestamp for the highest processed block, because the
     // highest processed block lets them know up to when they have accurate
     // balance information, and they may want to tell the user e.g. this was
@@ -0,0 +1,137 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Partitioning of the view store between fog view store servers.
+//!
+//! Each store server only loads the ETxOutRecords whose search key falls in
+//! its range into its enclave. The router then asks every store about every
+//! search key of a query, and merges their answers.
+
+use displaydoc::Display;
+use serde::Serialize;
+use std::{fmt, str::FromStr};
+
+/// An inclusive range of search keys, compared by their first 8 bytes read as
+/// a big-endian integer. Parsed from and displayed as two hex numbers
+/// separated by a dash, e.g. `0-7fffffffffffffff`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub struct SearchKeyRange {
+    /// The lowest search key prefix in the range.
+    pub start: u64,
+
+    /// The highest search key prefix in the range.
+    pub end: u64,
+}
+
+impl SearchKeyRange {
+    /// Whether a search key is in the range. Search keys shorter than 8 bytes
+    /// are padded with zeroes.
+    pub fn contains(&self, search_key: &[u8]) -> bool {
+        let mut prefix = [0u8; 8];
+        let len = search_key.len().min(prefix.len());
+        prefix[..len].copy_from_slice(&search_key[..len]);
+        let prefix = u64::from_be_bytes(prefix);
+        self.start <= prefix && prefix <= self.end
+    }
+}
+
+impl Default for SearchKeyRange {
+    /// The range of all search keys.
+    fn default() -> Self {
+        Self {
+            start: 0,
+            end: u64::MAX,
+        }
+    }
+}
+
+impl fmt::Display for SearchKeyRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:x}-{:x}", self.start, self.end)
+    }
+}
+
+/// An error parsing a SearchKeyRange
+#[derive(Clone, Debug, Display, Eq, PartialEq)]
+pub enum SearchKeyRangeParseError {
+    /// Expected two hex numbers separated by a dash
+    MissingDash,
+    /// Invalid hex number: {0}
+    InvalidNumber(String),
+    /// The start of the range is after its end
+    Empty,
+}
+
+impl std::error::Error for SearchKeyRangeParseError {}
+
+impl FromStr for SearchKeyRange {
+    type Err = SearchKeyRangeParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let (start, end) = src
+            .split_once('-')
+            .ok_or(SearchKeyRangeParseError::MissingDash)?;
+        let parse = |num: &str| {
+            u64::from_str_radix(num.trim(), 16)
+                .map_err(|_| SearchKeyRangeParseError::InvalidNumber(num.to_string()))
+        };
+        let range = Self {
+            start: parse(start)?,
+            end: parse(end)?,
+        };
+        if range.start > range.end {
+            return Err(SearchKeyRangeParseError::Empty);
+        }
+        Ok(range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_display() {
+        let range = SearchKeyRange::from_str("0-7fffffffffffffff").unwrap();
+        assert_eq!(
+            range,
+            SearchKeyRange {
+                start: 0,
+                end: 0x7fff_ffff_ffff_ffff
+            }
+        );
+        assert_eq!(range.to_string(), "0-7fffffffffffffff");
+        assert_eq!(
+            SearchKeyRange::from_str(&SearchKeyRange::default().to_string()).unwrap(),
+            SearchKeyRange::default()
+        );
+
+        assert_eq!(
+            SearchKeyRange::from_str("1234"),
+            Err(SearchKeyRangeParseError::MissingDash)
+        );
+        assert_eq!(
+            SearchKeyRange::from_str("0-xyz"),
+            Err(SearchKeyRangeParseError::InvalidNumber("xyz".to_string()))
+        );
+        assert_eq!(
+            SearchKeyRange::from_str("10-f"),
+            Err(SearchKeyRangeParseError::Empty)
+        );
+    }
+
+    #[test]
+    fn contains() {
+        let lower = SearchKeyRange::from_str("0-7fffffffffffffff").unwrap();
+        let upper = SearchKeyRange::from_str("8000000000000000-ffffffffffffffff").unwrap();
+
+        for search_key in [[0u8; 16], [0x7f; 16], [0x80; 16], [0xff; 16]] {
+            assert_ne!(lower.contains(&search_key), upper.contains(&search_key));
+            assert!(SearchKeyRange::default().contains(&search_key));
+        }
+        assert!(lower.contains(&[0x7f; 16]));
+        assert!(upper.contains(&[0x80; 16]));
+
+        assert!(lower.contains(&[]));
+        assert!(!upper.contains(&[0x7f]));
+    }
+}
@@ -0,0 +1,107 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! A fog view router's connection to one of the fog view stores.
+
+use crate::error::RouterError;
+use grpcio::{ChannelBuilder, ClientUnaryReceiver, Environment};
+use mc_attest_api::attest;
+use mc_attest_enclave_api::{EnclaveMessage, PeerSession};
+use mc_common::{
+    logger::{log, Logger},
+    ResponderId,
+};
+use mc_fog_api::view_grpc::FogViewStoreApiClient;
+use mc_fog_uri::FogViewUri;
+use mc_fog_view_enclave::ViewEnclaveProxy;
+use mc_util_grpc::ConnectionUriGrpcioChannel;
+use mc_util_uri::ConnectionUri;
+use std::sync::Arc;
+
+/// A connection to a fog view store, attested lazily by the router enclave.
+pub struct ViewStoreConnection<E: ViewEnclaveProxy> {
+    /// The URI of the store.
+    uri: FogViewUri,
+
+    /// The responder id the store enclave attests with.
+    responder_id: ResponderId,
+
+    /// The grpc client for the store.
+    grpc_client: FogViewStoreApiClient,
+
+    /// The router enclave.
+    enclave: E,
+
+    /// The peer session with the store enclave, if attested.
+    peer_session: Option<PeerSession>,
+
+    /// Logger.
+    logger: Logger,
+}
+
+impl<E: ViewEnclaveProxy> ViewStoreConnection<E> {
+    /// Create a new connection to a store. This does not attest yet.
+    pub fn new(
+        uri: FogViewUri,
+        enclave: E,
+        env: Arc<Environment>,
+        logger: Logger,
+    ) -> Result<Self, RouterError> {
+        let responder_id = uri.responder_id()?;
+        let ch = ChannelBuilder::default_channel_builder(env).connect_to_uri(&uri, &logger);
+        let grpc_client = FogViewStoreApiClient::new(ch);
+
+        Ok(Self {
+            uri,
+            responder_id,
+            grpc_client,
+            enclave,
+            peer_session: None,
+            logger,
+        })
+    }
+
+    /// The URI of the store.
+    pub fn uri(&self) -> &FogViewUri {
+        &self.uri
+    }
+
+    /// Get the peer session with the store enclave, attesting first if needed.
+    pub fn attest(&mut self) -> Result<PeerSession, RouterError> {
+        if let Some(peer_session) = self.peer_session.as_ref() {
+            return Ok(peer_session.clone());
+        }
+
+        let auth_request = self.enclave.peer_init(&self.responder_id)?;
+        let auth_response = self.grpc_client.auth(&auth_request.into())?;
+        let (peer_session, _verification_report) = self
+            .enclave
+            .peer_connect(&self.responder_id, auth_response.into())?;
+
+        log::debug!(self.logger, "Attested fog view store {}", self.uri);
+        self.peer_session = Some(peer_session.clone());
+        Ok(peer_session)
+    }
+
+    /// Forget the peer session with the store enclave, so that the next query
+    /// attests again.
+    pub fn deattest(&mut self) {
+        if let Some(peer_session) = self.peer_session.take() {
+            if let Err(err) = self.enclave.peer_close(&peer_session) {
+                log::warn!(
+                    self.logger,
+                    "Failed closing peer session with fog view store {}: {}",
+                    self.uri,
+                    err
+                );
+            }
+        }
+    }
+
+    /// Send a shard query to the store, without waiting for the response.
+    pub fn query_async(
+        &self,
+        shard_query: EnclaveMessage<PeerSession>,
+    ) -> Result<ClientUnaryReceiver<attest::Message>, RouterError> {
+        Ok(self.grpc_client.query_async(&shard_query.into())?)
+    }
+}
@@ -0,0 +1,234 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+// This integration-level test shards the view store across two view servers
+// by search key range, and checks that a client talking to a fog view router
+// sees the records of both stores.
+
+use mc_attest_net::Client as AttestClient;
+use mc_attest_verifier::{MrSignerVerifier, Verifier, DEBUG_ENCLAVE};
+use mc_common::{
+    logger::{log, test_with_logger, Logger},
+    time::SystemTimeProvider,
+    ResponderId,
+};
+use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPublic};
+use mc_fog_recovery_db_iface::RecoveryDb;
+use mc_fog_sql_recovery_db::{test_utils::SqlRecoveryDbTestContext, SqlRecoveryDb};
+use mc_fog_test_infra::{
+    db_tests::{random_block, random_kex_rng_pubkey},
+    get_enclave_path,
+};
+use mc_fog_types::view::TxOutSearchResultCode;
+use mc_fog_uri::{ConnectionUri, FogViewUri};
+use mc_fog_view_connection::FogViewGrpcClient;
+use mc_fog_view_enclave::SgxViewEnclave;
+use mc_fog_view_protocol::FogViewConnection;
+use mc_fog_view_server::{
+    config::{FogViewRouterConfig, MobileAcctViewConfig as ViewConfig},
+    router_server::{FogViewRouterServer, ROUTER_OMAP_CAPACITY},
+    server::ViewServer,
+    sharding::SearchKeyRange,
+};
+use mc_util_from_random::FromRandom;
+use mc_util_grpc::GrpcRetryConfig;
+use rand::{rngs::StdRng, SeedableRng};
+use std::{str::FromStr, sync::Arc, thread::sleep, time::Duration};
+
+const GRPC_RETRY_CONFIG: GrpcRetryConfig = GrpcRetryConfig {
+    grpc_retry_count: 3,
+    grpc_retry_millis: 20,
+};
+
+fn pick_uri() -> FogViewUri {
+    let port = portpicker::pick_unused_port().expect("pick_unused_port");
+    FogViewUri::from_str(&format!("insecure-fog-view://127.0.0.1:{}", port)).unwrap()
+}
+
+fn start_store(
+    db: SqlRecoveryDb,
+    search_key_range: SearchKeyRange,
+    logger: Logger,
+) -> (
+    ViewServer<SgxViewEnclave, AttestClient, SqlRecoveryDb>,
+    FogViewUri,
+) {
+    let uri = pick_uri();
+    let config = ViewConfig {
+        client_responder_id: ResponderId::from_str(&uri.addr()).unwrap(),
+        client_listen_uri: uri.clone(),
+        client_auth_token_secret: None,
+        omap_capacity: Some(512),
+        omap_capacity_headroom_percent: Default::default(),
+        omap_stash_size: mc_fog_view_enclave::DEFAULT_STASH_SIZE,
+        search_key_range: Some(search_key_range),
+        view_shard_id: None,
+        ias_spid: Default::default(),
+        ias_api_key: Default::default(),
+        admin_listen_uri: Default::default(),
+        client_auth_token_max_lifetime: Default::default(),
+        postgres_config: Default::default(),
+    };
+
+    let enclave = SgxViewEnclave::new(
+        get_enclave_path(mc_fog_view_enclave::ENCLAVE_FILE),
+        config.client_responder_id.clone(),
+        512,
+        config.omap_stash_size,
+        logger.clone(),
+    );
+
+    let ra_client = AttestClient::new(&config.ias_api_key).expect("Could not create IAS client");
+
+    let mut server = ViewServer::new(
+        config,
+        enclave,
+        db,
+        ra_client,
+        SystemTimeProvider::default(),
+        logger,
+    );
+    server.start();
+    (server, uri)
+}
+
+fn start_router(
+    shard_uris: Vec<FogViewUri>,
+    logger: Logger,
+) -> (
+    FogViewRouterServer<SgxViewEnclave, AttestClient>,
+    FogViewGrpcClient,
+) {
+    let uri = pick_uri();
+    let config = FogViewRouterConfig {
+        client_responder_id: ResponderId::from_str(&uri.addr()).unwrap(),
+        client_listen_uri: uri.clone(),
+        shard_uris,
+        client_auth_token_secret: None,
+        ias_spid: Default::default(),
+        ias_api_key: Default::default(),
+        admin_listen_uri: Default::default(),
+        client_auth_token_max_lifetime: Default::default(),
+    };
+
+    let enclave = SgxViewEnclave::new(
+        get_enclave_path(mc_fog_view_enclave::ENCLAVE_FILE),
+        config.client_responder_id.clone(),
+        ROUTER_OMAP_CAPACITY,
+        mc_fog_view_enclave::DEFAULT_STASH_SIZE,
+        logger.clone(),
+    );
+
+    let ra_client = AttestClient::new(&config.ias_api_key).expect("Could not create IAS client");
+
+    let mut server = FogViewRouterServer::new(
+        config,
+        enclave,
+        ra_client,
+        SystemTimeProvider::default(),
+        logger.clone(),
+    );
+    server.start();
+
+    let client = {
+        let grpcio_env = Arc::new(grpcio::EnvBuilder::new().build());
+        let mut mr_signer_verifier =
+            MrSignerVerifier::from(mc_fog_view_enclave_measurement::sigstruct());
+        mr_signer_verifier.allow_hardening_advisory("INTEL-SA-00334");
+
+        let mut verifier = Verifier::default();
+        verifier.mr_signer(mr_signer_verifier).debug(DEBUG_ENCLAVE);
+
+        FogViewGrpcClient::new(uri, GRPC_RETRY_CONFIG, verifier, grpcio_env, logger)
+    };
+
+    (server, client)
+}
+
+#[test_with_logger]
+fn test_router_merges_store_results(logger: Logger) {
+    let mut rng: StdRng = SeedableRng::from_seed([123u8; 32]);
+    let db_test_context = SqlRecoveryDbTestContext::new(logger.clone());
+    let db = db_test_context.get_db_instance();
+
+    let (lower_store, lower_uri) = start_store(
+        db_test_context.get_db_instance(),
+        SearchKeyRange::from_str("0-7fffffffffffffff").unwrap(),
+        logger.clone(),
+    );
+    let (upper_store, upper_uri) = start_store(
+        db_test_context.get_db_instance(),
+        SearchKeyRange::from_str("8000000000000000-ffffffffffffffff").unwrap(),
+        logger.clone(),
+    );
+    let (_router, mut view_client) = start_router(vec![lower_uri, upper_uri], logger.clone());
+
+    let ingress_key = CompressedRistrettoPublic::from(RistrettoPublic::from_random(&mut rng));
+    db.new_ingress_key(&ingress_key, 0).unwrap();
+    let invoc_id = db
+        .new_ingest_invocation(None, &ingress_key, &random_kex_rng_pubkey(&mut rng), 0)
+        .unwrap();
+
+    let mut expected_records = Vec::new();
+    for i in 0..5 {
+        let (block, records) = random_block(&mut rng, i, 5); // 5 outputs per block
+        db.add_block_data(&invoc_id, &block, 0, &records).unwrap();
+        expected_records.extend(records);
+    }
+
+    let mut allowed_tries = 60usize;
+    loop {
+        let lower_num_blocks = lower_store.highest_processed_block_count();
+        let upper_num_blocks = upper_store.highest_processed_block_count();
+        if lower_num_blocks >= 5 && upper_num_blocks >= 5 {
+            break;
+        }
+        log::info!(
+            logger,
+            "Waiting for stores to catch up to db... {}, {} < 5",
+            lower_num_blocks,
+            upper_num_blocks,
+        );
+        if allowed_tries == 0 {
+            panic!("Stores did not catch up to database!");
+        }
+        allowed_tries -= 1;
+        sleep(Duration::from_millis(1000));
+    }
+
+    let mut search_keys: Vec<_> = expected_records
+        .iter()
+        .map(|record| record.search_key.clone())
+        .collect();
+    // A search key which no store has
+    search_keys.push(vec![1; 16]);
+
+    let result = view_client.request(0, 0, search_keys.clone()).unwrap();
+    assert_eq!(result.highest_processed_block_count, 5);
+    assert_eq!(result.tx_out_search_results.len(), search_keys.len());
+
+    for (record, search_result) in expected_records
+        .iter()
+        .zip(result.tx_out_search_results.iter())
+    {
+        assert_eq!(search_result.search_key, record.search_key);
+        assert_eq!(
+            search_result.result_code,
+            TxOutSearchResultCode::Found as u32
+        );
+        assert_eq!(search_result.ciphertext, record.payload);
+    }
+
+    let not_found = result.tx_out_search_results.last().unwrap();
+    assert_eq!(not_found.search_key, vec![1; 16]);
+    assert_eq!(
+        not_found.result_code,
+        TxOutSearchResultCode::NotFound as u32
+    );
+
+    // A second query reuses the attested sessions with the stores
+    let result = view_client.request(0, 0, search_keys).unwrap();
+    assert_eq!(
+        result.tx_out_search_results[0].result_code,
+        TxOutSearchResultCode::Found as u32
+    );
+}
@@ -63,7 +63,11 @@ fn get_test_environment(
             client_responder_id: ResponderId::from_str(&uri.addr()).unwrap(),
             client_listen_uri: uri.clone(),
             client_auth_token_secret: None,
-            omap_capacity: view_omap_capacity,
+            omap_capacity: Some(view_omap_capacity),
+            omap_capacity_headroom_percent: Default::default(),
+            omap_stash_size: mc_fog_view_enclave::DEFAULT_STASH_SIZE,
+            search_key_range: None,
+            view_shard_id: None,
             ias_spid: Default::default(),
             ias_api_key: Default::default(),
             admin_listen_uri: Default::default(),
@@ -74,7 +78,8 @@ fn get_test_environment(
         let enclave = SgxViewEnclave::new(
             get_enclave_path(mc_fog_view_enclave::ENCLAVE_FILE),
             config.client_responder_id.clone(),
-            config.omap_capacity,
+            view_omap_capacity,
+            config.omap_stash_size,
             logger.clone(),
         );
 
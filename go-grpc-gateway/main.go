@@ -101,6 +101,11 @@ func run() error {
 		return err
 	}
 
+	err = gw.RegisterFogUntrustedKeyImageApiHandlerFromEndpoint(ctx, mux, *grpcServerEndpoint, opts)
+	if err != nil {
+		return err
+	}
+
 	err = gw.RegisterFogMerkleProofAPIHandlerFromEndpoint(ctx, mux, *grpcServerEndpoint, opts)
 	if err != nil {
 		return err
@@ -87,6 +87,9 @@ pub enum Error {
 
     /// Block metadata is required at this block version
     BlockMetadataRequired,
+
+    /// Not enough TxOuts to sample from: requested {0}, available {1}
+    NotEnoughTxOuts(u64, u64),
 }
 
 impl From<lmdb::Error> for Error {
@@ -12,6 +12,7 @@ mod ledger_trait;
 mod metrics;
 mod mint_config_store;
 mod mint_tx_store;
+mod ring_sampler;
 
 pub mod ledger_db;
 #[cfg(any(test, feature = "test_utils"))]
@@ -25,6 +26,10 @@ pub use crate::{
     metrics::LedgerMetrics,
     mint_config_store::{ActiveMintConfig, ActiveMintConfigs, MintConfigStore},
     mint_tx_store::MintTxStore,
+    ring_sampler::{
+        sample_rings, ExcludeOwnOutputsRingSampler, RecencyWeightedRingSampler, RingSampler,
+        UniformRingSampler,
+    },
     tx_out_store::TxOutStore,
 };
 pub use mc_util_lmdb::{MetadataStore, MetadataStoreError, MetadataStoreSettings};
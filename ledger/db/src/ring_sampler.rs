@@ -0,0 +1,289 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Selection of the decoy TxOuts ("mixins") which go into the rings of a
+//! transaction.
+//!
+//! A `RingSampler` chooses the indices of the TxOuts to use, and
+//! `sample_rings` gets those TxOuts and their membership proofs from a
+//! `Ledger`, so that every client picks rings the same way.
+
+use crate::{Error, Ledger};
+use mc_common::HashSet;
+use mc_transaction_core::tx::{TxOut, TxOutMembershipProof};
+use rand::{Rng, RngCore};
+
+/// A strategy for choosing decoy TxOuts out of the ledger.
+pub trait RingSampler {
+    /// Choose `num_requested` distinct indices of TxOuts, out of a ledger which
+    /// has `num_txos` TxOuts, none of which is in `excluded_tx_out_indices`.
+    fn sample_indices(
+        &self,
+        num_txos: u64,
+        num_requested: usize,
+        excluded_tx_out_indices: &HashSet<u64>,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<u64>, Error>;
+}
+
+/// Chooses decoys uniformly out of all TxOuts of the ledger.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UniformRingSampler;
+
+impl RingSampler for UniformRingSampler {
+    fn sample_indices(
+        &self,
+        num_txos: u64,
+        num_requested: usize,
+        excluded_tx_out_indices: &HashSet<u64>,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<u64>, Error> {
+        sample_distinct(num_txos, num_requested, excluded_tx_out_indices, || {
+            rng.gen_range(0..num_txos)
+        })
+    }
+}
+
+/// Chooses decoys out of the most recent TxOuts of the ledger more often than
+/// out of the older ones.
+///
+/// Real inputs tend to be recent TxOuts, so decoys chosen uniformly out of the
+/// whole ledger are easier to tell apart from them. Each decoy is chosen out
+/// of the last `recent_window` TxOuts with probability `recent_probability`,
+/// and uniformly out of all TxOuts otherwise.
+#[derive(Clone, Copy, Debug)]
+pub struct RecencyWeightedRingSampler {
+    recent_window: u64,
+    recent_probability: f64,
+}
+
+impl RecencyWeightedRingSampler {
+    /// Create a sampler which chooses decoys out of the last `recent_window`
+    /// TxOuts with probability `recent_probability`.
+    ///
+    /// # Panics
+    /// If `recent_probability` is not at least 0 and less than 1. Some decoys
+    /// must come from the whole ledger, or there may not be enough recent
+    /// TxOuts to choose from.
+    pub fn new(recent_window: u64, recent_probability: f64) -> Self {
+        assert!(
+            (0.0..1.0).contains(&recent_probability),
+            "recent_probability must be in [0, 1)"
+        );
+        Self {
+            recent_window,
+            recent_probability,
+        }
+    }
+}
+
+impl RingSampler for RecencyWeightedRingSampler {
+    fn sample_indices(
+        &self,
+        num_txos: u64,
+        num_requested: usize,
+        excluded_tx_out_indices: &HashSet<u64>,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<u64>, Error> {
+        let recent_start = num_txos.saturating_sub(self.recent_window);
+        sample_distinct(num_txos, num_requested, excluded_tx_out_indices, || {
+            if recent_start < num_txos && rng.gen_bool(self.recent_probability) {
+                rng.gen_range(recent_start..num_txos)
+            } else {
+                rng.gen_range(0..num_txos)
+            }
+        })
+    }
+}
+
+/// Chooses decoys like another sampler, but never any of our own TxOuts.
+///
+/// Using our own TxOuts as decoys links our transactions together, so a
+/// wallet should exclude all of the TxOuts it owns, not only the ones it is
+/// spending.
+#[derive(Clone, Debug)]
+pub struct ExcludeOwnOutputsRingSampler<S: RingSampler> {
+    inner: S,
+    own_tx_out_indices: HashSet<u64>,
+}
+
+impl<S: RingSampler> ExcludeOwnOutputsRingSampler<S> {
+    /// Create a sampler which chooses decoys like `inner`, but never any of
+    /// the TxOuts at `own_tx_out_indices`.
+    pub fn new(inner: S, own_tx_out_indices: impl IntoIterator<Item = u64>) -> Self {
+        Self {
+            inner,
+            own_tx_out_indices: own_tx_out_indices.into_iter().collect(),
+        }
+    }
+}
+
+impl<S: RingSampler> RingSampler for ExcludeOwnOutputsRingSampler<S> {
+    fn sample_indices(
+        &self,
+        num_txos: u64,
+        num_requested: usize,
+        excluded_tx_out_indices: &HashSet<u64>,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<u64>, Error> {
+        let excluded = excluded_tx_out_indices
+            .union(&self.own_tx_out_indices)
+            .cloned()
+            .collect();
+        self.inner
+            .sample_indices(num_txos, num_requested, &excluded, rng)
+    }
+}
+
+/// Get `num_rings` rings of `ring_size` decoys each, with their membership
+/// proofs, out of the ledger.
+///
+/// All of the decoys are distinct, and none of them is at one of
+/// `excluded_tx_out_indices`, which should usually contain the indices of the
+/// TxOuts being spent.
+pub fn sample_rings<L: Ledger + ?Sized, S: RingSampler + ?Sized>(
+    ledger: &L,
+    sampler: &S,
+    ring_size: usize,
+    num_rings: usize,
+    excluded_tx_out_indices: &[u64],
+    rng: &mut dyn RngCore,
+) -> Result<Vec<Vec<(TxOut, TxOutMembershipProof)>>, Error> {
+    let num_txos = ledger.num_txos()?;
+    let excluded = excluded_tx_out_indices.iter().cloned().collect();
+    let indices = sampler.sample_indices(num_txos, ring_size * num_rings, &excluded, rng)?;
+
+    let tx_outs = indices
+        .iter()
+        .map(|index| ledger.get_tx_out_by_index(*index))
+        .collect::<Result<Vec<_>, _>>()?;
+    let membership_proofs = ledger.get_tx_out_proof_of_memberships(&indices)?;
+
+    let tx_outs_with_proofs: Vec<_> = tx_outs.into_iter().zip(membership_proofs).collect();
+    Ok(tx_outs_with_proofs
+        .chunks(ring_size)
+        .map(|chunk| chunk.to_vec())
+        .collect())
+}
+
+// Draw indices until there are `num_requested` distinct ones which are not
+// excluded
+fn sample_distinct(
+    num_txos: u64,
+    num_requested: usize,
+    excluded_tx_out_indices: &HashSet<u64>,
+    mut draw: impl FnMut() -> u64,
+) -> Result<Vec<u64>, Error> {
+    let num_excluded = excluded_tx_out_indices
+        .iter()
+        .filter(|index| **index < num_txos)
+        .count() as u64;
+    if num_requested as u64 > num_txos - num_excluded {
+        return Err(Error::NotEnoughTxOuts(
+            num_requested as u64,
+            num_txos - num_excluded,
+        ));
+    }
+
+    let mut samples = HashSet::default();
+    let mut indices = Vec::with_capacity(num_requested);
+    while indices.len() < num_requested {
+        let index = draw();
+        if !excluded_tx_out_indices.contains(&index) && samples.insert(index) {
+            indices.push(index);
+        }
+    }
+    Ok(indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{create_ledger, initialize_ledger};
+    use mc_account_keys::AccountKey;
+    use mc_transaction_core::BlockVersion;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_samplers_respect_exclusions() {
+        let mut rng: StdRng = SeedableRng::from_seed([7u8; 32]);
+        let excluded: HashSet<u64> = (0..50).collect();
+
+        let samplers: Vec<Box<dyn RingSampler>> = vec![
+            Box::new(UniformRingSampler),
+            Box::new(RecencyWeightedRingSampler::new(20, 0.5)),
+            Box::new(ExcludeOwnOutputsRingSampler::new(
+                UniformRingSampler,
+                50..60,
+            )),
+        ];
+        for sampler in samplers {
+            let indices = sampler
+                .sample_indices(100, 30, &excluded, &mut rng)
+                .unwrap();
+            assert_eq!(indices.len(), 30);
+            assert_eq!(indices.iter().collect::<HashSet<_>>().len(), 30);
+            assert!(indices.iter().all(|index| (50..100).contains(index)));
+        }
+
+        let sampler = ExcludeOwnOutputsRingSampler::new(UniformRingSampler, 50..60);
+        let indices = sampler
+            .sample_indices(100, 40, &excluded, &mut rng)
+            .unwrap();
+        assert!(indices.iter().all(|index| (60..100).contains(index)));
+        assert_eq!(
+            sampler.sample_indices(100, 41, &excluded, &mut rng),
+            Err(Error::NotEnoughTxOuts(41, 40))
+        );
+    }
+
+    #[test]
+    fn test_recency_weighted_sampler_prefers_recent_tx_outs() {
+        let mut rng: StdRng = SeedableRng::from_seed([7u8; 32]);
+        let sampler = RecencyWeightedRingSampler::new(100, 0.9);
+
+        let indices = sampler
+            .sample_indices(100_000, 1000, &HashSet::default(), &mut rng)
+            .unwrap();
+        let num_recent = indices.iter().filter(|index| **index >= 99_900).count();
+        // Only 100 recent TxOuts exist, so they all get picked
+        assert_eq!(num_recent, 100);
+
+        let sampler = RecencyWeightedRingSampler::new(10_000, 0.9);
+        let indices = sampler
+            .sample_indices(100_000, 1000, &HashSet::default(), &mut rng)
+            .unwrap();
+        let num_recent = indices.iter().filter(|index| **index >= 90_000).count();
+        assert!(num_recent > 800);
+    }
+
+    #[test]
+    fn test_sample_rings_from_ledger() {
+        let mut rng: StdRng = SeedableRng::from_seed([7u8; 32]);
+        let mut ledger = create_ledger();
+        let account_key = AccountKey::random(&mut rng);
+        initialize_ledger(BlockVersion::MAX, &mut ledger, 20, &account_key, &mut rng);
+        let num_txos = ledger.num_txos().unwrap();
+
+        let rings = sample_rings(&ledger, &UniformRingSampler, 3, 2, &[0, 1], &mut rng).unwrap();
+        assert_eq!(rings.len(), 2);
+        for ring in rings {
+            assert_eq!(ring.len(), 3);
+            for (tx_out, proof) in ring {
+                assert!(proof.index >= 2 && proof.index < num_txos);
+                assert_eq!(ledger.get_tx_out_by_index(proof.index).unwrap(), tx_out);
+            }
+        }
+
+        assert_eq!(
+            sample_rings(
+                &ledger,
+                &UniformRingSampler,
+                num_txos as usize,
+                1,
+                &[0],
+                &mut rng
+            ),
+            Err(Error::NotEnoughTxOuts(num_txos, num_txos - 1))
+        );
+    }
+}
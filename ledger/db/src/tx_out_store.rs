@@ -774,6 +774,81 @@ mod membership_proof_tests {
         )
         .unwrap());
     }
+
+    #[test]
+    // A proof "rederived" at an earlier highest index should equal the
+    // proof-of-membership for that TxOut when the tree had that many members.
+    fn test_derive_proof_at_highest_index() {
+        let (tx_out_store, env) = init_tx_out_store();
+        let mut tx_outs = get_tx_outs(100);
+        let more_tx_outs = tx_outs.split_off(21);
+
+        {
+            // Populate the tx_out_store with the first set of TxOuts.
+            let mut rw_transaction = env.begin_rw_txn().unwrap();
+            for tx_out in &tx_outs {
+                tx_out_store.push(tx_out, &mut rw_transaction).unwrap();
+            }
+            rw_transaction.commit().unwrap();
+        }
+
+        // Proofs-of-membership for all TxOuts, when TxOut 20 is the most
+        // recently-added member.
+        let (known_root_hash, proofs_at_21) = {
+            let db_transaction = env.begin_ro_txn().unwrap();
+            let known_root_hash = tx_out_store.get_root_merkle_hash(&db_transaction).unwrap();
+            let proofs: Vec<TxOutMembershipProof> = (0..21)
+                .map(|index| {
+                    tx_out_store
+                        .get_merkle_proof_of_membership(index, &db_transaction)
+                        .unwrap()
+                })
+                .collect();
+            (known_root_hash, proofs)
+        };
+
+        {
+            // Populate the tx_out_store with the rest of the TxOuts.
+            let mut rw_transaction = env.begin_rw_txn().unwrap();
+            for tx_out in &more_tx_outs {
+                tx_out_store.push(tx_out, &mut rw_transaction).unwrap();
+            }
+            rw_transaction.commit().unwrap();
+        }
+
+        let db_transaction = env.begin_ro_txn().unwrap();
+        let proof_of_20_at_100 = tx_out_store
+            .get_merkle_proof_of_membership(20, &db_transaction)
+            .unwrap();
+
+        for (index, proof_at_21) in proofs_at_21.iter().enumerate() {
+            let proof_at_100 = tx_out_store
+                .get_merkle_proof_of_membership(index as u64, &db_transaction)
+                .unwrap();
+            let rederived_proof =
+                derive_proof_at_highest_index(&proof_at_100, &proof_of_20_at_100).unwrap();
+
+            // The rederived proof must equal the original proof.
+            assert_eq!(proof_at_21, &rederived_proof);
+
+            // The rederived proof must be a valid proof.
+            assert!(is_membership_proof_valid(
+                tx_outs.get(index).unwrap(),
+                &rederived_proof,
+                &known_root_hash
+            )
+            .unwrap());
+        }
+
+        // TxOuts added after the highest index have no proof at that index.
+        let proof_of_21_at_100 = tx_out_store
+            .get_merkle_proof_of_membership(21, &db_transaction)
+            .unwrap();
+        assert_eq!(
+            derive_proof_at_highest_index(&proof_of_21_at_100, &proof_of_20_at_100),
+            Err(MembershipProofError::HighestIndexMismatch)
+        );
+    }
 }
 
 #[cfg(test)]
@@ -843,6 +918,7 @@ pub mod tx_out_store_tests {
                 public_key: public_key.into(),
                 e_fog_hint: EncryptedFogHint::new(&[7u8; ENCRYPTED_FOG_HINT_LEN]),
                 e_memo: Some(MemoPayload::default().encrypt(&shared_secret)),
+                additional_e_fog_hints: Default::default(),
             };
             tx_outs.push(tx_out);
         }
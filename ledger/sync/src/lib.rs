@@ -16,6 +16,8 @@ pub use crate::{
     },
     metadata_provider::{BlockMetadataProvider, PassThroughMetadataProvider},
     network_state::{NetworkState, PollingNetworkState, SCPNetworkState},
-    reqwest_transactions_fetcher::{ReqwestTransactionsFetcher, ReqwestTransactionsFetcherError},
+    reqwest_transactions_fetcher::{
+        RequestAuthenticator, ReqwestTransactionsFetcher, ReqwestTransactionsFetcherError,
+    },
     transactions_fetcher_trait::{TransactionFetcherError, TransactionsFetcher},
 };
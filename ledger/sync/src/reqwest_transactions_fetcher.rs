@@ -14,7 +14,10 @@ use mc_common::{
     ResponderId,
 };
 use protobuf::Message;
-use reqwest::Error as ReqwestError;
+use reqwest::{
+    blocking::{Client, RequestBuilder},
+    Error as ReqwestError, StatusCode,
+};
 use std::{
     fs,
     sync::{
@@ -51,6 +54,12 @@ pub enum ReqwestTransactionsFetcherError {
 
     /// No URLs configured
     NoUrlsConfigured,
+
+    /// Failed authenticating the request to {0}: {1}
+    Authentication(String, String),
+
+    /// Nothing found at {0}
+    NotFound(String),
 }
 
 impl From<ReqwestError> for ReqwestTransactionsFetcherError {
@@ -64,13 +73,23 @@ impl From<ReqwestError> for ReqwestTransactionsFetcherError {
 
 impl TransactionFetcherError for ReqwestTransactionsFetcherError {}
 
+/// Authenticates requests to archives that are not publicly readable.
+pub trait RequestAuthenticator: Send + Sync {
+    /// Build an authenticated GET request for `url`. Implementations may
+    /// request a different URL, e.g. a presigned one.
+    fn authenticate(&self, client: &Client, url: &Url) -> Result<RequestBuilder, String>;
+}
+
 #[derive(Clone)]
 pub struct ReqwestTransactionsFetcher {
     /// List of URLs to try and fetch objects from.
     pub source_urls: Vec<Url>,
 
     /// Client used for HTTP(s) requests.
-    client: reqwest::blocking::Client,
+    client: Client,
+
+    /// Authenticates requests, if the archive requires it.
+    authenticator: Option<Arc<dyn RequestAuthenticator>>,
 
     /// Logger.
     logger: Logger,
@@ -99,12 +118,12 @@ impl ReqwestTransactionsFetcher {
         source_urls: Vec<String>,
         logger: Logger,
     ) -> Result<Self, ReqwestTransactionsFetcherError> {
-        Self::new_with_client(source_urls, reqwest::blocking::Client::new(), logger)
+        Self::new_with_client(source_urls, Client::new(), logger)
     }
 
     pub fn new_with_client(
         source_urls: Vec<String>,
-        client: reqwest::blocking::Client,
+        client: Client,
         logger: Logger,
     ) -> Result<Self, ReqwestTransactionsFetcherError> {
         let source_urls: Result<Vec<Url>, ReqwestTransactionsFetcherError> = source_urls
@@ -126,6 +145,7 @@ impl ReqwestTransactionsFetcher {
         Ok(Self {
             source_urls: source_urls?,
             client,
+            authenticator: None,
             logger,
             source_index_counter: Arc::new(AtomicU64::new(0)),
             blocks_cache: Arc::new(Mutex::new(LruCache::new(MAX_PREFETCHED_BLOCKS))),
@@ -139,6 +159,12 @@ impl ReqwestTransactionsFetcher {
         self.merged_blocks_bucket_sizes = bucket_sizes.to_vec();
     }
 
+    /// Authenticate http(s) requests with the given authenticator. `file://`
+    /// urls are not affected.
+    pub fn set_authenticator(&mut self, authenticator: Option<Arc<dyn RequestAuthenticator>>) {
+        self.authenticator = authenticator;
+    }
+
     pub fn block_from_url(&self, url: &Url) -> Result<BlockData, ReqwestTransactionsFetcherError> {
         let archive_block: blockchain::ArchiveBlock = self.fetch_protobuf_object(url)?;
 
@@ -175,12 +201,30 @@ impl ReqwestTransactionsFetcher {
         let bytes: Vec<u8> = if url.scheme() == "file" {
             let path = &url[url::Position::BeforeHost..url::Position::AfterPath];
             fs::read(path)
-                .map_err(|err| ReqwestTransactionsFetcherError::IO(path.to_string(), err))?
+                .map_err(|err| match err.kind() {
+                    std::io::ErrorKind::NotFound => {
+                        ReqwestTransactionsFetcherError::NotFound(path.to_string())
+                    }
+                    _ => ReqwestTransactionsFetcherError::IO(path.to_string(), err),
+                })?
                 .to_vec()
         } else {
-            let mut response = self.client.get(url.as_str()).send().map_err(|err| {
+            let request = match &self.authenticator {
+                Some(authenticator) => {
+                    authenticator
+                        .authenticate(&self.client, url)
+                        .map_err(|err| {
+                            ReqwestTransactionsFetcherError::Authentication(url.to_string(), err)
+                        })?
+                }
+                None => self.client.get(url.as_str()),
+            };
+            let mut response = request.send().map_err(|err| {
                 ReqwestTransactionsFetcherError::ReqwestError(url.to_string(), err)
             })?;
+            if response.status() == StatusCode::NOT_FOUND {
+                return Err(ReqwestTransactionsFetcherError::NotFound(url.to_string()));
+            }
 
             let mut bytes = Vec::new();
             response.copy_to(&mut bytes)?;
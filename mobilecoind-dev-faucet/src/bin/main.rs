@@ -7,17 +7,43 @@
 
 use clap::Parser;
 use mc_common::logger::{create_app_logger, log, o};
-use mc_mobilecoind_dev_faucet::{data_types::*, Config, State};
-use rocket::{get, post, routes, serde::json::Json, Shutdown};
+use mc_mobilecoind_dev_faucet::{data_types::*, decode_public_address, Config, State};
+use rocket::{get, http::Status, post, routes, serde::json::Json, Shutdown};
+use std::net::IpAddr;
 
 /// Request payment from the faucet, and map the rust result onto json for
-/// rocket appropriately
+/// rocket appropriately. Requests for invalid addresses get a 400 status,
+/// requests rejected by the rate limits get a 429 status, and requests rejected
+/// by the allowlists get a 403 status.
 #[post("/", data = "<req>")]
 async fn post(
     state: &rocket::State<State>,
     req: Json<JsonFaucetRequest>,
-) -> Json<JsonSubmitTxResponse> {
-    Json(state.handle_post(&req).await.into())
+    client_ip: Option<IpAddr>,
+) -> (Status, Json<JsonSubmitTxResponse>) {
+    let public_address = match decode_public_address(&req.b58_address) {
+        Ok(public_address) => public_address,
+        Err(err) => return (Status::BadRequest, Json(Err(err).into())),
+    };
+
+    let reservation = match state.check_rate_limits(&req, &public_address, client_ip) {
+        Ok(reservation) => reservation,
+        Err(err) => {
+            let status = if err.is_rate_limited() {
+                Status::TooManyRequests
+            } else {
+                Status::Forbidden
+            };
+            return (status, Json(Err(err.to_string()).into()));
+        }
+    };
+
+    // Only successful payouts count against the rate limits.
+    let result = state.handle_post(&req).await;
+    if result.is_err() {
+        state.rate_limiter.release(reservation);
+    }
+    (Status::Ok, Json(result.into()))
 }
 
 /// Request to initiate a slam, and map the rust result onto json for
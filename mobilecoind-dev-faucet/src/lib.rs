@@ -7,6 +7,9 @@
 pub mod data_types;
 use data_types::*;
 
+mod rate_limiter;
+pub use rate_limiter::{RateLimitError, RateLimitReservation, RateLimiter, RateLimits};
+
 mod slam;
 use slam::{SlamParams, SlamState};
 
@@ -17,7 +20,7 @@ use worker::{GetUtxoError, UtxoRecord, Worker};
 
 use clap::Parser;
 use grpcio::ChannelBuilder;
-use mc_account_keys::AccountKey;
+use mc_account_keys::{AccountKey, PublicAddress};
 use mc_api::printable::PrintableWrapper;
 use mc_common::logger::{log, o, Logger};
 use mc_mobilecoind_api::{self as api, mobilecoind_api_grpc::MobilecoindApiClient, MobilecoindUri};
@@ -25,7 +28,9 @@ use mc_transaction_core::{ring_signature::KeyImage, TokenId};
 use mc_util_grpc::ConnectionUriGrpcioChannel;
 use mc_util_keyfile::read_keyfile;
 use mc_util_uri::ConsensusClientUri;
-use std::{collections::HashMap, future::Future, path::PathBuf, sync::Arc, time::Duration};
+use std::{
+    collections::HashMap, future::Future, net::IpAddr, path::PathBuf, sync::Arc, time::Duration,
+};
 use tokio::select;
 
 /// Command line config, set with defaults that will work with
@@ -81,6 +86,85 @@ pub struct Config {
     ///     env MC_PEER=mc://foo:123,mc://bar:456
     #[clap(long = "peer", env = "MC_PEER", use_value_delimiter = true)]
     pub peers: Option<Vec<ConsensusClientUri>>,
+
+    /// The length of the period over which payment requests are counted by
+    /// the rate limits, in seconds. It cannot be zero.
+    #[clap(
+        long,
+        default_value = "3600",
+        parse(try_from_str = parse_rate_limit_period_secs),
+        env = "MC_RATE_LIMIT_PERIOD_SECS"
+    )]
+    pub rate_limit_period_secs: u64,
+
+    /// The maximum number of payments to a single address per period.
+    #[clap(long, env = "MC_MAX_REQUESTS_PER_ADDRESS")]
+    pub max_requests_per_address: Option<u32>,
+
+    /// The maximum number of payments requested by a single IP address per
+    /// period.
+    #[clap(long, env = "MC_MAX_REQUESTS_PER_IP")]
+    pub max_requests_per_ip: Option<u32>,
+
+    /// The maximum number of payments the faucet makes per period. This
+    /// bounds the amount paid per period to this many times the payout amount
+    /// of each token id.
+    #[clap(long, env = "MC_MAX_PAYOUTS_PER_PERIOD")]
+    pub max_payouts_per_period: Option<u32>,
+
+    /// If set, only these b58 addresses can be paid by the faucet.
+    #[clap(
+        long = "allowed-address",
+        env = "MC_ALLOWED_ADDRESSES",
+        use_value_delimiter = true,
+        parse(try_from_str = decode_public_address)
+    )]
+    pub allowed_addresses: Vec<PublicAddress>,
+
+    /// If set, only requests from these IP addresses are accepted.
+    #[clap(
+        long = "allowed-ip",
+        env = "MC_ALLOWED_IPS",
+        use_value_delimiter = true
+    )]
+    pub allowed_ips: Vec<IpAddr>,
+}
+
+impl Config {
+    /// Get the rate limits on payment requests
+    pub fn rate_limits(&self) -> RateLimits {
+        RateLimits {
+            period: Duration::from_secs(self.rate_limit_period_secs),
+            max_requests_per_address: self.max_requests_per_address,
+            max_requests_per_ip: self.max_requests_per_ip,
+            max_payouts_per_period: self.max_payouts_per_period,
+            allowed_addresses: self.allowed_addresses.iter().cloned().collect(),
+            allowed_ips: self.allowed_ips.iter().cloned().collect(),
+        }
+    }
+}
+
+fn parse_rate_limit_period_secs(src: &str) -> Result<u64, String> {
+    match src.parse::<u64>() {
+        Ok(0) => Err("The rate limit period must not be zero".to_string()),
+        Ok(secs) => Ok(secs),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Decode the b58 public address of a payment request
+pub fn decode_public_address(b58_address: &str) -> Result<PublicAddress, String> {
+    let printable_wrapper = PrintableWrapper::b58_decode(b58_address.to_string())
+        .map_err(|err| format!("Could not decode b58 address: {}", err))?;
+
+    if !printable_wrapper.has_public_address() {
+        return Err(format!(
+            "b58 address '{}' is not a public address",
+            b58_address
+        ));
+    }
+    PublicAddress::try_from(printable_wrapper.get_public_address())
+        .map_err(|err| format!("Invalid public address '{}': {}", b58_address, err))
 }
 
 /// Connection to the mobilecoind client, and other state tracked by the running
@@ -108,6 +192,8 @@ pub struct State {
     pub slam_state: Arc<SlamState>,
     /// List of consensus uri's to submit to during slam operation
     pub consensus_uris: Option<Vec<ConsensusClientUri>>,
+    /// Rate limits and allowlists checked before making a payment
+    pub rate_limiter: RateLimiter,
     /// Logger
     pub logger: Logger,
 }
@@ -166,6 +252,7 @@ impl State {
             worker,
             slam_state,
             consensus_uris: config.peers.clone(),
+            rate_limiter: RateLimiter::new(config.rate_limits()),
             logger: logger.clone(),
         }
     }
@@ -241,23 +328,37 @@ impl State {
         ))
     }
 
+    /// Check a "post" to the faucet against the rate limits and allowlists,
+    /// and count it if it is allowed. This should be called before
+    /// handle_post, and the reservation released if handle_post fails, so
+    /// that only successful payouts count against the limits.
+    pub fn check_rate_limits(
+        &self,
+        req: &JsonFaucetRequest,
+        public_address: &PublicAddress,
+        client_ip: Option<IpAddr>,
+    ) -> Result<RateLimitReservation, RateLimitError> {
+        self.rate_limiter
+            .check(public_address, client_ip)
+            .map_err(|err| {
+                log::debug!(
+                    self.logger,
+                    "Rejected request for {} from {:?}: {}",
+                    req.b58_address,
+                    client_ip,
+                    err
+                );
+                err
+            })
+    }
+
     /// Handle a "post" to the faucet, which requests a payment from the faucet.
     /// Returns either the mobilecoind success response or an error string.
     pub async fn handle_post(
         &self,
         req: &JsonFaucetRequest,
     ) -> Result<api::SubmitTxResponse, String> {
-        let printable_wrapper = PrintableWrapper::b58_decode(req.b58_address.clone())
-            .map_err(|err| format!("Could not decode b58 address: {}", err))?;
-
-        let public_address = if printable_wrapper.has_public_address() {
-            printable_wrapper.get_public_address()
-        } else {
-            return Err(format!(
-                "b58 address '{}' is not a public address",
-                req.b58_address
-            ));
-        };
+        let public_address = decode_public_address(&req.b58_address)?;
 
         let token_id = TokenId::from(req.token_id.as_ref());
 
@@ -273,7 +374,7 @@ impl State {
         let mut req = api::GenerateTxFromTxOutListRequest::new();
         req.set_account_key((&self.account_key).into());
         req.set_input_list(vec![utxo_record.utxo].into());
-        req.set_receiver(public_address.clone());
+        req.set_receiver((&public_address).into());
         req.set_token_id(*token_id);
 
         let resp = self
@@ -0,0 +1,363 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Rate limits and allowlists for faucet payment requests.
+//!
+//! Requests are counted in fixed periods, per requested address, per client IP
+//! address, and in total. All counts are reset when a new period begins.
+//!
+//! Addresses are compared once decoded, so the different b58 encodings of an
+//! address share their limits. A request is counted as soon as it is accepted,
+//! so that concurrent requests cannot exceed the limits, and its count is
+//! released if its payment fails.
+
+use displaydoc::Display;
+use mc_account_keys::PublicAddress;
+use std::{
+    collections::{HashMap, HashSet},
+    net::IpAddr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// The limits on the payment requests the faucet accepts
+#[derive(Clone, Debug)]
+pub struct RateLimits {
+    /// The length of the period over which requests are counted, which is not
+    /// zero
+    pub period: Duration,
+    /// The maximum number of payments to a single address per period
+    pub max_requests_per_address: Option<u32>,
+    /// The maximum number of payments requested by a single IP per period
+    pub max_requests_per_ip: Option<u32>,
+    /// The maximum number of payments the faucet makes per period
+    pub max_payouts_per_period: Option<u32>,
+    /// If not empty, only these addresses can be paid
+    pub allowed_addresses: HashSet<PublicAddress>,
+    /// If not empty, only requests from these IPs are accepted
+    pub allowed_ips: HashSet<IpAddr>,
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self {
+            period: Duration::from_secs(3600),
+            max_requests_per_address: None,
+            max_requests_per_ip: None,
+            max_payouts_per_period: None,
+            allowed_addresses: Default::default(),
+            allowed_ips: Default::default(),
+        }
+    }
+}
+
+/// An error indicating that a payment request was rejected by the rate limiter
+#[derive(Clone, Debug, Display, Eq, PartialEq)]
+pub enum RateLimitError {
+    /// The address is not on the allowlist
+    AddressNotAllowed,
+    /// The IP address of the client is not on the allowlist
+    IpNotAllowed,
+    /// Too many requests for this address, retry in {0} seconds
+    AddressRateLimited(u64),
+    /// Too many requests from this IP address, retry in {0} seconds
+    IpRateLimited(u64),
+    /// The faucet reached its maximum payouts for this period, retry in {0}
+    /// seconds
+    PayoutLimitReached(u64),
+}
+
+impl RateLimitError {
+    /// Whether the request is rejected until the end of the current period,
+    /// as opposed to being rejected by the allowlist.
+    pub fn is_rate_limited(&self) -> bool {
+        !matches!(self, Self::AddressNotAllowed | Self::IpNotAllowed)
+    }
+}
+
+/// A request accepted by the rate limiter, which counts against the limits
+/// unless it is released.
+#[derive(Debug)]
+#[must_use]
+pub struct RateLimitReservation {
+    period_start: Instant,
+    address: PublicAddress,
+    client_ip: Option<IpAddr>,
+}
+
+/// The requests counted in the current period
+struct PeriodCounts {
+    start: Instant,
+    by_address: HashMap<PublicAddress, u32>,
+    by_ip: HashMap<IpAddr, u32>,
+    total: u32,
+}
+
+/// Enforces rate limits and allowlists on payment requests
+pub struct RateLimiter {
+    limits: RateLimits,
+    counts: Mutex<PeriodCounts>,
+}
+
+impl RateLimiter {
+    /// Create a new rate limiter, whose first period starts now
+    ///
+    /// # Panics
+    /// If the period of the limits is zero.
+    pub fn new(limits: RateLimits) -> Self {
+        Self::new_at(limits, Instant::now())
+    }
+
+    fn new_at(limits: RateLimits, now: Instant) -> Self {
+        assert!(
+            !limits.period.is_zero(),
+            "The rate limit period must not be zero"
+        );
+        Self {
+            limits,
+            counts: Mutex::new(PeriodCounts {
+                start: now,
+                by_address: Default::default(),
+                by_ip: Default::default(),
+                total: 0,
+            }),
+        }
+    }
+
+    /// Check that a payment request is allowed, and if it is, count it against
+    /// the limits of the current period. The returned reservation should be
+    /// released if the payment fails.
+    ///
+    /// Arguments:
+    /// * The address the payment is requested for
+    /// * The IP address of the client, if known
+    pub fn check(
+        &self,
+        address: &PublicAddress,
+        client_ip: Option<IpAddr>,
+    ) -> Result<RateLimitReservation, RateLimitError> {
+        self.check_at(address, client_ip, Instant::now())
+    }
+
+    /// Stop counting a request whose payment failed. Requests accepted in an
+    /// earlier period were already forgotten.
+    pub fn release(&self, reservation: RateLimitReservation) {
+        let mut counts = self.counts.lock().expect("mutex poisoned");
+        if counts.start != reservation.period_start {
+            return;
+        }
+
+        counts.total = counts.total.saturating_sub(1);
+        if let Some(count) = counts.by_address.get_mut(&reservation.address) {
+            *count = count.saturating_sub(1);
+        }
+        if let Some(count) = reservation
+            .client_ip
+            .and_then(|ip| counts.by_ip.get_mut(&ip))
+        {
+            *count = count.saturating_sub(1);
+        }
+    }
+
+    fn check_at(
+        &self,
+        address: &PublicAddress,
+        client_ip: Option<IpAddr>,
+        now: Instant,
+    ) -> Result<RateLimitReservation, RateLimitError> {
+        if !self.limits.allowed_addresses.is_empty()
+            && !self.limits.allowed_addresses.contains(address)
+        {
+            return Err(RateLimitError::AddressNotAllowed);
+        }
+        if !self.limits.allowed_ips.is_empty()
+            && !client_ip.map_or(false, |ip| self.limits.allowed_ips.contains(&ip))
+        {
+            return Err(RateLimitError::IpNotAllowed);
+        }
+
+        let mut counts = self.counts.lock().expect("mutex poisoned");
+
+        // Start a new period if the current one is over.
+        let elapsed = now.saturating_duration_since(counts.start);
+        if elapsed >= self.limits.period {
+            let num_periods = elapsed.as_nanos() / self.limits.period.as_nanos();
+            counts.start += self.limits.period * num_periods as u32;
+            counts.by_address.clear();
+            counts.by_ip.clear();
+            counts.total = 0;
+        }
+        let retry_in = (counts.start + self.limits.period)
+            .saturating_duration_since(now)
+            .as_secs()
+            .max(1);
+
+        if let Some(max) = self.limits.max_payouts_per_period {
+            if counts.total >= max {
+                return Err(RateLimitError::PayoutLimitReached(retry_in));
+            }
+        }
+        if let Some(max) = self.limits.max_requests_per_address {
+            if counts.by_address.get(address).copied().unwrap_or(0) >= max {
+                return Err(RateLimitError::AddressRateLimited(retry_in));
+            }
+        }
+        if let (Some(max), Some(ip)) = (self.limits.max_requests_per_ip, client_ip) {
+            if counts.by_ip.get(&ip).copied().unwrap_or(0) >= max {
+                return Err(RateLimitError::IpRateLimited(retry_in));
+            }
+        }
+
+        counts.total += 1;
+        *counts.by_address.entry(address.clone()).or_default() += 1;
+        if let Some(ip) = client_ip {
+            *counts.by_ip.entry(ip).or_default() += 1;
+        }
+        Ok(RateLimitReservation {
+            period_start: counts.start,
+            address: address.clone(),
+            client_ip,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_account_keys::AccountKey;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    const PERIOD: Duration = Duration::from_secs(60);
+
+    fn address(seed: u64) -> PublicAddress {
+        AccountKey::random(&mut StdRng::seed_from_u64(seed)).default_subaddress()
+    }
+
+    #[test]
+    fn test_rate_limits() {
+        let start = Instant::now();
+        let limiter = RateLimiter::new_at(
+            RateLimits {
+                period: PERIOD,
+                max_requests_per_address: Some(2),
+                max_requests_per_ip: Some(3),
+                max_payouts_per_period: Some(4),
+                ..Default::default()
+            },
+            start,
+        );
+        let ip1 = Some(IpAddr::from([10, 0, 0, 1]));
+        let ip2 = Some(IpAddr::from([10, 0, 0, 2]));
+        let (a, b, c, d) = (address(1), address(2), address(3), address(4));
+
+        // Per address limit
+        assert!(limiter.check_at(&a, ip1, start).is_ok());
+        assert!(limiter.check_at(&a, ip1, start).is_ok());
+        assert_eq!(
+            limiter
+                .check_at(&a, ip2, start + Duration::from_secs(20))
+                .unwrap_err(),
+            RateLimitError::AddressRateLimited(40)
+        );
+
+        // Per IP limit
+        assert!(limiter.check_at(&b, ip1, start).is_ok());
+        assert_eq!(
+            limiter.check_at(&c, ip1, start).unwrap_err(),
+            RateLimitError::IpRateLimited(60)
+        );
+
+        // Total limit
+        assert!(limiter.check_at(&c, ip2, start).is_ok());
+        assert_eq!(
+            limiter.check_at(&d, None, start).unwrap_err(),
+            RateLimitError::PayoutLimitReached(60)
+        );
+
+        // Everything is allowed again in the next periods
+        let later = start + PERIOD * 3 + Duration::from_secs(10);
+        assert!(limiter.check_at(&a, ip1, later).is_ok());
+        assert!(limiter.check_at(&a, ip1, later).is_ok());
+        assert_eq!(
+            limiter.check_at(&a, ip1, later).unwrap_err(),
+            RateLimitError::AddressRateLimited(50)
+        );
+    }
+
+    #[test]
+    fn test_release() {
+        let start = Instant::now();
+        let limiter = RateLimiter::new_at(
+            RateLimits {
+                period: PERIOD,
+                max_requests_per_address: Some(1),
+                max_requests_per_ip: Some(1),
+                max_payouts_per_period: Some(1),
+                ..Default::default()
+            },
+            start,
+        );
+        let ip = Some(IpAddr::from([10, 0, 0, 1]));
+        let a = address(1);
+
+        // A request whose payment failed does not count against any limit.
+        let reservation = limiter.check_at(&a, ip, start).unwrap();
+        limiter.release(reservation);
+        let reservation = limiter.check_at(&a, ip, start).unwrap();
+        assert_eq!(
+            limiter.check_at(&a, ip, start).unwrap_err(),
+            RateLimitError::PayoutLimitReached(60)
+        );
+
+        // Releasing a request of an earlier period does not affect the current one.
+        let later = start + PERIOD;
+        let _reservation = limiter.check_at(&a, ip, later).unwrap();
+        limiter.release(reservation);
+        assert_eq!(
+            limiter.check_at(&a, ip, later).unwrap_err(),
+            RateLimitError::PayoutLimitReached(60)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The rate limit period must not be zero")]
+    fn test_zero_period() {
+        RateLimiter::new(RateLimits {
+            period: Duration::ZERO,
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn test_allowlists() {
+        let (a, b) = (address(1), address(2));
+        let limiter = RateLimiter::new(RateLimits {
+            allowed_addresses: [a.clone()].into_iter().collect(),
+            allowed_ips: [IpAddr::from([10, 0, 0, 1])].into_iter().collect(),
+            ..Default::default()
+        });
+
+        assert!(limiter.check(&a, Some(IpAddr::from([10, 0, 0, 1]))).is_ok());
+        assert_eq!(
+            limiter
+                .check(&b, Some(IpAddr::from([10, 0, 0, 1])))
+                .unwrap_err(),
+            RateLimitError::AddressNotAllowed
+        );
+        assert_eq!(
+            limiter
+                .check(&a, Some(IpAddr::from([10, 0, 0, 2])))
+                .unwrap_err(),
+            RateLimitError::IpNotAllowed
+        );
+        assert_eq!(
+            limiter.check(&a, None).unwrap_err(),
+            RateLimitError::IpNotAllowed
+        );
+
+        // Without limits, requests are never rate limited
+        let limiter = RateLimiter::new(Default::default());
+        for _ in 0..100 {
+            assert!(limiter.check(&a, None).is_ok());
+        }
+    }
+}
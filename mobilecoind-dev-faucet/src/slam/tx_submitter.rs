@@ -56,7 +56,7 @@ impl TxSubmitter {
             Ok(block_height) => Ok(block_height),
             Err(RetryError::Operation { error, .. }) => match error {
                 ConnectionError::TransactionValidation(
-                    TransactionValidationError::TombstoneBlockExceeded,
+                    TransactionValidationError::TombstoneBlockExceeded(..),
                 ) => {
                     log::debug!(logger, "Transaction {} tombstone block exceeded", counter);
                     Err(SubmitTxError::Rebuild)
@@ -13,7 +13,8 @@ use mc_mobilecoind_api::{self as api, mobilecoind_api_grpc::MobilecoindApiClient
 use mc_mobilecoind_json::data_types::*;
 use mc_util_grpc::ConnectionUriGrpcioChannel;
 use protobuf::RepeatedField;
-use rocket::{delete, get, post, routes, serde::json::Json};
+use rocket::{delete, get, post, serde::json::Json};
+use rocket_okapi::{openapi, openapi_get_routes};
 use std::sync::Arc;
 
 /// Command line config, set with defaults that will work with
@@ -44,6 +45,7 @@ struct State {
 }
 
 /// Set the password for the mobilecoind-db
+#[openapi]
 #[post("/set-password", format = "json", data = "<password>")]
 fn set_password(
     state: &rocket::State<State>,
@@ -62,6 +64,7 @@ fn set_password(
 }
 
 /// Unlock a previously-encrypted mobilecoind-db
+#[openapi]
 #[post("/unlock-db", format = "json", data = "<password>")]
 fn unlock_db(
     state: &rocket::State<State>,
@@ -80,6 +83,7 @@ fn unlock_db(
 }
 
 /// Gets current mobilecoind version
+#[openapi]
 #[get("/version")]
 fn version(state: &rocket::State<State>) -> Result<Json<JsonMobilecoindVersionResponse>, String> {
     let resp = state
@@ -90,6 +94,7 @@ fn version(state: &rocket::State<State>) -> Result<Json<JsonMobilecoindVersionRe
 }
 
 /// Requests a new root entropy from mobilecoind
+#[openapi]
 #[post("/entropy")]
 fn entropy(state: &rocket::State<State>) -> Result<Json<JsonRootEntropyResponse>, String> {
     let resp = state
@@ -99,6 +104,7 @@ fn entropy(state: &rocket::State<State>) -> Result<Json<JsonRootEntropyResponse>
     Ok(Json(JsonRootEntropyResponse::from(&resp)))
 }
 
+#[openapi]
 #[get("/entropy/<root_entropy>")]
 fn account_key_from_root_entropy(
     state: &rocket::State<State>,
@@ -119,6 +125,7 @@ fn account_key_from_root_entropy(
 }
 
 /// Requests a new mnemonic from mobilecoind
+#[openapi]
 #[post("/mnemonic")]
 fn mnemonic(state: &rocket::State<State>) -> Result<Json<JsonMnemonicResponse>, String> {
     let resp = state
@@ -128,6 +135,7 @@ fn mnemonic(state: &rocket::State<State>) -> Result<Json<JsonMnemonicResponse>,
     Ok(Json(JsonMnemonicResponse::from(&resp)))
 }
 
+#[openapi]
 #[post("/account-key-from-mnemonic", format = "json", data = "<mnemonic>")]
 fn account_key_from_mnemonic(
     state: &rocket::State<State>,
@@ -146,6 +154,7 @@ fn account_key_from_mnemonic(
 
 /// Creates a monitor. Data for the key and range is POSTed using the struct
 /// above.
+#[openapi]
 #[post("/monitors", format = "json", data = "<monitor>")]
 fn add_monitor(
     state: &rocket::State<State>,
@@ -180,6 +189,7 @@ fn add_monitor(
 }
 
 /// Remove a monitor
+#[openapi]
 #[delete("/monitors/<monitor_hex>")]
 fn remove_monitor(state: &rocket::State<State>, monitor_hex: String) -> Result<(), String> {
     let monitor_id =
@@ -197,6 +207,7 @@ fn remove_monitor(state: &rocket::State<State>, monitor_hex: String) -> Result<(
 }
 
 /// Gets a list of existing monitors
+#[openapi]
 #[get("/monitors")]
 fn monitors(state: &rocket::State<State>) -> Result<Json<JsonMonitorListResponse>, String> {
     let resp = state
@@ -207,6 +218,7 @@ fn monitors(state: &rocket::State<State>) -> Result<Json<JsonMonitorListResponse
 }
 
 /// Get the current status of a created monitor
+#[openapi]
 #[get("/monitors/<monitor_hex>")]
 fn monitor_status(
     state: &rocket::State<State>,
@@ -226,12 +238,15 @@ fn monitor_status(
     Ok(Json(JsonMonitorStatusResponse::from(&resp)))
 }
 
-/// Balance check using a created monitor and subaddress index
-#[get("/monitors/<monitor_hex>/subaddresses/<subaddress_index>/balance")]
+/// Balance check using a created monitor and subaddress index, in an optional
+/// token id
+#[openapi]
+#[get("/monitors/<monitor_hex>/subaddresses/<subaddress_index>/balance?<token_id>")]
 fn balance(
     state: &rocket::State<State>,
     monitor_hex: String,
     subaddress_index: u64,
+    token_id: Option<u64>,
 ) -> Result<Json<JsonBalanceResponse>, String> {
     let monitor_id =
         hex::decode(monitor_hex).map_err(|err| format!("Failed to decode monitor hex: {}", err))?;
@@ -239,6 +254,7 @@ fn balance(
     let mut req = api::GetBalanceRequest::new();
     req.set_monitor_id(monitor_id);
     req.set_subaddress_index(subaddress_index);
+    req.set_token_id(token_id.unwrap_or(0));
 
     let resp = state
         .mobilecoind_api_client
@@ -248,11 +264,15 @@ fn balance(
     Ok(Json(JsonBalanceResponse::from(&resp)))
 }
 
-#[get("/monitors/<monitor_hex>/subaddresses/<subaddress_index>/utxos")]
+/// Gets the UnspentTxOuts of a monitor and subaddress index, in an optional
+/// token id
+#[openapi]
+#[get("/monitors/<monitor_hex>/subaddresses/<subaddress_index>/utxos?<token_id>")]
 fn utxos(
     state: &rocket::State<State>,
     monitor_hex: String,
     subaddress_index: u64,
+    token_id: Option<u64>,
 ) -> Result<Json<JsonUtxosResponse>, String> {
     let monitor_id =
         hex::decode(monitor_hex).map_err(|err| format!("Failed to decode monitor hex: {}", err))?;
@@ -260,6 +280,7 @@ fn utxos(
     let mut req = api::GetUnspentTxOutListRequest::new();
     req.set_monitor_id(monitor_id);
     req.set_subaddress_index(subaddress_index);
+    req.set_token_id(token_id.unwrap_or(0));
 
     let resp = state
         .mobilecoind_api_client
@@ -270,6 +291,7 @@ fn utxos(
 }
 
 /// Balance check using a created monitor and subaddress index
+#[openapi]
 #[get("/monitors/<monitor_hex>/subaddresses/<subaddress_index>/public-address")]
 fn public_address(
     state: &rocket::State<State>,
@@ -292,24 +314,38 @@ fn public_address(
     Ok(Json(JsonPublicAddressResponse::from(&resp)))
 }
 
-/// Generates a request code with an optional value and memo
+/// Generates a request code with an optional value, memo and token id. The
+/// receiver is either a public address or a subaddress of a monitor.
+#[openapi]
 #[post("/codes/request", format = "json", data = "<request>")]
 fn create_request_code(
     state: &rocket::State<State>,
     request: Json<JsonCreateRequestCodeRequest>,
 ) -> Result<Json<JsonCreateRequestCodeResponse>, String> {
-    let receiver = api::external::PublicAddress::try_from(&request.receiver)
-        .map_err(|err| format!("Failed to parse receiver's public address: {}", err))?;
-
     // Generate b58 code
     let mut req = api::CreateRequestCodeRequest::new();
-    req.set_receiver(receiver);
+    if let Some(receiver) = request.receiver.as_ref() {
+        req.set_receiver(
+            api::external::PublicAddress::try_from(receiver)
+                .map_err(|err| format!("Failed to parse receiver's public address: {}", err))?,
+        );
+    }
+    if let Some(monitor_hex) = request.monitor_id.as_ref() {
+        req.set_monitor_id(
+            hex::decode(monitor_hex)
+                .map_err(|err| format!("Failed to decode monitor hex: {}", err))?,
+        );
+        req.set_subaddress_index(request.subaddress_index.unwrap_or(0));
+    }
     if let Some(value) = request.value {
         req.set_value(u64::from(value));
     }
     if let Some(memo) = request.memo.clone() {
         req.set_memo(memo);
     }
+    if let Some(token_id) = request.token_id {
+        req.set_token_id(u64::from(token_id));
+    }
 
     let resp = state
         .mobilecoind_api_client
@@ -320,6 +356,7 @@ fn create_request_code(
 }
 
 /// Retrieves the data in a request b58_code
+#[openapi]
 #[get("/codes/request/<b58_code>")]
 fn parse_request_code(
     state: &rocket::State<State>,
@@ -339,6 +376,7 @@ fn parse_request_code(
 }
 
 /// Generates an address code
+#[openapi]
 #[post("/codes/address", format = "json", data = "<request>")]
 fn create_address_code(
     state: &rocket::State<State>,
@@ -360,6 +398,7 @@ fn create_address_code(
 }
 
 /// Retrieves the data in an address b58_code
+#[openapi]
 #[get("/codes/address/<b58_code>")]
 fn parse_address_code(
     state: &rocket::State<State>,
@@ -378,6 +417,7 @@ fn parse_address_code(
 
 /// Performs a transfer from a monitor and subaddress. The public keys and
 /// amount are in the POST data.
+#[openapi]
 #[post(
     "/monitors/<monitor_hex>/subaddresses/<subaddress_index>/build-and-submit",
     format = "json",
@@ -416,6 +456,7 @@ fn build_and_submit(
         req.set_override_change_subaddress(true);
         req.set_change_subaddress(u64::from(subaddress))
     }
+    req.set_token_id(transfer.request_data.token_id.into());
 
     let resp = state
         .mobilecoind_api_client
@@ -428,6 +469,7 @@ fn build_and_submit(
 
 /// Performs a transfer from a monitor and subaddress to a given address
 /// code/amount.
+#[openapi]
 #[post(
     "/monitors/<monitor_hex>/subaddresses/<subaddress_index>/pay-address-code",
     format = "json",
@@ -463,6 +505,9 @@ fn pay_address_code(
         req.set_override_change_subaddress(true);
         req.set_change_subaddress(u64::from(subaddress))
     }
+    if let Some(token_id) = transfer.token_id.as_ref() {
+        req.set_token_id(u64::from(token_id));
+    }
 
     let resp = state
         .mobilecoind_api_client
@@ -476,6 +521,7 @@ fn pay_address_code(
 /// Creates a transaction proposal. This can be used in an offline transaction
 /// construction flow, where the proposal is created on the offline machine, and
 /// copied to the connected machine for submission, via submit-tx.
+#[openapi]
 #[post(
     "/monitors/<monitor_hex>/subaddresses/<subaddress_index>/generate-request-code-transaction",
     format = "json",
@@ -512,6 +558,7 @@ fn generate_request_code_transaction(
     req.set_change_subaddress(subaddress_index);
     req.set_outlay_list(RepeatedField::from_vec(vec![outlay]));
     req.set_input_list(RepeatedField::from_vec(inputs));
+    req.set_token_id(request.transfer.token_id.into());
 
     let resp = state
         .mobilecoind_api_client
@@ -521,7 +568,93 @@ fn generate_request_code_transaction(
     Ok(Json(JsonCreateTxProposalResponse::from(&resp)))
 }
 
+/// Creates a signed contingent input offering an UnspentTxOut of a monitor to
+/// any counterparty paying the required amount to the subaddress.
+#[openapi]
+#[post(
+    "/monitors/<monitor_hex>/subaddresses/<subaddress_index>/create-signed-contingent-input",
+    format = "json",
+    data = "<request>"
+)]
+fn create_signed_contingent_input(
+    state: &rocket::State<State>,
+    monitor_hex: String,
+    subaddress_index: u64,
+    request: Json<JsonCreateSignedContingentInputRequest>,
+) -> Result<Json<JsonSignedContingentInputResponse>, String> {
+    let monitor_id =
+        hex::decode(monitor_hex).map_err(|err| format!("Failed to decode monitor hex: {}", err))?;
+
+    let utxo = api::UnspentTxOut::try_from(&request.utxo)
+        .map_err(|err| format!("Failed to convert utxo: {}", err))?;
+
+    let mut req = api::CreateSignedContingentInputRequest::new();
+    req.set_monitor_id(monitor_id);
+    req.set_utxo(utxo);
+    req.set_required_value(request.required_value.into());
+    req.set_required_token_id(request.required_token_id.into());
+    req.set_receiver_subaddress(subaddress_index);
+    if let Some(tombstone) = request.tombstone.as_ref() {
+        req.set_tombstone(u64::from(tombstone));
+    }
+
+    let resp = state
+        .mobilecoind_api_client
+        .create_signed_contingent_input(&req)
+        .map_err(|err| format!("Failed to create signed contingent input: {}", err))?;
+
+    Ok(Json(JsonSignedContingentInputResponse::try_from(&resp)?))
+}
+
+/// Creates a transaction proposal spending a signed contingent input, paying
+/// the amounts it requires from a monitor. The offered amount and change go to
+/// the subaddress, and the proposal is submitted with submit-tx.
+#[openapi]
+#[post(
+    "/monitors/<monitor_hex>/subaddresses/<subaddress_index>/fulfill-signed-contingent-input",
+    format = "json",
+    data = "<request>"
+)]
+fn fulfill_signed_contingent_input(
+    state: &rocket::State<State>,
+    monitor_hex: String,
+    subaddress_index: u64,
+    request: Json<JsonFulfillSignedContingentInputRequest>,
+) -> Result<Json<JsonCreateTxProposalResponse>, String> {
+    let monitor_id =
+        hex::decode(monitor_hex).map_err(|err| format!("Failed to decode monitor hex: {}", err))?;
+
+    let inputs: Vec<api::UnspentTxOut> = request
+        .input_list
+        .iter()
+        .map(|input| {
+            api::UnspentTxOut::try_from(input)
+                .map_err(|err| format!("Failed to convert input: {}", err))
+        })
+        .collect::<Result<_, String>>()?;
+
+    let mut req = api::FulfillSignedContingentInputRequest::new();
+    req.set_sender_monitor_id(monitor_id);
+    req.set_change_subaddress(subaddress_index);
+    req.set_input_list(RepeatedField::from_vec(inputs));
+    req.set_sci(api::external::SignedContingentInput::try_from(&request.0)?);
+    if let Some(fee) = request.fee.as_ref() {
+        req.set_fee(u64::from(fee));
+    }
+    if let Some(tombstone) = request.tombstone.as_ref() {
+        req.set_tombstone(u64::from(tombstone));
+    }
+
+    let resp = state
+        .mobilecoind_api_client
+        .fulfill_signed_contingent_input(&req)
+        .map_err(|err| format!("Failed to fulfill signed contingent input: {}", err))?;
+
+    Ok(Json(JsonCreateTxProposalResponse::from(&resp)))
+}
+
 /// Submit a prepared TxProposal
+#[openapi]
 #[post("/submit-tx", format = "json", data = "<proposal>")]
 fn submit_tx(
     state: &rocket::State<State>,
@@ -544,6 +677,7 @@ fn submit_tx(
 }
 
 /// Checks the status of a transfer given a key image and tombstone block
+#[openapi]
 #[post("/tx/status-as-sender", format = "json", data = "<submit_response>")]
 fn check_transfer_status(
     state: &rocket::State<State>,
@@ -567,6 +701,7 @@ fn check_transfer_status(
 /// can prove they initiated it. This API is tied to a specific monitor id since
 /// the account information is required in order to validate the confirmation
 /// number.
+#[openapi]
 #[post(
     "/monitors/<monitor_hex>/tx-status-as-receiver",
     format = "json",
@@ -604,6 +739,7 @@ fn check_receiver_transfer_status(
 }
 
 /// Gets information about the entire ledger
+#[openapi]
 #[get("/ledger/local")]
 fn ledger_info(state: &rocket::State<State>) -> Result<Json<JsonLedgerInfoResponse>, String> {
     let resp = state
@@ -615,6 +751,7 @@ fn ledger_info(state: &rocket::State<State>) -> Result<Json<JsonLedgerInfoRespon
 }
 
 /// Retrieves the data in a request code
+#[openapi]
 #[get("/ledger/blocks/<block_num>/header")]
 fn block_info(
     state: &rocket::State<State>,
@@ -632,6 +769,7 @@ fn block_info(
 }
 
 /// Retrieves the details for a given block.
+#[openapi]
 #[get("/ledger/blocks/<block_num>")]
 fn block_details(
     state: &rocket::State<State>,
@@ -648,6 +786,7 @@ fn block_details(
     Ok(Json(JsonBlockDetailsResponse::from(&resp)))
 }
 /// Retreives processed block information.
+#[openapi]
 #[get("/monitors/<monitor_hex>/processed-block/<block_num>")]
 fn processed_block(
     state: &rocket::State<State>,
@@ -670,6 +809,7 @@ fn processed_block(
 }
 
 /// Get the block index of a given tx out, identified by its public key.
+#[openapi]
 #[get("/tx-out/<public_key_hex>/block-index")]
 fn tx_out_get_block_index_by_public_key(
     state: &rocket::State<State>,
@@ -692,6 +832,7 @@ fn tx_out_get_block_index_by_public_key(
     Ok(Json(JsonBlockIndexByTxPubKeyResponse::from(&resp)))
 }
 
+#[openapi]
 #[post("/tx-out/proof-of-membership", format = "json", data = "<request>")]
 /// Get a proof of membership for each queried TxOut.
 fn get_proof_of_membership(
@@ -734,6 +875,7 @@ fn get_proof_of_membership(
     Ok(Json(response))
 }
 
+#[openapi]
 #[post("/tx-out/mixin", format = "json", data = "<request>")]
 /// Get a list of TxOuts for use as mixins.
 fn get_mixins(
@@ -808,7 +950,7 @@ async fn main() -> Result<(), rocket::Error> {
     let _rocket = rocket::custom(figment)
         .mount(
             "/",
-            routes![
+            openapi_get_routes![
                 set_password,
                 unlock_db,
                 version,
@@ -830,6 +972,8 @@ async fn main() -> Result<(), rocket::Error> {
                 build_and_submit,
                 pay_address_code,
                 generate_request_code_transaction,
+                create_signed_contingent_input,
+                fulfill_signed_contingent_input,
                 submit_tx,
                 check_transfer_status,
                 check_receiver_transfer_status,
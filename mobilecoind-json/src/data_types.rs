@@ -4,35 +4,36 @@
 
 use mc_api::external::{
     CompressedRistretto, EncryptedFogHint, EncryptedMemo, InputRules, KeyImage, MaskedAmount,
-    PublicAddress, RingMLSAG, SignatureRctBulletproofs, Tx, TxIn, TxOutMembershipElement,
-    TxOutMembershipHash, TxOutMembershipProof, TxPrefix,
+    PublicAddress, RingMLSAG, SignatureRctBulletproofs, SignedContingentInput, Tx, TxIn,
+    TxOutMembershipElement, TxOutMembershipHash, TxOutMembershipProof, TxPrefix,
 };
 use mc_mobilecoind_api as api;
 use mc_util_serial::JsonU64;
-use protobuf::RepeatedField;
+use protobuf::{Message, RepeatedField};
+use schemars::JsonSchema;
 use serde_derive::{Deserialize, Serialize};
 
-#[derive(Deserialize, Default, Debug)]
+#[derive(Deserialize, Default, Debug, JsonSchema)]
 pub struct JsonPasswordRequest {
     pub password: String,
 }
 
-#[derive(Serialize, Default, Debug)]
+#[derive(Serialize, Default, Debug, JsonSchema)]
 pub struct JsonPasswordResponse {
     pub success: bool,
 }
 
-#[derive(Deserialize, Default, Debug)]
+#[derive(Deserialize, Default, Debug, JsonSchema)]
 pub struct JsonUnlockDbRequest {
     pub password: String,
 }
 
-#[derive(Serialize, Default, Debug)]
+#[derive(Serialize, Default, Debug, JsonSchema)]
 pub struct JsonUnlockDbResponse {
     pub success: bool,
 }
 
-#[derive(Serialize, Default, Debug)]
+#[derive(Serialize, Default, Debug, JsonSchema)]
 pub struct JsonRootEntropyResponse {
     pub entropy: String,
 }
@@ -45,7 +46,7 @@ impl From<&api::GenerateRootEntropyResponse> for JsonRootEntropyResponse {
     }
 }
 
-#[derive(Deserialize, Serialize, Default, Debug)]
+#[derive(Deserialize, Serialize, Default, Debug, JsonSchema)]
 pub struct JsonMnemonicResponse {
     pub mnemonic: String,
 }
@@ -58,7 +59,7 @@ impl From<&api::GenerateMnemonicResponse> for JsonMnemonicResponse {
     }
 }
 
-#[derive(Deserialize, Serialize, Default, Debug)]
+#[derive(Deserialize, Serialize, Default, Debug, JsonSchema)]
 pub struct JsonAccountKeyResponse {
     pub view_private_key: String,
     pub spend_private_key: String,
@@ -75,14 +76,14 @@ impl From<&api::GetAccountKeyResponse> for JsonAccountKeyResponse {
     }
 }
 
-#[derive(Deserialize, Default, Debug)]
+#[derive(Deserialize, Default, Debug, JsonSchema)]
 pub struct JsonMonitorRequest {
     pub account_key: JsonAccountKeyResponse,
     pub first_subaddress: u64,
     pub num_subaddresses: u64,
 }
 
-#[derive(Serialize, Default, Debug)]
+#[derive(Serialize, Default, Debug, JsonSchema)]
 pub struct JsonMonitorResponse {
     pub monitor_id: String,
     pub is_new: bool,
@@ -97,7 +98,7 @@ impl From<&api::AddMonitorResponse> for JsonMonitorResponse {
     }
 }
 
-#[derive(Serialize, Default, Debug)]
+#[derive(Serialize, Default, Debug, JsonSchema)]
 pub struct JsonMonitorListResponse {
     pub monitor_ids: Vec<String>,
 }
@@ -110,7 +111,7 @@ impl From<&api::GetMonitorListResponse> for JsonMonitorListResponse {
     }
 }
 
-#[derive(Serialize, Default, Debug)]
+#[derive(Serialize, Default, Debug, JsonSchema)]
 pub struct JsonMonitorStatusResponse {
     pub first_subaddress: u64,
     pub num_subaddresses: u64,
@@ -131,7 +132,7 @@ impl From<&api::GetMonitorStatusResponse> for JsonMonitorStatusResponse {
     }
 }
 
-#[derive(Serialize, Default, Debug)]
+#[derive(Serialize, Default, Debug, JsonSchema)]
 pub struct JsonBalanceResponse {
     pub balance: String,
 }
@@ -144,15 +145,68 @@ impl From<&api::GetBalanceResponse> for JsonBalanceResponse {
     }
 }
 
-#[derive(Serialize, Deserialize, Default, Debug)]
+#[derive(Serialize, Deserialize, Default, Debug, Clone, JsonSchema)]
+pub struct JsonDecodedMemo {
+    /// One of "unused", "unknown", "authenticated_sender",
+    /// "authenticated_sender_with_payment_request_id" or "destination"
+    pub memo_type: String,
+    pub memo_type_bytes: u32,
+    pub sender_address_hash: String,
+    #[schemars(with = "String")]
+    pub payment_request_id: JsonU64,
+    pub recipient_address_hash: String,
+    pub num_recipients: u32,
+    #[schemars(with = "String")]
+    pub fee: JsonU64,
+    #[schemars(with = "String")]
+    pub total_outlay: JsonU64,
+    pub validated: bool,
+    pub sender: Option<JsonPublicAddress>,
+}
+
+impl From<&api::DecodedMemo> for JsonDecodedMemo {
+    fn from(src: &api::DecodedMemo) -> Self {
+        let memo_type_str = match src.memo_type {
+            api::MemoType::MemoUnused => "unused",
+            api::MemoType::MemoUnknown => "unknown",
+            api::MemoType::AuthenticatedSender => "authenticated_sender",
+            api::MemoType::AuthenticatedSenderWithPaymentRequestId => {
+                "authenticated_sender_with_payment_request_id"
+            }
+            api::MemoType::Destination => "destination",
+        };
+
+        Self {
+            memo_type: memo_type_str.to_owned(),
+            memo_type_bytes: src.memo_type_bytes,
+            sender_address_hash: hex::encode(src.get_sender_address_hash()),
+            payment_request_id: JsonU64(src.payment_request_id),
+            recipient_address_hash: hex::encode(src.get_recipient_address_hash()),
+            num_recipients: src.num_recipients,
+            fee: JsonU64(src.fee),
+            total_outlay: JsonU64(src.total_outlay),
+            validated: src.validated,
+            sender: src.sender.as_ref().map(JsonPublicAddress::from),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, JsonSchema)]
 pub struct JsonUnspentTxOut {
     pub tx_out: JsonTxOut,
     pub subaddress_index: u64,
     pub key_image: String,
+    #[schemars(with = "String")]
     pub value: JsonU64,
     pub attempted_spend_height: u64,
     pub attempted_spend_tombstone: u64,
     pub monitor_id: String,
+    #[serde(default)]
+    #[schemars(with = "String")]
+    pub token_id: JsonU64,
+    /// Ignored when the UnspentTxOut is provided as an input
+    #[serde(default)]
+    pub memo: Option<JsonDecodedMemo>,
 }
 
 impl From<&api::UnspentTxOut> for JsonUnspentTxOut {
@@ -165,6 +219,8 @@ impl From<&api::UnspentTxOut> for JsonUnspentTxOut {
             attempted_spend_height: src.get_attempted_spend_height(),
             attempted_spend_tombstone: src.get_attempted_spend_tombstone(),
             monitor_id: hex::encode(&src.get_monitor_id()),
+            token_id: JsonU64(src.token_id),
+            memo: src.memo.as_ref().map(JsonDecodedMemo::from),
         }
     }
 }
@@ -191,6 +247,7 @@ impl TryFrom<&JsonUnspentTxOut> for api::UnspentTxOut {
         utxo.set_value(src.value.into());
         utxo.set_attempted_spend_height(src.attempted_spend_height);
         utxo.set_attempted_spend_tombstone(src.attempted_spend_tombstone);
+        utxo.set_token_id(src.token_id.into());
         utxo.set_monitor_id(
             hex::decode(&src.monitor_id)
                 .map_err(|err| format!("Failed to decode monitor id hex: {}", err))?,
@@ -200,7 +257,7 @@ impl TryFrom<&JsonUnspentTxOut> for api::UnspentTxOut {
     }
 }
 
-#[derive(Serialize, Default, Debug)]
+#[derive(Serialize, Default, Debug, JsonSchema)]
 pub struct JsonUtxosResponse {
     pub output_list: Vec<JsonUnspentTxOut>,
 }
@@ -217,14 +274,21 @@ impl From<&api::GetUnspentTxOutListResponse> for JsonUtxosResponse {
     }
 }
 
-#[derive(Deserialize, Default, Debug)]
+#[derive(Deserialize, Default, Debug, JsonSchema)]
 pub struct JsonCreateRequestCodeRequest {
-    pub receiver: JsonPublicAddress,
+    /// The receiver, unless it is given as a subaddress of a monitor
+    pub receiver: Option<JsonPublicAddress>,
+    #[schemars(with = "Option<String>")]
     pub value: Option<JsonU64>,
     pub memo: Option<String>,
+    #[schemars(with = "Option<String>")]
+    pub token_id: Option<JsonU64>,
+    /// Hex encoded monitor id, when the receiver is a subaddress of a monitor
+    pub monitor_id: Option<String>,
+    pub subaddress_index: Option<u64>,
 }
 
-#[derive(Serialize, Default, Debug)]
+#[derive(Serialize, Default, Debug, JsonSchema)]
 pub struct JsonCreateRequestCodeResponse {
     pub b58_request_code: String,
 }
@@ -237,7 +301,7 @@ impl From<&api::CreateRequestCodeResponse> for JsonCreateRequestCodeResponse {
     }
 }
 
-#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+#[derive(Deserialize, Serialize, Default, Debug, Clone, JsonSchema)]
 pub struct JsonPublicAddress {
     /// Hex encoded compressed ristretto bytes
     pub view_public_key: String,
@@ -267,7 +331,7 @@ impl From<&PublicAddress> for JsonPublicAddress {
     }
 }
 
-#[derive(Deserialize, Serialize, Default, Debug)]
+#[derive(Deserialize, Serialize, Default, Debug, JsonSchema)]
 pub struct JsonPublicAddressResponse {
     /// Hex encoded compressed ristretto bytes
     pub view_public_key: String,
@@ -334,11 +398,24 @@ impl TryFrom<&JsonPublicAddress> for PublicAddress {
     }
 }
 
-#[derive(Deserialize, Serialize, Default, Debug)]
+#[derive(Deserialize, Serialize, Default, Debug, JsonSchema)]
 pub struct JsonParseRequestCodeResponse {
     pub receiver: JsonPublicAddress,
+    #[schemars(with = "String")]
     pub value: JsonU64,
     pub memo: String,
+    #[serde(default)]
+    #[schemars(with = "String")]
+    pub token_id: JsonU64,
+
+    /// Hex encoded monitor id, set when the receiver is a subaddress watched
+    /// by a monitor
+    #[serde(default)]
+    pub monitor_id: String,
+    #[serde(default)]
+    pub subaddress_index: u64,
+    #[serde(default)]
+    pub subaddress_label: String,
 }
 
 impl From<&api::ParseRequestCodeResponse> for JsonParseRequestCodeResponse {
@@ -347,16 +424,20 @@ impl From<&api::ParseRequestCodeResponse> for JsonParseRequestCodeResponse {
             receiver: JsonPublicAddress::from(src.get_receiver()),
             value: JsonU64(src.get_value()),
             memo: src.get_memo().to_string(),
+            token_id: JsonU64(src.get_token_id()),
+            monitor_id: hex::encode(src.get_monitor_id()),
+            subaddress_index: src.get_subaddress_index(),
+            subaddress_label: src.get_subaddress_label().to_string(),
         }
     }
 }
 
-#[derive(Deserialize, Default, Debug)]
+#[derive(Deserialize, Default, Debug, JsonSchema)]
 pub struct JsonCreateAddressCodeRequest {
     pub receiver: JsonPublicAddress,
 }
 
-#[derive(Serialize, Default, Debug)]
+#[derive(Serialize, Default, Debug, JsonSchema)]
 pub struct JsonCreateAddressCodeResponse {
     pub b58_code: String,
 }
@@ -369,7 +450,7 @@ impl From<&api::CreateAddressCodeResponse> for JsonCreateAddressCodeResponse {
     }
 }
 
-#[derive(Deserialize, Serialize, Default, Debug)]
+#[derive(Deserialize, Serialize, Default, Debug, JsonSchema)]
 pub struct JsonParseAddressCodeResponse {
     pub receiver: JsonPublicAddress,
 }
@@ -382,7 +463,7 @@ impl From<&api::ParseAddressCodeResponse> for JsonParseAddressCodeResponse {
     }
 }
 
-#[derive(Deserialize, Serialize, Default, Debug)]
+#[derive(Deserialize, Serialize, Default, Debug, JsonSchema)]
 pub struct JsonSenderTxReceipt {
     pub key_images: Vec<String>,
     pub tombstone: u64,
@@ -401,7 +482,7 @@ impl From<&api::SenderTxReceipt> for JsonSenderTxReceipt {
     }
 }
 
-#[derive(Deserialize, Serialize, Default, Debug)]
+#[derive(Deserialize, Serialize, Default, Debug, JsonSchema)]
 pub struct JsonReceiverTxReceipt {
     pub recipient: JsonPublicAddress,
     pub tx_public_key: String,
@@ -422,14 +503,16 @@ impl From<&api::ReceiverTxReceipt> for JsonReceiverTxReceipt {
     }
 }
 
-#[derive(Deserialize, Serialize, Default, Debug)]
+#[derive(Deserialize, Serialize, Default, Debug, JsonSchema)]
 pub struct JsonSendPaymentRequest {
     pub request_data: JsonParseRequestCodeResponse,
+    #[schemars(with = "Option<String>")]
     pub max_input_utxo_value: Option<JsonU64>,
+    #[schemars(with = "Option<String>")]
     pub change_subaddress: Option<JsonU64>,
 }
 
-#[derive(Deserialize, Serialize, Default, Debug)]
+#[derive(Deserialize, Serialize, Default, Debug, JsonSchema)]
 pub struct JsonSendPaymentResponse {
     pub sender_tx_receipt: JsonSenderTxReceipt,
     pub receiver_tx_receipt_list: Vec<JsonReceiverTxReceipt>,
@@ -448,16 +531,22 @@ impl From<&api::SendPaymentResponse> for JsonSendPaymentResponse {
     }
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, JsonSchema)]
 pub struct JsonPayAddressCodeRequest {
     pub receiver_b58_address_code: String,
+    #[schemars(with = "String")]
     pub value: JsonU64,
+    #[schemars(with = "Option<String>")]
     pub max_input_utxo_value: Option<JsonU64>,
+    #[schemars(with = "Option<String>")]
     pub change_subaddress: Option<JsonU64>,
+    #[schemars(with = "Option<String>")]
+    pub token_id: Option<JsonU64>,
 }
 
-#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+#[derive(Deserialize, Serialize, Default, Debug, Clone, JsonSchema)]
 pub struct JsonOutlay {
+    #[schemars(with = "String")]
     pub value: JsonU64,
     pub receiver: JsonPublicAddress,
 }
@@ -486,9 +575,10 @@ impl TryFrom<&JsonOutlay> for api::Outlay {
     }
 }
 
-#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+#[derive(Deserialize, Serialize, Default, Debug, Clone, JsonSchema)]
 pub struct JsonMaskedAmount {
     pub commitment: String,
+    #[schemars(with = "String")]
     pub masked_value: JsonU64,
     pub masked_token_id: String,
 }
@@ -503,13 +593,15 @@ impl From<&MaskedAmount> for JsonMaskedAmount {
     }
 }
 
-#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+#[derive(Deserialize, Serialize, Default, Debug, Clone, JsonSchema)]
 pub struct JsonTxOut {
     pub masked_amount: JsonMaskedAmount,
     pub target_key: String,
     pub public_key: String,
     pub e_fog_hint: String,
     pub e_memo: String,
+    #[serde(default)]
+    pub additional_e_fog_hints: Vec<String>,
 }
 
 impl From<&mc_api::external::TxOut> for JsonTxOut {
@@ -520,6 +612,11 @@ impl From<&mc_api::external::TxOut> for JsonTxOut {
             public_key: hex::encode(src.get_public_key().get_data()),
             e_fog_hint: hex::encode(src.get_e_fog_hint().get_data()),
             e_memo: hex::encode(src.get_e_memo().get_data()),
+            additional_e_fog_hints: src
+                .get_additional_e_fog_hints()
+                .iter()
+                .map(|hint| hex::encode(hint.get_data()))
+                .collect(),
         }
     }
 }
@@ -570,18 +667,32 @@ impl TryFrom<&JsonTxOut> for mc_api::external::TxOut {
         if !e_memo.get_data().is_empty() {
             txo.set_e_memo(e_memo);
         }
+        txo.set_additional_e_fog_hints(
+            src.additional_e_fog_hints
+                .iter()
+                .map(|hint| {
+                    let mut e_fog_hint = EncryptedFogHint::new();
+                    e_fog_hint.set_data(hex::decode(hint).map_err(|err| {
+                        format!("Failed to decode additional e_fog_hint hex: {}", err)
+                    })?);
+                    Ok(e_fog_hint)
+                })
+                .collect::<Result<_, String>>()?,
+        );
 
         Ok(txo)
     }
 }
 
-#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+#[derive(Deserialize, Serialize, Default, Debug, Clone, JsonSchema)]
 pub struct JsonRange {
+    #[schemars(with = "String")]
     pub from: JsonU64,
+    #[schemars(with = "String")]
     pub to: JsonU64,
 }
 
-#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+#[derive(Deserialize, Serialize, Default, Debug, Clone, JsonSchema)]
 pub struct JsonTxOutMembershipElement {
     pub range: JsonRange,
     pub hash: String,
@@ -599,9 +710,11 @@ impl From<&TxOutMembershipElement> for JsonTxOutMembershipElement {
     }
 }
 
-#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+#[derive(Deserialize, Serialize, Default, Debug, Clone, JsonSchema)]
 pub struct JsonTxOutMembershipProof {
+    #[schemars(with = "String")]
     pub index: JsonU64,
+    #[schemars(with = "String")]
     pub highest_index: JsonU64,
     pub elements: Vec<JsonTxOutMembershipElement>,
 }
@@ -651,7 +764,7 @@ impl TryFrom<&JsonTxOutMembershipProof> for TxOutMembershipProof {
     }
 }
 
-#[derive(Deserialize, Serialize, Default, Debug)]
+#[derive(Deserialize, Serialize, Default, Debug, JsonSchema)]
 /// A request for randomly sampled TxOuts for use as mixins.
 pub struct JsonMixinRequest {
     /// Number of mixins requested.
@@ -660,7 +773,7 @@ pub struct JsonMixinRequest {
     pub excluded: Vec<JsonTxOut>,
 }
 
-#[derive(Deserialize, Serialize, Default, Debug)]
+#[derive(Deserialize, Serialize, Default, Debug, JsonSchema)]
 /// Randomly sampled TxOuts for use as mixins, with membership proofs.
 pub struct JsonMixinResponse {
     /// TxOuts to use as mixins.
@@ -669,13 +782,13 @@ pub struct JsonMixinResponse {
     pub membership_proofs: Vec<JsonTxOutMembershipProof>,
 }
 
-#[derive(Deserialize, Serialize, Default, Debug)]
+#[derive(Deserialize, Serialize, Default, Debug, JsonSchema)]
 /// Requests Merkle proof-of-membership for each queried TxOut
 pub struct JsonMembershipProofRequest {
     pub outputs: Vec<JsonTxOut>,
 }
 
-#[derive(Deserialize, Serialize, Default, Debug)]
+#[derive(Deserialize, Serialize, Default, Debug, JsonSchema)]
 /// Outputs and their corresponding proofs of membership.
 pub struct JsonMembershipProofResponse {
     /// Queried outputs.
@@ -684,7 +797,7 @@ pub struct JsonMembershipProofResponse {
     pub membership_proofs: Vec<JsonTxOutMembershipProof>,
 }
 
-#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+#[derive(Deserialize, Serialize, Default, Debug, Clone, JsonSchema)]
 pub struct JsonInputRules {
     pub required_outputs: Vec<JsonTxOut>,
     pub max_tombstone_block: u64,
@@ -722,7 +835,7 @@ impl TryFrom<&JsonInputRules> for InputRules {
     }
 }
 
-#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+#[derive(Deserialize, Serialize, Default, Debug, Clone, JsonSchema)]
 pub struct JsonTxIn {
     pub ring: Vec<JsonTxOut>,
     pub proofs: Vec<JsonTxOutMembershipProof>,
@@ -778,12 +891,14 @@ impl TryFrom<&JsonTxIn> for TxIn {
     }
 }
 
-#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+#[derive(Deserialize, Serialize, Default, Debug, Clone, JsonSchema)]
 pub struct JsonTxPrefix {
     pub inputs: Vec<JsonTxIn>,
     pub outputs: Vec<JsonTxOut>,
+    #[schemars(with = "String")]
     pub fee: JsonU64,
-    tombstone_block: JsonU64,
+    #[schemars(with = "String")]
+    Nonetombstone_block: JsonU64,
 }
 
 impl From<&TxPrefix> for JsonTxPrefix {
@@ -825,7 +940,7 @@ impl TryFrom<&JsonTxPrefix> for TxPrefix {
     }
 }
 
-#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+#[derive(Deserialize, Serialize, Default, Debug, Clone, JsonSchema)]
 pub struct JsonRingMLSAG {
     pub c_zero: String,
     pub responses: Vec<String>,
@@ -846,13 +961,15 @@ impl From<&RingMLSAG> for JsonRingMLSAG {
     }
 }
 
-#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+#[derive(Deserialize, Serialize, Default, Debug, Clone, JsonSchema)]
 pub struct JsonSignatureRctBulletproofs {
     pub ring_signatures: Vec<JsonRingMLSAG>,
     pub pseudo_output_commitments: Vec<String>,
     pub range_proof_bytes: String,
     pub range_proofs: Vec<String>,
+    #[schemars(with = "Vec<String>")]
     pub pseudo_output_token_ids: Vec<JsonU64>,
+    #[schemars(with = "Vec<String>")]
     pub output_token_ids: Vec<JsonU64>,
 }
 
@@ -955,7 +1072,7 @@ impl TryFrom<&JsonSignatureRctBulletproofs> for SignatureRctBulletproofs {
     }
 }
 
-#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+#[derive(Deserialize, Serialize, Default, Debug, Clone, JsonSchema)]
 pub struct JsonTx {
     pub prefix: JsonTxPrefix,
     pub signature: JsonSignatureRctBulletproofs,
@@ -989,7 +1106,7 @@ impl TryFrom<&JsonTx> for Tx {
     }
 }
 
-#[derive(Deserialize, Serialize, Default, Debug)]
+#[derive(Deserialize, Serialize, Default, Debug, JsonSchema)]
 pub struct JsonTxProposal {
     pub input_list: Vec<JsonUnspentTxOut>,
     pub outlay_list: Vec<JsonOutlay>,
@@ -997,6 +1114,7 @@ pub struct JsonTxProposal {
     pub fee: u64,
     pub outlay_index_to_tx_out_index: Vec<(usize, usize)>,
     pub outlay_confirmation_numbers: Vec<Vec<u8>>,
+    pub outlay_shared_secrets: Vec<String>,
 }
 
 impl From<&api::TxProposal> for JsonTxProposal {
@@ -1017,6 +1135,11 @@ impl From<&api::TxProposal> for JsonTxProposal {
             fee: src.get_fee(),
             outlay_index_to_tx_out_index: outlay_map,
             outlay_confirmation_numbers: src.get_outlay_confirmation_numbers().to_vec(),
+            outlay_shared_secrets: src
+                .get_outlay_shared_secrets()
+                .iter()
+                .map(|shared_secret| hex::encode(shared_secret.get_data()))
+                .collect(),
         }
     }
 }
@@ -1057,17 +1180,28 @@ impl TryFrom<&JsonTxProposal> for api::TxProposal {
             src.outlay_confirmation_numbers.clone(),
         ));
 
+        let mut outlay_shared_secrets = Vec::new();
+        for shared_secret_hex in src.outlay_shared_secrets.iter() {
+            let mut shared_secret = CompressedRistretto::new();
+            shared_secret.set_data(
+                hex::decode(shared_secret_hex)
+                    .map_err(|err| format!("Could not decode from hex: {}", err))?,
+            );
+            outlay_shared_secrets.push(shared_secret);
+        }
+        proposal.set_outlay_shared_secrets(RepeatedField::from_vec(outlay_shared_secrets));
+
         Ok(proposal)
     }
 }
 
-#[derive(Deserialize, Serialize, Default, Debug)]
+#[derive(Deserialize, Serialize, Default, Debug, JsonSchema)]
 pub struct JsonCreateTxProposalRequest {
     pub input_list: Vec<JsonUnspentTxOut>,
     pub transfer: JsonParseRequestCodeResponse,
 }
 
-#[derive(Deserialize, Serialize, Default, Debug)]
+#[derive(Deserialize, Serialize, Default, Debug, JsonSchema)]
 pub struct JsonCreateTxProposalResponse {
     pub tx_proposal: JsonTxProposal,
 }
@@ -1080,12 +1214,74 @@ impl From<&api::GenerateTxResponse> for JsonCreateTxProposalResponse {
     }
 }
 
-#[derive(Deserialize, Serialize, Default, Debug)]
+#[derive(Deserialize, Serialize, Default, Debug, JsonSchema)]
 pub struct JsonTxProposalRequest {
     pub tx_proposal: JsonTxProposal,
 }
 
-#[derive(Deserialize, Serialize, Default, Debug)]
+#[derive(Deserialize, Serialize, Default, Debug, JsonSchema)]
+pub struct JsonCreateSignedContingentInputRequest {
+    pub utxo: JsonUnspentTxOut,
+    #[schemars(with = "String")]
+    pub required_value: JsonU64,
+    #[serde(default)]
+    #[schemars(with = "String")]
+    pub required_token_id: JsonU64,
+    #[schemars(with = "Option<String>")]
+    pub tombstone: Option<JsonU64>,
+}
+
+#[derive(Deserialize, Serialize, Default, Debug, JsonSchema)]
+pub struct JsonSignedContingentInputResponse {
+    /// Hex encoded protobuf serialization of the signed contingent input
+    pub sci: String,
+}
+
+impl TryFrom<&api::CreateSignedContingentInputResponse> for JsonSignedContingentInputResponse {
+    type Error = String;
+
+    fn try_from(src: &api::CreateSignedContingentInputResponse) -> Result<Self, String> {
+        let bytes = src
+            .get_sci()
+            .write_to_bytes()
+            .map_err(|err| format!("Failed to serialize signed contingent input: {}", err))?;
+        Ok(Self {
+            sci: hex::encode(bytes),
+        })
+    }
+}
+
+#[derive(Deserialize, Serialize, Default, Debug, JsonSchema)]
+pub struct JsonFulfillSignedContingentInputRequest {
+    pub input_list: Vec<JsonUnspentTxOut>,
+    /// Hex encoded protobuf serialization of the signed contingent input
+    pub sci: String,
+    #[schemars(with = "Option<String>")]
+    pub fee: Option<JsonU64>,
+    #[schemars(with = "Option<String>")]
+    pub tombstone: Option<JsonU64>,
+}
+
+impl TryFrom<&JsonFulfillSignedContingentInputRequest> for SignedContingentInput {
+    type Error = String;
+
+    fn try_from(src: &JsonFulfillSignedContingentInputRequest) -> Result<Self, String> {
+        let bytes = hex::decode(&src.sci)
+            .map_err(|err| format!("Failed to decode signed contingent input hex: {}", err))?;
+        SignedContingentInput::parse_from_bytes(&bytes)
+            .map_err(|err| format!("Failed to parse signed contingent input: {}", err))
+    }
+}
+
+impl From<&api::FulfillSignedContingentInputResponse> for JsonCreateTxProposalResponse {
+    fn from(src: &api::FulfillSignedContingentInputResponse) -> Self {
+        Self {
+            tx_proposal: src.get_tx_proposal().into(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Default, Debug, JsonSchema)]
 pub struct JsonSubmitTxResponse {
     pub sender_tx_receipt: JsonSenderTxReceipt,
     pub receiver_tx_receipt_list: Vec<JsonReceiverTxReceipt>,
@@ -1161,7 +1357,7 @@ impl TryFrom<&JsonSubmitTxResponse> for api::SubmitTxResponse {
     }
 }
 
-#[derive(Serialize, Default, Debug)]
+#[derive(Serialize, Default, Debug, JsonSchema)]
 pub struct JsonStatusResponse {
     pub status: String,
 }
@@ -1210,9 +1406,11 @@ impl From<&api::GetTxStatusAsReceiverResponse> for JsonStatusResponse {
     }
 }
 
-#[derive(Serialize, Default, Debug)]
+#[derive(Serialize, Default, Debug, JsonSchema)]
 pub struct JsonLedgerInfoResponse {
+    #[schemars(with = "String")]
     pub block_count: JsonU64,
+    #[schemars(with = "String")]
     pub txo_count: JsonU64,
 }
 
@@ -1225,9 +1423,11 @@ impl From<&api::GetLedgerInfoResponse> for JsonLedgerInfoResponse {
     }
 }
 
-#[derive(Serialize, Default, Debug)]
+#[derive(Serialize, Default, Debug, JsonSchema)]
 pub struct JsonBlockInfoResponse {
+    #[schemars(with = "String")]
     pub key_image_count: JsonU64,
+    #[schemars(with = "String")]
     pub txo_count: JsonU64,
 }
 
@@ -1240,12 +1440,14 @@ impl From<&api::GetBlockInfoResponse> for JsonBlockInfoResponse {
     }
 }
 
-#[derive(Serialize, Default, Debug)]
+#[derive(Serialize, Default, Debug, JsonSchema)]
 pub struct JsonBlockDetailsResponse {
     pub block_id: String,
     pub version: u32,
     pub parent_id: String,
+    #[schemars(with = "String")]
     pub index: JsonU64,
+    #[schemars(with = "String")]
     pub cumulative_txo_count: JsonU64,
     pub contents_hash: String,
     pub key_images: Vec<String>,
@@ -1273,14 +1475,20 @@ impl From<&api::GetBlockResponse> for JsonBlockDetailsResponse {
     }
 }
 
-#[derive(Serialize, Default, Debug)]
+#[derive(Serialize, Default, Debug, JsonSchema)]
 pub struct JsonProcessedTxOut {
     pub monitor_id: String,
     pub subaddress_index: u64,
     pub public_key: String,
     pub key_image: String,
+    #[schemars(with = "String")]
     pub value: JsonU64,
     pub direction: String,
+    pub address_code: String,
+    #[schemars(with = "String")]
+    pub token_id: JsonU64,
+    pub memo: Option<JsonDecodedMemo>,
+    pub subaddress_label: String,
 }
 
 impl From<&api::ProcessedTxOut> for JsonProcessedTxOut {
@@ -1298,11 +1506,15 @@ impl From<&api::ProcessedTxOut> for JsonProcessedTxOut {
             key_image: hex::encode(&src.get_key_image().get_data()),
             value: JsonU64(src.value),
             direction: direction_str.to_owned(),
+            address_code: src.get_address_code().to_string(),
+            token_id: JsonU64(src.token_id),
+            memo: src.memo.as_ref().map(JsonDecodedMemo::from),
+            subaddress_label: src.get_subaddress_label().to_string(),
         }
     }
 }
 
-#[derive(Serialize, Default, Debug)]
+#[derive(Serialize, Default, Debug, JsonSchema)]
 pub struct JsonProcessedBlockResponse {
     pub tx_outs: Vec<JsonProcessedTxOut>,
 }
@@ -1319,7 +1531,7 @@ impl From<&api::GetProcessedBlockResponse> for JsonProcessedBlockResponse {
     }
 }
 
-#[derive(Serialize, Default, Debug)]
+#[derive(Serialize, Default, Debug, JsonSchema)]
 pub struct JsonBlockIndexByTxPubKeyResponse {
     pub block_index: String,
 }
@@ -1332,7 +1544,7 @@ impl From<&api::GetBlockIndexByTxPubKeyResponse> for JsonBlockIndexByTxPubKeyRes
     }
 }
 
-#[derive(Serialize, Default, Debug)]
+#[derive(Serialize, Default, Debug, JsonSchema)]
 pub struct JsonMobilecoindVersionResponse {
     pub version: String,
 }
@@ -1400,6 +1612,7 @@ mod test {
                 public_key: RistrettoPublic::from_random(&mut rng).into(),
                 e_fog_hint: (&[0u8; ENCRYPTED_FOG_HINT_LEN]).into(),
                 e_memo: Some(Default::default()),
+                additional_e_fog_hints: Default::default(),
             };
 
             let subaddress_index = 123;
@@ -1443,6 +1656,9 @@ mod test {
                 .map(|x| x.to_vec())
                 .collect(),
         ));
+        let mut shared_secret = CompressedRistretto::new();
+        shared_secret.set_data(vec![1u8; 32]);
+        proto_proposal.set_outlay_shared_secrets(RepeatedField::from_vec(vec![shared_secret]));
 
         // Proto -> Json
         let json_proposal = JsonTxProposal::from(&proto_proposal);
@@ -1524,6 +1740,10 @@ mod test {
             proto_proposal.outlay_confirmation_numbers,
             proto2.outlay_confirmation_numbers
         );
+        assert_eq!(
+            proto_proposal.outlay_shared_secrets,
+            proto2.outlay_shared_secrets
+        );
         assert_eq!(proto_proposal, proto2);
     }
 }
@@ -117,13 +117,14 @@ fn main() {
             let mobilecoind_db = Database::new(mobilecoind_db, logger.clone())
                 .expect("Could not open mobilecoind_db");
 
-            let transactions_manager = TransactionsManager::new(
+            let mut transactions_manager = TransactionsManager::new(
                 ledger_db.clone(),
                 mobilecoind_db.clone(),
                 peer_manager,
                 config.get_fog_resolver_factory(logger.clone()),
                 logger.clone(),
             );
+            transactions_manager.set_tombstone_offset(config.tombstone_offset);
 
             let _api_server = Service::new(
                 ledger_db,
@@ -131,6 +132,8 @@ fn main() {
                 watcher_db,
                 transactions_manager,
                 network_state,
+                config.trusted_fog_authorities.clone(),
+                config.idempotency_retention,
                 listen_uri,
                 config.num_workers,
                 logger,
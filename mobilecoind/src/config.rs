@@ -13,6 +13,7 @@ use mc_fog_report_connection::GrpcFogReportConnection;
 use mc_fog_report_validation::FogResolver;
 use mc_mobilecoind_api::MobilecoindUri;
 use mc_sgx_css::Signature;
+use mc_transaction_core::constants::MAX_TOMBSTONE_BLOCKS;
 use mc_util_parse::{load_css_file, parse_duration_in_seconds};
 use mc_util_uri::{ConnectionUri, ConsensusClientUri, FogUri};
 #[cfg(feature = "ip-check")]
@@ -78,6 +79,8 @@ pub struct Config {
     pub mobilecoind_db: Option<PathBuf>,
 
     /// URI to listen on and serve requests from.
+    /// Clients can be required to present a certificate signed by one of the
+    /// certificates of a CA bundle with the `tls-client-ca` query parameter.
     #[clap(long, env = "MC_LISTEN_URI")]
     pub listen_uri: Option<MobilecoindUri>,
 
@@ -95,6 +98,28 @@ pub struct Config {
     #[clap(long, parse(try_from_str = load_css_file), env = "MC_FOG_INGEST_ENCLAVE_CSS")]
     pub fog_ingest_enclave_css: Option<Signature>,
 
+    /// PEM files holding the public keys of the fog authorities trusted by
+    /// ValidateFogAddress. The fog authority signature of a fog address has
+    /// to be signed over one of them for the address to be valid.
+    #[clap(
+        long = "trusted-fog-authority",
+        parse(try_from_str = load_fog_authority_spki),
+        env = "MC_TRUSTED_FOG_AUTHORITIES",
+        use_value_delimiter = true
+    )]
+    pub trusted_fog_authorities: Vec<Vec<u8>>,
+
+    /// Number of blocks past the network tip used as the tombstone block of
+    /// new transactions, when the request does not set one.
+    #[clap(long, default_value = "50", parse(try_from_str = parse_tombstone_offset), env = "MC_TOMBSTONE_OFFSET")]
+    pub tombstone_offset: u64,
+
+    /// How many seconds mobilecoind remembers the idempotency keys of
+    /// SendPayment and SubmitTx requests, and answers replays of those
+    /// requests with their original response.
+    #[clap(long, default_value = "86400", parse(try_from_str = parse_duration_in_seconds), env = "MC_IDEMPOTENCY_RETENTION")]
+    pub idempotency_retention: Duration,
+
     /// Automatically migrate the ledger db (if it exists) into the most recent
     /// version.
     #[clap(long, env = "MC_LEDGER_DB_MIGRATE")]
@@ -105,6 +130,35 @@ pub struct Config {
     pub ip_info_token: String,
 }
 
+/// Load the DER subjectPublicKeyInfo of a fog authority from a PEM file.
+fn load_fog_authority_spki(path: &str) -> Result<Vec<u8>, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|err| format!("Failed reading fog authority {}: {}", path, err))?;
+    let pem = pem::parse(contents)
+        .map_err(|err| format!("Failed parsing fog authority {}: {}", path, err))?;
+    if pem.tag != "PUBLIC KEY" {
+        return Err(format!(
+            "Fog authority {} is not a PEM public key: {}",
+            path, pem.tag
+        ));
+    }
+    Ok(pem.contents)
+}
+
+/// Parse a tombstone offset, which the network must accept.
+fn parse_tombstone_offset(src: &str) -> Result<u64, String> {
+    let offset = src
+        .parse::<u64>()
+        .map_err(|err| format!("Invalid tombstone offset {}: {}", src, err))?;
+    if offset == 0 || offset > MAX_TOMBSTONE_BLOCKS {
+        return Err(format!(
+            "Tombstone offset must be between 1 and {}",
+            MAX_TOMBSTONE_BLOCKS
+        ));
+    }
+    Ok(offset)
+}
+
 fn parse_quorum_set_from_json(src: &str) -> Result<QuorumSet<ResponderId>, String> {
     let quorum_set: QuorumSet<ResponderId> = serde_json::from_str(src)
         .map_err(|err| format!("Error parsing quorum set {}: {:?}", src, err))?;
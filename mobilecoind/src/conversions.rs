@@ -4,16 +4,20 @@
 //! types.
 
 use crate::{
-    payments::{Outlay, TxProposal},
+    payments::{Outlay, OutlayV2, TxProposal, UnsignedTx},
     utxo_store::UnspentTxOut,
 };
 use mc_account_keys::PublicAddress;
 use mc_api::ConversionError;
+use mc_blockchain_types::BlockVersion;
 use mc_common::HashMap;
+use mc_crypto_keys::RistrettoPublic;
+use mc_fog_report_validation::FullyValidatedFogPubkey;
 use mc_mobilecoind_api as api;
 use mc_transaction_core::{
     ring_signature::KeyImage,
-    tx::{Tx, TxOut, TxOutConfirmationNumber},
+    tx::{Tx, TxOut, TxOutConfirmationNumber, TxOutMembershipProof},
+    Amount, TokenId,
 };
 use protobuf::RepeatedField;
 
@@ -79,6 +83,29 @@ impl TryFrom<&api::Outlay> for Outlay {
     }
 }
 
+impl From<&OutlayV2> for api::OutlayV2 {
+    fn from(src: &OutlayV2) -> Self {
+        let mut dst = Self::new();
+
+        dst.set_value(src.amount.value);
+        dst.set_token_id(*src.amount.token_id);
+        dst.set_receiver((&src.receiver).into());
+
+        dst
+    }
+}
+
+impl TryFrom<&api::OutlayV2> for OutlayV2 {
+    type Error = ConversionError;
+
+    fn try_from(src: &api::OutlayV2) -> Result<Self, Self::Error> {
+        let amount = Amount::new(src.value, TokenId::from(src.token_id));
+        let receiver = PublicAddress::try_from(src.get_receiver())?;
+
+        Ok(Self { amount, receiver })
+    }
+}
+
 impl From<&TxProposal> for api::TxProposal {
     fn from(src: &TxProposal) -> api::TxProposal {
         let mut dst = api::TxProposal::new();
@@ -103,6 +130,12 @@ impl From<&TxProposal> for api::TxProposal {
                 .map(|val| val.to_vec())
                 .collect(),
         );
+        dst.set_outlay_shared_secrets(
+            src.outlay_shared_secrets
+                .iter()
+                .map(|shared_secret| shared_secret.into())
+                .collect(),
+        );
 
         dst
     }
@@ -160,12 +193,130 @@ impl TryFrom<&api::TxProposal> for TxProposal {
             })
             .collect::<Result<Vec<TxOutConfirmationNumber>, ConversionError>>()?;
 
+        let outlay_shared_secrets = src
+            .get_outlay_shared_secrets()
+            .iter()
+            .map(RistrettoPublic::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
         Ok(Self {
             utxos,
             outlays,
             tx,
             outlay_index_to_tx_out_index,
             outlay_confirmation_numbers,
+            outlay_shared_secrets,
+        })
+    }
+}
+
+impl From<&UnsignedTx> for api::UnsignedTx {
+    fn from(src: &UnsignedTx) -> api::UnsignedTx {
+        let mut dst = api::UnsignedTx::new();
+
+        dst.set_input_list(RepeatedField::from_vec(
+            src.inputs
+                .iter()
+                .zip(src.rings.iter())
+                .map(|((utxo, membership_proof), ring)| {
+                    let mut input = api::UnsignedTxInput::new();
+                    input.set_utxo(utxo.into());
+                    input.set_membership_proof(membership_proof.into());
+                    input.set_ring(ring.iter().map(|(tx_out, _)| tx_out.into()).collect());
+                    input.set_ring_membership_proofs(
+                        ring.iter().map(|(_, proof)| proof.into()).collect(),
+                    );
+                    input
+                })
+                .collect(),
+        ));
+        dst.set_outlay_list(RepeatedField::from_vec(
+            src.outlays.iter().map(|outlay| outlay.into()).collect(),
+        ));
+        dst.set_fee(src.fee);
+        dst.set_token_id(*src.token_id);
+        dst.set_block_version(*src.block_version);
+        dst.set_tombstone_block(src.tombstone_block);
+        dst.set_change_subaddress(src.change_subaddress);
+        dst.set_fog_pubkey_list(
+            src.fog_pubkeys
+                .iter()
+                .map(|(address, fog_pubkey)| {
+                    let mut resolved = api::ResolvedFogPubkey::new();
+                    resolved.set_address(address.into());
+                    resolved.set_pubkey((&fog_pubkey.pubkey).into());
+                    resolved.set_pubkey_expiry(fog_pubkey.pubkey_expiry);
+                    resolved
+                })
+                .collect(),
+        );
+
+        dst
+    }
+}
+
+impl TryFrom<&api::UnsignedTx> for UnsignedTx {
+    type Error = ConversionError;
+
+    fn try_from(src: &api::UnsignedTx) -> Result<Self, Self::Error> {
+        let mut inputs = Vec::new();
+        let mut rings = Vec::new();
+        for input in src.get_input_list() {
+            if input.get_ring().len() != input.get_ring_membership_proofs().len() {
+                return Err(ConversionError::IndexOutOfBounds);
+            }
+
+            inputs.push((
+                UnspentTxOut::try_from(input.get_utxo())?,
+                TxOutMembershipProof::try_from(input.get_membership_proof())?,
+            ));
+            rings.push(
+                input
+                    .get_ring()
+                    .iter()
+                    .zip(input.get_ring_membership_proofs().iter())
+                    .map(|(tx_out, proof)| {
+                        Ok((
+                            TxOut::try_from(tx_out)?,
+                            TxOutMembershipProof::try_from(proof)?,
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, ConversionError>>()?,
+            );
+        }
+
+        let outlays = src
+            .get_outlay_list()
+            .iter()
+            .map(Outlay::try_from)
+            .collect::<Result<Vec<Outlay>, ConversionError>>()?;
+
+        let block_version =
+            BlockVersion::try_from(src.block_version).map_err(|_| ConversionError::Other)?;
+
+        let fog_pubkeys = src
+            .get_fog_pubkey_list()
+            .iter()
+            .map(|resolved| {
+                let address = PublicAddress::try_from(resolved.get_address())?;
+                let fog_pubkey = FullyValidatedFogPubkey {
+                    pubkey: RistrettoPublic::try_from(resolved.get_pubkey())?,
+                    pubkey_expiry: resolved.pubkey_expiry,
+                };
+                Ok((address, fog_pubkey))
+            })
+            .collect::<Result<HashMap<_, _>, ConversionError>>()?;
+
+        Ok(Self {
+            inputs,
+            rings,
+            outlays,
+            block_version,
+            token_id: TokenId::from(src.token_id),
+            fee: src.fee,
+            change_subaddress: src.change_subaddress,
+            tombstone_block: src.tombstone_block,
+            fog_pubkeys,
         })
     }
 }
@@ -174,14 +325,12 @@ impl TryFrom<&api::TxProposal> for TxProposal {
 mod test {
     use super::*;
     use mc_account_keys::AccountKey;
-    use mc_crypto_keys::RistrettoPublic;
     use mc_ledger_db::{
         test_utils::{create_ledger, create_transaction, initialize_ledger},
         Ledger,
     };
     use mc_transaction_core::{
-        encrypted_fog_hint::ENCRYPTED_FOG_HINT_LEN, tokens::Mob, Amount, BlockVersion,
-        MaskedAmount, Token,
+        encrypted_fog_hint::ENCRYPTED_FOG_HINT_LEN, tokens::Mob, MaskedAmount, Token,
     };
     use mc_util_from_random::FromRandom;
     use rand::{rngs::StdRng, SeedableRng};
@@ -202,6 +351,7 @@ mod test {
             public_key: RistrettoPublic::from_random(&mut rng).into(),
             e_fog_hint: (&[0u8; ENCRYPTED_FOG_HINT_LEN]).into(),
             e_memo: Some(Default::default()),
+            additional_e_fog_hints: Default::default(),
         };
 
         let subaddress_index = 123;
@@ -258,6 +408,29 @@ mod test {
         assert_eq!(rust, Outlay::try_from(&proto).unwrap());
     }
 
+    #[test]
+    fn test_outlay_v2_conversion() {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+        let public_addr = AccountKey::random(&mut rng).default_subaddress();
+
+        // Rust -> Proto
+        let rust = OutlayV2 {
+            amount: Amount::new(1234, TokenId::from(2)),
+            receiver: public_addr.clone(),
+        };
+        let proto = api::OutlayV2::from(&rust);
+
+        assert_eq!(proto.value, 1234);
+        assert_eq!(proto.token_id, 2);
+        assert_eq!(
+            PublicAddress::try_from(proto.get_receiver()).unwrap(),
+            public_addr
+        );
+
+        // Proto -> Rust
+        assert_eq!(rust, OutlayV2::try_from(&proto).unwrap());
+    }
+
     #[test]
     fn test_tx_proposal_conversion() {
         let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
@@ -294,6 +467,7 @@ mod test {
                 public_key: RistrettoPublic::from_random(&mut rng).into(),
                 e_fog_hint: (&[0u8; ENCRYPTED_FOG_HINT_LEN]).into(),
                 e_memo: Some(Default::default()),
+                additional_e_fog_hints: Default::default(),
             };
 
             let subaddress_index = 123;
@@ -323,6 +497,7 @@ mod test {
 
         let outlay_index_to_tx_out_index = HashMap::from_iter(vec![(0, 0)]);
         let outlay_confirmation_numbers = vec![TxOutConfirmationNumber::from([0u8; 32])];
+        let outlay_shared_secrets = vec![RistrettoPublic::from_random(&mut rng)];
 
         // Rust -> Proto
         let rust = TxProposal {
@@ -331,6 +506,7 @@ mod test {
             tx,
             outlay_index_to_tx_out_index,
             outlay_confirmation_numbers,
+            outlay_shared_secrets,
         };
 
         let proto = api::TxProposal::from(&rust);
@@ -353,4 +529,74 @@ mod test {
         // Proto -> Rust
         assert_eq!(rust, TxProposal::try_from(&proto).unwrap());
     }
+
+    #[test]
+    fn test_unsigned_tx_conversion() {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+
+        let mut ledger = create_ledger();
+        let sender = AccountKey::random(&mut rng);
+        initialize_ledger(BlockVersion::MAX, &mut ledger, 1, &sender, &mut rng);
+
+        let tx_outs = ledger.get_block_contents(0).unwrap().outputs;
+        let proofs = ledger
+            .get_tx_out_proof_of_memberships(&(0..tx_outs.len() as u64).collect::<Vec<_>>())
+            .unwrap();
+
+        let utxo = UnspentTxOut {
+            tx_out: tx_outs[0].clone(),
+            subaddress_index: 0,
+            key_image: KeyImage::from(456),
+            value: 789,
+            attempted_spend_height: 0,
+            attempted_spend_tombstone: 0,
+            token_id: *Mob::ID,
+        };
+
+        let recipient = AccountKey::random(&mut rng).default_subaddress();
+        let fog_pubkey = FullyValidatedFogPubkey {
+            pubkey: RistrettoPublic::from_random(&mut rng),
+            pubkey_expiry: 100,
+        };
+
+        // Rust -> Proto
+        let rust = UnsignedTx {
+            inputs: vec![(utxo, proofs[0].clone())],
+            rings: vec![tx_outs.into_iter().zip(proofs.into_iter()).collect()],
+            outlays: vec![Outlay {
+                receiver: recipient.clone(),
+                value: 1234,
+            }],
+            block_version: BlockVersion::MAX,
+            token_id: Mob::ID,
+            fee: 10,
+            change_subaddress: 2,
+            tombstone_block: 50,
+            fog_pubkeys: HashMap::from_iter(vec![(recipient, fog_pubkey)]),
+        };
+
+        let proto = api::UnsignedTx::from(&rust);
+
+        assert_eq!(proto.get_input_list().len(), 1);
+        assert_eq!(
+            proto.get_input_list()[0].get_ring().len(),
+            rust.rings[0].len()
+        );
+        assert_eq!(proto.fee, 10);
+        assert_eq!(proto.tombstone_block, 50);
+        assert_eq!(proto.get_fog_pubkey_list().len(), 1);
+
+        // Proto -> Rust
+        assert_eq!(rust, UnsignedTx::try_from(&proto).unwrap());
+
+        // A ring must have a membership proof for each of its elements.
+        let mut invalid = proto;
+        invalid.mut_input_list()[0]
+            .mut_ring_membership_proofs()
+            .pop();
+        assert_eq!(
+            UnsignedTx::try_from(&invalid),
+            Err(ConversionError::IndexOutOfBounds)
+        );
+    }
 }
@@ -0,0 +1,64 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Prometheus counters.
+//!
+//! These are exposed through the admin server, alongside the gRPC request
+//! counters and latencies tracked by `SVC_COUNTERS` and the ledger database
+//! metrics.
+
+use crate::monitor_store::MonitorId;
+use mc_util_metrics::{register, Histogram, IntCounterVec, IntGauge, IntGaugeVec, OpMetrics, Opts};
+
+lazy_static::lazy_static! {
+    /// Prometheus counters.
+    pub static ref OP_COUNTERS: OpMetrics = OpMetrics::new_and_registered("mobilecoind");
+
+    /// Number of monitors in the database.
+    pub static ref NUM_MONITORS: IntGauge = OP_COUNTERS.gauge("num_monitors");
+
+    /// The size (in bytes) of the mobilecoind database.
+    pub static ref DB_FILE_SIZE: IntGauge = OP_COUNTERS.gauge("db_file_size");
+
+    /// Time it takes a monitor to process a single block.
+    pub static ref BLOCK_PROCESSING_TIME: Histogram = OP_COUNTERS.histogram("block_processing_time");
+
+    /// Number of blocks processed by each monitor since mobilecoind started.
+    pub static ref MONITOR_BLOCKS_PROCESSED: IntCounterVec = {
+        let counter = IntCounterVec::new(
+            Opts::new("mobilecoind_monitor_blocks_processed", "Number of blocks processed by each monitor"),
+            &["monitor_id"],
+        )
+        .unwrap();
+        register(Box::new(counter.clone())).expect("Failed registering monitor counters");
+        counter
+    };
+
+    /// The next block each monitor needs to process.
+    pub static ref MONITOR_NEXT_BLOCK: IntGaugeVec = {
+        let gauge = IntGaugeVec::new(
+            Opts::new("mobilecoind_monitor_next_block", "The next block each monitor needs to process"),
+            &["monitor_id"],
+        )
+        .unwrap();
+        register(Box::new(gauge.clone())).expect("Failed registering monitor gauges");
+        gauge
+    };
+
+    /// Number of transactions submitted to the network, by outcome.
+    pub static ref TX_SUBMISSIONS: IntCounterVec = {
+        let counter = IntCounterVec::new(
+            Opts::new("mobilecoind_tx_submissions", "Number of transactions submitted, by outcome"),
+            &["outcome"],
+        )
+        .unwrap();
+        register(Box::new(counter.clone())).expect("Failed registering tx submission counters");
+        counter
+    };
+}
+
+/// Stop reporting the per-monitor metrics of a monitor that was removed.
+pub fn remove_monitor(monitor_id: &MonitorId) {
+    let label = monitor_id.to_string();
+    let _ = MONITOR_BLOCKS_PROCESSED.remove_label_values(&[&label]);
+    let _ = MONITOR_NEXT_BLOCK.remove_label_values(&[&label]);
+}
@@ -5,21 +5,33 @@
 use crate::{
     db_crypto::DbCryptoProvider,
     error::Error,
+    idempotency_store::{IdempotencyKey, IdempotencyStore, IdempotentRequest},
+    monitor_backup::{BackupProcessedTxOut, BackupSubaddressLabel, MonitorBackup},
+    monitor_notifier::MonitorNotifier,
     monitor_store::{MonitorData, MonitorId, MonitorStore},
-    processed_block_store::{ProcessedBlockStore, ProcessedTxOut},
+    processed_block_store::{ProcessedBlockStore, ProcessedTxOut, SentPayment},
     subaddress_store::{SubaddressId, SubaddressSPKId, SubaddressStore},
     utxo_store::{UtxoId, UtxoStore},
 };
 
 use crate::utxo_store::UnspentTxOut;
-use lmdb::{Environment, Transaction};
+use futures::channel::mpsc;
+use lmdb::{Environment, RwTransaction, Transaction};
+use mc_account_keys::{AccountKey, ViewAccountKey};
 use mc_common::{
     logger::{log, Logger},
-    HashMap,
+    HashMap, HashSet,
 };
+use mc_crypto_keys::CompressedRistrettoPublic;
+use mc_ledger_db::Ledger;
 use mc_transaction_core::ring_signature::KeyImage;
 use mc_util_lmdb::{MetadataStore, MetadataStoreSettings};
-use std::{path::Path, sync::Arc};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 // LMDB Constants
 const MAX_LMDB_FILE_SIZE: usize = 1_099_511_627_776; // 1 TB
@@ -42,12 +54,32 @@ impl MetadataStoreSettings for MobilecoindDbMetadataStoreSettings {
     const DB_NAME: &'static str = "mobilecoind_db_metadata";
 }
 
+/// The outcome of providing the key image of an UnspentTxOut of a watch-only
+/// monitor.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum KeyImageUpdate {
+    /// The monitor has no UnspentTxOut with this public key, either because it
+    /// never received it or because it was already found spent.
+    NotFound,
+
+    /// The key image is not in the ledger yet. The UnspentTxOut is removed
+    /// once a block containing it is processed.
+    Unspent,
+
+    /// The key image was spent in the given block, so the UnspentTxOut was
+    /// removed.
+    Spent(u64),
+}
+
 /// The main mobilecoind database.
 #[derive(Clone)]
 pub struct Database {
     // LMDB Environment (database).
     env: Arc<Environment>,
 
+    /// Path to the database directory.
+    path: PathBuf,
+
     /// Crypto provider, used for managing database encryption.
     crypto_provider: DbCryptoProvider,
 
@@ -63,6 +95,12 @@ pub struct Database {
     /// Processed block store.
     processed_block_store: ProcessedBlockStore,
 
+    /// Idempotency store.
+    idempotency_store: IdempotencyStore,
+
+    /// Notifies the subscribers to monitor changes.
+    monitor_notifier: MonitorNotifier,
+
     /// Logger.
     logger: Logger,
 }
@@ -71,7 +109,7 @@ impl Database {
     pub fn new<P: AsRef<Path>>(path: P, logger: Logger) -> Result<Self, Error> {
         let env = Arc::new(
             Environment::new()
-                .set_max_dbs(10)
+                .set_max_dbs(16)
                 .set_map_size(MAX_LMDB_FILE_SIZE)
                 .open(path.as_ref())?,
         );
@@ -97,18 +135,39 @@ impl Database {
         let subaddress_store = SubaddressStore::new(env.clone(), logger.clone())?;
         let utxo_store = UtxoStore::new(env.clone(), logger.clone())?;
         let processed_block_store = ProcessedBlockStore::new(env.clone(), logger.clone())?;
+        let idempotency_store = IdempotencyStore::new(env.clone(), logger.clone())?;
 
         Ok(Self {
             env,
+            path: path.as_ref().to_path_buf(),
             crypto_provider,
             monitor_store,
             subaddress_store,
             utxo_store,
             processed_block_store,
+            idempotency_store,
+            monitor_notifier: MonitorNotifier::default(),
             logger,
         })
     }
 
+    /// Get notified each time a monitor processes a block or is removed, or
+    /// the ledger grows.
+    pub fn subscribe_to_monitor_changes(&self) -> mpsc::Receiver<()> {
+        self.monitor_notifier.subscribe()
+    }
+
+    /// Notify the subscribers to monitor changes, e.g. because the ledger grew.
+    pub fn notify_monitor_changes(&self) {
+        self.monitor_notifier.notify();
+    }
+
+    /// Get the database file size, in bytes.
+    pub fn db_file_size(&self) -> std::io::Result<u64> {
+        let metadata = fs::metadata(self.path.join("data.mdb"))?;
+        Ok(metadata.len())
+    }
+
     /// Check if data is currently being encrypted.
     pub fn is_db_encrypted(&self) -> bool {
         self.crypto_provider.is_db_encrypted()
@@ -132,8 +191,34 @@ impl Database {
     /// since part of the re-encryption process relies on being able to
     /// decrypt the existing data.
     pub fn re_encrypt(&self, new_password: &[u8]) -> Result<(), Error> {
-        let mut db_txn = self.env.begin_rw_txn()?;
+        let db_txn = self.env.begin_rw_txn()?;
+        self.re_encrypt_in_txn(db_txn, new_password)
+    }
+
+    /// Re-encrypt the encrypted parts of the database with a new password,
+    /// after checking that the current password is correct. The check and the
+    /// re-encryption happen in the same transaction, so concurrent password
+    /// changes cannot bypass the check.
+    pub fn change_password(
+        &self,
+        current_password: &[u8],
+        new_password: &[u8],
+    ) -> Result<(), Error> {
+        let db_txn = self.env.begin_rw_txn()?;
+
+        self.crypto_provider
+            .check_password(&db_txn, current_password)?;
+
+        self.re_encrypt_in_txn(db_txn, new_password)
+    }
 
+    // Re-encrypt the encrypted parts of the database with a new password, and
+    // commit the transaction.
+    fn re_encrypt_in_txn(
+        &self,
+        mut db_txn: RwTransaction<'_>,
+        new_password: &[u8],
+    ) -> Result<(), Error> {
         // Currently only the monitor store stores encrypted data.
         self.monitor_store.re_encrypt(&mut db_txn, new_password)?;
 
@@ -173,10 +258,227 @@ impl Database {
 
         self.processed_block_store.remove(&mut db_txn, id)?;
 
+        self.subaddress_store.remove_labels(&mut db_txn, id)?;
+
         self.monitor_store.remove(&mut db_txn, id)?;
 
         db_txn.commit()?;
+        self.monitor_notifier.notify();
+
+        Ok(())
+    }
+
+    /// Get a backup of a monitor and everything it learned from the blocks it
+    /// processed. The backup does not contain the spend private key.
+    pub fn export_monitor(&self, id: &MonitorId) -> Result<MonitorBackup, Error> {
+        let db_txn = self.env.begin_ro_txn()?;
+
+        let data = self.monitor_store.get_data(&db_txn, id)?;
+
+        let mut utxos = Vec::new();
+        for index in data.subaddress_indexes() {
+            utxos.extend(self.utxo_store.get_utxos(&db_txn, id, index)?);
+        }
+
+        let processed_tx_outs = self
+            .processed_block_store
+            .get_processed_tx_outs(&db_txn, id)?
+            .into_iter()
+            .map(|(block_index, processed_tx_out)| BackupProcessedTxOut {
+                block_index,
+                processed_tx_out,
+            })
+            .collect();
+
+        let labels = self
+            .subaddress_store
+            .get_labels(&db_txn, id)?
+            .into_iter()
+            .map(|(subaddress_index, label)| BackupSubaddressLabel {
+                subaddress_index,
+                label,
+            })
+            .collect();
+
+        Ok(MonitorBackup {
+            view_account_key: data.view_account_key()?,
+            watch_only: data.is_watch_only(),
+            default_public_address: data.default_public_address.clone(),
+            first_subaddress: data.first_subaddress,
+            num_subaddresses: data.num_subaddresses,
+            first_block: data.first_block,
+            next_block: data.next_block,
+            name: data.name,
+            utxos,
+            processed_tx_outs,
+            labels,
+        })
+    }
+
+    /// Add a monitor from a backup, which resumes processing blocks where the
+    /// exported monitor stopped. Backups of monitors that were not watch-only
+    /// need the account key of the monitor, since it is not part of the
+    /// backup.
+    pub fn import_monitor(
+        &self,
+        backup: &MonitorBackup,
+        account_key: Option<AccountKey>,
+    ) -> Result<MonitorId, Error> {
+        mc_common::trace_time!(self.logger, "import_monitor");
+
+        let mut data = match (backup.watch_only, account_key) {
+            (false, Some(account_key)) => {
+                if ViewAccountKey::from(&account_key) != backup.view_account_key {
+                    return Err(Error::BackupAccountKeyMismatch);
+                }
+                MonitorData::new(
+                    account_key,
+                    backup.first_subaddress,
+                    backup.num_subaddresses,
+                    backup.first_block,
+                    &backup.name,
+                )?
+            }
+            (false, None) => return Err(Error::BackupAccountKeyNeeded),
+            (true, None) => MonitorData::new_watch_only(
+                backup.view_account_key.clone(),
+                backup.default_public_address.clone(),
+                backup.first_subaddress,
+                backup.num_subaddresses,
+                backup.first_block,
+                &backup.name,
+            )?,
+            (true, Some(_)) => {
+                return Err(Error::InvalidArgument(
+                    "account_key".to_string(),
+                    "must not be set for backups of watch-only monitors".to_string(),
+                ))
+            }
+        };
+        data.next_block = backup.next_block;
+
+        let mut db_txn = self.env.begin_rw_txn()?;
+        let id = self.monitor_store.add(&mut db_txn, &data)?;
+
+        for index in data.subaddress_indexes() {
+            self.subaddress_store
+                .insert(&mut db_txn, &id, &data, index)?;
+        }
+
+        for utxo in backup.utxos.iter() {
+            self.utxo_store
+                .append_utxo(&mut db_txn, &id, utxo.subaddress_index, utxo)?;
+        }
+
+        for processed in backup.processed_tx_outs.iter() {
+            self.processed_block_store.insert(
+                &mut db_txn,
+                &id,
+                processed.block_index,
+                &processed.processed_tx_out,
+            )?;
+        }
+
+        for label in backup.labels.iter() {
+            self.subaddress_store.set_label(
+                &mut db_txn,
+                &id,
+                label.subaddress_index,
+                &label.label,
+            )?;
+        }
+
+        db_txn.commit()?;
+        Ok(id)
+    }
+
+    /// Look up the request previously made with an idempotency key.
+    /// If there is none, or its retention window is over, the key is reserved
+    /// for a new request and None is returned. Reusing a key for a different
+    /// request is an error.
+    pub fn reserve_idempotency_key(
+        &self,
+        key: &IdempotencyKey,
+        fingerprint: &[u8],
+        retention: Duration,
+    ) -> Result<Option<IdempotentRequest>, Error> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+
+        let mut db_txn = self.env.begin_rw_txn()?;
+
+        // Expired requests are removed whenever a key is reserved, so the store
+        // only holds the requests of the retention window.
+        self.idempotency_store
+            .prune_expired(&mut db_txn, now, retention)?;
+
+        let result = match self.idempotency_store.get(&db_txn, key)? {
+            Some(request) if request.fingerprint != fingerprint => {
+                Err(Error::IdempotencyKeyMismatch)
+            }
+            Some(request) => Ok(Some(request)),
+            None => {
+                let request = IdempotentRequest {
+                    created_at: now,
+                    fingerprint: fingerprint.to_vec(),
+                    ..Default::default()
+                };
+                self.idempotency_store.put(&mut db_txn, key, &request)?;
+                Ok(None)
+            }
+        };
+
+        db_txn.commit()?;
+        result
+    }
+
+    /// Record the serialized transaction built for the request made with an
+    /// idempotency key.
+    pub fn set_idempotent_tx_proposal(
+        &self,
+        key: &IdempotencyKey,
+        tx_proposal: &[u8],
+    ) -> Result<(), Error> {
+        self.update_idempotent_request(key, |request| {
+            request.tx_proposal = tx_proposal.to_vec();
+        })
+    }
+
+    /// Record the serialized response to the request made with an idempotency
+    /// key.
+    pub fn set_idempotent_response(
+        &self,
+        key: &IdempotencyKey,
+        response: &[u8],
+    ) -> Result<(), Error> {
+        self.update_idempotent_request(key, |request| {
+            request.response = response.to_vec();
+        })
+    }
+
+    /// Forget an idempotency key, so that the request can be retried with it.
+    pub fn release_idempotency_key(&self, key: &IdempotencyKey) -> Result<(), Error> {
+        let mut db_txn = self.env.begin_rw_txn()?;
+        self.idempotency_store.remove(&mut db_txn, key)?;
+        db_txn.commit()?;
+        Ok(())
+    }
 
+    fn update_idempotent_request(
+        &self,
+        key: &IdempotencyKey,
+        update: impl FnOnce(&mut IdempotentRequest),
+    ) -> Result<(), Error> {
+        let mut db_txn = self.env.begin_rw_txn()?;
+        let mut request = self
+            .idempotency_store
+            .get(&db_txn, key)?
+            .ok_or(Error::IdempotencyKeyNotFound)?;
+        update(&mut request);
+        self.idempotency_store.put(&mut db_txn, key, &request)?;
+        db_txn.commit()?;
         Ok(())
     }
 
@@ -204,6 +506,85 @@ impl Database {
             .get_index_data(&db_txn, subaddress_spk)
     }
 
+    /// Label the first subaddress of a monitor that does not have a label yet,
+    /// and return its index.
+    pub fn allocate_subaddress(&self, monitor_id: &MonitorId, label: &str) -> Result<u64, Error> {
+        if label.is_empty() {
+            return Err(Error::InvalidArgument(
+                "label".to_string(),
+                "must not be empty".to_string(),
+            ));
+        }
+
+        let mut db_txn = self.env.begin_rw_txn()?;
+
+        let monitor_data = self.monitor_store.get_data(&db_txn, monitor_id)?;
+        let labeled_indexes: HashSet<u64> = self
+            .subaddress_store
+            .get_labels(&db_txn, monitor_id)?
+            .into_iter()
+            .map(|(index, _label)| index)
+            .collect();
+        let index = monitor_data
+            .subaddress_indexes()
+            .find(|index| !labeled_indexes.contains(index))
+            .ok_or(Error::NoUnlabeledSubaddress)?;
+
+        self.subaddress_store
+            .set_label(&mut db_txn, monitor_id, index, label)?;
+
+        db_txn.commit()?;
+        Ok(index)
+    }
+
+    /// Set the label of a subaddress of a monitor. An empty label removes it.
+    pub fn set_subaddress_label(
+        &self,
+        monitor_id: &MonitorId,
+        index: u64,
+        label: &str,
+    ) -> Result<(), Error> {
+        let mut db_txn = self.env.begin_rw_txn()?;
+
+        let monitor_data = self.monitor_store.get_data(&db_txn, monitor_id)?;
+        if !monitor_data.subaddress_indexes().contains(&index) {
+            return Err(Error::InvalidArgument(
+                "index".to_string(),
+                format!("subaddress {} is not watched by the monitor", index),
+            ));
+        }
+
+        self.subaddress_store
+            .set_label(&mut db_txn, monitor_id, index, label)?;
+
+        db_txn.commit()?;
+        Ok(())
+    }
+
+    /// Get the label of a subaddress of a monitor, if it has one.
+    pub fn get_subaddress_label(
+        &self,
+        monitor_id: &MonitorId,
+        index: u64,
+    ) -> Result<Option<String>, Error> {
+        let db_txn = self.env.begin_ro_txn()?;
+        self.subaddress_store.get_label(&db_txn, monitor_id, index)
+    }
+
+    /// Get the labeled subaddresses of a monitor, as (index, label) pairs
+    /// ordered by index.
+    pub fn get_subaddress_labels(
+        &self,
+        monitor_id: &MonitorId,
+    ) -> Result<Vec<(u64, String)>, Error> {
+        let db_txn = self.env.begin_ro_txn()?;
+
+        // Make sure the monitor exists.
+        self.monitor_store.get_data(&db_txn, monitor_id)?;
+
+        self.subaddress_store.get_labels(&db_txn, monitor_id)
+    }
+
     pub fn get_subaddress_id_by_utxo_id(&self, utxo_id: &UtxoId) -> Result<SubaddressId, Error> {
         let db_txn = self.env.begin_ro_txn()?;
         self.utxo_store
@@ -219,6 +600,19 @@ impl Database {
         self.utxo_store.get_utxos(&db_txn, monitor_id, index)
     }
 
+    pub fn get_utxos_page_for_subaddress(
+        &self,
+        monitor_id: &MonitorId,
+        index: u64,
+        start_utxo_id: Option<&UtxoId>,
+        limit: usize,
+        filter: impl Fn(&UnspentTxOut) -> bool,
+    ) -> Result<(Vec<UnspentTxOut>, Option<UtxoId>), Error> {
+        let db_txn = self.env.begin_ro_txn()?;
+        self.utxo_store
+            .get_utxos_page(&db_txn, monitor_id, index, start_utxo_id, limit, filter)
+    }
+
     pub fn update_attempted_spend(
         &self,
         utxo_ids: &[UtxoId],
@@ -292,6 +686,7 @@ impl Database {
 
         // Commit.
         db_txn.commit()?;
+        self.monitor_notifier.notify();
 
         // Success.
         if discovered_utxos.is_empty() && removed_utxos.is_empty() {
@@ -316,6 +711,106 @@ impl Database {
         Ok(())
     }
 
+    /// Set the key images of UnspentTxOuts of a watch-only monitor, given as
+    /// (TxOut public key, key image) pairs. Key images can only be computed
+    /// with the spend private key, so watch-only monitors store their
+    /// UnspentTxOuts under their TxOut public key until the holder of the
+    /// spend private key provides their key images. The key images are
+    /// trusted, since they cannot be checked without the spend private key.
+    ///
+    /// UnspentTxOuts whose key image is already in the ledger are removed, and
+    /// recorded as spent in the block they were spent in if the monitor has
+    /// processed it. The others are found spent when the monitor processes
+    /// the block that spends them.
+    pub fn set_watch_only_key_images(
+        &self,
+        monitor_id: &MonitorId,
+        key_images: &[(CompressedRistrettoPublic, KeyImage)],
+        ledger_db: &impl Ledger,
+    ) -> Result<Vec<KeyImageUpdate>, Error> {
+        let mut db_txn = self.env.begin_rw_txn()?;
+
+        let monitor_data = self.monitor_store.get_data(&db_txn, monitor_id)?;
+        if !monitor_data.is_watch_only() {
+            return Err(Error::InvalidArgument(
+                "monitor_id".to_string(),
+                "key images can only be set for watch-only monitors".to_string(),
+            ));
+        }
+
+        // The ledger is checked while the database is locked for writing, so that
+        // the monitor cannot process a block in between: key images spent before
+        // its next block are found here, and later ones when it processes them.
+        let mut updates = Vec::with_capacity(key_images.len());
+        for (tx_public_key, key_image) in key_images {
+            let placeholder = KeyImage::from(*tx_public_key.as_bytes());
+
+            let owner = |utxo_id: &UtxoId| match self
+                .utxo_store
+                .get_subaddress_id_by_utxo_id(&db_txn, utxo_id)
+            {
+                Ok(subaddress_id) => Ok(subaddress_id.monitor_id == *monitor_id),
+                Err(Error::UtxoIdNotFound) => Ok(false),
+                Err(err) => Err(err),
+            };
+            if !owner(&UtxoId::from(&placeholder))? {
+                // Key images that were already set are left alone.
+                updates.push(if owner(&UtxoId::from(key_image))? {
+                    KeyImageUpdate::Unspent
+                } else {
+                    KeyImageUpdate::NotFound
+                });
+                continue;
+            }
+
+            let mut utxo = self
+                .utxo_store
+                .remove_utxos_by_key_images(&mut db_txn, monitor_id, &[placeholder])?
+                .pop()
+                .ok_or(Error::UtxoIdNotFound)?;
+            utxo.key_image = *key_image;
+
+            match ledger_db.check_key_image(key_image)? {
+                Some(block_index) => {
+                    if block_index < monitor_data.next_block {
+                        let mut processed_tx_out = ProcessedTxOut::from_spent_utxo(&utxo);
+                        processed_tx_out.payment =
+                            self.processed_block_store.take_pending_payment(
+                                &mut db_txn,
+                                monitor_id,
+                                key_image,
+                                |key_image| {
+                                    ledger_db.check_key_image(key_image).ok().flatten()
+                                        == Some(block_index)
+                                },
+                            )?;
+                        self.processed_block_store.insert(
+                            &mut db_txn,
+                            monitor_id,
+                            block_index,
+                            &processed_tx_out,
+                        )?;
+                    }
+                    updates.push(KeyImageUpdate::Spent(block_index));
+                }
+                None => {
+                    self.utxo_store.append_utxo(
+                        &mut db_txn,
+                        monitor_id,
+                        utxo.subaddress_index,
+                        &utxo,
+                    )?;
+                    updates.push(KeyImageUpdate::Unspent);
+                }
+            }
+        }
+
+        db_txn.commit()?;
+        self.monitor_notifier.notify();
+
+        Ok(updates)
+    }
+
     /// Get processed block information for a given (monitor id, block number).
     pub fn get_processed_block(
         &self,
@@ -342,12 +837,59 @@ impl Database {
         self.processed_block_store
             .get_processed_block(&db_txn, monitor_id, block_num)
     }
+
+    /// Get a page of the history of TxOuts received and spent by a given
+    /// monitor, as (block index, processed tx out) pairs ordered by block
+    /// index, along with the total number of entries in the history.
+    pub fn get_transaction_history(
+        &self,
+        monitor_id: &MonitorId,
+        offset: u64,
+        limit: u64,
+        newest_first: bool,
+    ) -> Result<(Vec<(u64, ProcessedTxOut)>, u64), Error> {
+        let db_txn = self.env.begin_ro_txn()?;
+
+        // Make sure the monitor exists.
+        self.monitor_store.get_data(&db_txn, monitor_id)?;
+
+        self.processed_block_store.get_processed_tx_outs_page(
+            &db_txn,
+            monitor_id,
+            offset,
+            limit,
+            newest_first,
+        )
+    }
+
+    /// Record a payment submitted by a given monitor, so that its history
+    /// shows the recipients and the fee of the payment once its inputs are
+    /// found spent.
+    pub fn add_pending_payment(
+        &self,
+        monitor_id: &MonitorId,
+        payment: &SentPayment,
+    ) -> Result<(), Error> {
+        let mut db_txn = self.env.begin_rw_txn()?;
+
+        // Make sure the monitor exists.
+        self.monitor_store.get_data(&db_txn, monitor_id)?;
+
+        self.processed_block_store
+            .add_pending_payment(&mut db_txn, monitor_id, payment)?;
+
+        db_txn.commit()?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{error::Error, test_utils::get_test_databases};
+    use crate::{
+        db_crypto::DbCryptoError, error::Error, idempotency_store::idempotency_key,
+        test_utils::get_test_databases,
+    };
     use mc_account_keys::AccountKey;
     use mc_blockchain_types::BlockVersion;
     use mc_common::logger::{test_with_logger, Logger};
@@ -518,6 +1060,149 @@ mod test {
         );
     }
 
+    // Test that changing the password requires the current password.
+    #[test_with_logger]
+    fn test_change_password(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([124u8; 32]);
+        let account_key = AccountKey::random(&mut rng);
+
+        let mobilecoind_db_tmp =
+            TempDir::new("mobilecoind_db").expect("Could not make tempdir for mobilecoind db");
+        let mobilecoind_db_path = mobilecoind_db_tmp
+            .path()
+            .to_str()
+            .expect("Could not get path as string");
+
+        let mobilecoind_db = Database::new(mobilecoind_db_path, logger.clone())
+            .expect("failed creating new mobilecoind db");
+
+        let monitor_data = MonitorData::new(
+            account_key,
+            0,  // first_subaddress
+            10, // num_subaddresses
+            0,  // first_block
+            "", // name
+        )
+        .unwrap();
+        let monitor_id = mobilecoind_db
+            .add_monitor(&monitor_data)
+            .expect("failed adding monitor");
+
+        // The current password of an unencrypted db is empty.
+        assert!(matches!(
+            mobilecoind_db.change_password(&[10; 32], &[11; 32]),
+            Err(Error::DbCrypto(DbCryptoError::InvalidPassword))
+        ));
+        assert!(!mobilecoind_db.is_db_encrypted());
+
+        mobilecoind_db.change_password(&[], &[10; 32]).unwrap();
+        assert!(mobilecoind_db.is_db_encrypted());
+
+        // The wrong current password leaves the db untouched.
+        assert!(matches!(
+            mobilecoind_db.change_password(&[], &[11; 32]),
+            Err(Error::DbCrypto(DbCryptoError::InvalidPassword))
+        ));
+        assert!(matches!(
+            mobilecoind_db.change_password(&[12; 32], &[11; 32]),
+            Err(Error::DbCrypto(DbCryptoError::InvalidPassword))
+        ));
+
+        mobilecoind_db
+            .change_password(&[10; 32], &[11; 32])
+            .unwrap();
+
+        // Only the new password unlocks the db.
+        let mobilecoind_db =
+            Database::new(mobilecoind_db_path, logger).expect("failed creating new mobilecoind db");
+        assert!(mobilecoind_db.check_and_store_password(&[10; 32]).is_err());
+        mobilecoind_db.check_and_store_password(&[11; 32]).unwrap();
+        assert_eq!(
+            mobilecoind_db.get_monitor_map().unwrap(),
+            HashMap::from_iter(vec![(monitor_id, monitor_data)])
+        );
+
+        // Changing to an empty password disables encryption.
+        mobilecoind_db.change_password(&[11; 32], &[]).unwrap();
+        assert!(!mobilecoind_db.is_db_encrypted());
+        assert!(mobilecoind_db.is_unlocked());
+    }
+
+    // Idempotency keys return the request previously made with them, until their
+    // retention window is over.
+    #[test_with_logger]
+    fn test_idempotency_keys(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([123u8; 32]);
+        let (_ledger_db, mobilecoind_db) =
+            get_test_databases(BlockVersion::ZERO, 3, &[], 10, logger.clone(), &mut rng);
+
+        let retention = Duration::from_secs(3600);
+        let key = idempotency_key("SendPayment", "withdrawal-1");
+
+        // The same key is a different key for another method.
+        assert_ne!(key, idempotency_key("SubmitTx", "withdrawal-1"));
+
+        // The first use of a key reserves it.
+        assert_eq!(
+            mobilecoind_db
+                .reserve_idempotency_key(&key, b"request", retention)
+                .unwrap(),
+            None
+        );
+
+        // Replays see the pending request, and then its results.
+        let request = mobilecoind_db
+            .reserve_idempotency_key(&key, b"request", retention)
+            .unwrap()
+            .unwrap();
+        assert_eq!(request.fingerprint, b"request");
+        assert!(request.tx_proposal.is_empty());
+        assert!(request.response.is_empty());
+
+        mobilecoind_db
+            .set_idempotent_tx_proposal(&key, b"tx_proposal")
+            .unwrap();
+        mobilecoind_db
+            .set_idempotent_response(&key, b"response")
+            .unwrap();
+        let request = mobilecoind_db
+            .reserve_idempotency_key(&key, b"request", retention)
+            .unwrap()
+            .unwrap();
+        assert_eq!(request.tx_proposal, b"tx_proposal");
+        assert_eq!(request.response, b"response");
+
+        // The key cannot be used for a different request.
+        assert!(matches!(
+            mobilecoind_db.reserve_idempotency_key(&key, b"other request", retention),
+            Err(Error::IdempotencyKeyMismatch)
+        ));
+
+        // Once the retention window is over, the key can be reused.
+        assert_eq!(
+            mobilecoind_db
+                .reserve_idempotency_key(&key, b"other request", Duration::ZERO)
+                .unwrap(),
+            None
+        );
+
+        // Released keys can be reused.
+        mobilecoind_db.release_idempotency_key(&key).unwrap();
+        assert_eq!(
+            mobilecoind_db
+                .reserve_idempotency_key(&key, b"request", retention)
+                .unwrap(),
+            None
+        );
+
+        // Only reserved keys can be updated.
+        let unknown_key = idempotency_key("SendPayment", "withdrawal-2");
+        assert!(matches!(
+            mobilecoind_db.set_idempotent_response(&unknown_key, b"response"),
+            Err(Error::IdempotencyKeyNotFound)
+        ));
+    }
+
     // Inserting a monitor that overlaps subaddresses of another monitor should
     // result in an error.
     #[test_with_logger]
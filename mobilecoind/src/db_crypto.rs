@@ -133,27 +133,47 @@ impl DbCryptoProvider {
     /// db, and if so store it for future encryption/decryption operations.
     pub fn check_and_store_password(&self, password: &[u8]) -> Result<(), DbCryptoError> {
         let mut state = self.state.lock().expect("mutex poisoned");
+        let db_txn = self.env.begin_ro_txn()?;
+        self.check_password(&db_txn, password)?;
         if state.is_db_encrypted {
-            // Database is encrypted, see if we can decrypt our test value with the provided
-            // password.
-            let db_txn = self.env.begin_ro_txn()?;
-            let test_val = db_txn.get(self.database, &ENCRYPTION_STATE_KEY.as_bytes())?;
-            let expected_val =
-                self.encrypt_with_password(password, ENCRYPTION_STATE_VAL.as_bytes())?;
-            if test_val == expected_val {
-                state.encryption_key = password.to_vec();
-                Ok(())
-            } else {
-                Err(DbCryptoError::InvalidPassword)
-            }
+            state.encryption_key = password.to_vec();
         } else {
-            // Db is not encrypted, password should be empty.
-            if password.is_empty() {
-                assert!(state.encryption_key.is_empty());
-                Ok(())
-            } else {
-                Err(DbCryptoError::InvalidPassword)
+            assert!(state.encryption_key.is_empty());
+        }
+        Ok(())
+    }
+
+    /// Check if a given password is the password used to encrypt data in the
+    /// db, as seen by a transaction, without storing it.
+    pub fn check_password(
+        &self,
+        db_txn: &impl Transaction,
+        password: &[u8],
+    ) -> Result<(), DbCryptoError> {
+        match db_txn.get(self.database, &ENCRYPTION_STATE_KEY.as_bytes()) {
+            Ok(test_val) => {
+                // Database is encrypted, see if we can decrypt our test value with the provided
+                // password.
+                if password.len() != PASSWORD_LEN {
+                    return Err(DbCryptoError::InvalidPassword);
+                }
+                let expected_val =
+                    self.encrypt_with_password(password, ENCRYPTION_STATE_VAL.as_bytes())?;
+                if test_val == expected_val {
+                    Ok(())
+                } else {
+                    Err(DbCryptoError::InvalidPassword)
+                }
+            }
+            Err(LmdbError::NotFound) => {
+                // Db is not encrypted, password should be empty.
+                if password.is_empty() {
+                    Ok(())
+                } else {
+                    Err(DbCryptoError::InvalidPassword)
+                }
             }
+            Err(err) => Err(err.into()),
         }
     }
 
@@ -2,13 +2,16 @@
 
 //! Errors generated by the mobilecoind system
 
-use crate::db_crypto::DbCryptoError;
+#[cfg(feature = "hardware-wallet")]
+use crate::hardware_wallet::HardwareWalletError;
+use crate::{db_crypto::DbCryptoError, monitor_backup::MonitorBackupError};
 use displaydoc::Display;
 use lmdb::Error as LmdbError;
 use mc_connection::Error as ConnectionError;
 use mc_consensus_api::ConversionError;
 use mc_crypto_keys::KeyError;
 use mc_ledger_db::Error as LedgerDbError;
+use mc_transaction_core::SignedContingentInputError;
 use mc_util_lmdb::MetadataStoreError;
 use mc_util_serial::{decode::Error as DecodeError, encode::Error as EncodeError};
 use prost::DecodeError as ProstDecodeError;
@@ -64,6 +67,9 @@ pub enum Error {
     /// An entry in SubaddressStore already exists for this index
     SubaddressSPKIdExists,
 
+    /// All the subaddresses of the monitor already have a label
+    NoUnlabeledSubaddress,
+
     /// Got transactions data but no key images - this should never happen
     MissingKeyImagesInLedgerDb,
 
@@ -111,6 +117,47 @@ pub enum Error {
 
     /// Db encryption: {0}
     DbCrypto(DbCryptoError),
+
+    /// Monitor backup: {0}
+    MonitorBackup(MonitorBackupError),
+
+    /// The backup is of a monitor with an account key, which must be provided
+    /// to import it
+    BackupAccountKeyNeeded,
+
+    /// The account key does not match the view key of the backup
+    BackupAccountKeyMismatch,
+
+    /// The monitor is watch-only and cannot build transactions
+    WatchOnlyMonitor,
+
+    /// The monitor data has neither an account key nor a view key
+    MonitorKeysMissing,
+
+    /// The signed transaction does not match the unsigned transaction: {0}
+    SignedTxMismatch(String),
+
+    /// Signed contingent input: {0}
+    SignedContingentInput(SignedContingentInputError),
+
+    /// The signed contingent input was already spent
+    SignedContingentInputSpent,
+
+    /// Tombstone block {0} is too near: it must be after the next block {1}
+    TombstoneTooNear(u64, u64),
+
+    /// Tombstone block {0} is too far: it cannot be after block {1}
+    TombstoneTooFar(u64, u64),
+
+    /// The idempotency key was already used for a different request
+    IdempotencyKeyMismatch,
+
+    /// No request was found for the idempotency key
+    IdempotencyKeyNotFound,
+
+    /// Hardware wallet: {0}
+    #[cfg(feature = "hardware-wallet")]
+    HardwareWallet(HardwareWalletError),
 }
 
 impl From<RetryError<ConnectionError>> for Error {
@@ -184,3 +231,22 @@ impl From<DbCryptoError> for Error {
         Self::DbCrypto(e)
     }
 }
+
+impl From<MonitorBackupError> for Error {
+    fn from(e: MonitorBackupError) -> Self {
+        Self::MonitorBackup(e)
+    }
+}
+
+impl From<SignedContingentInputError> for Error {
+    fn from(e: SignedContingentInputError) -> Self {
+        Self::SignedContingentInput(e)
+    }
+}
+
+#[cfg(feature = "hardware-wallet")]
+impl From<HardwareWalletError> for Error {
+    fn from(e: HardwareWalletError) -> Self {
+        Self::HardwareWallet(e)
+    }
+}
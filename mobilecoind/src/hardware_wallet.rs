@@ -0,0 +1,94 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Signing transactions with a hardware wallet.
+//!
+//! A hardware wallet holds the spend private key of an account and never
+//! reveals it. It shares the view private key, which lets `mobilecoind` track
+//! the account with a watch-only monitor, shows each output of a transaction to
+//! the user for confirmation, and then signs the rings of the transaction.
+//!
+//! The device transport is provided by implementing [HardwareWallet], and the
+//! device is attached with [TransactionsManager::set_hardware_wallet].
+//! Watch-only monitors whose view key matches the one of the device then build
+//! their transactions by delegating ring signing to the device.
+//!
+//! [TransactionsManager::set_hardware_wallet]: crate::payments::TransactionsManager::set_hardware_wallet
+
+use displaydoc::Display;
+use mc_account_keys::{PublicAddress, ViewAccountKey};
+use mc_crypto_ring_signature_signer::{Error as RingSignerError, RingSigner, SignableInputRing};
+use mc_transaction_core::{
+    ring_signature::{CryptoRngCore, RingMLSAG, Scalar},
+    Amount,
+};
+
+/// An error reported by a hardware wallet.
+#[derive(Clone, Debug, Display, Eq, PartialEq)]
+pub enum HardwareWalletError {
+    /// Failed communicating with the device: {0}
+    Connection(String),
+
+    /// The user rejected the transaction on the device
+    Rejected,
+
+    /// The device failed: {0}
+    Device(String),
+}
+
+impl From<HardwareWalletError> for RingSignerError {
+    fn from(src: HardwareWalletError) -> Self {
+        Self::ConnectionFailed(src.to_string())
+    }
+}
+
+/// The hardware wallet signing protocol.
+pub trait HardwareWallet: Send + Sync {
+    /// Get the view private key and spend public key of the account on the
+    /// device.
+    fn view_account_key(&self) -> Result<ViewAccountKey, HardwareWalletError>;
+
+    /// Show an output of the transaction being built to the user, and wait for
+    /// them to confirm it. Returns [HardwareWalletError::Rejected] if they do
+    /// not.
+    fn confirm_output(
+        &self,
+        receiver: &PublicAddress,
+        amount: Amount,
+    ) -> Result<(), HardwareWalletError>;
+
+    /// Sign a ring. The one-time private key of the real input is derived by
+    /// the device from the subaddress index in the input secret, and the
+    /// device provides its own randomness.
+    fn sign_ring(
+        &self,
+        message: &[u8],
+        signable_ring: &SignableInputRing,
+        output_blinding: Scalar,
+    ) -> Result<RingMLSAG, HardwareWalletError>;
+}
+
+/// A RingSigner that delegates signing to a hardware wallet.
+pub struct HardwareWalletRingSigner<'a> {
+    wallet: &'a dyn HardwareWallet,
+}
+
+impl<'a> HardwareWalletRingSigner<'a> {
+    /// Create a new ring signer for the given device.
+    pub fn new(wallet: &'a dyn HardwareWallet) -> Self {
+        Self { wallet }
+    }
+}
+
+impl RingSigner for HardwareWalletRingSigner<'_> {
+    fn sign(
+        &self,
+        message: &[u8],
+        signable_ring: &SignableInputRing,
+        output_blinding: Scalar,
+        _rng: &mut dyn CryptoRngCore,
+    ) -> Result<RingMLSAG, RingSignerError> {
+        Ok(self
+            .wallet
+            .sign_ring(message, signable_ring, output_blinding)?)
+    }
+}
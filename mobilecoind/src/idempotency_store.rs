@@ -0,0 +1,165 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Database storage for idempotency keys
+//! * Maps the idempotency key a caller attached to a payment request to what
+//!   mobilecoind did with that request: the transaction it submitted, and the
+//!   response it returned. Replays of the request are answered from this store,
+//!   instead of building and submitting a new transaction.
+//! * Records are kept for a retention window, after which the key can be
+//!   reused.
+
+use crate::{database_key::DatabaseByteArrayKey, error::Error};
+
+use lmdb::{Cursor, Database, DatabaseFlags, Environment, RwTransaction, Transaction, WriteFlags};
+use mc_common::logger::{log, Logger};
+use mc_crypto_hashes::{Blake2b256, Digest};
+use prost::Message;
+use std::{sync::Arc, time::Duration};
+
+// LMDB Database Names
+pub const IDEMPOTENCY_KEY_TO_REQUEST_DB_NAME: &str =
+    "mobilecoind_db:idempotency_store:key_to_request";
+
+/// Domain tag for hashing idempotency keys.
+pub const MOBILECOIND_IDEMPOTENCY_KEY_DOMAIN_TAG: &str = "mc_mobilecoind_idempotency_key";
+
+/// Type used as the key in the key_to_request database.
+/// This is a hash of the method name and of the caller's idempotency key, so
+/// the same key can be used with different methods.
+pub type IdempotencyKey = DatabaseByteArrayKey;
+
+/// Get the IdempotencyKey of the idempotency key a caller attached to a request
+/// to a method.
+pub fn idempotency_key(method: &str, key: &str) -> IdempotencyKey {
+    let mut hasher = Blake2b256::new();
+    hasher.update(MOBILECOIND_IDEMPOTENCY_KEY_DOMAIN_TAG);
+    hasher.update((method.len() as u64).to_le_bytes());
+    hasher.update(method);
+    hasher.update(key);
+    let result: [u8; 32] = hasher.finalize().into();
+    IdempotencyKey::from(result)
+}
+
+/// A request made with an idempotency key.
+#[derive(Clone, Eq, PartialEq, Message)]
+pub struct IdempotentRequest {
+    /// When the request was first received, in seconds since the UNIX epoch.
+    #[prost(uint64, tag = "1")]
+    pub created_at: u64,
+
+    /// A hash of the request, used to refuse reusing a key for a different
+    /// request.
+    #[prost(bytes, tag = "2")]
+    pub fingerprint: Vec<u8>,
+
+    /// The serialized transaction that was (or is being) submitted for the
+    /// request, empty until it is built.
+    #[prost(bytes, tag = "3")]
+    pub tx_proposal: Vec<u8>,
+
+    /// The serialized response to the request, empty until the transaction
+    /// was submitted successfully.
+    #[prost(bytes, tag = "4")]
+    pub response: Vec<u8>,
+}
+
+impl IdempotentRequest {
+    /// Whether the retention window of the request is over at `now`.
+    pub fn is_expired(&self, now: u64, retention: Duration) -> bool {
+        now >= self.created_at.saturating_add(retention.as_secs())
+    }
+}
+
+#[derive(Clone)]
+pub struct IdempotencyStore {
+    /// Retain a reference to the Environment so the Database handles are valid.
+    _env: Arc<Environment>,
+
+    /// Mapping of IdempotencyKey -> IdempotentRequest
+    key_to_request: Database,
+
+    /// Logger.
+    logger: Logger,
+}
+
+impl IdempotencyStore {
+    pub fn new(env: Arc<Environment>, logger: Logger) -> Result<Self, Error> {
+        let key_to_request = env.create_db(
+            Some(IDEMPOTENCY_KEY_TO_REQUEST_DB_NAME),
+            DatabaseFlags::empty(),
+        )?;
+        Ok(Self {
+            _env: env,
+            key_to_request,
+            logger,
+        })
+    }
+
+    /// Get the request made with an idempotency key, if any.
+    pub fn get(
+        &self,
+        db_txn: &impl Transaction,
+        key: &IdempotencyKey,
+    ) -> Result<Option<IdempotentRequest>, Error> {
+        match db_txn.get(self.key_to_request, key) {
+            Ok(value_bytes) => Ok(Some(mc_util_serial::decode(value_bytes)?)),
+            Err(lmdb::Error::NotFound) => Ok(None),
+            Err(err) => Err(Error::Lmdb(err)),
+        }
+    }
+
+    /// Insert or replace the request made with an idempotency key.
+    pub fn put<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        key: &IdempotencyKey,
+        request: &IdempotentRequest,
+    ) -> Result<(), Error> {
+        db_txn.put(
+            self.key_to_request,
+            key,
+            &mc_util_serial::encode(request),
+            WriteFlags::empty(),
+        )?;
+        Ok(())
+    }
+
+    /// Remove an idempotency key.
+    pub fn remove<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        key: &IdempotencyKey,
+    ) -> Result<(), Error> {
+        match db_txn.del(self.key_to_request, key, None) {
+            Ok(()) | Err(lmdb::Error::NotFound) => Ok(()),
+            Err(err) => Err(Error::Lmdb(err)),
+        }
+    }
+
+    /// Remove all the requests whose retention window is over.
+    pub fn prune_expired<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        now: u64,
+        retention: Duration,
+    ) -> Result<usize, Error> {
+        let mut num_pruned = 0;
+        let mut cursor = db_txn.open_rw_cursor(self.key_to_request)?;
+        for result in cursor.iter_start() {
+            let (_key_bytes, value_bytes) = result?;
+            let request: IdempotentRequest = mc_util_serial::decode(value_bytes)?;
+            if request.is_expired(now, retention) {
+                cursor.del(WriteFlags::empty())?;
+                num_pruned += 1;
+            }
+        }
+        if num_pruned > 0 {
+            log::debug!(
+                self.logger,
+                "Pruned {} expired idempotency keys",
+                num_pruned
+            );
+        }
+        Ok(num_pruned)
+    }
+}
@@ -6,13 +6,20 @@ extern crate alloc;
 
 pub mod config;
 pub mod database;
+#[cfg(feature = "hardware-wallet")]
+pub mod hardware_wallet;
 pub mod payments;
 pub mod service;
 
 mod conversions;
+mod counters;
 mod database_key;
 mod db_crypto;
 mod error;
+mod idempotency_store;
+mod memo;
+mod monitor_backup;
+mod monitor_notifier;
 mod monitor_store;
 mod processed_block_store;
 mod subaddress_store;
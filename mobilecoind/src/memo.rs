@@ -0,0 +1,272 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Decoding of recoverable transaction history memos attached to the TxOuts
+//! owned by a monitor.
+
+use crate::{error::Error, monitor_store::MonitorData};
+use mc_account_keys::{PublicAddress, ShortAddressHash, CHANGE_SUBADDRESS_INDEX};
+use mc_crypto_keys::RistrettoPublic;
+use mc_mobilecoind_api as api;
+use mc_transaction_core::{get_tx_out_shared_secret, tx::TxOut};
+use mc_transaction_std::{MemoDecodingError, MemoType};
+
+/// Decrypt the memo of a TxOut received by a monitor at a given subaddress.
+///
+/// Sender memos are validated against the given known senders. This requires
+/// the subaddress view private key of the receiving subaddress, so the memos
+/// received by watch-only monitors are never validated. Destination memos are
+/// valid when they are found on the change subaddress, since only the owner
+/// of the account can send to it.
+pub fn decode_memo(
+    tx_out: &TxOut,
+    subaddress_index: u64,
+    monitor_data: &MonitorData,
+    known_senders: &[PublicAddress],
+) -> Result<api::DecodedMemo, Error> {
+    let tx_public_key = RistrettoPublic::try_from(&tx_out.public_key)?;
+    let shared_secret = get_tx_out_shared_secret(
+        monitor_data.view_account_key()?.view_private_key(),
+        &tx_public_key,
+    );
+    let memo_payload = tx_out.decrypt_memo(&shared_secret);
+
+    let mut decoded = api::DecodedMemo::new();
+    decoded.set_memo_type_bytes(u16::from_be_bytes(*memo_payload.get_memo_type()) as u32);
+
+    // Sender memos can only be validated with the spend private key.
+    let receiving_view_private_key = monitor_data
+        .account_key
+        .as_ref()
+        .map(|account_key| account_key.subaddress_view_private(subaddress_index));
+    let find_sender = |address_hash: &ShortAddressHash| {
+        known_senders
+            .iter()
+            .find(|sender| ShortAddressHash::from(*sender) == *address_hash)
+    };
+
+    match MemoType::try_from(&memo_payload) {
+        Ok(MemoType::AuthenticatedSender(memo)) => {
+            decoded.set_memo_type(api::MemoType::AuthenticatedSender);
+            let address_hash = memo.sender_address_hash();
+            decoded.set_sender_address_hash(address_hash.as_ref().to_vec());
+            if let (Some(view_private_key), Some(sender)) = (
+                receiving_view_private_key.as_ref(),
+                find_sender(&address_hash),
+            ) {
+                if bool::from(memo.validate(sender, view_private_key, &tx_out.public_key)) {
+                    decoded.set_validated(true);
+                    decoded.set_sender(sender.into());
+                }
+            }
+        }
+        Ok(MemoType::AuthenticatedSenderWithPaymentRequestId(memo)) => {
+            decoded.set_memo_type(api::MemoType::AuthenticatedSenderWithPaymentRequestId);
+            decoded.set_payment_request_id(memo.payment_request_id());
+            let address_hash = memo.sender_address_hash();
+            decoded.set_sender_address_hash(address_hash.as_ref().to_vec());
+            if let (Some(view_private_key), Some(sender)) = (
+                receiving_view_private_key.as_ref(),
+                find_sender(&address_hash),
+            ) {
+                if bool::from(memo.validate(sender, view_private_key, &tx_out.public_key)) {
+                    decoded.set_validated(true);
+                    decoded.set_sender(sender.into());
+                }
+            }
+        }
+        Ok(MemoType::Destination(memo)) => {
+            decoded.set_memo_type(api::MemoType::Destination);
+            decoded.set_recipient_address_hash(memo.get_address_hash().as_ref().to_vec());
+            decoded.set_num_recipients(memo.get_num_recipients() as u32);
+            decoded.set_fee(memo.get_fee());
+            decoded.set_total_outlay(memo.get_total_outlay());
+            decoded.set_validated(subaddress_index == CHANGE_SUBADDRESS_INDEX);
+        }
+        Ok(MemoType::Unused(_)) => {
+            decoded.set_memo_type(api::MemoType::MemoUnused);
+        }
+        Ok(_) | Err(MemoDecodingError::UnknownMemoType(_)) => {
+            decoded.set_memo_type(api::MemoType::MemoUnknown);
+        }
+    }
+
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::BlockVersion;
+    use mc_account_keys::{AccountKey, ViewAccountKey, DEFAULT_SUBADDRESS_INDEX};
+    use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPrivate};
+    use mc_transaction_core::{
+        encrypted_fog_hint::EncryptedFogHint, tokens::Mob, Amount, MemoContext, MemoPayload,
+        NewMemoError, Token,
+    };
+    use mc_transaction_std::{
+        AuthenticatedSenderWithPaymentRequestIdMemo, DestinationMemo, SenderMemoCredential,
+    };
+    use mc_util_from_random::FromRandom;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn tx_out_with_memo(
+        block_version: BlockVersion,
+        recipient: &PublicAddress,
+        rng: &mut StdRng,
+        memo_fn: impl FnOnce(MemoContext) -> Result<MemoPayload, NewMemoError>,
+    ) -> TxOut {
+        TxOut::new_with_memo(
+            block_version,
+            Amount::new(1000, Mob::ID),
+            recipient,
+            &RistrettoPrivate::from_random(rng),
+            EncryptedFogHint::fake_onetime_hint(rng),
+            memo_fn,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_decode_sender_memo() {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+        let sender = AccountKey::random(&mut rng);
+        let receiver = AccountKey::random(&mut rng);
+        let monitor_data = MonitorData::new(receiver.clone(), 0, 1, 0, "").unwrap();
+
+        let tx_out = tx_out_with_memo(
+            BlockVersion::MAX,
+            &receiver.default_subaddress(),
+            &mut rng,
+            |ctx| {
+                Ok(AuthenticatedSenderWithPaymentRequestIdMemo::new(
+                    &SenderMemoCredential::from(&sender),
+                    receiver.default_subaddress().view_public_key(),
+                    &CompressedRistrettoPublic::from(ctx.tx_public_key),
+                    42,
+                )
+                .into())
+            },
+        );
+        let sender_address = sender.default_subaddress();
+        let sender_address_hash = ShortAddressHash::from(&sender_address);
+
+        // The sender is known, so the memo is validated.
+        let decoded = decode_memo(
+            &tx_out,
+            DEFAULT_SUBADDRESS_INDEX,
+            &monitor_data,
+            &[
+                AccountKey::random(&mut rng).default_subaddress(),
+                sender_address.clone(),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            decoded.get_memo_type(),
+            api::MemoType::AuthenticatedSenderWithPaymentRequestId
+        );
+        assert_eq!(decoded.get_memo_type_bytes(), 0x0101);
+        assert_eq!(
+            decoded.get_sender_address_hash(),
+            sender_address_hash.as_ref()
+        );
+        assert_eq!(decoded.get_payment_request_id(), 42);
+        assert!(decoded.get_validated());
+        assert_eq!(
+            PublicAddress::try_from(decoded.get_sender()).unwrap(),
+            sender_address
+        );
+
+        // An unknown sender is reported but not validated.
+        let decoded = decode_memo(&tx_out, DEFAULT_SUBADDRESS_INDEX, &monitor_data, &[]).unwrap();
+        assert_eq!(
+            decoded.get_sender_address_hash(),
+            sender_address_hash.as_ref()
+        );
+        assert!(!decoded.get_validated());
+        assert!(!decoded.has_sender());
+
+        // Decoding with the wrong subaddress fails validation.
+        let decoded = decode_memo(&tx_out, 1, &monitor_data, &[sender_address.clone()]).unwrap();
+        assert!(!decoded.get_validated());
+
+        // Watch-only monitors cannot validate sender memos.
+        let watch_only_monitor_data =
+            MonitorData::new_watch_only(ViewAccountKey::from(&receiver), None, 0, 1, 0, "")
+                .unwrap();
+        let decoded = decode_memo(
+            &tx_out,
+            DEFAULT_SUBADDRESS_INDEX,
+            &watch_only_monitor_data,
+            &[sender_address],
+        )
+        .unwrap();
+        assert_eq!(decoded.get_payment_request_id(), 42);
+        assert!(!decoded.get_validated());
+    }
+
+    #[test]
+    fn test_decode_destination_memo() {
+        let mut rng: StdRng = SeedableRng::from_seed([2u8; 32]);
+        let account_key = AccountKey::random(&mut rng);
+        let recipient = AccountKey::random(&mut rng).default_subaddress();
+        let monitor_data = MonitorData::new(account_key.clone(), 0, 1, 0, "").unwrap();
+
+        let tx_out = tx_out_with_memo(
+            BlockVersion::MAX,
+            &account_key.change_subaddress(),
+            &mut rng,
+            |_ctx| {
+                Ok(
+                    DestinationMemo::new(ShortAddressHash::from(&recipient), 1400, 400)
+                        .unwrap()
+                        .into(),
+                )
+            },
+        );
+
+        let decoded = decode_memo(&tx_out, CHANGE_SUBADDRESS_INDEX, &monitor_data, &[]).unwrap();
+        assert_eq!(decoded.get_memo_type(), api::MemoType::Destination);
+        assert_eq!(
+            decoded.get_recipient_address_hash(),
+            ShortAddressHash::from(&recipient).as_ref()
+        );
+        assert_eq!(decoded.get_num_recipients(), 1);
+        assert_eq!(decoded.get_fee(), 400);
+        assert_eq!(decoded.get_total_outlay(), 1400);
+        assert!(decoded.get_validated());
+
+        // A destination memo is only valid on the change subaddress.
+        let decoded = decode_memo(&tx_out, DEFAULT_SUBADDRESS_INDEX, &monitor_data, &[]).unwrap();
+        assert!(!decoded.get_validated());
+    }
+
+    #[test]
+    fn test_decode_no_memo() {
+        let mut rng: StdRng = SeedableRng::from_seed([3u8; 32]);
+        let account_key = AccountKey::random(&mut rng);
+        let monitor_data = MonitorData::new(account_key.clone(), 0, 1, 0, "").unwrap();
+
+        // Block version zero does not support memos.
+        let tx_out = tx_out_with_memo(
+            BlockVersion::ZERO,
+            &account_key.default_subaddress(),
+            &mut rng,
+            |_ctx| Ok(MemoPayload::default()),
+        );
+        let decoded = decode_memo(&tx_out, DEFAULT_SUBADDRESS_INDEX, &monitor_data, &[]).unwrap();
+        assert_eq!(decoded.get_memo_type(), api::MemoType::MemoUnused);
+        assert!(!decoded.get_validated());
+
+        // Unknown memo types are reported as such.
+        let tx_out = tx_out_with_memo(
+            BlockVersion::MAX,
+            &account_key.default_subaddress(),
+            &mut rng,
+            |_ctx| Ok(MemoPayload::new([0x7f, 0x7f], [0u8; 64])),
+        );
+        let decoded = decode_memo(&tx_out, DEFAULT_SUBADDRESS_INDEX, &monitor_data, &[]).unwrap();
+        assert_eq!(decoded.get_memo_type(), api::MemoType::MemoUnknown);
+        assert_eq!(decoded.get_memo_type_bytes(), 0x7f7f);
+    }
+}
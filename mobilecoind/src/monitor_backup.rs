@@ -0,0 +1,247 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Backups of the state of a monitor.
+//! * A backup holds the definition of a monitor and everything it learned while
+//!   processing blocks, so it can be imported by another mobilecoind instance
+//!   without rescanning the ledger.
+//! * Spend private keys are never part of a backup. Importing a backup of a
+//!   monitor that was not watch-only requires providing the account key.
+//! * Backups are encrypted with a password, using a random salt.
+
+use crate::{
+    db_crypto::PASSWORD_LEN, error::Error, processed_block_store::ProcessedTxOut,
+    utxo_store::UnspentTxOut,
+};
+use aes_gcm::{
+    aead::{
+        generic_array::{sequence::Split, GenericArray},
+        Aead,
+    },
+    AeadCore, Aes256Gcm, Error as AeadError, NewAead,
+};
+use displaydoc::Display;
+use mc_account_keys::{PublicAddress, ViewAccountKey};
+use mc_crypto_hashes::{Blake2b512, Digest};
+use mc_util_serial::Message;
+use rand::{CryptoRng, RngCore};
+
+/// Domain tag for monitor backup encryption.
+pub const MOBILECOIND_MONITOR_BACKUP_DOMAIN_TAG: &str = "mc_mobilecoind_monitor_backup";
+
+/// Length of the random salt prepended to encrypted backups.
+const SALT_LEN: usize = 32;
+
+/// Possible monitor backup error types.
+#[derive(Debug, Display)]
+pub enum MonitorBackupError {
+    /// Invalid password length
+    InvalidPasswordLength,
+
+    /// The backup is too short
+    Truncated,
+
+    /// AEAD: {0}
+    Aead(AeadError),
+}
+
+impl From<AeadError> for MonitorBackupError {
+    fn from(src: AeadError) -> Self {
+        Self::Aead(src)
+    }
+}
+
+/// A TxOut processed by a monitor, and the block it was processed in.
+#[derive(Clone, Eq, PartialEq, Message)]
+pub struct BackupProcessedTxOut {
+    #[prost(uint64, tag = "1")]
+    pub block_index: u64,
+
+    #[prost(message, required, tag = "2")]
+    pub processed_tx_out: ProcessedTxOut,
+}
+
+/// The label of a subaddress of a monitor.
+#[derive(Clone, Eq, PartialEq, Message)]
+pub struct BackupSubaddressLabel {
+    #[prost(uint64, tag = "1")]
+    pub subaddress_index: u64,
+
+    #[prost(string, tag = "2")]
+    pub label: String,
+}
+
+/// The state of a monitor, without its spend private key.
+#[derive(Clone, Eq, PartialEq, Message)]
+pub struct MonitorBackup {
+    /// The view private key and spend public key of the account.
+    #[prost(message, required, tag = "1")]
+    pub view_account_key: ViewAccountKey,
+
+    /// Whether the monitor was watch-only. If not, its UnspentTxOuts have real
+    /// key images and the account key is needed to import it.
+    #[prost(bool, tag = "2")]
+    pub watch_only: bool,
+
+    /// The smallest subaddress index in the range the monitor watches.
+    #[prost(uint64, tag = "3")]
+    pub first_subaddress: u64,
+
+    /// The number of subaddresses the monitor watches.
+    #[prost(uint64, tag = "4")]
+    pub num_subaddresses: u64,
+
+    /// The first block the monitor processed.
+    #[prost(uint64, tag = "5")]
+    pub first_block: u64,
+
+    /// The next block the monitor needs to process.
+    #[prost(uint64, tag = "6")]
+    pub next_block: u64,
+
+    /// Monitor name.
+    #[prost(string, tag = "7")]
+    pub name: String,
+
+    /// The UnspentTxOuts of the monitor.
+    #[prost(message, repeated, tag = "8")]
+    pub utxos: Vec<UnspentTxOut>,
+
+    /// The TxOuts processed by the monitor, ordered by block index.
+    #[prost(message, repeated, tag = "9")]
+    pub processed_tx_outs: Vec<BackupProcessedTxOut>,
+
+    /// The subaddress labels of the monitor.
+    #[prost(message, repeated, tag = "10")]
+    pub labels: Vec<BackupSubaddressLabel>,
+
+    /// The default subaddress of a watch-only monitor, with its fog details.
+    #[prost(message, optional, tag = "11")]
+    pub default_public_address: Option<PublicAddress>,
+}
+
+impl MonitorBackup {
+    /// Serialize and encrypt the backup with a password.
+    pub fn encrypt<R: RngCore + CryptoRng>(
+        &self,
+        password: &[u8],
+        rng: &mut R,
+    ) -> Result<Vec<u8>, Error> {
+        let mut salt = [0u8; SALT_LEN];
+        rng.fill_bytes(&mut salt);
+
+        let (key, nonce) = expand_password(password, &salt)?;
+        let cipher = Aes256Gcm::new(&key);
+        let ciphertext = cipher
+            .encrypt(&nonce, mc_util_serial::encode(self).as_slice())
+            .map_err(MonitorBackupError::from)?;
+
+        let mut bytes = salt.to_vec();
+        bytes.extend_from_slice(&ciphertext);
+        Ok(bytes)
+    }
+
+    /// Decrypt and deserialize a backup created by [MonitorBackup::encrypt].
+    pub fn decrypt(password: &[u8], bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() < SALT_LEN {
+            return Err(MonitorBackupError::Truncated.into());
+        }
+        let (salt, ciphertext) = bytes.split_at(SALT_LEN);
+
+        let (key, nonce) = expand_password(password, salt)?;
+        let cipher = Aes256Gcm::new(&key);
+        let plaintext = cipher
+            .decrypt(&nonce, ciphertext)
+            .map_err(MonitorBackupError::from)?;
+
+        Ok(mc_util_serial::decode(&plaintext)?)
+    }
+}
+
+/// Expands the password and salt into an encryption key and a nonce.
+fn expand_password(
+    password: &[u8],
+    salt: &[u8],
+) -> Result<
+    (
+        GenericArray<u8, <Aes256Gcm as NewAead>::KeySize>,
+        GenericArray<u8, <Aes256Gcm as AeadCore>::NonceSize>,
+    ),
+    MonitorBackupError,
+> {
+    if password.len() != PASSWORD_LEN {
+        return Err(MonitorBackupError::InvalidPasswordLength);
+    }
+
+    // Hash the salt and password with Blake2b to get 64 bytes, first 32 for aeskey,
+    // second 32 for nonce
+    let mut hasher = Blake2b512::new();
+    hasher.update(&MOBILECOIND_MONITOR_BACKUP_DOMAIN_TAG);
+    hasher.update(salt);
+    hasher.update(password);
+    let result = hasher.finalize();
+
+    let (key, remainder) = Split::<u8, <Aes256Gcm as NewAead>::KeySize>::split(result);
+    let (nonce, _remainder) = Split::<u8, <Aes256Gcm as AeadCore>::NonceSize>::split(remainder);
+
+    Ok((key, nonce))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use mc_account_keys::AccountKey;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+        let account_key = AccountKey::random(&mut rng);
+
+        let backup = MonitorBackup {
+            view_account_key: ViewAccountKey::from(&account_key),
+            watch_only: false,
+            first_subaddress: 0,
+            num_subaddresses: 10,
+            first_block: 3,
+            next_block: 17,
+            name: "test".to_owned(),
+            utxos: vec![],
+            processed_tx_outs: vec![],
+            labels: vec![BackupSubaddressLabel {
+                subaddress_index: 2,
+                label: "alice".to_owned(),
+            }],
+            default_public_address: None,
+        };
+
+        let password = [7u8; PASSWORD_LEN];
+        let bytes = backup.encrypt(&password, &mut rng).unwrap();
+        assert_eq!(MonitorBackup::decrypt(&password, &bytes).unwrap(), backup);
+
+        // The salt is random, so encrypting twice gives different backups.
+        let other_bytes = backup.encrypt(&password, &mut rng).unwrap();
+        assert_ne!(bytes, other_bytes);
+        assert_eq!(
+            MonitorBackup::decrypt(&password, &other_bytes).unwrap(),
+            backup
+        );
+
+        // The wrong password, a tampered backup or a bad password length fail.
+        assert!(MonitorBackup::decrypt(&[8u8; PASSWORD_LEN], &bytes).is_err());
+
+        let mut tampered_bytes = bytes.clone();
+        *tampered_bytes.last_mut().unwrap() ^= 1;
+        assert!(MonitorBackup::decrypt(&password, &tampered_bytes).is_err());
+
+        assert!(matches!(
+            MonitorBackup::decrypt(&password, &bytes[..SALT_LEN - 1]),
+            Err(Error::MonitorBackup(MonitorBackupError::Truncated))
+        ));
+        assert!(matches!(
+            backup.encrypt(&[7u8; 16], &mut rng),
+            Err(Error::MonitorBackup(
+                MonitorBackupError::InvalidPasswordLength
+            ))
+        ));
+    }
+}
@@ -0,0 +1,71 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Notifies the streaming API calls when monitors change.
+//!
+//! The database notifies its subscribers each time a monitor processes a block
+//! or is removed, and the sync thread does so each time the ledger grows.
+//! Subscribers then check what changed themselves, so a notification carries
+//! no data, and a subscriber that has not consumed its last notification yet
+//! is not notified again.
+
+use futures::channel::mpsc;
+use std::sync::{Arc, Mutex};
+
+/// A notifier shared by the database and all its subscribers.
+#[derive(Clone, Default)]
+pub struct MonitorNotifier {
+    subscribers: Arc<Mutex<Vec<mpsc::Sender<()>>>>,
+}
+
+impl MonitorNotifier {
+    /// Get notified of the changes that happen from now on.
+    pub fn subscribe(&self) -> mpsc::Receiver<()> {
+        // A channel with no buffer holds one message per sender.
+        let (sender, receiver) = mpsc::channel(0);
+        self.subscribers
+            .lock()
+            .expect("mutex poisoned")
+            .push(sender);
+        receiver
+    }
+
+    /// Notify all subscribers, and forget those that went away.
+    pub fn notify(&self) {
+        let mut subscribers = self.subscribers.lock().expect("mutex poisoned");
+        subscribers.retain(|sender| !sender.is_closed());
+        for sender in subscribers.iter_mut() {
+            // The channel is full if the subscriber has not consumed its last
+            // notification yet, in which case it is enough.
+            let _ = sender.try_send(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[test]
+    fn test_notifications_are_coalesced() {
+        let notifier = MonitorNotifier::default();
+
+        // Subscribers only get notified of what happens after they subscribe.
+        notifier.notify();
+        let mut receiver = notifier.subscribe();
+        assert!(receiver.try_next().is_err());
+
+        notifier.notify();
+        notifier.notify();
+        assert_eq!(receiver.try_next().unwrap(), Some(()));
+        assert!(receiver.try_next().is_err());
+
+        notifier.notify();
+        assert_eq!(futures::executor::block_on(receiver.next()), Some(()));
+
+        // Subscribers that went away are forgotten.
+        drop(receiver);
+        notifier.notify();
+        assert!(notifier.subscribers.lock().unwrap().is_empty());
+    }
+}
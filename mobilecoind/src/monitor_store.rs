@@ -7,7 +7,7 @@
 use crate::{database_key::DatabaseByteArrayKey, db_crypto::DbCryptoProvider, error::Error};
 
 use lmdb::{Cursor, Database, DatabaseFlags, Environment, RwTransaction, Transaction, WriteFlags};
-use mc_account_keys::AccountKey;
+use mc_account_keys::{AccountKey, PublicAddress, ViewAccountKey, DEFAULT_SUBADDRESS_INDEX};
 use mc_common::{
     logger::{log, Logger},
     HashMap,
@@ -24,9 +24,10 @@ pub const MONITOR_ID_TO_MONITOR_DATA_DB_NAME: &str =
 /// Type used as the stored data in the monitor_id_to_monitor_data database.
 #[derive(Clone, Eq, Hash, PartialEq, Message)]
 pub struct MonitorData {
-    /// The private key pair for the account this monitor watches.
-    #[prost(message, required, tag = "1")]
-    pub account_key: AccountKey,
+    /// The private key pair for the account this monitor watches. Unset for
+    /// watch-only monitors.
+    #[prost(message, optional, tag = "1")]
+    pub account_key: Option<AccountKey>,
 
     /// The smallest subaddress index in the range this monitor watches.
     #[prost(uint64, tag = "2")]
@@ -47,6 +48,18 @@ pub struct MonitorData {
     /// Optional monitor name.
     #[prost(string, tag = "6")]
     pub name: String,
+
+    /// The view private key and spend public key for the account a watch-only
+    /// monitor watches. Unset for monitors that have the account key.
+    #[prost(message, optional, tag = "7")]
+    pub view_account_key: Option<ViewAccountKey>,
+
+    /// The default subaddress of the account a watch-only monitor watches,
+    /// with its fog details. The fog authority signature of a subaddress can
+    /// only be computed with the spend private key, so it is provided when the
+    /// monitor is added.
+    #[prost(message, optional, tag = "8")]
+    pub default_public_address: Option<PublicAddress>,
 }
 
 impl MonitorData {
@@ -56,6 +69,63 @@ impl MonitorData {
         num_subaddresses: u64,
         first_block: u64,
         name: &str,
+    ) -> Result<Self, Error> {
+        Self::new_impl(
+            Some(account_key),
+            None,
+            None,
+            first_subaddress,
+            num_subaddresses,
+            first_block,
+            name,
+        )
+    }
+
+    /// Create a watch-only monitor, which can find and decode incoming
+    /// outputs without the spend private key, but cannot spend them.
+    ///
+    /// The default public address of accounts that use fog must be provided,
+    /// so that the monitor has the same id as a monitor of the account key.
+    /// Its keys must match the view account key.
+    pub fn new_watch_only(
+        view_account_key: ViewAccountKey,
+        default_public_address: Option<PublicAddress>,
+        first_subaddress: u64,
+        num_subaddresses: u64,
+        first_block: u64,
+        name: &str,
+    ) -> Result<Self, Error> {
+        if let Some(address) = default_public_address.as_ref() {
+            let expected = view_account_key.default_subaddress();
+            if address.view_public_key() != expected.view_public_key()
+                || address.spend_public_key() != expected.spend_public_key()
+            {
+                return Err(Error::InvalidArgument(
+                    "default_public_address".to_string(),
+                    "must be the default subaddress of the view account key".to_string(),
+                ));
+            }
+        }
+
+        Self::new_impl(
+            None,
+            Some(view_account_key),
+            default_public_address,
+            first_subaddress,
+            num_subaddresses,
+            first_block,
+            name,
+        )
+    }
+
+    fn new_impl(
+        account_key: Option<AccountKey>,
+        view_account_key: Option<ViewAccountKey>,
+        default_public_address: Option<PublicAddress>,
+        first_subaddress: u64,
+        num_subaddresses: u64,
+        first_block: u64,
+        name: &str,
     ) -> Result<Self, Error> {
         if num_subaddresses == 0 {
             return Err(Error::InvalidArgument(
@@ -72,12 +142,52 @@ impl MonitorData {
             // The next block we need to sync is our first block.
             next_block: first_block,
             name: name.to_owned(),
+            view_account_key,
+            default_public_address,
         })
     }
 
     pub fn subaddress_indexes(&self) -> Range<u64> {
         self.first_subaddress..self.first_subaddress + self.num_subaddresses
     }
+
+    /// Whether this monitor only has the view key of its account.
+    pub fn is_watch_only(&self) -> bool {
+        self.account_key.is_none()
+    }
+
+    /// The account key, needed for spending. Fails for watch-only monitors.
+    pub fn account_key(&self) -> Result<&AccountKey, Error> {
+        self.account_key.as_ref().ok_or(Error::WatchOnlyMonitor)
+    }
+
+    /// The view key of the account this monitor watches.
+    pub fn view_account_key(&self) -> Result<ViewAccountKey, Error> {
+        match (self.account_key.as_ref(), self.view_account_key.as_ref()) {
+            (Some(account_key), _) => Ok(ViewAccountKey::from(account_key)),
+            (None, Some(view_account_key)) => Ok(view_account_key.clone()),
+            (None, None) => Err(Error::MonitorKeysMissing),
+        }
+    }
+
+    /// The public address of a subaddress of the account this monitor watches.
+    /// For watch-only monitors, fog details are only included in the default
+    /// subaddress.
+    pub fn subaddress(&self, index: u64) -> Result<PublicAddress, Error> {
+        if let Some(account_key) = self.account_key.as_ref() {
+            return Ok(account_key.subaddress(index));
+        }
+        match self.default_public_address.as_ref() {
+            Some(address) if index == DEFAULT_SUBADDRESS_INDEX => Ok(address.clone()),
+            _ => Ok(self.view_account_key()?.subaddress(index)),
+        }
+    }
+
+    /// The public address of the default subaddress of the account this
+    /// monitor watches.
+    pub fn default_subaddress(&self) -> Result<PublicAddress, Error> {
+        self.subaddress(DEFAULT_SUBADDRESS_INDEX)
+    }
 }
 
 /// Type used as the key in the monitor_id_to_monitor_data database
@@ -119,7 +229,9 @@ impl From<&MonitorData> for MonitorId {
             pub first_block: u64,
         }
 
-        let real_subaddress = src.account_key.default_subaddress();
+        // MonitorStore::add rejects monitor data without keys, so the default
+        // address is only ever used for monitors that have one.
+        let real_subaddress = src.default_subaddress().unwrap_or_default();
 
         let const_data = ConstMonitorData {
             address: PublicAddress {
@@ -191,6 +303,8 @@ impl MonitorStore {
         db_txn: &mut RwTransaction<'env>,
         data: &MonitorData,
     ) -> Result<MonitorId, Error> {
+        data.view_account_key()?;
+
         let monitor_id = MonitorId::from(data);
         let key_bytes = monitor_id.as_bytes();
 
@@ -402,6 +516,108 @@ pKZkdp8MQU5TLFOE9qjNeVsCAwEAAQ==
         );
     }
 
+    /// Watch-only monitors behave like monitors that have the account key,
+    /// except for spending.
+    #[test]
+    fn watch_only_monitor_data() {
+        let mut rng = ChaChaRng::seed_from_u64(0);
+
+        let key = AccountKey::random(&mut rng);
+        let data = MonitorData::new(key.clone(), 1, 10, 1, "test").unwrap();
+        let watch_only_data =
+            MonitorData::new_watch_only(ViewAccountKey::from(&key), None, 1, 10, 1, "test")
+                .unwrap();
+
+        assert!(!data.is_watch_only());
+        assert!(watch_only_data.is_watch_only());
+        assert_eq!(data.account_key().unwrap(), &key);
+        assert_matches!(watch_only_data.account_key(), Err(Error::WatchOnlyMonitor));
+
+        // Both watch the same account.
+        assert_eq!(MonitorId::from(&data), MonitorId::from(&watch_only_data));
+        assert_eq!(
+            data.view_account_key().unwrap(),
+            watch_only_data.view_account_key().unwrap()
+        );
+        for index in data.subaddress_indexes() {
+            assert_eq!(
+                data.subaddress(index).unwrap(),
+                watch_only_data.subaddress(index).unwrap()
+            );
+        }
+
+        // Watch-only monitors survive a serialization round trip.
+        let decoded: MonitorData =
+            mc_util_serial::decode(&mc_util_serial::encode(&watch_only_data)).unwrap();
+        assert_eq!(decoded, watch_only_data);
+        assert!(decoded.is_watch_only());
+
+        // Monitor data without keys is an error, not a panic.
+        let mut keyless_data = watch_only_data;
+        keyless_data.view_account_key = None;
+        assert_matches!(
+            keyless_data.view_account_key(),
+            Err(Error::MonitorKeysMissing)
+        );
+        assert_matches!(
+            keyless_data.default_subaddress(),
+            Err(Error::MonitorKeysMissing)
+        );
+    }
+
+    /// Watch-only monitors of fog accounts keep the fog details of their
+    /// default subaddress, and get the same id as monitors of the account key.
+    #[test]
+    fn watch_only_fog_monitor_data() {
+        let mut rng = ChaChaRng::seed_from_u64(0);
+
+        let fog_authority_spki = pem::parse(AUTHORITY_PUBKEY)
+            .expect("Could not parse pubkey")
+            .contents;
+        let fog_key = AccountKey::from(&RootIdentity::random_with_fog(
+            &mut rng,
+            "fog://fog.unittest.mobilecoin.com",
+            "",
+            &fog_authority_spki,
+        ));
+        let data = MonitorData::new(fog_key.clone(), 0, 10, 1, "test").unwrap();
+
+        let watch_only_data = MonitorData::new_watch_only(
+            ViewAccountKey::from(&fog_key),
+            Some(fog_key.default_subaddress()),
+            0,
+            10,
+            1,
+            "test",
+        )
+        .unwrap();
+        assert_eq!(
+            watch_only_data.default_subaddress().unwrap(),
+            fog_key.default_subaddress()
+        );
+        assert_eq!(MonitorId::from(&data), MonitorId::from(&watch_only_data));
+
+        // Without the fog details, the id is different.
+        let fogless_data =
+            MonitorData::new_watch_only(ViewAccountKey::from(&fog_key), None, 0, 10, 1, "test")
+                .unwrap();
+        assert_ne!(MonitorId::from(&data), MonitorId::from(&fogless_data));
+
+        // The address must belong to the account.
+        let other_key = AccountKey::random(&mut rng);
+        assert_matches!(
+            MonitorData::new_watch_only(
+                ViewAccountKey::from(&fog_key),
+                Some(other_key.default_subaddress()),
+                0,
+                10,
+                1,
+                "test",
+            ),
+            Err(Error::InvalidArgument(_, _))
+        );
+    }
+
     // MonitorStore basic functionality tests
     #[test_with_logger]
     fn test_monitor_store(logger: Logger) {
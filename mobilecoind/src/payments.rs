@@ -2,7 +2,17 @@
 
 //! Construct and submit transactions to the validator network.
 
-use crate::{database::Database, error::Error, monitor_store::MonitorId, utxo_store::UnspentTxOut};
+#[cfg(feature = "hardware-wallet")]
+use crate::hardware_wallet::{HardwareWallet, HardwareWalletRingSigner};
+use crate::{
+    counters,
+    database::Database,
+    error::Error,
+    monitor_store::{MonitorData, MonitorId},
+    utxo_store::UnspentTxOut,
+};
+#[cfg(feature = "hardware-wallet")]
+use mc_account_keys::ViewAccountKey;
 use mc_account_keys::{AccountKey, PublicAddress};
 use mc_blockchain_types::{BlockIndex, BlockVersion};
 use mc_common::{
@@ -10,28 +20,36 @@ use mc_common::{
     HashMap, HashSet,
 };
 use mc_connection::{
-    BlockInfo, BlockchainConnection, ConnectionManager, RetryableUserTxConnection, UserTxConnection,
+    BlockInfo, BlockchainConnection, ConnectionManager, Error as ConnectionError,
+    RetryableUserTxConnection, UserTxConnection,
 };
-use mc_crypto_keys::RistrettoPublic;
+use mc_crypto_keys::{RistrettoPrivate, RistrettoPublic};
 use mc_crypto_rand::{CryptoRng, RngCore};
-use mc_crypto_ring_signature_signer::NoKeysRingSigner;
-use mc_fog_report_validation::FogPubkeyResolver;
-use mc_ledger_db::{Error as LedgerError, Ledger, LedgerDB};
+use mc_crypto_ring_signature_signer::{
+    Error as RingSignerError, NoKeysRingSigner, OneTimeKeyDeriveData, RingSigner, SignableInputRing,
+};
+use mc_fog_report_validation::{FogPubkeyError, FogPubkeyResolver, FullyValidatedFogPubkey};
+use mc_ledger_db::{sample_rings, Error as LedgerError, Ledger, LedgerDB, UniformRingSampler};
 use mc_transaction_core::{
-    constants::{MAX_INPUTS, MILLIMOB_TO_PICOMOB, RING_SIZE},
-    onetime_keys::recover_onetime_private_key,
-    ring_signature::KeyImage,
+    constants::{MAX_INPUTS, MAX_TOMBSTONE_BLOCKS, MILLIMOB_TO_PICOMOB, RING_SIZE},
+    get_tx_out_shared_secret,
+    onetime_keys::{
+        recover_onetime_private_key, recover_public_subaddress_spend_key,
+        recover_public_subaddress_spend_key_from_shared_secret,
+    },
+    ring_signature::{CryptoRngCore, RingMLSAG, Scalar},
     tx::{Tx, TxOut, TxOutConfirmationNumber, TxOutMembershipProof},
-    Amount, TokenId,
+    Amount, SignedContingentInput, TokenId,
 };
 use mc_transaction_std::{
-    EmptyMemoBuilder, InputCredentials, MemoBuilder, ReservedSubaddresses, TransactionBuilder,
-    TxOutContext,
+    DustPolicy, DustPolicyError, EmptyMemoBuilder, InputCredentials, MemoBuilder,
+    ReservedSubaddresses, SignedContingentInputBuilder, TransactionBuilder, TxOutContext,
 };
 use mc_util_uri::FogUri;
-use rand::Rng;
+use retry::Error as RetryError;
 use std::{
     cmp::{max, Reverse},
+    collections::BTreeMap,
     iter::empty,
     str::FromStr,
     sync::{
@@ -42,7 +60,6 @@ use std::{
 
 /// Default number of blocks used for calculating transaction tombstone block
 /// number.
-// TODO support for making this configurable
 pub const DEFAULT_NEW_TX_BLOCK_ATTEMPTS: u64 = 50;
 
 /// Default ring size
@@ -62,6 +79,16 @@ pub struct Outlay {
     pub receiver: PublicAddress,
 }
 
+/// An outlay in a given token id, for transactions that mix several token ids.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OutlayV2 {
+    /// Amount being sent.
+    pub amount: Amount,
+
+    /// Destination.
+    pub receiver: PublicAddress,
+}
+
 /// A single pending transaction.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct TxProposal {
@@ -81,6 +108,11 @@ pub struct TxProposal {
     /// A list of the confirmation numbers, in the same order
     /// as the outlays.
     pub outlay_confirmation_numbers: Vec<TxOutConfirmationNumber>,
+
+    /// The shared secrets of the TxOuts paying the outlays, in the same order
+    /// as the outlays. They let the sender check that each TxOut pays its
+    /// outlay without the recipient's view private key.
+    pub outlay_shared_secrets: Vec<RistrettoPublic>,
 }
 
 impl TxProposal {
@@ -89,6 +121,85 @@ impl TxProposal {
     }
 }
 
+/// A transaction that is ready to be signed, containing everything needed to
+/// sign it without access to the ledger or the network.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnsignedTx {
+    /// UTXOs to spend, with membership proofs.
+    pub inputs: Vec<(UnspentTxOut, TxOutMembershipProof)>,
+
+    /// A set of mixins for each input, with membership proofs.
+    pub rings: Vec<Vec<(TxOut, TxOutMembershipProof)>>,
+
+    /// Destinations the transaction is being sent to.
+    pub outlays: Vec<Outlay>,
+
+    /// The block version to target.
+    pub block_version: BlockVersion,
+
+    /// The token id to transact in.
+    pub token_id: TokenId,
+
+    /// Transaction fee.
+    pub fee: u64,
+
+    /// Subaddress for the change recipient.
+    pub change_subaddress: u64,
+
+    /// Tombstone block of the transaction.
+    pub tombstone_block: BlockIndex,
+
+    /// Validated fog public keys of the fog recipients.
+    pub fog_pubkeys: HashMap<PublicAddress, FullyValidatedFogPubkey>,
+}
+
+/// A plan for merging the UTXOs of a subaddress into fewer UTXOs, by paying
+/// them back to the same subaddress.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConsolidationPlan {
+    /// The number of spendable UTXOs of the subaddress.
+    pub num_utxos: usize,
+
+    /// The number of those UTXOs which are dust, and are left alone.
+    pub num_dust_utxos: usize,
+
+    /// The number of transactions needed to reach the target number of UTXOs.
+    pub num_txs: usize,
+
+    /// The fee paid by each transaction.
+    pub fee: u64,
+
+    /// The inputs of the transactions that can be built right away. The other
+    /// transactions spend the outputs of these, so they can only be built once
+    /// these have landed in the ledger.
+    pub tx_inputs: Vec<Vec<UnspentTxOut>>,
+}
+
+impl ConsolidationPlan {
+    /// The fee paid by all the transactions of the plan.
+    pub fn total_fee(&self) -> u64 {
+        self.num_txs as u64 * self.fee
+    }
+}
+
+/// A fog pubkey resolver for fog public keys that were resolved ahead of time.
+struct ResolvedFogPubkeys {
+    fog_pubkeys: HashMap<PublicAddress, FullyValidatedFogPubkey>,
+}
+
+impl FogPubkeyResolver for ResolvedFogPubkeys {
+    fn get_fog_pubkey(
+        &self,
+        recipient: &PublicAddress,
+    ) -> Result<FullyValidatedFogPubkey, FogPubkeyError> {
+        self.fog_pubkeys.get(recipient).cloned().ok_or_else(|| {
+            FogPubkeyError::NoMatchingReportResponse(
+                recipient.fog_report_url().unwrap_or_default().to_owned(),
+            )
+        })
+    }
+}
+
 pub struct TransactionsManager<
     T: BlockchainConnection + UserTxConnection + 'static,
     FPR: FogPubkeyResolver,
@@ -111,6 +222,15 @@ pub struct TransactionsManager<
     /// connections to fog
     fog_resolver_factory: Arc<dyn Fn(&[FogUri]) -> Result<FPR, String> + Send + Sync>,
 
+    /// Hardware wallet that signs the transactions of watch-only monitors
+    /// sharing its view key.
+    #[cfg(feature = "hardware-wallet")]
+    hardware_wallet: Option<Arc<dyn HardwareWallet>>,
+
+    /// Number of blocks past the network tip used as the default tombstone
+    /// block of new transactions.
+    tombstone_offset: u64,
+
     /// Logger.
     logger: Logger,
 }
@@ -125,11 +245,113 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
             peer_manager: self.peer_manager.clone(),
             submit_node_offset: self.submit_node_offset.clone(),
             fog_resolver_factory: self.fog_resolver_factory.clone(),
+            tombstone_offset: self.tombstone_offset,
+            #[cfg(feature = "hardware-wallet")]
+            hardware_wallet: self.hardware_wallet.clone(),
             logger: self.logger.clone(),
         }
     }
 }
 
+/// The keys that authorize spending the inputs of a transaction.
+enum TxSigner<'a> {
+    /// The account key of the sender.
+    AccountKey(&'a AccountKey),
+
+    /// A hardware wallet holding the spend private key of the sender, and the
+    /// view account key it shared.
+    #[cfg(feature = "hardware-wallet")]
+    HardwareWallet(&'a dyn HardwareWallet, ViewAccountKey),
+}
+
+impl TxSigner<'_> {
+    fn subaddress(&self, index: u64) -> PublicAddress {
+        match self {
+            Self::AccountKey(account_key) => account_key.subaddress(index),
+            #[cfg(feature = "hardware-wallet")]
+            Self::HardwareWallet(_, view_account_key) => view_account_key.subaddress(index),
+        }
+    }
+
+    fn view_private_key(&self) -> &RistrettoPrivate {
+        match self {
+            Self::AccountKey(account_key) => account_key.view_private_key(),
+            #[cfg(feature = "hardware-wallet")]
+            Self::HardwareWallet(_, view_account_key) => view_account_key.view_private_key(),
+        }
+    }
+
+    fn reserved_subaddresses(&self, change_subaddress: u64) -> ReservedSubaddresses {
+        match self {
+            Self::AccountKey(account_key) => ReservedSubaddresses::from_subaddress_index(
+                account_key,
+                Some(change_subaddress),
+                None,
+            ),
+            #[cfg(feature = "hardware-wallet")]
+            Self::HardwareWallet(_, view_account_key) => ReservedSubaddresses {
+                primary_address: view_account_key.default_subaddress(),
+                change_subaddress: view_account_key.subaddress(change_subaddress),
+                gift_code_subaddress: view_account_key.gift_code_subaddress(),
+            },
+        }
+    }
+
+    /// The one-time private key of an input, or the means for the ring signer
+    /// to derive it.
+    fn onetime_key_derive_data(&self, utxo: &UnspentTxOut) -> Result<OneTimeKeyDeriveData, Error> {
+        match self {
+            Self::AccountKey(account_key) => {
+                let public_key = RistrettoPublic::try_from(&utxo.tx_out.public_key)?;
+                Ok(recover_onetime_private_key(
+                    &public_key,
+                    account_key.view_private_key(),
+                    &account_key.subaddress_spend_private(utxo.subaddress_index),
+                )
+                .into())
+            }
+            #[cfg(feature = "hardware-wallet")]
+            Self::HardwareWallet(..) => {
+                Ok(OneTimeKeyDeriveData::SubaddressIndex(utxo.subaddress_index))
+            }
+        }
+    }
+
+    /// Have the outputs of the transaction confirmed before signing it.
+    #[cfg_attr(not(feature = "hardware-wallet"), allow(unused_variables))]
+    fn confirm_output(&self, receiver: &PublicAddress, amount: Amount) -> Result<(), Error> {
+        match self {
+            Self::AccountKey(_) => Ok(()),
+            #[cfg(feature = "hardware-wallet")]
+            Self::HardwareWallet(wallet, _) => Ok(wallet.confirm_output(receiver, amount)?),
+        }
+    }
+}
+
+impl RingSigner for TxSigner<'_> {
+    fn sign(
+        &self,
+        message: &[u8],
+        signable_ring: &SignableInputRing,
+        output_blinding: Scalar,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<RingMLSAG, RingSignerError> {
+        match self {
+            // The one-time private keys are already in the input credentials.
+            Self::AccountKey(_) => {
+                NoKeysRingSigner {}.sign(message, signable_ring, output_blinding, rng)
+            }
+            #[cfg(feature = "hardware-wallet")]
+            Self::HardwareWallet(wallet, _) => HardwareWalletRingSigner::new(*wallet).sign(
+                message,
+                signable_ring,
+                output_blinding,
+                rng,
+            ),
+        }
+    }
+}
+
 fn get_network_block_version(block_infos: &[BlockInfo]) -> u32 {
     block_infos
         .iter()
@@ -138,6 +360,30 @@ fn get_network_block_version(block_infos: &[BlockInfo]) -> u32 {
         .unwrap_or(0)
 }
 
+/// Get the index of the next block the network will build, from the number of
+/// blocks in the local ledger and the last block reported by each peer.
+fn get_next_block_index(num_blocks_in_ledger: u64, block_infos: &[BlockInfo]) -> BlockIndex {
+    block_infos
+        .iter()
+        .map(|block_info| block_info.block_index + 1)
+        .max()
+        .unwrap_or(0)
+        .max(num_blocks_in_ledger)
+}
+
+/// Check that the network would accept a transaction with the given tombstone
+/// block, when it builds the block at `next_block_index`.
+pub fn validate_tombstone(next_block_index: BlockIndex, tombstone: u64) -> Result<(), Error> {
+    if tombstone <= next_block_index {
+        return Err(Error::TombstoneTooNear(tombstone, next_block_index));
+    }
+    let max_tombstone = next_block_index + MAX_TOMBSTONE_BLOCKS;
+    if tombstone > max_tombstone {
+        return Err(Error::TombstoneTooFar(tombstone, max_tombstone));
+    }
+    Ok(())
+}
+
 fn get_fee(block_infos: &[BlockInfo], token_id: TokenId, opt_fee: u64) -> u64 {
     if opt_fee > 0 {
         opt_fee
@@ -172,10 +418,80 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
             peer_manager,
             submit_node_offset: Arc::new(AtomicUsize::new(rng.next_u64() as usize)),
             fog_resolver_factory,
+            #[cfg(feature = "hardware-wallet")]
+            hardware_wallet: None,
+            tombstone_offset: DEFAULT_NEW_TX_BLOCK_ATTEMPTS,
             logger,
         }
     }
 
+    /// Set the number of blocks past the network tip used as the default
+    /// tombstone block of new transactions.
+    pub fn set_tombstone_offset(&mut self, tombstone_offset: u64) {
+        self.tombstone_offset = tombstone_offset;
+    }
+
+    /// Get the tombstone block suggested for new transactions.
+    ///
+    /// Returns the index of the next block the network will build, and the
+    /// suggested tombstone block, which is the configured offset past it.
+    ///
+    /// # Arguments
+    /// * `last_block_infos` - Last block info responses from the network, for
+    ///   determining the network tip.
+    pub fn suggested_tombstone_block(
+        &self,
+        last_block_infos: &[BlockInfo],
+    ) -> Result<(BlockIndex, BlockIndex), Error> {
+        let next_block_index = get_next_block_index(self.ledger_db.num_blocks()?, last_block_infos);
+        Ok((next_block_index, next_block_index + self.tombstone_offset))
+    }
+
+    /// Get the tombstone block of a new transaction: `opt_tombstone` if it is
+    /// non-zero and the network would accept it, or the suggested tombstone
+    /// block otherwise.
+    fn get_tombstone_block(
+        &self,
+        opt_tombstone: u64,
+        last_block_infos: &[BlockInfo],
+    ) -> Result<BlockIndex, Error> {
+        let (next_block_index, suggested_tombstone_block) =
+            self.suggested_tombstone_block(last_block_infos)?;
+        if opt_tombstone == 0 {
+            return Ok(suggested_tombstone_block);
+        }
+        validate_tombstone(next_block_index, opt_tombstone)?;
+        Ok(opt_tombstone)
+    }
+
+    /// Attach a hardware wallet. Watch-only monitors whose view key matches the
+    /// one of the device build their transactions by having the device
+    /// confirm the outputs and sign the rings.
+    #[cfg(feature = "hardware-wallet")]
+    pub fn set_hardware_wallet(&mut self, hardware_wallet: Arc<dyn HardwareWallet>) {
+        self.hardware_wallet = Some(hardware_wallet);
+    }
+
+    /// Get the signer for transactions spending the outputs of a monitor.
+    fn tx_signer<'a>(&'a self, monitor_data: &'a MonitorData) -> Result<TxSigner<'a>, Error> {
+        match monitor_data.account_key() {
+            Ok(account_key) => Ok(TxSigner::AccountKey(account_key)),
+            #[cfg(feature = "hardware-wallet")]
+            Err(Error::WatchOnlyMonitor) => {
+                let wallet = self
+                    .hardware_wallet
+                    .as_deref()
+                    .ok_or(Error::WatchOnlyMonitor)?;
+                let view_account_key = wallet.view_account_key()?;
+                if view_account_key != monitor_data.view_account_key()? {
+                    return Err(Error::WatchOnlyMonitor);
+                }
+                Ok(TxSigner::HardwareWallet(wallet, view_account_key))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
     // Gets the network fee and block_version, unless opt_fee is nonzero.
     // If opt fee is nonzero then we use local ledger block version and this fee,
     // and don't make a network call
@@ -230,6 +546,280 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         let logger = self.logger.new(o!("sender_monitor_id" => sender_monitor_id.to_string(), "outlays" => format!("{:?}", outlays)));
         log::trace!(logger, "Building pending transaction...");
 
+        // Get sender monitor data. Watch-only monitors cannot sign transactions,
+        // unless a hardware wallet signs for them.
+        let sender_monitor_data = self.mobilecoind_db.get_monitor_data(sender_monitor_id)?;
+        let signer = self.tx_signer(&sender_monitor_data)?;
+
+        let unsigned_tx = self.prepare_transaction(
+            token_id,
+            change_subaddress,
+            inputs,
+            outlays,
+            last_block_infos,
+            opt_fee,
+            opt_tombstone,
+            &logger,
+        )?;
+
+        // Build and return the TxProposal object
+        let mut rng = rand::thread_rng();
+        let tx_proposal = Self::build_tx_proposal(
+            &unsigned_tx.inputs,
+            unsigned_tx.rings,
+            unsigned_tx.block_version,
+            token_id,
+            unsigned_tx.fee,
+            &signer,
+            change_subaddress,
+            outlays,
+            unsigned_tx.tombstone_block,
+            &*self.fog_resolver_factory,
+            opt_memo_builder,
+            &mut rng,
+            &self.logger,
+        )?;
+        log::trace!(logger, "Tx constructed, hash={}", tx_proposal.tx.tx_hash());
+
+        Ok(tx_proposal)
+    }
+
+    /// Create a TxProposal whose outlays may be of several token ids. Inputs
+    /// are selected in each token id of the outlays, and the fee is paid in
+    /// the given fee token id. This requires a block version that supports
+    /// mixed transactions.
+    ///
+    /// # Arguments
+    /// * `sender_monitor_id` - Indicates the the account key needed to spend
+    ///   the txo's.
+    /// * `fee_token_id` - The token id to pay the fee in.
+    /// * `change_subaddress` - Recipient of any change.
+    /// * `inputs` - UTXOs that may be spent by the transaction, of any token
+    ///   id.
+    /// * `outlays` - Output amounts and recipients.
+    /// * `last_block_infos` - Last block info responses from the network, for
+    ///   determining fees. This should normally come from polling_network_state
+    /// * `opt_fee` - Transaction fee in the fee token id. If zero, defaults to
+    ///   the network fee.
+    /// * `opt_tombstone` - Tombstone block. If zero, sets to default.
+    pub fn build_mixed_transaction(
+        &self,
+        sender_monitor_id: &MonitorId,
+        fee_token_id: TokenId,
+        change_subaddress: u64,
+        inputs: &[UnspentTxOut],
+        outlays: &[OutlayV2],
+        last_block_infos: &[BlockInfo],
+        opt_fee: u64,
+        opt_tombstone: u64,
+    ) -> Result<TxProposal, Error> {
+        let logger = self.logger.new(o!("sender_monitor_id" => sender_monitor_id.to_string(), "outlays" => format!("{:?}", outlays)));
+        log::trace!(logger, "Building pending mixed transaction...");
+
+        let sender_monitor_data = self.mobilecoind_db.get_monitor_data(sender_monitor_id)?;
+        let signer = self.tx_signer(&sender_monitor_data)?;
+
+        // Must have at least one output
+        if outlays.is_empty() {
+            return Err(Error::TxBuild("Must have at least one destination".into()));
+        }
+
+        let (fee, block_version) =
+            self.get_network_fee_and_block_version(fee_token_id, opt_fee, last_block_infos)?;
+        let block_version =
+            BlockVersion::try_from(block_version).map_err(|err| Error::TxBuild(err.to_string()))?;
+        if !block_version.mixed_transactions_are_supported() {
+            return Err(Error::TxBuild(format!(
+                "Block version {} does not support mixed transactions",
+                block_version
+            )));
+        }
+
+        // Figure out the value needed in each token id, including the fee.
+        let mut required_values: BTreeMap<TokenId, u64> = BTreeMap::new();
+        *required_values.entry(fee_token_id).or_default() += fee;
+        for outlay in outlays {
+            *required_values.entry(outlay.amount.token_id).or_default() += outlay.amount.value;
+        }
+        if required_values.len() > MAX_INPUTS as usize {
+            return Err(Error::TxBuild(format!(
+                "Cannot transact in more than {} token ids",
+                MAX_INPUTS
+            )));
+        }
+
+        // Select the inputs of each token id, leaving room for at least one input
+        // of each of the remaining token ids.
+        let mut selected_utxos_with_proofs = Vec::new();
+        let mut rings = Vec::new();
+        for (i, (token_id, value)) in required_values.iter().enumerate() {
+            let remaining_token_ids = required_values.len() - i - 1;
+            let max_inputs =
+                MAX_INPUTS as usize - selected_utxos_with_proofs.len() - remaining_token_ids;
+            let (token_utxos_with_proofs, token_rings) =
+                self.select_inputs_with_rings(*token_id, inputs, *value, max_inputs, &logger)?;
+            selected_utxos_with_proofs.extend(token_utxos_with_proofs);
+            rings.extend(token_rings);
+        }
+
+        // Come up with tombstone block.
+        let tombstone_block = self.get_tombstone_block(opt_tombstone, last_block_infos)?;
+        log::trace!(logger, "Tombstone block set to {}", tombstone_block);
+
+        let mut rng = rand::thread_rng();
+        let tx_proposal = Self::build_mixed_tx_proposal(
+            &selected_utxos_with_proofs,
+            rings,
+            block_version,
+            Amount::new(fee, fee_token_id),
+            &signer,
+            change_subaddress,
+            outlays,
+            tombstone_block,
+            &*self.fog_resolver_factory,
+            None,
+            &mut rng,
+            &self.logger,
+        )?;
+        log::trace!(logger, "Tx constructed, hash={}", tx_proposal.tx.tx_hash());
+
+        Ok(tx_proposal)
+    }
+
+    /// Create an UnsignedTx, to be signed by an offline signer that has the
+    /// account key. This also works for watch-only monitors.
+    ///
+    /// Fog public keys for the outlays, and for the change subaddress if the
+    /// monitor has the account key, are resolved and validated here, since the
+    /// signer is not expected to have network access.
+    ///
+    /// # Arguments
+    /// * `sender_monitor_id` - The monitor that owns the inputs.
+    /// * `token_id` - The token id to transact in.
+    /// * `change_subaddress` - Recipient of any change.
+    /// * `inputs` - UTXOs that will be spent by the transaction.
+    /// * `outlays` - Output amounts and recipients.
+    /// * `last_block_infos` - Last block info responses from the network, for
+    ///   determining fees. This should normally come from polling_network_state
+    /// * `opt_fee` - Transaction fee in picoMOB. If zero, defaults to MIN_FEE.
+    /// * `opt_tombstone` - Tombstone block. If zero, sets to default.
+    pub fn build_unsigned_transaction(
+        &self,
+        sender_monitor_id: &MonitorId,
+        token_id: TokenId,
+        change_subaddress: u64,
+        inputs: &[UnspentTxOut],
+        outlays: &[Outlay],
+        last_block_infos: &[BlockInfo],
+        opt_fee: u64,
+        opt_tombstone: u64,
+    ) -> Result<UnsignedTx, Error> {
+        let logger = self.logger.new(o!("sender_monitor_id" => sender_monitor_id.to_string(), "outlays" => format!("{:?}", outlays)));
+        log::trace!(logger, "Building unsigned transaction...");
+
+        let sender_monitor_data = self.mobilecoind_db.get_monitor_data(sender_monitor_id)?;
+
+        let mut unsigned_tx = self.prepare_transaction(
+            token_id,
+            change_subaddress,
+            inputs,
+            outlays,
+            last_block_infos,
+            opt_fee,
+            opt_tombstone,
+            &logger,
+        )?;
+
+        // Resolve the fog public keys of all fog recipients.
+        let change_address = sender_monitor_data.subaddress(change_subaddress)?;
+        let fog_addresses = core::slice::from_ref(&change_address)
+            .iter()
+            .chain(outlays.iter().map(|outlay| &outlay.receiver))
+            .filter(|address| address.fog_report_url().is_some())
+            .cloned()
+            .collect::<HashSet<_>>();
+        if !fog_addresses.is_empty() {
+            let fog_uris = fog_addresses
+                .iter()
+                .filter_map(|address| extract_fog_uri(address).transpose())
+                .collect::<Result<Vec<_>, _>>()?;
+            let fog_resolver = (self.fog_resolver_factory)(&fog_uris).map_err(Error::Fog)?;
+            for address in fog_addresses {
+                let fog_pubkey = fog_resolver
+                    .get_fog_pubkey(&address)
+                    .map_err(|err| Error::Fog(format!("Could not get fog pubkey: {}", err)))?;
+                unsigned_tx.fog_pubkeys.insert(address, fog_pubkey);
+            }
+        }
+        log::trace!(
+            logger,
+            "Resolved {} fog pubkeys",
+            unsigned_tx.fog_pubkeys.len()
+        );
+
+        Ok(unsigned_tx)
+    }
+
+    /// Sign an UnsignedTx. This does not need access to the ledger or the
+    /// network, so it can be done by an offline mobilecoind.
+    ///
+    /// The UTXOs in the returned TxProposal are the ones in the UnsignedTx, so
+    /// the TxProposal can be submitted by the mobilecoind that created the
+    /// UnsignedTx even if its monitor is watch-only.
+    ///
+    /// # Arguments
+    /// * `unsigned_tx` - The transaction to sign.
+    /// * `account_key` - Owns the inputs. Also the recipient of any change.
+    pub fn sign_unsigned_tx(
+        &self,
+        unsigned_tx: &UnsignedTx,
+        account_key: &AccountKey,
+    ) -> Result<TxProposal, Error> {
+        let fog_pubkeys = unsigned_tx.fog_pubkeys.clone();
+        let fog_resolver_factory = move |_fog_uris: &[FogUri]| -> Result<_, String> {
+            Ok(ResolvedFogPubkeys {
+                fog_pubkeys: fog_pubkeys.clone(),
+            })
+        };
+
+        let mut rng = rand::thread_rng();
+        let tx_proposal = Self::build_tx_proposal(
+            &unsigned_tx.inputs,
+            unsigned_tx.rings.clone(),
+            unsigned_tx.block_version,
+            unsigned_tx.token_id,
+            unsigned_tx.fee,
+            &TxSigner::AccountKey(account_key),
+            unsigned_tx.change_subaddress,
+            &unsigned_tx.outlays,
+            unsigned_tx.tombstone_block,
+            &fog_resolver_factory,
+            None,
+            &mut rng,
+            &self.logger,
+        )?;
+        log::trace!(
+            self.logger,
+            "Unsigned tx signed, hash={}",
+            tx_proposal.tx.tx_hash()
+        );
+
+        Ok(tx_proposal)
+    }
+
+    /// Validate the arguments of a new transaction, then select its inputs and
+    /// get everything needed to sign it from the ledger.
+    fn prepare_transaction(
+        &self,
+        token_id: TokenId,
+        change_subaddress: u64,
+        inputs: &[UnspentTxOut],
+        outlays: &[Outlay],
+        last_block_infos: &[BlockInfo],
+        opt_fee: u64,
+        opt_tombstone: u64,
+        logger: &Logger,
+    ) -> Result<UnsignedTx, Error> {
         // All inputs must be of the correct token id.
         if inputs.iter().any(|utxo| utxo.token_id != *token_id) {
             return Err(Error::InvalidArgument(
@@ -243,9 +833,6 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
             return Err(Error::TxBuild("Must have at least one destination".into()));
         }
 
-        // Get sender monitor data.
-        let sender_monitor_data = self.mobilecoind_db.get_monitor_data(sender_monitor_id)?;
-
         // Figure out total amount of transaction (excluding fee).
         let total_value: u64 = outlays.iter().map(|outlay| outlay.value).sum();
         log::trace!(
@@ -264,8 +851,48 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
             BlockVersion::try_from(block_version).map_err(|err| Error::TxBuild(err.to_string()))?;
 
         // Select the UTXOs to be used for this transaction.
-        let selected_utxos =
-            Self::select_utxos_for_value(token_id, inputs, total_value + fee, MAX_INPUTS as usize)?;
+        let (selected_utxos_with_proofs, rings) = self.select_inputs_with_rings(
+            token_id,
+            inputs,
+            total_value + fee,
+            MAX_INPUTS as usize,
+            logger,
+        )?;
+
+        // Come up with tombstone block.
+        let tombstone_block = self.get_tombstone_block(opt_tombstone, last_block_infos)?;
+        log::trace!(logger, "Tombstone block set to {}", tombstone_block);
+
+        Ok(UnsignedTx {
+            inputs: selected_utxos_with_proofs,
+            rings,
+            outlays: outlays.to_vec(),
+            block_version,
+            token_id,
+            fee,
+            change_subaddress,
+            tombstone_block,
+            fog_pubkeys: HashMap::default(),
+        })
+    }
+
+    /// Select UTXOs totalling at least the given value, and get their proofs of
+    /// membership and a ring of mixins for each of them.
+    fn select_inputs_with_rings(
+        &self,
+        token_id: TokenId,
+        inputs: &[UnspentTxOut],
+        value: u64,
+        max_inputs: usize,
+        logger: &Logger,
+    ) -> Result<
+        (
+            Vec<(UnspentTxOut, TxOutMembershipProof)>,
+            Vec<Vec<(TxOut, TxOutMembershipProof)>>,
+        ),
+        Error,
+    > {
+        let selected_utxos = Self::select_utxos_for_value(token_id, inputs, value, max_inputs)?;
         log::trace!(
             logger,
             "Selected {} utxos ({:?})",
@@ -300,35 +927,7 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         };
         log::trace!(logger, "Got {} rings", rings.len());
 
-        // Come up with tombstone block.
-        let tombstone_block = if opt_tombstone > 0 {
-            opt_tombstone
-        } else {
-            let num_blocks_in_ledger = self.ledger_db.num_blocks()?;
-            num_blocks_in_ledger + DEFAULT_NEW_TX_BLOCK_ATTEMPTS
-        };
-        log::trace!(logger, "Tombstone block set to {}", tombstone_block);
-
-        // Build and return the TxProposal object
-        let mut rng = rand::thread_rng();
-        let tx_proposal = Self::build_tx_proposal(
-            &selected_utxos_with_proofs,
-            rings,
-            block_version,
-            token_id,
-            fee,
-            &sender_monitor_data.account_key,
-            change_subaddress,
-            outlays,
-            tombstone_block,
-            &self.fog_resolver_factory,
-            opt_memo_builder,
-            &mut rng,
-            &self.logger,
-        )?;
-        log::trace!(logger, "Tx constructed, hash={}", tx_proposal.tx.tx_hash());
-
-        Ok(tx_proposal)
+        Ok((selected_utxos_with_proofs, rings))
     }
 
     /// Create a TxProposal that attempts to merge multiple UTXOs into a single
@@ -355,8 +954,9 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         );
         log::trace!(logger, "Generating optimization transaction...");
 
-        // Get monitor data.
+        // Get monitor data. Watch-only monitors cannot sign transactions.
         let monitor_data = self.mobilecoind_db.get_monitor_data(monitor_id)?;
+        let account_key = monitor_data.account_key()?;
 
         let num_blocks_in_ledger = self.ledger_db.num_blocks()?;
 
@@ -426,12 +1026,12 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         log::trace!(logger, "Got {} rings", rings.len());
 
         // Come up with tombstone block.
-        let tombstone_block = num_blocks_in_ledger + DEFAULT_NEW_TX_BLOCK_ATTEMPTS;
+        let tombstone_block = self.get_tombstone_block(0, last_block_infos)?;
         log::trace!(logger, "Tombstone block set to {}", tombstone_block);
 
         // We are paying ourselves the entire amount.
         let outlays = vec![Outlay {
-            receiver: monitor_data.account_key.subaddress(subaddress_index),
+            receiver: account_key.subaddress(subaddress_index),
             value: total_value - fee,
         }];
 
@@ -443,11 +1043,11 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
             block_version,
             token_id,
             fee,
-            &monitor_data.account_key,
+            &TxSigner::AccountKey(account_key),
             subaddress_index,
             &outlays,
             tombstone_block,
-            &self.fog_resolver_factory,
+            &*self.fog_resolver_factory,
             None,
             &mut rng,
             &self.logger,
@@ -532,44 +1132,456 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         let rings = self.get_rings(DEFAULT_RING_SIZE, inputs_with_proofs.len(), &input_indices)?;
         log::trace!(logger, "Got {} rings", rings.len());
 
-        // Come up with tombstone block.
-        let tombstone_block = self.ledger_db.num_blocks()? + DEFAULT_NEW_TX_BLOCK_ATTEMPTS;
-        log::trace!(logger, "Tombstone block set to {}", tombstone_block);
+        // Come up with tombstone block.
+        let tombstone_block = self.get_tombstone_block(0, last_block_infos)?;
+        log::trace!(logger, "Tombstone block set to {}", tombstone_block);
+
+        // The entire value goes to receiver
+        let outlays = vec![Outlay {
+            receiver: receiver.clone(),
+            value: total_value - fee,
+        }];
+
+        // Build and return the TxProposal object
+        let mut rng = rand::thread_rng();
+        let tx_proposal = Self::build_tx_proposal(
+            &inputs_with_proofs,
+            rings,
+            block_version,
+            token_id,
+            fee,
+            &TxSigner::AccountKey(account_key),
+            0,
+            &outlays,
+            tombstone_block,
+            &*self.fog_resolver_factory,
+            None,
+            &mut rng,
+            &self.logger,
+        )?;
+        log::trace!(
+            logger,
+            "Tx list tx constructed, hash={}",
+            tx_proposal.tx.tx_hash()
+        );
+
+        Ok(tx_proposal)
+    }
+
+    /// Plan the consolidation of the UTXOs of a subaddress into at most the
+    /// given number of UTXOs, with the fewest transactions. Each transaction
+    /// merges up to MAX_INPUTS UTXOs into one. UTXOs already being spent by
+    /// pending transactions are not counted, and dust UTXOs, which are worth
+    /// too little to ever cover a fee, are left alone.
+    ///
+    /// # Arguments
+    /// * `monitor_id` - Monitor ID of the inputs to spend.
+    /// * `subaddress_index` - Subaddress of the inputs to spend.
+    /// * `token_id` - Token id to transact in.
+    /// * `target_num_utxos` - The number of UTXOs to consolidate into.
+    /// * `last_block_infos` - Last block info responses from the network, for
+    ///   determining fees. This should normally come from polling_network_state
+    /// * `opt_fee` - Optional fee to use. If zero, we will attempt to query the
+    ///   network for fee information.
+    pub fn plan_consolidation(
+        &self,
+        monitor_id: &MonitorId,
+        subaddress_index: u64,
+        token_id: TokenId,
+        target_num_utxos: usize,
+        last_block_infos: &[BlockInfo],
+        opt_fee: u64,
+    ) -> Result<ConsolidationPlan, Error> {
+        if target_num_utxos == 0 {
+            return Err(Error::InvalidArgument(
+                "target_num_utxos".to_owned(),
+                "must be at least 1".to_owned(),
+            ));
+        }
+
+        let num_blocks_in_ledger = self.ledger_db.num_blocks()?;
+        let (fee, _block_version) =
+            self.get_network_fee_and_block_version(token_id, opt_fee, last_block_infos)?;
+
+        let spendable_utxos: Vec<UnspentTxOut> = self
+            .mobilecoind_db
+            .get_utxos_for_subaddress(monitor_id, subaddress_index)?
+            .into_iter()
+            .filter(|utxo| {
+                utxo.token_id == *token_id && num_blocks_in_ledger >= utxo.attempted_spend_tombstone
+            })
+            .collect();
+
+        Self::select_utxos_for_consolidation(&spendable_utxos, target_num_utxos, token_id, fee)
+    }
+
+    /// Create the transactions of a consolidation plan that can be built right
+    /// away. Each of them pays the total value of its inputs minus the fee
+    /// back to the subaddress they belong to.
+    ///
+    /// # Arguments
+    /// * `monitor_id` - Monitor ID of the inputs to spend.
+    /// * `subaddress_index` - Subaddress of the inputs to spend.
+    /// * `token_id` - Token id to transact in.
+    /// * `plan` - The plan returned by `plan_consolidation`.
+    /// * `last_block_infos` - Last block info responses from the network, for
+    ///   determining the block version.
+    pub fn generate_consolidation_txs(
+        &self,
+        monitor_id: &MonitorId,
+        subaddress_index: u64,
+        token_id: TokenId,
+        plan: &ConsolidationPlan,
+        last_block_infos: &[BlockInfo],
+    ) -> Result<Vec<TxProposal>, Error> {
+        // Watch-only monitors cannot sign transactions.
+        let monitor_data = self.mobilecoind_db.get_monitor_data(monitor_id)?;
+        let account_key = monitor_data.account_key()?;
+        let receiver = account_key.subaddress(subaddress_index);
+
+        plan.tx_inputs
+            .iter()
+            .map(|inputs| {
+                self.generate_tx_from_tx_list(
+                    account_key,
+                    token_id,
+                    inputs,
+                    &receiver,
+                    last_block_infos,
+                    plan.fee,
+                )
+            })
+            .collect()
+    }
+
+    /// Create a signed contingent input, offering a UTXO of a monitor to any
+    /// counterparty that pays the required amount to one of its subaddresses.
+    ///
+    /// # Arguments
+    /// * `monitor_id` - The monitor that owns the offered UTXO.
+    /// * `utxo` - The UTXO to offer.
+    /// * `required_amount` - The amount the counterparty must pay.
+    /// * `receiver_subaddress` - Subaddress receiving the required amount.
+    /// * `last_block_infos` - Last block info responses from the network, for
+    ///   determining the block version. This should normally come from
+    ///   polling_network_state
+    /// * `opt_tombstone` - Block index the signed contingent input expires at.
+    ///   If zero, it does not expire.
+    pub fn create_signed_contingent_input(
+        &self,
+        monitor_id: &MonitorId,
+        utxo: &UnspentTxOut,
+        required_amount: Amount,
+        receiver_subaddress: u64,
+        last_block_infos: &[BlockInfo],
+        opt_tombstone: u64,
+    ) -> Result<SignedContingentInput, Error> {
+        let logger = self.logger.new(o!("monitor_id" => monitor_id.to_string()));
+        log::trace!(logger, "Creating signed contingent input...");
+
+        let monitor_data = self.mobilecoind_db.get_monitor_data(monitor_id)?;
+        let signer = self.tx_signer(&monitor_data)?;
+
+        // The fee is paid by the counterparty, so only the block version matters here.
+        let (_fee, block_version) = self.get_network_fee_and_block_version(
+            TokenId::from(utxo.token_id),
+            0,
+            last_block_infos,
+        )?;
+        let block_version =
+            BlockVersion::try_from(block_version).map_err(|err| Error::TxBuild(err.to_string()))?;
+
+        // The offered UTXO, in a ring of mixins.
+        let proof = self
+            .get_membership_proofs(core::slice::from_ref(&utxo.tx_out))?
+            .pop()
+            .ok_or_else(|| Error::TxBuild("membership proofs were empty".to_string()))?;
+        let (ring, membership_proofs) = self
+            .get_rings(DEFAULT_RING_SIZE, 1, &[proof.index])?
+            .pop()
+            .ok_or_else(|| Error::TxBuild("rings were empty".to_string()))?
+            .into_iter()
+            .unzip();
+        let (ring, membership_proofs, real_key_index) =
+            insert_real_input(utxo, &proof, ring, membership_proofs);
+        let input_credentials = InputCredentials::new(
+            ring,
+            membership_proofs,
+            real_key_index,
+            signer.onetime_key_derive_data(utxo)?,
+            *signer.view_private_key(),
+        )
+        .map_err(|_| Error::TxBuild("failed creating InputCredentials".into()))?;
+
+        let receiver = signer.subaddress(receiver_subaddress);
+        let fog_resolver = {
+            let fog_uris = extract_fog_uri(&receiver)?.into_iter().collect::<Vec<_>>();
+            (self.fog_resolver_factory)(&fog_uris).map_err(Error::Fog)?
+        };
+
+        let mut sci_builder = SignedContingentInputBuilder::new(
+            block_version,
+            input_credentials,
+            fog_resolver,
+            EmptyMemoBuilder::default(),
+        )
+        .map_err(|err| {
+            Error::TxBuild(format!(
+                "Error creating signed contingent input builder: {}",
+                err
+            ))
+        })?;
+
+        let mut rng = rand::thread_rng();
+        sci_builder
+            .add_required_output(required_amount, &receiver, &mut rng)
+            .map_err(|err| Error::TxBuild(format!("failed adding required output: {}", err)))?;
+        if opt_tombstone > 0 {
+            validate_tombstone(
+                get_next_block_index(self.ledger_db.num_blocks()?, last_block_infos),
+                opt_tombstone,
+            )?;
+            sci_builder.set_tombstone_block(opt_tombstone);
+        }
+
+        let sci = sci_builder.build(&signer, &mut rng).map_err(|err| {
+            Error::TxBuild(format!("build signed contingent input failed: {}", err))
+        })?;
+        log::trace!(
+            logger,
+            "Signed contingent input constructed, key image={:?}",
+            sci.key_image()
+        );
+
+        Ok(sci)
+    }
+
+    /// Create a TxProposal that fulfills a signed contingent input presented by
+    /// a counterparty. The transaction pays the amounts required by the
+    /// signed contingent input from UTXOs of the monitor, and sends the amount
+    /// it offers to the change subaddress of the monitor.
+    ///
+    /// # Arguments
+    /// * `sender_monitor_id` - The monitor paying the required amounts.
+    /// * `change_subaddress` - Recipient of the offered amount and of any
+    ///   change.
+    /// * `inputs` - UTXOs that may be spent to pay the required amounts and the
+    ///   fee.
+    /// * `sci` - The signed contingent input to fulfill.
+    /// * `last_block_infos` - Last block info responses from the network, for
+    ///   determining fees. This should normally come from polling_network_state
+    /// * `opt_fee` - Transaction fee. If zero, defaults to the network fee.
+    /// * `opt_tombstone` - Tombstone block. If zero, sets to default. In both
+    ///   cases it is limited by the expiry of the signed contingent input.
+    pub fn fulfill_signed_contingent_input(
+        &self,
+        sender_monitor_id: &MonitorId,
+        change_subaddress: u64,
+        inputs: &[UnspentTxOut],
+        mut sci: SignedContingentInput,
+        last_block_infos: &[BlockInfo],
+        opt_fee: u64,
+        opt_tombstone: u64,
+    ) -> Result<TxProposal, Error> {
+        let logger = self
+            .logger
+            .new(o!("sender_monitor_id" => sender_monitor_id.to_string()));
+        log::trace!(logger, "Fulfilling signed contingent input...");
+
+        let sender_monitor_data = self.mobilecoind_db.get_monitor_data(sender_monitor_id)?;
+        let signer = self.tx_signer(&sender_monitor_data)?;
+
+        sci.validate()?;
+        if self.ledger_db.contains_key_image(&sci.key_image())? {
+            return Err(Error::SignedContingentInputSpent);
+        }
+
+        // The required amounts are paid in a single token.
+        let token_id = match sci.required_output_amounts.first() {
+            Some(amount) => TokenId::from(amount.token_id),
+            None => {
+                return Err(Error::InvalidArgument(
+                    "sci".to_string(),
+                    "No required outputs".to_string(),
+                ))
+            }
+        };
+        if sci
+            .required_output_amounts
+            .iter()
+            .any(|amount| amount.token_id != *token_id)
+        {
+            return Err(Error::InvalidArgument(
+                "sci".to_string(),
+                "All required outputs must be of the same token id".to_string(),
+            ));
+        }
+        let required_value: u64 = sci
+            .required_output_amounts
+            .iter()
+            .map(|amount| amount.value)
+            .sum();
+        if inputs.iter().any(|utxo| utxo.token_id != *token_id) {
+            return Err(Error::InvalidArgument(
+                "inputs".to_string(),
+                format!("All inputs must be of token_id {}", token_id),
+            ));
+        }
+
+        let (fee, block_version) =
+            self.get_network_fee_and_block_version(token_id, opt_fee, last_block_infos)?;
+        let block_version =
+            BlockVersion::try_from(block_version).map_err(|err| Error::TxBuild(err.to_string()))?;
+
+        // The signed contingent input takes one of the inputs of the transaction.
+        let (inputs_with_proofs, rings) = self.select_inputs_with_rings(
+            token_id,
+            inputs,
+            required_value + fee,
+            MAX_INPUTS as usize - 1,
+            &logger,
+        )?;
+
+        // Proofs of membership are not included in signed contingent inputs.
+        sci.tx_in.proofs = self
+            .ledger_db
+            .get_tx_out_proof_of_memberships(&sci.tx_out_global_indices)?;
+
+        let tombstone_block = self.get_tombstone_block(opt_tombstone, last_block_infos)?;
+
+        let change_dest = signer.reserved_subaddresses(change_subaddress);
+        let fog_resolver = {
+            let fog_uris = extract_fog_uri(&change_dest.primary_address)?
+                .into_iter()
+                .collect::<Vec<_>>();
+            (self.fog_resolver_factory)(&fog_uris).map_err(Error::Fog)?
+        };
+        let mut tx_builder = TransactionBuilder::new(
+            block_version,
+            Amount::new(fee, token_id),
+            fog_resolver,
+            EmptyMemoBuilder::default(),
+        )
+        .map_err(|err| Error::TxBuild(format!("Error creating transaction builder: {}", err)))?;
+
+        let offered_amount = Amount::new(
+            sci.pseudo_output_amount.value,
+            TokenId::from(sci.pseudo_output_amount.token_id),
+        );
+        tx_builder.add_presigned_input(sci)?;
+
+        for ((utxo, proof), ring) in inputs_with_proofs.iter().zip(rings) {
+            let (ring, membership_proofs) = ring.into_iter().unzip();
+            let (ring, membership_proofs, real_key_index) =
+                insert_real_input(utxo, proof, ring, membership_proofs);
+            tx_builder.add_input(
+                InputCredentials::new(
+                    ring,
+                    membership_proofs,
+                    real_key_index,
+                    signer.onetime_key_derive_data(utxo)?,
+                    *signer.view_private_key(),
+                )
+                .map_err(|_| Error::TxBuild("failed creating InputCredentials".into()))?,
+            );
+        }
+
+        // The offered amount, and any change, go back to the monitor.
+        let mut rng = rand::thread_rng();
+        tx_builder
+            .add_change_output(offered_amount, &change_dest, &mut rng)
+            .map_err(|err| Error::TxBuild(format!("failed adding output (offered): {}", err)))?;
+        let input_value: u64 = inputs_with_proofs.iter().map(|(utxo, _)| utxo.value).sum();
+        let change = input_value - required_value - fee;
+        if change > 0 {
+            tx_builder
+                .add_change_output(Amount::new(change, token_id), &change_dest, &mut rng)
+                .map_err(|err| Error::TxBuild(format!("failed adding output (change): {}", err)))?;
+        }
 
-        // The entire value goes to receiver
-        let outlays = vec![Outlay {
-            receiver: receiver.clone(),
-            value: total_value - fee,
-        }];
+        tx_builder.set_tombstone_block(tombstone_block);
 
-        // Build and return the TxProposal object
-        let mut rng = rand::thread_rng();
-        let tx_proposal = Self::build_tx_proposal(
-            &inputs_with_proofs,
-            rings,
-            block_version,
-            token_id,
-            fee,
-            account_key,
-            0,
-            &outlays,
-            tombstone_block,
-            &self.fog_resolver_factory,
-            None,
-            &mut rng,
-            &self.logger,
-        )?;
+        let tx = tx_builder
+            .build(&signer, &mut rng)
+            .map_err(|err| Error::TxBuild(format!("build tx failed: {}", err)))?;
         log::trace!(
             logger,
-            "Tx list tx constructed, hash={}",
-            tx_proposal.tx.tx_hash()
+            "Signed contingent input fulfilled, hash={}",
+            tx.tx_hash()
         );
 
-        Ok(tx_proposal)
+        Ok(TxProposal {
+            utxos: inputs_with_proofs
+                .into_iter()
+                .map(|(utxo, _proof)| utxo)
+                .collect(),
+            outlays: vec![],
+            tx,
+            outlay_index_to_tx_out_index: HashMap::default(),
+            outlay_confirmation_numbers: vec![],
+            outlay_shared_secrets: vec![],
+        })
+    }
+
+    /// Estimate the fee of a transaction without building it.
+    ///
+    /// Returns the minimum network fee for the token id, and the UTXOs that
+    /// would be selected to pay for the outlays and the fee.
+    ///
+    /// # Arguments
+    /// * `token_id` - The token id to transact in.
+    /// * `inputs` - UTXOs that may be spent by the transaction.
+    /// * `outlays` - Output amounts and recipients.
+    /// * `last_block_infos` - Last block info responses from the network, for
+    ///   determining fees. This should normally come from polling_network_state
+    pub fn estimate_fee(
+        &self,
+        token_id: TokenId,
+        inputs: &[UnspentTxOut],
+        outlays: &[Outlay],
+        last_block_infos: &[BlockInfo],
+    ) -> Result<(u64, Vec<UnspentTxOut>), Error> {
+        // All inputs must be of the correct token id.
+        if inputs.iter().any(|utxo| utxo.token_id != *token_id) {
+            return Err(Error::InvalidArgument(
+                "inputs".to_string(),
+                format!("All inputs must be of token_id {}", token_id),
+            ));
+        }
+
+        // Must have at least one output
+        if outlays.is_empty() {
+            return Err(Error::TxBuild("Must have at least one destination".into()));
+        }
+
+        let total_value: u64 = outlays.iter().map(|outlay| outlay.value).sum();
+        let fee = get_fee(last_block_infos, token_id, 0);
+
+        let selected_utxos =
+            Self::select_utxos_for_value(token_id, inputs, total_value + fee, MAX_INPUTS as usize)?;
+
+        Ok((fee, selected_utxos))
     }
 
     /// Submit a previously built tx proposal to the network.
     pub fn submit_tx_proposal(&self, tx_proposal: &TxProposal) -> Result<u64, Error> {
+        let result = self.submit_tx_proposal_impl(tx_proposal);
+
+        let outcome = match &result {
+            Ok(_) => "success",
+            Err(Error::Connection(RetryError::Operation {
+                error: ConnectionError::TransactionValidation(_),
+                ..
+            })) => "rejected",
+            Err(Error::NoPeersConfigured) | Err(Error::NodeNotFound) => "no_peer",
+            Err(_) => "connection_error",
+        };
+        counters::TX_SUBMISSIONS.with_label_values(&[outcome]).inc();
+
+        result
+    }
+
+    fn submit_tx_proposal_impl(&self, tx_proposal: &TxProposal) -> Result<u64, Error> {
         // Pick a peer to submit to.
         let responder_ids = self.peer_manager.responder_ids();
         if responder_ids.is_empty() {
@@ -598,6 +1610,40 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         Ok(block_height)
     }
 
+    /// Plan the consolidation of UTXOs of a token, with the policy that wallets
+    /// use: the smallest UTXOs are merged first, up to MAX_INPUTS of them per
+    /// transaction, and dust is left alone. See
+    /// `DustPolicy::suggest_consolidation`.
+    fn select_utxos_for_consolidation(
+        inputs: &[UnspentTxOut],
+        target_num_utxos: usize,
+        token_id: TokenId,
+        fee: u64,
+    ) -> Result<ConsolidationPlan, Error> {
+        let policy = DustPolicy::new(BTreeMap::from([(token_id, fee)]));
+        let values: Vec<u64> = inputs.iter().map(|utxo| utxo.value).collect();
+        let suggestion = policy
+            .suggest_consolidation(token_id, &values, target_num_utxos)
+            .map_err(|err| match err {
+                DustPolicyError::ConsolidationNotBeneficial => Error::OptimizationNotBeneficial(
+                    "Merging UTXOs would result in a loss".to_owned(),
+                ),
+                err => Error::InvalidArgument("token_id".to_owned(), err.to_string()),
+            })?;
+
+        Ok(ConsolidationPlan {
+            num_utxos: inputs.len(),
+            num_dust_utxos: suggestion.dust.len(),
+            num_txs: suggestion.num_txs,
+            fee,
+            tx_inputs: suggestion
+                .tx_inputs
+                .iter()
+                .map(|indices| indices.iter().map(|index| inputs[*index].clone()).collect())
+                .collect(),
+        })
+    }
+
     /// Returns a subset of UTXOs totalling at least the given amount.
     // TODO: This method should take attempted_spend_height into account.
     fn select_utxos_for_value(
@@ -779,42 +1825,14 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
             return Err(Error::InsufficientTxOuts);
         }
 
-        // Randomly sample `num_requested` indices of TxOuts to use as mixins.
-        let mixin_indices: Vec<u64> = {
-            let mut rng = rand::thread_rng();
-            let mut samples: HashSet<u64> = HashSet::default();
-            while samples.len() < num_requested {
-                let index = rng.gen_range(0..num_txos);
-                if excluded_tx_out_indices.contains(&index) {
-                    continue;
-                }
-                samples.insert(index);
-            }
-            samples.into_iter().collect()
-        };
-
-        let mixins_result: Result<Vec<TxOut>, _> = mixin_indices
-            .iter()
-            .map(|&index| self.ledger_db.get_tx_out_by_index(index))
-            .collect();
-        let mixins: Vec<TxOut> = mixins_result?;
-
-        let membership_proofs = self
-            .ledger_db
-            .get_tx_out_proof_of_memberships(&mixin_indices)?;
-
-        let mixins_with_proofs: Vec<(TxOut, TxOutMembershipProof)> = mixins
-            .into_iter()
-            .zip(membership_proofs.into_iter())
-            .collect();
-
-        // Group mixins and proofs into individual rings.
-        let result: Vec<Vec<(_, _)>> = mixins_with_proofs
-            .chunks(ring_size)
-            .map(|chunk| chunk.to_vec())
-            .collect();
-
-        Ok(result)
+        Ok(sample_rings(
+            &self.ledger_db,
+            &UniformRingSampler,
+            ring_size,
+            num_rings,
+            excluded_tx_out_indices,
+            &mut rand::thread_rng(),
+        )?)
     }
 
     /// Create a TxProposal.
@@ -825,8 +1843,7 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
     /// * `block_version` - The block version to target for this transaction
     /// * `token_id` - The token id to transact in
     /// * `fee` - Transaction fee, in picoMOB.
-    /// * `from_account_key` - Owns the inputs. Also the recipient of any
-    ///   change.
+    /// * `signer` - Owns the inputs. Also the recipient of any change.
     /// * `change_subaddress` - Subaddress for change recipient.
     /// * `destinations` - Outputs of the transaction.
     /// * `tombstone_block` - Tombstone block of the transaciton.
@@ -835,17 +1852,57 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
     ///   default one (EmptyMemoBuilder).
     /// * `rng` - randomness
     /// * `logger` - Logger
-    fn build_tx_proposal(
+    fn build_tx_proposal<R: FogPubkeyResolver>(
         inputs: &[(UnspentTxOut, TxOutMembershipProof)],
         rings: Vec<Vec<(TxOut, TxOutMembershipProof)>>,
         block_version: BlockVersion,
         token_id: TokenId,
         fee: u64,
-        from_account_key: &AccountKey,
+        signer: &TxSigner,
         change_subaddress: u64,
         destinations: &[Outlay],
         tombstone_block: BlockIndex,
-        fog_resolver_factory: &Arc<dyn Fn(&[FogUri]) -> Result<FPR, String> + Send + Sync>,
+        fog_resolver_factory: &(dyn Fn(&[FogUri]) -> Result<R, String> + Send + Sync),
+        opt_memo_builder: Option<Box<dyn MemoBuilder + 'static + Send + Sync>>,
+        rng: &mut (impl RngCore + CryptoRng),
+        logger: &Logger,
+    ) -> Result<TxProposal, Error> {
+        let destinations: Vec<OutlayV2> = destinations
+            .iter()
+            .map(|outlay| OutlayV2 {
+                amount: Amount::new(outlay.value, token_id),
+                receiver: outlay.receiver.clone(),
+            })
+            .collect();
+
+        Self::build_mixed_tx_proposal(
+            inputs,
+            rings,
+            block_version,
+            Amount::new(fee, token_id),
+            signer,
+            change_subaddress,
+            &destinations,
+            tombstone_block,
+            fog_resolver_factory,
+            opt_memo_builder,
+            rng,
+            logger,
+        )
+    }
+
+    /// Build a TxProposal whose inputs and outlays may be of several token ids.
+    /// Change is returned for each token id of the inputs.
+    fn build_mixed_tx_proposal<R: FogPubkeyResolver>(
+        inputs: &[(UnspentTxOut, TxOutMembershipProof)],
+        rings: Vec<Vec<(TxOut, TxOutMembershipProof)>>,
+        block_version: BlockVersion,
+        fee: Amount,
+        signer: &TxSigner,
+        change_subaddress: u64,
+        destinations: &[OutlayV2],
+        tombstone_block: BlockIndex,
+        fog_resolver_factory: &(dyn Fn(&[FogUri]) -> Result<R, String> + Send + Sync),
         opt_memo_builder: Option<Box<dyn MemoBuilder + 'static + Send + Sync>>,
         rng: &mut (impl RngCore + CryptoRng),
         logger: &Logger,
@@ -869,7 +1926,7 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         // Collect all required FogUris from public addresses, then pass to resolver
         // factory
         let fog_resolver = {
-            let change_address = from_account_key.subaddress(change_subaddress);
+            let change_address = signer.subaddress(change_subaddress);
             let fog_uris = core::slice::from_ref(&change_address)
                 .iter()
                 .chain(destinations.iter().map(|x| &x.receiver))
@@ -883,9 +1940,8 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         let memo_builder: Box<dyn MemoBuilder + Send + Sync> =
             opt_memo_builder.unwrap_or_else(|| Box::new(EmptyMemoBuilder::default()));
 
-        let fee_amount = Amount::new(fee, token_id);
         let mut tx_builder =
-            TransactionBuilder::new_with_box(block_version, fee_amount, fog_resolver, memo_builder)
+            TransactionBuilder::new_with_box(block_version, fee, fog_resolver, memo_builder)
                 .map_err(|err| {
                     Error::TxBuild(format!("Error creating transaction builder: {}", err))
                 })?;
@@ -898,61 +1954,18 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
 
         // Add inputs to the tx.
         for (utxo, proof) in inputs {
-            let (mut ring, mut membership_proofs) = rings_and_proofs
+            let (ring, membership_proofs) = rings_and_proofs
                 .pop()
                 .ok_or_else(|| Error::TxBuild("rings_and_proofs was empty".to_string()))?;
-            assert_eq!(
-                ring.len(),
-                membership_proofs.len(),
-                "Each ring element must have a corresponding membership proof."
-            );
-
-            // Add the input to the ring.
-            let position_opt = ring.iter().position(|tx_out| *tx_out == utxo.tx_out);
-            let real_key_index = match position_opt {
-                Some(position) => {
-                    // The input is already present in the ring.
-                    // This could happen if ring elements are sampled randomly from the ledger.
-                    position
-                }
-                None => {
-                    // The input is not already in the ring.
-                    if ring.is_empty() {
-                        // Append the input and its proof of membership.
-                        ring.push(utxo.tx_out.clone());
-                        membership_proofs.push(proof.clone());
-                    } else {
-                        // Replace the first element of the ring.
-                        ring[0] = utxo.tx_out.clone();
-                        membership_proofs[0] = proof.clone();
-                    }
-                    // The real input is always the first element. This is safe because
-                    // TransactionBuilder sorts each ring.
-                    0
-                }
-            };
-
-            assert_eq!(
-                ring.len(),
-                membership_proofs.len(),
-                "Each ring element must have a corresponding membership proof."
-            );
-
-            let public_key = RistrettoPublic::try_from(&utxo.tx_out.public_key).unwrap();
-            let onetime_private_key = recover_onetime_private_key(
-                &public_key,
-                from_account_key.view_private_key(),
-                &from_account_key.subaddress_spend_private(utxo.subaddress_index),
-            );
+            let (ring, membership_proofs, real_key_index) =
+                insert_real_input(utxo, proof, ring, membership_proofs);
 
-            let key_image = KeyImage::from(&onetime_private_key);
             log::debug!(
                 logger,
-                "Adding input: ring {:?}, utxo index {:?}, key image {:?}, pubkey {:?}",
+                "Adding input: ring {:?}, utxo index {:?}, pubkey {:?}",
                 ring,
                 real_key_index,
-                key_image,
-                public_key
+                utxo.tx_out.public_key
             );
 
             tx_builder.add_input(
@@ -960,67 +1973,63 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
                     ring,
                     membership_proofs,
                     real_key_index,
-                    onetime_private_key,
-                    *from_account_key.view_private_key(),
+                    signer.onetime_key_derive_data(utxo)?,
+                    *signer.view_private_key(),
                 )
                 .map_err(|_| Error::TxBuild("failed creating InputCredentials".into()))?,
             );
         }
 
         // Add outputs to our destinations.
-        let mut total_value = 0;
+        let mut output_values: BTreeMap<TokenId, u64> = BTreeMap::new();
+        *output_values.entry(fee.token_id).or_default() += fee.value;
         let mut tx_out_to_outlay_index = HashMap::default();
         let mut outlay_confirmation_numbers = Vec::default();
+        let mut outlay_shared_secrets = Vec::default();
         for (i, outlay) in destinations.iter().enumerate() {
-            // TODO (GH #1867): If you want to support mixed transactions, use
-            // outlay-specific token id here
-            let amount = Amount {
-                value: outlay.value,
-                token_id,
-            };
+            let amount = outlay.amount;
+            signer.confirm_output(&outlay.receiver, amount)?;
             let TxOutContext {
                 tx_out,
                 confirmation,
-                ..
+                shared_secret,
             } = tx_builder
                 .add_output(amount, &outlay.receiver, rng)
                 .map_err(|err| Error::TxBuild(format!("failed adding output: {}", err)))?;
 
             tx_out_to_outlay_index.insert(tx_out, i);
             outlay_confirmation_numbers.push(confirmation);
+            outlay_shared_secrets.push(shared_secret);
 
-            total_value += outlay.value;
+            *output_values.entry(amount.token_id).or_default() += amount.value;
         }
 
-        // Figure out if we have change.
-        let input_value = inputs
-            .iter()
-            .fold(0, |acc, (utxo, _proof)| acc + utxo.value);
-        if total_value > input_value {
-            return Err(Error::InsufficientFunds);
+        // Figure out if we have change, in each token id.
+        let mut input_values: BTreeMap<TokenId, u64> = BTreeMap::new();
+        for (utxo, _proof) in inputs {
+            *input_values
+                .entry(TokenId::from(utxo.token_id))
+                .or_default() += utxo.value;
+        }
+        for (token_id, output_value) in output_values.iter() {
+            if *output_value > input_values.get(token_id).copied().unwrap_or(0) {
+                return Err(Error::InsufficientFunds);
+            }
         }
-        let change = input_value - total_value - tx_builder.get_fee();
 
         // If we do have nonzero change, add an output for that as well.
         // TODO (GH #1522): Should the exchange write destination memos?
         // If so then we must always write a change output, even if the change is zero
-        if change > 0 {
-            // TODO: If you want to support mixed transactions, use outlay-specific token id
-            // here
-            let change_amount = Amount {
-                value: change,
-                token_id,
-            };
-
-            let change_dest = ReservedSubaddresses::from_subaddress_index(
-                from_account_key,
-                Some(change_subaddress),
-                None,
-            );
-
-            tx_builder
-                .add_change_output(change_amount, &change_dest, rng)
-                .map_err(|err| Error::TxBuild(format!("failed adding output (change): {}", err)))?;
+        let change_dest = signer.reserved_subaddresses(change_subaddress);
+        for (token_id, input_value) in input_values {
+            let change = input_value - output_values.get(&token_id).copied().unwrap_or(0);
+            if change > 0 {
+                tx_builder
+                    .add_change_output(Amount::new(change, token_id), &change_dest, rng)
+                    .map_err(|err| {
+                        Error::TxBuild(format!("failed adding output (change): {}", err))
+                    })?;
+            }
         }
 
         // Set tombstone block.
@@ -1028,7 +2037,7 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
 
         // Build tx.
         let tx = tx_builder
-            .build(&NoKeysRingSigner {}, rng)
+            .build(signer, rng)
             .map_err(|err| Error::TxBuild(format!("build tx failed: {}", err)))?;
 
         // Map each TxOut in the constructed transaction to its respective outlay.
@@ -1064,14 +2073,193 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
 
         Ok(TxProposal {
             utxos: selected_utxos,
-            outlays: destinations.to_vec(),
+            outlays: destinations
+                .iter()
+                .map(|outlay| Outlay {
+                    value: outlay.amount.value,
+                    receiver: outlay.receiver.clone(),
+                })
+                .collect(),
             tx,
             outlay_index_to_tx_out_index,
             outlay_confirmation_numbers,
+            outlay_shared_secrets,
         })
     }
 }
 
+/// Check that a signed TxProposal spends the inputs of an UnsignedTx, and
+/// pays its outlays with its fee, token id and tombstone block.
+///
+/// The TxOuts of the outlays are decoded with their shared secrets, and must
+/// pay the amount of their outlay to its receiver. All the other TxOuts are
+/// decoded with the view private key of the sender, and must return change to
+/// the change subaddress of the UnsignedTx.
+pub fn verify_signed_tx(
+    unsigned_tx: &UnsignedTx,
+    tx_proposal: &TxProposal,
+    sender_monitor_data: &MonitorData,
+) -> Result<(), Error> {
+    let mismatch = |what: &str| Error::SignedTxMismatch(what.to_owned());
+
+    let utxos: Vec<UnspentTxOut> = unsigned_tx
+        .inputs
+        .iter()
+        .map(|(utxo, _proof)| utxo.clone())
+        .collect();
+    if tx_proposal.utxos != utxos {
+        return Err(mismatch("inputs"));
+    }
+    if tx_proposal.outlays != unsigned_tx.outlays {
+        return Err(mismatch("outlays"));
+    }
+
+    let prefix = &tx_proposal.tx.prefix;
+    if prefix.fee != unsigned_tx.fee || prefix.fee_token_id != *unsigned_tx.token_id {
+        return Err(mismatch("fee"));
+    }
+    // The tombstone block can only be lowered, e.g. by the fog public key
+    // expiry of a recipient.
+    if prefix.tombstone_block == 0 || prefix.tombstone_block > unsigned_tx.tombstone_block {
+        return Err(mismatch("tombstone_block"));
+    }
+
+    // Every input must be in the ring of a distinct tx input.
+    if prefix.inputs.len() != utxos.len() {
+        return Err(mismatch("inputs"));
+    }
+    let mut unused_tx_inputs: Vec<_> = prefix.inputs.iter().collect();
+    for utxo in utxos.iter() {
+        let position = unused_tx_inputs
+            .iter()
+            .position(|tx_in| tx_in.ring.contains(&utxo.tx_out))
+            .ok_or_else(|| mismatch("inputs"))?;
+        unused_tx_inputs.remove(position);
+    }
+
+    // Every outlay must be paid by a distinct TxOut.
+    let num_outlays = unsigned_tx.outlays.len();
+    if tx_proposal.outlay_index_to_tx_out_index.len() != num_outlays
+        || tx_proposal.outlay_confirmation_numbers.len() != num_outlays
+        || tx_proposal.outlay_shared_secrets.len() != num_outlays
+    {
+        return Err(mismatch("outlays"));
+    }
+    let mut outlay_tx_out_indexes = HashSet::default();
+    let mut total_output_value = unsigned_tx.fee as u128;
+    for (outlay_index, outlay) in unsigned_tx.outlays.iter().enumerate() {
+        let tx_out_index = *tx_proposal
+            .outlay_index_to_tx_out_index
+            .get(&outlay_index)
+            .ok_or_else(|| mismatch("outlay_index_to_tx_out_index"))?;
+        let tx_out = prefix
+            .outputs
+            .get(tx_out_index)
+            .ok_or_else(|| mismatch("outlay_index_to_tx_out_index"))?;
+        if !outlay_tx_out_indexes.insert(tx_out_index) {
+            return Err(mismatch("outlay_index_to_tx_out_index"));
+        }
+
+        let shared_secret = &tx_proposal.outlay_shared_secrets[outlay_index];
+        if TxOutConfirmationNumber::from(shared_secret)
+            != tx_proposal.outlay_confirmation_numbers[outlay_index]
+        {
+            return Err(mismatch("outlay_confirmation_numbers"));
+        }
+
+        let target_key = RistrettoPublic::try_from(&tx_out.target_key)?;
+        if recover_public_subaddress_spend_key_from_shared_secret(shared_secret, &target_key)
+            != *outlay.receiver.spend_public_key()
+        {
+            return Err(mismatch("outlay receiver"));
+        }
+        let (amount, _blinding) = tx_out
+            .masked_amount
+            .get_value(shared_secret)
+            .map_err(|_| mismatch("outlay amount"))?;
+        if amount != Amount::new(outlay.value, unsigned_tx.token_id) {
+            return Err(mismatch("outlay amount"));
+        }
+        total_output_value += amount.value as u128;
+    }
+
+    // All the other TxOuts are change.
+    let view_private_key = *sender_monitor_data.view_account_key()?.view_private_key();
+    let change_address = sender_monitor_data.subaddress(unsigned_tx.change_subaddress)?;
+    for (tx_out_index, tx_out) in prefix.outputs.iter().enumerate() {
+        if outlay_tx_out_indexes.contains(&tx_out_index) {
+            continue;
+        }
+
+        let target_key = RistrettoPublic::try_from(&tx_out.target_key)?;
+        let tx_public_key = RistrettoPublic::try_from(&tx_out.public_key)?;
+        if recover_public_subaddress_spend_key(&view_private_key, &target_key, &tx_public_key)
+            != *change_address.spend_public_key()
+        {
+            return Err(mismatch("change receiver"));
+        }
+        let shared_secret = get_tx_out_shared_secret(&view_private_key, &tx_public_key);
+        let (amount, _blinding) = tx_out
+            .masked_amount
+            .get_value(&shared_secret)
+            .map_err(|_| mismatch("change amount"))?;
+        if amount.token_id != unsigned_tx.token_id {
+            return Err(mismatch("change amount"));
+        }
+        total_output_value += amount.value as u128;
+    }
+
+    let total_input_value: u128 = utxos.iter().map(|utxo| utxo.value as u128).sum();
+    if total_output_value != total_input_value {
+        return Err(mismatch("change amount"));
+    }
+
+    Ok(())
+}
+
+/// Add the real input to a ring of mixins, unless it is already a member.
+/// Returns the ring, the membership proofs of its members and the index of the
+/// real input.
+fn insert_real_input(
+    utxo: &UnspentTxOut,
+    proof: &TxOutMembershipProof,
+    mut ring: Vec<TxOut>,
+    mut membership_proofs: Vec<TxOutMembershipProof>,
+) -> (Vec<TxOut>, Vec<TxOutMembershipProof>, usize) {
+    assert_eq!(
+        ring.len(),
+        membership_proofs.len(),
+        "Each ring element must have a corresponding membership proof."
+    );
+
+    // Add the input to the ring.
+    let position_opt = ring.iter().position(|tx_out| *tx_out == utxo.tx_out);
+    let real_key_index = match position_opt {
+        Some(position) => {
+            // The input is already present in the ring.
+            // This could happen if ring elements are sampled randomly from the ledger.
+            position
+        }
+        None => {
+            // The input is not already in the ring.
+            if ring.is_empty() {
+                // Append the input and its proof of membership.
+                ring.push(utxo.tx_out.clone());
+                membership_proofs.push(proof.clone());
+            } else {
+                // Replace the first element of the ring.
+                ring[0] = utxo.tx_out.clone();
+                membership_proofs[0] = proof.clone();
+            }
+            // The real input is always the first element. This is safe because
+            // TransactionBuilder sorts each ring.
+            0
+        }
+    };
+
+    (ring, membership_proofs, real_key_index)
+}
+
 // Helper which extracts FogUri from PublicAddress or returns None, or returns
 // an error
 fn extract_fog_uri(addr: &PublicAddress) -> Result<Option<FogUri>, Error> {
@@ -1088,11 +2276,73 @@ fn extract_fog_uri(addr: &PublicAddress) -> Result<Option<FogUri>, Error> {
 mod test {
     use super::*;
     use mc_connection::{HardcodedCredentialsProvider, ThickClient};
-    use mc_crypto_keys::RistrettoPrivate;
     use mc_fog_report_validation::MockFogPubkeyResolver;
     use mc_transaction_core::{constants::MILLIMOB_TO_PICOMOB, tokens::Mob, Amount, Token};
     use mc_util_from_random::FromRandom;
     use rand::{rngs::StdRng, SeedableRng};
+    #[cfg(feature = "hardware-wallet")]
+    use {
+        crate::{
+            hardware_wallet::HardwareWalletError,
+            test_utils::{get_test_databases, DEFAULT_PER_RECIPIENT_AMOUNT},
+        },
+        mc_common::logger::test_with_logger,
+        mc_connection_test_utils::{test_client_uri, MockBlockchainConnection},
+        mc_consensus_enclave_api::FeeMap,
+        mc_crypto_ring_signature_signer::{LocalRingSigner, SignableInputRing},
+        mc_fog_report_validation_test_utils::MockFogResolver,
+        mc_transaction_core::{
+            onetime_keys::recover_public_subaddress_spend_key,
+            ring_signature::{KeyImage, RingMLSAG, Scalar},
+        },
+        std::sync::Mutex,
+    };
+
+    /// A hardware wallet that keeps the account key in memory.
+    #[cfg(feature = "hardware-wallet")]
+    struct MockHardwareWallet {
+        account_key: AccountKey,
+        reject: bool,
+        confirmed_outputs: Mutex<Vec<(PublicAddress, Amount)>>,
+    }
+
+    #[cfg(feature = "hardware-wallet")]
+    impl HardwareWallet for MockHardwareWallet {
+        fn view_account_key(&self) -> Result<ViewAccountKey, HardwareWalletError> {
+            Ok(ViewAccountKey::from(&self.account_key))
+        }
+
+        fn confirm_output(
+            &self,
+            receiver: &PublicAddress,
+            amount: Amount,
+        ) -> Result<(), HardwareWalletError> {
+            if self.reject {
+                return Err(HardwareWalletError::Rejected);
+            }
+            self.confirmed_outputs
+                .lock()
+                .unwrap()
+                .push((receiver.clone(), amount));
+            Ok(())
+        }
+
+        fn sign_ring(
+            &self,
+            message: &[u8],
+            signable_ring: &SignableInputRing,
+            output_blinding: Scalar,
+        ) -> Result<RingMLSAG, HardwareWalletError> {
+            LocalRingSigner::from(&self.account_key)
+                .sign(
+                    message,
+                    signable_ring,
+                    output_blinding,
+                    &mut rand::thread_rng(),
+                )
+                .map_err(|err| HardwareWalletError::Device(err.to_string()))
+        }
+    }
 
     fn generate_utxos(num_utxos: usize) -> Vec<UnspentTxOut> {
         let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
@@ -1358,4 +2608,218 @@ mod test {
         );
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_validate_tombstone() {
+        let block_info = |block_index| BlockInfo {
+            block_index,
+            minimum_fees: Default::default(),
+            network_block_version: 0,
+        };
+
+        // The network tip is the highest of the local ledger and the peers.
+        assert_eq!(get_next_block_index(10, &[]), 10);
+        assert_eq!(
+            get_next_block_index(10, &[block_info(4), block_info(14)]),
+            15
+        );
+        assert_eq!(get_next_block_index(10, &[block_info(4)]), 10);
+
+        assert!(validate_tombstone(10, 11).is_ok());
+        assert!(validate_tombstone(10, 10 + MAX_TOMBSTONE_BLOCKS).is_ok());
+        assert!(matches!(
+            validate_tombstone(10, 10),
+            Err(Error::TombstoneTooNear(10, 10))
+        ));
+        assert!(matches!(
+            validate_tombstone(10, 3),
+            Err(Error::TombstoneTooNear(3, 10))
+        ));
+        assert!(matches!(
+            validate_tombstone(10, 11 + MAX_TOMBSTONE_BLOCKS),
+            Err(Error::TombstoneTooFar(_, max)) if max == 10 + MAX_TOMBSTONE_BLOCKS
+        ));
+    }
+
+    #[test]
+    fn test_select_utxos_for_consolidation() {
+        let mut utxos = generate_utxos(40);
+        for (i, utxo) in utxos.iter_mut().enumerate() {
+            utxo.value = 1000 + i as u64;
+        }
+        let select = |utxos: &[UnspentTxOut], target_num_utxos: usize, fee: u64| {
+            TransactionsManager::<
+                ThickClient<HardcodedCredentialsProvider>,
+                MockFogPubkeyResolver,
+            >::select_utxos_for_consolidation(utxos, target_num_utxos, Mob::ID, fee)
+        };
+
+        // 40 UTXOs into 1 takes 3 transactions, the first two of which can be built
+        // right away from the 32 smallest UTXOs.
+        let plan = select(&utxos, 1, 100).unwrap();
+        assert_eq!(plan.num_txs, 3);
+        assert_eq!(plan.num_dust_utxos, 0);
+        assert_eq!(
+            plan.tx_inputs,
+            vec![utxos[0..16].to_vec(), utxos[16..32].to_vec()]
+        );
+
+        // 40 UTXOs into 20 takes two transactions, the second one merging 6 UTXOs.
+        let plan = select(&utxos, 20, 100).unwrap();
+        assert_eq!(plan.num_txs, 2);
+        assert_eq!(
+            plan.tx_inputs,
+            vec![utxos[0..16].to_vec(), utxos[16..22].to_vec()]
+        );
+
+        // Nothing to do when there are fewer UTXOs than the target.
+        assert!(select(&utxos, 40, 100).unwrap().tx_inputs.is_empty());
+        assert!(select(&utxos, 50, 100).unwrap().tx_inputs.is_empty());
+
+        // Merging UTXOs that do not cover the fee is refused.
+        match select(&utxos, 38, 10_000) {
+            Err(Error::OptimizationNotBeneficial(_)) => {}
+            result => panic!("Unexpected result: {:?}", result),
+        }
+
+        // Dust UTXOs, which could not cover the fee even merged MAX_INPUTS at a time,
+        // are left alone.
+        let plan = select(&utxos, 1, 16_000_000).unwrap();
+        assert_eq!(plan.num_txs, 0);
+        assert_eq!(plan.num_dust_utxos, 40);
+        assert!(plan.tx_inputs.is_empty());
+    }
+
+    #[cfg(feature = "hardware-wallet")]
+    #[test_with_logger]
+    fn test_build_transaction_with_hardware_wallet(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([2u8; 32]);
+        let sender = AccountKey::random(&mut rng);
+        let (ledger_db, mobilecoind_db) = get_test_databases(
+            BlockVersion::MAX,
+            3,
+            &[sender.default_subaddress()],
+            10,
+            logger.clone(),
+            &mut rng,
+        );
+
+        let peer = MockBlockchainConnection::new(
+            test_client_uri(1),
+            ledger_db.clone(),
+            0,
+            FeeMap::default(),
+        );
+        let mut transactions_manager = TransactionsManager::new(
+            ledger_db.clone(),
+            mobilecoind_db.clone(),
+            ConnectionManager::new(vec![peer], logger.clone()),
+            Arc::new(|_| Ok(MockFogResolver::default())),
+            logger,
+        );
+
+        let monitor_data =
+            MonitorData::new_watch_only(ViewAccountKey::from(&sender), None, 0, 1, 0, "").unwrap();
+        let monitor_id = mobilecoind_db.add_monitor(&monitor_data).unwrap();
+
+        // Two outputs of the sender, as a watch-only monitor stores them.
+        let spend_public_key = *sender.default_subaddress().spend_public_key();
+        let utxos: Vec<UnspentTxOut> = (0..ledger_db.num_txos().unwrap())
+            .map(|index| ledger_db.get_tx_out_by_index(index).unwrap())
+            .filter(|tx_out| {
+                recover_public_subaddress_spend_key(
+                    sender.view_private_key(),
+                    &RistrettoPublic::try_from(&tx_out.target_key).unwrap(),
+                    &RistrettoPublic::try_from(&tx_out.public_key).unwrap(),
+                ) == spend_public_key
+            })
+            .take(2)
+            .map(|tx_out| UnspentTxOut {
+                key_image: KeyImage::from(*tx_out.public_key.as_bytes()),
+                tx_out,
+                subaddress_index: 0,
+                value: DEFAULT_PER_RECIPIENT_AMOUNT,
+                attempted_spend_height: 0,
+                attempted_spend_tombstone: 0,
+                token_id: *Mob::ID,
+            })
+            .collect();
+        assert_eq!(utxos.len(), 2);
+
+        let outlays = vec![Outlay {
+            value: 123,
+            receiver: AccountKey::random(&mut rng).default_subaddress(),
+        }];
+        let build_transaction = |transactions_manager: &TransactionsManager<_, _>| {
+            transactions_manager.build_transaction(
+                &monitor_id,
+                Mob::ID,
+                0,
+                &utxos,
+                &outlays,
+                &[],
+                Mob::MINIMUM_FEE,
+                0,
+                None,
+            )
+        };
+
+        // Without a hardware wallet, the monitor cannot build transactions.
+        assert!(matches!(
+            build_transaction(&transactions_manager),
+            Err(Error::WatchOnlyMonitor)
+        ));
+
+        // Nor with the hardware wallet of another account.
+        transactions_manager.set_hardware_wallet(Arc::new(MockHardwareWallet {
+            account_key: AccountKey::random(&mut rng),
+            reject: false,
+            confirmed_outputs: Mutex::default(),
+        }));
+        assert!(matches!(
+            build_transaction(&transactions_manager),
+            Err(Error::WatchOnlyMonitor)
+        ));
+
+        // The user can reject the transaction on the device.
+        transactions_manager.set_hardware_wallet(Arc::new(MockHardwareWallet {
+            account_key: sender.clone(),
+            reject: true,
+            confirmed_outputs: Mutex::default(),
+        }));
+        assert!(matches!(
+            build_transaction(&transactions_manager),
+            Err(Error::HardwareWallet(HardwareWalletError::Rejected))
+        ));
+
+        // The device confirms the outlays and signs the inputs.
+        let wallet = Arc::new(MockHardwareWallet {
+            account_key: sender.clone(),
+            reject: false,
+            confirmed_outputs: Mutex::default(),
+        });
+        transactions_manager.set_hardware_wallet(wallet.clone());
+        let tx_proposal = build_transaction(&transactions_manager).unwrap();
+
+        assert_eq!(
+            *wallet.confirmed_outputs.lock().unwrap(),
+            vec![(outlays[0].receiver.clone(), Amount::new(123, Mob::ID))]
+        );
+        assert_eq!(tx_proposal.utxos, utxos);
+
+        let mut expected_key_images: Vec<KeyImage> = utxos
+            .iter()
+            .map(|utxo| {
+                KeyImage::from(&recover_onetime_private_key(
+                    &RistrettoPublic::try_from(&utxo.tx_out.public_key).unwrap(),
+                    sender.view_private_key(),
+                    &sender.subaddress_spend_private(0),
+                ))
+            })
+            .collect();
+        let mut key_images = tx_proposal.tx.key_images();
+        expected_key_images.sort();
+        key_images.sort();
+        assert_eq!(key_images, expected_key_images);
+    }
 }
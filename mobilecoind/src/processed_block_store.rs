@@ -3,10 +3,13 @@
 //! Database storage for data obtained by processing blocks.
 //! * Stores a map of (monitor id, block number) -> list of transactions that
 //!   appeared in the given block number and belong to a given monitor id.
+//! * Stores a map of (monitor id, key image) -> payment submitted by the
+//!   monitor, until the block spending the key image is processed.
 
 use crate::{error::Error, monitor_store::MonitorId, utxo_store::UnspentTxOut};
 use lmdb::{Cursor, Database, DatabaseFlags, Environment, RwTransaction, Transaction, WriteFlags};
-use mc_common::logger::Logger;
+use mc_account_keys::PublicAddress;
+use mc_common::{logger::Logger, HashSet};
 use mc_crypto_keys::CompressedRistrettoPublic;
 use mc_transaction_core::ring_signature::KeyImage;
 use prost::{Enumeration, Message};
@@ -15,6 +18,17 @@ use std::sync::Arc;
 // LMDB Database Names
 pub const PROCESSED_BLOCK_KEY_TO_PROCESSED_TX_OUTS_DB_NAME: &str =
     "mobilecoind_db:processed_block_store:processed_block_key_to_processed_tx_outs";
+pub const PENDING_PAYMENT_KEY_TO_PAYMENT_DB_NAME: &str =
+    "mobilecoind_db:processed_block_store:pending_payment_key_to_payment";
+
+/// The key of a pending payment: 32 bytes of MonitorId followed by the 32
+/// bytes of the key image of one of its inputs.
+fn pending_payment_key(monitor_id: &MonitorId, key_image: &KeyImage) -> [u8; 64] {
+    let mut buf = [0u8; 64];
+    buf[0..32].copy_from_slice(monitor_id.as_bytes());
+    buf[32..64].copy_from_slice(key_image.as_bytes());
+    buf
+}
 
 /// Type used as the key in the databases managed by the processed block store.
 #[derive(Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -107,6 +121,49 @@ pub struct ProcessedTxOut {
     /// Token id.
     #[prost(uint64, tag = "8")]
     pub token_id: u64,
+
+    /// The payment submitted by the monitor that spent this TxOut. Only set
+    /// for the first input of the payment, if it was submitted through this
+    /// mobilecoind.
+    #[prost(message, optional, tag = "9")]
+    pub payment: Option<SentPayment>,
+}
+
+/// A payment submitted by a monitor.
+#[derive(Clone, Eq, Hash, PartialEq, Message)]
+pub struct SentPayment {
+    /// The key images of the inputs of the payment.
+    #[prost(message, repeated, tag = "1")]
+    pub key_images: Vec<KeyImage>,
+
+    /// The recipients of the payment, not counting the change.
+    #[prost(message, repeated, tag = "2")]
+    pub outlays: Vec<SentOutlay>,
+
+    /// The fee of the payment.
+    #[prost(uint64, tag = "3")]
+    pub fee: u64,
+
+    /// The token id of the outlays and of the fee.
+    #[prost(uint64, tag = "4")]
+    pub token_id: u64,
+
+    /// The tombstone block of the payment, after which it is forgotten if its
+    /// inputs were not spent.
+    #[prost(uint64, tag = "5")]
+    pub tombstone_block: u64,
+}
+
+/// A recipient of a SentPayment.
+#[derive(Clone, Eq, Hash, PartialEq, Message)]
+pub struct SentOutlay {
+    /// The address that was paid.
+    #[prost(message, required, tag = "1")]
+    pub receiver: PublicAddress,
+
+    /// The value paid to it.
+    #[prost(uint64, tag = "2")]
+    pub value: u64,
 }
 
 impl ProcessedTxOut {
@@ -118,6 +175,7 @@ impl ProcessedTxOut {
             value: src.value,
             direction: ProcessedTxOutDirection::Received as i32,
             token_id: src.token_id,
+            payment: None,
         }
     }
 
@@ -129,6 +187,7 @@ impl ProcessedTxOut {
             value: src.value,
             direction: ProcessedTxOutDirection::Spent as i32,
             token_id: src.token_id,
+            payment: None,
         }
     }
 }
@@ -141,6 +200,10 @@ pub struct ProcessedBlockStore {
 
     /// Mapping of ProcessedBlockKey -> [ProcessedTxOut].
     processed_block_key_to_processed_tx_outs: Database,
+
+    /// Mapping of (MonitorId, KeyImage) -> SentPayment, for each input of the
+    /// payments whose inputs were not found spent yet.
+    pending_payment_key_to_payment: Database,
 }
 
 impl ProcessedBlockStore {
@@ -149,10 +212,15 @@ impl ProcessedBlockStore {
             Some(PROCESSED_BLOCK_KEY_TO_PROCESSED_TX_OUTS_DB_NAME),
             DatabaseFlags::DUP_SORT,
         )?;
+        let pending_payment_key_to_payment = env.create_db(
+            Some(PENDING_PAYMENT_KEY_TO_PAYMENT_DB_NAME),
+            DatabaseFlags::empty(),
+        )?;
 
         Ok(Self {
             _env: env,
             processed_block_key_to_processed_tx_outs,
+            pending_payment_key_to_payment,
         })
     }
 
@@ -181,6 +249,177 @@ impl ProcessedBlockStore {
             .collect::<Result<Vec<_>, Error>>()
     }
 
+    /// Get the processed TxOuts of a given monitor id in all blocks, as (block
+    /// index, processed tx out) pairs ordered by block index.
+    pub fn get_processed_tx_outs(
+        &self,
+        db_txn: &impl Transaction,
+        monitor_id: &MonitorId,
+    ) -> Result<Vec<(u64, ProcessedTxOut)>, Error> {
+        let start_key = ProcessedBlockKey::new(monitor_id, 0);
+        let start_key_bytes = start_key.to_vec();
+
+        let mut cursor = db_txn.open_ro_cursor(self.processed_block_key_to_processed_tx_outs)?;
+
+        let mut processed_tx_outs = Vec::new();
+        for result in cursor.iter_from(&start_key_bytes) {
+            let (db_key, db_value) = result?;
+            let key = ProcessedBlockKey::try_from(db_key)?;
+            if key.monitor_id != *monitor_id {
+                break;
+            }
+            processed_tx_outs.push((key.block_index, mc_util_serial::decode(db_value)?));
+        }
+
+        Ok(processed_tx_outs)
+    }
+
+    /// Get a page of the processed TxOuts of a given monitor id in all blocks,
+    /// as (block index, processed tx out) pairs ordered by block index, along
+    /// with the total number of processed TxOuts.
+    ///
+    /// Only the TxOuts of the page are decoded, so large histories are cheap
+    /// to page through.
+    ///
+    /// # Arguments
+    /// * `offset` - The number of TxOuts to skip.
+    /// * `limit` - The maximum number of TxOuts to return.
+    /// * `newest_first` - Whether the page starts at the most recent TxOuts.
+    pub fn get_processed_tx_outs_page(
+        &self,
+        db_txn: &impl Transaction,
+        monitor_id: &MonitorId,
+        offset: u64,
+        limit: u64,
+        newest_first: bool,
+    ) -> Result<(Vec<(u64, ProcessedTxOut)>, u64), Error> {
+        let start_key_bytes = ProcessedBlockKey::new(monitor_id, 0).to_vec();
+        let mut cursor = db_txn.open_ro_cursor(self.processed_block_key_to_processed_tx_outs)?;
+
+        // Count the TxOuts of the monitor, without decoding them.
+        let mut total_count = 0u64;
+        for result in cursor.iter_from(&start_key_bytes) {
+            let (db_key, _db_value) = result?;
+            if db_key[0..32] != *monitor_id.as_bytes() {
+                break;
+            }
+            total_count += 1;
+        }
+
+        // The range of the page, in block order.
+        let (start, end) = if newest_first {
+            (
+                total_count.saturating_sub(offset.saturating_add(limit)),
+                total_count.saturating_sub(offset),
+            )
+        } else {
+            (
+                offset.min(total_count),
+                offset.saturating_add(limit).min(total_count),
+            )
+        };
+
+        let mut page = Vec::with_capacity((end - start) as usize);
+        if start < end {
+            for result in cursor
+                .iter_from(&start_key_bytes)
+                .skip(start as usize)
+                .take((end - start) as usize)
+            {
+                let (db_key, db_value) = result?;
+                let key = ProcessedBlockKey::try_from(db_key)?;
+                page.push((key.block_index, mc_util_serial::decode(db_value)?));
+            }
+        }
+        if newest_first {
+            page.reverse();
+        }
+
+        Ok((page, total_count))
+    }
+
+    /// Record a payment submitted by a given monitor id, so that it is
+    /// attached to the first of its inputs once they are found spent.
+    pub fn add_pending_payment<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        monitor_id: &MonitorId,
+        payment: &SentPayment,
+    ) -> Result<(), Error> {
+        let payment_bytes = mc_util_serial::encode(payment);
+        for key_image in payment.key_images.iter() {
+            db_txn.put(
+                self.pending_payment_key_to_payment,
+                &pending_payment_key(monitor_id, key_image),
+                &payment_bytes,
+                WriteFlags::empty(),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Take the pending payment of a given monitor id that spends a key image,
+    /// if `is_spent_with` holds for all of its key images, i.e. if they were
+    /// all spent in the same block. Otherwise, the key image was spent by
+    /// another transaction and the payment is left alone.
+    pub fn take_pending_payment<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        monitor_id: &MonitorId,
+        key_image: &KeyImage,
+        is_spent_with: impl Fn(&KeyImage) -> bool,
+    ) -> Result<Option<SentPayment>, Error> {
+        let payment: SentPayment = match db_txn.get(
+            self.pending_payment_key_to_payment,
+            &pending_payment_key(monitor_id, key_image),
+        ) {
+            Ok(payment_bytes) => mc_util_serial::decode(payment_bytes)?,
+            Err(lmdb::Error::NotFound) => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+
+        if !payment.key_images.iter().all(is_spent_with) {
+            return Ok(None);
+        }
+
+        for key_image in payment.key_images.iter() {
+            match db_txn.del(
+                self.pending_payment_key_to_payment,
+                &pending_payment_key(monitor_id, key_image),
+                None,
+            ) {
+                Ok(()) | Err(lmdb::Error::NotFound) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(Some(payment))
+    }
+
+    /// Forget the pending payments of a given monitor id that can no longer
+    /// be included in a block after the given one.
+    fn remove_expired_pending_payments<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        monitor_id: &MonitorId,
+        block_index: u64,
+    ) -> Result<(), Error> {
+        let mut cursor = db_txn.open_rw_cursor(self.pending_payment_key_to_payment)?;
+
+        for result in cursor.iter_from(monitor_id.as_bytes()) {
+            let (db_key, db_value) = result?;
+            if db_key[0..32] != *monitor_id.as_bytes() {
+                break;
+            }
+            let payment: SentPayment = mc_util_serial::decode(db_value)?;
+            if payment.tombstone_block <= block_index + 1 {
+                cursor.del(WriteFlags::empty())?;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Remove the data associated with a given monitor id.
     pub fn remove<'env>(
         &self,
@@ -200,10 +439,42 @@ impl ProcessedBlockStore {
                 break;
             }
         }
+        drop(cursor);
+
+        let mut cursor = db_txn.open_rw_cursor(self.pending_payment_key_to_payment)?;
+        for (db_key, _db_value) in cursor
+            .iter_from(monitor_id.as_bytes())
+            .filter_map(|r| r.ok())
+        {
+            if db_key[0..32] == *monitor_id.as_bytes() {
+                cursor.del(WriteFlags::empty())?;
+            } else {
+                break;
+            }
+        }
 
         Ok(())
     }
 
+    /// Store a processed TxOut of a given monitor id at a given block, when
+    /// restoring a monitor from a backup.
+    pub fn insert<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        monitor_id: &MonitorId,
+        block_index: u64,
+        processed_tx_out: &ProcessedTxOut,
+    ) -> Result<(), Error> {
+        let key = ProcessedBlockKey::new(monitor_id, block_index);
+        db_txn.put(
+            self.processed_block_key_to_processed_tx_outs,
+            &key.to_vec(),
+            &mc_util_serial::encode(processed_tx_out),
+            WriteFlags::empty(),
+        )?;
+        Ok(())
+    }
+
     /// Feed data processed from a given block.
     pub fn block_processed<'env>(
         &self,
@@ -227,8 +498,14 @@ impl ProcessedBlockStore {
             )?;
         }
 
+        let spent_key_images: HashSet<KeyImage> =
+            spent_utxos.iter().map(|utxo| utxo.key_image).collect();
         for utxo in spent_utxos.iter() {
-            let processed_tx_out = ProcessedTxOut::from_spent_utxo(utxo);
+            let mut processed_tx_out = ProcessedTxOut::from_spent_utxo(utxo);
+            processed_tx_out.payment =
+                self.take_pending_payment(db_txn, monitor_id, &utxo.key_image, |key_image| {
+                    spent_key_images.contains(key_image)
+                })?;
             let processed_tx_out_bytes = mc_util_serial::encode(&processed_tx_out);
             db_txn.put(
                 self.processed_block_key_to_processed_tx_outs,
@@ -238,6 +515,8 @@ impl ProcessedBlockStore {
             )?;
         }
 
+        self.remove_expired_pending_payments(db_txn, monitor_id, block_index)?;
+
         Ok(())
     }
 }
@@ -586,4 +865,192 @@ mod test {
             db_txn.commit().unwrap();
         }
     }
+
+    // All the processed TxOuts of a monitor are returned in block order.
+    #[test_with_logger]
+    fn test_get_processed_tx_outs(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([124u8; 32]);
+        let (env, _ledger_db, store, account, utxos) =
+            setup_test_processed_block_store(&mut rng, &logger);
+
+        let monitor_data = MonitorData::new(
+            account.clone(),
+            0,  // first_subaddress
+            20, // num_subaddresses
+            0,  // first_block
+            "", // name
+        )
+        .expect("failed to create data");
+        let monitor_id = MonitorId::from(&monitor_data);
+
+        let monitor_data2 = MonitorData::new(
+            account, 30, // first_subaddress
+            20, // num_subaddresses
+            0,  // first_block
+            "", // name
+        )
+        .expect("failed to create data");
+        let monitor_id2 = MonitorId::from(&monitor_data2);
+
+        let mut db_txn = env.begin_rw_txn().unwrap();
+        assert!(store
+            .get_processed_tx_outs(&db_txn, &monitor_id)
+            .expect("get_processed_tx_outs failed")
+            .is_empty());
+
+        // Insert the blocks out of order, and data for another monitor in between.
+        store
+            .block_processed(&mut db_txn, &monitor_id, 5, &utxos[2..3], &utxos[0..1])
+            .expect("block_processed failed");
+        store
+            .block_processed(&mut db_txn, &monitor_id2, 0, &utxos[3..4], &[])
+            .expect("block_processed failed");
+        store
+            .block_processed(&mut db_txn, &monitor_id, 1, &utxos[0..2], &[])
+            .expect("block_processed failed");
+
+        let processed_tx_outs = store
+            .get_processed_tx_outs(&db_txn, &monitor_id)
+            .expect("get_processed_tx_outs failed");
+        let block_indexes: Vec<u64> = processed_tx_outs.iter().map(|(index, _)| *index).collect();
+        assert_eq!(block_indexes, vec![1, 1, 5, 5]);
+        assert_eq!(
+            HashSet::from_iter(processed_tx_outs.into_iter().map(|(_, tx_out)| tx_out)),
+            HashSet::from_iter(vec![
+                ProcessedTxOut::from_received_utxo(&utxos[0]),
+                ProcessedTxOut::from_received_utxo(&utxos[1]),
+                ProcessedTxOut::from_received_utxo(&utxos[2]),
+                ProcessedTxOut::from_spent_utxo(&utxos[0]),
+            ])
+        );
+
+        let processed_tx_outs = store
+            .get_processed_tx_outs(&db_txn, &monitor_id2)
+            .expect("get_processed_tx_outs failed");
+        assert_eq!(
+            processed_tx_outs,
+            vec![(0, ProcessedTxOut::from_received_utxo(&utxos[3]))]
+        );
+
+        // Pages are slices of the full history, in either order.
+        let history = store
+            .get_processed_tx_outs(&db_txn, &monitor_id)
+            .expect("get_processed_tx_outs failed");
+        let mut reversed_history = history.clone();
+        reversed_history.reverse();
+        for (offset, limit) in [(0, u64::MAX), (0, 2), (1, 2), (3, 5), (4, 1), (10, 1)] {
+            let expected_len = (history.len() as u64).saturating_sub(offset).min(limit) as usize;
+            let page = |newest_first| {
+                store
+                    .get_processed_tx_outs_page(&db_txn, &monitor_id, offset, limit, newest_first)
+                    .expect("get_processed_tx_outs_page failed")
+            };
+            assert_eq!(
+                page(false),
+                (
+                    history
+                        .iter()
+                        .skip(offset as usize)
+                        .take(expected_len)
+                        .cloned()
+                        .collect(),
+                    4
+                )
+            );
+            assert_eq!(
+                page(true),
+                (
+                    reversed_history
+                        .iter()
+                        .skip(offset as usize)
+                        .take(expected_len)
+                        .cloned()
+                        .collect(),
+                    4
+                )
+            );
+        }
+    }
+
+    // A pending payment is attached to the first of its inputs once all of them
+    // are spent in the same block, and forgotten after its tombstone block.
+    #[test_with_logger]
+    fn test_pending_payments(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([125u8; 32]);
+        let (env, _ledger_db, store, account, utxos) =
+            setup_test_processed_block_store(&mut rng, &logger);
+
+        let monitor_data = MonitorData::new(
+            account, 0,  // first_subaddress
+            20, // num_subaddresses
+            0,  // first_block
+            "", // name
+        )
+        .expect("failed to create data");
+        let monitor_id = MonitorId::from(&monitor_data);
+
+        let payment = |inputs: &[UnspentTxOut], tombstone_block| SentPayment {
+            key_images: inputs.iter().map(|utxo| utxo.key_image).collect(),
+            outlays: vec![SentOutlay {
+                receiver: AccountKey::random(&mut StdRng::from_seed([126u8; 32]))
+                    .default_subaddress(),
+                value: 10,
+            }],
+            fee: 1,
+            token_id: *Mob::ID,
+            tombstone_block,
+        };
+        let spent_payments = |db_txn: &RwTransaction, block_index| -> Vec<Option<SentPayment>> {
+            let mut spent = store
+                .get_processed_block(db_txn, &monitor_id, block_index)
+                .expect("get_processed_block failed")
+                .into_iter()
+                .filter(|tx_out| tx_out.direction == ProcessedTxOutDirection::Spent as i32)
+                .collect::<Vec<_>>();
+            spent.sort_by_key(|tx_out| tx_out.key_image.as_bytes().to_vec());
+            spent.into_iter().map(|tx_out| tx_out.payment).collect()
+        };
+
+        let mut db_txn = env.begin_rw_txn().unwrap();
+        let payment1 = payment(&utxos[0..2], 20);
+        let payment2 = payment(&utxos[2..4], 20);
+        let payment3 = payment(&utxos[4..5], 3);
+        for payment in [&payment1, &payment2, &payment3] {
+            store
+                .add_pending_payment(&mut db_txn, &monitor_id, payment)
+                .expect("add_pending_payment failed");
+        }
+
+        // Both inputs of the first payment are spent in block 1, so it is attached
+        // to one of them.
+        store
+            .block_processed(&mut db_txn, &monitor_id, 1, &[], &utxos[0..2])
+            .expect("block_processed failed");
+        let payments = spent_payments(&db_txn, 1);
+        assert_eq!(payments.len(), 2);
+        assert_eq!(
+            payments.iter().flatten().collect::<Vec<_>>(),
+            vec![&payment1]
+        );
+
+        // Only one input of the second payment is spent in block 2, so it was spent
+        // by another transaction. Block 2 also expires the third payment.
+        store
+            .block_processed(&mut db_txn, &monitor_id, 2, &[], &utxos[2..3])
+            .expect("block_processed failed");
+        assert_eq!(spent_payments(&db_txn, 2), vec![None]);
+        store
+            .block_processed(&mut db_txn, &monitor_id, 3, &[], &utxos[3..5])
+            .expect("block_processed failed");
+        assert_eq!(spent_payments(&db_txn, 3), vec![None, None]);
+
+        // Nothing is left once the second payment expires.
+        store
+            .block_processed(&mut db_txn, &monitor_id, 19, &[], &[])
+            .expect("block_processed failed");
+        let mut cursor = db_txn
+            .open_ro_cursor(store.pending_payment_key_to_payment)
+            .unwrap();
+        assert_eq!(cursor.iter_start().count(), 0);
+    }
 }
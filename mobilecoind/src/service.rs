@@ -7,17 +7,28 @@
 //! * writes matching transactions to a local DB, organized by subaddress_id
 
 use crate::{
-    database::Database,
+    counters,
+    database::{Database, KeyImageUpdate},
+    db_crypto::DbCryptoError,
     error::Error,
+    idempotency_store::{idempotency_key, IdempotencyKey, IdempotentRequest},
+    memo::decode_memo,
+    monitor_backup::MonitorBackup,
     monitor_store::{MonitorData, MonitorId},
-    payments::{Outlay, TransactionsManager, TxProposal},
+    payments::{verify_signed_tx, Outlay, OutlayV2, TransactionsManager, TxProposal, UnsignedTx},
+    processed_block_store::{ProcessedTxOut, SentOutlay, SentPayment},
+    subaddress_store::{SubaddressId, SubaddressSPKId},
     sync::SyncThread,
     utxo_store::{UnspentTxOut, UtxoId},
 };
 use bip39::{Language, Mnemonic, MnemonicType};
-use grpcio::{EnvBuilder, RpcContext, RpcStatus, RpcStatusCode, ServerBuilder, UnarySink};
+use futures::{channel::mpsc, FutureExt, SinkExt, StreamExt, TryFutureExt};
+use grpcio::{
+    EnvBuilder, RpcContext, RpcStatus, RpcStatusCode, ServerBuilder, ServerStreamingSink,
+    UnarySink, WriteFlags,
+};
 use mc_account_keys::{
-    burn_address, AccountKey, PublicAddress, RootIdentity, DEFAULT_SUBADDRESS_INDEX,
+    burn_address, AccountKey, PublicAddress, RootIdentity, ViewAccountKey, DEFAULT_SUBADDRESS_INDEX,
 };
 use mc_account_keys_slip10::Slip10KeyGenerator;
 use mc_common::{
@@ -25,8 +36,10 @@ use mc_common::{
     HashMap,
 };
 use mc_connection::{BlockInfo, BlockchainConnection, UserTxConnection};
-use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPublic};
+use mc_crypto_hashes::{Blake2b256, Digest};
+use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPublic, RistrettoSignature};
 use mc_fog_report_validation::FogPubkeyResolver;
+use mc_fog_sig_authority::Verifier as AuthorityVerifier;
 use mc_ledger_db::{Error as LedgerError, Ledger, LedgerDB};
 use mc_ledger_sync::{NetworkState, PollingNetworkState};
 use mc_mobilecoind_api::{
@@ -35,21 +48,29 @@ use mc_mobilecoind_api::{
     MobilecoindUri,
 };
 use mc_transaction_core::{
+    constants::MAX_TOMBSTONE_BLOCKS,
     get_tx_out_shared_secret,
-    onetime_keys::recover_onetime_private_key,
+    onetime_keys::{recover_onetime_private_key, recover_public_subaddress_spend_key},
     ring_signature::KeyImage,
     tx::{TxOut, TxOutConfirmationNumber, TxOutMembershipProof},
-    TokenId,
+    Amount, SignedContingentInput, TokenId,
 };
 use mc_transaction_std::{BurnRedemptionMemo, BurnRedemptionMemoBuilder};
 use mc_util_from_random::FromRandom;
 use mc_util_grpc::{
-    rpc_internal_error, rpc_invalid_arg_error, rpc_logger, send_result, AdminService,
-    BuildInfoService, ConnectionUriGrpcioServer,
+    rpc_internal_error, rpc_invalid_arg_error, rpc_logger, rpc_permissions_error,
+    rpc_precondition_error, send_result, AdminService, BuildInfoService, ConnectionUriGrpcioServer,
 };
+use mc_util_metrics::SVC_COUNTERS;
+use mc_util_uri::FogUri;
 use mc_watcher::watcher_db::WatcherDB;
-use protobuf::{ProtobufEnum, RepeatedField};
-use std::sync::{Arc, Mutex, RwLock};
+use protobuf::{Message, ProtobufEnum, RepeatedField};
+use std::{
+    cmp::max,
+    str::FromStr,
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
+};
 
 pub struct Service {
     /// Sync thread.
@@ -69,6 +90,8 @@ impl Service {
         watcher_db: Option<WatcherDB>,
         transactions_manager: TransactionsManager<T, FPR>,
         network_state: Arc<RwLock<PollingNetworkState<T>>>,
+        trusted_fog_authorities: Vec<Vec<u8>>,
+        idempotency_retention: Duration,
         listen_uri: &MobilecoindUri,
         num_workers: Option<usize>,
         logger: Logger,
@@ -110,6 +133,8 @@ impl Service {
             mobilecoind_db,
             watcher_db,
             network_state,
+            trusted_fog_authorities,
+            idempotency_retention,
             start_sync_thread,
             logger.clone(),
         );
@@ -167,6 +192,11 @@ pub struct ServiceApi<
     mobilecoind_db: Database,
     watcher_db: Option<WatcherDB>,
     network_state: Arc<RwLock<PollingNetworkState<T>>>,
+    /// DER subjectPublicKeyInfos of the fog authorities trusted by
+    /// ValidateFogAddress.
+    trusted_fog_authorities: Arc<Vec<Vec<u8>>>,
+    /// How long idempotency keys of payment requests are remembered.
+    idempotency_retention: Duration,
     start_sync_thread: Arc<dyn Fn() + Send + Sync>,
     logger: Logger,
 }
@@ -181,6 +211,8 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
             mobilecoind_db: self.mobilecoind_db.clone(),
             watcher_db: self.watcher_db.clone(),
             network_state: self.network_state.clone(),
+            trusted_fog_authorities: self.trusted_fog_authorities.clone(),
+            idempotency_retention: self.idempotency_retention,
             start_sync_thread: self.start_sync_thread.clone(),
             logger: self.logger.clone(),
         }
@@ -196,6 +228,8 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         mobilecoind_db: Database,
         watcher_db: Option<WatcherDB>,
         network_state: Arc<RwLock<PollingNetworkState<T>>>,
+        trusted_fog_authorities: Vec<Vec<u8>>,
+        idempotency_retention: Duration,
         start_sync_thread: Arc<dyn Fn() + Send + Sync>,
         logger: Logger,
     ) -> Self {
@@ -205,6 +239,8 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
             mobilecoind_db,
             watcher_db,
             network_state,
+            trusted_fog_authorities: Arc::new(trusted_fog_authorities),
+            idempotency_retention,
             start_sync_thread,
             logger,
         }
@@ -235,21 +271,65 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         &mut self,
         request: api::AddMonitorRequest,
     ) -> Result<api::AddMonitorResponse, RpcStatus> {
-        // Get the AccountKey from the GRPC request.
-        let proto_account_key = request.account_key.as_ref().ok_or_else(|| {
-            RpcStatus::with_message(RpcStatusCode::INVALID_ARGUMENT, "account_key".into())
-        })?;
-        let account_key = AccountKey::try_from(proto_account_key)
-            .map_err(|err| rpc_internal_error("account_key.try_from", err, &self.logger))?;
-
-        // Populate a new `MonitorData` instance.
-        let data = MonitorData::new(
-            account_key,
-            request.first_subaddress,
-            request.num_subaddresses,
-            request.first_block,
-            &request.name,
-        )
+        // Populate a new `MonitorData` instance, from either the AccountKey or the
+        // ViewAccountKey in the GRPC request.
+        let data = match (
+            request.account_key.as_ref(),
+            request.view_account_key.as_ref(),
+        ) {
+            (Some(_), None) if request.has_default_public_address() => {
+                return Err(RpcStatus::with_message(
+                    RpcStatusCode::INVALID_ARGUMENT,
+                    "default_public_address must not be set with account_key".into(),
+                ))
+            }
+            (Some(proto_account_key), None) => {
+                let account_key = AccountKey::try_from(proto_account_key)
+                    .map_err(|err| rpc_internal_error("account_key.try_from", err, &self.logger))?;
+                MonitorData::new(
+                    account_key,
+                    request.first_subaddress,
+                    request.num_subaddresses,
+                    request.first_block,
+                    &request.name,
+                )
+            }
+            (None, Some(proto_view_account_key)) => {
+                let view_account_key =
+                    ViewAccountKey::try_from(proto_view_account_key).map_err(|err| {
+                        rpc_internal_error("view_account_key.try_from", err, &self.logger)
+                    })?;
+                let default_public_address = if request.has_default_public_address() {
+                    Some(
+                        PublicAddress::try_from(request.get_default_public_address()).map_err(
+                            |err| {
+                                rpc_internal_error(
+                                    "default_public_address.try_from",
+                                    err,
+                                    &self.logger,
+                                )
+                            },
+                        )?,
+                    )
+                } else {
+                    None
+                };
+                MonitorData::new_watch_only(
+                    view_account_key,
+                    default_public_address,
+                    request.first_subaddress,
+                    request.num_subaddresses,
+                    request.first_block,
+                    &request.name,
+                )
+            }
+            _ => {
+                return Err(RpcStatus::with_message(
+                    RpcStatusCode::INVALID_ARGUMENT,
+                    "exactly one of account_key and view_account_key must be set".into(),
+                ))
+            }
+        }
         .map_err(|err| rpc_internal_error("monitor_data.new", err, &self.logger))?;
 
         // Insert into database. Return the id and flag if the monitor already existed.
@@ -281,6 +361,7 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
             .map_err(|err| {
                 rpc_internal_error("mobilecoind_db.remove_monitor", err, &self.logger)
             })?;
+        counters::remove_monitor(&monitor_id);
 
         // Return success response.
         let response = api::Empty::new();
@@ -318,7 +399,22 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
             })?;
 
         let mut status = api::MonitorStatus::new();
-        status.set_account_key(mc_api::external::AccountKey::from(&data.account_key));
+        match data.account_key.as_ref() {
+            Some(account_key) => {
+                status.set_account_key(mc_api::external::AccountKey::from(account_key))
+            }
+            None => {
+                let view_account_key = data.view_account_key().map_err(|err| {
+                    rpc_internal_error("monitor_data.view_account_key", err, &self.logger)
+                })?;
+                status.set_view_account_key(mc_api::external::ViewAccountKey::from(
+                    &view_account_key,
+                ));
+                if let Some(address) = data.default_public_address.as_ref() {
+                    status.set_default_public_address(address.into());
+                }
+            }
+        }
         status.set_first_subaddress(data.first_subaddress);
         status.set_num_subaddresses(data.num_subaddresses);
         status.set_first_block(data.first_block);
@@ -337,26 +433,544 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         let monitor_id = MonitorId::try_from(&request.monitor_id)
             .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
 
-        // Get UnspentTxOuts.
-        let utxos = self
+        // The start key is the UtxoId the page starts at.
+        let start_utxo_id = if request.start_key.is_empty() {
+            None
+        } else {
+            Some(
+                UtxoId::try_from(&request.start_key[..])
+                    .map_err(|err| rpc_invalid_arg_error("start_key", err, &self.logger))?,
+            )
+        };
+        let limit = if request.limit == 0 {
+            usize::MAX
+        } else {
+            request.limit as usize
+        };
+
+        // Get the requested page of UnspentTxOuts, leaving out those that don't have
+        // the requested token id or are outside the requested value range.
+        let (utxos, next_utxo_id) = self
             .mobilecoind_db
-            .get_utxos_for_subaddress(&monitor_id, request.subaddress_index)
+            .get_utxos_page_for_subaddress(
+                &monitor_id,
+                request.subaddress_index,
+                start_utxo_id.as_ref(),
+                limit,
+                |utxo| {
+                    utxo.token_id == request.token_id
+                        && utxo.value >= request.min_value
+                        && (request.max_value == 0 || utxo.value <= request.max_value)
+                },
+            )
             .map_err(|err| {
-                rpc_internal_error("mobilecoind_db.get_utxos_for_subaddress", err, &self.logger)
+                rpc_internal_error(
+                    "mobilecoind_db.get_utxos_page_for_subaddress",
+                    err,
+                    &self.logger,
+                )
             })?;
 
-        // Filter out those that don't have the requested token id
-        let utxos: Vec<_> = utxos
-            .into_iter()
-            .filter(|utxo| utxo.token_id == request.token_id)
-            .collect();
+        // Decode the memos of the UnspentTxOuts with the monitor's keys.
+        let monitor_data = self
+            .mobilecoind_db
+            .get_monitor_data(&monitor_id)
+            .map_err(|err| {
+                rpc_internal_error("mobilecoind_db.get_monitor_data", err, &self.logger)
+            })?;
+        let known_senders = self.parse_known_senders(request.get_known_senders())?;
 
         // Convert to protos.
-        let proto_utxos: Vec<api::UnspentTxOut> = utxos.iter().map(|utxo| utxo.into()).collect();
+        let proto_utxos = utxos
+            .iter()
+            .map(|utxo| {
+                let mut proto_utxo = api::UnspentTxOut::from(utxo);
+                let memo = decode_memo(
+                    &utxo.tx_out,
+                    utxo.subaddress_index,
+                    &monitor_data,
+                    &known_senders,
+                )
+                .map_err(|err| rpc_internal_error("decode_memo", err, &self.logger))?;
+                proto_utxo.set_memo(memo);
+                Ok(proto_utxo)
+            })
+            .collect::<Result<Vec<_>, RpcStatus>>()?;
 
         // Returrn response.
         let mut response = api::GetUnspentTxOutListResponse::new();
         response.set_output_list(RepeatedField::from_vec(proto_utxos));
+        if let Some(next_utxo_id) = next_utxo_id {
+            response.set_next_start_key(next_utxo_id.to_vec());
+        }
+        Ok(response)
+    }
+
+    fn subscribe_monitor_events_impl(
+        &mut self,
+        ctx: RpcContext,
+        mut request: api::SubscribeMonitorEventsRequest,
+        mut sink: ServerStreamingSink<api::MonitorEvent>,
+    ) {
+        let logger = rpc_logger(&ctx, &self.logger);
+
+        // Subscribe before looking at the monitor, so that no change is missed.
+        let changes = self.mobilecoind_db.subscribe_to_monitor_changes();
+
+        let monitor_id_and_data = MonitorId::try_from(&request.monitor_id)
+            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &logger))
+            .and_then(|monitor_id| {
+                let monitor_data =
+                    self.mobilecoind_db
+                        .get_monitor_data(&monitor_id)
+                        .map_err(|err| {
+                            rpc_internal_error("mobilecoind_db.get_monitor_data", err, &logger)
+                        })?;
+                self.parse_known_senders(request.get_known_senders())?;
+                Ok((monitor_id, monitor_data))
+            });
+        let (monitor_id, monitor_data) = match monitor_id_and_data {
+            Ok(monitor_id_and_data) => monitor_id_and_data,
+            Err(status) => {
+                let future = sink
+                    .fail(status)
+                    .map_err(move |err| log::error!(logger, "failed to reply: {:?}", err))
+                    .map(|_| ());
+                ctx.spawn(future);
+                return;
+            }
+        };
+
+        let next_block = if request.start_block == 0 {
+            monitor_data.next_block
+        } else {
+            max(request.start_block, monitor_data.first_block)
+        };
+
+        let service = self.clone();
+        let known_senders = request.take_known_senders();
+        let stream_logger = logger.clone();
+        let future = async move {
+            let result = service
+                .send_monitor_events(
+                    monitor_id,
+                    next_block,
+                    known_senders,
+                    changes,
+                    &mut sink,
+                    &stream_logger,
+                )
+                .await;
+            end_subscription(sink, result).await
+        }
+        .map_err(move |err: grpcio::Error| {
+            log::debug!(logger, "Monitor events subscription ended: {}", err)
+        })
+        .map(|_| ());
+        ctx.spawn(future);
+    }
+
+    /// Send the events of a monitor, starting at a given block, each time the
+    /// monitor processes blocks, until the monitor is removed.
+    async fn send_monitor_events(
+        mut self,
+        monitor_id: MonitorId,
+        mut next_block: u64,
+        known_senders: RepeatedField<api::external::PublicAddress>,
+        mut changes: mpsc::Receiver<()>,
+        sink: &mut ServerStreamingSink<api::MonitorEvent>,
+        logger: &Logger,
+    ) -> Result<(), SubscriptionError> {
+        loop {
+            let monitor_data = match self.mobilecoind_db.get_monitor_data(&monitor_id) {
+                Ok(monitor_data) => monitor_data,
+                Err(Error::MonitorIdNotFound) => return Ok(()),
+                Err(err) => {
+                    return Err(
+                        rpc_internal_error("mobilecoind_db.get_monitor_data", err, logger).into(),
+                    )
+                }
+            };
+
+            if next_block < monitor_data.next_block {
+                for block_index in next_block..monitor_data.next_block {
+                    let mut request = api::GetProcessedBlockRequest::new();
+                    request.set_monitor_id(monitor_id.to_vec());
+                    request.set_block(block_index);
+                    request.set_known_senders(known_senders.clone());
+                    let response = self.get_processed_block_impl(request)?;
+
+                    for tx_out in response.tx_outs.into_iter() {
+                        let mut event = api::MonitorEvent::new();
+                        event.set_event_type(
+                            if tx_out.get_direction() == api::ProcessedTxOutDirection::Spent {
+                                api::MonitorEventType::TxOutSpent
+                            } else {
+                                api::MonitorEventType::TxOutReceived
+                            },
+                        );
+                        event.set_block_index(block_index);
+                        event.set_tx_out(tx_out);
+                        sink.send((event, WriteFlags::default())).await?;
+                    }
+                }
+                next_block = monitor_data.next_block;
+
+                let mut event = api::MonitorEvent::new();
+                event.set_event_type(api::MonitorEventType::MonitorSynced);
+                event.set_block_index(next_block - 1);
+                sink.send((event, WriteFlags::default())).await?;
+            }
+
+            if changes.next().await.is_none() {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Compare the next block of a monitor with the highest block known
+    /// locally or on the network.
+    fn monitor_sync_progress(
+        &self,
+        monitor_id: &MonitorId,
+    ) -> Result<api::MonitorSyncProgress, Error> {
+        let monitor_data = self.mobilecoind_db.get_monitor_data(monitor_id)?;
+        let local_num_blocks = self.ledger_db.num_blocks()?;
+        let network_num_blocks = self
+            .network_state
+            .read()
+            .expect("lock poisoned")
+            .highest_block_index_on_network()
+            .map_or(0, |block_index| block_index + 1);
+        let num_blocks = max(local_num_blocks, network_num_blocks);
+
+        let blocks_to_process = num_blocks.saturating_sub(monitor_data.first_block);
+        let blocks_remaining = num_blocks.saturating_sub(monitor_data.next_block);
+        let percent_complete = if blocks_to_process == 0 {
+            100.0
+        } else {
+            (blocks_to_process - blocks_remaining.min(blocks_to_process)) as f64 * 100.0
+                / blocks_to_process as f64
+        };
+
+        let mut progress = api::MonitorSyncProgress::new();
+        progress.set_first_block(monitor_data.first_block);
+        progress.set_next_block(monitor_data.next_block);
+        progress.set_network_highest_block_index(num_blocks.saturating_sub(1));
+        progress.set_blocks_remaining(blocks_remaining);
+        progress.set_percent_complete(percent_complete);
+        progress.set_is_synced(blocks_remaining == 0);
+        Ok(progress)
+    }
+
+    fn get_monitor_sync_progress_impl(
+        &mut self,
+        request: api::GetMonitorSyncProgressRequest,
+    ) -> Result<api::GetMonitorSyncProgressResponse, RpcStatus> {
+        let monitor_id = MonitorId::try_from(&request.monitor_id)
+            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
+
+        let progress = self
+            .monitor_sync_progress(&monitor_id)
+            .map_err(|err| rpc_internal_error("monitor_sync_progress", err, &self.logger))?;
+
+        let mut response = api::GetMonitorSyncProgressResponse::new();
+        response.set_progress(progress);
+        Ok(response)
+    }
+
+    fn subscribe_monitor_sync_progress_impl(
+        &mut self,
+        ctx: RpcContext,
+        request: api::GetMonitorSyncProgressRequest,
+        mut sink: ServerStreamingSink<api::MonitorSyncProgress>,
+    ) {
+        let logger = rpc_logger(&ctx, &self.logger);
+
+        // Subscribe before looking at the monitor, so that no change is missed.
+        let changes = self.mobilecoind_db.subscribe_to_monitor_changes();
+
+        let monitor_id_and_progress = MonitorId::try_from(&request.monitor_id)
+            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &logger))
+            .and_then(|monitor_id| {
+                let progress = self
+                    .monitor_sync_progress(&monitor_id)
+                    .map_err(|err| rpc_internal_error("monitor_sync_progress", err, &logger))?;
+                Ok((monitor_id, progress))
+            });
+        let (monitor_id, progress) = match monitor_id_and_progress {
+            Ok(monitor_id_and_progress) => monitor_id_and_progress,
+            Err(status) => {
+                let future = sink
+                    .fail(status)
+                    .map_err(move |err| log::error!(logger, "failed to reply: {:?}", err))
+                    .map(|_| ());
+                ctx.spawn(future);
+                return;
+            }
+        };
+
+        let service = self.clone();
+        let stream_logger = logger.clone();
+        let future = async move {
+            let result = service
+                .send_monitor_sync_progress(
+                    monitor_id,
+                    progress,
+                    changes,
+                    &mut sink,
+                    &stream_logger,
+                )
+                .await;
+            end_subscription(sink, result).await
+        }
+        .map_err(move |err: grpcio::Error| {
+            log::debug!(logger, "Monitor sync progress subscription ended: {}", err)
+        })
+        .map(|_| ());
+        ctx.spawn(future);
+    }
+
+    /// Send the sync progress of a monitor each time it changes, until the
+    /// monitor is removed.
+    async fn send_monitor_sync_progress(
+        self,
+        monitor_id: MonitorId,
+        mut progress: api::MonitorSyncProgress,
+        mut changes: mpsc::Receiver<()>,
+        sink: &mut ServerStreamingSink<api::MonitorSyncProgress>,
+        logger: &Logger,
+    ) -> Result<(), SubscriptionError> {
+        sink.send((progress.clone(), WriteFlags::default())).await?;
+
+        while changes.next().await.is_some() {
+            let new_progress = match self.monitor_sync_progress(&monitor_id) {
+                Ok(new_progress) => new_progress,
+                Err(Error::MonitorIdNotFound) => return Ok(()),
+                Err(err) => {
+                    return Err(rpc_internal_error("monitor_sync_progress", err, logger).into())
+                }
+            };
+
+            if new_progress != progress {
+                progress = new_progress;
+                sink.send((progress.clone(), WriteFlags::default())).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn allocate_subaddress_impl(
+        &mut self,
+        request: api::AllocateSubaddressRequest,
+    ) -> Result<api::AllocateSubaddressResponse, RpcStatus> {
+        let monitor_id = MonitorId::try_from(&request.monitor_id)
+            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
+
+        let subaddress_index = self
+            .mobilecoind_db
+            .allocate_subaddress(&monitor_id, request.get_label())
+            .map_err(|err| match err {
+                Error::InvalidArgument(_, _) => rpc_invalid_arg_error("label", err, &self.logger),
+                Error::NoUnlabeledSubaddress => {
+                    rpc_precondition_error("mobilecoind_db.allocate_subaddress", err, &self.logger)
+                }
+                err => rpc_internal_error("mobilecoind_db.allocate_subaddress", err, &self.logger),
+            })?;
+
+        let data = self
+            .mobilecoind_db
+            .get_monitor_data(&monitor_id)
+            .map_err(|err| {
+                rpc_internal_error("mobilecoind_db.get_monitor_data", err, &self.logger)
+            })?;
+        let subaddress = data
+            .subaddress(subaddress_index)
+            .map_err(|err| rpc_internal_error("monitor_data.subaddress", err, &self.logger))?;
+
+        let mut wrapper = api::printable::PrintableWrapper::new();
+        wrapper.set_public_address((&subaddress).into());
+
+        let mut response = api::AllocateSubaddressResponse::new();
+        response.set_subaddress_index(subaddress_index);
+        response.set_public_address((&subaddress).into());
+        response.set_b58_code(
+            wrapper
+                .b58_encode()
+                .map_err(|err| rpc_internal_error("b58_encode", err, &self.logger))?,
+        );
+        Ok(response)
+    }
+
+    fn set_subaddress_label_impl(
+        &mut self,
+        request: api::SetSubaddressLabelRequest,
+    ) -> Result<api::Empty, RpcStatus> {
+        let monitor_id = MonitorId::try_from(&request.monitor_id)
+            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
+
+        self.mobilecoind_db
+            .set_subaddress_label(&monitor_id, request.subaddress_index, request.get_label())
+            .map_err(|err| match err {
+                Error::InvalidArgument(_, _) => {
+                    rpc_invalid_arg_error("subaddress_index", err, &self.logger)
+                }
+                err => rpc_internal_error("mobilecoind_db.set_subaddress_label", err, &self.logger),
+            })?;
+
+        Ok(api::Empty::new())
+    }
+
+    fn get_subaddress_labels_impl(
+        &mut self,
+        request: api::GetSubaddressLabelsRequest,
+    ) -> Result<api::GetSubaddressLabelsResponse, RpcStatus> {
+        let monitor_id = MonitorId::try_from(&request.monitor_id)
+            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
+
+        let data = self
+            .mobilecoind_db
+            .get_monitor_data(&monitor_id)
+            .map_err(|err| {
+                rpc_internal_error("mobilecoind_db.get_monitor_data", err, &self.logger)
+            })?;
+        let labels = self
+            .mobilecoind_db
+            .get_subaddress_labels(&monitor_id)
+            .map_err(|err| {
+                rpc_internal_error("mobilecoind_db.get_subaddress_labels", err, &self.logger)
+            })?;
+
+        let mut response = api::GetSubaddressLabelsResponse::new();
+        for (subaddress_index, label) in labels {
+            let mut proto_label = api::SubaddressLabel::new();
+            proto_label.set_subaddress_index(subaddress_index);
+            proto_label.set_label(label);
+            let subaddress = data
+                .subaddress(subaddress_index)
+                .map_err(|err| rpc_internal_error("monitor_data.subaddress", err, &self.logger))?;
+            proto_label.set_public_address((&subaddress).into());
+            response.mut_labels().push(proto_label);
+        }
+        Ok(response)
+    }
+
+    fn export_monitor_impl(
+        &mut self,
+        request: api::ExportMonitorRequest,
+    ) -> Result<api::ExportMonitorResponse, RpcStatus> {
+        let monitor_id = MonitorId::try_from(&request.monitor_id)
+            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
+
+        let backup = self
+            .mobilecoind_db
+            .export_monitor(&monitor_id)
+            .map_err(|err| {
+                rpc_internal_error("mobilecoind_db.export_monitor", err, &self.logger)
+            })?;
+
+        let mut rng = rand::thread_rng();
+        let bytes = backup
+            .encrypt(request.get_password(), &mut rng)
+            .map_err(|err| rpc_invalid_arg_error("password", err, &self.logger))?;
+
+        let mut response = api::ExportMonitorResponse::new();
+        response.set_backup(bytes);
+        Ok(response)
+    }
+
+    fn import_monitor_impl(
+        &mut self,
+        request: api::ImportMonitorRequest,
+    ) -> Result<api::ImportMonitorResponse, RpcStatus> {
+        let backup = MonitorBackup::decrypt(request.get_password(), request.get_backup())
+            .map_err(|err| rpc_invalid_arg_error("backup", err, &self.logger))?;
+
+        let account_key = request
+            .account_key
+            .as_ref()
+            .map(AccountKey::try_from)
+            .transpose()
+            .map_err(|err| rpc_internal_error("account_key.try_from", err, &self.logger))?;
+
+        let id = self
+            .mobilecoind_db
+            .import_monitor(&backup, account_key)
+            .map_err(|err| match err {
+                Error::BackupAccountKeyNeeded
+                | Error::BackupAccountKeyMismatch
+                | Error::InvalidArgument(_, _) => {
+                    rpc_invalid_arg_error("account_key", err, &self.logger)
+                }
+                Error::MonitorIdExists => {
+                    rpc_precondition_error("mobilecoind_db.import_monitor", err, &self.logger)
+                }
+                err => rpc_internal_error("mobilecoind_db.import_monitor", err, &self.logger),
+            })?;
+
+        let mut response = api::ImportMonitorResponse::new();
+        response.set_monitor_id(id.to_vec());
+        Ok(response)
+    }
+
+    fn set_watch_only_key_images_impl(
+        &mut self,
+        request: api::SetWatchOnlyKeyImagesRequest,
+    ) -> Result<api::SetWatchOnlyKeyImagesResponse, RpcStatus> {
+        let monitor_id = MonitorId::try_from(&request.monitor_id)
+            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
+
+        let key_images = request
+            .get_key_images()
+            .iter()
+            .map(|proto_key_image| {
+                let tx_public_key =
+                    CompressedRistrettoPublic::try_from(proto_key_image.get_tx_public_key())
+                        .map_err(|err| {
+                            rpc_invalid_arg_error("tx_public_key.try_from", err, &self.logger)
+                        })?;
+                let key_image =
+                    KeyImage::try_from(proto_key_image.get_key_image()).map_err(|err| {
+                        rpc_invalid_arg_error("key_image.try_from", err, &self.logger)
+                    })?;
+                Ok((tx_public_key, key_image))
+            })
+            .collect::<Result<Vec<_>, RpcStatus>>()?;
+
+        let updates = self
+            .mobilecoind_db
+            .set_watch_only_key_images(&monitor_id, &key_images, &self.ledger_db)
+            .map_err(|err| match err {
+                Error::InvalidArgument(_, _) => {
+                    rpc_invalid_arg_error("monitor_id", err, &self.logger)
+                }
+                err => rpc_internal_error(
+                    "mobilecoind_db.set_watch_only_key_images",
+                    err,
+                    &self.logger,
+                ),
+            })?;
+
+        let mut response = api::SetWatchOnlyKeyImagesResponse::new();
+        for update in updates {
+            let (status, spent_at_block_index) = match update {
+                KeyImageUpdate::NotFound => {
+                    (api::WatchOnlyKeyImageStatus::WatchOnlyKeyImageNotFound, 0)
+                }
+                KeyImageUpdate::Unspent => {
+                    (api::WatchOnlyKeyImageStatus::WatchOnlyKeyImageUnspent, 0)
+                }
+                KeyImageUpdate::Spent(block_index) => (
+                    api::WatchOnlyKeyImageStatus::WatchOnlyKeyImageSpent,
+                    block_index,
+                ),
+            };
+            response.mut_statuses().push(status);
+            response
+                .mut_spent_at_block_indexes()
+                .push(spent_at_block_index);
+        }
         Ok(response)
     }
 
@@ -450,7 +1064,9 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         }
 
         // Get the subaddress.
-        let subaddress = data.account_key.subaddress(request.subaddress_index);
+        let subaddress = data
+            .subaddress(request.subaddress_index)
+            .map_err(|err| rpc_internal_error("monitor_data.subaddress", err, &self.logger))?;
 
         // Also build the b58 wrapper
         let mut wrapper = api::printable::PrintableWrapper::new();
@@ -479,44 +1095,130 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
                 })?;
 
         // A request code could be a public address or a payment request
-        if wrapper.has_payment_request() {
+        let mut response = if wrapper.has_payment_request() {
             let payment_request = wrapper.get_payment_request();
             let mut response = api::ParseRequestCodeResponse::new();
             response.set_receiver(payment_request.get_public_address().clone());
             response.set_value(payment_request.get_value());
             response.set_memo(payment_request.get_memo().to_string());
             response.set_token_id(payment_request.get_token_id());
-            Ok(response)
+            response
         } else if wrapper.has_public_address() {
             let public_address = wrapper.get_public_address();
             let mut response = api::ParseRequestCodeResponse::new();
             response.set_receiver(public_address.clone());
             response.set_value(0);
             response.set_memo(String::new());
-            Ok(response)
+            response
         } else {
-            Err(RpcStatus::with_message(
+            return Err(RpcStatus::with_message(
                 RpcStatusCode::INVALID_ARGUMENT,
                 "Neither payment request nor public address".into(),
-            ))
-        }
-    }
+            ));
+        };
 
-    fn create_request_code_impl(
-        &mut self,
-        request: api::CreateRequestCodeRequest,
-    ) -> Result<api::CreateRequestCodeResponse, RpcStatus> {
-        let receiver = PublicAddress::try_from(request.get_receiver())
+        // Report the monitor subaddress the code pays to, if there is one.
+        let receiver = PublicAddress::try_from(response.get_receiver())
             .map_err(|err| rpc_internal_error("PublicAddress.try_from", err, &self.logger))?;
+        if let Some(subaddress_id) = self.find_monitor_subaddress(&receiver)? {
+            let label = self
+                .mobilecoind_db
+                .get_subaddress_label(&subaddress_id.monitor_id, subaddress_id.index)
+                .map_err(|err| {
+                    rpc_internal_error("mobilecoind_db.get_subaddress_label", err, &self.logger)
+                })?;
+            response.set_monitor_id(subaddress_id.monitor_id.to_vec());
+            response.set_subaddress_index(subaddress_id.index);
+            response.set_subaddress_label(label.unwrap_or_default());
+        }
 
-        let mut payment_request = api::printable::PaymentRequest::new();
-        payment_request.set_public_address((&receiver).into());
-        payment_request.set_value(request.get_value());
-        payment_request.set_memo(request.get_memo().to_string());
-        payment_request.set_token_id(request.get_token_id());
-
-        let mut wrapper = api::printable::PrintableWrapper::new();
-        wrapper.set_payment_request(payment_request);
+        Ok(response)
+    }
+
+    /// Find the monitor subaddress that a public address belongs to, if any.
+    fn find_monitor_subaddress(
+        &self,
+        public_address: &PublicAddress,
+    ) -> Result<Option<SubaddressId>, RpcStatus> {
+        let subaddress_spk = SubaddressSPKId::from(public_address.spend_public_key());
+        let subaddress_id = match self
+            .mobilecoind_db
+            .get_subaddress_id_by_spk(&subaddress_spk)
+        {
+            Ok(subaddress_id) => subaddress_id,
+            Err(Error::SubaddressSPKNotFound) => return Ok(None),
+            Err(err) => {
+                return Err(rpc_internal_error(
+                    "mobilecoind_db.get_subaddress_id_by_spk",
+                    err,
+                    &self.logger,
+                ))
+            }
+        };
+
+        // The spend public key matched, make sure the view public key does too.
+        let monitor_data = self
+            .mobilecoind_db
+            .get_monitor_data(&subaddress_id.monitor_id)
+            .map_err(|err| {
+                rpc_internal_error("mobilecoind_db.get_monitor_data", err, &self.logger)
+            })?;
+        let subaddress = monitor_data
+            .subaddress(subaddress_id.index)
+            .map_err(|err| rpc_internal_error("monitor_data.subaddress", err, &self.logger))?;
+        if subaddress.view_public_key() != public_address.view_public_key() {
+            return Ok(None);
+        }
+
+        Ok(Some(subaddress_id))
+    }
+
+    fn create_request_code_impl(
+        &mut self,
+        request: api::CreateRequestCodeRequest,
+    ) -> Result<api::CreateRequestCodeResponse, RpcStatus> {
+        let receiver = match (request.has_receiver(), request.get_monitor_id().is_empty()) {
+            (true, true) => PublicAddress::try_from(request.get_receiver())
+                .map_err(|err| rpc_internal_error("PublicAddress.try_from", err, &self.logger))?,
+            (false, false) => {
+                let monitor_id = MonitorId::try_from(&request.monitor_id).map_err(|err| {
+                    rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger)
+                })?;
+                let data = self
+                    .mobilecoind_db
+                    .get_monitor_data(&monitor_id)
+                    .map_err(|err| {
+                        rpc_internal_error("mobilecoind_db.get_monitor_data", err, &self.logger)
+                    })?;
+                if !data
+                    .subaddress_indexes()
+                    .contains(&request.subaddress_index)
+                {
+                    return Err(RpcStatus::with_message(
+                        RpcStatusCode::INVALID_ARGUMENT,
+                        "subaddress_index".into(),
+                    ));
+                }
+                data.subaddress(request.subaddress_index).map_err(|err| {
+                    rpc_internal_error("monitor_data.subaddress", err, &self.logger)
+                })?
+            }
+            _ => {
+                return Err(RpcStatus::with_message(
+                    RpcStatusCode::INVALID_ARGUMENT,
+                    "exactly one of receiver and monitor_id must be set".into(),
+                ))
+            }
+        };
+
+        let mut payment_request = api::printable::PaymentRequest::new();
+        payment_request.set_public_address((&receiver).into());
+        payment_request.set_value(request.get_value());
+        payment_request.set_memo(request.get_memo().to_string());
+        payment_request.set_token_id(request.get_token_id());
+
+        let mut wrapper = api::printable::PrintableWrapper::new();
+        wrapper.set_payment_request(payment_request);
 
         let encoded = wrapper
             .b58_encode()
@@ -596,6 +1298,30 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
             .get_value(&shared_secret)
             .map_err(|err| rpc_internal_error("amount.get_value", err, &self.logger))?;
 
+        // Codes must match the token id of the TxOut, which is MOB for older codes,
+        // and the value when they encode it. Older codes have a zero value.
+        if transfer_payload.get_token_id() != *amount.token_id
+            || (transfer_payload.get_value() != 0 && transfer_payload.get_value() != amount.value)
+        {
+            return Err(RpcStatus::with_message(
+                RpcStatusCode::INVALID_ARGUMENT,
+                "transfer_payload.value/token_id".into(),
+            ));
+        }
+
+        // Decode the memo the sender attached to the TxOut.
+        let known_senders = self.parse_known_senders(request.get_known_senders())?;
+        let monitor_data =
+            MonitorData::new(account_key.clone(), DEFAULT_SUBADDRESS_INDEX, 1, 0, "")
+                .map_err(|err| rpc_internal_error("MonitorData.new", err, &self.logger))?;
+        let decoded_memo = decode_memo(
+            &tx_out,
+            DEFAULT_SUBADDRESS_INDEX,
+            &monitor_data,
+            &known_senders,
+        )
+        .map_err(|err| rpc_internal_error("decode_memo", err, &self.logger))?;
+
         let onetime_private_key = recover_onetime_private_key(
             &tx_public_key,
             account_key.view_private_key(),
@@ -620,6 +1346,9 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         response.set_tx_public_key((&tx_public_key).into());
         response.set_memo(transfer_payload.get_memo().to_string());
         response.set_utxo((&utxo).into());
+        response.set_value(amount.value);
+        response.set_token_id(*amount.token_id);
+        response.set_decoded_memo(decoded_memo);
 
         Ok(response)
     }
@@ -676,6 +1405,8 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         transfer_payload.set_bip39_entropy(request.get_bip39_entropy().to_vec());
         transfer_payload.set_tx_out_public_key(request.get_tx_public_key().clone());
         transfer_payload.set_memo(request.get_memo().to_string());
+        transfer_payload.set_value(request.value);
+        transfer_payload.set_token_id(request.token_id);
 
         let mut transfer_wrapper = api::printable::PrintableWrapper::new();
         transfer_wrapper.set_transfer_payload(transfer_payload);
@@ -718,6 +1449,42 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         }
     }
 
+    fn validate_fog_address_impl(
+        &mut self,
+        request: api::ValidateFogAddressRequest,
+    ) -> Result<api::ValidateFogAddressResponse, RpcStatus> {
+        if self.trusted_fog_authorities.is_empty() {
+            return Err(rpc_precondition_error(
+                "trusted_fog_authorities",
+                "No trusted fog authorities are configured",
+                &self.logger,
+            ));
+        }
+
+        let mut parse_address_code_request = api::ParseAddressCodeRequest::new();
+        parse_address_code_request.set_b58_code(request.get_b58_code().to_owned());
+        let receiver = self
+            .parse_address_code_impl(parse_address_code_request)?
+            .take_receiver();
+        let public_address = PublicAddress::try_from(&receiver)
+            .map_err(|err| rpc_invalid_arg_error("PublicAddress.try_from", err, &self.logger))?;
+
+        let mut response = api::ValidateFogAddressResponse::new();
+        match validate_fog_address(&public_address, &self.trusted_fog_authorities) {
+            Ok(None) => response.set_status(api::FogAddressStatus::FogAddressNoFog),
+            Ok(Some(spki)) => {
+                response.set_status(api::FogAddressStatus::FogAddressValid);
+                response.set_fog_authority_spki(spki.to_vec());
+            }
+            Err((status, error)) => {
+                response.set_status(status);
+                response.set_error(error);
+            }
+        }
+        response.set_receiver(receiver);
+        Ok(response)
+    }
+
     fn create_address_code_impl(
         &mut self,
         request: api::CreateAddressCodeRequest,
@@ -813,36 +1580,29 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         Ok(response)
     }
 
-    fn generate_tx_impl(
-        &mut self,
-        request: api::GenerateTxRequest,
-    ) -> Result<api::GenerateTxResponse, RpcStatus> {
-        // Get sender monitor id from request.
-        let sender_monitor_id = MonitorId::try_from(&request.sender_monitor_id)
-            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
-
-        // Get monitor data for this monitor.
-        let sender_monitor_data = self
-            .mobilecoind_db
-            .get_monitor_data(&sender_monitor_id)
-            .map_err(|err| {
-                rpc_internal_error("mobilecoind_db.get_monitor_data", err, &self.logger)
-            })?;
-
-        // Check that change_subaddress is covered by this monitor.
-        if !sender_monitor_data
-            .subaddress_indexes()
-            .contains(&request.change_subaddress)
-        {
-            return Err(RpcStatus::with_message(
-                RpcStatusCode::INVALID_ARGUMENT,
-                "change_subaddress".into(),
-            ));
-        }
+    /// Parse the addresses sender memos are validated against.
+    fn parse_known_senders(
+        &self,
+        known_senders: &[mc_api::external::PublicAddress],
+    ) -> Result<Vec<PublicAddress>, RpcStatus> {
+        known_senders
+            .iter()
+            .map(|sender| {
+                PublicAddress::try_from(sender)
+                    .map_err(|err| rpc_internal_error("PublicAddress.try_from", err, &self.logger))
+            })
+            .collect()
+    }
 
-        // Get the list of potential inputs passed to.
-        let input_list: Vec<UnspentTxOut> = request
-            .get_input_list()
+    /// Check that the UnspentTxOuts of a request are of the given token id, and
+    /// belong to the given monitor.
+    fn parse_input_list(
+        &self,
+        monitor_id: &MonitorId,
+        proto_utxos: &[api::UnspentTxOut],
+        token_id: u64,
+    ) -> Result<Vec<UnspentTxOut>, RpcStatus> {
+        proto_utxos
             .iter()
             .enumerate()
             .map(|(i, proto_utxo)| {
@@ -852,7 +1612,7 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
                 })?;
 
                 // Verify token id matches.
-                if utxo.token_id != request.token_id {
+                if utxo.token_id != token_id {
                     return Err(RpcStatus::with_message(
                         RpcStatusCode::INVALID_ARGUMENT,
                         format!("input_list[{}].token_id", i),
@@ -871,7 +1631,7 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
                         )
                     })?;
 
-                if subaddress_id.monitor_id != sender_monitor_id {
+                if subaddress_id.monitor_id != *monitor_id {
                     return Err(RpcStatus::with_message(
                         RpcStatusCode::INVALID_ARGUMENT,
                         format!("input_list.{}", i),
@@ -881,7 +1641,43 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
                 // Success.
                 Ok(utxo)
             })
-            .collect::<Result<Vec<UnspentTxOut>, RpcStatus>>()?;
+            .collect()
+    }
+
+    /// Validate the monitor, inputs and outlays of a GenerateTxRequest.
+    fn parse_generate_tx_request(
+        &self,
+        request: &api::GenerateTxRequest,
+    ) -> Result<(MonitorId, Vec<UnspentTxOut>, Vec<Outlay>), RpcStatus> {
+        // Get sender monitor id from request.
+        let sender_monitor_id = MonitorId::try_from(&request.sender_monitor_id)
+            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
+
+        // Get monitor data for this monitor.
+        let sender_monitor_data = self
+            .mobilecoind_db
+            .get_monitor_data(&sender_monitor_id)
+            .map_err(|err| {
+                rpc_internal_error("mobilecoind_db.get_monitor_data", err, &self.logger)
+            })?;
+
+        // Check that change_subaddress is covered by this monitor.
+        if !sender_monitor_data
+            .subaddress_indexes()
+            .contains(&request.change_subaddress)
+        {
+            return Err(RpcStatus::with_message(
+                RpcStatusCode::INVALID_ARGUMENT,
+                "change_subaddress".into(),
+            ));
+        }
+
+        // Get the list of potential inputs passed to.
+        let input_list = self.parse_input_list(
+            &sender_monitor_id,
+            request.get_input_list(),
+            request.token_id,
+        )?;
 
         // Get the list of outlays.
         let outlays: Vec<Outlay> = request
@@ -893,6 +1689,15 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
             })
             .collect::<Result<Vec<Outlay>, RpcStatus>>()?;
 
+        Ok((sender_monitor_id, input_list, outlays))
+    }
+
+    fn generate_tx_impl(
+        &mut self,
+        request: api::GenerateTxRequest,
+    ) -> Result<api::GenerateTxResponse, RpcStatus> {
+        let (sender_monitor_id, input_list, outlays) = self.parse_generate_tx_request(&request)?;
+
         // Attempt to construct a transaction.
         let tx_proposal = self
             .transactions_manager
@@ -908,7 +1713,7 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
                 None,
             )
             .map_err(|err| {
-                rpc_internal_error("transactions_manager.build_transaction", err, &self.logger)
+                tx_build_error("transactions_manager.build_transaction", err, &self.logger)
             })?;
 
         // Success.
@@ -917,6 +1722,129 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         Ok(response)
     }
 
+    fn get_fee_estimate_impl(
+        &mut self,
+        request: api::GetFeeEstimateRequest,
+    ) -> Result<api::GetFeeEstimateResponse, RpcStatus> {
+        // Get sender monitor id from request.
+        let sender_monitor_id = MonitorId::try_from(&request.sender_monitor_id)
+            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
+
+        // Get the list of potential inputs passed to.
+        let input_list = self.parse_input_list(
+            &sender_monitor_id,
+            request.get_input_list(),
+            request.token_id,
+        )?;
+
+        // Get the list of outlays.
+        let outlays: Vec<Outlay> = request
+            .get_outlay_list()
+            .iter()
+            .map(|outlay_proto| {
+                Outlay::try_from(outlay_proto)
+                    .map_err(|err| rpc_internal_error("outlay.try_from", err, &self.logger))
+            })
+            .collect::<Result<Vec<Outlay>, RpcStatus>>()?;
+
+        let (fee, selected_utxos) = self
+            .transactions_manager
+            .estimate_fee(
+                TokenId::from(request.token_id),
+                &input_list,
+                &outlays,
+                &self.get_last_block_infos(),
+            )
+            .map_err(|err| {
+                rpc_internal_error("transactions_manager.estimate_fee", err, &self.logger)
+            })?;
+
+        // Success.
+        let mut response = api::GetFeeEstimateResponse::new();
+        response.set_fee(fee);
+        response.set_num_inputs(selected_utxos.len() as u64);
+        response.set_total_input_value(selected_utxos.iter().map(|utxo| utxo.value).sum());
+        Ok(response)
+    }
+
+    fn get_suggested_tombstone_block_impl(
+        &mut self,
+        _request: api::Empty,
+    ) -> Result<api::GetSuggestedTombstoneBlockResponse, RpcStatus> {
+        let (next_block_index, tombstone_block) = self
+            .transactions_manager
+            .suggested_tombstone_block(&self.get_last_block_infos())
+            .map_err(|err| {
+                rpc_internal_error(
+                    "transactions_manager.suggested_tombstone_block",
+                    err,
+                    &self.logger,
+                )
+            })?;
+
+        let mut response = api::GetSuggestedTombstoneBlockResponse::new();
+        response.set_tombstone_block(tombstone_block);
+        response.set_next_block_index(next_block_index);
+        response.set_min_tombstone_block(next_block_index + 1);
+        response.set_max_tombstone_block(next_block_index + MAX_TOMBSTONE_BLOCKS);
+        Ok(response)
+    }
+
+    fn generate_unsigned_tx_impl(
+        &mut self,
+        request: api::GenerateTxRequest,
+    ) -> Result<api::GenerateUnsignedTxResponse, RpcStatus> {
+        let (sender_monitor_id, input_list, outlays) = self.parse_generate_tx_request(&request)?;
+
+        // Attempt to construct an unsigned transaction.
+        let unsigned_tx = self
+            .transactions_manager
+            .build_unsigned_transaction(
+                &sender_monitor_id,
+                TokenId::from(request.token_id),
+                request.change_subaddress,
+                &input_list,
+                &outlays,
+                &self.get_last_block_infos(),
+                request.fee,
+                request.tombstone,
+            )
+            .map_err(|err| {
+                tx_build_error(
+                    "transactions_manager.build_unsigned_transaction",
+                    err,
+                    &self.logger,
+                )
+            })?;
+
+        // Success.
+        let mut response = api::GenerateUnsignedTxResponse::new();
+        response.set_unsigned_tx((&unsigned_tx).into());
+        Ok(response)
+    }
+
+    fn sign_unsigned_tx_impl(
+        &mut self,
+        request: api::SignUnsignedTxRequest,
+    ) -> Result<api::SignUnsignedTxResponse, RpcStatus> {
+        let account_key = AccountKey::try_from(request.get_account_key())
+            .map_err(|err| rpc_internal_error("account_key.try_from", err, &self.logger))?;
+
+        let unsigned_tx = UnsignedTx::try_from(request.get_unsigned_tx())
+            .map_err(|err| rpc_internal_error("unsigned_tx.try_from", err, &self.logger))?;
+
+        let tx_proposal = self
+            .transactions_manager
+            .sign_unsigned_tx(&unsigned_tx, &account_key)
+            .map_err(|err| {
+                rpc_internal_error("transactions_manager.sign_unsigned_tx", err, &self.logger)
+            })?;
+
+        let mut response = api::SignUnsignedTxResponse::new();
+        response.set_tx_proposal((&tx_proposal).into());
+        Ok(response)
+    }
+
     fn generate_optimization_tx_impl(
         &mut self,
         request: api::GenerateOptimizationTxRequest,
@@ -1115,7 +2043,7 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
                 Some(Box::new(memo_builder)),
             )
             .map_err(|err| {
-                rpc_internal_error("transactions_manager.build_transaction", err, &self.logger)
+                tx_build_error("transactions_manager.build_transaction", err, &self.logger)
             })?;
 
         // Success.
@@ -1208,6 +2136,8 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         transfer_payload.set_bip39_entropy(bip39_entropy.to_vec());
         transfer_payload.set_tx_out_public_key((&tx_public_key).into());
         transfer_payload.set_memo(request.get_memo().to_string());
+        transfer_payload.set_value(request.value);
+        transfer_payload.set_token_id(request.token_id);
 
         let mut transfer_wrapper = api::printable::PrintableWrapper::new();
         transfer_wrapper.set_transfer_payload(transfer_payload);
@@ -1223,6 +2153,8 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         response.set_tx_public_key(proto_tx_public_key);
         response.set_memo(request.get_memo().to_string());
         response.set_b58_code(b58_code);
+        response.set_value(request.value);
+        response.set_token_id(request.token_id);
         Ok(response)
     }
 
@@ -1234,47 +2166,360 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         let tx_proposal = TxProposal::try_from(request.get_tx_proposal())
             .map_err(|err| rpc_internal_error("tx_proposal.try_from", err, &self.logger))?;
 
-        // Submit to network.
-        let block_height = self
-            .transactions_manager
-            .submit_tx_proposal(&tx_proposal)
-            .map_err(|err| {
-                rpc_internal_error("transactions_manager.submit_tx_proposal", err, &self.logger)
-            })?;
+        if request.idempotency_key.is_empty() {
+            return self.submit_tx_proposal(&tx_proposal);
+        }
 
-        // Update the attempted spend block height in db. Note that we swallow the error
-        // here since our transaction did get sent to the network, and its
-        // better to have the user attempt a double spend by having stale
-        // UnspentTxOut data than having them not be aware that the transaction
-        // was submitted.
-        let utxo_ids: Vec<UtxoId> = tx_proposal.utxos.iter().map(UtxoId::from).collect();
-        if let Err(err) = self.mobilecoind_db.update_attempted_spend(
-            &utxo_ids,
-            block_height,
-            tx_proposal.tx.prefix.tombstone_block,
-        ) {
-            log::error!(
-                self.logger,
-                "failed updating attempted_spend_height after submitting tx {}: {:?}",
-                tx_proposal.tx,
-                err
-            );
+        let key = idempotency_key("SubmitTx", &request.idempotency_key);
+        let mut fingerprint_request = request.clone();
+        fingerprint_request.clear_idempotency_key();
+        if let Some(idempotent_request) =
+            self.reserve_idempotency_key(&key, &fingerprint_request)?
+        {
+            if !idempotent_request.response.is_empty() {
+                return self.idempotent_response(&idempotent_request);
+            }
+            // The transaction was not submitted successfully yet. Submitting it
+            // again is harmless, since it can only land in the ledger once.
         }
 
-        // Construct sender receipt.
-        let mut sender_tx_receipt = api::SenderTxReceipt::new();
-        sender_tx_receipt.set_key_image_list(RepeatedField::from_vec(
-            tx_proposal
-                .utxos
-                .iter()
-                .map(|utxo| (&utxo.key_image).into())
-                .collect(),
-        ));
-        sender_tx_receipt.set_tombstone(tx_proposal.tx.prefix.tombstone_block);
+        let response = match self.submit_tx_proposal(&tx_proposal) {
+            Ok(response) => response,
+            Err(err) => {
+                self.release_idempotency_key(&key);
+                return Err(err);
+            }
+        };
+        self.set_idempotent_response(&key, &response)?;
+        Ok(response)
+    }
 
-        // Construct receiver receipts.
-        let receiver_tx_receipts: Vec<_> = tx_proposal
-            .outlays
+    fn submit_signed_tx_impl(
+        &mut self,
+        request: api::SubmitSignedTxRequest,
+    ) -> Result<api::SubmitTxResponse, RpcStatus> {
+        let unsigned_tx = UnsignedTx::try_from(request.get_unsigned_tx())
+            .map_err(|err| rpc_internal_error("unsigned_tx.try_from", err, &self.logger))?;
+
+        let tx_proposal = TxProposal::try_from(request.get_tx_proposal())
+            .map_err(|err| rpc_internal_error("tx_proposal.try_from", err, &self.logger))?;
+
+        // The change is checked with the view key of the monitor owning the inputs.
+        let first_utxo = unsigned_tx
+            .inputs
+            .first()
+            .map(|(utxo, _proof)| utxo)
+            .ok_or_else(|| {
+                RpcStatus::with_message(RpcStatusCode::INVALID_ARGUMENT, "unsigned_tx".into())
+            })?;
+        let subaddress_id = self
+            .mobilecoind_db
+            .get_subaddress_id_by_utxo_id(&UtxoId::from(first_utxo))
+            .map_err(|err| {
+                rpc_internal_error(
+                    "mobilecoind_db.get_subaddress_id_by_utxo_id",
+                    err,
+                    &self.logger,
+                )
+            })?;
+        let sender_monitor_data = self
+            .mobilecoind_db
+            .get_monitor_data(&subaddress_id.monitor_id)
+            .map_err(|err| {
+                rpc_internal_error("mobilecoind_db.get_monitor_data", err, &self.logger)
+            })?;
+
+        // Refuse proposals that spend or pay anything the unsigned transaction did
+        // not.
+        verify_signed_tx(&unsigned_tx, &tx_proposal, &sender_monitor_data)
+            .map_err(|err| rpc_internal_error("verify_signed_tx", err, &self.logger))?;
+
+        self.submit_tx_proposal(&tx_proposal)
+    }
+
+    fn create_signed_contingent_input_impl(
+        &mut self,
+        request: api::CreateSignedContingentInputRequest,
+    ) -> Result<api::CreateSignedContingentInputResponse, RpcStatus> {
+        let monitor_id = MonitorId::try_from(&request.monitor_id)
+            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
+
+        let monitor_data = self
+            .mobilecoind_db
+            .get_monitor_data(&monitor_id)
+            .map_err(|err| {
+                rpc_internal_error("mobilecoind_db.get_monitor_data", err, &self.logger)
+            })?;
+
+        // Check that receiver_subaddress is covered by this monitor.
+        if !monitor_data
+            .subaddress_indexes()
+            .contains(&request.receiver_subaddress)
+        {
+            return Err(RpcStatus::with_message(
+                RpcStatusCode::INVALID_ARGUMENT,
+                "receiver_subaddress".into(),
+            ));
+        }
+
+        let utxo = self
+            .parse_input_list(
+                &monitor_id,
+                core::slice::from_ref(request.get_utxo()),
+                request.get_utxo().token_id,
+            )?
+            .remove(0);
+
+        let sci = self
+            .transactions_manager
+            .create_signed_contingent_input(
+                &monitor_id,
+                &utxo,
+                Amount::new(
+                    request.required_value,
+                    TokenId::from(request.required_token_id),
+                ),
+                request.receiver_subaddress,
+                &self.get_last_block_infos(),
+                request.tombstone,
+            )
+            .map_err(|err| {
+                tx_build_error(
+                    "transactions_manager.create_signed_contingent_input",
+                    err,
+                    &self.logger,
+                )
+            })?;
+
+        let mut response = api::CreateSignedContingentInputResponse::new();
+        response.set_sci((&sci).into());
+        Ok(response)
+    }
+
+    fn fulfill_signed_contingent_input_impl(
+        &mut self,
+        request: api::FulfillSignedContingentInputRequest,
+    ) -> Result<api::FulfillSignedContingentInputResponse, RpcStatus> {
+        let sender_monitor_id = MonitorId::try_from(&request.sender_monitor_id)
+            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
+
+        let sender_monitor_data = self
+            .mobilecoind_db
+            .get_monitor_data(&sender_monitor_id)
+            .map_err(|err| {
+                rpc_internal_error("mobilecoind_db.get_monitor_data", err, &self.logger)
+            })?;
+
+        // Check that change_subaddress is covered by this monitor.
+        if !sender_monitor_data
+            .subaddress_indexes()
+            .contains(&request.change_subaddress)
+        {
+            return Err(RpcStatus::with_message(
+                RpcStatusCode::INVALID_ARGUMENT,
+                "change_subaddress".into(),
+            ));
+        }
+
+        let sci = SignedContingentInput::try_from(request.get_sci())
+            .map_err(|err| rpc_internal_error("sci.try_from", err, &self.logger))?;
+
+        // The inputs pay the required amounts, so they must be of their token id.
+        let token_id = sci
+            .required_output_amounts
+            .first()
+            .map(|amount| amount.token_id)
+            .ok_or_else(|| {
+                RpcStatus::with_message(RpcStatusCode::INVALID_ARGUMENT, "sci".into())
+            })?;
+        let input_list =
+            self.parse_input_list(&sender_monitor_id, request.get_input_list(), token_id)?;
+
+        let tx_proposal = self
+            .transactions_manager
+            .fulfill_signed_contingent_input(
+                &sender_monitor_id,
+                request.change_subaddress,
+                &input_list,
+                sci,
+                &self.get_last_block_infos(),
+                request.fee,
+                request.tombstone,
+            )
+            .map_err(|err| {
+                tx_build_error(
+                    "transactions_manager.fulfill_signed_contingent_input",
+                    err,
+                    &self.logger,
+                )
+            })?;
+
+        let mut response = api::FulfillSignedContingentInputResponse::new();
+        response.set_tx_proposal((&tx_proposal).into());
+        Ok(response)
+    }
+
+    /// Submit a transaction to the network and construct the receipts for it.
+    /// Look up the request previously made with an idempotency key, or reserve
+    /// the key for this request.
+    fn reserve_idempotency_key(
+        &self,
+        key: &IdempotencyKey,
+        request: &impl Message,
+    ) -> Result<Option<IdempotentRequest>, RpcStatus> {
+        let request_bytes = request
+            .write_to_bytes()
+            .map_err(|err| rpc_internal_error("request.write_to_bytes", err, &self.logger))?;
+        let fingerprint: [u8; 32] = Blake2b256::digest(&request_bytes).into();
+
+        self.mobilecoind_db
+            .reserve_idempotency_key(key, &fingerprint, self.idempotency_retention)
+            .map_err(|err| match err {
+                Error::IdempotencyKeyMismatch => {
+                    rpc_invalid_arg_error("idempotency_key", err, &self.logger)
+                }
+                err => {
+                    rpc_internal_error("mobilecoind_db.reserve_idempotency_key", err, &self.logger)
+                }
+            })
+    }
+
+    /// The response returned to the request made with an idempotency key.
+    fn idempotent_response<M: Message>(
+        &self,
+        idempotent_request: &IdempotentRequest,
+    ) -> Result<M, RpcStatus> {
+        M::parse_from_bytes(&idempotent_request.response)
+            .map_err(|err| rpc_internal_error("response.parse_from_bytes", err, &self.logger))
+    }
+
+    /// Remember the response to the request made with an idempotency key.
+    fn set_idempotent_response(
+        &self,
+        key: &IdempotencyKey,
+        response: &impl Message,
+    ) -> Result<(), RpcStatus> {
+        let response_bytes = response
+            .write_to_bytes()
+            .map_err(|err| rpc_internal_error("response.write_to_bytes", err, &self.logger))?;
+        self.mobilecoind_db
+            .set_idempotent_response(key, &response_bytes)
+            .map_err(|err| {
+                rpc_internal_error("mobilecoind_db.set_idempotent_response", err, &self.logger)
+            })
+    }
+
+    /// Forget an idempotency key after its request failed, so it can be
+    /// retried. This only logs errors, since the request already failed.
+    fn release_idempotency_key(&self, key: &IdempotencyKey) {
+        if let Err(err) = self.mobilecoind_db.release_idempotency_key(key) {
+            log::error!(self.logger, "failed releasing idempotency key: {:?}", err);
+        }
+    }
+
+    /// Record a submitted payment in the history of the monitor that owns its
+    /// inputs.
+    fn add_pending_payment(&self, tx_proposal: &TxProposal) -> Result<(), Error> {
+        let utxo = match tx_proposal.utxos.first() {
+            Some(utxo) => utxo,
+            None => return Ok(()),
+        };
+        let subaddress_id = self
+            .mobilecoind_db
+            .get_subaddress_id_by_utxo_id(&UtxoId::from(utxo))?;
+
+        let prefix = &tx_proposal.tx.prefix;
+        let payment = SentPayment {
+            key_images: tx_proposal.tx.key_images(),
+            outlays: tx_proposal
+                .outlays
+                .iter()
+                .map(|outlay| SentOutlay {
+                    receiver: outlay.receiver.clone(),
+                    value: outlay.value,
+                })
+                .collect(),
+            fee: prefix.fee,
+            token_id: prefix.fee_token_id,
+            tombstone_block: prefix.tombstone_block,
+        };
+        self.mobilecoind_db
+            .add_pending_payment(&subaddress_id.monitor_id, &payment)
+    }
+
+    fn submit_tx_proposal(
+        &mut self,
+        tx_proposal: &TxProposal,
+    ) -> Result<api::SubmitTxResponse, RpcStatus> {
+        // Submit to network.
+        let block_height = self
+            .transactions_manager
+            .submit_tx_proposal(tx_proposal)
+            .map_err(|err| {
+                rpc_internal_error("transactions_manager.submit_tx_proposal", err, &self.logger)
+            })?;
+
+        // Update the attempted spend block height in db. Note that we swallow the error
+        // here since our transaction did get sent to the network, and its
+        // better to have the user attempt a double spend by having stale
+        // UnspentTxOut data than having them not be aware that the transaction
+        // was submitted.
+        let utxo_ids: Vec<UtxoId> = tx_proposal.utxos.iter().map(UtxoId::from).collect();
+        if let Err(err) = self.mobilecoind_db.update_attempted_spend(
+            &utxo_ids,
+            block_height,
+            tx_proposal.tx.prefix.tombstone_block,
+        ) {
+            log::error!(
+                self.logger,
+                "failed updating attempted_spend_height after submitting tx {}: {:?}",
+                tx_proposal.tx,
+                err
+            );
+        }
+
+        // Record the recipients of the payment, which the ledger does not reveal,
+        // so that the transaction history can show them once the inputs are spent.
+        // This also only logs errors, for the same reason.
+        if let Err(err) = self.add_pending_payment(tx_proposal) {
+            log::error!(
+                self.logger,
+                "failed recording the payment of tx {}: {:?}",
+                tx_proposal.tx,
+                err
+            );
+        }
+
+        // Construct sender receipt. The UnspentTxOuts of watch-only monitors do not
+        // hold real key images, so the receipt uses the ones of the transaction.
+        let mut sender_tx_receipt = api::SenderTxReceipt::new();
+        sender_tx_receipt.set_key_image_list(RepeatedField::from_vec(
+            tx_proposal
+                .tx
+                .key_images()
+                .iter()
+                .map(|key_image| key_image.into())
+                .collect(),
+        ));
+        sender_tx_receipt.set_tombstone(tx_proposal.tx.prefix.tombstone_block);
+
+        // Construct receiver receipts.
+        let receiver_tx_receipts = Self::receiver_tx_receipts(tx_proposal)?;
+
+        // Return response.
+        let mut response = api::SubmitTxResponse::new();
+        response.set_sender_tx_receipt(sender_tx_receipt);
+        response.set_receiver_tx_receipt_list(RepeatedField::from_vec(receiver_tx_receipts));
+        Ok(response)
+    }
+
+    /// Construct the receipts of the recipients of a transaction, in the same
+    /// order as its outlays.
+    fn receiver_tx_receipts(
+        tx_proposal: &TxProposal,
+    ) -> Result<Vec<api::ReceiverTxReceipt>, RpcStatus> {
+        tx_proposal
+            .outlays
             .iter()
             .enumerate()
             .map(|(outlay_index, outlay)| {
@@ -1305,6 +2550,8 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
                 receiver_tx_receipt.set_tx_public_key((&tx_out.public_key).into());
                 receiver_tx_receipt.set_tx_out_hash(tx_out.hash().to_vec());
                 receiver_tx_receipt.set_tombstone(tx_proposal.tx.prefix.tombstone_block);
+                receiver_tx_receipt.set_value(outlay.value);
+                receiver_tx_receipt.set_token_id(tx_proposal.tx.prefix.fee_token_id);
 
                 if tx_proposal.outlay_confirmation_numbers.len() > outlay_index {
                     receiver_tx_receipt.set_confirmation_number(
@@ -1314,11 +2561,19 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
 
                 Ok(receiver_tx_receipt)
             })
-            .collect::<Result<Vec<api::ReceiverTxReceipt>, RpcStatus>>()?;
+            .collect()
+    }
 
-        // Return response.
-        let mut response = api::SubmitTxResponse::new();
-        response.set_sender_tx_receipt(sender_tx_receipt);
+    fn create_receiver_tx_receipts_impl(
+        &mut self,
+        request: api::CreateReceiverTxReceiptsRequest,
+    ) -> Result<api::CreateReceiverTxReceiptsResponse, RpcStatus> {
+        let tx_proposal = TxProposal::try_from(request.get_tx_proposal())
+            .map_err(|err| rpc_internal_error("tx_proposal.try_from", err, &self.logger))?;
+
+        let receiver_tx_receipts = Self::receiver_tx_receipts(&tx_proposal)?;
+
+        let mut response = api::CreateReceiverTxReceiptsResponse::new();
         response.set_receiver_tx_receipt_list(RepeatedField::from_vec(receiver_tx_receipts));
         Ok(response)
     }
@@ -1623,7 +2878,10 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
                                         &self.logger,
                                     )
                                 })?;
-                        let view_private_key = monitor_data.account_key.view_private_key();
+                        let view_account_key = monitor_data.view_account_key().map_err(|err| {
+                            rpc_internal_error("monitor_data.view_account_key", err, &self.logger)
+                        })?;
+                        let view_private_key = view_account_key.view_private_key();
 
                         if request.get_receipt().get_confirmation_number().len() != 32 {
                             return Err(RpcStatus::with_message(
@@ -1693,73 +2951,370 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         Ok(response)
     }
 
-    fn get_processed_block_impl(
+    fn verify_receiver_tx_receipt_impl(
         &mut self,
-        request: api::GetProcessedBlockRequest,
-    ) -> Result<api::GetProcessedBlockResponse, RpcStatus> {
-        // Get MonitorId from from the GRPC request.
-        let monitor_id = MonitorId::try_from(&request.monitor_id)
-            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
+        request: api::VerifyReceiverTxReceiptRequest,
+    ) -> Result<api::VerifyReceiverTxReceiptResponse, RpcStatus> {
+        let receipt = request.get_receipt();
 
-        // We will use the AccountKey to compute the Address Code
-        let account_key = self
-            .mobilecoind_db
-            .get_monitor_data(&monitor_id)
-            .map_err(|err| {
-                rpc_internal_error("mobilecoind_db.get_monitor_data", err, &self.logger)
-            })?
-            .account_key;
+        // Sanity-test the request.
+        if receipt.get_tx_out_hash().len() != 32 {
+            return Err(RpcStatus::with_message(
+                RpcStatusCode::INVALID_ARGUMENT,
+                "receipt.tx_out_hash".into(),
+            ));
+        }
 
-        // Get all processed block data for the requested block.
-        let processed_tx_outs = self
-            .mobilecoind_db
-            .get_processed_block(&monitor_id, request.block)
-            .map_err(|err| {
-                rpc_internal_error("mobilecoind_db.get_processed_block", err, &self.logger)
-            })?
-            .iter()
-            .map(|src| {
-                let mut dst = api::ProcessedTxOut::new();
-                dst.set_monitor_id(monitor_id.to_vec());
-                dst.set_subaddress_index(src.subaddress_index);
-                dst.set_public_key((&src.public_key).into());
-                dst.set_key_image((&src.key_image).into());
-                dst.set_value(src.value);
-                dst.set_direction(
-                    api::ProcessedTxOutDirection::from_i32(src.direction)
-                        .unwrap_or(api::ProcessedTxOutDirection::Invalid),
-                );
+        if receipt.tombstone == 0 {
+            return Err(RpcStatus::with_message(
+                RpcStatusCode::INVALID_ARGUMENT,
+                "receipt.tombstone".into(),
+            ));
+        }
 
-                let subaddress = account_key.subaddress(src.subaddress_index);
-                let mut wrapper = api::printable::PrintableWrapper::new();
-                wrapper.set_public_address((&subaddress).into());
-                let encoded = wrapper
-                    .b58_encode()
-                    .map_err(|err| rpc_internal_error("wrapper.b58_encode", err, &self.logger))?;
-                dst.set_address_code(encoded);
-                dst.set_token_id(src.token_id);
-                Ok(dst)
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+        if receipt.get_confirmation_number().len() != 32 {
+            return Err(RpcStatus::with_message(
+                RpcStatusCode::INVALID_ARGUMENT,
+                "receipt.confirmation_number".into(),
+            ));
+        }
 
-        // Return response
-        let mut response = api::GetProcessedBlockResponse::new();
-        response.set_tx_outs(RepeatedField::from_vec(processed_tx_outs));
-        Ok(response)
-    }
+        let tx_public_key = CompressedRistrettoPublic::try_from(receipt.get_tx_public_key())
+            .map_err(|err| rpc_invalid_arg_error("receipt.tx_public_key", err, &self.logger))?;
 
-    fn get_block_index_by_tx_pub_key_impl(
-        &mut self,
-        request: api::GetBlockIndexByTxPubKeyRequest,
-    ) -> Result<api::GetBlockIndexByTxPubKeyResponse, RpcStatus> {
-        let tx_public_key = RistrettoPublic::try_from(request.get_tx_public_key())
-            .map_err(|err| rpc_internal_error("RistrettoPublic.try_from", err, &self.logger))?;
+        let monitors = if request.get_monitor_id().is_empty() {
+            self.mobilecoind_db
+                .get_monitor_map()
+                .map_err(|err| {
+                    rpc_internal_error("mobilecoind_db.get_monitor_map", err, &self.logger)
+                })?
+                .into_iter()
+                .collect::<Vec<_>>()
+        } else {
+            let monitor_id = MonitorId::try_from(&request.monitor_id).map_err(|err| {
+                rpc_invalid_arg_error("monitor_id.try_from.bytes", err, &self.logger)
+            })?;
+            let monitor_data =
+                self.mobilecoind_db
+                    .get_monitor_data(&monitor_id)
+                    .map_err(|err| {
+                        rpc_internal_error("mobilecoind_db.get_monitor_data", err, &self.logger)
+                    })?;
+            vec![(monitor_id, monitor_data)]
+        };
 
-        let compressed_tx_public_key = CompressedRistrettoPublic::from(&tx_public_key);
+        let mut response = api::VerifyReceiverTxReceiptResponse::new();
 
-        let tx_out_index = self
-            .ledger_db
-            .get_tx_out_index_by_public_key(&compressed_tx_public_key)
+        // Find the TxOut in the ledger, and make sure both of its identifiers in the
+        // receipt refer to it.
+        let mut hash_bytes = [0u8; 32];
+        hash_bytes.copy_from_slice(receipt.get_tx_out_hash());
+
+        let tx_out_index = match self.ledger_db.get_tx_out_index_by_hash(&hash_bytes) {
+            Ok(tx_out_index) => tx_out_index,
+            Err(LedgerError::NotFound) => {
+                let status = match self
+                    .ledger_db
+                    .get_tx_out_index_by_public_key(&tx_public_key)
+                {
+                    Ok(_) => api::ReceiptStatus::ReceiptTxOutMismatch,
+                    Err(LedgerError::NotFound) => {
+                        let num_blocks = self.ledger_db.num_blocks().map_err(|err| {
+                            rpc_internal_error("ledger_db.num_blocks", err, &self.logger)
+                        })?;
+                        if num_blocks >= receipt.tombstone {
+                            api::ReceiptStatus::ReceiptTombstoneBlockExceeded
+                        } else {
+                            api::ReceiptStatus::ReceiptPending
+                        }
+                    }
+                    Err(err) => {
+                        return Err(rpc_internal_error(
+                            "ledger_db.get_tx_out_index_by_public_key",
+                            err,
+                            &self.logger,
+                        ))
+                    }
+                };
+                response.set_status(status);
+                return Ok(response);
+            }
+            Err(err) => {
+                return Err(rpc_internal_error(
+                    "ledger_db.get_tx_out_index_by_hash",
+                    err,
+                    &self.logger,
+                ))
+            }
+        };
+
+        let tx_out = self
+            .ledger_db
+            .get_tx_out_by_index(tx_out_index)
+            .map_err(|err| {
+                rpc_internal_error("ledger_db.get_tx_out_by_index", err, &self.logger)
+            })?;
+        if tx_out.public_key != tx_public_key {
+            response.set_status(api::ReceiptStatus::ReceiptTxOutMismatch);
+            return Ok(response);
+        }
+
+        let block_index = self
+            .ledger_db
+            .get_block_index_by_tx_out_index(tx_out_index)
+            .map_err(|err| {
+                rpc_internal_error(
+                    "ledger_db.get_block_index_by_tx_out_index",
+                    err,
+                    &self.logger,
+                )
+            })?;
+
+        let tx_out_target_key = RistrettoPublic::try_from(&tx_out.target_key)
+            .map_err(|err| rpc_internal_error("RistrettoPublic.try_from", err, &self.logger))?;
+        let tx_out_public_key = RistrettoPublic::try_from(&tx_out.public_key)
+            .map_err(|err| rpc_internal_error("RistrettoPublic.try_from", err, &self.logger))?;
+
+        // Find the monitor the TxOut was sent to.
+        for (monitor_id, monitor_data) in monitors {
+            let view_account_key = monitor_data.view_account_key().map_err(|err| {
+                rpc_internal_error("monitor_data.view_account_key", err, &self.logger)
+            })?;
+            let view_private_key = view_account_key.view_private_key();
+
+            let subaddress_spk = SubaddressSPKId::from(&recover_public_subaddress_spend_key(
+                view_private_key,
+                &tx_out_target_key,
+                &tx_out_public_key,
+            ));
+            let subaddress_id = match self
+                .mobilecoind_db
+                .get_subaddress_id_by_spk(&subaddress_spk)
+            {
+                Ok(subaddress_id) if subaddress_id.monitor_id == monitor_id => subaddress_id,
+                Ok(_) | Err(Error::SubaddressSPKNotFound) => continue,
+                Err(err) => {
+                    return Err(rpc_internal_error(
+                        "mobilecoind_db.get_subaddress_id_by_spk",
+                        err,
+                        &self.logger,
+                    ))
+                }
+            };
+
+            let shared_secret = get_tx_out_shared_secret(view_private_key, &tx_out_public_key);
+            let (amount, _blinding) = tx_out
+                .masked_amount
+                .get_value(&shared_secret)
+                .map_err(|err| rpc_internal_error("masked_amount.get_value", err, &self.logger))?;
+
+            response.set_monitor_id(monitor_id.to_vec());
+            response.set_subaddress_index(subaddress_id.index);
+            response.set_block_index(block_index);
+            response.set_value(amount.value);
+            response.set_token_id(*amount.token_id);
+
+            // Only the party constructing the transaction could have created the
+            // correct confirmation number.
+            let confirmation_number = {
+                let mut confirmation_bytes = [0u8; 32];
+                confirmation_bytes.copy_from_slice(receipt.get_confirmation_number());
+                TxOutConfirmationNumber::from(confirmation_bytes)
+            };
+            let status = if !confirmation_number.validate(&tx_out_public_key, view_private_key) {
+                api::ReceiptStatus::ReceiptInvalidConfirmationNumber
+            } else if receipt.value != 0
+                && (receipt.value != amount.value || receipt.token_id != *amount.token_id)
+            {
+                api::ReceiptStatus::ReceiptValueMismatch
+            } else {
+                api::ReceiptStatus::ReceiptVerified
+            };
+            response.set_status(status);
+            return Ok(response);
+        }
+
+        response.set_block_index(block_index);
+        response.set_status(api::ReceiptStatus::ReceiptNotOwned);
+        Ok(response)
+    }
+
+    /// Convert a ProcessedTxOut of a monitor into its proto representation,
+    /// decoding the memo of received TxOuts.
+    fn processed_tx_out_to_proto(
+        &self,
+        monitor_id: &MonitorId,
+        monitor_data: &MonitorData,
+        src: &ProcessedTxOut,
+        known_senders: &[PublicAddress],
+    ) -> Result<api::ProcessedTxOut, RpcStatus> {
+        let mut dst = api::ProcessedTxOut::new();
+        dst.set_monitor_id(monitor_id.to_vec());
+        dst.set_subaddress_index(src.subaddress_index);
+        dst.set_public_key((&src.public_key).into());
+        dst.set_key_image((&src.key_image).into());
+        dst.set_value(src.value);
+        dst.set_direction(
+            api::ProcessedTxOutDirection::from_i32(src.direction)
+                .unwrap_or(api::ProcessedTxOutDirection::Invalid),
+        );
+
+        let subaddress = monitor_data
+            .subaddress(src.subaddress_index)
+            .map_err(|err| rpc_internal_error("monitor_data.subaddress", err, &self.logger))?;
+        let mut wrapper = api::printable::PrintableWrapper::new();
+        wrapper.set_public_address((&subaddress).into());
+        let encoded = wrapper
+            .b58_encode()
+            .map_err(|err| rpc_internal_error("wrapper.b58_encode", err, &self.logger))?;
+        dst.set_address_code(encoded);
+        dst.set_token_id(src.token_id);
+
+        let label = self
+            .mobilecoind_db
+            .get_subaddress_label(monitor_id, src.subaddress_index)
+            .map_err(|err| {
+                rpc_internal_error("mobilecoind_db.get_subaddress_label", err, &self.logger)
+            })?;
+        dst.set_subaddress_label(label.unwrap_or_default());
+
+        // Spent TxOuts carry the memo of the payment that created them, which is
+        // already reported for the block that received them.
+        if src.direction == api::ProcessedTxOutDirection::Received as i32 {
+            let tx_out = self
+                .ledger_db
+                .get_tx_out_index_by_public_key(&src.public_key)
+                .and_then(|index| self.ledger_db.get_tx_out_by_index(index))
+                .map_err(|err| {
+                    rpc_internal_error("ledger_db.get_tx_out_by_public_key", err, &self.logger)
+                })?;
+            let memo = decode_memo(&tx_out, src.subaddress_index, monitor_data, known_senders)
+                .map_err(|err| rpc_internal_error("decode_memo", err, &self.logger))?;
+            dst.set_memo(memo);
+        }
+
+        if let Some(payment) = &src.payment {
+            let mut sent_payment = api::SentPayment::new();
+            sent_payment.set_outlays(RepeatedField::from_vec(
+                payment
+                    .outlays
+                    .iter()
+                    .map(|outlay| {
+                        let mut sent_outlay = api::SentOutlay::new();
+                        sent_outlay.set_receiver((&outlay.receiver).into());
+                        sent_outlay.set_value(outlay.value);
+                        sent_outlay
+                    })
+                    .collect(),
+            ));
+            sent_payment.set_fee(payment.fee);
+            sent_payment.set_token_id(payment.token_id);
+            dst.set_payment(sent_payment);
+        }
+        Ok(dst)
+    }
+
+    fn get_processed_block_impl(
+        &mut self,
+        request: api::GetProcessedBlockRequest,
+    ) -> Result<api::GetProcessedBlockResponse, RpcStatus> {
+        // Get MonitorId from from the GRPC request.
+        let monitor_id = MonitorId::try_from(&request.monitor_id)
+            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
+
+        // We will use the monitor's keys to compute the Address Code
+        let monitor_data = self
+            .mobilecoind_db
+            .get_monitor_data(&monitor_id)
+            .map_err(|err| {
+                rpc_internal_error("mobilecoind_db.get_monitor_data", err, &self.logger)
+            })?;
+
+        let known_senders = self.parse_known_senders(request.get_known_senders())?;
+
+        // Get all processed block data for the requested block.
+        let processed_tx_outs = self
+            .mobilecoind_db
+            .get_processed_block(&monitor_id, request.block)
+            .map_err(|err| {
+                rpc_internal_error("mobilecoind_db.get_processed_block", err, &self.logger)
+            })?
+            .iter()
+            .map(|src| {
+                self.processed_tx_out_to_proto(&monitor_id, &monitor_data, src, &known_senders)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Return response
+        let mut response = api::GetProcessedBlockResponse::new();
+        response.set_tx_outs(RepeatedField::from_vec(processed_tx_outs));
+        Ok(response)
+    }
+
+    fn get_transaction_history_impl(
+        &mut self,
+        request: api::GetTransactionHistoryRequest,
+    ) -> Result<api::GetTransactionHistoryResponse, RpcStatus> {
+        // Get MonitorId from from the GRPC request.
+        let monitor_id = MonitorId::try_from(&request.monitor_id)
+            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
+
+        let monitor_data = self
+            .mobilecoind_db
+            .get_monitor_data(&monitor_id)
+            .map_err(|err| {
+                rpc_internal_error("mobilecoind_db.get_monitor_data", err, &self.logger)
+            })?;
+
+        let known_senders = self.parse_known_senders(request.get_known_senders())?;
+
+        // A limit of 0 means no limit.
+        let limit = if request.limit == 0 {
+            u64::MAX
+        } else {
+            request.limit
+        };
+        let (history, total_count) = self
+            .mobilecoind_db
+            .get_transaction_history(&monitor_id, request.offset, limit, request.newest_first)
+            .map_err(|err| {
+                rpc_internal_error("mobilecoind_db.get_transaction_history", err, &self.logger)
+            })?;
+
+        let entries = history
+            .iter()
+            .map(|(block_index, src)| {
+                let mut entry = api::TransactionHistoryEntry::new();
+                entry.set_block_index(*block_index);
+                entry.set_tx_out(self.processed_tx_out_to_proto(
+                    &monitor_id,
+                    &monitor_data,
+                    src,
+                    &known_senders,
+                )?);
+                Ok(entry)
+            })
+            .collect::<Result<Vec<_>, RpcStatus>>()?;
+
+        // Return response
+        let mut response = api::GetTransactionHistoryResponse::new();
+        response.set_entries(RepeatedField::from_vec(entries));
+        response.set_total_count(total_count);
+        Ok(response)
+    }
+
+    fn get_block_index_by_tx_pub_key_impl(
+        &mut self,
+        request: api::GetBlockIndexByTxPubKeyRequest,
+    ) -> Result<api::GetBlockIndexByTxPubKeyResponse, RpcStatus> {
+        let tx_public_key = RistrettoPublic::try_from(request.get_tx_public_key())
+            .map_err(|err| rpc_internal_error("RistrettoPublic.try_from", err, &self.logger))?;
+
+        let compressed_tx_public_key = CompressedRistrettoPublic::from(&tx_public_key);
+
+        let tx_out_index = self
+            .ledger_db
+            .get_tx_out_index_by_public_key(&compressed_tx_public_key)
             .map_err(|err| {
                 rpc_internal_error(
                     "ledger_db.get_tx_out_index_by_public_key",
@@ -1784,6 +3339,51 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         Ok(response)
     }
 
+    fn check_key_images_impl(
+        &mut self,
+        request: api::CheckKeyImagesRequest,
+    ) -> Result<api::CheckKeyImagesResponse, RpcStatus> {
+        if request.get_key_images().is_empty() {
+            return Err(RpcStatus::with_message(
+                RpcStatusCode::INVALID_ARGUMENT,
+                "key_images".into(),
+            ));
+        }
+
+        // Key images found unspent were not spent in any of the blocks counted here,
+        // since blocks cannot be removed from the ledger.
+        let num_blocks = self
+            .ledger_db
+            .num_blocks()
+            .map_err(|err| rpc_internal_error("ledger_db.num_blocks", err, &self.logger))?;
+
+        let statuses = request
+            .get_key_images()
+            .iter()
+            .map(|proto_key_image| {
+                let key_image = KeyImage::try_from(proto_key_image).map_err(|err| {
+                    rpc_invalid_arg_error("key_image.try_from", err, &self.logger)
+                })?;
+                let spent_at = self.ledger_db.check_key_image(&key_image).map_err(|err| {
+                    rpc_internal_error("ledger_db.check_key_image", err, &self.logger)
+                })?;
+
+                let mut status = api::KeyImageStatus::new();
+                status.set_key_image(proto_key_image.clone());
+                if let Some(block_index) = spent_at {
+                    status.set_spent(true);
+                    status.set_spent_at_block_index(block_index);
+                }
+                Ok(status)
+            })
+            .collect::<Result<Vec<_>, RpcStatus>>()?;
+
+        let mut response = api::CheckKeyImagesResponse::new();
+        response.set_statuses(RepeatedField::from_vec(statuses));
+        response.set_num_blocks(num_blocks);
+        Ok(response)
+    }
+
     fn get_balance_impl(
         &mut self,
         request: api::GetBalanceRequest,
@@ -1829,6 +3429,70 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         &mut self,
         request: api::SendPaymentRequest,
     ) -> Result<api::SendPaymentResponse, RpcStatus> {
+        if request.idempotency_key.is_empty() {
+            let tx_proposal = self.build_payment(&request)?;
+            return self.submit_payment(api::TxProposal::from(&tx_proposal));
+        }
+
+        let key = idempotency_key("SendPayment", &request.idempotency_key);
+        let mut fingerprint_request = request.clone();
+        fingerprint_request.clear_idempotency_key();
+        let proto_tx_proposal = match self.reserve_idempotency_key(&key, &fingerprint_request)? {
+            Some(idempotent_request) if !idempotent_request.response.is_empty() => {
+                return self.idempotent_response(&idempotent_request);
+            }
+            // The transaction of the payment was built, but maybe not submitted
+            // successfully. Submitting it again cannot pay twice, since it spends the
+            // same inputs.
+            Some(idempotent_request) if !idempotent_request.tx_proposal.is_empty() => {
+                api::TxProposal::parse_from_bytes(&idempotent_request.tx_proposal).map_err(
+                    |err| rpc_internal_error("tx_proposal.parse_from_bytes", err, &self.logger),
+                )?
+            }
+            Some(_) => {
+                return Err(RpcStatus::with_message(
+                    RpcStatusCode::ABORTED,
+                    "a payment with this idempotency_key is in progress".into(),
+                ));
+            }
+            None => {
+                let tx_proposal = match self.build_payment(&request) {
+                    Ok(tx_proposal) => tx_proposal,
+                    Err(err) => {
+                        self.release_idempotency_key(&key);
+                        return Err(err);
+                    }
+                };
+                let proto_tx_proposal = api::TxProposal::from(&tx_proposal);
+
+                // Remember the transaction before submitting it, so that replays never
+                // build another one.
+                let tx_proposal_bytes = proto_tx_proposal.write_to_bytes().map_err(|err| {
+                    rpc_internal_error("tx_proposal.write_to_bytes", err, &self.logger)
+                })?;
+                self.mobilecoind_db
+                    .set_idempotent_tx_proposal(&key, &tx_proposal_bytes)
+                    .map_err(|err| {
+                        rpc_internal_error(
+                            "mobilecoind_db.set_idempotent_tx_proposal",
+                            err,
+                            &self.logger,
+                        )
+                    })?;
+                proto_tx_proposal
+            }
+        };
+
+        let response = self.submit_payment(proto_tx_proposal)?;
+        self.set_idempotent_response(&key, &response)?;
+        Ok(response)
+    }
+
+    /// Build the transaction of a SendPayment request.
+    fn build_payment(
+        &mut self,
+        request: &api::SendPaymentRequest,
+    ) -> Result<TxProposal, RpcStatus> {
         // Get sender monitor id from request.
         let sender_monitor_id = MonitorId::try_from(&request.sender_monitor_id)
             .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
@@ -1846,19 +3510,6 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
             utxos.retain(|utxo| utxo.value <= request.max_input_utxo_value);
         }
 
-        // Filter for requested token id.
-        utxos.retain(|utxo| utxo.token_id == request.token_id);
-
-        // Get the list of outlays.
-        let outlays: Vec<Outlay> = request
-            .get_outlay_list()
-            .iter()
-            .map(|outlay_proto| {
-                Outlay::try_from(outlay_proto)
-                    .map_err(|err| rpc_internal_error("outlay.try_from", err, &self.logger))
-            })
-            .collect::<Result<Vec<Outlay>, RpcStatus>>()?;
-
         // Set change address to sender address unless it has been overridden
         let change_subaddress = if request.override_change_subaddress {
             request.change_subaddress
@@ -1867,25 +3518,74 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         };
 
         // Attempt to construct a transaction.
-        let tx_proposal = self
-            .transactions_manager
-            .build_transaction(
-                &sender_monitor_id,
-                TokenId::from(request.token_id),
-                change_subaddress,
-                &utxos,
-                &outlays,
-                &self.get_last_block_infos(),
-                request.fee,
-                request.tombstone,
-                None,
-            )
-            .map_err(|err| {
-                rpc_internal_error("transactions_manager.build_transaction", err, &self.logger)
-            })?;
+        let tx_proposal = if request.get_outlay_v2_list().is_empty() {
+            // Filter for requested token id.
+            utxos.retain(|utxo| utxo.token_id == request.token_id);
 
-        let proto_tx_proposal = api::TxProposal::from(&tx_proposal);
+            // Get the list of outlays.
+            let outlays: Vec<Outlay> = request
+                .get_outlay_list()
+                .iter()
+                .map(|outlay_proto| {
+                    Outlay::try_from(outlay_proto)
+                        .map_err(|err| rpc_internal_error("outlay.try_from", err, &self.logger))
+                })
+                .collect::<Result<Vec<Outlay>, RpcStatus>>()?;
+
+            self.transactions_manager
+                .build_transaction(
+                    &sender_monitor_id,
+                    TokenId::from(request.token_id),
+                    change_subaddress,
+                    &utxos,
+                    &outlays,
+                    &self.get_last_block_infos(),
+                    request.fee,
+                    request.tombstone,
+                    None,
+                )
+                .map_err(|err| {
+                    tx_build_error("transactions_manager.build_transaction", err, &self.logger)
+                })?
+        } else {
+            // Get the list of outlays, which may be of several token ids.
+            let outlays: Vec<OutlayV2> = request
+                .get_outlay_v2_list()
+                .iter()
+                .map(|outlay_proto| {
+                    OutlayV2::try_from(outlay_proto)
+                        .map_err(|err| rpc_internal_error("outlay_v2.try_from", err, &self.logger))
+                })
+                .collect::<Result<Vec<OutlayV2>, RpcStatus>>()?;
+
+            self.transactions_manager
+                .build_mixed_transaction(
+                    &sender_monitor_id,
+                    TokenId::from(request.fee_token_id),
+                    change_subaddress,
+                    &utxos,
+                    &outlays,
+                    &self.get_last_block_infos(),
+                    request.fee,
+                    request.tombstone,
+                )
+                .map_err(|err| {
+                    tx_build_error(
+                        "transactions_manager.build_mixed_transaction",
+                        err,
+                        &self.logger,
+                    )
+                })?
+        };
+
+        Ok(tx_proposal)
+    }
 
+    /// Submit the transaction of a SendPayment request.
+    fn submit_payment(
+        &mut self,
+        proto_tx_proposal: api::TxProposal,
+    ) -> Result<api::SendPaymentResponse, RpcStatus> {
         // Submit transaction.
         let mut submit_tx_request = api::SubmitTxRequest::new();
         submit_tx_request.set_tx_proposal(proto_tx_proposal.clone());
@@ -1936,6 +3636,136 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
         self.send_payment_impl(send_payment_request)
     }
 
+    fn burn_funds_impl(
+        &mut self,
+        request: api::BurnFundsRequest,
+    ) -> Result<api::SendPaymentResponse, RpcStatus> {
+        // Sanity check.
+        if request.get_burn_amount() == 0 {
+            return Err(RpcStatus::with_message(
+                RpcStatusCode::INVALID_ARGUMENT,
+                "burn_amount".into(),
+            ));
+        }
+
+        // Get sender monitor id from request.
+        let sender_monitor_id = MonitorId::try_from(&request.sender_monitor_id)
+            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
+
+        // Get the utxos of the requested token id for this subaddress.
+        let mut utxos = self
+            .mobilecoind_db
+            .get_utxos_for_subaddress(&sender_monitor_id, request.sender_subaddress)
+            .map_err(|err| {
+                rpc_internal_error("mobilecoind_db.get_utxos_for_subaddress", err, &self.logger)
+            })?;
+        utxos.retain(|utxo| utxo.token_id == request.token_id);
+
+        // Optionally filter for max value.
+        if request.max_input_utxo_value > 0 {
+            utxos.retain(|utxo| utxo.value <= request.max_input_utxo_value);
+        }
+
+        // Set change address to sender address unless it has been overridden
+        let change_subaddress = if request.override_change_subaddress {
+            request.change_subaddress
+        } else {
+            request.sender_subaddress
+        };
+
+        // Forward to GenerateBurnRedemptionTx
+        let mut generate_request = api::GenerateBurnRedemptionTxRequest::new();
+        generate_request.set_sender_monitor_id(request.get_sender_monitor_id().to_vec());
+        generate_request.set_change_subaddress(change_subaddress);
+        generate_request.set_input_list(utxos.iter().map(api::UnspentTxOut::from).collect());
+        generate_request.set_burn_amount(request.get_burn_amount());
+        generate_request.set_fee(request.get_fee());
+        generate_request.set_tombstone(request.get_tombstone());
+        generate_request.set_token_id(request.token_id);
+        generate_request.set_redemption_memo(request.get_redemption_memo().to_vec());
+        generate_request.set_enable_destination_memo(request.enable_destination_memo);
+        let mut generate_response = self.generate_burn_redemption_tx_impl(generate_request)?;
+        let proto_tx_proposal = generate_response.take_tx_proposal();
+
+        // Submit transaction.
+        let mut submit_tx_request = api::SubmitTxRequest::new();
+        submit_tx_request.set_tx_proposal(proto_tx_proposal.clone());
+        let mut submit_tx_response = self.submit_tx_impl(submit_tx_request)?;
+
+        // Return response.
+        let mut response = api::SendPaymentResponse::new();
+        response.set_sender_tx_receipt(submit_tx_response.take_sender_tx_receipt());
+        response.set_receiver_tx_receipt_list(submit_tx_response.take_receiver_tx_receipt_list());
+        response.set_tx_proposal(proto_tx_proposal);
+        Ok(response)
+    }
+
+    fn consolidate_utxos_impl(
+        &mut self,
+        request: api::ConsolidateUtxosRequest,
+    ) -> Result<api::ConsolidateUtxosResponse, RpcStatus> {
+        // Get monitor id from request.
+        let monitor_id = MonitorId::try_from(&request.monitor_id)
+            .map_err(|err| rpc_internal_error("monitor_id.try_from.bytes", err, &self.logger))?;
+        let token_id = TokenId::from(request.token_id);
+        let last_block_infos = self.get_last_block_infos();
+
+        // Figure out the transactions needed, and their fees.
+        let plan = self
+            .transactions_manager
+            .plan_consolidation(
+                &monitor_id,
+                request.subaddress,
+                token_id,
+                request.target_num_utxos as usize,
+                &last_block_infos,
+                request.fee,
+            )
+            .map_err(|err| {
+                rpc_internal_error("transactions_manager.plan_consolidation", err, &self.logger)
+            })?;
+
+        let mut response = api::ConsolidateUtxosResponse::new();
+        response.set_num_utxos(plan.num_utxos as u64);
+        response.set_num_dust_utxos(plan.num_dust_utxos as u64);
+        response.set_num_txs(plan.num_txs as u64);
+        response.set_total_fee(plan.total_fee());
+
+        if request.submit {
+            let tx_proposals = self
+                .transactions_manager
+                .generate_consolidation_txs(
+                    &monitor_id,
+                    request.subaddress,
+                    token_id,
+                    &plan,
+                    &last_block_infos,
+                )
+                .map_err(|err| {
+                    rpc_internal_error(
+                        "transactions_manager.generate_consolidation_txs",
+                        err,
+                        &self.logger,
+                    )
+                })?;
+
+            for tx_proposal in tx_proposals.iter() {
+                let proto_tx_proposal = api::TxProposal::from(tx_proposal);
+
+                let mut submit_tx_request = api::SubmitTxRequest::new();
+                submit_tx_request.set_tx_proposal(proto_tx_proposal.clone());
+                let mut submit_tx_response = self.submit_tx_impl(submit_tx_request)?;
+
+                response.mut_tx_proposal_list().push(proto_tx_proposal);
+                response
+                    .mut_sender_tx_receipt_list()
+                    .push(submit_tx_response.take_sender_tx_receipt());
+            }
+        }
+
+        Ok(response)
+    }
+
     fn get_network_status_impl(
         &mut self,
         _request: api::Empty,
@@ -2024,6 +3854,135 @@ impl<T: BlockchainConnection + UserTxConnection + 'static, FPR: FogPubkeyResolve
 
         Ok(api::Empty::default())
     }
+
+    fn change_db_password_impl(
+        &mut self,
+        request: api::ChangeDbPasswordRequest,
+    ) -> Result<api::Empty, RpcStatus> {
+        if !self.mobilecoind_db.is_unlocked() {
+            return Err(RpcStatus::with_message(
+                RpcStatusCode::FAILED_PRECONDITION,
+                "must unlock before changing current password".to_owned(),
+            ));
+        }
+
+        // Check the current password and re-encrypt data using the new one.
+        self.mobilecoind_db
+            .change_password(request.get_current_password(), request.get_new_password())
+            .map_err(|err| match err {
+                Error::DbCrypto(DbCryptoError::InvalidPassword) => {
+                    rpc_permissions_error("current_password", err, &self.logger)
+                }
+                Error::DbCrypto(DbCryptoError::InvalidPasswordLength) => {
+                    rpc_invalid_arg_error("new_password", err, &self.logger)
+                }
+                err => rpc_internal_error("mobilecoind_db.change_password", err, &self.logger),
+            })?;
+
+        log::info!(self.logger, "DB encryption password changed successfully.");
+
+        Ok(api::Empty::default())
+    }
+
+    fn get_db_encryption_status_impl(
+        &mut self,
+        _request: api::Empty,
+    ) -> Result<api::GetDbEncryptionStatusResponse, RpcStatus> {
+        let mut response = api::GetDbEncryptionStatusResponse::new();
+        response.set_is_encrypted(self.mobilecoind_db.is_db_encrypted());
+        response.set_is_unlocked(self.mobilecoind_db.is_unlocked());
+        Ok(response)
+    }
+}
+
+/// Errors building a transaction are internal errors, except for invalid
+/// tombstone blocks, which are the caller's.
+fn tx_build_error(context: &str, err: Error, logger: &Logger) -> RpcStatus {
+    match err {
+        Error::TombstoneTooNear(_, _) | Error::TombstoneTooFar(_, _) => {
+            rpc_invalid_arg_error(context, err, logger)
+        }
+        err => rpc_internal_error(context, err, logger),
+    }
+}
+
+/// Why a monitor subscription stream stopped before the monitor was removed.
+enum SubscriptionError {
+    /// The subscriber went away.
+    Sink(grpcio::Error),
+
+    /// The subscription failed, and the stream ends with this status.
+    Status(RpcStatus),
+}
+
+impl From<grpcio::Error> for SubscriptionError {
+    fn from(src: grpcio::Error) -> Self {
+        Self::Sink(src)
+    }
+}
+
+impl From<RpcStatus> for SubscriptionError {
+    fn from(src: RpcStatus) -> Self {
+        Self::Status(src)
+    }
+}
+
+/// End a monitor subscription stream, with an OK status if the monitor was
+/// removed, or with the status of the error that stopped it.
+async fn end_subscription<T>(
+    mut sink: ServerStreamingSink<T>,
+    result: Result<(), SubscriptionError>,
+) -> Result<(), grpcio::Error> {
+    match result {
+        Ok(()) => sink.close().await,
+        Err(SubscriptionError::Status(status)) => sink.fail(status).await,
+        Err(SubscriptionError::Sink(err)) => Err(err),
+    }
+}
+
+/// Validate the fog information of a public address against the DER
+/// subjectPublicKeyInfos of the trusted fog authorities.
+///
+/// Returns the subjectPublicKeyInfo the address is signed over, None if the
+/// address does not use fog, or the reason the address failed validation.
+fn validate_fog_address<'a>(
+    public_address: &PublicAddress,
+    trusted_fog_authorities: &'a [Vec<u8>],
+) -> Result<Option<&'a [u8]>, (api::FogAddressStatus, String)> {
+    let fog_report_url = match public_address.fog_report_url() {
+        Some(fog_report_url) => fog_report_url,
+        None => return Ok(None),
+    };
+    FogUri::from_str(fog_report_url).map_err(|err| {
+        (
+            api::FogAddressStatus::FogAddressInvalidReportUrl,
+            format!("Invalid fog report url: {}", err),
+        )
+    })?;
+
+    let sig_bytes = public_address.fog_authority_sig().ok_or_else(|| {
+        (
+            api::FogAddressStatus::FogAddressMissingSignature,
+            "The address has a fog report url but no fog authority signature".to_owned(),
+        )
+    })?;
+    let sig = RistrettoSignature::try_from(sig_bytes).map_err(|err| {
+        (
+            api::FogAddressStatus::FogAddressMalformedSignature,
+            format!("Invalid fog authority signature: {}", err),
+        )
+    })?;
+
+    trusted_fog_authorities
+        .iter()
+        .find(|spki| public_address.verify_authority(spki, &sig).is_ok())
+        .map(|spki| Some(spki.as_slice()))
+        .ok_or_else(|| {
+            (
+                api::FogAddressStatus::FogAddressUntrustedAuthority,
+                "The fog authority signature does not match any trusted fog authority".to_owned(),
+            )
+        })
 }
 
 macro_rules! build_api {
@@ -2038,6 +3997,7 @@ macro_rules! build_api {
                     request: api::$service_request_type,
                     sink: UnarySink<api::$service_response_type>,
                 ) {
+                    let _timer = SVC_COUNTERS.req(&ctx);
                     let logger = rpc_logger(&ctx, &self.logger);
                     send_result(
                         ctx,
@@ -2047,6 +4007,27 @@ macro_rules! build_api {
                     )
                 }
             )+
+
+            // Server streaming calls
+            fn subscribe_monitor_events(
+                &mut self,
+                ctx: RpcContext,
+                request: api::SubscribeMonitorEventsRequest,
+                sink: ServerStreamingSink<api::MonitorEvent>,
+            ) {
+                let _timer = SVC_COUNTERS.req(&ctx);
+                self.subscribe_monitor_events_impl(ctx, request, sink)
+            }
+
+            fn subscribe_monitor_sync_progress(
+                &mut self,
+                ctx: RpcContext,
+                request: api::GetMonitorSyncProgressRequest,
+                sink: ServerStreamingSink<api::MonitorSyncProgress>,
+            ) {
+                let _timer = SVC_COUNTERS.req(&ctx);
+                self.subscribe_monitor_sync_progress_impl(ctx, request, sink)
+            }
         }
     );
 }
@@ -2058,6 +4039,13 @@ build_api! {
     get_monitor_list Empty GetMonitorListResponse get_monitor_list_impl,
     get_monitor_status GetMonitorStatusRequest GetMonitorStatusResponse get_monitor_status_impl,
     get_unspent_tx_out_list GetUnspentTxOutListRequest GetUnspentTxOutListResponse get_unspent_tx_out_list_impl,
+    allocate_subaddress AllocateSubaddressRequest AllocateSubaddressResponse allocate_subaddress_impl,
+    set_subaddress_label SetSubaddressLabelRequest Empty set_subaddress_label_impl,
+    get_subaddress_labels GetSubaddressLabelsRequest GetSubaddressLabelsResponse get_subaddress_labels_impl,
+    export_monitor ExportMonitorRequest ExportMonitorResponse export_monitor_impl,
+    import_monitor ImportMonitorRequest ImportMonitorResponse import_monitor_impl,
+    set_watch_only_key_images SetWatchOnlyKeyImagesRequest SetWatchOnlyKeyImagesResponse set_watch_only_key_images_impl,
+    get_monitor_sync_progress GetMonitorSyncProgressRequest GetMonitorSyncProgressResponse get_monitor_sync_progress_impl,
 
     // Utilities
     generate_root_entropy Empty GenerateRootEntropyResponse generate_root_entropy_impl,
@@ -2073,16 +4061,29 @@ build_api! {
     create_transfer_code CreateTransferCodeRequest CreateTransferCodeResponse create_transfer_code_impl,
     parse_address_code ParseAddressCodeRequest ParseAddressCodeResponse parse_address_code_impl,
     create_address_code CreateAddressCodeRequest CreateAddressCodeResponse create_address_code_impl,
+    validate_fog_address ValidateFogAddressRequest ValidateFogAddressResponse validate_fog_address_impl,
 
     // Transactions
     get_mixins GetMixinsRequest GetMixinsResponse get_mixins_impl,
     get_membership_proofs GetMembershipProofsRequest GetMembershipProofsResponse get_membership_proofs_impl,
     generate_tx GenerateTxRequest GenerateTxResponse generate_tx_impl,
+    get_fee_estimate GetFeeEstimateRequest GetFeeEstimateResponse get_fee_estimate_impl,
+    get_suggested_tombstone_block Empty GetSuggestedTombstoneBlockResponse get_suggested_tombstone_block_impl,
     generate_optimization_tx GenerateOptimizationTxRequest GenerateOptimizationTxResponse generate_optimization_tx_impl,
     generate_transfer_code_tx GenerateTransferCodeTxRequest GenerateTransferCodeTxResponse generate_transfer_code_tx_impl,
     generate_tx_from_tx_out_list GenerateTxFromTxOutListRequest GenerateTxFromTxOutListResponse generate_tx_from_tx_out_list_impl,
     generate_burn_redemption_tx GenerateBurnRedemptionTxRequest GenerateBurnRedemptionTxResponse generate_burn_redemption_tx_impl,
     submit_tx SubmitTxRequest SubmitTxResponse submit_tx_impl,
+    create_receiver_tx_receipts CreateReceiverTxReceiptsRequest CreateReceiverTxReceiptsResponse create_receiver_tx_receipts_impl,
+
+    // Offline signing
+    generate_unsigned_tx GenerateTxRequest GenerateUnsignedTxResponse generate_unsigned_tx_impl,
+    sign_unsigned_tx SignUnsignedTxRequest SignUnsignedTxResponse sign_unsigned_tx_impl,
+    submit_signed_tx SubmitSignedTxRequest SubmitTxResponse submit_signed_tx_impl,
+
+    // Signed contingent inputs
+    create_signed_contingent_input CreateSignedContingentInputRequest CreateSignedContingentInputResponse create_signed_contingent_input_impl,
+    fulfill_signed_contingent_input FulfillSignedContingentInputRequest FulfillSignedContingentInputResponse fulfill_signed_contingent_input_impl,
 
     // Databases
     get_ledger_info Empty GetLedgerInfoResponse get_ledger_info_impl,
@@ -2090,13 +4091,18 @@ build_api! {
     get_block GetBlockRequest GetBlockResponse get_block_impl,
     get_tx_status_as_sender SubmitTxResponse GetTxStatusAsSenderResponse get_tx_status_as_sender_impl,
     get_tx_status_as_receiver GetTxStatusAsReceiverRequest GetTxStatusAsReceiverResponse get_tx_status_as_receiver_impl,
+    verify_receiver_tx_receipt VerifyReceiverTxReceiptRequest VerifyReceiverTxReceiptResponse verify_receiver_tx_receipt_impl,
     get_processed_block GetProcessedBlockRequest GetProcessedBlockResponse get_processed_block_impl,
+    get_transaction_history GetTransactionHistoryRequest GetTransactionHistoryResponse get_transaction_history_impl,
     get_block_index_by_tx_pub_key GetBlockIndexByTxPubKeyRequest GetBlockIndexByTxPubKeyResponse get_block_index_by_tx_pub_key_impl,
+    check_key_images CheckKeyImagesRequest CheckKeyImagesResponse check_key_images_impl,
 
     // Convenience calls
     get_balance GetBalanceRequest GetBalanceResponse get_balance_impl,
     send_payment SendPaymentRequest SendPaymentResponse send_payment_impl,
     pay_address_code PayAddressCodeRequest SendPaymentResponse pay_address_code_impl,
+    burn_funds BurnFundsRequest SendPaymentResponse burn_funds_impl,
+    consolidate_utxos ConsolidateUtxosRequest ConsolidateUtxosResponse consolidate_utxos_impl,
 
     // Network status
     get_network_status Empty GetNetworkStatusResponse get_network_status_impl,
@@ -2104,6 +4110,8 @@ build_api! {
     // Database encryption
     set_db_password SetDbPasswordRequest Empty set_db_password_impl,
     unlock_db UnlockDbRequest Empty unlock_db_impl,
+    change_db_password ChangeDbPasswordRequest Empty change_db_password_impl,
+    get_db_encryption_status Empty GetDbEncryptionStatusResponse get_db_encryption_status_impl,
 
     get_version Empty MobilecoindVersionResponse get_version_impl,
 }
@@ -2121,6 +4129,7 @@ mod test {
         },
         utxo_store::UnspentTxOut,
     };
+    use futures::executor::block_on;
     use grpcio::Error as GrpcError;
     use mc_account_keys::{
         burn_address_view_private, AccountKey, PublicAddress, ShortAddressHash,
@@ -2134,7 +4143,7 @@ mod test {
     use mc_fog_report_validation_test_utils::MockFogResolver;
     use mc_ledger_db::test_utils::add_txos_and_key_images_to_ledger;
     use mc_transaction_core::{
-        constants::{MAX_INPUTS, RING_SIZE},
+        constants::{MAX_INPUTS, MILLIMOB_TO_PICOMOB, RING_SIZE},
         fog_hint::FogHint,
         get_tx_out_shared_secret,
         onetime_keys::{recover_onetime_private_key, recover_public_subaddress_spend_key},
@@ -2169,7 +4178,9 @@ mod test {
         .expect("failed to create data");
 
         let mut request = api::AddMonitorRequest::new();
-        request.set_account_key(mc_api::external::AccountKey::from(&data.account_key));
+        request.set_account_key(mc_api::external::AccountKey::from(
+            data.account_key().unwrap(),
+        ));
         request.set_first_subaddress(data.first_subaddress);
         request.set_num_subaddresses(data.num_subaddresses);
         request.set_first_block(data.first_block);
@@ -2185,22 +4196,287 @@ mod test {
             .expect("failed to convert response to MonitorId");
         let expected_monitor_id = MonitorId::from(&data);
 
-        assert_eq!(expected_monitor_id, monitor_id);
+        assert_eq!(expected_monitor_id, monitor_id);
+
+        // Check that the monitor is reported as new
+        assert!(response.is_new);
+
+        // Add the same monitor again
+        let repeated_response = client.add_monitor(&request).expect("failed to add monitor");
+
+        // Compare the MonitorId we got back to the value we expected.
+        let repeated_monitor_id = MonitorId::try_from(&repeated_response.monitor_id)
+            .expect("failed to convert repeated_response to MonitorId");
+
+        assert_eq!(expected_monitor_id, repeated_monitor_id);
+
+        // Check that the monitor is not reported as new
+        assert!(!repeated_response.is_new);
+    }
+
+    #[test_with_logger]
+    fn test_watch_only_monitor(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([21u8; 32]);
+
+        let account_key = AccountKey::random(&mut rng);
+        let view_account_key = ViewAccountKey::from(&account_key);
+
+        // 1 known recipient, 3 random recipients and no monitors.
+        let (ledger_db, mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(
+                BLOCK_VERSION,
+                3,
+                &[account_key.default_subaddress()],
+                &[],
+                logger.clone(),
+                &mut rng,
+            );
+
+        // A monitor needs exactly one of the keys.
+        let mut request = api::AddMonitorRequest::new();
+        request.set_first_subaddress(0);
+        request.set_num_subaddresses(20);
+        assert!(client.add_monitor(&request).is_err());
+        request.set_account_key(mc_api::external::AccountKey::from(&account_key));
+        request.set_view_account_key(mc_api::external::ViewAccountKey::from(&view_account_key));
+        assert!(client.add_monitor(&request).is_err());
+
+        // Add a watch-only monitor.
+        request.clear_account_key();
+        let response = client.add_monitor(&request).expect("failed to add monitor");
+        let monitor_id = MonitorId::try_from(&response.monitor_id).unwrap();
+
+        // Allow the new monitor to process the ledger.
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+        // The status only holds the view key.
+        let mut request = api::GetMonitorStatusRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        let status = client.get_monitor_status(&request).unwrap().take_status();
+        assert!(!status.has_account_key());
+        assert_eq!(
+            ViewAccountKey::try_from(status.get_view_account_key()).unwrap(),
+            view_account_key
+        );
+
+        // Incoming outputs are found.
+        let mut request = api::GetBalanceRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_subaddress_index(0);
+        let response = client.get_balance(&request).unwrap();
+        assert_eq!(
+            response.balance,
+            test_utils::DEFAULT_PER_RECIPIENT_AMOUNT * ledger_db.num_blocks().unwrap()
+        );
+
+        // Addresses match those of the full account key.
+        let mut request = api::GetPublicAddressRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_subaddress_index(0);
+        let response = client.get_public_address(&request).unwrap();
+        assert_eq!(
+            PublicAddress::try_from(response.get_public_address()).unwrap(),
+            account_key.default_subaddress()
+        );
+
+        // Building transactions fails.
+        let mut request = api::SendPaymentRequest::new();
+        request.set_sender_monitor_id(monitor_id.to_vec());
+        request.set_sender_subaddress(0);
+        request.set_outlay_list(RepeatedField::from_vec(vec![api::Outlay::from(&Outlay {
+            value: 123,
+            receiver: AccountKey::random(&mut rng).default_subaddress(),
+        })]));
+        match client.send_payment(&request) {
+            Ok(_) => panic!("Should've returned an error"),
+            Err(GrpcError::RpcFailure(rpc_status)) => {
+                assert_eq!(
+                    rpc_status.message(),
+                    "transactions_manager.build_transaction: The monitor is watch-only and cannot build transactions".to_owned()
+                );
+            }
+            Err(err) => panic!("Unexpected error: {:?}", err),
+        };
+
+        let mut request = api::GenerateOptimizationTxRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_subaddress(0);
+        assert!(client.generate_optimization_tx(&request).is_err());
+    }
+
+    #[test_with_logger]
+    fn test_set_watch_only_key_images(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([21u8; 32]);
+
+        let account_key = AccountKey::random(&mut rng);
+
+        // 1 known recipient, 3 random recipients and no monitors.
+        let (mut ledger_db, mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(
+                BLOCK_VERSION,
+                3,
+                &[account_key.default_subaddress()],
+                &[],
+                logger.clone(),
+                &mut rng,
+            );
+
+        let data = MonitorData::new_watch_only(
+            ViewAccountKey::from(&account_key),
+            None, // default_public_address
+            0,    // first_subaddress
+            1,    // num_subaddresses
+            0,    // first_block
+            "",   // name
+        )
+        .unwrap();
+        let monitor_id = mobilecoind_db.add_monitor(&data).unwrap();
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+        // The key images of the outputs, as computed by the holder of the spend
+        // private key.
+        let utxos = mobilecoind_db
+            .get_utxos_for_subaddress(&monitor_id, 0)
+            .unwrap();
+        let key_image_of = |utxo: &UnspentTxOut| {
+            let tx_public_key = RistrettoPublic::try_from(&utxo.tx_out.public_key).unwrap();
+            KeyImage::from(&recover_onetime_private_key(
+                &tx_public_key,
+                account_key.view_private_key(),
+                &account_key.subaddress_spend_private(0),
+            ))
+        };
+        let proto_key_image = |tx_public_key: &CompressedRistrettoPublic, key_image: &KeyImage| {
+            let mut proto_key_image = api::WatchOnlyKeyImage::new();
+            proto_key_image.set_tx_public_key(tx_public_key.into());
+            proto_key_image.set_key_image(key_image.into());
+            proto_key_image
+        };
+        let balance = || {
+            let mut request = api::GetBalanceRequest::new();
+            request.set_monitor_id(monitor_id.to_vec());
+            request.set_subaddress_index(0);
+            client.get_balance(&request).unwrap().balance
+        };
+        let initial_balance = balance();
+
+        // Spend the first output in a new block. The monitor does not know its key
+        // image yet, so it is still unspent.
+        let spent_key_image = key_image_of(&utxos[0]);
+        add_block_to_ledger(
+            &mut ledger_db,
+            BLOCK_VERSION,
+            &[AccountKey::random(&mut rng).default_subaddress()],
+            Amount::new(DEFAULT_PER_RECIPIENT_AMOUNT, Mob::ID),
+            &[spent_key_image],
+            &mut rng,
+        )
+        .unwrap();
+        let spent_block_index = ledger_db.num_blocks().unwrap() - 1;
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+        assert_eq!(balance(), initial_balance);
+
+        // Provide the key images of the first two outputs, and of an output the
+        // monitor does not have.
+        let mut request = api::SetWatchOnlyKeyImagesRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_key_images(RepeatedField::from_vec(vec![
+            proto_key_image(&utxos[0].tx_out.public_key, &spent_key_image),
+            proto_key_image(&utxos[1].tx_out.public_key, &key_image_of(&utxos[1])),
+            proto_key_image(
+                &CompressedRistrettoPublic::from(&RistrettoPublic::from_random(&mut rng)),
+                &KeyImage::from(rng.next_u64()),
+            ),
+        ]));
+        let response = client.set_watch_only_key_images(&request).unwrap();
+        assert_eq!(
+            response.get_statuses(),
+            &[
+                api::WatchOnlyKeyImageStatus::WatchOnlyKeyImageSpent,
+                api::WatchOnlyKeyImageStatus::WatchOnlyKeyImageUnspent,
+                api::WatchOnlyKeyImageStatus::WatchOnlyKeyImageNotFound,
+            ]
+        );
+        assert_eq!(
+            response.get_spent_at_block_indexes(),
+            &[spent_block_index, 0, 0]
+        );
+
+        // The first output is spent, in the block that spent it.
+        assert_eq!(balance(), initial_balance - utxos[0].value);
+        let processed_block = mobilecoind_db
+            .get_processed_block(&monitor_id, spent_block_index)
+            .unwrap();
+        assert!(processed_block.iter().any(|processed_tx_out| {
+            processed_tx_out.key_image == spent_key_image
+                && processed_tx_out.direction == api::ProcessedTxOutDirection::Spent as i32
+        }));
+
+        // Providing a key image again is harmless.
+        request.set_key_images(RepeatedField::from_vec(vec![proto_key_image(
+            &utxos[1].tx_out.public_key,
+            &key_image_of(&utxos[1]),
+        )]));
+        let response = client.set_watch_only_key_images(&request).unwrap();
+        assert_eq!(
+            response.get_statuses(),
+            &[api::WatchOnlyKeyImageStatus::WatchOnlyKeyImageUnspent]
+        );
+
+        // The second output is found spent when the monitor processes the block
+        // that spends it.
+        add_block_to_ledger(
+            &mut ledger_db,
+            BLOCK_VERSION,
+            &[AccountKey::random(&mut rng).default_subaddress()],
+            Amount::new(DEFAULT_PER_RECIPIENT_AMOUNT, Mob::ID),
+            &[key_image_of(&utxos[1])],
+            &mut rng,
+        )
+        .unwrap();
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+        assert_eq!(balance(), initial_balance - utxos[0].value - utxos[1].value);
 
-        // Check that the monitor is reported as new
-        assert!(response.is_new);
+        // Monitors with the account key already have real key images.
+        let full_data = MonitorData::new(AccountKey::random(&mut rng), 0, 1, 0, "").unwrap();
+        let full_monitor_id = mobilecoind_db.add_monitor(&full_data).unwrap();
+        request.set_monitor_id(full_monitor_id.to_vec());
+        assert!(client.set_watch_only_key_images(&request).is_err());
+    }
 
-        // Add the same monitor again
-        let repeated_response = client.add_monitor(&request).expect("failed to add monitor");
+    #[test_with_logger]
+    fn test_watch_only_fog_monitor(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([21u8; 32]);
 
-        // Compare the MonitorId we got back to the value we expected.
-        let repeated_monitor_id = MonitorId::try_from(&repeated_response.monitor_id)
-            .expect("failed to convert repeated_response to MonitorId");
+        let account_key = AccountKey::random_with_fog(&mut rng);
+        let view_account_key = ViewAccountKey::from(&account_key);
 
-        assert_eq!(expected_monitor_id, repeated_monitor_id);
+        let (_ledger_db, _mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(BLOCK_VERSION, 3, &[], &[], logger.clone(), &mut rng);
 
-        // Check that the monitor is not reported as new
-        assert!(!repeated_response.is_new);
+        // A monitor of the account key.
+        let mut request = api::AddMonitorRequest::new();
+        request.set_account_key(mc_api::external::AccountKey::from(&account_key));
+        request.set_num_subaddresses(10);
+        let monitor_id = client.add_monitor(&request).unwrap().monitor_id;
+
+        // The fog details of the default address cannot be set with the account key.
+        request.set_default_public_address((&account_key.default_subaddress()).into());
+        assert!(client.add_monitor(&request).is_err());
+
+        // A watch-only monitor of the same account, with its fog details, is the
+        // same monitor.
+        request.clear_account_key();
+        request.set_view_account_key(mc_api::external::ViewAccountKey::from(&view_account_key));
+        let response = client.add_monitor(&request).unwrap();
+        assert_eq!(response.monitor_id, monitor_id);
+        assert!(!response.is_new);
+
+        // The address must belong to the account.
+        request.set_default_public_address(
+            (&AccountKey::random_with_fog(&mut rng).default_subaddress()).into(),
+        );
+        assert!(client.add_monitor(&request).is_err());
     }
 
     #[test_with_logger]
@@ -2333,8 +4609,8 @@ mod test {
 
         // Verify the data we got matches what we expected
         assert_eq!(
-            data.account_key,
-            AccountKey::try_from(status.account_key.as_ref().unwrap()).unwrap(),
+            data.account_key().unwrap(),
+            &AccountKey::try_from(status.account_key.as_ref().unwrap()).unwrap(),
         );
         assert_eq!(status.first_subaddress, data.first_subaddress);
         assert_eq!(status.num_subaddresses, data.num_subaddresses);
@@ -2505,6 +4781,55 @@ mod test {
             HashSet::from_iter(utxos.iter()),
             HashSet::from_iter(expected_utxos.iter().filter(|utxo| utxo.token_id == 2))
         );
+
+        // Query the MOB utxos in pages.
+        request.set_token_id(*Mob::ID);
+        request.set_limit(4);
+        let mut paged_utxos = Vec::new();
+        loop {
+            let response = client
+                .get_unspent_tx_out_list(&request)
+                .expect("failed to get unspent tx out list");
+            assert!(response.output_list.len() <= 4);
+            paged_utxos.extend(response.output_list.iter().map(|proto_utxo| {
+                UnspentTxOut::try_from(proto_utxo).expect("failed converting proto utxo")
+            }));
+            if response.get_next_start_key().is_empty() {
+                break;
+            }
+            request.set_start_key(response.get_next_start_key().to_vec());
+        }
+        assert_eq!(paged_utxos.len(), num_blocks as usize - 1);
+        assert_eq!(
+            HashSet::from_iter(paged_utxos.iter()),
+            HashSet::from_iter(
+                expected_utxos
+                    .iter()
+                    .filter(|utxo| utxo.token_id == *Mob::ID)
+            )
+        );
+
+        // An invalid start key is rejected.
+        request.set_start_key(vec![1, 2, 3]);
+        assert!(client.get_unspent_tx_out_list(&request).is_err());
+
+        // Filter by value.
+        request.clear_start_key();
+        request.set_limit(0);
+        request.set_max_value(test_utils::DEFAULT_PER_RECIPIENT_AMOUNT);
+        let response = client
+            .get_unspent_tx_out_list(&request)
+            .expect("failed to get unspent tx out list");
+        assert_eq!(response.output_list.len(), num_blocks as usize - 1);
+        assert!(response.get_next_start_key().is_empty());
+
+        request.set_min_value(test_utils::DEFAULT_PER_RECIPIENT_AMOUNT + 1);
+        request.set_max_value(0);
+        let response = client
+            .get_unspent_tx_out_list(&request)
+            .expect("failed to get unspent tx out list");
+        assert!(response.output_list.is_empty());
+        assert!(response.get_next_start_key().is_empty());
     }
 
     #[test_with_logger]
@@ -2673,6 +4998,254 @@ mod test {
         assert!(client.get_public_address(&request).is_err());
     }
 
+    #[test_with_logger]
+    fn test_export_import_monitor(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([33u8; 32]);
+        let account_key = AccountKey::random(&mut rng);
+        let data = MonitorData::new(
+            account_key.clone(),
+            0,      // first_subaddress
+            3,      // num_subaddresses
+            0,      // first_block
+            "test", // name
+        )
+        .unwrap();
+
+        // 1 known recipient, 3 random recipients and no monitors.
+        let (ledger_db, mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(
+                BLOCK_VERSION,
+                3,
+                &[account_key.default_subaddress()],
+                &[],
+                logger.clone(),
+                &mut rng,
+            );
+
+        let monitor_id = mobilecoind_db.add_monitor(&data).unwrap();
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+        mobilecoind_db
+            .set_subaddress_label(&monitor_id, 0, "alice")
+            .unwrap();
+
+        let monitor_data = mobilecoind_db.get_monitor_data(&monitor_id).unwrap();
+        let utxos = mobilecoind_db
+            .get_utxos_for_subaddress(&monitor_id, 0)
+            .unwrap();
+        let history = mobilecoind_db
+            .get_transaction_history(&monitor_id, 0, u64::MAX, false)
+            .unwrap();
+        assert!(!utxos.is_empty());
+
+        // Export the monitor.
+        let password = [5u8; 32];
+        let mut request = api::ExportMonitorRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_password(password.to_vec());
+        let backup = client.export_monitor(&request).unwrap().take_backup();
+
+        // The password must be 32 bytes long.
+        request.set_password(vec![5u8; 16]);
+        assert!(client.export_monitor(&request).is_err());
+
+        // Restore the monitor from the backup.
+        let mut request = api::RemoveMonitorRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        client.remove_monitor(&request).unwrap();
+
+        // The account key is needed, and must match the view key of the backup.
+        let mut request = api::ImportMonitorRequest::new();
+        request.set_backup(backup.clone());
+        request.set_password(password.to_vec());
+        assert!(client.import_monitor(&request).is_err());
+
+        request.set_account_key((&AccountKey::random(&mut rng)).into());
+        assert!(client.import_monitor(&request).is_err());
+
+        // The wrong password fails.
+        request.set_account_key((&account_key).into());
+        request.set_password(vec![6u8; 32]);
+        assert!(client.import_monitor(&request).is_err());
+
+        request.set_password(password.to_vec());
+        let response = client.import_monitor(&request).unwrap();
+        assert_eq!(response.get_monitor_id(), monitor_id.to_vec());
+
+        // The restored monitor has the state of the exported one.
+        assert_eq!(
+            mobilecoind_db.get_monitor_data(&monitor_id).unwrap(),
+            monitor_data
+        );
+        assert_eq!(
+            mobilecoind_db
+                .get_utxos_for_subaddress(&monitor_id, 0)
+                .unwrap(),
+            utxos
+        );
+        assert_eq!(
+            mobilecoind_db
+                .get_transaction_history(&monitor_id, 0, u64::MAX, false)
+                .unwrap(),
+            history
+        );
+        assert_eq!(
+            mobilecoind_db.get_subaddress_label(&monitor_id, 0).unwrap(),
+            Some("alice".to_owned())
+        );
+
+        // Importing a monitor that already exists fails.
+        assert!(client.import_monitor(&request).is_err());
+
+        // Watch-only monitors are imported without an account key.
+        let view_account_key = ViewAccountKey::from(&AccountKey::random(&mut rng));
+        let watch_only_data =
+            MonitorData::new_watch_only(view_account_key, None, 0, 1, 0, "").unwrap();
+        let watch_only_id = mobilecoind_db.add_monitor(&watch_only_data).unwrap();
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+        let mut request = api::ExportMonitorRequest::new();
+        request.set_monitor_id(watch_only_id.to_vec());
+        request.set_password(password.to_vec());
+        let backup = client.export_monitor(&request).unwrap().take_backup();
+
+        mobilecoind_db.remove_monitor(&watch_only_id).unwrap();
+
+        let mut request = api::ImportMonitorRequest::new();
+        request.set_backup(backup);
+        request.set_password(password.to_vec());
+        request.set_account_key((&account_key).into());
+        assert!(client.import_monitor(&request).is_err());
+
+        request.clear_account_key();
+        let response = client.import_monitor(&request).unwrap();
+        assert_eq!(response.get_monitor_id(), watch_only_id.to_vec());
+        assert_eq!(
+            mobilecoind_db
+                .get_monitor_data(&watch_only_id)
+                .unwrap()
+                .next_block,
+            ledger_db.num_blocks().unwrap()
+        );
+    }
+
+    #[test_with_logger]
+    fn test_subaddress_labels(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([32u8; 32]);
+        let account_key = AccountKey::random(&mut rng);
+        let data = MonitorData::new(
+            account_key.clone(),
+            0,  // first_subaddress
+            3,  // num_subaddresses
+            0,  // first_block
+            "", // name
+        )
+        .unwrap();
+
+        // 1 known recipient, 3 random recipients and no monitors.
+        let (ledger_db, mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(
+                BLOCK_VERSION,
+                3,
+                &[account_key.default_subaddress()],
+                &[],
+                logger.clone(),
+                &mut rng,
+            );
+
+        // Insert into database.
+        let monitor_id = mobilecoind_db.add_monitor(&data).unwrap();
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+        // Subaddresses are allocated in order.
+        let mut request = api::AllocateSubaddressRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_label("alice".to_owned());
+        let response = client.allocate_subaddress(&request).unwrap();
+        assert_eq!(response.get_subaddress_index(), 0);
+        assert_eq!(
+            PublicAddress::try_from(response.get_public_address()).unwrap(),
+            account_key.subaddress(0)
+        );
+        let mut wrapper = api::printable::PrintableWrapper::new();
+        wrapper.set_public_address((&account_key.subaddress(0)).into());
+        assert_eq!(response.get_b58_code(), wrapper.b58_encode().unwrap());
+
+        request.set_label("bob".to_owned());
+        let response = client.allocate_subaddress(&request).unwrap();
+        assert_eq!(response.get_subaddress_index(), 1);
+
+        // Label the last subaddress directly.
+        let mut request = api::SetSubaddressLabelRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_subaddress_index(2);
+        request.set_label("carol".to_owned());
+        client.set_subaddress_label(&request).unwrap();
+
+        // All subaddresses are labeled.
+        let mut request = api::AllocateSubaddressRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_label("dave".to_owned());
+        assert!(client.allocate_subaddress(&request).is_err());
+
+        let mut request = api::GetSubaddressLabelsRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        let response = client.get_subaddress_labels(&request).unwrap();
+        let labels: Vec<_> = response
+            .get_labels()
+            .iter()
+            .map(|label| (label.get_subaddress_index(), label.get_label().to_owned()))
+            .collect();
+        assert_eq!(
+            labels,
+            vec![
+                (0, "alice".to_owned()),
+                (1, "bob".to_owned()),
+                (2, "carol".to_owned())
+            ]
+        );
+        assert_eq!(
+            PublicAddress::try_from(response.get_labels()[1].get_public_address()).unwrap(),
+            account_key.subaddress(1)
+        );
+
+        // Removing a label frees the subaddress.
+        let mut request = api::SetSubaddressLabelRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_subaddress_index(1);
+        client.set_subaddress_label(&request).unwrap();
+
+        let mut request = api::AllocateSubaddressRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_label("dave".to_owned());
+        let response = client.allocate_subaddress(&request).unwrap();
+        assert_eq!(response.get_subaddress_index(), 1);
+
+        // Received TxOuts are attributed to the label of their subaddress.
+        let mut request = api::GetProcessedBlockRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_block(1);
+        let response = client.get_processed_block(&request).unwrap();
+        assert_eq!(response.get_tx_outs().len(), 1);
+        assert_eq!(response.get_tx_outs()[0].get_subaddress_index(), 0);
+        assert_eq!(response.get_tx_outs()[0].get_subaddress_label(), "alice");
+
+        // Empty labels, subaddresses that are not monitored, and unknown monitors are
+        // rejected.
+        let mut request = api::AllocateSubaddressRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        assert!(client.allocate_subaddress(&request).is_err());
+
+        let mut request = api::SetSubaddressLabelRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_subaddress_index(3);
+        request.set_label("erin".to_owned());
+        assert!(client.set_subaddress_label(&request).is_err());
+
+        let mut request = api::GetSubaddressLabelsRequest::new();
+        request.set_monitor_id(vec![3; 32]);
+        assert!(client.get_subaddress_labels(&request).is_err());
+    }
+
     #[test_with_logger]
     fn test_get_ledger_info_impl(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([23u8; 32]);
@@ -3066,40 +5639,201 @@ mod test {
         {
             let hash = tx_out.hash();
 
-            let mut receipt = api::ReceiverTxReceipt::new();
-            receipt.set_tx_public_key(api::external::CompressedRistretto::from(&tx_out.public_key));
-            receipt.set_tx_out_hash(hash.to_vec());
-            receipt.set_tombstone(10);
-            receipt.set_confirmation_number(confirmation.to_vec());
+            let mut receipt = api::ReceiverTxReceipt::new();
+            receipt.set_tx_public_key(api::external::CompressedRistretto::from(&tx_out.public_key));
+            receipt.set_tx_out_hash(hash.to_vec());
+            receipt.set_tombstone(10);
+            receipt.set_confirmation_number(confirmation.to_vec());
+
+            let mut request = api::GetTxStatusAsReceiverRequest::new();
+            request.set_receipt(receipt);
+            request.set_monitor_id(monitor_id.to_vec());
+
+            let response = client.get_tx_status_as_receiver(&request).unwrap();
+            assert_eq!(response.get_status(), api::TxStatus::Verified);
+        }
+
+        // A request with an a bad confirmation number and a monitor ID should return
+        // InvalidConfirmationNumber
+        {
+            let hash = tx_out.hash();
+
+            let mut receipt = api::ReceiverTxReceipt::new();
+            receipt.set_tx_public_key(api::external::CompressedRistretto::from(&tx_out.public_key));
+            receipt.set_tx_out_hash(hash.to_vec());
+            receipt.set_tombstone(10);
+            receipt.set_confirmation_number(vec![0u8; 32]);
+
+            let mut request = api::GetTxStatusAsReceiverRequest::new();
+            request.set_receipt(receipt);
+            request.set_monitor_id(monitor_id.to_vec());
+
+            let response = client.get_tx_status_as_receiver(&request).unwrap();
+            assert_eq!(
+                response.get_status(),
+                api::TxStatus::InvalidConfirmationNumber
+            );
+        }
+    }
+
+    #[test_with_logger]
+    fn test_receiver_tx_receipts(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([40u8; 32]);
+
+        let sender = AccountKey::random(&mut rng);
+        let receiver = AccountKey::random(&mut rng);
+
+        let (mut ledger_db, mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(
+                BLOCK_VERSION,
+                3,
+                &[sender.default_subaddress()],
+                &[],
+                logger.clone(),
+                &mut rng,
+            );
+
+        let sender_monitor_id = mobilecoind_db
+            .add_monitor(&MonitorData::new(sender.clone(), 0, 20, 0, "").unwrap())
+            .unwrap();
+        let receiver_monitor_id = mobilecoind_db
+            .add_monitor(&MonitorData::new(receiver.clone(), 0, 20, 0, "").unwrap())
+            .unwrap();
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+        // Pay the receiver.
+        let utxos = mobilecoind_db
+            .get_utxos_for_subaddress(&sender_monitor_id, 0)
+            .unwrap();
+        let outlays = vec![Outlay {
+            value: 123,
+            receiver: receiver.subaddress(2),
+        }];
+
+        let mut request = api::GenerateTxRequest::new();
+        request.set_sender_monitor_id(sender_monitor_id.to_vec());
+        request.set_change_subaddress(0);
+        request.set_input_list(RepeatedField::from_vec(
+            utxos.iter().map(api::UnspentTxOut::from).collect(),
+        ));
+        request.set_outlay_list(RepeatedField::from_vec(
+            outlays.iter().map(api::Outlay::from).collect(),
+        ));
+        let response = client.generate_tx(&request).unwrap();
+        let tx_proposal = TxProposal::try_from(response.get_tx_proposal()).unwrap();
+
+        // The receipts are the ones SubmitTx returns.
+        let mut request = api::CreateReceiverTxReceiptsRequest::new();
+        request.set_tx_proposal(api::TxProposal::from(&tx_proposal));
+        let response = client.create_receiver_tx_receipts(&request).unwrap();
+        assert_eq!(response.get_receiver_tx_receipt_list().len(), 1);
+        let receipt = response.get_receiver_tx_receipt_list()[0].clone();
+        assert_eq!(receipt.value, 123);
+        assert_eq!(
+            PublicAddress::try_from(receipt.get_recipient()).unwrap(),
+            receiver.subaddress(2)
+        );
+
+        let mut request = api::SubmitTxRequest::new();
+        request.set_tx_proposal(api::TxProposal::from(&tx_proposal));
+        let submit_response = client.submit_tx(&request).unwrap();
+        assert_eq!(
+            submit_response.get_receiver_tx_receipt_list(),
+            response.get_receiver_tx_receipt_list()
+        );
+
+        let verify = |receipt: &api::ReceiverTxReceipt, monitor_id: Option<&MonitorId>| {
+            let mut request = api::VerifyReceiverTxReceiptRequest::new();
+            request.set_receipt(receipt.clone());
+            if let Some(monitor_id) = monitor_id {
+                request.set_monitor_id(monitor_id.to_vec());
+            }
+            client.verify_receiver_tx_receipt(&request).unwrap()
+        };
+
+        // The TxOut is not in the ledger yet.
+        assert_eq!(
+            verify(&receipt, None).get_status(),
+            api::ReceiptStatus::ReceiptPending
+        );
 
-            let mut request = api::GetTxStatusAsReceiverRequest::new();
-            request.set_receipt(receipt);
-            request.set_monitor_id(monitor_id.to_vec());
+        // Once it is, the receipt is verified against the receiver's monitor.
+        let tx_out_index = tx_proposal.outlay_index_to_tx_out_index[&0];
+        let tx_out = tx_proposal.tx.prefix.outputs[tx_out_index].clone();
+        add_txos_to_ledger(&mut ledger_db, BLOCK_VERSION, &[tx_out], &mut rng).unwrap();
+
+        let response = verify(&receipt, None);
+        assert_eq!(response.get_status(), api::ReceiptStatus::ReceiptVerified);
+        assert_eq!(response.get_monitor_id(), receiver_monitor_id.to_vec());
+        assert_eq!(response.subaddress_index, 2);
+        assert_eq!(response.block_index, ledger_db.num_blocks().unwrap() - 1);
+        assert_eq!(response.value, 123);
+        assert_eq!(response.token_id, *Mob::ID);
+
+        let response = verify(&receipt, Some(&receiver_monitor_id));
+        assert_eq!(response.get_status(), api::ReceiptStatus::ReceiptVerified);
+
+        // The sender does not own the TxOut.
+        let response = verify(&receipt, Some(&sender_monitor_id));
+        assert_eq!(response.get_status(), api::ReceiptStatus::ReceiptNotOwned);
+
+        // A wrong confirmation number or value is detected.
+        let mut bad_receipt = receipt.clone();
+        bad_receipt.set_confirmation_number(vec![0u8; 32]);
+        assert_eq!(
+            verify(&bad_receipt, None).get_status(),
+            api::ReceiptStatus::ReceiptInvalidConfirmationNumber
+        );
 
-            let response = client.get_tx_status_as_receiver(&request).unwrap();
-            assert_eq!(response.get_status(), api::TxStatus::Verified);
-        }
+        let mut bad_receipt = receipt.clone();
+        bad_receipt.set_value(124);
+        assert_eq!(
+            verify(&bad_receipt, None).get_status(),
+            api::ReceiptStatus::ReceiptValueMismatch
+        );
 
-        // A request with an a bad confirmation number and a monitor ID should return
-        // InvalidConfirmationNumber
-        {
-            let hash = tx_out.hash();
+        let mut bad_receipt = receipt.clone();
+        bad_receipt.set_token_id(1);
+        assert_eq!(
+            verify(&bad_receipt, None).get_status(),
+            api::ReceiptStatus::ReceiptValueMismatch
+        );
 
-            let mut receipt = api::ReceiverTxReceipt::new();
-            receipt.set_tx_public_key(api::external::CompressedRistretto::from(&tx_out.public_key));
-            receipt.set_tx_out_hash(hash.to_vec());
-            receipt.set_tombstone(10);
-            receipt.set_confirmation_number(vec![0u8; 32]);
+        // The hash and the public key must refer to the same TxOut.
+        let mut bad_receipt = receipt.clone();
+        bad_receipt.set_tx_out_hash(ledger_db.get_tx_out_by_index(0).unwrap().hash().to_vec());
+        assert_eq!(
+            verify(&bad_receipt, None).get_status(),
+            api::ReceiptStatus::ReceiptTxOutMismatch
+        );
 
-            let mut request = api::GetTxStatusAsReceiverRequest::new();
-            request.set_receipt(receipt);
-            request.set_monitor_id(monitor_id.to_vec());
+        let mut bad_receipt = receipt.clone();
+        bad_receipt.set_tx_out_hash(vec![0u8; 32]);
+        assert_eq!(
+            verify(&bad_receipt, None).get_status(),
+            api::ReceiptStatus::ReceiptTxOutMismatch
+        );
 
-            let response = client.get_tx_status_as_receiver(&request).unwrap();
-            assert_eq!(
-                response.get_status(),
-                api::TxStatus::InvalidConfirmationNumber
-            );
+        // A TxOut that never landed before the tombstone block.
+        let mut bad_receipt = receipt.clone();
+        bad_receipt.set_tx_out_hash(vec![0u8; 32]);
+        bad_receipt.set_tx_public_key((&RistrettoPublic::from_random(&mut rng)).into());
+        bad_receipt.set_tombstone(ledger_db.num_blocks().unwrap());
+        assert_eq!(
+            verify(&bad_receipt, None).get_status(),
+            api::ReceiptStatus::ReceiptTombstoneBlockExceeded
+        );
+
+        // Malformed receipts are rejected.
+        let mut bad_receipt = receipt;
+        bad_receipt.clear_confirmation_number();
+        let mut request = api::VerifyReceiverTxReceiptRequest::new();
+        request.set_receipt(bad_receipt);
+        match client.verify_receiver_tx_receipt(&request) {
+            Err(GrpcError::RpcFailure(rpc_status)) => {
+                assert_eq!(rpc_status.code(), RpcStatusCode::INVALID_ARGUMENT)
+            }
+            result => panic!("unexpected result {:?}", result),
         }
     }
 
@@ -3344,6 +6078,297 @@ mod test {
         assert!(client.get_processed_block(&request).is_err());
     }
 
+    #[test_with_logger]
+    fn test_get_transaction_history(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([28u8; 32]);
+
+        let account_key = AccountKey::random(&mut rng);
+        let monitor_data = MonitorData::new(
+            account_key.clone(),
+            0,  // first_subaddress
+            20, // num_subaddresses
+            0,  // first_block
+            "", // name
+        )
+        .unwrap();
+
+        // 1 known recipient, 3 random recipients and no monitors.
+        let (mut ledger_db, mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(
+                BLOCK_VERSION,
+                3,
+                &[account_key.default_subaddress()],
+                &[],
+                logger.clone(),
+                &mut rng,
+            );
+
+        // Insert into database.
+        let monitor_id = mobilecoind_db.add_monitor(&monitor_data).unwrap();
+
+        // Allow the new monitor to process the ledger.
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+        // Add a block that spends one utxo and receives another one.
+        let num_blocks = ledger_db.num_blocks().unwrap();
+        let utxos = mobilecoind_db
+            .get_utxos_for_subaddress(&monitor_id, 0)
+            .unwrap();
+        add_block_to_ledger(
+            &mut ledger_db,
+            BLOCK_VERSION,
+            &[account_key.default_subaddress()],
+            Amount::new(DEFAULT_PER_RECIPIENT_AMOUNT, Mob::ID),
+            &[utxos[0].key_image],
+            &mut rng,
+        )
+        .unwrap();
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+        // The full history has one received TxOut per block, and the spent one.
+        let mut request = api::GetTransactionHistoryRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        let response = client.get_transaction_history(&request).unwrap();
+        assert_eq!(response.get_total_count(), num_blocks + 2);
+        let entries = response.get_entries();
+        assert_eq!(entries.len() as u64, num_blocks + 2);
+        for (block_index, entry) in entries.iter().take(num_blocks as usize).enumerate() {
+            assert_eq!(entry.get_block_index(), block_index as u64);
+            assert_eq!(
+                entry.get_tx_out().get_direction(),
+                api::ProcessedTxOutDirection::Received
+            );
+            assert_eq!(
+                entry.get_tx_out().get_value(),
+                test_utils::DEFAULT_PER_RECIPIENT_AMOUNT
+            );
+        }
+
+        // Query a page.
+        request.set_offset(2);
+        request.set_limit(3);
+        let response = client.get_transaction_history(&request).unwrap();
+        assert_eq!(response.get_total_count(), num_blocks + 2);
+        let block_indexes: Vec<u64> = response
+            .get_entries()
+            .iter()
+            .map(|entry| entry.get_block_index())
+            .collect();
+        assert_eq!(block_indexes, vec![2, 3, 4]);
+
+        // The most recent entries are for the last block.
+        request.set_offset(0);
+        request.set_limit(2);
+        request.set_newest_first(true);
+        let response = client.get_transaction_history(&request).unwrap();
+        let entries = response.get_entries();
+        assert_eq!(entries.len(), 2);
+        assert!(entries
+            .iter()
+            .all(|entry| entry.get_block_index() == num_blocks));
+        let spent = entries
+            .iter()
+            .find(|entry| entry.get_tx_out().get_direction() == api::ProcessedTxOutDirection::Spent)
+            .expect("no spent entry");
+        assert_eq!(
+            spent.get_tx_out().get_key_image(),
+            &(&utxos[0].key_image).into()
+        );
+
+        // Query with an unknown monitor id.
+        let mut request = api::GetTransactionHistoryRequest::new();
+        request.set_monitor_id(vec![1; 32]);
+        assert!(client.get_transaction_history(&request).is_err());
+    }
+
+    #[test_with_logger]
+    fn test_subscribe_monitor_events(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([27u8; 32]);
+
+        let account_key = AccountKey::random(&mut rng);
+        let monitor_data = MonitorData::new(
+            account_key.clone(),
+            0,  // first_subaddress
+            20, // num_subaddresses
+            0,  // first_block
+            "", // name
+        )
+        .unwrap();
+
+        // 1 known recipient, 3 random recipients and no monitors.
+        let (mut ledger_db, mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(
+                BLOCK_VERSION,
+                3,
+                &[account_key.default_subaddress()],
+                &[],
+                logger.clone(),
+                &mut rng,
+            );
+
+        // Insert into database.
+        let monitor_id = mobilecoind_db.add_monitor(&monitor_data).unwrap();
+
+        // Allow the new monitor to process the ledger.
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+        // Subscribe starting at the second block. The events of the blocks that were
+        // already processed are replayed.
+        let mut request = api::SubscribeMonitorEventsRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_start_block(1);
+        let mut events = client.subscribe_monitor_events(&request).unwrap();
+        let mut next_event = || {
+            block_on(events.next())
+                .expect("stream ended")
+                .expect("stream failed")
+        };
+
+        let num_blocks = ledger_db.num_blocks().unwrap();
+        for block_index in 1..num_blocks {
+            let event = next_event();
+            assert_eq!(event.get_event_type(), api::MonitorEventType::TxOutReceived);
+            assert_eq!(event.get_block_index(), block_index);
+            assert_eq!(
+                event.get_tx_out().get_value(),
+                test_utils::DEFAULT_PER_RECIPIENT_AMOUNT
+            );
+            assert_eq!(
+                event.get_tx_out().get_monitor_id().to_vec(),
+                monitor_id.to_vec()
+            );
+        }
+        let event = next_event();
+        assert_eq!(event.get_event_type(), api::MonitorEventType::MonitorSynced);
+        assert_eq!(event.get_block_index(), num_blocks - 1);
+
+        // Add a block that spends one utxo and receives another one.
+        let utxos = mobilecoind_db
+            .get_utxos_for_subaddress(&monitor_id, 0)
+            .unwrap();
+        add_block_to_ledger(
+            &mut ledger_db,
+            BLOCK_VERSION,
+            &[account_key.default_subaddress()],
+            Amount::new(DEFAULT_PER_RECIPIENT_AMOUNT, Mob::ID),
+            &[utxos[0].key_image],
+            &mut rng,
+        )
+        .unwrap();
+
+        let mut event_types = vec![next_event().get_event_type(), next_event().get_event_type()];
+        event_types.sort_by_key(|event_type| *event_type as i32);
+        assert_eq!(
+            event_types,
+            vec![
+                api::MonitorEventType::TxOutReceived,
+                api::MonitorEventType::TxOutSpent
+            ]
+        );
+        let event = next_event();
+        assert_eq!(event.get_event_type(), api::MonitorEventType::MonitorSynced);
+        assert_eq!(event.get_block_index(), num_blocks);
+
+        // Removing the monitor ends the stream.
+        let mut request = api::RemoveMonitorRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        client.remove_monitor(&request).unwrap();
+        assert!(block_on(events.next()).is_none());
+
+        // Subscribing to an unknown monitor fails.
+        let mut request = api::SubscribeMonitorEventsRequest::new();
+        request.set_monitor_id(vec![1; 32]);
+        let mut events = client.subscribe_monitor_events(&request).unwrap();
+        assert!(block_on(events.next()).unwrap().is_err());
+    }
+
+    #[test_with_logger]
+    fn test_monitor_sync_progress(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([37u8; 32]);
+
+        let account_key = AccountKey::random(&mut rng);
+        let monitor_data = MonitorData::new(
+            account_key.clone(),
+            0,  // first_subaddress
+            1,  // num_subaddresses
+            0,  // first_block
+            "", // name
+        )
+        .unwrap();
+
+        // 1 known recipient, 3 random recipients and no monitors.
+        let (mut ledger_db, mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(
+                BLOCK_VERSION,
+                3,
+                &[account_key.default_subaddress()],
+                &[],
+                logger.clone(),
+                &mut rng,
+            );
+
+        // Insert into database.
+        let monitor_id = mobilecoind_db.add_monitor(&monitor_data).unwrap();
+
+        // Allow the new monitor to process the ledger.
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+        let num_blocks = ledger_db.num_blocks().unwrap();
+        let mut request = api::GetMonitorSyncProgressRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        let response = client.get_monitor_sync_progress(&request).unwrap();
+        let progress = response.get_progress();
+        assert_eq!(progress.get_first_block(), 0);
+        assert_eq!(progress.get_next_block(), num_blocks);
+        assert_eq!(progress.get_network_highest_block_index(), num_blocks - 1);
+        assert_eq!(progress.get_blocks_remaining(), 0);
+        assert_eq!(progress.get_percent_complete(), 100.0);
+        assert!(progress.get_is_synced());
+
+        // The stream starts with the current progress.
+        let mut updates = client.subscribe_monitor_sync_progress(&request).unwrap();
+        let mut next_update = || {
+            block_on(updates.next())
+                .expect("stream ended")
+                .expect("stream failed")
+        };
+        assert_eq!(&next_update(), progress);
+
+        // A new block is reported, then processed by the monitor.
+        add_block_to_ledger(
+            &mut ledger_db,
+            BLOCK_VERSION,
+            &[account_key.default_subaddress()],
+            Amount::new(DEFAULT_PER_RECIPIENT_AMOUNT, Mob::ID),
+            &[],
+            &mut rng,
+        )
+        .unwrap();
+        loop {
+            let progress = next_update();
+            assert_eq!(progress.get_network_highest_block_index(), num_blocks);
+            if progress.get_next_block() == num_blocks + 1 {
+                assert_eq!(progress.get_blocks_remaining(), 0);
+                assert!(progress.get_is_synced());
+                break;
+            }
+            assert_eq!(progress.get_blocks_remaining(), 1);
+            assert!(progress.get_percent_complete() < 100.0);
+            assert!(!progress.get_is_synced());
+        }
+
+        // Removing the monitor ends the stream.
+        let mut remove_request = api::RemoveMonitorRequest::new();
+        remove_request.set_monitor_id(monitor_id.to_vec());
+        client.remove_monitor(&remove_request).unwrap();
+        assert!(block_on(updates.next()).is_none());
+
+        // Unknown monitors fail.
+        assert!(client.get_monitor_sync_progress(&request).is_err());
+        let mut updates = client.subscribe_monitor_sync_progress(&request).unwrap();
+        assert!(block_on(updates.next()).unwrap().is_err());
+    }
+
     #[test_with_logger]
     /// Get mixins should return the correct number of distinct mixins.
     fn test_get_mixins(logger: Logger) {
@@ -3578,10 +6603,91 @@ mod test {
                 let proofs = ledger_db.get_tx_out_proof_of_memberships(&[index]).unwrap();
                 assert_eq!(proofs.len(), 1);
 
-                api::external::TxOutMembershipProof::from(&proofs[0])
-            };
+                api::external::TxOutMembershipProof::from(&proofs[0])
+            };
+
+            assert_eq!(output_with_proof.get_proof(), &expected_proof);
+        }
+    }
+
+    #[test_with_logger]
+    fn test_tombstone_block(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([41u8; 32]);
+
+        let sender = AccountKey::random(&mut rng);
+        let (ledger_db, mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(
+                BLOCK_VERSION,
+                3,
+                &[sender.default_subaddress()],
+                &[],
+                logger.clone(),
+                &mut rng,
+            );
+
+        let monitor_id = mobilecoind_db
+            .add_monitor(&MonitorData::new(sender.clone(), 0, 20, 0, "").unwrap())
+            .unwrap();
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+        // The suggestion is the default offset past the tip of the ledger.
+        let num_blocks = ledger_db.num_blocks().unwrap();
+        let response = client
+            .get_suggested_tombstone_block(&api::Empty::new())
+            .unwrap();
+        assert_eq!(response.next_block_index, num_blocks);
+        assert_eq!(
+            response.tombstone_block,
+            num_blocks + DEFAULT_NEW_TX_BLOCK_ATTEMPTS
+        );
+        assert_eq!(response.min_tombstone_block, num_blocks + 1);
+        assert_eq!(
+            response.max_tombstone_block,
+            num_blocks + MAX_TOMBSTONE_BLOCKS
+        );
+
+        let utxos = mobilecoind_db
+            .get_utxos_for_subaddress(&monitor_id, 0)
+            .unwrap();
+        let outlays = vec![Outlay {
+            value: 123,
+            receiver: AccountKey::random(&mut rng).default_subaddress(),
+        }];
+        let generate_tx = |tombstone| {
+            let mut request = api::GenerateTxRequest::new();
+            request.set_sender_monitor_id(monitor_id.to_vec());
+            request.set_change_subaddress(0);
+            request.set_input_list(RepeatedField::from_vec(
+                utxos.iter().map(api::UnspentTxOut::from).collect(),
+            ));
+            request.set_outlay_list(RepeatedField::from_vec(
+                outlays.iter().map(api::Outlay::from).collect(),
+            ));
+            request.set_tombstone(tombstone);
+            client.generate_tx(&request)
+        };
+
+        // Tombstone blocks the network accepts are used as is.
+        for tombstone in [response.min_tombstone_block, response.max_tombstone_block] {
+            let response = generate_tx(tombstone).unwrap();
+            assert_eq!(
+                response
+                    .get_tx_proposal()
+                    .get_tx()
+                    .get_prefix()
+                    .tombstone_block,
+                tombstone
+            );
+        }
 
-            assert_eq!(output_with_proof.get_proof(), &expected_proof);
+        // Others are rejected.
+        for tombstone in [response.next_block_index, response.max_tombstone_block + 1] {
+            match generate_tx(tombstone) {
+                Err(GrpcError::RpcFailure(rpc_status)) => {
+                    assert_eq!(rpc_status.code(), RpcStatusCode::INVALID_ARGUMENT)
+                }
+                result => panic!("unexpected result {:?}", result),
+            }
         }
     }
 
@@ -3894,6 +7000,102 @@ mod test {
         }
     }
 
+    #[test_with_logger]
+    fn test_get_fee_estimate(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([26u8; 32]);
+
+        let sender = AccountKey::random(&mut rng);
+        let data = MonitorData::new(
+            sender.clone(),
+            0,  // first_subaddress
+            20, // num_subaddresses
+            0,  // first_block
+            "", // name
+        )
+        .unwrap();
+
+        // 1 known recipient, 3 random recipients and no monitors.
+        let (ledger_db, mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(
+                BLOCK_VERSION,
+                3,
+                &[sender.default_subaddress()],
+                &[],
+                logger.clone(),
+                &mut rng,
+            );
+
+        // Insert into database.
+        let monitor_id = mobilecoind_db.add_monitor(&data).unwrap();
+
+        // Allow the new monitor to process the ledger.
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+        // Get list of unspent tx outs
+        let utxos = mobilecoind_db
+            .get_utxos_for_subaddress(&monitor_id, 0)
+            .unwrap();
+        assert!(!utxos.is_empty());
+
+        // Sending twice the value of a single utxo requires three of them, because of
+        // the fee.
+        let receiver = AccountKey::random(&mut rng);
+        let mut request = api::GetFeeEstimateRequest::new();
+        request.set_sender_monitor_id(monitor_id.to_vec());
+        request.set_input_list(RepeatedField::from_vec(
+            utxos.iter().map(api::UnspentTxOut::from).collect(),
+        ));
+        request.set_outlay_list(RepeatedField::from_vec(vec![api::Outlay::from(&Outlay {
+            receiver: receiver.default_subaddress(),
+            value: 2 * test_utils::DEFAULT_PER_RECIPIENT_AMOUNT,
+        })]));
+
+        let response = client.get_fee_estimate(&request).unwrap();
+        assert_eq!(response.get_fee(), Mob::MINIMUM_FEE);
+        assert_eq!(response.get_num_inputs(), 3);
+        assert_eq!(
+            response.get_total_input_value(),
+            3 * test_utils::DEFAULT_PER_RECIPIENT_AMOUNT
+        );
+
+        // A single input covers a small outlay.
+        {
+            let mut request = request.clone();
+            request.set_outlay_list(RepeatedField::from_vec(vec![api::Outlay::from(&Outlay {
+                receiver: receiver.default_subaddress(),
+                value: 123,
+            })]));
+            let response = client.get_fee_estimate(&request).unwrap();
+            assert_eq!(response.get_fee(), Mob::MINIMUM_FEE);
+            assert_eq!(response.get_num_inputs(), 1);
+        }
+
+        {
+            // No outlays
+            let mut request = request.clone();
+            request.clear_outlay_list();
+            assert!(client.get_fee_estimate(&request).is_err());
+        }
+
+        {
+            // Attempt to spend more than we have
+            let num_blocks = ledger_db.num_blocks().unwrap();
+            let mut request = request.clone();
+            request.set_outlay_list(RepeatedField::from_vec(vec![api::Outlay::from(&Outlay {
+                receiver: receiver.default_subaddress(),
+                value: test_utils::DEFAULT_PER_RECIPIENT_AMOUNT * num_blocks,
+            })]));
+            assert!(client.get_fee_estimate(&request).is_err());
+        }
+
+        {
+            // Inputs of another token id
+            let mut request = request.clone();
+            request.set_token_id(2);
+            assert!(client.get_fee_estimate(&request).is_err());
+        }
+    }
+
     #[test_with_logger]
     fn test_generate_burn_redemption_tx(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([23u8; 32]);
@@ -4052,6 +7254,123 @@ mod test {
         }
     }
 
+    #[test_with_logger]
+    fn test_burn_funds(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([35u8; 32]);
+        let token_id2 = TokenId::from(2);
+
+        let sender = AccountKey::random(&mut rng);
+        let data = MonitorData::new(
+            sender.clone(),
+            0,  // first_subaddress
+            20, // num_subaddresses
+            0,  // first_block
+            "", // name
+        )
+        .unwrap();
+
+        // 1 known recipient, 3 random recipients and no monitors.
+        let (mut ledger_db, mobilecoind_db, client, _server, server_conn_manager) =
+            get_testing_environment(
+                BLOCK_VERSION,
+                3,
+                &[sender.default_subaddress()],
+                &[],
+                logger.clone(),
+                &mut rng,
+            );
+
+        // Add a block with a non-MOB token ID.
+        add_block_to_ledger(
+            &mut ledger_db,
+            BlockVersion::MAX,
+            &[
+                AccountKey::random(&mut rng).default_subaddress(),
+                sender.default_subaddress(),
+            ],
+            Amount::new(1_000_000_000_000, token_id2),
+            &[KeyImage::from(101)],
+            &mut rng,
+        )
+        .unwrap();
+
+        // Insert into database.
+        let monitor_id = mobilecoind_db.add_monitor(&data).unwrap();
+
+        // Allow the new monitor to process the ledger.
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+        let mut request = api::BurnFundsRequest::new();
+        request.set_sender_monitor_id(monitor_id.to_vec());
+        request.set_sender_subaddress(0);
+        request.set_burn_amount(100_000);
+        request.set_fee(200_000);
+        request.set_token_id(*token_id2);
+        request.set_redemption_memo(vec![7u8; BurnRedemptionMemo::MEMO_DATA_LEN]);
+
+        // Burning nothing is rejected.
+        {
+            let mut request = request.clone();
+            request.set_burn_amount(0);
+            assert!(client.burn_funds(&request).is_err());
+        }
+
+        // Burning more than we have in the token id is rejected.
+        {
+            let mut request = request.clone();
+            request.set_burn_amount(1_000_000_000_000);
+            assert!(client.burn_funds(&request).is_err());
+        }
+
+        let response = client.burn_funds(&request).unwrap();
+
+        // The transaction was submitted to exactly one of our mock peers.
+        let submitted_txs: Vec<Tx> = server_conn_manager
+            .conns()
+            .iter()
+            .flat_map(|mock_peer| mock_peer.read().proposed_txs.clone())
+            .collect();
+        assert_eq!(submitted_txs.len(), 1);
+        let submitted_tx = submitted_txs[0].clone();
+        assert_eq!(
+            submitted_tx,
+            Tx::try_from(response.get_tx_proposal().get_tx()).unwrap()
+        );
+        assert_eq!(
+            response.get_sender_tx_receipt().tombstone,
+            submitted_tx.prefix.tombstone_block
+        );
+
+        // The burn output carries the redemption memo.
+        let (burn_tx_out, burn_amount) = submitted_tx
+            .prefix
+            .outputs
+            .iter()
+            .find_map(|tx_out| {
+                tx_out
+                    .view_key_match(&burn_address_view_private())
+                    .map(|(amount, _commitment)| (tx_out.clone(), amount))
+                    .ok()
+            })
+            .expect("Didn't find burn output");
+        assert_eq!(burn_amount, Amount::new(100_000, token_id2));
+
+        let ss = get_tx_out_shared_secret(
+            &burn_address_view_private(),
+            &RistrettoPublic::try_from(&burn_tx_out.public_key).unwrap(),
+        );
+        let memo = burn_tx_out.e_memo.unwrap().decrypt(&ss);
+        assert_matches!(MemoType::try_from(&memo).expect("Couldn't decrypt memo"), MemoType::BurnRedemption(memo) if memo.memo_data() == &[7u8; 64]);
+
+        // The receipt is for the burn address.
+        assert_eq!(response.get_receiver_tx_receipt_list().len(), 1);
+        assert_eq!(
+            PublicAddress::try_from(response.get_receiver_tx_receipt_list()[0].get_recipient())
+                .unwrap(),
+            burn_address()
+        );
+    }
+
     #[test_with_logger]
     fn test_get_block_index_by_tx_pub_key(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([23u8; 32]);
@@ -4074,6 +7393,56 @@ mod test {
         }
     }
 
+    #[test_with_logger]
+    fn test_check_key_images(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([38u8; 32]);
+
+        // no known recipient, 3 random recipients and no monitors.
+        let (mut ledger_db, _mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(BLOCK_VERSION, 3, &[], &[], logger.clone(), &mut rng);
+
+        // Spend two key images in a new block.
+        let spent_block_index = ledger_db.num_blocks().unwrap();
+        add_block_to_ledger(
+            &mut ledger_db,
+            BLOCK_VERSION,
+            &[AccountKey::random(&mut rng).default_subaddress()],
+            Amount::new(DEFAULT_PER_RECIPIENT_AMOUNT, Mob::ID),
+            &[KeyImage::from(1), KeyImage::from(2)],
+            &mut rng,
+        )
+        .unwrap();
+
+        let key_images = vec![KeyImage::from(2), KeyImage::from(3), KeyImage::from(1)];
+        let mut request = api::CheckKeyImagesRequest::new();
+        request.set_key_images(RepeatedField::from_vec(
+            key_images
+                .iter()
+                .map(|key_image| key_image.into())
+                .collect(),
+        ));
+        let response = client.check_key_images(&request).unwrap();
+        assert_eq!(response.get_num_blocks(), spent_block_index + 1);
+
+        let statuses = response.get_statuses();
+        assert_eq!(statuses.len(), 3);
+        for (status, key_image) in statuses.iter().zip(key_images.iter()) {
+            assert_eq!(
+                KeyImage::try_from(status.get_key_image()).unwrap(),
+                *key_image
+            );
+        }
+        assert!(statuses[0].get_spent());
+        assert_eq!(statuses[0].get_spent_at_block_index(), spent_block_index);
+        assert!(!statuses[1].get_spent());
+        assert!(statuses[2].get_spent());
+        assert_eq!(statuses[2].get_spent_at_block_index(), spent_block_index);
+
+        // An empty list is rejected.
+        let request = api::CheckKeyImagesRequest::new();
+        assert!(client.check_key_images(&request).is_err());
+    }
+
     #[test_with_logger]
     fn test_generate_transfer_code_tx(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([23u8; 32]);
@@ -4142,37 +7511,180 @@ mod test {
             let key = mnemonic.derive_slip10_key(0);
             let account_key = AccountKey::from(key);
 
-            // Add a monitor based on the entropy we received.
-            let monitor_data = MonitorData::new(
-                account_key,
-                DEFAULT_SUBADDRESS_INDEX, // first_subaddress
-                1,                        // num_subaddresses
-                0,                        // first_block
-                "",                       // name
-            )
-            .unwrap();
+            // Add a monitor based on the entropy we received.
+            let monitor_data = MonitorData::new(
+                account_key,
+                DEFAULT_SUBADDRESS_INDEX, // first_subaddress
+                1,                        // num_subaddresses
+                0,                        // first_block
+                "",                       // name
+            )
+            .unwrap();
+
+            let monitor_id = mobilecoind_db.add_monitor(&monitor_data).unwrap();
+
+            // Wait for sync to complete.
+            wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+            // Get utxos for the new account and verify we only have one utxo.
+            let utxos = mobilecoind_db
+                .get_utxos_for_subaddress(&monitor_id, DEFAULT_SUBADDRESS_INDEX)
+                .unwrap();
+            assert_eq!(utxos.len(), 1);
+
+            let utxo = &utxos[0];
+
+            assert_eq!(utxo.value, 1337);
+            assert_eq!(
+                utxo.tx_out.public_key,
+                RistrettoPublic::try_from(response.get_tx_public_key())
+                    .unwrap()
+                    .into()
+            );
+        }
+    }
+
+    #[test_with_logger]
+    fn test_transfer_code_non_mob_token(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([31u8; 32]);
+        let token2 = TokenId::from(2);
+
+        let sender = AccountKey::random(&mut rng);
+        let data = MonitorData::new(
+            sender.clone(),
+            0,  // first_subaddress
+            20, // num_subaddresses
+            0,  // first_block
+            "", // name
+        )
+        .unwrap();
+
+        // 1 known recipient, 3 random recipients and no monitors.
+        let (mut ledger_db, mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(
+                BLOCK_VERSION,
+                3,
+                &[sender.default_subaddress()],
+                &[],
+                logger.clone(),
+                &mut rng,
+            );
+
+        // Add a block with a non-MOB token ID.
+        add_block_to_ledger(
+            &mut ledger_db,
+            BlockVersion::MAX,
+            &[sender.default_subaddress()],
+            Amount::new(1_000_000, token2),
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        )
+        .unwrap();
+
+        // Insert into database.
+        let monitor_id = mobilecoind_db.add_monitor(&data).unwrap();
+
+        // Allow the new monitor to process the ledger.
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+        let utxos: Vec<api::UnspentTxOut> = mobilecoind_db
+            .get_utxos_for_subaddress(&monitor_id, 0)
+            .unwrap()
+            .iter()
+            .filter(|utxo| utxo.token_id == *token2)
+            .map(Into::into)
+            .collect();
+        assert_eq!(utxos.len(), 1);
 
-            let monitor_id = mobilecoind_db.add_monitor(&monitor_data).unwrap();
+        // Generate a transfer code for some token2.
+        let mut request = api::GenerateTransferCodeTxRequest::new();
+        request.set_sender_monitor_id(monitor_id.to_vec());
+        request.set_change_subaddress(0);
+        request.set_input_list(RepeatedField::from_vec(utxos));
+        request.set_value(1337);
+        request.set_fee(200_000);
+        request.set_token_id(*token2);
+        request.set_memo("gift".to_owned());
 
-            // Wait for sync to complete.
-            wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+        let response = client.generate_transfer_code_tx(&request).unwrap();
+        assert_eq!(response.get_value(), 1337);
+        assert_eq!(response.get_token_id(), *token2);
 
-            // Get utxos for the new account and verify we only have one utxo.
-            let utxos = mobilecoind_db
-                .get_utxos_for_subaddress(&monitor_id, DEFAULT_SUBADDRESS_INDEX)
+        // The amount is encoded in the code.
+        let wrapper =
+            api::printable::PrintableWrapper::b58_decode(response.get_b58_code().to_owned())
                 .unwrap();
-            assert_eq!(utxos.len(), 1);
+        assert_eq!(wrapper.get_transfer_payload().get_value(), 1337);
+        assert_eq!(wrapper.get_transfer_payload().get_token_id(), *token2);
 
-            let utxo = &utxos[0];
+        // Add the transaction to the ledger.
+        let tx_proposal = TxProposal::try_from(response.get_tx_proposal()).unwrap();
+        add_txos_and_key_images_to_ledger(
+            &mut ledger_db,
+            BlockVersion::MAX,
+            tx_proposal.tx.prefix.outputs.clone(),
+            tx_proposal.tx.key_images(),
+            &mut rng,
+        )
+        .unwrap();
 
-            assert_eq!(utxo.value, 1337);
-            assert_eq!(
-                utxo.tx_out.public_key,
-                RistrettoPublic::try_from(response.get_tx_public_key())
-                    .unwrap()
-                    .into()
-            );
-        }
+        // Parsing the code returns the amount from the ledger, and the memo of the
+        // TxOut.
+        let mut request = api::ParseTransferCodeRequest::new();
+        request.set_b58_code(response.get_b58_code().to_owned());
+        let parse_response = client.parse_transfer_code(&request).unwrap();
+        assert_eq!(parse_response.get_value(), 1337);
+        assert_eq!(parse_response.get_token_id(), *token2);
+        assert_eq!(parse_response.get_utxo().get_token_id(), *token2);
+        assert_eq!(parse_response.get_memo(), "gift");
+        assert_eq!(
+            parse_response.get_decoded_memo().get_memo_type(),
+            api::MemoType::MemoUnused
+        );
+
+        // A code whose amount does not match the TxOut is rejected.
+        let mut request = api::CreateTransferCodeRequest::new();
+        request.set_bip39_entropy(response.get_bip39_entropy().to_vec());
+        request.set_tx_public_key(response.get_tx_public_key().clone());
+        request.set_value(1337);
+        request.set_token_id(*Mob::ID);
+        let b58_code = client
+            .create_transfer_code(&request)
+            .unwrap()
+            .take_b58_code();
+
+        let mut request = api::ParseTransferCodeRequest::new();
+        request.set_b58_code(b58_code);
+        assert!(client.parse_transfer_code(&request).is_err());
+
+        // So is a code without a value whose token id does not match.
+        let mut request = api::CreateTransferCodeRequest::new();
+        request.set_bip39_entropy(response.get_bip39_entropy().to_vec());
+        request.set_tx_public_key(response.get_tx_public_key().clone());
+        let b58_code = client
+            .create_transfer_code(&request)
+            .unwrap()
+            .take_b58_code();
+
+        let mut request = api::ParseTransferCodeRequest::new();
+        request.set_b58_code(b58_code);
+        assert!(client.parse_transfer_code(&request).is_err());
+
+        // A code without a value but with the right token id is accepted.
+        let mut request = api::CreateTransferCodeRequest::new();
+        request.set_bip39_entropy(response.get_bip39_entropy().to_vec());
+        request.set_tx_public_key(response.get_tx_public_key().clone());
+        request.set_token_id(*token2);
+        let b58_code = client
+            .create_transfer_code(&request)
+            .unwrap()
+            .take_b58_code();
+
+        let mut request = api::ParseTransferCodeRequest::new();
+        request.set_b58_code(b58_code);
+        let parse_response = client.parse_transfer_code(&request).unwrap();
+        assert_eq!(parse_response.get_value(), 1337);
+        assert_eq!(parse_response.get_token_id(), *token2);
     }
 
     #[test_with_logger]
@@ -4242,10 +7754,7 @@ mod test {
         assert_eq!(tx_proposal.tx.prefix.inputs.len(), expected_num_inputs);
 
         assert_eq!(tx_proposal.outlays.len(), 1);
-        assert_eq!(
-            tx_proposal.outlays[0].receiver,
-            data.account_key.subaddress(0)
-        );
+        assert_eq!(tx_proposal.outlays[0].receiver, data.subaddress(0).unwrap());
         assert_eq!(
             tx_proposal.outlays[0].value,
             // Each UTXO we have has PER_RECIPIENT_AMOUNT coins. We will be merging MAX_INPUTS of
@@ -4259,8 +7768,10 @@ mod test {
         assert_eq!(tx_proposal.tx.prefix.outputs.len(), 1);
         let tx_out = &tx_proposal.tx.prefix.outputs[0];
         let tx_public_key = RistrettoPublic::try_from(&tx_out.public_key).unwrap();
-        let shared_secret =
-            get_tx_out_shared_secret(data.account_key.view_private_key(), &tx_public_key);
+        let shared_secret = get_tx_out_shared_secret(
+            data.account_key().unwrap().view_private_key(),
+            &tx_public_key,
+        );
         let (amount, _blinding) = tx_out.masked_amount.get_value(&shared_secret).unwrap();
         assert_eq!(amount.value, tx_proposal.outlays[0].value);
         assert_eq!(amount.token_id, Mob::ID);
@@ -4527,6 +8038,276 @@ mod test {
         }
     }
 
+    #[test_with_logger]
+    fn test_offline_signing(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([24u8; 32]);
+
+        let sender = AccountKey::random(&mut rng);
+        let data = MonitorData::new_watch_only(
+            ViewAccountKey::from(&sender),
+            None, // default_public_address
+            0,    // first_subaddress
+            20,   // num_subaddresses
+            0,    // first_block
+            "",   // name
+        )
+        .unwrap();
+
+        // 1 known recipient, 3 random recipients and no monitors.
+        let (ledger_db, mobilecoind_db, client, _server, server_conn_manager) =
+            get_testing_environment(
+                BLOCK_VERSION,
+                3,
+                &[sender.default_subaddress()],
+                &[],
+                logger.clone(),
+                &mut rng,
+            );
+
+        // Insert a watch-only monitor into the database.
+        let monitor_id = mobilecoind_db.add_monitor(&data).unwrap();
+
+        // Allow the new monitor to process the ledger.
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+        let utxos = mobilecoind_db
+            .get_utxos_for_subaddress(&monitor_id, 0)
+            .unwrap();
+        assert!(!utxos.is_empty());
+
+        let outlays = vec![Outlay {
+            value: 123,
+            receiver: AccountKey::random(&mut rng).default_subaddress(),
+        }];
+
+        // The watch-only side generates the unsigned transaction.
+        let mut request = api::GenerateTxRequest::new();
+        request.set_sender_monitor_id(monitor_id.to_vec());
+        request.set_change_subaddress(0);
+        request.set_input_list(RepeatedField::from_vec(
+            utxos.iter().map(api::UnspentTxOut::from).collect(),
+        ));
+        request.set_outlay_list(RepeatedField::from_vec(
+            outlays.iter().map(api::Outlay::from).collect(),
+        ));
+        let response = client.generate_unsigned_tx(&request).unwrap();
+        let unsigned_tx = UnsignedTx::try_from(response.get_unsigned_tx()).unwrap();
+        assert_eq!(unsigned_tx.outlays, outlays);
+        assert_eq!(unsigned_tx.inputs.len(), unsigned_tx.rings.len());
+
+        // The signer holds the account key.
+        let mut request = api::SignUnsignedTxRequest::new();
+        request.set_account_key((&sender).into());
+        request.set_unsigned_tx((&unsigned_tx).into());
+        let response = client.sign_unsigned_tx(&request).unwrap();
+        let tx_proposal = TxProposal::try_from(response.get_tx_proposal()).unwrap();
+        assert_eq!(tx_proposal.outlays, outlays);
+
+        // A signed transaction that does not match the unsigned one is rejected.
+        let mut other_unsigned_tx = unsigned_tx.clone();
+        other_unsigned_tx.outlays[0].value = 456;
+        let mut request = api::SubmitSignedTxRequest::new();
+        request.set_unsigned_tx((&other_unsigned_tx).into());
+        request.set_tx_proposal((&tx_proposal).into());
+        match client.submit_signed_tx(&request) {
+            Ok(_) => panic!("Should've returned an error"),
+            Err(GrpcError::RpcFailure(rpc_status)) => {
+                assert_eq!(
+                    rpc_status.message(),
+                    "verify_signed_tx: The signed transaction does not match the unsigned transaction: outlays".to_owned()
+                );
+            }
+            Err(err) => panic!("Unexpected error: {:?}", err),
+        };
+
+        // So is one whose TxOuts do not pay the outlays it lists, to the right
+        // receiver or with the right amount.
+        let sign = |unsigned_tx: &UnsignedTx| {
+            let mut request = api::SignUnsignedTxRequest::new();
+            request.set_account_key((&sender).into());
+            request.set_unsigned_tx(unsigned_tx.into());
+            let response = client.sign_unsigned_tx(&request).unwrap();
+            TxProposal::try_from(response.get_tx_proposal()).unwrap()
+        };
+        let mut diverted_unsigned_tx = unsigned_tx.clone();
+        diverted_unsigned_tx.outlays[0].receiver =
+            AccountKey::random(&mut rng).default_subaddress();
+        let mut overpaid_unsigned_tx = unsigned_tx.clone();
+        overpaid_unsigned_tx.outlays[0].value = 456;
+        for (tampered_unsigned_tx, error) in [
+            (diverted_unsigned_tx, "outlay receiver"),
+            (overpaid_unsigned_tx, "outlay amount"),
+        ] {
+            let mut tampered_tx_proposal = sign(&tampered_unsigned_tx);
+            tampered_tx_proposal.outlays = outlays.clone();
+
+            let mut request = api::SubmitSignedTxRequest::new();
+            request.set_unsigned_tx((&unsigned_tx).into());
+            request.set_tx_proposal((&tampered_tx_proposal).into());
+            match client.submit_signed_tx(&request) {
+                Ok(_) => panic!("Should've returned an error"),
+                Err(GrpcError::RpcFailure(rpc_status)) => {
+                    assert_eq!(
+                        rpc_status.message(),
+                        format!("verify_signed_tx: The signed transaction does not match the unsigned transaction: {}", error)
+                    );
+                }
+                Err(err) => panic!("Unexpected error: {:?}", err),
+            };
+        }
+
+        // So is one without the shared secrets of its outlays.
+        let mut tampered_tx_proposal = tx_proposal.clone();
+        tampered_tx_proposal.outlay_shared_secrets.clear();
+        let mut request = api::SubmitSignedTxRequest::new();
+        request.set_unsigned_tx((&unsigned_tx).into());
+        request.set_tx_proposal((&tampered_tx_proposal).into());
+        assert!(client.submit_signed_tx(&request).is_err());
+
+        for mock_peer in server_conn_manager.conns() {
+            assert!(mock_peer.read().proposed_txs.is_empty());
+        }
+
+        // Submit the signed transaction.
+        let mut request = api::SubmitSignedTxRequest::new();
+        request.set_unsigned_tx((&unsigned_tx).into());
+        request.set_tx_proposal((&tx_proposal).into());
+        let response = client.submit_signed_tx(&request).unwrap();
+
+        let submitted_txs: Vec<Tx> = server_conn_manager
+            .conns()
+            .iter()
+            .flat_map(|mock_peer| mock_peer.read().proposed_txs.clone())
+            .collect();
+        assert_eq!(submitted_txs, vec![tx_proposal.tx.clone()]);
+
+        // The sender receipt holds the key images of the signed transaction.
+        let key_images: Vec<KeyImage> = response
+            .get_sender_tx_receipt()
+            .get_key_image_list()
+            .iter()
+            .map(|key_image| KeyImage::try_from(key_image).unwrap())
+            .collect();
+        assert_eq!(key_images, tx_proposal.tx.key_images());
+        assert_eq!(response.get_receiver_tx_receipt_list().len(), 1);
+
+        // The spent utxos are marked as such.
+        let tx_proposal_utxo_ids: Vec<UtxoId> =
+            tx_proposal.utxos.iter().map(UtxoId::from).collect();
+        for utxo in mobilecoind_db
+            .get_utxos_for_subaddress(&monitor_id, 0)
+            .unwrap()
+        {
+            assert_eq!(
+                utxo.attempted_spend_height > 0,
+                tx_proposal_utxo_ids.contains(&UtxoId::from(&utxo))
+            );
+        }
+    }
+
+    #[test_with_logger]
+    fn test_signed_contingent_input(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([25u8; 32]);
+
+        let alice = AccountKey::random(&mut rng);
+        let bob = AccountKey::random(&mut rng);
+        let token2 = TokenId::from(2);
+
+        // 2 known recipients, 3 random recipients and no monitors.
+        let (mut ledger_db, mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(
+                BLOCK_VERSION,
+                3,
+                &[alice.default_subaddress(), bob.default_subaddress()],
+                &[],
+                logger.clone(),
+                &mut rng,
+            );
+
+        // Alice also owns some token2.
+        add_block_to_ledger(
+            &mut ledger_db,
+            BLOCK_VERSION,
+            &[alice.default_subaddress()],
+            Amount::new(1000, token2),
+            &[KeyImage::from(101)],
+            &mut rng,
+        )
+        .unwrap();
+
+        let alice_monitor_id = mobilecoind_db
+            .add_monitor(&MonitorData::new(alice.clone(), 0, 20, 0, "").unwrap())
+            .unwrap();
+        let bob_monitor_id = mobilecoind_db
+            .add_monitor(&MonitorData::new(bob.clone(), 0, 20, 0, "").unwrap())
+            .unwrap();
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+        // Alice offers her token2 for 1 MOB.
+        let alice_utxo = mobilecoind_db
+            .get_utxos_for_subaddress(&alice_monitor_id, 0)
+            .unwrap()
+            .into_iter()
+            .find(|utxo| utxo.token_id == *token2)
+            .unwrap();
+        let mut request = api::CreateSignedContingentInputRequest::new();
+        request.set_monitor_id(alice_monitor_id.to_vec());
+        request.set_utxo((&alice_utxo).into());
+        request.set_required_value(1000 * MILLIMOB_TO_PICOMOB);
+        request.set_required_token_id(*Mob::ID);
+        request.set_receiver_subaddress(0);
+        let response = client.create_signed_contingent_input(&request).unwrap();
+        let sci = SignedContingentInput::try_from(response.get_sci()).unwrap();
+        sci.validate().unwrap();
+        assert_eq!(sci.pseudo_output_amount.value, 1000);
+        assert_eq!(sci.pseudo_output_amount.token_id, *token2);
+        assert_eq!(sci.required_output_amounts.len(), 1);
+        assert_eq!(
+            sci.required_output_amounts[0].value,
+            1000 * MILLIMOB_TO_PICOMOB
+        );
+
+        // Only the monitor's own UTXOs can be offered.
+        let mut bad_request = request.clone();
+        bad_request.set_monitor_id(bob_monitor_id.to_vec());
+        assert!(client.create_signed_contingent_input(&bad_request).is_err());
+
+        // Bob fulfills the SCI with his MOB.
+        let bob_utxos = mobilecoind_db
+            .get_utxos_for_subaddress(&bob_monitor_id, 0)
+            .unwrap();
+        let mut request = api::FulfillSignedContingentInputRequest::new();
+        request.set_sender_monitor_id(bob_monitor_id.to_vec());
+        request.set_change_subaddress(0);
+        request.set_input_list(RepeatedField::from_vec(
+            bob_utxos.iter().map(api::UnspentTxOut::from).collect(),
+        ));
+        request.set_sci((&sci).into());
+        let response = client.fulfill_signed_contingent_input(&request).unwrap();
+        let tx_proposal = TxProposal::try_from(response.get_tx_proposal()).unwrap();
+        let tx = &tx_proposal.tx;
+
+        // The transaction spends the SCI, and creates the output it requires.
+        assert!(tx.key_images().contains(&sci.key_image()));
+        let required_output = &sci.tx_in.input_rules.as_ref().unwrap().required_outputs[0];
+        assert!(tx.prefix.outputs.contains(required_output));
+        assert!(tx_proposal
+            .utxos
+            .iter()
+            .all(|utxo| bob_utxos.contains(utxo)));
+
+        // Bob receives the offered token2.
+        let bob_token2: Vec<Amount> = tx
+            .prefix
+            .outputs
+            .iter()
+            .filter_map(|tx_out| tx_out.view_key_match(bob.view_private_key()).ok())
+            .map(|(amount, _)| amount)
+            .filter(|amount| amount.token_id == token2)
+            .collect();
+        assert_eq!(bob_token2, vec![Amount::new(1000, token2)]);
+    }
+
     #[test_with_logger]
     fn test_get_balance_impl(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([23u8; 32]);
@@ -4610,7 +8391,7 @@ mod test {
         .unwrap();
 
         // 1 known recipient, 3 random recipients and no monitors.
-        let (ledger_db, mobilecoind_db, client, _server, server_conn_manager) =
+        let (mut ledger_db, mobilecoind_db, client, _server, server_conn_manager) =
             get_testing_environment(
                 BLOCK_VERSION,
                 3,
@@ -4766,6 +8547,140 @@ mod test {
             }
         }
         assert_eq!(matched_utxos, tx_proposal.utxos.len());
+
+        // Once the inputs are spent, the history shows the recipients of the payment.
+        add_block_to_ledger(
+            &mut ledger_db,
+            BLOCK_VERSION,
+            &[receiver1.default_subaddress()],
+            Amount::new(123, Mob::ID),
+            &key_images,
+            &mut rng,
+        )
+        .unwrap();
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+        let mut request = api::GetTransactionHistoryRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_newest_first(true);
+        request.set_limit(key_images.len() as u64);
+        let response = client.get_transaction_history(&request).unwrap();
+        let payments: Vec<_> = response
+            .get_entries()
+            .iter()
+            .filter(|entry| entry.get_tx_out().has_payment())
+            .map(|entry| entry.get_tx_out().get_payment())
+            .collect();
+        assert_eq!(payments.len(), 1);
+        assert_eq!(payments[0].get_fee(), submitted_tx.prefix.fee);
+        let sent_outlays: Vec<(PublicAddress, u64)> = payments[0]
+            .get_outlays()
+            .iter()
+            .map(|outlay| {
+                (
+                    PublicAddress::try_from(outlay.get_receiver()).unwrap(),
+                    outlay.get_value(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            sent_outlays,
+            outlays
+                .iter()
+                .map(|outlay| (outlay.receiver.clone(), outlay.value))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test_with_logger]
+    fn test_send_payment_idempotency_key(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([42u8; 32]);
+
+        let sender = AccountKey::random(&mut rng);
+        let data = MonitorData::new(
+            sender.clone(),
+            0,  // first_subaddress
+            20, // num_subaddresses
+            0,  // first_block
+            "", // name
+        )
+        .unwrap();
+
+        // 1 known recipient, 3 random recipients and no monitors.
+        let (ledger_db, mobilecoind_db, client, _server, server_conn_manager) =
+            get_testing_environment(
+                BLOCK_VERSION,
+                3,
+                &[sender.default_subaddress()],
+                &[],
+                logger.clone(),
+                &mut rng,
+            );
+
+        // Insert into database.
+        let monitor_id = mobilecoind_db.add_monitor(&data).unwrap();
+
+        // Allow the new monitor to process the ledger.
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+        let receiver = AccountKey::random(&mut rng);
+        let outlay = Outlay {
+            value: 123,
+            receiver: receiver.default_subaddress(),
+        };
+
+        let num_submitted_txs = || -> usize {
+            server_conn_manager
+                .conns()
+                .iter()
+                .map(|mock_peer| mock_peer.read().proposed_txs.len())
+                .sum()
+        };
+
+        let mut request = api::SendPaymentRequest::new();
+        request.set_sender_monitor_id(monitor_id.to_vec());
+        request.set_sender_subaddress(0);
+        request.set_outlay_list(RepeatedField::from_vec(vec![api::Outlay::from(&outlay)]));
+        request.set_idempotency_key("withdrawal-1".to_owned());
+
+        let response = client.send_payment(&request).unwrap();
+        assert_eq!(num_submitted_txs(), 1);
+
+        // Replays return the original response, without submitting anything.
+        let replay_response = client.send_payment(&request).unwrap();
+        assert_eq!(replay_response, response);
+        assert_eq!(num_submitted_txs(), 1);
+
+        // The key cannot be used for a different payment.
+        {
+            let mut request = request.clone();
+            request.mut_outlay_list()[0].set_value(456);
+            match client.send_payment(&request) {
+                Err(GrpcError::RpcFailure(rpc_status)) => {
+                    assert_eq!(rpc_status.code(), RpcStatusCode::INVALID_ARGUMENT)
+                }
+                result => panic!("unexpected result {:?}", result),
+            }
+            assert_eq!(num_submitted_txs(), 1);
+        }
+
+        // SubmitTx keys are separate from SendPayment keys. Submitting the same
+        // transaction again with a key submits it once, and replays return the
+        // original response.
+        let mut submit_request = api::SubmitTxRequest::new();
+        submit_request.set_tx_proposal(response.get_tx_proposal().clone());
+        submit_request.set_idempotency_key("withdrawal-1".to_owned());
+
+        let submit_response = client.submit_tx(&submit_request).unwrap();
+        assert_eq!(
+            submit_response.get_sender_tx_receipt(),
+            response.get_sender_tx_receipt()
+        );
+        assert_eq!(num_submitted_txs(), 2);
+
+        let replay_submit_response = client.submit_tx(&submit_request).unwrap();
+        assert_eq!(replay_submit_response, submit_response);
+        assert_eq!(num_submitted_txs(), 2);
     }
 
     #[test_with_logger]
@@ -4891,6 +8806,234 @@ mod test {
         );
     }
 
+    #[test_with_logger]
+    fn test_send_payment_mixed_token_ids(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([29u8; 32]);
+        let token2 = TokenId::from(2);
+
+        let sender = AccountKey::random(&mut rng);
+        let data = MonitorData::new(
+            sender.clone(),
+            0,  // first_subaddress
+            20, // num_subaddresses
+            0,  // first_block
+            "", // name
+        )
+        .unwrap();
+
+        // 1 known recipient, 3 random recipients and no monitors.
+        let (mut ledger_db, mobilecoind_db, client, _server, server_conn_manager) =
+            get_testing_environment(
+                BLOCK_VERSION,
+                3,
+                &[sender.default_subaddress()],
+                &[],
+                logger.clone(),
+                &mut rng,
+            );
+
+        // Add a block with a non-MOB token ID.
+        add_block_to_ledger(
+            &mut ledger_db,
+            BlockVersion::MAX,
+            &[sender.default_subaddress()],
+            Amount::new(1_000_000, token2),
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        )
+        .unwrap();
+
+        // Insert into database.
+        let monitor_id = mobilecoind_db.add_monitor(&data).unwrap();
+
+        // Allow the new monitor to process the ledger.
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+        let receiver = AccountKey::random(&mut rng);
+        let outlays = vec![
+            OutlayV2 {
+                amount: Amount::new(123, Mob::ID),
+                receiver: receiver.default_subaddress(),
+            },
+            OutlayV2 {
+                amount: Amount::new(456, token2),
+                receiver: receiver.default_subaddress(),
+            },
+        ];
+
+        // Call send payment, paying the fee in MOB.
+        let mut request = api::SendPaymentRequest::new();
+        request.set_sender_monitor_id(monitor_id.to_vec());
+        request.set_sender_subaddress(0);
+        request.set_outlay_v2_list(RepeatedField::from_vec(
+            outlays.iter().map(api::OutlayV2::from).collect(),
+        ));
+        request.set_fee_token_id(*Mob::ID);
+
+        let response = client.send_payment(&request).unwrap();
+
+        // The transaction was submitted, and spends inputs of both token ids.
+        let submitted_txs: Vec<Tx> = server_conn_manager
+            .conns()
+            .iter()
+            .flat_map(|mock_peer| mock_peer.read().proposed_txs.clone())
+            .collect();
+        assert_eq!(submitted_txs.len(), 1);
+        assert_eq!(
+            submitted_txs[0],
+            Tx::try_from(response.get_tx_proposal().get_tx()).unwrap()
+        );
+        assert_eq!(submitted_txs[0].prefix.fee_token_id, *Mob::ID);
+
+        let tx_proposal = TxProposal::try_from(response.get_tx_proposal()).unwrap();
+        let input_token_ids: HashSet<u64> =
+            tx_proposal.utxos.iter().map(|utxo| utxo.token_id).collect();
+        assert_eq!(input_token_ids, HashSet::from_iter([*Mob::ID, *token2]));
+
+        // Two outlays plus one change output per token id.
+        assert_eq!(response.get_receiver_tx_receipt_list().len(), 2);
+        assert_eq!(submitted_txs[0].prefix.outputs.len(), 4);
+
+        // The outlays can be recovered by the receiver with their token ids.
+        for (outlay, receipt) in outlays
+            .iter()
+            .zip(response.get_receiver_tx_receipt_list().iter())
+        {
+            let hash: [u8; 32] = receipt.get_tx_out_hash().try_into().unwrap();
+            let tx_out = submitted_txs[0]
+                .prefix
+                .outputs
+                .iter()
+                .find(|tx_out| tx_out.hash() == hash)
+                .unwrap();
+            let (amount, _) = tx_out.view_key_match(receiver.view_private_key()).unwrap();
+            assert_eq!(amount, outlay.amount);
+        }
+
+        // Paying a fee larger than the balance of the fee token id fails.
+        request.set_fee_token_id(*token2);
+        request.set_fee(2_000_000);
+        match client.send_payment(&request) {
+            Ok(_) => panic!("Should've returned an error"),
+            Err(GrpcError::RpcFailure(rpc_status)) => {
+                assert_eq!(
+                    rpc_status.message(),
+                    "transactions_manager.build_mixed_transaction: Insufficient funds".to_owned()
+                );
+            }
+            Err(err) => panic!("Unexpected error: {:?}", err),
+        };
+    }
+
+    #[test_with_logger]
+    fn test_consolidate_utxos(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([30u8; 32]);
+
+        let sender = AccountKey::random(&mut rng);
+        let data = MonitorData::new(
+            sender.clone(),
+            0,  // first_subaddress
+            20, // num_subaddresses
+            0,  // first_block
+            "", // name
+        )
+        .unwrap();
+
+        // 1 known recipient, and a bunch of random recipients and no monitors.
+        // The random recipients are needed for mixins.
+        let num_random_recipients = MAX_INPUTS as u32 * RING_SIZE as u32
+            / test_utils::GET_TESTING_ENVIRONMENT_NUM_BLOCKS as u32;
+        let (mut ledger_db, mobilecoind_db, client, _server, server_conn_manager) =
+            get_testing_environment(
+                BLOCK_VERSION,
+                num_random_recipients,
+                &[sender.default_subaddress()],
+                &[],
+                logger.clone(),
+                &mut rng,
+            );
+
+        // Add some utxos that are only worth the fee, for a total of 30 utxos. They
+        // are not dust, since merging them covers the fee.
+        for _ in 0..20 {
+            add_block_to_ledger(
+                &mut ledger_db,
+                BLOCK_VERSION,
+                &[sender.default_subaddress()],
+                Amount::new(Mob::MINIMUM_FEE, Mob::ID),
+                &[KeyImage::from(rng.next_u64())],
+                &mut rng,
+            )
+            .unwrap();
+        }
+
+        // Insert into database.
+        let monitor_id = mobilecoind_db.add_monitor(&data).unwrap();
+
+        // Allow the new monitor to process the ledger.
+        wait_for_monitors(&mobilecoind_db, &ledger_db, &logger);
+
+        // Merging 30 utxos into one takes two transactions.
+        let mut request = api::ConsolidateUtxosRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_subaddress(0);
+        request.set_target_num_utxos(1);
+
+        let response = client.consolidate_utxos(&request).unwrap();
+        assert_eq!(response.get_num_utxos(), 30);
+        assert_eq!(response.get_num_dust_utxos(), 0);
+        assert_eq!(response.get_num_txs(), 2);
+        assert_eq!(response.get_total_fee(), 2 * Mob::MINIMUM_FEE);
+        assert!(response.get_tx_proposal_list().is_empty());
+
+        // Nothing was submitted without setting submit.
+        for mock_peer in server_conn_manager.conns() {
+            assert!(mock_peer.read().proposed_txs.is_empty());
+        }
+
+        // Nothing to do when there are already fewer utxos than the target.
+        request.set_target_num_utxos(30);
+        let response = client.consolidate_utxos(&request).unwrap();
+        assert_eq!(response.get_num_txs(), 0);
+        assert_eq!(response.get_total_fee(), 0);
+
+        // The target must be at least one utxo.
+        request.set_target_num_utxos(0);
+        assert!(client.consolidate_utxos(&request).is_err());
+
+        // Submitting only builds the first transaction, since the second one spends
+        // its output.
+        request.set_target_num_utxos(1);
+        request.set_submit(true);
+        let response = client.consolidate_utxos(&request).unwrap();
+        assert_eq!(response.get_num_txs(), 2);
+        assert_eq!(response.get_tx_proposal_list().len(), 1);
+        assert_eq!(response.get_sender_tx_receipt_list().len(), 1);
+
+        let submitted_txs: Vec<Tx> = server_conn_manager
+            .conns()
+            .iter()
+            .flat_map(|mock_peer| mock_peer.read().proposed_txs.clone())
+            .collect();
+        assert_eq!(submitted_txs.len(), 1);
+        assert_eq!(
+            submitted_txs[0],
+            Tx::try_from(response.get_tx_proposal_list()[0].get_tx()).unwrap()
+        );
+        assert_eq!(submitted_txs[0].prefix.inputs.len(), MAX_INPUTS as usize);
+        assert_eq!(submitted_txs[0].prefix.outputs.len(), 1);
+
+        // The spent utxos are paid back to the subaddress, minus the fee.
+        let tx_proposal = TxProposal::try_from(&response.get_tx_proposal_list()[0]).unwrap();
+        let total_input_value: u64 = tx_proposal.utxos.iter().map(|utxo| utxo.value).sum();
+        assert_eq!(tx_proposal.outlays.len(), 1);
+        assert_eq!(tx_proposal.outlays[0].receiver, sender.subaddress(0));
+        assert_eq!(
+            tx_proposal.outlays[0].value,
+            total_input_value - Mob::MINIMUM_FEE
+        );
+    }
+
     #[test_with_logger]
     fn test_send_payment_to_fog(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([23u8; 32]);
@@ -4944,6 +9087,7 @@ mod test {
             mobilecoind_db.clone(),
             None,
             Some(fog_pubkey_resolver_factory),
+            vec![],
             &uri,
         );
         log::debug!(logger, "Setting up client {:?}", port);
@@ -5423,6 +9567,80 @@ mod test {
         }
     }
 
+    #[test_with_logger]
+    fn test_request_code_for_monitor_subaddress(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([34u8; 32]);
+        let account_key = AccountKey::random(&mut rng);
+        let data = MonitorData::new(
+            account_key.clone(),
+            10, // first_subaddress
+            5,  // num_subaddresses
+            0,  // first_block
+            "", // name
+        )
+        .unwrap();
+
+        // no known recipient, 3 random recipients and no monitors.
+        let (_ledger_db, mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(BLOCK_VERSION, 3, &[], &[], logger.clone(), &mut rng);
+
+        let monitor_id = mobilecoind_db.add_monitor(&data).unwrap();
+        mobilecoind_db
+            .set_subaddress_label(&monitor_id, 12, "table 4")
+            .unwrap();
+
+        // Generate a request code for a subaddress of the monitor.
+        let mut request = api::CreateRequestCodeRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_subaddress_index(12);
+        request.set_value(1234567890);
+        request.set_token_id(1);
+        request.set_memo("order 42".to_owned());
+        let response = client.create_request_code(&request).unwrap();
+        let b58_code = response.get_b58_code();
+
+        // Parsing it reports the monitor subaddress it pays to.
+        let mut request = api::ParseRequestCodeRequest::new();
+        request.set_b58_code(b58_code.to_string());
+        let response = client.parse_request_code(&request).unwrap();
+        assert_eq!(
+            PublicAddress::try_from(response.get_receiver()).unwrap(),
+            account_key.subaddress(12)
+        );
+        assert_eq!(response.get_value(), 1234567890);
+        assert_eq!(response.get_token_id(), 1);
+        assert_eq!(response.get_memo(), "order 42");
+        assert_eq!(response.get_monitor_id(), monitor_id.to_vec());
+        assert_eq!(response.get_subaddress_index(), 12);
+        assert_eq!(response.get_subaddress_label(), "table 4");
+
+        // Codes paying to other addresses do not report a monitor.
+        let mut request = api::CreateRequestCodeRequest::new();
+        request.set_receiver((&AccountKey::random(&mut rng).default_subaddress()).into());
+        let response = client.create_request_code(&request).unwrap();
+
+        let mut request = api::ParseRequestCodeRequest::new();
+        request.set_b58_code(response.get_b58_code().to_string());
+        let response = client.parse_request_code(&request).unwrap();
+        assert!(response.get_monitor_id().is_empty());
+        assert_eq!(response.get_subaddress_label(), "");
+
+        // Subaddresses the monitor does not watch are rejected.
+        let mut request = api::CreateRequestCodeRequest::new();
+        request.set_monitor_id(monitor_id.to_vec());
+        request.set_subaddress_index(15);
+        assert!(client.create_request_code(&request).is_err());
+
+        // Setting both a receiver and a monitor, or neither, is rejected.
+        request.set_subaddress_index(10);
+        request.set_receiver((&account_key.subaddress(10)).into());
+        assert!(client.create_request_code(&request).is_err());
+
+        assert!(client
+            .create_request_code(&api::CreateRequestCodeRequest::new())
+            .is_err());
+    }
+
     #[test_with_logger]
     fn test_transfer_code_root_entropy(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([23u8; 32]);
@@ -5711,6 +9929,187 @@ mod test {
         }
     }
 
+    #[test_with_logger]
+    fn test_validate_fog_address(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([36u8; 32]);
+
+        let trusted_spki = vec![1u8; 64];
+        let untrusted_spki = vec![2u8; 64];
+
+        let (ledger_db, mobilecoind_db) = test_utils::get_test_databases(
+            BLOCK_VERSION,
+            3,
+            &[],
+            test_utils::GET_TESTING_ENVIRONMENT_NUM_BLOCKS,
+            logger.clone(),
+            &mut rng,
+        );
+        let port = test_utils::get_free_port();
+        let uri = MobilecoindUri::from_str(&format!("insecure-mobilecoind://127.0.0.1:{}/", port))
+            .unwrap();
+        let (_server, _server_conn_manager) = test_utils::setup_server::<MockFogResolver>(
+            logger.clone(),
+            ledger_db,
+            mobilecoind_db,
+            None,
+            None,
+            vec![untrusted_spki.clone(), trusted_spki.clone()],
+            &uri,
+        );
+        let client = test_utils::setup_client(&uri, &logger);
+
+        let validate = |address: &PublicAddress| {
+            let mut wrapper = api::printable::PrintableWrapper::new();
+            wrapper.set_public_address(address.into());
+
+            let mut request = api::ValidateFogAddressRequest::new();
+            request.set_b58_code(wrapper.b58_encode().unwrap());
+            client.validate_fog_address(&request).unwrap()
+        };
+
+        // An address signed over a trusted fog authority is valid.
+        let fog_report_url = "fog://fog.unittest.mobilecoin.com";
+        let account_key = AccountKey::from(&RootIdentity::random_with_fog(
+            &mut rng,
+            fog_report_url,
+            "",
+            &trusted_spki,
+        ));
+        let address = account_key.default_subaddress();
+        let response = validate(&address);
+        assert_eq!(
+            response.get_status(),
+            api::FogAddressStatus::FogAddressValid
+        );
+        assert_eq!(response.get_fog_authority_spki(), trusted_spki.as_slice());
+        assert_eq!(
+            PublicAddress::try_from(response.get_receiver()).unwrap(),
+            address
+        );
+
+        // An address signed over another fog authority is not.
+        let account_key = AccountKey::from(&RootIdentity::random_with_fog(
+            &mut rng,
+            fog_report_url,
+            "",
+            &[3u8; 64],
+        ));
+        let response = validate(&account_key.default_subaddress());
+        assert_eq!(
+            response.get_status(),
+            api::FogAddressStatus::FogAddressUntrustedAuthority
+        );
+        assert!(response.get_fog_authority_spki().is_empty());
+        assert!(!response.get_error().is_empty());
+
+        // Addresses without fog have nothing to validate.
+        let response = validate(&AccountKey::random(&mut rng).default_subaddress());
+        assert_eq!(
+            response.get_status(),
+            api::FogAddressStatus::FogAddressNoFog
+        );
+
+        // Missing or malformed signatures and bad fog report urls are reported.
+        let with_fog = |fog_report_url: &str, fog_authority_sig: &[u8]| {
+            PublicAddress::new_with_fog(
+                address.spend_public_key(),
+                address.view_public_key(),
+                fog_report_url,
+                String::new(),
+                fog_authority_sig,
+            )
+        };
+        let sig = address.fog_authority_sig().unwrap();
+        assert_eq!(
+            validate(&with_fog(fog_report_url, &[])).get_status(),
+            api::FogAddressStatus::FogAddressMissingSignature
+        );
+        assert_eq!(
+            validate(&with_fog(fog_report_url, &sig[1..])).get_status(),
+            api::FogAddressStatus::FogAddressMalformedSignature
+        );
+        assert_eq!(
+            validate(&with_fog("https://fog.unittest.mobilecoin.com", sig)).get_status(),
+            api::FogAddressStatus::FogAddressInvalidReportUrl
+        );
+
+        // Junk codes are rejected.
+        let mut request = api::ValidateFogAddressRequest::new();
+        request.set_b58_code("junk".to_owned());
+        assert!(client.validate_fog_address(&request).is_err());
+
+        // Validation fails when there are no trusted fog authorities.
+        let (_ledger_db, _mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(BLOCK_VERSION, 3, &[], &[], logger.clone(), &mut rng);
+        let mut wrapper = api::printable::PrintableWrapper::new();
+        wrapper.set_public_address((&address).into());
+        let mut request = api::ValidateFogAddressRequest::new();
+        request.set_b58_code(wrapper.b58_encode().unwrap());
+        match client.validate_fog_address(&request) {
+            Ok(_) => panic!("Should've returned an error"),
+            Err(GrpcError::RpcFailure(rpc_status)) => {
+                assert_eq!(rpc_status.code(), RpcStatusCode::FAILED_PRECONDITION);
+            }
+            Err(err) => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    #[test_with_logger]
+    fn test_change_db_password(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([39u8; 32]);
+
+        let (_ledger_db, _mobilecoind_db, client, _server, _server_conn_manager) =
+            get_testing_environment(BLOCK_VERSION, 3, &[], &[], logger.clone(), &mut rng);
+
+        let response = client.get_db_encryption_status(&api::Empty::new()).unwrap();
+        assert!(!response.get_is_encrypted());
+        assert!(response.get_is_unlocked());
+
+        // Enable encryption.
+        let mut request = api::ChangeDbPasswordRequest::new();
+        request.set_new_password(vec![5; 32]);
+        client.change_db_password(&request).unwrap();
+
+        let response = client.get_db_encryption_status(&api::Empty::new()).unwrap();
+        assert!(response.get_is_encrypted());
+        assert!(response.get_is_unlocked());
+
+        // The current password is required.
+        let mut request = api::ChangeDbPasswordRequest::new();
+        request.set_current_password(vec![4; 32]);
+        request.set_new_password(vec![6; 32]);
+        match client.change_db_password(&request) {
+            Ok(_) => panic!("Should've returned an error"),
+            Err(GrpcError::RpcFailure(rpc_status)) => {
+                assert_eq!(rpc_status.code(), RpcStatusCode::PERMISSION_DENIED);
+            }
+            Err(err) => panic!("Unexpected error: {:?}", err),
+        }
+
+        // Bad password lengths are rejected.
+        request.set_current_password(vec![5; 32]);
+        request.set_new_password(vec![6; 16]);
+        match client.change_db_password(&request) {
+            Ok(_) => panic!("Should've returned an error"),
+            Err(GrpcError::RpcFailure(rpc_status)) => {
+                assert_eq!(rpc_status.code(), RpcStatusCode::INVALID_ARGUMENT);
+            }
+            Err(err) => panic!("Unexpected error: {:?}", err),
+        }
+
+        // Rotate the password, then disable encryption.
+        request.set_new_password(vec![6; 32]);
+        client.change_db_password(&request).unwrap();
+
+        request.set_current_password(vec![6; 32]);
+        request.set_new_password(vec![]);
+        client.change_db_password(&request).unwrap();
+
+        let response = client.get_db_encryption_status(&api::Empty::new()).unwrap();
+        assert!(!response.get_is_encrypted());
+        assert!(response.get_is_unlocked());
+    }
+
     #[test_with_logger]
     fn test_get_network_status(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([23u8; 32]);
@@ -5757,7 +10156,9 @@ mod test {
             );
 
         let mut request = api::AddMonitorRequest::new();
-        request.set_account_key(mc_api::external::AccountKey::from(&data.account_key));
+        request.set_account_key(mc_api::external::AccountKey::from(
+            data.account_key().unwrap(),
+        ));
         request.set_first_subaddress(data.first_subaddress);
         request.set_num_subaddresses(data.num_subaddresses);
         request.set_first_block(data.first_block);
@@ -5846,7 +10247,9 @@ mod test {
 
         // Re-add the monitor.
         let mut request = api::AddMonitorRequest::new();
-        request.set_account_key(mc_api::external::AccountKey::from(&data.account_key));
+        request.set_account_key(mc_api::external::AccountKey::from(
+            data.account_key().unwrap(),
+        ));
         request.set_first_subaddress(data.first_subaddress);
         request.set_num_subaddresses(data.num_subaddresses);
         request.set_first_block(data.first_block);
@@ -4,6 +4,8 @@
 //! * A lookup table, mapping subaddress_spend_public_key to monitor_id and
 //!   subaddress index. This is used by the ledger sync code, allowing it to
 //!   match TxOuts into specific monitor_ids.
+//! * The labels of subaddresses, mapping monitor_id and subaddress index to a
+//!   label chosen by the user, e.g. the customer a deposit address belongs to.
 
 use crate::{
     database_key::DatabaseByteArrayKey,
@@ -11,7 +13,7 @@ use crate::{
     monitor_store::{MonitorData, MonitorId},
 };
 
-use lmdb::{Database, DatabaseFlags, Environment, RwTransaction, Transaction, WriteFlags};
+use lmdb::{Cursor, Database, DatabaseFlags, Environment, RwTransaction, Transaction, WriteFlags};
 use mc_common::logger::{log, Logger};
 use mc_crypto_keys::RistrettoPublic;
 use prost::Message;
@@ -20,6 +22,7 @@ use std::sync::Arc;
 // LMDB Database Names
 pub const SUBADDRESS_PUBLIC_SPEND_KEY_TO_INDEX_DATA_DB_NAME: &str =
     "mobilecoind_db:subaddress_store:spk_to_index_data";
+pub const SUBADDRESS_ID_TO_LABEL_DB_NAME: &str = "mobilecoind_db:subaddress_store:id_to_label";
 
 /// Type used to associate a monitor id and subaddress index.
 /// It is used as the stored data in the spk_to_index_data database,
@@ -92,6 +95,9 @@ pub struct SubaddressStore {
     /// Mapping of Subaddress Spend Public Key -> SubaddressId
     spk_to_index_data: Database,
 
+    /// Mapping of SubaddressId -> label
+    id_to_label: Database,
+
     /// Logger.
     logger: Logger,
 }
@@ -102,9 +108,12 @@ impl SubaddressStore {
             Some(SUBADDRESS_PUBLIC_SPEND_KEY_TO_INDEX_DATA_DB_NAME),
             DatabaseFlags::empty(),
         )?;
+        let id_to_label =
+            env.create_db(Some(SUBADDRESS_ID_TO_LABEL_DB_NAME), DatabaseFlags::empty())?;
         Ok(Self {
             _env: env,
             spk_to_index_data,
+            id_to_label,
             logger,
         })
     }
@@ -117,8 +126,7 @@ impl SubaddressStore {
         data: &MonitorData,
         index: u64,
     ) -> Result<(), Error> {
-        let subaddress_spk =
-            SubaddressSPKId::from(data.account_key.subaddress(index).spend_public_key());
+        let subaddress_spk = SubaddressSPKId::from(data.subaddress(index)?.spend_public_key());
         let subaddress_id: SubaddressId = SubaddressId::new(monitor_id, index);
 
         let value_bytes = mc_util_serial::encode(&subaddress_id);
@@ -164,20 +172,105 @@ impl SubaddressStore {
         data: &MonitorData,
         index: u64,
     ) -> Result<(), Error> {
-        let subaddress_spk =
-            SubaddressSPKId::from(data.account_key.subaddress(index).spend_public_key());
+        let subaddress_spk = SubaddressSPKId::from(data.subaddress(index)?.spend_public_key());
 
         db_txn.del(self.spk_to_index_data, &subaddress_spk, None)?;
 
         Ok(())
     }
+
+    /// Set the label of a subaddress. An empty label removes it.
+    pub fn set_label<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        monitor_id: &MonitorId,
+        index: u64,
+        label: &str,
+    ) -> Result<(), Error> {
+        let key = SubaddressId::new(monitor_id, index).to_bytes();
+        if label.is_empty() {
+            match db_txn.del(self.id_to_label, &key, None) {
+                Ok(_) | Err(lmdb::Error::NotFound) => Ok(()),
+                Err(err) => Err(err.into()),
+            }
+        } else {
+            db_txn.put(self.id_to_label, &key, &label, WriteFlags::empty())?;
+            Ok(())
+        }
+    }
+
+    /// Returns the label of a subaddress, if it has one.
+    pub fn get_label(
+        &self,
+        db_txn: &impl Transaction,
+        monitor_id: &MonitorId,
+        index: u64,
+    ) -> Result<Option<String>, Error> {
+        let key = SubaddressId::new(monitor_id, index).to_bytes();
+        match db_txn.get(self.id_to_label, &key) {
+            Ok(value_bytes) => Ok(Some(String::from_utf8_lossy(value_bytes).into_owned())),
+            Err(lmdb::Error::NotFound) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Returns the labeled subaddresses of a monitor, as (index, label) pairs
+    /// ordered by index.
+    pub fn get_labels(
+        &self,
+        db_txn: &impl Transaction,
+        monitor_id: &MonitorId,
+    ) -> Result<Vec<(u64, String)>, Error> {
+        let start_key = SubaddressId::new(monitor_id, 0).to_bytes();
+
+        let mut cursor = db_txn.open_ro_cursor(self.id_to_label)?;
+
+        let mut labels = Vec::new();
+        for result in cursor.iter_from(&start_key) {
+            let (db_key, db_value) = result?;
+            let subaddress_id = SubaddressId::try_from(db_key)?;
+            if subaddress_id.monitor_id != *monitor_id {
+                break;
+            }
+            labels.push((
+                subaddress_id.index,
+                String::from_utf8_lossy(db_value).into_owned(),
+            ));
+        }
+
+        Ok(labels)
+    }
+
+    /// Remove the labels of a given monitor id.
+    pub fn remove_labels<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        monitor_id: &MonitorId,
+    ) -> Result<(), Error> {
+        let start_key = SubaddressId::new(monitor_id, 0).to_bytes();
+
+        let mut cursor = db_txn.open_rw_cursor(self.id_to_label)?;
+
+        for (db_key, _db_value) in cursor.iter_from(&start_key).filter_map(|r| r.ok()) {
+            let subaddress_id = SubaddressId::try_from(db_key)?;
+            if subaddress_id.monitor_id == *monitor_id {
+                cursor.del(WriteFlags::empty())?;
+            } else {
+                break;
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::test_utils::get_test_monitor_data_and_id;
+    use mc_common::logger::test_with_logger;
     use rand::{rngs::StdRng, SeedableRng};
+    use tempdir::TempDir;
 
     #[test]
     fn test_subaddress_id_to_and_from_bytes() {
@@ -207,4 +300,82 @@ mod test {
         assert!(SubaddressId::try_from(&vec![0u8; 40][..]).is_ok());
         assert!(SubaddressId::try_from(&vec![0u8; 41][..]).is_err());
     }
+
+    #[test_with_logger]
+    fn test_subaddress_labels(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([124u8; 32]);
+        let (_, monitor_id0) = get_test_monitor_data_and_id(&mut rng);
+        let (_, monitor_id1) = get_test_monitor_data_and_id(&mut rng);
+
+        // The instance to test.
+        let db_tmp = TempDir::new("subaddress_store_db")
+            .expect("Could not make tempdir for subaddress store db");
+        let env = Arc::new(
+            Environment::new()
+                .set_max_dbs(10)
+                .set_map_size(10000000)
+                .open(db_tmp.path())
+                .unwrap(),
+        );
+        let subaddress_store = SubaddressStore::new(env.clone(), logger).unwrap();
+
+        let mut db_txn = env.begin_rw_txn().unwrap();
+        subaddress_store
+            .set_label(&mut db_txn, &monitor_id0, 5, "alice")
+            .unwrap();
+        subaddress_store
+            .set_label(&mut db_txn, &monitor_id0, 2, "bob")
+            .unwrap();
+        subaddress_store
+            .set_label(&mut db_txn, &monitor_id1, 2, "carol")
+            .unwrap();
+        db_txn.commit().unwrap();
+
+        // Labels are listed per monitor, ordered by subaddress index.
+        let db_txn = env.begin_ro_txn().unwrap();
+        assert_eq!(
+            subaddress_store.get_labels(&db_txn, &monitor_id0).unwrap(),
+            vec![(2, "bob".to_owned()), (5, "alice".to_owned())]
+        );
+        assert_eq!(
+            subaddress_store
+                .get_label(&db_txn, &monitor_id1, 2)
+                .unwrap(),
+            Some("carol".to_owned())
+        );
+        assert_eq!(
+            subaddress_store
+                .get_label(&db_txn, &monitor_id1, 5)
+                .unwrap(),
+            None
+        );
+        db_txn.commit().unwrap();
+
+        // Labels can be changed, and are removed by an empty label.
+        let mut db_txn = env.begin_rw_txn().unwrap();
+        subaddress_store
+            .set_label(&mut db_txn, &monitor_id0, 5, "dave")
+            .unwrap();
+        subaddress_store
+            .set_label(&mut db_txn, &monitor_id0, 2, "")
+            .unwrap();
+        assert_eq!(
+            subaddress_store.get_labels(&db_txn, &monitor_id0).unwrap(),
+            vec![(5, "dave".to_owned())]
+        );
+
+        // Removing the labels of a monitor does not affect other monitors.
+        subaddress_store
+            .remove_labels(&mut db_txn, &monitor_id0)
+            .unwrap();
+        assert!(subaddress_store
+            .get_labels(&db_txn, &monitor_id0)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            subaddress_store.get_labels(&db_txn, &monitor_id1).unwrap(),
+            vec![(2, "carol".to_owned())]
+        );
+        db_txn.commit().unwrap();
+    }
 }
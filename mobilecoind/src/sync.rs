@@ -21,6 +21,7 @@
 //! queue to be picked up by the next available worker thread.
 
 use crate::{
+    counters,
     database::Database,
     error::Error,
     monitor_store::{MonitorData, MonitorId},
@@ -129,6 +130,7 @@ impl SyncThread {
                 .spawn(move || {
                     log::debug!(logger, "Syncthread started.");
 
+                    let mut last_num_blocks = 0;
                     loop {
                         if thread_stop_requested.load(Ordering::SeqCst) {
                             log::debug!(logger, "SyncThread stop requested.");
@@ -140,6 +142,12 @@ impl SyncThread {
                             .num_blocks()
                             .expect("failed getting number of blocks");
 
+                        // The sync progress of the monitors changes with the ledger.
+                        if num_blocks != last_num_blocks {
+                            mobilecoind_db.notify_monitor_changes();
+                            last_num_blocks = num_blocks;
+                        }
+
                         // A flag to track whether we sent a message to our work queue.
                         // If we sent a message, that means new blocks have arrived and we can skip
                         // sleeping. If no new blocks arrived, and we
@@ -147,12 +155,20 @@ impl SyncThread {
                         // a bit so that we do not use 100% cpu.
                         let mut message_sent = false;
 
+                        let monitor_map = mobilecoind_db
+                            .get_monitor_map()
+                            .expect("failed getting monitor map");
+                        counters::NUM_MONITORS.set(monitor_map.len() as i64);
+                        counters::DB_FILE_SIZE
+                            .set(mobilecoind_db.db_file_size().unwrap_or(0) as i64);
+
                         // Go over our list of monitors and see which one needs to process these
                         // blocks.
-                        for (monitor_id, monitor_data) in mobilecoind_db
-                            .get_monitor_map()
-                            .expect("failed getting monitor map")
-                        {
+                        for (monitor_id, monitor_data) in monitor_map {
+                            counters::MONITOR_NEXT_BLOCK
+                                .with_label_values(&[&monitor_id.to_string()])
+                                .set(monitor_data.next_block as i64);
+
                             // If there are no new blocks for this monitor, don't do anything.
                             if monitor_data.next_block >= num_blocks {
                                 continue;
@@ -315,6 +331,8 @@ fn sync_monitor(
             monitor_id,
         );
 
+        let _timer = counters::BLOCK_PROCESSING_TIME.start_timer();
+
         // Match tx outs into UTXOs.
         let utxos = match_tx_outs_into_utxos(
             mobilecoind_db,
@@ -331,6 +349,10 @@ fn sync_monitor(
             &utxos,
             &block_contents.key_images,
         )?;
+
+        counters::MONITOR_BLOCKS_PROCESSED
+            .with_label_values(&[&monitor_id.to_string()])
+            .inc();
     }
 
     Ok(SyncMonitorOk::MoreBlocksPotentiallyAvailable)
@@ -344,7 +366,7 @@ fn match_tx_outs_into_utxos(
     monitor_data: &MonitorData,
     logger: &Logger,
 ) -> Result<Vec<UnspentTxOut>, Error> {
-    let account_key = &monitor_data.account_key;
+    let view_private_key = *monitor_data.view_account_key()?.view_private_key();
     let mut results = Vec::new();
 
     for tx_out in outputs {
@@ -353,7 +375,7 @@ fn match_tx_outs_into_utxos(
         let tx_public_key = RistrettoPublic::try_from(&tx_out.public_key)?;
 
         let subaddress_spk = SubaddressSPKId::from(&recover_public_subaddress_spend_key(
-            account_key.view_private_key(),
+            &view_private_key,
             &tx_out_target_key,
             &tx_public_key,
         ));
@@ -379,21 +401,27 @@ fn match_tx_outs_into_utxos(
         // Sanity - we should only get a match for our own monitor id.
         assert_eq!(monitor_id, &subaddress_id.monitor_id);
 
-        let shared_secret =
-            get_tx_out_shared_secret(account_key.view_private_key(), &tx_public_key);
+        let shared_secret = get_tx_out_shared_secret(&view_private_key, &tx_public_key);
 
         let (amount, _blinding) = tx_out
             .masked_amount
             .get_value(&shared_secret)
             .expect("Malformed amount"); // TODO
 
-        let onetime_private_key = recover_onetime_private_key(
-            &tx_public_key,
-            account_key.view_private_key(),
-            &account_key.subaddress_spend_private(subaddress_id.index),
-        );
-
-        let key_image = KeyImage::from(&onetime_private_key);
+        let key_image = match monitor_data.account_key.as_ref() {
+            Some(account_key) => {
+                let onetime_private_key = recover_onetime_private_key(
+                    &tx_public_key,
+                    account_key.view_private_key(),
+                    &account_key.subaddress_spend_private(subaddress_id.index),
+                );
+                KeyImage::from(&onetime_private_key)
+            }
+            // Computing the key image requires the spend private key. Watch-only
+            // outputs are identified by their tx public key instead, which means they
+            // will never be matched by the key images of a spend.
+            None => KeyImage::from(*tx_out.public_key.as_bytes()),
+        };
 
         results.push(UnspentTxOut {
             tx_out: tx_out.clone(),
@@ -31,6 +31,7 @@ use mc_watcher::watcher_db::WatcherDB;
 use std::{
     str::FromStr,
     sync::{Arc, RwLock},
+    time::Duration,
 };
 use tempdir::TempDir;
 
@@ -42,6 +43,9 @@ pub const DEFAULT_PER_RECIPIENT_AMOUNT: u64 = 5_000 * 1_000_000_000_000;
 /// Number of initial blocks generated by `get_testing_environment`;
 pub const GET_TESTING_ENVIRONMENT_NUM_BLOCKS: usize = 10;
 
+/// How long the test server remembers idempotency keys.
+pub const IDEMPOTENCY_RETENTION: Duration = Duration::from_secs(3600);
+
 /// Sets up ledger_db and mobilecoind_db. Each block will contains one txo per
 /// recipient.
 ///
@@ -129,6 +133,7 @@ pub fn setup_server<FPR: FogPubkeyResolver + Default + Send + Sync + 'static>(
     mobilecoind_db: Database,
     watcher_db: Option<WatcherDB>,
     fog_resolver_factory: Option<Arc<dyn Fn(&[FogUri]) -> Result<FPR, String> + Send + Sync>>,
+    trusted_fog_authorities: Vec<Vec<u8>>,
     uri: &MobilecoindUri,
 ) -> (
     Service,
@@ -172,6 +177,8 @@ pub fn setup_server<FPR: FogPubkeyResolver + Default + Send + Sync + 'static>(
         watcher_db,
         transactions_manager,
         network_state,
+        trusted_fog_authorities,
+        IDEMPOTENCY_RETENTION,
         uri,
         None,
         logger,
@@ -238,6 +245,7 @@ pub fn get_testing_environment(
         mobilecoind_db.clone(),
         None,
         None,
+        vec![],
         &uri,
     );
     log::debug!(logger, "Setting up client {:?}", port);
@@ -332,6 +332,53 @@ impl UtxoStore {
             .collect()
     }
 
+    /// Get a page of the UnspentTxOuts of a given address that match a
+    /// filter, in UtxoId order, along with the UtxoId the next page starts at
+    /// (`None` once there are no more).
+    ///
+    /// Only the UnspentTxOuts from `start_utxo_id` on are decoded, so large
+    /// lists are cheap to page through.
+    ///
+    /// # Arguments
+    /// * `start_utxo_id` - The UtxoId to start at (`None` starts at the first
+    ///   one).
+    /// * `limit` - The maximum number of UnspentTxOuts to return.
+    /// * `filter` - Which UnspentTxOuts to return.
+    pub fn get_utxos_page(
+        &self,
+        db_txn: &impl Transaction,
+        monitor_id: &MonitorId,
+        index: u64,
+        start_utxo_id: Option<&UtxoId>,
+        limit: usize,
+        filter: impl Fn(&UnspentTxOut) -> bool,
+    ) -> Result<(Vec<UnspentTxOut>, Option<UtxoId>), Error> {
+        let subaddress_id = SubaddressId::new(monitor_id, index);
+        let mut cursor = db_txn.open_ro_cursor(self.subaddress_id_to_utxo_id)?;
+
+        let mut page = Vec::new();
+        for result in cursor.iter_dup_of(&subaddress_id.to_vec()) {
+            let (_db_key, utxo_id_bytes) = result?;
+            let utxo_id = UtxoId::try_from(utxo_id_bytes)?;
+
+            // UtxoIds are sorted, so skipping the ones before the start only
+            // compares keys.
+            if start_utxo_id.map_or(false, |start| utxo_id < *start) {
+                continue;
+            }
+            if page.len() >= limit {
+                return Ok((page, Some(utxo_id)));
+            }
+
+            let utxo = self.get_utxo_by_id(db_txn, &utxo_id)?;
+            if filter(&utxo) {
+                page.push(utxo);
+            }
+        }
+
+        Ok((page, None))
+    }
+
     /// Get subaddress id by utxo id.
     pub fn get_subaddress_id_by_utxo_id(
         &self,
@@ -899,6 +946,80 @@ mod test {
         }
     }
 
+    #[test_with_logger]
+    fn test_get_utxos_page(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([123u8; 32]);
+        let (env, _ledger_db, utxo_store, utxos) = setup_test_utxo_store(&mut rng, &logger);
+        let (_monitor_data, monitor_id) = get_test_monitor_data_and_id(&mut rng);
+
+        {
+            let mut db_txn = env.begin_rw_txn().unwrap();
+            for utxo in utxos.iter() {
+                utxo_store
+                    .append_utxo(&mut db_txn, &monitor_id, 0, utxo)
+                    .unwrap();
+            }
+            db_txn.commit().unwrap();
+        }
+
+        let db_txn = env.begin_ro_txn().unwrap();
+
+        // Pages of two, in UtxoId order.
+        let mut expected_utxo_ids: Vec<UtxoId> = utxos.iter().map(UtxoId::from).collect();
+        expected_utxo_ids.sort();
+
+        let mut paged_utxo_ids = Vec::new();
+        let mut start_utxo_id = None;
+        loop {
+            let (page, next_utxo_id) = utxo_store
+                .get_utxos_page(&db_txn, &monitor_id, 0, start_utxo_id.as_ref(), 2, |_| true)
+                .unwrap();
+            assert!(page.len() <= 2);
+            paged_utxo_ids.extend(page.iter().map(UtxoId::from));
+            if next_utxo_id.is_none() {
+                break;
+            }
+            start_utxo_id = next_utxo_id;
+        }
+        assert_eq!(paged_utxo_ids, expected_utxo_ids);
+
+        // Filtered out UnspentTxOuts don't count towards the limit.
+        let (page, next_utxo_id) = utxo_store
+            .get_utxos_page(&db_txn, &monitor_id, 0, None, usize::MAX, |utxo| {
+                utxo.value % 2 == 0
+            })
+            .unwrap();
+        assert_eq!(
+            HashSet::from_iter(page),
+            HashSet::from_iter(utxos.iter().filter(|utxo| utxo.value % 2 == 0).cloned()),
+        );
+        assert_eq!(next_utxo_id, None);
+
+        // Starting at the last UtxoId only returns its UnspentTxOut.
+        let (page, next_utxo_id) = utxo_store
+            .get_utxos_page(
+                &db_txn,
+                &monitor_id,
+                0,
+                expected_utxo_ids.last(),
+                usize::MAX,
+                |_| true,
+            )
+            .unwrap();
+        assert_eq!(
+            page.iter().map(UtxoId::from).collect::<Vec<_>>(),
+            vec![*expected_utxo_ids.last().unwrap()],
+        );
+        assert_eq!(next_utxo_id, None);
+
+        // Other subaddresses have no UnspentTxOuts.
+        let (page, next_utxo_id) = utxo_store
+            .get_utxos_page(&db_txn, &monitor_id, 1, None, usize::MAX, |_| true)
+            .unwrap();
+        assert!(page.is_empty());
+        assert_eq!(next_utxo_id, None);
+    }
+
     #[test_with_logger]
     fn test_update_attempted_spend(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([123u8; 32]);
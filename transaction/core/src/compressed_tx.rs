@@ -0,0 +1,274 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! A compact encoding of transactions, for sending them to consensus.
+//!
+//! Most of the size of a transaction is in the membership proofs of its ring
+//! elements, and the proofs of TxOuts which are near each other in the ledger
+//! share most of their elements. A `CompressedTx` stores each distinct element
+//! once, and the proofs as indices into the table of elements.
+//!
+//! Clients propose a `CompressedTx` to consensus, marking the message with
+//! `COMPRESSED_TX_AAD`, and the consensus enclave converts it back with
+//! `Tx::try_from` when it receives it. The conversion is lossless: the
+//! transaction has the same hash and signature.
+
+use crate::tx::{Tx, TxOutMembershipElement, TxOutMembershipProof};
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::convert::TryFrom;
+use displaydoc::Display;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+
+/// The additional authenticated data of a client message proposing a
+/// `CompressedTx` to consensus, rather than a `Tx`.
+pub const COMPRESSED_TX_AAD: &[u8] = b"CompressedTx";
+
+/// Membership proofs, with the elements which several of them share stored
+/// once.
+#[derive(Clone, Deserialize, Eq, Message, PartialEq, Serialize)]
+pub struct CompressedTxOutMembershipProofs {
+    /// The distinct elements of the proofs, in order of first appearance.
+    #[prost(message, repeated, tag = "1")]
+    pub elements: Vec<TxOutMembershipElement>,
+
+    /// The proofs, in order.
+    #[prost(message, repeated, tag = "2")]
+    pub proofs: Vec<CompressedTxOutMembershipProof>,
+}
+
+/// A membership proof whose elements are stored in a
+/// `CompressedTxOutMembershipProofs`.
+#[derive(Clone, Deserialize, Eq, Message, PartialEq, Serialize)]
+pub struct CompressedTxOutMembershipProof {
+    /// Index of the TxOut that this proof refers to.
+    #[prost(uint64, tag = "1")]
+    pub index: u64,
+
+    /// Index of the last TxOut at the time the proof was created.
+    #[prost(uint64, tag = "2")]
+    pub highest_index: u64,
+
+    /// The indices of the elements of the proof in
+    /// `CompressedTxOutMembershipProofs::elements`, in order.
+    #[prost(uint32, repeated, tag = "3")]
+    pub element_indices: Vec<u32>,
+}
+
+impl CompressedTxOutMembershipProofs {
+    /// Compress membership proofs.
+    pub fn new<'a>(proofs: impl IntoIterator<Item = &'a TxOutMembershipProof>) -> Self {
+        let mut elements = Vec::new();
+        let mut element_indices: BTreeMap<&TxOutMembershipElement, u32> = BTreeMap::new();
+        let proofs = proofs
+            .into_iter()
+            .map(|proof| CompressedTxOutMembershipProof {
+                index: proof.index,
+                highest_index: proof.highest_index,
+                element_indices: proof
+                    .elements
+                    .iter()
+                    .map(|element| {
+                        *element_indices.entry(element).or_insert_with(|| {
+                            elements.push(element.clone());
+                            (elements.len() - 1) as u32
+                        })
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Self { elements, proofs }
+    }
+
+    /// Get the membership proofs back, in order.
+    pub fn decompress(&self) -> Result<Vec<TxOutMembershipProof>, CompressedTxError> {
+        self.proofs
+            .iter()
+            .map(|proof| {
+                let elements = proof
+                    .element_indices
+                    .iter()
+                    .map(|index| {
+                        self.elements
+                            .get(*index as usize)
+                            .cloned()
+                            .ok_or(CompressedTxError::ElementIndexOutOfBounds(*index))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(TxOutMembershipProof {
+                    index: proof.index,
+                    highest_index: proof.highest_index,
+                    elements,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A transaction whose membership proofs are compressed.
+#[derive(Clone, Deserialize, Eq, Message, PartialEq, Serialize)]
+pub struct CompressedTx {
+    /// The transaction, without the membership proofs of its inputs.
+    #[prost(message, required, tag = "1")]
+    pub tx: Tx,
+
+    /// The membership proofs of all of the inputs, in order.
+    #[prost(message, required, tag = "2")]
+    pub proofs: CompressedTxOutMembershipProofs,
+}
+
+impl From<&Tx> for CompressedTx {
+    fn from(src: &Tx) -> Self {
+        let proofs = CompressedTxOutMembershipProofs::new(
+            src.prefix
+                .inputs
+                .iter()
+                .flat_map(|input| input.proofs.iter()),
+        );
+        let mut tx = src.clone();
+        for input in tx.prefix.inputs.iter_mut() {
+            input.proofs.clear();
+        }
+        Self { tx, proofs }
+    }
+}
+
+impl TryFrom<CompressedTx> for Tx {
+    type Error = CompressedTxError;
+
+    fn try_from(src: CompressedTx) -> Result<Self, Self::Error> {
+        let mut tx = src.tx;
+        if tx
+            .prefix
+            .inputs
+            .iter()
+            .any(|input| !input.proofs.is_empty())
+        {
+            return Err(CompressedTxError::UncompressedProofs);
+        }
+
+        let mut proofs = src.proofs.decompress()?.into_iter();
+        let expected_num_proofs: usize =
+            tx.prefix.inputs.iter().map(|input| input.ring.len()).sum();
+        if proofs.len() != expected_num_proofs {
+            return Err(CompressedTxError::WrongNumberOfProofs(
+                expected_num_proofs,
+                proofs.len(),
+            ));
+        }
+
+        // Each input gets as many proofs as it has ring elements
+        for input in tx.prefix.inputs.iter_mut() {
+            input.proofs = proofs.by_ref().take(input.ring.len()).collect();
+        }
+        Ok(tx)
+    }
+}
+
+/// An error that can occur when decompressing a transaction
+#[derive(Clone, Debug, Display, Eq, PartialEq)]
+pub enum CompressedTxError {
+    /// A proof refers to element {0}, which does not exist
+    ElementIndexOutOfBounds(u32),
+    /// The ring elements need {0} proofs, but there are {1}
+    WrongNumberOfProofs(usize, usize),
+    /// The inputs of the transaction already have proofs
+    UncompressedProofs,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        membership_proofs::Range,
+        tx::{TxIn, TxOut},
+    };
+
+    fn element(from: u64, to: u64) -> TxOutMembershipElement {
+        TxOutMembershipElement::new(Range::new(from, to).unwrap(), [(from + to) as u8; 32])
+    }
+
+    // Proofs of the first four TxOuts of a ledger with eight TxOuts
+    fn proofs() -> Vec<TxOutMembershipProof> {
+        (0..4)
+            .map(|index| TxOutMembershipProof {
+                index,
+                highest_index: 7,
+                elements: vec![
+                    element(index, index),
+                    element(index ^ 1, index ^ 1),
+                    element(2 - index / 2 * 2, 3 - index / 2 * 2),
+                    element(4, 7),
+                ],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compress_and_decompress_proofs() {
+        let proofs = proofs();
+        let compressed = CompressedTxOutMembershipProofs::new(&proofs);
+
+        // The leaves appear twice each, and the two upper elements are shared
+        assert_eq!(compressed.elements.len(), 4 + 2 + 1);
+        assert_eq!(compressed.decompress().unwrap(), proofs);
+        assert!(
+            mc_util_serial::encode(&compressed).len()
+                < proofs
+                    .iter()
+                    .map(|proof| mc_util_serial::encode(proof).len())
+                    .sum::<usize>()
+        );
+
+        let mut bad = compressed;
+        bad.proofs[2].element_indices[0] = 7;
+        assert_eq!(
+            bad.decompress(),
+            Err(CompressedTxError::ElementIndexOutOfBounds(7))
+        );
+    }
+
+    #[test]
+    fn test_compressed_tx_round_trip() {
+        let proofs = proofs();
+        let mut tx = Tx::default();
+        tx.prefix.inputs = vec![
+            TxIn {
+                ring: vec![TxOut::default(); 3],
+                proofs: proofs[..3].to_vec(),
+                input_rules: None,
+            },
+            TxIn {
+                ring: vec![TxOut::default()],
+                proofs: proofs[3..].to_vec(),
+                input_rules: None,
+            },
+        ];
+
+        let compressed = CompressedTx::from(&tx);
+        assert!(compressed
+            .tx
+            .prefix
+            .inputs
+            .iter()
+            .all(|input| input.proofs.is_empty()));
+
+        let bytes = mc_util_serial::encode(&compressed);
+        let decoded: CompressedTx = mc_util_serial::decode(&bytes).unwrap();
+        assert_eq!(Tx::try_from(decoded).unwrap(), tx);
+
+        let mut missing_proof = compressed.clone();
+        missing_proof.proofs.proofs.pop();
+        assert_eq!(
+            Tx::try_from(missing_proof),
+            Err(CompressedTxError::WrongNumberOfProofs(4, 3))
+        );
+
+        let mut uncompressed = compressed;
+        uncompressed.tx = tx;
+        assert_eq!(
+            Tx::try_from(uncompressed),
+            Err(CompressedTxError::UncompressedProofs)
+        );
+    }
+}
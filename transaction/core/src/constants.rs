@@ -16,6 +16,11 @@ pub const MAX_INPUTS: u64 = 16;
 /// Each transaction must contain no more than this many outputs.
 pub const MAX_OUTPUTS: u64 = 16;
 
+/// Each output may contain no more than this many fog hints, in addition to
+/// its main fog hint. This is enough to address an output to the old and the
+/// new fog deployments of a recipient who is migrating between them.
+pub const MAX_ADDITIONAL_FOG_HINTS: usize = 2;
+
 /// Maximum number of blocks in the future a transaction's tombstone block can
 /// be set to.
 ///
@@ -41,6 +41,13 @@ pub const TXOUT_CONFIRMATION_NUMBER_DOMAIN_TAG: &str = "mc_tx_out_confirmation_n
 /// Domain separator for computing the extended message digest
 pub const EXTENDED_MESSAGE_DOMAIN_TAG: &str = "mc_extended_message";
 
+/// Domain separator for computing the tx summary digest
+pub const TX_SUMMARY_DOMAIN_TAG: &str = "mc_tx_summary";
+
+/// Domain separator for computing the digest of the extended message and of the
+/// tx summary, which the MLSAGs sign from block version 4
+pub const EXTENDED_MESSAGE_AND_TX_SUMMARY_DOMAIN_TAG: &str = "mc_extended_message_and_tx_summary";
+
 /// Domain separator for hashing MintConfigTxPrefixs
 pub const MINT_CONFIG_TX_PREFIX_DOMAIN_TAG: &str = "mc_mint_config_tx_prefix";
 
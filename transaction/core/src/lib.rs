@@ -16,13 +16,16 @@ extern crate std;
 extern crate lazy_static;
 
 mod amount;
+mod compressed_tx;
 mod domain_separators;
 mod input_rules;
 mod memo;
+mod scanning;
 mod signed_contingent_input;
 mod token;
 mod tx_error;
 mod tx_out_gift_code;
+mod tx_summary;
 
 pub mod constants;
 pub mod encrypted_fog_hint;
@@ -38,8 +41,13 @@ pub mod validation;
 pub mod proptest_fixtures;
 
 pub use amount::{AmountError, MaskedAmount};
+pub use compressed_tx::{
+    CompressedTx, CompressedTxError, CompressedTxOutMembershipProof,
+    CompressedTxOutMembershipProofs, COMPRESSED_TX_AAD,
+};
 pub use input_rules::{InputRuleError, InputRules};
 pub use memo::{EncryptedMemo, MemoError, MemoPayload};
+pub use scanning::{ScanMatch, ViewKeyScanner};
 pub use signed_contingent_input::{
     SignedContingentInput, SignedContingentInputError, UnmaskedAmount,
 };
@@ -47,6 +55,10 @@ pub use token::{tokens, Token};
 pub use tx::MemoContext;
 pub use tx_error::{NewMemoError, NewTxError, ViewKeyMatchError};
 pub use tx_out_gift_code::TxOutGiftCode;
+pub use tx_summary::{
+    compute_mlsag_signing_digest, TxOutSummary, TxOutSummaryUnblindingData, TxSummary,
+    TxSummaryError, TxSummaryStreamingDigest,
+};
 
 // Re-export from transaction-types, and some from RingSignature crate.
 pub use mc_crypto_ring_signature::{Commitment, CompressedCommitment};
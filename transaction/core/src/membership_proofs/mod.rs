@@ -310,6 +310,91 @@ pub fn derive_proof_at_index(
     Ok(TxOutMembershipProof::new(index, index, derived_elements))
 }
 
+/// Compute a proof-of-membership against the root hash of the tree at the time
+/// another TxOut was added.
+///
+/// This generalizes `derive_proof_at_index` to TxOuts which are not the last
+/// member of the earlier tree, so that proofs for several TxOuts can be made to
+/// share the same root hash.
+///
+/// # Arguments
+/// * `initial_proof` - Proof-of-membership for the TxOut at a given index.
+///   Assumed to be valid.
+/// * `last_proof` - Proof-of-membership, in the same tree as `initial_proof`,
+///   for the last TxOut of the earlier tree. Its index must not be less than
+///   the index of `initial_proof`. Assumed to be valid.
+///
+/// # Returns
+/// Returns a proof for the TxOut of `initial_proof` when the TxOut of
+/// `last_proof` was the last member added to the tree.
+pub fn derive_proof_at_highest_index(
+    initial_proof: &TxOutMembershipProof,
+    last_proof: &TxOutMembershipProof,
+) -> Result<TxOutMembershipProof, Error> {
+    let index: u64 = initial_proof.index;
+    let highest_index: u64 = last_proof.index;
+    if index > highest_index {
+        return Err(Error::HighestIndexMismatch);
+    }
+
+    // The proof of the last TxOut in the earlier tree. Its elements, combined
+    // in order, give the hashes of all the nodes above that TxOut.
+    let derived_last_proof = derive_proof_at_index(last_proof)?;
+    let derived_root_range = derived_last_proof
+        .elements
+        .last()
+        .ok_or(Error::MissingLeafHash(highest_index))?
+        .range;
+
+    // Elements of the derived proof.
+    let mut derived_elements = Vec::<TxOutMembershipElement>::default();
+
+    // This assumes that `elements` is in the correct order to be combined.
+    for (element_idx, element) in initial_proof.elements.iter().enumerate() {
+        if element.range > derived_root_range {
+            // This range is not part of the derived proof.
+            continue;
+        }
+
+        let hash = if element.range.from > highest_index {
+            // This range exceeds `highest_index`.
+            TxOutMembershipHash::from(hash_nil())
+        } else if element.range.to <= highest_index {
+            // This range is unchanged. Re-use the supplied hash.
+            element.hash.clone()
+        } else if element.range.from <= index && index <= element.range.to {
+            // An internal node that contains `index`.
+            // This is unexpected, none of the proof elements should cover index.
+            return Err(Error::UnexpectedMembershipElement(element_idx));
+        } else {
+            // An internal node that contains `highest_index`, whose hash changed
+            // since. Compute it from the proof of the last TxOut.
+            let mut node = derived_last_proof.elements[0].clone();
+            for next in derived_last_proof.elements[1..].iter() {
+                if node.range == element.range {
+                    break;
+                }
+                node = compose_adjacent_membership_elements(&node, next)
+                    .map_err(|_| Error::UnexpectedMembershipElement(element_idx))?;
+            }
+            if node.range != element.range {
+                return Err(Error::UnexpectedMembershipElement(element_idx));
+            }
+            node.hash
+        };
+        derived_elements.push(TxOutMembershipElement {
+            range: element.range,
+            hash,
+        });
+    }
+
+    Ok(TxOutMembershipProof::new(
+        index,
+        highest_index,
+        derived_elements,
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     // TODO: the tests for derive_proof_at_index are currently in
@@ -23,8 +23,8 @@ pub fn validate_tombstone(
 ) -> Result<(), Error> {
     transaction_validate_tombstone(current_block_index, tombstone_block_index).map_err(|err| {
         match err {
-            TransactionValidationError::TombstoneBlockExceeded => Error::TombstoneBlockExceeded,
-            TransactionValidationError::TombstoneBlockTooFar => Error::TombstoneBlockTooFar,
+            TransactionValidationError::TombstoneBlockExceeded(..) => Error::TombstoneBlockExceeded,
+            TransactionValidationError::TombstoneBlockTooFar(..) => Error::TombstoneBlockTooFar,
             _ => Error::Unknown, /* This should never happen since validate_tombstone only
                                   * returns one of the two error types above */
         }
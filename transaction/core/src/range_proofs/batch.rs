@@ -0,0 +1,451 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Verification of many aggregated range proofs at once.
+//!
+//! Verifying a Bulletproof amounts to checking that one multiscalar
+//! multiplication is the identity. The checks of many proofs can be combined
+//! into one, by weighting each of them with a random scalar and adding them
+//! up: all of the proofs share the Bulletproofs generators and the blinding
+//! base point, so the combined multiplication is much smaller than the
+//! separate ones put together.
+//!
+//! `bulletproofs_og` doesn't expose the verification equation, so it is
+//! reconstructed here from the serialized proofs, following
+//! `RangeProof::verify_multiple_with_rng`. If the combined check fails, each
+//! proof is checked with `check_range_proofs`, to find the invalid one.
+
+#![allow(non_snake_case)]
+
+use super::{check_range_proofs, error::Error, BP_GENERATORS};
+use crate::domain_separators::BULLETPROOF_DOMAIN_TAG;
+use alloc::{vec, vec::Vec};
+use bulletproofs_og::{ProofError, RangeProof};
+use core::iter;
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+    traits::{IsIdentity, VartimeMultiscalarMul},
+};
+use mc_crypto_ring_signature::PedersenGens;
+use merlin::Transcript;
+use rand_core::{CryptoRng, RngCore};
+
+// The number of bits of the values in a range proof
+const BIT_SIZE: usize = 64;
+
+/// A range proof to verify with `check_range_proofs_batch`, with the
+/// arguments `check_range_proofs` takes.
+#[derive(Clone, Debug)]
+pub struct RangeProofVerification {
+    /// The range proof
+    pub range_proof: RangeProof,
+    /// The commitments to the values which the proof is for
+    pub commitments: Vec<CompressedRistretto>,
+    /// The Pedersen generators on which the commitments are based
+    pub pedersen_generators: PedersenGens,
+}
+
+/// Verifies many aggregated 64-bit range proofs together, e.g. the range
+/// proofs of all the transactions of a block.
+///
+/// This accepts exactly the proofs which `check_range_proofs` accepts, but
+/// checks all of them with one multiscalar multiplication.
+///
+/// Returns the index of the first proof which is not valid, and why.
+///
+/// # Arguments
+/// `items` - The range proofs, with their commitments and generators.
+/// `rng` - Randomness.
+pub fn check_range_proofs_batch<T: RngCore + CryptoRng>(
+    items: &[RangeProofVerification],
+    rng: &mut T,
+) -> Result<(), (usize, Error)> {
+    // A single proof gains nothing from batching
+    if items.len() > 1 && batch_check(items, rng).is_ok() {
+        return Ok(());
+    }
+
+    items.iter().enumerate().try_for_each(|(index, item)| {
+        check_range_proofs(
+            &item.range_proof,
+            &item.commitments,
+            &item.pedersen_generators,
+            rng,
+        )
+        .map_err(|err| (index, err))
+    })
+}
+
+// The parts of a serialized range proof
+struct ProofParts {
+    A: CompressedRistretto,
+    S: CompressedRistretto,
+    T_1: CompressedRistretto,
+    T_2: CompressedRistretto,
+    t_x: Scalar,
+    t_x_blinding: Scalar,
+    e_blinding: Scalar,
+    L_vec: Vec<CompressedRistretto>,
+    R_vec: Vec<CompressedRistretto>,
+    a: Scalar,
+    b: Scalar,
+}
+
+impl ProofParts {
+    // Split a proof into its parts. The layout is the one of
+    // `RangeProof::to_bytes`: A, S, T_1, T_2, t_x, t_x_blinding, e_blinding,
+    // then the inner product proof, as pairs L_i, R_i followed by a and b.
+    fn new(range_proof: &RangeProof) -> Result<Self, ProofError> {
+        let bytes = range_proof.to_bytes();
+        if bytes.len() % 32 != 0 || bytes.len() < 9 * 32 || (bytes.len() / 32 - 9) % 2 != 0 {
+            return Err(ProofError::FormatError);
+        }
+        let chunk = |index: usize| &bytes[32 * index..32 * (index + 1)];
+        let point = |index: usize| CompressedRistretto::from_slice(chunk(index));
+        let scalar = |index: usize| {
+            let mut buf = [0u8; 32];
+            buf.copy_from_slice(chunk(index));
+            Scalar::from_canonical_bytes(buf).ok_or(ProofError::FormatError)
+        };
+
+        let lg_n = (bytes.len() / 32 - 9) / 2;
+        Ok(Self {
+            A: point(0),
+            S: point(1),
+            T_1: point(2),
+            T_2: point(3),
+            t_x: scalar(4)?,
+            t_x_blinding: scalar(5)?,
+            e_blinding: scalar(6)?,
+            L_vec: (0..lg_n).map(|i| point(7 + 2 * i)).collect(),
+            R_vec: (0..lg_n).map(|i| point(8 + 2 * i)).collect(),
+            a: scalar(7 + 2 * lg_n)?,
+            b: scalar(8 + 2 * lg_n)?,
+        })
+    }
+}
+
+// The transcript operations of the range proof protocol
+trait RangeProofTranscript {
+    fn append_point(&mut self, label: &'static [u8], point: &CompressedRistretto);
+    fn validate_and_append_point(
+        &mut self,
+        label: &'static [u8],
+        point: &CompressedRistretto,
+    ) -> Result<(), ProofError>;
+    fn append_scalar(&mut self, label: &'static [u8], scalar: &Scalar);
+    fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar;
+}
+
+impl RangeProofTranscript for Transcript {
+    fn append_point(&mut self, label: &'static [u8], point: &CompressedRistretto) {
+        self.append_message(label, point.as_bytes());
+    }
+
+    fn validate_and_append_point(
+        &mut self,
+        label: &'static [u8],
+        point: &CompressedRistretto,
+    ) -> Result<(), ProofError> {
+        if point.is_identity() {
+            return Err(ProofError::VerificationError);
+        }
+        self.append_point(label, point);
+        Ok(())
+    }
+
+    fn append_scalar(&mut self, label: &'static [u8], scalar: &Scalar) {
+        self.append_message(label, scalar.as_bytes());
+    }
+
+    fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar {
+        let mut buf = [0u8; 64];
+        self.challenge_bytes(label, &mut buf);
+        Scalar::from_bytes_mod_order_wide(&buf)
+    }
+}
+
+// The scalars and points which one proof contributes to the combined check,
+// already multiplied by the weight of the proof
+struct WeightedCheck {
+    // Scalars of the points which are specific to this proof: A, S, T_1, T_2,
+    // L_vec, R_vec, the value generator and the commitments
+    scalars: Vec<Scalar>,
+    points: Vec<CompressedRistretto>,
+    value_generator_scalar: Scalar,
+    value_generator: RistrettoPoint,
+    // Scalars of the shared points: the blinding base point, and the
+    // Bulletproofs generators of the first m parties
+    blinding_scalar: Scalar,
+    g_scalars: Vec<Scalar>,
+    h_scalars: Vec<Scalar>,
+}
+
+impl WeightedCheck {
+    fn new<T: RngCore + CryptoRng>(
+        item: &RangeProofVerification,
+        rng: &mut T,
+    ) -> Result<Self, ProofError> {
+        let proof = ProofParts::new(&item.range_proof)?;
+        let n = BIT_SIZE;
+        let m = item.commitments.len().next_power_of_two();
+        if BP_GENERATORS.gens_capacity < n || BP_GENERATORS.party_capacity < m {
+            return Err(ProofError::InvalidGeneratorsLength);
+        }
+        let nm = n * m;
+        let lg_nm = proof.L_vec.len();
+        if lg_nm >= 32 || nm != 1 << lg_nm {
+            return Err(ProofError::VerificationError);
+        }
+
+        // Check the commitments padded to a power of two, as
+        // `check_range_proofs` does
+        let last_commitment = *item.commitments.last().ok_or(ProofError::FormatError)?;
+        let commitments: Vec<CompressedRistretto> = item
+            .commitments
+            .iter()
+            .cloned()
+            .chain(iter::repeat(last_commitment))
+            .take(m)
+            .collect();
+
+        let mut transcript = Transcript::new(BULLETPROOF_DOMAIN_TAG.as_ref());
+        transcript.append_message(b"dom-sep", b"rangeproof v1");
+        transcript.append_u64(b"n", n as u64);
+        transcript.append_u64(b"m", m as u64);
+        for commitment in &commitments {
+            transcript.append_point(b"V", commitment);
+        }
+        transcript.validate_and_append_point(b"A", &proof.A)?;
+        transcript.validate_and_append_point(b"S", &proof.S)?;
+        let y = transcript.challenge_scalar(b"y");
+        let z = transcript.challenge_scalar(b"z");
+        let zz = z * z;
+        transcript.validate_and_append_point(b"T_1", &proof.T_1)?;
+        transcript.validate_and_append_point(b"T_2", &proof.T_2)?;
+        let x = transcript.challenge_scalar(b"x");
+        transcript.append_scalar(b"t_x", &proof.t_x);
+        transcript.append_scalar(b"t_x_blinding", &proof.t_x_blinding);
+        transcript.append_scalar(b"e_blinding", &proof.e_blinding);
+        let w = transcript.challenge_scalar(b"w");
+
+        // The challenges of the inner product proof
+        transcript.append_message(b"dom-sep", b"ipp v1");
+        transcript.append_u64(b"n", nm as u64);
+        // These are squared once they are all known
+        let mut u_sq = Vec::with_capacity(lg_nm);
+        for (L, R) in proof.L_vec.iter().zip(proof.R_vec.iter()) {
+            transcript.validate_and_append_point(b"L", L)?;
+            transcript.validate_and_append_point(b"R", R)?;
+            u_sq.push(transcript.challenge_scalar(b"u"));
+        }
+        let mut u_inv_sq = u_sq.clone();
+        let all_inv = Scalar::batch_invert(&mut u_inv_sq);
+        for (u, u_inv) in u_sq.iter_mut().zip(u_inv_sq.iter_mut()) {
+            *u *= *u;
+            *u_inv *= *u_inv;
+        }
+        let mut s = Vec::with_capacity(nm);
+        s.push(all_inv);
+        for i in 1..nm {
+            let lg_i = (31 - (i as u32).leading_zeros()) as usize;
+            let k = 1 << lg_i;
+            s.push(s[i - k] * u_sq[(lg_nm - 1) - lg_i]);
+        }
+
+        // c combines the two equations of the proof, and weight combines this
+        // proof with the others
+        let c = Scalar::random(rng);
+        let weight = Scalar::random(rng);
+
+        let powers_of_z: Vec<Scalar> = powers(z).take(m).collect();
+        let powers_of_2: Vec<Scalar> = powers(Scalar::from(2u64)).take(n).collect();
+        let g_scalars = s.iter().map(|s_i| weight * (-z - proof.a * s_i)).collect();
+        let h_scalars = s
+            .iter()
+            .rev()
+            .zip(powers(y.invert()))
+            .enumerate()
+            .map(|(i, (s_i_inv, y_inv_i))| {
+                let z_and_2 = powers_of_z[i / n] * powers_of_2[i % n];
+                weight * (z + y_inv_i * (zz * z_and_2 - proof.b * s_i_inv))
+            })
+            .collect();
+
+        let delta = (z - zz) * sum_of_powers(y, nm)
+            - zz * z * sum_of_powers(Scalar::from(2u64), n) * sum_of_powers(z, m);
+        let value_generator_scalar =
+            weight * (w * (proof.t_x - proof.a * proof.b) + c * (delta - proof.t_x));
+        let blinding_scalar = weight * (-proof.e_blinding - c * proof.t_x_blinding);
+
+        let scalars = [Scalar::one(), x, c * x, c * x * x]
+            .iter()
+            .chain(u_sq.iter())
+            .chain(u_inv_sq.iter())
+            .map(|scalar| weight * scalar)
+            .chain(powers_of_z.iter().map(|z_j| weight * c * zz * z_j))
+            .collect();
+        let points = [proof.A, proof.S, proof.T_1, proof.T_2]
+            .iter()
+            .chain(proof.L_vec.iter())
+            .chain(proof.R_vec.iter())
+            .chain(commitments.iter())
+            .cloned()
+            .collect();
+
+        Ok(Self {
+            scalars,
+            points,
+            value_generator_scalar,
+            value_generator: item.pedersen_generators.B,
+            blinding_scalar,
+            g_scalars,
+            h_scalars,
+        })
+    }
+}
+
+// Check all of the proofs with one multiscalar multiplication
+fn batch_check<T: RngCore + CryptoRng>(
+    items: &[RangeProofVerification],
+    rng: &mut T,
+) -> Result<(), ProofError> {
+    // All of the generators share the blinding base point
+    let blinding_base_point = items
+        .first()
+        .map(|item| item.pedersen_generators.B_blinding)
+        .ok_or(ProofError::FormatError)?;
+    let checks = items
+        .iter()
+        .map(|item| {
+            if item.pedersen_generators.B_blinding != blinding_base_point {
+                return Err(ProofError::VerificationError);
+            }
+            WeightedCheck::new(item, rng)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // The scalars of the shared points are added up across the proofs. Party j's
+    // generators are at j * BIT_SIZE.. in g_scalars and h_scalars.
+    let num_gens = checks
+        .iter()
+        .map(|check| check.g_scalars.len())
+        .max()
+        .unwrap_or(0);
+    let mut blinding_scalar = Scalar::zero();
+    let mut g_scalars = vec![Scalar::zero(); num_gens];
+    let mut h_scalars = vec![Scalar::zero(); num_gens];
+    for check in &checks {
+        blinding_scalar += check.blinding_scalar;
+        for (sum, scalar) in g_scalars.iter_mut().zip(check.g_scalars.iter()) {
+            *sum += scalar;
+        }
+        for (sum, scalar) in h_scalars.iter_mut().zip(check.h_scalars.iter()) {
+            *sum += scalar;
+        }
+    }
+    let num_parties = num_gens / BIT_SIZE;
+    let g_points = (0..num_parties).flat_map(|j| BP_GENERATORS.share(j).G(BIT_SIZE));
+    let h_points = (0..num_parties).flat_map(|j| BP_GENERATORS.share(j).H(BIT_SIZE));
+
+    let scalars = checks
+        .iter()
+        .flat_map(|check| {
+            check
+                .scalars
+                .iter()
+                .chain(iter::once(&check.value_generator_scalar))
+        })
+        .chain(iter::once(&blinding_scalar))
+        .chain(g_scalars.iter())
+        .chain(h_scalars.iter());
+    let points = checks
+        .iter()
+        .flat_map(|check| {
+            check
+                .points
+                .iter()
+                .map(|point| point.decompress())
+                .chain(iter::once(Some(check.value_generator)))
+        })
+        .chain(iter::once(Some(blinding_base_point)))
+        .chain(g_points.map(|point| Some(*point)))
+        .chain(h_points.map(|point| Some(*point)));
+
+    let mega_check =
+        RistrettoPoint::optional_multiscalar_mul(scalars, points).ok_or(ProofError::FormatError)?;
+    if mega_check.is_identity() {
+        Ok(())
+    } else {
+        Err(ProofError::VerificationError)
+    }
+}
+
+// 1, x, x^2, ...
+fn powers(x: Scalar) -> impl Iterator<Item = Scalar> {
+    iter::successors(Some(Scalar::one()), move |power| Some(power * x))
+}
+
+// 1 + x + ... + x^(n-1)
+fn sum_of_powers(x: Scalar, n: usize) -> Scalar {
+    powers(x).take(n).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::range_proofs::generate_range_proofs;
+    use mc_crypto_ring_signature::generators;
+    use mc_util_test_helper::get_seeded_rng;
+
+    fn make_item(
+        num_values: usize,
+        token_id: u64,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> RangeProofVerification {
+        let values: Vec<u64> = (0..num_values).map(|_| rng.next_u64()).collect();
+        let blindings: Vec<Scalar> = values.iter().map(|_| Scalar::random(rng)).collect();
+        let pedersen_generators = generators(token_id);
+        let (range_proof, commitments) =
+            generate_range_proofs(&values, &blindings, &pedersen_generators, rng).unwrap();
+        RangeProofVerification {
+            range_proof,
+            commitments: commitments[..num_values].to_vec(),
+            pedersen_generators,
+        }
+    }
+
+    #[test]
+    fn test_batch_accepts_valid_proofs() {
+        let mut rng = get_seeded_rng();
+        let items: Vec<_> = [(1, 0), (3, 0), (4, 1), (9, 2), (16, 0)]
+            .iter()
+            .map(|(num_values, token_id)| make_item(*num_values, *token_id, &mut rng))
+            .collect();
+
+        assert!(batch_check(&items, &mut rng).is_ok());
+        assert_eq!(check_range_proofs_batch(&items, &mut rng), Ok(()));
+        assert_eq!(check_range_proofs_batch(&[], &mut rng), Ok(()));
+    }
+
+    #[test]
+    fn test_batch_finds_first_invalid_proof() {
+        let mut rng = get_seeded_rng();
+        let mut items: Vec<_> = (0..4).map(|i| make_item(i + 2, 0, &mut rng)).collect();
+
+        // Proofs for the commitments of another token, and for the wrong
+        // commitments, are both rejected
+        items[1].pedersen_generators = generators(1);
+        items[3].commitments[0] = RistrettoPoint::random(&mut rng).compress();
+
+        assert!(batch_check(&items, &mut rng).is_err());
+        assert!(matches!(
+            check_range_proofs_batch(&items, &mut rng),
+            Err((1, Error::ProofError(_)))
+        ));
+        assert!(matches!(
+            check_range_proofs_batch(&items[2..], &mut rng),
+            Err((1, Error::ProofError(_)))
+        ));
+    }
+}
@@ -15,10 +15,13 @@ use mc_crypto_ring_signature::PedersenGens;
 use merlin::Transcript;
 use rand_core::{CryptoRng, RngCore};
 
+mod batch;
 pub mod error;
 use crate::domain_separators::BULLETPROOF_DOMAIN_TAG;
 use error::Error;
 
+pub use batch::{check_range_proofs_batch, RangeProofVerification};
+
 lazy_static! {
     /// Generators (base points) for Bulletproofs.
     /// The `party_capacity` is the maximum number of values in one proof. It should
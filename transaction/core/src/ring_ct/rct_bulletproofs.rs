@@ -18,7 +18,7 @@ use curve25519_dalek::{
 use mc_common::HashSet;
 use mc_crypto_digestible::{DigestTranscript, Digestible, MerlinTranscript};
 use mc_crypto_ring_signature::{
-    Commitment, CompressedCommitment, KeyImage, ReducedTxOut, RingMLSAG, Scalar,
+    Commitment, CompressedCommitment, KeyImage, MlsagVerification, ReducedTxOut, RingMLSAG, Scalar,
 };
 use mc_crypto_ring_signature_signer::{RingSigner, SignableInputRing};
 use mc_util_serial::prost::Message;
@@ -30,9 +30,12 @@ use zeroize::Zeroize;
 use crate::{
     constants::FEE_BLINDING,
     domain_separators::EXTENDED_MESSAGE_DOMAIN_TAG,
-    range_proofs::{check_range_proofs, generate_range_proofs},
+    range_proofs::{
+        check_range_proofs, check_range_proofs_batch, generate_range_proofs, RangeProofVerification,
+    },
     ring_ct::{Error, GeneratorCache},
-    Amount, BlockVersion,
+    tx_summary::{compute_mlsag_signing_digest, TxSummary},
+    Amount, BlockVersion, TokenId,
 };
 
 /// A presigned RingMLSAG and ancillary data needed to incorporate it into a
@@ -141,6 +144,8 @@ impl SignatureRctBulletproofs {
     /// # Arguments
     /// * `block_version` - This may influence details of the signature
     /// * `message` - The messages to be signed, e.g. Hash(TxPrefix).
+    /// * `tx_summary` - The summary of the transaction, which is signed from
+    ///   block version 4
     /// * `rings` - One or more rings of one-time addresses and amount
     ///   commitments.
     /// * `real_input_indices` - The index of the real input in each ring.
@@ -153,6 +158,7 @@ impl SignatureRctBulletproofs {
     pub fn sign<CSPRNG: RngCore + CryptoRng, S: RingSigner + ?Sized>(
         block_version: BlockVersion,
         message: &[u8; 32],
+        tx_summary: &TxSummary,
         input_rings: &[InputRing],
         output_secrets: &[OutputSecret],
         fee: Amount,
@@ -162,6 +168,7 @@ impl SignatureRctBulletproofs {
         sign_with_balance_check(
             block_version,
             message,
+            tx_summary,
             input_rings,
             output_secrets,
             fee,
@@ -176,6 +183,8 @@ impl SignatureRctBulletproofs {
     /// # Arguments
     /// * `block_version` - This may influence details of the signature
     /// * `message` - The message which was signed
+    /// * `tx_summary` - The summary of the transaction, which is signed from
+    ///   block version 4
     /// * `rings` - One or more rings which were signed to create this signature
     /// * `output_commitments` - Output amount commitments.
     /// * `fee` - Amount of the implicit fee output. commitment
@@ -184,11 +193,173 @@ impl SignatureRctBulletproofs {
         &self,
         block_version: BlockVersion,
         message: &[u8; 32],
+        tx_summary: &TxSummary,
         rings: &[SignedInputRing],
         output_commitments: &[CompressedCommitment],
         fee: Amount,
         rng: &mut CSPRNG,
     ) -> Result<(), Error> {
+        let extended_message_digest = self.verify_all_but_ring_signatures(
+            block_version,
+            message,
+            tx_summary,
+            rings,
+            output_commitments,
+            fee,
+            rng,
+        )?;
+
+        // Each MLSAG must be valid.
+        for (i, ring) in rings.iter().enumerate() {
+            // Normally, the ring signature is made over the entire extended messages
+            // digest. If there are input rules, then the signature is over a
+            // reduced digest. See MCIP #31 for rationale
+            let this_was_signed: &[u8] = if let Some(signed_digest) = ring.signed_digest.as_ref() {
+                &signed_digest[..]
+            } else {
+                &extended_message_digest
+            };
+
+            let ring_signature = &self.ring_signatures[i];
+            let pseudo_output = self.pseudo_output_commitments[i];
+            ring_signature.verify(this_was_signed, &ring.members, &pseudo_output)?;
+        }
+
+        // Signature is valid.
+        Ok(())
+    }
+
+    /// Verify many signatures together, e.g. the signatures of the transactions
+    /// of a block.
+    ///
+    /// This accepts exactly the signatures which `verify` accepts, but where
+    /// `verify` checks each MLSAG on its own with `RingMLSAG::verify`, the
+    /// MLSAGs of all of them are verified in one batch, see
+    /// `RingMLSAG::verify_batch`.
+    ///
+    /// Returns the index of the first signature which is not valid, and why.
+    ///
+    /// # Arguments
+    /// * `items` - The signatures, with the arguments `verify` takes for each
+    /// * `rng` - randomness
+    pub fn verify_batch<CSPRNG: RngCore + CryptoRng>(
+        items: &[SignatureVerification],
+        rng: &mut CSPRNG,
+    ) -> Result<(), (usize, Error)> {
+        let mut results: Vec<Result<(), Error>> = Vec::with_capacity(items.len());
+        let mut extended_message_digests: Vec<(usize, Vec<u8>)> = Vec::with_capacity(items.len());
+        for (index, item) in items.iter().enumerate() {
+            match item.signature.verify_all_but_ring_signatures(
+                item.block_version,
+                item.message,
+                item.tx_summary,
+                item.rings,
+                item.output_commitments,
+                item.fee,
+                rng,
+            ) {
+                Ok(extended_message_digest) => {
+                    results.push(Ok(()));
+                    extended_message_digests.push((index, extended_message_digest));
+                }
+                Err(err) => results.push(Err(err)),
+            }
+        }
+
+        // The MLSAGs of the signatures which are otherwise valid, in order, and the
+        // index of the signature each of them belongs to.
+        let mut mlsags: Vec<MlsagVerification> = Vec::new();
+        let mut mlsag_signature_indices: Vec<usize> = Vec::new();
+        for (index, extended_message_digest) in extended_message_digests.iter() {
+            let item = &items[*index];
+            for ((ring, ring_signature), pseudo_output) in item
+                .rings
+                .iter()
+                .zip(item.signature.ring_signatures.iter())
+                .zip(item.signature.pseudo_output_commitments.iter())
+            {
+                // Normally, the ring signature is made over the entire extended messages
+                // digest. If there are input rules, then the signature is over a
+                // reduced digest. See MCIP #31 for rationale
+                let this_was_signed: &[u8] =
+                    if let Some(signed_digest) = ring.signed_digest.as_ref() {
+                        &signed_digest[..]
+                    } else {
+                        extended_message_digest
+                    };
+
+                mlsags.push(MlsagVerification {
+                    mlsag: ring_signature,
+                    message: this_was_signed,
+                    ring: &ring.members,
+                    output_commitment: pseudo_output,
+                });
+                mlsag_signature_indices.push(*index);
+            }
+        }
+
+        // Each MLSAG must be valid. The first invalid MLSAG belongs to the first
+        // signature with an invalid MLSAG.
+        if let Err((mlsag_index, err)) = RingMLSAG::verify_batch(&mlsags) {
+            results[mlsag_signature_indices[mlsag_index]] = Err(err.into());
+        }
+
+        results
+            .into_iter()
+            .enumerate()
+            .find_map(|(index, result)| result.err().map(|err| (index, err)))
+            .map_or(Ok(()), Err)
+    }
+
+    /// Verify only the range proofs of many signatures together, e.g. the
+    /// range proofs of all the outputs of the transactions of a block.
+    ///
+    /// The range proofs of all of the signatures are verified in one batch,
+    /// see `check_range_proofs_batch`.
+    ///
+    /// Returns the index of the first signature with a range proof which is
+    /// missing or not valid, and why.
+    ///
+    /// # Arguments
+    /// * `items` - The signatures, with the arguments `verify` takes for each.
+    ///   Their messages, tx summaries and rings are not used.
+    /// * `rng` - randomness
+    pub fn verify_range_proofs_batch<CSPRNG: RngCore + CryptoRng>(
+        items: &[SignatureVerification],
+        rng: &mut CSPRNG,
+    ) -> Result<(), (usize, Error)> {
+        let mut generator_cache = GeneratorCache::default();
+        let range_proofs = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                item.signature
+                    .range_proofs_to_check(
+                        item.block_version,
+                        item.output_commitments,
+                        item.fee.token_id,
+                        &mut generator_cache,
+                    )
+                    .map(|range_proofs| (index, range_proofs))
+                    .map_err(|err| (index, err))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        check_range_proofs_of_signatures(&range_proofs, rng)
+    }
+
+    // Everything `verify` checks, except for the MLSAGs. This returns the
+    // extended message digest which the MLSAGs sign.
+    fn verify_all_but_ring_signatures<CSPRNG: RngCore + CryptoRng>(
+        &self,
+        block_version: BlockVersion,
+        message: &[u8; 32],
+        tx_summary: &TxSummary,
+        rings: &[SignedInputRing],
+        output_commitments: &[CompressedCommitment],
+        fee: Amount,
+        rng: &mut CSPRNG,
+    ) -> Result<Vec<u8>, Error> {
         if !block_version.masked_token_id_feature_is_supported() && fee.token_id != 0 {
             return Err(Error::TokenIdNotAllowed);
         }
@@ -257,88 +428,22 @@ impl SignatureRctBulletproofs {
             decompressed_pseudo_output_commitments.push(commitment);
         }
 
-        // Collect list of of unique token ids
-        let token_ids = {
-            let mut token_ids = BTreeSet::default();
-            token_ids.insert(fee.token_id);
-            for token_id in &self.output_token_ids {
-                token_ids.insert(token_id.into());
-            }
-            for token_id in &self.pseudo_output_token_ids {
-                token_ids.insert(token_id.into());
-            }
-            token_ids
-        };
-
         // Get a generator cache
         let mut generator_cache = GeneratorCache::default();
 
         // pseudo_output_commitments and output commitments must be in [0, 2^64).
-        // this is done differently depending on if mixed transactions are supported
-        if !block_version.mixed_transactions_are_supported() {
-            // Before mixed transactions, we expect the range proof to appear in
-            // self.range_proof_bytes, not self.range_proofs
-            if !self.range_proofs.is_empty() {
-                return Err(Error::TooManyRangeProofs);
-            }
-
-            let generator = generator_cache.get(fee.token_id);
-            let commitments: Vec<CompressedRistretto> = self
-                .pseudo_output_commitments
-                .iter()
-                .chain(output_commitments.iter())
-                .map(|compressed_commitment| compressed_commitment.point)
-                .collect();
-
-            let range_proof = RangeProof::from_bytes(&self.range_proof_bytes)
-                .map_err(|_e| Error::RangeProofDeserialization)?;
-
-            check_range_proofs(&range_proof, &commitments, generator, rng)?
-        } else {
-            // When mixed transactions are supported, self.range_proofs should contain
-            // a range proof corresponding to each token id used in the transaction, in
-            // sorted order. range_proof_bytes should be empty
-            if !self.range_proof_bytes.is_empty() {
-                return Err(Error::UnexpectedRangeProof);
-            }
-            if token_ids.len() != self.range_proofs.len() {
-                return Err(Error::MissingRangeProofs(
-                    token_ids.len(),
-                    self.range_proofs.len(),
-                ));
-            }
-
-            // For each used token id, and range proof, we have to pick out the matching
-            // outputs and pseudo outputs and verify the range proof.
-            for (token_id, range_proof) in zip_exact(token_ids.iter(), self.range_proofs.iter())? {
-                let generator = generator_cache.get(*token_id);
-
-                let commitments: Vec<CompressedRistretto> = zip_exact(
-                    self.pseudo_output_commitments.iter(),
-                    self.pseudo_output_token_ids.iter(),
-                )?
-                .chain(zip_exact(
-                    output_commitments.iter(),
-                    self.output_token_ids.iter(),
-                )?)
-                .filter_map(|(compressed_commitment, this_token_id)| {
-                    if token_id == this_token_id {
-                        Some(compressed_commitment.point)
-                    } else {
-                        None
-                    }
-                })
-                .collect();
-
-                if commitments.is_empty() {
-                    return Err(Error::NoCommitmentsForTokenId(*token_id));
-                }
-
-                let range_proof = RangeProof::from_bytes(range_proof)
-                    .map_err(|_e| Error::RangeProofDeserialization)?;
-
-                check_range_proofs(&range_proof, &commitments, generator, rng)?
-            }
+        for range_proof in self.range_proofs_to_check(
+            block_version,
+            output_commitments,
+            fee.token_id,
+            &mut generator_cache,
+        )? {
+            check_range_proofs(
+                &range_proof.range_proof,
+                &range_proof.commitments,
+                &range_proof.pedersen_generators,
+                rng,
+            )?
         }
 
         // Transaction must be balanced (not create or destroy value).
@@ -404,32 +509,118 @@ impl SignatureRctBulletproofs {
         }
 
         // Extend the message with the range proof and pseudo_output_commitments.
-        let extended_message_digest = compute_extended_message_either_version(
+        Ok(compute_extended_message_either_version(
             block_version,
             message,
+            tx_summary,
             &self.pseudo_output_commitments,
             &self.range_proof_bytes,
             &self.range_proofs,
-        );
+        ))
+    }
 
-        // Each MLSAG must be valid.
-        for (i, ring) in rings.iter().enumerate() {
-            // Normally, the ring signature is made over the entire extended messages
-            // digest. If there are input rules, then the signature is over a
-            // reduced digest. See MCIP #31 for rationale
-            let this_was_signed: &[u8] = if let Some(signed_digest) = ring.signed_digest.as_ref() {
-                &signed_digest[..]
-            } else {
-                &extended_message_digest
-            };
+    // The range proofs of the signature, with the commitments and generators
+    // each of them is for.
+    fn range_proofs_to_check(
+        &self,
+        block_version: BlockVersion,
+        output_commitments: &[CompressedCommitment],
+        fee_token_id: TokenId,
+        generator_cache: &mut GeneratorCache,
+    ) -> Result<Vec<RangeProofVerification>, Error> {
+        // Collect list of of unique token ids
+        let token_ids = {
+            let mut token_ids = BTreeSet::default();
+            token_ids.insert(fee_token_id);
+            for token_id in &self.output_token_ids {
+                token_ids.insert(token_id.into());
+            }
+            for token_id in &self.pseudo_output_token_ids {
+                token_ids.insert(token_id.into());
+            }
+            token_ids
+        };
 
-            let ring_signature = &self.ring_signatures[i];
-            let pseudo_output = self.pseudo_output_commitments[i];
-            ring_signature.verify(this_was_signed, &ring.members, &pseudo_output)?;
-        }
+        // This is done differently depending on if mixed transactions are supported
+        if !block_version.mixed_transactions_are_supported() {
+            // Before mixed transactions, we expect the range proof to appear in
+            // self.range_proof_bytes, not self.range_proofs
+            if !self.range_proofs.is_empty() {
+                return Err(Error::TooManyRangeProofs);
+            }
 
-        // Signature is valid.
-        Ok(())
+            let generator = *generator_cache.get(fee_token_id);
+            let commitments: Vec<CompressedRistretto> = self
+                .pseudo_output_commitments
+                .iter()
+                .chain(output_commitments.iter())
+                .map(|compressed_commitment| compressed_commitment.point)
+                .collect();
+
+            if commitments.is_empty() {
+                return Err(Error::NoCommitmentsForTokenId(fee_token_id));
+            }
+
+            let range_proof = RangeProof::from_bytes(&self.range_proof_bytes)
+                .map_err(|_e| Error::RangeProofDeserialization)?;
+
+            Ok(vec![RangeProofVerification {
+                range_proof,
+                commitments,
+                pedersen_generators: generator,
+            }])
+        } else {
+            // When mixed transactions are supported, self.range_proofs should contain
+            // a range proof corresponding to each token id used in the transaction, in
+            // sorted order. range_proof_bytes should be empty
+            if !self.range_proof_bytes.is_empty() {
+                return Err(Error::UnexpectedRangeProof);
+            }
+            if token_ids.len() != self.range_proofs.len() {
+                return Err(Error::MissingRangeProofs(
+                    token_ids.len(),
+                    self.range_proofs.len(),
+                ));
+            }
+
+            // For each used token id, and range proof, we have to pick out the matching
+            // outputs and pseudo outputs which the range proof is for.
+            let mut range_proofs = Vec::with_capacity(token_ids.len());
+            for (token_id, range_proof) in zip_exact(token_ids.iter(), self.range_proofs.iter())? {
+                let generator = *generator_cache.get(*token_id);
+
+                let commitments: Vec<CompressedRistretto> = zip_exact(
+                    self.pseudo_output_commitments.iter(),
+                    self.pseudo_output_token_ids.iter(),
+                )?
+                .chain(zip_exact(
+                    output_commitments.iter(),
+                    self.output_token_ids.iter(),
+                )?)
+                .filter_map(|(compressed_commitment, this_token_id)| {
+                    if token_id == this_token_id {
+                        Some(compressed_commitment.point)
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+
+                if commitments.is_empty() {
+                    return Err(Error::NoCommitmentsForTokenId(*token_id));
+                }
+
+                let range_proof = RangeProof::from_bytes(range_proof)
+                    .map_err(|_e| Error::RangeProofDeserialization)?;
+
+                range_proofs.push(RangeProofVerification {
+                    range_proof,
+                    commitments,
+                    pedersen_generators: generator,
+                });
+            }
+            Ok(range_proofs)
+        }
     }
 
     /// Key images spent by this signature.
@@ -441,11 +632,53 @@ impl SignatureRctBulletproofs {
     }
 }
 
+// Check the range proofs of many signatures in one batch, given with the
+// index of the signature they belong to. Returns the index of the first
+// signature with an invalid range proof.
+fn check_range_proofs_of_signatures<CSPRNG: RngCore + CryptoRng>(
+    range_proofs: &[(usize, Vec<RangeProofVerification>)],
+    rng: &mut CSPRNG,
+) -> Result<(), (usize, Error)> {
+    let signature_indices: Vec<usize> = range_proofs
+        .iter()
+        .flat_map(|(index, proofs)| proofs.iter().map(move |_| *index))
+        .collect();
+    let items: Vec<RangeProofVerification> = range_proofs
+        .iter()
+        .flat_map(|(_, proofs)| proofs.iter().cloned())
+        .collect();
+
+    check_range_proofs_batch(&items, rng)
+        .map_err(|(item_index, err)| (signature_indices[item_index], err.into()))
+}
+
+/// A signature to verify with `SignatureRctBulletproofs::verify_batch`, along
+/// with the arguments `SignatureRctBulletproofs::verify` takes.
+#[derive(Clone, Copy, Debug)]
+pub struct SignatureVerification<'a> {
+    /// The signature to verify
+    pub signature: &'a SignatureRctBulletproofs,
+    /// This may influence details of the signature
+    pub block_version: BlockVersion,
+    /// The message which was signed
+    pub message: &'a [u8; 32],
+    /// The summary of the transaction, which is signed from block version 4
+    pub tx_summary: &'a TxSummary,
+    /// One or more rings which were signed to create this signature
+    pub rings: &'a [SignedInputRing],
+    /// Output amount commitments
+    pub output_commitments: &'a [CompressedCommitment],
+    /// Amount of the implicit fee output
+    pub fee: Amount,
+}
+
 /// Sign, with optional check for inputs = outputs.
 ///
 /// # Arguments
 /// * `block_version` - This may influence details of the signature
 /// * `message` - The messages to be signed, e.g. Hash(TxPrefix).
+/// * `tx_summary` - The summary of the transaction, which is signed from block
+///   version 4
 /// * `rings` - One or more rings of one-time addresses and amount commitments,
 ///   with secrets for the real input
 /// * `output_secrets` - Output secret for each output amount commitment.
@@ -455,6 +688,7 @@ impl SignatureRctBulletproofs {
 fn sign_with_balance_check<CSPRNG: RngCore + CryptoRng, S: RingSigner + ?Sized>(
     block_version: BlockVersion,
     message: &[u8; 32],
+    tx_summary: &TxSummary,
     rings: &[InputRing],
     output_secrets: &[OutputSecret],
     fee: Amount,
@@ -663,6 +897,7 @@ fn sign_with_balance_check<CSPRNG: RngCore + CryptoRng, S: RingSigner + ?Sized>(
     let extended_message_digest = compute_extended_message_either_version(
         block_version,
         message,
+        tx_summary,
         &pseudo_output_commitments,
         &range_proof,
         &range_proofs,
@@ -782,15 +1017,26 @@ fn compute_pseudo_output_blindings<CSPRNG: RngCore + CryptoRng>(
         .collect())
 }
 
-/// Toggles between old-style and new-style extended message
+/// Toggles between old-style and new-style extended message, and adds the
+/// tx summary from block version 4
 fn compute_extended_message_either_version(
     block_version: BlockVersion,
     message: &[u8],
+    tx_summary: &TxSummary,
     pseudo_output_commitments: &[CompressedCommitment],
     range_proof_bytes: &[u8],
     range_proofs: &[Vec<u8>],
 ) -> Vec<u8> {
-    if block_version.mlsags_sign_extended_message_digest() {
+    if block_version.mlsags_sign_extended_message_and_tx_summary_digest() {
+        let extended_message_digest = digest_extended_message(
+            message,
+            pseudo_output_commitments,
+            range_proof_bytes,
+            range_proofs,
+        );
+        compute_mlsag_signing_digest(&extended_message_digest, &tx_summary.digest(block_version))
+            .to_vec()
+    } else if block_version.mlsags_sign_extended_message_digest() {
         // New-style extended message using merlin
         digest_extended_message(
             message,
@@ -878,6 +1124,9 @@ mod rct_bulletproofs_tests {
         /// Message to be signed.
         message: [u8; 32],
 
+        /// Summary of the transaction, signed from block version 4.
+        tx_summary: TxSummary,
+
         /// Rings of input onetime addresses and amount commitments.
         rings: Vec<SignableInputRing>,
 
@@ -999,6 +1248,7 @@ mod rct_bulletproofs_tests {
 
             SignatureParams {
                 message,
+                tx_summary: TxSummary::default(),
                 rings,
                 output_secrets,
                 block_version,
@@ -1039,6 +1289,7 @@ mod rct_bulletproofs_tests {
             SignatureRctBulletproofs::sign(
                 self.block_version,
                 &self.message,
+                &self.tx_summary,
                 &self.get_input_rings(),
                 &self.output_secrets,
                 Amount::new(fee, self.fee_token_id),
@@ -1055,6 +1306,7 @@ mod rct_bulletproofs_tests {
             sign_with_balance_check(
                 self.block_version,
                 &self.message,
+                &self.tx_summary,
                 &self.get_input_rings(),
                 &self.output_secrets,
                 Amount::new(fee, self.fee_token_id),
@@ -1153,6 +1405,7 @@ mod rct_bulletproofs_tests {
             let result = signature.verify(
                 block_version,
                 &params.message,
+                &params.tx_summary,
                 &params.get_signed_input_rings(),
                 &params.get_output_commitments(),
                 Amount::new(fee, params.fee_token_id),
@@ -1179,6 +1432,7 @@ mod rct_bulletproofs_tests {
             let result = signature.verify(
                 block_version,
                 &params.message,
+                &params.tx_summary,
                 &params.get_signed_input_rings(),
                 &params.get_output_commitments(),
                 Amount::new(fee, params.fee_token_id),
@@ -1209,6 +1463,7 @@ mod rct_bulletproofs_tests {
             let result = signature.verify(
                 block_version,
                 &params.message,
+                &params.tx_summary,
                 &params.get_signed_input_rings(),
                 &params.get_output_commitments(),
                 Amount::new(fee, params.fee_token_id),
@@ -1242,6 +1497,7 @@ mod rct_bulletproofs_tests {
             let result = invalid_signature.verify(
                 block_version,
                 &params.message,
+                &params.tx_summary,
                 &params.get_signed_input_rings(),
                 &params.get_output_commitments(),
                 Amount::new(fee, params.fee_token_id),
@@ -1282,6 +1538,7 @@ mod rct_bulletproofs_tests {
             let result = signature.verify(
                 block_version,
                 &params.message,
+                &params.tx_summary,
                 &params.get_signed_input_rings(),
                 &params.get_output_commitments(),
                 Amount::new(fee, params.fee_token_id),
@@ -1322,6 +1579,7 @@ mod rct_bulletproofs_tests {
             let result = signature.verify(
                 block_version,
                 &params.message,
+                &params.tx_summary,
                 &params.get_signed_input_rings(),
                 &params.get_output_commitments(),
                 Amount::new(fee, params.fee_token_id),
@@ -1355,6 +1613,7 @@ mod rct_bulletproofs_tests {
             let result = signature.verify(
                 block_version,
                 &params.message,
+                &params.tx_summary,
                 &params.get_signed_input_rings(),
                 &params.get_output_commitments(),
                 Amount::new(fee, params.fee_token_id),
@@ -1409,6 +1668,7 @@ mod rct_bulletproofs_tests {
             let result = signature.verify(
                 block_version,
                 &params.message,
+                &params.tx_summary,
                 &params.get_signed_input_rings(),
                 &params.get_output_commitments(),
                 Amount::new(fee, params.fee_token_id),
@@ -1421,6 +1681,7 @@ mod rct_bulletproofs_tests {
             match signature.verify(
                 block_version,
                 &params.message,
+                &params.tx_summary,
                 &params.get_signed_input_rings(),
                 &params.get_output_commitments(),
                 Amount::new(wrong_fee, params.fee_token_id),
@@ -1452,6 +1713,7 @@ mod rct_bulletproofs_tests {
             let result = signature.verify(
                 BlockVersion::TWO,
                 &params.message,
+                &params.tx_summary,
                 &params.get_signed_input_rings(),
                 &params.get_output_commitments(),
                 Amount::new(fee, params.fee_token_id),
@@ -1478,6 +1740,7 @@ mod rct_bulletproofs_tests {
             let result = signature.verify(
                 BlockVersion::ONE,
                 &params.message,
+                &params.tx_summary,
                 &params.get_signed_input_rings(),
                 &params.get_output_commitments(),
                 Amount::new(fee, params.fee_token_id),
@@ -1504,6 +1767,7 @@ mod rct_bulletproofs_tests {
             signature.verify(
                 BlockVersion::TWO,
                 &params.message,
+                &params.tx_summary,
                 &params.get_signed_input_rings(),
                 &params.get_output_commitments(),
                 Amount::new(fee, params.fee_token_id),
@@ -1514,6 +1778,7 @@ mod rct_bulletproofs_tests {
             let result = signature.verify(
                 BlockVersion::TWO,
                 &params.message,
+                &params.tx_summary,
                 &params.get_signed_input_rings(),
                 &params.get_output_commitments(),
                 Amount::new(fee, TokenId::from(*params.fee_token_id + 1)),
@@ -1560,6 +1825,7 @@ mod rct_bulletproofs_tests {
             signature.verify(
                 block_version,
                 &params.message,
+                &params.tx_summary,
                 &params.get_signed_input_rings(),
                 &params.get_output_commitments(),
                 Amount::new(fee, params.fee_token_id),
@@ -1571,6 +1837,7 @@ mod rct_bulletproofs_tests {
             let result = signature.verify(
                 block_version,
                 &params.message,
+                &params.tx_summary,
                 &params.get_signed_input_rings(),
                 &params.get_output_commitments(),
                 Amount::new(fee, params.fee_token_id),
@@ -1599,6 +1866,7 @@ mod rct_bulletproofs_tests {
             signature.verify(
                 block_version,
                 &params.message,
+                &params.tx_summary,
                 &params.get_signed_input_rings(),
                 &params.get_output_commitments(),
                 Amount::new(fee, params.fee_token_id),
@@ -1610,6 +1878,7 @@ mod rct_bulletproofs_tests {
             let result = signature.verify(
                 block_version,
                 &params.message,
+                &params.tx_summary,
                 &params.get_signed_input_rings(),
                 &params.get_output_commitments(),
                 Amount::new(fee, params.fee_token_id),
@@ -1619,5 +1888,40 @@ mod rct_bulletproofs_tests {
             assert_matches!(result, Err(Error::RangeProof(_)));
         }
 
+        #[test]
+        // signatures should not validate with another tx summary, from the block
+        // version where the tx summary is signed
+        fn test_verify_signature_rejects_change_to_tx_summary(
+            num_inputs in 2..8usize,
+            num_mixins in 1..17usize,
+            seed in any::<[u8; 32]>(),
+        ) {
+            let block_version = BlockVersion::MAX;
+            let mut rng: RngType = SeedableRng::from_seed(seed);
+            let mut params = SignatureParams::random(block_version, num_inputs, num_mixins, &mut rng);
+            params.tx_summary.tombstone_block = 100;
+
+            let fee = 0;
+            let signature = params.sign(fee, &mut rng).unwrap();
+
+            let mut other_tx_summary = params.tx_summary.clone();
+            other_tx_summary.tombstone_block += 1;
+
+            let result = signature.verify(
+                block_version,
+                &params.message,
+                &other_tx_summary,
+                &params.get_signed_input_rings(),
+                &params.get_output_commitments(),
+                Amount::new(fee, params.fee_token_id),
+                &mut rng,
+            );
+
+            if block_version.mlsags_sign_extended_message_and_tx_summary_digest() {
+                assert_eq!(result, Err(Error::RingSignature(RingSignatureError::InvalidSignature)));
+            } else {
+                assert_eq!(result, Ok(()));
+            }
+        }
     } // end proptest
 }
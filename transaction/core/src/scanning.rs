@@ -0,0 +1,267 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Scanning many TxOuts against many accounts at once.
+//!
+//! `TxOut::view_key_match` checks one TxOut against one view key. Services
+//! which watch many accounts, like exchange backends and block explorers, need
+//! to check every TxOut of every block against every account they know. A
+//! `ViewKeyScanner` does this with a table of the subaddress spend public keys
+//! of each account, computed once up front, so that checking a TxOut against an
+//! account costs one scalar multiplication and one table lookup.
+//!
+//! With the `parallel` feature, `ViewKeyScanner::scan_parallel` spreads the
+//! TxOuts across threads.
+
+use crate::{get_tx_out_shared_secret, tx::TxOut, Amount};
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::convert::TryFrom;
+use mc_account_keys::ViewAccountKey;
+use mc_crypto_keys::{RistrettoPrivate, RistrettoPublic};
+use mc_crypto_ring_signature::onetime_keys::recover_public_subaddress_spend_key;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// A TxOut which belongs to one of the accounts of a `ViewKeyScanner`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScanMatch {
+    /// The index of the TxOut in the scanned slice
+    pub tx_out_index: usize,
+    /// The index of the account, as returned by `ViewKeyScanner::add_account`
+    pub account_index: usize,
+    /// The subaddress of the account which the TxOut was sent to
+    pub subaddress_index: u64,
+    /// The (unmasked) amount of the TxOut
+    pub amount: Amount,
+    /// The TxOut shared secret, e.g. for decrypting the memo
+    pub shared_secret: RistrettoPublic,
+}
+
+// An account, with the precomputed spend public keys of the subaddresses being
+// scanned for
+#[derive(Clone, Debug)]
+struct ScanAccount {
+    view_private_key: RistrettoPrivate,
+    subaddress_spend_public_keys: BTreeMap<RistrettoPublic, u64>,
+}
+
+/// Scans TxOuts against the view keys of many accounts.
+///
+/// Only the subaddresses which are added with an account are found. TxOuts
+/// whose keys are not valid curve points, or whose amount doesn't decode for
+/// the matching account, are not matches.
+#[derive(Clone, Debug, Default)]
+pub struct ViewKeyScanner {
+    accounts: Vec<ScanAccount>,
+}
+
+impl ViewKeyScanner {
+    /// Create a scanner without any accounts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an account to scan for, with the subaddresses of it to look for.
+    ///
+    /// Returns the index of the account, which identifies it in the matches.
+    pub fn add_account(
+        &mut self,
+        view_account_key: &ViewAccountKey,
+        subaddress_indices: impl IntoIterator<Item = u64>,
+    ) -> usize {
+        let subaddress_spend_public_keys = subaddress_indices
+            .into_iter()
+            .map(|index| (view_account_key.subaddress_spend_public(index), index))
+            .collect();
+        self.accounts.push(ScanAccount {
+            view_private_key: *view_account_key.view_private_key(),
+            subaddress_spend_public_keys,
+        });
+        self.accounts.len() - 1
+    }
+
+    /// The number of accounts being scanned for.
+    pub fn num_accounts(&self) -> usize {
+        self.accounts.len()
+    }
+
+    /// Find the TxOuts which belong to the accounts of this scanner.
+    ///
+    /// The matches are in order of TxOut index, then account index.
+    pub fn scan(&self, tx_outs: &[TxOut]) -> Vec<ScanMatch> {
+        tx_outs
+            .iter()
+            .enumerate()
+            .flat_map(|(tx_out_index, tx_out)| self.scan_one(tx_out_index, tx_out))
+            .collect()
+    }
+
+    /// Find the TxOuts which belong to the accounts of this scanner, like
+    /// `scan`, but checking the TxOuts across threads.
+    ///
+    /// The matches are the same, in the same order, as those of `scan`.
+    #[cfg(feature = "parallel")]
+    pub fn scan_parallel(&self, tx_outs: &[TxOut]) -> Vec<ScanMatch> {
+        tx_outs
+            .par_iter()
+            .enumerate()
+            .flat_map_iter(|(tx_out_index, tx_out)| self.scan_one(tx_out_index, tx_out))
+            .collect()
+    }
+
+    // Check one TxOut against all of the accounts
+    fn scan_one(&self, tx_out_index: usize, tx_out: &TxOut) -> Vec<ScanMatch> {
+        // The keys of the TxOut are decompressed once, for all of the accounts
+        let (target_key, public_key) = match (
+            RistrettoPublic::try_from(&tx_out.target_key),
+            RistrettoPublic::try_from(&tx_out.public_key),
+        ) {
+            (Ok(target_key), Ok(public_key)) => (target_key, public_key),
+            _ => return Vec::new(),
+        };
+
+        self.accounts
+            .iter()
+            .enumerate()
+            .filter_map(|(account_index, account)| {
+                let spend_public_key = recover_public_subaddress_spend_key(
+                    &account.view_private_key,
+                    &target_key,
+                    &public_key,
+                );
+                let subaddress_index = *account
+                    .subaddress_spend_public_keys
+                    .get(&spend_public_key)?;
+
+                let shared_secret =
+                    get_tx_out_shared_secret(&account.view_private_key, &public_key);
+                let (amount, _blinding) = tx_out.masked_amount.get_value(&shared_secret).ok()?;
+
+                Some(ScanMatch {
+                    tx_out_index,
+                    account_index,
+                    subaddress_index,
+                    amount,
+                    shared_secret,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encrypted_fog_hint::EncryptedFogHint, tokens::Mob, BlockVersion, Token};
+    use mc_account_keys::{AccountKey, CHANGE_SUBADDRESS_INDEX, DEFAULT_SUBADDRESS_INDEX};
+    use mc_crypto_keys::CompressedRistrettoPublic;
+    use mc_util_from_random::FromRandom;
+    use mc_util_test_helper::get_seeded_rng;
+
+    // Make alice's and bob's accounts, and TxOuts to both of them and to others
+    fn test_tx_outs() -> (AccountKey, AccountKey, Vec<TxOut>) {
+        let mut rng = get_seeded_rng();
+        let alice = AccountKey::random(&mut rng);
+        let bob = AccountKey::random(&mut rng);
+        let carol = AccountKey::random(&mut rng);
+
+        let recipients = [
+            (carol.default_subaddress(), 1),
+            (alice.default_subaddress(), 2),
+            (bob.change_subaddress(), 3),
+            (alice.subaddress(7), 4),
+            (alice.change_subaddress(), 5),
+        ];
+        let tx_outs = recipients
+            .iter()
+            .map(|(recipient, value)| {
+                TxOut::new(
+                    BlockVersion::MAX,
+                    Amount::new(*value, Mob::ID),
+                    recipient,
+                    &RistrettoPrivate::from_random(&mut rng),
+                    EncryptedFogHint::fake_onetime_hint(&mut rng),
+                )
+                .unwrap()
+            })
+            .collect();
+        (alice, bob, tx_outs)
+    }
+
+    #[test]
+    fn test_scan_finds_tx_outs_of_all_accounts() {
+        let (alice, bob, tx_outs) = test_tx_outs();
+
+        let mut scanner = ViewKeyScanner::new();
+        let alice_index = scanner.add_account(
+            &ViewAccountKey::from(&alice),
+            [DEFAULT_SUBADDRESS_INDEX, CHANGE_SUBADDRESS_INDEX],
+        );
+        let bob_index = scanner.add_account(
+            &ViewAccountKey::from(&bob),
+            [DEFAULT_SUBADDRESS_INDEX, CHANGE_SUBADDRESS_INDEX],
+        );
+        assert_eq!(scanner.num_accounts(), 2);
+
+        let matches = scanner.scan(&tx_outs);
+        let found: Vec<_> = matches
+            .iter()
+            .map(|m| {
+                (
+                    m.tx_out_index,
+                    m.account_index,
+                    m.subaddress_index,
+                    m.amount.value,
+                )
+            })
+            .collect();
+        // Alice's subaddress 7 is not scanned for
+        assert_eq!(
+            found,
+            vec![
+                (1, alice_index, DEFAULT_SUBADDRESS_INDEX, 2),
+                (2, bob_index, CHANGE_SUBADDRESS_INDEX, 3),
+                (4, alice_index, CHANGE_SUBADDRESS_INDEX, 5),
+            ]
+        );
+
+        // The matches agree with view_key_match
+        for m in matches {
+            let account = if m.account_index == alice_index {
+                &alice
+            } else {
+                &bob
+            };
+            let (amount, shared_secret) = tx_outs[m.tx_out_index]
+                .view_key_match(account.view_private_key())
+                .unwrap();
+            assert_eq!(amount, m.amount);
+            assert_eq!(shared_secret, m.shared_secret);
+        }
+    }
+
+    #[test]
+    fn test_scan_skips_invalid_tx_outs() {
+        let (alice, _bob, mut tx_outs) = test_tx_outs();
+        tx_outs[1].public_key = CompressedRistrettoPublic::from(&[0xFFu8; 32]);
+
+        let mut scanner = ViewKeyScanner::new();
+        scanner.add_account(&ViewAccountKey::from(&alice), [DEFAULT_SUBADDRESS_INDEX]);
+        assert!(scanner.scan(&tx_outs).is_empty());
+        assert!(ViewKeyScanner::new().scan(&tx_outs).is_empty());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_scan_parallel_matches_scan() {
+        let (alice, bob, tx_outs) = test_tx_outs();
+
+        let mut scanner = ViewKeyScanner::new();
+        scanner.add_account(&ViewAccountKey::from(&alice), 0..10);
+        scanner.add_account(&ViewAccountKey::from(&bob), [CHANGE_SUBADDRESS_INDEX]);
+
+        let matches = scanner.scan(&tx_outs);
+        assert_eq!(matches.len(), 4);
+        assert_eq!(scanner.scan_parallel(&tx_outs), matches);
+    }
+}
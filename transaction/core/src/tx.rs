@@ -306,6 +306,11 @@ pub struct TxOut {
     /// The encrypted memo (except for old TxOut's, which don't have this.)
     #[prost(message, tag = "5")]
     pub e_memo: Option<EncryptedMemo>,
+
+    /// Encrypted fog hints for other fog ingest servers, for recipients who
+    /// use more than one fog deployment. (Only allowed from block version 4.)
+    #[prost(message, repeated, tag = "6")]
+    pub additional_e_fog_hints: Vec<EncryptedFogHint>,
 }
 
 /// When creating a MemoPayload for a TxOut, sometimes it is important to be
@@ -323,10 +328,29 @@ pub struct TxOut {
 /// up into a MemoContext object, and this is passed to the callback. This makes
 /// the callbacks more readable and it makes it easy to pass new variables into
 /// the callbacks in the future if needed without disrupting existing working
-/// code.
+/// code. For that reason, a MemoContext can only be created with
+/// `MemoContext::new` outside of this crate.
+#[derive(Clone, Copy)]
+#[non_exhaustive]
 pub struct MemoContext<'a> {
     /// The tx_public_key of the TxOut we are creating that we need a memo for
     pub tx_public_key: &'a RistrettoPublic,
+    /// The commitment to the amount of the TxOut we are creating
+    pub amount_commitment: &'a CompressedCommitment,
+}
+
+impl<'a> MemoContext<'a> {
+    /// Create a MemoContext for a TxOut, given its tx_public_key and amount
+    /// commitment
+    pub fn new(
+        tx_public_key: &'a RistrettoPublic,
+        amount_commitment: &'a CompressedCommitment,
+    ) -> Self {
+        Self {
+            tx_public_key,
+            amount_commitment,
+        }
+    }
 }
 
 impl TxOut {
@@ -387,6 +411,7 @@ impl TxOut {
         let e_memo = if block_version.e_memo_feature_is_supported() {
             let memo_ctxt = MemoContext {
                 tx_public_key: &public_key,
+                amount_commitment: &masked_amount.commitment,
             };
             let memo = memo_fn(memo_ctxt).map_err(NewTxError::Memo)?;
             Some(memo.encrypt(&shared_secret))
@@ -408,9 +433,15 @@ impl TxOut {
             public_key: public_key.into(),
             e_fog_hint: hint,
             e_memo,
+            additional_e_fog_hints: Vec::new(),
         })
     }
 
+    /// All of the encrypted fog hints of this TxOut, the main one first.
+    pub fn e_fog_hints(&self) -> impl Iterator<Item = &EncryptedFogHint> {
+        core::iter::once(&self.e_fog_hint).chain(self.additional_e_fog_hints.iter())
+    }
+
     /// A merlin-based hash of this TxOut.
     pub fn hash(&self) -> Hash {
         self.digest32::<MerlinTranscript>(b"mobilecoin-txout")
@@ -709,6 +740,7 @@ mod tests {
                 public_key,
                 e_fog_hint: EncryptedFogHint::from(&[1u8; ENCRYPTED_FOG_HINT_LEN]),
                 e_memo: Default::default(),
+                additional_e_fog_hints: Default::default(),
             }
         };
 
@@ -773,6 +805,7 @@ mod tests {
                 public_key,
                 e_fog_hint: EncryptedFogHint::from(&[1u8; ENCRYPTED_FOG_HINT_LEN]),
                 e_memo: Some(MemoPayload::default().encrypt(&shared_secret)),
+                additional_e_fog_hints: Default::default(),
             }
         };
 
@@ -0,0 +1,276 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! A summary of a transaction, for signers which can't hold the whole `Tx`.
+//!
+//! A hardware wallet asked to sign a transaction should confirm what the
+//! transaction does with its user before signing it. The full `Tx` is too big
+//! for such a device, so instead the host streams the outlays of the
+//! transaction to the device one output at a time, followed by its fee, fee
+//! token id and tombstone block.
+//!
+//! Each output comes with its unblinding data: the amount it carries and the
+//! address it is sent to, along with the tx private key it was created with.
+//! The device checks these against the masked amount and the keys of the
+//! output, through the shared secret, displays them to the user, and computes
+//! a digest of the outputs as they go by, which needs only constant memory.
+//!
+//! From block version 4, the MLSAGs of the transaction sign a digest of both
+//! the extended message digest and the tx summary digest, see
+//! `compute_mlsag_signing_digest`, so the device only signs the transaction
+//! whose summary the user confirmed. The host can also check that the digest
+//! computed by the device matches the `Tx` it is about to submit.
+
+use crate::{
+    domain_separators::{EXTENDED_MESSAGE_AND_TX_SUMMARY_DOMAIN_TAG, TX_SUMMARY_DOMAIN_TAG},
+    onetime_keys::{create_shared_secret, create_tx_out_public_key, create_tx_out_target_key},
+    tx::{Tx, TxOut, TxPrefix},
+    Amount, BlockVersion, MaskedAmount,
+};
+use alloc::vec::Vec;
+use displaydoc::Display;
+use mc_account_keys::PublicAddress;
+use mc_crypto_digestible::{DigestTranscript, Digestible, MerlinTranscript};
+use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPrivate};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+
+/// A summary of a transaction: its outlays, fee, fee token id and tombstone
+/// block.
+#[derive(Clone, Deserialize, Digestible, Eq, Hash, Message, PartialEq, Serialize)]
+pub struct TxSummary {
+    /// A summary of each output of the transaction, in order
+    #[prost(message, repeated, tag = "1")]
+    pub outputs: Vec<TxOutSummary>,
+
+    /// The fee paid by the transaction
+    #[prost(uint64, tag = "2")]
+    pub fee: u64,
+
+    /// The token id of the fee
+    #[prost(fixed64, tag = "3")]
+    pub fee_token_id: u64,
+
+    /// The block index at which the transaction is no longer valid
+    #[prost(fixed64, tag = "4")]
+    pub tombstone_block: u64,
+}
+
+/// A summary of one output of a transaction: the amount it carries, including
+/// its token id, and who can spend it, as they appear in the `TxOut`. See
+/// `TxOutSummaryUnblindingData` for what they are in the clear.
+#[derive(Clone, Deserialize, Digestible, Eq, Hash, Message, PartialEq, Serialize)]
+pub struct TxOutSummary {
+    /// The amount being sent.
+    #[prost(message, required, tag = "1")]
+    pub masked_amount: MaskedAmount,
+
+    /// The one-time public address of this output.
+    #[prost(message, required, tag = "2")]
+    pub target_key: CompressedRistrettoPublic,
+
+    /// The per output tx public key
+    #[prost(message, required, tag = "3")]
+    pub public_key: CompressedRistrettoPublic,
+}
+
+/// What an output of a transaction pays, and to whom, in the clear, along with
+/// the tx private key it was created with, which lets a signer check these
+/// against its `TxOutSummary`.
+#[derive(Clone, Debug)]
+pub struct TxOutSummaryUnblindingData {
+    /// The amount carried by the output, including its token id
+    pub amount: Amount,
+
+    /// The public address the output is sent to
+    pub recipient: PublicAddress,
+
+    /// The tx private key the output was created with
+    pub tx_private_key: RistrettoPrivate,
+}
+
+impl TxOutSummaryUnblindingData {
+    /// Check that an output is sent to `recipient` and carries `amount`.
+    ///
+    /// The keys of the output must be the ones `tx_private_key` makes for the
+    /// recipient, and the masked amount must unmask to `amount` with the
+    /// shared secret of the recipient and `tx_private_key`, which also checks
+    /// its commitment.
+    pub fn verify(&self, output: &TxOutSummary) -> Result<(), TxSummaryError> {
+        let target_key = create_tx_out_target_key(&self.tx_private_key, &self.recipient);
+        let public_key =
+            create_tx_out_public_key(&self.tx_private_key, self.recipient.spend_public_key());
+        if output.target_key != CompressedRistrettoPublic::from(&target_key)
+            || output.public_key != CompressedRistrettoPublic::from(&public_key)
+        {
+            return Err(TxSummaryError::RecipientMismatch);
+        }
+
+        let shared_secret =
+            create_shared_secret(self.recipient.view_public_key(), &self.tx_private_key);
+        let (amount, _blinding) = output
+            .masked_amount
+            .get_value(&shared_secret)
+            .map_err(|_| TxSummaryError::AmountMismatch)?;
+        if amount != self.amount {
+            return Err(TxSummaryError::AmountMismatch);
+        }
+        Ok(())
+    }
+}
+
+impl TxSummary {
+    /// Compute the digest of this summary, the same way
+    /// `TxSummaryStreamingDigest` does.
+    pub fn digest(&self, block_version: BlockVersion) -> [u8; 32] {
+        let mut digest = TxSummaryStreamingDigest::new(block_version, self.outputs.len());
+        for output in self.outputs.iter() {
+            digest
+                .append_output(output)
+                .expect("the number of outputs was given up front");
+        }
+        digest
+            .finalize(self.fee, self.fee_token_id, self.tombstone_block)
+            .expect("the number of outputs was given up front")
+    }
+
+    /// Check that a digest computed by a signer is the digest of the summary of
+    /// this transaction.
+    pub fn verify_tx(
+        tx: &Tx,
+        block_version: BlockVersion,
+        digest: &[u8; 32],
+    ) -> Result<(), TxSummaryError> {
+        let expected = Self::from(&tx.prefix).digest(block_version);
+        if bool::from(expected[..].ct_eq(&digest[..])) {
+            Ok(())
+        } else {
+            Err(TxSummaryError::DigestMismatch)
+        }
+    }
+}
+
+impl From<&TxPrefix> for TxSummary {
+    fn from(src: &TxPrefix) -> Self {
+        Self {
+            outputs: src.outputs.iter().map(TxOutSummary::from).collect(),
+            fee: src.fee,
+            fee_token_id: src.fee_token_id,
+            tombstone_block: src.tombstone_block,
+        }
+    }
+}
+
+impl From<&TxOut> for TxOutSummary {
+    fn from(src: &TxOut) -> Self {
+        Self {
+            masked_amount: src.masked_amount.clone(),
+            target_key: src.target_key,
+            public_key: src.public_key,
+        }
+    }
+}
+
+/// Computes the digest of a `TxSummary` one output at a time, in constant
+/// memory.
+///
+/// The number of outputs is committed to up front, so that a summary can't be
+/// cut short or extended after the user has confirmed it.
+pub struct TxSummaryStreamingDigest {
+    transcript: MerlinTranscript,
+    num_outputs: usize,
+    outputs_added: usize,
+}
+
+impl TxSummaryStreamingDigest {
+    /// Start the digest of the summary of a transaction with `num_outputs`
+    /// outputs, for the given block version.
+    pub fn new(block_version: BlockVersion, num_outputs: usize) -> Self {
+        let mut transcript = MerlinTranscript::new(TX_SUMMARY_DOMAIN_TAG.as_bytes());
+        (*block_version).append_to_transcript(b"block_version", &mut transcript);
+        (num_outputs as u64).append_to_transcript(b"num_outputs", &mut transcript);
+        Self {
+            transcript,
+            num_outputs,
+            outputs_added: 0,
+        }
+    }
+
+    /// Check the next output of the transaction against its unblinding data,
+    /// see `TxOutSummaryUnblindingData::verify`, and add it to the digest.
+    pub fn add_output(
+        &mut self,
+        output: &TxOutSummary,
+        unblinding_data: &TxOutSummaryUnblindingData,
+    ) -> Result<(), TxSummaryError> {
+        unblinding_data.verify(output)?;
+        self.append_output(output)
+    }
+
+    // Add the next output of the transaction to the digest
+    fn append_output(&mut self, output: &TxOutSummary) -> Result<(), TxSummaryError> {
+        if self.outputs_added == self.num_outputs {
+            return Err(TxSummaryError::TooManyOutputs(self.num_outputs));
+        }
+        output.append_to_transcript(b"output", &mut self.transcript);
+        self.outputs_added += 1;
+        Ok(())
+    }
+
+    /// Add the fee, fee token id and tombstone block of the transaction, which
+    /// come after all of its outputs, and get the digest.
+    pub fn finalize(
+        mut self,
+        fee: u64,
+        fee_token_id: u64,
+        tombstone_block: u64,
+    ) -> Result<[u8; 32], TxSummaryError> {
+        if self.outputs_added != self.num_outputs {
+            return Err(TxSummaryError::MissingOutputs(
+                self.num_outputs,
+                self.outputs_added,
+            ));
+        }
+        fee.append_to_transcript(b"fee", &mut self.transcript);
+        fee_token_id.append_to_transcript(b"fee_token_id", &mut self.transcript);
+        tombstone_block.append_to_transcript(b"tombstone_block", &mut self.transcript);
+
+        let mut output = [0u8; 32];
+        self.transcript.extract_digest(&mut output);
+        Ok(output)
+    }
+}
+
+/// Compute the digest which the MLSAGs of a transaction sign from block version
+/// 4: a digest of the extended message digest and of the tx summary digest.
+///
+/// A hardware wallet gets the extended message digest from the host, and
+/// computes the tx summary digest itself with `TxSummaryStreamingDigest`.
+pub fn compute_mlsag_signing_digest(
+    extended_message_digest: &[u8],
+    tx_summary_digest: &[u8; 32],
+) -> [u8; 32] {
+    let mut transcript =
+        MerlinTranscript::new(EXTENDED_MESSAGE_AND_TX_SUMMARY_DOMAIN_TAG.as_bytes());
+    extended_message_digest.append_to_transcript(b"extended_message_digest", &mut transcript);
+    tx_summary_digest[..].append_to_transcript(b"tx_summary_digest", &mut transcript);
+
+    let mut output = [0u8; 32];
+    transcript.extract_digest(&mut output);
+    output
+}
+
+/// An error that occurs when computing or checking a tx summary digest
+#[derive(Clone, Debug, Display, Ord, PartialOrd, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum TxSummaryError {
+    /// More outputs were added than the {0} the digest was started with
+    TooManyOutputs(usize),
+    /// The digest was started with {0} outputs, but only {1} were added
+    MissingOutputs(usize, usize),
+    /// The digest does not match the transaction
+    DigestMismatch,
+    /// The output is not sent to the recipient of its unblinding data
+    RecipientMismatch,
+    /// The output does not carry the amount of its unblinding data
+    AmountMismatch,
+}
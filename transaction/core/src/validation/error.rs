@@ -11,7 +11,12 @@ pub type TransactionValidationResult<T> = Result<T, TransactionValidationError>;
 
 /// Reasons why a single transaction may fail to be valid with respect to the
 /// current ledger.
+///
+/// The Display string of each variant is the first line of its doc comment,
+/// or its `displaydoc` attribute when that line would be too long. The rest of
+/// the doc comment describes the fields of the variant.
 #[derive(Clone, Debug, Display, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[ignore_extra_doc_attributes]
 pub enum TransactionValidationError {
     /// Each input should have one membership proof.
     InputsProofsLengthMismatch,
@@ -19,11 +24,11 @@ pub enum TransactionValidationError {
     /// A transaction must have at least one input.
     NoInputs,
 
-    /**
-     * A transaction must have no more than the maximum allowed number of
-     * inputs.
-     */
-    TooManyInputs,
+    /// A transaction must have no more than the maximum allowed number of
+    /// inputs. Contains the number of inputs, and the maximum allowed number of
+    /// inputs.
+    #[displaydoc("A transaction must have no more than the maximum allowed number of inputs.")]
+    TooManyInputs(u64, u64),
 
     /// Each input must have a signature.
     InsufficientInputSignatures,
@@ -37,38 +42,48 @@ pub enum TransactionValidationError {
     /// All Range Proofs in the transaction must be valid.
     InvalidRangeProof,
 
-    /**
-     * Each input must contain a ring with no fewer than the minimum number
-     * of elements.
-     */
-    InsufficientRingSize,
+    /// Each input must contain a ring with no fewer than the minimum number of
+    /// elements. Contains the index of the input, the size of its ring, and the
+    /// minimum ring size.
+    #[displaydoc(
+        "Each input must contain a ring with no fewer than the minimum number of elements."
+    )]
+    InsufficientRingSize(u64, u64, u64),
 
     /// Number of blocks in ledger exceeds the tombstone block number.
-    TombstoneBlockExceeded,
+    ///
+    /// Contains the current block index, and the tombstone block.
+    TombstoneBlockExceeded(u64, u64),
 
     /// Tombstone block is too far in the future.
-    TombstoneBlockTooFar,
+    ///
+    /// Contains the tombstone block, and the furthest tombstone block allowed.
+    TombstoneBlockTooFar(u64, u64),
 
     /// Must have at least one output.
     NoOutputs,
 
-    /**
-     * A transaction must have no more than the maximum allowed number of
-     * outputs.
-     */
-    TooManyOutputs,
+    /// A transaction must have no more than the maximum allowed number of
+    /// outputs. Contains the number of outputs, and the maximum allowed number
+    /// of outputs.
+    #[displaydoc("A transaction must have no more than the maximum allowed number of outputs.")]
+    TooManyOutputs(u64, u64),
 
-    /**
-     * Each input must contain a ring with no more than the maximum number
-     * of elements.
-     */
-    ExcessiveRingSize,
+    /// Each input must contain a ring with no more than the maximum number of
+    /// elements. Contains the index of the input, the size of its ring, and the
+    /// maximum ring size.
+    #[displaydoc(
+        "Each input must contain a ring with no more than the maximum number of elements."
+    )]
+    ExcessiveRingSize(u64, u64, u64),
 
     /// All elements in all rings within the transaction must be unique.
     DuplicateRingElements,
 
     /// The elements of each ring must be sorted.
-    UnsortedRingElements,
+    ///
+    /// Contains the index of the input.
+    UnsortedRingElements(u64),
 
     /// All rings in a transaction must be of the same size.
     UnequalRingSizes,
@@ -98,10 +113,15 @@ pub enum TransactionValidationError {
     ContainsExistingOutputPublicKey,
 
     /// Each ring element must have a corresponding proof of membership.
-    MissingTxOutMembershipProof,
+    ///
+    /// Contains the index of the input.
+    MissingTxOutMembershipProof(u64),
 
     /// Each ring element must have a valid proof of membership.
-    InvalidTxOutMembershipProof,
+    ///
+    /// Contains the index of the input, and the index of the element in its
+    /// ring.
+    InvalidTxOutMembershipProof(u64, u64),
 
     /// Public keys must be valid Ristretto points.
     InvalidRistrettoPublicKey,
@@ -119,7 +139,9 @@ pub enum TransactionValidationError {
     MembershipProofValidationError,
 
     /// An error occurred while checking transaction fees.
-    TxFeeError,
+    ///
+    /// Contains the fee, and the minimum fee.
+    TxFeeError(u64, u64),
 
     /// Public keys must be valid Ristretto points.
     KeyError,
@@ -147,6 +169,12 @@ pub enum TransactionValidationError {
 
     /// Input rule: {0}
     InputRule(InputRuleError),
+
+    /// A TxOut includes additional fog hints, but this is not allowed yet
+    AdditionalFogHintsNotAllowed,
+
+    /// A TxOut has {0} additional fog hints, but no more than {1} are allowed
+    TooManyAdditionalFogHints(u64, u64),
 }
 
 impl From<mc_crypto_keys::KeyError> for TransactionValidationError {
@@ -5,16 +5,25 @@
 mod error;
 mod validate;
 
+#[cfg(feature = "parallel")]
+mod parallel;
+
 pub use self::{
     error::{TransactionValidationError, TransactionValidationResult},
     validate::{
         validate, validate_all_input_rules, validate_inputs_are_sorted,
         validate_key_images_are_unique, validate_masked_token_id_exists,
-        validate_membership_proofs, validate_memo_exists, validate_number_of_inputs,
-        validate_number_of_outputs, validate_outputs_are_sorted,
-        validate_outputs_public_keys_are_unique, validate_ring_elements_are_sorted,
-        validate_ring_elements_are_unique, validate_ring_sizes, validate_signature,
+        validate_membership_proofs, validate_memo_exists, validate_number_of_additional_fog_hints,
+        validate_number_of_inputs, validate_number_of_outputs, validate_outputs_are_sorted,
+        validate_outputs_public_keys_are_unique, validate_range_proofs_batch,
+        validate_ring_elements_are_sorted, validate_ring_elements_are_unique, validate_ring_sizes,
+        validate_signature, validate_signatures_batch, validate_that_no_additional_fog_hints_exist,
         validate_that_no_masked_token_id_exists, validate_that_no_memo_exists, validate_tombstone,
         validate_transaction_fee, validate_tx_out,
     },
 };
+
+#[cfg(feature = "parallel")]
+pub use self::parallel::{
+    validate_membership_proofs_parallel, validate_parallel, validate_txs_parallel, TxValidation,
+};
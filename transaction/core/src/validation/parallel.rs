@@ -0,0 +1,179 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Transaction validation, spread across threads.
+//!
+//! These accept and reject exactly the transactions which the sequential
+//! validation routines accept and reject, with the same errors: when several
+//! checks fail, the error reported is the one which the sequential routines
+//! would report, regardless of which thread finishes first.
+
+use super::{
+    error::{TransactionValidationError, TransactionValidationResult},
+    validate::{
+        tx_outs_with_proofs, validate_membership_proof, validate_signature, validate_tx_out,
+        validate_with, Executor,
+    },
+};
+use crate::{
+    tx::{Tx, TxOut, TxOutMembershipProof, TxPrefix},
+    BlockVersion,
+};
+use alloc::vec::Vec;
+use rand_core::{CryptoRng, RngCore, SeedableRng};
+use rayon::prelude::*;
+
+/// Determines if the transaction is valid, with respect to the provided
+/// context, like `validate`, but checks the membership proofs of the ring
+/// elements, the signature and the outputs across threads.
+///
+/// # Arguments
+/// * `tx` - A pending transaction.
+/// * `current_block_index` - The index of the current block that is being
+///   built.
+/// * `block_version` - The version of the transaction rules we are testing
+/// * `root_proofs` - Membership proofs for each input ring element contained in
+///   `tx`.
+/// * `minimum_fee` - The minimum fee for the token indicated by
+///   tx.prefix.fee_token_id
+/// * `csprng` - Cryptographically secure random number generator.
+pub fn validate_parallel<R: RngCore + CryptoRng + Send>(
+    tx: &Tx,
+    current_block_index: u64,
+    block_version: BlockVersion,
+    root_proofs: &[TxOutMembershipProof],
+    minimum_fee: u64,
+    csprng: &mut R,
+) -> TransactionValidationResult<()> {
+    validate_with::<Rayon, R>(
+        tx,
+        current_block_index,
+        block_version,
+        root_proofs,
+        minimum_fee,
+        csprng,
+    )
+}
+
+/// Runs the checks across threads.
+struct Rayon;
+
+impl<R: RngCore + CryptoRng + Send> Executor<R> for Rayon {
+    fn validate_membership_proofs_and_signature(
+        block_version: BlockVersion,
+        tx: &Tx,
+        root_proofs: &[TxOutMembershipProof],
+        csprng: &mut R,
+    ) -> TransactionValidationResult<()> {
+        // Their results are looked at in the order `Sequential` checks them in.
+        let (membership_proofs_result, signature_result) = rayon::join(
+            || validate_membership_proofs_parallel(&tx.prefix, root_proofs),
+            || validate_signature(block_version, tx, csprng),
+        );
+        membership_proofs_result?;
+        signature_result
+    }
+
+    fn validate_tx_outs(
+        block_version: BlockVersion,
+        tx_outs: &[TxOut],
+    ) -> TransactionValidationResult<()> {
+        first_error(
+            tx_outs
+                .par_iter()
+                .map(|tx_out| validate_tx_out(block_version, tx_out)),
+        )
+    }
+}
+
+/// Validate TxOut membership proofs, like `validate_membership_proofs`, but
+/// checks the membership proof of each ring element on its own thread.
+///
+/// # Arguments
+/// * `tx_prefix` - Prefix of the transaction being validated.
+/// * `root_proofs` - Proofs of membership, provided by the untrusted system,
+///   that are used to check the root hashes of the transaction's membership
+///   proofs.
+pub fn validate_membership_proofs_parallel(
+    tx_prefix: &TxPrefix,
+    root_proofs: &[TxOutMembershipProof],
+) -> TransactionValidationResult<()> {
+    first_error(
+        tx_outs_with_proofs(tx_prefix, root_proofs)?
+            .par_iter()
+            .map(validate_membership_proof),
+    )
+}
+
+/// A transaction to validate with `validate_txs_parallel`, along with the
+/// arguments `validate` takes for it which differ between transactions.
+#[derive(Clone, Copy, Debug)]
+pub struct TxValidation<'a> {
+    /// A pending transaction.
+    pub tx: &'a Tx,
+    /// Membership proofs for each input ring element contained in `tx`.
+    pub root_proofs: &'a [TxOutMembershipProof],
+    /// The minimum fee for the token indicated by tx.prefix.fee_token_id
+    pub minimum_fee: u64,
+}
+
+/// Validates many independent transactions across threads, e.g. the
+/// transactions of a block being replayed, each with `validate_parallel`.
+///
+/// Only the transactions themselves are validated. Checks across
+/// transactions, e.g. that no two of them spend the same key image, are up to
+/// the caller.
+///
+/// Returns the index of the first transaction which is not valid, and the
+/// error `validate` returns for it.
+///
+/// # Arguments
+/// * `txs` - The transactions to validate.
+/// * `current_block_index` - The index of the current block that is being
+///   built.
+/// * `block_version` - The version of the transaction rules we are testing
+/// * `csprng` - Cryptographically secure random number generator. Each
+///   transaction is validated with its own generator, seeded from this one.
+pub fn validate_txs_parallel<R: RngCore + CryptoRng + SeedableRng + Send>(
+    txs: &[TxValidation],
+    current_block_index: u64,
+    block_version: BlockVersion,
+    csprng: &mut R,
+) -> Result<(), (usize, TransactionValidationError)> {
+    // The generators are seeded up front, so that the one a transaction gets
+    // doesn't depend on the order the threads run in.
+    let rngs: Vec<R> = txs
+        .iter()
+        .map(|_| {
+            let mut seed = R::Seed::default();
+            csprng.fill_bytes(seed.as_mut());
+            R::from_seed(seed)
+        })
+        .collect();
+
+    first_error(
+        txs.par_iter()
+            .zip(rngs)
+            .enumerate()
+            .map(|(index, (item, mut rng))| {
+                validate_parallel(
+                    item.tx,
+                    current_block_index,
+                    block_version,
+                    item.root_proofs,
+                    item.minimum_fee,
+                    &mut rng,
+                )
+                .map_err(|err| (index, err))
+            }),
+    )
+}
+
+// The first error, in the order of the iterator, as opposed to the first one
+// found by any thread.
+fn first_error<E: Send>(
+    results: impl IndexedParallelIterator<Item = Result<(), E>>,
+) -> Result<(), E> {
+    results
+        .find_map_first(|result| result.err())
+        .map_or(Ok(()), Err)
+}
@@ -8,8 +8,9 @@ use super::error::{TransactionValidationError, TransactionValidationResult};
 use crate::{
     constants::*,
     membership_proofs::{derive_proof_at_index, is_membership_proof_valid},
+    ring_ct::{SignatureRctBulletproofs, SignatureVerification},
     tx::{Tx, TxOut, TxOutMembershipProof, TxPrefix},
-    Amount, BlockVersion, TokenId,
+    Amount, BlockVersion, TokenId, TxSummary,
 };
 use alloc::{format, vec::Vec};
 use mc_common::HashSet;
@@ -35,6 +36,71 @@ pub fn validate<R: RngCore + CryptoRng>(
     root_proofs: &[TxOutMembershipProof],
     minimum_fee: u64,
     csprng: &mut R,
+) -> TransactionValidationResult<()> {
+    validate_with::<Sequential, R>(
+        tx,
+        current_block_index,
+        block_version,
+        root_proofs,
+        minimum_fee,
+        csprng,
+    )
+}
+
+/// Runs the checks of `validate_with` which are by far the most expensive,
+/// and which don't depend on each other.
+pub(super) trait Executor<R> {
+    /// Validate the membership proofs of the ring elements, then the
+    /// signature.
+    fn validate_membership_proofs_and_signature(
+        block_version: BlockVersion,
+        tx: &Tx,
+        root_proofs: &[TxOutMembershipProof],
+        csprng: &mut R,
+    ) -> TransactionValidationResult<()>;
+
+    /// Validate each output, and return the first error in the order of the
+    /// outputs.
+    fn validate_tx_outs(
+        block_version: BlockVersion,
+        tx_outs: &[TxOut],
+    ) -> TransactionValidationResult<()>;
+}
+
+/// Runs the checks one after the other.
+pub(super) struct Sequential;
+
+impl<R: RngCore + CryptoRng> Executor<R> for Sequential {
+    fn validate_membership_proofs_and_signature(
+        block_version: BlockVersion,
+        tx: &Tx,
+        root_proofs: &[TxOutMembershipProof],
+        csprng: &mut R,
+    ) -> TransactionValidationResult<()> {
+        validate_membership_proofs(&tx.prefix, root_proofs)?;
+        validate_signature(block_version, tx, csprng)
+    }
+
+    fn validate_tx_outs(
+        block_version: BlockVersion,
+        tx_outs: &[TxOut],
+    ) -> TransactionValidationResult<()> {
+        for tx_out in tx_outs {
+            validate_tx_out(block_version, tx_out)?;
+        }
+        Ok(())
+    }
+}
+
+/// The checks of `validate`, in the order their errors are reported in, with
+/// the most expensive ones run by the executor `E`.
+pub(super) fn validate_with<E: Executor<R>, R>(
+    tx: &Tx,
+    current_block_index: u64,
+    block_version: BlockVersion,
+    root_proofs: &[TxOutMembershipProof],
+    minimum_fee: u64,
+    csprng: &mut R,
 ) -> TransactionValidationResult<()> {
     if BlockVersion::MAX < block_version {
         return Err(TransactionValidationError::Ledger(format!(
@@ -55,9 +121,7 @@ pub fn validate<R: RngCore + CryptoRng>(
 
     validate_inputs_are_sorted(&tx.prefix)?;
 
-    validate_membership_proofs(&tx.prefix, root_proofs)?;
-
-    validate_signature(block_version, tx, csprng)?;
+    E::validate_membership_proofs_and_signature(block_version, tx, root_proofs, csprng)?;
 
     validate_transaction_fee(tx, minimum_fee)?;
 
@@ -72,9 +136,7 @@ pub fn validate<R: RngCore + CryptoRng>(
 
     // Each tx_out must conform to the structural rules for TxOut's at this block
     // version
-    for tx_out in tx.prefix.outputs.iter() {
-        validate_tx_out(block_version, tx_out)?;
-    }
+    E::validate_tx_outs(block_version, &tx.prefix.outputs)?;
 
     ////
     // Validate rules which depend on block version (see MCIP #26)
@@ -118,6 +180,14 @@ pub fn validate_tx_out(
         validate_that_no_masked_token_id_exists(tx_out)?;
     }
 
+    // If additional fog hints are supported, a few are allowed. Otherwise no
+    // outputs may have them.
+    if block_version.additional_fog_hints_are_supported() {
+        validate_number_of_additional_fog_hints(tx_out, MAX_ADDITIONAL_FOG_HINTS)?;
+    } else {
+        validate_that_no_additional_fog_hints_exist(tx_out)?;
+    }
+
     Ok(())
 }
 
@@ -136,7 +206,10 @@ pub fn validate_number_of_inputs(
 
     // Each transaction must have no more than the maximum allowed number of inputs.
     if num_inputs > maximum_allowed_inputs as usize {
-        return Err(TransactionValidationError::TooManyInputs);
+        return Err(TransactionValidationError::TooManyInputs(
+            num_inputs as u64,
+            maximum_allowed_inputs,
+        ));
     }
 
     Ok(())
@@ -157,7 +230,10 @@ pub fn validate_number_of_outputs(
     // Each transaction must have no more than the maximum allowed number of
     // outputs.
     if num_outputs > maximum_allowed_outputs as usize {
-        return Err(TransactionValidationError::TooManyOutputs);
+        return Err(TransactionValidationError::TooManyOutputs(
+            num_outputs as u64,
+            maximum_allowed_outputs,
+        ));
     }
 
     Ok(())
@@ -168,12 +244,13 @@ pub fn validate_ring_sizes(
     tx_prefix: &TxPrefix,
     ring_size: usize,
 ) -> TransactionValidationResult<()> {
-    for input in &tx_prefix.inputs {
+    for (index, input) in tx_prefix.inputs.iter().enumerate() {
         if input.ring.len() != ring_size {
-            let e = if input.ring.len() > ring_size {
-                TransactionValidationError::ExcessiveRingSize
+            let (index, len, ring_size) = (index as u64, input.ring.len() as u64, ring_size as u64);
+            let e = if len > ring_size {
+                TransactionValidationError::ExcessiveRingSize(index, len, ring_size)
             } else {
-                TransactionValidationError::InsufficientRingSize
+                TransactionValidationError::InsufficientRingSize(index, len, ring_size)
             };
             return Err(e);
         }
@@ -197,11 +274,11 @@ pub fn validate_ring_elements_are_unique(tx_prefix: &TxPrefix) -> TransactionVal
 
 /// Elements in a ring must be sorted.
 pub fn validate_ring_elements_are_sorted(tx_prefix: &TxPrefix) -> TransactionValidationResult<()> {
-    for tx_in in &tx_prefix.inputs {
+    for (index, tx_in) in tx_prefix.inputs.iter().enumerate() {
         check_sorted(
             &tx_in.ring,
             |a, b| a.public_key < b.public_key,
-            TransactionValidationError::UnsortedRingElements,
+            TransactionValidationError::UnsortedRingElements(index as u64),
         )?;
     }
 
@@ -261,6 +338,32 @@ pub fn validate_memo_exists(tx_out: &TxOut) -> TransactionValidationResult<()> {
     Ok(())
 }
 
+/// An output has no more than the maximum allowed number of additional fog
+/// hints.
+pub fn validate_number_of_additional_fog_hints(
+    tx_out: &TxOut,
+    maximum_allowed_hints: usize,
+) -> TransactionValidationResult<()> {
+    let num_hints = tx_out.additional_e_fog_hints.len();
+    if num_hints > maximum_allowed_hints {
+        return Err(TransactionValidationError::TooManyAdditionalFogHints(
+            num_hints as u64,
+            maximum_allowed_hints as u64,
+        ));
+    }
+    Ok(())
+}
+
+/// An output has no additional fog hints (TxOuts from before block version 4)
+pub fn validate_that_no_additional_fog_hints_exist(
+    tx_out: &TxOut,
+) -> TransactionValidationResult<()> {
+    if !tx_out.additional_e_fog_hints.is_empty() {
+        return Err(TransactionValidationError::AdditionalFogHintsNotAllowed);
+    }
+    Ok(())
+}
+
 /// All outputs have no masked token id (new-style TxOuts (Post MCIP #25) are
 /// rejected)
 pub fn validate_that_no_masked_token_id_exists(tx_out: &TxOut) -> TransactionValidationResult<()> {
@@ -300,10 +403,13 @@ pub fn validate_signature<R: RngCore + CryptoRng>(
     let tx_prefix_hash = tx.prefix.hash();
     let message = tx_prefix_hash.as_bytes();
 
+    let tx_summary = TxSummary::from(&tx.prefix);
+
     tx.signature
         .verify(
             block_version,
             message,
+            &tx_summary,
             &rings,
             &output_commitments,
             Amount::new(tx.prefix.fee, TokenId::from(tx.prefix.fee_token_id)),
@@ -312,10 +418,99 @@ pub fn validate_signature<R: RngCore + CryptoRng>(
         .map_err(TransactionValidationError::InvalidTransactionSignature)
 }
 
+/// Verifies the signatures of many transactions together, e.g. of the
+/// transactions of a block.
+///
+/// This accepts exactly the transactions which `validate_signature` accepts,
+/// but the ring signatures of all of them are verified in one batch, which is
+/// faster than validating them one by one.
+///
+/// Returns the index of the first transaction with an invalid signature, and
+/// the error.
+pub fn validate_signatures_batch<R: RngCore + CryptoRng>(
+    block_version: BlockVersion,
+    txs: &[Tx],
+    rng: &mut R,
+) -> Result<(), (usize, TransactionValidationError)> {
+    let rings: Vec<_> = txs.iter().map(|tx| tx.prefix.get_input_rings()).collect();
+    let output_commitments: Vec<_> = txs
+        .iter()
+        .map(|tx| tx.prefix.output_commitments())
+        .collect();
+    let tx_prefix_hashes: Vec<_> = txs.iter().map(|tx| tx.prefix.hash()).collect();
+    let tx_summaries: Vec<_> = txs.iter().map(|tx| TxSummary::from(&tx.prefix)).collect();
+
+    let items: Vec<SignatureVerification> = txs
+        .iter()
+        .enumerate()
+        .map(|(index, tx)| SignatureVerification {
+            signature: &tx.signature,
+            block_version,
+            message: tx_prefix_hashes[index].as_bytes(),
+            tx_summary: &tx_summaries[index],
+            rings: &rings[index],
+            output_commitments: &output_commitments[index],
+            fee: Amount::new(tx.prefix.fee, TokenId::from(tx.prefix.fee_token_id)),
+        })
+        .collect();
+
+    SignatureRctBulletproofs::verify_batch(&items, rng).map_err(|(index, err)| {
+        (
+            index,
+            TransactionValidationError::InvalidTransactionSignature(err),
+        )
+    })
+}
+
+/// Verifies the range proofs of many transactions together, e.g. of the
+/// transactions of a block, without checking the rest of their signatures.
+///
+/// The range proofs of all the outputs and pseudo outputs of the transactions
+/// are verified in one batch.
+///
+/// Returns the index of the first transaction with a missing or invalid range
+/// proof, and the error.
+pub fn validate_range_proofs_batch<R: RngCore + CryptoRng>(
+    block_version: BlockVersion,
+    txs: &[Tx],
+    rng: &mut R,
+) -> Result<(), (usize, TransactionValidationError)> {
+    let output_commitments: Vec<_> = txs
+        .iter()
+        .map(|tx| tx.prefix.output_commitments())
+        .collect();
+
+    // The range proofs don't depend on the message, the tx summary or the rings
+    let tx_summary = TxSummary::default();
+    let items: Vec<SignatureVerification> = txs
+        .iter()
+        .enumerate()
+        .map(|(index, tx)| SignatureVerification {
+            signature: &tx.signature,
+            block_version,
+            message: &[0u8; 32],
+            tx_summary: &tx_summary,
+            rings: &[],
+            output_commitments: &output_commitments[index],
+            fee: Amount::new(tx.prefix.fee, TokenId::from(tx.prefix.fee_token_id)),
+        })
+        .collect();
+
+    SignatureRctBulletproofs::verify_range_proofs_batch(&items, rng).map_err(|(index, err)| {
+        (
+            index,
+            TransactionValidationError::InvalidTransactionSignature(err),
+        )
+    })
+}
+
 /// The fee amount must be greater than or equal to the given minimum fee.
 pub fn validate_transaction_fee(tx: &Tx, minimum_fee: u64) -> TransactionValidationResult<()> {
     if tx.prefix.fee < minimum_fee {
-        Err(TransactionValidationError::TxFeeError)
+        Err(TransactionValidationError::TxFeeError(
+            tx.prefix.fee,
+            minimum_fee,
+        ))
     } else {
         Ok(())
     }
@@ -332,22 +527,65 @@ pub fn validate_membership_proofs(
     tx_prefix: &TxPrefix,
     root_proofs: &[TxOutMembershipProof],
 ) -> TransactionValidationResult<()> {
+    // Validate the membership proof for each TxOut used as an input ring element.
+    for tx_out_with_proofs in tx_outs_with_proofs(tx_prefix, root_proofs)? {
+        validate_membership_proof(&tx_out_with_proofs)?;
+    }
+
+    Ok(())
+}
+
+/// A TxOut used as an input ring element, with the proofs needed to check its
+/// membership in the ledger.
+pub(super) struct TxOutWithProofs<'a> {
+    /// A TxOut used as an input ring element.
+    tx_out: &'a TxOut,
+
+    /// The index of the input whose ring contains `tx_out`.
+    input_index: usize,
+
+    /// The index of `tx_out` in the ring.
+    ring_index: usize,
+
+    /// A membership proof for `tx_out` provided by the transaction author.
+    membership_proof: &'a TxOutMembershipProof,
+
+    /// A "root" membership proof, provided by the untrusted ledger server.
+    root_proof: &'a TxOutMembershipProof,
+}
+
+/// Pairs each input ring element and its membership proof with its root proof,
+/// checking that there is a proof of each kind for each ring element, and that
+/// the ranges in the root proofs are valid.
+pub(super) fn tx_outs_with_proofs<'a>(
+    tx_prefix: &'a TxPrefix,
+    root_proofs: &'a [TxOutMembershipProof],
+) -> TransactionValidationResult<Vec<TxOutWithProofs<'a>>> {
     // Each ring element must have a corresponding membership proof.
-    for tx_in in &tx_prefix.inputs {
+    for (input_index, tx_in) in tx_prefix.inputs.iter().enumerate() {
         if tx_in.ring.len() != tx_in.proofs.len() {
-            return Err(TransactionValidationError::MissingTxOutMembershipProof);
+            return Err(TransactionValidationError::MissingTxOutMembershipProof(
+                input_index as u64,
+            ));
         }
     }
 
-    let tx_out_with_membership_proof: Vec<(&TxOut, &TxOutMembershipProof)> = tx_prefix
-        .inputs
-        .iter()
-        .flat_map(|tx_in| {
-            let zipped: Vec<(&TxOut, &TxOutMembershipProof)> =
-                tx_in.ring.iter().zip(&tx_in.proofs).collect();
-            zipped
-        })
-        .collect();
+    let tx_out_with_membership_proof: Vec<(usize, usize, &TxOut, &TxOutMembershipProof)> =
+        tx_prefix
+            .inputs
+            .iter()
+            .enumerate()
+            .flat_map(|(input_index, tx_in)| {
+                let zipped: Vec<(usize, usize, &TxOut, &TxOutMembershipProof)> = tx_in
+                    .ring
+                    .iter()
+                    .zip(&tx_in.proofs)
+                    .enumerate()
+                    .map(|(ring_index, (tx_out, proof))| (input_index, ring_index, tx_out, proof))
+                    .collect();
+                zipped
+            })
+            .collect();
 
     // Each TxOut used as input must have a corresponding "root proof".
     // This could later be optimized if multiple input TxOuts have membership proofs
@@ -369,67 +607,49 @@ pub fn validate_membership_proofs(
         }
     }
 
-    struct TxOutWithProofs<'a> {
-        /// A TxOut used as an input ring element.
-        tx_out: &'a TxOut,
-
-        /// A membership proof for `tx_out` provided by the transaction author.
-        membership_proof: &'a TxOutMembershipProof,
-
-        /// A "root" membership proof, provided by the untrusted ledger server.
-        root_proof: &'a TxOutMembershipProof,
-    }
-
-    let mut tx_outs_with_proofs: Vec<TxOutWithProofs> = Vec::new();
-    for (i, (tx_out, membership_proof)) in tx_out_with_membership_proof.iter().enumerate() {
-        let root_proof: &TxOutMembershipProof = &root_proofs[i];
-        let tx_out_with_proofs = TxOutWithProofs {
-            tx_out,
-            membership_proof,
-            root_proof,
-        };
-        tx_outs_with_proofs.push(tx_out_with_proofs);
-    }
+    Ok(tx_out_with_membership_proof
+        .into_iter()
+        .zip(root_proofs)
+        .map(
+            |((input_index, ring_index, tx_out, membership_proof), root_proof)| TxOutWithProofs {
+                tx_out,
+                input_index,
+                ring_index,
+                membership_proof,
+                root_proof,
+            },
+        )
+        .collect())
+}
 
-    // Validate the membership proof for each TxOut used as an input ring element.
-    for tx_out_with_proofs in tx_outs_with_proofs {
-        match derive_proof_at_index(tx_out_with_proofs.root_proof) {
-            Err(_e) => {
-                return Err(TransactionValidationError::InvalidLedgerContext);
-            }
-            Ok(derived_proof) => {
-                match crate::membership_proofs::compute_implied_merkle_root(&derived_proof) {
-                    Err(_) => {
-                        return Err(TransactionValidationError::InvalidLedgerContext);
-                    }
-                    Ok(root_element) => {
-                        // Check the tx_out's membership proof against this root hash.
-                        match is_membership_proof_valid(
-                            tx_out_with_proofs.tx_out,
-                            tx_out_with_proofs.membership_proof,
-                            root_element.hash.as_ref(),
-                        ) {
-                            Err(_e) => {
-                                return Err(
-                                    TransactionValidationError::MembershipProofValidationError,
-                                );
-                            }
-                            Ok(is_valid) => {
-                                if !is_valid {
-                                    return Err(
-                                        TransactionValidationError::InvalidTxOutMembershipProof,
-                                    );
-                                }
-                                // Else, the membership proof is valid.
-                            }
-                        }
+/// Validate the membership proof of a TxOut used as an input ring element.
+pub(super) fn validate_membership_proof(
+    tx_out_with_proofs: &TxOutWithProofs,
+) -> TransactionValidationResult<()> {
+    match derive_proof_at_index(tx_out_with_proofs.root_proof) {
+        Err(_e) => Err(TransactionValidationError::InvalidLedgerContext),
+        Ok(derived_proof) => {
+            match crate::membership_proofs::compute_implied_merkle_root(&derived_proof) {
+                Err(_) => Err(TransactionValidationError::InvalidLedgerContext),
+                Ok(root_element) => {
+                    // Check the tx_out's membership proof against this root hash.
+                    match is_membership_proof_valid(
+                        tx_out_with_proofs.tx_out,
+                        tx_out_with_proofs.membership_proof,
+                        root_element.hash.as_ref(),
+                    ) {
+                        Err(_e) => Err(TransactionValidationError::MembershipProofValidationError),
+                        Ok(false) => Err(TransactionValidationError::InvalidTxOutMembershipProof(
+                            tx_out_with_proofs.input_index as u64,
+                            tx_out_with_proofs.ring_index as u64,
+                        )),
+                        // Else, the membership proof is valid.
+                        Ok(true) => Ok(()),
                     }
                 }
             }
         }
     }
-
-    Ok(())
 }
 
 /// The transaction must be not have expired, or be too long-lived.
@@ -443,12 +663,18 @@ pub fn validate_tombstone(
     tombstone_block_index: u64,
 ) -> TransactionValidationResult<()> {
     if current_block_index >= tombstone_block_index {
-        return Err(TransactionValidationError::TombstoneBlockExceeded);
+        return Err(TransactionValidationError::TombstoneBlockExceeded(
+            current_block_index,
+            tombstone_block_index,
+        ));
     }
 
     let limit = current_block_index + MAX_TOMBSTONE_BLOCKS;
     if tombstone_block_index > limit {
-        return Err(TransactionValidationError::TombstoneBlockTooFar);
+        return Err(TransactionValidationError::TombstoneBlockTooFar(
+            tombstone_block_index,
+            limit,
+        ));
     }
 
     Ok(())
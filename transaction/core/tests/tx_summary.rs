@@ -0,0 +1,216 @@
+mod util;
+
+use mc_crypto_keys::RistrettoPrivate;
+use mc_transaction_core::{
+    encrypted_fog_hint::EncryptedFogHint, tokens::Mob, tx::TxOut, Amount, BlockVersion, Token,
+    TokenId, TxOutSummary, TxOutSummaryUnblindingData, TxSummary, TxSummaryError,
+    TxSummaryStreamingDigest,
+};
+use mc_transaction_core_test_utils::AccountKey;
+use mc_util_from_random::FromRandom;
+use mc_util_test_helper::{RngType, SeedableRng};
+
+use util::create_test_tx;
+
+// Create outputs along with the unblinding data a signer gets for them
+fn create_outputs(
+    block_version: BlockVersion,
+    num_outputs: usize,
+    rng: &mut RngType,
+) -> Vec<(TxOut, TxOutSummaryUnblindingData)> {
+    (0..num_outputs)
+        .map(|index| {
+            let token_id = if block_version.masked_token_id_feature_is_supported() {
+                TokenId::from(index as u64)
+            } else {
+                Mob::ID
+            };
+            let amount = Amount::new(1000 + index as u64, token_id);
+            let recipient = AccountKey::random(rng).default_subaddress();
+            let tx_private_key = RistrettoPrivate::from_random(rng);
+            let tx_out = TxOut::new(
+                block_version,
+                amount,
+                &recipient,
+                &tx_private_key,
+                EncryptedFogHint::fake_onetime_hint(rng),
+            )
+            .unwrap();
+            let unblinding_data = TxOutSummaryUnblindingData {
+                amount,
+                recipient,
+                tx_private_key,
+            };
+            (tx_out, unblinding_data)
+        })
+        .collect()
+}
+
+// Test that the streaming digest matches the digest of the whole summary
+#[test]
+fn test_tx_summary_streaming_digest_matches_summary() {
+    let mut rng: RngType = SeedableRng::from_seed([1u8; 32]);
+    for block_version in BlockVersion::iterator() {
+        let outputs = create_outputs(block_version, 3, &mut rng);
+        let summary = TxSummary {
+            outputs: outputs
+                .iter()
+                .map(|(tx_out, _)| TxOutSummary::from(tx_out))
+                .collect(),
+            fee: 400,
+            fee_token_id: *Mob::ID,
+            tombstone_block: 10,
+        };
+
+        // What a signer computes as the outputs are streamed to it
+        let mut streaming = TxSummaryStreamingDigest::new(block_version, outputs.len());
+        for (tx_out, unblinding_data) in outputs.iter() {
+            streaming
+                .add_output(&TxOutSummary::from(tx_out), unblinding_data)
+                .unwrap();
+        }
+        let digest = streaming
+            .finalize(summary.fee, summary.fee_token_id, summary.tombstone_block)
+            .unwrap();
+
+        assert_eq!(digest, summary.digest(block_version));
+    }
+}
+
+// Test that the host accepts the digest of the summary of the Tx
+#[test]
+fn test_tx_summary_verify_tx_accepts_summary_of_tx() {
+    for block_version in BlockVersion::iterator() {
+        let (tx, _ledger) = create_test_tx(block_version);
+        let summary = TxSummary::from(&tx.prefix);
+        assert_eq!(summary.outputs.len(), tx.prefix.outputs.len());
+        assert_eq!(summary.fee, tx.prefix.fee);
+        assert_eq!(summary.fee_token_id, tx.prefix.fee_token_id);
+        assert_eq!(summary.tombstone_block, tx.prefix.tombstone_block);
+
+        TxSummary::verify_tx(&tx, block_version, &summary.digest(block_version)).unwrap();
+    }
+}
+
+// Test that the unblinding data of an output must match its amount and
+// recipient
+#[test]
+fn test_tx_out_summary_unblinding_data_must_match_output() {
+    let mut rng: RngType = SeedableRng::from_seed([2u8; 32]);
+    let block_version = BlockVersion::MAX;
+    let mut outputs = create_outputs(block_version, 2, &mut rng);
+    let (_, other_unblinding_data) = outputs.pop().unwrap();
+    let (tx_out, unblinding_data) = outputs.pop().unwrap();
+    let output = TxOutSummary::from(&tx_out);
+    unblinding_data.verify(&output).unwrap();
+
+    let mut other_value = unblinding_data.clone();
+    other_value.amount.value += 1;
+    assert_eq!(
+        other_value.verify(&output),
+        Err(TxSummaryError::AmountMismatch)
+    );
+
+    let mut other_token_id = unblinding_data.clone();
+    other_token_id.amount.token_id = TokenId::from(*other_token_id.amount.token_id + 1);
+    assert_eq!(
+        other_token_id.verify(&output),
+        Err(TxSummaryError::AmountMismatch)
+    );
+
+    let mut other_recipient = unblinding_data.clone();
+    other_recipient.recipient = other_unblinding_data.recipient.clone();
+    assert_eq!(
+        other_recipient.verify(&output),
+        Err(TxSummaryError::RecipientMismatch)
+    );
+
+    let mut other_tx_private_key = unblinding_data.clone();
+    other_tx_private_key.tx_private_key = other_unblinding_data.tx_private_key;
+    assert_eq!(
+        other_tx_private_key.verify(&output),
+        Err(TxSummaryError::RecipientMismatch)
+    );
+
+    // The masked amount is checked against its commitment
+    let mut other_masked_value = output.clone();
+    other_masked_value.masked_amount.masked_value += 1;
+    assert_eq!(
+        unblinding_data.verify(&other_masked_value),
+        Err(TxSummaryError::AmountMismatch)
+    );
+
+    // A signer being streamed an output checks it
+    let mut streaming = TxSummaryStreamingDigest::new(block_version, 1);
+    assert_eq!(
+        streaming.add_output(&output, &other_unblinding_data),
+        Err(TxSummaryError::RecipientMismatch)
+    );
+    streaming.add_output(&output, &unblinding_data).unwrap();
+}
+
+// Test that the host rejects a digest of anything but the summary of the Tx
+#[test]
+fn test_tx_summary_verify_tx_rejects_other_summaries() {
+    let block_version = BlockVersion::MAX;
+    let (tx, _ledger) = create_test_tx(block_version);
+    let summary = TxSummary::from(&tx.prefix);
+
+    let mut other_fee = summary.clone();
+    other_fee.fee += 1;
+
+    let mut other_fee_token_id = summary.clone();
+    other_fee_token_id.fee_token_id += 1;
+
+    let mut other_tombstone_block = summary.clone();
+    other_tombstone_block.tombstone_block += 1;
+
+    let mut other_output = summary.clone();
+    other_output.outputs[0].masked_amount.masked_value += 1;
+
+    let mut missing_output = summary.clone();
+    missing_output.outputs.pop();
+
+    for other in [
+        other_fee,
+        other_fee_token_id,
+        other_tombstone_block,
+        other_output,
+        missing_output,
+    ] {
+        assert_eq!(
+            TxSummary::verify_tx(&tx, block_version, &other.digest(block_version)),
+            Err(TxSummaryError::DigestMismatch)
+        );
+    }
+
+    // The block version is part of the digest
+    assert_eq!(
+        TxSummary::verify_tx(&tx, block_version, &summary.digest(BlockVersion::ONE)),
+        Err(TxSummaryError::DigestMismatch)
+    );
+}
+
+// Test that the streaming digest enforces the number of outputs it was started
+// with
+#[test]
+fn test_tx_summary_streaming_digest_checks_number_of_outputs() {
+    let mut rng: RngType = SeedableRng::from_seed([3u8; 32]);
+    let block_version = BlockVersion::MAX;
+    let (tx_out, unblinding_data) = create_outputs(block_version, 1, &mut rng).pop().unwrap();
+    let output = TxOutSummary::from(&tx_out);
+
+    let mut streaming = TxSummaryStreamingDigest::new(block_version, 2);
+    streaming.add_output(&output, &unblinding_data).unwrap();
+    assert_eq!(
+        streaming.finalize(0, 0, 0),
+        Err(TxSummaryError::MissingOutputs(2, 1))
+    );
+
+    let mut streaming = TxSummaryStreamingDigest::new(block_version, 1);
+    streaming.add_output(&output, &unblinding_data).unwrap();
+    assert_eq!(
+        streaming.add_output(&output, &unblinding_data),
+        Err(TxSummaryError::TooManyOutputs(1))
+    );
+}
@@ -14,12 +14,13 @@ use crate::util::{
 use alloc::vec::Vec;
 use mc_account_keys::AccountKey;
 use mc_crypto_keys::{CompressedRistrettoPublic, ReprBytes};
+use mc_crypto_ring_signature::{CompressedCommitment, CurveScalar, KeyImage, Scalar};
 use mc_ledger_db::{
     test_utils::{InverseTxOutputsOrdering, INITIALIZE_LEDGER_AMOUNT},
     Ledger,
 };
 use mc_transaction_core::{
-    constants::{MAX_TOMBSTONE_BLOCKS, RING_SIZE},
+    constants::{MAX_ADDITIONAL_FOG_HINTS, MAX_TOMBSTONE_BLOCKS, RING_SIZE},
     membership_proofs::Range,
     tokens::Mob,
     tx::{TxOutMembershipHash, TxOutMembershipProof},
@@ -105,6 +106,32 @@ fn test_validate_no_masked_token_id_exists() {
     );
 }
 
+#[test]
+// Additional fog hints are only allowed from block version 4, and only a few
+fn test_validate_additional_fog_hints() {
+    let (tx, _) = create_test_tx(BlockVersion::FOUR);
+    let mut tx_out = tx.prefix.outputs.first().unwrap().clone();
+    assert_eq!(validate_tx_out(BlockVersion::FOUR, &tx_out), Ok(()));
+
+    tx_out.additional_e_fog_hints = vec![tx_out.e_fog_hint.clone(); MAX_ADDITIONAL_FOG_HINTS];
+    assert_eq!(validate_tx_out(BlockVersion::FOUR, &tx_out), Ok(()));
+    assert_eq!(
+        validate_tx_out(BlockVersion::THREE, &tx_out),
+        Err(TransactionValidationError::AdditionalFogHintsNotAllowed)
+    );
+
+    tx_out
+        .additional_e_fog_hints
+        .push(tx_out.e_fog_hint.clone());
+    assert_eq!(
+        validate_tx_out(BlockVersion::FOUR, &tx_out),
+        Err(TransactionValidationError::TooManyAdditionalFogHints(
+            MAX_ADDITIONAL_FOG_HINTS as u64 + 1,
+            MAX_ADDITIONAL_FOG_HINTS as u64
+        ))
+    );
+}
+
 #[test]
 // Should return Ok(()) when the Tx's membership proofs are correct and agree
 // with ledger.
@@ -123,7 +150,9 @@ fn test_validate_membership_proofs() {
             broken_proofs[0].elements[0].hash = TxOutMembershipHash::from([1u8; 32]);
             assert_eq!(
                 validate_membership_proofs(&tx.prefix, &broken_proofs),
-                Err(TransactionValidationError::InvalidTxOutMembershipProof)
+                Err(TransactionValidationError::InvalidTxOutMembershipProof(
+                    0, 0
+                ))
             );
         }
 
@@ -206,7 +235,9 @@ fn test_validate_number_of_inputs() {
             let expected_result = if num_inputs == 0 {
                 Err(TransactionValidationError::NoInputs)
             } else if num_inputs > max_inputs {
-                Err(TransactionValidationError::TooManyInputs)
+                Err(TransactionValidationError::TooManyInputs(
+                    num_inputs, max_inputs,
+                ))
             } else {
                 Ok(())
             };
@@ -236,7 +267,10 @@ fn test_validate_number_of_outputs() {
             let expected_result = if num_outputs == 0 {
                 Err(TransactionValidationError::NoOutputs)
             } else if num_outputs > max_outputs {
-                Err(TransactionValidationError::TooManyOutputs)
+                Err(TransactionValidationError::TooManyOutputs(
+                    num_outputs,
+                    max_outputs,
+                ))
             } else {
                 Ok(())
             };
@@ -267,7 +301,11 @@ fn test_validate_ring_sizes() {
 
             assert_eq!(
                 validate_ring_sizes(&tx_prefix, RING_SIZE),
-                Err(TransactionValidationError::InsufficientRingSize),
+                Err(TransactionValidationError::InsufficientRingSize(
+                    0,
+                    0,
+                    RING_SIZE as u64
+                )),
             );
         }
 
@@ -278,7 +316,11 @@ fn test_validate_ring_sizes() {
 
             assert_eq!(
                 validate_ring_sizes(&tx_prefix, RING_SIZE),
-                Err(TransactionValidationError::InsufficientRingSize),
+                Err(TransactionValidationError::InsufficientRingSize(
+                    0,
+                    RING_SIZE as u64 - 1,
+                    RING_SIZE as u64
+                )),
             );
         }
 
@@ -290,7 +332,11 @@ fn test_validate_ring_sizes() {
 
             assert_eq!(
                 validate_ring_sizes(&tx_prefix, RING_SIZE),
-                Err(TransactionValidationError::ExcessiveRingSize),
+                Err(TransactionValidationError::ExcessiveRingSize(
+                    0,
+                    RING_SIZE as u64 + 1,
+                    RING_SIZE as u64
+                )),
             );
         }
 
@@ -312,7 +358,11 @@ fn test_validate_ring_sizes() {
 
             assert_eq!(
                 validate_ring_sizes(&tx_prefix, RING_SIZE),
-                Err(TransactionValidationError::InsufficientRingSize),
+                Err(TransactionValidationError::InsufficientRingSize(
+                    1,
+                    RING_SIZE as u64 - 1,
+                    RING_SIZE as u64
+                )),
             );
         }
     }
@@ -380,7 +430,7 @@ fn test_validate_ring_elements_are_sorted() {
         tx.prefix.inputs[0].ring.swap(0, 3);
         assert_eq!(
             validate_ring_elements_are_sorted(&tx.prefix),
-            Err(TransactionValidationError::UnsortedRingElements)
+            Err(TransactionValidationError::UnsortedRingElements(0))
         );
     }
 }
@@ -521,6 +571,146 @@ fn test_validate_signature_ok() {
     }
 }
 
+#[test]
+// `validate_signatures_batch` should return OK for valid transactions, and the
+// index of the first transaction with an invalid signature otherwise.
+fn test_validate_signatures_batch() {
+    let mut rng = get_seeded_rng();
+
+    for block_version in BlockVersion::iterator() {
+        let mut txs: Vec<_> = (0..3).map(|_| create_test_tx(block_version).0).collect();
+        assert_eq!(
+            validate_signatures_batch(block_version, &txs, &mut rng),
+            Ok(()),
+            "failed at block version: {}",
+            block_version
+        );
+        assert_eq!(
+            validate_signatures_batch(block_version, &[], &mut rng),
+            Ok(())
+        );
+
+        // Add an output to the second and third transactions.
+        for tx in txs[1..].iter_mut() {
+            let output = tx.prefix.outputs.get(0).unwrap().clone();
+            tx.prefix.outputs.push(output);
+        }
+
+        match validate_signatures_batch(block_version, &txs, &mut rng) {
+            Err((1, TransactionValidationError::InvalidTransactionSignature(_e))) => {} /* Expected. */
+            Err((index, e)) => {
+                panic!("Unexpected error at {}: {}", index, e);
+            }
+            Ok(()) => panic!("Unexpected success"),
+        }
+    }
+}
+
+#[test]
+// `validate_signatures_batch` should agree with `validate_signature` on each
+// transaction, and on the first invalid one, whichever way their ring
+// signatures or rings are malformed.
+fn test_validate_signatures_batch_agrees_with_validate_signature() {
+    let mut rng = get_seeded_rng();
+
+    for block_version in BlockVersion::iterator() {
+        let txs: Vec<_> = (0..7)
+            .map(|malformation| {
+                let mut tx = create_test_tx(block_version).0;
+                let ring_signature = &mut tx.signature.ring_signatures[0];
+                match malformation {
+                    0 => {}
+                    1 => {
+                        ring_signature.responses.pop();
+                    }
+                    // This does not decompress.
+                    2 => ring_signature.key_image = KeyImage::from([0xffu8; 32]),
+                    3 => ring_signature.c_zero = CurveScalar::from(Scalar::random(&mut rng)),
+                    4 => {
+                        tx.prefix.inputs[0].ring[1].target_key =
+                            CompressedRistrettoPublic::from(&[0xffu8; 32])
+                    }
+                    5 => {
+                        tx.signature.ring_signatures.pop();
+                    }
+                    _ => {
+                        tx.signature.pseudo_output_commitments[0] =
+                            CompressedCommitment::from(&[0xffu8; 32])
+                    }
+                }
+                tx
+            })
+            .collect();
+
+        let expected: Vec<_> = txs
+            .iter()
+            .map(|tx| validate_signature(block_version, tx, &mut rng))
+            .collect();
+        assert_eq!(expected[0], Ok(()));
+
+        for (index, tx) in txs.iter().enumerate() {
+            assert_eq!(
+                validate_signatures_batch(block_version, core::slice::from_ref(tx), &mut rng)
+                    .map_err(|(_, err)| err),
+                expected[index],
+                "disagreement on malformation {} at block version: {}",
+                index,
+                block_version
+            );
+        }
+
+        for start in 0..txs.len() {
+            let expected_first_err = expected[start..]
+                .iter()
+                .enumerate()
+                .find_map(|(index, result)| result.clone().err().map(|err| (index, err)))
+                .map_or(Ok(()), Err);
+            assert_eq!(
+                validate_signatures_batch(block_version, &txs[start..], &mut rng),
+                expected_first_err
+            );
+        }
+    }
+}
+
+#[test]
+// `validate_range_proofs_batch` should return OK for valid transactions, and
+// the index of the first transaction with an invalid range proof otherwise.
+fn test_validate_range_proofs_batch() {
+    let mut rng = get_seeded_rng();
+
+    for block_version in BlockVersion::iterator() {
+        let mut txs: Vec<_> = (0..3).map(|_| create_test_tx(block_version).0).collect();
+        assert_eq!(
+            validate_range_proofs_batch(block_version, &txs, &mut rng),
+            Ok(()),
+            "failed at block version: {}",
+            block_version
+        );
+        assert_eq!(
+            validate_range_proofs_batch(block_version, &[], &mut rng),
+            Ok(())
+        );
+
+        // Replace an output commitment of the second and third transactions, which
+        // their range proofs are no longer valid for.
+        for tx in txs[1..].iter_mut() {
+            let commitment = tx.prefix.outputs[0].masked_amount.commitment;
+            tx.prefix.outputs[0].masked_amount.commitment =
+                tx.signature.pseudo_output_commitments[0];
+            assert_ne!(tx.prefix.outputs[0].masked_amount.commitment, commitment);
+        }
+
+        match validate_range_proofs_batch(block_version, &txs, &mut rng) {
+            Err((1, TransactionValidationError::InvalidTransactionSignature(_e))) => {} /* Expected. */
+            Err((index, e)) => {
+                panic!("Unexpected error at {}: {}", index, e);
+            }
+            Ok(()) => panic!("Unexpected success"),
+        }
+    }
+}
+
 #[test]
 // Should return InvalidTransactionSignature if an input is modified.
 fn test_transaction_signature_err_modified_input() {
@@ -649,7 +839,7 @@ fn test_validate_transaction_fee() {
                 create_test_tx_with_amount(block_version, INITIALIZE_LEDGER_AMOUNT, 0);
             assert_eq!(
                 validate_transaction_fee(&tx, 1000),
-                Err(TransactionValidationError::TxFeeError)
+                Err(TransactionValidationError::TxFeeError(0, 1000))
             );
         }
 
@@ -660,7 +850,10 @@ fn test_validate_transaction_fee() {
                 create_test_tx_with_amount(block_version, INITIALIZE_LEDGER_AMOUNT - fee, fee);
             assert_eq!(
                 validate_transaction_fee(&tx, Mob::MINIMUM_FEE),
-                Err(TransactionValidationError::TxFeeError)
+                Err(TransactionValidationError::TxFeeError(
+                    fee,
+                    Mob::MINIMUM_FEE
+                ))
             );
         }
 
@@ -703,7 +896,10 @@ fn test_validate_tombstone_tombstone_block_exceeded() {
         let tombstone_block_index = 7;
         assert_eq!(
             validate_tombstone(current_block_index, tombstone_block_index),
-            Err(TransactionValidationError::TombstoneBlockExceeded)
+            Err(TransactionValidationError::TombstoneBlockExceeded(
+                current_block_index,
+                tombstone_block_index
+            ))
         );
     }
 
@@ -713,7 +909,10 @@ fn test_validate_tombstone_tombstone_block_exceeded() {
         let tombstone_block_index = 7;
         assert_eq!(
             validate_tombstone(current_block_index, tombstone_block_index),
-            Err(TransactionValidationError::TombstoneBlockExceeded)
+            Err(TransactionValidationError::TombstoneBlockExceeded(
+                current_block_index,
+                tombstone_block_index
+            ))
         );
     }
 }
@@ -748,7 +947,10 @@ fn test_validate_tombstone_tombstone_block_too_far() {
         let tombstone_block_index = current_block_index + MAX_TOMBSTONE_BLOCKS + 1;
         assert_eq!(
             validate_tombstone(current_block_index, tombstone_block_index),
-            Err(TransactionValidationError::TombstoneBlockTooFar)
+            Err(TransactionValidationError::TombstoneBlockTooFar(
+                tombstone_block_index,
+                current_block_index + MAX_TOMBSTONE_BLOCKS
+            ))
         );
     }
 }
@@ -846,3 +1048,112 @@ fn test_input_rules_validation() {
 
     validate_all_input_rules(block_version, &tx).unwrap();
 }
+
+#[cfg(feature = "parallel")]
+mod parallel {
+    use super::*;
+    use mc_transaction_core::tx::Tx;
+
+    fn root_proofs(tx: &Tx, ledger: &impl Ledger) -> Vec<TxOutMembershipProof> {
+        let highest_indices = tx.get_membership_proof_highest_indices();
+        ledger
+            .get_tx_out_proof_of_memberships(&highest_indices)
+            .expect("failed getting proofs")
+    }
+
+    #[test]
+    // `validate_parallel` should return the same result as `validate`.
+    fn test_validate_parallel_agrees_with_validate() {
+        let mut rng = get_seeded_rng();
+
+        for block_version in BlockVersion::iterator() {
+            let (tx, ledger) = create_test_tx(block_version);
+            let root_proofs = root_proofs(&tx, &ledger);
+            let current_block_index = tx.prefix.tombstone_block - 1;
+
+            let mut modified_fee = tx.clone();
+            modified_fee.prefix.fee += 1;
+
+            let mut modified_output = tx.clone();
+            let output = modified_output.prefix.outputs[0].clone();
+            modified_output.prefix.outputs.push(output);
+
+            let cases = [
+                (&tx, &root_proofs[..], 0),
+                // The fee is too low.
+                (&tx, &root_proofs[..], u64::MAX),
+                // A root proof is missing.
+                (&tx, &root_proofs[1..], 0),
+                // The signature is invalid.
+                (&modified_fee, &root_proofs[..], 0),
+                // The signature is invalid, and the output public keys are not unique.
+                (&modified_output, &root_proofs[..], 0),
+            ];
+
+            for (tx, root_proofs, minimum_fee) in cases {
+                let expected = validate(
+                    tx,
+                    current_block_index,
+                    block_version,
+                    root_proofs,
+                    minimum_fee,
+                    &mut rng,
+                );
+                assert_eq!(
+                    validate_parallel(
+                        tx,
+                        current_block_index,
+                        block_version,
+                        root_proofs,
+                        minimum_fee,
+                        &mut rng,
+                    ),
+                    expected,
+                    "failed at block version: {}",
+                    block_version
+                );
+            }
+        }
+    }
+
+    #[test]
+    // `validate_txs_parallel` should return OK for valid transactions, and the
+    // index of the first invalid transaction otherwise.
+    fn test_validate_txs_parallel() {
+        let mut rng = get_seeded_rng();
+
+        for block_version in BlockVersion::iterator() {
+            let txs_and_proofs: Vec<_> = (0..4)
+                .map(|_| {
+                    let (tx, ledger) = create_test_tx(block_version);
+                    let root_proofs = root_proofs(&tx, &ledger);
+                    (tx, root_proofs)
+                })
+                .collect();
+            let current_block_index = txs_and_proofs[0].0.prefix.tombstone_block - 1;
+
+            let mut items: Vec<TxValidation> = txs_and_proofs
+                .iter()
+                .map(|(tx, root_proofs)| TxValidation {
+                    tx,
+                    root_proofs,
+                    minimum_fee: 0,
+                })
+                .collect();
+            assert_eq!(
+                validate_txs_parallel(&items, current_block_index, block_version, &mut rng),
+                Ok(())
+            );
+
+            // The second transaction is missing a root proof, and the fee of the
+            // fourth is too low.
+            let root_proofs = items[1].root_proofs;
+            items[1].root_proofs = &root_proofs[1..];
+            items[3].minimum_fee = u64::MAX;
+            assert_eq!(
+                validate_txs_parallel(&items, current_block_index, block_version, &mut rng),
+                Err((1, TransactionValidationError::InvalidLedgerContext))
+            );
+        }
+    }
+}
@@ -0,0 +1,166 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! A transaction builder which derives all of its randomness from a seed.
+//!
+//! This is meant for generating test vectors: SDKs in other languages can
+//! build the same transaction from the same seed, inputs and outputs, and
+//! check that they get exactly the same bytes. It must not be used for real
+//! transactions, since anyone who knows the seed knows the output secrets.
+
+use crate::{
+    InputCredentials, ReservedSubaddresses, TransactionBuilder, TxBuilderError, TxOutContext,
+};
+use mc_account_keys::PublicAddress;
+use mc_crypto_ring_signature_signer::RingSigner;
+use mc_fog_report_validation::FogPubkeyResolver;
+use mc_transaction_core::{tx::Tx, Amount, SignedContingentInput, SignedContingentInputError};
+use rand_core::SeedableRng;
+use rand_hc::Hc128Rng;
+
+/// Wraps a `TransactionBuilder`, and supplies it with randomness from a
+/// seeded RNG, so that the same seed and the same sequence of calls always
+/// produce byte-identical transactions.
+///
+/// The RNG is HC-128, whose output only depends on the seed, on every
+/// platform. Inputs are sorted by the public key of their first ring element,
+/// and outputs by their public key, as the `TransactionBuilder` always does.
+///
+/// For the transaction to be reproducible, everything else which goes into it
+/// must be too: the input credentials (including their rings and membership
+/// proofs), the memo builder, the fog resolver and the ring signer.
+#[derive(Debug)]
+pub struct DeterministicTransactionBuilder<FPR: FogPubkeyResolver> {
+    builder: TransactionBuilder<FPR>,
+    rng: Hc128Rng,
+}
+
+impl<FPR: FogPubkeyResolver> DeterministicTransactionBuilder<FPR> {
+    /// Wrap a transaction builder, which should not have any outputs yet.
+    ///
+    /// # Arguments
+    /// * `builder` - The transaction builder to add inputs and outputs to
+    /// * `seed` - The seed of all of the randomness of the transaction
+    pub fn new(builder: TransactionBuilder<FPR>, seed: [u8; 32]) -> Self {
+        Self {
+            builder,
+            rng: Hc128Rng::from_seed(seed),
+        }
+    }
+
+    /// Add an Input to the transaction, see `TransactionBuilder::add_input`.
+    pub fn add_input(&mut self, input_credentials: InputCredentials) {
+        self.builder.add_input(input_credentials)
+    }
+
+    /// Add a pre-signed Input to the transaction, see
+    /// `TransactionBuilder::add_presigned_input`.
+    pub fn add_presigned_input(
+        &mut self,
+        sci: SignedContingentInput,
+    ) -> Result<(), SignedContingentInputError> {
+        self.builder.add_presigned_input(sci)
+    }
+
+    /// Add a non-change output to the transaction, see
+    /// `TransactionBuilder::add_output`.
+    pub fn add_output(
+        &mut self,
+        amount: Amount,
+        recipient: &PublicAddress,
+    ) -> Result<TxOutContext, TxBuilderError> {
+        self.builder.add_output(amount, recipient, &mut self.rng)
+    }
+
+    /// Add a change output to the transaction, see
+    /// `TransactionBuilder::add_change_output`.
+    pub fn add_change_output(
+        &mut self,
+        amount: Amount,
+        change_destination: &ReservedSubaddresses,
+    ) -> Result<TxOutContext, TxBuilderError> {
+        self.builder
+            .add_change_output(amount, change_destination, &mut self.rng)
+    }
+
+    /// Add a gift code output to the transaction, see
+    /// `TransactionBuilder::add_gift_code_output`.
+    pub fn add_gift_code_output(
+        &mut self,
+        amount: Amount,
+        reserved_subaddresses: &ReservedSubaddresses,
+    ) -> Result<TxOutContext, TxBuilderError> {
+        self.builder
+            .add_gift_code_output(amount, reserved_subaddresses, &mut self.rng)
+    }
+
+    /// Set the tombstone block, see `TransactionBuilder::set_tombstone_block`.
+    pub fn set_tombstone_block(&mut self, tombstone_block: u64) -> u64 {
+        self.builder.set_tombstone_block(tombstone_block)
+    }
+
+    /// Consume the builder and return the transaction.
+    pub fn build<S: RingSigner + ?Sized>(mut self, ring_signer: &S) -> Result<Tx, TxBuilderError> {
+        self.builder.build(ring_signer, &mut self.rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::get_input_credentials, EmptyMemoBuilder};
+    use mc_account_keys::AccountKey;
+    use mc_crypto_ring_signature_signer::NoKeysRingSigner;
+    use mc_fog_report_validation_test_utils::MockFogResolver;
+    use mc_transaction_core::{tokens::Mob, BlockVersion, Token};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    // Build a transaction from fixed inputs, with the given seed
+    fn build_tx(seed: [u8; 32]) -> Vec<u8> {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+        let block_version = BlockVersion::MAX;
+        let fpr = MockFogResolver::default();
+        let sender = AccountKey::random(&mut rng);
+        let recipient = AccountKey::random(&mut rng);
+
+        let value = 1_000_000 * Mob::MINIMUM_FEE;
+        let input_credentials = get_input_credentials(
+            block_version,
+            Amount::new(value, Mob::ID),
+            &sender,
+            &fpr,
+            &mut rng,
+        );
+
+        let builder = TransactionBuilder::new(
+            block_version,
+            Amount::new(Mob::MINIMUM_FEE, Mob::ID),
+            fpr,
+            EmptyMemoBuilder::default(),
+        )
+        .unwrap();
+        let mut builder = DeterministicTransactionBuilder::new(builder, seed);
+        builder.add_input(input_credentials);
+        builder
+            .add_output(
+                Amount::new(value / 2, Mob::ID),
+                &recipient.default_subaddress(),
+            )
+            .unwrap();
+        builder
+            .add_change_output(
+                Amount::new(value - value / 2 - Mob::MINIMUM_FEE, Mob::ID),
+                &ReservedSubaddresses::from(&sender),
+            )
+            .unwrap();
+        builder.set_tombstone_block(100);
+
+        let tx = builder.build(&NoKeysRingSigner {}).unwrap();
+        mc_util_serial::encode(&tx)
+    }
+
+    #[test]
+    fn test_same_seed_builds_identical_transactions() {
+        assert_eq!(build_tx([7u8; 32]), build_tx([7u8; 32]));
+        assert_ne!(build_tx([7u8; 32]), build_tx([8u8; 32]));
+    }
+}
@@ -0,0 +1,234 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Finding the outputs which cost more to spend than they are worth.
+//!
+//! The fee of a transaction is the same whatever its number of inputs, up to
+//! `MAX_INPUTS`. An output worth less than the fee is not worth spending on
+//! its own, but it is worth merging with others, as long as they cover the fee
+//! together. If even a transaction full of outputs like it would not cover the
+//! fee, the output is dust, and it is best left alone.
+//!
+//! A `DustPolicy` classifies outputs according to the current minimum fees,
+//! and suggests which ones to merge to reach a given number of outputs.
+
+use displaydoc::Display;
+use mc_transaction_core::{constants::MAX_INPUTS, Amount, TokenId};
+use std::collections::BTreeMap;
+
+/// How worthwhile it is to spend an output.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum OutputClass {
+    /// A transaction made only of outputs like this one, as many as it can
+    /// have, would not cover its fee.
+    Dust,
+    /// The output is worth at most the fee, so it is only worth spending
+    /// together with other outputs.
+    Uneconomic,
+    /// The output is worth more than the fee.
+    Economic,
+}
+
+/// Classifies outputs according to the minimum fee of their token.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DustPolicy {
+    minimum_fees: BTreeMap<TokenId, u64>,
+}
+
+/// The merges which bring a set of outputs down to a target number of
+/// outputs, with as few transactions as possible.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConsolidationSuggestion {
+    /// The fee paid by each transaction.
+    pub fee: u64,
+
+    /// The number of transactions needed to reach the target number of
+    /// outputs.
+    pub num_txs: usize,
+
+    /// The inputs of the transactions that can be built right away, as indices
+    /// into the values passed to `DustPolicy::suggest_consolidation`. The
+    /// other transactions spend the outputs of these, so they can only be
+    /// planned once these have landed in the ledger.
+    pub tx_inputs: Vec<Vec<usize>>,
+
+    /// The indices of the dust outputs, which are not merged and do not count
+    /// towards the target.
+    pub dust: Vec<usize>,
+}
+
+impl ConsolidationSuggestion {
+    /// The fee paid by all the transactions of the suggestion.
+    pub fn total_fee(&self) -> u64 {
+        self.num_txs as u64 * self.fee
+    }
+}
+
+impl DustPolicy {
+    /// Create a policy for the given minimum fee of each token, e.g. the
+    /// `minimum_fees` of the last block info from the network.
+    pub fn new(minimum_fees: BTreeMap<TokenId, u64>) -> Self {
+        Self { minimum_fees }
+    }
+
+    /// The minimum fee of a token.
+    pub fn fee(&self, token_id: TokenId) -> Result<u64, DustPolicyError> {
+        self.minimum_fees
+            .get(&token_id)
+            .cloned()
+            .ok_or(DustPolicyError::UnknownTokenId(token_id))
+    }
+
+    /// Classify an output by its amount.
+    pub fn classify(&self, amount: Amount) -> Result<OutputClass, DustPolicyError> {
+        let fee = self.fee(amount.token_id)?;
+        Ok(classify_value(amount.value, fee))
+    }
+
+    /// Suggest which outputs of a token to merge, so that at most
+    /// `target_num_outputs` of them are left, not counting the dust.
+    ///
+    /// Each transaction merges up to `MAX_INPUTS` outputs, the smallest ones
+    /// first, and all of them merge as many as they can except the one that
+    /// reaches the target.
+    ///
+    /// # Arguments
+    /// * `token_id` - The token of the outputs
+    /// * `values` - The values of the outputs
+    /// * `target_num_outputs` - The number of outputs to consolidate into. It
+    ///   is at least 1.
+    pub fn suggest_consolidation(
+        &self,
+        token_id: TokenId,
+        values: &[u64],
+        target_num_outputs: usize,
+    ) -> Result<ConsolidationSuggestion, DustPolicyError> {
+        let fee = self.fee(token_id)?;
+        let max_inputs = MAX_INPUTS as usize;
+
+        let (dust, mut mergeable): (Vec<usize>, Vec<usize>) = (0..values.len())
+            .partition(|index| classify_value(values[*index], fee) == OutputClass::Dust);
+        mergeable.sort_by_key(|index| values[*index]);
+
+        // Each transaction reduces the number of outputs by at most
+        // MAX_INPUTS - 1.
+        let num_merges = mergeable.len().saturating_sub(target_num_outputs.max(1));
+        let num_txs = (num_merges + max_inputs - 2) / (max_inputs - 1);
+
+        let mut remaining_merges = num_merges;
+        let mut remaining_inputs = mergeable.as_slice();
+        let mut tx_inputs = Vec::new();
+        while remaining_merges > 0 {
+            let num_inputs = max_inputs.min(remaining_merges + 1);
+            if remaining_inputs.len() < num_inputs {
+                break;
+            }
+            let (selected, rest) = remaining_inputs.split_at(num_inputs);
+
+            let total: u128 = selected.iter().map(|index| values[*index] as u128).sum();
+            if total <= fee as u128 {
+                return Err(DustPolicyError::ConsolidationNotBeneficial);
+            }
+
+            tx_inputs.push(selected.to_vec());
+            remaining_merges -= num_inputs - 1;
+            remaining_inputs = rest;
+        }
+
+        Ok(ConsolidationSuggestion {
+            fee,
+            num_txs,
+            tx_inputs,
+            dust,
+        })
+    }
+}
+
+// Classify the value of an output, given the fee of its token
+fn classify_value(value: u64, fee: u64) -> OutputClass {
+    if (value as u128) * (MAX_INPUTS as u128) <= fee as u128 {
+        OutputClass::Dust
+    } else if value <= fee {
+        OutputClass::Uneconomic
+    } else {
+        OutputClass::Economic
+    }
+}
+
+/// An error that can occur when applying a dust policy
+#[derive(Clone, Debug, Display, Eq, PartialEq)]
+pub enum DustPolicyError {
+    /// There is no minimum fee for token {0}
+    UnknownTokenId(TokenId),
+    /// Merging the outputs would cost more in fees than they are worth
+    ConsolidationNotBeneficial,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_transaction_core::{tokens::Mob, Token};
+
+    fn policy() -> DustPolicy {
+        DustPolicy::new(BTreeMap::from([(Mob::ID, 1600), (TokenId::from(1), 16)]))
+    }
+
+    #[test]
+    fn test_classify_outputs() {
+        let policy = policy();
+        let classify = |value, token_id| policy.classify(Amount::new(value, token_id));
+
+        assert_eq!(classify(0, Mob::ID), Ok(OutputClass::Dust));
+        assert_eq!(classify(100, Mob::ID), Ok(OutputClass::Dust));
+        assert_eq!(classify(101, Mob::ID), Ok(OutputClass::Uneconomic));
+        assert_eq!(classify(1600, Mob::ID), Ok(OutputClass::Uneconomic));
+        assert_eq!(classify(1601, Mob::ID), Ok(OutputClass::Economic));
+        assert_eq!(classify(101, TokenId::from(1)), Ok(OutputClass::Economic));
+        assert_eq!(classify(u64::MAX, Mob::ID), Ok(OutputClass::Economic));
+        assert_eq!(
+            classify(101, TokenId::from(2)),
+            Err(DustPolicyError::UnknownTokenId(TokenId::from(2)))
+        );
+    }
+
+    #[test]
+    fn test_suggest_consolidation() {
+        let policy = policy();
+
+        // 40 outputs, the first 5 of which are dust, in descending order
+        let mut values: Vec<u64> = (0..40).map(|i| 1000 + 10 * i).collect();
+        for value in values.iter_mut().take(5) {
+            *value = 50;
+        }
+        values.reverse();
+
+        // 35 outputs into 1 takes 3 transactions, the first two of which can be
+        // built right away from the 32 smallest outputs.
+        let suggestion = policy.suggest_consolidation(Mob::ID, &values, 1).unwrap();
+        assert_eq!(suggestion.num_txs, 3);
+        assert_eq!(suggestion.total_fee(), 3 * 1600);
+        assert_eq!(suggestion.dust, (35..40).collect::<Vec<_>>());
+        assert_eq!(
+            suggestion.tx_inputs,
+            vec![(19..35).rev().collect::<Vec<_>>(), (3..19).rev().collect()]
+        );
+
+        // 35 outputs into 20 takes one transaction.
+        let suggestion = policy.suggest_consolidation(Mob::ID, &values, 20).unwrap();
+        assert_eq!(suggestion.num_txs, 1);
+        assert_eq!(
+            suggestion.tx_inputs,
+            vec![(19..35).rev().collect::<Vec<_>>()]
+        );
+
+        // Nothing to do when there are fewer outputs than the target.
+        let suggestion = policy.suggest_consolidation(Mob::ID, &values, 35).unwrap();
+        assert_eq!(suggestion.num_txs, 0);
+        assert!(suggestion.tx_inputs.is_empty());
+
+        // Merging two uneconomic outputs which do not cover the fee is refused.
+        assert_eq!(
+            policy.suggest_consolidation(Mob::ID, &[400, 500, 2000], 2),
+            Err(DustPolicyError::ConsolidationNotBeneficial)
+        );
+    }
+}
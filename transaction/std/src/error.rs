@@ -4,7 +4,8 @@ use displaydoc::Display;
 use mc_crypto_ring_signature_signer::Error as SignerError;
 use mc_fog_report_validation::FogPubkeyError;
 use mc_transaction_core::{
-    ring_ct::Error as RingCtError, AmountError, NewMemoError, NewTxError, TokenId,
+    ring_ct::Error as RingCtError, AmountError, NewMemoError, NewTxError,
+    SignedContingentInputError, TokenId,
 };
 
 /// An error that can occur when using the TransactionBuilder
@@ -66,6 +67,12 @@ pub enum TxBuilderError {
 
     /// Signer: {0}
     Signer(SignerError),
+
+    /// Token {0} is not balanced: inputs total {1}, outputs and fee total {2}
+    UnbalancedToken(TokenId, u128, u128),
+
+    /// Too many additional fog hints: {0} > {1}
+    TooManyAdditionalFogHints(usize, usize),
 }
 
 impl From<mc_util_serial::encode::Error> for TxBuilderError {
@@ -136,3 +143,47 @@ impl From<NewMemoError> for SignedContingentInputBuilderError {
         SignedContingentInputBuilderError::Memo(src)
     }
 }
+
+/// An error that can occur when making or checking partial fills of a signed
+/// contingent input
+#[derive(Debug, Display)]
+pub enum PartialFillError {
+    /// The counter amount must be in another token than the input: {0}
+    SameTokenId(TokenId),
+    /// Fill value {0} is below the minimum fill value {1}
+    FillTooSmall(u64, u64),
+    /// Fill value {0} exceeds the input value {1}
+    FillTooLarge(u64, u64),
+    /// No fill values were added
+    NoFillValues,
+    /// Unexpected token id in the required outputs: {0}
+    UnexpectedTokenId(TokenId),
+    /// Change {0} exceeds the input value {1}
+    ChangeExceedsInput(u128, u64),
+    /// Payment {0} does not match the payment {1} required for this fill
+    PaymentMismatch(u128, u64),
+    /// Signed contingent input: {0}
+    SignedContingentInput(SignedContingentInputError),
+    /// Signed contingent input builder: {0}
+    Builder(SignedContingentInputBuilderError),
+    /// Tx builder: {0}
+    TxBuilder(TxBuilderError),
+}
+
+impl From<SignedContingentInputError> for PartialFillError {
+    fn from(src: SignedContingentInputError) -> Self {
+        PartialFillError::SignedContingentInput(src)
+    }
+}
+
+impl From<SignedContingentInputBuilderError> for PartialFillError {
+    fn from(src: SignedContingentInputBuilderError) -> Self {
+        PartialFillError::Builder(src)
+    }
+}
+
+impl From<TxBuilderError> for PartialFillError {
+    fn from(src: TxBuilderError) -> Self {
+        PartialFillError::TxBuilder(src)
+    }
+}
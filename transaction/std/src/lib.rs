@@ -7,35 +7,51 @@
 
 extern crate core;
 
+mod deterministic_transaction_builder;
+mod dust_policy;
 mod error;
 mod input_credentials;
 mod input_materials;
 mod memo;
 mod memo_builder;
+mod partial_fill;
 mod reserved_subaddresses;
 mod signed_contingent_input_builder;
+mod token_metadata;
 mod transaction_builder;
+mod tx_out_receipt;
 
 #[cfg(any(test, feature = "test-only"))]
 pub mod test_utils;
 
-pub use error::{SignedContingentInputBuilderError, TxBuilderError};
+pub use deterministic_transaction_builder::DeterministicTransactionBuilder;
+pub use dust_policy::{ConsolidationSuggestion, DustPolicy, DustPolicyError, OutputClass};
+pub use error::{PartialFillError, SignedContingentInputBuilderError, TxBuilderError};
 pub use input_credentials::InputCredentials;
 pub use memo::{
-    AuthenticatedSenderMemo, AuthenticatedSenderWithPaymentRequestIdMemo, BurnRedemptionMemo,
-    DestinationMemo, DestinationMemoError, GiftCodeCancellationMemo, GiftCodeFundingMemo,
-    GiftCodeSenderMemo, MemoDecodingError, MemoType, RegisteredMemoType, SenderMemoCredential,
-    UnusedMemo,
+    AuthenticatedPaymentRequestMemo, AuthenticatedSenderMemo,
+    AuthenticatedSenderWithPaymentRequestIdMemo, BurnRedemptionMemo, BurnRedemptionMemoError,
+    CustomMemo, DecodedMemo, DestinationMemo, DestinationMemoError, GiftCodeCancellationMemo,
+    GiftCodeFundingMemo, GiftCodeSenderMemo, MemoDecodingError, MemoRegistryError, MemoType,
+    MemoTypeRegistry, RegisteredMemoType, SenderMemoCredential, UnusedMemo,
 };
 pub use memo_builder::{
-    BurnRedemptionMemoBuilder, EmptyMemoBuilder, GiftCodeCancellationMemoBuilder,
-    GiftCodeFundingMemoBuilder, GiftCodeSenderMemoBuilder, MemoBuilder, RTHMemoBuilder,
+    BurnRedemptionMemoBuilder, CustomMemoBuilder, CustomMemoEncoder, EmptyMemoBuilder,
+    GiftCodeCancellationMemoBuilder, GiftCodeFundingMemoBuilder, GiftCodeSenderMemoBuilder,
+    MemoBuilder, RTHMemoBuilder,
+};
+pub use partial_fill::{
+    validate_partial_fill, PartialFill, PartialFillRules, PartialFillSciBuilder,
 };
 pub use reserved_subaddresses::ReservedSubaddresses;
 pub use signed_contingent_input_builder::SignedContingentInputBuilder;
+pub use token_metadata::{
+    token_metadata_signing_context, TokenMetadata, TokenMetadataError, TokenMetadataRegistry,
+};
 pub use transaction_builder::{
     DefaultTxOutputsOrdering, TransactionBuilder, TxOutContext, TxOutputsOrdering,
 };
+pub use tx_out_receipt::{TxOutAmountProof, TxOutReceipt, TxOutReceiptError};
 
 // Re-export this to help the exported macros work
 pub use mc_transaction_core::MemoPayload;
@@ -0,0 +1,176 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Object for 0x0103 Authenticated Payment Request memo type
+//!
+//! This extends the 0x0101 Authenticated Sender With Payment Request Id memo
+//! type with a commitment to the amount of the TxOut, so that a merchant can
+//! reconcile the payment against the invoice it requested.
+
+use super::{
+    authenticated_common::{compute_category1_hmac, validate_authenticated_sender},
+    credential::SenderMemoCredential,
+    RegisteredMemoType,
+};
+use crate::impl_memo_type_conversions;
+use mc_account_keys::{PublicAddress, ShortAddressHash};
+use mc_crypto_keys::{
+    CompressedRistrettoPublic, KexReusablePrivate, RistrettoPrivate, RistrettoPublic,
+};
+use mc_transaction_core::CompressedCommitment;
+use subtle::{Choice, ConstantTimeEq};
+
+/// A memo that the sender writes to convey their identity in an authenticated
+/// but deniable way, for the recipient of a TxOut, which also includes a
+/// payment request id number and a commitment to the amount paid under the
+/// MAC.
+///
+/// The amount commitment is the first 24 bytes of the amount commitment of
+/// the TxOut, which is all that fits in the memo alongside the payment request
+/// id.
+///
+/// The recipient of this memo type should:
+/// * First, use sender_address_hash to look up the address of the sender, from
+///   among their contacts. If the sender isn't known then we can't validate.
+/// * Then, call validate to check the mac and confirm authenticity.
+/// * Then, call validate_amount_commitment with the amount commitment of the
+///   TxOut, to confirm that the sender made this payment for this amount.
+/// * We can extract the payment request id to link this to a payment request.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct AuthenticatedPaymentRequestMemo {
+    /// The memo data
+    memo_data: [u8; 64],
+}
+
+impl RegisteredMemoType for AuthenticatedPaymentRequestMemo {
+    const MEMO_TYPE_BYTES: [u8; 2] = [0x01, 0x03];
+}
+
+impl AuthenticatedPaymentRequestMemo {
+    /// Create a new AuthenticatedPaymentRequestMemo given credential,
+    /// recipient public key, tx out public key, payment request id and amount
+    /// commitment
+    ///
+    /// # Arguments:
+    /// * cred: A sender memo credential tied to the address we wish to identify
+    ///   ourselves as
+    /// * receiving_subaddress_view_public_key: This is the view public key from
+    ///   the public address of recipient
+    /// * tx_out_public_key: The public_key of the TxOut to which we will attach
+    ///   this memo
+    /// * payment_request_id: The id of the payment request this pays
+    /// * amount_commitment: The amount commitment of the TxOut to which we will
+    ///   attach this memo
+    pub fn new(
+        cred: &SenderMemoCredential,
+        receiving_subaddress_view_public_key: &RistrettoPublic,
+        tx_out_public_key: &CompressedRistrettoPublic,
+        payment_request_id: u64,
+        amount_commitment: &CompressedCommitment,
+    ) -> Self {
+        // The layout of the memo is:
+        // [0-16) address hash
+        // [16-24) payment request id
+        // [24-48) amount commitment (first 24 bytes)
+        // [48-64) HMAC
+
+        let mut memo_data = [0u8; 64];
+        memo_data[..16].copy_from_slice(cred.address_hash.as_ref());
+        memo_data[16..24].copy_from_slice(&payment_request_id.to_be_bytes());
+        memo_data[24..48].copy_from_slice(&amount_commitment.as_ref()[..24]);
+
+        let shared_secret = cred
+            .subaddress_spend_private_key
+            .key_exchange(receiving_subaddress_view_public_key);
+
+        let hmac_value = compute_category1_hmac(
+            shared_secret.as_ref(),
+            tx_out_public_key,
+            Self::MEMO_TYPE_BYTES,
+            &memo_data,
+        );
+        memo_data[48..].copy_from_slice(&hmac_value);
+
+        Self { memo_data }
+    }
+
+    /// Get the sender address hash from the memo
+    pub fn sender_address_hash(&self) -> ShortAddressHash {
+        let bytes: [u8; 16] = self.memo_data[0..16].try_into().unwrap();
+        ShortAddressHash::from(bytes)
+    }
+
+    /// Get the payment request id from the memo
+    pub fn payment_request_id(&self) -> u64 {
+        u64::from_be_bytes(self.memo_data[16..24].try_into().unwrap())
+    }
+
+    /// Get the (first 24 bytes of the) amount commitment from the memo
+    pub fn amount_commitment_bytes(&self) -> &[u8] {
+        &self.memo_data[24..48]
+    }
+
+    /// Validate an AuthenticatedPaymentRequestMemo
+    ///
+    /// This checks the mac the same way as
+    /// AuthenticatedSenderWithPaymentRequestIdMemo::validate does. It does
+    /// not check the amount commitment, see validate_amount_commitment.
+    ///
+    /// Arguments:
+    /// * sender_address: The public address of the sender. This can be looked
+    ///   up by the ShortAddressHash provided.
+    /// * receiving_subaddress_view_private_key: This is usually our
+    ///   default_subaddress_view_private_key, but should correspond to whatever
+    ///   subaddress recieved this TxOut.
+    /// * tx_out_public_key: The public key of the TxOut to which this memo is
+    ///   attached.
+    ///
+    /// Returns:
+    /// * subtle::Choice(1u8) if validation passed, subtle::Choice(0u8) if hmac
+    ///   comparison failed.
+    ///
+    /// This function is constant-time.
+    pub fn validate(
+        &self,
+        sender_address: &PublicAddress,
+        receiving_subaddress_view_private_key: &RistrettoPrivate,
+        tx_out_public_key: &CompressedRistrettoPublic,
+    ) -> Choice {
+        validate_authenticated_sender(
+            sender_address,
+            receiving_subaddress_view_private_key,
+            tx_out_public_key,
+            Self::MEMO_TYPE_BYTES,
+            &self.memo_data,
+        )
+    }
+
+    /// Check that the memo commits to the given amount commitment, which
+    /// should be the amount commitment of the TxOut to which this memo is
+    /// attached.
+    ///
+    /// Returns:
+    /// * subtle::Choice(1u8) if the commitments match, subtle::Choice(0u8)
+    ///   otherwise.
+    ///
+    /// This function is constant-time.
+    pub fn validate_amount_commitment(&self, amount_commitment: &CompressedCommitment) -> Choice {
+        self.amount_commitment_bytes()
+            .ct_eq(&amount_commitment.as_ref()[..24])
+    }
+}
+
+impl From<&[u8; 64]> for AuthenticatedPaymentRequestMemo {
+    fn from(src: &[u8; 64]) -> Self {
+        let mut memo_data = [0u8; 64];
+        memo_data.copy_from_slice(src);
+        Self { memo_data }
+    }
+}
+
+impl From<AuthenticatedPaymentRequestMemo> for [u8; 64] {
+    fn from(src: AuthenticatedPaymentRequestMemo) -> [u8; 64] {
+        src.memo_data
+    }
+}
+
+impl_memo_type_conversions! { AuthenticatedPaymentRequestMemo }
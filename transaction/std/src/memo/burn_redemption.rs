@@ -7,12 +7,22 @@
 
 use super::RegisteredMemoType;
 use crate::impl_memo_type_conversions;
+use displaydoc::Display;
+use mc_account_keys::{burn_address, burn_address_view_private};
+use mc_crypto_keys::{KeyError, RistrettoPublic};
+use mc_transaction_core::{
+    get_tx_out_shared_secret, onetime_keys::recover_public_subaddress_spend_key, tx::TxOut,
+};
 
 /// A memo that the sender writes to associate a burn of an assert on the
 /// MobileCoin blockchain with a redemption of another asset on a different
 /// blockchain. The main intended use-case for this is burning of tokens that
 /// are correlated with redemption of some other asset on a different
 /// blockchain.
+///
+/// Bridges which identify the redemption by the id of a transaction on the
+/// other blockchain should use `from_external_tx_id` and `external_tx_id`, so
+/// that they all encode it the same way.
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub struct BurnRedemptionMemo {
     /// The memo data.
@@ -29,6 +39,9 @@ impl BurnRedemptionMemo {
     /// The length of the custom memo data.
     pub const MEMO_DATA_LEN: usize = 64;
 
+    /// The maximum length of an external chain tx id.
+    pub const EXTERNAL_TX_ID_MAX_LEN: usize = Self::MEMO_DATA_LEN - 1;
+
     /// Create a new BurnRedemptionMemo.
     pub fn new(memo_data: [u8; Self::MEMO_DATA_LEN]) -> Self {
         BurnRedemptionMemo { memo_data }
@@ -38,6 +51,62 @@ impl BurnRedemptionMemo {
     pub fn memo_data(&self) -> &[u8; Self::MEMO_DATA_LEN] {
         &self.memo_data
     }
+
+    /// Create a new BurnRedemptionMemo for the redemption made by a
+    /// transaction on another blockchain, given the id of that transaction.
+    pub fn from_external_tx_id(external_tx_id: &[u8]) -> Result<Self, BurnRedemptionMemoError> {
+        // The layout of the memo data is:
+        // [0-1) length of the external tx id, n
+        // [1-1+n) external tx id
+        // [1+n-64) zero padding
+        let len = external_tx_id.len();
+        if len == 0 || len > Self::EXTERNAL_TX_ID_MAX_LEN {
+            return Err(BurnRedemptionMemoError::InvalidExternalTxIdLength(len));
+        }
+        let mut memo_data = [0u8; Self::MEMO_DATA_LEN];
+        memo_data[0] = len as u8;
+        memo_data[1..1 + len].copy_from_slice(external_tx_id);
+        Ok(Self { memo_data })
+    }
+
+    /// Get the external chain tx id from the memo, if the memo data is an
+    /// external tx id encoded by `from_external_tx_id`.
+    pub fn external_tx_id(&self) -> Result<&[u8], BurnRedemptionMemoError> {
+        let len = self.memo_data[0] as usize;
+        if len == 0 || len > Self::EXTERNAL_TX_ID_MAX_LEN {
+            return Err(BurnRedemptionMemoError::InvalidExternalTxIdLength(len));
+        }
+        if self.memo_data[1 + len..].iter().any(|byte| *byte != 0) {
+            return Err(BurnRedemptionMemoError::NonCanonicalPadding);
+        }
+        Ok(&self.memo_data[1..1 + len])
+    }
+
+    /// Decode the BurnRedemptionMemo of a TxOut which was sent to the burn
+    /// address.
+    ///
+    /// This fails if the TxOut was not sent to the burn address, or if its memo
+    /// is not a BurnRedemptionMemo.
+    pub fn from_burn_tx_out(tx_out: &TxOut) -> Result<Self, BurnRedemptionMemoError> {
+        let tx_out_public_key = RistrettoPublic::try_from(&tx_out.public_key)?;
+        let spend_public_key = recover_public_subaddress_spend_key(
+            &burn_address_view_private(),
+            &RistrettoPublic::try_from(&tx_out.target_key)?,
+            &tx_out_public_key,
+        );
+        if &spend_public_key != burn_address().spend_public_key() {
+            return Err(BurnRedemptionMemoError::NotBurnTxOut);
+        }
+
+        let shared_secret =
+            get_tx_out_shared_secret(&burn_address_view_private(), &tx_out_public_key);
+        let memo_payload = tx_out.decrypt_memo(&shared_secret);
+        let memo_type_bytes = *memo_payload.get_memo_type();
+        if memo_type_bytes != Self::MEMO_TYPE_BYTES {
+            return Err(BurnRedemptionMemoError::WrongMemoType(memo_type_bytes));
+        }
+        Ok(Self::from(memo_payload.get_memo_data()))
+    }
 }
 
 impl From<&[u8; Self::MEMO_DATA_LEN]> for BurnRedemptionMemo {
@@ -54,4 +123,25 @@ impl From<BurnRedemptionMemo> for [u8; BurnRedemptionMemo::MEMO_DATA_LEN] {
     }
 }
 
+/// An error that can occur when encoding or decoding a burn redemption memo
+#[derive(Clone, Debug, Display, Eq, PartialEq)]
+pub enum BurnRedemptionMemoError {
+    /// An external tx id must be between 1 and 63 bytes long, found {0}
+    InvalidExternalTxIdLength(usize),
+    /// The external tx id is not followed by zero padding
+    NonCanonicalPadding,
+    /// The TxOut was not sent to the burn address
+    NotBurnTxOut,
+    /// Key: {0}
+    Key(KeyError),
+    /// The memo is not a burn redemption memo, its type is {0:02X?}
+    WrongMemoType([u8; 2]),
+}
+
+impl From<KeyError> for BurnRedemptionMemoError {
+    fn from(src: KeyError) -> Self {
+        Self::Key(src)
+    }
+}
+
 impl_memo_type_conversions! { BurnRedemptionMemo }
@@ -26,6 +26,10 @@
 //! exported, and will work as long as your memo types all implement
 //! RegisteredMemoType, and all have different MEMO_TYPE_BYTES.
 //!
+//! Or, to use memo types of your own alongside the ones of this crate without
+//! a new enum, register them with a `MemoTypeRegistry`, and decode memos with
+//! it. See `MemoTypeRegistry` and `CustomMemoBuilder`.
+//!
 //! If you want to put new memo types into transactions, you will need to
 //! implement a new `MemoBuilder`. See the `memo_builder` module for examples.
 //! Or, if you don't want to use the `TransactionBuilder`, you can call
@@ -39,24 +43,28 @@
 //! | 0x0002          | Gift Code Sender Memo                             |
 //! | 0x0100          | Authenticated Sender Memo                         |
 //! | 0x0101          | Authenticated Sender With Payment Request Id Memo |
+//! | 0x0103          | Authenticated Payment Request Memo                |
 //! | 0x0200          | Destination Memo                                  |
 //! | 0x0201          | Gift Code Funding Memo                            |
 //! | 0x0202          | Gift Code Cancellation Memo                       |
 
 pub use self::{
     authenticated_common::compute_category1_hmac,
+    authenticated_payment_request::AuthenticatedPaymentRequestMemo,
     authenticated_sender::AuthenticatedSenderMemo,
     authenticated_sender_with_payment_request_id::AuthenticatedSenderWithPaymentRequestIdMemo,
-    burn_redemption::BurnRedemptionMemo,
+    burn_redemption::{BurnRedemptionMemo, BurnRedemptionMemoError},
     credential::SenderMemoCredential,
     destination::{DestinationMemo, DestinationMemoError},
     gift_code_cancellation::GiftCodeCancellationMemo,
     gift_code_funding::GiftCodeFundingMemo,
     gift_code_sender::GiftCodeSenderMemo,
+    registry::{CustomMemo, DecodedMemo, MemoRegistryError, MemoTypeRegistry},
     unused::UnusedMemo,
 };
 
 mod authenticated_common;
+mod authenticated_payment_request;
 mod authenticated_sender;
 mod authenticated_sender_with_payment_request_id;
 mod burn_redemption;
@@ -66,6 +74,7 @@ mod gift_code_cancellation;
 mod gift_code_funding;
 mod gift_code_sender;
 mod macros;
+mod registry;
 mod unused;
 
 use crate::impl_memo_enum;
@@ -94,6 +103,7 @@ pub enum MemoDecodingError {
 }
 
 impl_memo_enum! { MemoType,
+    AuthenticatedPaymentRequest(AuthenticatedPaymentRequestMemo),
     AuthenticatedSender(AuthenticatedSenderMemo),
     AuthenticatedSenderWithPaymentRequestId(AuthenticatedSenderWithPaymentRequestIdMemo),
     BurnRedemption(BurnRedemptionMemo),
@@ -109,7 +119,7 @@ mod tests {
     use super::*;
     use mc_account_keys::{AccountKey, ShortAddressHash};
     use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPrivate};
-    use mc_transaction_core::MemoPayload;
+    use mc_transaction_core::{CompressedCommitment, MemoPayload};
     use mc_util_from_random::FromRandom;
     use rand::{rngs::StdRng, SeedableRng};
 
@@ -196,6 +206,22 @@ mod tests {
                 panic!("unexpected deserialization");
             }
         }
+
+        let memo7 = AuthenticatedPaymentRequestMemo::new(
+            &alice_cred,
+            bob_addr.view_public_key(),
+            &tx_public_key,
+            7u64,
+            &CompressedCommitment::from(&[3u8; 32]),
+        );
+        match MemoType::try_from(&MemoPayload::from(memo7.clone())).unwrap() {
+            MemoType::AuthenticatedPaymentRequest(memo) => {
+                assert_eq!(memo7, memo);
+            }
+            _ => {
+                panic!("unexpected deserialization");
+            }
+        }
     }
 
     #[test]
@@ -364,6 +390,52 @@ mod tests {
             )),
             "validation should have failed"
         );
+
+        let commitment = CompressedCommitment::from(&[3u8; 32]);
+        let memo3 = AuthenticatedPaymentRequestMemo::new(
+            &alice_cred,
+            bob_addr.view_public_key(),
+            &tx_public_key,
+            7u64,
+            &commitment,
+        );
+        assert_eq!(
+            memo3.sender_address_hash(),
+            ShortAddressHash::from(&alice_addr)
+        );
+        assert_eq!(memo3.payment_request_id(), 7u64);
+        assert!(
+            bool::from(memo3.validate(
+                &alice_addr,
+                &bob.default_subaddress_view_private(),
+                &tx_public_key
+            )),
+            "validation should have passed"
+        );
+        assert!(
+            !bool::from(memo3.validate(
+                &alice_addr,
+                &alice.default_subaddress_view_private(),
+                &tx_public_key
+            )),
+            "validation should have failed"
+        );
+        assert!(
+            !bool::from(memo3.validate(
+                &alice_addr,
+                &bob.default_subaddress_view_private(),
+                &tx_public_key2
+            )),
+            "validation should have failed"
+        );
+        assert!(
+            bool::from(memo3.validate_amount_commitment(&commitment)),
+            "amount commitment validation should have passed"
+        );
+        assert!(
+            !bool::from(memo3.validate_amount_commitment(&CompressedCommitment::from(&[4u8; 32]))),
+            "amount commitment validation should have failed"
+        );
     }
 
     #[test]
@@ -403,4 +475,45 @@ mod tests {
         assert_eq!(memo.get_fee(), 17u64);
         assert_eq!(memo.get_num_recipients(), 4);
     }
+
+    #[test]
+    fn test_burn_redemption_memo_external_tx_id() {
+        let external_tx_id = [7u8; 32];
+        let memo = BurnRedemptionMemo::from_external_tx_id(&external_tx_id).unwrap();
+        assert_eq!(memo.memo_data()[0], 32);
+        assert_eq!(memo.external_tx_id().unwrap(), &external_tx_id[..]);
+
+        // The encoding survives a round trip through a memo payload
+        let payload = MemoPayload::from(memo.clone());
+        match MemoType::try_from(&payload).unwrap() {
+            MemoType::BurnRedemption(decoded) => assert_eq!(decoded, memo),
+            _ => panic!("unexpected memo type"),
+        }
+
+        let longest = [1u8; BurnRedemptionMemo::EXTERNAL_TX_ID_MAX_LEN];
+        let memo = BurnRedemptionMemo::from_external_tx_id(&longest).unwrap();
+        assert_eq!(memo.external_tx_id().unwrap(), &longest[..]);
+
+        assert_eq!(
+            BurnRedemptionMemo::from_external_tx_id(&[]),
+            Err(BurnRedemptionMemoError::InvalidExternalTxIdLength(0))
+        );
+        assert_eq!(
+            BurnRedemptionMemo::from_external_tx_id(&[1u8; 64]),
+            Err(BurnRedemptionMemoError::InvalidExternalTxIdLength(64))
+        );
+
+        // Memo data which is not an encoded external tx id is rejected
+        assert_eq!(
+            BurnRedemptionMemo::new([0u8; 64]).external_tx_id(),
+            Err(BurnRedemptionMemoError::InvalidExternalTxIdLength(0))
+        );
+        let mut memo_data = [0u8; 64];
+        memo_data[0] = 2;
+        memo_data[10] = 1;
+        assert_eq!(
+            BurnRedemptionMemo::new(memo_data).external_tx_id(),
+            Err(BurnRedemptionMemoError::NonCanonicalPadding)
+        );
+    }
 }
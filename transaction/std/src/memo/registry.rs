@@ -0,0 +1,282 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! A registry of custom memo types, for memo types defined outside of this
+//! crate.
+//!
+//! A downstream crate which wants to use an organization-specific memo type
+//! defines it like the memo types in this crate: a struct implementing
+//! `RegisteredMemoType`, with conversions to and from `[u8; 64]` and
+//! `impl_memo_type_conversions!`. It then registers it:
+//!
+//!   let mut registry = MemoTypeRegistry::default();
+//!   registry.register::<MyMemo>()?;
+//!
+//! and decodes memos with `registry.decode(&memo_payload)` instead of
+//! `MemoType::try_from(&memo_payload)`. To write these memos into
+//! transactions, see `CustomMemoBuilder`.
+
+use super::{MemoDecodingError, MemoType, RegisteredMemoType};
+use core::{
+    any::Any,
+    fmt::{self, Debug, Formatter},
+};
+use displaydoc::Display;
+use mc_transaction_core::MemoPayload;
+use std::collections::BTreeMap;
+
+/// A memo of a custom memo type, decoded by a `MemoTypeRegistry`.
+///
+/// This is implemented for every `RegisteredMemoType`, so that the decoded
+/// memo can be downcast back to its type.
+pub trait CustomMemo: Any + Debug + Send + Sync {
+    /// The memo, as `Any`, to downcast it to its type.
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: RegisteredMemoType + Send + Sync + 'static> CustomMemo for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// A memo decoded by a `MemoTypeRegistry`: either one of the memo types of this
+/// crate, or a registered custom memo type.
+#[derive(Debug)]
+pub enum DecodedMemo {
+    /// A memo type of this crate
+    Known(MemoType),
+    /// A registered custom memo type
+    Custom {
+        /// The type bytes of the memo
+        memo_type_bytes: [u8; 2],
+        /// The decoded memo
+        memo: Box<dyn CustomMemo>,
+    },
+}
+
+impl DecodedMemo {
+    /// Get the memo as the custom memo type `T`, if it is one.
+    pub fn custom<T: RegisteredMemoType + 'static>(&self) -> Option<&T> {
+        match self {
+            Self::Known(_) => None,
+            Self::Custom { memo, .. } => memo.as_any().downcast_ref::<T>(),
+        }
+    }
+}
+
+/// An error that can occur when registering a custom memo type
+#[derive(Clone, Debug, Display, Eq, PartialEq)]
+pub enum MemoRegistryError {
+    /// Memo type bytes {0:02X?} are already used by a memo type of this crate
+    ReservedMemoType([u8; 2]),
+    /// Memo type bytes {0:02X?} are already registered, by {1}
+    AlreadyRegistered([u8; 2], &'static str),
+}
+
+/// A registry of custom memo types, and how to decode them.
+#[derive(Clone, Default)]
+pub struct MemoTypeRegistry {
+    custom_memo_types: BTreeMap<[u8; 2], CustomMemoType>,
+}
+
+impl Debug for MemoTypeRegistry {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.custom_memo_types
+                    .iter()
+                    .map(|(memo_type_bytes, custom_memo_type)| {
+                        (memo_type_bytes, custom_memo_type.name)
+                    }),
+            )
+            .finish()
+    }
+}
+
+#[derive(Clone)]
+struct CustomMemoType {
+    name: &'static str,
+    decode: fn(&[u8; 64]) -> Box<dyn CustomMemo>,
+}
+
+impl MemoTypeRegistry {
+    /// Register the custom memo type `T`.
+    ///
+    /// This fails if the type bytes of `T` are used by a memo type of this
+    /// crate, or by another registered memo type.
+    pub fn register<T: RegisteredMemoType + Send + Sync + 'static>(
+        &mut self,
+    ) -> Result<(), MemoRegistryError> {
+        let memo_type_bytes = T::MEMO_TYPE_BYTES;
+        if is_known_memo_type(memo_type_bytes) {
+            return Err(MemoRegistryError::ReservedMemoType(memo_type_bytes));
+        }
+        if let Some(existing) = self.custom_memo_types.get(&memo_type_bytes) {
+            return Err(MemoRegistryError::AlreadyRegistered(
+                memo_type_bytes,
+                existing.name,
+            ));
+        }
+        self.custom_memo_types.insert(
+            memo_type_bytes,
+            CustomMemoType {
+                name: core::any::type_name::<T>(),
+                decode: decode_custom_memo::<T>,
+            },
+        );
+        Ok(())
+    }
+
+    /// Whether memos with these type bytes can be decoded, either because they
+    /// are a memo type of this crate or a registered custom memo type.
+    pub fn is_registered(&self, memo_type_bytes: [u8; 2]) -> bool {
+        is_known_memo_type(memo_type_bytes) || self.custom_memo_types.contains_key(&memo_type_bytes)
+    }
+
+    /// Decode a memo payload, which can be of a memo type of this crate or of a
+    /// registered custom memo type.
+    pub fn decode(&self, src: &MemoPayload) -> Result<DecodedMemo, MemoDecodingError> {
+        match MemoType::try_from(src) {
+            Ok(memo) => Ok(DecodedMemo::Known(memo)),
+            Err(MemoDecodingError::UnknownMemoType(memo_type_bytes)) => {
+                match self.custom_memo_types.get(&memo_type_bytes) {
+                    Some(custom_memo_type) => Ok(DecodedMemo::Custom {
+                        memo_type_bytes,
+                        memo: (custom_memo_type.decode)(src.get_memo_data()),
+                    }),
+                    None => Err(MemoDecodingError::UnknownMemoType(memo_type_bytes)),
+                }
+            }
+        }
+    }
+}
+
+// Decode the memo data of a custom memo type
+fn decode_custom_memo<T: RegisteredMemoType + Send + Sync + 'static>(
+    memo_data: &[u8; 64],
+) -> Box<dyn CustomMemo> {
+    Box::new(T::from(memo_data))
+}
+
+// Whether these type bytes belong to a memo type of this crate
+fn is_known_memo_type(memo_type_bytes: [u8; 2]) -> bool {
+    MemoType::try_from(&MemoPayload::new(memo_type_bytes, [0u8; 64])).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{impl_memo_type_conversions, UnusedMemo};
+
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    struct InvoiceMemo {
+        invoice_number: u64,
+    }
+
+    impl RegisteredMemoType for InvoiceMemo {
+        const MEMO_TYPE_BYTES: [u8; 2] = [0xF0, 0x01];
+    }
+
+    impl From<&[u8; 64]> for InvoiceMemo {
+        fn from(src: &[u8; 64]) -> Self {
+            Self {
+                invoice_number: u64::from_be_bytes(src[..8].try_into().unwrap()),
+            }
+        }
+    }
+
+    impl From<InvoiceMemo> for [u8; 64] {
+        fn from(src: InvoiceMemo) -> [u8; 64] {
+            let mut memo_data = [0u8; 64];
+            memo_data[..8].copy_from_slice(&src.invoice_number.to_be_bytes());
+            memo_data
+        }
+    }
+
+    impl_memo_type_conversions! { InvoiceMemo }
+
+    #[derive(Clone, Debug)]
+    struct OtherInvoiceMemo;
+
+    impl RegisteredMemoType for OtherInvoiceMemo {
+        const MEMO_TYPE_BYTES: [u8; 2] = [0xF0, 0x01];
+    }
+
+    impl From<&[u8; 64]> for OtherInvoiceMemo {
+        fn from(_: &[u8; 64]) -> Self {
+            Self
+        }
+    }
+
+    impl From<OtherInvoiceMemo> for [u8; 64] {
+        fn from(_: OtherInvoiceMemo) -> [u8; 64] {
+            [0u8; 64]
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    struct NotUnusedMemo;
+
+    impl RegisteredMemoType for NotUnusedMemo {
+        const MEMO_TYPE_BYTES: [u8; 2] = [0x00, 0x00];
+    }
+
+    impl From<&[u8; 64]> for NotUnusedMemo {
+        fn from(_: &[u8; 64]) -> Self {
+            Self
+        }
+    }
+
+    impl From<NotUnusedMemo> for [u8; 64] {
+        fn from(_: NotUnusedMemo) -> [u8; 64] {
+            [0u8; 64]
+        }
+    }
+
+    #[test]
+    fn test_registry_decodes_custom_and_known_memos() {
+        let mut registry = MemoTypeRegistry::default();
+
+        let invoice = MemoPayload::from(InvoiceMemo { invoice_number: 17 });
+        assert!(!registry.is_registered(InvoiceMemo::MEMO_TYPE_BYTES));
+        assert!(matches!(
+            registry.decode(&invoice),
+            Err(MemoDecodingError::UnknownMemoType([0xF0, 0x01]))
+        ));
+
+        registry.register::<InvoiceMemo>().unwrap();
+        assert!(registry.is_registered(InvoiceMemo::MEMO_TYPE_BYTES));
+
+        let decoded = registry.decode(&invoice).unwrap();
+        assert_eq!(
+            decoded.custom::<InvoiceMemo>(),
+            Some(&InvoiceMemo { invoice_number: 17 })
+        );
+        assert!(decoded.custom::<OtherInvoiceMemo>().is_none());
+
+        // Memo types of this crate are still decoded as such
+        assert!(registry.is_registered(UnusedMemo::MEMO_TYPE_BYTES));
+        match registry.decode(&MemoPayload::from(UnusedMemo)).unwrap() {
+            DecodedMemo::Known(MemoType::Unused(_)) => {}
+            other => panic!("unexpected decoding: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_registry_rejects_duplicate_memo_types() {
+        let mut registry = MemoTypeRegistry::default();
+        registry.register::<InvoiceMemo>().unwrap();
+
+        assert_eq!(
+            registry.register::<OtherInvoiceMemo>(),
+            Err(MemoRegistryError::AlreadyRegistered(
+                [0xF0, 0x01],
+                core::any::type_name::<InvoiceMemo>()
+            ))
+        );
+        assert_eq!(
+            registry.register::<NotUnusedMemo>(),
+            Err(MemoRegistryError::ReservedMemoType([0x00, 0x00]))
+        );
+    }
+}
@@ -5,11 +5,14 @@
 //! envisioned in MCIP #TODO.
 
 use super::{
-    memo::{BurnRedemptionMemo, DestinationMemo, DestinationMemoError, UnusedMemo},
+    memo::{
+        BurnRedemptionMemo, BurnRedemptionMemoError, DestinationMemo, DestinationMemoError,
+        UnusedMemo,
+    },
     MemoBuilder,
 };
 use crate::ReservedSubaddresses;
-use mc_account_keys::{burn_address, PublicAddress, ShortAddressHash};
+use mc_account_keys::{burn_address, is_burn_address, PublicAddress, ShortAddressHash};
 use mc_transaction_core::{tokens::Mob, Amount, MemoContext, MemoPayload, NewMemoError, Token};
 
 /// This memo builder attaches 0x0001 Burn Redemption Memos to an output going
@@ -26,6 +29,13 @@ use mc_transaction_core::{tokens::Mob, Amount, MemoContext, MemoPayload, NewMemo
 ///
 /// Then use it to construct a transaction builder.
 ///
+/// Bridges which identify the redemption by the id of a transaction on the
+/// other blockchain should instead use:
+///
+///   let mut mb = BurnRedemptionMemoBuilder::new_with_external_tx_id(&tx_id)?;
+///
+/// which encodes it canonically, see `BurnRedemptionMemo::from_external_tx_id`.
+///
 /// A memo builder configured this way will use 0x0001 Burn Redemption Memo
 /// on the burn output and 0x0200 Destination Memo on the change output.
 ///
@@ -62,6 +72,14 @@ impl BurnRedemptionMemoBuilder {
             fee: Amount::new(Mob::MINIMUM_FEE, Mob::ID),
         }
     }
+
+    /// Construct a new BurnRedemptionMemoBuilder which attaches the id of the
+    /// redeeming transaction on another blockchain to the burn output.
+    pub fn new_with_external_tx_id(external_tx_id: &[u8]) -> Result<Self, BurnRedemptionMemoError> {
+        let memo = BurnRedemptionMemo::from_external_tx_id(external_tx_id)?;
+        Ok(Self::new(*memo.memo_data()))
+    }
+
     /// Enable destination memos
     pub fn enable_destination_memo(&mut self) {
         self.destination_memo_enabled = true;
@@ -90,7 +108,7 @@ impl MemoBuilder for BurnRedemptionMemoBuilder {
         recipient: &PublicAddress,
         _memo_context: MemoContext,
     ) -> Result<MemoPayload, NewMemoError> {
-        if *recipient != burn_address() {
+        if !is_burn_address(recipient) {
             return Err(NewMemoError::InvalidRecipient);
         }
         if self.burn_amount.is_some() {
@@ -0,0 +1,276 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Defines the CustomMemoBuilder.
+//! This MemoBuilder policy writes custom memo types, registered with a
+//! `MemoTypeRegistry`, onto the outputs of a transaction, and falls back to
+//! another memo builder for everything else.
+
+use super::{memo::MemoTypeRegistry, MemoBuilder};
+use crate::ReservedSubaddresses;
+use core::fmt::Debug;
+use mc_account_keys::PublicAddress;
+use mc_transaction_core::{Amount, MemoContext, MemoPayload, NewMemoError};
+
+/// Encodes the custom memos that a `CustomMemoBuilder` writes.
+///
+/// This is implemented by the crate which defines the custom memo types. The
+/// memos it returns are typically made with `MemoPayload::from` of a custom
+/// memo type.
+pub trait CustomMemoEncoder: Debug {
+    /// Encode the memo for a normal output (to another party), or return None
+    /// to use the memo of the underlying memo builder.
+    fn memo_for_output(
+        &mut self,
+        amount: Amount,
+        recipient: &PublicAddress,
+        memo_context: MemoContext,
+    ) -> Result<Option<MemoPayload>, NewMemoError>;
+
+    /// Encode the memo for a change output (to ourselves), or return None to
+    /// use the memo of the underlying memo builder.
+    fn memo_for_change_output(
+        &mut self,
+        _amount: Amount,
+        _change_destination: &ReservedSubaddresses,
+        _memo_context: MemoContext,
+    ) -> Result<Option<MemoPayload>, NewMemoError> {
+        Ok(None)
+    }
+}
+
+/// This memo builder attaches the custom memos of a `CustomMemoEncoder` to
+/// outputs, and the memos of an underlying memo builder to the outputs which
+/// the encoder has no memo for.
+///
+/// Usage:
+///
+///   let mut registry = MemoTypeRegistry::default();
+///   registry.register::<MyMemo>()?;
+///   let mb = CustomMemoBuilder::new(RTHMemoBuilder::default(), my_encoder,
+/// registry);
+///
+/// Then use it to construct a transaction builder.
+///
+/// The underlying memo builder is still told about every output, even the ones
+/// which get a custom memo, so that e.g. the 0x0200 Destination Memo of an
+/// RTHMemoBuilder accounts for all of them. Errors of the underlying memo
+/// builder are returned as usual.
+///
+/// The encoder may only produce memos of memo types of this crate or of memo
+/// types in the registry. Other memos are rejected, since recipients using the
+/// same registry would not be able to decode them.
+#[derive(Clone, Debug)]
+pub struct CustomMemoBuilder<B: MemoBuilder, E: CustomMemoEncoder> {
+    // The memo builder for outputs which don't get a custom memo
+    inner: B,
+    // Makes the custom memos
+    encoder: E,
+    // The custom memo types which may be written
+    registry: MemoTypeRegistry,
+}
+
+impl<B: MemoBuilder, E: CustomMemoEncoder> CustomMemoBuilder<B, E> {
+    /// Construct a new CustomMemoBuilder.
+    pub fn new(inner: B, encoder: E, registry: MemoTypeRegistry) -> Self {
+        Self {
+            inner,
+            encoder,
+            registry,
+        }
+    }
+
+    // Check that a custom memo is of a memo type the registry knows
+    fn check_registered(&self, memo: MemoPayload) -> Result<MemoPayload, NewMemoError> {
+        let memo_type_bytes = *memo.get_memo_type();
+        if !self.registry.is_registered(memo_type_bytes) {
+            return Err(NewMemoError::BadInputs(format!(
+                "Memo type {:02X?} is not registered",
+                memo_type_bytes
+            )));
+        }
+        Ok(memo)
+    }
+}
+
+impl<B: MemoBuilder, E: CustomMemoEncoder> MemoBuilder for CustomMemoBuilder<B, E> {
+    /// Set the fee
+    fn set_fee(&mut self, fee: Amount) -> Result<(), NewMemoError> {
+        self.inner.set_fee(fee)
+    }
+
+    /// Build a memo for a normal output (to another party).
+    fn make_memo_for_output(
+        &mut self,
+        amount: Amount,
+        recipient: &PublicAddress,
+        memo_context: MemoContext,
+    ) -> Result<MemoPayload, NewMemoError> {
+        let inner_memo = self
+            .inner
+            .make_memo_for_output(amount, recipient, memo_context)?;
+        match self
+            .encoder
+            .memo_for_output(amount, recipient, memo_context)?
+        {
+            Some(memo) => self.check_registered(memo),
+            None => Ok(inner_memo),
+        }
+    }
+
+    /// Build a memo for a change output (to ourselves).
+    fn make_memo_for_change_output(
+        &mut self,
+        amount: Amount,
+        change_destination: &ReservedSubaddresses,
+        memo_context: MemoContext,
+    ) -> Result<MemoPayload, NewMemoError> {
+        let inner_memo =
+            self.inner
+                .make_memo_for_change_output(amount, change_destination, memo_context)?;
+        match self
+            .encoder
+            .memo_for_change_output(amount, change_destination, memo_context)?
+        {
+            Some(memo) => self.check_registered(memo),
+            None => Ok(inner_memo),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        impl_memo_type_conversions, DecodedMemo, MemoType, RTHMemoBuilder, RegisteredMemoType,
+    };
+    use mc_account_keys::AccountKey;
+    use mc_crypto_keys::RistrettoPublic;
+    use mc_util_from_random::FromRandom;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    struct InvoiceMemo {
+        invoice_number: u64,
+    }
+
+    impl RegisteredMemoType for InvoiceMemo {
+        const MEMO_TYPE_BYTES: [u8; 2] = [0xF0, 0x01];
+    }
+
+    impl From<&[u8; 64]> for InvoiceMemo {
+        fn from(src: &[u8; 64]) -> Self {
+            Self {
+                invoice_number: u64::from_be_bytes(src[..8].try_into().unwrap()),
+            }
+        }
+    }
+
+    impl From<InvoiceMemo> for [u8; 64] {
+        fn from(src: InvoiceMemo) -> [u8; 64] {
+            let mut memo_data = [0u8; 64];
+            memo_data[..8].copy_from_slice(&src.invoice_number.to_be_bytes());
+            memo_data
+        }
+    }
+
+    impl_memo_type_conversions! { InvoiceMemo }
+
+    // Writes an invoice memo on each normal output
+    #[derive(Clone, Debug)]
+    struct InvoiceMemoEncoder {
+        invoice_number: u64,
+        memo_type_bytes: [u8; 2],
+    }
+
+    impl CustomMemoEncoder for InvoiceMemoEncoder {
+        fn memo_for_output(
+            &mut self,
+            _amount: Amount,
+            _recipient: &PublicAddress,
+            _memo_context: MemoContext,
+        ) -> Result<Option<MemoPayload>, NewMemoError> {
+            let memo = InvoiceMemo {
+                invoice_number: self.invoice_number,
+            };
+            Ok(Some(MemoPayload::new(self.memo_type_bytes, memo.into())))
+        }
+    }
+
+    #[test]
+    fn test_custom_memo_builder_writes_registered_memos() {
+        let mut rng: StdRng = SeedableRng::from_seed([0u8; 32]);
+        let alice = AccountKey::random_with_fog(&mut rng);
+        let alice_address_book = ReservedSubaddresses::from(&alice);
+        let bob = AccountKey::random(&mut rng).default_subaddress();
+
+        let mut registry = MemoTypeRegistry::default();
+        registry.register::<InvoiceMemo>().unwrap();
+
+        let mut rth_memo_builder = RTHMemoBuilder::default();
+        rth_memo_builder.enable_destination_memo();
+        let mut builder = CustomMemoBuilder::new(
+            rth_memo_builder,
+            InvoiceMemoEncoder {
+                invoice_number: 17,
+                memo_type_bytes: InvoiceMemo::MEMO_TYPE_BYTES,
+            },
+            registry.clone(),
+        );
+        builder.set_fee(Amount::new(1, 0.into())).unwrap();
+
+        let output_tx_public_key = RistrettoPublic::from_random(&mut rng);
+        let memo = builder
+            .make_memo_for_output(
+                Amount::new(10, 0.into()),
+                &bob,
+                MemoContext::new(&output_tx_public_key, &Default::default()),
+            )
+            .unwrap();
+        let decoded = registry.decode(&memo).unwrap();
+        assert_eq!(
+            decoded.custom::<InvoiceMemo>(),
+            Some(&InvoiceMemo { invoice_number: 17 })
+        );
+
+        // The change output gets the destination memo of the underlying builder,
+        // which accounts for the output with the custom memo
+        let change_tx_public_key = RistrettoPublic::from_random(&mut rng);
+        let memo = builder
+            .make_memo_for_change_output(
+                Amount::new(5, 0.into()),
+                &alice_address_book,
+                MemoContext::new(&change_tx_public_key, &Default::default()),
+            )
+            .unwrap();
+        match registry.decode(&memo).unwrap() {
+            DecodedMemo::Known(MemoType::Destination(memo)) => {
+                assert_eq!(memo.get_num_recipients(), 1);
+                assert_eq!(memo.get_total_outlay(), 11);
+            }
+            other => panic!("unexpected memo: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_custom_memo_builder_rejects_unregistered_memos() {
+        let mut rng: StdRng = SeedableRng::from_seed([0u8; 32]);
+        let bob = AccountKey::random(&mut rng).default_subaddress();
+
+        let mut builder = CustomMemoBuilder::new(
+            RTHMemoBuilder::default(),
+            InvoiceMemoEncoder {
+                invoice_number: 17,
+                memo_type_bytes: [0xF0, 0x02],
+            },
+            MemoTypeRegistry::default(),
+        );
+
+        let output_tx_public_key = RistrettoPublic::from_random(&mut rng);
+        let result = builder.make_memo_for_output(
+            Amount::new(10, 0.into()),
+            &bob,
+            MemoContext::new(&output_tx_public_key, &Default::default()),
+        );
+        assert!(matches!(result, Err(NewMemoError::BadInputs(_))));
+    }
+}
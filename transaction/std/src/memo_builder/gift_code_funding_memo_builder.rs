@@ -145,12 +145,8 @@ mod tests {
         let change_tx_pubkey = RistrettoPublic::from_random(&mut rng);
         let change_amount = Amount::new(1, 0.into());
         let funding_amount = Amount::new(10, 0.into());
-        let funding_context = MemoContext {
-            tx_public_key: funding_tx_pubkey,
-        };
-        let change_context = MemoContext {
-            tx_public_key: &change_tx_pubkey,
-        };
+        let funding_context = MemoContext::new(funding_tx_pubkey, &Default::default());
+        let change_context = MemoContext::new(&change_tx_pubkey, &Default::default());
 
         builder.set_fee(fee).unwrap();
         // Build blank output memo for TxOut at gift code address & funding memo to
@@ -269,17 +265,13 @@ mod tests {
             .make_memo_for_output(
                 change_amount,
                 &alice_address_book.gift_code_subaddress,
-                MemoContext {
-                    tx_public_key: &change_tx_public_key,
-                },
+                MemoContext::new(&change_tx_public_key, &Default::default()),
             )
             .unwrap();
         let memo_payload = builder.make_memo_for_change_output(
             change_amount,
             &alice_address_book,
-            MemoContext {
-                tx_public_key: &change_tx_public_key,
-            },
+            MemoContext::new(&change_tx_public_key, &Default::default()),
         );
 
         // Assert memo creation fails
@@ -309,26 +301,20 @@ mod tests {
             .make_memo_for_output(
                 change_amount,
                 &alice_address_book.gift_code_subaddress,
-                MemoContext {
-                    tx_public_key: &funding_tx_out_public_key,
-                },
+                MemoContext::new(&funding_tx_out_public_key, &Default::default()),
             )
             .unwrap();
         builder
             .make_memo_for_change_output(
                 change_amount,
                 &alice_address_book,
-                MemoContext {
-                    tx_public_key: &change_tx_public_key,
-                },
+                MemoContext::new(&change_tx_public_key, &Default::default()),
             )
             .unwrap();
         let memo_payload = builder.make_memo_for_change_output(
             change_amount,
             &alice_address_book,
-            MemoContext {
-                tx_public_key: &change_tx_public_key_2,
-            },
+            MemoContext::new(&change_tx_public_key_2, &Default::default()),
         );
 
         // Assert memo creation fails for second change output
@@ -7,8 +7,9 @@
 
 use super::{
     memo::{
-        AuthenticatedSenderMemo, AuthenticatedSenderWithPaymentRequestIdMemo, DestinationMemo,
-        DestinationMemoError, SenderMemoCredential, UnusedMemo,
+        AuthenticatedPaymentRequestMemo, AuthenticatedSenderMemo,
+        AuthenticatedSenderWithPaymentRequestIdMemo, DestinationMemo, DestinationMemoError,
+        SenderMemoCredential, UnusedMemo,
     },
     MemoBuilder,
 };
@@ -40,6 +41,13 @@ use mc_transaction_core::{
 /// If a payment request id is specified, then 0x0101 Authenticated Sender With
 /// Payment Request Id Memo is used instead of 0x0100.
 ///
+/// If the recipient also wants a commitment to the amount of each payment, to
+/// reconcile it against the payment request, you additionally call:
+///
+///   mb.enable_payment_request_amount_commitment();
+///
+/// Then 0x0103 Authenticated Payment Request Memo is used instead of 0x0101.
+///
 /// If no sender credential is provided then 0x0000 Unused will appear on
 /// regular outputs.
 ///
@@ -54,10 +62,12 @@ use mc_transaction_core::{
 /// address will be recorded in the 0x0200 Destination Memo.
 #[derive(Clone, Debug)]
 pub struct RTHMemoBuilder {
-    // The credential used to form 0x0100 and 0x0101 memos, if present.
+    // The credential used to form 0x0100, 0x0101 and 0x0103 memos, if present.
     sender_cred: Option<SenderMemoCredential>,
     // The payment request id, if any
     payment_request_id: Option<u64>,
+    // Whether 0x0103 memos, committing to the amount, are used for payment requests
+    payment_request_amount_commitment_enabled: bool,
     // Whether destination memos are enabled.
     destination_memo_enabled: bool,
     // Tracks if we already wrote a destination memo, for error reporting
@@ -79,6 +89,7 @@ impl Default for RTHMemoBuilder {
         Self {
             sender_cred: Default::default(),
             payment_request_id: None,
+            payment_request_amount_commitment_enabled: false,
             destination_memo_enabled: false,
             wrote_destination_memo: false,
             last_recipient: Default::default(),
@@ -124,6 +135,17 @@ impl RTHMemoBuilder {
         self.payment_request_id = None;
     }
 
+    /// Commit to the amount of each output in payment request memos, using
+    /// 0x0103 memos instead of 0x0101 memos.
+    pub fn enable_payment_request_amount_commitment(&mut self) {
+        self.payment_request_amount_commitment_enabled = true;
+    }
+
+    /// Don't commit to the amount of each output in payment request memos.
+    pub fn disable_payment_request_amount_commitment(&mut self) {
+        self.payment_request_amount_commitment_enabled = false;
+    }
+
     /// Enable destination memos
     pub fn enable_destination_memo(&mut self) {
         self.destination_memo_enabled = true;
@@ -175,13 +197,24 @@ impl MemoBuilder for RTHMemoBuilder {
         self.last_recipient = ShortAddressHash::from(recipient);
         let payload: MemoPayload = if let Some(cred) = &self.sender_cred {
             if let Some(payment_request_id) = self.payment_request_id {
-                AuthenticatedSenderWithPaymentRequestIdMemo::new(
-                    cred,
-                    recipient.view_public_key(),
-                    &memo_context.tx_public_key.into(),
-                    payment_request_id,
-                )
-                .into()
+                if self.payment_request_amount_commitment_enabled {
+                    AuthenticatedPaymentRequestMemo::new(
+                        cred,
+                        recipient.view_public_key(),
+                        &memo_context.tx_public_key.into(),
+                        payment_request_id,
+                        memo_context.amount_commitment,
+                    )
+                    .into()
+                } else {
+                    AuthenticatedSenderWithPaymentRequestIdMemo::new(
+                        cred,
+                        recipient.view_public_key(),
+                        &memo_context.tx_public_key.into(),
+                        payment_request_id,
+                    )
+                    .into()
+                }
             } else {
                 AuthenticatedSenderMemo::new(
                     cred,
@@ -0,0 +1,503 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Helpers for offering part of an input to a counterparty, using signed
+//! contingent inputs (see MCIP #31).
+//!
+//! The input rules of a signed contingent input fix its required outputs
+//! exactly, so a single signed contingent input can only be filled in full.
+//! To let a counterparty fill part of an input instead, its owner signs one
+//! signed contingent input per fill value they are willing to accept. Each of
+//! them requires a payment proportional to the fill value, and returns the
+//! unfilled part of the input to its owner as change. They all spend the same
+//! input, so at most one of them can ever appear in the ledger.
+//!
+//! The counterparty picks the signed contingent input for the fill they want,
+//! checks it against the published `PartialFillRules` with
+//! `validate_partial_fill`, and then adds it to their transaction with
+//! `TransactionBuilder::add_presigned_input`.
+
+use crate::{
+    InputCredentials, MemoBuilder, PartialFillError, ReservedSubaddresses,
+    SignedContingentInputBuilder,
+};
+use mc_crypto_ring_signature_signer::RingSigner;
+use mc_fog_report_validation::FogPubkeyResolver;
+use mc_transaction_core::{Amount, BlockVersion, SignedContingentInput};
+use rand_core::{CryptoRng, RngCore};
+use std::collections::BTreeSet;
+
+/// The terms on which the owner of an input lets a counterparty fill part of
+/// it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PartialFillRules {
+    /// The payment required for filling the entire input. Partial fills
+    /// require a proportional payment, rounded up.
+    pub counter_amount: Amount,
+    /// The smallest value of the input which may be filled
+    pub min_fill_value: u64,
+}
+
+impl PartialFillRules {
+    /// The payment required for filling `fill_value` of an input of value
+    /// `input_value`.
+    pub fn payment_for_fill(&self, input_value: u64, fill_value: u64) -> Amount {
+        let input_value = input_value as u128;
+        let payment = (self.counter_amount.value as u128 * fill_value as u128 + input_value - 1)
+            / input_value;
+        Amount::new(payment as u64, self.counter_amount.token_id)
+    }
+
+    /// Check that `fill_value` may be filled from an input of value
+    /// `input_value`.
+    pub fn check_fill_value(
+        &self,
+        input_value: u64,
+        fill_value: u64,
+    ) -> Result<(), PartialFillError> {
+        if fill_value == 0 || fill_value < self.min_fill_value {
+            return Err(PartialFillError::FillTooSmall(
+                fill_value,
+                self.min_fill_value,
+            ));
+        }
+        if fill_value > input_value {
+            return Err(PartialFillError::FillTooLarge(fill_value, input_value));
+        }
+        Ok(())
+    }
+}
+
+/// The fill offered by a signed contingent input, as found by
+/// `validate_partial_fill`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PartialFill {
+    /// The amount of the input being spent
+    pub input: Amount,
+    /// The value of the input which goes to the counterparty. The rest is
+    /// returned to the owner of the input as change.
+    pub fill_value: u64,
+    /// The payment the counterparty makes for the fill
+    pub payment: Amount,
+}
+
+/// Helper utility for signing a set of signed contingent inputs which offer
+/// different fills of the same input, under the same `PartialFillRules`.
+///
+/// The payments and the change are sent to the owner of the input: payments
+/// to the primary address of `change_destination`, and change to its change
+/// subaddress.
+#[derive(Debug)]
+pub struct PartialFillSciBuilder<FPR: FogPubkeyResolver + Clone, MB: MemoBuilder + Clone> {
+    /// The block version that we are targeting for these inputs
+    block_version: BlockVersion,
+    /// The input which is being offered
+    input_credentials: InputCredentials,
+    /// The terms of the offer
+    rules: PartialFillRules,
+    /// The fill values to sign a signed contingent input for
+    fill_values: BTreeSet<u64>,
+    /// Where the payments and change go
+    change_destination: ReservedSubaddresses,
+    /// The tombstone block of the signed contingent inputs
+    tombstone_block: u64,
+    /// The source of validated fog pubkeys used for the required outputs
+    fog_resolver: FPR,
+    /// The memo builder for the required outputs of each signed contingent
+    /// input
+    memo_builder: MB,
+}
+
+impl<FPR: FogPubkeyResolver + Clone, MB: MemoBuilder + Clone + 'static + Send + Sync>
+    PartialFillSciBuilder<FPR, MB>
+{
+    /// Initializes a new PartialFillSciBuilder.
+    ///
+    /// # Arguments
+    /// * `block_version` - The block version rules to use when signing the
+    ///   inputs
+    /// * `input_credentials` - Credentials for the input we are offering
+    /// * `rules` - The terms on which the input may be filled
+    /// * `change_destination` - The addresses of the owner of the input, which
+    ///   receive the payments and the change
+    /// * `fog_resolver` - Source of validated fog keys to use with the required
+    ///   outputs
+    /// * `memo_builder` - The memo builder for the required outputs, which is
+    ///   cloned for each signed contingent input
+    pub fn new(
+        block_version: BlockVersion,
+        input_credentials: InputCredentials,
+        rules: PartialFillRules,
+        change_destination: ReservedSubaddresses,
+        fog_resolver: FPR,
+        memo_builder: MB,
+    ) -> Result<Self, PartialFillError> {
+        let input_token_id = input_credentials.input_secret.amount.token_id;
+        if rules.counter_amount.token_id == input_token_id {
+            return Err(PartialFillError::SameTokenId(input_token_id));
+        }
+        Ok(Self {
+            block_version,
+            input_credentials,
+            rules,
+            fill_values: BTreeSet::new(),
+            change_destination,
+            tombstone_block: u64::max_value(),
+            fog_resolver,
+            memo_builder,
+        })
+    }
+
+    /// Offer a fill of `fill_value` of the input.
+    pub fn add_fill_value(&mut self, fill_value: u64) -> Result<(), PartialFillError> {
+        self.rules
+            .check_fill_value(self.input_credentials.input_secret.amount.value, fill_value)?;
+        self.fill_values.insert(fill_value);
+        Ok(())
+    }
+
+    /// Sets the tombstone block of the signed contingent inputs.
+    ///
+    /// # Arguments
+    /// * `tombstone_block` - Tombstone block number.
+    pub fn set_tombstone_block(&mut self, tombstone_block: u64) {
+        self.tombstone_block = tombstone_block;
+    }
+
+    /// Consume the builder and return a signed contingent input for each fill
+    /// value, in increasing order of fill value.
+    pub fn build<RNG: CryptoRng + RngCore>(
+        self,
+        ring_signer: &impl RingSigner,
+        rng: &mut RNG,
+    ) -> Result<Vec<SignedContingentInput>, PartialFillError> {
+        if self.fill_values.is_empty() {
+            return Err(PartialFillError::NoFillValues);
+        }
+
+        let input_amount = self.input_credentials.input_secret.amount;
+        self.fill_values
+            .iter()
+            .map(|fill_value| -> Result<_, PartialFillError> {
+                let mut builder = SignedContingentInputBuilder::new(
+                    self.block_version,
+                    self.input_credentials.clone(),
+                    self.fog_resolver.clone(),
+                    self.memo_builder.clone(),
+                )?;
+
+                let payment = self.rules.payment_for_fill(input_amount.value, *fill_value);
+                builder.add_required_output(
+                    payment,
+                    &self.change_destination.primary_address,
+                    rng,
+                )?;
+
+                let change_value = input_amount.value - fill_value;
+                if change_value > 0 {
+                    builder.add_required_change_output(
+                        Amount::new(change_value, input_amount.token_id),
+                        &self.change_destination,
+                        rng,
+                    )?;
+                }
+
+                builder.set_tombstone_block(self.tombstone_block);
+                Ok(builder.build(ring_signer, rng)?)
+            })
+            .collect()
+    }
+}
+
+/// Check that a signed contingent input offers a fill of its input which
+/// follows the given rules, before adding it to a transaction.
+///
+/// This validates the signed contingent input, then splits its required
+/// outputs into the payment (outputs in the token of the counter amount) and
+/// the change (outputs in the token of the input), and checks that the fill
+/// value and the payment agree with the rules.
+///
+/// Note: This can't check who the required outputs are sent to, only their
+/// amounts.
+pub fn validate_partial_fill(
+    sci: &SignedContingentInput,
+    rules: &PartialFillRules,
+) -> Result<PartialFill, PartialFillError> {
+    sci.validate()?;
+
+    let input = Amount::from(&sci.pseudo_output_amount);
+    let counter_token_id = rules.counter_amount.token_id;
+    if input.token_id == counter_token_id {
+        return Err(PartialFillError::SameTokenId(input.token_id));
+    }
+
+    let mut change_value = 0u128;
+    let mut payment_value = 0u128;
+    for amount in sci.required_output_amounts.iter() {
+        let amount = Amount::from(amount);
+        if amount.token_id == input.token_id {
+            change_value += amount.value as u128;
+        } else if amount.token_id == counter_token_id {
+            payment_value += amount.value as u128;
+        } else {
+            return Err(PartialFillError::UnexpectedTokenId(amount.token_id));
+        }
+    }
+
+    let fill_value = (input.value as u128).checked_sub(change_value).ok_or(
+        PartialFillError::ChangeExceedsInput(change_value, input.value),
+    )? as u64;
+    rules.check_fill_value(input.value, fill_value)?;
+
+    let payment = rules.payment_for_fill(input.value, fill_value);
+    if payment_value != payment.value as u128 {
+        return Err(PartialFillError::PaymentMismatch(
+            payment_value,
+            payment.value,
+        ));
+    }
+
+    Ok(PartialFill {
+        input,
+        fill_value,
+        payment,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::get_input_credentials, EmptyMemoBuilder, TransactionBuilder};
+    use mc_account_keys::AccountKey;
+    use mc_crypto_ring_signature_signer::NoKeysRingSigner;
+    use mc_fog_report_validation_test_utils::MockFogResolver;
+    use mc_transaction_core::{
+        constants::MILLIMOB_TO_PICOMOB,
+        ring_signature::KeyImage,
+        tokens::Mob,
+        validation::{validate_all_input_rules, validate_signature},
+        Token, TokenId,
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn get_rules() -> PartialFillRules {
+        PartialFillRules {
+            counter_amount: Amount::new(100_000, TokenId::from(2)),
+            min_fill_value: 100 * MILLIMOB_TO_PICOMOB,
+        }
+    }
+
+    #[test]
+    // Partial fill signed contingent inputs offer the fills they were built for
+    fn test_partial_fill_scis_are_valid() {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+        let fog_resolver = MockFogResolver::default();
+        let alice = AccountKey::random(&mut rng);
+        let rules = get_rules();
+        let amount = Amount::new(1000 * MILLIMOB_TO_PICOMOB, Mob::ID);
+
+        for block_version in 3..=*BlockVersion::MAX {
+            let block_version = BlockVersion::try_from(block_version).unwrap();
+
+            let input_credentials =
+                get_input_credentials(block_version, amount, &alice, &fog_resolver, &mut rng);
+            let key_image = KeyImage::from(input_credentials.assert_has_onetime_private_key());
+
+            let mut builder = PartialFillSciBuilder::new(
+                block_version,
+                input_credentials,
+                rules.clone(),
+                ReservedSubaddresses::from(&alice),
+                fog_resolver.clone(),
+                EmptyMemoBuilder::default(),
+            )
+            .unwrap();
+
+            assert!(matches!(
+                builder.add_fill_value(rules.min_fill_value - 1),
+                Err(PartialFillError::FillTooSmall(_, _))
+            ));
+            assert!(matches!(
+                builder.add_fill_value(amount.value + 1),
+                Err(PartialFillError::FillTooLarge(_, _))
+            ));
+
+            let fill_values = [rules.min_fill_value, amount.value / 3, amount.value];
+            for fill_value in fill_values {
+                builder.add_fill_value(fill_value).unwrap();
+            }
+
+            let scis = builder.build(&NoKeysRingSigner {}, &mut rng).unwrap();
+            assert_eq!(scis.len(), fill_values.len());
+
+            for (sci, fill_value) in scis.iter().zip(fill_values) {
+                // Only one of the offers can be used
+                assert_eq!(sci.key_image(), key_image);
+
+                let fill = validate_partial_fill(sci, &rules).unwrap();
+                assert_eq!(fill.input, amount);
+                assert_eq!(fill.fill_value, fill_value);
+                assert_eq!(
+                    fill.payment,
+                    rules.payment_for_fill(amount.value, fill_value)
+                );
+            }
+
+            // A third of the input needs a third of the counter amount, rounded up
+            assert_eq!(
+                scis[1].required_output_amounts.len(),
+                2,
+                "expected a payment and a change output"
+            );
+            assert_eq!(
+                rules.payment_for_fill(amount.value, amount.value / 3).value,
+                33_334
+            );
+
+            // The full fill has no change output
+            assert_eq!(scis[2].required_output_amounts.len(), 1);
+        }
+    }
+
+    #[test]
+    // A counterparty can spend a partial fill signed contingent input
+    fn test_partial_fill_sci_spendable() {
+        let mut rng: StdRng = SeedableRng::from_seed([2u8; 32]);
+        let fog_resolver = MockFogResolver::default();
+        let alice = AccountKey::random(&mut rng);
+        let bob = AccountKey::random(&mut rng);
+        let rules = get_rules();
+        let amount = Amount::new(1000 * MILLIMOB_TO_PICOMOB, Mob::ID);
+        let fill_value = amount.value / 2;
+        let token2 = rules.counter_amount.token_id;
+
+        for block_version in 3..=*BlockVersion::MAX {
+            let block_version = BlockVersion::try_from(block_version).unwrap();
+
+            let input_credentials =
+                get_input_credentials(block_version, amount, &alice, &fog_resolver, &mut rng);
+            let proofs = input_credentials.membership_proofs.clone();
+
+            let mut builder = PartialFillSciBuilder::new(
+                block_version,
+                input_credentials,
+                rules.clone(),
+                ReservedSubaddresses::from(&alice),
+                fog_resolver.clone(),
+                EmptyMemoBuilder::default(),
+            )
+            .unwrap();
+            builder.add_fill_value(fill_value).unwrap();
+            let mut sci = builder
+                .build(&NoKeysRingSigner {}, &mut rng)
+                .unwrap()
+                .pop()
+                .unwrap();
+
+            // Bob checks the offer before using it
+            let fill = validate_partial_fill(&sci, &rules).unwrap();
+            assert_eq!(fill.payment, Amount::new(50_000, token2));
+
+            let mut builder = TransactionBuilder::new(
+                block_version,
+                Amount::new(Mob::MINIMUM_FEE, Mob::ID),
+                fog_resolver.clone(),
+                EmptyMemoBuilder::default(),
+            )
+            .unwrap();
+
+            // Bob supplies the payment from his token id 2
+            builder.add_input(get_input_credentials(
+                block_version,
+                Amount::new(300_000, token2),
+                &bob,
+                &fog_resolver,
+                &mut rng,
+            ));
+
+            sci.tx_in.proofs = proofs;
+            builder.add_presigned_input(sci).unwrap();
+
+            builder
+                .add_change_output(
+                    Amount::new(300_000 - fill.payment.value, token2),
+                    &ReservedSubaddresses::from(&bob),
+                    &mut rng,
+                )
+                .unwrap();
+
+            // Bob keeps the filled Mob, less fees
+            builder
+                .add_output(
+                    Amount::new(fill.fill_value - Mob::MINIMUM_FEE, Mob::ID),
+                    &bob.default_subaddress(),
+                    &mut rng,
+                )
+                .unwrap();
+
+            let tx = builder.build(&NoKeysRingSigner {}, &mut rng).unwrap();
+
+            validate_signature(block_version, &tx, &mut rng).unwrap();
+            validate_all_input_rules(block_version, &tx).unwrap();
+        }
+    }
+
+    #[test]
+    // Offers which don't follow the rules are rejected
+    fn test_validate_partial_fill_rejects_other_rules() {
+        let mut rng: StdRng = SeedableRng::from_seed([3u8; 32]);
+        let fog_resolver = MockFogResolver::default();
+        let alice = AccountKey::random(&mut rng);
+        let rules = get_rules();
+        let amount = Amount::new(1000 * MILLIMOB_TO_PICOMOB, Mob::ID);
+        let block_version = BlockVersion::MAX;
+
+        let input_credentials =
+            get_input_credentials(block_version, amount, &alice, &fog_resolver, &mut rng);
+
+        let mut builder = PartialFillSciBuilder::new(
+            block_version,
+            input_credentials,
+            rules.clone(),
+            ReservedSubaddresses::from(&alice),
+            fog_resolver,
+            EmptyMemoBuilder::default(),
+        )
+        .unwrap();
+        builder.add_fill_value(rules.min_fill_value).unwrap();
+        let sci = builder
+            .build(&NoKeysRingSigner {}, &mut rng)
+            .unwrap()
+            .pop()
+            .unwrap();
+
+        // A cheaper price than was signed for
+        let cheaper_rules = PartialFillRules {
+            counter_amount: Amount::new(50_000, rules.counter_amount.token_id),
+            ..rules.clone()
+        };
+        assert!(matches!(
+            validate_partial_fill(&sci, &cheaper_rules),
+            Err(PartialFillError::PaymentMismatch(10_000, 5_000))
+        ));
+
+        // A larger minimum fill than was signed for
+        let larger_min_rules = PartialFillRules {
+            min_fill_value: rules.min_fill_value + 1,
+            ..rules.clone()
+        };
+        assert!(matches!(
+            validate_partial_fill(&sci, &larger_min_rules),
+            Err(PartialFillError::FillTooSmall(_, _))
+        ));
+
+        // Payment in another token
+        let other_token_rules = PartialFillRules {
+            counter_amount: Amount::new(100_000, TokenId::from(3)),
+            ..rules
+        };
+        assert!(matches!(
+            validate_partial_fill(&sci, &other_token_rules),
+            Err(PartialFillError::UnexpectedTokenId(_))
+        ));
+    }
+}
@@ -388,8 +388,8 @@ pub mod tests {
         tokens::Mob,
         validation::{
             validate_all_input_rules, validate_inputs_are_sorted, validate_outputs_are_sorted,
-            validate_ring_elements_are_sorted, validate_signature, validate_tx_out,
-            TransactionValidationError,
+            validate_ring_elements_are_sorted, validate_signature, validate_tombstone,
+            validate_tx_out, TransactionValidationError,
         },
         Amount, InputRuleError, SignedContingentInputError, Token, TokenId,
     };
@@ -2305,4 +2305,88 @@ pub mod tests {
             );
         }
     }
+
+    #[test]
+    // Test that the tombstone block limit of a signed contingent input is also
+    // the block at which it expires, whatever the tombstone block requested for
+    // the transaction which incorporates it
+    fn test_contingent_input_expires_at_max_tombstone_block() {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+
+        for block_version in 3..=*BlockVersion::MAX {
+            let block_version = BlockVersion::try_from(block_version).unwrap();
+
+            let alice = AccountKey::random(&mut rng);
+            let bob = AccountKey::random(&mut rng);
+            let fog_resolver = MockFogResolver::default();
+
+            let value = 1475 * MILLIMOB_TO_PICOMOB;
+            let amount = Amount::new(value, Mob::ID);
+            let amount2 = Amount::new(100_000, TokenId::from(2));
+
+            let input_credentials =
+                get_input_credentials(block_version, amount, &alice, &fog_resolver, &mut rng);
+            let proofs = input_credentials.membership_proofs.clone();
+
+            let mut builder = SignedContingentInputBuilder::new(
+                block_version,
+                input_credentials,
+                fog_resolver.clone(),
+                EmptyMemoBuilder::default(),
+            )
+            .unwrap();
+            builder
+                .add_required_output(amount2, &alice.default_subaddress(), &mut rng)
+                .unwrap();
+            builder.set_tombstone_block(500);
+
+            let mut sci = builder.build(&NoKeysRingSigner {}, &mut rng).unwrap();
+            sci.validate().unwrap();
+            assert_eq!(
+                sci.tx_in.input_rules.as_ref().unwrap().max_tombstone_block,
+                500
+            );
+
+            let mut builder = TransactionBuilder::new(
+                block_version,
+                Amount::new(Mob::MINIMUM_FEE, Mob::ID),
+                fog_resolver.clone(),
+                EmptyMemoBuilder::default(),
+            )
+            .unwrap();
+
+            sci.tx_in.proofs = proofs;
+            builder.add_presigned_input(sci).unwrap();
+            builder.add_input(get_input_credentials(
+                block_version,
+                amount2,
+                &bob,
+                &fog_resolver,
+                &mut rng,
+            ));
+            builder
+                .add_output(
+                    Amount::new(value - Mob::MINIMUM_FEE, Mob::ID),
+                    &bob.default_subaddress(),
+                    &mut rng,
+                )
+                .unwrap();
+
+            // The tombstone block is clamped to the limit of the signed input
+            assert_eq!(builder.set_tombstone_block(2000), 500);
+
+            let tx = builder.build(&NoKeysRingSigner {}, &mut rng).unwrap();
+            assert_eq!(tx.prefix.tombstone_block, 500);
+
+            validate_all_input_rules(block_version, &tx).unwrap();
+            validate_signature(block_version, &tx, &mut rng).unwrap();
+
+            // So the transaction can't be included in the ledger from block 500
+            validate_tombstone(499, tx.prefix.tombstone_block).unwrap();
+            assert_matches!(
+                validate_tombstone(500, tx.prefix.tombstone_block),
+                Err(TransactionValidationError::TombstoneBlockExceeded(..))
+            );
+        }
+    }
 }
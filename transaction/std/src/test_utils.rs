@@ -213,9 +213,7 @@ pub fn build_change_memo_with_amount(
     let alice = AccountKey::random_with_fog(&mut rng);
     let alice_address_book = ReservedSubaddresses::from(&alice);
     let change_tx_pubkey = RistrettoPublic::from_random(&mut rng);
-    let memo_context = MemoContext {
-        tx_public_key: &change_tx_pubkey,
-    };
+    let memo_context = MemoContext::new(&change_tx_pubkey, &Default::default());
 
     //Build memo
     builder.make_memo_for_change_output(change_amount, &alice_address_book, memo_context)
@@ -0,0 +1,346 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Display metadata for tokens: their symbol, number of decimals and name.
+//!
+//! Amounts on the blockchain are in the smallest denomination of their token,
+//! e.g. picoMOB. A `TokenMetadataRegistry` knows how to show them to users in
+//! whole units of the token, and how to parse what users type back.
+//!
+//! The registry can be loaded from a JSON config signed by a known key, so
+//! that wallets can pick up new tokens without shipping a new release:
+//!
+//! ```json
+//! {
+//!     "tokens": [
+//!         { "token_id": 0, "symbol": "MOB", "decimals": 12, "name": "MobileCoin" }
+//!     ],
+//!     "signature": "<hex encoded Ed25519 signature>"
+//! }
+//! ```
+//!
+//! The signature is over the digest of the registry, see
+//! `TokenMetadataRegistry::sign`.
+
+use displaydoc::Display;
+use mc_crypto_digestible::{Digestible, MerlinTranscript};
+use mc_crypto_keys::{
+    Ed25519Pair, Ed25519Public, Ed25519Signature, SignatureError, Signer, Verifier,
+};
+use mc_transaction_core::{tokens::Mob, Amount, AmountParseError, Token, TokenId};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Retrieve the canonical signing context byte string for token metadata
+/// configs.
+pub fn token_metadata_signing_context() -> &'static [u8] {
+    b"Token metadata signature"
+}
+
+/// How a token is shown to users.
+#[derive(Clone, Debug, Deserialize, Digestible, Eq, PartialEq, Serialize)]
+pub struct TokenMetadata {
+    /// The ticker symbol, e.g. "MOB"
+    pub symbol: String,
+    /// The number of decimals of one whole unit of the token, in the smallest
+    /// denomination, e.g. 12 for MOB, whose smallest denomination is picoMOB
+    pub decimals: u8,
+    /// The display name, e.g. "MobileCoin"
+    pub name: String,
+}
+
+/// A map of token id to the metadata of the token.
+///
+/// The default registry knows only MOB.
+#[derive(Clone, Debug, Digestible, Eq, PartialEq)]
+pub struct TokenMetadataRegistry {
+    /// Since we hash this map, it is important to use a BTreeMap as it
+    /// guarantees iterating over the map is in sorted and predictable order.
+    map: BTreeMap<TokenId, TokenMetadata>,
+}
+
+impl Default for TokenMetadataRegistry {
+    fn default() -> Self {
+        Self {
+            map: BTreeMap::from([(
+                Mob::ID,
+                TokenMetadata {
+                    symbol: "MOB".to_string(),
+                    decimals: 12,
+                    name: "MobileCoin".to_string(),
+                },
+            )]),
+        }
+    }
+}
+
+impl TokenMetadataRegistry {
+    /// Create a registry from an iterator of token ids and their metadata.
+    pub fn try_from_iter(
+        iter: impl IntoIterator<Item = (TokenId, TokenMetadata)>,
+    ) -> Result<Self, TokenMetadataError> {
+        let mut registry = Self {
+            map: BTreeMap::new(),
+        };
+        for (token_id, metadata) in iter {
+            registry.insert(token_id, metadata)?;
+        }
+        Ok(registry)
+    }
+
+    /// Add the metadata of a token, or replace it if the token is already
+    /// known.
+    pub fn insert(
+        &mut self,
+        token_id: TokenId,
+        metadata: TokenMetadata,
+    ) -> Result<(), TokenMetadataError> {
+        if metadata.symbol.is_empty() {
+            return Err(TokenMetadataError::EmptySymbol(token_id));
+        }
+        if metadata.decimals > Amount::MAX_DECIMALS {
+            return Err(TokenMetadataError::Amount(
+                AmountParseError::UnsupportedDecimals(metadata.decimals),
+            ));
+        }
+        self.map.insert(token_id, metadata);
+        Ok(())
+    }
+
+    /// Get the metadata of a token, or None if the token is not known.
+    pub fn get(&self, token_id: &TokenId) -> Option<&TokenMetadata> {
+        self.map.get(token_id)
+    }
+
+    /// Iterate over the known tokens, in order of token id.
+    pub fn iter(&self) -> impl Iterator<Item = (&TokenId, &TokenMetadata)> {
+        self.map.iter()
+    }
+
+    /// Format an amount in whole units of its token, followed by the symbol of
+    /// the token, e.g. "1.5 MOB".
+    pub fn format_amount(&self, amount: &Amount) -> Result<String, TokenMetadataError> {
+        let metadata = self.get_or_err(&amount.token_id)?;
+        let value = amount.display_with_decimals(metadata.decimals)?;
+        Ok(format!("{} {}", value, metadata.symbol))
+    }
+
+    /// Parse a value in whole units of a token, e.g. "1.5", into an amount of
+    /// that token.
+    pub fn parse_amount(&self, src: &str, token_id: TokenId) -> Result<Amount, TokenMetadataError> {
+        let metadata = self.get_or_err(&token_id)?;
+        Ok(Amount::parse_with_decimals(
+            src,
+            metadata.decimals,
+            token_id,
+        )?)
+    }
+
+    /// Sign the registry, for distributing it as a signed config.
+    pub fn sign(&self, signer: &Ed25519Pair) -> Result<Ed25519Signature, SignatureError> {
+        let message = self.digest32::<MerlinTranscript>(token_metadata_signing_context());
+        signer.try_sign(message.as_ref())
+    }
+
+    /// Verify a signature over the registry.
+    pub fn verify(
+        &self,
+        signer: &Ed25519Public,
+        signature: &Ed25519Signature,
+    ) -> Result<(), SignatureError> {
+        let message = self.digest32::<MerlinTranscript>(token_metadata_signing_context());
+        signer.verify(message.as_ref(), signature)
+    }
+
+    /// Load a registry from a signed JSON config, checking that it was signed
+    /// by `signer`.
+    pub fn from_signed_json(
+        json: &str,
+        signer: &Ed25519Public,
+    ) -> Result<Self, TokenMetadataError> {
+        let config: SignedTokenMetadataConfig = serde_json::from_str(json)
+            .map_err(|err| TokenMetadataError::InvalidConfig(err.to_string()))?;
+
+        let registry = Self::try_from_iter(config.tokens.into_iter().map(|token| {
+            (
+                TokenId::from(token.token_id),
+                TokenMetadata {
+                    symbol: token.symbol,
+                    decimals: token.decimals,
+                    name: token.name,
+                },
+            )
+        }))?;
+
+        let signature_bytes = hex::decode(&config.signature)
+            .map_err(|err| TokenMetadataError::InvalidConfig(err.to_string()))?;
+        let signature = Ed25519Signature::try_from(&signature_bytes[..])
+            .map_err(|_| TokenMetadataError::InvalidSignature)?;
+        registry
+            .verify(signer, &signature)
+            .map_err(|_| TokenMetadataError::InvalidSignature)?;
+
+        Ok(registry)
+    }
+
+    /// Write the registry as a JSON config signed by `signer`.
+    pub fn to_signed_json(&self, signer: &Ed25519Pair) -> Result<String, TokenMetadataError> {
+        let signature = self
+            .sign(signer)
+            .map_err(|_| TokenMetadataError::InvalidSignature)?;
+        let config = SignedTokenMetadataConfig {
+            tokens: self
+                .map
+                .iter()
+                .map(|(token_id, metadata)| TokenMetadataConfig {
+                    token_id: **token_id,
+                    symbol: metadata.symbol.clone(),
+                    decimals: metadata.decimals,
+                    name: metadata.name.clone(),
+                })
+                .collect(),
+            signature: hex::encode(signature),
+        };
+        serde_json::to_string_pretty(&config)
+            .map_err(|err| TokenMetadataError::InvalidConfig(err.to_string()))
+    }
+
+    fn get_or_err(&self, token_id: &TokenId) -> Result<&TokenMetadata, TokenMetadataError> {
+        self.get(token_id)
+            .ok_or(TokenMetadataError::UnknownToken(*token_id))
+    }
+}
+
+// The format of a signed token metadata config file
+#[derive(Deserialize, Serialize)]
+struct SignedTokenMetadataConfig {
+    tokens: Vec<TokenMetadataConfig>,
+    signature: String,
+}
+
+// The metadata of one token in a config file
+#[derive(Deserialize, Serialize)]
+struct TokenMetadataConfig {
+    token_id: u64,
+    symbol: String,
+    decimals: u8,
+    name: String,
+}
+
+/// An error that can occur when using a token metadata registry
+#[derive(Clone, Debug, Display, Eq, PartialEq)]
+pub enum TokenMetadataError {
+    /// Unknown token: {0}
+    UnknownToken(TokenId),
+    /// The symbol of token {0} is empty
+    EmptySymbol(TokenId),
+    /// Amount: {0}
+    Amount(AmountParseError),
+    /// Invalid config: {0}
+    InvalidConfig(String),
+    /// The config is not signed by the expected key
+    InvalidSignature,
+}
+
+impl From<AmountParseError> for TokenMetadataError {
+    fn from(src: AmountParseError) -> Self {
+        Self::Amount(src)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_util_from_random::FromRandom;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn test_registry() -> TokenMetadataRegistry {
+        let mut registry = TokenMetadataRegistry::default();
+        registry
+            .insert(
+                TokenId::from(1),
+                TokenMetadata {
+                    symbol: "eUSD".to_string(),
+                    decimals: 6,
+                    name: "Electronic Dollar".to_string(),
+                },
+            )
+            .unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_format_and_parse_amounts() {
+        let registry = test_registry();
+
+        assert_eq!(
+            registry
+                .format_amount(&Amount::new(1_500_000_000_000, Mob::ID))
+                .unwrap(),
+            "1.5 MOB"
+        );
+        assert_eq!(
+            registry
+                .format_amount(&Amount::new(1_230_000, TokenId::from(1)))
+                .unwrap(),
+            "1.23 eUSD"
+        );
+        assert_eq!(
+            registry.format_amount(&Amount::new(1, TokenId::from(2))),
+            Err(TokenMetadataError::UnknownToken(TokenId::from(2)))
+        );
+
+        assert_eq!(
+            registry.parse_amount("0.0004", Mob::ID).unwrap(),
+            Amount::new(400_000_000, Mob::ID)
+        );
+        assert_eq!(
+            registry.parse_amount("0.0000001", TokenId::from(1)),
+            Err(TokenMetadataError::Amount(
+                AmountParseError::TooManyDecimals(6)
+            ))
+        );
+
+        assert_eq!(
+            registry.insert(
+                TokenId::from(2),
+                TokenMetadata {
+                    symbol: String::new(),
+                    decimals: 6,
+                    name: "Nameless".to_string(),
+                },
+            ),
+            Err(TokenMetadataError::EmptySymbol(TokenId::from(2)))
+        );
+    }
+
+    #[test]
+    fn test_signed_config_round_trip() {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+        let signer = Ed25519Pair::from_random(&mut rng);
+        let other_signer = Ed25519Pair::from_random(&mut rng);
+        let registry = test_registry();
+
+        let json = registry.to_signed_json(&signer).unwrap();
+        assert_eq!(
+            TokenMetadataRegistry::from_signed_json(&json, &signer.public_key()).unwrap(),
+            registry
+        );
+        assert_eq!(
+            TokenMetadataRegistry::from_signed_json(&json, &other_signer.public_key()),
+            Err(TokenMetadataError::InvalidSignature)
+        );
+
+        // Changing the config invalidates the signature
+        let tampered = json.replace("\"decimals\": 6", "\"decimals\": 2");
+        assert_ne!(tampered, json);
+        assert_eq!(
+            TokenMetadataRegistry::from_signed_json(&tampered, &signer.public_key()),
+            Err(TokenMetadataError::InvalidSignature)
+        );
+
+        assert!(matches!(
+            TokenMetadataRegistry::from_signed_json("{}", &signer.public_key()),
+            Err(TokenMetadataError::InvalidConfig(_))
+        ));
+    }
+}
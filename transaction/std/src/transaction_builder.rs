@@ -14,6 +14,7 @@ use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPrivate, RistrettoPubli
 use mc_crypto_ring_signature_signer::RingSigner;
 use mc_fog_report_validation::FogPubkeyResolver;
 use mc_transaction_core::{
+    constants::MAX_ADDITIONAL_FOG_HINTS,
     encrypted_fog_hint::EncryptedFogHint,
     fog_hint::FogHint,
     onetime_keys::create_shared_secret,
@@ -21,11 +22,11 @@ use mc_transaction_core::{
     tokens::Mob,
     tx::{Tx, TxIn, TxOut, TxOutConfirmationNumber, TxPrefix},
     Amount, BlockVersion, MemoContext, MemoPayload, NewMemoError, SignedContingentInput,
-    SignedContingentInputError, Token, TokenId,
+    SignedContingentInputError, Token, TokenId, TxSummary,
 };
 use mc_util_from_random::FromRandom;
 use rand_core::{CryptoRng, RngCore};
-use std::cmp::Ordering;
+use std::{cmp::Ordering, collections::BTreeMap};
 
 /// A trait used to compare the transaction outputs
 pub trait TxOutputsOrdering {
@@ -78,7 +79,7 @@ pub struct TransactionBuilder<FPR: FogPubkeyResolver> {
     tombstone_block: u64,
     /// The fee paid in connection to this transaction
     /// If mixed transactions feature is off, then everything must be this token
-    /// id.
+    /// id. Otherwise, the inputs of this token id must also pay for the fee.
     fee: Amount,
     /// The source of validated fog pubkeys used for this transaction
     fog_resolver: FPR,
@@ -269,6 +270,64 @@ impl<FPR: FogPubkeyResolver> TransactionBuilder<FPR> {
         result
     }
 
+    /// Add a non-change output to the transaction, with a fog hint for each
+    /// of the fog deployments of the recipient.
+    ///
+    /// This is for recipients who are migrating between fog deployments: the
+    /// main fog hint is for the fog of `recipient`, and there is an additional
+    /// fog hint for the fog of each of `additional_fog_addresses`, which are
+    /// addresses of the same subaddress with other fog report urls. Fog
+    /// ingest servers of any of these deployments can then find the output.
+    ///
+    /// # Arguments
+    /// * `amount` - The amount of this output
+    /// * `recipient` - The recipient's public address
+    /// * `additional_fog_addresses` - The recipient's addresses with other fog
+    ///   deployments, no more than MAX_ADDITIONAL_FOG_HINTS
+    /// * `rng` - RNG used to generate blinding for commitment
+    pub fn add_output_with_additional_fog_hints<RNG: CryptoRng + RngCore>(
+        &mut self,
+        amount: Amount,
+        recipient: &PublicAddress,
+        additional_fog_addresses: &[PublicAddress],
+        rng: &mut RNG,
+    ) -> Result<TxOutContext, TxBuilderError> {
+        if !self.block_version.additional_fog_hints_are_supported() {
+            return Err(TxBuilderError::FeatureNotSupportedAtBlockVersion(
+                *self.block_version,
+                "additional fog hints",
+            ));
+        }
+        if additional_fog_addresses.len() > MAX_ADDITIONAL_FOG_HINTS {
+            return Err(TxBuilderError::TooManyAdditionalFogHints(
+                additional_fog_addresses.len(),
+                MAX_ADDITIONAL_FOG_HINTS,
+            ));
+        }
+
+        let mut additional_e_fog_hints = Vec::with_capacity(additional_fog_addresses.len());
+        let mut pubkey_expiries = Vec::with_capacity(additional_fog_addresses.len());
+        for fog_address in additional_fog_addresses {
+            let (hint, pubkey_expiry) = create_fog_hint(fog_address, &self.fog_resolver, rng)?;
+            additional_e_fog_hints.push(hint);
+            pubkey_expiries.push(pubkey_expiry);
+        }
+
+        let mut tx_out_context = self.add_output(amount, recipient, rng)?;
+        for pubkey_expiry in pubkey_expiries {
+            self.impose_tombstone_block_limit(pubkey_expiry);
+        }
+
+        // Adding the hints doesn't change the amount or the memo of the output
+        let (tx_out, _output_secret) = self
+            .outputs_and_secrets
+            .last_mut()
+            .expect("the output was just added");
+        tx_out.additional_e_fog_hints = additional_e_fog_hints;
+        tx_out_context.tx_out = tx_out.clone();
+        Ok(tx_out_context)
+    }
+
     /// Add a standard change output to the transaction.
     ///
     /// The change output is meant to send any value in the inputs not already
@@ -596,6 +655,8 @@ impl<FPR: FogPubkeyResolver> TransactionBuilder<FPR> {
             }
         }
 
+        self.check_token_balances()?;
+
         // Construct a list of sorted inputs.
         // Inputs are sorted by the first ring element's public key. Note that each ring
         // is also sorted.
@@ -620,9 +681,11 @@ impl<FPR: FogPubkeyResolver> TransactionBuilder<FPR> {
             .collect::<Vec<InputRing>>();
 
         let message = tx_prefix.hash().0;
+        let tx_summary = TxSummary::from(&tx_prefix);
         let signature = SignatureRctBulletproofs::sign(
             self.block_version,
             &message,
+            &tx_summary,
             &input_rings,
             &output_secrets,
             self.fee,
@@ -635,6 +698,41 @@ impl<FPR: FogPubkeyResolver> TransactionBuilder<FPR> {
             signature,
         })
     }
+
+    /// Check that, for each token id appearing in a mixed transaction, the
+    /// inputs of that token pay for exactly the outputs of that token, plus the
+    /// fee if it is the fee token.
+    ///
+    /// Signing fails anyway if any token does not balance, but without saying
+    /// which one. This is skipped when only one token id is involved, since
+    /// then there is nothing more to report.
+    fn check_token_balances(&self) -> Result<(), TxBuilderError> {
+        // Totals of (inputs, outputs and fee) for each token id
+        let mut balances = BTreeMap::<TokenId, (u128, u128)>::new();
+        balances.entry(self.fee.token_id).or_default().1 += self.fee.value as u128;
+        for input in self.input_materials.iter() {
+            let amount = input.amount();
+            balances.entry(amount.token_id).or_default().0 += amount.value as u128;
+        }
+        for (_, output_secret) in self.outputs_and_secrets.iter() {
+            let amount = output_secret.amount;
+            balances.entry(amount.token_id).or_default().1 += amount.value as u128;
+        }
+
+        if balances.len() < 2 {
+            return Ok(());
+        }
+        for (token_id, (inputs_total, outputs_total)) in balances {
+            if inputs_total != outputs_total {
+                return Err(TxBuilderError::UnbalancedToken(
+                    token_id,
+                    inputs_total,
+                    outputs_total,
+                ));
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Creates a TxOut that sends `value` to `recipient` using the provided
@@ -703,9 +801,10 @@ pub mod transaction_builder_tests {
     use super::*;
     use crate::{
         test_utils::{create_output, get_input_credentials, get_ring, get_transaction},
-        BurnRedemptionMemoBuilder, EmptyMemoBuilder, GiftCodeCancellationMemoBuilder,
+        AuthenticatedPaymentRequestMemo, BurnRedemptionMemo, BurnRedemptionMemoBuilder,
+        BurnRedemptionMemoError, EmptyMemoBuilder, GiftCodeCancellationMemoBuilder,
         GiftCodeFundingMemoBuilder, GiftCodeSenderMemoBuilder, MemoType, RTHMemoBuilder,
-        SenderMemoCredential,
+        RegisteredMemoType, SenderMemoCredential,
     };
     use assert_matches::assert_matches;
     use maplit::btreemap;
@@ -2085,6 +2184,199 @@ pub mod transaction_builder_tests {
         }
     }
 
+    #[test]
+    // Test that the RTH memo builder writes payment request memos committing to
+    // the amount of each output when that is enabled, and that the recipient can
+    // validate them against the outputs they receive
+    fn test_rth_memo_builder_payment_request_amount_commitment() {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+
+        for (block_version, token_id) in get_block_version_token_id_pairs() {
+            if !block_version.e_memo_feature_is_supported() {
+                continue;
+            }
+
+            let sender = AccountKey::random_with_fog(&mut rng);
+            let sender_addr = sender.default_subaddress();
+            let sender_change_dest = ReservedSubaddresses::from(&sender);
+            let recipient = AccountKey::random_with_fog(&mut rng);
+            let recipient_address = recipient.default_subaddress();
+            let ingest_private_key = RistrettoPrivate::from_random(&mut rng);
+            let value = 1475 * MILLIMOB_TO_PICOMOB;
+            let change_value = 128 * MILLIMOB_TO_PICOMOB;
+
+            let fog_resolver = MockFogResolver(btreemap! {
+                recipient_address.fog_report_url().unwrap().to_string() =>
+                    FullyValidatedFogPubkey {
+                        pubkey: RistrettoPublic::from(&ingest_private_key),
+                        pubkey_expiry: 1000,
+                    },
+            });
+
+            let mut build_tx = |memo_builder: RTHMemoBuilder| {
+                let mut transaction_builder = TransactionBuilder::new(
+                    block_version,
+                    Amount::new(Mob::MINIMUM_FEE, token_id),
+                    fog_resolver.clone(),
+                    memo_builder,
+                )
+                .unwrap();
+
+                transaction_builder.set_tombstone_block(2000);
+
+                let input_credentials = get_input_credentials(
+                    block_version,
+                    Amount { value, token_id },
+                    &sender,
+                    &fog_resolver,
+                    &mut rng,
+                );
+                transaction_builder.add_input(input_credentials);
+
+                transaction_builder
+                    .add_output(
+                        Amount::new(value - change_value - Mob::MINIMUM_FEE, token_id),
+                        &recipient_address,
+                        &mut rng,
+                    )
+                    .unwrap();
+
+                transaction_builder
+                    .add_change_output(
+                        Amount::new(change_value, token_id),
+                        &sender_change_dest,
+                        &mut rng,
+                    )
+                    .unwrap();
+
+                let tx = transaction_builder
+                    .build(&NoKeysRingSigner {}, &mut rng)
+                    .unwrap();
+                assert!(validate_signature(block_version, &tx, &mut rng).is_ok());
+                tx
+            };
+
+            // Enable sender and destination memos, and set a payment request id with an
+            // amount commitment
+            let mut memo_builder = RTHMemoBuilder::default();
+            memo_builder.set_sender_credential(SenderMemoCredential::from(&sender));
+            memo_builder.enable_destination_memo();
+            memo_builder.set_payment_request_id(43);
+            memo_builder.enable_payment_request_amount_commitment();
+            let tx = build_tx(memo_builder.clone());
+
+            let output = tx
+                .prefix
+                .outputs
+                .iter()
+                .find(|tx_out| {
+                    subaddress_matches_tx_out(&recipient, DEFAULT_SUBADDRESS_INDEX, tx_out).unwrap()
+                })
+                .expect("Didn't find recipient's output");
+            let change = tx
+                .prefix
+                .outputs
+                .iter()
+                .find(|tx_out| {
+                    subaddress_matches_tx_out(&sender, CHANGE_SUBADDRESS_INDEX, tx_out).unwrap()
+                })
+                .expect("Didn't find sender's output");
+
+            // The recipient finds a memo committing to the amount of their output
+            let ss = get_tx_out_shared_secret(
+                recipient.view_private_key(),
+                &RistrettoPublic::try_from(&output.public_key).unwrap(),
+            );
+            let (amount, _) = output.masked_amount.get_value(&ss).unwrap();
+            assert_eq!(amount.value, value - change_value - Mob::MINIMUM_FEE);
+            let memo_payload = output.e_memo.unwrap().decrypt(&ss);
+            assert_eq!(
+                memo_payload.get_memo_type(),
+                &AuthenticatedPaymentRequestMemo::MEMO_TYPE_BYTES
+            );
+            match MemoType::try_from(&memo_payload).expect("Couldn't decrypt memo") {
+                MemoType::AuthenticatedPaymentRequest(memo) => {
+                    assert_eq!(
+                        memo.sender_address_hash(),
+                        ShortAddressHash::from(&sender_addr),
+                        "lookup based on address hash failed"
+                    );
+                    assert!(
+                        bool::from(memo.validate(
+                            &sender_addr,
+                            &recipient.subaddress_view_private(DEFAULT_SUBADDRESS_INDEX),
+                            &output.public_key,
+                        )),
+                        "hmac validation failed"
+                    );
+                    assert_eq!(memo.payment_request_id(), 43);
+                    assert!(
+                        bool::from(
+                            memo.validate_amount_commitment(&output.masked_amount.commitment)
+                        ),
+                        "amount commitment validation failed"
+                    );
+                    assert!(
+                        !bool::from(
+                            memo.validate_amount_commitment(&change.masked_amount.commitment)
+                        ),
+                        "amount commitment validation should have failed"
+                    );
+                }
+                _ => {
+                    panic!("unexpected memo type")
+                }
+            }
+
+            // The change still gets a destination memo
+            let ss = get_tx_out_shared_secret(
+                sender.view_private_key(),
+                &RistrettoPublic::try_from(&change.public_key).unwrap(),
+            );
+            let memo = change.e_memo.unwrap().decrypt(&ss);
+            match MemoType::try_from(&memo).expect("Couldn't decrypt memo") {
+                MemoType::Destination(memo) => {
+                    assert_eq!(
+                        memo.get_address_hash(),
+                        &ShortAddressHash::from(&recipient_address),
+                        "lookup based on address hash failed"
+                    );
+                    assert_eq!(memo.get_fee(), Mob::MINIMUM_FEE);
+                }
+                _ => {
+                    panic!("unexpected memo type")
+                }
+            }
+
+            // Once disabled again, payment requests use memos without an amount
+            // commitment
+            memo_builder.disable_payment_request_amount_commitment();
+            let tx = build_tx(memo_builder);
+
+            let output = tx
+                .prefix
+                .outputs
+                .iter()
+                .find(|tx_out| {
+                    subaddress_matches_tx_out(&recipient, DEFAULT_SUBADDRESS_INDEX, tx_out).unwrap()
+                })
+                .expect("Didn't find recipient's output");
+            let ss = get_tx_out_shared_secret(
+                recipient.view_private_key(),
+                &RistrettoPublic::try_from(&output.public_key).unwrap(),
+            );
+            let memo = output.e_memo.unwrap().decrypt(&ss);
+            match MemoType::try_from(&memo).expect("Couldn't decrypt memo") {
+                MemoType::AuthenticatedSenderWithPaymentRequestId(memo) => {
+                    assert_eq!(memo.payment_request_id(), 43);
+                }
+                _ => {
+                    panic!("unexpected memo type")
+                }
+            }
+        }
+    }
+
     #[test]
     // Transaction builder with RTH memo builder and custom sender credential
     fn test_transaction_builder_memo_custom_sender() {
@@ -3027,6 +3319,71 @@ pub mod transaction_builder_tests {
         }
     }
 
+    #[test]
+    // Burn outputs made with an external tx id can be decoded with
+    // BurnRedemptionMemo::from_burn_tx_out
+    fn test_transaction_builder_burn_redemption_external_tx_id() {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+        let block_version = BlockVersion::MAX;
+        let token_id = TokenId::from(5);
+        let fog_resolver = MockFogResolver::default();
+        let sender = AccountKey::random(&mut rng);
+        let change_destination = ReservedSubaddresses::from(&sender);
+        let external_tx_id = [9u8; 32];
+
+        assert_matches!(
+            BurnRedemptionMemoBuilder::new_with_external_tx_id(&[]),
+            Err(BurnRedemptionMemoError::InvalidExternalTxIdLength(0))
+        );
+
+        let mut memo_builder =
+            BurnRedemptionMemoBuilder::new_with_external_tx_id(&external_tx_id).unwrap();
+        memo_builder.enable_destination_memo();
+
+        let mut transaction_builder = TransactionBuilder::new(
+            block_version,
+            Amount::new(3, token_id),
+            fog_resolver.clone(),
+            memo_builder,
+        )
+        .unwrap();
+
+        let input_credentials = get_input_credentials(
+            block_version,
+            Amount::new(113, token_id),
+            &AccountKey::random(&mut rng),
+            &fog_resolver,
+            &mut rng,
+        );
+        transaction_builder.add_input(input_credentials);
+
+        let TxOutContext {
+            tx_out: burn_output,
+            ..
+        } = transaction_builder
+            .add_output(Amount::new(100, token_id), &burn_address(), &mut rng)
+            .unwrap();
+
+        let TxOutContext {
+            tx_out: change_output,
+            ..
+        } = transaction_builder
+            .add_change_output(Amount::new(10, token_id), &change_destination, &mut rng)
+            .unwrap();
+
+        transaction_builder
+            .build(&NoKeysRingSigner {}, &mut rng)
+            .expect("build tx");
+
+        let memo = BurnRedemptionMemo::from_burn_tx_out(&burn_output).unwrap();
+        assert_eq!(memo.external_tx_id().unwrap(), &external_tx_id[..]);
+
+        assert_eq!(
+            BurnRedemptionMemo::from_burn_tx_out(&change_output),
+            Err(BurnRedemptionMemoError::NotBurnTxOut)
+        );
+    }
+
     #[test]
     // Test that sending mixed transactions works
     //
@@ -3241,6 +3598,87 @@ pub mod transaction_builder_tests {
         }
     }
 
+    #[test]
+    // Mixed transactions which don't balance report the token which doesn't
+    // balance
+    fn test_mixed_transactions_unbalanced_token_error() {
+        let mut rng: StdRng = SeedableRng::from_seed([19u8; 32]);
+
+        let fog_resolver = MockFogResolver::default();
+        let sender = AccountKey::random(&mut rng);
+        let recipient = AccountKey::random(&mut rng);
+        let recipient_addr = recipient.default_subaddress();
+
+        let amount1 = Amount::new(1475 * MILLIMOB_TO_PICOMOB, Mob::ID);
+        let amount2 = Amount::new(999999, 2.into());
+        let fee = Amount::new(Mob::MINIMUM_FEE, Mob::ID);
+
+        // Builds a transaction spending amount1 and amount2, with the given outputs
+        let mut test_fn = |block_version, output_amounts: &[Amount]| -> Result<_, _> {
+            let mut transaction_builder = TransactionBuilder::new(
+                block_version,
+                fee,
+                fog_resolver.clone(),
+                EmptyMemoBuilder::default(),
+            )
+            .unwrap();
+
+            for amount in [amount1, amount2] {
+                let input_credentials =
+                    get_input_credentials(block_version, amount, &sender, &fog_resolver, &mut rng);
+                transaction_builder.add_input(input_credentials);
+            }
+
+            for amount in output_amounts {
+                transaction_builder
+                    .add_output(*amount, &recipient_addr, &mut rng)
+                    .unwrap();
+            }
+
+            transaction_builder.build(&NoKeysRingSigner {}, &mut rng)
+        };
+
+        let tx_out1_amount = Amount::new(amount1.value - fee.value, Mob::ID);
+
+        for block_version in 3..=*BlockVersion::MAX {
+            let block_version = BlockVersion::try_from(block_version).unwrap();
+
+            assert!(test_fn(block_version, &[tx_out1_amount, amount2]).is_ok());
+
+            // The fee must be paid from the inputs of the fee token
+            match test_fn(block_version, &[amount1, amount2]) {
+                Err(TxBuilderError::UnbalancedToken(token_id, inputs, outputs)) => {
+                    assert_eq!(token_id, Mob::ID);
+                    assert_eq!(inputs, amount1.value as u128);
+                    assert_eq!(outputs, (amount1.value + fee.value) as u128);
+                }
+                result => panic!("Unexpected result {:?}", result),
+            }
+
+            // The other token must balance on its own
+            let short_amount2 = Amount::new(amount2.value - 1, amount2.token_id);
+            match test_fn(block_version, &[tx_out1_amount, short_amount2]) {
+                Err(TxBuilderError::UnbalancedToken(token_id, inputs, outputs)) => {
+                    assert_eq!(token_id, amount2.token_id);
+                    assert_eq!(inputs, amount2.value as u128);
+                    assert_eq!(outputs, short_amount2.value as u128);
+                }
+                result => panic!("Unexpected result {:?}", result),
+            }
+
+            // An output of a token with no inputs can't be paid for
+            let extra_amount = Amount::new(10, 99.into());
+            match test_fn(block_version, &[tx_out1_amount, amount2, extra_amount]) {
+                Err(TxBuilderError::UnbalancedToken(token_id, inputs, outputs)) => {
+                    assert_eq!(token_id, extra_amount.token_id);
+                    assert_eq!(inputs, 0);
+                    assert_eq!(outputs, extra_amount.value as u128);
+                }
+                result => panic!("Unexpected result {:?}", result),
+            }
+        }
+    }
+
     #[test]
     // Transaction builder with gift codes
     fn test_gift_code_transactions() {
@@ -3737,4 +4175,123 @@ pub mod transaction_builder_tests {
             );
         }
     }
+
+    #[test]
+    // An output can have fog hints for several fog deployments of the recipient
+    fn test_additional_fog_hints() {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+
+        for block_version in BlockVersion::iterator() {
+            let sender = AccountKey::random(&mut rng);
+            let spend_private = RistrettoPrivate::from_random(&mut rng);
+            let view_private = RistrettoPrivate::from_random(&mut rng);
+            let old_fog_recipient = AccountKey::new_with_fog(
+                &spend_private,
+                &view_private,
+                "fog://old.example.com",
+                "".to_string(),
+                [7u8; 32],
+            );
+            let new_fog_recipient = AccountKey::new_with_fog(
+                &spend_private,
+                &view_private,
+                "fog://new.example.com",
+                "".to_string(),
+                [8u8; 32],
+            );
+            let old_ingest_private_key = RistrettoPrivate::from_random(&mut rng);
+            let new_ingest_private_key = RistrettoPrivate::from_random(&mut rng);
+
+            let fog_resolver = MockFogResolver(btreemap! {
+                "fog://old.example.com".to_string() => FullyValidatedFogPubkey {
+                    pubkey: RistrettoPublic::from(&old_ingest_private_key),
+                    pubkey_expiry: 1000,
+                },
+                "fog://new.example.com".to_string() => FullyValidatedFogPubkey {
+                    pubkey: RistrettoPublic::from(&new_ingest_private_key),
+                    pubkey_expiry: 800,
+                },
+            });
+
+            let value = 1475 * MILLIMOB_TO_PICOMOB;
+            let mut transaction_builder = TransactionBuilder::new(
+                block_version,
+                Amount::new(Mob::MINIMUM_FEE, Mob::ID),
+                fog_resolver.clone(),
+                EmptyMemoBuilder::default(),
+            )
+            .unwrap();
+            transaction_builder.add_input(get_input_credentials(
+                block_version,
+                Amount::new(value, Mob::ID),
+                &sender,
+                &fog_resolver,
+                &mut rng,
+            ));
+
+            let result = transaction_builder.add_output_with_additional_fog_hints(
+                Amount::new(value - Mob::MINIMUM_FEE, Mob::ID),
+                &old_fog_recipient.default_subaddress(),
+                &[new_fog_recipient.default_subaddress()],
+                &mut rng,
+            );
+            if !block_version.additional_fog_hints_are_supported() {
+                assert_matches!(
+                    result,
+                    Err(TxBuilderError::FeatureNotSupportedAtBlockVersion(_, _))
+                );
+                continue;
+            }
+            let tx_out_context = result.unwrap();
+            assert_eq!(tx_out_context.tx_out.additional_e_fog_hints.len(), 1);
+
+            let tx = transaction_builder
+                .build(&NoKeysRingSigner {}, &mut rng)
+                .unwrap();
+            let output = &tx.prefix.outputs[0];
+            assert_eq!(output, &tx_out_context.tx_out);
+            validate_tx_out(block_version, output).unwrap();
+            assert!(validate_signature(block_version, &tx, &mut rng).is_ok());
+
+            // The tombstone block is limited by both fog pubkeys
+            assert_eq!(tx.prefix.tombstone_block, 800);
+
+            // Both fog deployments can decrypt their hint
+            let expected_view_pubkey = CompressedRistrettoPublic::from(
+                old_fog_recipient.default_subaddress().view_public_key(),
+            );
+            for (ingest_private_key, e_fog_hint) in [
+                (&old_ingest_private_key, &output.e_fog_hint),
+                (&new_ingest_private_key, &output.additional_e_fog_hints[0]),
+            ] {
+                let mut output_fog_hint = FogHint::new(RistrettoPublic::from_random(&mut rng));
+                assert!(bool::from(FogHint::ct_decrypt(
+                    ingest_private_key,
+                    e_fog_hint,
+                    &mut output_fog_hint,
+                )));
+                assert_eq!(output_fog_hint.get_view_pubkey(), &expected_view_pubkey);
+            }
+
+            // No more than MAX_ADDITIONAL_FOG_HINTS are allowed
+            let mut transaction_builder = TransactionBuilder::new(
+                block_version,
+                Amount::new(Mob::MINIMUM_FEE, Mob::ID),
+                fog_resolver.clone(),
+                EmptyMemoBuilder::default(),
+            )
+            .unwrap();
+            let too_many =
+                vec![new_fog_recipient.default_subaddress(); MAX_ADDITIONAL_FOG_HINTS + 1];
+            assert_matches!(
+                transaction_builder.add_output_with_additional_fog_hints(
+                    Amount::new(value, Mob::ID),
+                    &old_fog_recipient.default_subaddress(),
+                    &too_many,
+                    &mut rng,
+                ),
+                Err(TxBuilderError::TooManyAdditionalFogHints(_, _))
+            );
+        }
+    }
 }
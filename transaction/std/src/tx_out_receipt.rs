@@ -0,0 +1,231 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Receipts which the sender of a payment gives to its recipient.
+//!
+//! A `TxOutReceipt` identifies a TxOut of a transaction, and states the
+//! amount, in any token, which the sender claims to have sent in it. The
+//! recipient checks the receipt against the TxOut once it is in the ledger,
+//! using their view private key.
+//!
+//! The sender can also hand out a `TxOutAmountProof`, which is the shared
+//! secret of the TxOut. With it, anyone who has the TxOut can check the
+//! confirmation number and the amount of the receipt, without the view private
+//! key of the recipient, e.g. to settle a dispute about a payment.
+
+use crate::TxOutContext;
+use core::convert::TryFrom;
+use displaydoc::Display;
+use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPrivate, RistrettoPublic};
+use mc_transaction_core::{
+    get_tx_out_shared_secret,
+    tx::{TxOut, TxOutConfirmationNumber},
+    Amount, AmountError, TokenId,
+};
+
+/// A receipt for a TxOut, stating the amount which was sent in it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TxOutReceipt {
+    /// The public key of the TxOut
+    pub tx_public_key: CompressedRistrettoPublic,
+    /// The hash of the TxOut
+    pub tx_out_hash: [u8; 32],
+    /// The tombstone block of the transaction which contains the TxOut
+    pub tombstone_block: u64,
+    /// The confirmation number of the TxOut
+    pub confirmation_number: TxOutConfirmationNumber,
+    /// The value and token id sent in the TxOut
+    pub amount: Amount,
+}
+
+impl TxOutReceipt {
+    /// Create the receipt of an output added to a transaction builder.
+    ///
+    /// # Arguments
+    /// * `tx_out_context` - The context returned when adding the output
+    /// * `amount` - The amount of the output
+    /// * `tombstone_block` - The tombstone block of the transaction
+    pub fn new(tx_out_context: &TxOutContext, amount: Amount, tombstone_block: u64) -> Self {
+        Self {
+            tx_public_key: tx_out_context.tx_out.public_key,
+            tx_out_hash: tx_out_context.tx_out.hash(),
+            tombstone_block,
+            confirmation_number: tx_out_context.confirmation.clone(),
+            amount,
+        }
+    }
+
+    /// Check the receipt against a TxOut, as its recipient.
+    ///
+    /// This checks that the receipt is for this TxOut, that the confirmation
+    /// number is correct, and that the TxOut holds the amount of the receipt.
+    pub fn verify(
+        &self,
+        tx_out: &TxOut,
+        view_private_key: &RistrettoPrivate,
+    ) -> Result<(), TxOutReceiptError> {
+        let tx_public_key = self.check_tx_out(tx_out)?;
+        let shared_secret = get_tx_out_shared_secret(view_private_key, &tx_public_key);
+        self.check_shared_secret(tx_out, &shared_secret)
+    }
+
+    /// Check the receipt against a TxOut, with a proof from its sender,
+    /// without the view private key of the recipient.
+    pub fn verify_with_amount_proof(
+        &self,
+        tx_out: &TxOut,
+        proof: &TxOutAmountProof,
+    ) -> Result<(), TxOutReceiptError> {
+        self.check_tx_out(tx_out)?;
+        let shared_secret = RistrettoPublic::try_from(&proof.shared_secret)
+            .map_err(|_| TxOutReceiptError::InvalidKey)?;
+        self.check_shared_secret(tx_out, &shared_secret)
+    }
+
+    // Check that the receipt is for this TxOut, and get its public key
+    fn check_tx_out(&self, tx_out: &TxOut) -> Result<RistrettoPublic, TxOutReceiptError> {
+        if tx_out.public_key != self.tx_public_key || tx_out.hash() != self.tx_out_hash {
+            return Err(TxOutReceiptError::WrongTxOut);
+        }
+        RistrettoPublic::try_from(&tx_out.public_key).map_err(|_| TxOutReceiptError::InvalidKey)
+    }
+
+    // Check the confirmation number and the amount, with the shared secret of
+    // the TxOut
+    fn check_shared_secret(
+        &self,
+        tx_out: &TxOut,
+        shared_secret: &RistrettoPublic,
+    ) -> Result<(), TxOutReceiptError> {
+        if TxOutConfirmationNumber::from(shared_secret) != self.confirmation_number {
+            return Err(TxOutReceiptError::InvalidConfirmationNumber);
+        }
+
+        let (amount, _blinding) = tx_out.masked_amount.get_value(shared_secret)?;
+        if amount.token_id != self.amount.token_id {
+            return Err(TxOutReceiptError::TokenIdMismatch(
+                self.amount.token_id,
+                amount.token_id,
+            ));
+        }
+        if amount.value != self.amount.value {
+            return Err(TxOutReceiptError::ValueMismatch(
+                self.amount.value,
+                amount.value,
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A proof of the amount of a TxOut, which its sender can give to anyone.
+///
+/// This is the shared secret of the TxOut. It reveals the amount of that
+/// TxOut, and nothing about the other TxOuts of the recipient.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TxOutAmountProof {
+    /// The shared secret of the TxOut
+    pub shared_secret: CompressedRistrettoPublic,
+}
+
+impl From<&TxOutContext> for TxOutAmountProof {
+    fn from(tx_out_context: &TxOutContext) -> Self {
+        Self {
+            shared_secret: CompressedRistrettoPublic::from(&tx_out_context.shared_secret),
+        }
+    }
+}
+
+/// An error that can occur when verifying a TxOut receipt
+#[derive(Debug, Display, Eq, PartialEq)]
+pub enum TxOutReceiptError {
+    /// The receipt is not for this TxOut
+    WrongTxOut,
+    /// A key is not a valid Ristretto point
+    InvalidKey,
+    /// The confirmation number is not correct for this TxOut
+    InvalidConfirmationNumber,
+    /// Amount: {0}
+    Amount(AmountError),
+    /// The receipt is for token {0}, but the TxOut holds token {1}
+    TokenIdMismatch(TokenId, TokenId),
+    /// The receipt is for value {0}, but the TxOut holds value {1}
+    ValueMismatch(u64, u64),
+}
+
+impl From<AmountError> for TxOutReceiptError {
+    fn from(src: AmountError) -> Self {
+        Self::Amount(src)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::get_input_credentials, EmptyMemoBuilder, TransactionBuilder};
+    use mc_account_keys::AccountKey;
+    use mc_fog_report_validation_test_utils::MockFogResolver;
+    use mc_transaction_core::{tokens::Mob, BlockVersion, Token};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_verify_receipts_of_any_token() {
+        let mut rng: StdRng = SeedableRng::from_seed([1u8; 32]);
+        let block_version = BlockVersion::MAX;
+        let fpr = MockFogResolver::default();
+        let sender = AccountKey::random(&mut rng);
+        let recipient = AccountKey::random(&mut rng);
+        let token_id = TokenId::from(5);
+
+        let input_credentials = get_input_credentials(
+            block_version,
+            Amount::new(1000, token_id),
+            &sender,
+            &fpr,
+            &mut rng,
+        );
+        let mut builder = TransactionBuilder::new(
+            block_version,
+            Amount::new(Mob::MINIMUM_FEE, token_id),
+            fpr,
+            EmptyMemoBuilder::default(),
+        )
+        .unwrap();
+        builder.add_input(input_credentials);
+        let amount = Amount::new(300, token_id);
+        let tx_out_context = builder
+            .add_output(amount, &recipient.default_subaddress(), &mut rng)
+            .unwrap();
+
+        let receipt = TxOutReceipt::new(&tx_out_context, amount, 100);
+        let tx_out = &tx_out_context.tx_out;
+        assert_eq!(receipt.verify(tx_out, recipient.view_private_key()), Ok(()));
+        assert_eq!(
+            receipt.verify(tx_out, sender.view_private_key()),
+            Err(TxOutReceiptError::InvalidConfirmationNumber)
+        );
+
+        let proof = TxOutAmountProof::from(&tx_out_context);
+        assert_eq!(receipt.verify_with_amount_proof(tx_out, &proof), Ok(()));
+
+        // A receipt for another token or value doesn't verify
+        let mut wrong_token = receipt.clone();
+        wrong_token.amount.token_id = Mob::ID;
+        assert_eq!(
+            wrong_token.verify(tx_out, recipient.view_private_key()),
+            Err(TxOutReceiptError::TokenIdMismatch(Mob::ID, token_id))
+        );
+        let mut wrong_value = receipt.clone();
+        wrong_value.amount.value = 301;
+        assert_eq!(
+            wrong_value.verify_with_amount_proof(tx_out, &proof),
+            Err(TxOutReceiptError::ValueMismatch(301, 300))
+        );
+
+        let mut wrong_tx_out = receipt;
+        wrong_tx_out.tx_out_hash = [0u8; 32];
+        assert_eq!(
+            wrong_tx_out.verify(tx_out, recipient.view_private_key()),
+            Err(TxOutReceiptError::WrongTxOut)
+        );
+    }
+}
@@ -4,7 +4,10 @@
 //! blockchain.
 
 use crate::token::TokenId;
+use core::fmt;
+use displaydoc::Display;
 use mc_crypto_digestible::Digestible;
+use serde::{Deserialize, Serialize};
 use zeroize::Zeroize;
 
 /// An amount of some token, in the "base" (u64) denomination.
@@ -17,8 +20,175 @@ pub struct Amount {
 }
 
 impl Amount {
+    /// The largest number of decimals a token can have. (10^19 is the largest
+    /// power of ten which fits in a u64.)
+    pub const MAX_DECIMALS: u8 = 19;
+
     /// Create a new amount
     pub fn new(value: u64, token_id: TokenId) -> Self {
         Self { value, token_id }
     }
+
+    /// Display the value of this amount in whole units of a token with
+    /// `decimals` decimals, e.g. 1_500_000_000_000 picoMOB with 12 decimals is
+    /// displayed as "1.5". Trailing zeros after the decimal point are omitted.
+    pub fn display_with_decimals(&self, decimals: u8) -> Result<DecimalValue, AmountParseError> {
+        if decimals > Self::MAX_DECIMALS {
+            return Err(AmountParseError::UnsupportedDecimals(decimals));
+        }
+        Ok(DecimalValue {
+            value: self.value,
+            decimals,
+        })
+    }
+
+    /// Parse a value in whole units of a token with `decimals` decimals, e.g.
+    /// "1.5" with 12 decimals is 1_500_000_000_000 picoMOB.
+    ///
+    /// This accepts only digits with at most one decimal point, and at most
+    /// `decimals` digits after it.
+    pub fn parse_with_decimals(
+        src: &str,
+        decimals: u8,
+        token_id: TokenId,
+    ) -> Result<Self, AmountParseError> {
+        if decimals > Self::MAX_DECIMALS {
+            return Err(AmountParseError::UnsupportedDecimals(decimals));
+        }
+        let (whole, fraction) = src.split_once('.').unwrap_or((src, ""));
+        if whole.is_empty() && fraction.is_empty() {
+            return Err(AmountParseError::Empty);
+        }
+        if fraction.len() > decimals as usize {
+            return Err(AmountParseError::TooManyDecimals(decimals));
+        }
+
+        let mut value = 0u64;
+        let digits = whole
+            .bytes()
+            .chain(fraction.bytes())
+            .chain(core::iter::repeat(b'0').take(decimals as usize - fraction.len()));
+        for digit in digits {
+            if !digit.is_ascii_digit() {
+                return Err(AmountParseError::InvalidCharacter);
+            }
+            value = value
+                .checked_mul(10)
+                .and_then(|value| value.checked_add((digit - b'0') as u64))
+                .ok_or(AmountParseError::Overflow)?;
+        }
+        Ok(Self::new(value, token_id))
+    }
+}
+
+/// The value of an amount in whole units of its token, see
+/// `Amount::display_with_decimals`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DecimalValue {
+    value: u64,
+    decimals: u8,
+}
+
+impl fmt::Display for DecimalValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let scale = 10u64.pow(self.decimals as u32);
+        write!(f, "{}", self.value / scale)?;
+
+        let mut fraction = self.value % scale;
+        if fraction != 0 {
+            let mut width = self.decimals as usize;
+            while fraction % 10 == 0 {
+                fraction /= 10;
+                width -= 1;
+            }
+            write!(f, ".{:0width$}", fraction, width = width)?;
+        }
+        Ok(())
+    }
+}
+
+/// An error that can occur when formatting or parsing an amount in whole units
+/// of its token
+#[derive(Clone, Display, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum AmountParseError {
+    /// Tokens with {0} decimals are not supported
+    UnsupportedDecimals(u8),
+    /// No digits
+    Empty,
+    /// Only digits and a decimal point are allowed
+    InvalidCharacter,
+    /// More than {0} digits after the decimal point
+    TooManyDecimals(u8),
+    /// The value does not fit in a u64
+    Overflow,
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_display_with_decimals() {
+        let display = |value, decimals| {
+            Amount::new(value, TokenId::MOB)
+                .display_with_decimals(decimals)
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(display(1_500_000_000_000, 12), "1.5");
+        assert_eq!(display(1_000_000_000_000, 12), "1");
+        assert_eq!(display(400_000_000, 12), "0.0004");
+        assert_eq!(display(1, 12), "0.000000000001");
+        assert_eq!(display(0, 12), "0");
+        assert_eq!(display(123, 0), "123");
+        assert_eq!(display(u64::MAX, 19), "1.8446744073709551615");
+
+        assert_eq!(
+            Amount::new(1, TokenId::MOB).display_with_decimals(20),
+            Err(AmountParseError::UnsupportedDecimals(20))
+        );
+    }
+
+    #[test]
+    fn test_parse_with_decimals() {
+        let parse = |src, decimals| Amount::parse_with_decimals(src, decimals, TokenId::MOB);
+        assert_eq!(parse("1.5", 12).unwrap().value, 1_500_000_000_000);
+        assert_eq!(parse("1", 12).unwrap().value, 1_000_000_000_000);
+        assert_eq!(parse(".0004", 12).unwrap().value, 400_000_000);
+        assert_eq!(parse("0.000000000001", 12).unwrap().value, 1);
+        assert_eq!(parse("123", 0).unwrap().value, 123);
+        assert_eq!(parse("1.8446744073709551615", 19).unwrap().value, u64::MAX);
+
+        assert_eq!(parse("", 12), Err(AmountParseError::Empty));
+        assert_eq!(parse(".", 12), Err(AmountParseError::Empty));
+        assert_eq!(parse("1.5", 0), Err(AmountParseError::TooManyDecimals(0)));
+        assert_eq!(
+            parse("0.0000000000001", 12),
+            Err(AmountParseError::TooManyDecimals(12))
+        );
+        assert_eq!(parse("-1", 12), Err(AmountParseError::InvalidCharacter));
+        assert_eq!(parse("1.2.3", 12), Err(AmountParseError::InvalidCharacter));
+        assert_eq!(parse(" 1", 12), Err(AmountParseError::InvalidCharacter));
+        assert_eq!(
+            parse("18446744073709552", 3),
+            Err(AmountParseError::Overflow)
+        );
+        assert_eq!(
+            parse("1", 20),
+            Err(AmountParseError::UnsupportedDecimals(20))
+        );
+
+        // Formatting and parsing round trip
+        for value in [0, 1, 10, 1_500_000_000_000, u64::MAX] {
+            let amount = Amount::new(value, TokenId::from(3));
+            let formatted = amount.display_with_decimals(12).unwrap().to_string();
+            assert_eq!(
+                Amount::parse_with_decimals(&formatted, 12, amount.token_id),
+                Ok(amount)
+            );
+        }
+    }
 }
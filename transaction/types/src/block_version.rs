@@ -57,7 +57,7 @@ impl FromStr for BlockVersion {
 impl BlockVersion {
     /// The maximum value of block_version that this build of
     /// mc-transaction-core has support for
-    pub const MAX: Self = Self(3);
+    pub const MAX: Self = Self(4);
 
     /// Refers to the block version number at network launch.
     pub const ZERO: Self = Self(0);
@@ -71,6 +71,9 @@ impl BlockVersion {
     /// Constant for block version three
     pub const THREE: Self = Self(3);
 
+    /// Constant for block version four
+    pub const FOUR: Self = Self(4);
+
     /// Iterator over block versions from one up to max, inclusive. For use in
     /// tests.
     pub fn iterator() -> BlockVersionIterator {
@@ -108,6 +111,13 @@ impl BlockVersion {
         self.0 >= 2
     }
 
+    /// The MLSAGs sign a digest of the extended message digest and of the tx
+    /// summary digest from v4 onward, so that a signer which checked the tx
+    /// summary knows what it signs.
+    pub fn mlsags_sign_extended_message_and_tx_summary_digest(&self) -> bool {
+        self.0 >= 4
+    }
+
     /// Mixed transactions are introduced in v3
     /// [MCIP #31](https://github.com/mobilecoinfoundation/mcips/pull/31)
     pub fn mixed_transactions_are_supported(&self) -> bool {
@@ -120,6 +130,12 @@ impl BlockVersion {
         self.0 >= 3
     }
 
+    /// TxOuts may have additional fog hints, for other fog ingress keys, from
+    /// v4 onward.
+    pub fn additional_fog_hints_are_supported(&self) -> bool {
+        self.0 >= 4
+    }
+
     /// `BlockData.metadata` is required starting from v3.
     /// [MCIP #43](https://github.com/mobilecoinfoundation/mcips/pull/43)
     pub fn require_block_metadata(&self) -> bool {
@@ -12,7 +12,7 @@ mod block_version;
 mod token;
 
 pub use crate::{
-    amount::Amount,
+    amount::{Amount, AmountParseError, DecimalValue},
     block_version::{BlockVersion, BlockVersionError, BlockVersionIterator},
     token::TokenId,
 };
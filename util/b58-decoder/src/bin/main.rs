@@ -53,6 +53,14 @@ fn main() {
                     "BIP39 entropy: {}",
                     hex::encode(printable_wrapper.get_transfer_payload().get_bip39_entropy())
                 );
+                println!(
+                    "Value: {}",
+                    printable_wrapper.get_transfer_payload().get_value()
+                );
+                println!(
+                    "Token id: {}",
+                    printable_wrapper.get_transfer_payload().get_token_id()
+                );
             }
         }
 
@@ -4,10 +4,7 @@
 //! them using URIs.
 
 use crate::ServerCertReloader;
-use grpcio::{
-    CertificateRequestType, Channel, ChannelBuilder, ChannelCredentialsBuilder, Environment,
-    ServerBuilder,
-};
+use grpcio::{Channel, ChannelBuilder, ChannelCredentialsBuilder, Environment, ServerBuilder};
 use mc_common::logger::{log, Logger};
 use mc_util_uri::ConnectionUri;
 use std::{sync::Arc, time::Duration};
@@ -35,11 +32,21 @@ impl ConnectionUriGrpcioChannel for ChannelBuilder {
                 self = self.override_ssl_target(host_override);
             }
 
-            let creds = match uri.ca_bundle().expect("failed getting ca bundle") {
-                Some(cert) => ChannelCredentialsBuilder::new().root_cert(cert).build(),
-                None => ChannelCredentialsBuilder::new().build(),
+            let mut creds_builder = match uri.ca_bundle().expect("failed getting ca bundle") {
+                Some(cert) => ChannelCredentialsBuilder::new().root_cert(cert),
+                None => ChannelCredentialsBuilder::new(),
             };
 
+            // Present a client certificate to servers that require one.
+            if let (Some(chain_path), Some(key_path)) =
+                (uri.tls_client_chain_path(), uri.tls_client_key_path())
+            {
+                let chain = std::fs::read(&chain_path).expect("failed reading client TLS chain");
+                let key = std::fs::read(&key_path).expect("failed reading client TLS key");
+                creds_builder = creds_builder.cert(chain, key);
+            }
+            let creds = creds_builder.build();
+
             log::debug!(logger, "Creating secure gRPC connection to {}", uri.addr());
 
             self.secure_connect(&uri.addr(), creds)
@@ -86,8 +93,20 @@ impl ConnectionUriGrpcioServer for ServerBuilder {
                 .tls_key_path()
                 .expect("Uri must have tls-key in when using TLS");
 
-            let reloader = ServerCertReloader::new(&tls_chain_path, &tls_key_path, logger.clone())
-                .expect("Failed creating ServerCertReloader");
+            let mut reloader =
+                ServerCertReloader::new(&tls_chain_path, &tls_key_path, logger.clone())
+                    .expect("Failed creating ServerCertReloader");
+
+            // Only accept clients presenting an allowed certificate, if requested.
+            if let Some(client_ca_path) = uri.tls_client_ca_path() {
+                log::info!(
+                    logger,
+                    "Requiring client certificates signed by {}",
+                    client_ca_path
+                );
+                reloader = reloader.require_client_certificate(&client_ca_path);
+            }
+            let request_type = reloader.certificate_request_type();
 
             log::debug!(
                 logger,
@@ -96,12 +115,7 @@ impl ConnectionUriGrpcioServer for ServerBuilder {
                 uri.port(),
             );
 
-            self.bind_with_fetcher(
-                uri.host(),
-                uri.port(),
-                Box::new(reloader),
-                CertificateRequestType::DontRequestClientCertificate,
-            )
+            self.bind_with_fetcher(uri.host(), uri.port(), Box::new(reloader), request_type)
         } else {
             log::warn!(
                 logger,
@@ -19,8 +19,8 @@ use std::{
 /// The `grpcio::ServerCredentialsFetcher` demands a root certificate for
 /// verifying client identities, even when we explicitly specify
 /// DontRequestClientCertificate. As such, we need to provide it with some
-/// certificate. We don't use client certificate authentication, so we use a
-/// hardcoded certificate that was generated by this command:
+/// certificate. When client certificate authentication is not enabled, we use
+/// a hardcoded certificate that was generated by this command:
 /// openssl req \
 ///     -x509 \
 ///     -newkey rsa:4096 \
@@ -82,6 +82,10 @@ pub struct ServerCertReloader {
     /// Private key file to watch.
     key_file: PathBuf,
 
+    /// CA bundle file used to verify client certificates, if clients are
+    /// required to present one.
+    client_ca_file: Option<PathBuf>,
+
     /// Signal that we need to re-load the certificate/key files.
     load_needed: Arc<AtomicBool>,
 
@@ -103,10 +107,29 @@ impl ServerCertReloader {
         Ok(Self {
             cert_file: cert_file.as_ref().to_path_buf(),
             key_file: key_file.as_ref().to_path_buf(),
+            client_ca_file: None,
             load_needed,
             logger,
         })
     }
+
+    /// Require clients to present a certificate signed by one of the
+    /// certificates in `client_ca_file`. The file is re-loaded along with the
+    /// server certificate/key.
+    pub fn require_client_certificate(mut self, client_ca_file: &impl AsRef<Path>) -> Self {
+        self.client_ca_file = Some(client_ca_file.as_ref().to_path_buf());
+        self
+    }
+
+    /// The client certificate request type matching this reloader's
+    /// configuration.
+    pub fn certificate_request_type(&self) -> CertificateRequestType {
+        if self.client_ca_file.is_some() {
+            CertificateRequestType::RequestAndRequireClientCertificateAndVerify
+        } else {
+            CertificateRequestType::DontRequestClientCertificate
+        }
+    }
 }
 
 impl ServerCredentialsFetcher for ServerCertReloader {
@@ -120,14 +143,19 @@ impl ServerCredentialsFetcher for ServerCertReloader {
         let crt = fs::read_to_string(&self.cert_file)?;
         let key = fs::read_to_string(&self.key_file)?;
 
+        // This sets the client root certificate to verify client's identity.
+        // When client certificates are not required, grpcio still requires something
+        // to be set there when using the ServerCredentialsFetcher mechanism. As a
+        // workaround we are using a hardcoded certificate here.
+        let client_root_cert = match &self.client_ca_file {
+            Some(client_ca_file) => fs::read_to_string(client_ca_file)?,
+            None => HARDCODED_CLIENT_ROOT_CERT.to_owned(),
+        };
+
         let new_cred = ServerCredentialsBuilder::new()
-            // This sets the client root certificate to verify client's identity.
-            // We are not using this feature, however grpcio still requires something to be set
-            // there when using the ServerCredentialsFetcher mechanism. As a workaround we are
-            // using the server's certificate chain here.
             .root_cert(
-                HARDCODED_CLIENT_ROOT_CERT.as_bytes().to_vec(),
-                CertificateRequestType::DontRequestClientCertificate,
+                client_root_cert.into_bytes(),
+                self.certificate_request_type(),
             )
             .add_cert(crt.into(), key.into());
 
@@ -175,6 +203,24 @@ mod tests {
         (server, port)
     }
 
+    fn create_test_client_with_cert(
+        cert: &str,
+        ssl_target: &str,
+        port: u16,
+        client_cert: &str,
+        client_key: &str,
+    ) -> HealthClient {
+        let env = Arc::new(EnvBuilder::new().build());
+        let cred = ChannelCredentialsBuilder::new()
+            .root_cert(cert.into())
+            .cert(client_cert.into(), client_key.into())
+            .build();
+        let ch = ChannelBuilder::new(env)
+            .override_ssl_target(ssl_target)
+            .secure_connect(&format!("localhost:{}", port), cred);
+        HealthClient::new(ch)
+    }
+
     fn create_test_client(cert: &str, ssl_target: &str, port: u16) -> HealthClient {
         let env = Arc::new(EnvBuilder::new().build());
         let cred = ChannelCredentialsBuilder::new()
@@ -430,4 +476,68 @@ mod tests {
         let reply = client1.ping(&req).expect("rpc");
         assert_eq!(reply.get_data(), vec![1, 2, 3]);
     }
+
+    #[test_with_logger]
+    fn test_require_client_certificate(logger: Logger) {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let cert_file = temp_dir.path().join("server.crt");
+        let key_file = temp_dir.path().join("server.key");
+        let client_ca_file = temp_dir.path().join("client-ca.crt");
+
+        // Load test certs and keys. server2's certificate is used as the only
+        // allowed client certificate.
+        let (server1_cert, server1_key) = ok_self_signed_1();
+        let (server2_cert, server2_key) = ok_self_signed_2();
+
+        std::fs::write(&cert_file, &server1_cert).unwrap();
+        std::fs::write(&key_file, &server1_key).unwrap();
+        std::fs::write(&client_ca_file, &server2_cert).unwrap();
+
+        // Start the GRPC server.
+        let reloader = ServerCertReloader::new(&cert_file, &key_file, logger.clone())
+            .unwrap()
+            .require_client_certificate(&client_ca_file);
+        let request_type = reloader.certificate_request_type();
+
+        let env = Arc::new(EnvBuilder::new().build());
+        let service = HealthService::new(None, logger.clone()).into_service();
+        let mut server = ServerBuilder::new(env)
+            .register_service(service)
+            .bind_with_fetcher("localhost", 0, Box::new(reloader), request_type)
+            .build()
+            .unwrap();
+        server.start();
+        let port = server.bind_addrs().next().unwrap().1;
+
+        // A client presenting an allowed certificate can connect.
+        let client1 = create_test_client_with_cert(
+            &server1_cert,
+            "www.server1.com",
+            port,
+            &server2_cert,
+            &server2_key,
+        );
+        let mut req = PingRequest::default();
+        req.set_data(vec![1, 2, 3]);
+        let reply = client1.ping(&req).expect("rpc");
+        assert_eq!(reply.get_data(), vec![1, 2, 3]);
+
+        // A client without a certificate cannot.
+        let client2 = create_test_client(&server1_cert, "www.server1.com", port);
+        let mut req = PingRequest::default();
+        req.set_data(vec![1, 2, 3]);
+        assert!(client2.ping(&req).is_err());
+
+        // Neither can a client presenting a certificate that is not allowed.
+        let client3 = create_test_client_with_cert(
+            &server1_cert,
+            "www.server1.com",
+            port,
+            &server1_cert,
+            &server1_key,
+        );
+        let mut req = PingRequest::default();
+        req.set_data(vec![1, 2, 3]);
+        assert!(client3.ping(&req).is_err());
+    }
 }
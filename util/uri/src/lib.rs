@@ -297,6 +297,22 @@ mod consensus_client_uri_tests {
             Some("lol.com".into())
         );
     }
+
+    #[test]
+    fn test_tls_client_certificates() {
+        let uri = ClientUri::from_str("mc://node.com/?tls-chain=a.pem&tls-key=a.key").unwrap();
+        assert_eq!(uri.tls_client_ca_path(), None);
+        assert_eq!(uri.tls_client_chain_path(), None);
+        assert_eq!(uri.tls_client_key_path(), None);
+
+        let uri = ClientUri::from_str(
+            "mc://node.com/?tls-client-ca=ca.pem&tls-client-chain=b.pem&tls-client-key=b.key",
+        )
+        .unwrap();
+        assert_eq!(uri.tls_client_ca_path(), Some("ca.pem".into()));
+        assert_eq!(uri.tls_client_chain_path(), Some("b.pem".into()));
+        assert_eq!(uri.tls_client_key_path(), Some("b.key".into()));
+    }
 }
 #[cfg(test)]
 mod consensus_peer_uri_tests {
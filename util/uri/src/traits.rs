@@ -190,6 +190,25 @@ pub trait ConnectionUri:
         std::fs::read(path.clone())
             .map_err(|e| format!("Failed reading TLS key from {}: {:?}", path, e))
     }
+
+    /// Optional path to the CA bundle used to verify client certificates. When
+    /// set on a listening URI, only clients presenting a certificate signed by
+    /// one of these certificates are accepted.
+    fn tls_client_ca_path(&self) -> Option<String> {
+        self.get_param("tls-client-ca")
+    }
+
+    /// Optional TLS chain file path a client presents to servers requiring
+    /// client certificates.
+    fn tls_client_chain_path(&self) -> Option<String> {
+        self.get_param("tls-client-chain")
+    }
+
+    /// Optional TLS key file path a client presents to servers requiring
+    /// client certificates.
+    fn tls_client_key_path(&self) -> Option<String> {
+        self.get_param("tls-client-key")
+    }
 }
 
 /// A trait with associated constants, representing a URI scheme and default
@@ -0,0 +1,195 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Webhook alerts for block signature anomalies.
+//!
+//! Anomalies are always logged. When webhook urls are configured, each one is
+//! also POSTed to every webhook as a JSON object whose `type` field identifies
+//! the kind of alert.
+
+use crate::consistency::BlockIdDivergence;
+use mc_blockchain_types::BlockIndex;
+use mc_common::logger::{log, Logger};
+use mc_crypto_keys::Ed25519Public;
+use mc_util_repr_bytes::ReprBytes;
+use reqwest::blocking::{Client, Response};
+use serde::Serialize;
+use std::{collections::BTreeMap, fmt::Display, sync::Arc, thread, time::Duration};
+use url::Url;
+
+/// How long to wait for a webhook to accept an alert.
+pub const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An anomaly worth alerting about.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Alert {
+    /// Tx sources reported different block IDs for the same block index.
+    BlockIdDivergence {
+        /// The block index the sources disagree on.
+        block_index: BlockIndex,
+
+        /// Tx source url -> hex-encoded block ID.
+        block_ids: BTreeMap<String, String>,
+    },
+
+    /// A block signature does not match the block it was served with.
+    InvalidSignature {
+        /// The block index.
+        block_index: BlockIndex,
+
+        /// The tx source url the block was fetched from.
+        tx_src_url: String,
+
+        /// The hex-encoded signer public key.
+        signer: String,
+
+        /// The signature verification error.
+        error: String,
+    },
+
+    /// Blocks were signed by a key that the verification report of the node
+    /// does not vouch for.
+    UnknownSigner {
+        /// The tx source url the blocks were fetched from.
+        tx_src_url: String,
+
+        /// The hex-encoded signer public key.
+        signer: String,
+
+        /// The hex-encoded signer public key the verification report of the
+        /// node vouches for.
+        attested_signer: String,
+    },
+}
+
+impl Alert {
+    /// An alert for a signature that does not match its block.
+    pub fn invalid_signature(
+        tx_src_url: &Url,
+        block_index: BlockIndex,
+        signer: &Ed25519Public,
+        error: impl Display,
+    ) -> Self {
+        Self::InvalidSignature {
+            block_index,
+            tx_src_url: tx_src_url.to_string(),
+            signer: hex::encode(signer.to_bytes()),
+            error: error.to_string(),
+        }
+    }
+
+    /// An alert for a signer the verification report of the node does not
+    /// vouch for.
+    pub fn unknown_signer(
+        tx_src_url: &Url,
+        signer: &Ed25519Public,
+        attested_signer: &Ed25519Public,
+    ) -> Self {
+        Self::UnknownSigner {
+            tx_src_url: tx_src_url.to_string(),
+            signer: hex::encode(signer.to_bytes()),
+            attested_signer: hex::encode(attested_signer.to_bytes()),
+        }
+    }
+}
+
+impl From<&BlockIdDivergence> for Alert {
+    fn from(divergence: &BlockIdDivergence) -> Self {
+        Self::BlockIdDivergence {
+            block_index: divergence.block_index,
+            block_ids: divergence
+                .block_ids
+                .iter()
+                .map(|(url, block_id)| (url.to_string(), hex::encode(block_id)))
+                .collect(),
+        }
+    }
+}
+
+/// Delivers alerts to the configured webhooks. Delivery happens on a
+/// background thread so that a slow webhook never stalls syncing; failures
+/// are logged and not retried.
+#[derive(Clone)]
+pub struct AlertNotifier {
+    webhook_urls: Arc<Vec<Url>>,
+    client: Client,
+    logger: Logger,
+}
+
+impl AlertNotifier {
+    /// Create a new notifier POSTing to `webhook_urls`.
+    pub fn new(webhook_urls: Vec<Url>, logger: Logger) -> Result<Self, reqwest::Error> {
+        let client = Client::builder().timeout(WEBHOOK_TIMEOUT).build()?;
+        Ok(Self {
+            webhook_urls: Arc::new(webhook_urls),
+            client,
+            logger,
+        })
+    }
+
+    /// Send an alert to every webhook.
+    pub fn notify(&self, alert: Alert) {
+        let notifier = self.clone();
+        if let Err(err) = thread::Builder::new()
+            .name("WatcherAlert".into())
+            .spawn(move || notifier.deliver(&alert))
+        {
+            log::error!(
+                self.logger,
+                "Failed spawning alert delivery thread: {}",
+                err
+            );
+        }
+    }
+
+    fn deliver(&self, alert: &Alert) {
+        for webhook_url in self.webhook_urls.iter() {
+            match self
+                .client
+                .post(webhook_url.clone())
+                .json(alert)
+                .send()
+                .and_then(Response::error_for_status)
+            {
+                Ok(_) => log::debug!(self.logger, "Delivered alert to {}", webhook_url),
+                Err(err) => log::error!(
+                    self.logger,
+                    "Failed delivering alert {:?} to {}: {}",
+                    alert,
+                    webhook_url,
+                    err
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_blockchain_types::BlockID;
+    use mc_common::HashMap;
+    use serde_json::json;
+
+    #[test]
+    fn block_id_divergence_json() {
+        let url1 = Url::parse("http://www.my_url1.com").unwrap();
+        let url2 = Url::parse("http://www.my_url2.com").unwrap();
+        let divergence = BlockIdDivergence {
+            block_index: 5,
+            block_ids: HashMap::from_iter([(url1, BlockID([1; 32])), (url2, BlockID([2; 32]))]),
+        };
+
+        assert_eq!(
+            serde_json::to_value(Alert::from(&divergence)).unwrap(),
+            json!({
+                "type": "block_id_divergence",
+                "block_index": 5,
+                "block_ids": {
+                    "http://www.my_url1.com/": hex::encode([1; 32]),
+                    "http://www.my_url2.com/": hex::encode([2; 32]),
+                },
+            })
+        );
+    }
+}
@@ -0,0 +1,278 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Authentication for private block archives.
+//!
+//! Credentials are never part of the tx source url. They are configured per
+//! source in the sources file (see [TxSourceAuthConfig]), and secrets are read
+//! from the environment.
+
+use crate::config::TxSourceAuthConfig;
+use mc_ledger_sync::RequestAuthenticator;
+use reqwest::{
+    blocking::{Client, RequestBuilder},
+    header::{HeaderMap, HeaderName, HeaderValue},
+};
+use rusoto_core::{
+    credential::{AutoRefreshingProvider, ChainProvider, ProvideAwsCredentials},
+    signature::SignedRequest,
+    Region,
+};
+use std::{env, str::FromStr, sync::Arc, time::Duration};
+use tokio::runtime::{Builder as RuntimeBuilder, Runtime};
+use url::Url;
+
+/// Create the authenticator for a tx source auth config.
+pub fn create_authenticator(
+    config: &TxSourceAuthConfig,
+) -> Result<Arc<dyn RequestAuthenticator>, String> {
+    Ok(match config {
+        TxSourceAuthConfig::Basic {
+            username,
+            password_env,
+        } => {
+            let password = env::var(password_env)
+                .map_err(|err| format!("failed reading {}: {}", password_env, err))?;
+            Arc::new(BasicAuthenticator::new(username.clone(), password))
+        }
+
+        TxSourceAuthConfig::AwsSigV4 { region, service } => {
+            Arc::new(AwsAuthenticator::with_default_credentials(
+                parse_region(region)?,
+                service.clone(),
+                AwsSigningMode::Headers,
+            )?)
+        }
+
+        TxSourceAuthConfig::S3Presigned {
+            region,
+            expires_secs,
+        } => Arc::new(AwsAuthenticator::with_default_credentials(
+            parse_region(region)?,
+            "s3".to_owned(),
+            AwsSigningMode::Presigned(Duration::from_secs(*expires_secs)),
+        )?),
+    })
+}
+
+fn parse_region(region: &str) -> Result<Region, String> {
+    Region::from_str(region).map_err(|err| format!("invalid AWS region {}: {}", region, err))
+}
+
+/// HTTP basic authentication.
+pub struct BasicAuthenticator {
+    username: String,
+    password: String,
+}
+
+impl BasicAuthenticator {
+    /// Create a new basic authenticator.
+    pub fn new(username: String, password: String) -> Self {
+        Self { username, password }
+    }
+}
+
+impl RequestAuthenticator for BasicAuthenticator {
+    fn authenticate(&self, client: &Client, url: &Url) -> Result<RequestBuilder, String> {
+        Ok(client
+            .get(url.as_str())
+            .basic_auth(&self.username, Some(&self.password)))
+    }
+}
+
+/// How AWS requests get signed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AwsSigningMode {
+    /// Sign each request with SigV4 `Authorization` headers.
+    Headers,
+
+    /// Request a presigned URL, valid for the given duration, instead of the
+    /// original one. The signature is carried in the query string.
+    Presigned(Duration),
+}
+
+/// AWS Signature Version 4 authentication.
+pub struct AwsAuthenticator<P: ProvideAwsCredentials> {
+    region: Region,
+    service: String,
+    mode: AwsSigningMode,
+    credentials_provider: P,
+
+    /// Credential providers are async, and requests are made from the
+    /// synchronous fetch threads.
+    runtime: Runtime,
+}
+
+impl AwsAuthenticator<AutoRefreshingProvider<ChainProvider>> {
+    /// Create a new AWS authenticator that gets its credentials from the
+    /// environment, the AWS profile, the ECS container or the EC2 instance
+    /// metadata, in that order. Credentials are cached until they are about to
+    /// expire.
+    pub fn with_default_credentials(
+        region: Region,
+        service: String,
+        mode: AwsSigningMode,
+    ) -> Result<Self, String> {
+        let credentials_provider = AutoRefreshingProvider::new(ChainProvider::new())
+            .map_err(|err| format!("failed creating AWS credentials provider: {}", err))?;
+        Self::new(region, service, mode, credentials_provider)
+    }
+}
+
+impl<P: ProvideAwsCredentials> AwsAuthenticator<P> {
+    /// Create a new AWS authenticator.
+    pub fn new(
+        region: Region,
+        service: String,
+        mode: AwsSigningMode,
+        credentials_provider: P,
+    ) -> Result<Self, String> {
+        let runtime = RuntimeBuilder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|err| format!("failed creating runtime: {}", err))?;
+        Ok(Self {
+            region,
+            service,
+            mode,
+            credentials_provider,
+            runtime,
+        })
+    }
+
+    fn signed_request(&self, url: &Url) -> Result<SignedRequest, String> {
+        let host = url
+            .host_str()
+            .ok_or_else(|| format!("{} has no host", url))?;
+        let hostname = match url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_owned(),
+        };
+
+        let mut request = SignedRequest::new("GET", &self.service, &self.region, url.path());
+        request.scheme = Some(url.scheme().to_owned());
+        request.set_hostname(Some(hostname));
+        for (key, value) in url.query_pairs() {
+            request.add_param(key, value);
+        }
+        Ok(request)
+    }
+}
+
+impl<P: ProvideAwsCredentials + Send + Sync> RequestAuthenticator for AwsAuthenticator<P> {
+    fn authenticate(&self, client: &Client, url: &Url) -> Result<RequestBuilder, String> {
+        let credentials = self
+            .runtime
+            .block_on(self.credentials_provider.credentials())
+            .map_err(|err| format!("failed getting AWS credentials: {}", err))?;
+        let mut request = self.signed_request(url)?;
+
+        match self.mode {
+            AwsSigningMode::Headers => {
+                request.sign(&credentials);
+
+                let mut headers = HeaderMap::new();
+                for (name, values) in request.headers.iter() {
+                    // reqwest sets the host header itself.
+                    if name.eq_ignore_ascii_case("host") {
+                        continue;
+                    }
+                    let name = HeaderName::from_bytes(name.as_bytes())
+                        .map_err(|err| format!("invalid header name {}: {}", name, err))?;
+                    for value in values.iter() {
+                        let value = HeaderValue::from_bytes(value)
+                            .map_err(|err| format!("invalid value for {}: {}", name, err))?;
+                        headers.append(name.clone(), value);
+                    }
+                }
+                Ok(client.get(url.as_str()).headers(headers))
+            }
+
+            AwsSigningMode::Presigned(expires_in) => {
+                let presigned_url =
+                    request.generate_presigned_url(&credentials, &expires_in, false);
+                Ok(client.get(&presigned_url))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::AUTHORIZATION;
+    use rusoto_core::credential::StaticProvider;
+
+    fn test_url() -> Url {
+        Url::parse("https://s3-us-west-1.amazonaws.com/mobilecoin.chain/node1/0000000000000001.pb")
+            .unwrap()
+    }
+
+    fn aws_authenticator(mode: AwsSigningMode) -> AwsAuthenticator<StaticProvider> {
+        AwsAuthenticator::new(
+            Region::UsWest1,
+            "s3".to_owned(),
+            mode,
+            StaticProvider::new_minimal("AKIDEXAMPLE".to_owned(), "secret".to_owned()),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn basic_auth_sets_authorization_header() {
+        let authenticator = BasicAuthenticator::new("user".to_owned(), "pass".to_owned());
+        let request = authenticator
+            .authenticate(&Client::new(), &test_url())
+            .unwrap()
+            .build()
+            .unwrap();
+
+        assert_eq!(request.url(), &test_url());
+        // base64("user:pass")
+        assert_eq!(
+            request.headers().get(AUTHORIZATION).unwrap(),
+            "Basic dXNlcjpwYXNz"
+        );
+    }
+
+    #[test]
+    fn sigv4_sets_authorization_header() {
+        let authenticator = aws_authenticator(AwsSigningMode::Headers);
+        let request = authenticator
+            .authenticate(&Client::new(), &test_url())
+            .unwrap()
+            .build()
+            .unwrap();
+
+        assert_eq!(request.url(), &test_url());
+        let authorization = request
+            .headers()
+            .get(AUTHORIZATION)
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert!(authorization.starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/"));
+        assert!(authorization.contains("/us-west-1/s3/aws4_request"));
+        assert!(request.headers().contains_key("x-amz-date"));
+        assert!(!request.headers().contains_key("host"));
+    }
+
+    #[test]
+    fn s3_presigned_requests_a_signed_url() {
+        let authenticator = aws_authenticator(AwsSigningMode::Presigned(Duration::from_secs(60)));
+        let request = authenticator
+            .authenticate(&Client::new(), &test_url())
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let url = request.url();
+        assert_eq!(url.host_str(), test_url().host_str());
+        assert_eq!(url.path(), test_url().path());
+        let query = url.query_pairs().collect::<Vec<_>>();
+        assert!(query
+            .iter()
+            .any(|(key, value)| key == "X-Amz-Expires" && value == "60"));
+        assert!(query.iter().any(|(key, _value)| key == "X-Amz-Signature"));
+        assert!(!request.headers().contains_key(AUTHORIZATION));
+    }
+}
@@ -0,0 +1,364 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Worker thread for re-validating stored attestation evidence.
+//!
+//! A verification report that was acceptable when it was collected might not
+//! be acceptable anymore, e.g. after an advisory affecting an old MRENCLAVE is
+//! published and the enclave gets dropped from the policy. This thread
+//! periodically checks every verification report in the [WatcherDB], both the
+//! ones collected from nodes and the ones included in block metadata, against
+//! the current [AttestationPolicy] and records the outcome.
+
+use crate::{error::AttestationPolicyError, watcher_db::WatcherDB};
+use hex::FromHex;
+use mc_attest_core::{MrEnclave, MrSigner, ProductId, SecurityVersion};
+use mc_attest_verifier::{MrEnclaveVerifier, MrSignerVerifier, Verifier, DEBUG_ENCLAVE};
+use mc_common::logger::{log, Logger};
+use mc_util_serial::Message;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// An enclave that is trusted by its MRENCLAVE.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MrEnclavePolicy {
+    /// The MRENCLAVE, as 64 hexadecimal characters.
+    pub mr_enclave: String,
+
+    /// Config advisories the enclave is not affected by.
+    #[serde(default)]
+    pub config_advisories: Vec<String>,
+
+    /// Hardening advisories the enclave mitigates.
+    #[serde(default)]
+    pub hardening_advisories: Vec<String>,
+}
+
+/// Enclaves that are trusted by their MRSIGNER.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MrSignerPolicy {
+    /// The MRSIGNER, as 64 hexadecimal characters.
+    pub mr_signer: String,
+
+    /// The enclave product ID.
+    pub product_id: ProductId,
+
+    /// The minimum enclave security version.
+    pub minimum_svn: SecurityVersion,
+
+    /// Config advisories the enclaves are not affected by.
+    #[serde(default)]
+    pub config_advisories: Vec<String>,
+
+    /// Hardening advisories the enclaves mitigate.
+    #[serde(default)]
+    pub hardening_advisories: Vec<String>,
+}
+
+/// The enclaves whose verification reports are acceptable. A report passes if
+/// it matches any of the listed enclaves.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AttestationPolicy {
+    /// Enclaves trusted by MRENCLAVE.
+    #[serde(default, rename = "mr_enclave")]
+    pub mr_enclaves: Vec<MrEnclavePolicy>,
+
+    /// Enclaves trusted by MRSIGNER.
+    #[serde(default, rename = "mr_signer")]
+    pub mr_signers: Vec<MrSignerPolicy>,
+}
+
+impl AttestationPolicy {
+    /// Load the policy from a TOML file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AttestationPolicyError> {
+        let data = fs::read_to_string(path)?;
+        Ok(toml::from_str(&data)?)
+    }
+
+    /// Construct a verifier implementing the policy.
+    pub fn verifier(&self) -> Result<Verifier, AttestationPolicyError> {
+        if self.mr_enclaves.is_empty() && self.mr_signers.is_empty() {
+            return Err(AttestationPolicyError::Empty);
+        }
+
+        let mut verifier = Verifier::default();
+        verifier.debug(DEBUG_ENCLAVE);
+
+        for policy in self.mr_enclaves.iter() {
+            let mr_enclave = <[u8; 32]>::from_hex(&policy.mr_enclave).map_err(|_| {
+                AttestationPolicyError::InvalidMeasurement(policy.mr_enclave.clone())
+            })?;
+            let mut mr_enclave_verifier = MrEnclaveVerifier::new(MrEnclave::from(mr_enclave));
+            for id in policy.config_advisories.iter() {
+                mr_enclave_verifier.allow_config_advisory(id);
+            }
+            for id in policy.hardening_advisories.iter() {
+                mr_enclave_verifier.allow_hardening_advisory(id);
+            }
+            verifier.mr_enclave(mr_enclave_verifier);
+        }
+
+        for policy in self.mr_signers.iter() {
+            let mr_signer = <[u8; 32]>::from_hex(&policy.mr_signer).map_err(|_| {
+                AttestationPolicyError::InvalidMeasurement(policy.mr_signer.clone())
+            })?;
+            let mut mr_signer_verifier = MrSignerVerifier::new(
+                MrSigner::from(mr_signer),
+                policy.product_id,
+                policy.minimum_svn,
+            );
+            for id in policy.config_advisories.iter() {
+                mr_signer_verifier.allow_config_advisory(id);
+            }
+            for id in policy.hardening_advisories.iter() {
+                mr_signer_verifier.allow_hardening_advisory(id);
+            }
+            verifier.mr_signer(mr_signer_verifier);
+        }
+
+        Ok(verifier)
+    }
+}
+
+/// The outcome of re-validating a stored verification report against the
+/// attestation policy.
+#[derive(Clone, Eq, Message, PartialEq)]
+pub struct AttestationRevalidation {
+    /// When the report was checked, in seconds since the UNIX epoch.
+    #[prost(uint64, tag = 1)]
+    pub checked_at: u64,
+
+    /// Why the report no longer passes, unset if it does.
+    #[prost(string, optional, tag = 2)]
+    pub error: Option<String>,
+}
+
+impl AttestationRevalidation {
+    /// Whether the report passed.
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Re-validate every verification report stored in the database with the
+/// given verifier. Returns the number of reports that failed.
+pub fn revalidate_reports(
+    watcher_db: &WatcherDB,
+    verifier: &Verifier,
+    checked_at: u64,
+    logger: &Logger,
+) -> Result<usize, AttestationPolicyError> {
+    let mut num_failed = 0;
+    for (report_hash, report) in watcher_db.get_all_verification_reports()? {
+        let error = verifier.verify(&report).err().map(|err| err.to_string());
+        if let Some(error) = error.as_ref() {
+            log::warn!(
+                logger,
+                "Verification report {} does not pass the attestation policy: {}",
+                hex::encode(report_hash),
+                error
+            );
+            num_failed += 1;
+        }
+        watcher_db.set_attestation_revalidation(
+            &report_hash,
+            &AttestationRevalidation { checked_at, error },
+        )?;
+    }
+    Ok(num_failed)
+}
+
+/// Periodically re-validates the verification reports in the database
+/// against the attestation policy file. The file is re-read every time, so
+/// policy changes take effect without restarting the watcher.
+pub struct AttestationRevalidator {
+    join_handle: Option<thread::JoinHandle<()>>,
+    stop_requested: Arc<AtomicBool>,
+}
+
+impl AttestationRevalidator {
+    /// Create a new attestation revalidator thread.
+    pub fn new(
+        watcher_db: WatcherDB,
+        policy_path: PathBuf,
+        interval: Duration,
+        logger: Logger,
+    ) -> Self {
+        let stop_requested = Arc::new(AtomicBool::new(false));
+        let thread_stop_requested = stop_requested.clone();
+        let join_handle = Some(
+            thread::Builder::new()
+                .name("AttestationRevalidator".into())
+                .spawn(move || {
+                    Self::thread_entrypoint(
+                        watcher_db,
+                        policy_path,
+                        interval,
+                        thread_stop_requested,
+                        logger,
+                    );
+                })
+                .expect("Failed spawning AttestationRevalidator thread"),
+        );
+
+        Self {
+            join_handle,
+            stop_requested,
+        }
+    }
+
+    /// Stop the thread.
+    pub fn stop(&mut self) {
+        self.stop_requested.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.join_handle.take() {
+            thread.join().expect("thread join failed");
+        }
+    }
+
+    fn thread_entrypoint(
+        watcher_db: WatcherDB,
+        policy_path: PathBuf,
+        interval: Duration,
+        stop_requested: Arc<AtomicBool>,
+        logger: Logger,
+    ) {
+        log::debug!(logger, "AttestationRevalidator thread started");
+
+        let mut last_run: Option<Instant> = None;
+        loop {
+            if stop_requested.load(Ordering::SeqCst) {
+                log::debug!(logger, "AttestationRevalidator thread stop requested.");
+                break;
+            }
+
+            if last_run.map_or(true, |last_run| last_run.elapsed() >= interval) {
+                last_run = Some(Instant::now());
+                let checked_at = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |duration| duration.as_secs());
+                let result = AttestationPolicy::load(&policy_path)
+                    .and_then(|policy| policy.verifier())
+                    .and_then(|verifier| {
+                        revalidate_reports(&watcher_db, &verifier, checked_at, &logger)
+                    });
+                match result {
+                    Ok(num_failed) => log::info!(
+                        logger,
+                        "Re-validated verification reports, {} no longer pass",
+                        num_failed
+                    ),
+                    Err(err) => log::error!(
+                        logger,
+                        "Failed re-validating verification reports with {:?}: {}",
+                        policy_path,
+                        err
+                    ),
+                }
+            }
+
+            thread::sleep(Duration::from_secs(1));
+        }
+    }
+}
+
+impl Drop for AttestationRevalidator {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::watcher_db::tests::setup_watcher_db;
+    use mc_blockchain_types::VerificationReport;
+    use mc_common::logger::{test_with_logger, Logger};
+    use mc_crypto_keys::Ed25519Pair;
+    use mc_util_from_random::FromRandom;
+    use mc_util_test_helper::run_with_one_seed;
+    use url::Url;
+
+    #[test]
+    fn policy_toml() {
+        let input_toml: &str = r#"
+            [[mr_enclave]]
+            mr_enclave = "1111111111111111111111111111111111111111111111111111111111111111"
+            hardening_advisories = ["INTEL-SA-00334"]
+
+            [[mr_signer]]
+            mr_signer = "2222222222222222222222222222222222222222222222222222222222222222"
+            product_id = 1
+            minimum_svn = 4
+        "#;
+        let policy: AttestationPolicy = toml::from_str(input_toml).unwrap();
+        assert_eq!(policy.mr_enclaves.len(), 1);
+        assert_eq!(
+            policy.mr_enclaves[0].hardening_advisories,
+            vec!["INTEL-SA-00334".to_owned()]
+        );
+        assert!(policy.mr_enclaves[0].config_advisories.is_empty());
+        assert_eq!(policy.mr_signers[0].minimum_svn, 4);
+        assert!(policy.verifier().is_ok());
+
+        // A policy needs to trust some enclave, and measurements must be valid.
+        assert!(matches!(
+            AttestationPolicy::default().verifier(),
+            Err(AttestationPolicyError::Empty)
+        ));
+        let mut invalid = policy;
+        invalid.mr_enclaves[0].mr_enclave = "11".to_owned();
+        assert!(matches!(
+            invalid.verifier(),
+            Err(AttestationPolicyError::InvalidMeasurement(_))
+        ));
+    }
+
+    #[test_with_logger]
+    fn revalidate_reports_records_failures(logger: Logger) {
+        run_with_one_seed(|mut rng| {
+            let url1 = Url::parse("http://www.my_url1.com").unwrap();
+            let watcher_db = setup_watcher_db(&[url1.clone()], logger.clone());
+
+            let signer = Ed25519Pair::from_random(&mut rng).public_key();
+            let report = VerificationReport {
+                sig: vec![1u8; 32].into(),
+                chain: vec![vec![2; 16], vec![3; 32]],
+                http_body: "test body".to_owned(),
+            };
+            watcher_db
+                .add_verification_report(&url1, &signer, &report, &[])
+                .unwrap();
+            assert_eq!(watcher_db.get_attestation_revalidation(&report), Ok(None));
+
+            // The test report is not signed by IAS, so it cannot pass any policy.
+            let policy = AttestationPolicy {
+                mr_enclaves: vec![MrEnclavePolicy {
+                    mr_enclave: hex::encode([1u8; 32]),
+                    config_advisories: vec![],
+                    hardening_advisories: vec![],
+                }],
+                mr_signers: vec![],
+            };
+            let verifier = policy.verifier().unwrap();
+            assert_eq!(
+                revalidate_reports(&watcher_db, &verifier, 1234, &logger).unwrap(),
+                1
+            );
+
+            let revalidation = watcher_db
+                .get_attestation_revalidation(&report)
+                .unwrap()
+                .unwrap();
+            assert_eq!(revalidation.checked_at, 1234);
+            assert!(!revalidation.passed());
+        });
+    }
+}
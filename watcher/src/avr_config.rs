@@ -0,0 +1,703 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! AVR history, as exported from a [WatcherDB].
+//!
+//! The history is a list of [AvrConfigRecord]s, each mapping a contiguous
+//! range of blocks signed by a given signer on a given tx source to the
+//! verification report that signer was bound to. It is stored as TOML or JSON,
+//! depending on the file extension.
+
+use crate::{
+    error::{AvrConfigError, AvrValidationError},
+    verification_reports_collector::{ConsensusNodeClient, NodeClient},
+    watcher_db::WatcherDB,
+};
+use hex::{FromHex, ToHex};
+use mc_blockchain_types::{BlockIndex, VerificationReport};
+use mc_common::ResponderId;
+use mc_crypto_keys::Ed25519Public;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    ffi::OsStr,
+    fs,
+    path::Path,
+};
+use url::Url;
+
+/// A range of blocks signed by a single signer on a single tx source.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AvrConfigRecord {
+    /// The tx source url the signatures were collected from.
+    pub tx_src_url: String,
+
+    /// The block signer, as 64 hexadecimal characters.
+    pub signer: String,
+
+    /// The first block index signed by this signer.
+    pub first_block_index: BlockIndex,
+
+    /// The last block index signed by this signer.
+    pub last_block_index: BlockIndex,
+
+    /// The verification report the signer was bound to, if the watcher
+    /// managed to collect one.
+    pub avr: Option<VerificationReport>,
+}
+
+/// Restricts which blocks an export processes.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AvrExportFilter {
+    /// Skip blocks before this index.
+    pub start_block: Option<BlockIndex>,
+
+    /// Skip blocks after this index.
+    pub end_block: Option<BlockIndex>,
+
+    /// Only keep blocks whose metadata, as provided by the tx source, names
+    /// this responder ID. Blocks without metadata are skipped.
+    pub responder_id: Option<ResponderId>,
+}
+
+impl AvrExportFilter {
+    fn matches_responder_id(
+        &self,
+        watcher_db: &WatcherDB,
+        tx_src_url: &Url,
+        block_index: BlockIndex,
+    ) -> Result<bool, AvrConfigError> {
+        match self.responder_id.as_ref() {
+            None => Ok(true),
+            Some(responder_id) => Ok(watcher_db
+                .get_block_metadata(block_index)?
+                .get(tx_src_url)
+                .map_or(false, |metadata| {
+                    metadata.contents().responder_id() == responder_id
+                })),
+        }
+    }
+}
+
+/// AVR history.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AvrConfig {
+    /// The exported records.
+    #[serde(default, rename = "node")]
+    pub records: Vec<AvrConfigRecord>,
+
+    /// The last block index processed for each tx source url. Used for
+    /// resuming an incremental export.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub last_processed_blocks: BTreeMap<String, BlockIndex>,
+}
+
+impl AvrConfig {
+    /// Load the history as TOML or JSON from the given file path.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AvrConfigError> {
+        let path = path.as_ref();
+        let bytes = fs::read(path)?;
+        match path.extension().and_then(OsStr::to_str) {
+            Some("toml") => Ok(toml::from_slice(&bytes)?),
+            Some("json") => Ok(serde_json::from_slice(&bytes)?),
+            _ => Err(AvrConfigError::UnrecognizedExtension(path.into())),
+        }
+    }
+
+    /// Load the history from the given file path, or start an empty one if
+    /// the file does not exist yet.
+    pub fn load_or_default(path: impl AsRef<Path>) -> Result<Self, AvrConfigError> {
+        let path = path.as_ref();
+        if path.exists() {
+            Self::load(path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    /// Store the history as TOML or JSON to the given file path.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), AvrConfigError> {
+        let path = path.as_ref();
+        let bytes = match path.extension().and_then(OsStr::to_str) {
+            Some("toml") => toml::to_vec(self)?,
+            Some("json") => serde_json::to_vec_pretty(self)?,
+            _ => return Err(AvrConfigError::UnrecognizedExtension(path.into())),
+        };
+        fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    /// Check that the history is one consumers would accept:
+    /// * every signer is a valid key, and matches the block signer of its
+    ///   verification report, if there is one,
+    /// * the block ranges of each tx source follow each other without gaps or
+    ///   overlaps, in record order.
+    ///
+    /// Returns the first problem found. See [AvrConfig::validation_errors] for
+    /// all of them.
+    pub fn validate(&self) -> Result<(), AvrValidationError> {
+        match self.validation_errors().into_iter().next() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// All the problems [AvrConfig::validate] checks for, in record order.
+    pub fn validation_errors(&self) -> Vec<AvrValidationError> {
+        let mut errors = Vec::new();
+        let mut last_block_index_by_url: HashMap<&str, BlockIndex> = HashMap::new();
+        for (index, record) in self.records.iter().enumerate() {
+            let signer = <[u8; 32]>::from_hex(&record.signer)
+                .ok()
+                .and_then(|bytes| Ed25519Public::try_from(&bytes[..]).ok());
+            if signer.is_none() {
+                errors.push(AvrValidationError::InvalidSigner(
+                    index,
+                    record.signer.clone(),
+                ));
+            }
+
+            if record.first_block_index > record.last_block_index {
+                errors.push(AvrValidationError::InvalidRange(
+                    index,
+                    record.first_block_index,
+                    record.last_block_index,
+                ));
+                continue;
+            }
+
+            if let Some(prev_last_block_index) =
+                last_block_index_by_url.insert(&record.tx_src_url, record.last_block_index)
+            {
+                if record.first_block_index != prev_last_block_index + 1 {
+                    errors.push(AvrValidationError::NotContiguous(
+                        index,
+                        record.first_block_index,
+                        prev_last_block_index,
+                    ));
+                }
+            }
+
+            if let (Some(signer), Some(avr)) = (signer, record.avr.as_ref()) {
+                match ConsensusNodeClient::get_block_signer(avr) {
+                    Ok(report_signer) if report_signer != signer => {
+                        errors.push(AvrValidationError::SignerMismatch(
+                            index,
+                            report_signer.encode_hex(),
+                        ));
+                    }
+                    Ok(_) => {}
+                    Err(err) => errors.push(AvrValidationError::UnreadableReport(index, err)),
+                }
+            }
+        }
+        errors
+    }
+
+    /// Append records for all blocks synced by the watcher that were not
+    /// processed by a previous call.
+    ///
+    /// Records that directly continue the last record of the same tx source
+    /// and signer get merged into it, and records that still have no
+    /// verification report get the one the watcher collected since, if any.
+    /// Running repeatedly therefore produces the same history as a single run
+    /// on the final database.
+    ///
+    /// Returns the number of blocks processed.
+    pub fn update_from_watcher_db(
+        &mut self,
+        watcher_db: &WatcherDB,
+    ) -> Result<u64, AvrConfigError> {
+        self.update_from_watcher_db_with_filter(watcher_db, &AvrExportFilter::default())
+    }
+
+    /// Same as [AvrConfig::update_from_watcher_db], but only process the
+    /// blocks that pass `filter`. Blocks past `filter.end_block` are left for
+    /// a later call. The blocks skipped by `filter.start_block` or
+    /// `filter.responder_id` are left for a later call as well, so progress is
+    /// not recorded when there are any.
+    pub fn update_from_watcher_db_with_filter(
+        &mut self,
+        watcher_db: &WatcherDB,
+        filter: &AvrExportFilter,
+    ) -> Result<u64, AvrConfigError> {
+        let last_synced_blocks = watcher_db.last_synced_blocks()?;
+
+        let mut num_processed = 0;
+        let mut urls = last_synced_blocks.keys().cloned().collect::<Vec<_>>();
+        urls.sort();
+        for tx_src_url in urls {
+            let last_synced = match last_synced_blocks[&tx_src_url] {
+                Some(block_index) => block_index,
+                None => continue,
+            };
+            let last_synced = filter
+                .end_block
+                .map_or(last_synced, |end_block| end_block.min(last_synced));
+            let resume_from = self
+                .last_processed_blocks
+                .get(tx_src_url.as_str())
+                .map_or(0, |block_index| block_index + 1);
+            let start = resume_from.max(filter.start_block.unwrap_or(0));
+
+            // Progress is only recorded if no block since the previous progress gets
+            // skipped, so that a later run does not miss the skipped blocks.
+            let records_progress = start == resume_from && filter.responder_id.is_none();
+
+            for block_index in start..=last_synced {
+                num_processed += 1;
+                if !filter.matches_responder_id(watcher_db, &tx_src_url, block_index)? {
+                    continue;
+                }
+
+                let signer = watcher_db
+                    .get_block_signatures(block_index)?
+                    .into_iter()
+                    .find(|data| data.src_url == tx_src_url.as_str())
+                    .map(|data| *data.block_signature.signer());
+                if let Some(signer) = signer {
+                    self.add_signed_block(&tx_src_url, &signer, block_index);
+                }
+            }
+
+            if records_progress && start <= last_synced {
+                self.last_processed_blocks
+                    .insert(tx_src_url.to_string(), last_synced);
+            }
+        }
+
+        self.backfill_avrs(watcher_db)?;
+
+        Ok(num_processed)
+    }
+
+    /// Look up the verification report of every record that does not have one
+    /// yet, including the records just added. Reports are often collected
+    /// after the first blocks of their signer were synced, so they may also be
+    /// missing from records written by an earlier run.
+    fn backfill_avrs(&mut self, watcher_db: &WatcherDB) -> Result<(), AvrConfigError> {
+        for record in self
+            .records
+            .iter_mut()
+            .filter(|record| record.avr.is_none())
+        {
+            let tx_src_url = match Url::parse(&record.tx_src_url) {
+                Ok(tx_src_url) => tx_src_url,
+                Err(_) => continue,
+            };
+            let signer = match <[u8; 32]>::from_hex(&record.signer)
+                .ok()
+                .and_then(|bytes| Ed25519Public::try_from(&bytes[..]).ok())
+            {
+                Some(signer) => signer,
+                None => continue,
+            };
+            record.avr = get_avr(watcher_db, &tx_src_url, &signer)?;
+        }
+        Ok(())
+    }
+
+    /// Add a block to the records of its tx source, merging it with the
+    /// records of the same signer it is adjacent to. Blocks that are already
+    /// part of a record are left alone. The verification reports of new
+    /// records are looked up by [AvrConfig::backfill_avrs].
+    fn add_signed_block(
+        &mut self,
+        tx_src_url: &Url,
+        signer: &Ed25519Public,
+        block_index: BlockIndex,
+    ) {
+        let signer_hex: String = signer.encode_hex();
+
+        // The records of a tx source are in block order.
+        let url_records = self
+            .records
+            .iter()
+            .enumerate()
+            .filter(|(_index, record)| record.tx_src_url == tx_src_url.as_str())
+            .map(|(index, _record)| index)
+            .collect::<Vec<_>>();
+        if url_records.iter().any(|index| {
+            let record = &self.records[*index];
+            record.first_block_index <= block_index && block_index <= record.last_block_index
+        }) {
+            return;
+        }
+        let prev = url_records
+            .iter()
+            .rev()
+            .find(|index| self.records[**index].last_block_index < block_index)
+            .copied()
+            .filter(|index| {
+                let record = &self.records[*index];
+                record.signer == signer_hex && record.last_block_index + 1 == block_index
+            });
+        let next = url_records
+            .iter()
+            .find(|index| self.records[**index].first_block_index > block_index)
+            .copied();
+        let adjacent_next = next.filter(|index| {
+            let record = &self.records[*index];
+            record.signer == signer_hex && record.first_block_index == block_index + 1
+        });
+
+        match (prev, adjacent_next) {
+            (Some(prev), Some(next)) => {
+                let next_record = self.records.remove(next);
+                let record = &mut self.records[prev];
+                record.last_block_index = next_record.last_block_index;
+                if record.avr.is_none() {
+                    record.avr = next_record.avr;
+                }
+            }
+            (Some(prev), None) => {
+                self.records[prev].last_block_index = block_index;
+            }
+            (None, Some(next)) => {
+                self.records[next].first_block_index = block_index;
+            }
+            (None, None) => {
+                let record = AvrConfigRecord {
+                    tx_src_url: tx_src_url.to_string(),
+                    signer: signer_hex,
+                    first_block_index: block_index,
+                    last_block_index: block_index,
+                    avr: None,
+                };
+                match next {
+                    Some(next) => self.records.insert(next, record),
+                    None => self.records.push(record),
+                }
+            }
+        }
+    }
+}
+
+/// Get the verification report collected for a signer on a tx source, if any.
+fn get_avr(
+    watcher_db: &WatcherDB,
+    tx_src_url: &Url,
+    signer: &Ed25519Public,
+) -> Result<Option<VerificationReport>, AvrConfigError> {
+    Ok(watcher_db
+        .get_verification_report_for_signer_and_url(signer, tx_src_url)?
+        .into_iter()
+        .flatten()
+        .next())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::watcher_db::tests::{setup_blocks, setup_watcher_db};
+    use mc_blockchain_test_utils::make_block_metadata;
+    use mc_blockchain_types::BlockSignature;
+    use mc_common::logger::{test_with_logger, Logger};
+    use mc_crypto_keys::Ed25519Pair;
+    use mc_util_from_random::FromRandom;
+    use mc_util_test_helper::run_with_one_seed;
+    use std::str::FromStr;
+    use tempdir::TempDir;
+
+    #[test_with_logger]
+    fn incremental_export_matches_full_export(logger: Logger) {
+        run_with_one_seed(|mut rng| {
+            let url1 = Url::parse("http://www.my_url1.com").unwrap();
+            let urls = [url1.clone()];
+            let watcher_db = setup_watcher_db(&urls, logger.clone());
+            let blocks = setup_blocks();
+
+            let signing_key_a = Ed25519Pair::from_random(&mut rng);
+            let signing_key_b = Ed25519Pair::from_random(&mut rng);
+            let report_a = VerificationReport {
+                sig: vec![1u8; 32].into(),
+                chain: vec![vec![2; 16], vec![3; 32]],
+                http_body: "test body a".to_owned(),
+            };
+            watcher_db
+                .add_verification_report(&url1, &signing_key_a.public_key(), &report_a, &[])
+                .unwrap();
+
+            let add_block = |block_index: usize, key: &Ed25519Pair| {
+                let signature =
+                    BlockSignature::from_block_and_keypair(blocks[block_index].block(), key)
+                        .unwrap();
+                watcher_db
+                    .add_block_signature(
+                        &url1,
+                        block_index as u64,
+                        signature,
+                        format!("00/{:02}", block_index),
+                    )
+                    .unwrap();
+            };
+
+            // First run: blocks 0 and 1 signed by a.
+            add_block(0, &signing_key_a);
+            add_block(1, &signing_key_a);
+
+            let temp = TempDir::new("avr_config").unwrap();
+            let path = temp.path().join("avr-history.toml");
+            let mut config = AvrConfig::load_or_default(&path).unwrap();
+            assert_eq!(config.update_from_watcher_db(&watcher_db).unwrap(), 2);
+            config.save(&path).unwrap();
+
+            // Nothing new to process.
+            let mut config = AvrConfig::load(&path).unwrap();
+            assert_eq!(config.update_from_watcher_db(&watcher_db).unwrap(), 0);
+
+            // Second run: block 2 signed by a, block 3 by b.
+            add_block(2, &signing_key_a);
+            add_block(3, &signing_key_b);
+            assert_eq!(config.update_from_watcher_db(&watcher_db).unwrap(), 2);
+            config.save(&path).unwrap();
+
+            let expected_records = vec![
+                AvrConfigRecord {
+                    tx_src_url: url1.to_string(),
+                    signer: signing_key_a.public_key().encode_hex(),
+                    first_block_index: 0,
+                    last_block_index: 2,
+                    avr: Some(report_a),
+                },
+                AvrConfigRecord {
+                    tx_src_url: url1.to_string(),
+                    signer: signing_key_b.public_key().encode_hex(),
+                    first_block_index: 3,
+                    last_block_index: 3,
+                    avr: None,
+                },
+            ];
+            let incremental = AvrConfig::load(&path).unwrap();
+            assert_eq!(incremental.records, expected_records);
+            assert_eq!(
+                incremental.last_processed_blocks,
+                BTreeMap::from_iter([(url1.to_string(), 3)])
+            );
+
+            // A full export from scratch produces the same history.
+            let mut full = AvrConfig::default();
+            assert_eq!(full.update_from_watcher_db(&watcher_db).unwrap(), 4);
+            assert_eq!(full, incremental);
+
+            // The report of b is only collected now. The next run backfills it,
+            // even though it has no new block to process.
+            let report_b = VerificationReport {
+                sig: vec![4u8; 32].into(),
+                chain: vec![vec![5; 16], vec![6; 32]],
+                http_body: "test body b".to_owned(),
+            };
+            watcher_db
+                .add_verification_report(&url1, &signing_key_b.public_key(), &report_b, &[])
+                .unwrap();
+
+            let mut incremental = AvrConfig::load(&path).unwrap();
+            assert_eq!(incremental.update_from_watcher_db(&watcher_db).unwrap(), 0);
+            assert_eq!(incremental.records[0].avr, expected_records[0].avr);
+            assert_eq!(incremental.records[1].avr, Some(report_b));
+
+            let mut full = AvrConfig::default();
+            full.update_from_watcher_db(&watcher_db).unwrap();
+            assert_eq!(full, incremental);
+        });
+    }
+
+    #[test_with_logger]
+    fn export_with_filter(logger: Logger) {
+        run_with_one_seed(|mut rng| {
+            let url1 = Url::parse("http://www.my_url1.com").unwrap();
+            let url2 = Url::parse("http://www.my_url2.com").unwrap();
+            let urls = [url1.clone(), url2.clone()];
+            let watcher_db = setup_watcher_db(&urls, logger.clone());
+            let blocks = setup_blocks();
+
+            let signing_key = Ed25519Pair::from_random(&mut rng);
+            for (block_index, block_data) in blocks.iter().enumerate().take(6) {
+                for url in urls.iter() {
+                    let signature =
+                        BlockSignature::from_block_and_keypair(block_data.block(), &signing_key)
+                            .unwrap();
+                    watcher_db
+                        .add_block_signature(
+                            url,
+                            block_index as u64,
+                            signature,
+                            format!("00/{:02}", block_index),
+                        )
+                        .unwrap();
+                }
+            }
+
+            // Only url2 provides metadata, for blocks 2 to 5.
+            for (block_index, block_data) in blocks.iter().enumerate().take(6).skip(2) {
+                let metadata = make_block_metadata(block_data.block().id.clone(), &mut rng);
+                watcher_db
+                    .add_block_metadata(&url2, block_index as u64, &metadata)
+                    .unwrap();
+            }
+            // The responder ID used by make_block_metadata.
+            let responder_id = ResponderId::from_str("test.mobilecoin.com:443").unwrap();
+
+            let record = |url: &Url, first: u64, last: u64| AvrConfigRecord {
+                tx_src_url: url.to_string(),
+                signer: signing_key.public_key().encode_hex(),
+                first_block_index: first,
+                last_block_index: last,
+                avr: None,
+            };
+
+            // Block range only.
+            let mut config = AvrConfig::default();
+            let filter = AvrExportFilter {
+                start_block: Some(1),
+                end_block: Some(3),
+                responder_id: None,
+            };
+            assert_eq!(
+                config
+                    .update_from_watcher_db_with_filter(&watcher_db, &filter)
+                    .unwrap(),
+                6
+            );
+            assert_eq!(
+                config.records,
+                vec![record(&url1, 1, 3), record(&url2, 1, 3)]
+            );
+            // Block 0 was skipped, so no progress is recorded.
+            assert!(config.last_processed_blocks.is_empty());
+
+            // A later run processes all the blocks, and merges the ones the previous
+            // run skipped into the existing records.
+            assert_eq!(config.update_from_watcher_db(&watcher_db).unwrap(), 12);
+            assert_eq!(
+                config.records,
+                vec![record(&url1, 0, 5), record(&url2, 0, 5)]
+            );
+            assert_eq!(
+                config.last_processed_blocks,
+                BTreeMap::from_iter([(url1.to_string(), 5), (url2.to_string(), 5)])
+            );
+
+            // Without a start block, an incremental run continues after the end of
+            // the previous range.
+            let mut config = AvrConfig::default();
+            let filter = AvrExportFilter {
+                end_block: Some(3),
+                ..Default::default()
+            };
+            assert_eq!(
+                config
+                    .update_from_watcher_db_with_filter(&watcher_db, &filter)
+                    .unwrap(),
+                8
+            );
+            assert_eq!(
+                config.last_processed_blocks,
+                BTreeMap::from_iter([(url1.to_string(), 3), (url2.to_string(), 3)])
+            );
+            assert_eq!(config.update_from_watcher_db(&watcher_db).unwrap(), 4);
+            assert_eq!(
+                config.records,
+                vec![record(&url1, 0, 5), record(&url2, 0, 5)]
+            );
+
+            // Responder ID, combined with a start block. Only the blocks whose url2
+            // metadata names the responder are kept.
+            let mut config = AvrConfig::default();
+            let filter = AvrExportFilter {
+                start_block: Some(3),
+                end_block: None,
+                responder_id: Some(responder_id),
+            };
+            config
+                .update_from_watcher_db_with_filter(&watcher_db, &filter)
+                .unwrap();
+            assert_eq!(config.records, vec![record(&url2, 3, 5)]);
+            assert!(config.last_processed_blocks.is_empty());
+
+            // No block names other responders.
+            let mut config = AvrConfig::default();
+            let filter = AvrExportFilter {
+                responder_id: Some(ResponderId::from_str("other.mobilecoin.com:443").unwrap()),
+                ..Default::default()
+            };
+            config
+                .update_from_watcher_db_with_filter(&watcher_db, &filter)
+                .unwrap();
+            assert!(config.records.is_empty());
+        });
+    }
+
+    #[test]
+    fn validate_rejects_inconsistent_records() {
+        run_with_one_seed(|mut rng| {
+            let signer_a: String = Ed25519Pair::from_random(&mut rng).public_key().encode_hex();
+            let signer_b: String = Ed25519Pair::from_random(&mut rng).public_key().encode_hex();
+            let record = |tx_src_url: &str, signer: &str, first: u64, last: u64| AvrConfigRecord {
+                tx_src_url: tx_src_url.to_owned(),
+                signer: signer.to_owned(),
+                first_block_index: first,
+                last_block_index: last,
+                avr: None,
+            };
+            let url1 = "http://www.my_url1.com/";
+            let url2 = "http://www.my_url2.com/";
+
+            let mut config = AvrConfig {
+                records: vec![
+                    record(url1, &signer_a, 1, 10),
+                    record(url2, &signer_b, 1, 5),
+                    record(url1, &signer_b, 11, 20),
+                ],
+                last_processed_blocks: Default::default(),
+            };
+            assert_eq!(config.validate(), Ok(()));
+
+            config.records.push(record(url1, &signer_a, 20, 30));
+            assert_eq!(
+                config.validate(),
+                Err(AvrValidationError::NotContiguous(3, 20, 20))
+            );
+
+            config.records[3] = record(url1, &signer_a, 22, 30);
+            assert_eq!(
+                config.validate(),
+                Err(AvrValidationError::NotContiguous(3, 22, 20))
+            );
+
+            // Every problem is listed.
+            config.records.push(record(url2, &signer_a, 7, 8));
+            assert_eq!(
+                config.validation_errors(),
+                vec![
+                    AvrValidationError::NotContiguous(3, 22, 20),
+                    AvrValidationError::NotContiguous(4, 7, 5),
+                ]
+            );
+            config.records.pop();
+
+            config.records[3] = record(url1, &signer_a, 21, 19);
+            assert_eq!(
+                config.validate(),
+                Err(AvrValidationError::InvalidRange(3, 21, 19))
+            );
+
+            config.records[3] = record(url1, "not hex", 21, 30);
+            assert_eq!(
+                config.validate(),
+                Err(AvrValidationError::InvalidSigner(3, "not hex".to_owned()))
+            );
+
+            // A report the signer cannot be extracted from is rejected.
+            config.records[3] = record(url1, &signer_a, 21, 30);
+            config.records[3].avr = Some(VerificationReport {
+                sig: vec![1u8; 32].into(),
+                chain: vec![vec![2; 16], vec![3; 32]],
+                http_body: "test body".to_owned(),
+            });
+            assert!(matches!(
+                config.validate(),
+                Err(AvrValidationError::UnreadableReport(3, _))
+            ));
+        });
+    }
+}
@@ -0,0 +1,110 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+#![deny(missing_docs)]
+
+//! A utility for exporting the AVR history collected by a watcher db.
+
+use clap::Parser;
+use mc_blockchain_types::BlockIndex;
+use mc_common::{
+    logger::{create_app_logger, log, o},
+    ResponderId,
+};
+use mc_watcher::{
+    avr_config::{AvrConfig, AvrExportFilter},
+    watcher_db::WatcherDB,
+};
+use std::path::PathBuf;
+
+/// Command line configuration.
+#[derive(Debug, Parser)]
+#[clap(
+    name = "mc-watcher-avr-export",
+    about = "A utility for exporting the AVR history collected by a watcher db"
+)]
+pub struct Config {
+    /// Path to watcher db (lmdb).
+    #[clap(
+        long,
+        default_value = "/tmp/watcher-db",
+        parse(from_os_str),
+        env = "MC_WATCHER_DB"
+    )]
+    pub watcher_db: PathBuf,
+
+    /// Path to the avr-history file to write. The format (TOML or JSON) is
+    /// picked according to the file extension.
+    #[clap(long, parse(from_os_str), env = "MC_AVR_HISTORY")]
+    pub avr_history: PathBuf,
+
+    /// Only process blocks that were not processed by a previous run, merging
+    /// the new records into the existing avr-history file.
+    #[clap(long, env = "MC_INCREMENTAL")]
+    pub incremental: bool,
+
+    /// Skip blocks before this index.
+    #[clap(long, env = "MC_START_BLOCK")]
+    pub start_block: Option<BlockIndex>,
+
+    /// Skip blocks after this index.
+    #[clap(long, env = "MC_END_BLOCK")]
+    pub end_block: Option<BlockIndex>,
+
+    /// Only export blocks whose block metadata names this responder ID, e.g.
+    /// `node1.test.mobilecoin.com:443`. Blocks without metadata are skipped.
+    #[clap(long, env = "MC_RESPONDER_ID")]
+    pub responder_id: Option<ResponderId>,
+}
+
+fn main() {
+    let (logger, _global_logger_guard) = create_app_logger(o!());
+
+    let config = Config::parse();
+    let watcher_db =
+        WatcherDB::open_ro(&config.watcher_db, logger.clone()).expect("Failed opening watcher db");
+
+    let mut avr_config = if config.incremental {
+        AvrConfig::load_or_default(&config.avr_history).expect("Failed loading avr history")
+    } else {
+        AvrConfig::default()
+    };
+
+    let filter = AvrExportFilter {
+        start_block: config.start_block,
+        end_block: config.end_block,
+        responder_id: config.responder_id.clone(),
+    };
+    let num_processed = avr_config
+        .update_from_watcher_db_with_filter(&watcher_db, &filter)
+        .expect("Failed exporting avr history");
+
+    // Refuse to write a file consumers would reject, and report every offending
+    // record.
+    let validation_errors = avr_config.validation_errors();
+    if !validation_errors.is_empty() {
+        for err in validation_errors.iter() {
+            log::error!(logger, "Invalid avr history: {}", err);
+        }
+        log::error!(
+            logger,
+            "Not writing {:?}: found {} problems in {} records",
+            config.avr_history,
+            validation_errors.len(),
+            avr_config.records.len(),
+        );
+        // Flush the logs before exiting.
+        drop(_global_logger_guard);
+        std::process::exit(1);
+    }
+
+    avr_config
+        .save(&config.avr_history)
+        .expect("Failed writing avr history");
+
+    log::info!(
+        logger,
+        "Processed {} blocks, wrote {} records to {:?}",
+        num_processed,
+        avr_config.records.len(),
+        config.avr_history,
+    );
+}
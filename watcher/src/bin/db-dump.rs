@@ -8,7 +8,7 @@ use mc_attest_core::VerificationReportData;
 use mc_common::logger::{create_app_logger, o};
 use mc_crypto_keys::Ed25519Public;
 use mc_util_repr_bytes::ReprBytes;
-use mc_watcher::{error::WatcherDBError, watcher_db::WatcherDB};
+use mc_watcher::watcher_db::WatcherDB;
 use std::path::PathBuf;
 use url::Url;
 
@@ -67,13 +67,17 @@ fn main() {
         let mut cur_end_index = 0;
         let mut cur_signer = None;
         for block_index in 0..max_block_count {
-            let signer = match watcher_db.get_block_data(tx_src_url, block_index) {
-                Ok(block_data) => block_data.signature().map(|sig| *sig.signer()),
-                Err(WatcherDBError::NotFound) => None,
+            // Use the block signatures rather than the block data, since block data might
+            // have been pruned.
+            let signer = match watcher_db.get_block_signatures(block_index) {
+                Ok(signatures) => signatures
+                    .into_iter()
+                    .find(|data| data.src_url == tx_src_url.as_str())
+                    .map(|data| *data.block_signature.signer()),
                 Err(err) => {
                     panic!(
-                        "Failed getting block {}@{}: {}",
-                        tx_src_url, cur_start_index, err
+                        "Failed getting block signatures {}@{}: {}",
+                        tx_src_url, block_index, err
                     );
                 }
             };
@@ -6,24 +6,32 @@
 
 use displaydoc::Display;
 use mc_watcher::{
+    alerts::AlertNotifier,
+    archive_auth::create_authenticator,
+    attestation_revalidator::{AttestationPolicy, AttestationRevalidator},
     config::WatcherConfig,
+    http_api::{initialize_rocket_server, HttpApiState},
+    service::WatcherService,
     verification_reports_collector::VerificationReportsCollector,
+    verification_status::AttestedBlockSignerChecker,
     watcher::{SyncResult, Watcher},
     watcher_db::create_or_open_rw_watcher_db,
 };
 
 use clap::Parser;
 use futures::executor::block_on;
-use grpcio::{EnvBuilder, ServerBuilder};
+use grpcio::{EnvBuilder, RpcStatus, RpcStatusCode, ServerBuilder};
 use mc_common::logger::{create_app_logger, log, o, Logger};
-use mc_util_grpc::{ConnectionUriGrpcioServer, HealthCheckStatus, HealthService};
+use mc_util_grpc::{AdminServer, ConnectionUriGrpcioServer, HealthCheckStatus, HealthService};
 use std::{
+    collections::BTreeMap,
     io::Error as IOError,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
     thread::{sleep, Builder as ThreadBuilder, JoinHandle},
+    time::Instant,
 };
 
 fn main() {
@@ -33,22 +41,140 @@ fn main() {
     let config = WatcherConfig::parse();
     let sources_config = config.sources_config();
 
-    let watcher_db = create_or_open_rw_watcher_db(
+    let mut watcher_db = create_or_open_rw_watcher_db(
         &config.watcher_db,
         &sources_config.tx_source_urls()[..],
         logger.clone(),
     )
     .expect("Could not create or open watcher db");
-    let watcher = Watcher::new(watcher_db.clone(), config.store_block_data, logger.clone())
+
+    // Block signatures are only verified against reports for the enclaves of the
+    // attestation policy.
+    match config.attestation_policy.as_ref() {
+        Some(policy_path) => {
+            let verifier = AttestationPolicy::load(policy_path)
+                .and_then(|policy| policy.verifier())
+                .expect("Failed loading attestation policy");
+            watcher_db.set_verification_report_checker(AttestedBlockSignerChecker::new(verifier));
+        }
+        None => log::warn!(
+            logger,
+            "No attestation policy configured, block signatures will not be verified"
+        ),
+    }
+    let alert_notifier = if config.alert_webhook_urls.is_empty() {
+        None
+    } else {
+        Some(
+            AlertNotifier::new(config.alert_webhook_urls.clone(), logger.clone())
+                .expect("Failed creating alert notifier"),
+        )
+    };
+
+    let mut watcher = Watcher::new(watcher_db.clone(), config.store_block_data, logger.clone())
         .expect("Failed creating watcher");
+    watcher.set_alert_notifier(alert_notifier.clone());
+    watcher.set_block_id_check_interval(Some(config.block_id_check_interval));
+    watcher.set_block_data_retention(config.block_data_retention);
+    watcher
+        .set_poll_workers(
+            config
+                .poll_workers
+                .unwrap_or_else(|| sources_config.tx_source_urls().len()),
+        )
+        .expect("Failed creating poll workers");
+    watcher.set_source_rate_limit(config.max_requests_per_source_per_second);
+    for source_config in sources_config.sources() {
+        if let Some(tx_source_auth) = source_config.tx_source_auth() {
+            let authenticator = create_authenticator(tx_source_auth).unwrap_or_else(|err| {
+                panic!(
+                    "Failed creating authenticator for {}: {}",
+                    source_config.tx_source_url(),
+                    err
+                )
+            });
+            watcher
+                .set_tx_source_authenticator(&source_config.tx_source_url(), Some(authenticator))
+                .expect("Failed setting tx source authenticator");
+        }
+    }
 
     let _verification_reports_collector = <VerificationReportsCollector>::new(
-        watcher_db,
+        watcher_db.clone(),
         sources_config.sources().to_vec(),
         config.poll_interval,
+        alert_notifier,
         logger.clone(),
     );
 
+    let _attestation_revalidator = config.attestation_policy.as_ref().map(|policy_path| {
+        AttestationRevalidator::new(
+            watcher_db.clone(),
+            policy_path.clone(),
+            config.attestation_revalidation_interval,
+            logger.clone(),
+        )
+    });
+
+    // Start the HTTP sync status server, if enabled.
+    if let Some(http_listen_port) = config.http_listen_port {
+        let rocket_config = rocket::Config::figment()
+            .merge(("port", http_listen_port))
+            .merge(("address", config.http_listen_host.clone()));
+        let state = HttpApiState {
+            watcher_db: watcher_db.clone(),
+            sync_status: watcher.sync_status(),
+        };
+        let rocket = initialize_rocket_server(rocket_config, state);
+        ThreadBuilder::new()
+            .name("WatcherHttp".to_string())
+            .spawn(move || {
+                if let Err(err) = rocket::execute(rocket.launch()) {
+                    panic!("HTTP server failed: {}", err);
+                }
+            })
+            .expect("Failed starting HTTP server thread");
+    }
+
+    // Start the admin server, if enabled. Its config JSON reports blocks that are
+    // missing from source archives.
+    let tx_source_urls = sources_config
+        .tx_source_urls()
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    let admin_watcher_db = watcher_db.clone();
+    let get_config_json = Arc::new(move || {
+        let missing_blocks = admin_watcher_db
+            .get_missing_blocks()
+            .map_err(|err| RpcStatus::with_message(RpcStatusCode::INTERNAL, err.to_string()))?
+            .into_iter()
+            .map(|(src_url, block_indexes)| (src_url.to_string(), block_indexes))
+            .collect::<BTreeMap<_, _>>();
+        serde_json::to_string(&serde_json::json!({
+            "tx_source_urls": tx_source_urls,
+            "missing_blocks": missing_blocks,
+        }))
+        .map_err(|err| RpcStatus::with_message(RpcStatusCode::INTERNAL, format!("{:?}", err)))
+    });
+    let _admin_server = config.admin_listen_uri.as_ref().map(|admin_listen_uri| {
+        let local_hostname = hostname::get()
+            .expect("failed getting local hostname")
+            .to_str()
+            .expect("failed getting hostname as str")
+            .to_string();
+
+        AdminServer::start(
+            None,
+            admin_listen_uri,
+            "Watcher".to_owned(),
+            local_hostname,
+            Some(get_config_json),
+            logger.clone(),
+        )
+        .expect("Failed starting admin server")
+    });
+
     // Start watcher sync thread.
     let mut sync_thread = WatcherSyncThread::start(watcher, config.clone(), logger.clone())
         .expect("Failed starting watcher sync thread.");
@@ -58,6 +184,7 @@ fn main() {
         Arc::new(move |_| HealthCheckStatus::SERVING);
     let health_service =
         HealthService::new(Some(health_check_callback), logger.clone()).into_service();
+    let watcher_service = WatcherService::new(watcher_db, logger.clone()).into_service();
 
     let env = Arc::new(
         EnvBuilder::new()
@@ -67,6 +194,7 @@ fn main() {
 
     let server_builder = ServerBuilder::new(env)
         .register_service(health_service)
+        .register_service(watcher_service)
         .bind_using_uri(&config.client_listen_uri, logger.clone());
 
     let mut server = server_builder.build().unwrap();
@@ -148,6 +276,7 @@ impl WatcherSyncThread {
     ) {
         log::debug!(logger, "Watcher sync thread started");
 
+        let mut last_missing_blocks_retry = Instant::now();
         loop {
             if stop_requested.load(Ordering::SeqCst) {
                 log::debug!(logger, "Watcher sync thread stop requested.");
@@ -164,6 +293,17 @@ impl WatcherSyncThread {
                 )
                 .expect("Could not sync signatures");
 
+            watcher
+                .prune_block_data()
+                .expect("Could not prune block data");
+
+            if last_missing_blocks_retry.elapsed() >= config.missing_blocks_retry_interval {
+                watcher
+                    .retry_missing_blocks()
+                    .expect("Could not retry missing blocks");
+                last_missing_blocks_retry = Instant::now();
+            }
+
             watcher.collect_metrics(None);
 
             // Decide next step before continuing based on sync result
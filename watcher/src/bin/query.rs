@@ -0,0 +1,156 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+#![deny(missing_docs)]
+
+//! A utility for examining the provenance of a block's signatures: every
+//! signature the watcher collected for the block, who signed it, the
+//! attestation status of the signer and the timestamp each tx source reported.
+
+use clap::{ArgEnum, Parser};
+use mc_blockchain_types::BlockIndex;
+use mc_common::logger::{create_app_logger, o};
+use mc_watcher::{
+    provenance::{get_block_provenance, BlockProvenance, ReportProvenance},
+    watcher_db::WatcherDB,
+};
+use std::path::PathBuf;
+
+/// Output format.
+#[derive(ArgEnum, Clone, Debug)]
+pub enum OutputFormat {
+    /// Human readable table.
+    Table,
+
+    /// JSON object.
+    Json,
+}
+
+/// Command line configuration.
+#[derive(Debug, Parser)]
+#[clap(
+    name = "mc-watcher-query",
+    about = "A utility for examining the provenance of a block's signatures"
+)]
+pub struct Config {
+    /// Path to watcher db (lmdb).
+    #[clap(
+        long,
+        default_value = "/tmp/watcher-db",
+        parse(from_os_str),
+        env = "MC_WATCHER_DB"
+    )]
+    pub watcher_db: PathBuf,
+
+    /// The block index to query.
+    #[clap(long, env = "MC_BLOCK_INDEX")]
+    pub block_index: BlockIndex,
+
+    /// Output format.
+    #[clap(arg_enum, long, default_value = "table", env = "MC_FORMAT")]
+    pub format: OutputFormat,
+}
+
+fn main() {
+    let (logger, _global_logger_guard) = create_app_logger(o!());
+
+    let config = Config::parse();
+    let watcher_db =
+        WatcherDB::open_ro(&config.watcher_db, logger).expect("Failed opening watcher db");
+
+    let provenance =
+        get_block_provenance(&watcher_db, config.block_index).expect("Failed querying block");
+
+    match config.format {
+        OutputFormat::Table => print_table(&provenance),
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&provenance).expect("Failed serializing to JSON")
+        ),
+    }
+}
+
+fn print_table(provenance: &BlockProvenance) {
+    println!("Block index: {}", provenance.block_index);
+    println!(
+        "Timestamp:   {} ({})",
+        provenance.timestamp, provenance.timestamp_result_code
+    );
+    println!();
+
+    if provenance.signatures.is_empty() {
+        println!("No signatures");
+        return;
+    }
+
+    let rows = provenance
+        .signatures
+        .iter()
+        .map(|signature| {
+            let status = signature
+                .verification_status
+                .map(|status| format!("{:?}", status))
+                .unwrap_or_else(|| "-".to_owned());
+            let reports = if signature.verification_reports.is_empty() {
+                "not polled".to_owned()
+            } else {
+                signature
+                    .verification_reports
+                    .iter()
+                    .map(display_report)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            let attestation = match signature.attestation_passes {
+                None => "not re-validated".to_owned(),
+                Some(true) => "passes".to_owned(),
+                Some(false) => format!("FAILS ({})", signature.attestation_errors.join(", ")),
+            };
+            [
+                signature.src_url.clone(),
+                signature.signer.clone(),
+                signature.signed_at.to_string(),
+                status,
+                reports,
+                attestation,
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let header = [
+        "TX SOURCE",
+        "SIGNER",
+        "SIGNED AT",
+        "STATUS",
+        "REPORTS",
+        "ATTESTATION",
+    ];
+    let mut widths = header.map(str::len);
+    for row in rows.iter() {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let print_row = |cells: [&str; 6]| {
+        let line = cells
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("{}", line.trim_end());
+    };
+    print_row(header);
+    for row in rows.iter() {
+        print_row([&row[0], &row[1], &row[2], &row[3], &row[4], &row[5]]);
+    }
+}
+
+fn display_report(report: &ReportProvenance) -> String {
+    match report {
+        ReportProvenance::NoReport => "no report".to_owned(),
+        ReportProvenance::Available { id, timestamp } => {
+            format!("id {} generated at {}", id, timestamp)
+        }
+        ReportProvenance::Unreadable { error } => format!("unreadable ({})", error),
+    }
+}
@@ -0,0 +1,102 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+#![deny(missing_docs)]
+
+//! A utility for exporting a watcher db snapshot and seeding a new watcher db
+//! from it.
+
+use clap::{Parser, Subcommand};
+use mc_common::logger::{create_app_logger, log, o};
+use mc_watcher::watcher_db::WatcherDB;
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::PathBuf,
+};
+
+/// Clap configuration for each subcommand this program supports.
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Write a snapshot of a watcher db to a file.
+    Export {
+        /// Path to watcher db (lmdb).
+        #[clap(long, parse(from_os_str), env = "MC_WATCHER_DB")]
+        watcher_db: PathBuf,
+
+        /// Path to the snapshot file to write.
+        #[clap(long, parse(from_os_str), env = "MC_SNAPSHOT")]
+        snapshot: PathBuf,
+    },
+
+    /// Create a new watcher db from a snapshot file.
+    Import {
+        /// Path to the watcher db (lmdb) to create. If it already contains a
+        /// database, e.g. from an interrupted import, it must be configured
+        /// with the tx source urls of the snapshot.
+        #[clap(long, parse(from_os_str), env = "MC_WATCHER_DB")]
+        watcher_db: PathBuf,
+
+        /// Path to the snapshot file to read.
+        #[clap(long, parse(from_os_str), env = "MC_SNAPSHOT")]
+        snapshot: PathBuf,
+    },
+}
+
+/// Command line configuration.
+#[derive(Debug, Parser)]
+#[clap(
+    name = "mc-watcher-snapshot",
+    about = "A utility for exporting and importing watcher db snapshots"
+)]
+pub struct Config {
+    /// Command to run.
+    #[clap(subcommand)]
+    pub command: Command,
+}
+
+fn main() {
+    let (logger, _global_logger_guard) = create_app_logger(o!());
+
+    let config = Config::parse();
+    match config.command {
+        Command::Export {
+            watcher_db,
+            snapshot,
+        } => {
+            let watcher_db =
+                WatcherDB::open_ro(&watcher_db, logger.clone()).expect("Failed opening watcher db");
+
+            let mut writer =
+                BufWriter::new(File::create(&snapshot).expect("Failed creating snapshot file"));
+            let summary = watcher_db
+                .export_snapshot(&mut writer)
+                .expect("Failed exporting snapshot");
+            writer.flush().expect("Failed writing snapshot file");
+
+            log::info!(
+                logger,
+                "Exported {} block signatures and {} verification reports to {:?}",
+                summary.num_block_signatures,
+                summary.num_verification_reports,
+                snapshot,
+            );
+        }
+
+        Command::Import {
+            watcher_db,
+            snapshot,
+        } => {
+            let mut reader =
+                BufReader::new(File::open(&snapshot).expect("Failed opening snapshot file"));
+            let summary = WatcherDB::import_snapshot(&watcher_db, &mut reader, logger.clone())
+                .expect("Failed importing snapshot");
+
+            log::info!(
+                logger,
+                "Imported {} block signatures and {} verification reports into {:?}",
+                summary.num_block_signatures,
+                summary.num_verification_reports,
+                watcher_db,
+            );
+        }
+    }
+}
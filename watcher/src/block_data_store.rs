@@ -10,7 +10,7 @@ use mc_blockchain_types::{
 };
 use mc_common::{
     logger::{log, Logger},
-    HashMap,
+    HashMap, HashSet,
 };
 use mc_crypto_digestible::{Digestible, MerlinTranscript};
 use mc_util_serial::{decode, encode};
@@ -237,6 +237,83 @@ impl BlockDataStore {
         Ok(())
     }
 
+    /// Remove block data older than a per-source cutoff: for each source url in
+    /// `cutoffs`, all block data with an index lower than the cutoff is
+    /// deleted. Block/BlockContents are only removed once no source url
+    /// references them anymore.
+    ///
+    /// Returns the number of removed block data entries.
+    pub fn prune<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        cutoffs: &HashMap<Url, BlockIndex>,
+    ) -> Result<u64, WatcherDBError> {
+        let max_cutoff = match cutoffs.values().max() {
+            Some(max_cutoff) => *max_cutoff,
+            None => return Ok(0),
+        };
+
+        let index_size = std::mem::size_of::<BlockIndex>();
+        let mut num_removed = 0;
+        let mut removed_hashes = Vec::new();
+        let mut kept_block_hashes = HashSet::default();
+        let mut kept_block_contents_hashes = HashSet::default();
+        {
+            let mut cursor = db_txn.open_rw_cursor(self.block_datas_by_index)?;
+            for (key_bytes, value_bytes) in cursor.iter_start().filter_map(Result::ok) {
+                // The key is the block index, followed by the source url.
+                if key_bytes.len() < index_size {
+                    continue;
+                }
+
+                let mut index_bytes = [0u8; 8];
+                index_bytes.copy_from_slice(&key_bytes[..index_size]);
+                let block_index = BlockIndex::from_be_bytes(index_bytes);
+                if block_index >= max_cutoff {
+                    // Keys are sorted by block index, nothing else to prune.
+                    break;
+                }
+
+                let tx_source_url = Url::parse(str::from_utf8(&key_bytes[index_size..])?)?;
+                let stored_block_data: StoredBlockData = decode(value_bytes)?;
+
+                match cutoffs.get(&tx_source_url) {
+                    Some(cutoff) if block_index < *cutoff => {
+                        cursor.del(WriteFlags::empty())?;
+                        removed_hashes.push((
+                            stored_block_data.block_hash,
+                            stored_block_data.block_contents_hash,
+                        ));
+                        num_removed += 1;
+                    }
+                    _ => {
+                        kept_block_hashes.insert(stored_block_data.block_hash);
+                        kept_block_contents_hashes.insert(stored_block_data.block_contents_hash);
+                    }
+                }
+            }
+        }
+
+        for (block_hash, block_contents_hash) in removed_hashes {
+            if !kept_block_hashes.contains(&block_hash) {
+                match db_txn.del(self.blocks_by_hash, &block_hash, None) {
+                    Ok(()) | Err(lmdb::Error::NotFound) => {}
+                    Err(err) => Err(err)?,
+                }
+            }
+            if !kept_block_contents_hashes.contains(&block_contents_hash) {
+                match db_txn.del(self.block_contents_by_hash, &block_contents_hash, None) {
+                    Ok(()) | Err(lmdb::Error::NotFound) => {}
+                    Err(err) => Err(err)?,
+                }
+            }
+        }
+
+        log::debug!(self.logger, "Pruned {} block data entries", num_removed);
+
+        Ok(num_removed)
+    }
+
     fn store_block<'env>(
         &self,
         db_txn: &mut RwTransaction<'env>,
@@ -4,10 +4,13 @@
 //! Configuration parameters for the watcher test utility.
 
 use clap::Parser;
+use hex::FromHex;
+use mc_crypto_keys::Ed25519Public;
 use mc_util_parse::parse_duration_in_seconds;
-use mc_util_uri::{ConsensusClientUri, WatcherUri};
+use mc_util_repr_bytes::ReprBytes;
+use mc_util_uri::{AdminUri, ConsensusClientUri, WatcherUri};
 use serde::{Deserialize, Serialize};
-use std::{fs, path::PathBuf, str::FromStr, time::Duration};
+use std::{fs, num::NonZeroU32, path::PathBuf, str::FromStr, time::Duration};
 use url::Url;
 
 #[derive(Clone, Debug, Parser)]
@@ -38,10 +41,78 @@ pub struct WatcherConfig {
     /// How many seconds to wait between polling.
     #[clap(long, default_value = "1", parse(try_from_str = parse_duration_in_seconds), env = "MC_POLL_INTERVAL")]
     pub poll_interval: Duration,
+
+    /// (Optional) Number of worker threads used to poll the tx sources
+    /// concurrently. Defaults to one per tx source.
+    #[clap(long, env = "MC_POLL_WORKERS")]
+    pub poll_workers: Option<usize>,
+
+    /// (Optional) Maximum number of requests per second made to each tx
+    /// source.
+    #[clap(long, env = "MC_MAX_REQUESTS_PER_SOURCE_PER_SECOND")]
+    pub max_requests_per_source_per_second: Option<NonZeroU32>,
+
     /// Store block data for every fetched block.
     #[clap(long, env = "MC_STORE_BLOCK_DATA")]
     pub store_block_data: bool,
 
+    /// How many seconds to wait between attempts to fetch blocks that were
+    /// missing from a source archive.
+    #[clap(long, default_value = "60", parse(try_from_str = parse_duration_in_seconds), env = "MC_MISSING_BLOCKS_RETRY_INTERVAL")]
+    pub missing_blocks_retry_interval: Duration,
+
+    /// (Optional) Path to an attestation policy TOML file. When set, block
+    /// signatures are only marked as verified by verification reports for
+    /// the enclaves of the policy, and all stored verification reports are
+    /// periodically re-validated against it. When not set, no block signature
+    /// gets verified.
+    #[clap(long, parse(from_os_str), env = "MC_ATTESTATION_POLICY")]
+    pub attestation_policy: Option<PathBuf>,
+
+    /// How many seconds to wait between re-validations of the stored
+    /// verification reports.
+    #[clap(long, default_value = "3600", parse(try_from_str = parse_duration_in_seconds), env = "MC_ATTESTATION_REVALIDATION_INTERVAL")]
+    pub attestation_revalidation_interval: Duration,
+
+    /// (Optional) Only keep block data for this many of the most recent blocks
+    /// of each source. Block signatures, timestamps and verification reports
+    /// are kept regardless.
+    #[clap(long, env = "MC_BLOCK_DATA_RETENTION")]
+    pub block_data_retention: Option<u64>,
+
+    /// How often (in blocks) to cross-check block IDs between tx sources.
+    /// Every synced block is compared with the block data of the other sources
+    /// stored in the database, and every this many blocks, the watcher also
+    /// fetches the same block from the sources whose block data is not stored.
+    /// An alert is raised if their block IDs differ. Set to 0 to disable.
+    #[clap(long, default_value = "100", env = "MC_BLOCK_ID_CHECK_INTERVAL")]
+    pub block_id_check_interval: u64,
+
+    /// Webhook URLs to POST a JSON alert to when tx sources disagree on a
+    /// block ID, a block signature does not match its block, or blocks are
+    /// signed by a key the verification report of their node does not vouch
+    /// for. Alerts are logged regardless.
+    #[clap(
+        long = "alert-webhook-url",
+        use_value_delimiter = true,
+        env = "MC_ALERT_WEBHOOK_URL"
+    )]
+    pub alert_webhook_urls: Vec<Url>,
+
+    /// Host to serve the HTTP sync status endpoint on.
+    #[clap(long, default_value = "127.0.0.1", env = "MC_HTTP_LISTEN_HOST")]
+    pub http_listen_host: String,
+
+    /// (Optional) Port to serve the HTTP sync status endpoint on. The endpoint
+    /// is disabled when unset.
+    #[clap(long, env = "MC_HTTP_LISTEN_PORT")]
+    pub http_listen_port: Option<u16>,
+
+    /// (Optional) Admin gRPC listening URI. The admin service reports blocks
+    /// that are missing from source archives.
+    #[clap(long, env = "MC_ADMIN_LISTEN_URI")]
+    pub admin_listen_uri: Option<AdminUri>,
+
     /// gRPC listening URI.
     #[clap(
         long,
@@ -79,6 +150,17 @@ pub struct SourceConfig {
     /// (Optional) Client authentication token secret, for generating
     /// Authorization tokens when connecting to consensus nodes.
     consensus_client_auth_token_secret: Option<String>,
+
+    /// (Optional) Authentication for fetching blocks from a private
+    /// `tx_source_url`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tx_source_auth: Option<TxSourceAuthConfig>,
+
+    /// (Optional) Hex-encoded block signer keys known to belong to the node,
+    /// e.g. the keys it used before a rotation. Blocks signed by these keys do
+    /// not raise an unknown signer alert, even without a verification report.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    known_block_signers: Vec<String>,
 }
 
 impl SourceConfig {
@@ -92,9 +174,18 @@ impl SourceConfig {
             tx_source_url,
             consensus_client_url,
             consensus_client_auth_token_secret,
+            tx_source_auth: None,
+            known_block_signers: Vec::new(),
         }
     }
 
+    /// Set the authentication used for fetching blocks from the
+    /// tx_source_url.
+    pub fn with_tx_source_auth(mut self, tx_source_auth: Option<TxSourceAuthConfig>) -> Self {
+        self.tx_source_auth = tx_source_auth;
+        self
+    }
+
     /// Get the tx_source_url and ensure it has a trailing slash.
     /// This is compatible with the behavior inside ReqwestTransactionsFetcher
     /// and ensures everywhere we use URLs we always have "slash-terminated"
@@ -107,6 +198,33 @@ impl SourceConfig {
         Url::from_str(&url).unwrap_or_else(|err| panic!("invalid url {}: {}", url, err))
     }
 
+    /// Set the block signer keys known to belong to the node.
+    pub fn with_known_block_signers(mut self, known_block_signers: &[Ed25519Public]) -> Self {
+        self.known_block_signers = known_block_signers
+            .iter()
+            .map(|signer| hex::encode(signer.to_bytes()))
+            .collect();
+        self
+    }
+
+    /// Get the block signer keys known to belong to the node.
+    pub fn known_block_signers(&self) -> Vec<Ed25519Public> {
+        self.known_block_signers
+            .iter()
+            .map(|signer| {
+                <[u8; 32]>::from_hex(signer)
+                    .ok()
+                    .and_then(|bytes| Ed25519Public::try_from(&bytes[..]).ok())
+                    .unwrap_or_else(|| panic!("invalid known block signer: {}", signer))
+            })
+            .collect()
+    }
+
+    /// Get the authentication used for fetching blocks, if any.
+    pub fn tx_source_auth(&self) -> &Option<TxSourceAuthConfig> {
+        &self.tx_source_auth
+    }
+
     /// Get consensus client URL, if available.
     pub fn consensus_client_url(&self) -> &Option<ConsensusClientUri> {
         &self.consensus_client_url
@@ -115,13 +233,60 @@ impl SourceConfig {
     /// Get consensus client authentication token secret, if available.
     pub fn consensus_client_auth_token_secret(&self) -> Option<[u8; 32]> {
         self.consensus_client_auth_token_secret.as_ref().map(|s| {
-            hex::FromHex::from_hex(s).unwrap_or_else(|err| {
+            FromHex::from_hex(s).unwrap_or_else(|err| {
                 panic!("failed parsing consensus client auth token secret: {}", err)
             })
         })
     }
 }
 
+/// Authentication for fetching blocks from a private tx source. Secrets are
+/// read from the environment, so that the sources file does not contain them.
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Hash)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TxSourceAuthConfig {
+    /// HTTP basic authentication.
+    Basic {
+        /// The username.
+        username: String,
+
+        /// The environment variable holding the password.
+        password_env: String,
+    },
+
+    /// AWS Signature Version 4 `Authorization` headers. Credentials come from
+    /// the standard AWS environment variables, the AWS profile, or the
+    /// instance metadata service.
+    AwsSigV4 {
+        /// The AWS region of the archive, e.g. `us-west-1`.
+        region: String,
+
+        /// The AWS service name used in the signature.
+        #[serde(default = "default_aws_service")]
+        service: String,
+    },
+
+    /// S3 presigned URLs. Each block is fetched from a URL presigned with the
+    /// same credentials as [TxSourceAuthConfig::AwsSigV4], which works through
+    /// proxies that drop `Authorization` headers.
+    S3Presigned {
+        /// The AWS region of the bucket, e.g. `us-west-1`.
+        region: String,
+
+        /// How long each presigned URL stays valid, in seconds.
+        #[serde(default = "default_presigned_url_expires_secs")]
+        expires_secs: u64,
+    },
+}
+
+fn default_aws_service() -> String {
+    "s3".to_owned()
+}
+
+fn default_presigned_url_expires_secs() -> u64 {
+    300
+}
+
 /// Sources configuration - this configures which sources are being watched.
 #[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Hash)]
 pub struct SourcesConfig {
@@ -178,4 +343,53 @@ mod tests {
 
         assert_eq!(config, expected_config);
     }
+
+    #[test]
+    fn sources_config_toml_with_auth() {
+        let input_toml: &str = r#"
+            [[sources]]
+            tx_source_url = "https://www.source.com/"
+            [sources.tx_source_auth]
+            type = "basic"
+            username = "watcher"
+            password_env = "MC_SOURCE_PASSWORD"
+
+            [[sources]]
+            tx_source_url = "https://s3-us-west-1.amazonaws.com/bucket/node2/"
+            [sources.tx_source_auth]
+            type = "aws_sig_v4"
+            region = "us-west-1"
+
+            [[sources]]
+            tx_source_url = "https://s3-us-west-1.amazonaws.com/bucket/node3/"
+            [sources.tx_source_auth]
+            type = "s3_presigned"
+            region = "us-west-1"
+            expires_secs = 60
+        "#;
+        let config: SourcesConfig = toml::from_str(input_toml).expect("failed parsing toml");
+
+        let auths = config
+            .sources()
+            .iter()
+            .map(|source| source.tx_source_auth().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            auths,
+            vec![
+                Some(TxSourceAuthConfig::Basic {
+                    username: "watcher".to_owned(),
+                    password_env: "MC_SOURCE_PASSWORD".to_owned(),
+                }),
+                Some(TxSourceAuthConfig::AwsSigV4 {
+                    region: "us-west-1".to_owned(),
+                    service: "s3".to_owned(),
+                }),
+                Some(TxSourceAuthConfig::S3Presigned {
+                    region: "us-west-1".to_owned(),
+                    expires_secs: 60,
+                }),
+            ]
+        );
+    }
 }
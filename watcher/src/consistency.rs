@@ -0,0 +1,129 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Cross-source block ID consistency checks.
+//!
+//! All watched tx sources archive the same blockchain, so for any given block
+//! index they must all agree on the block ID. A source that disagrees with the
+//! others is either corrupt or compromised, and should not be silently
+//! followed.
+
+use mc_blockchain_types::{BlockID, BlockIndex};
+use mc_common::HashMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use url::Url;
+
+/// By default, every this many blocks the watcher also fetches the same block
+/// index from the sources that already synced it but whose copy of it is not in
+/// the database, and compares block IDs. The copies in the database are
+/// compared on every block.
+pub const DEFAULT_BLOCK_ID_CHECK_INTERVAL: u64 = 100;
+
+/// A disagreement between tx sources about the ID of a given block.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BlockIdDivergence {
+    /// The block index the sources disagree on.
+    pub block_index: BlockIndex,
+
+    /// The block ID reported by each of the sources that were checked.
+    pub block_ids: HashMap<Url, BlockID>,
+}
+
+impl BlockIdDivergence {
+    /// Group the sources by the block ID they reported. The groups are sorted
+    /// by size (largest first), so the first group is the majority view.
+    pub fn sources_by_block_id(&self) -> Vec<(BlockID, Vec<Url>)> {
+        let mut groups: HashMap<BlockID, Vec<Url>> = HashMap::default();
+        for (url, block_id) in self.block_ids.iter() {
+            groups
+                .entry(block_id.clone())
+                .or_insert_with(Vec::new)
+                .push(url.clone());
+        }
+
+        let mut groups = groups.into_iter().collect::<Vec<_>>();
+        for (_block_id, urls) in groups.iter_mut() {
+            urls.sort();
+        }
+        groups.sort_by(|(id_a, urls_a), (id_b, urls_b)| {
+            urls_b
+                .len()
+                .cmp(&urls_a.len())
+                .then_with(|| id_a.0.cmp(&id_b.0))
+        });
+        groups
+    }
+}
+
+impl Display for BlockIdDivergence {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "block {} has diverging block ids:", self.block_index)?;
+        for (block_id, urls) in self.sources_by_block_id() {
+            let urls = urls.iter().map(Url::as_str).collect::<Vec<_>>();
+            write!(f, " {} from [{}];", block_id, urls.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare the block IDs reported by multiple sources for the same block
+/// index. Returns `None` if all sources agree (or if there is nothing to
+/// compare), and a [BlockIdDivergence] otherwise.
+pub fn check_block_ids(
+    block_index: BlockIndex,
+    block_ids: HashMap<Url, BlockID>,
+) -> Option<BlockIdDivergence> {
+    let mut ids = block_ids.values();
+    let first = ids.next()?;
+    if ids.all(|block_id| block_id == first) {
+        None
+    } else {
+        Some(BlockIdDivergence {
+            block_index,
+            block_ids,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(n: u8) -> Url {
+        Url::parse(&format!("http://www.my_url{}.com/", n)).unwrap()
+    }
+
+    #[test]
+    fn agreeing_sources_have_no_divergence() {
+        assert_eq!(check_block_ids(5, HashMap::default()), None);
+
+        let block_ids = HashMap::from_iter([(url(1), BlockID([1; 32]))]);
+        assert_eq!(check_block_ids(5, block_ids), None);
+
+        let block_ids = HashMap::from_iter([
+            (url(1), BlockID([1; 32])),
+            (url(2), BlockID([1; 32])),
+            (url(3), BlockID([1; 32])),
+        ]);
+        assert_eq!(check_block_ids(5, block_ids), None);
+    }
+
+    #[test]
+    fn disagreeing_sources_are_reported() {
+        let block_ids = HashMap::from_iter([
+            (url(1), BlockID([1; 32])),
+            (url(2), BlockID([2; 32])),
+            (url(3), BlockID([1; 32])),
+        ]);
+        let divergence = check_block_ids(5, block_ids.clone()).expect("expected divergence");
+        assert_eq!(divergence.block_index, 5);
+        assert_eq!(divergence.block_ids, block_ids);
+
+        assert_eq!(
+            divergence.sources_by_block_id(),
+            vec![
+                (BlockID([1; 32]), vec![url(1), url(3)]),
+                (BlockID([2; 32]), vec![url(2)]),
+            ]
+        );
+    }
+}
@@ -3,11 +3,13 @@
 //! Error data types
 
 use displaydoc::Display;
+use mc_blockchain_types::BlockIndex;
 use mc_connection::Error as ConnectionError;
 use mc_crypto_keys::KeyError;
 use mc_ledger_sync::ReqwestTransactionsFetcherError;
 use mc_util_lmdb::MetadataStoreError;
-use std::string::FromUtf8Error;
+use serde_json::Error as JsonError;
+use std::{path::PathBuf, string::FromUtf8Error};
 
 /// Watcher Errors
 #[derive(Debug, Display)]
@@ -26,6 +28,26 @@ pub enum WatcherError {
 
     /// Unknown tx source url: {0}
     UnknownTxSourceUrl(String),
+
+    /// Thread pool: {0}
+    ThreadPool(String),
+}
+
+impl WatcherError {
+    /// Whether the error is a tx source not serving the requested block, which
+    /// is expected for blocks that were not uploaded yet.
+    pub fn is_not_found(&self) -> bool {
+        matches!(
+            self,
+            Self::BlockFetch(ReqwestTransactionsFetcherError::NotFound(_))
+        )
+    }
+}
+
+impl From<rayon::ThreadPoolBuildError> for WatcherError {
+    fn from(src: rayon::ThreadPoolBuildError) -> Self {
+        Self::ThreadPool(src.to_string())
+    }
 }
 
 impl From<url::ParseError> for WatcherError {
@@ -90,6 +112,12 @@ pub enum WatcherDBError {
 
     /// Cryptographic key: {0}
     CryptoKey(KeyError),
+
+    /// Unsupported snapshot format version: {0}
+    UnsupportedSnapshotVersion(u32),
+
+    /// Snapshot record of {0} bytes exceeds the maximum record size
+    SnapshotRecordTooLarge(u64),
 }
 
 impl From<lmdb::Error> for WatcherDBError {
@@ -149,3 +177,123 @@ impl From<KeyError> for WatcherDBError {
         Self::CryptoKey(src)
     }
 }
+
+/// AVR history errors
+#[derive(Debug, Display)]
+pub enum AvrConfigError {
+    /// Unrecognized extension in '{0}'
+    UnrecognizedExtension(PathBuf),
+
+    /// I/O error: {0}
+    Io(std::io::Error),
+
+    /// Failed to parse TOML: {0}
+    FromToml(toml::de::Error),
+
+    /// Failed to serialize TOML: {0}
+    ToToml(toml::ser::Error),
+
+    /// JSON: {0}
+    Json(JsonError),
+
+    /// DB: {0}
+    DB(WatcherDBError),
+
+    /// Invalid AVR history: {0}
+    Validation(AvrValidationError),
+}
+
+impl From<std::io::Error> for AvrConfigError {
+    fn from(src: std::io::Error) -> Self {
+        Self::Io(src)
+    }
+}
+
+impl From<toml::de::Error> for AvrConfigError {
+    fn from(src: toml::de::Error) -> Self {
+        Self::FromToml(src)
+    }
+}
+
+impl From<toml::ser::Error> for AvrConfigError {
+    fn from(src: toml::ser::Error) -> Self {
+        Self::ToToml(src)
+    }
+}
+
+impl From<JsonError> for AvrConfigError {
+    fn from(src: JsonError) -> Self {
+        Self::Json(src)
+    }
+}
+
+impl From<WatcherDBError> for AvrConfigError {
+    fn from(src: WatcherDBError) -> Self {
+        Self::DB(src)
+    }
+}
+
+impl From<AvrValidationError> for AvrConfigError {
+    fn from(src: AvrValidationError) -> Self {
+        Self::Validation(src)
+    }
+}
+
+/// AVR history validation errors. The `usize` is the index of the offending
+/// record.
+#[derive(Clone, Debug, Display, Eq, PartialEq)]
+pub enum AvrValidationError {
+    /// Record {0}: invalid signer '{1}'
+    InvalidSigner(usize, String),
+
+    /// Record {0}: first block index {1} is greater than last block index {2}
+    InvalidRange(usize, BlockIndex, BlockIndex),
+
+    /// Record {0}: block range starts at {1}, but the previous record of the
+    /// same tx source ends at {2}
+    NotContiguous(usize, BlockIndex, BlockIndex),
+
+    /// Record {0}: could not get the block signer out of the verification
+    /// report: {1}
+    UnreadableReport(usize, String),
+
+    /// Record {0}: the verification report is for signer {1}
+    SignerMismatch(usize, String),
+}
+
+/// Attestation policy errors
+#[derive(Debug, Display)]
+pub enum AttestationPolicyError {
+    /// I/O error: {0}
+    Io(std::io::Error),
+
+    /// Failed to parse TOML: {0}
+    FromToml(toml::de::Error),
+
+    /// Invalid measurement '{0}'
+    InvalidMeasurement(String),
+
+    /// The policy does not trust any enclave
+    Empty,
+
+    /// DB: {0}
+    DB(WatcherDBError),
+}
+
+impl From<std::io::Error> for AttestationPolicyError {
+    fn from(src: std::io::Error) -> Self {
+        Self::Io(src)
+    }
+}
+
+impl From<toml::de::Error> for AttestationPolicyError {
+    fn from(src: toml::de::Error) -> Self {
+        Self::FromToml(src)
+    }
+}
+
+impl From<WatcherDBError> for AttestationPolicyError {
+    fn from(src: WatcherDBError) -> Self {
+        Self::DB(src)
+    }
+}
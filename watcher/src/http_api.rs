@@ -0,0 +1,40 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! HTTP server exposing the sync status of each watched tx source, for use by
+//! probes and dashboards.
+
+use crate::{
+    sync_status::{SourceSyncStatus, SyncStatusTracker},
+    watcher_db::WatcherDB,
+};
+use rocket::{get, routes, serde::json::Json, State};
+
+/// State managed by rocket.
+pub struct HttpApiState {
+    /// The watcher database, used for looking up the last synced blocks.
+    pub watcher_db: WatcherDB,
+
+    /// The sync status tracker the watcher reports fetch results to.
+    pub sync_status: SyncStatusTracker,
+}
+
+/// Get the sync status of every watched tx source.
+#[get("/sync_status")]
+fn get_sync_status(state: &State<HttpApiState>) -> Result<Json<Vec<SourceSyncStatus>>, String> {
+    state
+        .sync_status
+        .statuses(&state.watcher_db)
+        .map(Json)
+        .map_err(|err| err.to_string())
+}
+
+/// Returns an instance of a Rocket server.
+#[must_use = "Use with a Client or call launch"]
+pub fn initialize_rocket_server<T: rocket::figment::Provider>(
+    rocket_config: T,
+    state: HttpApiState,
+) -> rocket::Rocket<rocket::Build> {
+    rocket::custom(rocket_config)
+        .manage(state)
+        .mount("/", routes![get_sync_status])
+}
@@ -4,10 +4,23 @@
 #![deny(missing_docs)]
 #![forbid(unsafe_code)]
 
+pub mod alerts;
+pub mod archive_auth;
+pub mod attestation_revalidator;
+pub mod avr_config;
 pub mod block_data_store;
 pub mod config;
+pub mod consistency;
 pub mod error;
+pub mod http_api;
 pub mod metrics;
+pub mod provenance;
+pub mod rate_limiter;
+pub mod service;
+pub mod snapshot;
+pub mod sync_status;
+pub mod timestamp_policy;
 pub mod verification_reports_collector;
+pub mod verification_status;
 pub mod watcher;
 pub mod watcher_db;
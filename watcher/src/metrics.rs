@@ -3,7 +3,8 @@
 //! Watcher metrics comparing ledger height and block height
 
 use mc_common::HashMap;
-use mc_util_metrics::{IntGauge, OpMetrics};
+use mc_util_metrics::{Histogram, IntCounter, IntGauge, OpMetrics};
+use std::time::Duration;
 use url::Url;
 
 lazy_static::lazy_static! {
@@ -12,9 +13,22 @@ lazy_static::lazy_static! {
 }
 
 /// Watcher metrics tracker used to report metrics on watcher to Prometheus
+#[derive(Clone)]
 pub struct WatcherMetrics {
     /// Number of blocks in the ledger
     ledger_block_height: IntGauge,
+
+    /// Number of block ID divergences detected between tx sources
+    block_id_divergences: IntCounter,
+
+    /// Time taken to fetch an archive block, in seconds
+    archive_fetch_latency: Histogram,
+
+    /// Number of attempts to get a verification report from a node
+    verification_report_polls: IntCounter,
+
+    /// Number of failed attempts to get a verification report from a node
+    verification_report_poll_errors: IntCounter,
 }
 
 impl Default for WatcherMetrics {
@@ -27,8 +41,16 @@ impl WatcherMetrics {
     /// Initialize new metrics object
     pub fn new() -> Self {
         let ledger_block_height = COLLECTOR.gauge("ledger_block_height");
+        let block_id_divergences = COLLECTOR.counter("block_id_divergences");
+        let archive_fetch_latency = COLLECTOR.histogram("archive_fetch_latency");
+        let verification_report_polls = COLLECTOR.counter("verification_report_polls");
+        let verification_report_poll_errors = COLLECTOR.counter("verification_report_poll_errors");
         Self {
             ledger_block_height,
+            block_id_divergences,
+            archive_fetch_latency,
+            verification_report_polls,
+            verification_report_poll_errors,
         }
     }
 
@@ -37,6 +59,29 @@ impl WatcherMetrics {
         self.ledger_block_height.set(ledger_height);
     }
 
+    /// Record a block ID divergence between tx sources
+    pub fn inc_block_id_divergences(&self) {
+        self.block_id_divergences.inc();
+    }
+
+    /// Record an archive block fetch from a peer, and whether it failed
+    pub fn observe_archive_fetch(&self, url: &Url, latency: Duration, failed: bool) {
+        self.archive_fetch_latency.observe(latency.as_secs_f64());
+        if failed {
+            COLLECTOR
+                .peer_counter("watcher_fetch_errors", url.as_str())
+                .inc();
+        }
+    }
+
+    /// Record an attempt to get a verification report, and whether it failed
+    pub fn inc_verification_report_polls(&self, failed: bool) {
+        self.verification_report_polls.inc();
+        if failed {
+            self.verification_report_poll_errors.inc();
+        }
+    }
+
     /// Measure blocks synced so far for each peer
     pub fn collect_peer_blocks_synced(&self, peer_sync_states: HashMap<Url, Option<u64>>) {
         peer_sync_states.iter().for_each(|(url, num_blocks)| {
@@ -45,4 +90,26 @@ impl WatcherMetrics {
                 .set(num_blocks.unwrap_or(0) as i64);
         });
     }
+
+    /// Measure how many blocks each peer is behind the network tip. The tip is
+    /// the highest block known to the watcher: the last block of the ledger,
+    /// when provided, or the highest block any peer synced.
+    pub fn collect_peer_blocks_behind(
+        &self,
+        peer_sync_states: &HashMap<Url, Option<u64>>,
+        ledger_height: Option<u64>,
+    ) {
+        let tip = peer_sync_states
+            .values()
+            .flatten()
+            .copied()
+            .chain(ledger_height.map(|num_blocks| num_blocks.saturating_sub(1)))
+            .max()
+            .unwrap_or(0);
+        peer_sync_states.iter().for_each(|(url, last_synced)| {
+            COLLECTOR
+                .peer_gauge("watcher_blocks_behind", url.as_str())
+                .set(tip.saturating_sub(last_synced.unwrap_or(0)) as i64);
+        });
+    }
 }
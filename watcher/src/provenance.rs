@@ -0,0 +1,290 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Everything the watcher knows about the signatures of a single block,
+//! gathered in one place for incident investigations.
+
+use crate::{
+    error::WatcherDBError, verification_status::SignatureVerificationStatus, watcher_db::WatcherDB,
+};
+use mc_attest_core::{VerificationReport, VerificationReportData};
+use mc_blockchain_types::BlockIndex;
+use mc_util_repr_bytes::ReprBytes;
+use mc_watcher_api::TimestampResultCode;
+use serde::Serialize;
+use url::Url;
+
+/// The provenance of a block's signatures.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct BlockProvenance {
+    /// The block index.
+    pub block_index: BlockIndex,
+
+    /// The effective timestamp of the block, as returned by
+    /// [WatcherDB::get_block_timestamp].
+    pub timestamp: u64,
+
+    /// The result code of the effective timestamp lookup.
+    pub timestamp_result_code: TimestampResultCode,
+
+    /// The signatures of the block, one per tx source that provided one,
+    /// sorted by tx source url.
+    pub signatures: Vec<SignatureProvenance>,
+}
+
+/// The provenance of a single block signature.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct SignatureProvenance {
+    /// The tx source url the signature was obtained from.
+    pub src_url: String,
+
+    /// The archive filename the signature was obtained from.
+    pub archive_filename: String,
+
+    /// The hex-encoded signer public key.
+    pub signer: String,
+
+    /// The time at which the signer signed the block, as reported by the tx
+    /// source.
+    pub signed_at: u64,
+
+    /// The verification status of the signature, if one was recorded.
+    pub verification_status: Option<SignatureVerificationStatus>,
+
+    /// The verification reports collected for the signer from the node
+    /// behind the tx source.
+    pub verification_reports: Vec<ReportProvenance>,
+
+    /// Whether the signer's verification reports and the attestation evidence
+    /// in the block metadata from the tx source still pass the attestation
+    /// policy. `None` if none of them was re-validated yet.
+    pub attestation_passes: Option<bool>,
+
+    /// Why the attestation evidence no longer passes the attestation policy.
+    pub attestation_errors: Vec<String>,
+}
+
+/// A verification report collected for a block signer.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ReportProvenance {
+    /// The node was polled but did not produce a verification report for the
+    /// signer.
+    NoReport,
+
+    /// A verification report is available.
+    Available {
+        /// The IAS report id.
+        id: String,
+
+        /// The time IAS generated the report at.
+        timestamp: String,
+    },
+
+    /// A verification report is stored but could not be parsed.
+    Unreadable {
+        /// The parse error.
+        error: String,
+    },
+}
+
+impl From<&Option<VerificationReport>> for ReportProvenance {
+    fn from(src: &Option<VerificationReport>) -> Self {
+        match src {
+            None => Self::NoReport,
+            Some(report) => match VerificationReportData::try_from(report) {
+                Ok(report_data) => Self::Available {
+                    id: report_data.id,
+                    timestamp: report_data.timestamp,
+                },
+                Err(err) => Self::Unreadable {
+                    error: err.to_string(),
+                },
+            },
+        }
+    }
+}
+
+/// Gather the provenance of the signatures of a given block.
+pub fn get_block_provenance(
+    watcher_db: &WatcherDB,
+    block_index: BlockIndex,
+) -> Result<BlockProvenance, WatcherDBError> {
+    let (timestamp, timestamp_result_code) = watcher_db.get_block_timestamp(block_index)?;
+    let verification_statuses = watcher_db.get_signature_verification_statuses(block_index)?;
+    let block_metadata = watcher_db.get_block_metadata(block_index)?;
+
+    let mut signatures = watcher_db
+        .get_block_signatures(block_index)?
+        .into_iter()
+        .map(|signature_data| {
+            let src_url = Url::parse(&signature_data.src_url)?;
+            let signer = signature_data.block_signature.signer();
+            let reports =
+                watcher_db.get_verification_report_for_signer_and_url(signer, &src_url)?;
+            let verification_reports = reports.iter().map(ReportProvenance::from).collect();
+
+            let mut attestation_passes = None;
+            let mut attestation_errors = Vec::new();
+            let evidence = reports.iter().flatten().chain(
+                block_metadata
+                    .get(&src_url)
+                    .map(|metadata| metadata.contents().verification_report()),
+            );
+            for report in evidence {
+                if let Some(revalidation) = watcher_db.get_attestation_revalidation(report)? {
+                    attestation_passes =
+                        Some(attestation_passes.unwrap_or(true) && revalidation.passed());
+                    attestation_errors.extend(revalidation.error);
+                }
+            }
+
+            Ok(SignatureProvenance {
+                signer: hex::encode(signer.to_bytes()),
+                signed_at: signature_data.block_signature.signed_at(),
+                verification_status: verification_statuses.get(&src_url).copied(),
+                verification_reports,
+                attestation_passes,
+                attestation_errors,
+                archive_filename: signature_data.archive_filename,
+                src_url: signature_data.src_url,
+            })
+        })
+        .collect::<Result<Vec<_>, WatcherDBError>>()?;
+    signatures.sort_by(|a, b| a.src_url.cmp(&b.src_url));
+
+    Ok(BlockProvenance {
+        block_index,
+        timestamp,
+        timestamp_result_code,
+        signatures,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        attestation_revalidator::{revalidate_reports, AttestationPolicy, MrEnclavePolicy},
+        watcher_db::tests::{setup_blocks, setup_watcher_db},
+    };
+    use mc_blockchain_types::BlockSignature;
+    use mc_common::logger::{test_with_logger, Logger};
+    use mc_crypto_keys::Ed25519Pair;
+    use mc_util_from_random::FromRandom;
+    use mc_util_test_helper::run_with_one_seed;
+
+    #[test_with_logger]
+    fn test_get_block_provenance(logger: Logger) {
+        run_with_one_seed(|mut rng| {
+            let url1 = Url::parse("http://www.my_url1.com").unwrap();
+            let url2 = Url::parse("http://www.my_url2.com").unwrap();
+            let urls = [url1, url2];
+            let watcher_db = setup_watcher_db(&urls, logger.clone());
+            let blocks = setup_blocks();
+
+            let signing_key_a = Ed25519Pair::from_random(&mut rng);
+            let signing_key_b = Ed25519Pair::from_random(&mut rng);
+
+            // Insert the signatures out of url order.
+            let mut signature_b =
+                BlockSignature::from_block_and_keypair(blocks[1].block(), &signing_key_b).unwrap();
+            signature_b.set_signed_at(1594679727);
+            watcher_db
+                .add_block_signature(&urls[1], 1, signature_b.clone(), "00/01".to_owned())
+                .unwrap();
+
+            let mut signature_a =
+                BlockSignature::from_block_and_keypair(blocks[1].block(), &signing_key_a).unwrap();
+            signature_a.set_signed_at(1594679718);
+            watcher_db
+                .add_block_signature(&urls[0], 1, signature_a.clone(), "00/01".to_owned())
+                .unwrap();
+
+            // The node behind url1 produced a report for its signer, while the node
+            // behind url2 only had a report for a newer signer.
+            let verification_report = VerificationReport {
+                sig: vec![1u8; 32].into(),
+                chain: vec![vec![2; 16], vec![3; 32]],
+                http_body: "test body".to_owned(),
+            };
+            watcher_db
+                .add_verification_report(&urls[0], signature_a.signer(), &verification_report, &[])
+                .unwrap();
+            let signing_key_c = Ed25519Pair::from_random(&mut rng);
+            watcher_db
+                .add_verification_report(
+                    &urls[1],
+                    &signing_key_c.public_key(),
+                    &verification_report,
+                    &[*signature_b.signer()],
+                )
+                .unwrap();
+
+            let provenance = get_block_provenance(&watcher_db, 1).unwrap();
+            assert_eq!(provenance.block_index, 1);
+            assert_eq!(provenance.timestamp, 1594679718);
+            assert_eq!(
+                provenance.timestamp_result_code,
+                TimestampResultCode::TimestampFound
+            );
+            assert_eq!(provenance.signatures.len(), 2);
+
+            let provenance_a = &provenance.signatures[0];
+            assert_eq!(provenance_a.src_url, urls[0].as_str());
+            assert_eq!(
+                provenance_a.signer,
+                hex::encode(signature_a.signer().to_bytes())
+            );
+            assert_eq!(provenance_a.signed_at, 1594679718);
+            assert_eq!(
+                provenance_a.verification_status,
+                Some(SignatureVerificationStatus::Verified)
+            );
+            // The test report is not a real IAS report.
+            assert_eq!(provenance_a.verification_reports.len(), 1);
+            assert!(matches!(
+                provenance_a.verification_reports[0],
+                ReportProvenance::Unreadable { .. }
+            ));
+
+            let provenance_b = &provenance.signatures[1];
+            assert_eq!(provenance_b.src_url, urls[1].as_str());
+            assert_eq!(provenance_b.signed_at, 1594679727);
+            assert_eq!(
+                provenance_b.verification_status,
+                Some(SignatureVerificationStatus::NoVerificationReport)
+            );
+            assert_eq!(
+                provenance_b.verification_reports,
+                vec![ReportProvenance::NoReport]
+            );
+            assert_eq!(provenance_b.attestation_passes, None);
+
+            // Re-validation results flag the signature.
+            assert_eq!(provenance_a.attestation_passes, None);
+            let policy = AttestationPolicy {
+                mr_enclaves: vec![MrEnclavePolicy {
+                    mr_enclave: hex::encode([1u8; 32]),
+                    config_advisories: vec![],
+                    hardening_advisories: vec![],
+                }],
+                mr_signers: vec![],
+            };
+            // The test report is not signed by IAS, so it fails.
+            revalidate_reports(&watcher_db, &policy.verifier().unwrap(), 1234, &logger).unwrap();
+            let provenance = get_block_provenance(&watcher_db, 1).unwrap();
+            assert_eq!(provenance.signatures[0].attestation_passes, Some(false));
+            assert_eq!(provenance.signatures[0].attestation_errors.len(), 1);
+            // No evidence for the url2 signer.
+            assert_eq!(provenance.signatures[1].attestation_passes, None);
+
+            // A block without signatures has no provenance.
+            let provenance = get_block_provenance(&watcher_db, 2).unwrap();
+            assert!(provenance.signatures.is_empty());
+            assert_eq!(
+                provenance.timestamp_result_code,
+                TimestampResultCode::WatcherBehind
+            );
+        });
+    }
+}
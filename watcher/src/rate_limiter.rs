@@ -0,0 +1,77 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Per tx source request rate limiting.
+
+use mc_common::HashMap;
+use std::{
+    num::NonZeroU32,
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+use url::Url;
+
+/// Spaces out the requests made to each tx source, so that no source gets
+/// more than a given number of requests per second. Sources are limited
+/// independently of each other.
+#[derive(Debug)]
+pub struct SourceRateLimiter {
+    min_interval: Duration,
+    next_request: Mutex<HashMap<Url, Instant>>,
+}
+
+impl SourceRateLimiter {
+    /// Create a rate limiter allowing `max_requests_per_second` requests per
+    /// second to each source.
+    pub fn new(max_requests_per_second: NonZeroU32) -> Self {
+        Self {
+            min_interval: Duration::from_secs(1) / max_requests_per_second.get(),
+            next_request: Mutex::new(HashMap::default()),
+        }
+    }
+
+    /// Reserve the next request slot for `src_url`. Returns how long the
+    /// caller needs to wait before making the request.
+    pub fn reserve(&self, src_url: &Url) -> Duration {
+        let now = Instant::now();
+        let mut next_request = self.next_request.lock().expect("mutex poisoned");
+        let slot = next_request
+            .get(src_url)
+            .copied()
+            .filter(|slot| *slot > now)
+            .unwrap_or(now);
+        next_request.insert(src_url.clone(), slot + self.min_interval);
+        slot - now
+    }
+
+    /// Block until a request can be made to `src_url`.
+    pub fn wait(&self, src_url: &Url) {
+        let delay = self.reserve(src_url);
+        if !delay.is_zero() {
+            thread::sleep(delay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sources_are_limited_independently() {
+        let url1 = Url::parse("http://www.my_url1.com").unwrap();
+        let url2 = Url::parse("http://www.my_url2.com").unwrap();
+        let rate_limiter = SourceRateLimiter::new(NonZeroU32::new(10).unwrap());
+
+        assert_eq!(rate_limiter.reserve(&url1), Duration::ZERO);
+
+        // The next requests to the same source get spaced out by 100ms.
+        let delay = rate_limiter.reserve(&url1);
+        assert!(delay > Duration::from_millis(90) && delay <= Duration::from_millis(100));
+        let delay = rate_limiter.reserve(&url1);
+        assert!(delay > Duration::from_millis(190) && delay <= Duration::from_millis(200));
+
+        // Other sources are not affected.
+        assert_eq!(rate_limiter.reserve(&url2), Duration::ZERO);
+    }
+}
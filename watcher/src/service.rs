@@ -0,0 +1,395 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Watcher gRPC service implementation, allowing remote queries against a
+//! [WatcherDB].
+
+use crate::{verification_status::SignatureVerificationStatus, watcher_db::WatcherDB};
+use grpcio::{RpcContext, RpcStatus, Service, UnarySink};
+use mc_common::logger::Logger;
+use mc_crypto_keys::Ed25519Public;
+use mc_util_grpc::{rpc_database_err, rpc_invalid_arg_error, rpc_logger, send_result};
+use mc_watcher_grpc_api::{
+    blockchain, external, watcher,
+    watcher_api::{
+        BlockMetadataData, BlockSignatureData, GetBlockMetadataRequest, GetBlockMetadataResponse,
+        GetBlockSignaturesRequest, GetBlockSignaturesResponse, GetBlockTimestampRequest,
+        GetBlockTimestampResponse, GetVerificationReportsRequest, GetVerificationReportsResponse,
+        SignatureVerificationStatus as GrpcSignatureVerificationStatus, VerificationReportData,
+    },
+    watcher_api_grpc::{create_watcher_api, WatcherApi},
+};
+use url::Url;
+
+/// Watcher gRPC service implementation.
+#[derive(Clone)]
+pub struct WatcherService {
+    /// Watcher database.
+    watcher_db: WatcherDB,
+
+    /// Logger.
+    logger: Logger,
+}
+
+impl WatcherService {
+    /// Create a new watcher service.
+    pub fn new(watcher_db: WatcherDB, logger: Logger) -> Self {
+        Self { watcher_db, logger }
+    }
+
+    /// Convert into a grpc service
+    pub fn into_service(self) -> Service {
+        create_watcher_api(self)
+    }
+
+    fn get_block_timestamp_impl(
+        &self,
+        request: &GetBlockTimestampRequest,
+    ) -> Result<GetBlockTimestampResponse, RpcStatus> {
+        let (timestamp, result_code) = self
+            .watcher_db
+            .get_block_timestamp(request.block_index)
+            .map_err(|err| rpc_database_err(err, &self.logger))?;
+
+        let mut response = GetBlockTimestampResponse::new();
+        response.set_timestamp(timestamp);
+        response.set_result_code(watcher::TimestampResultCode::from(&result_code));
+        Ok(response)
+    }
+
+    fn get_block_signatures_impl(
+        &self,
+        request: &GetBlockSignaturesRequest,
+    ) -> Result<GetBlockSignaturesResponse, RpcStatus> {
+        let signatures = self
+            .watcher_db
+            .get_block_signatures(request.block_index)
+            .map_err(|err| rpc_database_err(err, &self.logger))?;
+        let statuses = self
+            .watcher_db
+            .get_signature_verification_statuses(request.block_index)
+            .map_err(|err| rpc_database_err(err, &self.logger))?;
+
+        let mut response = GetBlockSignaturesResponse::new();
+        for signature_data in signatures.iter() {
+            let mut signature_message = BlockSignatureData::new();
+            signature_message.set_src_url(signature_data.src_url.clone());
+            signature_message.set_archive_filename(signature_data.archive_filename.clone());
+            signature_message.set_block_signature(blockchain::BlockSignature::from(
+                &signature_data.block_signature,
+            ));
+            if let Some(status) = Url::parse(&signature_data.src_url)
+                .ok()
+                .and_then(|src_url| statuses.get(&src_url))
+            {
+                signature_message.set_verification_status(status.into());
+            }
+            response.mut_signatures().push(signature_message);
+        }
+        Ok(response)
+    }
+
+    fn get_verification_reports_impl(
+        &self,
+        request: &GetVerificationReportsRequest,
+    ) -> Result<GetVerificationReportsResponse, RpcStatus> {
+        let block_signer = Ed25519Public::try_from(request.get_block_signer())
+            .map_err(|err| rpc_invalid_arg_error("block_signer", err, &self.logger))?;
+
+        let reports = if request.src_url.is_empty() {
+            self.watcher_db
+                .get_verification_reports_for_signer(&block_signer)
+                .map_err(|err| rpc_database_err(err, &self.logger))?
+                .into_iter()
+                .collect::<Vec<_>>()
+        } else {
+            let src_url = Url::parse(&request.src_url)
+                .map_err(|err| rpc_invalid_arg_error("src_url", err, &self.logger))?;
+            let reports = self
+                .watcher_db
+                .get_verification_report_for_signer_and_url(&block_signer, &src_url)
+                .map_err(|err| rpc_database_err(err, &self.logger))?;
+            vec![(src_url, reports)]
+        };
+
+        let mut response = GetVerificationReportsResponse::new();
+        for (src_url, reports) in reports {
+            for report in reports {
+                let mut report_message = VerificationReportData::new();
+                report_message.set_src_url(src_url.to_string());
+                if let Some(report) = report.as_ref() {
+                    report_message.set_report(external::VerificationReport::from(report));
+                }
+                response.mut_reports().push(report_message);
+            }
+        }
+        Ok(response)
+    }
+
+    fn get_block_metadata_impl(
+        &self,
+        request: &GetBlockMetadataRequest,
+    ) -> Result<GetBlockMetadataResponse, RpcStatus> {
+        let mut metadata = self
+            .watcher_db
+            .get_block_metadata(request.block_index)
+            .map_err(|err| rpc_database_err(err, &self.logger))?
+            .into_iter()
+            .collect::<Vec<_>>();
+        metadata.sort_by(|(url_a, _), (url_b, _)| url_a.cmp(url_b));
+
+        let mut response = GetBlockMetadataResponse::new();
+        for (src_url, block_metadata) in metadata {
+            let mut metadata_message = BlockMetadataData::new();
+            metadata_message.set_src_url(src_url.to_string());
+            metadata_message.set_block_metadata(blockchain::BlockMetadata::from(&block_metadata));
+            response.mut_metadata().push(metadata_message);
+        }
+        Ok(response)
+    }
+}
+
+impl From<&SignatureVerificationStatus> for GrpcSignatureVerificationStatus {
+    fn from(src: &SignatureVerificationStatus) -> Self {
+        match src {
+            SignatureVerificationStatus::Pending => Self::Pending,
+            SignatureVerificationStatus::Verified => Self::Verified,
+            SignatureVerificationStatus::NoVerificationReport => Self::NoVerificationReport,
+            SignatureVerificationStatus::InvalidSignature => Self::InvalidSignature,
+            SignatureVerificationStatus::InvalidVerificationReport => {
+                Self::InvalidVerificationReport
+            }
+        }
+    }
+}
+
+impl WatcherApi for WatcherService {
+    fn get_block_timestamp(
+        &mut self,
+        ctx: RpcContext,
+        request: GetBlockTimestampRequest,
+        sink: UnarySink<GetBlockTimestampResponse>,
+    ) {
+        let logger = rpc_logger(&ctx, &self.logger);
+        send_result(ctx, sink, self.get_block_timestamp_impl(&request), &logger)
+    }
+
+    fn get_block_signatures(
+        &mut self,
+        ctx: RpcContext,
+        request: GetBlockSignaturesRequest,
+        sink: UnarySink<GetBlockSignaturesResponse>,
+    ) {
+        let logger = rpc_logger(&ctx, &self.logger);
+        send_result(ctx, sink, self.get_block_signatures_impl(&request), &logger)
+    }
+
+    fn get_verification_reports(
+        &mut self,
+        ctx: RpcContext,
+        request: GetVerificationReportsRequest,
+        sink: UnarySink<GetVerificationReportsResponse>,
+    ) {
+        let logger = rpc_logger(&ctx, &self.logger);
+        send_result(
+            ctx,
+            sink,
+            self.get_verification_reports_impl(&request),
+            &logger,
+        )
+    }
+
+    fn get_block_metadata(
+        &mut self,
+        ctx: RpcContext,
+        request: GetBlockMetadataRequest,
+        sink: UnarySink<GetBlockMetadataResponse>,
+    ) {
+        let logger = rpc_logger(&ctx, &self.logger);
+        send_result(ctx, sink, self.get_block_metadata_impl(&request), &logger)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::watcher_db::tests::{setup_blocks, setup_watcher_db};
+    use mc_blockchain_test_utils::make_block_metadata;
+    use mc_blockchain_types::{BlockMetadata, BlockSignature, VerificationReport};
+    use mc_common::logger::{test_with_logger, Logger};
+    use mc_crypto_keys::Ed25519Pair;
+    use mc_util_from_random::FromRandom;
+    use mc_util_test_helper::run_with_one_seed;
+
+    #[test_with_logger]
+    fn test_get_block_timestamp_and_signatures(logger: Logger) {
+        run_with_one_seed(|mut rng| {
+            let url1 = Url::parse("http://www.my_url1.com").unwrap();
+            let url2 = Url::parse("http://www.my_url2.com").unwrap();
+            let urls = [url1, url2];
+            let watcher_db = setup_watcher_db(&urls, logger.clone());
+            let service = WatcherService::new(watcher_db.clone(), logger.clone());
+
+            let blocks = setup_blocks();
+            let signing_key_a = Ed25519Pair::from_random(&mut rng);
+            let signing_key_b = Ed25519Pair::from_random(&mut rng);
+
+            let mut signed_block_a1 =
+                BlockSignature::from_block_and_keypair(blocks[1].block(), &signing_key_a).unwrap();
+            signed_block_a1.set_signed_at(1594679718);
+            watcher_db
+                .add_block_signature(&urls[0], 1, signed_block_a1.clone(), "00/01".to_owned())
+                .unwrap();
+
+            let mut signed_block_b1 =
+                BlockSignature::from_block_and_keypair(blocks[1].block(), &signing_key_b).unwrap();
+            signed_block_b1.set_signed_at(1594679727);
+            watcher_db
+                .add_block_signature(&urls[1], 1, signed_block_b1.clone(), "00/01".to_owned())
+                .unwrap();
+
+            let mut request = GetBlockTimestampRequest::new();
+            request.set_block_index(1);
+            let response = service.get_block_timestamp_impl(&request).unwrap();
+            assert_eq!(response.timestamp, 1594679718);
+            assert_eq!(
+                response.result_code,
+                watcher::TimestampResultCode::TimestampFound
+            );
+
+            request.set_block_index(2);
+            let response = service.get_block_timestamp_impl(&request).unwrap();
+            assert_eq!(response.timestamp, u64::MAX);
+            assert_eq!(
+                response.result_code,
+                watcher::TimestampResultCode::WatcherBehind
+            );
+
+            let mut request = GetBlockSignaturesRequest::new();
+            request.set_block_index(1);
+            let response = service.get_block_signatures_impl(&request).unwrap();
+            let mut signatures = response
+                .get_signatures()
+                .iter()
+                .map(|data| {
+                    (
+                        data.get_src_url().to_owned(),
+                        BlockSignature::try_from(data.get_block_signature()).unwrap(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            signatures.sort_by(|a, b| a.0.cmp(&b.0));
+            assert!(response
+                .get_signatures()
+                .iter()
+                .all(|data| data.verification_status == GrpcSignatureVerificationStatus::Pending));
+            assert_eq!(
+                signatures,
+                vec![
+                    (urls[0].to_string(), signed_block_a1),
+                    (urls[1].to_string(), signed_block_b1),
+                ]
+            );
+
+            request.set_block_index(2);
+            let response = service.get_block_signatures_impl(&request).unwrap();
+            assert!(response.get_signatures().is_empty());
+        });
+    }
+
+    #[test_with_logger]
+    fn test_get_verification_reports(logger: Logger) {
+        run_with_one_seed(|mut rng| {
+            let url1 = Url::parse("http://www.my_url1.com").unwrap();
+            let url2 = Url::parse("http://www.my_url2.com").unwrap();
+            let urls = [url1.clone(), url2.clone()];
+            let watcher_db = setup_watcher_db(&urls, logger.clone());
+            let service = WatcherService::new(watcher_db.clone(), logger.clone());
+
+            let signing_key_a = Ed25519Pair::from_random(&mut rng).public_key();
+            let signing_key_b = Ed25519Pair::from_random(&mut rng).public_key();
+
+            let verification_report_a = VerificationReport {
+                sig: vec![1u8; 32].into(),
+                chain: vec![vec![2; 16], vec![3; 32]],
+                http_body: "test body a".to_owned(),
+            };
+
+            watcher_db
+                .add_verification_report(
+                    &url1,
+                    &signing_key_a,
+                    &verification_report_a,
+                    &[signing_key_b],
+                )
+                .unwrap();
+
+            let mut request = GetVerificationReportsRequest::new();
+            request.set_block_signer((&signing_key_a).into());
+            let response = service.get_verification_reports_impl(&request).unwrap();
+            assert_eq!(response.get_reports().len(), 1);
+            assert_eq!(response.get_reports()[0].get_src_url(), url1.as_str());
+            assert_eq!(
+                VerificationReport::from(response.get_reports()[0].get_report()),
+                verification_report_a
+            );
+
+            // signing_key_b was marked as having no report.
+            request.set_block_signer((&signing_key_b).into());
+            let response = service.get_verification_reports_impl(&request).unwrap();
+            assert_eq!(response.get_reports().len(), 1);
+            assert_eq!(response.get_reports()[0].get_src_url(), url1.as_str());
+            assert!(!response.get_reports()[0].has_report());
+
+            // Filtering by a url with no data returns nothing.
+            request.set_block_signer((&signing_key_a).into());
+            request.set_src_url(url2.to_string());
+            let response = service.get_verification_reports_impl(&request).unwrap();
+            assert!(response.get_reports().is_empty());
+
+            // Invalid arguments are rejected.
+            request.set_src_url("not a url".to_owned());
+            assert!(service.get_verification_reports_impl(&request).is_err());
+            request.set_src_url(String::new());
+            request.mut_block_signer().set_data(vec![1, 2, 3]);
+            assert!(service.get_verification_reports_impl(&request).is_err());
+        });
+    }
+
+    #[test_with_logger]
+    fn test_get_block_metadata(logger: Logger) {
+        run_with_one_seed(|mut rng| {
+            let url1 = Url::parse("http://www.my_url1.com").unwrap();
+            let url2 = Url::parse("http://www.my_url2.com").unwrap();
+            let watcher_db = setup_watcher_db(&[url1.clone(), url2.clone()], logger.clone());
+            let service = WatcherService::new(watcher_db.clone(), logger.clone());
+            let blocks = setup_blocks();
+
+            let metadata1 = make_block_metadata(blocks[1].block().id.clone(), &mut rng);
+            let metadata2 = make_block_metadata(blocks[1].block().id.clone(), &mut rng);
+            watcher_db.add_block_metadata(&url2, 1, &metadata2).unwrap();
+            watcher_db.add_block_metadata(&url1, 1, &metadata1).unwrap();
+
+            let mut request = GetBlockMetadataRequest::new();
+            request.set_block_index(1);
+            let response = service.get_block_metadata_impl(&request).unwrap();
+            let metadata = response
+                .get_metadata()
+                .iter()
+                .map(|data| {
+                    (
+                        data.get_src_url().to_owned(),
+                        BlockMetadata::try_from(data.get_block_metadata()).unwrap(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                metadata,
+                vec![(url1.to_string(), metadata1), (url2.to_string(), metadata2)]
+            );
+
+            request.set_block_index(2);
+            let response = service.get_block_metadata_impl(&request).unwrap();
+            assert!(response.get_metadata().is_empty());
+        });
+    }
+}
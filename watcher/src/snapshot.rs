@@ -0,0 +1,256 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Portable WatcherDB snapshots.
+//!
+//! A snapshot holds the block signatures (and therefore the timestamps),
+//! block metadata, verification reports and sync progress of a [WatcherDB],
+//! so that a new watcher can be seeded without re-polling the entire history.
+//! It is a stream of length-delimited protobuf [SnapshotRecord]s: a header,
+//! then all verification reports, then all block signatures, then all block
+//! metadata, then the sync progress of each tx source url.
+//!
+//! [WatcherDB]: crate::watcher_db::WatcherDB
+
+use crate::error::WatcherDBError;
+use mc_blockchain_types::{BlockMetadata, BlockSignature, VerificationReport};
+use prost::{Message, Oneof};
+use std::io::{ErrorKind, Read, Write};
+
+/// The current snapshot format version. Snapshots of other versions are
+/// rejected on import.
+///
+/// Version 2 added block metadata, missing blocks and signature verification
+/// statuses.
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 2;
+
+/// The maximum length of a single encoded record. Records are small, the
+/// largest being block metadata with its verification report, so anything
+/// bigger is a corrupt stream rather than something to allocate for.
+pub const MAX_SNAPSHOT_RECORD_LEN: u64 = 16 * 1024 * 1024;
+
+/// The first record of a snapshot.
+#[derive(Clone, Eq, Message, PartialEq)]
+pub struct SnapshotHeader {
+    /// The snapshot format version.
+    #[prost(uint32, tag = 1)]
+    pub format_version: u32,
+
+    /// The tx source urls the exported database was configured with.
+    #[prost(string, repeated, tag = 2)]
+    pub tx_source_urls: Vec<String>,
+}
+
+/// A verification report (or the lack of one) for a block signer.
+#[derive(Clone, Eq, Message, PartialEq)]
+pub struct SnapshotVerificationReport {
+    /// The tx source url the report was obtained from.
+    #[prost(string, tag = 1)]
+    pub src_url: String,
+
+    /// The block signer public key bytes.
+    #[prost(bytes, tag = 2)]
+    pub block_signer: Vec<u8>,
+
+    /// The report, unset if the node was polled but did not produce one.
+    #[prost(message, optional, tag = 3)]
+    pub report: Option<VerificationReport>,
+}
+
+/// A block signature.
+#[derive(Clone, Eq, Message, PartialEq)]
+pub struct SnapshotBlockSignature {
+    /// The block index.
+    #[prost(uint64, tag = 1)]
+    pub block_index: u64,
+
+    /// The tx source url the signature was obtained from.
+    #[prost(string, tag = 2)]
+    pub src_url: String,
+
+    /// The archive filename.
+    #[prost(string, tag = 3)]
+    pub archive_filename: String,
+
+    /// The block signature.
+    #[prost(message, required, tag = 4)]
+    pub block_signature: BlockSignature,
+
+    /// The verification status of the signature, as a
+    /// [SignatureVerificationStatus] value.
+    ///
+    /// [SignatureVerificationStatus]: crate::verification_status::SignatureVerificationStatus
+    #[prost(uint32, tag = 5)]
+    pub verification_status: u32,
+}
+
+/// The metadata of a block.
+#[derive(Clone, Eq, Message, PartialEq)]
+pub struct SnapshotBlockMetadata {
+    /// The block index.
+    #[prost(uint64, tag = 1)]
+    pub block_index: u64,
+
+    /// The tx source url the metadata was obtained from.
+    #[prost(string, tag = 2)]
+    pub src_url: String,
+
+    /// The block metadata.
+    #[prost(message, required, tag = 3)]
+    pub block_metadata: BlockMetadata,
+}
+
+/// The sync progress of a tx source url.
+#[derive(Clone, Eq, Message, PartialEq)]
+pub struct SnapshotLastSynced {
+    /// The tx source url.
+    #[prost(string, tag = 1)]
+    pub src_url: String,
+
+    /// The last synced block index, if any.
+    #[prost(uint64, optional, tag = 2)]
+    pub block_index: Option<u64>,
+
+    /// Blocks that are missing from the archive of the tx source url.
+    #[prost(uint64, repeated, tag = 3)]
+    pub missing_blocks: Vec<u64>,
+}
+
+/// The contents of a [SnapshotRecord].
+#[derive(Clone, Eq, Oneof, PartialEq)]
+pub enum SnapshotRecordKind {
+    /// See [SnapshotHeader].
+    #[prost(message, tag = 1)]
+    Header(SnapshotHeader),
+
+    /// See [SnapshotVerificationReport].
+    #[prost(message, tag = 2)]
+    VerificationReport(SnapshotVerificationReport),
+
+    /// See [SnapshotBlockSignature].
+    #[prost(message, tag = 3)]
+    BlockSignature(SnapshotBlockSignature),
+
+    /// See [SnapshotLastSynced].
+    #[prost(message, tag = 4)]
+    LastSynced(SnapshotLastSynced),
+
+    /// See [SnapshotBlockMetadata].
+    #[prost(message, tag = 5)]
+    BlockMetadata(SnapshotBlockMetadata),
+}
+
+/// A single snapshot record.
+#[derive(Clone, Eq, Message, PartialEq)]
+pub struct SnapshotRecord {
+    /// The record contents.
+    #[prost(oneof = "SnapshotRecordKind", tags = "1, 2, 3, 4, 5")]
+    pub kind: Option<SnapshotRecordKind>,
+}
+
+impl From<SnapshotRecordKind> for SnapshotRecord {
+    fn from(kind: SnapshotRecordKind) -> Self {
+        Self { kind: Some(kind) }
+    }
+}
+
+/// The number of entries exported or imported.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SnapshotSummary {
+    /// Number of verification report entries.
+    pub num_verification_reports: u64,
+
+    /// Number of block signatures.
+    pub num_block_signatures: u64,
+
+    /// Number of block metadata entries.
+    pub num_block_metadata: u64,
+}
+
+/// Write a single length-delimited record.
+pub fn write_record(
+    writer: &mut impl Write,
+    record: impl Into<SnapshotRecord>,
+) -> Result<(), WatcherDBError> {
+    writer.write_all(&record.into().encode_length_delimited_to_vec())?;
+    Ok(())
+}
+
+/// Read a single length-delimited record. Returns `None` once the end of the
+/// stream is reached.
+pub fn read_record(reader: &mut impl Read) -> Result<Option<SnapshotRecord>, WatcherDBError> {
+    // Read the varint length prefix.
+    let mut len: u64 = 0;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0u8];
+        match reader.read_exact(&mut byte) {
+            Ok(()) => {}
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof && shift == 0 => return Ok(None),
+            Err(err) => return Err(err.into()),
+        }
+        if shift >= 64 {
+            return Err(WatcherDBError::Deserialization);
+        }
+        len |= u64::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+    }
+
+    if len > MAX_SNAPSHOT_RECORD_LEN {
+        return Err(WatcherDBError::SnapshotRecordTooLarge(len));
+    }
+    let mut bytes = vec![0u8; len as usize];
+    reader.read_exact(&mut bytes)?;
+    Ok(Some(SnapshotRecord::decode(&bytes[..])?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_round_trip() {
+        let records: Vec<SnapshotRecord> = vec![
+            SnapshotRecordKind::Header(SnapshotHeader {
+                format_version: SNAPSHOT_FORMAT_VERSION,
+                tx_source_urls: vec!["http://www.my_url1.com/".to_owned()],
+            })
+            .into(),
+            SnapshotRecordKind::LastSynced(SnapshotLastSynced {
+                src_url: "http://www.my_url1.com/".to_owned(),
+                block_index: Some(300),
+                missing_blocks: vec![12, 15],
+            })
+            .into(),
+        ];
+
+        let mut bytes = Vec::new();
+        for record in records.iter() {
+            write_record(&mut bytes, record.clone()).unwrap();
+        }
+
+        let mut reader = &bytes[..];
+        let mut decoded = Vec::new();
+        while let Some(record) = read_record(&mut reader).unwrap() {
+            decoded.push(record);
+        }
+        assert_eq!(decoded, records);
+
+        // A truncated stream is an error.
+        let mut reader = &bytes[..bytes.len() - 1];
+        assert!(read_record(&mut reader).is_ok());
+        assert!(read_record(&mut reader).is_err());
+    }
+
+    #[test]
+    fn oversized_records_are_rejected() {
+        // A length prefix of u64::MAX, with no record behind it.
+        let bytes = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+        assert_eq!(
+            read_record(&mut &bytes[..]),
+            Err(WatcherDBError::SnapshotRecordTooLarge(u64::MAX))
+        );
+    }
+}
@@ -0,0 +1,141 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Per tx source sync status tracking.
+
+use crate::{error::WatcherDBError, watcher_db::WatcherDB};
+use mc_blockchain_types::BlockIndex;
+use mc_common::HashMap;
+use serde::Serialize;
+use std::{
+    fmt::Display,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use url::Url;
+
+/// The sync status of a single tx source.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct SourceSyncStatus {
+    /// The tx source url.
+    pub tx_src_url: String,
+
+    /// The highest block index synced from this source, if any.
+    pub highest_synced_block: Option<BlockIndex>,
+
+    /// When a block was last fetched successfully from this source, in
+    /// seconds since the Unix epoch.
+    pub last_successful_poll: Option<u64>,
+
+    /// The last error encountered while fetching from this source.
+    pub last_error: Option<String>,
+
+    /// When `last_error` happened, in seconds since the Unix epoch.
+    pub last_error_time: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct PollStatus {
+    last_successful_poll: Option<u64>,
+    last_error: Option<(String, u64)>,
+}
+
+/// Records the outcome of block fetches, per tx source url. Clones share the
+/// same underlying state.
+#[derive(Clone, Debug, Default)]
+pub struct SyncStatusTracker {
+    poll_statuses: Arc<Mutex<HashMap<Url, PollStatus>>>,
+}
+
+impl SyncStatusTracker {
+    /// Record a successful block fetch from `src_url`. This clears the last
+    /// error of the source.
+    pub fn record_success(&self, src_url: &Url) {
+        let mut poll_statuses = self.poll_statuses.lock().expect("mutex poisoned");
+        let poll_status = poll_statuses.entry(src_url.clone()).or_default();
+        poll_status.last_successful_poll = Some(now());
+        poll_status.last_error = None;
+    }
+
+    /// Record a failed block fetch from `src_url`. Blocks that are not found
+    /// should not be recorded, since sources are polled past their last block.
+    pub fn record_error(&self, src_url: &Url, err: impl Display) {
+        let mut poll_statuses = self.poll_statuses.lock().expect("mutex poisoned");
+        poll_statuses.entry(src_url.clone()).or_default().last_error =
+            Some((err.to_string(), now()));
+    }
+
+    /// Get the sync status of every tx source configured in `watcher_db`,
+    /// sorted by url.
+    pub fn statuses(
+        &self,
+        watcher_db: &WatcherDB,
+    ) -> Result<Vec<SourceSyncStatus>, WatcherDBError> {
+        let last_synced_blocks = watcher_db.last_synced_blocks()?;
+        let poll_statuses = self.poll_statuses.lock().expect("mutex poisoned");
+
+        let mut statuses = watcher_db
+            .get_config_urls()?
+            .into_iter()
+            .map(|src_url| {
+                let poll_status = poll_statuses.get(&src_url).cloned().unwrap_or_default();
+                let (last_error, last_error_time) = match poll_status.last_error {
+                    Some((err, time)) => (Some(err), Some(time)),
+                    None => (None, None),
+                };
+                SourceSyncStatus {
+                    highest_synced_block: last_synced_blocks.get(&src_url).copied().flatten(),
+                    tx_src_url: src_url.to_string(),
+                    last_successful_poll: poll_status.last_successful_poll,
+                    last_error,
+                    last_error_time,
+                }
+            })
+            .collect::<Vec<_>>();
+        statuses.sort_by(|a, b| a.tx_src_url.cmp(&b.tx_src_url));
+        Ok(statuses)
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::watcher_db::tests::setup_watcher_db;
+    use mc_common::logger::{test_with_logger, Logger};
+
+    #[test_with_logger]
+    fn tracks_each_source(logger: Logger) {
+        let url1 = Url::parse("http://www.my_url1.com").unwrap();
+        let url2 = Url::parse("http://www.my_url2.com").unwrap();
+        let watcher_db = setup_watcher_db(&[url1.clone(), url2.clone()], logger);
+        let tracker = SyncStatusTracker::default();
+
+        watcher_db.update_last_synced(&url1, 5).unwrap();
+        tracker.record_error(&url1, "connection refused");
+        tracker.record_success(&url1);
+        tracker.record_error(&url2, "connection refused");
+
+        let statuses = tracker.statuses(&watcher_db).unwrap();
+        assert_eq!(statuses.len(), 2);
+
+        assert_eq!(statuses[0].tx_src_url, url1.to_string());
+        assert_eq!(statuses[0].highest_synced_block, Some(5));
+        assert!(statuses[0].last_successful_poll.is_some());
+        assert_eq!(statuses[0].last_error, None);
+
+        assert_eq!(statuses[1].tx_src_url, url2.to_string());
+        assert_eq!(statuses[1].highest_synced_block, None);
+        assert_eq!(statuses[1].last_successful_poll, None);
+        assert_eq!(
+            statuses[1].last_error,
+            Some("connection refused".to_owned())
+        );
+        assert!(statuses[1].last_error_time.is_some());
+    }
+}
@@ -0,0 +1,128 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Policies for deriving the timestamp of a block from the signatures
+//! collected from each tx source.
+//!
+//! Every signature carries the time at which its node signed the block, and
+//! the nodes don't necessarily agree. Consumers with different trust
+//! assumptions can pick the policy that fits them, or implement their own, and
+//! pass it to [WatcherDB::get_block_timestamp_with_policy].
+//!
+//! [WatcherDB::get_block_timestamp_with_policy]:
+//! crate::watcher_db::WatcherDB::get_block_timestamp_with_policy
+
+use mc_common::{HashMap, HashSet};
+use url::Url;
+
+/// Derives the timestamp of a block from the `signed_at` timestamp reported
+/// by each tx source.
+pub trait TimestampPolicy {
+    /// Select the timestamp given the timestamps of all the tx sources that
+    /// provided a signature for the block. Returns `None` if the timestamps do
+    /// not satisfy the policy.
+    fn select_timestamp(&self, timestamps: &HashMap<Url, u64>) -> Option<u64>;
+}
+
+/// The earliest timestamp of all tx sources. This is what
+/// [WatcherDB::get_block_timestamp](crate::watcher_db::WatcherDB::
+/// get_block_timestamp) uses.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MinimumTimestamp;
+
+impl TimestampPolicy for MinimumTimestamp {
+    fn select_timestamp(&self, timestamps: &HashMap<Url, u64>) -> Option<u64> {
+        timestamps.values().min().copied()
+    }
+}
+
+/// The median timestamp, once at least `k` tx sources provided one. With an
+/// even number of timestamps the lower median is used, so that the result is
+/// always a timestamp reported by some source.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MedianOfK {
+    /// The minimal number of timestamps.
+    pub k: usize,
+}
+
+impl TimestampPolicy for MedianOfK {
+    fn select_timestamp(&self, timestamps: &HashMap<Url, u64>) -> Option<u64> {
+        if timestamps.is_empty() || timestamps.len() < self.k {
+            return None;
+        }
+        let mut values = timestamps.values().copied().collect::<Vec<_>>();
+        values.sort_unstable();
+        Some(values[(values.len() - 1) / 2])
+    }
+}
+
+/// The earliest timestamp among a trusted subset of the tx sources.
+/// Timestamps from other sources are ignored.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TrustedSources {
+    /// The trusted tx source urls.
+    pub src_urls: HashSet<Url>,
+}
+
+impl TimestampPolicy for TrustedSources {
+    fn select_timestamp(&self, timestamps: &HashMap<Url, u64>) -> Option<u64> {
+        timestamps
+            .iter()
+            .filter(|(src_url, _timestamp)| self.src_urls.contains(*src_url))
+            .map(|(_src_url, timestamp)| *timestamp)
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(n: u8) -> Url {
+        Url::parse(&format!("http://www.my_url{}.com/", n)).unwrap()
+    }
+
+    fn timestamps(values: &[u64]) -> HashMap<Url, u64> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| (url(i as u8), *value))
+            .collect()
+    }
+
+    #[test]
+    fn minimum() {
+        assert_eq!(MinimumTimestamp.select_timestamp(&timestamps(&[])), None);
+        assert_eq!(
+            MinimumTimestamp.select_timestamp(&timestamps(&[30, 10, 20])),
+            Some(10)
+        );
+    }
+
+    #[test]
+    fn median_of_k() {
+        let policy = MedianOfK { k: 3 };
+        assert_eq!(policy.select_timestamp(&timestamps(&[10, 20])), None);
+        assert_eq!(
+            policy.select_timestamp(&timestamps(&[30, 10, 20])),
+            Some(20)
+        );
+        assert_eq!(
+            policy.select_timestamp(&timestamps(&[40, 30, 10, 20])),
+            Some(20)
+        );
+
+        assert_eq!(MedianOfK { k: 0 }.select_timestamp(&timestamps(&[])), None);
+    }
+
+    #[test]
+    fn trusted_sources() {
+        let policy = TrustedSources {
+            src_urls: HashSet::from_iter([url(1), url(2)]),
+        };
+        assert_eq!(policy.select_timestamp(&timestamps(&[10])), None);
+        assert_eq!(
+            policy.select_timestamp(&timestamps(&[10, 30, 20])),
+            Some(20)
+        );
+    }
+}
@@ -2,7 +2,13 @@
 
 //! Worker thread for collecting verification reports from nodes.
 
-use crate::{config::SourceConfig, watcher_db::WatcherDB};
+use crate::{
+    alerts::{Alert, AlertNotifier},
+    config::SourceConfig,
+    metrics::WatcherMetrics,
+    verification_status::block_signer_from_report_data,
+    watcher_db::WatcherDB,
+};
 use grpcio::Environment;
 use mc_attest_core::{VerificationReport, VerificationReportData};
 use mc_attest_verifier::Verifier;
@@ -97,28 +103,7 @@ impl NodeClient for ConsensusNodeClient {
     fn get_block_signer(verification_report: &VerificationReport) -> Result<Ed25519Public, String> {
         let report_data = VerificationReportData::try_from(verification_report)
             .map_err(|err| format!("Failed constructing VerificationReportData: {}", err))?;
-
-        let report_body = report_data
-            .quote
-            .report_body()
-            .map_err(|err| format!("Failed getting report body: {}", err))?;
-
-        let custom_data = report_body.report_data();
-        let custom_data_bytes: &[u8] = custom_data.as_ref();
-
-        if custom_data_bytes.len() != 64 {
-            return Err(format!(
-                "Unspected report data length: expected 64, got {}",
-                custom_data_bytes.len()
-            ));
-        }
-
-        let signer_bytes = &custom_data_bytes[32..];
-
-        let signer_public_key = Ed25519Public::try_from(signer_bytes)
-            .map_err(|err| format!("Unable to construct key: {}", err))?;
-
-        Ok(signer_public_key)
+        block_signer_from_report_data(&report_data)
     }
 }
 
@@ -132,10 +117,13 @@ pub struct VerificationReportsCollector<NC: NodeClient = ConsensusNodeClient> {
 
 impl<NC: NodeClient> VerificationReportsCollector<NC> {
     /// Create a new verification reports collector thread.
+    /// `alert_notifier` gets notified of block signers that the verification
+    /// report of their node does not vouch for.
     pub fn new(
         watcher_db: WatcherDB,
         sources: Vec<SourceConfig>,
         poll_interval: Duration,
+        alert_notifier: Option<AlertNotifier>,
         logger: Logger,
     ) -> Self {
         let stop_requested = Arc::new(AtomicBool::new(false));
@@ -149,6 +137,7 @@ impl<NC: NodeClient> VerificationReportsCollector<NC> {
                         watcher_db,
                         sources,
                         poll_interval,
+                        alert_notifier,
                         logger,
                         thread_stop_requested,
                     );
@@ -184,9 +173,11 @@ struct VerificationReportsCollectorThread<NC: NodeClient> {
     watcher_db: WatcherDB,
     sources: Vec<SourceConfig>,
     poll_interval: Duration,
+    alert_notifier: Option<AlertNotifier>,
     logger: Logger,
     stop_requested: Arc<AtomicBool>,
     grpcio_env: Arc<Environment>,
+    metrics: WatcherMetrics,
     _nc: PhantomData<NC>,
 }
 
@@ -195,6 +186,7 @@ impl<NC: NodeClient> VerificationReportsCollectorThread<NC> {
         watcher_db: WatcherDB,
         sources: Vec<SourceConfig>,
         poll_interval: Duration,
+        alert_notifier: Option<AlertNotifier>,
         logger: Logger,
         stop_requested: Arc<AtomicBool>,
     ) -> Self {
@@ -208,9 +200,11 @@ impl<NC: NodeClient> VerificationReportsCollectorThread<NC> {
             watcher_db,
             sources,
             poll_interval,
+            alert_notifier,
             logger,
             stop_requested,
             grpcio_env,
+            metrics: WatcherMetrics::new(),
             _nc: Default::default(),
         }
     }
@@ -282,8 +276,12 @@ impl<NC: NodeClient> VerificationReportsCollectorThread<NC> {
                 self.grpcio_env.clone(),
                 self.logger.clone(),
             ) {
-                Ok(report) => report,
+                Ok(report) => {
+                    self.metrics.inc_verification_report_polls(false);
+                    report
+                }
                 Err(err) => {
+                    self.metrics.inc_verification_report_polls(true);
                     log::error!(
                         self.logger,
                         "Failed getting report for {}: {}",
@@ -346,6 +344,29 @@ impl<NC: NodeClient> VerificationReportsCollectorThread<NC> {
                     tx_src_url,
                     hex::encode(verification_report_block_signer.to_bytes())
                 );
+
+                // Any other signer we were polling for signed blocks without the node
+                // attesting to it.
+                for signer in potential_signers {
+                    if signer == &verification_report_block_signer
+                        || self.is_known_signer(tx_src_url, signer)
+                    {
+                        continue;
+                    }
+                    log::warn!(
+                        self.logger,
+                        "Blocks from {} were signed by {}, which is not the block signer attested by the node",
+                        tx_src_url,
+                        hex::encode(signer.to_bytes()),
+                    );
+                    if let Some(alert_notifier) = self.alert_notifier.as_ref() {
+                        alert_notifier.notify(Alert::unknown_signer(
+                            tx_src_url,
+                            signer,
+                            &verification_report_block_signer,
+                        ));
+                    }
+                }
             }
             Err(err) => {
                 log::error!(
@@ -359,6 +380,38 @@ impl<NC: NodeClient> VerificationReportsCollectorThread<NC> {
             }
         }
     }
+
+    /// Whether a block signer is known to belong to the node of `tx_src_url`,
+    /// although the verification report just collected from the node is for
+    /// another signer. That is the case of signers listed in the source
+    /// config, and of signers the watcher collected a verification report for
+    /// from the same source earlier, e.g. before a key rotation or a restart of
+    /// the watcher.
+    fn is_known_signer(&self, tx_src_url: &Url, signer: &Ed25519Public) -> bool {
+        let in_config = self
+            .sources
+            .iter()
+            .filter(|source| &source.tx_source_url() == tx_src_url)
+            .any(|source| source.known_block_signers().contains(signer));
+        if in_config {
+            return true;
+        }
+
+        match self.watcher_db.get_verification_reports_for_signer(signer) {
+            Ok(reports) => reports
+                .get(tx_src_url)
+                .map_or(false, |reports| reports.iter().any(Option::is_some)),
+            Err(err) => {
+                log::error!(
+                    self.logger,
+                    "Failed getting verification reports for {}: {}",
+                    hex::encode(signer.to_bytes()),
+                    err
+                );
+                false
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -463,6 +516,7 @@ mod tests {
             watcher_db.clone(),
             sources,
             Duration::from_millis(100),
+            None,
             logger,
         );
 
@@ -665,4 +719,58 @@ mod tests {
             sleep(Duration::from_millis(100));
         }
     }
+
+    // Signers with a verification report in the database, e.g. collected before
+    // the watcher restarted, and signers listed in the config are known to the
+    // collector of a fresh process.
+    #[test_with_logger]
+    #[serial]
+    fn test_known_signers_survive_restarts(logger: Logger) {
+        TestNodeClient::reset();
+
+        let tx_src_url1 = Url::parse("http://www.my_url1.com").unwrap();
+        let tx_src_url2 = Url::parse("http://www.my_url2.com").unwrap();
+        let watcher_db =
+            setup_watcher_db(&[tx_src_url1.clone(), tx_src_url2.clone()], logger.clone());
+
+        let node1_url = ConsensusClientUri::from_str("mc://node1.test.com:443/").unwrap();
+        let node2_url = ConsensusClientUri::from_str("mc://node2.test.com:443/").unwrap();
+        let old_signer = TestNodeClient::current_signer(&node1_url).public_key();
+        let configured_signer = TestNodeClient::current_signer(&node2_url).public_key();
+        let unknown_signer =
+            TestNodeClient::report_signer(&TestNodeClient::current_expected_report(
+                &ConsensusClientUri::from_str("mc://node3.test.com:443/").unwrap(),
+            ))
+            .public_key();
+
+        // A previous run of the watcher collected the report of the old signer.
+        watcher_db
+            .add_verification_report(
+                &tx_src_url1,
+                &old_signer,
+                &TestNodeClient::current_expected_report(&node1_url),
+                &[],
+            )
+            .unwrap();
+
+        let sources = vec![
+            SourceConfig::new(tx_src_url1.to_string(), Some(node1_url), None),
+            SourceConfig::new(tx_src_url2.to_string(), Some(node2_url), None)
+                .with_known_block_signers(&[configured_signer]),
+        ];
+        let collector = VerificationReportsCollectorThread::<TestNodeClient>::new(
+            watcher_db,
+            sources,
+            Duration::from_millis(100),
+            None,
+            logger,
+            Arc::new(AtomicBool::new(false)),
+        );
+
+        assert!(collector.is_known_signer(&tx_src_url1, &old_signer));
+        assert!(!collector.is_known_signer(&tx_src_url2, &old_signer));
+        assert!(collector.is_known_signer(&tx_src_url2, &configured_signer));
+        assert!(!collector.is_known_signer(&tx_src_url1, &configured_signer));
+        assert!(!collector.is_known_signer(&tx_src_url1, &unknown_signer));
+    }
 }
@@ -0,0 +1,179 @@
+// Copyright (c) 2018-2022 The MobileCoin Foundation
+
+//! Verification status of block signatures collected by the watcher.
+
+use crate::error::WatcherDBError;
+use displaydoc::Display;
+use mc_attest_core::{VerificationReport, VerificationReportData};
+use mc_attest_verifier::Verifier;
+use mc_crypto_keys::Ed25519Public;
+use mc_util_repr_bytes::ReprBytes;
+use serde::Serialize;
+
+/// The result of checking that a block signature was produced by a signer
+/// bound to a valid attestation verification report.
+#[derive(Clone, Copy, Debug, Display, Eq, Hash, PartialEq, Serialize)]
+#[repr(u32)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureVerificationStatus {
+    /// The verification report for the signer has not been collected yet.
+    Pending = 1,
+
+    /// The signer is bound to a verification report obtained from the node.
+    Verified,
+
+    /// The node was polled but did not produce a verification report for the
+    /// signer, e.g. because the enclave holding the signing key was restarted
+    /// before the watcher got a chance to poll it.
+    NoVerificationReport,
+
+    /// The signature does not match the block it was attached to.
+    InvalidSignature,
+
+    /// The node produced a verification report for the signer, but it did not
+    /// pass the verification report checker: its IAS signature is not valid,
+    /// it attests an enclave the watcher does not trust, or it attests a
+    /// different signer.
+    InvalidVerificationReport,
+}
+
+impl SignatureVerificationStatus {
+    /// Whether this status can still change as more verification reports get
+    /// collected.
+    pub fn is_final(&self) -> bool {
+        matches!(self, Self::Verified | Self::InvalidSignature)
+    }
+}
+
+impl TryFrom<u32> for SignatureVerificationStatus {
+    type Error = WatcherDBError;
+
+    fn try_from(src: u32) -> Result<Self, Self::Error> {
+        match src {
+            1 => Ok(Self::Pending),
+            2 => Ok(Self::Verified),
+            3 => Ok(Self::NoVerificationReport),
+            4 => Ok(Self::InvalidSignature),
+            5 => Ok(Self::InvalidVerificationReport),
+            _ => Err(WatcherDBError::Deserialization),
+        }
+    }
+}
+
+/// Checks the verification reports collected for block signers, before the
+/// signatures of the signers are marked as verified.
+pub trait VerificationReportChecker: Send + Sync {
+    /// Check that a verification report is genuine, attests an enclave the
+    /// watcher trusts, and binds `block_signer` to that enclave.
+    fn check(
+        &self,
+        verification_report: &VerificationReport,
+        block_signer: &Ed25519Public,
+    ) -> Result<(), String>;
+}
+
+/// A [VerificationReportChecker] which verifies the IAS signature and the
+/// enclave identity of reports with a [Verifier], and checks that the report
+/// data holds the block signer key.
+#[derive(Clone, Debug)]
+pub struct AttestedBlockSignerChecker {
+    verifier: Verifier,
+}
+
+impl AttestedBlockSignerChecker {
+    /// Create a checker accepting the enclaves that `verifier` accepts.
+    pub fn new(verifier: Verifier) -> Self {
+        Self { verifier }
+    }
+}
+
+impl VerificationReportChecker for AttestedBlockSignerChecker {
+    fn check(
+        &self,
+        verification_report: &VerificationReport,
+        block_signer: &Ed25519Public,
+    ) -> Result<(), String> {
+        let report_data = self
+            .verifier
+            .verify(verification_report)
+            .map_err(|err| format!("Verification report did not verify: {}", err))?;
+        let attested_signer = block_signer_from_report_data(&report_data)?;
+        if &attested_signer != block_signer {
+            return Err(format!(
+                "Verification report attests block signer {}, not {}",
+                hex::encode(attested_signer.to_bytes()),
+                hex::encode(block_signer.to_bytes())
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Get the block signer key out of the report data of a verification report.
+/// Consensus enclaves put it in the second half of the report data.
+pub fn block_signer_from_report_data(
+    report_data: &VerificationReportData,
+) -> Result<Ed25519Public, String> {
+    let report_body = report_data
+        .quote
+        .report_body()
+        .map_err(|err| format!("Failed getting report body: {}", err))?;
+
+    let custom_data = report_body.report_data();
+    let custom_data_bytes: &[u8] = custom_data.as_ref();
+
+    if custom_data_bytes.len() != 64 {
+        return Err(format!(
+            "Unspected report data length: expected 64, got {}",
+            custom_data_bytes.len()
+        ));
+    }
+
+    let signer_bytes = &custom_data_bytes[32..];
+
+    Ed25519Public::try_from(signer_bytes).map_err(|err| format!("Unable to construct key: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_crypto_keys::Ed25519Pair;
+    use mc_util_from_random::FromRandom;
+    use rand_core::SeedableRng;
+    use rand_hc::Hc128Rng;
+
+    #[test]
+    fn u32_round_trip() {
+        for status in [
+            SignatureVerificationStatus::Pending,
+            SignatureVerificationStatus::Verified,
+            SignatureVerificationStatus::NoVerificationReport,
+            SignatureVerificationStatus::InvalidSignature,
+            SignatureVerificationStatus::InvalidVerificationReport,
+        ] {
+            assert_eq!(
+                SignatureVerificationStatus::try_from(status as u32),
+                Ok(status)
+            );
+        }
+        assert!(SignatureVerificationStatus::try_from(0).is_err());
+        assert!(SignatureVerificationStatus::try_from(6).is_err());
+    }
+
+    #[test]
+    fn reports_without_a_valid_ias_signature_are_rejected() {
+        let mut rng = Hc128Rng::from_seed([1u8; 32]);
+        let block_signer = Ed25519Pair::from_random(&mut rng).public_key();
+        let verification_report = VerificationReport {
+            sig: vec![1u8; 32].into(),
+            chain: vec![vec![2; 16], vec![3; 32]],
+            http_body: "test body".to_owned(),
+        };
+
+        let checker = AttestedBlockSignerChecker::new(Verifier::default());
+        assert!(checker
+            .check(&verification_report, &block_signer)
+            .unwrap_err()
+            .starts_with("Verification report did not verify"));
+    }
+}
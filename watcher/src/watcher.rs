@@ -3,24 +3,32 @@
 //! Basic Watcher Node
 
 use crate::{
+    alerts::{Alert, AlertNotifier},
+    consistency::{check_block_ids, BlockIdDivergence, DEFAULT_BLOCK_ID_CHECK_INTERVAL},
     error::{WatcherDBError, WatcherError},
     metrics::WatcherMetrics,
+    rate_limiter::SourceRateLimiter,
+    sync_status::SyncStatusTracker,
     watcher_db::WatcherDB,
 };
 use mc_api::block_num_to_s3block_path;
 use mc_blockchain_types::{BlockData, BlockIndex};
 use mc_common::logger::{log, Logger};
 use mc_ledger_db::Ledger;
-use mc_ledger_sync::ReqwestTransactionsFetcher;
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use mc_ledger_sync::{RequestAuthenticator, ReqwestTransactionsFetcher};
+use rayon::{
+    iter::{IntoParallelIterator, ParallelIterator},
+    ThreadPool, ThreadPoolBuilder,
+};
 use std::{
     collections::HashMap,
+    num::NonZeroU32,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 use url::Url;
 
@@ -38,6 +46,14 @@ pub struct Watcher {
     transactions_fetcher_by_url: Arc<HashMap<Url, ReqwestTransactionsFetcher>>,
     watcher_db: WatcherDB,
     store_block_data: bool,
+    block_id_check_interval: Option<u64>,
+    block_data_retention: Option<u64>,
+    sync_status: SyncStatusTracker,
+    alert_notifier: Option<AlertNotifier>,
+    // Thread pool used for fetching from multiple sources concurrently. The global rayon pool is
+    // used when unset.
+    fetch_pool: Option<Arc<ThreadPool>>,
+    rate_limiter: Option<Arc<SourceRateLimiter>>,
     logger: Logger,
     metrics: WatcherMetrics,
 }
@@ -92,11 +108,84 @@ impl Watcher {
             transactions_fetcher_by_url,
             watcher_db,
             store_block_data,
+            block_id_check_interval: Some(DEFAULT_BLOCK_ID_CHECK_INTERVAL),
+            block_data_retention: None,
+            sync_status: SyncStatusTracker::default(),
+            alert_notifier: None,
+            fetch_pool: None,
+            rate_limiter: None,
             logger,
             metrics,
         })
     }
 
+    /// Set how often (in blocks) block IDs are cross-checked between tx
+    /// sources. `None` disables the check.
+    pub fn set_block_id_check_interval(&mut self, block_id_check_interval: Option<u64>) {
+        self.block_id_check_interval = block_id_check_interval.filter(|interval| *interval > 0);
+    }
+
+    /// Set how many of the most recent blocks of each tx source keep their
+    /// BlockData in the database. `None` keeps all of them.
+    pub fn set_block_data_retention(&mut self, block_data_retention: Option<u64>) {
+        self.block_data_retention = block_data_retention;
+    }
+
+    /// Set the notifier that block signature anomalies are reported to, in
+    /// addition to being logged.
+    pub fn set_alert_notifier(&mut self, alert_notifier: Option<AlertNotifier>) {
+        self.alert_notifier = alert_notifier;
+    }
+
+    /// Use a dedicated pool of `num_workers` threads for fetching blocks from
+    /// the tx sources concurrently. Fetching is network bound, so this can be
+    /// much larger than the number of CPUs.
+    pub fn set_poll_workers(&mut self, num_workers: usize) -> Result<(), WatcherError> {
+        let fetch_pool = ThreadPoolBuilder::new()
+            .num_threads(num_workers)
+            .thread_name(|index| format!("WatcherPoll-{}", index))
+            .build()?;
+        self.fetch_pool = Some(Arc::new(fetch_pool));
+        Ok(())
+    }
+
+    /// Limit the number of requests made to each tx source per second. `None`
+    /// removes the limit.
+    pub fn set_source_rate_limit(&mut self, max_requests_per_second: Option<NonZeroU32>) {
+        self.rate_limiter =
+            max_requests_per_second.map(|max| Arc::new(SourceRateLimiter::new(max)));
+    }
+
+    /// Authenticate the requests made to fetch blocks from `src_url`.
+    pub fn set_tx_source_authenticator(
+        &mut self,
+        src_url: &Url,
+        authenticator: Option<Arc<dyn RequestAuthenticator>>,
+    ) -> Result<(), WatcherError> {
+        Arc::make_mut(&mut self.transactions_fetcher_by_url)
+            .get_mut(src_url)
+            .ok_or_else(|| WatcherError::UnknownTxSourceUrl(src_url.to_string()))?
+            .set_authenticator(authenticator);
+        Ok(())
+    }
+
+    /// Get the tracker that records the outcome of block fetches for each tx
+    /// source.
+    pub fn sync_status(&self) -> SyncStatusTracker {
+        self.sync_status.clone()
+    }
+
+    /// Remove BlockData that fell out of the retention window, if one is set.
+    pub fn prune_block_data(&self) -> Result<(), WatcherError> {
+        if let Some(retention) = self.block_data_retention {
+            let num_removed = self.watcher_db.prune_block_data(retention)?;
+            if num_removed > 0 {
+                log::info!(self.logger, "Pruned {} block data entries", num_removed);
+            }
+        }
+        Ok(())
+    }
+
     /// The lowest next block we need to try and sync.
     pub fn lowest_next_block_to_sync(&self) -> Result<u64, WatcherError> {
         let last_synced = self.watcher_db.last_synced_blocks()?;
@@ -116,6 +205,8 @@ impl Watcher {
     /// available)
     pub fn collect_metrics(&self, ledger_height: Option<u64>) {
         let last_synced = self.watcher_db.last_synced_blocks().unwrap_or_default();
+        self.metrics
+            .collect_peer_blocks_behind(&last_synced, ledger_height);
         self.metrics.collect_peer_blocks_synced(last_synced);
         if let Some(ledger_height) = ledger_height {
             self.metrics.set_ledger_height(ledger_height as i64);
@@ -174,8 +265,7 @@ impl Watcher {
                 .collect();
 
             // Attempt to fetch block data for all urls in parallel.
-            let url_to_block_data_result =
-                parallel_fetch_blocks(url_to_block_index, self.transactions_fetcher_by_url.clone());
+            let url_to_block_data_result = self.parallel_fetch_blocks(url_to_block_index);
 
             // Store data for each successfully synced blocked. Track on whether any of the
             // sources was able to produce block data. If so, more data might be
@@ -191,33 +281,17 @@ impl Watcher {
                             src_url,
                             block_index
                         );
-                        if self.store_block_data {
-                            match self.watcher_db.add_block_data(&src_url, &block_data) {
-                                Ok(()) => {}
-                                Err(WatcherDBError::AlreadyExists) => {}
-                                Err(err) => Err(err)?,
-                            };
-                        }
-
-                        if let Some(signature) = block_data.signature() {
-                            let filename = block_num_to_s3block_path(block_index)
-                                .into_os_string()
-                                .into_string()
-                                .unwrap();
-                            self.watcher_db.add_block_signature(
-                                &src_url,
-                                block_index,
-                                signature.clone(),
-                                filename,
-                            )?;
-                        } else {
-                            self.watcher_db.update_last_synced(&src_url, block_index)?;
-                        }
-
+                        self.sync_status.record_success(&src_url);
+                        self.process_block_data(&src_url, block_index, &block_data, false)?;
                         had_success = true;
                     }
 
                     Err(err) => {
+                        // Sources are polled past their last block, so not finding a block
+                        // is the normal state of a synced source.
+                        if !err.is_not_found() {
+                            self.sync_status.record_error(&src_url, &err);
+                        }
                         if log_sync_failures {
                             log::error!(
                                 self.logger,
@@ -233,6 +307,10 @@ impl Watcher {
                                 err
                             );
                         }
+
+                        if self.skip_missing_block(&src_url, block_index, max_block_height)? {
+                            had_success = true;
+                        }
                     }
                 }
             }
@@ -257,6 +335,306 @@ impl Watcher {
             }
         }
     }
+
+    /// Store the data of a block fetched from `src_url`: its signature and
+    /// metadata, and the full block data when enabled.
+    ///
+    /// `backfill` indicates the block was previously recorded as missing, in
+    /// which case the last synced block of `src_url` is left untouched.
+    fn process_block_data(
+        &self,
+        src_url: &Url,
+        block_index: BlockIndex,
+        block_data: &BlockData,
+        backfill: bool,
+    ) -> Result<(), WatcherError> {
+        if self.should_check_block_id() {
+            self.check_block_id_consistency(src_url, block_data)?;
+        }
+
+        if self.store_block_data {
+            match self.watcher_db.add_block_data(src_url, block_data) {
+                Ok(()) => {}
+                Err(WatcherDBError::AlreadyExists) => {}
+                Err(err) => Err(err)?,
+            };
+        }
+
+        if let Some(metadata) = block_data.metadata() {
+            self.watcher_db
+                .add_block_metadata(src_url, block_index, metadata)?;
+        }
+
+        if let Some(signature) = block_data.signature() {
+            let filename = block_num_to_s3block_path(block_index)
+                .into_os_string()
+                .into_string()
+                .unwrap();
+            if backfill {
+                self.watcher_db.add_backfilled_block_signature(
+                    src_url,
+                    block_index,
+                    signature.clone(),
+                    filename,
+                )?;
+            } else {
+                self.watcher_db.add_block_signature(
+                    src_url,
+                    block_index,
+                    signature.clone(),
+                    filename,
+                )?;
+            }
+
+            if let Err(err) = signature.verify(block_data.block()) {
+                log::crit!(
+                    self.logger,
+                    "Block signature from {} does not match block {}: {}",
+                    src_url,
+                    block_index,
+                    err
+                );
+                self.notify(Alert::invalid_signature(
+                    src_url,
+                    block_index,
+                    signature.signer(),
+                    err,
+                ));
+                self.watcher_db
+                    .mark_signature_invalid(src_url, block_index)?;
+            }
+        } else if backfill {
+            self.watcher_db.remove_missing_block(src_url, block_index)?;
+        } else {
+            self.watcher_db.update_last_synced(src_url, block_index)?;
+        }
+
+        Ok(())
+    }
+
+    /// Called when `block_index` could not be fetched from `src_url`. If some
+    /// other source already synced past that block and `src_url` does serve
+    /// one of the following blocks, the blocks in between are a gap in the
+    /// archive of `src_url` rather than blocks that were not uploaded yet. In
+    /// that case they are recorded as missing, to be retried by
+    /// [Watcher::retry_missing_blocks], and the first block after the gap gets
+    /// processed instead.
+    ///
+    /// At most [MAX_MISSING_BLOCK_RANGE] blocks are skipped at once.
+    ///
+    /// Returns true if the block was skipped.
+    fn skip_missing_block(
+        &self,
+        src_url: &Url,
+        block_index: BlockIndex,
+        max_block_height: Option<u64>,
+    ) -> Result<bool, WatcherError> {
+        let highest_synced_elsewhere = self
+            .watcher_db
+            .last_synced_blocks()?
+            .into_iter()
+            .filter(|(url, _last_synced)| url != src_url)
+            .filter_map(|(_url, last_synced)| last_synced)
+            .max();
+        let mut last_candidate = match highest_synced_elsewhere {
+            Some(highest) => highest.min(block_index + MAX_MISSING_BLOCK_RANGE),
+            None => return Ok(false),
+        };
+        if let Some(max_block_height) = max_block_height {
+            last_candidate = last_candidate.min(max_block_height);
+        }
+
+        for next_block_index in block_index + 1..=last_candidate {
+            let next_block_data = match self.fetch_block(src_url, next_block_index) {
+                Ok(block_data) => block_data,
+                Err(err) if err.is_not_found() => continue,
+                Err(_) => return Ok(false),
+            };
+
+            log::warn!(
+                self.logger,
+                "Blocks {} to {} are missing from {}, skipping them until they become available",
+                block_index,
+                next_block_index - 1,
+                src_url
+            );
+            for missing_block_index in block_index..next_block_index {
+                self.watcher_db
+                    .add_missing_block(src_url, missing_block_index)?;
+            }
+            self.sync_status.record_success(src_url);
+            self.process_block_data(src_url, next_block_index, &next_block_data, false)?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Retry fetching the blocks that were previously found missing from the
+    /// archives of the tx sources.
+    pub fn retry_missing_blocks(&self) -> Result<(), WatcherError> {
+        for (src_url, block_indexes) in self.watcher_db.get_missing_blocks()? {
+            for block_index in block_indexes {
+                match self.fetch_block(&src_url, block_index) {
+                    Ok(block_data) => {
+                        log::info!(
+                            self.logger,
+                            "Backfilled missing block {} from {}",
+                            block_index,
+                            src_url
+                        );
+                        self.process_block_data(&src_url, block_index, &block_data, true)?;
+                    }
+                    Err(err) => {
+                        log::debug!(
+                            self.logger,
+                            "Block {} is still missing from {}: {}",
+                            block_index,
+                            src_url,
+                            err
+                        );
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Fetch a single block from `src_url`.
+    fn fetch_block(
+        &self,
+        src_url: &Url,
+        block_index: BlockIndex,
+    ) -> Result<BlockData, WatcherError> {
+        fetch_block(
+            src_url,
+            block_index,
+            &self.transactions_fetcher_by_url,
+            self.rate_limiter.as_deref(),
+            &self.metrics,
+        )
+    }
+
+    /// Fetch a block from each of the given source URLs concurrently.
+    fn parallel_fetch_blocks(
+        &self,
+        url_to_block_index: HashMap<Url, BlockIndex>,
+    ) -> HashMap<Url, (u64, Result<BlockData, WatcherError>)> {
+        let transactions_fetcher_by_url = self.transactions_fetcher_by_url.clone();
+        let rate_limiter = self.rate_limiter.clone();
+        let metrics = self.metrics.clone();
+        let fetch = move || {
+            parallel_fetch_blocks(
+                url_to_block_index,
+                transactions_fetcher_by_url,
+                rate_limiter.as_deref(),
+                &metrics,
+            )
+        };
+        match self.fetch_pool.as_ref() {
+            Some(fetch_pool) => fetch_pool.install(fetch),
+            None => fetch(),
+        }
+    }
+
+    /// Whether block IDs should be cross-checked against other sources.
+    fn should_check_block_id(&self) -> bool {
+        self.transactions_fetcher_by_url.len() > 1 && self.block_id_check_interval.is_some()
+    }
+
+    /// Whether a block at the given index should be fetched from the sources
+    /// whose copy of it is not in the database, to cross-check its block ID.
+    fn should_fetch_for_block_id_check(&self, block_index: BlockIndex) -> bool {
+        self.block_id_check_interval
+            .map(|interval| block_index % interval == 0)
+            .unwrap_or(false)
+    }
+
+    /// Compare the ID of a block fetched from `src_url` with the ID of the
+    /// same block as served by every other source that has already synced
+    /// past it. Sources that have not reached the block yet will perform the
+    /// comparison themselves once they do.
+    ///
+    /// The block data stored in the database is compared on every block. The
+    /// sources whose block data is not stored (or was pruned) are only asked
+    /// for the block every `block_id_check_interval` blocks, so that syncing
+    /// does not cost an extra request to every other source per block.
+    ///
+    /// Returns the divergence, if one was detected.
+    pub fn check_block_id_consistency(
+        &self,
+        src_url: &Url,
+        block_data: &BlockData,
+    ) -> Result<Option<BlockIdDivergence>, WatcherError> {
+        let block_index = block_data.block().index;
+
+        let synced_urls: Vec<Url> = self
+            .watcher_db
+            .last_synced_blocks()?
+            .into_iter()
+            .filter(|(url, last_synced)| {
+                url != src_url
+                    && last_synced
+                        .map(|last_synced| last_synced >= block_index)
+                        .unwrap_or(false)
+            })
+            .map(|(url, _last_synced)| url)
+            .collect();
+        if synced_urls.is_empty() {
+            return Ok(None);
+        }
+
+        let mut block_ids = HashMap::from_iter([(src_url.clone(), block_data.block().id.clone())]);
+        let stored_block_datas = self.watcher_db.get_block_data_map(block_index)?;
+        let mut url_to_block_index: HashMap<Url, BlockIndex> = HashMap::default();
+        for url in synced_urls {
+            match stored_block_datas.get(&url) {
+                Some(other_block_data) => {
+                    block_ids.insert(url, other_block_data.block().id.clone());
+                }
+                None => {
+                    url_to_block_index.insert(url, block_index);
+                }
+            }
+        }
+        if !self.should_fetch_for_block_id_check(block_index) {
+            url_to_block_index.clear();
+        }
+
+        for (url, (_block_index, result)) in self.parallel_fetch_blocks(url_to_block_index) {
+            match result {
+                Ok(other_block_data) => {
+                    block_ids.insert(url, other_block_data.block().id.clone());
+                }
+                Err(err) => {
+                    log::debug!(
+                        self.logger,
+                        "Could not fetch block {} from {} for block id check: {:?}",
+                        block_index,
+                        url,
+                        err
+                    );
+                }
+            }
+        }
+
+        let divergence = check_block_ids(block_index, block_ids.into_iter().collect());
+        if let Some(divergence) = divergence.as_ref() {
+            self.metrics.inc_block_id_divergences();
+            log::crit!(
+                self.logger,
+                "Tx sources disagree on block id: {}",
+                divergence
+            );
+            self.notify(Alert::from(divergence));
+        }
+        Ok(divergence)
+    }
+
+    fn notify(&self, alert: Alert) {
+        if let Some(alert_notifier) = self.alert_notifier.as_ref() {
+            alert_notifier.notify(alert);
+        }
+    }
 }
 
 /// Given a map of block indexes per source URL and a map of transaction
@@ -265,29 +643,56 @@ impl Watcher {
 fn parallel_fetch_blocks(
     url_to_block_index: HashMap<Url, BlockIndex>,
     transactions_fetcher_by_url: Arc<HashMap<Url, ReqwestTransactionsFetcher>>,
+    rate_limiter: Option<&SourceRateLimiter>,
+    metrics: &WatcherMetrics,
 ) -> HashMap<Url, (u64, Result<BlockData, WatcherError>)> {
     url_to_block_index
         .into_par_iter()
         .map(|(src_url, block_index)| {
-            let block_fetch_result = transactions_fetcher_by_url
-                .get(&src_url)
-                .ok_or_else(|| WatcherError::UnknownTxSourceUrl(src_url.to_string()))
-                .and_then(|transactions_fetcher| {
-                    assert_eq!(transactions_fetcher.source_urls, vec![src_url.clone()]);
-
-                    transactions_fetcher
-                        .get_block_data_by_index(block_index, None)
-                        .map_err(WatcherError::from)
-                });
-
+            let block_fetch_result = fetch_block(
+                &src_url,
+                block_index,
+                &transactions_fetcher_by_url,
+                rate_limiter,
+                metrics,
+            );
             (src_url, (block_index, block_fetch_result))
         })
         .collect()
 }
 
+/// Fetch a single block from `src_url`, waiting for the rate limit of the
+/// source first, and record the fetch in `metrics`.
+fn fetch_block(
+    src_url: &Url,
+    block_index: BlockIndex,
+    transactions_fetcher_by_url: &HashMap<Url, ReqwestTransactionsFetcher>,
+    rate_limiter: Option<&SourceRateLimiter>,
+    metrics: &WatcherMetrics,
+) -> Result<BlockData, WatcherError> {
+    let transactions_fetcher = transactions_fetcher_by_url
+        .get(src_url)
+        .ok_or_else(|| WatcherError::UnknownTxSourceUrl(src_url.to_string()))?;
+    assert_eq!(transactions_fetcher.source_urls, vec![src_url.clone()]);
+
+    if let Some(rate_limiter) = rate_limiter {
+        rate_limiter.wait(src_url);
+    }
+
+    let start = Instant::now();
+    let result = transactions_fetcher.get_block_data_by_index(block_index, None);
+    metrics.observe_archive_fetch(src_url, start.elapsed(), result.is_err());
+    Ok(result?)
+}
+
 /// Maximal number of blocks to attempt to sync at each loop iteration.
 const MAX_BLOCKS_PER_SYNC_ITERATION: usize = 1000;
 
+/// The largest gap in a source archive that gets skipped at once. Finding a
+/// gap takes a fetch per missing block, so this bounds the cost of polling a
+/// source that stopped uploading blocks.
+pub const MAX_MISSING_BLOCK_RANGE: u64 = 20;
+
 /// Syncs new ledger materials for the watcher when the local ledger
 /// appends new blocks.
 pub struct WatcherSyncThread {
@@ -360,6 +765,7 @@ impl WatcherSyncThread {
     ) {
         log::debug!(logger, "WatcherSyncThread has started.");
 
+        let mut last_missing_blocks_retry = Instant::now();
         loop {
             if stop_requested.load(Ordering::SeqCst) {
                 log::debug!(logger, "WatcherSyncThread stop requested.");
@@ -392,6 +798,14 @@ impl WatcherSyncThread {
                 );
             }
 
+            // Retry blocks that were missing from a source archive once per poll interval.
+            if last_missing_blocks_retry.elapsed() >= poll_interval {
+                watcher
+                    .retry_missing_blocks()
+                    .expect("Could not retry missing blocks");
+                last_missing_blocks_retry = Instant::now();
+            }
+
             // Maybe sync, maybe wait and check again.
             if is_behind {
                 let sync_result = watcher
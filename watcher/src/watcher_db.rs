@@ -2,9 +2,22 @@
 
 //! The watcher database
 
-use crate::{block_data_store::BlockDataStore, error::WatcherDBError};
+use crate::{
+    attestation_revalidator::AttestationRevalidation,
+    block_data_store::BlockDataStore,
+    error::WatcherDBError,
+    snapshot::{
+        read_record, write_record, SnapshotBlockMetadata, SnapshotBlockSignature, SnapshotHeader,
+        SnapshotLastSynced, SnapshotRecordKind, SnapshotSummary, SnapshotVerificationReport,
+        SNAPSHOT_FORMAT_VERSION,
+    },
+    timestamp_policy::{MinimumTimestamp, TimestampPolicy},
+    verification_status::{SignatureVerificationStatus, VerificationReportChecker},
+};
 
-use mc_blockchain_types::{BlockData, BlockIndex, BlockSignature, VerificationReport};
+use mc_blockchain_types::{
+    BlockData, BlockIndex, BlockMetadata, BlockSignature, VerificationReport,
+};
 use mc_common::{
     logger::{log, Logger},
     HashMap,
@@ -22,6 +35,7 @@ use lmdb::{
 };
 use mc_util_repr_bytes::typenum::Unsigned;
 use std::{
+    io::{Read, Write},
     path::Path,
     str,
     sync::Arc,
@@ -32,7 +46,7 @@ use url::Url;
 /// LMDB parameter: max file size.
 const MAX_LMDB_FILE_SIZE: usize = 1 << 40; // 1 TB
 /// LMDB parameter: max number of databases.
-const MAX_DATABASES: u32 = 10;
+const MAX_DATABASES: u32 = 15;
 
 /// Metadata store settings that are used for version control.
 #[derive(Clone, Default, Debug)]
@@ -66,6 +80,23 @@ pub const VERIFICATION_REPORTS_POLL_QUEUE_DB_NAME: &str =
 /// Verification reports by report hash database name.
 pub const VERIFICATION_REPORTS_BY_HASH_DB_NAME: &str = "watcher_db:verification_reports_by_hash";
 
+/// Attestation re-validation results by report hash database name.
+pub const ATTESTATION_REVALIDATION_BY_REPORT_HASH_DB_NAME: &str =
+    "watcher_db:attestation_revalidation_by_report_hash";
+
+/// Block signature verification status database name.
+pub const SIGNATURE_VERIFICATION_STATUS_DB_NAME: &str = "watcher_db:signature_verification_status";
+
+/// Unverified block signatures by block signer database name.
+pub const UNVERIFIED_SIGNATURES_BY_SIGNER_DB_NAME: &str =
+    "watcher_db:unverified_signatures_by_signer";
+
+/// Block metadata database name.
+pub const BLOCK_METADATA_DB_NAME: &str = "watcher_db:block_metadata";
+
+/// Missing archive blocks database name.
+pub const MISSING_BLOCKS_DB_NAME: &str = "watcher_db:missing_blocks";
+
 /// Last synced archive blocks database name.
 pub const LAST_SYNCED_DB_NAME: &str = "watcher_db:last_synced";
 
@@ -92,6 +123,9 @@ pub const POLL_BLOCK_TIMESTAMP_POLLING_FREQUENCY: Duration = Duration::from_mill
 /// "paused" state and signal for intervention.
 pub const POLL_BLOCK_TIMESTAMP_ERROR_RETRY_FREQUENCY: Duration = Duration::from_millis(1000);
 
+/// The number of snapshot records imported per transaction.
+const SNAPSHOT_IMPORT_BATCH_SIZE: usize = 1000;
+
 /// Block Signature Data for Signature Store.
 #[derive(Message, Eq, PartialEq)]
 pub struct BlockSignatureData {
@@ -130,9 +164,14 @@ pub struct WatcherDB {
     /// It shouldn't happen, but we sure don't want to miss it if it does.
     verification_reports_by_signer: Database,
 
-    /// Verification report hash -> VerificationReport.
+    /// Verification report hash -> VerificationReport. This also holds the
+    /// reports included in block metadata.
     verification_reports_by_hash: Database,
 
+    /// Verification report hash -> the outcome of re-validating the report
+    /// against the current attestation policy.
+    attestation_revalidation_by_report_hash: Database,
+
     /// Verification reports poll queue database.
     /// This database holds a map of tx source url -> list of observed block
     /// signers. A background thread polls this database, trying to fetch
@@ -143,6 +182,26 @@ pub struct WatcherDB {
     /// removed from the queue.
     verification_reports_poll_queue: Database,
 
+    /// Verification status of block signatures, keyed by (block index, tx
+    /// source url).
+    signature_verification_status: Database,
+
+    /// (Block signer, tx source url) -> block indexes whose signatures have
+    /// not been verified yet. Entries are removed once the signer is bound to
+    /// a verification report, at which point the status of all the listed
+    /// blocks is updated.
+    unverified_signatures_by_signer: Database,
+
+    /// Block metadata (quorum set, responder ID and attestation evidence of
+    /// the node that externalized the block), keyed by (block index, tx
+    /// source url).
+    block_metadata: Database,
+
+    /// Tx source url -> block indexes that were skipped over while syncing
+    /// because they were missing from the source's archive. These get retried
+    /// until they are found.
+    missing_blocks: Database,
+
     /// Last synced archive block.
     last_synced: Database,
 
@@ -154,6 +213,10 @@ pub struct WatcherDB {
     /// Were we opened in write mode?
     write_allowed: bool,
 
+    /// Checks verification reports before the signatures of their signer are
+    /// marked as verified. Without one, no signature gets verified.
+    verification_report_checker: Option<Arc<dyn VerificationReportChecker>>,
+
     /// Logger.
     logger: Logger,
 }
@@ -184,8 +247,16 @@ impl WatcherDB {
             env.open_db(Some(VERIFICATION_REPORTS_BY_BLOCK_SIGNER_DB_NAME))?;
         let verification_reports_by_hash =
             env.open_db(Some(VERIFICATION_REPORTS_BY_HASH_DB_NAME))?;
+        let attestation_revalidation_by_report_hash =
+            env.open_db(Some(ATTESTATION_REVALIDATION_BY_REPORT_HASH_DB_NAME))?;
         let verification_reports_poll_queue =
             env.open_db(Some(VERIFICATION_REPORTS_POLL_QUEUE_DB_NAME))?;
+        let signature_verification_status =
+            env.open_db(Some(SIGNATURE_VERIFICATION_STATUS_DB_NAME))?;
+        let unverified_signatures_by_signer =
+            env.open_db(Some(UNVERIFIED_SIGNATURES_BY_SIGNER_DB_NAME))?;
+        let block_metadata = env.open_db(Some(BLOCK_METADATA_DB_NAME))?;
+        let missing_blocks = env.open_db(Some(MISSING_BLOCKS_DB_NAME))?;
         let last_synced = env.open_db(Some(LAST_SYNCED_DB_NAME))?;
         let config = env.open_db(Some(CONFIG_DB_NAME))?;
 
@@ -197,14 +268,29 @@ impl WatcherDB {
             block_signatures,
             verification_reports_by_signer,
             verification_reports_by_hash,
+            attestation_revalidation_by_report_hash,
             verification_reports_poll_queue,
+            signature_verification_status,
+            unverified_signatures_by_signer,
+            block_metadata,
+            missing_blocks,
             last_synced,
             config,
             write_allowed: false,
+            verification_report_checker: None,
             logger,
         })
     }
 
+    /// Set the checker that verification reports must pass before the
+    /// signatures of their signer are marked as verified.
+    pub fn set_verification_report_checker(
+        &mut self,
+        checker: impl VerificationReportChecker + 'static,
+    ) {
+        self.verification_report_checker = Some(Arc::new(checker));
+    }
+
     /// Open an existing WatcherDB for read-write operations.
     pub fn open_rw(
         path: &Path,
@@ -237,10 +323,27 @@ impl WatcherDB {
             Some(VERIFICATION_REPORTS_BY_HASH_DB_NAME),
             DatabaseFlags::empty(),
         )?;
+        env.create_db(
+            Some(ATTESTATION_REVALIDATION_BY_REPORT_HASH_DB_NAME),
+            DatabaseFlags::empty(),
+        )?;
         env.create_db(
             Some(VERIFICATION_REPORTS_POLL_QUEUE_DB_NAME),
             DatabaseFlags::DUP_SORT,
         )?;
+        env.create_db(
+            Some(SIGNATURE_VERIFICATION_STATUS_DB_NAME),
+            DatabaseFlags::empty(),
+        )?;
+        env.create_db(
+            Some(UNVERIFIED_SIGNATURES_BY_SIGNER_DB_NAME),
+            DatabaseFlags::DUP_SORT | DatabaseFlags::DUP_FIXED,
+        )?;
+        env.create_db(Some(BLOCK_METADATA_DB_NAME), DatabaseFlags::empty())?;
+        env.create_db(
+            Some(MISSING_BLOCKS_DB_NAME),
+            DatabaseFlags::DUP_SORT | DatabaseFlags::DUP_FIXED,
+        )?;
         env.create_db(Some(LAST_SYNCED_DB_NAME), DatabaseFlags::empty())?;
         env.create_db(Some(CONFIG_DB_NAME), DatabaseFlags::DUP_SORT)?;
 
@@ -290,15 +393,74 @@ impl WatcherDB {
         block_index: u64,
         block_signature: BlockSignature,
         archive_filename: String,
+    ) -> Result<(), WatcherDBError> {
+        self.add_block_signature_impl(
+            src_url,
+            block_index,
+            block_signature,
+            archive_filename,
+            false,
+        )
+    }
+
+    /// Add a block signature for a block that was previously recorded as
+    /// missing. Unlike [WatcherDB::add_block_signature], this does not move the
+    /// last synced block for the URL, and removes the block from the list of
+    /// missing blocks.
+    pub fn add_backfilled_block_signature(
+        &self,
+        src_url: &Url,
+        block_index: u64,
+        block_signature: BlockSignature,
+        archive_filename: String,
+    ) -> Result<(), WatcherDBError> {
+        self.add_block_signature_impl(
+            src_url,
+            block_index,
+            block_signature,
+            archive_filename,
+            true,
+        )
+    }
+
+    fn add_block_signature_impl(
+        &self,
+        src_url: &Url,
+        block_index: u64,
+        block_signature: BlockSignature,
+        archive_filename: String,
+        backfill: bool,
     ) -> Result<(), WatcherDBError> {
         if !self.write_allowed {
             return Err(WatcherDBError::ReadOnly);
         }
 
         let mut db_txn = self.env.begin_rw_txn()?;
+        self.write_block_signature(
+            &mut db_txn,
+            src_url,
+            block_index,
+            block_signature,
+            archive_filename,
+            backfill,
+        )?;
+        db_txn.commit()?;
+        Ok(())
+    }
 
+    /// A helper for writing a block signature, and everything that depends on
+    /// it, in the given transaction.
+    fn write_block_signature<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        src_url: &Url,
+        block_index: u64,
+        block_signature: BlockSignature,
+        archive_filename: String,
+        backfill: bool,
+    ) -> Result<(), WatcherDBError> {
         // Sanity test - the URL needs to be configured.
-        let urls = self.get_config_urls_with_txn(&db_txn)?;
+        let urls = self.get_config_urls_with_txn(&*db_txn)?;
         if !urls.contains(src_url) {
             log::trace!(self.logger, "{} not in {:?}", src_url, urls);
             return Err(WatcherDBError::NotFound);
@@ -319,17 +481,43 @@ impl WatcherDB {
             WriteFlags::empty(),
         )?;
 
-        db_txn.put(
-            self.last_synced,
-            &src_url.as_str().as_bytes(),
-            &key_bytes,
-            WriteFlags::empty(),
+        if backfill {
+            self.remove_missing_block_impl(db_txn, src_url, block_index)?;
+        } else {
+            db_txn.put(
+                self.last_synced,
+                &src_url.as_str().as_bytes(),
+                &key_bytes,
+                WriteFlags::empty(),
+            )?;
+        }
+
+        // Record the verification status of the signature, based on what we currently
+        // know about the signer.
+        let status = self.verification_status_for_signer(
+            &*db_txn,
+            signature_data.block_signature.signer(),
+            src_url,
         )?;
+        self.write_signature_verification_status(db_txn, src_url, block_index, status)?;
+        if !status.is_final() {
+            db_txn
+                .put(
+                    self.unverified_signatures_by_signer,
+                    &signer_and_url_key(signature_data.block_signature.signer(), src_url),
+                    &key_bytes,
+                    WriteFlags::NO_DUP_DATA,
+                )
+                .or_else(|err| match err {
+                    lmdb::Error::KeyExist => Ok(()),
+                    err => Err(err),
+                })?;
+        }
 
         // Add the block signer to our polling queue, unless we already have a report
         // for it.
         if !self.has_verification_report_for_signer_and_url(
-            &db_txn,
+            &*db_txn,
             signature_data.block_signature.signer(),
             src_url,
         )? {
@@ -341,7 +529,7 @@ impl WatcherDB {
             );
 
             self.queue_verification_report_poll(
-                &mut db_txn,
+                db_txn,
                 src_url,
                 signature_data.block_signature.signer(),
             )?;
@@ -354,8 +542,6 @@ impl WatcherDB {
             );
         }
 
-        // Done
-        db_txn.commit()?;
         Ok(())
     }
 
@@ -418,13 +604,31 @@ impl WatcherDB {
     pub fn get_block_timestamp(
         &self,
         block_index: u64,
+    ) -> Result<(u64, TimestampResultCode), WatcherDBError> {
+        self.get_block_timestamp_with_policy(block_index, &MinimumTimestamp)
+    }
+
+    /// Get the timestamp of a block, as selected by `policy` out of the
+    /// timestamps of the signatures collected from each tx source.
+    pub fn get_block_timestamp_with_policy(
+        &self,
+        block_index: u64,
+        policy: &impl TimestampPolicy,
     ) -> Result<(u64, TimestampResultCode), WatcherDBError> {
         if block_index == 0 || block_index == u64::MAX {
             return Ok((u64::MAX, TimestampResultCode::BlockIndexOutOfBounds));
         }
-        let sigs = self.get_block_signatures(block_index)?;
-        match sigs.iter().map(|s| s.block_signature.signed_at()).min() {
-            Some(earliest) => Ok((earliest, TimestampResultCode::TimestampFound)),
+        let mut timestamps: HashMap<Url, u64> = HashMap::default();
+        for signature_data in self.get_block_signatures(block_index)? {
+            let src_url = Url::parse(&signature_data.src_url)?;
+            let signed_at = signature_data.block_signature.signed_at();
+            timestamps
+                .entry(src_url)
+                .and_modify(|timestamp| *timestamp = (*timestamp).min(signed_at))
+                .or_insert(signed_at);
+        }
+        match policy.select_timestamp(&timestamps) {
+            Some(timestamp) => Ok((timestamp, TimestampResultCode::TimestampFound)),
             None => {
                 // Check whether we are synced for all watched URLs
                 let highest_common = self.highest_common_block()?;
@@ -489,6 +693,85 @@ impl WatcherDB {
         }
     }
 
+    /// Store the metadata `src_url` provided for a given block.
+    pub fn add_block_metadata(
+        &self,
+        src_url: &Url,
+        block_index: BlockIndex,
+        block_metadata: &BlockMetadata,
+    ) -> Result<(), WatcherDBError> {
+        if !self.write_allowed {
+            return Err(WatcherDBError::ReadOnly);
+        }
+
+        let mut db_txn = self.env.begin_rw_txn()?;
+        self.write_block_metadata(&mut db_txn, src_url, block_index, block_metadata)?;
+        db_txn.commit()?;
+        Ok(())
+    }
+
+    /// A helper for writing the metadata of a block in the given transaction.
+    fn write_block_metadata<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        src_url: &Url,
+        block_index: BlockIndex,
+        block_metadata: &BlockMetadata,
+    ) -> Result<(), WatcherDBError> {
+        // Sanity test - the URL needs to be configured.
+        let urls = self.get_config_urls_with_txn(&*db_txn)?;
+        if !urls.contains(src_url) {
+            return Err(WatcherDBError::NotFound);
+        }
+
+        db_txn.put(
+            self.block_metadata,
+            &block_index_and_url_key(block_index, src_url),
+            &encode(block_metadata),
+            WriteFlags::empty(),
+        )?;
+
+        // Make the attestation evidence available for re-validation.
+        let value_bytes = encode(block_metadata.contents().verification_report());
+        match db_txn.put(
+            self.verification_reports_by_hash,
+            &verification_report_hash(&value_bytes),
+            &value_bytes,
+            WriteFlags::NO_OVERWRITE,
+        ) {
+            Ok(()) | Err(lmdb::Error::KeyExist) => {}
+            Err(err) => Err(err)?,
+        };
+
+        Ok(())
+    }
+
+    /// Get the metadata stored for a given block, mapped by tx source url.
+    /// Sources that did not provide metadata for the block are omitted.
+    pub fn get_block_metadata(
+        &self,
+        block_index: BlockIndex,
+    ) -> Result<HashMap<Url, BlockMetadata>, WatcherDBError> {
+        let db_txn = self.env.begin_ro_txn()?;
+        let mut cursor = db_txn.open_ro_cursor(self.block_metadata)?;
+        let first_key_bytes = block_index.to_be_bytes();
+
+        let mut results = HashMap::default();
+        for (key_bytes, value_bytes) in cursor.iter_from(&first_key_bytes).filter_map(Result::ok) {
+            // The key is the block index, followed by the source url.
+            if key_bytes.len() < first_key_bytes.len()
+                || key_bytes[..first_key_bytes.len()] != first_key_bytes
+            {
+                // Moved to the next index, we're done.
+                break;
+            }
+
+            let tx_source_url = bytes_to_url(&key_bytes[first_key_bytes.len()..])?;
+            results.insert(tx_source_url, decode(value_bytes)?);
+        }
+        Ok(results)
+    }
+
     /// Get the last synced block per configured url.
     pub fn last_synced_blocks(&self) -> Result<HashMap<Url, Option<u64>>, WatcherDBError> {
         let db_txn = self.env.begin_ro_txn()?;
@@ -507,16 +790,113 @@ impl WatcherDB {
         }
 
         let mut db_txn = self.env.begin_rw_txn()?;
-        db_txn.put(
+        self.write_last_synced(&mut db_txn, src_url, block_index)?;
+        db_txn.commit()?;
+        Ok(())
+    }
+
+    fn write_last_synced<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        src_url: &Url,
+        block_index: u64,
+    ) -> Result<(), WatcherDBError> {
+        Ok(db_txn.put(
             self.last_synced,
             &src_url.as_str().as_bytes(),
             &block_index.to_be_bytes(),
             WriteFlags::empty(),
-        )?;
+        )?)
+    }
+
+    /// Record that `block_index` is missing from the archive of `src_url`, so
+    /// that it gets retried later.
+    pub fn add_missing_block(
+        &self,
+        src_url: &Url,
+        block_index: BlockIndex,
+    ) -> Result<(), WatcherDBError> {
+        if !self.write_allowed {
+            return Err(WatcherDBError::ReadOnly);
+        }
+
+        let mut db_txn = self.env.begin_rw_txn()?;
+        self.write_missing_block(&mut db_txn, src_url, block_index)?;
+        db_txn.commit()?;
+        Ok(())
+    }
+
+    fn write_missing_block<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        src_url: &Url,
+        block_index: BlockIndex,
+    ) -> Result<(), WatcherDBError> {
+        match db_txn.put(
+            self.missing_blocks,
+            &src_url.as_str().as_bytes(),
+            &block_index.to_be_bytes(),
+            WriteFlags::NO_DUP_DATA,
+        ) {
+            Ok(()) | Err(lmdb::Error::KeyExist) => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Remove `block_index` from the list of blocks missing from the archive
+    /// of `src_url`. This is for backfilled blocks that have no signature;
+    /// [WatcherDB::add_backfilled_block_signature] takes care of it otherwise.
+    pub fn remove_missing_block(
+        &self,
+        src_url: &Url,
+        block_index: BlockIndex,
+    ) -> Result<(), WatcherDBError> {
+        if !self.write_allowed {
+            return Err(WatcherDBError::ReadOnly);
+        }
+
+        let mut db_txn = self.env.begin_rw_txn()?;
+        self.remove_missing_block_impl(&mut db_txn, src_url, block_index)?;
         db_txn.commit()?;
         Ok(())
     }
 
+    fn remove_missing_block_impl<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        src_url: &Url,
+        block_index: BlockIndex,
+    ) -> Result<(), WatcherDBError> {
+        match db_txn.del(
+            self.missing_blocks,
+            &src_url.as_str().as_bytes(),
+            Some(&block_index.to_be_bytes()),
+        ) {
+            Ok(()) | Err(lmdb::Error::NotFound) => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Get the blocks that are missing from the archive of each tx source
+    /// url, sorted by block index. Urls with no missing blocks are omitted.
+    pub fn get_missing_blocks(&self) -> Result<HashMap<Url, Vec<BlockIndex>>, WatcherDBError> {
+        let db_txn = self.env.begin_ro_txn()?;
+        let mut cursor = db_txn.open_ro_cursor(self.missing_blocks)?;
+
+        let mut results: HashMap<Url, Vec<BlockIndex>> = HashMap::default();
+        for (key_bytes, value_bytes) in cursor.iter_start().filter_map(Result::ok) {
+            let src_url = bytes_to_url(key_bytes)?;
+            let block_index_bytes = value_bytes
+                .try_into()
+                .map_err(|_| WatcherDBError::Deserialization)?;
+            results
+                .entry(src_url)
+                .or_default()
+                .push(u64::from_be_bytes(block_index_bytes));
+        }
+        Ok(results)
+    }
+
     /// Get the highest block that all currently-configured urls have synced.
     /// Note: In the case where one watched consensus validator dies and is no
     /// longer       reporting blocks to S3, this will cause the
@@ -635,6 +1015,33 @@ impl WatcherDB {
             .get_block_data_map(&db_txn, block_index)
     }
 
+    /// Remove BlockData that is more than `retention` blocks behind the last
+    /// synced block of its source url. Block signatures, timestamps and
+    /// verification reports are kept.
+    ///
+    /// Returns the number of removed BlockData entries.
+    pub fn prune_block_data(&self, retention: u64) -> Result<u64, WatcherDBError> {
+        if !self.write_allowed {
+            return Err(WatcherDBError::ReadOnly);
+        }
+
+        let mut db_txn = self.env.begin_rw_txn()?;
+
+        let cutoffs: HashMap<Url, BlockIndex> = self
+            .get_url_to_last_synced(&db_txn)?
+            .into_iter()
+            .filter_map(|(src_url, last_synced)| {
+                let cutoff = (last_synced? + 1).saturating_sub(retention);
+                (cutoff > 0).then(|| (src_url, cutoff))
+            })
+            .collect();
+
+        let num_removed = self.block_data_store.prune(&mut db_txn, &cutoffs)?;
+
+        db_txn.commit()?;
+        Ok(num_removed)
+    }
+
     /// Record a verification report for a given source URL, that is associated
     /// with a specific block signer.
     /// Additionally, record no report for an optional list of expected block
@@ -690,6 +1097,30 @@ impl WatcherDB {
             self.write_verification_report(&mut db_txn, src_url, block_signer, None)?;
         }
 
+        // Update the verification status of the signatures made by the signers we now
+        // have results for. This must happen after the signer got its report, so that
+        // it doesn't get downgraded if it also shows up in
+        // `potential_block_signers`.
+        for block_signer in potential_block_signers.iter() {
+            if block_signer == verification_report_block_signer {
+                continue;
+            }
+
+            let status = self.verification_status_for_signer(&db_txn, block_signer, src_url)?;
+            self.update_unverified_signatures(&mut db_txn, src_url, block_signer, status)?;
+        }
+        let status = self.verification_status_for_signer(
+            &db_txn,
+            verification_report_block_signer,
+            src_url,
+        )?;
+        self.update_unverified_signatures(
+            &mut db_txn,
+            src_url,
+            verification_report_block_signer,
+            status,
+        )?;
+
         // Remove all the keys we encountered from the queue - we no longer need to poll
         // for them.
         self.remove_verification_report_poll_from_queue(
@@ -732,7 +1163,7 @@ impl WatcherDB {
         );
 
         // First, write the hash -> verification report entry.
-        let hash: [u8; 32] = value_bytes.digest32::<MerlinTranscript>(b"verification_report");
+        let hash = verification_report_hash(&value_bytes);
         match db_txn.put(
             self.verification_reports_by_hash,
             &hash,
@@ -772,18 +1203,77 @@ impl WatcherDB {
         Ok(())
     }
 
-    /// Get a VerificationReport by hash.
-    fn get_verification_report_by_hash(
+    /// Get all stored verification reports, including the ones included in
+    /// block metadata, keyed by report hash.
+    pub fn get_all_verification_reports(
         &self,
-        db_txn: &impl Transaction,
-        hash: &[u8],
-    ) -> Result<Option<VerificationReport>, WatcherDBError> {
-        let value_bytes = db_txn.get(self.verification_reports_by_hash, &hash)?;
-        Ok(if value_bytes.is_empty() {
-            None
-        } else {
-            Some(mc_util_serial::decode(value_bytes)?)
-        })
+    ) -> Result<Vec<([u8; 32], VerificationReport)>, WatcherDBError> {
+        let db_txn = self.env.begin_ro_txn()?;
+        let mut cursor = db_txn.open_ro_cursor(self.verification_reports_by_hash)?;
+
+        let mut results = Vec::new();
+        for (key_bytes, value_bytes) in cursor.iter_start().filter_map(Result::ok) {
+            // An empty value stands for "no report".
+            if value_bytes.is_empty() {
+                continue;
+            }
+            let hash = key_bytes
+                .try_into()
+                .map_err(|_| WatcherDBError::Deserialization)?;
+            results.push((hash, decode(value_bytes)?));
+        }
+        Ok(results)
+    }
+
+    /// Store the outcome of re-validating the verification report with the
+    /// given hash.
+    pub fn set_attestation_revalidation(
+        &self,
+        report_hash: &[u8; 32],
+        revalidation: &AttestationRevalidation,
+    ) -> Result<(), WatcherDBError> {
+        if !self.write_allowed {
+            return Err(WatcherDBError::ReadOnly);
+        }
+
+        let mut db_txn = self.env.begin_rw_txn()?;
+        db_txn.put(
+            self.attestation_revalidation_by_report_hash,
+            report_hash,
+            &encode(revalidation),
+            WriteFlags::empty(),
+        )?;
+        db_txn.commit()?;
+        Ok(())
+    }
+
+    /// Get the outcome of the last re-validation of a verification report, if
+    /// it was re-validated.
+    pub fn get_attestation_revalidation(
+        &self,
+        verification_report: &VerificationReport,
+    ) -> Result<Option<AttestationRevalidation>, WatcherDBError> {
+        let db_txn = self.env.begin_ro_txn()?;
+        let report_hash = verification_report_hash(&encode(verification_report));
+        match db_txn.get(self.attestation_revalidation_by_report_hash, &report_hash) {
+            Ok(value_bytes) => Ok(Some(decode(value_bytes)?)),
+            Err(lmdb::Error::NotFound) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Get a VerificationReport by hash.
+    fn get_verification_report_by_hash(
+        &self,
+        db_txn: &impl Transaction,
+        hash: &[u8],
+    ) -> Result<Option<VerificationReport>, WatcherDBError> {
+        let value_bytes = db_txn.get(self.verification_reports_by_hash, &hash)?;
+        Ok(if value_bytes.is_empty() {
+            None
+        } else {
+            Some(mc_util_serial::decode(value_bytes)?)
+        })
     }
 
     /// Get a verification report for a given block signer.
@@ -857,22 +1347,231 @@ impl WatcherDB {
         src_url: &Url,
     ) -> Result<Vec<Option<VerificationReport>>, WatcherDBError> {
         let db_txn = self.env.begin_ro_txn()?;
+        self.get_verification_reports_for_signer_and_url_impl(&db_txn, block_signer, src_url)
+    }
 
-        let mut key_bytes = block_signer.to_bytes().to_vec();
-        key_bytes.extend(src_url.as_str().as_bytes());
+    fn get_verification_reports_for_signer_and_url_impl(
+        &self,
+        db_txn: &impl Transaction,
+        block_signer: &Ed25519Public,
+        src_url: &Url,
+    ) -> Result<Vec<Option<VerificationReport>>, WatcherDBError> {
+        let key_bytes = signer_and_url_key(block_signer, src_url);
 
         let mut cursor = db_txn.open_ro_cursor(self.verification_reports_by_signer)?;
         let mut results = Vec::new();
         for (key_bytes2, value_bytes) in cursor.iter_dup_of(&key_bytes).filter_map(Result::ok) {
             assert_eq!(key_bytes, key_bytes2);
 
-            let report = self.get_verification_report_by_hash(&db_txn, value_bytes)?;
+            let report = self.get_verification_report_by_hash(db_txn, value_bytes)?;
             results.push(report);
         }
 
         Ok(results)
     }
 
+    /// The verification status of the signatures `block_signer` made for
+    /// blocks provided by `src_url`, based on the verification reports
+    /// collected for the signer. The signatures are only verified once one of
+    /// the reports passes the verification report checker.
+    fn verification_status_for_signer(
+        &self,
+        db_txn: &impl Transaction,
+        block_signer: &Ed25519Public,
+        src_url: &Url,
+    ) -> Result<SignatureVerificationStatus, WatcherDBError> {
+        let reports =
+            self.get_verification_reports_for_signer_and_url_impl(db_txn, block_signer, src_url)?;
+        if reports.is_empty() {
+            return Ok(SignatureVerificationStatus::Pending);
+        }
+        if reports.iter().all(Option::is_none) {
+            return Ok(SignatureVerificationStatus::NoVerificationReport);
+        }
+
+        let checker = match self.verification_report_checker.as_ref() {
+            Some(checker) => checker,
+            None => return Ok(SignatureVerificationStatus::Pending),
+        };
+        for report in reports.iter().flatten() {
+            match checker.check(report, block_signer) {
+                Ok(()) => return Ok(SignatureVerificationStatus::Verified),
+                Err(err) => log::warn!(
+                    self.logger,
+                    "Verification report from {} for signer {} was rejected: {}",
+                    src_url,
+                    hex::encode(block_signer.to_bytes()),
+                    err
+                ),
+            }
+        }
+        Ok(SignatureVerificationStatus::InvalidVerificationReport)
+    }
+
+    /// Get the verification status of the signature `src_url` provided for a
+    /// given block.
+    /// Returns `WatcherDBError::NotFound` if no signature was stored for the
+    /// block by `src_url`.
+    pub fn get_signature_verification_status(
+        &self,
+        src_url: &Url,
+        block_index: BlockIndex,
+    ) -> Result<SignatureVerificationStatus, WatcherDBError> {
+        let db_txn = self.env.begin_ro_txn()?;
+        let value_bytes = db_txn.get(
+            self.signature_verification_status,
+            &block_index_and_url_key(block_index, src_url),
+        )?;
+        bytes_to_verification_status(value_bytes)
+    }
+
+    /// Get the verification status of all signatures stored for a given block,
+    /// mapped by tx source url.
+    pub fn get_signature_verification_statuses(
+        &self,
+        block_index: BlockIndex,
+    ) -> Result<HashMap<Url, SignatureVerificationStatus>, WatcherDBError> {
+        let db_txn = self.env.begin_ro_txn()?;
+        let mut cursor = db_txn.open_ro_cursor(self.signature_verification_status)?;
+        let first_key_bytes = block_index.to_be_bytes();
+
+        let mut results = HashMap::default();
+        for (key_bytes, value_bytes) in cursor.iter_from(&first_key_bytes).filter_map(Result::ok) {
+            // The key is the block index, followed by the source url.
+            if key_bytes.len() < first_key_bytes.len()
+                || key_bytes[..first_key_bytes.len()] != first_key_bytes
+            {
+                // Moved to the next index, we're done.
+                break;
+            }
+
+            let tx_source_url = bytes_to_url(&key_bytes[first_key_bytes.len()..])?;
+            results.insert(tx_source_url, bytes_to_verification_status(value_bytes)?);
+        }
+        Ok(results)
+    }
+
+    /// Mark the signature `src_url` provided for a given block as not matching
+    /// the block. This is final - collecting a verification report for the
+    /// signer will not change the status.
+    pub fn mark_signature_invalid(
+        &self,
+        src_url: &Url,
+        block_index: BlockIndex,
+    ) -> Result<(), WatcherDBError> {
+        if !self.write_allowed {
+            return Err(WatcherDBError::ReadOnly);
+        }
+
+        let mut db_txn = self.env.begin_rw_txn()?;
+        self.set_signature_verification_status(
+            &mut db_txn,
+            src_url,
+            block_index,
+            SignatureVerificationStatus::InvalidSignature,
+        )?;
+        db_txn.commit()?;
+        Ok(())
+    }
+
+    /// Overwrite the verification status of the signature `src_url` provided
+    /// for a given block. Signatures given a final status are no longer
+    /// tracked as unverified.
+    fn set_signature_verification_status<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        src_url: &Url,
+        block_index: BlockIndex,
+        status: SignatureVerificationStatus,
+    ) -> Result<(), WatcherDBError> {
+        let signature_data = self
+            .get_block_signatures_impl(&*db_txn, block_index)?
+            .into_iter()
+            .find(|signature_data| signature_data.src_url == src_url.as_str())
+            .ok_or(WatcherDBError::NotFound)?;
+
+        self.write_signature_verification_status(db_txn, src_url, block_index, status)?;
+
+        if status.is_final() {
+            match db_txn.del(
+                self.unverified_signatures_by_signer,
+                &signer_and_url_key(signature_data.block_signature.signer(), src_url),
+                Some(&block_index.to_be_bytes()),
+            ) {
+                Ok(()) | Err(lmdb::Error::NotFound) => {}
+                Err(err) => Err(err)?,
+            };
+        }
+
+        Ok(())
+    }
+
+    /// A helper for writing the verification status of a single (block index,
+    /// src_url) signature.
+    fn write_signature_verification_status<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        src_url: &Url,
+        block_index: BlockIndex,
+        status: SignatureVerificationStatus,
+    ) -> Result<(), WatcherDBError> {
+        Ok(db_txn.put(
+            self.signature_verification_status,
+            &block_index_and_url_key(block_index, src_url),
+            &(status as u32).to_be_bytes(),
+            WriteFlags::empty(),
+        )?)
+    }
+
+    /// Update the verification status of all not-yet-verified signatures made
+    /// by `block_signer` for blocks provided by `src_url`. Once a signature is
+    /// verified it is no longer tracked.
+    fn update_unverified_signatures<'env>(
+        &self,
+        db_txn: &mut RwTransaction<'env>,
+        src_url: &Url,
+        block_signer: &Ed25519Public,
+        status: SignatureVerificationStatus,
+    ) -> Result<(), WatcherDBError> {
+        let key_bytes = signer_and_url_key(block_signer, src_url);
+
+        let block_indexes = {
+            let mut cursor = db_txn.open_ro_cursor(self.unverified_signatures_by_signer)?;
+            cursor
+                .iter_dup_of(&key_bytes)
+                .map(|result| {
+                    let (_key_bytes, value_bytes) = result?;
+                    let block_index_bytes = value_bytes
+                        .try_into()
+                        .map_err(|_| WatcherDBError::Deserialization)?;
+                    Ok(u64::from_be_bytes(block_index_bytes))
+                })
+                .collect::<Result<Vec<_>, WatcherDBError>>()?
+        };
+
+        log::trace!(
+            self.logger,
+            "Marking {} signatures by {} from {} as {}",
+            block_indexes.len(),
+            hex::encode(block_signer.to_bytes()),
+            src_url,
+            status,
+        );
+
+        for block_index in block_indexes {
+            self.write_signature_verification_status(db_txn, src_url, block_index, status)?;
+        }
+
+        if status.is_final() {
+            match db_txn.del(self.unverified_signatures_by_signer, &key_bytes, None) {
+                Ok(()) | Err(lmdb::Error::NotFound) => {}
+                Err(err) => Err(err)?,
+            };
+        }
+
+        Ok(())
+    }
+
     /// Check if a given pair of src_url/block_signer have already been polled.
     fn has_verification_report_for_signer_and_url(
         &self,
@@ -1020,6 +1719,36 @@ impl WatcherDB {
             }
         }
 
+        // Remove signature verification statuses associated with this source URL.
+        for block_index in 0..=last_synced_block_index {
+            match db_txn.del(
+                self.signature_verification_status,
+                &block_index_and_url_key(block_index, src_url),
+                None,
+            ) {
+                Ok(()) | Err(lmdb::Error::NotFound) => {}
+                Err(err) => Err(err)?,
+            };
+        }
+
+        // Remove block metadata associated with this source URL.
+        for block_index in 0..=last_synced_block_index {
+            match db_txn.del(
+                self.block_metadata,
+                &block_index_and_url_key(block_index, src_url),
+                None,
+            ) {
+                Ok(()) | Err(lmdb::Error::NotFound) => {}
+                Err(err) => Err(err)?,
+            };
+        }
+
+        // Remove missing blocks.
+        match db_txn.del(self.missing_blocks, &src_url.as_str().as_bytes(), None) {
+            Ok(()) | Err(lmdb::Error::NotFound) => {}
+            Err(err) => Err(err)?,
+        };
+
         // Remove last synced.
         match db_txn.del(self.last_synced, &src_url.as_str().as_bytes(), None) {
             Ok(()) | Err(lmdb::Error::NotFound) => {}
@@ -1043,10 +1772,294 @@ impl WatcherDB {
         }
         drop(cursor);
 
+        // Remove unverified signatures. The key format is the same as above.
+        let mut cursor = db_txn.open_rw_cursor(self.unverified_signatures_by_signer)?;
+        for (key_bytes, _value_bytes) in cursor.iter_start().filter_map(Result::ok) {
+            if key_bytes.len() < signer_key_size {
+                continue;
+            }
+
+            let tx_source_url_bytes = &key_bytes[signer_key_size..];
+            let tx_source_url = bytes_to_url(tx_source_url_bytes)?;
+            if &tx_source_url == src_url {
+                cursor.del(WriteFlags::empty())?;
+            }
+        }
+        drop(cursor);
+
         // Done
         db_txn.commit()?;
         Ok(())
     }
+
+    /// Write a snapshot of the block signatures, block metadata, verification
+    /// reports and sync progress of the configured tx source urls to `writer`.
+    /// The snapshot is taken in a single transaction, so it is consistent
+    /// even while the database is being written to. Block data is not
+    /// included. See [crate::snapshot] for the format.
+    pub fn export_snapshot(
+        &self,
+        writer: &mut impl Write,
+    ) -> Result<SnapshotSummary, WatcherDBError> {
+        let db_txn = self.env.begin_ro_txn()?;
+        let tx_source_urls = self.get_config_urls_with_txn(&db_txn)?;
+        let mut summary = SnapshotSummary::default();
+
+        write_record(
+            writer,
+            SnapshotRecordKind::Header(SnapshotHeader {
+                format_version: SNAPSHOT_FORMAT_VERSION,
+                tx_source_urls: tx_source_urls.iter().map(Url::to_string).collect(),
+            }),
+        )?;
+
+        // Verification reports go first, so that importing signatures afterwards
+        // gives them the right verification status.
+        let signer_key_size = <Ed25519Public as ReprBytes>::Size::USIZE;
+        let mut cursor = db_txn.open_ro_cursor(self.verification_reports_by_signer)?;
+        for (key_bytes, value_bytes) in cursor.iter_start().filter_map(Result::ok) {
+            // The key format is 32 bytes signer public key followed by tx source url.
+            if key_bytes.len() < signer_key_size {
+                continue;
+            }
+
+            let src_url = bytes_to_url(&key_bytes[signer_key_size..])?;
+            if !tx_source_urls.contains(&src_url) {
+                continue;
+            }
+
+            write_record(
+                writer,
+                SnapshotRecordKind::VerificationReport(SnapshotVerificationReport {
+                    src_url: src_url.to_string(),
+                    block_signer: key_bytes[..signer_key_size].to_vec(),
+                    report: self.get_verification_report_by_hash(&db_txn, value_bytes)?,
+                }),
+            )?;
+            summary.num_verification_reports += 1;
+        }
+        drop(cursor);
+
+        // Block signatures, in block index order.
+        let mut cursor = db_txn.open_ro_cursor(self.block_signatures)?;
+        for (key_bytes, value_bytes) in cursor.iter_start().filter_map(Result::ok) {
+            let block_index = u64::from_be_bytes(
+                key_bytes
+                    .try_into()
+                    .map_err(|_| WatcherDBError::Deserialization)?,
+            );
+            let signature_data: BlockSignatureData = decode(value_bytes)?;
+            let src_url = Url::parse(&signature_data.src_url)?;
+            if !tx_source_urls.contains(&src_url) {
+                continue;
+            }
+
+            let verification_status = match db_txn.get(
+                self.signature_verification_status,
+                &block_index_and_url_key(block_index, &src_url),
+            ) {
+                Ok(value_bytes) => bytes_to_verification_status(value_bytes)?,
+                Err(lmdb::Error::NotFound) => SignatureVerificationStatus::Pending,
+                Err(err) => Err(err)?,
+            };
+
+            write_record(
+                writer,
+                SnapshotRecordKind::BlockSignature(SnapshotBlockSignature {
+                    block_index,
+                    src_url: signature_data.src_url,
+                    archive_filename: signature_data.archive_filename,
+                    block_signature: signature_data.block_signature,
+                    verification_status: verification_status as u32,
+                }),
+            )?;
+            summary.num_block_signatures += 1;
+        }
+        drop(cursor);
+
+        // Block metadata.
+        let mut cursor = db_txn.open_ro_cursor(self.block_metadata)?;
+        for (key_bytes, value_bytes) in cursor.iter_start().filter_map(Result::ok) {
+            // The key is the block index, followed by the source url.
+            if key_bytes.len() < 8 {
+                continue;
+            }
+            let block_index = u64::from_be_bytes(key_bytes[..8].try_into().unwrap());
+            let src_url = bytes_to_url(&key_bytes[8..])?;
+            if !tx_source_urls.contains(&src_url) {
+                continue;
+            }
+
+            write_record(
+                writer,
+                SnapshotRecordKind::BlockMetadata(SnapshotBlockMetadata {
+                    block_index,
+                    src_url: src_url.to_string(),
+                    block_metadata: decode(value_bytes)?,
+                }),
+            )?;
+            summary.num_block_metadata += 1;
+        }
+        drop(cursor);
+
+        // Sync progress.
+        let last_synced = self.get_url_to_last_synced(&db_txn)?;
+        let mut cursor = db_txn.open_ro_cursor(self.missing_blocks)?;
+        for src_url in tx_source_urls.iter() {
+            let missing_blocks = cursor
+                .iter_dup_of(&src_url.as_str().as_bytes())
+                .filter_map(Result::ok)
+                .map(|(_key_bytes, value_bytes)| {
+                    value_bytes
+                        .try_into()
+                        .map(u64::from_be_bytes)
+                        .map_err(|_| WatcherDBError::Deserialization)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            write_record(
+                writer,
+                SnapshotRecordKind::LastSynced(SnapshotLastSynced {
+                    src_url: src_url.to_string(),
+                    block_index: last_synced.get(src_url).copied().flatten(),
+                    missing_blocks,
+                }),
+            )?;
+        }
+
+        Ok(summary)
+    }
+
+    /// Create a WatcherDB at `path` from a snapshot written by
+    /// [WatcherDB::export_snapshot]. The new database is configured with the
+    /// tx source urls of the exported one.
+    ///
+    /// Importing a record that is already in the database has no effect, so
+    /// an import that failed part way can be resumed by importing the same
+    /// snapshot again. Fails with `WatcherDBError::AlreadyExists` if there
+    /// already is a database at `path` that is configured with different tx
+    /// source urls.
+    pub fn import_snapshot(
+        path: &Path,
+        reader: &mut impl Read,
+        logger: Logger,
+    ) -> Result<SnapshotSummary, WatcherDBError> {
+        let header = match read_record(reader)?.and_then(|record| record.kind) {
+            Some(SnapshotRecordKind::Header(header)) => header,
+            _ => return Err(WatcherDBError::Deserialization),
+        };
+        if header.format_version != SNAPSHOT_FORMAT_VERSION {
+            return Err(WatcherDBError::UnsupportedSnapshotVersion(
+                header.format_version,
+            ));
+        }
+        let tx_source_urls = header
+            .tx_source_urls
+            .iter()
+            .map(|src_url| Url::parse(src_url))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let watcher_db = if path.join("data.mdb").exists() {
+            let mut existing_urls = WatcherDB::open_ro(path, logger.clone())?.get_config_urls()?;
+            let mut expected_urls = tx_source_urls.clone();
+            existing_urls.sort();
+            expected_urls.sort();
+            if existing_urls != expected_urls {
+                return Err(WatcherDBError::AlreadyExists);
+            }
+            WatcherDB::open_rw(path, &tx_source_urls, logger)?
+        } else {
+            create_or_open_rw_watcher_db(path, &tx_source_urls, logger)?
+        };
+        let mut summary = SnapshotSummary::default();
+
+        // Records are written in batches, as a transaction per record would make
+        // importing a long history very slow.
+        let mut db_txn = watcher_db.env.begin_rw_txn()?;
+        let mut num_pending_records = 0;
+        while let Some(record) = read_record(reader)? {
+            match record.kind {
+                Some(SnapshotRecordKind::VerificationReport(entry)) => {
+                    let src_url = Url::parse(&entry.src_url)?;
+                    let signer = Ed25519Public::try_from(&entry.block_signer[..])?;
+                    watcher_db.write_verification_report(
+                        &mut db_txn,
+                        &src_url,
+                        &signer,
+                        entry.report.as_ref(),
+                    )?;
+                    summary.num_verification_reports += 1;
+                }
+
+                Some(SnapshotRecordKind::BlockSignature(entry)) => {
+                    let src_url = Url::parse(&entry.src_url)?;
+                    let existing = watcher_db
+                        .get_block_signatures_impl(&db_txn, entry.block_index)?
+                        .into_iter()
+                        .find(|signature_data| signature_data.src_url == src_url.as_str());
+                    match existing {
+                        None => watcher_db.write_block_signature(
+                            &mut db_txn,
+                            &src_url,
+                            entry.block_index,
+                            entry.block_signature,
+                            entry.archive_filename,
+                            false,
+                        )?,
+                        Some(signature_data)
+                            if signature_data.block_signature == entry.block_signature => {}
+                        Some(_) => return Err(WatcherDBError::AlreadyExists),
+                    }
+
+                    // The exporting watcher may have checked verification reports the
+                    // importing one has no checker for, so its verdict is kept.
+                    let status = SignatureVerificationStatus::try_from(entry.verification_status)?;
+                    watcher_db.set_signature_verification_status(
+                        &mut db_txn,
+                        &src_url,
+                        entry.block_index,
+                        status,
+                    )?;
+                    summary.num_block_signatures += 1;
+                }
+
+                Some(SnapshotRecordKind::BlockMetadata(entry)) => {
+                    let src_url = Url::parse(&entry.src_url)?;
+                    watcher_db.write_block_metadata(
+                        &mut db_txn,
+                        &src_url,
+                        entry.block_index,
+                        &entry.block_metadata,
+                    )?;
+                    summary.num_block_metadata += 1;
+                }
+
+                Some(SnapshotRecordKind::LastSynced(entry)) => {
+                    let src_url = Url::parse(&entry.src_url)?;
+                    if let Some(block_index) = entry.block_index {
+                        watcher_db.write_last_synced(&mut db_txn, &src_url, block_index)?;
+                    }
+                    for block_index in entry.missing_blocks {
+                        watcher_db.write_missing_block(&mut db_txn, &src_url, block_index)?;
+                    }
+                }
+
+                Some(SnapshotRecordKind::Header(_)) | None => {
+                    return Err(WatcherDBError::Deserialization);
+                }
+            }
+
+            num_pending_records += 1;
+            if num_pending_records == SNAPSHOT_IMPORT_BATCH_SIZE {
+                db_txn.commit()?;
+                db_txn = watcher_db.env.begin_rw_txn()?;
+                num_pending_records = 0;
+            }
+        }
+        db_txn.commit()?;
+
+        Ok(summary)
+    }
 }
 
 /// Open an existing WatcherDB or create a new one in read-write mode.
@@ -1085,12 +2098,47 @@ fn bytes_to_url(bytes: &[u8]) -> Result<Url, WatcherDBError> {
     Ok(Url::parse(str::from_utf8(bytes)?)?)
 }
 
+fn bytes_to_verification_status(
+    bytes: &[u8],
+) -> Result<SignatureVerificationStatus, WatcherDBError> {
+    let bytes = bytes
+        .try_into()
+        .map_err(|_| WatcherDBError::Deserialization)?;
+    SignatureVerificationStatus::try_from(u32::from_be_bytes(bytes))
+}
+
+/// Database key for data associated with a (block signer, tx source url)
+/// pair.
+fn signer_and_url_key(signer: &Ed25519Public, src_url: &Url) -> Vec<u8> {
+    let mut key_bytes = signer.to_bytes().to_vec();
+    key_bytes.extend(src_url.as_str().as_bytes());
+    key_bytes
+}
+
+/// Database key for data associated with a (block index, tx source url)
+/// pair.
+fn block_index_and_url_key(block_index: BlockIndex, src_url: &Url) -> Vec<u8> {
+    let mut key_bytes = block_index.to_be_bytes().to_vec();
+    key_bytes.extend(src_url.as_str().as_bytes());
+    key_bytes
+}
+
+/// The key of an encoded verification report in the
+/// `verification_reports_by_hash` database.
+fn verification_report_hash(value_bytes: &[u8]) -> [u8; 32] {
+    value_bytes.digest32::<MerlinTranscript>(b"verification_report")
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
-    use mc_blockchain_test_utils::get_blocks;
+    use crate::timestamp_policy::{MedianOfK, TrustedSources};
+    use mc_blockchain_test_utils::{get_blocks, make_block_metadata};
     use mc_blockchain_types::BlockVersion;
-    use mc_common::logger::{test_with_logger, Logger};
+    use mc_common::{
+        logger::{test_with_logger, Logger},
+        HashSet,
+    };
     use mc_crypto_keys::Ed25519Pair;
     use mc_util_from_random::FromRandom;
     use mc_util_test_helper::{get_seeded_rng, run_with_one_seed};
@@ -1098,10 +2146,33 @@ pub mod tests {
     use rand_hc::Hc128Rng;
     use tempdir::TempDir;
 
+    /// The body of the test verification reports that [TestReportChecker]
+    /// rejects.
+    pub const INVALID_REPORT_BODY: &str = "invalid report";
+
+    /// Accepts all verification reports, except the ones whose body is
+    /// [INVALID_REPORT_BODY], since test reports are not real IAS reports.
+    pub struct TestReportChecker;
+    impl VerificationReportChecker for TestReportChecker {
+        fn check(
+            &self,
+            verification_report: &VerificationReport,
+            _block_signer: &Ed25519Public,
+        ) -> Result<(), String> {
+            if verification_report.http_body == INVALID_REPORT_BODY {
+                Err("invalid test report".to_owned())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
     pub fn setup_watcher_db(src_urls: &[Url], logger: Logger) -> WatcherDB {
         let db_tmp = TempDir::new("wallet_db").expect("Could not make tempdir for wallet db");
         WatcherDB::create(db_tmp.path()).expect("Failed to create WatcherDB");
-        WatcherDB::open_rw(db_tmp.path(), src_urls, logger).unwrap()
+        let mut watcher_db = WatcherDB::open_rw(db_tmp.path(), src_urls, logger).unwrap();
+        watcher_db.set_verification_report_checker(TestReportChecker);
+        watcher_db
     }
 
     pub fn setup_blocks() -> Vec<BlockData> {
@@ -1299,10 +2370,33 @@ pub mod tests {
                 (u64::MAX, TimestampResultCode::Unavailable)
             );
 
-            // Verify that block index 0 is out of bounds
+            // Other policies pick among the same timestamps.
             assert_eq!(
-                watcher_db.get_block_timestamp(0).unwrap(),
-                (u64::MAX, TimestampResultCode::BlockIndexOutOfBounds)
+                watcher_db
+                    .get_block_timestamp_with_policy(1, &MedianOfK { k: 2 })
+                    .unwrap(),
+                (1594679718, TimestampResultCode::TimestampFound)
+            );
+            assert_eq!(
+                watcher_db
+                    .get_block_timestamp_with_policy(1, &MedianOfK { k: 3 })
+                    .unwrap(),
+                (u64::MAX, TimestampResultCode::Unavailable)
+            );
+            let trusted = TrustedSources {
+                src_urls: HashSet::from_iter([urls[1].clone()]),
+            };
+            assert_eq!(
+                watcher_db
+                    .get_block_timestamp_with_policy(1, &trusted)
+                    .unwrap(),
+                (1594679727, TimestampResultCode::TimestampFound)
+            );
+
+            // Verify that block index 0 is out of bounds
+            assert_eq!(
+                watcher_db.get_block_timestamp(0).unwrap(),
+                (u64::MAX, TimestampResultCode::BlockIndexOutOfBounds)
             );
 
             // Verify that u64::MAX is out of bounds
@@ -1985,4 +3079,535 @@ pub mod tests {
             ])
         );
     }
+
+    // Pruning should only remove block data that fell out of the retention
+    // window, and keep signatures and timestamps.
+    #[test_with_logger]
+    fn test_prune_block_data(logger: Logger) {
+        let url1 = Url::parse("http://www.my_url1.com").unwrap();
+        let url2 = Url::parse("http://www.my_url2.com").unwrap();
+        let urls = vec![url1.clone(), url2.clone()];
+        let watcher_db = setup_watcher_db(&urls, logger.clone());
+        let blocks_data = setup_blocks();
+        let filename = String::from("00/00");
+
+        // Nothing synced yet, nothing to prune.
+        assert_eq!(watcher_db.prune_block_data(3), Ok(0));
+
+        // url1 syncs all blocks, url2 only the first five.
+        for (i, block_data) in blocks_data.iter().enumerate() {
+            let block_index = block_data.block().index;
+            let signature = block_data.signature().cloned().unwrap();
+
+            watcher_db.add_block_data(&url1, block_data).unwrap();
+            watcher_db
+                .add_block_signature(&url1, block_index, signature.clone(), filename.clone())
+                .unwrap();
+
+            if i < 5 {
+                watcher_db.add_block_data(&url2, block_data).unwrap();
+                watcher_db
+                    .add_block_signature(&url2, block_index, signature, filename.clone())
+                    .unwrap();
+            }
+        }
+
+        // Keep the last 3 blocks of each source: url1 keeps 7..=9, url2 keeps 2..=4.
+        assert_eq!(watcher_db.prune_block_data(3), Ok(7 + 2));
+        assert_eq!(watcher_db.prune_block_data(3), Ok(0));
+
+        for (i, block_data) in blocks_data.iter().enumerate() {
+            let block_index = block_data.block().index;
+
+            let url1_data = watcher_db.get_block_data(&url1, block_index);
+            if i < 7 {
+                assert_eq!(url1_data, Err(WatcherDBError::NotFound));
+            } else {
+                assert_eq!(url1_data.unwrap(), *block_data);
+            }
+
+            let url2_data = watcher_db.get_block_data(&url2, block_index);
+            if (2..5).contains(&i) {
+                assert_eq!(url2_data.unwrap(), *block_data);
+            } else {
+                assert_eq!(url2_data, Err(WatcherDBError::NotFound));
+            }
+
+            // Signatures are kept.
+            let num_signatures = if i < 5 { 2 } else { 1 };
+            assert_eq!(
+                watcher_db.get_block_signatures(block_index).unwrap().len(),
+                num_signatures
+            );
+        }
+
+        // Timestamps are still available for pruned blocks.
+        assert_eq!(
+            watcher_db.get_block_timestamp(1).unwrap().1,
+            TimestampResultCode::TimestampFound
+        );
+    }
+
+    // Block metadata should be stored per block and url.
+    #[test_with_logger]
+    fn test_block_metadata(logger: Logger) {
+        run_with_one_seed(|mut rng| {
+            let url1 = Url::parse("http://www.my_url1.com").unwrap();
+            let url2 = Url::parse("http://www.my_url2.com").unwrap();
+            let url3 = Url::parse("http://www.my_url3.com").unwrap();
+            let watcher_db = setup_watcher_db(&[url1.clone(), url2.clone()], logger.clone());
+            let blocks = setup_blocks();
+
+            let metadata1 = make_block_metadata(blocks[1].block().id.clone(), &mut rng);
+            let metadata2 = make_block_metadata(blocks[1].block().id.clone(), &mut rng);
+            let metadata3 = make_block_metadata(blocks[2].block().id.clone(), &mut rng);
+
+            assert_eq!(watcher_db.get_block_metadata(1), Ok(HashMap::default()));
+
+            watcher_db.add_block_metadata(&url1, 1, &metadata1).unwrap();
+            watcher_db.add_block_metadata(&url2, 1, &metadata2).unwrap();
+            watcher_db.add_block_metadata(&url1, 2, &metadata3).unwrap();
+            assert_eq!(
+                watcher_db.add_block_metadata(&url3, 1, &metadata1),
+                Err(WatcherDBError::NotFound)
+            );
+
+            assert_eq!(
+                watcher_db.get_block_metadata(1),
+                Ok(HashMap::from_iter([
+                    (url1.clone(), metadata1),
+                    (url2.clone(), metadata2.clone()),
+                ]))
+            );
+            assert_eq!(
+                watcher_db.get_block_metadata(2),
+                Ok(HashMap::from_iter([(url1.clone(), metadata3)]))
+            );
+
+            // Removing a source removes its metadata.
+            watcher_db.update_last_synced(&url1, 2).unwrap();
+            watcher_db.remove_all_for_source_url(&url1).unwrap();
+            assert_eq!(
+                watcher_db.get_block_metadata(1),
+                Ok(HashMap::from_iter([(url2, metadata2)]))
+            );
+            assert_eq!(watcher_db.get_block_metadata(2), Ok(HashMap::default()));
+        });
+    }
+
+    // Missing blocks should be tracked per url until they get backfilled.
+    #[test_with_logger]
+    fn test_missing_blocks(logger: Logger) {
+        let url1 = Url::parse("http://www.my_url1.com").unwrap();
+        let url2 = Url::parse("http://www.my_url2.com").unwrap();
+        let urls = vec![url1.clone(), url2.clone()];
+        let watcher_db = setup_watcher_db(&urls, logger.clone());
+        let blocks_data = setup_blocks();
+        let filename = String::from("00/00");
+
+        assert_eq!(watcher_db.get_missing_blocks(), Ok(HashMap::default()));
+
+        // url1 synced up to block 5, but blocks 2 and 4 were missing.
+        watcher_db.update_last_synced(&url1, 5).unwrap();
+        watcher_db.add_missing_block(&url1, 4).unwrap();
+        watcher_db.add_missing_block(&url1, 2).unwrap();
+        // Adding the same block twice is fine.
+        watcher_db.add_missing_block(&url1, 2).unwrap();
+        watcher_db.add_missing_block(&url2, 3).unwrap();
+
+        assert_eq!(
+            watcher_db.get_missing_blocks(),
+            Ok(HashMap::from_iter([
+                (url1.clone(), vec![2, 4]),
+                (url2.clone(), vec![3]),
+            ]))
+        );
+
+        // Backfilling a signed block removes it from the list, without moving the last
+        // synced block.
+        watcher_db
+            .add_backfilled_block_signature(
+                &url1,
+                2,
+                blocks_data[2].signature().cloned().unwrap(),
+                filename,
+            )
+            .unwrap();
+        assert_eq!(watcher_db.get_block_signatures(2).unwrap().len(), 1);
+        assert_eq!(
+            watcher_db.last_synced_blocks().unwrap().get(&url1),
+            Some(&Some(5))
+        );
+
+        // Unsigned blocks get removed explicitly.
+        watcher_db.remove_missing_block(&url1, 4).unwrap();
+        assert_eq!(
+            watcher_db.get_missing_blocks(),
+            Ok(HashMap::from_iter([(url2.clone(), vec![3])]))
+        );
+
+        // Removing a url removes its missing blocks.
+        watcher_db.remove_all_for_source_url(&url2).unwrap();
+        assert_eq!(watcher_db.get_missing_blocks(), Ok(HashMap::default()));
+    }
+
+    // Signature verification statuses should track the verification reports
+    // collected for the signers.
+    #[test_with_logger]
+    fn test_signature_verification_status(logger: Logger) {
+        run_with_one_seed(|mut rng| {
+            let url1 = Url::parse("http://www.my_url1.com").unwrap();
+            let url2 = Url::parse("http://www.my_url2.com").unwrap();
+            let urls = [url1.clone(), url2.clone()];
+            let watcher_db = setup_watcher_db(&urls, logger.clone());
+
+            let blocks = setup_blocks();
+            let signing_key_a = Ed25519Pair::from_random(&mut rng);
+            let signing_key_b = Ed25519Pair::from_random(&mut rng);
+            let signing_key_c = Ed25519Pair::from_random(&mut rng);
+            let signing_key_d = Ed25519Pair::from_random(&mut rng);
+            let filename = String::from("00/00");
+
+            let verification_report = VerificationReport {
+                sig: vec![1u8; 32].into(),
+                chain: vec![vec![2; 16], vec![3; 32]],
+                http_body: "test body".to_owned(),
+            };
+
+            let sign = |block_index: usize, key: &Ed25519Pair| {
+                BlockSignature::from_block_and_keypair(blocks[block_index].block(), key).unwrap()
+            };
+
+            // Nothing is known for blocks without signatures.
+            assert_eq!(
+                watcher_db.get_signature_verification_status(&url1, 1),
+                Err(WatcherDBError::NotFound)
+            );
+
+            // A signature for a signer we know nothing about is pending.
+            watcher_db
+                .add_block_signature(&url1, 1, sign(1, &signing_key_a), filename.clone())
+                .unwrap();
+            assert_eq!(
+                watcher_db.get_signature_verification_status(&url1, 1),
+                Ok(SignatureVerificationStatus::Pending)
+            );
+
+            // Getting a report for the signer verifies it, and marks the other potential
+            // signers as having no report.
+            watcher_db
+                .add_verification_report(
+                    &url1,
+                    &signing_key_a.public_key(),
+                    &verification_report,
+                    &[signing_key_b.public_key()],
+                )
+                .unwrap();
+            assert_eq!(
+                watcher_db.get_signature_verification_status(&url1, 1),
+                Ok(SignatureVerificationStatus::Verified)
+            );
+
+            // New signatures by known signers get their status right away.
+            watcher_db
+                .add_block_signature(&url1, 2, sign(2, &signing_key_a), filename.clone())
+                .unwrap();
+            assert_eq!(
+                watcher_db.get_signature_verification_status(&url1, 2),
+                Ok(SignatureVerificationStatus::Verified)
+            );
+            watcher_db
+                .add_block_signature(&url1, 3, sign(3, &signing_key_b), filename.clone())
+                .unwrap();
+            assert_eq!(
+                watcher_db.get_signature_verification_status(&url1, 3),
+                Ok(SignatureVerificationStatus::NoVerificationReport)
+            );
+
+            // Reports are associated with a specific url.
+            watcher_db
+                .add_block_signature(&url2, 3, sign(3, &signing_key_c), filename.clone())
+                .unwrap();
+            assert_eq!(
+                watcher_db.get_signature_verification_status(&url2, 3),
+                Ok(SignatureVerificationStatus::Pending)
+            );
+            watcher_db
+                .add_verification_report(
+                    &url2,
+                    &signing_key_d.public_key(),
+                    &verification_report,
+                    &[signing_key_c.public_key()],
+                )
+                .unwrap();
+            assert_eq!(
+                watcher_db.get_signature_verification_statuses(3).unwrap(),
+                HashMap::from_iter([
+                    (
+                        url1.clone(),
+                        SignatureVerificationStatus::NoVerificationReport
+                    ),
+                    (
+                        url2.clone(),
+                        SignatureVerificationStatus::NoVerificationReport
+                    ),
+                ])
+            );
+
+            // A report showing up late for a signer still gets it verified.
+            watcher_db
+                .add_verification_report(
+                    &url2,
+                    &signing_key_c.public_key(),
+                    &verification_report,
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(
+                watcher_db.get_signature_verification_status(&url2, 3),
+                Ok(SignatureVerificationStatus::Verified)
+            );
+
+            // Reports that do not pass the verification report checker do not verify
+            // signatures.
+            let signing_key_e = Ed25519Pair::from_random(&mut rng);
+            let invalid_report = VerificationReport {
+                http_body: INVALID_REPORT_BODY.to_owned(),
+                ..verification_report.clone()
+            };
+            watcher_db
+                .add_block_signature(&url2, 5, sign(5, &signing_key_e), filename.clone())
+                .unwrap();
+            watcher_db
+                .add_verification_report(&url2, &signing_key_e.public_key(), &invalid_report, &[])
+                .unwrap();
+            assert_eq!(
+                watcher_db.get_signature_verification_status(&url2, 5),
+                Ok(SignatureVerificationStatus::InvalidVerificationReport)
+            );
+
+            // Invalid signatures stay invalid.
+            watcher_db
+                .add_block_signature(&url1, 4, sign(5, &signing_key_b), filename)
+                .unwrap();
+            watcher_db.mark_signature_invalid(&url1, 4).unwrap();
+            watcher_db
+                .add_verification_report(
+                    &url1,
+                    &signing_key_b.public_key(),
+                    &verification_report,
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(
+                watcher_db.get_signature_verification_status(&url1, 4),
+                Ok(SignatureVerificationStatus::InvalidSignature)
+            );
+            assert_eq!(
+                watcher_db.get_signature_verification_status(&url1, 3),
+                Ok(SignatureVerificationStatus::Verified)
+            );
+            assert_eq!(
+                watcher_db.mark_signature_invalid(&url2, 4),
+                Err(WatcherDBError::NotFound)
+            );
+
+            // Removing a source url removes its statuses.
+            watcher_db.remove_all_for_source_url(&url1).unwrap();
+            assert_eq!(
+                watcher_db.get_signature_verification_statuses(3).unwrap(),
+                HashMap::from_iter([(url2, SignatureVerificationStatus::Verified)])
+            );
+            assert_eq!(
+                watcher_db.get_signature_verification_status(&url1, 1),
+                Err(WatcherDBError::NotFound)
+            );
+        });
+    }
+
+    // Importing an exported snapshot should reproduce the signatures, reports and
+    // sync progress of the original database.
+    #[test_with_logger]
+    fn test_snapshot_round_trip(logger: Logger) {
+        run_with_one_seed(|mut rng| {
+            let url1 = Url::parse("http://www.my_url1.com").unwrap();
+            let url2 = Url::parse("http://www.my_url2.com").unwrap();
+            let urls = [url1.clone(), url2.clone()];
+            let watcher_db = setup_watcher_db(&urls, logger.clone());
+
+            let blocks = setup_blocks();
+            let signing_key_a = Ed25519Pair::from_random(&mut rng);
+            let signing_key_b = Ed25519Pair::from_random(&mut rng);
+            let filename = String::from("00/00");
+
+            let verification_report = VerificationReport {
+                sig: vec![1u8; 32].into(),
+                chain: vec![vec![2; 16], vec![3; 32]],
+                http_body: "test body".to_owned(),
+            };
+
+            let sign = |block_index: usize, key: &Ed25519Pair| {
+                BlockSignature::from_block_and_keypair(blocks[block_index].block(), key).unwrap()
+            };
+
+            watcher_db
+                .add_verification_report(
+                    &url1,
+                    &signing_key_a.public_key(),
+                    &verification_report,
+                    &[signing_key_b.public_key()],
+                )
+                .unwrap();
+            for block_index in 1..4 {
+                watcher_db
+                    .add_block_signature(
+                        &url1,
+                        block_index as u64,
+                        sign(block_index, &signing_key_a),
+                        filename.clone(),
+                    )
+                    .unwrap();
+            }
+            watcher_db
+                .add_block_signature(&url1, 5, sign(6, &signing_key_b), filename.clone())
+                .unwrap();
+            watcher_db.mark_signature_invalid(&url1, 5).unwrap();
+            watcher_db.add_missing_block(&url1, 4).unwrap();
+            watcher_db
+                .add_block_signature(&url2, 1, sign(1, &signing_key_b), filename)
+                .unwrap();
+            watcher_db
+                .add_block_metadata(
+                    &url2,
+                    1,
+                    &make_block_metadata(blocks[1].block().id.clone(), &mut rng),
+                )
+                .unwrap();
+
+            let mut snapshot = Vec::new();
+            let summary = watcher_db.export_snapshot(&mut snapshot).unwrap();
+            assert_eq!(
+                summary,
+                SnapshotSummary {
+                    num_verification_reports: 2,
+                    num_block_signatures: 5,
+                    num_block_metadata: 1,
+                }
+            );
+
+            let db_tmp = TempDir::new("watcher_db_import").unwrap();
+            assert_eq!(
+                WatcherDB::import_snapshot(db_tmp.path(), &mut &snapshot[..], logger.clone()),
+                Ok(summary)
+            );
+            let imported_db = WatcherDB::open_ro(db_tmp.path(), logger.clone()).unwrap();
+
+            assert_eq!(
+                imported_db.get_config_urls().unwrap(),
+                watcher_db.get_config_urls().unwrap()
+            );
+            assert_eq!(
+                imported_db.last_synced_blocks(),
+                watcher_db.last_synced_blocks()
+            );
+            assert_eq!(
+                imported_db.get_missing_blocks(),
+                watcher_db.get_missing_blocks()
+            );
+            for block_index in 0..7 {
+                assert_eq!(
+                    imported_db.get_block_signatures(block_index),
+                    watcher_db.get_block_signatures(block_index)
+                );
+                assert_eq!(
+                    imported_db.get_signature_verification_statuses(block_index),
+                    watcher_db.get_signature_verification_statuses(block_index)
+                );
+                assert_eq!(
+                    imported_db.get_block_metadata(block_index),
+                    watcher_db.get_block_metadata(block_index)
+                );
+            }
+            for signing_key in [&signing_key_a, &signing_key_b] {
+                assert_eq!(
+                    imported_db.get_verification_reports_for_signer(&signing_key.public_key()),
+                    watcher_db.get_verification_reports_for_signer(&signing_key.public_key())
+                );
+            }
+            assert_eq!(
+                imported_db.get_verification_report_poll_queue(),
+                watcher_db.get_verification_report_poll_queue()
+            );
+
+            drop(imported_db);
+
+            // Importing again changes nothing.
+            assert_eq!(
+                WatcherDB::import_snapshot(db_tmp.path(), &mut &snapshot[..], logger.clone()),
+                Ok(summary)
+            );
+            let imported_db = WatcherDB::open_ro(db_tmp.path(), logger.clone()).unwrap();
+            for block_index in 0..7 {
+                assert_eq!(
+                    imported_db.get_block_signatures(block_index),
+                    watcher_db.get_block_signatures(block_index)
+                );
+                assert_eq!(
+                    imported_db.get_signature_verification_statuses(block_index),
+                    watcher_db.get_signature_verification_statuses(block_index)
+                );
+            }
+            drop(imported_db);
+
+            // An import that failed part way can be resumed.
+            let db_tmp = TempDir::new("watcher_db_import").unwrap();
+            assert!(WatcherDB::import_snapshot(
+                db_tmp.path(),
+                &mut &snapshot[..snapshot.len() - 1],
+                logger.clone()
+            )
+            .is_err());
+            assert_eq!(
+                WatcherDB::import_snapshot(db_tmp.path(), &mut &snapshot[..], logger.clone()),
+                Ok(summary)
+            );
+            let imported_db = WatcherDB::open_ro(db_tmp.path(), logger.clone()).unwrap();
+            assert_eq!(
+                imported_db.last_synced_blocks(),
+                watcher_db.last_synced_blocks()
+            );
+            for block_index in 0..7 {
+                assert_eq!(
+                    imported_db.get_block_signatures(block_index),
+                    watcher_db.get_block_signatures(block_index)
+                );
+            }
+
+            // Importing over a database configured with other tx source urls is
+            // refused.
+            let other_db_tmp = TempDir::new("watcher_db_import").unwrap();
+            WatcherDB::create(other_db_tmp.path()).unwrap();
+            WatcherDB::open_rw(other_db_tmp.path(), &[url1.clone()], logger.clone()).unwrap();
+            assert_eq!(
+                WatcherDB::import_snapshot(other_db_tmp.path(), &mut &snapshot[..], logger.clone()),
+                Err(WatcherDBError::AlreadyExists)
+            );
+
+            // Snapshots of an older format are refused.
+            let mut old_snapshot = Vec::new();
+            write_record(
+                &mut old_snapshot,
+                SnapshotRecordKind::Header(SnapshotHeader {
+                    format_version: 1,
+                    tx_source_urls: urls.iter().map(Url::to_string).collect(),
+                }),
+            )
+            .unwrap();
+            let db_tmp = TempDir::new("watcher_db_import").unwrap();
+            assert_eq!(
+                WatcherDB::import_snapshot(db_tmp.path(), &mut &old_snapshot[..], logger.clone()),
+                Err(WatcherDBError::UnsupportedSnapshotVersion(1))
+            );
+        });
+    }
 }